@@ -26,8 +26,11 @@ use database::{
     Token,
 };
 use file_storage::{
+    ConditionalFile,
+    FileConditions,
     FileRangeStream,
     FileStream,
+    UploadSessionStatus,
 };
 use futures::{
     future::BoxFuture,
@@ -42,10 +45,13 @@ use isolate::{
     HttpActionRequest,
     HttpActionResponseStreamer,
 };
-use keybroker::Identity;
+use keybroker::{
+    Identity,
+    UploadConstraints,
+};
 use model::{
     file_storage::FileStorageId,
-    session_requests::types::SessionRequestIdentifier,
+    idempotency_keys::types::MutationIdentifier,
 };
 use serde_json::Value as JsonValue;
 use sync_types::{
@@ -93,6 +99,15 @@ pub trait ApplicationApi: Send + Sync {
         auth_token: AuthenticationToken,
     ) -> anyhow::Result<Identity>;
 
+    /// Fail with an unauthenticated error if `identity`'s session has been
+    /// revoked since it was authenticated.
+    async fn ensure_session_not_revoked(
+        &self,
+        host: &str,
+        request_id: RequestId,
+        identity: &Identity,
+    ) -> anyhow::Result<()>;
+
     async fn execute_public_query(
         &self,
         host: &str,
@@ -114,7 +129,7 @@ pub trait ApplicationApi: Send + Sync {
         args: Vec<JsonValue>,
         caller: FunctionCaller,
         // Identifier used to make this mutation idempotent.
-        mutation_identifier: Option<SessionRequestIdentifier>,
+        mutation_identifier: Option<MutationIdentifier>,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>>;
 
     async fn execute_public_action(
@@ -162,7 +177,7 @@ pub trait ApplicationApi: Send + Sync {
         request_id: RequestId,
         token: &str,
         validity: Duration,
-    ) -> anyhow::Result<()>;
+    ) -> anyhow::Result<UploadConstraints>;
 
     async fn store_file(
         &self,
@@ -175,6 +190,39 @@ pub trait ApplicationApi: Send + Sync {
         body: BoxStream<'_, anyhow::Result<Bytes>>,
     ) -> anyhow::Result<DeveloperDocumentId>;
 
+    async fn create_upload_session(
+        &self,
+        host: &str,
+        request_id: RequestId,
+        content_type: Option<ContentType>,
+        expected_sha256: Option<Sha256Digest>,
+        constraints: UploadConstraints,
+    ) -> anyhow::Result<String>;
+
+    async fn upload_session_part(
+        &self,
+        host: &str,
+        request_id: RequestId,
+        upload_id: &str,
+        part_number: u16,
+        part: Bytes,
+    ) -> anyhow::Result<()>;
+
+    async fn upload_session_status(
+        &self,
+        host: &str,
+        request_id: RequestId,
+        upload_id: &str,
+    ) -> anyhow::Result<UploadSessionStatus>;
+
+    async fn finish_upload_session(
+        &self,
+        host: &str,
+        request_id: RequestId,
+        component: ComponentId,
+        upload_id: &str,
+    ) -> anyhow::Result<DeveloperDocumentId>;
+
     async fn get_file_range(
         &self,
         host: &str,
@@ -182,7 +230,8 @@ pub trait ApplicationApi: Send + Sync {
         component: ComponentId,
         file_storage_id: FileStorageId,
         range: (Bound<u64>, Bound<u64>),
-    ) -> anyhow::Result<FileRangeStream>;
+        conditions: FileConditions,
+    ) -> anyhow::Result<ConditionalFile<FileRangeStream>>;
 
     async fn get_file(
         &self,
@@ -190,7 +239,8 @@ pub trait ApplicationApi: Send + Sync {
         request_id: RequestId,
         component: ComponentId,
         file_storage_id: FileStorageId,
-    ) -> anyhow::Result<FileStream>;
+        conditions: FileConditions,
+    ) -> anyhow::Result<ConditionalFile<FileStream>>;
 }
 
 // Implements ApplicationApi via Application.
@@ -206,6 +256,15 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         self.authenticate(auth_token, validate_time).await
     }
 
+    async fn ensure_session_not_revoked(
+        &self,
+        _host: &str,
+        _request_id: RequestId,
+        identity: &Identity,
+    ) -> anyhow::Result<()> {
+        self.ensure_session_not_revoked(identity).await
+    }
+
     async fn execute_public_query(
         &self,
         _host: &str,
@@ -239,7 +298,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         args: Vec<JsonValue>,
         caller: FunctionCaller,
         // Identifier used to make this mutation idempotent.
-        mutation_identifier: Option<SessionRequestIdentifier>,
+        mutation_identifier: Option<MutationIdentifier>,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
         anyhow::ensure!(
             caller.allowed_visibility() == AllowedVisibility::PublicOnly,
@@ -334,7 +393,7 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         _request_id: RequestId,
         token: &str,
         validity: Duration,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<UploadConstraints> {
         self.key_broker()
             .check_store_file_authorization(&self.runtime, token, validity)
     }
@@ -359,6 +418,48 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         .await
     }
 
+    async fn create_upload_session(
+        &self,
+        _host: &str,
+        _request_id: RequestId,
+        content_type: Option<ContentType>,
+        expected_sha256: Option<Sha256Digest>,
+        constraints: UploadConstraints,
+    ) -> anyhow::Result<String> {
+        self.create_upload_session(content_type, expected_sha256, constraints)
+            .await
+    }
+
+    async fn upload_session_part(
+        &self,
+        _host: &str,
+        _request_id: RequestId,
+        upload_id: &str,
+        part_number: u16,
+        part: Bytes,
+    ) -> anyhow::Result<()> {
+        self.upload_session_part(upload_id, part_number, part).await
+    }
+
+    async fn upload_session_status(
+        &self,
+        _host: &str,
+        _request_id: RequestId,
+        upload_id: &str,
+    ) -> anyhow::Result<UploadSessionStatus> {
+        self.upload_session_status(upload_id).await
+    }
+
+    async fn finish_upload_session(
+        &self,
+        _host: &str,
+        _request_id: RequestId,
+        component: ComponentId,
+        upload_id: &str,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        self.finish_upload_session(component, upload_id).await
+    }
+
     async fn get_file_range(
         &self,
         _host: &str,
@@ -366,8 +467,10 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         component: ComponentId,
         file_storage_id: FileStorageId,
         range: (Bound<u64>, Bound<u64>),
-    ) -> anyhow::Result<FileRangeStream> {
-        self.get_file_range(component, file_storage_id, range).await
+        conditions: FileConditions,
+    ) -> anyhow::Result<ConditionalFile<FileRangeStream>> {
+        self.get_file_range(component, file_storage_id, range, conditions)
+            .await
     }
 
     async fn get_file(
@@ -376,8 +479,9 @@ impl<RT: Runtime> ApplicationApi for Application<RT> {
         _request_id: RequestId,
         component: ComponentId,
         file_storage_id: FileStorageId,
-    ) -> anyhow::Result<FileStream> {
-        self.get_file(component, file_storage_id).await
+        conditions: FileConditions,
+    ) -> anyhow::Result<ConditionalFile<FileStream>> {
+        self.get_file(component, file_storage_id, conditions).await
     }
 }
 
@@ -0,0 +1,107 @@
+//! Rate limiting of calls to a component's functions by its
+//! `max_function_calls_per_minute` quota.
+//!
+//! Each component gets a bucket holding a minute's worth of calls that
+//! refills continuously, and every call takes a token. Buckets are kept in
+//! memory, so each backend instance enforces the quota separately.
+
+use std::{
+    num::NonZeroUsize,
+    time::Duration,
+};
+
+use common::runtime::Runtime;
+use errors::ErrorMetadata;
+use lru::LruCache;
+use parking_lot::Mutex;
+use value::InternalId;
+
+/// Maximum number of components to track call rates for. When there are
+/// more, the least recently called component's bucket is dropped, which
+/// refills it.
+const MAX_TRACKED_COMPONENTS: usize = 10_000;
+
+struct CallBucket<RT: Runtime> {
+    tokens: f64,
+    updated: RT::Instant,
+}
+
+pub struct ComponentCallLimiter<RT: Runtime> {
+    buckets: Mutex<LruCache<InternalId, CallBucket<RT>>>,
+}
+
+impl<RT: Runtime> ComponentCallLimiter<RT> {
+    pub fn new() -> Self {
+        Self {
+            buckets: Mutex::new(LruCache::new(
+                NonZeroUsize::new(MAX_TRACKED_COMPONENTS).expect("MAX_TRACKED_COMPONENTS is zero"),
+            )),
+        }
+    }
+
+    /// Take a token for a call to `component`, failing if the component has
+    /// used up its quota.
+    pub fn check(
+        &self,
+        component: InternalId,
+        max_calls_per_minute: u64,
+        now: RT::Instant,
+    ) -> anyhow::Result<()> {
+        let capacity = max_calls_per_minute as f64;
+        let mut buckets = self.buckets.lock();
+        let bucket = buckets.get_or_insert_mut(component, || CallBucket {
+            tokens: capacity,
+            updated: now.clone(),
+        });
+        let elapsed = if now > bucket.updated {
+            now.clone() - bucket.updated.clone()
+        } else {
+            Duration::ZERO
+        };
+        // The quota may have changed since the bucket was filled.
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * capacity / 60.0).min(capacity);
+        bucket.updated = now;
+        anyhow::ensure!(
+            bucket.tokens >= 1.0,
+            ErrorMetadata::rate_limited(
+                "ComponentQuotaExceeded",
+                format!(
+                    "Component is over its quota of {max_calls_per_minute} function calls per \
+                     minute"
+                ),
+            )
+        );
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::runtime::Runtime;
+    use runtime::testing::TestRuntime;
+    use value::InternalId;
+
+    use super::ComponentCallLimiter;
+
+    #[convex_macro::test_runtime]
+    async fn test_component_call_limiter(rt: TestRuntime) -> anyhow::Result<()> {
+        let limiter = ComponentCallLimiter::<TestRuntime>::new();
+        let component = InternalId::MIN;
+        let other_component = InternalId::MAX;
+        let now = rt.monotonic_now();
+        for _ in 0..2 {
+            limiter.check(component, 2, now.clone())?;
+        }
+        assert!(limiter.check(component, 2, now.clone()).is_err());
+        // Components have separate buckets.
+        limiter.check(other_component, 2, now.clone())?;
+        // Half a minute later, one call has been refilled.
+        let later = now + Duration::from_secs(30);
+        limiter.check(component, 2, later.clone())?;
+        assert!(limiter.check(component, 2, later).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Caching of `_function_flags` and `_function_canaries` for the function
+//! router, so checking whether a function is disabled or has a canary doesn't
+//! read the tables on every call.
+//!
+//! The flags are cached with the token of the transaction that loaded them
+//! and refreshed through the write log like cached query results, so a flag
+//! change applies to every call that begins after it commits.
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+};
+
+use common::{
+    components::CanonicalizedComponentFunctionPath,
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::{
+    Database,
+    Token,
+};
+use keybroker::Identity;
+use model::{
+    function_canaries::{
+        types::FunctionCanary,
+        FunctionCanariesModel,
+    },
+    function_flags::{
+        types::FunctionFlag,
+        FunctionFlagsModel,
+    },
+};
+use parking_lot::Mutex;
+use usage_tracking::FunctionUsageTracker;
+
+struct Flags {
+    flags: BTreeMap<CanonicalizedComponentFunctionPath, FunctionFlag>,
+    canaries: BTreeMap<CanonicalizedComponentFunctionPath, FunctionCanary>,
+}
+
+type FunctionFlags = Arc<Flags>;
+
+#[derive(Clone)]
+struct CachedFlags {
+    token: Token,
+    flags: FunctionFlags,
+}
+
+pub struct FunctionFlagsCache<RT: Runtime> {
+    database: Database<RT>,
+    cached: Mutex<Option<CachedFlags>>,
+}
+
+impl<RT: Runtime> FunctionFlagsCache<RT> {
+    pub fn new(database: Database<RT>) -> Self {
+        Self {
+            database,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Fail with a `FunctionDisabled` error if a flag keeps `identity` from
+    /// calling `path` at `ts`.
+    pub async fn check(
+        &self,
+        identity: &Identity,
+        path: &CanonicalizedComponentFunctionPath,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        if path.udf_path.is_system() {
+            return Ok(());
+        }
+        let flags = self.flags_at(ts).await?;
+        if let Some(flag) = flags.flags.get(path) {
+            flag.check(identity)?;
+        }
+        Ok(())
+    }
+
+    /// The canary running alongside `path`'s active version at `ts`, if any.
+    pub async fn canary(
+        &self,
+        path: &CanonicalizedComponentFunctionPath,
+        ts: Timestamp,
+    ) -> anyhow::Result<Option<FunctionCanary>> {
+        if path.udf_path.is_system() {
+            return Ok(None);
+        }
+        Ok(self.flags_at(ts).await?.canaries.get(path).cloned())
+    }
+
+    async fn flags_at(&self, ts: Timestamp) -> anyhow::Result<FunctionFlags> {
+        let cached = self.cached.lock().clone();
+        if let Some(CachedFlags { token, flags }) = cached
+            && token.ts() <= ts
+            && let Some(token) = self.database.refresh_token(token, ts).await?
+        {
+            self.store(CachedFlags {
+                token,
+                flags: flags.clone(),
+            });
+            return Ok(flags);
+        }
+        let mut tx = self
+            .database
+            .begin_with_ts(Identity::system(), ts, FunctionUsageTracker::new())
+            .await?;
+        let flags: FunctionFlags = Arc::new(Flags {
+            flags: FunctionFlagsModel::new(&mut tx)
+                .list()
+                .await?
+                .into_iter()
+                .map(|flag| {
+                    let flag = flag.into_value();
+                    (flag.path.clone(), flag)
+                })
+                .collect(),
+            canaries: FunctionCanariesModel::new(&mut tx)
+                .list()
+                .await?
+                .into_iter()
+                .map(|canary| {
+                    let canary = canary.into_value();
+                    (canary.path.clone(), canary)
+                })
+                .collect(),
+        });
+        self.store(CachedFlags {
+            token: tx.into_token()?,
+            flags: flags.clone(),
+        });
+        Ok(flags)
+    }
+
+    /// Keep whichever entry is valid at the later timestamp, since most calls
+    /// begin at recent timestamps.
+    fn store(&self, entry: CachedFlags) {
+        let mut cached = self.cached.lock();
+        if cached
+            .as_ref()
+            .map_or(true, |cached| cached.token.ts() < entry.token.ts())
+        {
+            *cached = Some(entry);
+        }
+    }
+}
@@ -157,3 +157,29 @@ pub fn function_run_timer(udf_type: UdfType) -> StatusTimer {
     timer.add_label(udf_type.metric_label());
     timer
 }
+
+/// Which version of a function with a canary a call ran.
+#[derive(Clone, Copy)]
+pub enum FunctionVersion {
+    Active,
+    Canary,
+}
+
+register_convex_histogram!(
+    APPLICATION_FUNCTION_RUNNER_VERSION_SECONDS,
+    "The time a call of a function with a canary took to run, by the version it ran. Calls that \
+     throw are logged with status developer_error.",
+    &[STATUS_LABEL[0], "udf_type", "version"]
+);
+pub fn function_version_timer(udf_type: UdfType, version: FunctionVersion) -> StatusTimer {
+    let mut timer = StatusTimer::new(&APPLICATION_FUNCTION_RUNNER_VERSION_SECONDS);
+    timer.add_label(udf_type.metric_label());
+    timer.add_label(StaticMetricLabel::new(
+        "version",
+        match version {
+            FunctionVersion::Active => "active",
+            FunctionVersion::Canary => "canary",
+        },
+    ));
+    timer
+}
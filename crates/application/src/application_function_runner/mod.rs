@@ -24,7 +24,11 @@ use common::{
         ComponentId,
     },
     errors::JsError,
-    execution_context::ExecutionContext,
+    execution_context::{
+        ExecutionContext,
+        ExecutionId,
+        TraceParent,
+    },
     http::fetch::FetchClient,
     knobs::{
         APPLICATION_FUNCTION_RUNNER_SEMAPHORE_TIMEOUT,
@@ -35,6 +39,7 @@ use common::{
         APPLICATION_MAX_CONCURRENT_V8_ACTIONS,
         BACKEND_ISOLATE_ACTIVE_THREADS_PERCENT,
         ISOLATE_MAX_USER_HEAP_SIZE,
+        TABLE_CHANGES_ROWS_READ_LIMIT,
         UDF_EXECUTOR_OCC_INITIAL_BACKOFF,
         UDF_EXECUTOR_OCC_MAX_BACKOFF,
         UDF_EXECUTOR_OCC_MAX_RETRIES,
@@ -72,9 +77,11 @@ use common::{
     RequestId,
 };
 use database::{
+    triggers::run_triggers,
     unauthorized_error,
     BootstrapComponentsModel,
     Database,
+    TableChanges,
     Token,
     Transaction,
 };
@@ -102,6 +109,7 @@ use isolate::{
         ValidatedUdfOutcome,
     },
     parse_udf_args,
+    secrets::SecretResolver,
     validate_schedule_args,
     ActionCallbacks,
     ActionOutcome,
@@ -134,6 +142,7 @@ use model::{
         module_loader::ModuleLoader,
         types::ModuleConfig,
     },
+    egress_policy::EgressPolicyModel,
     environment_variables::{
         types::{
             EnvVarName,
@@ -149,6 +158,13 @@ use model::{
         types::FileStorageEntry,
         FileStorageId,
     },
+    idempotency_keys::{
+        types::{
+            IdempotencyKeyRecord,
+            MutationIdentifier,
+        },
+        IdempotencyKeyModel,
+    },
     modules::{
         module_versions::{
             AnalyzedModule,
@@ -157,10 +173,12 @@ use model::{
         },
         ModuleModel,
     },
-    scheduled_jobs::VirtualSchedulerModel,
+    scheduled_jobs::{
+        types::ScheduleOptions,
+        VirtualSchedulerModel,
+    },
     session_requests::{
         types::{
-            SessionRequestIdentifier,
             SessionRequestOutcome,
             SessionRequestRecord,
         },
@@ -171,6 +189,7 @@ use model::{
         SourcePackageModel,
     },
     udf_config::types::UdfConfig,
+    workflows::WorkflowModel,
 };
 use node_executor::{
     Actions,
@@ -188,6 +207,12 @@ use usage_tracking::{
 use value::{
     heap_size::HeapSize,
     id_v6::DeveloperDocumentId,
+    sha256::{
+        Sha256,
+        Sha256Digest,
+    },
+    ConvexValue,
+    TableName,
     TableNamespace,
 };
 use vector::{
@@ -195,27 +220,35 @@ use vector::{
     VectorSearch,
 };
 
-use self::metrics::{
-    function_waiter_timer,
-    log_occ_retries,
-    log_outstanding_functions,
-    log_udf_executor_result,
-    mutation_timer,
-    OutstandingFunctionState,
-    UdfExecutorResult,
+use self::{
+    component_call_limiter::ComponentCallLimiter,
+    function_flags_cache::FunctionFlagsCache,
+    metrics::{
+        function_waiter_timer,
+        log_occ_retries,
+        log_outstanding_functions,
+        log_udf_executor_result,
+        mutation_timer,
+        OutstandingFunctionState,
+        UdfExecutorResult,
+    },
+    trigger_runner::MutationTriggerRunner,
 };
 use crate::{
     application_function_runner::metrics::{
         function_run_timer,
         function_total_timer,
+        function_version_timer,
         log_function_wait_timeout,
         log_mutation_already_committed,
+        FunctionVersion,
     },
     cache::CacheManager,
     function_log::{
         ActionCompletion,
         FunctionExecutionLog,
         HttpActionStatusCode,
+        IndexRangesRead,
     },
     ActionError,
     ActionReturn,
@@ -224,7 +257,10 @@ use crate::{
     QueryReturn,
 };
 
+mod component_call_limiter;
+mod function_flags_cache;
 mod metrics;
+mod trigger_runner;
 
 static BUILD_DEPS_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| Duration::from_secs(1200));
 
@@ -236,6 +272,8 @@ pub struct FunctionRouter<RT: Runtime> {
     query_limiter: Arc<Limiter>,
     mutation_limiter: Arc<Limiter>,
     action_limiter: Arc<Limiter>,
+    component_call_limiter: Arc<ComponentCallLimiter<RT>>,
+    function_flags: Arc<FunctionFlagsCache<RT>>,
 
     rt: RT,
     database: Database<RT>,
@@ -252,6 +290,7 @@ impl<RT: Runtime> FunctionRouter<RT> {
         Self {
             function_runner,
             rt,
+            function_flags: Arc::new(FunctionFlagsCache::new(database.clone())),
             database,
             system_env_vars,
             query_limiter: Arc::new(Limiter::new(
@@ -269,6 +308,7 @@ impl<RT: Runtime> FunctionRouter<RT> {
                 UdfType::Action,
                 *APPLICATION_MAX_CONCURRENT_V8_ACTIONS,
             )),
+            component_call_limiter: Arc::new(ComponentCallLimiter::new()),
         }
     }
 }
@@ -325,7 +365,7 @@ impl<RT: Runtime> FunctionRouter<RT> {
     async fn function_runner_execute(
         &self,
         mut tx: Transaction<RT>,
-        path_and_args: ValidatedPathAndArgs,
+        mut path_and_args: ValidatedPathAndArgs,
         udf_type: UdfType,
         journal: QueryJournal,
         context: ExecutionContext,
@@ -337,6 +377,27 @@ impl<RT: Runtime> FunctionRouter<RT> {
             .in_memory_indexes
             .in_memory_indexes_last_modified();
 
+        self.check_function_flag(tx.identity(), path_and_args.path(), *tx.begin_timestamp())
+            .await?;
+        self.check_component_call_quota(&mut tx, path_and_args.path())
+            .await?;
+
+        // Callers in a canary's rollout run its version, and calls of
+        // functions with a canary are timed by version so the two can be
+        // compared.
+        let version = match self
+            .function_flags
+            .canary(path_and_args.path(), *tx.begin_timestamp())
+            .await?
+        {
+            Some(canary) if canary.routes_to_canary(tx.identity()) => {
+                path_and_args = path_and_args.with_source_package(canary.source_package_id);
+                Some(FunctionVersion::Canary)
+            },
+            Some(_) => Some(FunctionVersion::Active),
+            None => None,
+        };
+
         let limiter = match udf_type {
             UdfType::Query => &self.query_limiter,
             UdfType::Mutation => &self.mutation_limiter,
@@ -347,6 +408,7 @@ impl<RT: Runtime> FunctionRouter<RT> {
         let request_guard = limiter.acquire_permit_with_timeout(&self.rt).await?;
 
         let timer = function_run_timer(udf_type);
+        let version_timer = version.map(|version| function_version_timer(udf_type, version));
         let (function_tx, outcome, usage_stats) = self
             .function_runner
             .run_function(
@@ -363,6 +425,20 @@ impl<RT: Runtime> FunctionRouter<RT> {
             )
             .await?;
         timer.finish();
+        if let Some(version_timer) = version_timer {
+            let is_error = match &outcome {
+                FunctionOutcome::Query(outcome) | FunctionOutcome::Mutation(outcome) => {
+                    outcome.result.is_err()
+                },
+                FunctionOutcome::Action(outcome) => outcome.result.is_err(),
+                FunctionOutcome::HttpAction(_) => false,
+            };
+            if is_error {
+                version_timer.finish_developer_error();
+            } else {
+                version_timer.finish();
+            }
+        }
         drop(request_guard);
 
         // Add the usage stats to the current transaction tracker.
@@ -397,6 +473,53 @@ impl<RT: Runtime> FunctionRouter<RT> {
 
         Ok((tx, outcome))
     }
+
+    /// Fail if the function has been disabled, or is being rolled out to a
+    /// percentage of users that doesn't include the caller.
+    pub(crate) async fn check_function_flag(
+        &self,
+        identity: &Identity,
+        path: &CanonicalizedComponentFunctionPath,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.function_flags.check(identity, path, ts).await
+    }
+
+    /// Take a token for a call to a component's function if the component
+    /// has a function call quota.
+    async fn check_component_call_quota(
+        &self,
+        tx: &mut Transaction<RT>,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<()> {
+        if path.component.is_root() {
+            return Ok(());
+        }
+        let Some(component) = BootstrapComponentsModel::new(tx)
+            .resolve_path(path.component.clone())
+            .await?
+        else {
+            return Ok(());
+        };
+        if let Some(max_calls_per_minute) = component.quotas.max_function_calls_per_minute {
+            self.component_call_limiter.check(
+                component.id().internal_id(),
+                max_calls_per_minute,
+                self.rt.monotonic_now(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// How many functions of each kind may run at once. More requests wait for a
+/// running function to finish, and fail if they wait too long.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FunctionConcurrencyLimits {
+    pub queries: usize,
+    pub mutations: usize,
+    pub v8_actions: usize,
+    pub node_actions: usize,
 }
 
 // Used to limit upstream concurrency for a given function type. It also tracks
@@ -407,7 +530,10 @@ struct Limiter {
 
     // Used to limit running functions.
     semaphore: Semaphore,
-    total_permits: usize,
+    total_permits: AtomicUsize,
+    // Permits to take out of the semaphore as running functions release them,
+    // after the limit was lowered below the number running.
+    permits_to_forget: AtomicUsize,
 
     // Total function requests, including ones still waiting on the semaphore.
     total_outstanding: AtomicUsize,
@@ -419,7 +545,8 @@ impl Limiter {
             udf_type,
             env,
             semaphore: Semaphore::new(total_permits),
-            total_permits,
+            total_permits: AtomicUsize::new(total_permits),
+            permits_to_forget: AtomicUsize::new(0),
             total_outstanding: AtomicUsize::new(0),
         };
         // Update the gauges on startup.
@@ -445,6 +572,45 @@ impl Limiter {
         Ok(request_guard)
     }
 
+    fn total_permits(&self) -> usize {
+        self.total_permits.load(Ordering::SeqCst)
+    }
+
+    /// Change how many functions may run at once. Running functions aren't
+    /// interrupted if there are more than the new limit, but no more start
+    /// until enough of them finish.
+    fn set_total_permits(&self, total_permits: usize) {
+        let previous = self.total_permits.swap(total_permits, Ordering::SeqCst);
+        if total_permits > previous {
+            let mut added = total_permits - previous;
+            while added > 0 && self.take_permit_to_forget() {
+                added -= 1;
+            }
+            self.semaphore.add_permits(added);
+        } else {
+            self.permits_to_forget
+                .fetch_add(previous - total_permits, Ordering::SeqCst);
+            while let Ok(permit) = self.semaphore.try_acquire() {
+                if !self.take_permit_to_forget() {
+                    break;
+                }
+                permit.forget();
+            }
+        }
+        self.update_gauges();
+    }
+
+    /// Functions that are running or waiting to run.
+    fn num_outstanding(&self) -> usize {
+        self.total_outstanding.load(Ordering::SeqCst)
+    }
+
+    fn take_permit_to_forget(&self) -> bool {
+        self.permits_to_forget
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+
     fn start(&self) -> RequestGuard {
         self.total_outstanding.fetch_add(1, Ordering::SeqCst);
         // Update the gauge to account for the newly waiting request.
@@ -457,7 +623,8 @@ impl Limiter {
 
     // Updates the current waiting and running function gauges.
     fn update_gauges(&self) {
-        let running = self.total_permits - self.semaphore.available_permits();
+        let capacity = self.total_permits() + self.permits_to_forget.load(Ordering::SeqCst);
+        let running = capacity.saturating_sub(self.semaphore.available_permits());
         let waiting = self
             .total_outstanding
             .load(Ordering::SeqCst)
@@ -501,8 +668,13 @@ impl<'a> RequestGuard<'a> {
 
 impl<'a> Drop for RequestGuard<'a> {
     fn drop(&mut self) {
-        // Drop the semaphore permit before updating gauges.
-        drop(self.permit.take());
+        // Release the semaphore permit before updating gauges, unless the
+        // limit was lowered and it has to be taken out.
+        if let Some(permit) = self.permit.take()
+            && self.limiter.take_permit_to_forget()
+        {
+            permit.forget();
+        }
         // Remove the request from the running ones.
         self.limiter
             .total_outstanding
@@ -539,6 +711,7 @@ pub struct ApplicationFunctionRunner<RT: Runtime> {
     system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
     node_action_limiter: Limiter,
     fetch_client: Arc<dyn FetchClient>,
+    secret_resolver: SecretResolver,
 }
 
 impl<RT: Runtime> HeapSize for ApplicationFunctionRunner<RT> {
@@ -641,9 +814,40 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 *APPLICATION_MAX_CONCURRENT_NODE_ACTIONS,
             ),
             fetch_client,
+            secret_resolver: SecretResolver::from_env(),
         }
     }
 
+    pub fn concurrency_limits(&self) -> FunctionConcurrencyLimits {
+        let router = &self.isolate_functions;
+        FunctionConcurrencyLimits {
+            queries: router.query_limiter.total_permits(),
+            mutations: router.mutation_limiter.total_permits(),
+            v8_actions: router.action_limiter.total_permits(),
+            node_actions: self.node_action_limiter.total_permits(),
+        }
+    }
+
+    /// Change how many functions of each kind may run at once, e.g. to make
+    /// room for a spike in traffic without restarting the backend.
+    pub fn set_concurrency_limits(&self, limits: FunctionConcurrencyLimits) {
+        let router = &self.isolate_functions;
+        router.query_limiter.set_total_permits(limits.queries);
+        router.mutation_limiter.set_total_permits(limits.mutations);
+        router.action_limiter.set_total_permits(limits.v8_actions);
+        self.node_action_limiter
+            .set_total_permits(limits.node_actions);
+    }
+
+    /// Queries, mutations and actions that are running or waiting to run.
+    pub fn num_running_functions(&self) -> usize {
+        let router = &self.isolate_functions;
+        router.query_limiter.num_outstanding()
+            + router.mutation_limiter.num_outstanding()
+            + router.action_limiter.num_outstanding()
+            + self.node_action_limiter.num_outstanding()
+    }
+
     pub(crate) async fn shutdown(&self) -> anyhow::Result<()> {
         self.analyze_isolate.shutdown().await?;
         self.http_actions.shutdown().await?;
@@ -674,7 +878,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
 
         let identity = tx.inert_identity();
         let start = self.runtime.monotonic_now();
-        let validate_result = ValidatedPathAndArgs::new(
+        let validate_result = ValidatedPathAndArgs::new_with_returns_validator(
             caller.allowed_visibility(),
             &mut tx,
             path.clone(),
@@ -684,8 +888,9 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         .await?;
         let context = ExecutionContext::new(request_id, &caller);
         let (mut tx, outcome) = match validate_result {
-            Ok(path_and_args) => {
-                self.isolate_functions
+            Ok((path_and_args, returns_validator, errors_validator)) => {
+                let (mut tx, outcome) = self
+                    .isolate_functions
                     .execute_query_or_mutation(
                         tx,
                         path_and_args,
@@ -693,7 +898,38 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                         QueryJournal::new(),
                         context.clone(),
                     )
-                    .await?
+                    .await?;
+                let outcome = match outcome {
+                    FunctionOutcome::Query(mut query_outcome) => {
+                        let (_, component) = BootstrapComponentsModel::new(&mut tx)
+                            .component_path_to_ids(path.component.clone())
+                            .await?;
+                        let table_mapping = tx.table_mapping().namespace(component.into());
+                        let virtual_table_mapping =
+                            tx.virtual_table_mapping().namespace(component.into());
+                        let validation_error = match &query_outcome.result {
+                            Ok(json_packed_value) => {
+                                let output: ConvexValue = json_packed_value.unpack();
+                                returns_validator.check_output(
+                                    &output,
+                                    &table_mapping,
+                                    &virtual_table_mapping,
+                                )
+                            },
+                            Err(js_err) => errors_validator.check_error(
+                                js_err,
+                                &table_mapping,
+                                &virtual_table_mapping,
+                            ),
+                        };
+                        if let Some(js_err) = validation_error {
+                            query_outcome.result = Err(js_err);
+                        }
+                        FunctionOutcome::Query(query_outcome)
+                    },
+                    outcome => outcome,
+                };
+                (tx, outcome)
             },
             Err(js_err) => {
                 let query_outcome = UdfOutcome::from_error(
@@ -712,12 +948,15 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             _ => anyhow::bail!("Received non-query outcome for query"),
         };
         let stats = tx.take_stats();
+        let execution_time = start.elapsed();
+        let index_ranges = IndexRangesRead::capture_if_slow(execution_time, tx.reads().read_set());
 
         self.function_log.log_query(
             outcome.clone(),
             stats,
+            index_ranges,
             false,
-            start.elapsed(),
+            execution_time,
             caller,
             tx.usage_tracker,
             context,
@@ -734,7 +973,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         path: ComponentFunctionPath,
         arguments: Vec<JsonValue>,
         identity: Identity,
-        mutation_identifier: Option<SessionRequestIdentifier>,
+        mutation_identifier: Option<MutationIdentifier>,
         caller: FunctionCaller,
         pause_client: PauseClient,
     ) -> anyhow::Result<Result<MutationReturn, MutationError>> {
@@ -757,6 +996,39 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         result
     }
 
+    /// Runs a mutation once and discards its writes instead of committing
+    /// them, e.g. to compare its result against another deployment's.
+    #[minitrace::trace]
+    pub async fn run_mutation_without_commit(
+        &self,
+        request_id: RequestId,
+        path: ComponentFunctionPath,
+        arguments: Vec<JsonValue>,
+        identity: Identity,
+        caller: FunctionCaller,
+    ) -> anyhow::Result<Result<ConvexValue, JsError>> {
+        if path.udf_path.is_system() && !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("mutation"));
+        }
+        let arguments = match parse_udf_args(&path, arguments) {
+            Ok(arguments) => arguments,
+            Err(error) => return Ok(Err(error)),
+        };
+        let context = ExecutionContext::new(request_id, &caller);
+        let tx = self.database.begin(identity).await?;
+        let (tx, outcome) = self
+            .run_mutation_no_udf_log(
+                tx,
+                path.canonicalize(),
+                arguments,
+                caller.allowed_visibility(),
+                context,
+            )
+            .await?;
+        drop(tx);
+        Ok(outcome.result.map(|value| value.unpack()))
+    }
+
     /// Runs a mutations and retries on OCC errors.
     #[minitrace::trace]
     async fn _retry_mutation(
@@ -765,7 +1037,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         path: ComponentFunctionPath,
         arguments: Vec<JsonValue>,
         identity: Identity,
-        mutation_identifier: Option<SessionRequestIdentifier>,
+        mutation_identifier: Option<MutationIdentifier>,
         caller: FunctionCaller,
         mut pause_client: PauseClient,
     ) -> anyhow::Result<Result<MutationReturn, MutationError>> {
@@ -783,6 +1055,12 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         };
         let path = path.canonicalize();
         let udf_path_string = (!path.udf_path.is_system()).then_some(path.udf_path.to_string());
+        let request_hash = match mutation_identifier {
+            Some(MutationIdentifier::IdempotencyKey(_)) => {
+                Some(mutation_request_hash(&path, &arguments)?)
+            },
+            _ => None,
+        };
 
         let mut backoff = Backoff::new(
             *UDF_EXECUTOR_OCC_INITIAL_BACKOFF,
@@ -805,7 +1083,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
 
             // Return the previous execution's result if the mutation was committed already.
             if let Some(result) = self
-                .check_mutation_status(&mut tx, &mutation_identifier)
+                .check_mutation_status(&mut tx, &mutation_identifier, request_hash.as_ref())
                 .await?
             {
                 return Ok(result);
@@ -838,11 +1116,18 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
 
             // Save a CommittedMutation object so we won't rerun this mutation if
             // successful.
-            self.write_mutation_status(&mut tx, &mutation_identifier, &outcome)
-                .await?;
+            self.write_mutation_status(
+                &mut tx,
+                &mutation_identifier,
+                request_hash.as_ref(),
+                &outcome,
+            )
+            .await?;
 
             let stats = tx.take_stats();
             let execution_time = start.elapsed();
+            let index_ranges =
+                IndexRangesRead::capture_if_slow(execution_time, tx.reads().read_set());
             let log_lines = outcome.log_lines.clone();
             let value = match outcome.result {
                 Ok(ref value) => value.clone(),
@@ -853,6 +1138,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     self.function_log.log_mutation(
                         outcome.clone(),
                         stats,
+                        index_ranges,
                         execution_time,
                         caller,
                         usage_tracker,
@@ -906,6 +1192,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                             self.function_log.log_mutation_occ_error(
                                 outcome,
                                 stats,
+                                index_ranges,
                                 execution_time,
                                 caller,
                                 context.clone(),
@@ -924,6 +1211,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             self.function_log.log_mutation(
                 outcome.clone(),
                 stats,
+                index_ranges,
                 execution_time,
                 caller,
                 usage_tracker,
@@ -991,7 +1279,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         )
         .await?;
 
-        let (path_and_args, returns_validator) = match validate_result {
+        let (path_and_args, returns_validator, errors_validator) = match validate_result {
             Ok(tuple) => tuple,
             Err(js_err) => {
                 let mutation_outcome = ValidatedUdfOutcome::from_error(
@@ -1006,6 +1294,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             },
         };
 
+        tx.record_writes_for_triggers();
         let (mut tx, outcome) = self
             .isolate_functions
             .execute_query_or_mutation(
@@ -1013,7 +1302,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 path_and_args,
                 UdfType::Mutation,
                 QueryJournal::new(),
-                context,
+                context.clone(),
             )
             .await?;
         let mutation_outcome = match outcome {
@@ -1027,13 +1316,26 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         let table_mapping = tx.table_mapping().namespace(component.into());
         let virtual_table_mapping = tx.virtual_table_mapping().namespace(component.into());
 
-        let outcome = ValidatedUdfOutcome::new(
+        let mut outcome = ValidatedUdfOutcome::new(
             mutation_outcome,
             returns_validator,
+            errors_validator,
             &table_mapping,
             &virtual_table_mapping,
         );
 
+        // Run the triggers for the mutation's writes before it commits.
+        if outcome.result.is_ok() {
+            let trigger_runner =
+                MutationTriggerRunner::new(&self.isolate_functions, path.component, context);
+            let trigger_result;
+            (tx, trigger_result) = run_triggers(tx, component.into(), &trigger_runner).await?;
+            outcome.log_lines.extend(trigger_runner.into_log_lines());
+            if let Err(error) = trigger_result {
+                outcome.result = Err(error);
+            }
+        }
+
         Ok((tx, outcome))
     }
 
@@ -1168,9 +1470,9 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
             .component_path_to_ids(path.component.clone())
             .await?;
 
-        // Fetch the returns_validator now to be used at a later ts.
-        let (path_and_args, returns_validator) = match validate_result {
-            Ok((path_and_args, returns_validator)) => (path_and_args, returns_validator),
+        // Fetch the returns and errors validators now to be used at a later ts.
+        let (path_and_args, returns_validator, errors_validator) = match validate_result {
+            Ok(tuple) => tuple,
             Err(js_error) => {
                 return Ok(ActionCompletion {
                     outcome: ValidatedActionOutcome::from_error(
@@ -1238,6 +1540,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     ValidatedActionOutcome::new(
                         outcome,
                         returns_validator,
+                        errors_validator,
                         &table_mapping,
                         &virtual_table_mapping,
                     )
@@ -1256,6 +1559,11 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 })
             },
             ModuleEnvironment::Node => {
+                // Isolate actions check their flag in the function router, but Node
+                // actions don't go through it.
+                self.isolate_functions
+                    .check_function_flag(tx.identity(), &path, *tx.begin_timestamp())
+                    .await?;
                 // We should not be missing the module given we validated the path above
                 // which requires the module to exist.
                 let module_path = BootstrapComponentsModel::new(&mut tx)
@@ -1282,8 +1590,16 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     .into_value();
                 let mut environment_variables =
                     EnvironmentVariablesModel::new(&mut tx).get_all().await?;
+                self.secret_resolver
+                    .resolve(
+                        &*self.fetch_client,
+                        &mut environment_variables,
+                        self.runtime.system_time(),
+                    )
+                    .await?;
                 // Insert special environment variables if not already provided by user
                 environment_variables.extend(self.system_env_vars.clone());
+                let egress_policy = EgressPolicyModel::new(&mut tx).get().await?;
 
                 // Fetch source and external_deps presigned URI first
                 let source_uri_future = self
@@ -1329,6 +1645,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     user_identity: tx.user_identity(),
                     auth_header: token_to_authorization_header(tx.authentication_token())?,
                     environment_variables,
+                    egress_policy,
                     callback_token: self.key_broker.issue_action_token(),
                     context: context.clone(),
                     encoded_parent_trace: EncodedSpan::from_parent().0,
@@ -1380,6 +1697,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     let outcome = ValidatedActionOutcome::new(
                         outcome,
                         returns_validator,
+                        errors_validator,
                         &table_mapping,
                         &virtual_table_mapping,
                     );
@@ -1471,7 +1789,9 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                 Err(e) => return Ok(isolate::HttpActionResult::Error(e)),
             };
         let unix_timestamp = self.runtime.unix_timestamp();
-        let context = ExecutionContext::new(request_id, &caller);
+        let mut context = ExecutionContext::new(request_id, &caller);
+        // Join the trace of the client calling the HTTP action.
+        context.trace_parent = TraceParent::from_headers(&http_request.head.headers);
 
         let request_head = http_request.head.clone();
         let route = http_request.head.route_for_failure();
@@ -1850,6 +2170,26 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
         self.node_actions.enable()
     }
 
+    /// Receive a chunk of the result of a running Node action, which the
+    /// executor sends when the result is too large for its response.
+    pub fn append_action_result_chunk(
+        &self,
+        execution_id: &ExecutionId,
+        index: usize,
+        chunk: String,
+    ) -> anyhow::Result<()> {
+        self.node_actions
+            .append_result_chunk(execution_id, index, chunk)
+    }
+
+    /// Drop cached results of queries with a TTL, or only those of `path`.
+    pub fn invalidate_query_cache(
+        &self,
+        path: Option<&CanonicalizedComponentFunctionPath>,
+    ) -> usize {
+        self.cache_manager.invalidate_ttl_results(path)
+    }
+
     #[minitrace::trace]
     pub async fn run_query_at_ts(
         &self,
@@ -1905,6 +2245,7 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
                     log_lines: vec![].into(),
                     token: Token::empty(ts),
                     journal: QueryJournal::new(),
+                    cache_expiry: None,
                 });
             },
         };
@@ -1931,14 +2272,41 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
     async fn check_mutation_status(
         &self,
         tx: &mut Transaction<RT>,
-        mutation_identifier: &Option<SessionRequestIdentifier>,
+        mutation_identifier: &Option<MutationIdentifier>,
+        request_hash: Option<&Sha256Digest>,
     ) -> anyhow::Result<Option<Result<MutationReturn, MutationError>>> {
         let Some(ref identifier) = mutation_identifier else {
             return Ok(None);
         };
-        let mutation_status = SessionRequestModel::new(tx)
-            .get_session_request_record(identifier, Identity::system())
-            .await?;
+        let mutation_status = match identifier {
+            MutationIdentifier::Session(identifier) => {
+                SessionRequestModel::new(tx)
+                    .get_session_request_record(identifier, Identity::system())
+                    .await?
+            },
+            MutationIdentifier::IdempotencyKey(key) => {
+                let identity = tx.inert_identity();
+                let record = IdempotencyKeyModel::new(tx)
+                    .get_record(key, Identity::system())
+                    .await?;
+                match record {
+                    Some((ts, record)) => {
+                        if record.identity != identity || Some(&record.request_hash) != request_hash
+                        {
+                            anyhow::bail!(ErrorMetadata::bad_request(
+                                "IdempotencyKeyReused",
+                                format!(
+                                    "Idempotency key {key} was already used for a different \
+                                     request"
+                                ),
+                            ));
+                        }
+                        Some((ts, record.outcome))
+                    },
+                    None => None,
+                }
+            },
+        };
         let result = match mutation_status {
             Some((ts, SessionRequestOutcome::Mutation { result, log_lines })) => {
                 tracing::info!("Mutation already executed so skipping {:?}", identifier);
@@ -1958,30 +2326,65 @@ impl<RT: Runtime> ApplicationFunctionRunner<RT> {
     async fn write_mutation_status(
         &self,
         tx: &mut Transaction<RT>,
-        mutation_identifier: &Option<SessionRequestIdentifier>,
+        mutation_identifier: &Option<MutationIdentifier>,
+        request_hash: Option<&Sha256Digest>,
         outcome: &ValidatedUdfOutcome,
     ) -> anyhow::Result<()> {
         let Some(ref identifier) = mutation_identifier else {
             return Ok(());
         };
         if let Ok(ref value) = outcome.result {
-            let record = SessionRequestRecord {
-                session_id: identifier.session_id,
-                request_id: identifier.request_id,
-                outcome: SessionRequestOutcome::Mutation {
-                    result: value.unpack(),
-                    log_lines: outcome.log_lines.clone(),
-                },
-                identity: outcome.identity.clone(),
+            let outcome_record = SessionRequestOutcome::Mutation {
+                result: value.unpack(),
+                log_lines: outcome.log_lines.clone(),
             };
-            SessionRequestModel::new(tx)
-                .record_session_request(record, Identity::system())
-                .await?;
+            match identifier {
+                MutationIdentifier::Session(identifier) => {
+                    let record = SessionRequestRecord {
+                        session_id: identifier.session_id,
+                        request_id: identifier.request_id,
+                        outcome: outcome_record,
+                        identity: outcome.identity.clone(),
+                    };
+                    SessionRequestModel::new(tx)
+                        .record_session_request(record, Identity::system())
+                        .await?;
+                },
+                MutationIdentifier::IdempotencyKey(key) => {
+                    let record = IdempotencyKeyRecord {
+                        key: key.clone(),
+                        request_hash: request_hash
+                            .context("Missing request hash for idempotency key")?
+                            .clone(),
+                        identity: outcome.identity.clone(),
+                        outcome: outcome_record,
+                        created_ts: self.runtime.generate_timestamp()?,
+                    };
+                    IdempotencyKeyModel::new(tx)
+                        .record(record, Identity::system())
+                        .await?;
+                },
+            }
         }
         Ok(())
     }
 }
 
+/// Hash identifying a mutation request, so an idempotency key can't be reused
+/// to return the result of a different function or arguments.
+fn mutation_request_hash(
+    path: &CanonicalizedComponentFunctionPath,
+    arguments: &ConvexArray,
+) -> anyhow::Result<Sha256Digest> {
+    let mut hasher = Sha256::new();
+    hasher.update(String::from(path.component.clone()).as_bytes());
+    hasher.update(&[0]);
+    hasher.update(path.udf_path.to_string().as_bytes());
+    hasher.update(&[0]);
+    hasher.update(&serde_json::to_vec(&JsonValue::from(arguments.clone()))?);
+    Ok(hasher.finalize())
+}
+
 #[async_trait]
 impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
     #[minitrace::trace]
@@ -1995,7 +2398,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         let ts = self.database.now_ts_for_reads();
         let result = self
             .run_query_at_ts(
-                context.request_id,
+                context.request_id.clone(),
                 path,
                 args,
                 identity,
@@ -2003,6 +2406,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                 None,
                 FunctionCaller::Action {
                     parent_scheduled_job: context.parent_scheduled_job,
+                    trace_parent: Some(context.traceparent()),
                 },
             )
             .await?
@@ -2020,13 +2424,14 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
     ) -> anyhow::Result<FunctionResult> {
         let result = self
             .retry_mutation(
-                context.request_id,
+                context.request_id.clone(),
                 path,
                 args,
                 identity,
                 None,
                 FunctionCaller::Action {
                     parent_scheduled_job: context.parent_scheduled_job,
+                    trace_parent: Some(context.traceparent()),
                 },
                 PauseClient::new(),
             )
@@ -2049,12 +2454,13 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         let _tx = self.database.begin(identity.clone()).await?;
         let result = self
             .run_action(
-                context.request_id,
+                context.request_id.clone(),
                 path,
                 args,
                 identity,
                 FunctionCaller::Action {
                     parent_scheduled_job: context.parent_scheduled_job,
+                    trace_parent: Some(context.traceparent()),
                 },
             )
             .await
@@ -2144,12 +2550,48 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         Ok(())
     }
 
+    async fn storage_set_metadata(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        tags: Option<BTreeSet<String>>,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<()> {
+        self.database
+            .execute_with_occ_retries(
+                identity,
+                FunctionUsageTracker::new(),
+                PauseClient::new(),
+                "app_funrun_storage_set_metadata",
+                |tx| {
+                    async {
+                        self.file_storage
+                            .set_file_metadata(
+                                tx,
+                                component.into(),
+                                storage_id.clone(),
+                                tags.clone(),
+                                metadata.clone(),
+                            )
+                            .await?;
+                        Ok(())
+                    }
+                    .into()
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
     async fn schedule_job(
         &self,
         identity: Identity,
         path: ComponentFunctionPath,
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let (_ts, virtual_id, _stats) = self
@@ -2178,7 +2620,7 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
                             .component_path_to_ids(path.component.clone())
                             .await?;
                         let virtual_id = VirtualSchedulerModel::new(tx, component.into())
-                            .schedule(path.udf_path, udf_args, scheduled_ts, context)
+                            .schedule(path.udf_path, udf_args, scheduled_ts, options, context)
                             .await?;
                         Ok(virtual_id)
                     }
@@ -2224,4 +2666,84 @@ impl<RT: Runtime> ActionCallbacks for ApplicationFunctionRunner<RT> {
         })?;
         self.database.vector_search(identity, query).await
     }
+
+    async fn load_workflow_step(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        workflow_id: DeveloperDocumentId,
+        step_name: String,
+    ) -> anyhow::Result<Option<ConvexValue>> {
+        // Starting a step declares the action a workflow, so it's restarted if
+        // this step fails.
+        let (_, step, _) = self
+            .database
+            .execute_with_occ_retries(
+                identity,
+                FunctionUsageTracker::new(),
+                PauseClient::new(),
+                "app_funrun_load_workflow_step",
+                |tx| {
+                    let step_name = step_name.clone();
+                    async move {
+                        let mut model = WorkflowModel::new(tx, component.into());
+                        model.declare(workflow_id).await?;
+                        let step = model.get_step(workflow_id, &step_name).await?;
+                        Ok(step.map(|step| step.into_value().result))
+                    }
+                    .into()
+                },
+            )
+            .await?;
+        Ok(step)
+    }
+
+    async fn checkpoint_workflow_step(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        workflow_id: DeveloperDocumentId,
+        step_name: String,
+        result: ConvexValue,
+    ) -> anyhow::Result<()> {
+        self.database
+            .execute_with_occ_retries(
+                identity,
+                FunctionUsageTracker::new(),
+                PauseClient::new(),
+                "app_funrun_checkpoint_workflow_step",
+                |tx| {
+                    let step_name = step_name.clone();
+                    let result = result.clone();
+                    async move {
+                        WorkflowModel::new(tx, component.into())
+                            .checkpoint_step(workflow_id, step_name, result)
+                            .await
+                    }
+                    .into()
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn table_changes(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        table: TableName,
+        cursor: Option<Timestamp>,
+        limit: usize,
+    ) -> anyhow::Result<TableChanges> {
+        self.database
+            .table_changes(
+                identity,
+                component.into(),
+                table,
+                cursor,
+                *TABLE_CHANGES_ROWS_READ_LIMIT,
+                limit,
+            )
+            .await
+    }
 }
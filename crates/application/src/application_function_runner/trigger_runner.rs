@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    document::{
+        DocumentUpdate,
+        ResolvedDocument,
+    },
+    errors::JsError,
+    execution_context::ExecutionContext,
+    log_lines::LogLines,
+    query_journal::QueryJournal,
+    runtime::Runtime,
+    schemas::TriggerSchema,
+    types::{
+        AllowedVisibility,
+        UdfType,
+    },
+    value::ConvexArray,
+};
+use database::{
+    triggers::TriggerRunner,
+    Transaction,
+};
+use isolate::{
+    FunctionOutcome,
+    ValidatedPathAndArgs,
+};
+use parking_lot::Mutex;
+use value::{
+    id_v6::DeveloperDocumentId,
+    obj,
+    ConvexValue,
+};
+
+use super::FunctionRouter;
+
+/// Runs the triggers for a mutation's writes as mutations in its component,
+/// collecting their log lines so they can be shown with the mutation's.
+pub(crate) struct MutationTriggerRunner<'a, RT: Runtime> {
+    function_router: &'a FunctionRouter<RT>,
+    component: ComponentPath,
+    context: ExecutionContext,
+    log_lines: Mutex<LogLines>,
+}
+
+impl<'a, RT: Runtime> MutationTriggerRunner<'a, RT> {
+    pub(crate) fn new(
+        function_router: &'a FunctionRouter<RT>,
+        component: ComponentPath,
+        context: ExecutionContext,
+    ) -> Self {
+        Self {
+            function_router,
+            component,
+            context,
+            log_lines: Mutex::new(LogLines::default()),
+        }
+    }
+
+    pub(crate) fn into_log_lines(self) -> LogLines {
+        self.log_lines.into_inner()
+    }
+}
+
+#[async_trait]
+impl<'a, RT: Runtime> TriggerRunner<RT> for MutationTriggerRunner<'a, RT> {
+    async fn run_trigger(
+        &self,
+        mut tx: Transaction<RT>,
+        trigger: &TriggerSchema,
+        change: DocumentUpdate,
+    ) -> anyhow::Result<(Transaction<RT>, Result<(), JsError>)> {
+        let path = CanonicalizedComponentFunctionPath {
+            component: self.component.clone(),
+            udf_path: trigger.function.clone(),
+        };
+        let document_value = |document: Option<ResolvedDocument>| match document {
+            Some(document) => ConvexValue::Object(document.to_developer().into_value().0),
+            None => ConvexValue::Null,
+        };
+        let arguments = obj!(
+            "id" => DeveloperDocumentId::from(change.id),
+            "oldDoc" => document_value(change.old_document),
+            "newDoc" => document_value(change.new_document),
+        )?;
+        let arguments = ConvexArray::try_from(vec![ConvexValue::Object(arguments)])?;
+        // Triggers are usually internal functions, which only the deployment
+        // itself can call.
+        let path_and_args = match ValidatedPathAndArgs::new(
+            AllowedVisibility::All,
+            &mut tx,
+            path,
+            arguments,
+            UdfType::Mutation,
+        )
+        .await?
+        {
+            Ok(path_and_args) => path_and_args,
+            Err(error) => return Ok((tx, Err(error))),
+        };
+        let (tx, outcome) = self
+            .function_router
+            .execute_query_or_mutation(
+                tx,
+                path_and_args,
+                UdfType::Mutation,
+                QueryJournal::new(),
+                self.context.clone(),
+            )
+            .await?;
+        let FunctionOutcome::Mutation(outcome) = outcome else {
+            anyhow::bail!(
+                "Received non-mutation outcome for trigger {}",
+                trigger.function
+            );
+        };
+        self.log_lines.lock().extend(outcome.log_lines);
+        Ok((tx, outcome.result.map(|_| ())))
+    }
+}
@@ -0,0 +1,330 @@
+//! Built-in auth, which signs users in with an email address and password or
+//! with a magic link, and issues them tokens signed by the deployment itself.
+//! This lets apps on self-hosted deployments log users in without a
+//! third-party auth provider.
+//!
+//! Emails are sent by the app's `auth:sendEmail` action, which is scheduled
+//! with `{ to, type, code }`, where `type` is `"verifyEmail"` or
+//! `"magicLink"`. The app builds its link from `code`, and the page it links
+//! to passes the code back to `verify_email` or `sign_in_with_magic_link`.
+
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use common::{
+    bounded_thread_pool::BoundedThreadPool,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    execution_context::ExecutionContext,
+    knobs::{
+        BUILT_IN_AUTH_CODE_TTL,
+        BUILT_IN_AUTH_PASSWORD_THREADS,
+        BUILT_IN_AUTH_TOKEN_TTL,
+    },
+    obj,
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::{
+        FunctionCaller,
+        UdfType,
+    },
+    RequestId,
+};
+use database::Transaction;
+use errors::ErrorMetadata;
+use keybroker::{
+    password::{
+        hash_password,
+        verify_password,
+    },
+    Identity,
+};
+use model::{
+    built_in_auth::{
+        types::{
+            validate_password,
+            AuthAccount,
+            AuthCodeKind,
+            AuthEmail,
+        },
+        BuiltInAuthModel,
+    },
+    modules::ModuleModel,
+    scheduled_jobs::{
+        types::ScheduleOptions,
+        SchedulerModel,
+    },
+};
+use sync_types::{
+    CanonicalizedUdfPath,
+    UdfPath,
+};
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexArray,
+    ConvexValue,
+    TableNamespace,
+};
+
+use crate::Application;
+
+/// The action that built-in auth schedules to send emails.
+pub static SEND_EMAIL_FUNCTION: LazyLock<CanonicalizedUdfPath> = LazyLock::new(|| {
+    "auth:sendEmail"
+        .parse::<UdfPath>()
+        .expect("Invalid built-in auth email function")
+        .canonicalize()
+});
+
+/// Maximum number of password hashes waiting for a thread.
+const PASSWORD_QUEUE_SIZE: usize = 1000;
+
+/// A password hash that's checked, and then ignored, when signing into an
+/// account that doesn't exist or has no password. This makes those sign-ins
+/// take as long as ones with the wrong password, so their timing doesn't reveal
+/// which email addresses have accounts.
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    hash_password("built-in auth dummy password").expect("Failed to hash dummy password")
+});
+
+/// Hashes and checks passwords on a bounded thread pool, since Argon2 is
+/// deliberately slow and memory hungry and would otherwise block the async
+/// executor.
+#[derive(Clone)]
+pub struct PasswordHasher<RT: Runtime> {
+    thread_pool: BoundedThreadPool<RT>,
+}
+
+impl<RT: Runtime> PasswordHasher<RT> {
+    pub fn new(rt: RT) -> Self {
+        Self {
+            thread_pool: BoundedThreadPool::new(
+                rt,
+                PASSWORD_QUEUE_SIZE,
+                *BUILT_IN_AUTH_PASSWORD_THREADS,
+                "built_in_auth_password",
+            ),
+        }
+    }
+
+    async fn hash(&self, password: &str) -> anyhow::Result<String> {
+        let password = password.to_string();
+        self.thread_pool
+            .execute(move || hash_password(&password))
+            .await?
+    }
+
+    /// Check `password` against `hash`, or against a dummy hash if there's
+    /// no hash to check so that the check takes just as long.
+    async fn verify(&self, hash: Option<String>, password: &str) -> anyhow::Result<bool> {
+        let password = password.to_string();
+        self.thread_pool
+            .execute(move || match hash {
+                Some(hash) => verify_password(&hash, &password),
+                None => {
+                    verify_password(&DUMMY_PASSWORD_HASH, &password);
+                    false
+                },
+            })
+            .await
+    }
+}
+
+/// A signed-in session of a built-in auth user. Clients send `token` as their
+/// auth token and refresh it before `expires_at`.
+#[derive(Clone, Debug)]
+pub struct BuiltInAuthSession {
+    pub token: String,
+    pub expires_at: UnixTimestamp,
+}
+
+impl<RT: Runtime> Application<RT> {
+    /// Create an account with a password and sign into it. Sends an email to
+    /// verify the account's email address if the app can send emails.
+    pub async fn sign_up(&self, email: &str, password: &str) -> anyhow::Result<BuiltInAuthSession> {
+        let email: AuthEmail = email.parse()?;
+        validate_password(password)?;
+        let account = AuthAccount {
+            email: email.clone(),
+            password_hash: Some(self.password_hasher.hash(password).await?),
+            email_verified: false,
+        };
+        let mut tx = self.begin(Identity::system()).await?;
+        let account_id: DeveloperDocumentId = BuiltInAuthModel::new(&mut tx)
+            .create_account(account.clone())
+            .await?
+            .into();
+        if Self::can_send_email(&mut tx).await? {
+            self.send_auth_email(&mut tx, account_id, &email, AuthCodeKind::VerifyEmail)
+                .await?;
+        }
+        self.commit(tx, "built_in_auth_sign_up").await?;
+        self.issue_session(account_id, &account)
+    }
+
+    pub async fn sign_in(&self, email: &str, password: &str) -> anyhow::Result<BuiltInAuthSession> {
+        let email: AuthEmail = email.parse()?;
+        let mut tx = self.begin(Identity::system()).await?;
+        let account = BuiltInAuthModel::new(&mut tx)
+            .get_account_by_email(&email)
+            .await?;
+        let password_hash = account
+            .as_ref()
+            .and_then(|account| account.password_hash.clone());
+        let is_valid = self.password_hasher.verify(password_hash, password).await?;
+        let Some(account) = account.filter(|_| is_valid) else {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "InvalidCredentials",
+                "The email address or password is incorrect",
+            ));
+        };
+        self.issue_session(account.id().into(), &account)
+    }
+
+    /// Email a magic link for signing in, creating an account without a
+    /// password for `email` if there isn't one yet.
+    pub async fn send_magic_link(&self, email: &str) -> anyhow::Result<()> {
+        let email: AuthEmail = email.parse()?;
+        let mut tx = self.begin(Identity::system()).await?;
+        if !Self::can_send_email(&mut tx).await? {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "BuiltInAuthEmailNotConfigured",
+                format!(
+                    "Magic links need an action named {} to send emails",
+                    String::from(SEND_EMAIL_FUNCTION.clone().strip())
+                ),
+            ));
+        }
+        let mut model = BuiltInAuthModel::new(&mut tx);
+        let account_id = match model.get_account_by_email(&email).await? {
+            Some(account) => account.id(),
+            None => {
+                model
+                    .create_account(AuthAccount {
+                        email: email.clone(),
+                        password_hash: None,
+                        email_verified: false,
+                    })
+                    .await?
+            },
+        };
+        self.send_auth_email(&mut tx, account_id.into(), &email, AuthCodeKind::MagicLink)
+            .await?;
+        self.commit(tx, "built_in_auth_send_magic_link").await?;
+        Ok(())
+    }
+
+    /// Verify an account's email address with the code emailed to it, and
+    /// sign into the account.
+    pub async fn verify_email(&self, code: &str) -> anyhow::Result<BuiltInAuthSession> {
+        self.redeem_auth_code(code, AuthCodeKind::VerifyEmail).await
+    }
+
+    /// Sign in with the code from a magic link. This also verifies the
+    /// account's email address, since the user received the link.
+    pub async fn sign_in_with_magic_link(&self, code: &str) -> anyhow::Result<BuiltInAuthSession> {
+        self.redeem_auth_code(code, AuthCodeKind::MagicLink).await
+    }
+
+    /// Issue a new token for a signed-in built-in auth user.
+    pub async fn refresh_session(&self, identity: &Identity) -> anyhow::Result<BuiltInAuthSession> {
+        let account_id = match identity {
+            Identity::User(user)
+                if user.issuer.trim_end_matches('/') == self.convex_site.trim_end_matches('/') =>
+            {
+                DeveloperDocumentId::decode(&user.subject)?
+            },
+            _ => anyhow::bail!(ErrorMetadata::unauthenticated(
+                "NotBuiltInAuthUser",
+                "Only users signed in with built-in auth can refresh their session",
+            )),
+        };
+        let mut tx = self.begin(Identity::system()).await?;
+        let account = BuiltInAuthModel::new(&mut tx)
+            .get_account(account_id)
+            .await?
+            .context(ErrorMetadata::unauthenticated(
+                "AccountNotFound",
+                "The signed-in user's account no longer exists",
+            ))?;
+        self.issue_session(account_id, &account)
+    }
+
+    async fn redeem_auth_code(
+        &self,
+        code: &str,
+        kind: AuthCodeKind,
+    ) -> anyhow::Result<BuiltInAuthSession> {
+        let mut tx = self.begin(Identity::system()).await?;
+        let mut model = BuiltInAuthModel::new(&mut tx);
+        let account_id = model.redeem_code(code, kind).await?;
+        model.mark_email_verified(account_id).await?;
+        let account = model
+            .get_account(account_id)
+            .await?
+            .context("Auth code for missing account")?;
+        self.commit(tx, "built_in_auth_redeem_code").await?;
+        self.issue_session(account_id, &account)
+    }
+
+    /// Whether the app has an action for sending built-in auth emails.
+    async fn can_send_email(tx: &mut Transaction<RT>) -> anyhow::Result<bool> {
+        let path = CanonicalizedComponentFunctionPath {
+            component: ComponentPath::root(),
+            udf_path: SEND_EMAIL_FUNCTION.clone(),
+        };
+        Ok(ModuleModel::new(tx)
+            .get_analyzed_function(&path)
+            .await?
+            .is_ok_and(|function| function.udf_type == UdfType::Action))
+    }
+
+    async fn send_auth_email(
+        &self,
+        tx: &mut Transaction<RT>,
+        account_id: DeveloperDocumentId,
+        email: &AuthEmail,
+        kind: AuthCodeKind,
+    ) -> anyhow::Result<()> {
+        let code = BuiltInAuthModel::new(tx)
+            .create_code(account_id, kind, *BUILT_IN_AUTH_CODE_TTL)
+            .await?;
+        let args = ConvexArray::try_from(vec![ConvexValue::Object(obj!(
+            "to" => email.to_string(),
+            "type" => kind.as_str(),
+            "code" => code,
+        )?)])?;
+        SchedulerModel::new(tx, TableNamespace::by_component_TODO())
+            .schedule(
+                SEND_EMAIL_FUNCTION.clone().into(),
+                args,
+                self.runtime.unix_timestamp(),
+                ScheduleOptions::default(),
+                ExecutionContext::new(RequestId::new(), &FunctionCaller::HttpEndpoint),
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn issue_session(
+        &self,
+        account_id: DeveloperDocumentId,
+        account: &AuthAccount,
+    ) -> anyhow::Result<BuiltInAuthSession> {
+        let issued_at = self.runtime.unix_timestamp();
+        let expires_at = issued_at + *BUILT_IN_AUTH_TOKEN_TTL;
+        let token = self.key_broker.issue_user_token(
+            self.convex_site.as_str(),
+            account_id.encode(),
+            account.email.to_string(),
+            account.email_verified,
+            issued_at.as_system_time(),
+            expires_at.as_system_time(),
+        )?;
+        Ok(BuiltInAuthSession { token, expires_at })
+    }
+}
@@ -156,6 +156,22 @@ pub fn log_validate_system_time_in_the_future() {
     log_counter(&CACHE_VALIDATE_SYSTEM_TIME_IN_THE_FUTURE_TOTAL, 1);
 }
 
+register_convex_counter!(
+    CACHE_VALIDATE_SERVE_STALE_TOTAL,
+    "Number of times an outdated cache entry was served because it's within its query's TTL"
+);
+pub fn log_validate_serve_stale() {
+    log_counter(&CACHE_VALIDATE_SERVE_STALE_TOTAL, 1);
+}
+
+register_convex_counter!(
+    CACHE_TTL_RESULTS_INVALIDATED_TOTAL,
+    "Number of cached results of queries with a TTL that were invalidated on demand"
+);
+pub fn log_ttl_results_invalidated(count: usize) {
+    log_counter(&CACHE_TTL_RESULTS_INVALIDATED_TOTAL, count as u64);
+}
+
 register_convex_gauge!(CACHE_SIZE_BYTES, "Size of the cache in bytes");
 pub fn log_cache_size(size: usize) {
     log_gauge(&CACHE_SIZE_BYTES, size as f64)
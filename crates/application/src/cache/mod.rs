@@ -47,6 +47,11 @@ use database::{
 };
 use errors::ErrorMetadataAnyhowExt;
 use futures::{
+    channel::oneshot,
+    future::{
+        BoxFuture,
+        Shared,
+    },
     select_biased,
     FutureExt,
 };
@@ -69,13 +74,16 @@ use metrics::{
     log_plan_ready,
     log_plan_wait,
     log_success,
+    log_ttl_results_invalidated,
     log_validate_refresh_failed,
+    log_validate_serve_stale,
     log_validate_system_time_in_the_future,
     log_validate_system_time_too_old,
     log_validate_ts_too_old,
     succeed_get_timer,
     GoReason,
 };
+use model::modules::ModuleModel;
 use parking_lot::Mutex;
 use usage_tracking::FunctionUsageTracker;
 use value::{
@@ -85,7 +93,11 @@ use value::{
 
 use crate::{
     application_function_runner::FunctionRouter,
-    function_log::FunctionExecutionLog,
+    function_log::{
+        FunctionExecutionLog,
+        IndexRangesRead,
+    },
+    QueryCacheExpiry,
     QueryReturn,
 };
 
@@ -105,6 +117,24 @@ pub struct CacheManager<RT: Runtime> {
     udf_execution: FunctionExecutionLog<RT>,
 
     cache: Cache<RT>,
+    ttl_invalidation: Arc<Mutex<TtlInvalidation>>,
+}
+
+/// Signals subscribers waiting for outdated results of queries with a TTL to
+/// expire that the results were invalidated on demand.
+struct TtlInvalidation {
+    sender: oneshot::Sender<()>,
+    invalidated: Shared<BoxFuture<'static, ()>>,
+}
+
+impl TtlInvalidation {
+    fn new() -> Self {
+        let (sender, receiver) = oneshot::channel();
+        Self {
+            sender,
+            invalidated: receiver.map(|_| ()).boxed().shared(),
+        }
+    }
 }
 
 impl<RT: Runtime> HeapSize for CacheManager<RT> {
@@ -180,6 +210,8 @@ struct CacheResult {
     outcome: UdfOutcome,
     original_ts: Timestamp,
     token: Token,
+    // The query's declared TTL, if it opted into serving outdated results.
+    ttl: Option<Duration>,
 }
 
 impl HeapSize for CacheResult {
@@ -201,9 +233,27 @@ impl<RT: Runtime> CacheManager<RT> {
             function_router,
             udf_execution,
             cache: Cache::new(),
+            ttl_invalidation: Arc::new(Mutex::new(TtlInvalidation::new())),
         }
     }
 
+    /// Drop cached results of queries with a TTL, so the next request for
+    /// them recomputes the result and subscribers waiting for them to expire
+    /// rerun the query. Only drops results of `path` if it's given. Returns
+    /// the number of results dropped.
+    pub fn invalidate_ttl_results(
+        &self,
+        path: Option<&CanonicalizedComponentFunctionPath>,
+    ) -> usize {
+        let count = self.cache.remove_ttl_results(path);
+        // Wake up all waiting subscribers, even those of other queries: they
+        // just rerun the query and keep waiting if its result is still cached.
+        let invalidation = mem::replace(&mut *self.ttl_invalidation.lock(), TtlInvalidation::new());
+        let _: Result<_, _> = invalidation.sender.send(());
+        log_ttl_results_invalidated(count);
+        count
+    }
+
     /// Execute a UDF with the given arguments and identity at a particular
     /// timestamp. This function internally handles LRU caching these
     /// function executions and ensuring that served cache values are
@@ -266,6 +316,11 @@ impl<RT: Runtime> CacheManager<RT> {
             allowed_visibility: caller.allowed_visibility(),
         };
         let context = ExecutionContext::new(request_id, &caller);
+        // Cached results don't go through the function router, so check the
+        // function's flag here.
+        self.function_router
+            .check_function_flag(&identity, &path, ts)
+            .await?;
 
         let mut num_attempts = 0;
         'top: loop {
@@ -328,10 +383,11 @@ impl<RT: Runtime> CacheManager<RT> {
             // Step 3: Validate that the cache result we got is good enough. Is our desired
             // timestamp in its validity interval? If it looked at system time, is it not
             // too old?
-            let cache_result = match self.validate_cache_result(&key, ts, result).await? {
-                Some(r) => r,
-                None => continue 'top,
-            };
+            let (cache_result, cache_expiry) =
+                match self.validate_cache_result(&key, ts, result).await? {
+                    Some(r) => r,
+                    None => continue 'top,
+                };
 
             // Step 4: Rewrite the value into the cache. This method will discard the new
             // value if the UDF failed or if a newer (i.e. higher `original_ts`)
@@ -345,11 +401,19 @@ impl<RT: Runtime> CacheManager<RT> {
 
             // Step 5: Log some stuff and return.
             log_success(num_attempts);
+            let execution_time = start.elapsed();
+            // A cache hit's reads were captured when it first ran.
+            let index_ranges = if is_cache_hit {
+                None
+            } else {
+                IndexRangesRead::capture_if_slow(execution_time, cache_result.token.reads())
+            };
             self.udf_execution.log_query(
                 cache_result.outcome.clone(),
                 table_stats,
+                index_ranges,
                 is_cache_hit,
-                start.elapsed(),
+                execution_time,
                 caller,
                 usage_tracker,
                 context.clone(),
@@ -360,6 +424,7 @@ impl<RT: Runtime> CacheManager<RT> {
                 log_lines: cache_result.outcome.log_lines,
                 token: cache_result.token,
                 journal: cache_result.outcome.journal,
+                cache_expiry,
             };
             return Ok((result, is_cache_hit));
         }
@@ -455,7 +520,7 @@ impl<RT: Runtime> CacheManager<RT> {
                         )?;
                         (tx, query_outcome)
                     },
-                    Ok((path_and_args, returns_validator)) => {
+                    Ok((path_and_args, returns_validator, errors_validator)) => {
                         let (mut tx, outcome) = self
                             .function_router
                             .execute_query_or_mutation(
@@ -469,26 +534,43 @@ impl<RT: Runtime> CacheManager<RT> {
                         let FunctionOutcome::Query(mut query_outcome) = outcome else {
                             anyhow::bail!("Received non-query outcome when executing a query")
                         };
-                        if let Ok(ref json_packed_value) = &query_outcome.result {
-                            let output: ConvexValue = json_packed_value.unpack();
-                            let (_, component) = BootstrapComponentsModel::new(&mut tx)
-                                .component_path_to_ids(path.component.clone())
-                                .await?;
-                            let table_mapping = tx.table_mapping().namespace(component.into());
-                            let virtual_table_mapping =
-                                tx.virtual_table_mapping().namespace(component.into());
-                            let returns_validation_error = returns_validator.check_output(
-                                &output,
+                        let (_, component) = BootstrapComponentsModel::new(&mut tx)
+                            .component_path_to_ids(path.component.clone())
+                            .await?;
+                        let table_mapping = tx.table_mapping().namespace(component.into());
+                        let virtual_table_mapping =
+                            tx.virtual_table_mapping().namespace(component.into());
+                        let validation_error = match &query_outcome.result {
+                            Ok(json_packed_value) => {
+                                let output: ConvexValue = json_packed_value.unpack();
+                                returns_validator.check_output(
+                                    &output,
+                                    &table_mapping,
+                                    &virtual_table_mapping,
+                                )
+                            },
+                            Err(js_err) => errors_validator.check_error(
+                                js_err,
                                 &table_mapping,
                                 &virtual_table_mapping,
-                            );
-                            if let Some(js_err) = returns_validation_error {
-                                query_outcome.result = Err(js_err);
-                            }
+                            ),
+                        };
+                        if let Some(js_err) = validation_error {
+                            query_outcome.result = Err(js_err);
                         }
                         (tx, query_outcome)
                     },
                 };
+                let ttl = if query_outcome.result.is_ok() && !path.udf_path.is_system() {
+                    ModuleModel::new(&mut tx)
+                        .get_analyzed_function(&path)
+                        .await?
+                        .ok()
+                        .and_then(|function| function.cache)
+                        .map(|policy| policy.ttl)
+                } else {
+                    None
+                };
                 let ts = tx.begin_timestamp();
                 let table_stats = tx.take_stats();
                 let token = tx.into_token()?;
@@ -496,6 +578,7 @@ impl<RT: Runtime> CacheManager<RT> {
                     outcome: query_outcome,
                     original_ts: *ts,
                     token,
+                    ttl,
                 };
                 if result.outcome.result.is_ok() {
                     let _: Result<_, _> = sender.try_broadcast(result.clone());
@@ -507,37 +590,64 @@ impl<RT: Runtime> CacheManager<RT> {
         Ok(Some(r))
     }
 
+    /// Returns the result to serve, along with when it expires if it's
+    /// outdated but within its query's TTL.
     #[minitrace::trace]
     async fn validate_cache_result(
         &self,
         key: &CacheKey,
         ts: Timestamp,
         mut result: CacheResult,
-    ) -> anyhow::Result<Option<CacheResult>> {
+    ) -> anyhow::Result<Option<(CacheResult, Option<QueryCacheExpiry>)>> {
         if ts < result.original_ts {
             // If the cached value is newer than the requested timestamp,
             // we have to re-execute the UDF.
             log_validate_ts_too_old();
             return Ok(None);
         }
+        let entry_age = self
+            .rt
+            .unix_timestamp()
+            .checked_sub(result.outcome.unix_timestamp);
+        // Queries with a TTL tolerate outdated results until it lapses, so keep
+        // the original token around to serve the result with if it can't be
+        // refreshed.
+        let stale_fallback = match (result.ttl, entry_age) {
+            (Some(ttl), Some(entry_age)) if entry_age < ttl => {
+                Some((result.token.clone(), ttl - entry_age))
+            },
+            _ => None,
+        };
+        let mut cache_expiry = None;
         result.token = match self.database.refresh_token(result.token, ts).await? {
             Some(t) => t,
-            None => {
-                tracing::debug!(
-                    "Couldn't refresh cache entry from {} to {}, retrying...",
-                    result.original_ts,
-                    ts
-                );
-                self.cache.remove_ready(key, result.original_ts);
-                log_validate_refresh_failed();
-                return Ok(None);
+            None => match stale_fallback {
+                Some((token, expires_in)) => {
+                    log_validate_serve_stale();
+                    cache_expiry = Some(QueryCacheExpiry {
+                        expires_in,
+                        invalidated: self.ttl_invalidation.lock().invalidated.clone(),
+                    });
+                    token
+                },
+                None => {
+                    tracing::debug!(
+                        "Couldn't refresh cache entry from {} to {}, retrying...",
+                        result.original_ts,
+                        ts
+                    );
+                    self.cache.remove_ready(key, result.original_ts);
+                    log_validate_refresh_failed();
+                    return Ok(None);
+                },
             },
         };
         if result.outcome.observed_time {
             let sys_now = self.rt.unix_timestamp();
             let cached_time = result.outcome.unix_timestamp;
-            match sys_now.checked_sub(cached_time) {
-                Some(entry_age) if entry_age > MAX_CACHE_AGE => {
+            let max_age = result.ttl.unwrap_or(MAX_CACHE_AGE);
+            match entry_age {
+                Some(entry_age) if entry_age > max_age => {
                     tracing::debug!(
                         "Log entry for {:?} used system time and is too old ({:?}), retrying...",
                         key,
@@ -560,7 +670,7 @@ impl<RT: Runtime> CacheManager<RT> {
                 Some(..) => (),
             }
         }
-        Ok(Some(result))
+        Ok(Some((result, cache_expiry)))
     }
 }
 
@@ -728,6 +838,10 @@ impl<RT: Runtime> Cache<RT> {
     fn put_ready(&self, key: CacheKey, result: CacheResult) {
         self.inner.lock().put_ready(key, result)
     }
+
+    fn remove_ttl_results(&self, path: Option<&CanonicalizedComponentFunctionPath>) -> usize {
+        self.inner.lock().remove_ttl_results(path)
+    }
 }
 
 impl<RT: Runtime> HeapSize for Cache<RT> {
@@ -830,6 +944,25 @@ impl<RT: Runtime> Inner<RT> {
         self.enforce_size_limit();
     }
 
+    fn remove_ttl_results(&mut self, path: Option<&CanonicalizedComponentFunctionPath>) -> usize {
+        let keys: Vec<_> = self
+            .cache
+            .iter()
+            .filter(|(key, entry)| {
+                matches!(entry, CacheEntry::Ready(result) if result.ttl.is_some())
+                    && path.map_or(true, |path| &key.path == path)
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            if let Some(entry) = self.cache.pop(key) {
+                self.size -= key.size() + entry.size();
+            }
+        }
+        log_cache_size(self.size);
+        keys.len()
+    }
+
     /// Pop records until the cache is under the given size.
     fn enforce_size_limit(&mut self) {
         while self.size > *UDF_CACHE_MAX_SIZE {
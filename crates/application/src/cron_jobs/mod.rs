@@ -2,6 +2,7 @@ use std::{
     collections::{
         BTreeMap,
         HashSet,
+        VecDeque,
     },
     sync::Arc,
     time::Duration,
@@ -22,6 +23,7 @@ use common::{
     execution_context::ExecutionContext,
     identity::InertIdentity,
     knobs::{
+        CRON_JOB_MAX_CATCH_UP_RUNS,
         SCHEDULED_JOB_EXECUTION_PARALLELISM,
         UDF_EXECUTOR_OCC_MAX_RETRIES,
     },
@@ -35,6 +37,7 @@ use common::{
     runtime::{
         Runtime,
         RuntimeInstant,
+        UnixTimestamp,
     },
     sync::mpsc,
     types::{
@@ -66,6 +69,7 @@ use model::{
     cron_jobs::{
         next_ts::compute_next_ts,
         types::{
+            CronCatchUpPolicy,
             CronJob,
             CronJobLogLines,
             CronJobResult,
@@ -76,6 +80,14 @@ use model::{
         CRON_JOBS_INDEX_BY_NEXT_TS,
         CRON_JOBS_TABLE,
     },
+    function_executions::types::{
+        FunctionExecutionStatus,
+        FunctionExecutionUsage,
+    },
+    job_executions::types::{
+        JobExecutionRecord,
+        JobKind,
+    },
     modules::ModuleModel,
 };
 use sync_types::Timestamp;
@@ -87,7 +99,14 @@ use value::{
 
 use crate::{
     application_function_runner::ApplicationFunctionRunner,
-    function_log::FunctionExecutionLog,
+    function_log::{
+        FunctionExecutionLog,
+        IndexRangesRead,
+    },
+    job_execution_history::{
+        job_execution_usage,
+        record_job_execution,
+    },
 };
 
 mod metrics;
@@ -161,7 +180,9 @@ impl<RT: Runtime> CronJobExecutor<RT> {
         loop {
             let mut tx = self.database.begin(Identity::Unknown).await?;
             let backend_state = BackendStateModel::new(&mut tx).get_backend_state().await?;
-            let is_backend_stopped = backend_state.is_stopped();
+            // Jobs run mutations and actions, so hold them while the deployment is
+            // read-only too.
+            let is_backend_stopped = backend_state.is_stopped() || backend_state.is_read_only();
 
             next_job_ready_time = if is_backend_stopped {
                 None
@@ -344,6 +365,28 @@ impl<RT: Runtime> CronJobExecutor<RT> {
             })?
             .udf_type;
 
+        if job.state == CronJobState::Pending {
+            // If the backend was down, later runs may have come due while this
+            // one was waiting to execute.
+            let identity = tx.inert_identity();
+            let context = ExecutionContext::new(request_id.clone(), &FunctionCaller::Cron);
+            let next_ts = self
+                .apply_catch_up_policy(identity, &mut tx, job_id, &job, udf_type, context)
+                .await?;
+            if next_ts != job.next_ts {
+                let (component, _) = self.get_job_component(&mut tx, job_id).await?;
+                let mut updated_job = job;
+                updated_job.next_ts = next_ts;
+                CronModel::new(&mut tx, component)
+                    .update_job_state(job_id, updated_job)
+                    .await?;
+                self.database
+                    .commit_with_write_source(tx, "cron_catch_up")
+                    .await?;
+                return Ok(job_id);
+            }
+        }
+
         match udf_type {
             UdfType::Mutation => {
                 self.handle_mutation(request_id, tx, job, job_id, usage_tracker)
@@ -424,6 +467,7 @@ impl<RT: Runtime> CronJobExecutor<RT> {
         usage_tracker: FunctionUsageTracker,
     ) -> anyhow::Result<()> {
         let start = self.rt.monotonic_now();
+        let start_ts = self.rt.unix_timestamp();
         let identity = tx.inert_identity();
         let caller = FunctionCaller::Cron;
         let (component, component_path) = self.get_job_component(&mut tx, job_id).await?;
@@ -459,6 +503,7 @@ impl<RT: Runtime> CronJobExecutor<RT> {
         };
         let stats = tx.take_stats();
         let execution_time = start.elapsed();
+        let index_ranges = IndexRangesRead::capture_if_slow(execution_time, tx.reads().read_set());
         let execution_time_f64 = execution_time.as_secs_f64();
         let truncated_log_lines = self.truncate_log_lines(outcome.log_lines.clone());
 
@@ -526,10 +571,19 @@ impl<RT: Runtime> CronJobExecutor<RT> {
                 .commit_with_write_source(tx, "cron_save_mutation_error")
                 .await?;
         }
+        self.record_execution(
+            &job,
+            path.component,
+            start_ts,
+            outcome.result.as_ref().err().map(|e| e.to_string()),
+            job_execution_usage(usage_tracker.clone(), None),
+        )
+        .await;
 
         self.function_log.log_mutation(
             outcome,
             stats,
+            index_ranges,
             execution_time,
             caller,
             usage_tracker,
@@ -568,9 +622,10 @@ impl<RT: Runtime> CronJobExecutor<RT> {
                     .await?;
 
                 // Execute the action
+                let start = self.rt.unix_timestamp();
                 let context = ExecutionContext::new(request_id, &caller);
                 let path = CanonicalizedComponentFunctionPath {
-                    component: component_path,
+                    component: component_path.clone(),
                     udf_path: job.cron_spec.udf_path.clone(),
                 };
                 let completion = self
@@ -618,6 +673,19 @@ impl<RT: Runtime> CronJobExecutor<RT> {
                     report_error(&mut err);
                     self.rt.wait(delay).await;
                 }
+                self.record_execution(
+                    &updated_job,
+                    component_path,
+                    start,
+                    completion
+                        .outcome
+                        .result
+                        .as_ref()
+                        .err()
+                        .map(|e| e.to_string()),
+                    job_execution_usage(usage_tracker.clone(), Some(completion.memory_in_mb)),
+                )
+                .await;
                 self.function_log.log_action(completion, usage_tracker);
             },
             CronJobState::InProgress => {
@@ -655,6 +723,14 @@ impl<RT: Runtime> CronJobExecutor<RT> {
                 self.database
                     .commit_with_write_source(tx, "cron_finish_action")
                     .await?;
+                self.record_execution(
+                    &job,
+                    component_path.clone(),
+                    self.rt.unix_timestamp(),
+                    Some(err.to_string()),
+                    FunctionExecutionUsage::default(),
+                )
+                .await;
 
                 let path = CanonicalizedComponentFunctionPath {
                     component: component_path,
@@ -675,6 +751,35 @@ impl<RT: Runtime> CronJobExecutor<RT> {
         Ok(())
     }
 
+    /// Records a run of `job` that started at `start` and has just finished,
+    /// failing with `error` if it's set.
+    async fn record_execution(
+        &self,
+        job: &CronJob,
+        component: ComponentPath,
+        start: UnixTimestamp,
+        error: Option<String>,
+        usage: FunctionExecutionUsage,
+    ) {
+        let status = match error {
+            Some(_) => FunctionExecutionStatus::Failure,
+            None => FunctionExecutionStatus::Success,
+        };
+        let record = JobExecutionRecord {
+            kind: JobKind::Cron,
+            job: job.name.to_string(),
+            component,
+            udf_path: job.cron_spec.udf_path.clone(),
+            start,
+            end: self.rt.unix_timestamp(),
+            status,
+            error,
+            attempts: 0,
+            usage,
+        };
+        record_job_execution(&self.database, record).await;
+    }
+
     // Creates a new transaction and verifies the job state matches the given one.
     async fn new_transaction_for_job_state(
         &self,
@@ -751,85 +856,133 @@ impl<RT: Runtime> CronJobExecutor<RT> {
     ) -> anyhow::Result<()> {
         let now = self.rt.generate_timestamp()?;
         let prev_ts = job.next_ts;
-        let mut next_ts = compute_next_ts(&job.cron_spec, Some(prev_ts), now)?;
-        let mut num_skipped = 0;
-        let first_skipped_ts = next_ts;
-        let (component, component_path) = self.get_job_component(tx, job_id).await?;
-        let mut model = CronModel::new(tx, component);
-        while next_ts < now {
-            num_skipped += 1;
-            next_ts = compute_next_ts(&job.cron_spec, Some(next_ts), now)?;
-        }
-        if num_skipped > 0 {
-            let name = &job.name;
-            tracing::info!(
-                "Skipping {num_skipped} run(s) of {name} because multiple scheduled runs are in \
-                 the past"
-            );
-            match udf_type {
-                // These aren't system errors in the sense that they represent an issue with Convex
-                // (e.g. they can occur due to the developer pausing their deployment)
-                // but they get logged similarly, since they shouldn't count towards usage and
-                // should appear as errors
-                UdfType::Mutation => {
-                    self.function_log.log_mutation_system_error(
-                        &anyhow::anyhow!(
-                            "Skipping {num_skipped} run(s) of {name} because multiple scheduled \
-                             runs are in the past"
-                        ),
-                        CanonicalizedComponentFunctionPath {
-                            component: component_path,
-                            udf_path: job.cron_spec.udf_path.clone(),
-                        },
-                        job.cron_spec.udf_args.clone(),
-                        identity,
-                        self.rt.monotonic_now(),
-                        FunctionCaller::Cron,
-                        context,
-                    )?;
-                },
-                UdfType::Action => {
-                    self.function_log.log_action_system_error(
-                        &anyhow::anyhow!(
-                            "Skipping {num_skipped} run(s) of {name} because multiple scheduled \
-                             runs are in the past"
-                        ),
-                        CanonicalizedComponentFunctionPath {
-                            component: component_path,
-                            udf_path: job.cron_spec.udf_path.clone(),
-                        },
-                        job.cron_spec.udf_args.clone(),
-                        identity,
-                        self.rt.monotonic_now(),
-                        FunctionCaller::Cron,
-                        vec![].into(),
-                        context,
-                    )?;
-                },
-                UdfType::Query | UdfType::HttpAction => {
-                    anyhow::bail!("Executing unexpected function type as a cron")
-                },
-            }
-
-            let status = CronJobStatus::Canceled {
-                num_canceled: num_skipped,
-            };
-            let log_lines = CronJobLogLines {
-                log_lines: vec![].into(),
-                is_truncated: false,
-            };
-            let mut canceled_job = job.clone();
-            canceled_job.next_ts = first_skipped_ts;
-            model
-                .insert_cron_job_log(&canceled_job, status, log_lines, 0.0)
-                .await?;
-        }
-
+        let next_ts = self
+            .rt
+            .with_rng(|rng| compute_next_ts(&job.cron_spec, Some(prev_ts), now, rng))?;
         let mut updated_job = job.clone();
         updated_job.state = CronJobState::Pending;
         updated_job.prev_ts = Some(prev_ts);
         updated_job.next_ts = next_ts;
-        model.update_job_state(job_id, updated_job.clone()).await?;
+        if next_ts <= now {
+            // The run overran its schedule.
+            updated_job.next_ts = self
+                .apply_catch_up_policy(identity, tx, job_id, &updated_job, udf_type, context)
+                .await?;
+        }
+        let (component, _) = self.get_job_component(tx, job_id).await?;
+        CronModel::new(tx, component)
+            .update_job_state(job_id, updated_job)
+            .await?;
         Ok(())
     }
+
+    // Applies the cron's catch-up policy to a job whose `next_ts` is due. If
+    // later runs are also due, the ones the policy doesn't run are logged as
+    // canceled. Returns the timestamp the job should run at next, which is
+    // `job.next_ts` if no runs were skipped.
+    async fn apply_catch_up_policy(
+        &self,
+        identity: InertIdentity,
+        tx: &mut Transaction<RT>,
+        job_id: ResolvedDocumentId,
+        job: &CronJob,
+        udf_type: UdfType,
+        context: ExecutionContext,
+    ) -> anyhow::Result<Timestamp> {
+        let now = self.rt.generate_timestamp()?;
+        let max_runs = match job.cron_spec.catch_up {
+            CronCatchUpPolicy::Skip => 0,
+            CronCatchUpPolicy::RunOnce => 1,
+            CronCatchUpPolicy::RunAll => *CRON_JOB_MAX_CATCH_UP_RUNS,
+        };
+        // The most recent `max_runs` runs that are due, oldest first.
+        let mut due_runs = VecDeque::from([job.next_ts]);
+        let mut num_due = 1;
+        let mut next_ts = job.next_ts;
+        loop {
+            next_ts = self
+                .rt
+                .with_rng(|rng| compute_next_ts(&job.cron_spec, Some(next_ts), now, rng))?;
+            if next_ts > now {
+                break;
+            }
+            num_due += 1;
+            due_runs.push_back(next_ts);
+            while due_runs.len() > max_runs {
+                due_runs.pop_front();
+            }
+        }
+        // Only `job.next_ts` is due, so no runs were missed.
+        if num_due == 1 {
+            return Ok(job.next_ts);
+        }
+        let num_skipped = num_due - due_runs.len();
+        if num_skipped == 0 {
+            return Ok(job.next_ts);
+        }
+
+        let name = &job.name;
+        let catch_up = job.cron_spec.catch_up;
+        tracing::info!(
+            "Skipping {num_skipped} run(s) of {name} because multiple scheduled runs are in the \
+             past (catch-up policy: {catch_up})"
+        );
+        let (component, component_path) = self.get_job_component(tx, job_id).await?;
+        match udf_type {
+            // These aren't system errors in the sense that they represent an issue with Convex
+            // (e.g. they can occur due to the developer pausing their deployment)
+            // but they get logged similarly, since they shouldn't count towards usage and
+            // should appear as errors
+            UdfType::Mutation => {
+                self.function_log.log_mutation_system_error(
+                    &anyhow::anyhow!(
+                        "Skipping {num_skipped} run(s) of {name} because multiple scheduled runs \
+                         are in the past"
+                    ),
+                    CanonicalizedComponentFunctionPath {
+                        component: component_path,
+                        udf_path: job.cron_spec.udf_path.clone(),
+                    },
+                    job.cron_spec.udf_args.clone(),
+                    identity,
+                    self.rt.monotonic_now(),
+                    FunctionCaller::Cron,
+                    context,
+                )?;
+            },
+            UdfType::Action => {
+                self.function_log.log_action_system_error(
+                    &anyhow::anyhow!(
+                        "Skipping {num_skipped} run(s) of {name} because multiple scheduled runs \
+                         are in the past"
+                    ),
+                    CanonicalizedComponentFunctionPath {
+                        component: component_path,
+                        udf_path: job.cron_spec.udf_path.clone(),
+                    },
+                    job.cron_spec.udf_args.clone(),
+                    identity,
+                    self.rt.monotonic_now(),
+                    FunctionCaller::Cron,
+                    vec![].into(),
+                    context,
+                )?;
+            },
+            UdfType::Query | UdfType::HttpAction => {
+                anyhow::bail!("Executing unexpected function type as a cron")
+            },
+        }
+
+        let status = CronJobStatus::Canceled {
+            num_canceled: num_skipped as i64,
+        };
+        let log_lines = CronJobLogLines {
+            log_lines: vec![].into(),
+            is_truncated: false,
+        };
+        CronModel::new(tx, component)
+            .insert_cron_job_log(job, status, log_lines, 0.0)
+            .await?;
+        Ok(due_runs.front().copied().unwrap_or(next_ts))
+    }
 }
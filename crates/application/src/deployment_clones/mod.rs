@@ -0,0 +1,401 @@
+use std::{
+    io,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use bytes::Bytes;
+use common::{
+    backoff::Backoff,
+    bootstrap_model::schema::SchemaState,
+    document::ParsedDocument,
+    errors::report_error,
+    runtime::Runtime,
+    schemas::DatabaseSchema,
+};
+use database::{
+    Database,
+    SchemaModel,
+};
+use futures::{
+    stream::BoxStream,
+    Future,
+};
+use keybroker::Identity;
+use model::{
+    config::ConfigModel,
+    deployment_clones::{
+        types::{
+            DeploymentClone,
+            DeploymentCloneState,
+        },
+        DeploymentClonesModel,
+    },
+    environment_variables::EnvironmentVariablesModel,
+    scheduled_jobs::{
+        types::ScheduledJobState,
+        SchedulerModel,
+    },
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use storage::Storage;
+use usage_tracking::UsageCounter;
+use value::{
+    ConvexObject,
+    ResolvedDocumentId,
+    TableNamespace,
+};
+
+use self::target::TargetDeployment;
+use crate::{
+    export_worker::ExportWorker,
+    module_cache::ModuleCache,
+};
+
+mod target;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+/// How often to check on the target's schema and import while waiting for
+/// them.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The most pending scheduled jobs copied to the target in one request.
+const SCHEDULED_JOBS_PER_REQUEST: usize = 1000;
+
+/// Runs the clones in `_deployment_clones`, one at a time and oldest first.
+/// Each copies the deployment's schema, modules (and so its crons),
+/// environment variables, a ZIP snapshot of its tables and file storage, and
+/// its pending scheduled jobs into the target deployment through the target's
+/// admin API.
+///
+/// A clone interrupted by a restart starts over, since every step but the last
+/// replaces what's in the target. One interrupted while copying scheduled jobs
+/// fails instead, since some of them may already be scheduled in the target.
+pub struct DeploymentCloneWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    exports_storage: Arc<dyn Storage>,
+    export_worker: ExportWorker<RT>,
+    module_cache: ModuleCache<RT>,
+}
+
+impl<RT: Runtime> DeploymentCloneWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        exports_storage: Arc<dyn Storage>,
+        files_storage: Arc<dyn Storage>,
+        usage_tracking: UsageCounter,
+        module_cache: ModuleCache<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        let export_worker = ExportWorker::create(
+            runtime.clone(),
+            database.clone(),
+            exports_storage.clone(),
+            files_storage,
+            usage_tracking,
+        );
+        let mut worker = Self {
+            runtime,
+            database,
+            exports_storage,
+            export_worker,
+            module_cache,
+        };
+        async move {
+            tracing::info!("Starting DeploymentCloneWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("DeploymentCloneWorker died"));
+                tracing::error!("Deployment clone worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&mut self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let unfinished = DeploymentClonesModel::new(&mut tx)
+                .list()
+                .await?
+                .into_iter()
+                .find(|clone| !clone.state.is_finished());
+            if let Some(clone) = unfinished {
+                self.run_clone(clone).await?;
+                backoff.reset();
+                continue;
+            }
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            subscription.wait_for_invalidation().await;
+            backoff.reset();
+        }
+    }
+
+    /// Run `clone`. Failing to copy fails the clone rather than the worker,
+    /// so the clones after it still run.
+    async fn run_clone(&mut self, clone: ParsedDocument<DeploymentClone>) -> anyhow::Result<()> {
+        let (id, clone) = clone.into_id_and_value();
+        if let DeploymentCloneState::CopyingScheduledJobs { .. } = clone.state {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            DeploymentClonesModel::new(&mut tx)
+                .fail(
+                    id,
+                    "Interrupted while copying scheduled jobs, some of which may already be \
+                     scheduled in the target"
+                        .to_string(),
+                )
+                .await?;
+            self.database
+                .commit_with_write_source(tx, "deployment_clone_interrupted")
+                .await?;
+            return Ok(());
+        }
+        let target_admin_key = clone
+            .target_admin_key
+            .clone()
+            .context("Unfinished deployment clone is missing its admin key")?;
+        tracing::info!("Cloning deployment into {}", clone.target_url);
+        let target = TargetDeployment::new(clone.target_url.clone(), target_admin_key);
+        let state = match self.clone_into(id, &clone, &target).await {
+            Ok(state) => state,
+            Err(mut e) => {
+                report_error(&mut e);
+                DeploymentCloneState::Failed {
+                    error: format!("{e:#}"),
+                    failed_ts: self.runtime.generate_timestamp()?,
+                }
+            },
+        };
+        self.set_state(id, state).await
+    }
+
+    async fn clone_into(
+        &mut self,
+        id: ResolvedDocumentId,
+        clone: &DeploymentClone,
+        target: &TargetDeployment,
+    ) -> anyhow::Result<DeploymentCloneState> {
+        self.set_state(id, DeploymentCloneState::CopyingCode)
+            .await?;
+        self.copy_code(target)
+            .await
+            .context("Failed to copy code")?;
+
+        self.set_state(id, DeploymentCloneState::CopyingEnvironmentVariables)
+            .await?;
+        self.copy_environment_variables(target)
+            .await
+            .context("Failed to copy environment variables")?;
+
+        self.set_state(id, DeploymentCloneState::Exporting).await?;
+        let (snapshot_ts, export_key) =
+            self.export_worker.export_zip(clone.include_storage).await?;
+        self.set_state(id, DeploymentCloneState::Importing { snapshot_ts })
+            .await?;
+        let export = self
+            .exports_storage
+            .get(&export_key)
+            .await?
+            .with_context(|| format!("Export {export_key:?} is missing from storage"))?;
+        self.import_snapshot(target, export.stream)
+            .await
+            .context("Failed to import the snapshot")?;
+
+        self.set_state(
+            id,
+            DeploymentCloneState::CopyingScheduledJobs { snapshot_ts },
+        )
+        .await?;
+        self.copy_scheduled_jobs(target)
+            .await
+            .context("Failed to copy scheduled jobs")?;
+        tracing::info!("Cloned deployment into {}", clone.target_url);
+        Ok(DeploymentCloneState::Completed {
+            snapshot_ts,
+            completed_ts: self.runtime.generate_timestamp()?,
+        })
+    }
+
+    /// Push the active schema and the current version of every module. Crons
+    /// are registered by the target when it analyzes the modules.
+    async fn copy_code(&self, target: &TargetDeployment) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let (config, modules, udf_config) = ConfigModel::new(&mut tx)
+            .get_with_module_source(&self.module_cache)
+            .await?;
+        let Some(udf_config) = udf_config else {
+            // Code has never been pushed to this deployment.
+            return Ok(());
+        };
+        let schema = SchemaModel::new(&mut tx, TableNamespace::by_component_TODO())
+            .get_by_state(SchemaState::Active)
+            .await?;
+        let schema_id = match schema {
+            Some((_, schema)) => Some(self.push_schema(target, schema).await?),
+            None => None,
+        };
+        let modules: Vec<_> = modules
+            .into_iter()
+            .map(|module| {
+                json!({
+                    "path": String::from(module.path),
+                    "source": module.source,
+                    "sourceMap": module.source_map,
+                    "environment": module.environment.to_string(),
+                })
+            })
+            .collect();
+        target
+            .push_config(json!({
+                "config": JsonValue::from(ConvexObject::try_from(config)?),
+                "modules": modules,
+                "udfServerVersion": udf_config.server_version.to_string(),
+                "schemaId": schema_id,
+            }))
+            .await
+    }
+
+    /// Submit `schema` to the target and wait for it to be validated and for
+    /// its indexes to be backfilled. Returns the id of the target's schema.
+    async fn push_schema(
+        &self,
+        target: &TargetDeployment,
+        schema: DatabaseSchema,
+    ) -> anyhow::Result<String> {
+        // Only the schema's definition is stored, not the module it was
+        // evaluated from, so push a module that evaluates to the same
+        // definition.
+        let schema_json = serde_json::to_string(&JsonValue::try_from(schema)?)?;
+        let schema_bundle = format!(
+            "export default {{ export() {{ return {}; }} }};",
+            serde_json::to_string(&schema_json)?
+        );
+        let schema_id = target.prepare_schema(schema_bundle).await?;
+        loop {
+            let state = target.schema_state(&schema_id).await?;
+            match &state.state[..] {
+                "validated" | "active" if state.indexes_backfilled => return Ok(schema_id),
+                "failed" => anyhow::bail!(
+                    "The target rejected the schema: {}",
+                    state.error.unwrap_or_default()
+                ),
+                "overwritten" => {
+                    anyhow::bail!("The schema was overwritten by another push to the target")
+                },
+                _ => self.runtime.wait(POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    async fn copy_environment_variables(&self, target: &TargetDeployment) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let environment_variables = EnvironmentVariablesModel::new(&mut tx).get_all().await?;
+        if environment_variables.is_empty() {
+            return Ok(());
+        }
+        target
+            .set_environment_variables(
+                environment_variables
+                    .into_iter()
+                    .map(|(name, value)| (name.as_ref().to_string(), value.as_ref().to_string()))
+                    .collect(),
+            )
+            .await
+    }
+
+    /// Upload the ZIP snapshot to the target and import it, replacing the
+    /// target's tables and file storage.
+    async fn import_snapshot(
+        &self,
+        target: &TargetDeployment,
+        snapshot: BoxStream<'static, io::Result<Bytes>>,
+    ) -> anyhow::Result<()> {
+        let import_id = target.upload_zip_import(snapshot).await?;
+        let state = self.wait_for_import(target, &import_id).await?;
+        anyhow::ensure!(
+            state == "waiting_for_confirmation",
+            "Import {import_id} is {state} instead of waiting for confirmation"
+        );
+        target.perform_import(&import_id).await?;
+        let state = self.wait_for_import(target, &import_id).await?;
+        anyhow::ensure!(
+            state == "completed",
+            "Import {import_id} is {state} instead of completed"
+        );
+        Ok(())
+    }
+
+    /// Wait for the target's import to stop making progress on its own,
+    /// returning its state.
+    async fn wait_for_import(
+        &self,
+        target: &TargetDeployment,
+        import_id: &str,
+    ) -> anyhow::Result<String> {
+        loop {
+            let state = target.import_state(import_id).await?;
+            match state["state"].as_str() {
+                Some("uploaded" | "in_progress") => self.runtime.wait(POLL_INTERVAL).await,
+                Some("failed") => anyhow::bail!(
+                    "Import {import_id} failed: {}",
+                    state["error_message"].as_str().unwrap_or_default()
+                ),
+                Some(state) => return Ok(state.to_string()),
+                None => anyhow::bail!("Import {import_id} has no state: {state}"),
+            }
+        }
+    }
+
+    /// Schedule the jobs pending in this deployment in the target, in batches
+    /// of `SCHEDULED_JOBS_PER_REQUEST`. Their arguments keep referring to the
+    /// same documents, since the import keeps documents' ids.
+    async fn copy_scheduled_jobs(&self, target: &TargetDeployment) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let jobs = SchedulerModel::new(&mut tx, TableNamespace::by_component_TODO())
+            .list()
+            .await?
+            .into_iter()
+            .filter(|job| job.state == ScheduledJobState::Pending)
+            .map(|job| {
+                let job = job.into_value();
+                let scheduled_time_ms =
+                    u64::try_from(i64::from(job.original_scheduled_ts))? / 1_000_000;
+                Ok(json!({
+                    "udfPath": String::from(job.udf_path),
+                    "args": JsonValue::from(job.udf_args),
+                    "scheduledTimeMs": scheduled_time_ms,
+                    "priority": job.priority.to_string(),
+                    "maxConcurrency": job.max_concurrency,
+                }))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for jobs in jobs.chunks(SCHEDULED_JOBS_PER_REQUEST) {
+            target.schedule_jobs(jobs.to_vec()).await?;
+        }
+        Ok(())
+    }
+
+    async fn set_state(
+        &self,
+        id: ResolvedDocumentId,
+        state: DeploymentCloneState,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        DeploymentClonesModel::new(&mut tx)
+            .set_state(id, state)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "deployment_clone_state")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use std::io;
+
+use anyhow::Context;
+use bytes::{
+    Bytes,
+    BytesMut,
+};
+use futures::{
+    stream::BoxStream,
+    StreamExt,
+};
+use reqwest::{
+    header::AUTHORIZATION,
+    RequestBuilder,
+};
+use serde::{
+    de::DeserializeOwned,
+    Deserialize,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+
+/// Imports are uploaded in parts of at least this size, except for the last
+/// one.
+const IMPORT_PART_SIZE: usize = 5 * (1 << 20);
+
+/// The state of a schema pushed to the target and its indexes' backfills.
+pub(super) struct TargetSchemaState {
+    // "pending", "validated", "active", "failed" or "overwritten".
+    pub(super) state: String,
+    pub(super) error: Option<String>,
+    pub(super) indexes_backfilled: bool,
+}
+
+/// The deployment a clone copies into, called through the same admin API
+/// that the CLI uses.
+pub(super) struct TargetDeployment {
+    client: reqwest::Client,
+    url: String,
+    admin_key: String,
+}
+
+impl TargetDeployment {
+    pub(super) fn new(url: String, admin_key: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            admin_key,
+        }
+    }
+
+    fn request(&self, request: RequestBuilder) -> RequestBuilder {
+        request.header(AUTHORIZATION, format!("Convex {}", self.admin_key))
+    }
+
+    fn api_url(&self, path: &str) -> String {
+        format!("{}/api/{path}", self.url)
+    }
+
+    async fn post(&self, path: &str, body: JsonValue) -> anyhow::Result<reqwest::Response> {
+        let request = self
+            .request(self.client.post(self.api_url(path)))
+            .json(&body);
+        check_response(path, request.send().await?).await
+    }
+
+    async fn post_json<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: JsonValue,
+    ) -> anyhow::Result<T> {
+        parse_json(path, self.post(path, body).await?).await
+    }
+
+    /// Submit `schema_bundle` as the target's pending schema, starting the
+    /// backfills of its indexes. Returns the schema's id.
+    pub(super) async fn prepare_schema(&self, schema_bundle: String) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PrepareSchemaResponse {
+            schema_id: String,
+        }
+        let response: PrepareSchemaResponse = self
+            .post_json(
+                "prepare_schema",
+                json!({
+                    "bundle": {
+                        "path": "schema.js",
+                        "source": schema_bundle,
+                        "sourceMap": null,
+                        "environment": "isolate",
+                    },
+                    "adminKey": self.admin_key,
+                    "dryRun": false,
+                }),
+            )
+            .await?;
+        Ok(response.schema_id)
+    }
+
+    pub(super) async fn schema_state(&self, schema_id: &str) -> anyhow::Result<TargetSchemaState> {
+        let path = format!("schema_state/{schema_id}");
+        let request = self.request(self.client.get(self.api_url(&path)));
+        let response: JsonValue =
+            parse_json(&path, check_response(&path, request.send().await?).await?).await?;
+        let indexes_backfilled = response["indexes"]
+            .as_array()
+            .context("schema_state response is missing indexes")?
+            .iter()
+            .all(|index| index["backfill"]["state"] == "done");
+        let schema_state = &response["schemaState"];
+        Ok(TargetSchemaState {
+            state: schema_state["state"]
+                .as_str()
+                .context("schema_state response is missing its state")?
+                .to_string(),
+            error: schema_state["error"].as_str().map(|e| e.to_string()),
+            indexes_backfilled,
+        })
+    }
+
+    /// Replace the target's modules and config. `config` has the fields of
+    /// the `/api/push_config` request other than the admin key.
+    pub(super) async fn push_config(&self, mut config: JsonValue) -> anyhow::Result<()> {
+        config["adminKey"] = JsonValue::String(self.admin_key.clone());
+        self.post("push_config", config).await?;
+        Ok(())
+    }
+
+    pub(super) async fn set_environment_variables(
+        &self,
+        environment_variables: Vec<(String, String)>,
+    ) -> anyhow::Result<()> {
+        let changes: Vec<_> = environment_variables
+            .into_iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect();
+        self.post(
+            "update_environment_variables",
+            json!({ "changes": changes }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Upload a ZIP snapshot to import into the target, replacing all of its
+    /// tables. Returns the import's id.
+    pub(super) async fn upload_zip_import(
+        &self,
+        mut body: BoxStream<'static, io::Result<Bytes>>,
+    ) -> anyhow::Result<String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct StartUploadResponse {
+            upload_token: String,
+        }
+        let StartUploadResponse { upload_token } =
+            self.post_json("import/start_upload", json!({})).await?;
+        let mut part_tokens = vec![];
+        let mut part = BytesMut::new();
+        loop {
+            let chunk = body.next().await.transpose()?;
+            if let Some(chunk) = &chunk {
+                part.extend_from_slice(chunk);
+            }
+            let is_last = chunk.is_none();
+            if part.len() >= IMPORT_PART_SIZE
+                || (is_last && (!part.is_empty() || part_tokens.is_empty()))
+            {
+                let part_number = part_tokens.len() + 1;
+                let request = self
+                    .request(self.client.post(self.api_url("import/upload_part")))
+                    .query(&[
+                        ("uploadToken", upload_token.clone()),
+                        ("partNumber", part_number.to_string()),
+                    ])
+                    .body(part.split().freeze());
+                let response = check_response("import/upload_part", request.send().await?).await?;
+                part_tokens.push(response.text().await?);
+            }
+            if is_last {
+                break;
+            }
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct FinishUploadResponse {
+            import_id: String,
+        }
+        let FinishUploadResponse { import_id } = self
+            .post_json(
+                "import/finish_upload",
+                json!({
+                    "import": { "format": "zip", "mode": "replace" },
+                    "uploadToken": upload_token,
+                    "partTokens": part_tokens,
+                }),
+            )
+            .await?;
+        Ok(import_id)
+    }
+
+    /// The `state` of an import in `_snapshot_imports`, like
+    /// `{"state": "completed", ...}`.
+    pub(super) async fn import_state(&self, import_id: &str) -> anyhow::Result<JsonValue> {
+        let response: JsonValue = self
+            .post_json(
+                "query",
+                json!({
+                    "path": "_system/cli/queryImport",
+                    "args": { "importId": import_id },
+                    "format": "json",
+                }),
+            )
+            .await?;
+        if response["status"] != "success" {
+            anyhow::bail!(
+                "Failed to query the state of import {import_id}: {}",
+                response["errorMessage"]
+            );
+        }
+        Ok(response["value"]["state"].clone())
+    }
+
+    pub(super) async fn perform_import(&self, import_id: &str) -> anyhow::Result<()> {
+        self.post("perform_import", json!({ "importId": import_id }))
+            .await?;
+        Ok(())
+    }
+
+    /// Schedule `jobs`, in the format of the `/api/schedule_jobs` request,
+    /// atomically as one batch.
+    pub(super) async fn schedule_jobs(&self, jobs: Vec<JsonValue>) -> anyhow::Result<()> {
+        self.post("schedule_jobs", json!({ "jobs": jobs })).await?;
+        Ok(())
+    }
+}
+
+async fn parse_json<T: DeserializeOwned>(
+    path: &str,
+    response: reqwest::Response,
+) -> anyhow::Result<T> {
+    response
+        .json()
+        .await
+        .with_context(|| format!("Invalid response from /api/{path}"))
+}
+
+async fn check_response(
+    path: &str,
+    response: reqwest::Response,
+) -> anyhow::Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    anyhow::bail!(
+        "Target deployment responded to /api/{path} with {status}: {}",
+        body.chars().take(1000).collect::<String>()
+    )
+}
@@ -0,0 +1,650 @@
+//! Exports for analytics engines: an NDJSON or Parquet file per table, and a
+//! manifest describing each table's columns and how their Convex types map to
+//! Arrow types.
+//!
+//! A table's columns are the top-level fields of its inferred shape. Fields
+//! whose values all have one scalar type get the matching Arrow type, and
+//! everything else, e.g. nested objects and arrays or fields that mix types,
+//! is stored as JSON.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+        HashMap,
+    },
+    sync::Arc,
+};
+
+use arrow_array::{
+    builder::{
+        BinaryBuilder,
+        BooleanBuilder,
+        Float64Builder,
+        Int64Builder,
+        StringBuilder,
+    },
+    ArrayRef,
+    NullArray,
+    RecordBatch,
+};
+use arrow_schema::{
+    DataType,
+    Field,
+    Schema,
+    SchemaRef,
+};
+use common::{
+    document::ResolvedDocument,
+    types::{
+        ObjectKey,
+        TableName,
+    },
+};
+use parquet::{
+    arrow::ArrowWriter,
+    basic::Compression,
+    file::properties::WriterProperties,
+};
+use serde::Serialize;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use shape_inference::{
+    CountedShape,
+    ProdConfigWithOptionalFields,
+    ShapeEnum,
+};
+use storage::{
+    Storage,
+    Upload,
+};
+use value::{
+    export::ValueFormat,
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+    Namespace,
+};
+
+/// Documents are encoded into Parquet in record batches of this many rows.
+const PARQUET_BATCH_SIZE: usize = 1000;
+
+/// Parquet row groups are closed, and their bytes uploaded, once they're this
+/// big.
+const PARQUET_MAX_ROW_GROUP_BYTES: usize = 64 << 20;
+
+/// The Arrow extension type for strings that hold JSON.
+const ARROW_JSON_EXTENSION: &str = "arrow.json";
+
+/// Whether an export is NDJSON or Parquet.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColumnarFormat {
+    Ndjson,
+    Parquet,
+}
+
+impl ColumnarFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ColumnarFormat::Ndjson => "ndjson",
+            ColumnarFormat::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    Null,
+    Boolean,
+    Int64,
+    Float64,
+    Utf8,
+    Binary,
+    Json,
+}
+
+impl ColumnType {
+    fn of_shape(shape: &CountedShape<ProdConfigWithOptionalFields>) -> (Self, bool) {
+        let column_type = match shape.variant() {
+            ShapeEnum::Never | ShapeEnum::Null => return (ColumnType::Null, true),
+            ShapeEnum::Union(union) => return Self::combine(union.iter().map(Self::of_shape)),
+            ShapeEnum::Int64 => ColumnType::Int64,
+            ShapeEnum::NegativeInf
+            | ShapeEnum::PositiveInf
+            | ShapeEnum::NegativeZero
+            | ShapeEnum::NaN
+            | ShapeEnum::NormalFloat64
+            | ShapeEnum::Float64 => ColumnType::Float64,
+            // Parquet decimals have a fixed precision and scale, so write decimals as their
+            // canonical strings instead.
+            ShapeEnum::Decimal => ColumnType::Utf8,
+            ShapeEnum::Boolean => ColumnType::Boolean,
+            ShapeEnum::StringLiteral(_)
+            | ShapeEnum::Id(_)
+            | ShapeEnum::FieldName
+            | ShapeEnum::String => ColumnType::Utf8,
+            ShapeEnum::Bytes => ColumnType::Binary,
+            ShapeEnum::GeoPoint
+            | ShapeEnum::Array(_)
+            | ShapeEnum::Set(_)
+            | ShapeEnum::Map(_)
+            | ShapeEnum::Object(_)
+            | ShapeEnum::Record(_)
+            | ShapeEnum::Unknown => ColumnType::Json,
+        };
+        (column_type, false)
+    }
+
+    /// The type of a column holding values of each of `types`, which is
+    /// nullable if any of them are.
+    fn combine(types: impl Iterator<Item = (Self, bool)>) -> (Self, bool) {
+        let mut combined = None;
+        let mut nullable = false;
+        for (column_type, is_nullable) in types {
+            nullable |= is_nullable;
+            combined = match (combined, column_type) {
+                (_, ColumnType::Null) => combined,
+                (None, column_type) => Some(column_type),
+                (Some(combined), column_type) if combined == column_type => Some(combined),
+                (Some(_), _) => Some(ColumnType::Json),
+            };
+        }
+        (combined.unwrap_or(ColumnType::Null), nullable)
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            ColumnType::Null => DataType::Null,
+            ColumnType::Boolean => DataType::Boolean,
+            ColumnType::Int64 => DataType::Int64,
+            ColumnType::Float64 => DataType::Float64,
+            ColumnType::Utf8 | ColumnType::Json => DataType::Utf8,
+            ColumnType::Binary => DataType::Binary,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnSource {
+    Field(FieldName),
+    /// All of the document's user fields as a JSON object, for tables without
+    /// statically known fields.
+    OtherFields,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    pub source: ColumnSource,
+    /// The column's Convex type, as inferred from the table's documents.
+    pub shape: String,
+    pub column_type: ColumnType,
+    pub nullable: bool,
+}
+
+impl Column {
+    pub fn name(&self) -> String {
+        match &self.source {
+            ColumnSource::Field(field) => field.to_string(),
+            ColumnSource::OtherFields => "fields".to_string(),
+        }
+    }
+
+    fn field(&self) -> Field {
+        let field = Field::new(self.name(), self.column_type.data_type(), self.nullable);
+        if self.column_type == ColumnType::Json {
+            field.with_metadata(HashMap::from([(
+                "ARROW:extension:name".to_string(),
+                ARROW_JSON_EXTENSION.to_string(),
+            )]))
+        } else {
+            field
+        }
+    }
+
+    fn value(&self, document: &ConvexObject) -> Option<ConvexValue> {
+        match &self.source {
+            ColumnSource::Field(field) => document.get(field).cloned(),
+            ColumnSource::OtherFields => {
+                Some(ConvexValue::Object(document.clone().filter_system_fields()))
+            },
+        }
+    }
+}
+
+/// The columns for a table with the inferred `shape`, with the system fields
+/// first, leaving out `excluded_fields`.
+pub fn table_columns(
+    shape: &CountedShape<ProdConfigWithOptionalFields>,
+    excluded_fields: Option<&BTreeSet<FieldName>>,
+) -> anyhow::Result<Vec<Column>> {
+    let objects = match shape.variant() {
+        ShapeEnum::Object(object) => vec![object],
+        ShapeEnum::Union(union) => union
+            .iter()
+            .map(|variant| match variant.variant() {
+                ShapeEnum::Object(object) => Some(object),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_default(),
+        _ => vec![],
+    };
+    let mut columns = vec![];
+    if objects.is_empty() {
+        // Empty tables, and tables whose documents have too many different
+        // fields to infer an object shape.
+        columns.push(Column {
+            source: ColumnSource::Field("_id".parse()?),
+            shape: "string".to_string(),
+            column_type: ColumnType::Utf8,
+            nullable: false,
+        });
+        columns.push(Column {
+            source: ColumnSource::Field("_creationTime".parse()?),
+            shape: "float64".to_string(),
+            column_type: ColumnType::Float64,
+            nullable: false,
+        });
+        if !shape.is_empty() {
+            columns.push(Column {
+                source: ColumnSource::OtherFields,
+                shape: shape.to_string(),
+                column_type: ColumnType::Json,
+                nullable: false,
+            });
+        }
+    } else {
+        let field_names: BTreeSet<_> = objects
+            .iter()
+            .flat_map(|object| object.fields().keys())
+            .collect();
+        for field_name in field_names {
+            let fields: Vec<_> = objects
+                .iter()
+                .filter_map(|object| object.fields().get(field_name))
+                .collect();
+            let missing = fields.len() < objects.len() || fields.iter().any(|f| f.optional);
+            let (column_type, nullable) =
+                ColumnType::combine(fields.iter().map(|f| ColumnType::of_shape(&f.value_shape)));
+            let shapes: BTreeSet<_> = fields.iter().map(|f| f.value_shape.to_string()).collect();
+            columns.push(Column {
+                source: ColumnSource::Field(field_name[..].parse()?),
+                shape: shapes.into_iter().collect::<Vec<_>>().join(" | "),
+                column_type,
+                nullable: nullable || missing,
+            });
+        }
+        // `_id`, then `_creationTime`, then the user fields.
+        columns.sort_by_key(|column| match &column.source {
+            ColumnSource::Field(field) if &field[..] == "_id" => 0,
+            ColumnSource::Field(field) if field.is_system() => 1,
+            _ => 2,
+        });
+    }
+    if let Some(excluded_fields) = excluded_fields {
+        columns.retain(|column| match &column.source {
+            ColumnSource::Field(field) => !excluded_fields.contains(field),
+            ColumnSource::OtherFields => true,
+        });
+    }
+    Ok(columns)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportManifest {
+    pub format: &'static str,
+    /// Timestamp of the snapshot, as a string since it doesn't fit in a
+    /// double.
+    pub snapshot_ts: String,
+    pub tables: BTreeMap<String, TableManifest>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableManifest {
+    pub file: String,
+    pub num_documents: u64,
+    pub columns: Vec<ColumnManifest>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnManifest {
+    pub name: String,
+    pub convex_type: String,
+    pub arrow_type: String,
+    pub nullable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arrow_extension: Option<&'static str>,
+}
+
+impl TableManifest {
+    pub fn new(
+        format: ColumnarFormat,
+        table_name: &TableName,
+        num_documents: u64,
+        columns: &[Column],
+    ) -> Self {
+        Self {
+            file: format!("{table_name}.{}", format.extension()),
+            num_documents,
+            columns: columns
+                .iter()
+                .map(|column| ColumnManifest {
+                    name: column.name(),
+                    convex_type: column.shape.clone(),
+                    arrow_type: column.column_type.data_type().to_string(),
+                    nullable: column.nullable,
+                    arrow_extension: (column.column_type == ColumnType::Json)
+                        .then_some(ARROW_JSON_EXTENSION),
+                })
+                .collect(),
+        }
+    }
+}
+
+enum ColumnBuilder {
+    Null(usize),
+    Boolean(BooleanBuilder),
+    Int64(Int64Builder),
+    Float64(Float64Builder),
+    Utf8(StringBuilder),
+    Binary(BinaryBuilder),
+    Json(StringBuilder),
+}
+
+impl ColumnBuilder {
+    fn new(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::Null => ColumnBuilder::Null(0),
+            ColumnType::Boolean => ColumnBuilder::Boolean(BooleanBuilder::new()),
+            ColumnType::Int64 => ColumnBuilder::Int64(Int64Builder::new()),
+            ColumnType::Float64 => ColumnBuilder::Float64(Float64Builder::new()),
+            ColumnType::Utf8 => ColumnBuilder::Utf8(StringBuilder::new()),
+            ColumnType::Binary => ColumnBuilder::Binary(BinaryBuilder::new()),
+            ColumnType::Json => ColumnBuilder::Json(StringBuilder::new()),
+        }
+    }
+
+    fn append(&mut self, value: Option<ConvexValue>) -> anyhow::Result<()> {
+        let value = match value {
+            None | Some(ConvexValue::Null) => {
+                match self {
+                    ColumnBuilder::Null(len) => *len += 1,
+                    ColumnBuilder::Boolean(b) => b.append_null(),
+                    ColumnBuilder::Int64(b) => b.append_null(),
+                    ColumnBuilder::Float64(b) => b.append_null(),
+                    ColumnBuilder::Utf8(b) | ColumnBuilder::Json(b) => b.append_null(),
+                    ColumnBuilder::Binary(b) => b.append_null(),
+                }
+                return Ok(());
+            },
+            Some(value) => value,
+        };
+        match (self, value) {
+            (ColumnBuilder::Boolean(b), ConvexValue::Boolean(v)) => b.append_value(v),
+            (ColumnBuilder::Int64(b), ConvexValue::Int64(v)) => b.append_value(v),
+            (ColumnBuilder::Float64(b), ConvexValue::Float64(v)) => b.append_value(v),
+            (ColumnBuilder::Utf8(b), ConvexValue::String(v)) => b.append_value(&v[..]),
+            (ColumnBuilder::Utf8(b), ConvexValue::Decimal(v)) => b.append_value(v.to_string()),
+            (ColumnBuilder::Binary(b), ConvexValue::Bytes(v)) => b.append_value(&v[..]),
+            (ColumnBuilder::Json(b), v) => b.append_value(serde_json::to_string(
+                &v.export(ValueFormat::ConvexCleanJSON),
+            )?),
+            (_, v) => anyhow::bail!("{} doesn't match the column's type", v.type_name()),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::Null(len) => Arc::new(NullArray::new(std::mem::take(len))),
+            ColumnBuilder::Boolean(b) => Arc::new(b.finish()),
+            ColumnBuilder::Int64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Float64(b) => Arc::new(b.finish()),
+            ColumnBuilder::Utf8(b) | ColumnBuilder::Json(b) => Arc::new(b.finish()),
+            ColumnBuilder::Binary(b) => Arc::new(b.finish()),
+        }
+    }
+}
+
+/// A value in an NDJSON export. Int64s and floats are JSON numbers, unlike
+/// in the clean export format, with non-finite floats as the strings `NaN`,
+/// `Infinity` and `-Infinity`.
+fn ndjson_value(column_type: ColumnType, value: Option<ConvexValue>) -> JsonValue {
+    match (column_type, value) {
+        (_, None) => JsonValue::Null,
+        (ColumnType::Int64, Some(ConvexValue::Int64(v))) => json!(v),
+        (ColumnType::Float64, Some(ConvexValue::Float64(v))) => {
+            if v.is_nan() {
+                json!("NaN")
+            } else if v.is_infinite() {
+                json!(if v > 0. { "Infinity" } else { "-Infinity" })
+            } else {
+                json!(v)
+            }
+        },
+        (_, Some(v)) => v.export(ValueFormat::ConvexCleanJSON),
+    }
+}
+
+enum Encoder {
+    Ndjson,
+    Parquet {
+        schema: SchemaRef,
+        writer: ArrowWriter<Vec<u8>>,
+        builders: Vec<ColumnBuilder>,
+        num_buffered: usize,
+    },
+}
+
+/// Writes a table's documents to storage as NDJSON or Parquet.
+pub struct ColumnarTableUpload {
+    upload: Box<dyn Upload>,
+    columns: Vec<Column>,
+    encoder: Encoder,
+    num_documents: u64,
+}
+
+impl ColumnarTableUpload {
+    pub async fn new(
+        storage: Arc<dyn Storage>,
+        format: ColumnarFormat,
+        columns: Vec<Column>,
+    ) -> anyhow::Result<Self> {
+        let encoder = match format {
+            ColumnarFormat::Ndjson => Encoder::Ndjson,
+            ColumnarFormat::Parquet => {
+                let schema = Arc::new(Schema::new(
+                    columns.iter().map(Column::field).collect::<Vec<_>>(),
+                ));
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                Encoder::Parquet {
+                    writer: ArrowWriter::try_new(vec![], schema.clone(), Some(properties))?,
+                    schema,
+                    builders: columns
+                        .iter()
+                        .map(|column| ColumnBuilder::new(column.column_type))
+                        .collect(),
+                    num_buffered: 0,
+                }
+            },
+        };
+        Ok(Self {
+            upload: storage.start_upload().await?,
+            columns,
+            encoder,
+            num_documents: 0,
+        })
+    }
+
+    pub async fn write(&mut self, doc: ResolvedDocument) -> anyhow::Result<()> {
+        let document = doc.into_value().0;
+        self.num_documents += 1;
+        match &mut self.encoder {
+            Encoder::Ndjson => {
+                let row: serde_json::Map<_, _> = self
+                    .columns
+                    .iter()
+                    .map(|column| {
+                        let value = ndjson_value(column.column_type, column.value(&document));
+                        (column.name(), value)
+                    })
+                    .collect();
+                let mut buf = serde_json::to_vec(&row)?;
+                buf.push(b'\n');
+                self.upload.write(buf.into()).await?;
+            },
+            Encoder::Parquet {
+                builders,
+                num_buffered,
+                ..
+            } => {
+                for (column, builder) in self.columns.iter().zip(builders.iter_mut()) {
+                    builder.append(column.value(&document))?;
+                }
+                *num_buffered += 1;
+                if *num_buffered >= PARQUET_BATCH_SIZE {
+                    self.write_batch().await?;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Encodes the buffered documents, uploading whatever the Parquet writer
+    /// has produced so far.
+    async fn write_batch(&mut self) -> anyhow::Result<()> {
+        let Encoder::Parquet {
+            schema,
+            writer,
+            builders,
+            num_buffered,
+        } = &mut self.encoder
+        else {
+            return Ok(());
+        };
+        if *num_buffered > 0 {
+            let arrays = builders.iter_mut().map(ColumnBuilder::finish).collect();
+            writer.write(&RecordBatch::try_new(schema.clone(), arrays)?)?;
+            *num_buffered = 0;
+        }
+        if writer.in_progress_size() >= PARQUET_MAX_ROW_GROUP_BYTES {
+            writer.flush()?;
+        }
+        let buf = std::mem::take(writer.inner_mut());
+        if !buf.is_empty() {
+            self.upload.write(buf.into()).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn complete(mut self) -> anyhow::Result<(ObjectKey, u64)> {
+        self.write_batch().await?;
+        if let Encoder::Parquet { writer, .. } = self.encoder {
+            let buf = writer.into_inner()?;
+            self.upload.write(buf.into()).await?;
+        }
+        Ok((self.upload.complete().await?, self.num_documents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use shape_inference::{
+        CountedShape,
+        ProdConfigWithOptionalFields,
+    };
+    use value::{
+        assert_obj,
+        assert_val,
+        ConvexValue,
+    };
+
+    use super::{
+        ndjson_value,
+        table_columns,
+        ColumnSource,
+        ColumnType,
+    };
+
+    fn shape_of(documents: Vec<ConvexValue>) -> CountedShape<ProdConfigWithOptionalFields> {
+        documents
+            .iter()
+            .fold(CountedShape::empty(), |shape, document| {
+                shape.insert_value(document)
+            })
+    }
+
+    #[test]
+    fn test_table_columns() -> anyhow::Result<()> {
+        let shape = shape_of(vec![
+            ConvexValue::Object(assert_obj!(
+                "_id" => "k57a9rvnc6ft0bhcdp8qr0rtcn6z7w9s",
+                "_creationTime" => 1715000000000.,
+                "name" => "Sarah",
+                "age" => 35,
+                "tags" => ["admin"],
+                "email" => "sarah@example.com",
+            )),
+            ConvexValue::Object(assert_obj!(
+                "_id" => "k57bnzrmgaf5r9s8vp5h1q6xr96z7k2d",
+                "_creationTime" => 1715000000001.,
+                "name" => "Sam",
+                "age" => 36.5,
+                "tags" => [],
+                "email" => "sam@example.com",
+            )),
+        ]);
+        let excluded = BTreeSet::from(["email".parse()?]);
+        let columns = table_columns(&shape, Some(&excluded))?;
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|c| (c.name(), c.column_type, c.nullable))
+            .collect();
+        assert_eq!(
+            columns,
+            vec![
+                ("_id".to_string(), ColumnType::Utf8, false),
+                ("_creationTime".to_string(), ColumnType::Float64, false),
+                ("age".to_string(), ColumnType::Json, false),
+                ("name".to_string(), ColumnType::Utf8, false),
+                ("tags".to_string(), ColumnType::Json, false),
+            ]
+        );
+
+        let columns = table_columns(&CountedShape::empty(), None)?;
+        assert_eq!(columns.len(), 2);
+        assert!(columns
+            .iter()
+            .all(|c| c.source != ColumnSource::OtherFields));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ndjson_value() {
+        assert_eq!(
+            ndjson_value(ColumnType::Int64, Some(ConvexValue::Int64(1 << 40))),
+            serde_json::json!(1i64 << 40)
+        );
+        assert_eq!(
+            ndjson_value(ColumnType::Float64, Some(ConvexValue::Float64(f64::NAN))),
+            serde_json::json!("NaN")
+        );
+        assert_eq!(
+            ndjson_value(ColumnType::Json, Some(assert_val!(["a", "b"]))),
+            serde_json::json!(["a", "b"])
+        );
+        assert_eq!(
+            ndjson_value(ColumnType::Utf8, None),
+            serde_json::Value::Null
+        );
+    }
+}
@@ -74,6 +74,8 @@ use model::{
             Export,
             ExportFormat,
             ExportObjectKeys,
+            ExportSelection,
+            FileStorageLayout,
         },
         EXPORTS_BY_STATE_AND_TS_INDEX,
         EXPORTS_STATE_FIELD,
@@ -122,11 +124,20 @@ use value::{
     VirtualTableMapping,
 };
 
+use self::columnar::{
+    table_columns,
+    ColumnarFormat,
+    ColumnarTableUpload,
+    ExportManifest,
+    TableManifest,
+};
 use crate::metrics::{
     export_timer,
     log_worker_starting,
 };
 
+mod columnar;
+
 const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
 const MAX_BACKOFF: Duration = Duration::from_secs(900); // 15 minutes
 static BEGIN_JSON_ARRAY: Bytes = Bytes::from_static("[\n".as_bytes());
@@ -167,14 +178,7 @@ impl<RT: Runtime> ExportWorker<RT> {
         file_storage: Arc<dyn Storage>,
         usage_tracking: UsageCounter,
     ) -> impl Future<Output = ()> + Send {
-        let mut worker = Self {
-            runtime,
-            database,
-            storage,
-            file_storage,
-            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
-            usage_tracking,
-        };
+        let mut worker = Self::create(runtime, database, storage, file_storage, usage_tracking);
         async move {
             loop {
                 if let Err(e) = worker.run().await {
@@ -188,6 +192,25 @@ impl<RT: Runtime> ExportWorker<RT> {
         }
     }
 
+    /// A worker that isn't subscribed to `_exports`, for exporting snapshots
+    /// on behalf of another worker.
+    pub(crate) fn create(
+        runtime: RT,
+        database: Database<RT>,
+        storage: Arc<dyn Storage>,
+        file_storage: Arc<dyn Storage>,
+        usage_tracking: UsageCounter,
+    ) -> Self {
+        Self {
+            runtime,
+            database,
+            storage,
+            file_storage,
+            backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
+            usage_tracking,
+        }
+    }
+
     #[cfg(test)]
     pub fn new_test(
         runtime: RT,
@@ -335,6 +358,7 @@ impl<RT: Runtime> ExportWorker<RT> {
     async fn export_inner(
         &mut self,
         format: ExportFormat,
+        selection: &ExportSelection,
     ) -> anyhow::Result<(Timestamp, ExportObjectKeys, FunctionUsageTracker)> {
         tracing::info!("Beginning snapshot export...");
         let storage = &self.storage;
@@ -345,6 +369,7 @@ impl<RT: Runtime> ExportWorker<RT> {
             let tables: BTreeMap<_, _> = snapshot
                 .table_registry
                 .iter_active_user_tables()
+                .filter(|(.., table_name)| selection.includes_table(table_name))
                 .map(|(tablet_id, _, table_number, table_name)| {
                     (
                         tablet_id,
@@ -374,7 +399,10 @@ impl<RT: Runtime> ExportWorker<RT> {
             tables.iter().map(|(tablet_id, ..)| *tablet_id).collect();
 
         match format {
-            ExportFormat::Zip { include_storage } => {
+            ExportFormat::Zip {
+                include_storage,
+                storage_layout,
+            } => {
                 // Start upload.
                 let mut upload = storage.start_upload().await?;
                 let (sender, receiver) = mpsc::channel::<Bytes>(1);
@@ -389,7 +417,8 @@ impl<RT: Runtime> ExportWorker<RT> {
                     by_id_indexes,
                     system_tables,
                     virtual_tables,
-                    include_storage,
+                    include_storage.then_some(storage_layout),
+                    selection,
                     usage.clone(),
                 );
                 let (_, ()) = try_join!(uploader, zipper)?;
@@ -416,9 +445,14 @@ impl<RT: Runtime> ExportWorker<RT> {
                     let (tablet_id, mut table_upload) = table_uploads
                         .remove_entry(tablet_id)
                         .ok_or_else(|| anyhow::anyhow!("No table with id {} found", tablet_id))?;
+                    let table_name = &tables
+                        .get(&tablet_id)
+                        .ok_or_else(|| anyhow::anyhow!("No table with id {} found", tablet_id))?
+                        .1;
 
                     // Write documents from stream to table uploads
                     while let Some((doc, _ts)) = stream.try_next().await? {
+                        let doc = selection.filter_document(table_name, doc)?;
                         table_upload = table_upload.write(doc).await?;
                     }
                     table_uploads.insert(tablet_id, table_upload);
@@ -451,6 +485,62 @@ impl<RT: Runtime> ExportWorker<RT> {
                     FunctionUsageTracker::new(),
                 ))
             },
+            ExportFormat::Ndjson | ExportFormat::Parquet => {
+                let format = if format == ExportFormat::Ndjson {
+                    ColumnarFormat::Ndjson
+                } else {
+                    ColumnarFormat::Parquet
+                };
+                let mut manifest = ExportManifest {
+                    format: format.extension(),
+                    snapshot_ts: ts.to_string(),
+                    tables: BTreeMap::new(),
+                };
+                let mut table_object_keys = BTreeMap::new();
+                for (tablet_id, (_, table_name, table_summary)) in tables.iter() {
+                    let by_id = by_id_indexes
+                        .get(tablet_id)
+                        .ok_or_else(|| anyhow::anyhow!("no by_id index for {} found", tablet_id))?;
+                    let columns = table_columns(
+                        table_summary.inferred_type(),
+                        selection.excluded_fields.get(table_name),
+                    )?;
+                    let mut table_upload =
+                        ColumnarTableUpload::new(self.storage.clone(), format, columns.clone())
+                            .await?;
+                    let table_iterator = self.database.table_iterator(ts, 1000, None);
+                    let stream = table_iterator.stream_documents_in_table(*tablet_id, *by_id, None);
+                    pin_mut!(stream);
+                    while let Some((doc, _ts)) = stream.try_next().await? {
+                        let doc = selection.filter_document(table_name, doc)?;
+                        table_upload.write(doc).await?;
+                    }
+                    let (object_key, num_documents) = table_upload.complete().await?;
+                    manifest.tables.insert(
+                        table_name.to_string(),
+                        TableManifest::new(format, table_name, num_documents, &columns),
+                    );
+                    table_object_keys.insert(table_name.clone(), object_key);
+                }
+
+                let mut upload = storage.start_upload().await?;
+                upload
+                    .write(serde_json::to_vec_pretty(&manifest)?.into())
+                    .await?;
+                let manifest = upload.complete().await?;
+                tracing::info!(
+                    "Export succeeded! {} snapshots written to storage. Format: {format:?}",
+                    tablet_ids.len()
+                );
+                Ok((
+                    *ts,
+                    ExportObjectKeys::ByTableWithManifest {
+                        tables: table_object_keys,
+                        manifest,
+                    },
+                    FunctionUsageTracker::new(),
+                ))
+            },
         }
     }
 
@@ -462,7 +552,8 @@ impl<RT: Runtime> ExportWorker<RT> {
         by_id_indexes: BTreeMap<TabletId, IndexId>,
         system_tables: BTreeMap<TableName, TabletId>,
         virtual_tables: VirtualTableMapping,
-        include_storage: bool,
+        storage_layout: Option<FileStorageLayout>,
+        selection: &ExportSelection,
         usage: FunctionUsageTracker,
     ) -> anyhow::Result<()> {
         let mut zip_snapshot_upload = ZipSnapshotUpload::new(&mut writer).await?;
@@ -492,7 +583,7 @@ impl<RT: Runtime> ExportWorker<RT> {
             table_upload.complete().await?;
         }
 
-        if include_storage {
+        if let Some(storage_layout) = storage_layout {
             // _storage
             let tablet_id = system_tables
                 .get(&FILE_STORAGE_TABLE)
@@ -535,6 +626,9 @@ impl<RT: Runtime> ExportWorker<RT> {
             }
             table_upload.complete().await?;
 
+            // Then the files themselves, either as entries in the ZIP or as
+            // separate objects listed in _storage/manifest.jsonl.
+            let mut manifest = vec![];
             let table_iterator = self.database.table_iterator(snapshot_ts, 1000, None);
             let stream = table_iterator.stream_documents_in_table(*tablet_id, *by_id, None);
             pin_mut!(stream);
@@ -571,9 +665,32 @@ impl<RT: Runtime> ExportWorker<RT> {
                 usage
                     .track_storage_call("snapshot_export")
                     .track_storage_egress_size(file_stream.content_length as u64);
-                zip_snapshot_upload
-                    .stream_full_file(path, file_stream.stream)
-                    .await?;
+                match storage_layout {
+                    FileStorageLayout::InZip => {
+                        zip_snapshot_upload
+                            .stream_full_file(path, file_stream.stream)
+                            .await?;
+                    },
+                    FileStorageLayout::SeparateObjects => {
+                        let mut upload = self.storage.start_upload().await?;
+                        upload
+                            .try_write_parallel_and_hash(
+                                file_stream.stream.map_err(anyhow::Error::from),
+                            )
+                            .await?;
+                        manifest.push(FileStorageObjectManifestEntry {
+                            id: virtual_storage_id.encode(),
+                            object_key: upload.complete().await?.to_string(),
+                        });
+                    },
+                }
+            }
+            if storage_layout == FileStorageLayout::SeparateObjects {
+                let mut manifest_upload = zip_snapshot_upload.start_storage_manifest().await?;
+                for entry in manifest {
+                    manifest_upload.write_json_line(json!(entry)).await?;
+                }
+                manifest_upload.complete().await?;
             }
         }
 
@@ -590,6 +707,7 @@ impl<RT: Runtime> ExportWorker<RT> {
                 let stream = table_iterator.stream_documents_in_table(*tablet_id, *by_id, None);
                 pin_mut!(stream);
                 while let Some((doc, _ts)) = stream.try_next().await? {
+                    let doc = selection.filter_document(&table_name, doc)?;
                     generated_schema.insert(doc.value(), doc.developer_id());
                 }
             }
@@ -605,6 +723,7 @@ impl<RT: Runtime> ExportWorker<RT> {
             // Write documents from stream to table uploads
             while let Some((doc, _ts)) = stream.try_next().await? {
                 usage.track_database_egress_size(table_name.to_string(), doc.size() as u64, false);
+                let doc = selection.filter_document(&table_name, doc)?;
                 table_upload.write(doc).await?;
             }
             table_upload.complete().await?;
@@ -620,7 +739,9 @@ impl<RT: Runtime> ExportWorker<RT> {
         &mut self,
         export: ParsedDocument<Export>,
     ) -> anyhow::Result<()> {
-        let (ts, object_keys, usage) = self.export_inner(export.format()).await?;
+        let (ts, object_keys, usage) = self
+            .export_inner(export.format(), export.selection())
+            .await?;
 
         let mut tx = self.database.begin(Identity::system()).await?;
         let completed_export =
@@ -641,6 +762,35 @@ impl<RT: Runtime> ExportWorker<RT> {
         );
         Ok(())
     }
+
+    /// Export every user table as a ZIP snapshot without recording it in
+    /// `_exports`, returning its snapshot timestamp and object key.
+    pub(crate) async fn export_zip(
+        &mut self,
+        include_storage: bool,
+    ) -> anyhow::Result<(Timestamp, ObjectKey)> {
+        // The ZIP is imported by other deployments, which can't read this
+        // one's export storage, so files always go in the ZIP.
+        let (ts, object_keys, usage) = self
+            .export_inner(
+                ExportFormat::Zip {
+                    include_storage,
+                    storage_layout: FileStorageLayout::InZip,
+                },
+                &ExportSelection::default(),
+            )
+            .await?;
+        self.usage_tracking.track_call(
+            UdfIdentifier::Cli("export".to_string()),
+            ExecutionId::new(),
+            CallType::Export,
+            usage.gather_user_stats(),
+        );
+        let ExportObjectKeys::Zip(object_key) = object_keys else {
+            anyhow::bail!("ZIP export produced {object_keys:?}");
+        };
+        Ok((ts, object_key))
+    }
 }
 
 struct TableUpload {
@@ -664,16 +814,20 @@ impl TableUpload {
 
     async fn write(mut self, doc: ResolvedDocument) -> anyhow::Result<Self> {
         let json = match self.format {
-            ExportFormat::CleanJsonl | ExportFormat::Zip { .. } => {
-                doc.export(ValueFormat::ConvexCleanJSON)
-            },
+            ExportFormat::CleanJsonl
+            | ExportFormat::Zip { .. }
+            | ExportFormat::Ndjson
+            | ExportFormat::Parquet => doc.export(ValueFormat::ConvexCleanJSON),
             ExportFormat::InternalJson => doc.export(ValueFormat::ConvexEncodedJSON),
         };
         if !self.empty {
             // Between documents.
             match self.format {
                 ExportFormat::InternalJson => self.upload.write(BETWEEN_DOCUMENTS.clone()).await?,
-                ExportFormat::CleanJsonl | ExportFormat::Zip { .. } => {},
+                ExportFormat::CleanJsonl
+                | ExportFormat::Zip { .. }
+                | ExportFormat::Ndjson
+                | ExportFormat::Parquet => {},
             }
         }
         self.empty = false;
@@ -683,9 +837,10 @@ impl TableUpload {
 
         // After documents.
         match self.format {
-            ExportFormat::CleanJsonl | ExportFormat::Zip { .. } => {
-                self.upload.write(AFTER_DOCUMENTS_CLEAN.clone()).await?
-            },
+            ExportFormat::CleanJsonl
+            | ExportFormat::Zip { .. }
+            | ExportFormat::Ndjson
+            | ExportFormat::Parquet => self.upload.write(AFTER_DOCUMENTS_CLEAN.clone()).await?,
             ExportFormat::InternalJson => {},
         }
 
@@ -713,6 +868,17 @@ pub struct FileStorageZipMetadata {
     pub internal_id: Option<String>,
 }
 
+/// A line of `_storage/manifest.jsonl` in a ZIP export whose files were
+/// written as separate objects.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStorageObjectManifestEntry {
+    #[serde(rename = "_id")]
+    pub id: String,
+    /// The key of the file's contents in export storage.
+    pub object_key: String,
+}
+
 // 'a is lifetime of entire zip file writer.
 // 'b is lifetime of entry writer for a single table.
 struct ZipSnapshotTableUpload<'a, 'b> {
@@ -724,8 +890,14 @@ impl<'a, 'b> ZipSnapshotTableUpload<'a, 'b> {
         zip_writer: &'b mut ZipFileWriter<&'a mut ChannelWriter>,
         table_name: TableName,
     ) -> anyhow::Result<Self> {
-        let source_path = format!("{table_name}/documents.jsonl");
-        let builder = ZipEntryBuilder::new(source_path.clone(), Compression::Deflate)
+        Self::new_at_path(zip_writer, format!("{table_name}/documents.jsonl")).await
+    }
+
+    async fn new_at_path(
+        zip_writer: &'b mut ZipFileWriter<&'a mut ChannelWriter>,
+        source_path: String,
+    ) -> anyhow::Result<Self> {
+        let builder = ZipEntryBuilder::new(source_path, Compression::Deflate)
             .unix_permissions(ZIP_ENTRY_PERMISSIONS);
         let entry_writer = zip_writer.write_entry_stream(builder.build()).await?;
         Ok(Self { entry_writer })
@@ -813,6 +985,11 @@ impl<'a> ZipSnapshotUpload<'a> {
         ZipSnapshotTableUpload::new(&mut self.writer, table_name).await
     }
 
+    async fn start_storage_manifest(&mut self) -> anyhow::Result<ZipSnapshotTableUpload<'a, '_>> {
+        let path = format!("{}/manifest.jsonl", *FILE_STORAGE_VIRTUAL_TABLE);
+        ZipSnapshotTableUpload::new_at_path(&mut self.writer, path).await
+    }
+
     async fn write_generated_schema<T: ShapeConfig>(
         &mut self,
         table_name: &TableName,
@@ -880,11 +1057,17 @@ mod tests {
     };
     use headers::ContentType;
     use keybroker::Identity;
+    use maplit::{
+        btreemap,
+        btreeset,
+    };
     use model::{
         exports::types::{
             Export,
             ExportFormat,
             ExportObjectKeys,
+            ExportSelection,
+            FileStorageLayout,
         },
         file_storage::types::FileStorageEntry,
         test_helpers::DbFixturesWithModel,
@@ -908,6 +1091,7 @@ mod tests {
 
     use super::{
         ExportWorker,
+        FileStorageObjectManifestEntry,
         TableUpload,
     };
     use crate::export_worker::README_MD_CONTENTS;
@@ -929,7 +1113,9 @@ mod tests {
                 .await?;
             db.commit(tx).await?;
         }
-        let (_, tables, _) = export_worker.export_inner(ExportFormat::CleanJsonl).await?;
+        let (_, tables, _) = export_worker
+            .export_inner(ExportFormat::CleanJsonl, &ExportSelection::default())
+            .await?;
         must_let!(let ExportObjectKeys::ByTable(tables) = tables);
         let mut expected_tables = BTreeMap::new();
         for i in 0..2 {
@@ -1038,9 +1224,13 @@ mod tests {
             db.commit(tx).await?;
         }
         let (_, object_keys, usage) = export_worker
-            .export_inner(ExportFormat::Zip {
-                include_storage: true,
-            })
+            .export_inner(
+                ExportFormat::Zip {
+                    include_storage: true,
+                    storage_layout: FileStorageLayout::InZip,
+                },
+                &ExportSelection::default(),
+            )
             .await?;
         must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
 
@@ -1132,9 +1322,13 @@ mod tests {
         );
 
         let (_, object_keys, usage) = export_worker
-            .export_inner(ExportFormat::Zip {
-                include_storage: true,
-            })
+            .export_inner(
+                ExportFormat::Zip {
+                    include_storage: true,
+                    storage_layout: FileStorageLayout::InZip,
+                },
+                &ExportSelection::default(),
+            )
             .await?;
         must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
 
@@ -1165,6 +1359,187 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_export_storage_separate_objects(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new_with_model(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker = ExportWorker::new_test(
+            rt.clone(),
+            db.clone(),
+            storage.clone(),
+            file_storage.clone(),
+        );
+        let file_storage_wrapper = FileStorage {
+            database: db.clone(),
+            transactional_file_storage: TransactionalFileStorage::new(
+                rt,
+                file_storage,
+                ConvexOrigin::from("origin".to_string()),
+            ),
+        };
+        let file_id = file_storage_wrapper
+            .store_file(
+                TableNamespace::test_user(),
+                None,
+                Some(ContentType::jpeg()),
+                futures::stream::iter(vec![Ok(Bytes::from_static(b"abc"))]),
+                None,
+                &FunctionUsageTracker::new(),
+            )
+            .await?;
+
+        let (_, object_keys, _) = export_worker
+            .export_inner(
+                ExportFormat::Zip {
+                    include_storage: true,
+                    storage_layout: FileStorageLayout::SeparateObjects,
+                },
+                &ExportSelection::default(),
+            )
+            .await?;
+        must_let!(let ExportObjectKeys::Zip(object_key) = object_keys);
+        let stored_bytes = storage
+            .get(&object_key)
+            .await?
+            .context("object missing from storage")?
+            .collect_as_bytes()
+            .await?;
+        let mut zip_reader = async_zip::read::mem::ZipFileReader::new(&stored_bytes).await?;
+        let filenames: Vec<_> = zip_reader
+            .entries()
+            .into_iter()
+            .map(|entry| entry.filename().to_string())
+            .collect();
+        // The file's metadata is in the ZIP, but its contents aren't.
+        assert!(filenames.contains(&"_storage/documents.jsonl".to_string()));
+        assert!(!filenames.contains(&format!("_storage/{file_id}.jpeg")));
+        let i = filenames
+            .iter()
+            .position(|filename| filename == "_storage/manifest.jsonl")
+            .context("manifest missing from zip")?;
+        let manifest =
+            String::from_utf8(zip_reader.entry_reader(i).await?.read_to_end_crc().await?)?;
+        let entries: Vec<FileStorageObjectManifestEntry> = manifest
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, file_id.encode());
+        let contents = storage
+            .get(&entries[0].object_key.clone().try_into()?)
+            .await?
+            .context("file object missing from storage")?
+            .collect_as_bytes()
+            .await?;
+        assert_eq!(contents, Bytes::from_static(b"abc"));
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_export_selection(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker =
+            ExportWorker::new_test(rt, db.clone(), storage.clone(), file_storage);
+
+        for table in ["users", "messages"] {
+            let mut tx = db.begin(Identity::system()).await?;
+            UserFacingModel::new_root_for_test(&mut tx)
+                .insert(
+                    table.parse()?,
+                    assert_obj!("name" => "Sarah", "email" => "sarah@example.com"),
+                )
+                .await?;
+            db.commit(tx).await?;
+        }
+        let users: TableName = "users".parse()?;
+        let selection = ExportSelection {
+            tables: Some(btreeset! { users.clone() }),
+            excluded_fields: btreemap! { users.clone() => btreeset! { "email".parse()? } },
+        };
+        let (_, tables, _) = export_worker
+            .export_inner(ExportFormat::CleanJsonl, &selection)
+            .await?;
+        must_let!(let ExportObjectKeys::ByTable(tables) = tables);
+        assert_eq!(tables.keys().collect::<Vec<_>>(), vec![&users]);
+
+        let content = storage
+            .get(&tables[&users])
+            .await?
+            .context("Not found")?
+            .collect_as_bytes()
+            .await?;
+        let exported: serde_json::Value = serde_json::from_slice(&content)?;
+        assert_eq!(exported["name"], json!("Sarah"));
+        assert!(exported.get("email").is_none());
+        assert!(exported.get("_id").is_some());
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_export_ndjson(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let file_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt.clone())?);
+        let mut export_worker =
+            ExportWorker::new_test(rt, db.clone(), storage.clone(), file_storage);
+
+        let mut tx = db.begin(Identity::system()).await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .insert(
+                "users".parse()?,
+                assert_obj!("name" => "Sarah", "visits" => 1i64 << 60, "tags" => ["admin"]),
+            )
+            .await?;
+        db.commit(tx).await?;
+
+        let (_, object_keys, _) = export_worker
+            .export_inner(ExportFormat::Ndjson, &ExportSelection::default())
+            .await?;
+        must_let!(let ExportObjectKeys::ByTableWithManifest { tables, manifest } = object_keys);
+        let users: TableName = "users".parse()?;
+        let content = storage
+            .get(&tables[&users])
+            .await?
+            .context("Not found")?
+            .collect_as_bytes()
+            .await?;
+        let exported: serde_json::Value = serde_json::from_slice(&content)?;
+        assert_eq!(exported["name"], json!("Sarah"));
+        assert_eq!(exported["visits"], json!(1i64 << 60));
+        assert_eq!(exported["tags"], json!(["admin"]));
+
+        let content = storage
+            .get(&manifest)
+            .await?
+            .context("Not found")?
+            .collect_as_bytes()
+            .await?;
+        let manifest: serde_json::Value = serde_json::from_slice(&content)?;
+        let table = &manifest["tables"]["users"];
+        assert_eq!(table["file"], json!("users.ndjson"));
+        assert_eq!(table["numDocuments"], json!(1));
+        let column_types: Vec<_> = table["columns"]
+            .as_array()
+            .context("columns must be an array")?
+            .iter()
+            .map(|column| (column["name"].clone(), column["arrowType"].clone()))
+            .collect();
+        assert_eq!(
+            column_types,
+            vec![
+                (json!("_id"), json!("Utf8")),
+                (json!("_creationTime"), json!("Float64")),
+                (json!("name"), json!("Utf8")),
+                (json!("tags"), json!("Utf8")),
+                (json!("visits"), json!("Int64")),
+            ]
+        );
+        Ok(())
+    }
+
     // Regression test: previously we were trying to export documents from deleted
     // tables and table_mapping was failing.
     #[convex_macro::test_runtime]
@@ -1192,7 +1567,9 @@ mod tests {
             .await?;
         db.commit(tx).await?;
 
-        let (_, tables, _) = export_worker.export_inner(ExportFormat::CleanJsonl).await?;
+        let (_, tables, _) = export_worker
+            .export_inner(ExportFormat::CleanJsonl, &ExportSelection::default())
+            .await?;
         must_let!(let ExportObjectKeys::ByTable(tables) = tables);
         let tables: Vec<_> = tables.into_keys().collect();
         assert_eq!(tables, vec!["table_1".parse()?]);
@@ -1269,7 +1646,8 @@ mod tests {
         let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
 
         // Requested
-        let requested_export = Export::requested(ExportFormat::CleanJsonl);
+        let requested_export =
+            Export::requested(ExportFormat::CleanJsonl, ExportSelection::default());
         let object: ConvexObject = requested_export.clone().try_into()?;
         let deserialized_export = object.try_into()?;
         assert_eq!(requested_export, deserialized_export);
@@ -0,0 +1,381 @@
+use std::{
+    collections::BTreeSet,
+    sync::Arc,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use common::{
+    backoff::Backoff,
+    document::{
+        CreationTime,
+        ParsedDocument,
+    },
+    errors::report_error,
+    knobs::{
+        FILE_LIFECYCLE_BATCH_SIZE,
+        FILE_LIFECYCLE_RULE_INTERVAL,
+    },
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::{
+    Database,
+    IndexModel,
+};
+use futures::{
+    future::Either,
+    pin_mut,
+    select_biased,
+    Future,
+    FutureExt,
+    TryStreamExt,
+};
+use keybroker::Identity;
+use model::{
+    file_lifecycle_rules::{
+        types::{
+            FileLifecycleRule,
+            LifecycleAction,
+            LifecycleRunReport,
+            MAX_REPORT_SAMPLE_IDS,
+        },
+        FileLifecycleRulesModel,
+    },
+    file_storage::{
+        types::{
+            FileStorageEntry,
+            FileStorageFilter,
+            StorageUuid,
+        },
+        FileStorageId,
+        FileStorageModel,
+        FILE_STORAGE_TABLE,
+        FILE_STORAGE_VIRTUAL_TABLE,
+    },
+};
+use storage::Storage;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    InternalId,
+    TableNamespace,
+    TableNumber,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+/// Runs the rules in `_file_lifecycle_rules` every
+/// `FILE_LIFECYCLE_RULE_INTERVAL`, deleting the files each rule matches or
+/// moving them to a colder storage class. A dry run only counts the files.
+/// The report of each rule's latest run, including any error, is stored on
+/// the rule.
+pub struct FileLifecycleWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    files_storage: Arc<dyn Storage>,
+}
+
+impl<RT: Runtime> FileLifecycleWorker<RT> {
+    pub fn new(runtime: RT, database: Database<RT>, files_storage: Arc<dyn Storage>) -> Self {
+        Self {
+            runtime,
+            database,
+            files_storage,
+        }
+    }
+
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        files_storage: Arc<dyn Storage>,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self::new(runtime, database, files_storage);
+        async move {
+            tracing::info!("Starting FileLifecycleWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("FileLifecycleWorker died"));
+                tracing::error!("File lifecycle worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let now = self.runtime.generate_timestamp()?;
+            let mut due = None;
+            let mut next_ts: Option<Timestamp> = None;
+            for rule in FileLifecycleRulesModel::new(&mut tx).list().await? {
+                let rule_next_ts = match &rule.last_run {
+                    Some(report) => report.started_ts.add(*FILE_LIFECYCLE_RULE_INTERVAL)?,
+                    None => now,
+                };
+                if rule_next_ts <= now {
+                    due = Some(rule);
+                    break;
+                }
+                next_ts = Some(next_ts.map_or(rule_next_ts, |ts| ts.min(rule_next_ts)));
+            }
+            if let Some(rule) = due {
+                self.run_rule(rule).await?;
+                backoff.reset();
+                continue;
+            }
+
+            let next_rule_future = match next_ts {
+                Some(next_ts) => Either::Left(self.runtime.wait(next_ts - now)),
+                None => Either::Right(std::future::pending()),
+            };
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            select_biased! {
+                _ = next_rule_future.fuse() => {},
+                _ = subscription.wait_for_invalidation().fuse() => {},
+            }
+            backoff.reset();
+        }
+    }
+
+    /// Run `rule` and record its report. Failing to apply the rule fails the
+    /// run rather than the worker, so the other rules still run.
+    async fn run_rule(&self, rule: ParsedDocument<FileLifecycleRule>) -> anyhow::Result<()> {
+        tracing::info!("Running file lifecycle rule {}", rule.name);
+        let mut report = LifecycleRunReport {
+            started_ts: self.runtime.generate_timestamp()?,
+            finished_ts: Timestamp::MIN,
+            dry_run: rule.dry_run,
+            files_matched: 0,
+            bytes_matched: 0,
+            files_applied: 0,
+            sample_storage_ids: vec![],
+            error: None,
+        };
+        if let Err(mut e) = self
+            .apply_rule(&rule, self.runtime.system_time(), &mut report)
+            .await
+        {
+            report_error(&mut e);
+            report.error = Some(format!("{e:#}"));
+        }
+        report.finished_ts = self.runtime.generate_timestamp()?;
+        tracing::info!(
+            "File lifecycle rule {} matched {} files and applied to {}",
+            rule.name,
+            report.files_matched,
+            report.files_applied
+        );
+        let mut tx = self.database.begin(Identity::system()).await?;
+        FileLifecycleRulesModel::new(&mut tx)
+            .set_last_run(rule.id(), report)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "file_lifecycle_rule_report")
+            .await?;
+        Ok(())
+    }
+
+    /// Apply `rule` to the files of every component as of `now`, counting
+    /// what it matches and changes in `report`.
+    pub(crate) async fn apply_rule(
+        &self,
+        rule: &FileLifecycleRule,
+        now: SystemTime,
+        report: &mut LifecycleRunReport,
+    ) -> anyhow::Result<()> {
+        let min_age = Duration::from_secs(rule.min_age_days * 24 * 60 * 60);
+        let uploaded_before = now
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .saturating_sub(min_age);
+        let filter = FileStorageFilter {
+            uploaded_before: Some(CreationTime::try_from(
+                uploaded_before.as_secs_f64() * 1000.,
+            )?),
+            ..Default::default()
+        };
+        let namespaces: BTreeSet<TableNamespace> = {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let table_mapping = tx.table_mapping();
+            table_mapping
+                .iter()
+                .filter(|(tablet_id, .., table_name)| {
+                    **table_name == *FILE_STORAGE_TABLE && table_mapping.is_active(*tablet_id)
+                })
+                .map(|(_, namespace, ..)| namespace)
+                .collect()
+        };
+        for namespace in namespaces {
+            let references = if rule.only_orphaned {
+                Some(self.file_references(namespace).await?)
+            } else {
+                None
+            };
+            let mut cursor = None;
+            loop {
+                let mut tx = self.database.begin(Identity::system()).await?;
+                let page = FileStorageModel::new(&mut tx, namespace)
+                    .list_page(&filter, cursor, *FILE_LIFECYCLE_BATCH_SIZE)
+                    .await?;
+                let mut matched = vec![];
+                for (storage_id, file) in page.files {
+                    if let Some(references) = &references
+                        && references.contains(&file)
+                    {
+                        continue;
+                    }
+                    if let LifecycleAction::Transition { storage_class } = &rule.action
+                        && file.storage_class.as_ref() == Some(storage_class)
+                    {
+                        continue;
+                    }
+                    report.files_matched += 1;
+                    report.bytes_matched += u64::try_from(file.size)?;
+                    if report.sample_storage_ids.len() < MAX_REPORT_SAMPLE_IDS {
+                        report.sample_storage_ids.push(storage_id.encode());
+                    }
+                    matched.push((storage_id, file));
+                }
+                if !rule.dry_run && !matched.is_empty() {
+                    match &rule.action {
+                        LifecycleAction::Delete => {
+                            let mut model = FileStorageModel::new(&mut tx, namespace);
+                            for (storage_id, _) in &matched {
+                                model
+                                    .delete_file(
+                                        FileStorageId::DocumentId(*storage_id),
+                                        Identity::system(),
+                                    )
+                                    .await?;
+                            }
+                            self.database
+                                .commit_with_write_source(tx, "file_lifecycle_delete")
+                                .await?;
+                            report.files_applied += matched.len() as u64;
+                        },
+                        LifecycleAction::Transition { storage_class } => {
+                            drop(tx);
+                            for (_, file) in matched {
+                                self.transition(namespace, file, storage_class).await?;
+                                report.files_applied += 1;
+                            }
+                        },
+                    }
+                }
+                cursor = page.cursor;
+                if cursor.is_none() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Move a file's object to `storage_class`, then record that it's there.
+    async fn transition(
+        &self,
+        namespace: TableNamespace,
+        file: ParsedDocument<FileStorageEntry>,
+        storage_class: &str,
+    ) -> anyhow::Result<()> {
+        self.files_storage
+            .set_storage_class(&file.storage_key, storage_class)
+            .await?;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        FileStorageModel::new(&mut tx, namespace)
+            .set_storage_class(file.id(), storage_class.to_string())
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "file_lifecycle_transition")
+            .await?;
+        Ok(())
+    }
+
+    /// Find the files that documents in the tables of `namespace` reference,
+    /// by their `_storage` ids or, for files stored before those existed, by
+    /// their storage ids. References from the arguments of scheduled
+    /// functions aren't found.
+    async fn file_references(&self, namespace: TableNamespace) -> anyhow::Result<FileReferences> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let by_id_indexes = IndexModel::new(&mut tx).by_id_indexes().await?;
+        let snapshot = self.database.snapshot(tx.begin_timestamp())?;
+        let mut references = FileReferences {
+            storage_table: snapshot
+                .table_registry
+                .virtual_table_mapping()
+                .namespace(namespace)
+                .number(&FILE_STORAGE_VIRTUAL_TABLE)?,
+            ids: BTreeSet::new(),
+            legacy_ids: BTreeSet::new(),
+        };
+        let tablet_ids: Vec<_> = snapshot
+            .table_registry
+            .iter_active_user_tables()
+            .filter(|(_, table_namespace, ..)| *table_namespace == namespace)
+            .map(|(tablet_id, ..)| tablet_id)
+            .collect();
+        for tablet_id in tablet_ids {
+            let by_id = by_id_indexes
+                .get(&tablet_id)
+                .ok_or_else(|| anyhow::anyhow!("no by_id index for {tablet_id} found"))?;
+            let table_iterator = self
+                .database
+                .table_iterator(tx.begin_timestamp(), 1000, None);
+            let stream = table_iterator.stream_documents_in_table(tablet_id, *by_id, None);
+            pin_mut!(stream);
+            while let Some((doc, _ts)) = stream.try_next().await? {
+                for (_, value) in doc.value().iter() {
+                    references.add(value);
+                }
+            }
+        }
+        Ok(references)
+    }
+}
+
+/// The files that a component's documents reference.
+struct FileReferences {
+    storage_table: TableNumber,
+    ids: BTreeSet<InternalId>,
+    legacy_ids: BTreeSet<String>,
+}
+
+impl FileReferences {
+    fn add(&mut self, value: &ConvexValue) {
+        match value {
+            ConvexValue::String(s) => {
+                if let Ok(id) = DeveloperDocumentId::decode(s) {
+                    if id.table() == self.storage_table {
+                        self.ids.insert(id.internal_id());
+                    }
+                } else if s.parse::<StorageUuid>().is_ok() {
+                    self.legacy_ids.insert(s.to_string());
+                }
+            },
+            ConvexValue::Array(array) => array.iter().for_each(|value| self.add(value)),
+            ConvexValue::Set(set) => set.iter().for_each(|value| self.add(value)),
+            ConvexValue::Map(map) => map.iter().for_each(|(key, value)| {
+                self.add(key);
+                self.add(value);
+            }),
+            ConvexValue::Object(object) => object.iter().for_each(|(_, value)| self.add(value)),
+            ConvexValue::Null
+            | ConvexValue::Int64(_)
+            | ConvexValue::Float64(_)
+            | ConvexValue::Decimal(_)
+            | ConvexValue::GeoPoint(_)
+            | ConvexValue::Boolean(_)
+            | ConvexValue::Bytes(_) => {},
+        }
+    }
+
+    fn contains(&self, file: &ParsedDocument<FileStorageEntry>) -> bool {
+        self.ids.contains(&file.id().internal_id())
+            || self.legacy_ids.contains(&file.storage_id.to_string())
+    }
+}
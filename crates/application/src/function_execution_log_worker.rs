@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    knobs::{
+        FUNCTION_EXECUTION_LOG_BATCH_SIZE,
+        FUNCTION_EXECUTION_LOG_RETENTION,
+        JOB_EXECUTION_HISTORY_RETENTION,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    sync::mpsc,
+};
+use database::Database;
+use futures::{
+    select_biased,
+    Future,
+    FutureExt,
+};
+use keybroker::Identity;
+use model::{
+    error_groups::ErrorGroupsModel,
+    function_executions::FunctionExecutionsModel,
+    job_executions::JobExecutionsModel,
+    slow_queries::SlowQueriesModel,
+};
+
+use crate::{
+    function_log::{
+        PersistedExecution,
+        SlowExecution,
+    },
+    metrics::{
+        log_function_executions_expired,
+        log_function_executions_persisted,
+    },
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How often executions older than `FUNCTION_EXECUTION_LOG_RETENTION` are
+/// deleted.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Persists the executions logged to the `FunctionExecutionLog` into
+/// `_function_executions`, and deletes them once they are older than
+/// `FUNCTION_EXECUTION_LOG_RETENTION`. Errors thrown by those executions are
+/// grouped in `_error_groups`, and slow executions are also recorded in
+/// `_slow_queries` for the same retention. Scheduled job and cron runs
+/// recorded in `_job_executions` are deleted here too, once they are older
+/// than `JOB_EXECUTION_HISTORY_RETENTION`.
+pub struct FunctionExecutionLogWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    receiver: mpsc::Receiver<PersistedExecution>,
+}
+
+impl<RT: Runtime> FunctionExecutionLogWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        receiver: mpsc::Receiver<PersistedExecution>,
+    ) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            receiver,
+        };
+        async move {
+            tracing::info!("Starting FunctionExecutionLogWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("FunctionExecutionLogWorker died"));
+                tracing::error!("Function execution log worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&mut self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        let mut retention_timer = self.runtime.wait(Duration::ZERO);
+        loop {
+            let execution = select_biased! {
+                execution = self.receiver.recv().fuse() => match execution {
+                    Some(execution) => Some(execution),
+                    // The function log has been dropped, so the application is
+                    // shutting down.
+                    None => return Ok(()),
+                },
+                _ = retention_timer => None,
+            };
+            match execution {
+                Some(execution) => self.persist(execution).await?,
+                None => {
+                    self.delete_expired().await?;
+                    retention_timer = self.runtime.wait(RETENTION_INTERVAL);
+                },
+            }
+            backoff.reset();
+        }
+    }
+
+    /// Persists `first` along with any other executions that are already
+    /// waiting, up to `FUNCTION_EXECUTION_LOG_BATCH_SIZE`.
+    async fn persist(&mut self, first: PersistedExecution) -> anyhow::Result<()> {
+        let mut executions = vec![first];
+        while executions.len() < *FUNCTION_EXECUTION_LOG_BATCH_SIZE
+            && let Ok(execution) = self.receiver.try_recv()
+        {
+            executions.push(execution);
+        }
+        let num_executions = executions.len();
+        let mut tx = self.database.begin(Identity::system()).await?;
+        for PersistedExecution {
+            record,
+            error,
+            slow,
+        } in executions
+        {
+            FunctionExecutionsModel::new(&mut tx).insert(record).await?;
+            if let Some(error) = error {
+                ErrorGroupsModel::new(&mut tx).record(error).await?;
+            }
+            if let Some(SlowExecution {
+                mut query,
+                index_ranges,
+            }) = slow
+            {
+                query.index_ranges = index_ranges.resolve(tx.table_mapping());
+                SlowQueriesModel::new(&mut tx).insert(query).await?;
+            }
+        }
+        self.database
+            .commit_with_write_source(tx, "function_execution_log")
+            .await?;
+        log_function_executions_persisted(num_executions);
+        Ok(())
+    }
+
+    async fn delete_expired(&self) -> anyhow::Result<()> {
+        let cutoff = self.runtime.unix_timestamp() - *FUNCTION_EXECUTION_LOG_RETENTION;
+        self.delete_expired_executions(cutoff).await?;
+        self.delete_expired_slow_queries(cutoff).await?;
+        let job_cutoff = self.runtime.unix_timestamp() - *JOB_EXECUTION_HISTORY_RETENTION;
+        self.delete_expired_job_executions(job_cutoff).await
+    }
+
+    async fn delete_expired_executions(&self, cutoff: UnixTimestamp) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let expired = FunctionExecutionsModel::new(&mut tx)
+                .expired(cutoff, *FUNCTION_EXECUTION_LOG_BATCH_SIZE)
+                .await?;
+            if expired.is_empty() {
+                return Ok(());
+            }
+            let num_expired = expired.len();
+            tracing::debug!("Deleting {num_expired} expired function executions");
+            for id in expired {
+                FunctionExecutionsModel::new(&mut tx).delete(id).await?;
+            }
+            self.database
+                .commit_with_write_source(tx, "function_execution_log_gc")
+                .await?;
+            log_function_executions_expired(num_expired);
+            if num_expired < *FUNCTION_EXECUTION_LOG_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn delete_expired_slow_queries(&self, cutoff: UnixTimestamp) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let expired = SlowQueriesModel::new(&mut tx)
+                .expired(cutoff, *FUNCTION_EXECUTION_LOG_BATCH_SIZE)
+                .await?;
+            if expired.is_empty() {
+                return Ok(());
+            }
+            let num_expired = expired.len();
+            tracing::debug!("Deleting {num_expired} expired slow queries");
+            for id in expired {
+                SlowQueriesModel::new(&mut tx).delete(id).await?;
+            }
+            self.database
+                .commit_with_write_source(tx, "slow_query_log_gc")
+                .await?;
+            if num_expired < *FUNCTION_EXECUTION_LOG_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn delete_expired_job_executions(&self, cutoff: UnixTimestamp) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let expired = JobExecutionsModel::new(&mut tx)
+                .expired(cutoff, *FUNCTION_EXECUTION_LOG_BATCH_SIZE)
+                .await?;
+            if expired.is_empty() {
+                return Ok(());
+            }
+            let num_expired = expired.len();
+            tracing::debug!("Deleting {num_expired} expired job executions");
+            for id in expired {
+                JobExecutionsModel::new(&mut tx).delete(id).await?;
+            }
+            self.database
+                .commit_with_write_source(tx, "job_execution_history_gc")
+                .await?;
+            if num_expired < *FUNCTION_EXECUTION_LOG_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+}
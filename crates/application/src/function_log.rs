@@ -4,6 +4,7 @@ use std::{
         BTreeMap,
         VecDeque,
     },
+    mem,
     str::FromStr,
     sync::Arc,
     time::{
@@ -13,6 +14,8 @@ use std::{
 };
 
 use common::{
+    backoff::Backoff,
+    bootstrap_model::index::database_index::IndexedFields,
     components::CanonicalizedComponentFunctionPath,
     errors::{
         report_error,
@@ -20,7 +23,10 @@ use common::{
     },
     execution_context::ExecutionContext,
     identity::InertIdentity,
-    knobs::MAX_UDF_EXECUTION,
+    knobs::{
+        MAX_UDF_EXECUTION,
+        SLOW_QUERY_THRESHOLD,
+    },
     log_lines::{
         LogLine,
         LogLines,
@@ -37,6 +43,7 @@ use common::{
         RuntimeInstant,
         UnixTimestamp,
     },
+    sync::mpsc,
     types::{
         CursorMs,
         FunctionCaller,
@@ -44,12 +51,20 @@ use common::{
         ModuleEnvironment,
         TableName,
         TableStats,
+        TabletIndexName,
         UdfIdentifier,
         UdfType,
     },
 };
+use database::{
+    Database,
+    ReadSet,
+};
 use float_next_after::NextAfter;
-use futures::channel::oneshot;
+use futures::{
+    channel::oneshot,
+    Future,
+};
 use http::{
     Method,
     StatusCode,
@@ -64,7 +79,29 @@ use isolate::{
     SyscallTrace,
     UdfOutcome,
 };
-use parking_lot::Mutex;
+use keybroker::Identity;
+use model::{
+    error_groups::fingerprint::ErrorOccurrence,
+    function_executions::types::{
+        FunctionExecutionRecord,
+        FunctionExecutionStatus,
+        FunctionExecutionUsage,
+    },
+    log_settings::{
+        types::LogSettings,
+        LogSettingsModel,
+    },
+    slow_queries::types::{
+        SlowQuery,
+        SlowQueryIndexRanges,
+        SlowQuerySyscall,
+        SlowQueryTableStats,
+    },
+};
+use parking_lot::{
+    Mutex,
+    RwLock,
+};
 use serde::Deserialize;
 use serde_json::{
     json,
@@ -84,7 +121,17 @@ use value::{
         WithHeapSize,
     },
     ConvexArray,
+    TableMapping,
 };
+
+use crate::metrics::{
+    log_api_key_function_execution,
+    log_function_execution_dropped,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
 /// A function's execution is summarized by this structure and stored in the
 /// UdfExecutionLog
 #[derive(Debug, Clone)]
@@ -266,6 +313,166 @@ impl FunctionExecution {
 
         Ok(events)
     }
+
+    /// The record of this execution that's persisted to
+    /// `_function_executions`.
+    fn to_record(&self) -> FunctionExecutionRecord {
+        let (status, error) = match self.params.err() {
+            Some(e) => (FunctionExecutionStatus::Failure, Some(e.to_string())),
+            None => (FunctionExecutionStatus::Success, None),
+        };
+        let typed_error = self.params.err().and_then(|e| e.typed_error());
+        let error_code = typed_error.as_ref().map(|e| e.code.to_string());
+        let error_data = typed_error
+            .and_then(|e| e.data)
+            .map(|data| JsonValue::from(data.clone()).to_string());
+        // Attribute requests made with an API key to the key. Functions called
+        // by those requests, e.g. from actions, aren't attributed separately.
+        let api_key = match self.identity {
+            InertIdentity::ApiKey(ref api_key) if self.caller.is_root() => Some(api_key.clone()),
+            _ => None,
+        };
+        FunctionExecutionRecord {
+            timestamp: self.unix_timestamp,
+            function: self.params.identifier_str(),
+            udf_type: self.udf_type,
+            status,
+            error,
+            error_code,
+            error_data,
+            execution_time: Duration::from_secs_f64(self.execution_time),
+            cached: self.cached_result,
+            request_id: self.context.request_id.to_string(),
+            api_key,
+            usage: FunctionExecutionUsage {
+                database_read_bytes: self.usage_stats.database_read_bytes,
+                database_write_bytes: self.usage_stats.database_write_bytes,
+                storage_read_bytes: self.usage_stats.storage_read_bytes,
+                storage_write_bytes: self.usage_stats.storage_write_bytes,
+                action_memory_used_mb: self.action_memory_used_mb,
+            },
+            log_lines: self
+                .log_lines
+                .iter()
+                .map(|line| line.clone().to_pretty_string())
+                .collect(),
+        }
+    }
+
+    /// The slow query log entry for this execution, without the index ranges
+    /// it read, which are resolved when it's persisted.
+    fn to_slow_query(&self) -> SlowQuery {
+        SlowQuery {
+            timestamp: self.unix_timestamp,
+            function: self.params.identifier_str(),
+            udf_type: self.udf_type,
+            request_id: self.context.request_id.to_string(),
+            execution_time: Duration::from_secs_f64(self.execution_time),
+            database_read_bytes: self.usage_stats.database_read_bytes,
+            tables: self
+                .tables_touched
+                .iter()
+                .map(|(table, stats)| SlowQueryTableStats {
+                    table: table.to_string(),
+                    rows_read: stats.rows_read,
+                    rows_written: stats.rows_written,
+                })
+                .collect(),
+            index_ranges: vec![],
+            syscalls: self
+                .syscall_trace
+                .async_syscalls
+                .iter()
+                .map(|(name, stats)| SlowQuerySyscall {
+                    name: name.clone(),
+                    invocations: stats.invocations,
+                    errors: stats.errors,
+                    duration: stats.total_duration,
+                })
+                .collect(),
+        }
+    }
+
+    fn to_persisted(&self, index_ranges: Option<IndexRangesRead>) -> PersistedExecution {
+        // Cached results didn't run, so how long they took isn't interesting.
+        let is_slow = !self.cached_result
+            && Duration::from_secs_f64(self.execution_time) >= *SLOW_QUERY_THRESHOLD;
+        PersistedExecution {
+            record: self.to_record(),
+            error: self.params.err().map(|e| {
+                ErrorOccurrence::new(e, self.params.identifier_str(), self.unix_timestamp)
+            }),
+            slow: is_slow.then(|| SlowExecution {
+                query: self.to_slow_query(),
+                index_ranges: index_ranges.unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+/// The indexes an execution read and how many ranges of each it scanned,
+/// captured for the slow query log. Indexes are identified by tablet since the
+/// function log can't resolve table names, so they're resolved when the
+/// execution is persisted.
+#[derive(Clone, Debug, Default)]
+pub struct IndexRangesRead(Vec<(TabletIndexName, IndexedFields, usize)>);
+
+impl IndexRangesRead {
+    /// Captures the index ranges in `read_set` if the execution took longer
+    /// than `SLOW_QUERY_THRESHOLD`, and so will be in the slow query log.
+    pub fn capture_if_slow(execution_time: Duration, read_set: &ReadSet) -> Option<Self> {
+        if execution_time < *SLOW_QUERY_THRESHOLD {
+            return None;
+        }
+        let index_ranges = read_set
+            .iter_indexed()
+            .map(|(index_name, reads)| {
+                (
+                    index_name.clone(),
+                    reads.fields.clone(),
+                    reads.intervals.len(),
+                )
+            })
+            .collect();
+        Some(Self(index_ranges))
+    }
+
+    /// Names each index by its table, or by its tablet ID if the table has
+    /// since been deleted.
+    pub fn resolve(self, table_mapping: &TableMapping) -> Vec<SlowQueryIndexRanges> {
+        self.0
+            .into_iter()
+            .map(|(index_name, fields, num_ranges)| {
+                let tablet_id = *index_name.table();
+                let table = match table_mapping.tablet_name(tablet_id) {
+                    Ok(table_name) => table_name.to_string(),
+                    Err(_) => tablet_id.to_string(),
+                };
+                SlowQueryIndexRanges {
+                    index: format!("{table}.{}", index_name.descriptor()),
+                    fields: fields.iter().map(|field| field.to_string()).collect(),
+                    num_ranges: num_ranges as u64,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A completed execution sent to the `FunctionExecutionLogWorker` to be
+/// persisted.
+pub struct PersistedExecution {
+    pub record: FunctionExecutionRecord,
+    /// Set if the execution threw, so the error can be grouped with others
+    /// that have the same cause.
+    pub error: Option<ErrorOccurrence>,
+    /// Set if the execution took longer than `SLOW_QUERY_THRESHOLD`.
+    pub slow: Option<SlowExecution>,
+}
+
+/// An execution for the slow query log, along with the index ranges it read.
+pub struct SlowExecution {
+    pub query: SlowQuery,
+    pub index_ranges: IndexRangesRead,
 }
 
 #[derive(Debug, Clone)]
@@ -517,6 +724,8 @@ pub struct FunctionExecutionLog<RT: Runtime> {
     inner: Arc<Mutex<Inner<RT>>>,
     usage_tracking: UsageCounter,
     rt: RT,
+    // Kept up to date with `_log_settings` by `start_log_settings_worker`.
+    log_settings: Arc<RwLock<LogSettings>>,
 }
 
 impl<RT: Runtime> HeapSize for FunctionExecutionLog<RT> {
@@ -526,26 +735,79 @@ impl<RT: Runtime> HeapSize for FunctionExecutionLog<RT> {
 }
 
 impl<RT: Runtime> FunctionExecutionLog<RT> {
-    pub fn new(rt: RT, usage_tracking: UsageCounter, log_manager: Arc<dyn LogSender>) -> Self {
+    pub fn new(
+        rt: RT,
+        usage_tracking: UsageCounter,
+        log_manager: Arc<dyn LogSender>,
+        persisted_executions: mpsc::Sender<PersistedExecution>,
+    ) -> Self {
         let inner = Inner {
             rt: rt.clone(),
             num_execution_completions: 0,
             log: WithHeapSize::default(),
             log_waiters: vec![].into(),
             log_manager,
+            persisted_executions,
             metrics: Metrics::default(),
         };
         Self {
             inner: Arc::new(Mutex::new(inner)),
             rt,
             usage_tracking,
+            log_settings: Arc::new(RwLock::new(LogSettings::default())),
+        }
+    }
+
+    /// Keeps the log settings applied to collected log lines up to date with
+    /// `_log_settings`, so changing them takes effect without a redeploy.
+    pub fn start_log_settings_worker(
+        self,
+        database: Database<RT>,
+    ) -> impl Future<Output = ()> + Send {
+        async move {
+            tracing::info!("Starting log settings worker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(mut e) = self.watch_log_settings(&database, &mut backoff).await {
+                let delay = self.rt.with_rng(|rng| backoff.fail(rng));
+                tracing::error!("Log settings worker failed, sleeping {delay:?}");
+                report_error(&mut e);
+                self.rt.wait(delay).await;
+            }
+        }
+    }
+
+    async fn watch_log_settings(
+        &self,
+        database: &Database<RT>,
+        backoff: &mut Backoff,
+    ) -> anyhow::Result<()> {
+        loop {
+            let mut tx = database.begin(Identity::system()).await?;
+            let settings = LogSettingsModel::new(&mut tx).settings().await?;
+            *self.log_settings.write() = settings;
+            let token = tx.into_token()?;
+            let subscription = database.subscribe(token).await?;
+            backoff.reset();
+            subscription.wait_for_invalidation().await;
         }
     }
 
+    fn filter_log_lines(
+        &self,
+        udf_path: Option<&CanonicalizedUdfPath>,
+        context: &ExecutionContext,
+        log_lines: LogLines,
+    ) -> LogLines {
+        self.log_settings
+            .read()
+            .filter(udf_path, &context.execution_id, log_lines)
+    }
+
     pub fn log_query(
         &self,
         outcome: UdfOutcome,
         tables_touched: BTreeMap<TableName, TableStats>,
+        index_ranges: Option<IndexRangesRead>,
         was_cached: bool,
         execution_time: Duration,
         caller: FunctionCaller,
@@ -555,6 +817,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         self._log_query(
             outcome,
             tables_touched,
+            index_ranges,
             was_cached,
             execution_time,
             caller,
@@ -587,6 +850,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         self._log_query(
             outcome,
             BTreeMap::new(),
+            None,
             false,
             start.elapsed(),
             caller,
@@ -601,6 +865,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         &self,
         outcome: UdfOutcome,
         tables_touched: BTreeMap<TableName, TableStats>,
+        index_ranges: Option<IndexRangesRead>,
         was_cached: bool,
         execution_time: Duration,
         caller: FunctionCaller,
@@ -663,13 +928,14 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             identity: outcome.identity,
             context,
         };
-        self.log_execution(execution, true);
+        self.log_execution(execution, index_ranges, true);
     }
 
     pub fn log_mutation(
         &self,
         outcome: ValidatedUdfOutcome,
         tables_touched: BTreeMap<TableName, TableStats>,
+        index_ranges: Option<IndexRangesRead>,
         execution_time: Duration,
         caller: FunctionCaller,
         usage: FunctionUsageTracker,
@@ -678,6 +944,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         self._log_mutation(
             outcome,
             tables_touched,
+            index_ranges,
             execution_time,
             caller,
             TrackUsage::Track(usage),
@@ -709,6 +976,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         self._log_mutation(
             outcome,
             BTreeMap::new(),
+            None,
             start.elapsed(),
             caller,
             TrackUsage::SystemError,
@@ -721,6 +989,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         &self,
         outcome: ValidatedUdfOutcome,
         tables_touched: BTreeMap<TableName, TableStats>,
+        index_ranges: Option<IndexRangesRead>,
         execution_time: Duration,
         caller: FunctionCaller,
         context: ExecutionContext,
@@ -728,6 +997,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         self._log_mutation(
             outcome,
             tables_touched,
+            index_ranges,
             execution_time,
             caller,
             TrackUsage::SystemError,
@@ -739,6 +1009,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         &self,
         outcome: ValidatedUdfOutcome,
         tables_touched: BTreeMap<TableName, TableStats>,
+        index_ranges: Option<IndexRangesRead>,
         execution_time: Duration,
         caller: FunctionCaller,
         usage: TrackUsage,
@@ -796,7 +1067,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             identity: outcome.identity,
             context,
         };
-        self.log_execution(execution, true);
+        self.log_execution(execution, index_ranges, true);
     }
 
     pub fn log_action(&self, completion: ActionCompletion, usage: FunctionUsageTracker) {
@@ -895,7 +1166,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             identity: outcome.identity,
             context: completion.context,
         };
-        self.log_execution(execution, /* send_console_events */ false)
+        self.log_execution(execution, None, /* send_console_events */ false)
     }
 
     pub fn log_action_progress(
@@ -912,6 +1183,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         if udf_path.is_system() {
             return;
         }
+        let log_lines = self.filter_log_lines(Some(&udf_path), &context, log_lines);
         let event_source = FunctionEventSource {
             path: udf_path.strip().to_string(),
             udf_type: UdfType::Action,
@@ -1023,7 +1295,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
             identity: outcome.identity,
             context,
         };
-        self.log_execution(execution, /* send_console_events */ false);
+        self.log_execution(execution, None, /* send_console_events */ false);
     }
 
     pub fn log_http_action_progress(
@@ -1034,6 +1306,7 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         log_lines: LogLines,
         module_environment: ModuleEnvironment,
     ) {
+        let log_lines = self.filter_log_lines(None, &context, log_lines);
         let event_source = FunctionEventSource {
             path: identifier.to_string(),
             udf_type: UdfType::HttpAction,
@@ -1045,11 +1318,25 @@ impl<RT: Runtime> FunctionExecutionLog<RT> {
         self.log_execution_progress(log_lines, event_source, unix_timestamp)
     }
 
-    fn log_execution(&self, execution: FunctionExecution, send_console_events: bool) {
-        if let Err(mut e) = self
-            .inner
-            .lock()
-            .log_execution(execution, send_console_events)
+    fn log_execution(
+        &self,
+        mut execution: FunctionExecution,
+        index_ranges: Option<IndexRangesRead>,
+        send_console_events: bool,
+    ) {
+        let udf_path = match &execution.params {
+            UdfParams::Function { identifier, .. } => Some(identifier),
+            UdfParams::Http { .. } => None,
+        };
+        execution.log_lines = self.filter_log_lines(
+            udf_path,
+            &execution.context,
+            mem::take(&mut execution.log_lines),
+        );
+        if let Err(mut e) =
+            self.inner
+                .lock()
+                .log_execution(execution, index_ranges, send_console_events)
         {
             report_error(&mut e);
         }
@@ -1243,6 +1530,9 @@ struct Inner<RT: Runtime> {
     num_execution_completions: usize,
     log_waiters: WithHeapSize<Vec<oneshot::Sender<()>>>,
     log_manager: Arc<dyn LogSender>,
+    // Completed executions are also sent to the `FunctionExecutionLogWorker`,
+    // which persists them so they outlive this in-memory log.
+    persisted_executions: mpsc::Sender<PersistedExecution>,
 
     metrics: Metrics,
 }
@@ -1257,9 +1547,22 @@ impl<RT: Runtime> Inner<RT> {
     fn log_execution(
         &mut self,
         execution: FunctionExecution,
+        index_ranges: Option<IndexRangesRead>,
         send_console_events: bool,
     ) -> anyhow::Result<()> {
         self.metrics.append(&execution)?;
+        // Count requests made with an API key. Functions called by those
+        // requests, e.g. from actions, aren't counted separately.
+        if let InertIdentity::ApiKey(_) = execution.identity
+            && execution.caller.is_root()
+        {
+            log_api_key_function_execution(
+                execution.udf_type,
+                !execution.params.is_err(),
+                execution.usage_stats.database_read_bytes,
+                execution.usage_stats.database_write_bytes,
+            );
+        }
         let next_time = self.next_time()?;
 
         // Gather log lines
@@ -1281,6 +1584,16 @@ impl<RT: Runtime> Inner<RT> {
 
         self.log_manager.send_logs(log_events);
 
+        match self
+            .persisted_executions
+            .try_send(execution.to_persisted(index_ranges))
+        {
+            Ok(()) => (),
+            Err(mpsc::error::TrySendError::Full(_)) => log_function_execution_dropped(),
+            // The worker has shut down.
+            Err(mpsc::error::TrySendError::Closed(_)) => (),
+        }
+
         self.log
             .push_back((next_time, FunctionExecutionPart::Completion(execution)));
         self.num_execution_completions += 1;
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    knobs::{
+        IDEMPOTENCY_KEY_GARBAGE_COLLECTION_BATCH_SIZE,
+        MUTATION_IDEMPOTENCY_KEY_RETENTION,
+    },
+    runtime::Runtime,
+};
+use database::Database;
+use futures::{
+    future::Either,
+    select_biased,
+    Future,
+    FutureExt,
+};
+use keybroker::Identity;
+use model::idempotency_keys::IdempotencyKeyModel;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Deletes the records of mutations called with an idempotency key once they
+/// are older than `MUTATION_IDEMPOTENCY_KEY_RETENTION`.
+pub struct IdempotencyKeyWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> IdempotencyKeyWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let worker = Self { runtime, database };
+        async move {
+            tracing::info!("Starting IdempotencyKeyWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("IdempotencyKeyWorker died"));
+                tracing::error!("Idempotency key worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let now = self.runtime.generate_timestamp()?;
+            let records = IdempotencyKeyModel::new(&mut tx)
+                .oldest(*IDEMPOTENCY_KEY_GARBAGE_COLLECTION_BATCH_SIZE)
+                .await?;
+
+            let mut next_expiry_wait = None;
+            let mut to_delete = vec![];
+            for record in records {
+                let expires_ts = record.created_ts.add(*MUTATION_IDEMPOTENCY_KEY_RETENTION)?;
+                if expires_ts > now {
+                    next_expiry_wait = Some(expires_ts - now);
+                    break;
+                }
+                to_delete.push(record.id());
+            }
+            if !to_delete.is_empty() {
+                tracing::debug!("Deleting {} expired idempotency keys", to_delete.len());
+                for id in to_delete {
+                    IdempotencyKeyModel::new(&mut tx).delete(id).await?;
+                }
+                self.database
+                    .commit_with_write_source(tx, "idempotency_key_gc")
+                    .await?;
+                continue;
+            }
+
+            let next_expiry_future = if let Some(next_expiry_wait) = next_expiry_wait {
+                Either::Left(self.runtime.wait(next_expiry_wait))
+            } else {
+                Either::Right(std::future::pending())
+            };
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            select_biased! {
+                _ = next_expiry_future.fuse() => {
+                }
+                _ = subscription.wait_for_invalidation().fuse() => {
+                },
+            }
+            backoff.reset();
+        }
+    }
+}
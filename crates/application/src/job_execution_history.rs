@@ -0,0 +1,50 @@
+use common::{
+    errors::report_error,
+    runtime::Runtime,
+};
+use database::Database;
+use keybroker::Identity;
+use model::{
+    function_executions::types::FunctionExecutionUsage,
+    job_executions::{
+        types::JobExecutionRecord,
+        JobExecutionsModel,
+    },
+};
+use usage_tracking::FunctionUsageTracker;
+
+/// Records a finished scheduled job or cron run in `_job_executions`. This
+/// happens after the job's new state has been committed, so failing to record
+/// the run is reported rather than failing the job.
+pub(crate) async fn record_job_execution<RT: Runtime>(
+    database: &Database<RT>,
+    record: JobExecutionRecord,
+) {
+    let result = async {
+        let mut tx = database.begin(Identity::system()).await?;
+        JobExecutionsModel::new(&mut tx).insert(record).await?;
+        database
+            .commit_with_write_source(tx, "job_execution_history")
+            .await?;
+        anyhow::Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        report_error(&mut e.context("Failed to record job execution"));
+    }
+}
+
+/// The resources a job's run used, as tracked by `usage_tracker`.
+pub(crate) fn job_execution_usage(
+    usage_tracker: FunctionUsageTracker,
+    action_memory_used_mb: Option<u64>,
+) -> FunctionExecutionUsage {
+    let stats = usage_tracker.gather_user_stats().aggregate();
+    FunctionExecutionUsage {
+        database_read_bytes: stats.database_read_bytes,
+        database_write_bytes: stats.database_write_bytes,
+        storage_read_bytes: stats.storage_read_bytes,
+        storage_write_bytes: stats.storage_write_bytes,
+        action_memory_used_mb,
+    }
+}
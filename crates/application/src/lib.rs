@@ -16,15 +16,22 @@ use std::{
         Arc,
         LazyLock,
     },
-    time::SystemTime,
+    fmt,
+    time::{
+        Duration,
+        SystemTime,
+    },
 };
 
 use anyhow::Context;
 use authentication::{
     application_auth::ApplicationAuth,
+    provider_metadata_cache::ProviderMetadataCache,
+    session_claims,
     validate_id_token,
     Auth0IdToken,
 };
+use built_in_auth::PasswordHasher;
 use bytes::Bytes;
 use common::{
     auth::AuthInfo,
@@ -48,6 +55,7 @@ use common::{
         ComponentPath,
     },
     document::{
+        CreationTime,
         DocumentUpdate,
         ParsedDocument,
         CREATION_TIME_FIELD_PATH,
@@ -58,6 +66,7 @@ use common::{
     },
     http::fetch::FetchClient,
     knobs::{
+        FUNCTION_EXECUTION_LOG_CHANNEL_SIZE,
         MAX_JOBS_CANCEL_BATCH,
         SNAPSHOT_LIST_LIMIT,
     },
@@ -73,6 +82,7 @@ use common::{
         UnixTimestamp,
     },
     schemas::DatabaseSchema,
+    sync::mpsc,
     types::{
         env_var_limit_met,
         env_var_name_not_unique,
@@ -93,6 +103,7 @@ use common::{
         UdfType,
         ENV_VAR_LIMIT,
     },
+    version::ClientVersion,
     RequestId,
 };
 use cron_jobs::CronJobExecutor;
@@ -104,6 +115,7 @@ use database::{
     IndexModel,
     IndexWorker,
     OccRetryStats,
+    ReplicationLogPage,
     SearchIndexWorkers,
     ShortBoxFuture,
     Snapshot,
@@ -120,9 +132,13 @@ use errors::{
     ErrorMetadataAnyhowExt,
 };
 use file_storage::{
+    ConditionalFile,
+    FileConditions,
     FileRangeStream,
     FileStorage,
     FileStream,
+    FileValidators,
+    UploadSessionStatus,
 };
 use function_log::{
     FunctionExecution,
@@ -131,6 +147,10 @@ use function_log::{
 use function_runner::FunctionRunner;
 use futures::{
     channel::oneshot,
+    future::{
+        BoxFuture,
+        Shared,
+    },
     stream::BoxStream,
 };
 use headers::{
@@ -138,9 +158,10 @@ use headers::{
     ContentType,
 };
 use http_client::{
-    cached_http_client_for,
+    http_client_for,
     ClientPurpose,
 };
+use idempotency_key_worker::IdempotencyKeyWorker;
 use isolate::{
     parse_udf_args,
     AuthConfig,
@@ -153,9 +174,13 @@ use isolate::{
     CONVEX_SITE,
 };
 use keybroker::{
+    AdminOperation,
+    ApiKeyIdentity,
     Identity,
     InstanceSecret,
     KeyBroker,
+    UploadConstraints,
+    UserIdentity,
 };
 use maplit::btreemap;
 use minitrace::{
@@ -165,6 +190,10 @@ use minitrace::{
     Span,
 };
 use model::{
+    api_keys::{
+        types::API_KEY_PREFIX,
+        ApiKeyModel,
+    },
     auth::AuthInfoModel,
     config::{
         module_loader::ModuleLoader,
@@ -177,6 +206,7 @@ use model::{
         ConfigModel,
     },
     deployment_audit_log::{
+        audit_log_actor,
         types::DeploymentAuditLogEvent,
         DeploymentAuditLogModel,
     },
@@ -190,6 +220,7 @@ use model::{
             Export,
             ExportFormat,
             ExportObjectKeys,
+            ExportSelection,
         },
         EXPORTS_TABLE,
     },
@@ -201,8 +232,28 @@ use model::{
         ExternalPackagesModel,
     },
     file_storage::{
-        types::FileStorageEntry,
+        types::{
+            FileStorageEntry,
+            FileStorageFilter,
+        },
         FileStorageId,
+        FileStoragePage,
+    },
+    function_executions::{
+        types::{
+            FunctionExecutionFilter,
+            FunctionExecutionRecord,
+        },
+        FunctionExecutionsModel,
+    },
+    idempotency_keys::types::MutationIdentifier,
+    job_executions::{
+        types::{
+            CronExecutionSummary,
+            JobExecutionFilter,
+            JobExecutionRecord,
+        },
+        JobExecutionsModel,
     },
     modules::{
         module_versions::{
@@ -211,8 +262,15 @@ use model::{
         },
         ModuleModel,
     },
-    scheduled_jobs::SchedulerModel,
-    session_requests::types::SessionRequestIdentifier,
+    revoked_sessions::RevokedSessionsModel,
+    scheduled_jobs::{
+        types::{
+            ScheduledJobFilter,
+            ScheduledJobStateKind,
+        },
+        ScheduledJobPage,
+        SchedulerModel,
+    },
     snapshot_imports::types::{
         ImportFormat,
         ImportMode,
@@ -229,6 +287,7 @@ use model::{
 };
 use node_executor::Actions;
 use parking_lot::Mutex;
+use provider_metadata_worker::ProviderMetadataWorker;
 use rand::Rng;
 use scheduled_jobs::ScheduledJobRunner;
 use schema_worker::SchemaWorker;
@@ -286,7 +345,10 @@ use vector::{
 
 use crate::{
     application_function_runner::ApplicationFunctionRunner,
+    deployment_clones::DeploymentCloneWorker,
     export_worker::ExportWorker,
+    file_lifecycle::FileLifecycleWorker,
+    function_execution_log_worker::FunctionExecutionLogWorker,
     function_log::{
         FunctionExecutionLog,
         MetricsWindow,
@@ -302,25 +364,56 @@ use crate::{
         RedactedJsError,
         RedactedLogLines,
     },
+    scheduled_exports::ScheduledExportWorker,
+    search_index_cache::{
+        SearchIndexCacheStatus,
+        SearchIndexPinWorker,
+    },
+    outbox_worker::OutboxWorker,
     snapshot_import::SnapshotImportWorker,
+    table_storage_snapshot_worker::TableStorageSnapshotWorker,
+    traffic_shadowing::{
+        ShadowFunctionRequest,
+        ShadowFunctionResponse,
+        TrafficShadower,
+        TrafficShadowingReport,
+    },
+    trash_worker::TrashWorker,
 };
 
 pub mod api;
 pub mod application_function_runner;
+pub mod built_in_auth;
 mod cache;
 pub mod cron_jobs;
+mod deployment_clones;
 mod export_worker;
+mod file_lifecycle;
+mod function_execution_log_worker;
 pub mod function_log;
+mod idempotency_key_worker;
+mod job_execution_history;
+pub mod log_streaming;
 pub mod log_visibility;
 mod metrics;
 mod module_cache;
+mod outbox_worker;
+mod provider_metadata_worker;
 pub mod redaction;
+mod scheduled_exports;
 pub mod scheduled_jobs;
 mod schema_worker;
+pub mod search_index_cache;
 pub mod snapshot_import;
+pub mod standby;
+mod table_storage_snapshot_worker;
 mod table_summary_worker;
+pub mod traffic_shadowing;
+mod trash_worker;
 pub mod valid_identifier;
 
+#[cfg(any(test, feature = "testing"))]
+pub mod test_harness;
 #[cfg(any(test, feature = "testing"))]
 pub mod test_helpers;
 #[cfg(test)]
@@ -353,6 +446,9 @@ pub struct QueryReturn {
     pub log_lines: LogLines,
     pub token: Token,
     pub journal: QueryJournal,
+    // Set if the result is outdated but was served from the query result cache
+    // because it's within its query's TTL.
+    pub cache_expiry: Option<QueryCacheExpiry>,
 }
 
 #[derive(Debug)]
@@ -361,6 +457,26 @@ pub struct RedactedQueryReturn {
     pub log_lines: RedactedLogLines,
     pub token: Token,
     pub journal: SerializedQueryJournal,
+    pub cache_expiry: Option<QueryCacheExpiry>,
+}
+
+/// When an outdated result served from the query result cache stops being
+/// served. The result's token is already invalid, so subscribers should wait
+/// for this rather than rerunning the query as soon as it's invalidated.
+#[derive(Clone)]
+pub struct QueryCacheExpiry {
+    pub expires_in: Duration,
+    // Resolves if the query's cached results are invalidated on demand before
+    // they expire.
+    pub invalidated: Shared<BoxFuture<'static, ()>>,
+}
+
+impl fmt::Debug for QueryCacheExpiry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QueryCacheExpiry")
+            .field("expires_in", &self.expires_in)
+            .finish()
+    }
 }
 
 #[derive(Debug)]
@@ -451,6 +567,7 @@ pub struct Application<RT: Runtime> {
     usage_tracking: UsageCounter,
     key_broker: KeyBroker,
     instance_name: String,
+    convex_site: ConvexSite,
     scheduled_job_runner: ScheduledJobRunner<RT>,
     cron_job_executor: Arc<Mutex<RT::Handle>>,
     index_worker: Arc<Mutex<RT::Handle>>,
@@ -459,13 +576,28 @@ pub struct Application<RT: Runtime> {
     search_and_vector_bootstrap_worker: Arc<Mutex<RT::Handle>>,
     table_summary_worker: TableSummaryClient<RT>,
     schema_worker: Arc<Mutex<RT::Handle>>,
+    idempotency_key_worker: Arc<Mutex<RT::Handle>>,
+    function_execution_log_worker: Arc<Mutex<RT::Handle>>,
+    provider_metadata_cache: Arc<ProviderMetadataCache>,
+    provider_metadata_worker: Arc<Mutex<RT::Handle>>,
     snapshot_import_worker: Arc<Mutex<RT::Handle>>,
     export_worker: Arc<Mutex<RT::Handle>>,
+    scheduled_export_worker: Arc<Mutex<RT::Handle>>,
+    deployment_clone_worker: Arc<Mutex<RT::Handle>>,
+    file_lifecycle_worker: Arc<Mutex<RT::Handle>>,
+    trash_worker: Arc<Mutex<RT::Handle>>,
+    search_index_pin_worker: Arc<Mutex<RT::Handle>>,
+    table_storage_snapshot_worker: Arc<Mutex<RT::Handle>>,
+    outbox_worker: Arc<Mutex<RT::Handle>>,
+    traffic_shadower: TrafficShadower<RT>,
+    traffic_shadowing_worker: Arc<Mutex<RT::Handle>>,
+    log_settings_worker: Arc<Mutex<RT::Handle>>,
     log_sender: Arc<dyn LogSender>,
     log_visibility: Arc<dyn LogVisibility<RT>>,
     module_cache: ModuleCache<RT>,
     system_env_var_names: HashSet<EnvVarName>,
     app_auth: Arc<ApplicationAuth>,
+    password_hasher: PasswordHasher<RT>,
 }
 
 impl<RT: Runtime> Clone for Application<RT> {
@@ -484,6 +616,7 @@ impl<RT: Runtime> Clone for Application<RT> {
             usage_tracking: self.usage_tracking.clone(),
             key_broker: self.key_broker.clone(),
             instance_name: self.instance_name.clone(),
+            convex_site: self.convex_site.clone(),
             scheduled_job_runner: self.scheduled_job_runner.clone(),
             cron_job_executor: self.cron_job_executor.clone(),
             index_worker: self.index_worker.clone(),
@@ -492,13 +625,28 @@ impl<RT: Runtime> Clone for Application<RT> {
             search_and_vector_bootstrap_worker: self.search_and_vector_bootstrap_worker.clone(),
             table_summary_worker: self.table_summary_worker.clone(),
             schema_worker: self.schema_worker.clone(),
+            idempotency_key_worker: self.idempotency_key_worker.clone(),
+            function_execution_log_worker: self.function_execution_log_worker.clone(),
+            provider_metadata_cache: self.provider_metadata_cache.clone(),
+            provider_metadata_worker: self.provider_metadata_worker.clone(),
             snapshot_import_worker: self.snapshot_import_worker.clone(),
             export_worker: self.export_worker.clone(),
+            scheduled_export_worker: self.scheduled_export_worker.clone(),
+            deployment_clone_worker: self.deployment_clone_worker.clone(),
+            file_lifecycle_worker: self.file_lifecycle_worker.clone(),
+            trash_worker: self.trash_worker.clone(),
+            search_index_pin_worker: self.search_index_pin_worker.clone(),
+            table_storage_snapshot_worker: self.table_storage_snapshot_worker.clone(),
+            outbox_worker: self.outbox_worker.clone(),
+            traffic_shadower: self.traffic_shadower.clone(),
+            traffic_shadowing_worker: self.traffic_shadowing_worker.clone(),
+            log_settings_worker: self.log_settings_worker.clone(),
             log_sender: self.log_sender.clone(),
             log_visibility: self.log_visibility.clone(),
             module_cache: self.module_cache.clone(),
             system_env_var_names: self.system_env_var_names.clone(),
             app_auth: self.app_auth.clone(),
+            password_hasher: self.password_hasher.clone(),
         }
     }
 }
@@ -569,11 +717,31 @@ impl<RT: Runtime> Application<RT> {
             "schema_worker",
             SchemaWorker::start(runtime.clone(), database.clone()),
         )));
+        let idempotency_key_worker = Arc::new(Mutex::new(runtime.spawn(
+            "idempotency_key_worker",
+            IdempotencyKeyWorker::start(runtime.clone(), database.clone()),
+        )));
+        let provider_metadata_cache = Arc::new(ProviderMetadataCache::default());
+        let provider_metadata_worker = Arc::new(Mutex::new(runtime.spawn(
+            "provider_metadata_worker",
+            ProviderMetadataWorker::start(runtime.clone(), provider_metadata_cache.clone()),
+        )));
 
+        let (persisted_executions_tx, persisted_executions_rx) =
+            mpsc::channel(*FUNCTION_EXECUTION_LOG_CHANNEL_SIZE);
+        let function_execution_log_worker = Arc::new(Mutex::new(runtime.spawn(
+            "function_execution_log_worker",
+            FunctionExecutionLogWorker::start(
+                runtime.clone(),
+                database.clone(),
+                persisted_executions_rx,
+            ),
+        )));
         let function_log = FunctionExecutionLog::new(
             runtime.clone(),
             database.usage_counter(),
             log_sender.clone(),
+            persisted_executions_tx,
         );
         let runner = Arc::new(ApplicationFunctionRunner::new(
             instance_name.clone(),
@@ -588,7 +756,7 @@ impl<RT: Runtime> Application<RT> {
             module_loader,
             function_log.clone(),
             system_env_vars.clone(),
-            fetch_client,
+            fetch_client.clone(),
         ));
         function_runner.set_action_callbacks(runner.clone());
 
@@ -619,17 +787,84 @@ impl<RT: Runtime> Application<RT> {
         );
         let export_worker = Arc::new(Mutex::new(runtime.spawn("export_worker", export_worker)));
 
+        let scheduled_export_worker = ScheduledExportWorker::start(
+            runtime.clone(),
+            database.clone(),
+            exports_storage.clone(),
+            files_storage.clone(),
+            database.usage_counter().clone(),
+            instance_name.clone(),
+        );
+        let scheduled_export_worker = Arc::new(Mutex::new(
+            runtime.spawn("scheduled_export_worker", scheduled_export_worker),
+        ));
+
+        let deployment_clone_worker = DeploymentCloneWorker::start(
+            runtime.clone(),
+            database.clone(),
+            exports_storage.clone(),
+            files_storage.clone(),
+            database.usage_counter().clone(),
+            module_cache.clone(),
+        );
+        let deployment_clone_worker = Arc::new(Mutex::new(
+            runtime.spawn("deployment_clone_worker", deployment_clone_worker),
+        ));
+
+        let file_lifecycle_worker =
+            FileLifecycleWorker::start(runtime.clone(), database.clone(), files_storage.clone());
+        let file_lifecycle_worker = Arc::new(Mutex::new(
+            runtime.spawn("file_lifecycle_worker", file_lifecycle_worker),
+        ));
+
+        let trash_worker = Arc::new(Mutex::new(runtime.spawn(
+            "trash_worker",
+            TrashWorker::start(runtime.clone(), database.clone()),
+        )));
+
+        let search_index_pin_worker = Arc::new(Mutex::new(runtime.spawn(
+            "search_index_pin_worker",
+            SearchIndexPinWorker::start(runtime.clone(), database.clone()),
+        )));
+
+        let table_storage_snapshot_worker = Arc::new(Mutex::new(runtime.spawn(
+            "table_storage_snapshot_worker",
+            TableStorageSnapshotWorker::start(
+                runtime.clone(),
+                database.clone(),
+                usage_tracking.clone(),
+            ),
+        )));
+
+        let outbox_worker = Arc::new(Mutex::new(runtime.spawn(
+            "outbox_worker",
+            OutboxWorker::start(runtime.clone(), database.clone(), fetch_client.clone()),
+        )));
+
+        let traffic_shadower = TrafficShadower::new(runtime.clone(), fetch_client);
+        let traffic_shadowing_worker = Arc::new(Mutex::new(runtime.spawn(
+            "traffic_shadowing_worker",
+            traffic_shadower.clone().start_config_worker(database.clone()),
+        )));
+        let log_settings_worker = Arc::new(Mutex::new(runtime.spawn(
+            "log_settings_worker",
+            function_log.clone().start_log_settings_worker(database.clone()),
+        )));
+
         let snapshot_import_worker = SnapshotImportWorker::new(
             runtime.clone(),
             database.clone(),
             snapshot_imports_storage.clone(),
+            exports_storage.clone(),
             file_storage.clone(),
             database.usage_counter().clone(),
+            log_sender.clone(),
             snapshot_import_pause_client,
         );
         let snapshot_import_worker = Arc::new(Mutex::new(
             runtime.spawn("snapshot_import_worker", snapshot_import_worker),
         ));
+        let password_hasher = PasswordHasher::new(runtime.clone());
 
         Ok(Self {
             runtime,
@@ -647,19 +882,35 @@ impl<RT: Runtime> Application<RT> {
             scheduled_job_runner,
             cron_job_executor,
             instance_name,
+            convex_site,
             index_worker,
             fast_forward_worker,
             search_worker,
             search_and_vector_bootstrap_worker,
             table_summary_worker,
             schema_worker,
+            idempotency_key_worker,
+            function_execution_log_worker,
+            provider_metadata_cache,
+            provider_metadata_worker,
             export_worker,
+            scheduled_export_worker,
+            deployment_clone_worker,
+            file_lifecycle_worker,
+            trash_worker,
+            search_index_pin_worker,
+            table_storage_snapshot_worker,
+            outbox_worker,
+            traffic_shadower,
+            traffic_shadowing_worker,
+            log_settings_worker,
             snapshot_import_worker,
             log_sender,
             log_visibility,
             module_cache,
             system_env_var_names: system_env_vars.into_keys().collect(),
             app_auth,
+            password_hasher,
         })
     }
 
@@ -675,6 +926,22 @@ impl<RT: Runtime> Application<RT> {
         &self.module_cache
     }
 
+    /// Where the segments of a table's text and vector indexes are cached by
+    /// the searcher.
+    pub async fn search_index_cache_status(
+        &self,
+        identity: Identity,
+        table_name: &TableName,
+    ) -> anyhow::Result<Vec<SearchIndexCacheStatus>> {
+        search_index_cache::search_index_cache_status(&self.database, identity, table_name).await
+    }
+
+    /// Loads the segments of a newly pinned index without waiting for the
+    /// `SearchIndexPinWorker`.
+    pub async fn prewarm_search_index(&self, index_name: &IndexName) -> anyhow::Result<()> {
+        search_index_cache::prewarm_search_index(&self.database, index_name).await
+    }
+
     pub fn key_broker(&self) -> &KeyBroker {
         &self.key_broker
     }
@@ -687,6 +954,22 @@ impl<RT: Runtime> Application<RT> {
         self.function_log.clone()
     }
 
+    pub async fn check_persistence(&self) -> anyhow::Result<()> {
+        self.database.check_persistence().await
+    }
+
+    pub fn check_search_ready(&self) -> anyhow::Result<()> {
+        self.database.check_search_ready()
+    }
+
+    pub fn check_scheduler_live(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.scheduled_job_runner.is_live(),
+            "Scheduled job executor isn't running"
+        );
+        Ok(())
+    }
+
     pub fn now_ts_for_reads(&self) -> RepeatableTimestamp {
         self.database.now_ts_for_reads()
     }
@@ -744,6 +1027,18 @@ impl<RT: Runtime> Application<RT> {
             .await
     }
 
+    #[minitrace::trace]
+    pub async fn replication_log(
+        &self,
+        identity: Identity,
+        cursor: Option<Timestamp>,
+        rows_limit: usize,
+    ) -> anyhow::Result<ReplicationLogPage> {
+        self.database
+            .replication_log(identity, cursor, rows_limit)
+            .await
+    }
+
     #[minitrace::trace]
     pub async fn list_snapshot(
         &self,
@@ -832,12 +1127,28 @@ impl<RT: Runtime> Application<RT> {
         Ok(Some(source_map_content.to_owned()))
     }
 
-    pub async fn storage_generate_upload_url(&self) -> anyhow::Result<String> {
+    pub async fn storage_generate_upload_url(
+        &self,
+        constraints: UploadConstraints,
+    ) -> anyhow::Result<String> {
+        let issued_ts = self.runtime().unix_timestamp();
+        let url = self
+            .file_storage
+            .transactional_file_storage
+            .generate_upload_url(self.key_broker(), issued_ts, &constraints)?;
+
+        Ok(url)
+    }
+
+    pub async fn storage_generate_resumable_upload_url(
+        &self,
+        constraints: UploadConstraints,
+    ) -> anyhow::Result<String> {
         let issued_ts = self.runtime().unix_timestamp();
         let url = self
             .file_storage
             .transactional_file_storage
-            .generate_upload_url(self.key_broker(), issued_ts)?;
+            .generate_resumable_upload_url(self.key_broker(), issued_ts, &constraints)?;
 
         Ok(url)
     }
@@ -866,6 +1177,10 @@ impl<RT: Runtime> Application<RT> {
         journal: Option<Option<String>>,
         caller: FunctionCaller,
     ) -> anyhow::Result<RedactedQueryReturn> {
+        identity.ensure_can_run_function(UdfType::Query)?;
+        let shadowed_call = self
+            .traffic_shadower
+            .sample(UdfType::Query, &path, &args, &identity, &caller);
         let persistence_version = self.database.persistence_version();
         let block_logging = self
             .log_visibility
@@ -907,6 +1222,7 @@ impl<RT: Runtime> Application<RT> {
                 journal: self
                     .key_broker
                     .encrypt_query_journal(&query_return.journal, persistence_version),
+                cache_expiry: query_return.cache_expiry,
             },
             Err(e) if e.is_deterministic_user_error() => RedactedQueryReturn {
                 result: Err(RedactedJsError::from_js_error(
@@ -921,9 +1237,17 @@ impl<RT: Runtime> Application<RT> {
                 journal: self
                     .key_broker
                     .encrypt_query_journal(&QueryJournal::new(), persistence_version),
+                cache_expiry: None,
             },
             Err(e) => anyhow::bail!(e),
         };
+        if let Some(call) = shadowed_call {
+            let result = match &redacted_query_return.result {
+                Ok(value) => Ok(JsonValue::from(value.clone())),
+                Err(e) => Err(e.to_string()),
+            };
+            self.traffic_shadower.shadow(call, result);
+        }
         Ok(redacted_query_return)
     }
 
@@ -935,11 +1259,14 @@ impl<RT: Runtime> Application<RT> {
         args: Vec<JsonValue>,
         identity: Identity,
         // Identifier used to make this mutation idempotent.
-        mutation_identifier: Option<SessionRequestIdentifier>,
+        mutation_identifier: Option<MutationIdentifier>,
         caller: FunctionCaller,
         pause_client: PauseClient,
     ) -> anyhow::Result<Result<RedactedMutationReturn, RedactedMutationError>> {
         identity.ensure_can_run_function(UdfType::Mutation)?;
+        let shadowed_call = self
+            .traffic_shadower
+            .sample(UdfType::Mutation, &path, &args, &identity, &caller);
         let block_logging = self
             .log_visibility
             .should_redact_logs_and_error(
@@ -948,6 +1275,11 @@ impl<RT: Runtime> Application<RT> {
                 caller.allowed_visibility(),
             )
             .await?;
+        // The dashboard edits data with the `_system/frontend` mutations.
+        let is_dashboard_function = path.udf_path.is_system()
+            && path.udf_path.module().components().nth(1) == Some("frontend");
+        let dashboard_edit = (identity.is_admin() && is_dashboard_function)
+            .then(|| (identity.clone(), path.udf_path.to_string()));
         let result = match self
             .runner
             .retry_mutation(
@@ -961,14 +1293,20 @@ impl<RT: Runtime> Application<RT> {
             )
             .await
         {
-            Ok(Ok(mutation_return)) => Ok(RedactedMutationReturn {
-                value: mutation_return.value,
-                log_lines: RedactedLogLines::from_log_lines(
-                    mutation_return.log_lines,
-                    block_logging,
-                ),
-                ts: mutation_return.ts,
-            }),
+            Ok(Ok(mutation_return)) => {
+                if let Some((identity, function)) = dashboard_edit {
+                    self.audit_dashboard_edit(identity, function, &request_id)
+                        .await?;
+                }
+                Ok(RedactedMutationReturn {
+                    value: mutation_return.value,
+                    log_lines: RedactedLogLines::from_log_lines(
+                        mutation_return.log_lines,
+                        block_logging,
+                    ),
+                    ts: mutation_return.ts,
+                })
+            },
             Ok(Err(mutation_error)) => Err(RedactedMutationError {
                 error: RedactedJsError::from_js_error(
                     mutation_error.error,
@@ -990,9 +1328,97 @@ impl<RT: Runtime> Application<RT> {
             }),
             Err(e) => anyhow::bail!(e),
         };
+        if let Some(call) = shadowed_call {
+            let result = match &result {
+                Ok(mutation_return) => Ok(JsonValue::from(mutation_return.value.clone())),
+                Err(mutation_error) => Err(mutation_error.error.to_string()),
+            };
+            self.traffic_shadower.shadow(call, result);
+        }
         Ok(result)
     }
 
+    /// Runs a call that another deployment is shadowing to this one with
+    /// `TrafficShadower`. Mutations run without committing, so shadowing
+    /// never writes to this deployment.
+    pub async fn execute_shadowed_function(
+        &self,
+        request_id: RequestId,
+        identity: Identity,
+        request: ShadowFunctionRequest,
+    ) -> anyhow::Result<ShadowFunctionResponse> {
+        let Identity::InstanceAdmin(admin_identity) = identity else {
+            anyhow::bail!(unauthorized_error("shadow_function"));
+        };
+        let identity = match request.identity {
+            Some(attributes) => Identity::ActingUser(admin_identity, attributes.try_into()?),
+            None => Identity::Unknown,
+        };
+        let path = ComponentFunctionPath {
+            component: request.component_path.parse()?,
+            udf_path: request.path.parse()?,
+        };
+        let caller = FunctionCaller::HttpApi(ClientVersion::unknown());
+        let start = self.runtime.monotonic_now();
+        let result = match request.udf_type.parse()? {
+            UdfType::Query => self
+                .read_only_udf(request_id, path, request.args, identity, caller)
+                .await?
+                .result
+                .map(JsonValue::from)
+                .map_err(|e| e.to_string()),
+            UdfType::Mutation => {
+                identity.ensure_can_run_function(UdfType::Mutation)?;
+                match self
+                    .runner
+                    .run_mutation_without_commit(request_id, path, request.args, identity, caller)
+                    .await
+                {
+                    Ok(result) => result.map(JsonValue::from).map_err(|e| e.to_string()),
+                    Err(e) if e.is_deterministic_user_error() => {
+                        Err(JsError::from_error(e).to_string())
+                    },
+                    Err(e) => anyhow::bail!(e),
+                }
+            },
+            udf_type => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidShadowedFunction",
+                format!("{udf_type} functions can't be shadowed"),
+            )),
+        };
+        let execution_time = self.runtime.monotonic_now() - start;
+        Ok(ShadowFunctionResponse {
+            result: result.into(),
+            execution_time_ms: execution_time.as_secs_f64() * 1000.0,
+        })
+    }
+
+    pub fn traffic_shadowing_report(&self) -> TrafficShadowingReport {
+        self.traffic_shadower.report()
+    }
+
+    /// Record a successful dashboard mutation in the deployment audit log. This
+    /// commits separately from the mutation itself, so a crash in between can
+    /// leave an edit unrecorded.
+    async fn audit_dashboard_edit(
+        &self,
+        identity: Identity,
+        function: String,
+        request_id: &RequestId,
+    ) -> anyhow::Result<()> {
+        let tx = self.begin(identity).await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DashboardEdit {
+                function,
+                request_id: request_id.to_string(),
+            }],
+            "audit_dashboard_edit",
+        )
+        .await?;
+        Ok(())
+    }
+
     #[minitrace::trace]
     pub async fn action_udf(
         &self,
@@ -1234,11 +1660,13 @@ impl<RT: Runtime> Application<RT> {
         }
     }
 
+    /// Requests an export in `format`, or in the JSON format that the
+    /// deployment's version of the `convex` package expects if it's None.
     pub async fn request_export(
         &self,
         identity: Identity,
-        zip: bool,
-        include_storage: bool,
+        format: Option<ExportFormat>,
+        selection: ExportSelection,
     ) -> anyhow::Result<()> {
         anyhow::ensure!(identity.is_admin(), unauthorized_error("request_export"));
         let snapshot = self.latest_snapshot()?;
@@ -1250,15 +1678,16 @@ impl<RT: Runtime> Application<RT> {
             )
             .into());
         }
+        let user_table_names: BTreeSet<_> = snapshot.table_registry.user_table_names().collect();
+        selection.validate(&user_table_names)?;
         let mut tx = self.begin(identity).await?;
         let export_requested = ExportWorker::export_in_state(&mut tx, "requested").await?;
         let export_in_progress = ExportWorker::export_in_state(&mut tx, "in_progress").await?;
-        match (export_requested, export_in_progress) {
+        let format = match (export_requested, export_in_progress) {
             (None, None) => {
-                let format = if zip {
-                    ExportFormat::Zip { include_storage }
-                } else {
-                    match UdfConfigModel::new(&mut tx, TableNamespace::by_component_TODO())
+                let format = match format {
+                    Some(format) => format,
+                    None => match UdfConfigModel::new(&mut tx, TableNamespace::by_component_TODO())
                         .get()
                         .await?
                     {
@@ -1274,12 +1703,15 @@ impl<RT: Runtime> Application<RT> {
                         },
                         // They haven't pushed functions yet - give them clean export.
                         None => ExportFormat::CleanJsonl,
-                    }
+                    },
                 };
                 SystemMetadataModel::new_global(&mut tx)
-                    .insert(&EXPORTS_TABLE, Export::requested(format).try_into()?)
+                    .insert(
+                        &EXPORTS_TABLE,
+                        Export::requested(format, selection).try_into()?,
+                    )
                     .await?;
-                Ok(())
+                Ok(format)
             },
             _ => Err(
                 anyhow::anyhow!("Can only have one export requested or in progress at once")
@@ -1289,7 +1721,12 @@ impl<RT: Runtime> Application<RT> {
                     )),
             ),
         }?;
-        self.commit(tx, "request_export").await?;
+        self.commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::RequestExport { format }],
+            "request_export",
+        )
+        .await?;
         Ok(())
     }
 
@@ -1326,7 +1763,8 @@ impl<RT: Runtime> Application<RT> {
     ) -> anyhow::Result<StorageGetStream> {
         self.get_export_inner(identity, snapshot_ts, move |keys| {
             let key = match keys {
-                ExportObjectKeys::ByTable(tables) => tables
+                ExportObjectKeys::ByTable(tables)
+                | ExportObjectKeys::ByTableWithManifest { tables, .. } => tables
                     .get(&table_name)
                     .context(ErrorMetadata::bad_request(
                         "NoExportForTable",
@@ -1346,6 +1784,21 @@ impl<RT: Runtime> Application<RT> {
         .await
     }
 
+    pub async fn get_export_manifest(
+        &self,
+        identity: Identity,
+        snapshot_ts: Timestamp,
+    ) -> anyhow::Result<StorageGetStream> {
+        self.get_export_inner(identity, snapshot_ts, move |keys| match keys {
+            ExportObjectKeys::ByTableWithManifest { manifest, .. } => Ok(manifest),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "NoExportManifest",
+                format!("The requested export {snapshot_ts} does not have a manifest")
+            )),
+        })
+        .await
+    }
+
     async fn get_export_inner(
         &self,
         identity: Identity,
@@ -1608,6 +2061,43 @@ impl<RT: Runtime> Application<RT> {
             .await
     }
 
+    /// Evaluate the definition at `definition_path` and the definitions it
+    /// depends on, without the app's definition, e.g. to install a component
+    /// at runtime.
+    pub async fn evaluate_component_definitions(
+        &self,
+        definition_path: ComponentDefinitionPath,
+        mut component_definitions: BTreeMap<ComponentDefinitionPath, ModuleConfig>,
+        dependency_graph: BTreeSet<(ComponentDefinitionPath, ComponentDefinitionPath)>,
+    ) -> anyhow::Result<EvaluateAppDefinitionsResult> {
+        anyhow::ensure!(!definition_path.is_root());
+        // Definitions are evaluated starting at the root, so evaluate the
+        // component's definition in place of the app's.
+        let definition = component_definitions
+            .remove(&definition_path)
+            .context("Missing component definition")?;
+        let dependency_graph = dependency_graph
+            .into_iter()
+            .map(|(from, to)| {
+                if from == definition_path {
+                    (ComponentDefinitionPath::root(), to)
+                } else {
+                    (from, to)
+                }
+            })
+            .collect();
+        let mut definitions = self
+            .runner
+            .evaluate_app_definitions(definition, component_definitions, dependency_graph)
+            .await?;
+        let mut metadata = definitions
+            .remove(&ComponentDefinitionPath::root())
+            .context("Missing evaluated component definition")?;
+        metadata.path = definition_path.clone();
+        definitions.insert(definition_path, metadata);
+        Ok(definitions)
+    }
+
     #[minitrace::trace]
     pub async fn get_evaluated_auth_config(
         runner: Arc<ApplicationFunctionRunner<RT>>,
@@ -1814,6 +2304,8 @@ impl<RT: Runtime> Application<RT> {
         identity: Identity,
         format: ImportFormat,
         mode: ImportMode,
+        dry_run: bool,
+        remap_ids: bool,
         upload_token: ClientDrivenUploadToken,
         part_tokens: Vec<ClientDrivenUploadPartToken>,
     ) -> anyhow::Result<DeveloperDocumentId> {
@@ -1827,7 +2319,16 @@ impl<RT: Runtime> Application<RT> {
             .snapshot_imports_storage
             .finish_client_driven_upload(upload_token, part_tokens)
             .await?;
-        store_uploaded_import(self, identity, format, mode, object_key).await
+        store_uploaded_import(
+            self,
+            identity,
+            format,
+            mode,
+            dry_run,
+            remap_ids,
+            object_key,
+        )
+        .await
     }
 
     pub async fn upload_snapshot_import(
@@ -2210,33 +2711,85 @@ impl<RT: Runtime> Application<RT> {
         Ok(storage_id)
     }
 
-    pub async fn get_file(
+    pub async fn create_upload_session(
+        &self,
+        content_type: Option<ContentType>,
+        expected_sha256: Option<Sha256Digest>,
+        constraints: UploadConstraints,
+    ) -> anyhow::Result<String> {
+        self.file_storage
+            .create_upload_session(content_type, expected_sha256, &constraints)
+            .await
+    }
+
+    pub async fn upload_session_part(
+        &self,
+        upload_id: &str,
+        part_number: u16,
+        part: Bytes,
+    ) -> anyhow::Result<()> {
+        self.file_storage
+            .upload_session_part(upload_id, part_number, part)
+            .await
+    }
+
+    pub async fn upload_session_status(
+        &self,
+        upload_id: &str,
+    ) -> anyhow::Result<UploadSessionStatus> {
+        self.file_storage.upload_session_status(upload_id).await
+    }
+
+    pub async fn finish_upload_session(
         &self,
         component: ComponentId,
-        storage_id: FileStorageId,
-    ) -> anyhow::Result<FileStream> {
-        let mut file_storage_tx = self.begin(Identity::system()).await?;
+        upload_id: &str,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        self.file_storage
+            .finish_upload_session(component.into(), upload_id, &self.usage_tracking)
+            .await
+    }
 
-        let Some(file_entry) = self
-            .file_storage
+    /// Lists the files matching the filter, oldest first, for finding files
+    /// to clean up.
+    pub async fn list_files(
+        &self,
+        identity: Identity,
+        filter: FileStorageFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<FileStoragePage> {
+        let mut tx = self.begin(identity).await?;
+        self.file_storage
             .transactional_file_storage
-            // The transaction is not part of UDF so use the global usage counters.
-            .get_file_entry(&mut file_storage_tx, component.into(), storage_id.clone())
-            .await?
-        else {
-            return Err(ErrorMetadata::not_found(
-                "FileNotFound",
-                format!("File {storage_id} not found"),
+            .list_files(
+                &mut tx,
+                TableNamespace::by_component_TODO(),
+                &filter,
+                cursor,
+                limit,
             )
-            .into());
-        };
+            .await
+    }
 
-        self
+    pub async fn get_file(
+        &self,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        conditions: FileConditions,
+    ) -> anyhow::Result<ConditionalFile<FileStream>> {
+        let (file_entry, validators) = self.get_file_entry(component, storage_id).await?;
+        if conditions.is_not_modified(&validators) {
+            return Ok(ConditionalFile::NotModified(validators));
+        }
+
+        let stream = self
             .file_storage
             .transactional_file_storage
             // The transaction is not part of UDF so use the global usage counters.
             .get_file_stream(file_entry, self.usage_tracking.clone())
-            .await
+            .await?;
+        Ok(ConditionalFile::Modified(validators, stream))
     }
 
     pub async fn get_file_range(
@@ -2244,14 +2797,37 @@ impl<RT: Runtime> Application<RT> {
         component: ComponentId,
         storage_id: FileStorageId,
         bytes_range: (Bound<u64>, Bound<u64>),
-    ) -> anyhow::Result<FileRangeStream> {
+        conditions: FileConditions,
+    ) -> anyhow::Result<ConditionalFile<FileRangeStream>> {
+        let (file_entry, validators) = self.get_file_entry(component, storage_id).await?;
+        if conditions.is_not_modified(&validators) {
+            return Ok(ConditionalFile::NotModified(validators));
+        }
+
+        let stream = self
+            .file_storage
+            .transactional_file_storage
+            // The transaction is not part of UDF so use the global usage counters.
+            .get_file_range_stream(file_entry, bytes_range, self.usage_tracking.clone())
+            .await?;
+        Ok(ConditionalFile::Modified(validators, stream))
+    }
+
+    async fn get_file_entry(
+        &self,
+        component: ComponentId,
+        storage_id: FileStorageId,
+    ) -> anyhow::Result<(FileStorageEntry, FileValidators)> {
         let mut file_storage_tx = self.begin(Identity::system()).await?;
 
         let Some(file_entry) = self
             .file_storage
             .transactional_file_storage
-            // The transaction is not part of UDF so use the global usage counters.
-            .get_file_entry(&mut file_storage_tx, component.into(), storage_id.clone())
+            .get_file_entry_with_validators(
+                &mut file_storage_tx,
+                component.into(),
+                storage_id.clone(),
+            )
             .await?
         else {
             return Err(ErrorMetadata::not_found(
@@ -2260,13 +2836,7 @@ impl<RT: Runtime> Application<RT> {
             )
             .into());
         };
-
-        self
-            .file_storage
-            .transactional_file_storage
-            // The transaction is not part of UDF so use the global usage counters.
-            .get_file_range_stream(file_entry, bytes_range, self.usage_tracking.clone())
-            .await
+        Ok(file_entry)
     }
 
     pub async fn authenticate(
@@ -2298,20 +2868,50 @@ impl<RT: Runtime> Application<RT> {
                     None => admin_identity,
                 }
             },
+            AuthenticationToken::User(api_key) if api_key.starts_with(API_KEY_PREFIX) => {
+                let mut tx = self.begin(Identity::system()).await?;
+                let api_key_doc = ApiKeyModel::new(&mut tx)
+                    .get_by_key(&api_key)
+                    .await?
+                    .context(ErrorMetadata::unauthenticated(
+                        "InvalidApiKey",
+                        "The provided API key was invalid for this deployment",
+                    ))?;
+                let id = api_key_doc.id();
+                let metadata = api_key_doc.into_value();
+                Identity::ApiKey(ApiKeyIdentity::new(
+                    id.to_string(),
+                    metadata.name.to_string(),
+                    api_key,
+                    metadata.scope,
+                ))
+            },
             AuthenticationToken::User(id_token) => {
                 let mut tx = self.begin(Identity::system()).await?;
-                let auth_infos = AuthInfoModel::new(&mut tx).get().await?;
-
-                let identity = validate_id_token(
-                    Auth0IdToken(id_token),
-                    cached_http_client_for(ClientPurpose::ProviderMetadata),
-                    auth_infos
-                        .into_iter()
-                        .map(|auth_info| auth_info.into_value())
-                        .collect(),
+                // Tokens issued by built-in auth are signed by the deployment
+                // itself, so they're checked before the configured providers.
+                let identity = match self.key_broker.check_user_token(
+                    self.convex_site.as_str(),
+                    &id_token,
                     system_time,
-                )
-                .await?;
+                )? {
+                    Some(identity) => identity,
+                    None => {
+                        let auth_infos = AuthInfoModel::new(&mut tx).get().await?;
+                        validate_id_token(
+                            Auth0IdToken(id_token),
+                            http_client_for(ClientPurpose::ProviderMetadata),
+                            auth_infos
+                                .into_iter()
+                                .map(|auth_info| auth_info.into_value())
+                                .collect(),
+                            &self.provider_metadata_cache,
+                            system_time,
+                        )
+                        .await?
+                    },
+                };
+                Self::ensure_user_session_not_revoked(&mut tx, &identity).await?;
                 Identity::user(identity)
             },
             AuthenticationToken::None => Identity::Unknown,
@@ -2319,6 +2919,33 @@ impl<RT: Runtime> Application<RT> {
         Ok(identity)
     }
 
+    /// Check that `identity`'s session hasn't been revoked since it was
+    /// authenticated, for revalidating long-lived sessions.
+    pub async fn ensure_session_not_revoked(&self, identity: &Identity) -> anyhow::Result<()> {
+        let Identity::User(user) = identity else {
+            return Ok(());
+        };
+        let mut tx = self.begin(Identity::system()).await?;
+        Self::ensure_user_session_not_revoked(&mut tx, user).await
+    }
+
+    async fn ensure_user_session_not_revoked(
+        tx: &mut Transaction<RT>,
+        user: &UserIdentity,
+    ) -> anyhow::Result<()> {
+        let claims = session_claims(user)?;
+        if RevokedSessionsModel::new(tx)
+            .is_revoked(&user.issuer, &user.subject, &claims)
+            .await?
+        {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "SessionRevoked",
+                "This session has been revoked"
+            ));
+        }
+        Ok(())
+    }
+
     pub async fn udf_rate(
         &self,
         identity: Identity,
@@ -2344,6 +2971,21 @@ impl<RT: Runtime> Application<RT> {
         self.function_log.cache_hit_percentage(identifier, window)
     }
 
+    /// Drop cached results of queries that declared a TTL, so they're
+    /// recomputed on their next request instead of when the TTL lapses. Only
+    /// drops results of `path` if it's given. Returns the number of results
+    /// dropped.
+    pub fn invalidate_query_cache(
+        &self,
+        identity: Identity,
+        path: Option<CanonicalizedComponentFunctionPath>,
+    ) -> anyhow::Result<usize> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("invalidate_query_cache"));
+        }
+        Ok(self.runner.invalidate_query_cache(path.as_ref()))
+    }
+
     pub async fn latency_percentiles(
         &self,
         identity: Identity,
@@ -2390,6 +3032,7 @@ impl<RT: Runtime> Application<RT> {
         if !(identity.is_admin() || identity.is_system()) {
             anyhow::bail!(unauthorized_error("stream_udf_execution"));
         }
+        identity.ensure_admin_role_allows(AdminOperation::ReadData)?;
         Ok(self.function_log.stream(cursor).await)
     }
 
@@ -2401,9 +3044,60 @@ impl<RT: Runtime> Application<RT> {
         if !(identity.is_admin() || identity.is_system()) {
             anyhow::bail!(unauthorized_error("stream_function_logs"));
         }
+        identity.ensure_admin_role_allows(AdminOperation::ReadData)?;
         Ok(self.function_log.stream_parts(cursor).await)
     }
 
+    /// Completed function executions retained in `_function_executions`,
+    /// newest first. Unlike `stream_udf_execution` these survive restarts.
+    pub async fn query_function_executions(
+        &self,
+        identity: Identity,
+        filter: FunctionExecutionFilter,
+    ) -> anyhow::Result<Vec<FunctionExecutionRecord>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("query_function_executions"));
+        }
+        identity.ensure_admin_role_allows(AdminOperation::ReadData)?;
+        let mut tx = self.begin(identity).await?;
+        let executions = FunctionExecutionsModel::new(&mut tx).query(filter).await?;
+        Ok(executions
+            .into_iter()
+            .map(|execution| execution.into_value())
+            .collect())
+    }
+
+    pub async fn query_job_executions(
+        &self,
+        identity: Identity,
+        filter: JobExecutionFilter,
+    ) -> anyhow::Result<Vec<JobExecutionRecord>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("query_job_executions"));
+        }
+        identity.ensure_admin_role_allows(AdminOperation::ReadData)?;
+        let mut tx = self.begin(identity).await?;
+        let executions = JobExecutionsModel::new(&mut tx).query(filter).await?;
+        Ok(executions
+            .into_iter()
+            .map(|execution| execution.into_value())
+            .collect())
+    }
+
+    /// How each cron's runs since `since` went.
+    pub async fn summarize_cron_executions(
+        &self,
+        identity: Identity,
+        since: UnixTimestamp,
+    ) -> anyhow::Result<Vec<CronExecutionSummary>> {
+        if !(identity.is_admin() || identity.is_system()) {
+            anyhow::bail!(unauthorized_error("summarize_cron_executions"));
+        }
+        identity.ensure_admin_role_allows(AdminOperation::ReadData)?;
+        let mut tx = self.begin(identity).await?;
+        JobExecutionsModel::new(&mut tx).summarize_crons(since).await
+    }
+
     pub async fn cancel_all_jobs(
         &self,
         path: Option<CanonicalizedComponentFunctionPath>,
@@ -2435,6 +3129,140 @@ impl<RT: Runtime> Application<RT> {
         Ok((count, vec![]))
     }
 
+    pub async fn list_scheduled_jobs(
+        &self,
+        identity: Identity,
+        filter: ScheduledJobFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<ScheduledJobPage> {
+        let mut tx = self.begin(identity).await?;
+        SchedulerModel::new(&mut tx, TableNamespace::by_component_TODO())
+            .list_page(&filter, cursor, limit)
+            .await
+    }
+
+    /// Counts the scheduled jobs matching the filter by state. Jobs are read a
+    /// page per transaction, so the counts aren't from a single snapshot.
+    pub async fn count_scheduled_jobs(
+        &self,
+        identity: Identity,
+        filter: ScheduledJobFilter,
+    ) -> anyhow::Result<BTreeMap<ScheduledJobStateKind, usize>> {
+        let mut counts: BTreeMap<_, _> = ScheduledJobStateKind::ALL
+            .into_iter()
+            .map(|state| (state, 0))
+            .collect();
+        let mut cursor = None;
+        loop {
+            let mut tx = self.begin(identity.clone()).await?;
+            let (page_counts, next_cursor) =
+                SchedulerModel::new(&mut tx, TableNamespace::by_component_TODO())
+                    .count_page(&filter, cursor)
+                    .await?;
+            for (state, count) in page_counts {
+                *counts.entry(state).or_default() += count;
+            }
+            match next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Cancels all pending and in-progress jobs matching the filter, in
+    /// batches of `MAX_JOBS_CANCEL_BATCH`. Returns the number of jobs canceled.
+    pub async fn cancel_scheduled_jobs(
+        &self,
+        identity: Identity,
+        filter: ScheduledJobFilter,
+    ) -> anyhow::Result<usize> {
+        let mut total = 0;
+        let mut cursor = None;
+        loop {
+            let (count, next_cursor) = self
+                .execute_with_audit_log_events_and_occ_retries(
+                    identity.clone(),
+                    "application_cancel_scheduled_jobs",
+                    |tx| {
+                        Self::_cancel_scheduled_jobs(
+                            tx,
+                            filter.clone(),
+                            cursor,
+                            *MAX_JOBS_CANCEL_BATCH,
+                        )
+                        .into()
+                    },
+                )
+                .await?;
+            total += count;
+            match next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+        Ok(total)
+    }
+
+    async fn _cancel_scheduled_jobs(
+        tx: &mut Transaction<RT>,
+        filter: ScheduledJobFilter,
+        cursor: Option<CreationTime>,
+        max_jobs: usize,
+    ) -> anyhow::Result<((usize, Option<CreationTime>), Vec<DeploymentAuditLogEvent>)> {
+        let result = SchedulerModel::new(tx, TableNamespace::by_component_TODO())
+            .cancel_page(&filter, cursor, max_jobs)
+            .await?;
+        Ok((result, vec![]))
+    }
+
+    /// Schedules a new run of every completed job matching the filter, in
+    /// batches of `MAX_JOBS_CANCEL_BATCH`. Returns the number of jobs re-run.
+    pub async fn rerun_scheduled_jobs(
+        &self,
+        identity: Identity,
+        filter: ScheduledJobFilter,
+    ) -> anyhow::Result<usize> {
+        let mut total = 0;
+        let mut cursor = None;
+        loop {
+            let (count, next_cursor) = self
+                .execute_with_audit_log_events_and_occ_retries(
+                    identity.clone(),
+                    "application_rerun_scheduled_jobs",
+                    |tx| {
+                        Self::_rerun_scheduled_jobs(
+                            tx,
+                            filter.clone(),
+                            cursor,
+                            *MAX_JOBS_CANCEL_BATCH,
+                        )
+                        .into()
+                    },
+                )
+                .await?;
+            total += count;
+            match next_cursor {
+                Some(next_cursor) => cursor = Some(next_cursor),
+                None => break,
+            }
+        }
+        Ok(total)
+    }
+
+    async fn _rerun_scheduled_jobs(
+        tx: &mut Transaction<RT>,
+        filter: ScheduledJobFilter,
+        cursor: Option<CreationTime>,
+        max_jobs: usize,
+    ) -> anyhow::Result<((usize, Option<CreationTime>), Vec<DeploymentAuditLogEvent>)> {
+        let result = SchedulerModel::new(tx, TableNamespace::by_component_TODO())
+            .rerun_page(&filter, cursor, max_jobs)
+            .await?;
+        Ok((result, vec![]))
+    }
+
     /// Commit a transaction and send audit log events to the log manager if the
     /// transaction commits successfully.
     pub async fn commit_with_audit_log_events(
@@ -2446,11 +3274,16 @@ impl<RT: Runtime> Application<RT> {
         DeploymentAuditLogModel::new(&mut transaction)
             .insert(events.clone())
             .await?;
+        let actor = audit_log_actor(transaction.identity(), None)?;
         let ts = self.commit(transaction, write_source).await?;
         let logs = events
             .into_iter()
             .map(|event| {
-                DeploymentAuditLogEvent::to_log_event(event, UnixTimestamp::from_nanos(ts.into()))
+                DeploymentAuditLogEvent::to_log_event(
+                    event,
+                    actor.clone(),
+                    UnixTimestamp::from_nanos(ts.into()),
+                )
             })
             .try_collect()?;
 
@@ -2557,6 +3390,7 @@ impl<RT: Runtime> Application<RT> {
         >,
     {
         let db = self.database.clone();
+        let actor = audit_log_actor(&identity, None)?;
         let (ts, (t, events), stats) = db
             .execute_with_occ_retries(
                 identity,
@@ -2571,7 +3405,11 @@ impl<RT: Runtime> Application<RT> {
         let logs = events
             .into_iter()
             .map(|event| {
-                DeploymentAuditLogEvent::to_log_event(event, UnixTimestamp::from_nanos(ts.into()))
+                DeploymentAuditLogEvent::to_log_event(
+                    event,
+                    actor.clone(),
+                    UnixTimestamp::from_nanos(ts.into()),
+                )
             })
             .try_collect()?;
 
@@ -2602,24 +3440,71 @@ impl<RT: Runtime> Application<RT> {
         self.files_storage.clone()
     }
 
+    pub fn num_running_scheduled_jobs(&self) -> usize {
+        self.scheduled_job_runner.num_running_jobs()
+    }
+
+    /// Stop starting scheduled jobs and cron jobs, and wait up to `timeout`
+    /// for running functions and scheduled jobs to finish, so the application
+    /// can then be shut down without aborting any of them. The caller should
+    /// stop accepting new function calls first.
+    pub async fn drain(&self, timeout: Duration) -> anyhow::Result<()> {
+        tracing::info!("Draining application");
+        self.scheduled_job_runner.stop_executor();
+        self.cron_job_executor.lock().shutdown();
+        let deadline = self.runtime.monotonic_now() + timeout;
+        loop {
+            let num_functions = self.runner.num_running_functions();
+            let num_jobs = self.scheduled_job_runner.num_running_jobs();
+            if num_functions == 0 && num_jobs == 0 {
+                tracing::info!("Application drained");
+                return Ok(());
+            }
+            if self.runtime.monotonic_now() >= deadline {
+                tracing::warn!(
+                    "Drain timed out after {timeout:?} with {num_functions} functions and \
+                     {num_jobs} scheduled jobs still running"
+                );
+                return Ok(());
+            }
+            self.runtime.wait(DRAIN_POLL_INTERVAL).await;
+        }
+    }
+
     pub async fn shutdown(&self) -> anyhow::Result<()> {
         self.log_sender.shutdown()?;
         self.table_summary_worker.shutdown().await?;
         self.schema_worker.lock().shutdown();
+        self.idempotency_key_worker.lock().shutdown();
+        self.function_execution_log_worker.lock().shutdown();
+        self.provider_metadata_worker.lock().shutdown();
         self.index_worker.lock().shutdown();
         self.search_worker.lock().shutdown();
         self.search_and_vector_bootstrap_worker.lock().shutdown();
         self.export_worker.lock().shutdown();
+        self.scheduled_export_worker.lock().shutdown();
+        self.deployment_clone_worker.lock().shutdown();
+        self.file_lifecycle_worker.lock().shutdown();
+        self.trash_worker.lock().shutdown();
+        self.search_index_pin_worker.lock().shutdown();
+        self.table_storage_snapshot_worker.lock().shutdown();
+        self.outbox_worker.lock().shutdown();
+        self.traffic_shadowing_worker.lock().shutdown();
+        self.log_settings_worker.lock().shutdown();
         self.snapshot_import_worker.lock().shutdown();
         self.runner.shutdown().await?;
         self.scheduled_job_runner.shutdown();
         self.cron_job_executor.lock().shutdown();
+        self.usage_tracking.shutdown().await?;
         self.database.shutdown().await?;
         tracing::info!("Application shut down");
         Ok(())
     }
 }
 
+/// How often `Application::drain` checks whether everything has finished.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 // Newer clients get a clean export in JSONL format
 static MAX_UDF_SERVER_VERSION_WITHOUT_CLEAN_EXPORT: LazyLock<Version> =
     LazyLock::new(|| Version::parse("1.3.999").unwrap());
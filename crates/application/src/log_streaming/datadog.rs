@@ -0,0 +1,124 @@
+use bytes::Bytes;
+use common::log_streaming::LogEvent;
+use model::log_sinks::types::{
+    DatadogConfig,
+    SinkType,
+};
+use reqwest::header::{
+    CONTENT_ENCODING,
+    CONTENT_TYPE,
+};
+use serde_json::Value as JsonValue;
+
+use super::LogSink;
+
+/// Delivers logs to Datadog's HTTP log intake, see
+/// <https://docs.datadoghq.com/api/latest/logs/#send-logs>.
+pub(super) struct DatadogSink {
+    config: DatadogConfig,
+    url: String,
+    service: String,
+}
+
+impl DatadogSink {
+    pub(super) fn new(config: DatadogConfig, instance_name: &str) -> Self {
+        let url = format!("https://{}/api/v2/logs", config.site_location.intake_host());
+        let service = config
+            .service
+            .clone()
+            .unwrap_or_else(|| instance_name.to_string());
+        Self {
+            config,
+            url,
+            service,
+        }
+    }
+}
+
+impl LogSink for DatadogSink {
+    fn sink_type(&self) -> SinkType {
+        SinkType::Datadog
+    }
+
+    /// Encodes the batch as a JSON array of logs, tagging each with the
+    /// configured service and tags.
+    fn encode_batch(&self, events: Vec<LogEvent>) -> anyhow::Result<Vec<u8>> {
+        let tags = self.config.dd_tags.join(",");
+        let logs = events
+            .into_iter()
+            .map(|event| {
+                let mut fields = event.to_json_map(self.config.version)?;
+                fields.insert("ddsource".to_string(), "convex".into());
+                fields.insert("ddtags".to_string(), tags.clone().into());
+                fields.insert("service".to_string(), self.service.clone().into());
+                Ok(JsonValue::Object(fields))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(serde_json::to_vec(&logs)?)
+    }
+
+    fn request(&self, client: &reqwest::Client, body: Bytes) -> reqwest::RequestBuilder {
+        client
+            .post(&self.url)
+            .header("DD-API-KEY", &self.config.dd_api_key)
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_ENCODING, "gzip")
+            .body(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        log_streaming::{
+            LogEvent,
+            LogEventFormatVersion,
+            StructuredLogEvent,
+        },
+        runtime::UnixTimestamp,
+    };
+    use model::log_sinks::types::{
+        DatadogConfig,
+        DatadogSiteLocation,
+    };
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use super::DatadogSink;
+    use crate::log_streaming::LogSink;
+
+    #[test]
+    fn test_encode_batch() -> anyhow::Result<()> {
+        let sink = DatadogSink::new(
+            DatadogConfig {
+                site_location: DatadogSiteLocation::EU,
+                dd_api_key: "api-key".to_string(),
+                dd_tags: vec!["env:prod".to_string(), "team:core".to_string()],
+                service: None,
+                version: LogEventFormatVersion::V2,
+            },
+            "carnitas",
+        );
+        assert_eq!(
+            sink.url,
+            "https://http-intake.logs.datadoghq.eu/api/v2/logs"
+        );
+
+        let event = LogEvent {
+            timestamp: UnixTimestamp::from_millis(1715000000000),
+            event: StructuredLogEvent::Verification,
+        };
+        let body: JsonValue = serde_json::from_slice(&sink.encode_batch(vec![event])?)?;
+        let JsonValue::Array(logs) = body else {
+            panic!("Expected an array of logs");
+        };
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0]["ddsource"], json!("convex"));
+        assert_eq!(logs[0]["ddtags"], json!("env:prod,team:core"));
+        assert_eq!(logs[0]["service"], json!("carnitas"));
+        assert_eq!(logs[0]["timestamp"], json!(1715000000000u64));
+        Ok(())
+    }
+}
@@ -0,0 +1,493 @@
+//! Streams the deployment's logs to the log sinks configured in `_log_sinks`.
+//!
+//! The [`LogManager`] runs a worker per active sink. Each worker batches the
+//! events it's sent, gzips the encoded batch and delivers it to the sink's
+//! service, retrying with backoff when the service is unavailable. A sink's
+//! routing rules decide which events it's sent, among the events its service
+//! accepts.
+
+mod datadog;
+mod routing;
+mod sentry;
+mod splunk;
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    mem,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use async_compression::tokio::write::GzipEncoder;
+use bytes::Bytes;
+use common::{
+    backoff::Backoff,
+    document::ParsedDocument,
+    errors::report_error,
+    knobs::{
+        LOG_SINK_BATCH_INTERVAL,
+        LOG_SINK_CHANNEL_SIZE,
+        LOG_SINK_MAX_BATCH_SIZE,
+        LOG_SINK_MAX_RETRIES,
+    },
+    log_streaming::{
+        LogEvent,
+        LogSender,
+    },
+    runtime::{
+        Runtime,
+        SpawnHandle,
+    },
+    sync::mpsc,
+};
+use database::Database;
+use errors::ErrorMetadata;
+use futures::{
+    select_biased,
+    FutureExt,
+};
+use keybroker::Identity;
+use model::log_sinks::{
+    types::{
+        LogRoutingRules,
+        LogSinksRow,
+        SinkConfig,
+        SinkState,
+        SinkType,
+    },
+    LogSinksModel,
+};
+use parking_lot::Mutex;
+use reqwest::StatusCode;
+use tokio::io::AsyncWriteExt;
+use value::ResolvedDocumentId;
+
+use self::{
+    datadog::DatadogSink,
+    routing::should_route,
+    sentry::SentrySink,
+    splunk::SplunkSink,
+};
+use crate::metrics::{
+    log_sink_events_delivered,
+    log_sink_events_dropped,
+    log_sink_request_retry,
+    log_sink_request_timer,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How a sink encodes a batch of log events and delivers it to its service.
+trait LogSink: Send + Sync + 'static {
+    fn sink_type(&self) -> SinkType;
+
+    /// Whether the service accepts this kind of event at all, regardless of
+    /// the sink's routing rules.
+    fn accepts(&self, _event: &LogEvent) -> bool {
+        true
+    }
+
+    fn max_batch_size(&self) -> usize {
+        *LOG_SINK_MAX_BATCH_SIZE
+    }
+
+    /// Encode a batch of events as the uncompressed body of a request.
+    fn encode_batch(&self, events: Vec<LogEvent>) -> anyhow::Result<Vec<u8>>;
+
+    /// Build the request that delivers a gzipped batch.
+    fn request(&self, client: &reqwest::Client, body: Bytes) -> reqwest::RequestBuilder;
+}
+
+fn new_sink(config: SinkConfig, instance_name: &str) -> anyhow::Result<Arc<dyn LogSink>> {
+    Ok(match config {
+        SinkConfig::Datadog(config) => Arc::new(DatadogSink::new(config, instance_name)),
+        SinkConfig::Splunk(config) => Arc::new(SplunkSink::new(config, instance_name)?),
+        SinkConfig::Sentry(config) => Arc::new(SentrySink::new(config, instance_name)?),
+    })
+}
+
+/// Check a sink's config before it's saved, so that obviously invalid configs
+/// are rejected up front rather than failing once the sink starts.
+pub fn validate_sink_config(config: &SinkConfig) -> anyhow::Result<()> {
+    match config {
+        SinkConfig::Datadog(config) => {
+            if config.dd_api_key.is_empty() {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidDatadogApiKey",
+                    "The Datadog API key must not be empty",
+                ));
+            }
+        },
+        SinkConfig::Splunk(config) => {
+            splunk::parse_hec_endpoint(&config.hec_endpoint)?;
+            if config.token.is_empty() {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidSplunkToken",
+                    "The Splunk HEC token must not be empty",
+                ));
+            }
+        },
+        SinkConfig::Sentry(config) => {
+            sentry::parse_dsn(&config.dsn)?;
+        },
+    }
+    Ok(())
+}
+
+async fn gzip(body: Vec<u8>) -> anyhow::Result<Bytes> {
+    let mut encoder = GzipEncoder::new(Vec::new());
+    encoder.write_all(&body).await?;
+    encoder.shutdown().await?;
+    Ok(encoder.into_inner().into())
+}
+
+struct RunningSink<RT: Runtime> {
+    config: SinkConfig,
+    sink: Arc<dyn LogSink>,
+    rules: LogRoutingRules,
+    sender: mpsc::Sender<Vec<LogEvent>>,
+    handle: RT::Handle,
+}
+
+/// Sends the deployment's logs to every active log sink.
+pub struct LogManager<RT: Runtime> {
+    sinks: Arc<Mutex<BTreeMap<SinkType, RunningSink<RT>>>>,
+    config_worker: Mutex<Option<RT::Handle>>,
+}
+
+impl<RT: Runtime> LogManager<RT> {
+    /// Start the worker that starts and stops sinks as `_log_sinks` changes.
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        instance_name: String,
+    ) -> anyhow::Result<Arc<Self>> {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()?;
+        let sinks = Arc::new(Mutex::new(BTreeMap::new()));
+        let worker = LogSinkConfigWorker {
+            runtime: runtime.clone(),
+            database,
+            instance_name,
+            client,
+            sinks: sinks.clone(),
+        };
+        let handle = runtime.spawn("log_sink_config_worker", worker.go());
+        Ok(Arc::new(Self {
+            sinks,
+            config_worker: Mutex::new(Some(handle)),
+        }))
+    }
+}
+
+impl<RT: Runtime> LogSender for LogManager<RT> {
+    fn send_logs(&self, logs: Vec<LogEvent>) {
+        if logs.is_empty() {
+            return;
+        }
+        let sinks = self.sinks.lock();
+        for (sink_type, sink) in sinks.iter() {
+            let logs: Vec<_> = logs
+                .iter()
+                .filter(|event| sink.sink.accepts(event) && should_route(&sink.rules, event))
+                .cloned()
+                .collect();
+            if logs.is_empty() {
+                continue;
+            }
+            if let Err(e) = sink.sender.try_send(logs) {
+                let (mpsc::error::TrySendError::Full(logs)
+                | mpsc::error::TrySendError::Closed(logs)) = e;
+                log_sink_events_dropped(sink_type.as_str(), "queue_full", logs.len());
+            }
+        }
+    }
+
+    fn shutdown(&self) -> anyhow::Result<()> {
+        if let Some(mut handle) = self.config_worker.lock().take() {
+            handle.shutdown();
+        }
+        for (_, mut sink) in mem::take(&mut *self.sinks.lock()) {
+            sink.handle.shutdown();
+        }
+        Ok(())
+    }
+}
+
+/// Keeps the running sinks in sync with `_log_sinks`, and records whether
+/// each sink could be started in its status.
+struct LogSinkConfigWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    instance_name: String,
+    client: reqwest::Client,
+    sinks: Arc<Mutex<BTreeMap<SinkType, RunningSink<RT>>>>,
+}
+
+impl<RT: Runtime> LogSinkConfigWorker<RT> {
+    async fn go(self) {
+        tracing::info!("Starting LogSinkConfigWorker");
+        let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+        while let Err(e) = self.run(&mut backoff).await {
+            let delay = self.runtime.with_rng(|rng| backoff.fail(rng));
+            report_error(&mut e.context("LogSinkConfigWorker died"));
+            tracing::error!("Log sink config worker failed, sleeping {delay:?}");
+            self.runtime.wait(delay).await;
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let rows = LogSinksModel::new(&mut tx).get_all().await?;
+            let status_updates = self.reconcile(rows).await;
+            if !status_updates.is_empty() {
+                for (id, status) in status_updates {
+                    LogSinksModel::new(&mut tx).set_status(id, status).await?;
+                }
+                self.database
+                    .commit_with_write_source(tx, "log_sink_config_worker")
+                    .await?;
+                continue;
+            }
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            subscription.wait_for_invalidation().await;
+            backoff.reset();
+        }
+    }
+
+    /// Start, restart or stop sinks to match `rows`, returning the statuses
+    /// that need updating.
+    async fn reconcile(
+        &self,
+        rows: Vec<ParsedDocument<LogSinksRow>>,
+    ) -> Vec<(ResolvedDocumentId, SinkState)> {
+        let configured: BTreeSet<SinkType> =
+            rows.iter().map(|row| row.config.sink_type()).collect();
+        self.sinks.lock().retain(|sink_type, sink| {
+            if configured.contains(sink_type) {
+                return true;
+            }
+            tracing::info!("Stopping {sink_type} log sink");
+            sink.handle.shutdown();
+            false
+        });
+
+        let mut status_updates = vec![];
+        for row in rows {
+            let sink_type = row.config.sink_type();
+            // Rules can change without restarting the sink.
+            let is_running = match self.sinks.lock().get_mut(&sink_type) {
+                Some(sink) if sink.config == row.config => {
+                    sink.rules = row.rules.clone();
+                    true
+                },
+                _ => false,
+            };
+            match (&row.status, is_running) {
+                (SinkState::Failed { .. }, _) => self.stop_sink(sink_type),
+                (SinkState::Active, true) => {},
+                (SinkState::Pending, true) => status_updates.push((row.id(), SinkState::Active)),
+                (status, false) => {
+                    // Only newly configured sinks are verified, so that active sinks resume
+                    // after a restart even if their service is briefly unreachable.
+                    let verify = matches!(status, SinkState::Pending);
+                    match self
+                        .start_sink(row.config.clone(), row.rules.clone(), verify)
+                        .await
+                    {
+                        Ok(sink) => {
+                            if let Some(mut previous) = self.sinks.lock().insert(sink_type, sink) {
+                                previous.handle.shutdown();
+                            }
+                            if verify {
+                                status_updates.push((row.id(), SinkState::Active));
+                            }
+                        },
+                        Err(e) => {
+                            tracing::warn!("Failed to start {sink_type} log sink: {e:#}");
+                            self.stop_sink(sink_type);
+                            status_updates.push((
+                                row.id(),
+                                SinkState::Failed {
+                                    reason: format!("{e:#}"),
+                                },
+                            ));
+                        },
+                    }
+                },
+            }
+        }
+        status_updates
+    }
+
+    async fn start_sink(
+        &self,
+        config: SinkConfig,
+        rules: LogRoutingRules,
+        verify: bool,
+    ) -> anyhow::Result<RunningSink<RT>> {
+        let sink = new_sink(config.clone(), &self.instance_name)?;
+        let worker = LogSinkWorker {
+            runtime: self.runtime.clone(),
+            client: self.client.clone(),
+            sink: sink.clone(),
+        };
+        if verify {
+            worker.verify().await?;
+        }
+        tracing::info!("Starting {} log sink", config.sink_type());
+        let (sender, receiver) = mpsc::channel(*LOG_SINK_CHANNEL_SIZE);
+        let handle = self.runtime.spawn("log_sink_worker", worker.go(receiver));
+        Ok(RunningSink {
+            config,
+            sink,
+            rules,
+            sender,
+            handle,
+        })
+    }
+
+    fn stop_sink(&self, sink_type: SinkType) {
+        if let Some(mut sink) = self.sinks.lock().remove(&sink_type) {
+            tracing::info!("Stopping {sink_type} log sink");
+            sink.handle.shutdown();
+        }
+    }
+}
+
+enum DeliveryError {
+    /// The service may accept the batch if it's sent again later, e.g. after
+    /// a network error or a 5xx response.
+    Retryable(anyhow::Error),
+    Permanent(anyhow::Error),
+}
+
+impl DeliveryError {
+    fn into_inner(self) -> anyhow::Error {
+        match self {
+            DeliveryError::Retryable(e) | DeliveryError::Permanent(e) => e,
+        }
+    }
+}
+
+/// Batches the events sent to a single sink and delivers them.
+struct LogSinkWorker<RT: Runtime> {
+    runtime: RT,
+    client: reqwest::Client,
+    sink: Arc<dyn LogSink>,
+}
+
+impl<RT: Runtime> LogSinkWorker<RT> {
+    /// Check that the sink's service accepts our logs by sending it a test
+    /// event.
+    async fn verify(&self) -> anyhow::Result<()> {
+        let event = LogEvent::default_for_verification(&self.runtime)?;
+        let body = gzip(self.sink.encode_batch(vec![event])?).await?;
+        self.send(body)
+            .await
+            .map_err(DeliveryError::into_inner)
+            .with_context(|| format!("Failed to send a test log to {}", self.sink.sink_type()))
+    }
+
+    async fn go(self, mut receiver: mpsc::Receiver<Vec<LogEvent>>) {
+        let mut buffer = vec![];
+        loop {
+            if buffer.is_empty() {
+                match receiver.recv().await {
+                    Some(events) => buffer.extend(events),
+                    None => return,
+                }
+            }
+            self.fill_batch(&mut receiver, &mut buffer).await;
+            let rest = buffer.split_off(buffer.len().min(self.sink.max_batch_size()));
+            let batch = mem::replace(&mut buffer, rest);
+            self.deliver(batch).await;
+        }
+    }
+
+    /// Wait for `buffer` to fill up to a full batch, for at most
+    /// `LOG_SINK_BATCH_INTERVAL`.
+    async fn fill_batch(
+        &self,
+        receiver: &mut mpsc::Receiver<Vec<LogEvent>>,
+        buffer: &mut Vec<LogEvent>,
+    ) {
+        let mut deadline = self.runtime.wait(*LOG_SINK_BATCH_INTERVAL);
+        while buffer.len() < self.sink.max_batch_size() {
+            select_biased! {
+                events = receiver.recv().fuse() => match events {
+                    Some(events) => buffer.extend(events),
+                    None => break,
+                },
+                _ = deadline => break,
+            }
+        }
+    }
+
+    async fn deliver(&self, events: Vec<LogEvent>) {
+        let sink = self.sink.sink_type().as_str();
+        let num_events = events.len();
+        match self.try_deliver(events).await {
+            Ok(()) => log_sink_events_delivered(sink, num_events),
+            Err(e) => {
+                tracing::warn!("Dropping {num_events} logs for {sink} log sink: {e:#}");
+                log_sink_events_dropped(sink, "delivery_failed", num_events);
+            },
+        }
+    }
+
+    async fn try_deliver(&self, events: Vec<LogEvent>) -> anyhow::Result<()> {
+        let body = gzip(self.sink.encode_batch(events)?).await?;
+        let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+        loop {
+            match self.send(body.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(DeliveryError::Retryable(e)) if backoff.failures() < *LOG_SINK_MAX_RETRIES => {
+                    let delay = self.runtime.with_rng(|rng| backoff.fail(rng));
+                    tracing::debug!(
+                        "Failed to deliver logs to {} log sink, retrying in {delay:?}: {e:#}",
+                        self.sink.sink_type()
+                    );
+                    log_sink_request_retry(self.sink.sink_type().as_str());
+                    self.runtime.wait(delay).await;
+                },
+                Err(e) => return Err(e.into_inner()),
+            }
+        }
+    }
+
+    async fn send(&self, body: Bytes) -> Result<(), DeliveryError> {
+        let sink_type = self.sink.sink_type();
+        let timer = log_sink_request_timer(sink_type.as_str());
+        let response = self
+            .sink
+            .request(&self.client, body)
+            .send()
+            .await
+            .map_err(|e| DeliveryError::Retryable(e.into()))?;
+        let status = response.status();
+        if status.is_success() {
+            timer.finish();
+            return Ok(());
+        }
+        let text = response.text().await.unwrap_or_default();
+        let error = anyhow::anyhow!("{sink_type} responded with {status}: {text}");
+        if status.is_server_error()
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::REQUEST_TIMEOUT
+        {
+            Err(DeliveryError::Retryable(error))
+        } else {
+            Err(DeliveryError::Permanent(error))
+        }
+    }
+}
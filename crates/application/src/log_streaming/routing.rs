@@ -0,0 +1,185 @@
+use common::{
+    log_lines::{
+        LogLevel,
+        LogLine,
+    },
+    log_streaming::{
+        LogEvent,
+        StructuredLogEvent,
+    },
+};
+use model::log_sinks::types::{
+    LogEventType,
+    LogRoutingRules,
+};
+
+/// Whether a sink with `rules` should receive `event`. Verification events
+/// are always routed, since they check that the sink is reachable.
+pub(super) fn should_route(rules: &LogRoutingRules, event: &LogEvent) -> bool {
+    let (event_type, function_path, level) = match &event.event {
+        StructuredLogEvent::Verification => return true,
+        StructuredLogEvent::Console { source, log_line } => {
+            let LogLine::Structured { level, .. } = log_line;
+            (LogEventType::Console, Some(&source.path), level.clone())
+        },
+        StructuredLogEvent::FunctionExecution { source, error, .. } => {
+            let level = if error.is_some() {
+                LogLevel::Error
+            } else {
+                LogLevel::Info
+            };
+            (LogEventType::FunctionExecution, Some(&source.path), level)
+        },
+        StructuredLogEvent::Exception { source, .. } => {
+            (LogEventType::Exception, Some(&source.path), LogLevel::Error)
+        },
+        StructuredLogEvent::DeploymentAuditLog { .. } => {
+            (LogEventType::AuditLog, None, LogLevel::Info)
+        },
+    };
+    if !rules.event_types.is_empty() && !rules.event_types.contains(&event_type) {
+        return false;
+    }
+    if !rules.function_paths.is_empty() {
+        let Some(function_path) = function_path else {
+            return false;
+        };
+        if !rules
+            .function_paths
+            .iter()
+            .any(|pattern| matches_function_path(pattern, function_path))
+        {
+            return false;
+        }
+    }
+    rules.log_levels.is_empty() || rules.log_levels.contains(&level)
+}
+
+fn matches_function_path(pattern: &str, function_path: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => function_path.starts_with(prefix),
+        None => pattern == function_path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::{
+        errors::JsError,
+        execution_context::ExecutionContext,
+        log_lines::{
+            LogLevel,
+            LogLine,
+        },
+        log_streaming::{
+            AggregatedFunctionUsageStats,
+            FunctionEventSource,
+            LogEvent,
+            StructuredLogEvent,
+        },
+        runtime::UnixTimestamp,
+        types::{
+            ModuleEnvironment,
+            UdfType,
+        },
+    };
+    use model::log_sinks::types::{
+        LogEventType,
+        LogRoutingRules,
+    };
+
+    use super::should_route;
+
+    fn source(path: &str) -> FunctionEventSource {
+        FunctionEventSource {
+            context: ExecutionContext::new_for_test(),
+            path: path.to_string(),
+            udf_type: UdfType::Mutation,
+            module_environment: ModuleEnvironment::Isolate,
+            cached: None,
+        }
+    }
+
+    fn event(event: StructuredLogEvent) -> LogEvent {
+        LogEvent {
+            timestamp: UnixTimestamp::from_millis(1715000000000),
+            event,
+        }
+    }
+
+    fn console(path: &str, level: LogLevel) -> LogEvent {
+        event(StructuredLogEvent::Console {
+            source: source(path),
+            log_line: LogLine::new_developer_log_line(
+                level,
+                vec!["hello".to_string()],
+                UnixTimestamp::from_millis(1715000000000),
+            ),
+        })
+    }
+
+    fn execution(path: &str, failed: bool) -> LogEvent {
+        event(StructuredLogEvent::FunctionExecution {
+            source: source(path),
+            error: failed.then(|| JsError::from_message("boom".to_string())),
+            execution_time: Duration::from_millis(10),
+            usage_stats: AggregatedFunctionUsageStats::default(),
+        })
+    }
+
+    #[test]
+    fn test_default_rules_route_everything() {
+        let rules = LogRoutingRules::default();
+        assert!(should_route(
+            &rules,
+            &console("messages:send", LogLevel::Debug)
+        ));
+        assert!(should_route(&rules, &execution("messages:send", false)));
+    }
+
+    #[test]
+    fn test_only_errors() {
+        let rules = LogRoutingRules {
+            log_levels: vec![LogLevel::Error],
+            ..Default::default()
+        };
+        assert!(should_route(
+            &rules,
+            &console("messages:send", LogLevel::Error)
+        ));
+        assert!(!should_route(
+            &rules,
+            &console("messages:send", LogLevel::Log)
+        ));
+        assert!(should_route(&rules, &execution("messages:send", true)));
+        assert!(!should_route(&rules, &execution("messages:send", false)));
+        assert!(should_route(
+            &rules,
+            &event(StructuredLogEvent::Verification)
+        ));
+    }
+
+    #[test]
+    fn test_event_types_and_function_paths() {
+        let rules = LogRoutingRules {
+            event_types: vec![LogEventType::Console],
+            function_paths: vec!["admin/*".to_string(), "messages:send".to_string()],
+            ..Default::default()
+        };
+        assert!(should_route(
+            &rules,
+            &console("admin/users:list", LogLevel::Log)
+        ));
+        assert!(should_route(
+            &rules,
+            &console("messages:send", LogLevel::Log)
+        ));
+        assert!(!should_route(
+            &rules,
+            &console("messages:list", LogLevel::Log)
+        ));
+        assert!(!should_route(&rules, &execution("admin/users:list", false)));
+    }
+}
@@ -0,0 +1,357 @@
+use bytes::Bytes;
+use common::{
+    errors::JsError,
+    log_streaming::{
+        LogEvent,
+        StructuredLogEvent,
+    },
+    sha256::Sha256,
+};
+use errors::ErrorMetadata;
+use model::log_sinks::types::{
+    SentryConfig,
+    SinkType,
+};
+use reqwest::{
+    header::{
+        CONTENT_ENCODING,
+        CONTENT_TYPE,
+    },
+    Url,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+
+use super::LogSink;
+
+/// Reports exceptions thrown by functions to Sentry's envelope endpoint, see
+/// <https://develop.sentry.dev/sdk/envelopes/>.
+///
+/// Each event's release is the version of the `convex` package that the
+/// function's modules were pushed with, and its user is a hash of the caller's
+/// token identifier so that no personal data is sent to Sentry.
+pub(super) struct SentrySink {
+    url: Url,
+    auth_header: String,
+    server_name: String,
+}
+
+/// The parts of a DSN like `https://<key>@o1.ingest.sentry.io/<project>`.
+pub(super) struct SentryDsn {
+    pub(super) public_key: String,
+    pub(super) envelope_url: Url,
+}
+
+pub(super) fn parse_dsn(dsn: &str) -> anyhow::Result<SentryDsn> {
+    let invalid = |reason: &str| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidSentryDsn",
+            format!("Invalid Sentry DSN: {reason}"),
+        ))
+    };
+    let url: Url = dsn.parse().map_err(|_| invalid("it must be a URL"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid("it must be an http or https URL"));
+    }
+    if url.username().is_empty() {
+        return Err(invalid("it's missing the public key"));
+    }
+    // The project ID is the last path segment, and any earlier segments are a
+    // prefix for the API's path.
+    let path = url.path().trim_end_matches('/');
+    let (prefix, project_id) = path.rsplit_once('/').unwrap_or(("", path));
+    if project_id.is_empty() || !project_id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid("it's missing the project ID"));
+    }
+    let mut envelope_url = url.clone();
+    envelope_url
+        .set_username("")
+        .map_err(|_| invalid("it must have a host"))?;
+    envelope_url
+        .set_password(None)
+        .map_err(|_| invalid("it must have a host"))?;
+    envelope_url.set_path(&format!("{prefix}/api/{project_id}/envelope/"));
+    Ok(SentryDsn {
+        public_key: url.username().to_string(),
+        envelope_url,
+    })
+}
+
+impl SentrySink {
+    pub(super) fn new(config: SentryConfig, instance_name: &str) -> anyhow::Result<Self> {
+        let dsn = parse_dsn(&config.dsn)?;
+        Ok(Self {
+            url: dsn.envelope_url,
+            auth_header: format!(
+                "Sentry sentry_version=7, sentry_client=convex-backend/1.0, sentry_key={}",
+                dsn.public_key
+            ),
+            server_name: instance_name.to_string(),
+        })
+    }
+
+    fn to_sentry_event(&self, event: LogEvent) -> anyhow::Result<JsonValue> {
+        let mut sentry_event = json!({
+            "event_id": format!("{:032x}", rand::random::<u128>()),
+            "timestamp": event.timestamp.as_secs_f64(),
+            "platform": "javascript",
+            "logger": "convex",
+            "server_name": self.server_name,
+        });
+        match event.event {
+            StructuredLogEvent::Exception {
+                error,
+                user_identifier,
+                source,
+                udf_server_version,
+            } => {
+                sentry_event["level"] = "error".into();
+                sentry_event["exception"] = json!({ "values": [exception(error)] });
+                sentry_event["tags"] = json!({
+                    "func": source.path,
+                    "func_type": source.udf_type.to_string(),
+                    "func_runtime": source.module_environment.to_string(),
+                    "request_id": source.context.request_id.to_string(),
+                });
+                let traceparent = source.context.traceparent();
+                sentry_event["contexts"] = json!({
+                    "trace": {
+                        "trace_id": traceparent.trace_id(),
+                        "span_id": traceparent.parent_id(),
+                    },
+                });
+                if let Some(version) = udf_server_version {
+                    sentry_event["release"] = version.to_string().into();
+                }
+                if let Some(user_identifier) = user_identifier {
+                    let id = Sha256::hash(user_identifier.0.as_bytes()).as_hex();
+                    sentry_event["user"] = json!({ "id": id });
+                }
+            },
+            StructuredLogEvent::Verification => {
+                sentry_event["level"] = "info".into();
+                sentry_event["message"] = json!({
+                    "formatted": format!(
+                        "Convex deployment {} is reporting exceptions to Sentry",
+                        self.server_name
+                    ),
+                });
+            },
+            _ => anyhow::bail!("Only exceptions are reported to Sentry"),
+        }
+        Ok(sentry_event)
+    }
+}
+
+/// Sentry's exception interface for a thrown JS error, with the stack trace
+/// ordered from the outermost frame as Sentry expects.
+fn exception(error: JsError) -> JsonValue {
+    let message = error
+        .message
+        .strip_prefix("Uncaught ")
+        .unwrap_or(&error.message);
+    let (error_type, value) = match message.split_once(": ") {
+        Some((error_type, value)) if error_type.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            (error_type, value)
+        },
+        _ => ("Error", message),
+    };
+    let frames: Vec<JsonValue> = error
+        .frames
+        .iter()
+        .flat_map(|frames| frames.0.iter())
+        .rev()
+        .map(|frame| {
+            json!({
+                "function": frame.function_name,
+                "filename": frame.file_name,
+                "lineno": frame.line_number,
+                "colno": frame.column_number,
+                "in_app": frame
+                    .file_name
+                    .as_ref()
+                    .is_some_and(|file_name| !file_name.contains("node_modules")),
+            })
+        })
+        .collect();
+    json!({
+        "type": error_type,
+        "value": value,
+        "stacktrace": { "frames": frames },
+    })
+}
+
+impl LogSink for SentrySink {
+    fn sink_type(&self) -> SinkType {
+        SinkType::Sentry
+    }
+
+    fn accepts(&self, event: &LogEvent) -> bool {
+        matches!(
+            event.event,
+            StructuredLogEvent::Exception { .. } | StructuredLogEvent::Verification
+        )
+    }
+
+    /// An envelope can only contain a single event.
+    fn max_batch_size(&self) -> usize {
+        1
+    }
+
+    fn encode_batch(&self, events: Vec<LogEvent>) -> anyhow::Result<Vec<u8>> {
+        let [event] = <[LogEvent; 1]>::try_from(events)
+            .map_err(|events| anyhow::anyhow!("Expected one event, got {}", events.len()))?;
+        let sentry_event = serde_json::to_vec(&self.to_sentry_event(event)?)?;
+        let mut body = vec![];
+        serde_json::to_writer(&mut body, &json!({}))?;
+        body.push(b'\n');
+        serde_json::to_writer(
+            &mut body,
+            &json!({ "type": "event", "length": sentry_event.len() }),
+        )?;
+        body.push(b'\n');
+        body.extend(sentry_event);
+        body.push(b'\n');
+        Ok(body)
+    }
+
+    fn request(&self, client: &reqwest::Client, body: Bytes) -> reqwest::RequestBuilder {
+        client
+            .post(self.url.clone())
+            .header("X-Sentry-Auth", &self.auth_header)
+            .header(CONTENT_TYPE, "application/x-sentry-envelope")
+            .header(CONTENT_ENCODING, "gzip")
+            .body(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        errors::JsError,
+        execution_context::ExecutionContext,
+        log_streaming::{
+            FunctionEventSource,
+            LogEvent,
+            StructuredLogEvent,
+        },
+        runtime::UnixTimestamp,
+        types::{
+            ModuleEnvironment,
+            UdfType,
+        },
+    };
+    use model::log_sinks::types::SentryConfig;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+    use sync_types::UserIdentifier;
+
+    use super::{
+        parse_dsn,
+        SentrySink,
+    };
+    use crate::log_streaming::LogSink;
+
+    #[test]
+    fn test_parse_dsn() -> anyhow::Result<()> {
+        let dsn = parse_dsn("https://abc123@o42.ingest.sentry.io/4506")?;
+        assert_eq!(dsn.public_key, "abc123");
+        assert_eq!(
+            dsn.envelope_url.as_str(),
+            "https://o42.ingest.sentry.io/api/4506/envelope/"
+        );
+        let dsn = parse_dsn("https://abc123@sentry.example.com/sentry/7")?;
+        assert_eq!(
+            dsn.envelope_url.as_str(),
+            "https://sentry.example.com/sentry/api/7/envelope/"
+        );
+        assert!(parse_dsn("not a url").is_err());
+        assert!(parse_dsn("https://o42.ingest.sentry.io/4506").is_err());
+        assert!(parse_dsn("https://abc123@o42.ingest.sentry.io/").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_exception() -> anyhow::Result<()> {
+        let sink = SentrySink::new(
+            SentryConfig {
+                dsn: "https://abc123@o42.ingest.sentry.io/4506".to_string(),
+            },
+            "carnitas",
+        )?;
+        let event = LogEvent {
+            timestamp: UnixTimestamp::from_millis(1715000000000),
+            event: StructuredLogEvent::Exception {
+                error: JsError::from_frames_for_test(
+                    "Uncaught TypeError: x is undefined",
+                    vec!["../convex/inner.ts", "../convex/outer.ts"],
+                ),
+                user_identifier: Some(UserIdentifier("issuer|subject".to_string())),
+                source: FunctionEventSource {
+                    context: ExecutionContext::new_for_test(),
+                    path: "messages:send".to_string(),
+                    udf_type: UdfType::Mutation,
+                    module_environment: ModuleEnvironment::Isolate,
+                    cached: None,
+                },
+                udf_server_version: Some(semver::Version::new(1, 17, 0)),
+            },
+        };
+        assert!(sink.accepts(&event));
+
+        let body = String::from_utf8(sink.encode_batch(vec![event])?)?;
+        let lines: Vec<JsonValue> = body
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?;
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1]["type"], json!("event"));
+        let sentry_event = &lines[2];
+        assert_eq!(sentry_event["release"], json!("1.17.0"));
+        assert_eq!(sentry_event["tags"]["func"], json!("messages:send"));
+        assert_eq!(sentry_event["server_name"], json!("carnitas"));
+        assert_eq!(
+            sentry_event["contexts"]["trace"]["trace_id"]
+                .as_str()
+                .unwrap()
+                .len(),
+            32
+        );
+        let user_id = sentry_event["user"]["id"].as_str().unwrap();
+        assert_eq!(user_id.len(), 64);
+        assert!(!body.contains("subject"));
+
+        let exception = &sentry_event["exception"]["values"][0];
+        assert_eq!(exception["type"], json!("TypeError"));
+        assert_eq!(exception["value"], json!("x is undefined"));
+        assert_eq!(
+            exception["stacktrace"]["frames"][0]["filename"],
+            json!("../convex/outer.ts")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_exceptions() -> anyhow::Result<()> {
+        let sink = SentrySink::new(
+            SentryConfig {
+                dsn: "https://abc123@o42.ingest.sentry.io/4506".to_string(),
+            },
+            "carnitas",
+        )?;
+        let event = LogEvent {
+            timestamp: UnixTimestamp::from_millis(1715000000000),
+            event: StructuredLogEvent::DeploymentAuditLog {
+                action: "create_environment_variable".to_string(),
+                metadata: Default::default(),
+                actor: Default::default(),
+            },
+        };
+        assert!(!sink.accepts(&event));
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+use bytes::Bytes;
+use common::log_streaming::LogEvent;
+use errors::ErrorMetadata;
+use model::log_sinks::types::{
+    SinkType,
+    SplunkConfig,
+};
+use reqwest::{
+    header::{
+        AUTHORIZATION,
+        CONTENT_ENCODING,
+        CONTENT_TYPE,
+    },
+    Url,
+};
+use serde_json::json;
+
+use super::LogSink;
+
+/// Delivers logs to a Splunk HTTP Event Collector, see
+/// <https://docs.splunk.com/Documentation/Splunk/latest/Data/FormateventsforHTTPEventCollector>.
+pub(super) struct SplunkSink {
+    config: SplunkConfig,
+    url: Url,
+    host: String,
+}
+
+impl SplunkSink {
+    pub(super) fn new(config: SplunkConfig, instance_name: &str) -> anyhow::Result<Self> {
+        let url = parse_hec_endpoint(&config.hec_endpoint)?;
+        Ok(Self {
+            config,
+            url,
+            host: instance_name.to_string(),
+        })
+    }
+}
+
+pub(super) fn parse_hec_endpoint(hec_endpoint: &str) -> anyhow::Result<Url> {
+    let url: Url = hec_endpoint.parse().map_err(|e| {
+        ErrorMetadata::bad_request(
+            "InvalidSplunkEndpoint",
+            format!("Invalid Splunk HEC endpoint {hec_endpoint}: {e}"),
+        )
+    })?;
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidSplunkEndpoint",
+            format!("Splunk HEC endpoint {hec_endpoint} must be an http or https URL"),
+        ));
+    }
+    Ok(url)
+}
+
+impl LogSink for SplunkSink {
+    fn sink_type(&self) -> SinkType {
+        SinkType::Splunk
+    }
+
+    /// Encodes the batch as newline separated HEC events, which the collector
+    /// accepts in a single request.
+    fn encode_batch(&self, events: Vec<LogEvent>) -> anyhow::Result<Vec<u8>> {
+        let source_type = self.config.source_type.as_deref().unwrap_or("_json");
+        let mut body = vec![];
+        for event in events {
+            let time = event.timestamp.as_secs_f64();
+            let fields = event.to_json_map(self.config.version)?;
+            let mut hec_event = json!({
+                "time": time,
+                "host": self.host,
+                "source": "convex",
+                "sourcetype": source_type,
+                "event": fields,
+            });
+            if let Some(index) = &self.config.index {
+                hec_event["index"] = index.clone().into();
+            }
+            serde_json::to_writer(&mut body, &hec_event)?;
+            body.push(b'\n');
+        }
+        Ok(body)
+    }
+
+    fn request(&self, client: &reqwest::Client, body: Bytes) -> reqwest::RequestBuilder {
+        client
+            .post(self.url.clone())
+            .header(AUTHORIZATION, format!("Splunk {}", self.config.token))
+            .header(CONTENT_TYPE, "application/json")
+            .header(CONTENT_ENCODING, "gzip")
+            .body(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        log_streaming::{
+            LogEvent,
+            LogEventFormatVersion,
+            StructuredLogEvent,
+        },
+        runtime::UnixTimestamp,
+    };
+    use model::log_sinks::types::SplunkConfig;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use super::SplunkSink;
+    use crate::log_streaming::LogSink;
+
+    fn config(hec_endpoint: &str) -> SplunkConfig {
+        SplunkConfig {
+            hec_endpoint: hec_endpoint.to_string(),
+            token: "token".to_string(),
+            index: Some("convex_logs".to_string()),
+            source_type: None,
+            version: LogEventFormatVersion::V2,
+        }
+    }
+
+    #[test]
+    fn test_encode_batch() -> anyhow::Result<()> {
+        let sink = SplunkSink::new(
+            config("https://splunk.example.com:8088/services/collector/event"),
+            "carnitas",
+        )?;
+        let events = vec![
+            LogEvent {
+                timestamp: UnixTimestamp::from_millis(1715000000500),
+                event: StructuredLogEvent::Verification,
+            };
+            2
+        ];
+        let body = String::from_utf8(sink.encode_batch(events)?)?;
+        let hec_events = body
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<JsonValue>, _>>()?;
+        assert_eq!(hec_events.len(), 2);
+        assert_eq!(hec_events[0]["time"], json!(1715000000.5));
+        assert_eq!(hec_events[0]["host"], json!("carnitas"));
+        assert_eq!(hec_events[0]["sourcetype"], json!("_json"));
+        assert_eq!(hec_events[0]["index"], json!("convex_logs"));
+        assert_eq!(hec_events[0]["event"]["topic"], json!("verification"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_endpoint() {
+        assert!(SplunkSink::new(config("not a url"), "carnitas").is_err());
+        assert!(SplunkSink::new(config("ftp://splunk.example.com"), "carnitas").is_err());
+    }
+}
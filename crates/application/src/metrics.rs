@@ -1,13 +1,17 @@
 use std::time::Duration;
 
+use common::types::UdfType;
 use metrics::{
+    log_counter,
     log_counter_with_labels,
     log_distribution,
     log_distribution_with_labels,
+    log_gauge,
     log_gauge_with_labels,
     register_convex_counter,
     register_convex_gauge,
     register_convex_histogram,
+    MetricLabel,
     StaticMetricLabel,
     StatusTimer,
     STATUS_LABEL,
@@ -103,3 +107,137 @@ fn log_worker_status(is_working: bool, name: &'static str) {
         vec![StaticMetricLabel::new("worker", name)],
     )
 }
+
+// Keys are named by developers, so they aren't labels. Usage is attributed to
+// each key in `_function_executions` instead.
+register_convex_counter!(
+    API_KEY_FUNCTION_EXECUTIONS_TOTAL,
+    "Count of top-level function executions made with a deployment API key",
+    &["udf_type", "status"],
+);
+register_convex_counter!(
+    API_KEY_DATABASE_READ_BYTES_TOTAL,
+    "Database bytes read by functions called with a deployment API key"
+);
+register_convex_counter!(
+    API_KEY_DATABASE_WRITE_BYTES_TOTAL,
+    "Database bytes written by functions called with a deployment API key"
+);
+pub fn log_api_key_function_execution(
+    udf_type: UdfType,
+    is_ok: bool,
+    database_read_bytes: u64,
+    database_write_bytes: u64,
+) {
+    log_counter_with_labels(
+        &API_KEY_FUNCTION_EXECUTIONS_TOTAL,
+        1,
+        vec![
+            MetricLabel::new("udf_type", udf_type.to_lowercase_string()),
+            MetricLabel::status(is_ok),
+        ],
+    );
+    log_counter(&API_KEY_DATABASE_READ_BYTES_TOTAL, database_read_bytes);
+    log_counter(&API_KEY_DATABASE_WRITE_BYTES_TOTAL, database_write_bytes);
+}
+
+register_convex_histogram!(
+    LOG_SINK_REQUEST_SECONDS,
+    "Time taken to deliver a batch of logs to a log sink",
+    &["status", "sink"],
+);
+pub fn log_sink_request_timer(sink: &'static str) -> StatusTimer {
+    let mut timer = StatusTimer::new(&LOG_SINK_REQUEST_SECONDS);
+    timer.add_label(StaticMetricLabel::new("sink", sink));
+    timer
+}
+
+register_convex_counter!(
+    LOG_SINK_EVENTS_DELIVERED_TOTAL,
+    "Number of log events delivered to a log sink",
+    &["sink"],
+);
+pub fn log_sink_events_delivered(sink: &'static str, num_events: usize) {
+    log_counter_with_labels(
+        &LOG_SINK_EVENTS_DELIVERED_TOTAL,
+        num_events as u64,
+        vec![StaticMetricLabel::new("sink", sink)],
+    );
+}
+
+register_convex_counter!(
+    LOG_SINK_EVENTS_DROPPED_TOTAL,
+    "Number of log events dropped before they were delivered to a log sink",
+    &["sink", "reason"],
+);
+pub fn log_sink_events_dropped(sink: &'static str, reason: &'static str, num_events: usize) {
+    log_counter_with_labels(
+        &LOG_SINK_EVENTS_DROPPED_TOTAL,
+        num_events as u64,
+        vec![
+            StaticMetricLabel::new("sink", sink),
+            StaticMetricLabel::new("reason", reason),
+        ],
+    );
+}
+
+register_convex_counter!(
+    LOG_SINK_REQUEST_RETRIES_TOTAL,
+    "Number of retried requests to a log sink",
+    &["sink"],
+);
+pub fn log_sink_request_retry(sink: &'static str) {
+    log_counter_with_labels(
+        &LOG_SINK_REQUEST_RETRIES_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("sink", sink)],
+    );
+}
+
+register_convex_counter!(
+    FUNCTION_EXECUTIONS_PERSISTED_TOTAL,
+    "Number of function executions persisted to _function_executions"
+);
+pub fn log_function_executions_persisted(num_executions: usize) {
+    log_counter(&FUNCTION_EXECUTIONS_PERSISTED_TOTAL, num_executions as u64);
+}
+
+register_convex_counter!(
+    FUNCTION_EXECUTIONS_DROPPED_TOTAL,
+    "Number of function executions dropped because persisting them fell behind"
+);
+pub fn log_function_execution_dropped() {
+    log_counter(&FUNCTION_EXECUTIONS_DROPPED_TOTAL, 1);
+}
+
+register_convex_counter!(
+    FUNCTION_EXECUTIONS_EXPIRED_TOTAL,
+    "Number of expired function executions deleted from _function_executions"
+);
+pub fn log_function_executions_expired(num_executions: usize) {
+    log_counter(&FUNCTION_EXECUTIONS_EXPIRED_TOTAL, num_executions as u64);
+}
+
+register_convex_gauge!(
+    STANDBY_REPLICATION_LAG_SECONDS,
+    "How far this standby is behind its primary, as of the last page it applied"
+);
+pub fn log_standby_replication_lag(lag_seconds: f64) {
+    log_gauge(&STANDBY_REPLICATION_LAG_SECONDS, lag_seconds);
+}
+
+register_convex_counter!(
+    TRAFFIC_SHADOWING_CALLS_TOTAL,
+    "Number of calls shadowed to the traffic shadowing target, by outcome",
+    &["udf_type", "outcome"]
+);
+pub fn log_traffic_shadowing_call(udf_type: UdfType, outcome: &'static str) {
+    log_counter_with_labels(
+        &TRAFFIC_SHADOWING_CALLS_TOTAL,
+        1,
+        vec![
+            udf_type.metric_label(),
+            StaticMetricLabel::new("outcome", outcome),
+        ],
+    );
+}
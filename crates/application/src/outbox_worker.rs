@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    http::{
+        fetch::FetchClient,
+        HttpRequest,
+    },
+    knobs::{
+        OUTBOX_DELIVERED_RETENTION,
+        OUTBOX_DELIVERY_BATCH_SIZE,
+        OUTBOX_DELIVERY_TIMEOUT,
+        OUTBOX_INITIAL_BACKOFF,
+        OUTBOX_MAX_BACKOFF,
+        UDF_EXECUTOR_OCC_MAX_RETRIES,
+    },
+    runtime::Runtime,
+};
+use database::Database;
+use errors::ErrorMetadataAnyhowExt;
+use futures::{
+    future::{
+        self,
+        Either,
+    },
+    select_biased,
+    Future,
+    FutureExt,
+};
+use http::{
+    header::CONTENT_TYPE,
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    Method,
+};
+use keybroker::Identity;
+use model::outbox::{
+    types::{
+        OutboxRecord,
+        OutboxTarget,
+        OutboxTargetConfig,
+    },
+    OutboxModel,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::{
+    id_v6::DeveloperDocumentId,
+    ResolvedDocumentId,
+};
+
+/// Every delivery of a record carries the record's id in this header, so
+/// targets can tell repeated deliveries of the same record apart from new
+/// records.
+const IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+const KAFKA_JSON_CONTENT_TYPE: &str = "application/vnd.kafka.json.v2+json";
+
+/// Delivers outbox records to their targets once they're due, retrying failed
+/// deliveries with exponential backoff. See `OutboxModel`.
+///
+/// Each attempt is claimed in its own transaction before the delivery is
+/// made, so an attempt that's interrupted, e.g. by a restart, is retried
+/// once its timeout passes, and its outcome is recorded in another
+/// transaction afterwards.
+pub struct OutboxWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    fetch_client: Arc<dyn FetchClient>,
+}
+
+impl<RT: Runtime> OutboxWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        fetch_client: Arc<dyn FetchClient>,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            fetch_client,
+        };
+        async move {
+            tracing::info!("Starting OutboxWorker");
+            let mut backoff = Backoff::new(*OUTBOX_INITIAL_BACKOFF, *OUTBOX_MAX_BACKOFF);
+            while let Err(mut e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                tracing::error!("Outbox worker failed, sleeping {delay:?}");
+                // Only report OCCs that happen repeatedly
+                if !e.is_occ() || (backoff.failures() as usize) > *UDF_EXECUTOR_OCC_MAX_RETRIES {
+                    report_error(&mut e);
+                }
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let now = self.runtime.generate_timestamp()?;
+            let (records, next_attempt_ts) = OutboxModel::new(&mut tx)
+                .due_records(now, *OUTBOX_DELIVERY_BATCH_SIZE)
+                .await?;
+            if !records.is_empty() {
+                let mut claimed = vec![];
+                for record in records {
+                    let id = record.id();
+                    if let Some((record, target)) = OutboxModel::new(&mut tx)
+                        .claim(record, *OUTBOX_DELIVERY_TIMEOUT)
+                        .await?
+                    {
+                        claimed.push((id, record, target));
+                    }
+                }
+                self.database
+                    .commit_with_write_source(tx, "outbox_claim")
+                    .await?;
+
+                let outcomes =
+                    future::join_all(claimed.into_iter().map(|(id, record, target)| async move {
+                        let result = self.deliver_with_timeout(id, &record, &target).await;
+                        (id, record.attempts, result)
+                    }))
+                    .await;
+                let num_delivered = outcomes.iter().filter(|(.., r)| r.is_ok()).count();
+                let mut tx = self.database.begin(Identity::system()).await?;
+                for (id, attempt, result) in outcomes {
+                    let result = result.map_err(|e| {
+                        tracing::warn!("Failed to deliver outbox record {id}: {e:#}");
+                        format!("{e:#}")
+                    });
+                    OutboxModel::new(&mut tx)
+                        .record_attempt(id, attempt, result)
+                        .await?;
+                }
+                self.database
+                    .commit_with_write_source(tx, "outbox_delivery")
+                    .await?;
+                tracing::debug!("Delivered {num_delivered} outbox records");
+                backoff.reset();
+                continue;
+            }
+
+            self.delete_expired().await?;
+            let next_record_future = if let Some(next_attempt_ts) = next_attempt_ts {
+                Either::Left(self.runtime.wait(next_attempt_ts - now))
+            } else {
+                Either::Right(std::future::pending())
+            };
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            select_biased! {
+                _ = next_record_future.fuse() => {
+                }
+                _ = subscription.wait_for_invalidation().fuse() => {
+                },
+            }
+            backoff.reset();
+        }
+    }
+
+    async fn deliver_with_timeout(
+        &self,
+        id: ResolvedDocumentId,
+        record: &OutboxRecord,
+        target: &OutboxTarget,
+    ) -> anyhow::Result<()> {
+        select_biased! {
+            result = self.deliver(id, record, target).fuse() => result,
+            _ = self.runtime.wait(*OUTBOX_DELIVERY_TIMEOUT).fuse() => {
+                anyhow::bail!("Delivery timed out after {:?}", *OUTBOX_DELIVERY_TIMEOUT)
+            },
+        }
+    }
+
+    async fn deliver(
+        &self,
+        id: ResolvedDocumentId,
+        record: &OutboxRecord,
+        target: &OutboxTarget,
+    ) -> anyhow::Result<()> {
+        let record_id = DeveloperDocumentId::from(id).encode();
+        let payload = JsonValue::from(record.payload.clone());
+        let mut headers = HeaderMap::new();
+        for header in target.config.headers() {
+            headers.append(
+                HeaderName::from_bytes(header.name.as_bytes())?,
+                HeaderValue::from_str(&header.value)?,
+            );
+        }
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_str(&record_id)?);
+        let (url, content_type, body) = match &target.config {
+            OutboxTargetConfig::Http { url, .. } => {
+                let body = json!({
+                    "id": record_id,
+                    "target": target.name,
+                    "payload": payload,
+                    "enqueuedTs": i64::from(record.enqueued_ts),
+                });
+                (url.parse()?, "application/json", body)
+            },
+            OutboxTargetConfig::Kafka {
+                rest_proxy_url,
+                topic,
+                ..
+            } => {
+                let url = format!("{}/topics/{topic}", rest_proxy_url.trim_end_matches('/'));
+                // Keying by the record's id lets compacted topics drop
+                // repeated deliveries.
+                let body = json!({ "records": [{ "key": record_id, "value": payload }] });
+                (url.parse()?, KAFKA_JSON_CONTENT_TYPE, body)
+            },
+        };
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        let request = HttpRequest {
+            headers,
+            url,
+            method: Method::POST,
+            body: Some(serde_json::to_vec(&body)?),
+        };
+        let response = self.fetch_client.fetch(request.into()).await?;
+        anyhow::ensure!(
+            response.status.is_success(),
+            "{} responded with {}",
+            target.name,
+            response.status
+        );
+        Ok(())
+    }
+
+    async fn delete_expired(&self) -> anyhow::Result<()> {
+        let cutoff = self
+            .runtime
+            .generate_timestamp()?
+            .sub(*OUTBOX_DELIVERED_RETENTION)?;
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let expired = OutboxModel::new(&mut tx)
+                .expired_delivered(cutoff, *OUTBOX_DELIVERY_BATCH_SIZE)
+                .await?;
+            if expired.is_empty() {
+                return Ok(());
+            }
+            let num_deleted = expired.len();
+            for id in expired {
+                OutboxModel::new(&mut tx).delete(id).await?;
+            }
+            self.database
+                .commit_with_write_source(tx, "outbox_purge")
+                .await?;
+            tracing::info!("Deleted {num_deleted} delivered outbox records");
+        }
+    }
+}
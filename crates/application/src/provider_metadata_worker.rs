@@ -0,0 +1,48 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use authentication::provider_metadata_cache::ProviderMetadataCache;
+use common::runtime::Runtime;
+use futures::Future;
+use http_client::{
+    http_client_for,
+    ClientPurpose,
+};
+
+/// How often to check for cached auth provider metadata that's due for a
+/// refresh.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Refetches the metadata and signing keys of the auth providers that tokens
+/// have been validated against before they get stale, so that validation
+/// rarely has to wait on the provider and picks up new keys ahead of their
+/// use.
+pub struct ProviderMetadataWorker<RT: Runtime> {
+    runtime: RT,
+    cache: Arc<ProviderMetadataCache>,
+}
+
+impl<RT: Runtime> ProviderMetadataWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        cache: Arc<ProviderMetadataCache>,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self { runtime, cache };
+        async move {
+            tracing::info!("Starting ProviderMetadataWorker");
+            loop {
+                worker.runtime.wait(CHECK_INTERVAL).await;
+                // Failures are logged per provider and retried on the next check.
+                worker
+                    .cache
+                    .refresh_stale(
+                        http_client_for(ClientPurpose::ProviderMetadata),
+                        worker.runtime.system_time(),
+                    )
+                    .await;
+            }
+        }
+    }
+}
@@ -2,7 +2,10 @@ use std::fmt;
 
 use anyhow::Context;
 use common::{
-    errors::JsError,
+    errors::{
+        CustomDataSource,
+        JsError,
+    },
     log_lines::LogLines,
     RequestId,
 };
@@ -88,6 +91,12 @@ impl TryFrom<RedactedLogLinesProto> for RedactedLogLines {
 ///
 /// The level of redaction will depend on the configuration of the backend and
 /// could be anything from full information to completely stripped out.
+///
+/// The data of a `ConvexError` is never redacted since the function chose to
+/// send it to clients. Typed application errors are the exception: when errors
+/// are redacted, their code and data are only included if the function
+/// declares the code and the data matched its validator. Functions that don't
+/// declare their errors can throw any typed error, so those are redacted.
 #[derive(thiserror::Error, Debug)]
 #[cfg_attr(
     any(test, feature = "testing"),
@@ -108,10 +117,31 @@ impl RedactedJsError {
         }
     }
 
+    /// The error's data, unless it's redacted. Data that validation attached
+    /// to an error is redacted along with the message, since it can reveal the
+    /// function's validators, and so are undeclared typed errors.
     pub fn custom_data_if_any(self) -> Option<ConvexValue> {
+        if self.block_logging {
+            match self.error.custom_data_source {
+                CustomDataSource::Validation => return None,
+                CustomDataSource::Thrown if self.error.typed_error().is_some() => return None,
+                CustomDataSource::Thrown | CustomDataSource::DeclaredTypedError => (),
+            }
+        }
         self.error.custom_data
     }
 
+    /// The code of a typed application error, e.g. "NotFound". When errors
+    /// are redacted, only declared codes are included.
+    pub fn error_code(&self) -> Option<&str> {
+        if self.block_logging
+            && self.error.custom_data_source != CustomDataSource::DeclaredTypedError
+        {
+            return None;
+        }
+        self.error.typed_error().map(|typed_error| typed_error.code)
+    }
+
     pub fn into_error_payload(self) -> ErrorPayload<ConvexValue> {
         let message = format!("{self}");
         if let Some(data) = self.custom_data_if_any() {
@@ -207,7 +237,10 @@ impl TryFrom<RedactedJsErrorProto> for RedactedJsError {
 #[cfg(test)]
 pub mod tests {
     use common::{
-        errors::JsError,
+        errors::{
+            CustomDataSource,
+            JsError,
+        },
         RequestId,
     };
     use isolate::HttpActionResponsePart;
@@ -218,7 +251,11 @@ pub mod tests {
     };
     use proptest::prelude::*;
     use serde_json::Value as JsonValue;
-    use value::testing::assert_roundtrips;
+    use value::{
+        assert_obj,
+        testing::assert_roundtrips,
+        ConvexValue,
+    };
 
     use crate::redaction::{
         RedactedJsError,
@@ -282,6 +319,48 @@ pub mod tests {
             );
         }
 
+        #[test]
+        fn validation_data_only_included_when_not_redacted(
+            js_error in any::<JsError>(),
+            block_logging in any::<bool>(),
+            request_id in any::<RequestId>(),
+        ) {
+            prop_assume!(js_error.typed_error().is_none());
+            let redacted =
+                RedactedJsError::from_js_error(js_error.clone(), block_logging, request_id);
+            let redact_data =
+                block_logging && js_error.custom_data_source == CustomDataSource::Validation;
+            let expected_data = if redact_data { None } else { js_error.custom_data };
+            assert_eq!(redacted.clone().custom_data_if_any(), expected_data.clone());
+            assert_eq!(get_data(redacted.to_http_response_parts()), expected_data.map(Into::into));
+        }
+
+        #[test]
+        fn typed_error_only_included_when_declared_or_not_redacted(
+            code in "[A-Za-z][A-Za-z0-9_]{0,15}",
+            data in any::<ConvexValue>(),
+            source in any::<CustomDataSource>(),
+            block_logging in any::<bool>(),
+            request_id in any::<RequestId>(),
+        ) {
+            let custom_data = ConvexValue::Object(
+                assert_obj!("code" => code.clone(), "data" => data),
+            );
+            let js_error = JsError {
+                custom_data_source: source,
+                ..JsError::convex_error("Uncaught ConvexError".to_string(), custom_data.clone())
+            };
+            let redacted = RedactedJsError::from_js_error(js_error, block_logging, request_id);
+            let included = !block_logging || source == CustomDataSource::DeclaredTypedError;
+            assert_eq!(redacted.error_code(), included.then_some(&code[..]));
+            let expected_data = included.then_some(custom_data);
+            assert_eq!(redacted.clone().custom_data_if_any(), expected_data.clone());
+            assert_eq!(
+                get_data(redacted.to_http_response_parts()),
+                expected_data.map(Into::into),
+            );
+        }
+
         #[test]
         fn test_redacted_js_error_roundtrips(left in any::<RedactedJsError>()) {
             assert_roundtrips::<RedactedJsError, RedactedJsErrorProto>(left);
@@ -294,6 +373,18 @@ pub mod tests {
     }
 
     fn get_code(http_response_parts: Vec<HttpActionResponsePart>) -> String {
+        let map = get_body(http_response_parts);
+        must_let!(let JsonValue::String(ref code) = map.get("code").unwrap());
+        code.clone()
+    }
+
+    fn get_data(http_response_parts: Vec<HttpActionResponsePart>) -> Option<JsonValue> {
+        get_body(http_response_parts).remove("data")
+    }
+
+    fn get_body(
+        http_response_parts: Vec<HttpActionResponsePart>,
+    ) -> serde_json::Map<String, JsonValue> {
         let mut body_bytes = vec![];
         for part in http_response_parts {
             match part {
@@ -303,7 +394,6 @@ pub mod tests {
         }
         let json = serde_json::from_slice(&body_bytes).unwrap();
         must_let!(let JsonValue::Object(map) = json);
-        must_let!(let JsonValue::String(ref code) = map.get("code").unwrap());
-        code.clone()
+        map
     }
 }
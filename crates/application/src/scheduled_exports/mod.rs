@@ -0,0 +1,278 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use common::{
+    backoff::Backoff,
+    document::ParsedDocument,
+    errors::report_error,
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::Database;
+use futures::{
+    future::Either,
+    select_biased,
+    Future,
+    FutureExt,
+};
+use keybroker::Identity;
+use model::scheduled_exports::{
+    next_schedule_ts,
+    types::{
+        ExportRunState,
+        ExportSchedule,
+    },
+    ScheduledExportsModel,
+};
+use storage::Storage;
+use usage_tracking::UsageCounter;
+use value::ResolvedDocumentId;
+
+use self::bucket::BucketClient;
+use crate::export_worker::ExportWorker;
+
+mod bucket;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+/// Runs the schedules in `_scheduled_exports`: when a schedule is due, exports
+/// a ZIP snapshot of the deployment, uploads it to the schedule's bucket and
+/// then deletes the oldest uploads beyond its retention count. Each run's
+/// progress, and the error if it fails, is recorded in
+/// `_scheduled_export_runs`.
+pub struct ScheduledExportWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    exports_storage: Arc<dyn Storage>,
+    export_worker: ExportWorker<RT>,
+    instance_name: String,
+}
+
+impl<RT: Runtime> ScheduledExportWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        exports_storage: Arc<dyn Storage>,
+        files_storage: Arc<dyn Storage>,
+        usage_tracking: UsageCounter,
+        instance_name: String,
+    ) -> impl Future<Output = ()> + Send {
+        let export_worker = ExportWorker::create(
+            runtime.clone(),
+            database.clone(),
+            exports_storage.clone(),
+            files_storage,
+            usage_tracking,
+        );
+        let mut worker = Self {
+            runtime,
+            database,
+            exports_storage,
+            export_worker,
+            instance_name,
+        };
+        async move {
+            tracing::info!("Starting ScheduledExportWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("ScheduledExportWorker died"));
+                tracing::error!("Scheduled export worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&mut self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let now = self.runtime.generate_timestamp()?;
+            let mut model = ScheduledExportsModel::new(&mut tx);
+            let mut due = None;
+            let mut next_ts: Option<Timestamp> = None;
+            for schedule in model.list().await? {
+                // A run that didn't finish, e.g. because the backend restarted
+                // during it, is resumed before anything else.
+                let latest_run = model.latest_runs(&schedule.name, 1).await?.pop();
+                if let Some(run) = latest_run
+                    && !run.state.is_finished()
+                {
+                    due = Some((schedule, Some(run.id())));
+                    break;
+                }
+                if schedule.next_ts <= now {
+                    due = Some((schedule, None));
+                    break;
+                }
+                next_ts = Some(next_ts.map_or(schedule.next_ts, |ts| ts.min(schedule.next_ts)));
+            }
+            if let Some((schedule, run_id)) = due {
+                self.run_schedule(schedule, run_id).await?;
+                backoff.reset();
+                continue;
+            }
+
+            let next_schedule_future = match next_ts {
+                Some(next_ts) => Either::Left(self.runtime.wait(next_ts - now)),
+                None => Either::Right(std::future::pending()),
+            };
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            select_biased! {
+                _ = next_schedule_future.fuse() => {},
+                _ = subscription.wait_for_invalidation().fuse() => {},
+            }
+            backoff.reset();
+        }
+    }
+
+    /// Run `schedule`, resuming `run_id` if it's given. Failing to export or
+    /// upload fails the run rather than the worker, so the schedule's next
+    /// run still happens.
+    async fn run_schedule(
+        &mut self,
+        schedule: ParsedDocument<ExportSchedule>,
+        run_id: Option<ResolvedDocumentId>,
+    ) -> anyhow::Result<()> {
+        let run_id = match run_id {
+            Some(run_id) => {
+                tracing::info!("Resuming scheduled export {}", schedule.name);
+                run_id
+            },
+            None => {
+                tracing::info!("Starting scheduled export {}", schedule.name);
+                let mut tx = self.database.begin(Identity::system()).await?;
+                let now = self.runtime.generate_timestamp()?;
+                let mut model = ScheduledExportsModel::new(&mut tx);
+                let run_id = model.start_run(&schedule.name, now).await?;
+                model
+                    .set_next_ts(schedule.id(), next_schedule_ts(&schedule, now)?)
+                    .await?;
+                self.database
+                    .commit_with_write_source(tx, "scheduled_export_start")
+                    .await?;
+                run_id
+            },
+        };
+        let state = match self.export_and_upload(&schedule, run_id).await {
+            Ok(state) => state,
+            Err(mut e) => {
+                report_error(&mut e);
+                ExportRunState::Failed {
+                    error: format!("{e:#}"),
+                    failed_ts: self.runtime.generate_timestamp()?,
+                }
+            },
+        };
+        self.set_run_state(run_id, state).await?;
+        if let Err(mut e) = self.apply_retention(&schedule).await {
+            report_error(&mut e.context(format!(
+                "Failed to delete expired exports of {}",
+                schedule.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Export a snapshot and upload it to the schedule's bucket. A resumed run
+    /// starts over from the export, since the snapshot it was uploading may
+    /// be incomplete.
+    async fn export_and_upload(
+        &mut self,
+        schedule: &ExportSchedule,
+        run_id: ResolvedDocumentId,
+    ) -> anyhow::Result<ExportRunState> {
+        self.set_run_state(run_id, ExportRunState::Exporting)
+            .await?;
+        let (snapshot_ts, export_key) = self
+            .export_worker
+            .export_zip(schedule.include_storage)
+            .await?;
+        self.set_run_state(run_id, ExportRunState::Uploading { snapshot_ts })
+            .await?;
+
+        let export = self
+            .exports_storage
+            .get(&export_key)
+            .await?
+            .with_context(|| format!("Export {export_key:?} is missing from storage"))?;
+        let size_bytes = export.content_length.try_into()?;
+        let object_key = format!(
+            "{}snapshot_{}_{}_{}.zip",
+            schedule.destination.prefix,
+            self.instance_name,
+            schedule.name,
+            i64::from(snapshot_ts)
+        );
+        BucketClient::new(schedule.destination.clone())
+            .put(&object_key, size_bytes, export.stream)
+            .await
+            .with_context(|| format!("Failed to upload to {}", schedule.destination.bucket))?;
+        tracing::info!(
+            "Uploaded scheduled export {} to {}/{object_key}",
+            schedule.name,
+            schedule.destination.bucket
+        );
+        Ok(ExportRunState::Completed {
+            snapshot_ts,
+            object_key,
+            size_bytes,
+            completed_ts: self.runtime.generate_timestamp()?,
+        })
+    }
+
+    async fn set_run_state(
+        &self,
+        run_id: ResolvedDocumentId,
+        state: ExportRunState,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        ScheduledExportsModel::new(&mut tx)
+            .set_run_state(run_id, state)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "scheduled_export_run_state")
+            .await?;
+        Ok(())
+    }
+
+    /// Delete all but the newest `retention_count` completed runs, along with
+    /// their uploads, and all but the newest `retention_count` failed runs.
+    async fn apply_retention(&self, schedule: &ExportSchedule) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let runs = ScheduledExportsModel::new(&mut tx)
+            .latest_runs(&schedule.name, usize::MAX)
+            .await?;
+        let retention_count = usize::try_from(schedule.retention_count)?;
+        let (completed, failed): (Vec<_>, Vec<_>) = runs
+            .into_iter()
+            .filter(|run| run.state.is_finished())
+            .partition(|run| matches!(run.state, ExportRunState::Completed { .. }));
+        let bucket = BucketClient::new(schedule.destination.clone());
+        for run in completed.into_iter().skip(retention_count) {
+            if let ExportRunState::Completed { object_key, .. } = &run.state {
+                bucket.delete(object_key).await?;
+            }
+            self.delete_run(run.id()).await?;
+        }
+        for run in failed.into_iter().skip(retention_count) {
+            self.delete_run(run.id()).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_run(&self, run_id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        ScheduledExportsModel::new(&mut tx)
+            .delete_run(run_id)
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "scheduled_export_retention")
+            .await?;
+        Ok(())
+    }
+}
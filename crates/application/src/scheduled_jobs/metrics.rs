@@ -2,15 +2,22 @@ use std::time::Duration;
 
 use errors::ErrorMetadataAnyhowExt;
 use metrics::{
+    log_counter,
     log_counter_with_labels,
     log_distribution,
+    log_distribution_with_labels,
     log_gauge,
+    log_gauge_with_labels,
     register_convex_counter,
     register_convex_gauge,
     register_convex_histogram,
     StaticMetricLabel,
     STATUS_LABEL,
 };
+use model::{
+    queues::types::QueueDeliveryOutcome,
+    scheduled_jobs::types::ScheduledJobPriority,
+};
 
 register_convex_counter!(
     SCHEDULED_JOB_RESULT_TOTAL,
@@ -53,3 +60,75 @@ register_convex_gauge!(
 pub fn log_num_running_jobs(num_running: usize) {
     log_gauge(&SCHEDULED_JOB_NUM_RUNNING_TOTAL, num_running as f64);
 }
+
+register_convex_gauge!(
+    SCHEDULED_JOB_NUM_RUNNING_BY_PRIORITY_TOTAL,
+    "Number of currently executing scheduled jobs in each priority lane",
+    &["priority"]
+);
+pub fn log_num_running_jobs_by_priority(priority: ScheduledJobPriority, num_running: usize) {
+    log_gauge_with_labels(
+        &SCHEDULED_JOB_NUM_RUNNING_BY_PRIORITY_TOTAL,
+        num_running as f64,
+        vec![StaticMetricLabel::new("priority", priority.as_str())],
+    );
+}
+
+register_convex_histogram!(
+    SCHEDULED_JOB_START_DELAY_SECONDS,
+    "Time between when a scheduled job was ready to run and when it started",
+    &["priority"]
+);
+pub fn log_scheduled_job_start_delay(priority: ScheduledJobPriority, delay: Duration) {
+    log_distribution_with_labels(
+        &SCHEDULED_JOB_START_DELAY_SECONDS,
+        delay.as_secs_f64(),
+        vec![StaticMetricLabel::new("priority", priority.as_str())],
+    );
+}
+
+register_convex_counter!(
+    SCHEDULED_JOB_THROTTLED_TOTAL,
+    "Number of times a ready scheduled job wasn't started because its priority lane or function \
+     was at its concurrency limit",
+    &["priority", "reason"]
+);
+pub fn log_scheduled_job_throttled(priority: ScheduledJobPriority, reason: &'static str) {
+    log_counter_with_labels(
+        &SCHEDULED_JOB_THROTTLED_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("priority", priority.as_str()),
+            StaticMetricLabel::new("reason", reason),
+        ],
+    );
+}
+
+register_convex_counter!(
+    SCHEDULED_JOB_WORKFLOW_RESTARTED_TOTAL,
+    "Number of times a workflow was restarted from its last checkpoint"
+);
+pub fn log_workflow_restarted() {
+    log_counter(&SCHEDULED_JOB_WORKFLOW_RESTARTED_TOTAL, 1);
+}
+
+register_convex_counter!(
+    SCHEDULED_JOB_BATCH_EXPANDED_TOTAL,
+    "Number of scheduled jobs written to the scheduled jobs table from batches"
+);
+pub fn log_scheduled_job_batch_expanded(num_jobs: usize) {
+    log_counter(&SCHEDULED_JOB_BATCH_EXPANDED_TOTAL, num_jobs as u64);
+}
+
+register_convex_counter!(
+    QUEUE_MESSAGE_DISPATCHED_TOTAL,
+    "Number of visible queue messages processed by the queue dispatcher",
+    &["outcome"]
+);
+pub fn log_queue_message_outcome(outcome: QueueDeliveryOutcome) {
+    log_counter_with_labels(
+        &QUEUE_MESSAGE_DISPATCHED_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("outcome", outcome.as_str())],
+    );
+}
@@ -1,16 +1,27 @@
 use std::{
     collections::{
         BTreeMap,
-        HashSet,
+        HashMap,
     },
     ops::Deref,
-    sync::Arc,
+    sync::{
+        atomic::{
+            AtomicBool,
+            AtomicUsize,
+            Ordering,
+        },
+        Arc,
+        Weak,
+    },
     time::Duration,
 };
 
 use common::{
     backoff::Backoff,
-    components::CanonicalizedComponentFunctionPath,
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
     document::ParsedDocument,
     errors::{
         report_error,
@@ -22,10 +33,15 @@ use common::{
         SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE,
         SCHEDULED_JOB_GARBAGE_COLLECTION_INITIAL_BACKOFF,
         SCHEDULED_JOB_GARBAGE_COLLECTION_MAX_BACKOFF,
+        SCHEDULED_JOB_HIGH_PRIORITY_RESERVED_PARALLELISM,
         SCHEDULED_JOB_INITIAL_BACKOFF,
+        SCHEDULED_JOB_LOW_PRIORITY_MAX_PARALLELISM,
         SCHEDULED_JOB_MAX_BACKOFF,
+        SCHEDULED_JOB_MAX_THROTTLED_PER_POLL,
         SCHEDULED_JOB_RETENTION,
         UDF_EXECUTOR_OCC_MAX_RETRIES,
+        WORKFLOW_MAX_ATTEMPTS,
+        WORKFLOW_RESTART_INITIAL_BACKOFF,
     },
     minitrace_helpers::get_sampled_span,
     pause::PauseClient,
@@ -39,6 +55,7 @@ use common::{
         Runtime,
         RuntimeInstant,
         SpawnHandle,
+        UnixTimestamp,
     },
     sync::mpsc,
     tokio::task::yield_now,
@@ -70,10 +87,23 @@ use minitrace::future::FutureExt as _;
 use model::{
     backend_state::BackendStateModel,
     components::ComponentsModel,
+    function_executions::types::{
+        FunctionExecutionStatus,
+        FunctionExecutionUsage,
+    },
+    job_executions::types::{
+        JobExecutionRecord,
+        JobKind,
+    },
     modules::ModuleModel,
+    scheduled_job_batches::{
+        ScheduledJobBatchModel,
+        SCHEDULED_JOB_BATCHES_TABLE,
+    },
     scheduled_jobs::{
         types::{
             ScheduledJob,
+            ScheduledJobPriority,
             ScheduledJobState,
         },
         SchedulerModel,
@@ -83,34 +113,61 @@ use model::{
         SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS,
         SCHEDULED_JOBS_TABLE,
     },
+    workflows::WorkflowModel,
 };
 use parking_lot::Mutex;
-use sync_types::Timestamp;
+use sync_types::{
+    CanonicalizedUdfPath,
+    Timestamp,
+};
 use usage_tracking::FunctionUsageTracker;
 use value::{
+    DeveloperDocumentId,
     ResolvedDocumentId,
     TableNamespace,
+    TabletId,
 };
 
+use self::queue_dispatcher::QueueDispatcher;
 use crate::{
     application_function_runner::ApplicationFunctionRunner,
-    function_log::FunctionExecutionLog,
+    function_log::{
+        FunctionExecutionLog,
+        IndexRangesRead,
+    },
+    job_execution_history::{
+        job_execution_usage,
+        record_job_execution,
+    },
 };
 
 mod metrics;
+mod queue_dispatcher;
 
 pub(crate) const SCHEDULED_JOB_EXECUTED: &str = "scheduled_job_executed";
 
 pub struct ScheduledJobRunner<RT: Runtime> {
     executor: Arc<Mutex<RT::Handle>>,
+    // Owned by the executor's future, so it can't be upgraded once the
+    // executor has stopped.
+    executor_live: Weak<AtomicBool>,
+    // Jobs the executor has started that haven't finished. They keep running
+    // if the executor is stopped.
+    num_running_jobs: Arc<AtomicUsize>,
     garbage_collector: Arc<Mutex<RT::Handle>>,
+    batch_expander: Arc<Mutex<RT::Handle>>,
+    queue_dispatcher: Arc<Mutex<RT::Handle>>,
 }
 
 impl<RT: Runtime> Clone for ScheduledJobRunner<RT> {
     fn clone(&self) -> Self {
         Self {
             executor: self.executor.clone(),
+            executor_live: self.executor_live.clone(),
+            num_running_jobs: self.num_running_jobs.clone(),
             garbage_collector: self.garbage_collector.clone(),
+            batch_expander: self.batch_expander.clone(),
+            queue_dispatcher: self.queue_dispatcher.clone(),
         }
     }
 }
@@ -123,34 +180,76 @@ impl<RT: Runtime> ScheduledJobRunner<RT> {
         function_log: FunctionExecutionLog<RT>,
         pause_client: PauseClient,
     ) -> Self {
+        let live = Arc::new(AtomicBool::new(false));
+        let executor_live = Arc::downgrade(&live);
+        let num_running_jobs = Arc::new(AtomicUsize::new(0));
         let executor_fut = ScheduledJobExecutor::start(
             rt.clone(),
             database.clone(),
             runner,
             function_log,
             pause_client,
+            live,
+            num_running_jobs.clone(),
         );
         let executor = Arc::new(Mutex::new(rt.spawn("scheduled_job_executor", executor_fut)));
 
-        let garbage_collector_fut = ScheduledJobGarbageCollector::start(rt.clone(), database);
+        let garbage_collector_fut =
+            ScheduledJobGarbageCollector::start(rt.clone(), database.clone());
         let garbage_collector = Arc::new(Mutex::new(
             rt.spawn("scheduled_job_garbage_collector", garbage_collector_fut),
         ));
+
+        let batch_expander_fut = ScheduledJobBatchExpander::start(rt.clone(), database.clone());
+        let batch_expander = Arc::new(Mutex::new(
+            rt.spawn("scheduled_job_batch_expander", batch_expander_fut),
+        ));
+
+        let queue_dispatcher_fut = QueueDispatcher::start(rt.clone(), database);
+        let queue_dispatcher = Arc::new(Mutex::new(
+            rt.spawn("queue_dispatcher", queue_dispatcher_fut),
+        ));
         Self {
             executor,
+            executor_live,
+            num_running_jobs,
             garbage_collector,
+            batch_expander,
+            queue_dispatcher,
         }
     }
 
+    /// Whether the executor is running and its last attempt to poll for jobs
+    /// succeeded.
+    pub fn is_live(&self) -> bool {
+        self.executor_live
+            .upgrade()
+            .is_some_and(|live| live.load(Ordering::SeqCst))
+    }
+
+    /// Stop starting scheduled jobs. Jobs that are already running aren't
+    /// interrupted.
+    pub fn stop_executor(&self) {
+        self.executor.lock().shutdown();
+    }
+
+    pub fn num_running_jobs(&self) -> usize {
+        self.num_running_jobs.load(Ordering::SeqCst)
+    }
+
     pub fn shutdown(&self) {
         self.executor.lock().shutdown();
         self.garbage_collector.lock().shutdown();
+        self.batch_expander.lock().shutdown();
+        self.queue_dispatcher.lock().shutdown();
     }
 }
 
 pub struct ScheduledJobExecutor<RT: Runtime> {
     context: ScheduledJobContext<RT>,
     pause_client: PauseClient,
+    live: Arc<AtomicBool>,
+    num_running_jobs: Arc<AtomicUsize>,
 }
 
 impl<RT: Runtime> Deref for ScheduledJobExecutor<RT> {
@@ -169,6 +268,98 @@ pub struct ScheduledJobContext<RT: Runtime> {
     function_log: FunctionExecutionLog<RT>,
 }
 
+/// Jobs that the executor has started but that haven't finished yet, counted by
+/// priority lane and by function so we can enforce their concurrency limits.
+#[derive(Default)]
+struct RunningJobs {
+    jobs: HashMap<ResolvedDocumentId, (ScheduledJobPriority, CanonicalizedUdfPath)>,
+    num_by_priority: HashMap<ScheduledJobPriority, usize>,
+    // Functions are keyed by the scheduled jobs table of their component.
+    num_by_function: HashMap<(TabletId, CanonicalizedUdfPath), usize>,
+}
+
+impl RunningJobs {
+    fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    fn contains(&self, job_id: &ResolvedDocumentId) -> bool {
+        self.jobs.contains_key(job_id)
+    }
+
+    fn num_with_priority(&self, priority: ScheduledJobPriority) -> usize {
+        self.num_by_priority.get(&priority).copied().unwrap_or(0)
+    }
+
+    fn insert(&mut self, job_id: ResolvedDocumentId, job: &ScheduledJob) {
+        if self
+            .jobs
+            .insert(job_id, (job.priority, job.udf_path.clone()))
+            .is_some()
+        {
+            return;
+        }
+        *self.num_by_priority.entry(job.priority).or_default() += 1;
+        *self
+            .num_by_function
+            .entry((job_id.tablet_id, job.udf_path.clone()))
+            .or_default() += 1;
+    }
+
+    fn remove(&mut self, job_id: &ResolvedDocumentId) {
+        let Some((priority, udf_path)) = self.jobs.remove(job_id) else {
+            return;
+        };
+        if let Some(num) = self.num_by_priority.get_mut(&priority) {
+            *num -= 1;
+        }
+        let key = (job_id.tablet_id, udf_path);
+        if let Some(num) = self.num_by_function.get_mut(&key) {
+            *num -= 1;
+            if *num == 0 {
+                self.num_by_function.remove(&key);
+            }
+        }
+    }
+
+    /// Returns why a ready job can't start yet, or None if it can. Normal and
+    /// low priority jobs can't use the slots reserved for high priority jobs,
+    /// and low priority jobs have their own cap on top of that.
+    fn throttle_reason(
+        &self,
+        job_id: ResolvedDocumentId,
+        job: &ScheduledJob,
+    ) -> Option<&'static str> {
+        let parallelism = *SCHEDULED_JOB_EXECUTION_PARALLELISM;
+        let shared_parallelism = parallelism
+            .saturating_sub(*SCHEDULED_JOB_HIGH_PRIORITY_RESERVED_PARALLELISM)
+            .max(1);
+        let lane_full = match job.priority {
+            ScheduledJobPriority::High => self.len() >= parallelism,
+            ScheduledJobPriority::Normal => self.len() >= shared_parallelism,
+            ScheduledJobPriority::Low => {
+                self.len() >= shared_parallelism
+                    || self.num_with_priority(ScheduledJobPriority::Low)
+                        >= *SCHEDULED_JOB_LOW_PRIORITY_MAX_PARALLELISM
+            },
+        };
+        if lane_full {
+            return Some("priority");
+        }
+        if let Some(max_concurrency) = job.max_concurrency {
+            let num_running = self
+                .num_by_function
+                .get(&(job_id.tablet_id, job.udf_path.clone()))
+                .copied()
+                .unwrap_or(0);
+            if num_running >= max_concurrency as usize {
+                return Some("function");
+            }
+        }
+        None
+    }
+}
+
 /// This roughly matches tokio's permits that it uses as part of cooperative
 /// scheduling. We shouldn't use this for anything sophisticated, it's just a
 /// simple way for us to yield occasionally for scheduled jobs but not yield too
@@ -182,6 +373,8 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
         runner: Arc<ApplicationFunctionRunner<RT>>,
         function_log: FunctionExecutionLog<RT>,
         pause_client: PauseClient,
+        live: Arc<AtomicBool>,
+        num_running_jobs: Arc<AtomicUsize>,
     ) -> impl Future<Output = ()> + Send {
         let mut executor = Self {
             context: ScheduledJobContext {
@@ -191,11 +384,14 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 function_log,
             },
             pause_client,
+            live,
+            num_running_jobs,
         };
         async move {
             let mut backoff =
                 Backoff::new(*SCHEDULED_JOB_INITIAL_BACKOFF, *SCHEDULED_JOB_MAX_BACKOFF);
             while let Err(mut e) = executor.run(&mut backoff).await {
+                executor.live.store(false, Ordering::SeqCst);
                 let delay = executor.rt.with_rng(|rng| backoff.fail(rng));
                 tracing::error!("Scheduled job executor failed, sleeping {delay:?}");
                 report_error(&mut e);
@@ -219,17 +415,19 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 function_log,
             },
             pause_client: PauseClient::new(),
+            live: Arc::new(AtomicBool::new(false)),
+            num_running_jobs: Arc::new(AtomicUsize::new(0)),
         }
     }
 
     async fn drain_finished_jobs(
-        running_job_ids: &mut HashSet<ResolvedDocumentId>,
+        running_jobs: &mut RunningJobs,
         rx: &mut mpsc::Receiver<ResolvedDocumentId>,
     ) {
         let mut total_drained = 0;
         while let Ok(job_id) = rx.try_recv() {
             total_drained += 1;
-            running_job_ids.remove(&job_id);
+            running_jobs.remove(&job_id);
             if total_drained % CHECKS_BETWEEN_YIELDS == 0 {
                 yield_now().await;
             }
@@ -241,32 +439,41 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
         tracing::info!("Starting scheduled job executor");
         let (job_finished_tx, mut job_finished_rx) =
             mpsc::channel(*SCHEDULED_JOB_EXECUTION_PARALLELISM);
-        let mut running_job_ids = HashSet::new();
+        let mut running_jobs = RunningJobs::default();
         // Some if there's at least one pending job. May be in the past!
         let mut next_job_ready_time = None;
         loop {
-            Self::drain_finished_jobs(&mut running_job_ids, &mut job_finished_rx).await;
+            Self::drain_finished_jobs(&mut running_jobs, &mut job_finished_rx).await;
 
             let mut tx = self.database.begin(Identity::Unknown).await?;
             let backend_state = BackendStateModel::new(&mut tx).get_backend_state().await?;
-            let is_backend_stopped = backend_state.is_stopped();
+            // Jobs run mutations and actions, so hold them while the deployment is
+            // read-only too.
+            let is_backend_stopped = backend_state.is_stopped() || backend_state.is_read_only();
 
             next_job_ready_time = if is_backend_stopped {
                 // If the backend is stopped we shouldn't poll. Our subscription will notify us
                 // when the backend is started again.
                 None
-            } else if running_job_ids.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
+            } else if running_jobs.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
                 // A scheduled job may have been added, but we can't do anything because we're
                 // still running jobs at our concurrency limit.
                 next_job_ready_time
             } else {
                 // Great! we have enough remaining concurrency and our backend is running, start
                 // new job(s) if we can and update our next ready time.
-                self.query_and_start_jobs(&mut tx, &mut running_job_ids, &job_finished_tx)
+                self.query_and_start_jobs(&mut tx, &mut running_jobs, &job_finished_tx)
                     .await?
             };
 
-            metrics::log_num_running_jobs(running_job_ids.len());
+            self.live.store(true, Ordering::SeqCst);
+            metrics::log_num_running_jobs(running_jobs.len());
+            for priority in ScheduledJobPriority::ALL {
+                metrics::log_num_running_jobs_by_priority(
+                    priority,
+                    running_jobs.num_with_priority(priority),
+                );
+            }
             let next_job_future = if let Some(next_job_ts) = next_job_ready_time {
                 let now = self.rt.generate_timestamp()?;
                 Either::Left(if next_job_ts < now {
@@ -287,10 +494,12 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
             let subscription = self.database.subscribe(token).await?;
 
             select_biased! {
+                // A running job finishing frees a slot for the jobs that were
+                // passed over.
                 job_id = job_finished_rx.recv().fuse() => {
                     if let Some(job_id) = job_id {
                     self.pause_client.wait(SCHEDULED_JOB_EXECUTED).await;
-                        running_job_ids.remove(&job_id);
+                        running_jobs.remove(&job_id);
                     } else {
                         anyhow::bail!("Job results channel closed, this is unexpected!");
                     }
@@ -305,23 +514,27 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
     }
 
     /// Reads through scheduled jobs in timestamp ascending order and starts any
-    /// that are allowed by our concurrency limit and the jobs' scheduled
-    /// time.
+    /// that are allowed by our concurrency limits and the jobs' scheduled
+    /// time. Ready jobs whose priority lane or function is at its concurrency
+    /// limit are passed over so that they don't block jobs behind them.
     ///
-    /// Returns the time at which the next job in the queue will be ready to
-    /// run. If the scheduler is behind, the returned time may be in the
-    /// past. Returns None if all jobs are finished or running.
+    /// Returns the time at which the next job in the queue that isn't passed
+    /// over will be ready to run. If the scheduler is behind, the returned time
+    /// may be in the past. Returns None if all jobs are finished, running or
+    /// passed over. Passed over jobs can only start once a running job
+    /// finishes, which wakes the executor to look at them again.
     async fn query_and_start_jobs(
         &self,
         tx: &mut Transaction<RT>,
-        running_job_ids: &mut HashSet<ResolvedDocumentId>,
+        running_jobs: &mut RunningJobs,
         job_finished_tx: &mpsc::Sender<ResolvedDocumentId>,
     ) -> anyhow::Result<Option<Timestamp>> {
         let now = self.rt.generate_timestamp()?;
+        let mut num_throttled = 0;
         let mut job_stream = self.stream_jobs_to_run(tx);
         while let Some(job) = job_stream.try_next().await? {
             let (job_id, job) = job.clone().into_id_and_value();
-            if running_job_ids.contains(&job_id) {
+            if running_jobs.contains(&job_id) {
                 continue;
             }
             let next_ts = job
@@ -331,12 +544,26 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
             // caught up, we can sleep until the timestamp. If we're behind and
             // at our concurrency limit, we can use the timestamp to log how far
             // behind we get.
-            if next_ts > now || running_job_ids.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
+            if next_ts > now || running_jobs.len() == *SCHEDULED_JOB_EXECUTION_PARALLELISM {
                 return Ok(Some(next_ts));
             }
+            if let Some(reason) = running_jobs.throttle_reason(job_id, &job) {
+                metrics::log_scheduled_job_throttled(job.priority, reason);
+                num_throttled += 1;
+                if num_throttled >= *SCHEDULED_JOB_MAX_THROTTLED_PER_POLL {
+                    // The jobs behind these are read again when a running job
+                    // finishes.
+                    return Ok(None);
+                }
+                continue;
+            }
+            metrics::log_scheduled_job_start_delay(job.priority, now - next_ts);
 
+            running_jobs.insert(job_id, &job);
             let context = self.context.clone();
             let tx = job_finished_tx.clone();
+            let num_running_jobs = self.num_running_jobs.clone();
+            num_running_jobs.fetch_add(1, Ordering::SeqCst);
 
             let root = self
                 .rt
@@ -345,13 +572,12 @@ impl<RT: Runtime> ScheduledJobExecutor<RT> {
                 "spawn_scheduled_job",
                 async move {
                     let result = context.execute_job(job, job_id).await;
+                    num_running_jobs.fetch_sub(1, Ordering::SeqCst);
                     let _ = tx.send(result).await;
                 }
                 .in_span(root),
             );
 
-            running_job_ids.insert(job_id);
-
             // We might have hit the concurrency limit by adding the new job, so
             // we could check and break immediately if we have.
             // However we want to know the time the next job in the
@@ -483,6 +709,15 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                 self.database
                     .commit_with_write_source(tx, "scheduled_job_analyze_failure")
                     .await?;
+                self.record_execution(
+                    &job,
+                    job_id,
+                    path.component.clone(),
+                    self.rt.unix_timestamp(),
+                    Some(error.user_facing_message()),
+                    FunctionExecutionUsage::default(),
+                )
+                .await;
                 // NOTE: We didn't actually run anything, so we are creating a request context
                 // just report the error.
                 let context = ExecutionContext::new(request_id, &caller);
@@ -527,6 +762,15 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                 self.database
                     .commit_with_write_source(tx, "scheduled_job_bad_udf")
                     .await?;
+                self.record_execution(
+                    &job,
+                    job_id,
+                    path.component.clone(),
+                    self.rt.unix_timestamp(),
+                    Some(message.clone()),
+                    FunctionExecutionUsage::default(),
+                )
+                .await;
                 // NOTE: We didn't actually run anything, so we are creating a request context
                 // just report the error.
                 let context = ExecutionContext::new(request_id, &caller);
@@ -585,6 +829,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         usage_tracker: FunctionUsageTracker,
     ) -> anyhow::Result<()> {
         let start = self.rt.monotonic_now();
+        let start_ts = self.rt.unix_timestamp();
         let context = ExecutionContext::new(request_id, &caller);
         let identity = tx.inert_identity();
         let namespace = tx.table_mapping().tablet_namespace(job_id.tablet_id)?;
@@ -623,6 +868,7 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
 
         let stats = tx.take_stats();
         let execution_time = start.elapsed();
+        let index_ranges = IndexRangesRead::capture_if_slow(execution_time, tx.reads().read_set());
 
         if outcome.result.is_ok() {
             SchedulerModel::new(&mut tx, namespace)
@@ -663,9 +909,19 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                 .commit_with_write_source(tx, "scheduled_job_mutation_error")
                 .await?;
         }
+        self.record_execution(
+            &job,
+            job_id,
+            path.component,
+            start_ts,
+            outcome.result.as_ref().err().map(|e| e.to_string()),
+            job_execution_usage(usage_tracker.clone(), None),
+        )
+        .await;
         self.function_log.log_mutation(
             outcome,
             stats,
+            index_ranges,
             execution_time,
             caller,
             usage_tracker,
@@ -703,9 +959,10 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     .await?;
 
                 // Execute the action
+                let start = self.rt.unix_timestamp();
                 let context = ExecutionContext::new(request_id, &caller);
                 let path = CanonicalizedComponentFunctionPath {
-                    component: component_path,
+                    component: component_path.clone(),
                     udf_path: job.udf_path.clone(),
                 };
                 let completion = self
@@ -738,13 +995,38 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                     report_error(&mut err);
                     self.rt.wait(delay).await;
                 }
+                self.record_execution(
+                    &updated_job,
+                    job_id,
+                    component_path,
+                    start,
+                    completion
+                        .outcome
+                        .result
+                        .as_ref()
+                        .err()
+                        .map(|e| e.to_string()),
+                    job_execution_usage(usage_tracker.clone(), Some(completion.memory_in_mb)),
+                )
+                .await;
                 self.function_log.log_action(completion, usage_tracker);
             },
             ScheduledJobState::InProgress => {
                 // This case can happen if there is a system error while executing
                 // the action or if backend exits after executing the action but
-                // before updating the state. Since we execute actions at most once,
-                // complete this job and log the error.
+                // before updating the state. Workflows resume from their last
+                // checkpointed step, so it's safe to restart them.
+                if self
+                    .maybe_restart_workflow(&mut tx, namespace, job_id, &job)
+                    .await?
+                {
+                    self.database
+                        .commit_with_write_source(tx, "scheduled_job_restart_workflow")
+                        .await?;
+                    return Ok(());
+                }
+                // Otherwise, since we execute actions at most once, complete this
+                // job and log the error.
                 let message = "Transient error while executing action".to_string();
                 SchedulerModel::new(&mut tx, namespace)
                     .complete(job_id, ScheduledJobState::Failed(message.clone()))
@@ -752,6 +1034,15 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
                 self.database
                     .commit_with_write_source(tx, "scheduled_job_action_error")
                     .await?;
+                self.record_execution(
+                    &job,
+                    job_id,
+                    component_path.clone(),
+                    self.rt.unix_timestamp(),
+                    Some(message.clone()),
+                    FunctionExecutionUsage::default(),
+                )
+                .await;
                 // TODO: This is wrong. We don't know the executionId the action has been
                 // started with. We generate a new executionId and use it to log the failures. I
                 // guess the correct behavior here is to store the executionId in the state so
@@ -782,6 +1073,36 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         Ok(())
     }
 
+    /// Records a run of `job` that started at `start` and has just finished,
+    /// failing with `error` if it's set.
+    async fn record_execution(
+        &self,
+        job: &ScheduledJob,
+        job_id: ResolvedDocumentId,
+        component: ComponentPath,
+        start: UnixTimestamp,
+        error: Option<String>,
+        usage: FunctionExecutionUsage,
+    ) {
+        let status = match error {
+            Some(_) => FunctionExecutionStatus::Failure,
+            None => FunctionExecutionStatus::Success,
+        };
+        let record = JobExecutionRecord {
+            kind: JobKind::Scheduled,
+            job: DeveloperDocumentId::from(job_id).encode(),
+            component,
+            udf_path: job.udf_path.clone(),
+            start,
+            end: self.rt.unix_timestamp(),
+            status,
+            error,
+            attempts: job.attempts,
+            usage,
+        };
+        record_job_execution(&self.database, record).await;
+    }
+
     // Creates a new transaction and verifies the job state matches the given one.
     async fn new_transaction_for_job_state(
         &self,
@@ -821,6 +1142,17 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
         }
         let namespace = tx.table_mapping().tablet_namespace(job_id.tablet_id)?;
 
+        if let ScheduledJobState::Failed(_) = job_state
+            && self
+                .maybe_restart_workflow(&mut tx, namespace, job_id, expected_state)
+                .await?
+        {
+            self.database
+                .commit_with_write_source(tx, "scheduled_job_restart_workflow")
+                .await?;
+            return Ok(());
+        }
+
         // Remove from the scheduled jobs table
         SchedulerModel::new(&mut tx, namespace)
             .complete(job_id, job_state)
@@ -830,6 +1162,43 @@ impl<RT: Runtime> ScheduledJobContext<RT> {
             .await?;
         Ok(())
     }
+
+    /// Workflows are scheduled actions that have started at least one step,
+    /// whether or not it completed. Rather than failing a workflow when an
+    /// attempt fails or gets interrupted, put it back in the queue (with
+    /// backoff) so the next attempt resumes after its last completed step.
+    /// Returns false if the job isn't a workflow or has run out of attempts.
+    async fn maybe_restart_workflow(
+        &self,
+        tx: &mut Transaction<RT>,
+        namespace: TableNamespace,
+        job_id: ResolvedDocumentId,
+        job: &ScheduledJob,
+    ) -> anyhow::Result<bool> {
+        if job.attempts + 1 >= *WORKFLOW_MAX_ATTEMPTS
+            || !WorkflowModel::new(tx, namespace)
+                .is_declared(job_id.into())
+                .await?
+        {
+            return Ok(false);
+        }
+        let delay = WORKFLOW_RESTART_INITIAL_BACKOFF
+            .saturating_mul(1 << job.attempts.min(16))
+            .min(*SCHEDULED_JOB_MAX_BACKOFF);
+        let mut restarted_job = job.clone();
+        restarted_job.state = ScheduledJobState::Pending;
+        restarted_job.next_ts = Some(self.rt.generate_timestamp()?.add(delay)?);
+        restarted_job.attempts += 1;
+        tracing::info!(
+            "Restarting workflow {job_id} (attempt {}) in {delay:?}",
+            restarted_job.attempts
+        );
+        SchedulerModel::new(tx, namespace)
+            .replace(job_id, restarted_job)
+            .await?;
+        metrics::log_workflow_restarted();
+        Ok(true)
+    }
 }
 
 pub struct ScheduledJobGarbageCollector<RT: Runtime> {
@@ -902,9 +1271,15 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
                     "Garbage collecting {} finished scheduled jobs",
                     jobs_to_delete.len()
                 );
-                let mut model = SchedulerModel::new(&mut tx, namespace);
                 for job_id in jobs_to_delete {
-                    model.delete(job_id).await?;
+                    SchedulerModel::new(&mut tx, namespace)
+                        .delete(job_id)
+                        .await?;
+                    // Checkpoints of finished workflows are kept around for as
+                    // long as their job for debugging.
+                    WorkflowModel::new(&mut tx, namespace)
+                        .delete_steps(job_id.into())
+                        .await?;
                 }
                 self.database
                     .commit_with_write_source(tx, "scheduled_job_gc")
@@ -929,3 +1304,69 @@ impl<RT: Runtime> ScheduledJobGarbageCollector<RT> {
         }
     }
 }
+
+/// Writes the jobs of batches scheduled with `SchedulerModel::schedule_batch`
+/// to the scheduled jobs table, one chunk per transaction.
+pub struct ScheduledJobBatchExpander<RT: Runtime> {
+    rt: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> ScheduledJobBatchExpander<RT> {
+    pub fn start(rt: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let expander = Self { rt, database };
+        async move {
+            let mut backoff =
+                Backoff::new(*SCHEDULED_JOB_INITIAL_BACKOFF, *SCHEDULED_JOB_MAX_BACKOFF);
+            while let Err(mut e) = expander.run(&mut backoff).await {
+                let delay = expander.rt.with_rng(|rng| backoff.fail(rng));
+                tracing::error!("Scheduled job batch expander failed, sleeping {delay:?}");
+                // Only report OCCs that happen repeatedly
+                if !e.is_occ() || (backoff.failures() as usize) > *UDF_EXECUTOR_OCC_MAX_RETRIES {
+                    report_error(&mut e);
+                }
+                expander.rt.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            // Batches are scheduled into the namespace of the component that
+            // scheduled them.
+            let namespaces: Vec<_> = tx
+                .table_mapping()
+                .iter()
+                .filter(|(_, _, _, name)| **name == *SCHEDULED_JOB_BATCHES_TABLE)
+                .map(|(_, namespace, ..)| namespace)
+                .collect();
+            let mut next_chunk = None;
+            for namespace in namespaces {
+                if let Some(chunk) = ScheduledJobBatchModel::new(&mut tx, namespace)
+                    .next_chunk()
+                    .await?
+                {
+                    next_chunk = Some((namespace, chunk));
+                    break;
+                }
+            }
+            if let Some((namespace, chunk)) = next_chunk {
+                let num_jobs = SchedulerModel::new(&mut tx, namespace)
+                    .expand_batch_chunk(chunk)
+                    .await?;
+                self.database
+                    .commit_with_write_source(tx, "scheduled_job_batch_expand")
+                    .await?;
+                tracing::debug!("Scheduled {num_jobs} jobs from a batch");
+                metrics::log_scheduled_job_batch_expanded(num_jobs);
+                continue;
+            }
+
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            subscription.wait_for_invalidation().await;
+            backoff.reset();
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    execution_context::{
+        ExecutionContext,
+        ExecutionId,
+    },
+    knobs::{
+        QUEUE_DISPATCH_BATCH_SIZE,
+        SCHEDULED_JOB_INITIAL_BACKOFF,
+        SCHEDULED_JOB_MAX_BACKOFF,
+        UDF_EXECUTOR_OCC_MAX_RETRIES,
+    },
+    runtime::Runtime,
+    RequestId,
+};
+use database::Database;
+use errors::ErrorMetadataAnyhowExt;
+use futures::{
+    future::Either,
+    select_biased,
+    Future,
+    FutureExt,
+};
+use keybroker::Identity;
+use model::queues::{
+    QueueModel,
+    QUEUE_MESSAGES_TABLE,
+};
+
+use super::metrics;
+
+/// Delivers queue messages to their consumer groups once they become visible,
+/// by scheduling runs of the groups' handlers. See `QueueModel`.
+pub struct QueueDispatcher<RT: Runtime> {
+    rt: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> QueueDispatcher<RT> {
+    pub fn start(rt: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let dispatcher = Self { rt, database };
+        async move {
+            let mut backoff =
+                Backoff::new(*SCHEDULED_JOB_INITIAL_BACKOFF, *SCHEDULED_JOB_MAX_BACKOFF);
+            while let Err(mut e) = dispatcher.run(&mut backoff).await {
+                let delay = dispatcher.rt.with_rng(|rng| backoff.fail(rng));
+                tracing::error!("Queue dispatcher failed, sleeping {delay:?}");
+                // Only report OCCs that happen repeatedly
+                if !e.is_occ() || (backoff.failures() as usize) > *UDF_EXECUTOR_OCC_MAX_RETRIES {
+                    report_error(&mut e);
+                }
+                dispatcher.rt.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let now = self.rt.generate_timestamp()?;
+            let namespaces: Vec<_> = tx
+                .table_mapping()
+                .iter()
+                .filter(|(_, _, _, name)| **name == *QUEUE_MESSAGES_TABLE)
+                .map(|(_, namespace, ..)| namespace)
+                .collect();
+            let mut num_processed = 0;
+            let mut next_visible_ts = None;
+            for namespace in namespaces {
+                let (messages, namespace_next_visible_ts) = QueueModel::new(&mut tx, namespace)
+                    .visible_messages(now, *QUEUE_DISPATCH_BATCH_SIZE - num_processed)
+                    .await?;
+                next_visible_ts = match (next_visible_ts, namespace_next_visible_ts) {
+                    (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                    (a, b) => a.or(b),
+                };
+                for message in messages {
+                    let context = ExecutionContext::new_from_parts(
+                        RequestId::new(),
+                        ExecutionId::new(),
+                        None,
+                        true,
+                        None,
+                    );
+                    let outcome = QueueModel::new(&mut tx, namespace)
+                        .deliver(message, context)
+                        .await?;
+                    metrics::log_queue_message_outcome(outcome);
+                    num_processed += 1;
+                }
+                if num_processed >= *QUEUE_DISPATCH_BATCH_SIZE {
+                    break;
+                }
+            }
+            if num_processed > 0 {
+                self.database
+                    .commit_with_write_source(tx, "queue_dispatch")
+                    .await?;
+                tracing::debug!("Dispatched {num_processed} queue messages");
+                continue;
+            }
+
+            let next_message_future = if let Some(next_visible_ts) = next_visible_ts {
+                Either::Left(self.rt.wait(next_visible_ts - now))
+            } else {
+                Either::Right(std::future::pending())
+            };
+            let token = tx.into_token()?;
+            let subscription = self.database.subscribe(token).await?;
+            select_biased! {
+                _ = next_message_future.fuse() => {
+                }
+                _ = subscription.wait_for_invalidation().fuse() => {
+                },
+            }
+            backoff.reset();
+        }
+    }
+}
@@ -8,6 +8,7 @@ use common::{
     schemas::DatabaseSchema,
 };
 use database::{
+    field_encryption::decrypt_document,
     Database,
     IndexModel,
     SchemaModel,
@@ -144,6 +145,10 @@ impl<RT: Runtime> SchemaWorker<RT> {
 
                 pin_mut!(stream);
                 while let Some((doc, _ts)) = stream.try_next().await? {
+                    let doc = match self.database.field_encryptor.get() {
+                        Some(field_encryptor) => decrypt_document(field_encryptor, doc)?,
+                        None => doc,
+                    };
                     let table_name = table_mapping.tablet_name(doc.id().tablet_id)?;
                     log_document_validated();
                     log_document_bytes(doc.size());
@@ -259,6 +264,12 @@ mod tests {
             search_indexes: btreemap! {},
             vector_indexes: btreemap! {},
             document_type: Some(DocumentSchema::Any),
+            encrypted_fields: Default::default(),
+            triggers: Default::default(),
+            track_updates: false,
+            soft_delete: None,
+            id_strategy: Default::default(),
+            sketches: Default::default(),
         };
         let db_schema = DatabaseSchema {
             tables: btreemap! { table_name.clone() => table_definition },
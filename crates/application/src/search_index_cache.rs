@@ -0,0 +1,310 @@
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    bootstrap_model::index::{
+        text_index::FragmentedTextSegment,
+        vector_index::FragmentedVectorSegment,
+        IndexConfig,
+    },
+    errors::report_error,
+    knobs::SEARCH_INDEX_PIN_REFRESH_INTERVAL,
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    Database,
+    IndexModel,
+    Transaction,
+};
+use futures::Future;
+use keybroker::Identity;
+use model::search_index_pins::SearchIndexPinsModel;
+use search::searcher::{
+    FragmentedTextStorageKeys,
+    SegmentCacheStatus,
+};
+use value::{
+    TableName,
+    TableNamespace,
+};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+/// Where the segments of one of a table's text or vector indexes are cached
+/// by the searcher.
+pub struct SearchIndexCacheStatus {
+    pub index_name: IndexName,
+    /// Whether the index is in `_search_index_pins`.
+    pub pinned: bool,
+    pub segments: Vec<SegmentCacheStatus>,
+}
+
+/// The current segments of an enabled text or vector index.
+#[derive(Clone)]
+enum IndexSegments {
+    Text(Vec<FragmentedTextSegment>),
+    Vector(Vec<FragmentedVectorSegment>),
+}
+
+impl IndexSegments {
+    fn from_config(config: &IndexConfig) -> anyhow::Result<Option<Self>> {
+        if !config.is_enabled() {
+            return Ok(None);
+        }
+        let segments = match config {
+            IndexConfig::Database { .. } => return Ok(None),
+            IndexConfig::Search { on_disk_state, .. } => {
+                IndexSegments::Text(on_disk_state.segments()?.clone())
+            },
+            IndexConfig::Vector { on_disk_state, .. } => {
+                IndexSegments::Vector(on_disk_state.segments()?.clone())
+            },
+        };
+        Ok(Some(segments))
+    }
+
+    /// The segments in `self` that aren't in `other`.
+    fn difference(&self, other: Option<&IndexSegments>) -> Option<IndexSegments> {
+        let difference = match (self, other) {
+            (IndexSegments::Text(segments), Some(IndexSegments::Text(other))) => {
+                IndexSegments::Text(
+                    segments
+                        .iter()
+                        .filter(|segment| !other.contains(segment))
+                        .cloned()
+                        .collect(),
+                )
+            },
+            (IndexSegments::Vector(segments), Some(IndexSegments::Vector(other))) => {
+                IndexSegments::Vector(
+                    segments
+                        .iter()
+                        .filter(|segment| !other.contains(segment))
+                        .cloned()
+                        .collect(),
+                )
+            },
+            _ => self.clone(),
+        };
+        Some(difference).filter(|segments| !segments.is_empty())
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            IndexSegments::Text(segments) => segments.is_empty(),
+            IndexSegments::Vector(segments) => segments.is_empty(),
+        }
+    }
+
+    async fn pin<RT: Runtime>(self, database: &Database<RT>) -> anyhow::Result<()> {
+        let storage = database.search_storage();
+        match self {
+            IndexSegments::Text(segments) => {
+                database
+                    .searcher
+                    .pin_text_segments(storage, text_storage_keys(segments))
+                    .await
+            },
+            IndexSegments::Vector(segments) => {
+                database
+                    .searcher
+                    .pin_vector_segments(storage, vector_segment_paths(segments)?)
+                    .await
+            },
+        }
+    }
+
+    async fn unpin<RT: Runtime>(self, database: &Database<RT>) -> anyhow::Result<()> {
+        let storage = database.search_storage();
+        match self {
+            IndexSegments::Text(segments) => {
+                database
+                    .searcher
+                    .unpin_text_segments(storage, text_storage_keys(segments))
+                    .await
+            },
+            IndexSegments::Vector(segments) => {
+                database
+                    .searcher
+                    .unpin_vector_segments(storage, vector_segment_paths(segments)?)
+                    .await
+            },
+        }
+    }
+
+    async fn cache_status<RT: Runtime>(
+        self,
+        database: &Database<RT>,
+    ) -> anyhow::Result<Vec<SegmentCacheStatus>> {
+        let storage = database.search_storage();
+        match self {
+            IndexSegments::Text(segments) => {
+                database
+                    .searcher
+                    .text_segment_cache_status(storage, text_storage_keys(segments))
+                    .await
+            },
+            IndexSegments::Vector(segments) => {
+                database
+                    .searcher
+                    .vector_segment_cache_status(storage, vector_segment_paths(segments)?)
+                    .await
+            },
+        }
+    }
+}
+
+fn text_storage_keys(segments: Vec<FragmentedTextSegment>) -> Vec<FragmentedTextStorageKeys> {
+    segments
+        .into_iter()
+        .map(FragmentedTextStorageKeys::from)
+        .collect()
+}
+
+fn vector_segment_paths(
+    segments: Vec<FragmentedVectorSegment>,
+) -> anyhow::Result<Vec<pb::searchlight::FragmentedVectorSegmentPaths>> {
+    segments
+        .into_iter()
+        .map(|segment| segment.to_paths_proto())
+        .collect()
+}
+
+/// The segments of an enabled text or vector index in the root component, or
+/// `None` if there isn't one with this name.
+fn enabled_index_segments<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    index_name: &IndexName,
+) -> anyhow::Result<Option<IndexSegments>> {
+    let Some(metadata) =
+        IndexModel::new(tx).enabled_index_metadata(TableNamespace::root_component(), index_name)?
+    else {
+        return Ok(None);
+    };
+    IndexSegments::from_config(&metadata.config)
+}
+
+/// Loads the current segments of a pinned index into the searcher right away,
+/// rather than waiting for the `SearchIndexPinWorker`.
+pub async fn prewarm_search_index<RT: Runtime>(
+    database: &Database<RT>,
+    index_name: &IndexName,
+) -> anyhow::Result<()> {
+    let mut tx = database.begin(Identity::system()).await?;
+    if let Some(segments) = enabled_index_segments(&mut tx, index_name)? {
+        segments.pin(database).await?;
+    }
+    Ok(())
+}
+
+/// Where the segments of a table's enabled text and vector indexes are cached.
+/// Never fetches segments.
+pub async fn search_index_cache_status<RT: Runtime>(
+    database: &Database<RT>,
+    identity: Identity,
+    table_name: &TableName,
+) -> anyhow::Result<Vec<SearchIndexCacheStatus>> {
+    let mut tx = database.begin(identity).await?;
+    let pinned: BTreeSet<IndexName> = SearchIndexPinsModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|pin| pin.into_value().index_name)
+        .collect();
+    let indexes = IndexModel::new(&mut tx)
+        .get_application_indexes(TableNamespace::root_component())
+        .await?;
+    let mut statuses = vec![];
+    for index in indexes {
+        let index = index.into_value();
+        if index.name.table() != table_name {
+            continue;
+        }
+        let Some(segments) = IndexSegments::from_config(&index.config)? else {
+            continue;
+        };
+        statuses.push(SearchIndexCacheStatus {
+            pinned: pinned.contains(&index.name),
+            index_name: index.name,
+            segments: segments.cache_status(database).await?,
+        });
+    }
+    Ok(statuses)
+}
+
+/// Keeps the segments of the indexes in `_search_index_pins` pinned in the
+/// searcher. Pins only last as long as the searcher's process, so this loads
+/// them again after a restart, and follows the indexes as they're flushed and
+/// compacted into new segments, unpinning the ones they no longer use.
+pub struct SearchIndexPinWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    pinned: BTreeMap<IndexName, IndexSegments>,
+}
+
+impl<RT: Runtime> SearchIndexPinWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let mut worker = Self {
+            runtime,
+            database,
+            pinned: BTreeMap::new(),
+        };
+        async move {
+            tracing::info!("Starting SearchIndexPinWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("SearchIndexPinWorker died"));
+                tracing::error!("Search index pin worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&mut self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            self.refresh_pins().await?;
+            backoff.reset();
+            self.runtime.wait(*SEARCH_INDEX_PIN_REFRESH_INTERVAL).await;
+        }
+    }
+
+    async fn refresh_pins(&mut self) -> anyhow::Result<()> {
+        let mut current = BTreeMap::new();
+        {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let pins = SearchIndexPinsModel::new(&mut tx).list().await?;
+            for pin in pins {
+                let index_name = pin.into_value().index_name;
+                if let Some(segments) = enabled_index_segments(&mut tx, &index_name)? {
+                    current.insert(index_name, segments);
+                }
+            }
+        }
+        // Pinning segments that are already pinned is cheap, and re-pins them
+        // if the searcher restarted.
+        for (index_name, segments) in &current {
+            if let Err(e) = segments.clone().pin(&self.database).await {
+                report_error(&mut e.context(format!("Failed to pin search index {index_name}")));
+            }
+        }
+        for (index_name, segments) in &self.pinned {
+            let Some(stale) = segments.difference(current.get(index_name)) else {
+                continue;
+            };
+            if let Err(e) = stale.unpin(&self.database).await {
+                report_error(&mut e.context(format!("Failed to unpin search index {index_name}")));
+            }
+        }
+        self.pinned = current;
+        Ok(())
+    }
+}
@@ -45,9 +45,17 @@ use common::{
         TRANSACTION_MAX_NUM_USER_WRITES,
         TRANSACTION_MAX_USER_WRITE_SIZE_BYTES,
     },
+    log_streaming::LogSender,
     pause::PauseClient,
-    runtime::Runtime,
-    schemas::DatabaseSchema,
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    schemas::{
+        validator::Validator,
+        DatabaseSchema,
+        DocumentSchema,
+    },
     types::{
         FieldName,
         MemberId,
@@ -101,6 +109,7 @@ use humansize::{
 use keybroker::Identity;
 use model::{
     deployment_audit_log::{
+        audit_log_actor,
         types::DeploymentAuditLogEvent,
         DeploymentAuditLogModel,
     },
@@ -111,10 +120,12 @@ use model::{
     },
     snapshot_imports::{
         types::{
+            CsvColumnType,
             ImportFormat,
             ImportMode,
             ImportState,
             ImportTableCheckpoint,
+            ImportTableValidation,
             SnapshotImport,
         },
         SnapshotImportModel,
@@ -154,11 +165,17 @@ use usage_tracking::{
 };
 use value::{
     id_v6::DeveloperDocumentId,
-    sha256::Sha256Digest,
+    sha256::{
+        Sha256,
+        Sha256Digest,
+    },
     val,
     ConvexObject,
     ConvexValue,
     IdentifierFieldName,
+    InternalId,
+    NamespacedTableMapping,
+    NamespacedVirtualTableMapping,
     ResolvedDocumentId,
     Size,
     TableMapping,
@@ -169,7 +186,10 @@ use value::{
 };
 
 use crate::{
-    export_worker::FileStorageZipMetadata,
+    export_worker::{
+        FileStorageObjectManifestEntry,
+        FileStorageZipMetadata,
+    },
     metrics::{
         log_snapshot_import_age,
         log_worker_starting,
@@ -192,8 +212,11 @@ pub struct SnapshotImportWorker<RT: Runtime> {
     runtime: RT,
     database: Database<RT>,
     snapshot_imports_storage: Arc<dyn Storage>,
+    // Where ZIP exports that wrote files as separate objects keep them.
+    exports_storage: Arc<dyn Storage>,
     file_storage: FileStorage<RT>,
     usage_tracking: UsageCounter,
+    log_sender: Arc<dyn LogSender>,
     backoff: Backoff,
     pause_client: PauseClient,
 }
@@ -204,16 +227,20 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
         runtime: RT,
         database: Database<RT>,
         snapshot_imports_storage: Arc<dyn Storage>,
+        exports_storage: Arc<dyn Storage>,
         file_storage: FileStorage<RT>,
         usage_tracking: UsageCounter,
+        log_sender: Arc<dyn LogSender>,
         pause_client: PauseClient,
     ) -> impl Future<Output = ()> + Send {
         let mut worker = Self {
             runtime,
             database,
             snapshot_imports_storage,
+            exports_storage,
             file_storage,
             usage_tracking,
+            log_sender,
             pause_client,
             backoff: Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF),
         };
@@ -278,7 +305,7 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
             },
         }
         match self.info_message_for_import(snapshot_import).await {
-            Ok((info_message, require_manual_confirmation, new_checkpoints)) => {
+            Ok((info_message, require_manual_confirmation, new_checkpoints, validation)) => {
                 self.database
                     .execute_with_overloaded_retries(
                         Identity::system(),
@@ -294,6 +321,7 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
                                         info_message.clone(),
                                         require_manual_confirmation,
                                         new_checkpoints.clone(),
+                                        validation.clone(),
                                     )
                                     .await?;
                                 Ok(())
@@ -333,19 +361,56 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
     }
 
     /// Parse the uploaded import file, compare it to existing data, and return
-    /// a message to display about the import before it begins.
+    /// a message to display about the import before it begins. For a dry run,
+    /// also validate every row.
     async fn info_message_for_import(
         &self,
         snapshot_import: ParsedDocument<SnapshotImport>,
-    ) -> anyhow::Result<(String, bool, Vec<ImportTableCheckpoint>)> {
+    ) -> anyhow::Result<(
+        String,
+        bool,
+        Vec<ImportTableCheckpoint>,
+        Option<Vec<ImportTableValidation>>,
+    )> {
         let mut message_lines = Vec::new();
-        let (content_confirmation_messages, require_manual_confirmation, new_checkpoints) =
+        let remap_ids = snapshot_import.remap_ids;
+        let validation = if snapshot_import.dry_run {
+            Some(self.validate_import(&snapshot_import).await?)
+        } else {
+            None
+        };
+        let (content_confirmation_messages, mut require_manual_confirmation, new_checkpoints) =
             self.messages_to_confirm_replace(snapshot_import).await?;
         message_lines.extend(content_confirmation_messages);
         // Consider adding confirmation messages about bandwidth usage.
         if !message_lines.is_empty() {
             message_lines.insert(0, format!("Import change summary:"))
         }
+        if let Some(validation) = &validation {
+            // Nothing is written until a dry run is explicitly confirmed.
+            require_manual_confirmation = true;
+            message_lines.push("Dry run validation:".to_string());
+            for table in validation {
+                message_lines.push(format!(
+                    "{}: {} rows, {} type mismatches, {} id collisions, {} ingress",
+                    table.display_table_name,
+                    table.num_rows.separate_with_commas(),
+                    table.num_type_mismatches.separate_with_commas(),
+                    table.num_id_collisions.separate_with_commas(),
+                    (table.ingress_bytes as u64).format_size(BINARY),
+                ));
+                for error in &table.errors {
+                    message_lines.push(format!("  {error}"));
+                }
+            }
+        }
+        if remap_ids {
+            message_lines.push(
+                "Imported documents will get new ids, and references to them in `v.id` fields of \
+                 your schema will be updated."
+                    .to_string(),
+            );
+        }
         message_lines.push(format!(
             "Once the import has started, it will run in the background.\nInterrupting `npx \
              convex import` will not cancel it."
@@ -354,9 +419,100 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
             message_lines.join("\n"),
             require_manual_confirmation,
             new_checkpoints,
+            validation,
         ))
     }
 
+    /// Check every row that the import would write against the active schema
+    /// and the existing documents, without writing anything. Foreign keys are
+    /// checked against the deployment's current tables.
+    async fn validate_import(
+        &self,
+        snapshot_import: &ParsedDocument<SnapshotImport>,
+    ) -> anyhow::Result<Vec<ImportTableValidation>> {
+        let mode = snapshot_import.mode;
+        let (_, mut objects) = self.parse_import(snapshot_import.id()).await?;
+        let (schema, table_mapping, virtual_table_mapping) = {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let schema = SchemaModel::new(&mut tx, TableNamespace::by_component_TODO())
+                .get_by_state(SchemaState::Active)
+                .await?
+                .map(|(_, schema)| schema)
+                .filter(|schema| schema.schema_validation);
+            (
+                schema,
+                tx.table_mapping()
+                    .namespace(TableNamespace::by_component_TODO()),
+                tx.virtual_table_mapping()
+                    .namespace(TableNamespace::by_component_TODO()),
+            )
+        };
+        let mut generated_schemas = BTreeMap::new();
+        let mut validation = vec![];
+        let mut current_table: Option<DryRunTable> = None;
+        while let Some(unit) = objects.try_next().await? {
+            match unit {
+                ImportUnit::GeneratedSchema(table_name, generated_schema) => {
+                    generated_schemas.insert(table_name, generated_schema);
+                },
+                ImportUnit::NewTable(table_name) => {
+                    if let Some(table) = current_table.take() {
+                        validation.push(table.finish(&self.database).await?);
+                    }
+                    // The _tables table only says which table numbers to use.
+                    if table_name == *TABLES_TABLE {
+                        continue;
+                    }
+                    let is_user_table = !table_name.is_system();
+                    let document_schema = schema
+                        .as_ref()
+                        .filter(|_| is_user_table)
+                        .and_then(|schema| schema.tables.get(&table_name))
+                        .and_then(|table_schema| table_schema.document_type.clone());
+                    let existing_table = match mode {
+                        // Remapped ids can't collide with existing documents.
+                        _ if snapshot_import.remap_ids => None,
+                        ImportMode::Append if is_user_table => {
+                            table_mapping.id_and_number_if_exists(&table_name)
+                        },
+                        // The existing documents are deleted or there aren't any.
+                        _ => None,
+                    };
+                    current_table = Some(DryRunTable::new(
+                        table_name,
+                        document_schema,
+                        existing_table,
+                    ));
+                },
+                ImportUnit::Object(exported_value) => {
+                    let Some(table) = &mut current_table else {
+                        continue;
+                    };
+                    let generated_schema =
+                        generated_schemas.get_mut(&table.validation.display_table_name);
+                    table.validate_row(
+                        exported_value,
+                        generated_schema,
+                        &table_mapping,
+                        &virtual_table_mapping,
+                    );
+                    if table.ids_to_check.len() >= DRY_RUN_ID_BATCH_SIZE {
+                        table.check_existing_ids(&self.database).await?;
+                    }
+                },
+                ImportUnit::StorageFileChunk(_, chunk) => {
+                    if let Some(table) = &mut current_table {
+                        table.validation.ingress_bytes += chunk.len() as i64;
+                    }
+                },
+            }
+        }
+        if let Some(table) = current_table {
+            validation.push(table.finish(&self.database).await?);
+        }
+        Ok(validation)
+    }
+
     async fn messages_to_confirm_replace(
         &self,
         snapshot_import: ParsedDocument<SnapshotImport>,
@@ -650,6 +806,12 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
 
         let usage = FunctionUsageTracker::new();
 
+        let id_remapping = if snapshot_import.remap_ids {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            Some(IdRemapping::new(&mut tx, snapshot_import.id()).await?)
+        } else {
+            None
+        };
         let (table_mapping_for_import, total_documents_imported) = import_objects(
             &self.database,
             &self.file_storage,
@@ -658,6 +820,7 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
             objects,
             usage.clone(),
             Some(snapshot_import.id()),
+            id_remapping,
         )
         .await?;
 
@@ -680,6 +843,7 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
         let (ts, _documents_deleted) = finalize_import(
             &self.database,
             &self.usage_tracking,
+            self.log_sender.as_ref(),
             Identity::system(),
             snapshot_import.member_id,
             initial_schemas,
@@ -717,7 +881,8 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
             let object_key = object_key.clone();
             async move { self.read_snapshot_import(&object_key).await }
         };
-        let objects = parse_objects(format.clone(), body_stream).boxed();
+        let objects =
+            parse_objects(format.clone(), body_stream, self.exports_storage.clone()).boxed();
 
         // Remapping could be more extensive here, it's just relatively simple to handle
         // optional types. We do remapping after parsing rather than during parsing
@@ -728,7 +893,7 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
         let initial_schemas = schemas_for_import(&mut tx).await?;
 
         let objects = match format {
-            ImportFormat::Csv(table_name) => {
+            ImportFormat::Csv(table_name, _) => {
                 remap_empty_string_by_schema(table_name, &mut tx, objects).await?
             },
             _ => objects,
@@ -746,6 +911,284 @@ impl<RT: Runtime> SnapshotImportWorker<RT> {
     }
 }
 
+/// How many of the problems found in each table a dry run describes.
+const MAX_DRY_RUN_ERRORS: usize = 10;
+/// How many `_id`s a dry run looks up in each transaction.
+const DRY_RUN_ID_BATCH_SIZE: usize = 1000;
+
+/// The validation of one table's rows during a dry run.
+struct DryRunTable {
+    validation: ImportTableValidation,
+    document_schema: Option<DocumentSchema>,
+    // The active table that the rows would be appended to.
+    existing_table: Option<TabletIdAndTableNumber>,
+    seen_ids: BTreeSet<DeveloperDocumentId>,
+    // Row numbers and `_id`s still to be looked up in `existing_table`.
+    ids_to_check: Vec<(usize, ResolvedDocumentId)>,
+}
+
+impl DryRunTable {
+    fn new(
+        display_table_name: TableName,
+        document_schema: Option<DocumentSchema>,
+        existing_table: Option<TabletIdAndTableNumber>,
+    ) -> Self {
+        Self {
+            validation: ImportTableValidation {
+                display_table_name,
+                num_rows: 0,
+                num_type_mismatches: 0,
+                num_id_collisions: 0,
+                ingress_bytes: 0,
+                errors: vec![],
+            },
+            document_schema,
+            existing_table,
+            seen_ids: BTreeSet::new(),
+            ids_to_check: vec![],
+        }
+    }
+
+    fn add_error(&mut self, error: String) {
+        if self.validation.errors.len() < MAX_DRY_RUN_ERRORS {
+            self.validation.errors.push(error);
+        }
+    }
+
+    fn validate_row(
+        &mut self,
+        exported_value: JsonValue,
+        generated_schema: Option<&mut GeneratedSchema<ProdConfigWithOptionalFields>>,
+        table_mapping: &NamespacedTableMapping,
+        virtual_table_mapping: &NamespacedVirtualTableMapping,
+    ) {
+        self.validation.num_rows += 1;
+        let row_number = self.validation.num_rows as usize;
+        let mut generated_schema = generated_schema;
+        let object = match GeneratedSchema::<ProdConfigWithOptionalFields>::apply(
+            &mut generated_schema,
+            exported_value,
+        ) {
+            Ok(ConvexValue::Object(object)) => object,
+            Ok(_) => {
+                self.validation.num_type_mismatches += 1;
+                self.add_error(ImportError::NotAnObject(row_number).to_string());
+                return;
+            },
+            Err(e) => {
+                self.validation.num_type_mismatches += 1;
+                self.add_error(ImportError::InvalidConvexValue(row_number, e).to_string());
+                return;
+            },
+        };
+        self.validation.ingress_bytes += object.size() as i64;
+        if let Some(document_schema) = &self.document_schema
+            && let Err(e) =
+                document_schema.check_value(&object, table_mapping, virtual_table_mapping)
+        {
+            self.validation.num_type_mismatches += 1;
+            self.add_error(format!("Row {row_number} doesn't match the schema: {e}"));
+        }
+        let Some(ConvexValue::String(id)) = object.get(&**ID_FIELD) else {
+            return;
+        };
+        let Ok(id) = DeveloperDocumentId::decode(id) else {
+            self.validation.num_type_mismatches += 1;
+            self.add_error(format!("Row {row_number} has an invalid _id {id}"));
+            return;
+        };
+        if !self.seen_ids.insert(id) {
+            self.validation.num_id_collisions += 1;
+            self.add_error(format!(
+                "Row {row_number} has the same _id as an earlier row: {}",
+                id.encode()
+            ));
+        } else if let Some(existing_table) = self.existing_table
+            && id.table() == existing_table.table_number
+        {
+            self.ids_to_check.push((
+                row_number,
+                ResolvedDocumentId::new(existing_table.tablet_id, id),
+            ));
+        }
+    }
+
+    async fn check_existing_ids<RT: Runtime>(
+        &mut self,
+        database: &Database<RT>,
+    ) -> anyhow::Result<()> {
+        let mut tx = database.begin(Identity::system()).await?;
+        for (row_number, id) in std::mem::take(&mut self.ids_to_check) {
+            if tx.get(id).await?.is_some() {
+                self.validation.num_id_collisions += 1;
+                self.add_error(format!(
+                    "Row {row_number} has the _id of an existing document: {}",
+                    id.developer_id.encode()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish<RT: Runtime>(
+        mut self,
+        database: &Database<RT>,
+    ) -> anyhow::Result<ImportTableValidation> {
+        self.check_existing_ids(database).await?;
+        Ok(self.validation)
+    }
+}
+
+/// Picks new ids for imported documents, so that data exported from another
+/// deployment doesn't collide with the documents in this one. `_id` fields
+/// are always remapped, and so are the fields that the active schema declares
+/// as `v.id`, when they reference a table in the import.
+struct IdRemapping {
+    // The import's id, which salts the new ids so that importing the same
+    // data twice picks different ones, while a resumed import picks the same.
+    salt: String,
+    // Table numbers in the import to the numbers of the tables they're
+    // imported into.
+    table_numbers: BTreeMap<TableNumber, TableNumber>,
+    // The document validators of the active schema, by table.
+    document_validators: BTreeMap<TableName, Validator>,
+}
+
+impl IdRemapping {
+    async fn new<RT: Runtime>(
+        tx: &mut Transaction<RT>,
+        import_id: ResolvedDocumentId,
+    ) -> anyhow::Result<Self> {
+        let mut document_validators = BTreeMap::new();
+        if let Some((_, schema)) = SchemaModel::new(tx, TableNamespace::by_component_TODO())
+            .get_by_state(SchemaState::Active)
+            .await?
+        {
+            for (table_name, table_definition) in schema.tables {
+                if let Some(DocumentSchema::Union(object_validators)) =
+                    table_definition.document_type
+                {
+                    let validator = Validator::Union(
+                        object_validators
+                            .into_iter()
+                            .map(|validator| Validator::Object(validator.filter_system_fields()))
+                            .collect(),
+                    );
+                    document_validators.insert(table_name, validator);
+                }
+            }
+        }
+        Ok(Self {
+            salt: import_id.developer_id.encode(),
+            table_numbers: BTreeMap::new(),
+            document_validators,
+        })
+    }
+
+    fn add_table(&mut self, import_table_number: TableNumber, table_number: TableNumber) {
+        self.table_numbers.insert(import_table_number, table_number);
+    }
+
+    /// The new id for `id`, or None if it isn't in a table of the import.
+    fn remap_id(&self, id: DeveloperDocumentId) -> Option<DeveloperDocumentId> {
+        let table_number = self.table_numbers.get(&id.table())?;
+        let internal_id = id.internal_id();
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(&internal_id);
+        let digest = hasher.finalize();
+        // Keep the day that the original id was created on in its last two
+        // bytes.
+        let mut bytes = [0u8; 16];
+        bytes[..14].copy_from_slice(&digest[..14]);
+        bytes[14..].copy_from_slice(&internal_id[14..]);
+        Some(DeveloperDocumentId::new(*table_number, InternalId(bytes)))
+    }
+
+    fn remap_id_string(&self, id: &mut String) {
+        if let Ok(developer_id) = DeveloperDocumentId::decode(id)
+            && let Some(new_id) = self.remap_id(developer_id)
+        {
+            *id = new_id.encode();
+        }
+    }
+
+    fn remap_document(&self, table_name: &TableName, document: &mut JsonValue) {
+        let Some(object) = document.as_object_mut() else {
+            return;
+        };
+        if let Some(JsonValue::String(id)) = object.get_mut(&**ID_FIELD) {
+            self.remap_id_string(id);
+        }
+        if let Some(validator) = self.document_validators.get(table_name) {
+            self.remap_value(document, &[validator]);
+        }
+    }
+
+    /// Remap the ids in `value`, which any of `validators` may describe.
+    fn remap_value(&self, value: &mut JsonValue, validators: &[&Validator]) {
+        fn flatten<'a>(validator: &'a Validator, flattened: &mut Vec<&'a Validator>) {
+            match validator {
+                Validator::Union(validators) => {
+                    for validator in validators {
+                        flatten(validator, flattened);
+                    }
+                },
+                validator => flattened.push(validator),
+            }
+        }
+        let mut flattened = vec![];
+        for validator in validators {
+            flatten(validator, &mut flattened);
+        }
+        match value {
+            JsonValue::String(id) => {
+                if flattened
+                    .iter()
+                    .any(|validator| matches!(validator, Validator::Id(_)))
+                {
+                    self.remap_id_string(id);
+                }
+            },
+            JsonValue::Array(elements) => {
+                let element_validators: Vec<_> = flattened
+                    .iter()
+                    .filter_map(|validator| match validator {
+                        Validator::Array(element_validator) => Some(&**element_validator),
+                        _ => None,
+                    })
+                    .collect();
+                if element_validators.is_empty() {
+                    return;
+                }
+                for element in elements {
+                    self.remap_value(element, &element_validators);
+                }
+            },
+            JsonValue::Object(fields) => {
+                for (field_name, field_value) in fields.iter_mut() {
+                    let field_validators: Vec<_> = flattened
+                        .iter()
+                        .filter_map(|validator| match validator {
+                            Validator::Object(object_validator) => object_validator
+                                .0
+                                .iter()
+                                .find(|(name, _)| ***name == **field_name)
+                                .map(|(_, field_validator)| field_validator.validator()),
+                            Validator::Record(_, value_validator) => Some(&**value_validator),
+                            _ => None,
+                        })
+                        .collect();
+                    if !field_validators.is_empty() {
+                        self.remap_value(field_value, &field_validators);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
 #[derive(AsRefStr, Debug, Error)]
 pub enum ImportError {
     #[error("Only deployment admins can import new tables")]
@@ -775,6 +1218,12 @@ pub enum ImportError {
     #[error("CSV row {0} doesn't have all of the fields in the header")]
     CsvRowMissingFields(usize),
 
+    #[error("CSV column type given for {0:?}, which isn't in the header")]
+    CsvUnknownColumn(String),
+
+    #[error("CSV row {0} column {1:?} isn't a valid {2}: {3}")]
+    CsvInvalidCell(usize, String, CsvColumnType, anyhow::Error),
+
     #[error("Row {0} wasn't valid JSON: {1}")]
     JsonInvalidRow(usize, serde_json::Error),
 
@@ -833,14 +1282,18 @@ fn map_zip_error(e: ZipError) -> anyhow::Error {
 /// stream_body returns the file as streamed bytes. stream_body() can be called
 /// multiple times to read the file multiple times, for cases where the file
 /// must be read out of order, e.g. because the _tables table must be imported
-/// first.
+/// first. exports_storage is where a ZIP export's _storage/manifest.jsonl
+/// points, if its files were written as separate objects.
 #[try_stream(ok = ImportUnit, error = anyhow::Error)]
-async fn parse_objects<'a, Fut>(format: ImportFormat, stream_body: impl Fn() -> Fut + 'a)
-where
+async fn parse_objects<'a, Fut>(
+    format: ImportFormat,
+    stream_body: impl Fn() -> Fut + 'a,
+    exports_storage: Arc<dyn Storage>,
+) where
     Fut: Future<Output = anyhow::Result<StorageObjectReader>> + 'a,
 {
     match format {
-        ImportFormat::Csv(table_name) => {
+        ImportFormat::Csv(table_name, column_types) => {
             let reader = stream_body().await?;
             yield ImportUnit::NewTable(table_name);
             let mut reader = csv_async::AsyncReader::from_reader(reader);
@@ -859,20 +1312,45 @@ where
                     })
                     .collect::<anyhow::Result<Vec<_>>>()?
             };
+            for column in column_types.keys() {
+                if !field_names
+                    .iter()
+                    .any(|field_name| field_name.to_string() == *column)
+                {
+                    anyhow::bail!(ImportError::CsvUnknownColumn(column.clone()));
+                }
+            }
+            let field_types: Vec<_> = field_names
+                .iter()
+                .map(|field_name| column_types.get(&field_name.to_string()).copied())
+                .collect();
             let mut enumerate_rows = reader.records().enumerate();
             while let Some((i, row_r)) = enumerate_rows.next().await {
                 let lineno = i + 1;
-                let parsed_row = row_r
-                    .map_err(|e| ImportError::CsvInvalidRow(lineno, e))?
-                    .iter()
-                    .map(parse_csv_cell)
-                    .collect::<Vec<JsonValue>>();
+                let row = row_r.map_err(|e| ImportError::CsvInvalidRow(lineno, e))?;
                 let mut obj = BTreeMap::new();
-                if field_names.len() != parsed_row.len() {
+                if field_names.len() != row.len() {
                     anyhow::bail!(ImportError::CsvRowMissingFields(lineno));
                 }
-                for (field_name, value) in field_names.iter().zip(parsed_row.into_iter()) {
-                    obj.insert(field_name.to_string(), value);
+                for ((field_name, field_type), cell) in
+                    field_names.iter().zip(&field_types).zip(row.iter())
+                {
+                    let value = match field_type {
+                        None => Some(parse_csv_cell(cell)),
+                        Some(field_type) => {
+                            parse_typed_csv_cell(cell, *field_type).map_err(|e| {
+                                ImportError::CsvInvalidCell(
+                                    lineno,
+                                    field_name.to_string(),
+                                    *field_type,
+                                    e,
+                                )
+                            })?
+                        },
+                    };
+                    if let Some(value) = value {
+                        obj.insert(field_name.to_string(), value);
+                    }
                 }
                 yield ImportUnit::Object(serde_json::to_value(obj)?);
             }
@@ -993,6 +1471,37 @@ where
                             if storage_id_str == "documents" {
                                 continue;
                             }
+                            if storage_id_str == "manifest" {
+                                let entry_reader =
+                                    zip_reader.entry_reader(i).await.map_err(map_zip_error)?;
+                                let manifest = parse_storage_manifest(entry_reader).await?;
+                                for (storage_id, object_key) in manifest {
+                                    tracing::info!(
+                                        "importing storage file {} from object {object_key:?}",
+                                        storage_id.encode()
+                                    );
+                                    let mut file_stream = exports_storage
+                                        .get(&object_key)
+                                        .await?
+                                        .with_context(|| {
+                                            ErrorMetadata::bad_request(
+                                                "StorageObjectMissing",
+                                                format!(
+                                                    "_storage file {} is in a separate object \
+                                                     that this deployment can't read. Export with \
+                                                     files in the ZIP to import them elsewhere.",
+                                                    storage_id.encode()
+                                                ),
+                                            )
+                                        })?
+                                        .stream;
+                                    while let Some(chunk) = file_stream.try_next().await? {
+                                        yield ImportUnit::StorageFileChunk(storage_id, chunk);
+                                    }
+                                    yield ImportUnit::StorageFileChunk(storage_id, Bytes::new());
+                                }
+                                continue;
+                            }
                             let entry_reader =
                                 zip_reader.entry_reader(i).await.map_err(map_zip_error)?;
                             let storage_id =
@@ -1063,6 +1572,31 @@ fn parse_documents_jsonl_table_name(filename: &str) -> anyhow::Result<Option<Tab
         .transpose()
 }
 
+/// Reads the `_storage/manifest.jsonl` of a ZIP export whose files were
+/// written as separate objects, returning the object key of each file.
+async fn parse_storage_manifest<R: TokioAsyncRead + Unpin>(
+    entry_reader: ZipEntryReader<'_, R>,
+) -> anyhow::Result<Vec<(DeveloperDocumentId, ObjectKey)>> {
+    let mut reader = BufReader::new(entry_reader.compat());
+    let mut line = String::new();
+    let mut lineno = 1;
+    let mut manifest = vec![];
+    while reader.read_line(&mut line).await? > 0 {
+        let entry: FileStorageObjectManifestEntry =
+            serde_json::from_str(&line).map_err(|e| ImportError::JsonInvalidRow(lineno, e))?;
+        let storage_id = DeveloperDocumentId::decode(&entry.id).map_err(|e| {
+            ErrorMetadata::bad_request(
+                "InvalidStorageId",
+                format!("_storage id '{}' invalid: {e}", entry.id),
+            )
+        })?;
+        manifest.push((storage_id, entry.object_key.try_into()?));
+        line.clear();
+        lineno += 1;
+    }
+    Ok(manifest)
+}
+
 #[try_stream(ok = ImportUnit, error = anyhow::Error)]
 async fn parse_documents_jsonl<R: TokioAsyncRead + Unpin>(entry_reader: ZipEntryReader<'_, R>) {
     let table_name = parse_documents_jsonl_table_name(entry_reader.entry().filename())?
@@ -1141,7 +1675,8 @@ async fn parse_generated_schema<'a, T: ShapeConfig, R: AsyncRead + Unpin>(
     Ok(generated_schema)
 }
 
-// For now, we only parse out floats and strings in CSV files.
+// Without a type for the column, we only parse out floats and strings in CSV
+// files.
 fn parse_csv_cell(s: &str) -> JsonValue {
     if let Ok(r) = s.parse::<f64>() {
         return json!(r);
@@ -1149,18 +1684,50 @@ fn parse_csv_cell(s: &str) -> JsonValue {
     json!(s)
 }
 
+/// Parse a cell of a column with a type hint. Empty cells are left out of the
+/// object unless the column is a string, since CSV can't distinguish them from
+/// missing values.
+fn parse_typed_csv_cell(s: &str, column_type: CsvColumnType) -> anyhow::Result<Option<JsonValue>> {
+    if s.is_empty() && column_type != CsvColumnType::String {
+        return Ok(None);
+    }
+    let value = match column_type {
+        CsvColumnType::String => json!(s),
+        CsvColumnType::Number => JsonValue::from(ConvexValue::Float64(s.trim().parse()?)),
+        CsvColumnType::Int64 => JsonValue::from(ConvexValue::Int64(s.trim().parse()?)),
+        CsvColumnType::Boolean => match s.trim().to_ascii_lowercase().as_str() {
+            "true" => json!(true),
+            "false" => json!(false),
+            _ => anyhow::bail!("expected true or false"),
+        },
+        CsvColumnType::Json => serde_json::from_str(s)?,
+    };
+    Ok(Some(value))
+}
+
 pub async fn upload_import_file<RT: Runtime>(
     application: &Application<RT>,
     identity: Identity,
     format: ImportFormat,
     mode: ImportMode,
+    dry_run: bool,
+    remap_ids: bool,
     body_stream: BoxStream<'_, anyhow::Result<Bytes>>,
 ) -> anyhow::Result<DeveloperDocumentId> {
     if !identity.is_admin() {
         anyhow::bail!(ImportError::Unauthorized);
     }
     let object_key = application.upload_snapshot_import(body_stream).await?;
-    store_uploaded_import(application, identity, format, mode, object_key).await
+    store_uploaded_import(
+        application,
+        identity,
+        format,
+        mode,
+        dry_run,
+        remap_ids,
+        object_key,
+    )
+    .await
 }
 
 pub async fn store_uploaded_import<RT: Runtime>(
@@ -1168,6 +1735,8 @@ pub async fn store_uploaded_import<RT: Runtime>(
     identity: Identity,
     format: ImportFormat,
     mode: ImportMode,
+    dry_run: bool,
+    remap_ids: bool,
     object_key: ObjectKey,
 ) -> anyhow::Result<DeveloperDocumentId> {
     let (_, id, _) = application
@@ -1181,7 +1750,7 @@ pub async fn store_uploaded_import<RT: Runtime>(
                 async {
                     let mut model = SnapshotImportModel::new(tx);
                     model
-                        .start_import(format.clone(), mode, object_key.clone())
+                        .start_import(format.clone(), mode, object_key.clone(), dry_run, remap_ids)
                         .await
                 }
                 .into()
@@ -1310,8 +1879,16 @@ pub async fn do_import<RT: Runtime>(
     mode: ImportMode,
     body_stream: BoxStream<'_, anyhow::Result<Bytes>>,
 ) -> anyhow::Result<u64> {
-    let import_id =
-        upload_import_file(application, identity.clone(), format, mode, body_stream).await?;
+    let import_id = upload_import_file(
+        application,
+        identity.clone(),
+        format,
+        mode,
+        false,
+        false,
+        body_stream,
+    )
+    .await?;
 
     let snapshot_import = wait_for_import_worker(application, identity.clone(), import_id).await?;
     match &snapshot_import.state {
@@ -1375,12 +1952,14 @@ pub async fn clear_tables<RT: Runtime>(
         objects,
         usage.clone(),
         None,
+        None,
     )
     .await?;
 
     let (_ts, documents_deleted) = finalize_import(
         &application.database,
         &application.usage_tracking,
+        application.log_sender.as_ref(),
         identity.clone(),
         None,
         initial_schemas,
@@ -1459,6 +2038,7 @@ async fn import_objects<RT: Runtime>(
     objects: Peekable<BoxStream<'_, anyhow::Result<ImportUnit>>>,
     usage: FunctionUsageTracker,
     import_id: Option<ResolvedDocumentId>,
+    mut id_remapping: Option<IdRemapping>,
 ) -> anyhow::Result<(TableMapping, u64)> {
     pin_mut!(objects);
     let mut generated_schemas = BTreeMap::new();
@@ -1476,6 +2056,7 @@ async fn import_objects<RT: Runtime>(
         &mut table_mapping_for_import,
         usage.clone(),
         import_id,
+        id_remapping.as_mut(),
     )
     .await?
     {
@@ -1608,6 +2189,7 @@ impl ImportSchemaConstraints {
 async fn finalize_import<RT: Runtime>(
     database: &Database<RT>,
     usage_tracking: &UsageCounter,
+    log_sender: &dyn LogSender,
     identity: Identity,
     member_id_override: Option<MemberId>,
     initial_schemas: Vec<Option<(ResolvedDocumentId, DatabaseSchema)>>,
@@ -1623,6 +2205,7 @@ async fn finalize_import<RT: Runtime>(
     // Ensure that schemas will be valid after the tables are activated.
     let schema_constraints =
         ImportSchemaConstraints::new(&table_mapping_for_import, initial_schemas);
+    let actor = audit_log_actor(&identity, member_id_override)?;
 
     // If we inserted into an existing table, we're done because the table is
     // now populated and active.
@@ -1656,6 +2239,11 @@ async fn finalize_import<RT: Runtime>(
             },
         )
         .await?;
+    log_sender.send_logs(vec![DeploymentAuditLogEvent::to_log_event(
+        audit_log_event,
+        actor,
+        UnixTimestamp::from_nanos(ts.into()),
+    )?]);
 
     usage_tracking.track_call(
         UdfIdentifier::Cli("import".to_string()),
@@ -1696,6 +2284,7 @@ async fn import_tables_table<RT: Runtime>(
     mode: ImportMode,
     mut objects: Pin<&mut Peekable<BoxStream<'_, anyhow::Result<ImportUnit>>>>,
     import_id: Option<ResolvedDocumentId>,
+    mut id_remapping: Option<&mut IdRemapping>,
 ) -> anyhow::Result<TableMapping> {
     let mut table_mapping_for_import = TableMapping::new();
     let mut import_tables: Vec<(TableName, TableNumber)> = vec![];
@@ -1743,11 +2332,14 @@ async fn import_tables_table<RT: Runtime>(
             identity,
             mode,
             table_name,
-            Some(*table_number),
+            Some(*table_number).filter(|_| id_remapping.is_none()),
             &tables_in_import,
             import_id,
         )
         .await?;
+        if let Some(id_remapping) = &mut id_remapping {
+            id_remapping.add_table(*table_number, table_id.table_number);
+        }
         table_mapping_for_import.insert(
             table_id.tablet_id,
             TableNamespace::by_component_TODO(),
@@ -1767,6 +2359,7 @@ async fn import_storage_table<RT: Runtime>(
     usage: &dyn StorageUsageTracker,
     import_id: Option<ResolvedDocumentId>,
     num_to_skip: u64,
+    mut id_remapping: Option<&mut IdRemapping>,
 ) -> anyhow::Result<()> {
     let snapshot = database.latest_snapshot()?;
     let namespace = snapshot
@@ -1777,6 +2370,10 @@ async fn import_storage_table<RT: Runtime>(
         .virtual_table_mapping()
         .namespace(namespace)
         .number(&FILE_STORAGE_VIRTUAL_TABLE)?;
+    if let Some(id_remapping) = &mut id_remapping {
+        // Storage ids are in the same virtual table in every deployment.
+        id_remapping.add_table(virtual_table_number, virtual_table_number);
+    }
     let mut lineno = 0;
     let mut storage_metadata = BTreeMap::new();
     while let Some(ImportUnit::Object(exported_value)) = objects
@@ -1855,6 +2452,13 @@ async fn import_storage_table<RT: Runtime>(
                     _ => Ok(None),
                 }
             });
+        if num_files < num_to_skip {
+            // The file was imported by an earlier attempt, so don't upload it
+            // again.
+            file_chunks.try_for_each(|_| async { Ok(()) }).await?;
+            num_files += 1;
+            continue;
+        }
         let mut entry = file_storage
             .transactional_file_storage
             .upload_file(content_length, content_type, file_chunks, expected_sha256)
@@ -1862,11 +2466,11 @@ async fn import_storage_table<RT: Runtime>(
         if let Some(storage_id) = storage_id {
             entry.storage_id = storage_id;
         }
-        if num_files < num_to_skip {
-            num_files += 1;
-            continue;
-        }
         let file_size = entry.size as u64;
+        let id = id_remapping
+            .as_deref()
+            .and_then(|id_remapping| id_remapping.remap_id(id))
+            .unwrap_or(id);
         database
             .execute_with_overloaded_retries(
                 identity.clone(),
@@ -1899,6 +2503,15 @@ async fn import_storage_table<RT: Runtime>(
                         ImportFacingModel::new(tx)
                             .insert(table_id, &FILE_STORAGE_TABLE, entry_object, &table_mapping)
                             .await?;
+                        if let Some(import_id) = import_id {
+                            SnapshotImportModel::new(tx)
+                                .checkpoint_rows_written(
+                                    import_id,
+                                    &FILE_STORAGE_VIRTUAL_TABLE,
+                                    (num_files + 1) as i64,
+                                )
+                                .await?;
+                        }
                         Ok(())
                     }
                     .into()
@@ -2005,6 +2618,7 @@ async fn import_single_table<RT: Runtime>(
     table_mapping_for_import: &mut TableMapping,
     usage: FunctionUsageTracker,
     import_id: Option<ResolvedDocumentId>,
+    mut id_remapping: Option<&mut IdRemapping>,
 ) -> anyhow::Result<Option<u64>> {
     while let Some(ImportUnit::GeneratedSchema(table_name, generated_schema)) = objects
         .as_mut()
@@ -2040,7 +2654,15 @@ async fn import_single_table<RT: Runtime>(
 
     if table_name == *TABLES_TABLE {
         table_mapping_for_import.update(
-            import_tables_table(database, identity, mode, objects.as_mut(), import_id).await?,
+            import_tables_table(
+                database,
+                identity,
+                mode,
+                objects.as_mut(),
+                import_id,
+                id_remapping.as_deref_mut(),
+            )
+            .await?,
         );
         return Ok(Some(0));
     }
@@ -2057,7 +2679,12 @@ async fn import_single_table<RT: Runtime>(
         Some(table_id) => {
             let mut tx = database.begin(identity.clone()).await?;
             let num_to_skip = if tx.table_mapping().is_active(table_id.tablet_id) {
-                0
+                let display_table_name = if table_name == *FILE_STORAGE_TABLE {
+                    &*FILE_STORAGE_VIRTUAL_TABLE
+                } else {
+                    &table_name
+                };
+                checkpointed_num_rows_written(&mut tx, import_id, display_table_name).await?
             } else {
                 TableModel::new(&mut tx)
                     .count_tablet(table_id.tablet_id)
@@ -2066,12 +2693,15 @@ async fn import_single_table<RT: Runtime>(
             (table_id, num_to_skip)
         },
         None => {
+            // Remapped ids can use any table number, so don't take the one in
+            // the documents, which may be in use by another table.
+            let table_number = table_number_from_docs.filter(|_| id_remapping.is_none());
             let (table_id, num_to_skip) = prepare_table_for_import(
                 database,
                 identity,
                 mode,
                 &table_name,
-                table_number_from_docs,
+                table_number,
                 &tables_in_import,
                 import_id,
             )
@@ -2085,6 +2715,11 @@ async fn import_single_table<RT: Runtime>(
             (table_id, num_to_skip)
         },
     };
+    if let Some(id_remapping) = &mut id_remapping
+        && let Some(table_number_from_docs) = table_number_from_docs
+    {
+        id_remapping.add_table(table_number_from_docs, table_id.table_number);
+    }
 
     if table_name == *FILE_STORAGE_TABLE {
         import_storage_table(
@@ -2096,6 +2731,7 @@ async fn import_single_table<RT: Runtime>(
             &usage,
             import_id,
             num_to_skip,
+            id_remapping,
         )
         .await?;
         return Ok(Some(0));
@@ -2109,7 +2745,7 @@ async fn import_single_table<RT: Runtime>(
     let mut objects_to_insert = vec![];
     let mut objects_to_insert_size = 0;
     // Peek so we don't pop ImportUnit::NewTable items.
-    while let Some(ImportUnit::Object(exported_value)) = objects
+    while let Some(ImportUnit::Object(mut exported_value)) = objects
         .as_mut()
         .try_next_if(|line| matches!(line, ImportUnit::Object(_)))
         .await?
@@ -2118,6 +2754,9 @@ async fn import_single_table<RT: Runtime>(
             num_objects += 1;
             continue;
         }
+        if let Some(id_remapping) = &id_remapping {
+            id_remapping.remap_document(&table_name, &mut exported_value);
+        }
         let row_number = (num_objects + 1) as usize;
         let convex_value = GeneratedSchema::<ProdConfigWithOptionalFields>::apply(
             &mut generated_schema,
@@ -2129,6 +2768,7 @@ async fn import_single_table<RT: Runtime>(
         };
         objects_to_insert_size += convex_object.size();
         objects_to_insert.push(convex_object);
+        num_objects += 1;
 
         if objects_to_insert_size > *TRANSACTION_MAX_USER_WRITE_SIZE_BYTES / 2
             || objects_to_insert.len() > *TRANSACTION_MAX_NUM_USER_WRITES / 2
@@ -2141,6 +2781,8 @@ async fn import_single_table<RT: Runtime>(
                 table_id,
                 &table_mapping_for_schema,
                 usage.clone(),
+                import_id,
+                num_objects,
             )
             .await?;
             objects_to_insert = Vec::new();
@@ -2160,7 +2802,6 @@ async fn import_single_table<RT: Runtime>(
                 .await;
             }
         }
-        num_objects += 1;
     }
 
     insert_import_objects(
@@ -2171,6 +2812,8 @@ async fn import_single_table<RT: Runtime>(
         table_id,
         &table_mapping_for_schema,
         usage,
+        import_id,
+        num_objects,
     )
     .await?;
 
@@ -2192,6 +2835,8 @@ async fn import_single_table<RT: Runtime>(
     Ok(Some(num_objects))
 }
 
+/// Insert a chunk of objects, checkpointing that `num_rows_written` rows of
+/// the table have been imported in the same transaction.
 async fn insert_import_objects<RT: Runtime>(
     database: &Database<RT>,
     identity: &Identity,
@@ -2200,6 +2845,8 @@ async fn insert_import_objects<RT: Runtime>(
     table_id: TabletIdAndTableNumber,
     table_mapping_for_schema: &TableMapping,
     usage: FunctionUsageTracker,
+    import_id: Option<ResolvedDocumentId>,
+    num_rows_written: u64,
 ) -> anyhow::Result<()> {
     if objects_to_insert.is_empty() {
         return Ok(());
@@ -2233,6 +2880,11 @@ async fn insert_import_objects<RT: Runtime>(
                             )
                             .await?;
                     }
+                    if let Some(import_id) = import_id {
+                        SnapshotImportModel::new(tx)
+                            .checkpoint_rows_written(import_id, table_name, num_rows_written as i64)
+                            .await?;
+                    }
                     Ok(())
                 }
                 .into()
@@ -2305,7 +2957,13 @@ async fn prepare_table_for_import<RT: Runtime>(
                 },
                 ImportMode::Replace => None,
             };
-            (tablet_id, 0)
+            // When appending to an existing table, skip the rows that an
+            // earlier attempt at this import wrote.
+            let num_to_skip = match (tablet_id, &existing_checkpoint) {
+                (Some(_), Some(checkpoint)) => checkpoint.num_rows_written as u64,
+                _ => 0,
+            };
+            (tablet_id, num_to_skip)
         },
     };
     drop(tx);
@@ -2360,6 +3018,22 @@ async fn prepare_table_for_import<RT: Runtime>(
     Ok((table_id, num_to_skip))
 }
 
+/// The number of rows of the table that an earlier attempt at the import has
+/// written.
+async fn checkpointed_num_rows_written<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    import_id: Option<ResolvedDocumentId>,
+    display_table_name: &TableName,
+) -> anyhow::Result<u64> {
+    let Some(import_id) = import_id else {
+        return Ok(0);
+    };
+    let checkpoint = SnapshotImportModel::new(tx)
+        .get_table_checkpoint(import_id, display_table_name)
+        .await?;
+    Ok(checkpoint.map_or(0, |checkpoint| checkpoint.num_rows_written as u64))
+}
+
 /// Waits for all indexes on a table to be backfilled, which may take a while
 /// for large tables. After the indexes are backfilled, enable them.
 async fn backfill_and_enable_indexes_on_table<RT: Runtime>(
@@ -2498,6 +3172,11 @@ mod tests {
     };
 
     use anyhow::Context;
+    use async_zip::{
+        write::ZipFileWriter,
+        Compression,
+        ZipEntryBuilder,
+    };
     use bytes::Bytes;
     use common::{
         bootstrap_model::index::{
@@ -2547,7 +3226,13 @@ mod tests {
         Identity,
     };
     use maplit::btreemap;
-    use model::snapshot_imports::types::ImportState;
+    use model::snapshot_imports::{
+        types::{
+            CsvColumnType,
+            ImportState,
+        },
+        SnapshotImportModel,
+    };
     use must_let::must_let;
     use runtime::testing::TestRuntime;
     use serde_json::{
@@ -2586,6 +3271,7 @@ mod tests {
     };
     use crate::{
         snapshot_import::{
+            perform_import,
             upload_import_file,
             wait_for_import_worker,
         },
@@ -2685,7 +3371,7 @@ mod tests {
         upload.write(Bytes::copy_from_slice(v.as_bytes())).await?;
         let object_key = upload.complete().await?;
         let stream = || storage.get_reader(&object_key);
-        parse_objects(format, stream)
+        parse_objects(format, stream, storage.clone())
             .filter_map(|line| async move {
                 match line {
                     Ok(super::ImportUnit::Object(object)) => Some(Ok(object)),
@@ -2703,6 +3389,69 @@ mod tests {
         stream::iter(vec![anyhow::Ok(str.to_string().into_bytes().into())]).boxed()
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_zip_storage_manifest(rt: TestRuntime) -> anyhow::Result<()> {
+        let storage_dir = tempfile::TempDir::new()?;
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::for_use_case(
+            rt.clone(),
+            &storage_dir.path().to_string_lossy(),
+            StorageUseCase::Exports,
+        )?);
+        // A file that a ZIP export wrote as a separate object.
+        let mut upload = storage.start_upload().await?;
+        upload.write(Bytes::from_static(b"abc")).await?;
+        let file_key = upload.complete().await?;
+        let storage_id = "kg21pzwemsm55e1fnt2kcsvgjh6h6gtf";
+
+        let mut zip = vec![];
+        let mut writer = ZipFileWriter::new(&mut zip);
+        for (path, contents) in [
+            ("_storage/documents.jsonl", json!({"_id": storage_id})),
+            (
+                "_storage/manifest.jsonl",
+                json!({"_id": storage_id, "objectKey": file_key.to_string()}),
+            ),
+        ] {
+            let builder = ZipEntryBuilder::new(path.to_string(), Compression::Deflate);
+            writer
+                .write_entry_whole(builder, format!("{contents}\n").as_bytes())
+                .await?;
+        }
+        writer.close().await?;
+        let mut upload = storage.start_upload().await?;
+        upload.write(zip.into()).await?;
+        let zip_key = upload.complete().await?;
+
+        let units: Vec<_> = parse_objects(
+            ImportFormat::Zip,
+            || storage.get_reader(&zip_key),
+            storage.clone(),
+        )
+        .try_collect()
+        .await?;
+        let mut contents = vec![];
+        for unit in units {
+            if let ImportUnit::StorageFileChunk(id, chunk) = unit {
+                assert_eq!(id.encode(), storage_id);
+                contents.extend_from_slice(&chunk);
+            }
+        }
+        assert_eq!(contents, b"abc");
+
+        // A deployment that can't read the export's objects can't import it.
+        let other_storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt)?);
+        let err = parse_objects(
+            ImportFormat::Zip,
+            || storage.get_reader(&zip_key),
+            other_storage,
+        )
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap_err();
+        assert_eq!(err.short_msg(), "StorageObjectMissing");
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn test_csv(rt: TestRuntime) -> anyhow::Result<()> {
         let test1 = r#"
@@ -2710,8 +3459,12 @@ a,b,c
 1,a string i guess,1.2
 5.10,-100,"a string in quotes"
 "#;
-        let objects =
-            run_parse_objects(rt, ImportFormat::Csv("table".parse().unwrap()), test1).await?;
+        let objects = run_parse_objects(
+            rt,
+            ImportFormat::Csv("table".parse().unwrap(), BTreeMap::new()),
+            test1,
+        )
+        .await?;
         let expected = vec![
             json!({
                 "a": 1.,
@@ -2756,8 +3509,12 @@ _id,value
 a,b,c,d
 "",,"""",""""""
 "#;
-        let objects =
-            run_parse_objects(rt, ImportFormat::Csv("table".parse().unwrap()), test1).await?;
+        let objects = run_parse_objects(
+            rt,
+            ImportFormat::Csv("table".parse().unwrap(), BTreeMap::new()),
+            test1,
+        )
+        .await?;
         let expected = vec![json!({
             "a": "",
             "b": "",
@@ -2768,6 +3525,115 @@ a,b,c,d
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_csv_column_types(rt: TestRuntime) -> anyhow::Result<()> {
+        let test1 = r#"
+a,b,c,d,e
+1,2,TRUE,"{""x"": [1]}",3
+-5,,false,null,
+"#;
+        let column_types = btreemap! {
+            "a".to_string() => CsvColumnType::Int64,
+            "b".to_string() => CsvColumnType::String,
+            "c".to_string() => CsvColumnType::Boolean,
+            "d".to_string() => CsvColumnType::Json,
+            "e".to_string() => CsvColumnType::Number,
+        };
+        let objects = run_parse_objects(
+            rt.clone(),
+            ImportFormat::Csv("table".parse()?, column_types.clone()),
+            test1,
+        )
+        .await?;
+        let expected = vec![
+            json!({
+                "a": JsonValue::from(ConvexValue::Int64(1)),
+                "b": "2",
+                "c": true,
+                "d": {"x": [1]},
+                "e": 3.,
+            }),
+            // Empty cells are left out unless the column is a string.
+            json!({
+                "a": JsonValue::from(ConvexValue::Int64(-5)),
+                "b": "",
+                "c": false,
+                "d": null,
+            }),
+        ];
+        assert_eq!(objects, expected);
+
+        let err = run_parse_objects(
+            rt.clone(),
+            ImportFormat::Csv("table".parse()?, column_types),
+            "a,b,c,d,e\n1.5,,,,\n",
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("CSV row 1 column \"a\" isn't a valid int64"),
+            "{err}"
+        );
+
+        let err = run_parse_objects(
+            rt,
+            ImportFormat::Csv(
+                "table".parse()?,
+                btreemap! { "z".to_string() => CsvColumnType::Number },
+            ),
+            "a\n1\n",
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("CSV column type given for \"z\", which isn't in the header"),
+            "{err}"
+        );
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn import_append_resumes_from_checkpoint(rt: TestRuntime) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let table_name = "table1";
+        run_csv_import(&app, table_name, "value\nexisting\n").await?;
+
+        let import_id = upload_import_file(
+            &app,
+            new_admin_id(),
+            ImportFormat::Csv(table_name.parse()?, BTreeMap::new()),
+            ImportMode::Append,
+            false,
+            false,
+            stream_from_str("value\nfirst\nsecond\nthird\n"),
+        )
+        .await?;
+        let snapshot_import = wait_for_import_worker(&app, new_admin_id(), import_id).await?;
+        must_let!(let ImportState::WaitingForConfirmation { .. } = &snapshot_import.state);
+
+        // Pretend that an earlier attempt wrote the first two rows and crashed.
+        let mut tx = app.begin(new_admin_id()).await?;
+        SnapshotImportModel::new(&mut tx)
+            .checkpoint_rows_written(snapshot_import.id(), &table_name.parse()?, 2)
+            .await?;
+        app.commit_test(tx).await?;
+
+        perform_import(&app, new_admin_id(), import_id).await?;
+        let snapshot_import = wait_for_import_worker(&app, new_admin_id(), import_id).await?;
+        must_let!(let ImportState::Completed { .. } = &snapshot_import.state);
+        let values = load_fields_as_maps(&app, table_name, vec!["value"]).await?;
+        assert_eq!(
+            values,
+            vec![
+                btreemap!("value" => assert_val!("existing")),
+                btreemap!("value" => assert_val!("third")),
+            ]
+        );
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     #[ignore]
     async fn import_huge_csv(rt: TestRuntime) -> anyhow::Result<()> {
@@ -2970,8 +3836,10 @@ a
         let import_id = upload_import_file(
             &app,
             new_admin_id(),
-            ImportFormat::Csv(table_name.parse()?),
+            ImportFormat::Csv(table_name.parse()?, BTreeMap::new()),
             ImportMode::Replace,
+            false,
+            false,
             stream_from_str(test_csv),
         )
         .await?;
@@ -2998,6 +3866,119 @@ Interrupting `npx convex import` will not cancel it."#
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn import_dry_run_validates_without_writing(rt: TestRuntime) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let table_name = "table1";
+        let schema = db_schema!(
+            table_name => DocumentSchema::Union(
+                vec![
+                    object_validator!(
+                        "a" => FieldValidator::required_field_type(Validator::Float64),
+                    )
+                ]
+            )
+        );
+        activate_schema(&app, schema).await?;
+        run_csv_import(&app, table_name, "a\n1\n").await?;
+        let existing = load_fields_as_maps(&app, table_name, vec!["_id"]).await?;
+        must_let!(let ConvexValue::String(existing_id) = &existing[0]["_id"]);
+
+        let test_jsonl = format!(
+            "{}\n{}\n{}\n",
+            json!({ "_id": existing_id.to_string(), "a": 2 }),
+            json!({ "a": "two" }),
+            json!({ "a": 3 }),
+        );
+        let import_id = upload_import_file(
+            &app,
+            new_admin_id(),
+            ImportFormat::JsonLines(table_name.parse()?),
+            ImportMode::Append,
+            true,
+            false,
+            stream_from_str(&test_jsonl),
+        )
+        .await?;
+        let snapshot_import = wait_for_import_worker(&app, new_admin_id(), import_id).await?;
+        must_let!(let ImportState::WaitingForConfirmation {
+            info_message,
+            require_manual_confirmation,
+        } = &snapshot_import.state);
+        assert!(require_manual_confirmation);
+        assert!(info_message.contains("table1: 3 rows, 1 type mismatches, 1 id collisions"));
+
+        must_let!(let Some(validation) = &snapshot_import.validation);
+        assert_eq!(validation.len(), 1);
+        assert_eq!(validation[0].num_rows, 3);
+        assert_eq!(validation[0].num_type_mismatches, 1);
+        assert_eq!(validation[0].num_id_collisions, 1);
+        assert!(validation[0].ingress_bytes > 0);
+        assert_eq!(validation[0].errors.len(), 2);
+
+        // Nothing is written until the import is confirmed.
+        let values = load_fields_as_maps(&app, table_name, vec!["a"]).await?;
+        assert_eq!(values, vec![btreemap!("a" => assert_val!(1.))]);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn import_remap_ids_rewrites_references(rt: TestRuntime) -> anyhow::Result<()> {
+        let app = Application::new_for_tests(&rt).await?;
+        let table_name = "users";
+        let schema = db_schema!(
+            table_name => DocumentSchema::Union(
+                vec![
+                    object_validator!(
+                        "name" => FieldValidator::required_field_type(Validator::String),
+                        "manager" => FieldValidator::optional_field_type(
+                            Validator::Id(table_name.parse()?)
+                        ),
+                    )
+                ]
+            )
+        );
+        activate_schema(&app, schema).await?;
+        run_csv_import(&app, table_name, "name\nalice\n").await?;
+        let existing = load_fields_as_maps(&app, table_name, vec!["_id"]).await?;
+        must_let!(let ConvexValue::String(alice_id) = &existing[0]["_id"]);
+
+        // Documents from another deployment, where bob has the same _id as
+        // alice does here.
+        let test_jsonl = format!(
+            "{}\n{}\n",
+            json!({ "_id": alice_id.to_string(), "name": "bob" }),
+            json!({ "name": "carol", "manager": alice_id.to_string() }),
+        );
+        let import_id = upload_import_file(
+            &app,
+            new_admin_id(),
+            ImportFormat::JsonLines(table_name.parse()?),
+            ImportMode::Append,
+            false,
+            true,
+            stream_from_str(&test_jsonl),
+        )
+        .await?;
+        wait_for_import_worker(&app, new_admin_id(), import_id).await?;
+        perform_import(&app, new_admin_id(), import_id).await?;
+        let snapshot_import = wait_for_import_worker(&app, new_admin_id(), import_id).await?;
+        must_let!(let ImportState::Completed { .. } = &snapshot_import.state);
+
+        let users = load_fields_as_maps(&app, table_name, vec!["_id", "name", "manager"]).await?;
+        assert_eq!(users.len(), 3);
+        let user = |name: &str| {
+            users
+                .iter()
+                .find(|user| user["name"] == assert_val!(name))
+                .unwrap()
+        };
+        assert_eq!(user("alice")["_id"], assert_val!(alice_id.to_string()));
+        assert_ne!(user("bob")["_id"], assert_val!(alice_id.to_string()));
+        assert_eq!(user("carol")["manager"], user("bob")["_id"]);
+        Ok(())
+    }
+
     // Hard to control timing in race test with background job moving state forward.
     #[convex_macro::test_runtime]
     async fn import_races_with_schema_update(rt: TestRuntime) -> anyhow::Result<()> {
@@ -3307,7 +4288,7 @@ a
         do_import(
             app,
             new_admin_id(),
-            ImportFormat::Csv(table_name.parse()?),
+            ImportFormat::Csv(table_name.parse()?, BTreeMap::new()),
             ImportMode::Replace,
             stream_from_str(input),
         )
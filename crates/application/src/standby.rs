@@ -0,0 +1,145 @@
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use common::{
+    backoff::Backoff,
+    persistence::Persistence,
+    runtime::{
+        Runtime,
+        RuntimeInstant,
+    },
+};
+use database::{
+    ReplicaWriter,
+    ReplicationLogPage,
+};
+use reqwest::header::AUTHORIZATION;
+use serde_json::Value as JsonValue;
+
+use crate::metrics::log_standby_replication_lag;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// How long to wait for new commits once the standby has caught up.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Most revisions requested in one page of the primary's log.
+const PAGE_ROWS_LIMIT: usize = 1000;
+
+/// Keeps a standby's persistence up to date with a primary backend by
+/// polling its `/api/replication/log` endpoint.
+pub struct StandbyReplicator<RT: Runtime> {
+    runtime: RT,
+    replica: ReplicaWriter<RT>,
+    client: reqwest::Client,
+    primary_url: String,
+    admin_key: String,
+    promote_after: Option<Duration>,
+}
+
+impl<RT: Runtime> StandbyReplicator<RT> {
+    /// `promote_after` is how long the primary must be unreachable before
+    /// [`Self::run`] returns so the standby can be promoted. If it's `None`,
+    /// the standby replicates until it's stopped.
+    pub async fn new(
+        runtime: RT,
+        persistence: Arc<dyn Persistence>,
+        primary_url: String,
+        admin_key: String,
+        promote_after: Option<Duration>,
+    ) -> anyhow::Result<Self> {
+        let replica = ReplicaWriter::new(runtime.clone(), persistence).await?;
+        Ok(Self {
+            runtime,
+            replica,
+            client: reqwest::Client::new(),
+            primary_url,
+            admin_key,
+            promote_after,
+        })
+    }
+
+    /// Replicate until the primary has been unreachable for `promote_after`.
+    /// Errors the primary responds with, like the standby falling out of its
+    /// retention window, and errors writing to the standby's persistence
+    /// aren't retried.
+    pub async fn run(mut self) -> anyhow::Result<()> {
+        tracing::info!(
+            "Replicating from {} starting after {:?}",
+            self.primary_url,
+            self.replica.cursor()
+        );
+        let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+        let mut last_contact = self.runtime.monotonic_now();
+        loop {
+            let page = match self.fetch_page().await? {
+                Some(page) => page,
+                None => {
+                    if let Some(promote_after) = self.promote_after
+                        && last_contact.elapsed() >= promote_after
+                    {
+                        tracing::warn!(
+                            "Primary has been unreachable for {promote_after:?}, promoting at {:?}",
+                            self.replica.cursor()
+                        );
+                        return Ok(());
+                    }
+                    let delay = self.runtime.with_rng(|rng| backoff.fail(rng));
+                    self.runtime.wait(delay).await;
+                    continue;
+                },
+            };
+            last_contact = self.runtime.monotonic_now();
+            backoff.reset();
+            let has_more = page.has_more;
+            let lag_seconds = page.primary_ts.secs_since_f64(page.cursor);
+            self.replica.apply(page).await?;
+            log_standby_replication_lag(lag_seconds);
+            tracing::debug!("Applied replication log page, {lag_seconds}s behind the primary");
+            if !has_more {
+                self.runtime.wait(POLL_INTERVAL).await;
+            }
+        }
+    }
+
+    /// Fetch the next page of the primary's log, or `None` if the primary
+    /// is unreachable.
+    async fn fetch_page(&self) -> anyhow::Result<Option<ReplicationLogPage>> {
+        let mut request = self
+            .client
+            .get(format!("{}/api/replication/log", self.primary_url))
+            .header(AUTHORIZATION, format!("Convex {}", self.admin_key))
+            .query(&[("limit", PAGE_ROWS_LIMIT.to_string())]);
+        if let Some(cursor) = self.replica.cursor() {
+            request = request.query(&[("cursor", cursor.to_string())]);
+        }
+        let response = match request.send().await {
+            Ok(response) if !response.status().is_server_error() => response,
+            Ok(response) => {
+                tracing::warn!("Primary responded with {}", response.status());
+                return Ok(None);
+            },
+            Err(e) => {
+                tracing::warn!("Failed to reach primary: {e}");
+                return Ok(None);
+            },
+        };
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "Primary responded to /api/replication/log with {status}: {}",
+                body.chars().take(1000).collect::<String>()
+            );
+        }
+        let page: JsonValue = response
+            .json()
+            .await
+            .context("Invalid response from /api/replication/log")?;
+        Ok(Some(page.try_into()?))
+    }
+}
@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    knobs::{
+        TABLE_STORAGE_SNAPSHOT_INTERVAL,
+        TABLE_STORAGE_SNAPSHOT_RETENTION_DAYS,
+        TRASH_PURGE_BATCH_SIZE,
+    },
+    runtime::Runtime,
+};
+use database::{
+    Database,
+    TableModel,
+};
+use events::usage::TableStorageBreakdown;
+use futures::Future;
+use keybroker::Identity;
+use model::table_storage_snapshots::{
+    types::TableStorageSnapshot,
+    TableStorageSnapshotsModel,
+};
+use usage_tracking::UsageCounter;
+use value::TableNamespace;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Once a day, records how much storage each user table in the root component
+/// uses in `_table_storage_snapshots`, split into its documents, its indexes
+/// and the overwritten and deleted revisions retention is still keeping, and
+/// reports the breakdown as a usage event.
+pub struct TableStorageSnapshotWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+    usage_tracking: UsageCounter,
+}
+
+impl<RT: Runtime> TableStorageSnapshotWorker<RT> {
+    pub fn start(
+        runtime: RT,
+        database: Database<RT>,
+        usage_tracking: UsageCounter,
+    ) -> impl Future<Output = ()> + Send {
+        let worker = Self {
+            runtime,
+            database,
+            usage_tracking,
+        };
+        async move {
+            tracing::info!("Starting TableStorageSnapshotWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("TableStorageSnapshotWorker died"));
+                tracing::error!("Table storage snapshot worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            let today = self.runtime.unix_timestamp().as_secs() / SECONDS_PER_DAY;
+            let latest_day = {
+                let mut tx = self.database.begin(Identity::system()).await?;
+                TableStorageSnapshotsModel::new(&mut tx)
+                    .latest_day()
+                    .await?
+            };
+            if latest_day.is_none_or(|day| day < today) {
+                self.snapshot(today).await?;
+            }
+            self.delete_expired(today.saturating_sub(*TABLE_STORAGE_SNAPSHOT_RETENTION_DAYS))
+                .await?;
+            backoff.reset();
+            self.runtime.wait(*TABLE_STORAGE_SNAPSHOT_INTERVAL).await;
+        }
+    }
+
+    async fn snapshot(&self, day: u64) -> anyhow::Result<()> {
+        let namespace = TableNamespace::root_component();
+        // Statistics take a read dependency on each table, so compute them in
+        // a transaction that's never committed.
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let snapshot_ts = tx.begin_timestamp();
+        let tables: Vec<_> = tx
+            .table_mapping()
+            .namespace(namespace)
+            .iter_active_user_tables()
+            .map(|(tablet_id, _, table_name)| (tablet_id, table_name.clone()))
+            .collect();
+        let mut snapshots = vec![];
+        for (tablet_id, table_name) in tables {
+            let statistics = TableModel::new(&mut tx)
+                .statistics(namespace, &table_name)
+                .await?;
+            let historical_size = self
+                .database
+                .historical_table_size(tablet_id, snapshot_ts)
+                .await?;
+            snapshots.push(TableStorageSnapshot {
+                day,
+                table_name: table_name.to_string(),
+                document_count: statistics.document_count,
+                document_size: statistics.total_bytes,
+                index_size: statistics
+                    .indexes
+                    .iter()
+                    .map(|index| index.size_bytes)
+                    .sum(),
+                historical_size,
+            });
+        }
+        drop(tx);
+
+        let num_tables = snapshots.len();
+        let mut tx = self.database.begin(Identity::system()).await?;
+        for snapshot in snapshots.clone() {
+            TableStorageSnapshotsModel::new(&mut tx)
+                .insert(snapshot)
+                .await?;
+        }
+        self.database
+            .commit_with_write_source(tx, "table_storage_snapshot")
+            .await?;
+        tracing::info!("Recorded storage snapshots of {num_tables} tables for day {day}");
+        self.usage_tracking.track_table_storage_snapshot(
+            snapshots
+                .into_iter()
+                .map(|snapshot| TableStorageBreakdown {
+                    table_name: snapshot.table_name,
+                    document_size: snapshot.document_size,
+                    index_size: snapshot.index_size,
+                    historical_size: snapshot.historical_size,
+                })
+                .collect(),
+        );
+        Ok(())
+    }
+
+    async fn delete_expired(&self, cutoff_day: u64) -> anyhow::Result<()> {
+        loop {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let expired = TableStorageSnapshotsModel::new(&mut tx)
+                .expired(cutoff_day, *TRASH_PURGE_BATCH_SIZE)
+                .await?;
+            if expired.is_empty() {
+                return Ok(());
+            }
+            let num_expired = expired.len();
+            for id in expired {
+                TableStorageSnapshotsModel::new(&mut tx).delete(id).await?;
+            }
+            self.database
+                .commit_with_write_source(tx, "table_storage_snapshot_cleanup")
+                .await?;
+            if num_expired < *TRASH_PURGE_BATCH_SIZE {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! An in-memory backend for integration tests of services that embed it.
+//!
+//! [`TestBackend`] runs an [`Application`] on in-memory persistence and
+//! temporary storage, with functions executed in-process (and Node actions by
+//! the local executor), on a [`TestRuntime`] whose clock only moves when the
+//! test advances it. Scheduled functions and crons run in the background like
+//! in production, so advancing time is how a test runs them.
+//!
+//! ```ignore
+//! #[convex_macro::test_runtime]
+//! async fn test_messages(rt: TestRuntime) -> anyhow::Result<()> {
+//!     let backend = TestBackend::new(&rt).await?;
+//!     backend.push_modules_from_dir("convex/dist").await?;
+//!     backend
+//!         .mutation("messages:send", assert_obj!("body" => "hi"))
+//!         .await??;
+//!     backend.advance_time(Duration::from_secs(60)).await?;
+//!     let messages = backend.query("messages:list", assert_obj!()).await??;
+//!     Ok(())
+//! }
+//! ```
+
+use std::{
+    fs,
+    path::Path,
+    time::Duration,
+};
+
+use anyhow::Context;
+use common::{
+    components::{
+        ComponentFunctionPath,
+        ComponentPath,
+    },
+    pause::PauseClient,
+    runtime::{
+        testing::TestRuntime,
+        Runtime,
+    },
+    types::{
+        FunctionCaller,
+        ModuleEnvironment,
+    },
+    RequestId,
+};
+use keybroker::Identity;
+use model::{
+    config::types::ModuleConfig,
+    scheduled_jobs::{
+        types::ScheduledJobState,
+        SchedulerModel,
+    },
+};
+use serde_json::Value as JsonValue;
+use sync_types::ModulePath;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    TableNamespace,
+};
+
+use crate::{
+    redaction::RedactedJsError,
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+/// How often [`TestBackend::advance_time`] checks whether the scheduled
+/// functions that are due have finished.
+const SCHEDULER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Most polls [`TestBackend::advance_time`] waits for due scheduled functions,
+/// so a function that schedules itself with no delay can't hang the test.
+const MAX_SCHEDULER_POLLS: usize = 1000;
+
+/// An in-memory backend with the root component's functions pushed from
+/// bundled modules. Cloning it shares the backend.
+#[derive(Clone)]
+pub struct TestBackend {
+    rt: TestRuntime,
+    application: Application<TestRuntime>,
+    identity: Identity,
+}
+
+impl TestBackend {
+    /// Start an empty backend. Functions are called as the system identity,
+    /// which can also call internal functions, until changed with
+    /// [`Self::with_identity`].
+    pub async fn new(rt: &TestRuntime) -> anyhow::Result<Self> {
+        Ok(Self {
+            rt: rt.clone(),
+            application: Application::new_for_tests(rt).await?,
+            identity: Identity::system(),
+        })
+    }
+
+    /// The same backend, calling functions as `identity`.
+    pub fn with_identity(&self, identity: Identity) -> Self {
+        Self {
+            identity,
+            ..self.clone()
+        }
+    }
+
+    pub fn application(&self) -> &Application<TestRuntime> {
+        &self.application
+    }
+
+    /// Replace the backend's modules with the bundled modules in `dir`, like
+    /// a `convex deploy`. Every `.js` file is a module at its path relative
+    /// to `dir`, with the source map in the `.js.map` file next to it if
+    /// there is one. Modules that start with a `"use node"` directive run in
+    /// Node.
+    pub async fn push_modules_from_dir(&self, dir: impl AsRef<Path>) -> anyhow::Result<()> {
+        let dir = dir.as_ref();
+        let mut modules = vec![];
+        read_modules(dir, dir, &mut modules)?;
+        anyhow::ensure!(!modules.is_empty(), "No modules in {}", dir.display());
+        self.application.load_test_modules(modules).await
+    }
+
+    /// Run the query at `path`, like `messages:list`, in the root component.
+    pub async fn query(
+        &self,
+        path: &str,
+        args: ConvexObject,
+    ) -> anyhow::Result<Result<ConvexValue, RedactedJsError>> {
+        let result = self
+            .application
+            .read_only_udf(
+                RequestId::new(),
+                root_function_path(path)?,
+                vec![JsonValue::from(args)],
+                self.identity.clone(),
+                FunctionCaller::HttpEndpoint,
+            )
+            .await?;
+        Ok(result.result)
+    }
+
+    pub async fn mutation(
+        &self,
+        path: &str,
+        args: ConvexObject,
+    ) -> anyhow::Result<Result<ConvexValue, RedactedJsError>> {
+        let result = self
+            .application
+            .mutation_udf(
+                RequestId::new(),
+                root_function_path(path)?,
+                vec![JsonValue::from(args)],
+                self.identity.clone(),
+                None,
+                FunctionCaller::HttpEndpoint,
+                PauseClient::new(),
+            )
+            .await?;
+        Ok(result.map(|r| r.value).map_err(|e| e.error))
+    }
+
+    pub async fn action(
+        &self,
+        path: &str,
+        args: ConvexObject,
+    ) -> anyhow::Result<Result<ConvexValue, RedactedJsError>> {
+        let result = self
+            .application
+            .action_udf(
+                RequestId::new(),
+                root_function_path(path)?,
+                vec![JsonValue::from(args)],
+                self.identity.clone(),
+                FunctionCaller::HttpEndpoint,
+            )
+            .await?;
+        Ok(result.map(|r| r.value).map_err(|e| e.error))
+    }
+
+    /// Move the backend's clock forward by `duration`, running the scheduled
+    /// functions and crons that come due along the way. Returns once the ones
+    /// due by the new time have finished.
+    pub async fn advance_time(&self, duration: Duration) -> anyhow::Result<()> {
+        self.rt.wait(duration).await;
+        for _ in 0..MAX_SCHEDULER_POLLS {
+            if !self.has_due_scheduled_jobs().await? {
+                return Ok(());
+            }
+            self.rt.wait(SCHEDULER_POLL_INTERVAL).await;
+        }
+        anyhow::bail!("Scheduled functions are still running after advancing time by {duration:?}")
+    }
+
+    async fn has_due_scheduled_jobs(&self) -> anyhow::Result<bool> {
+        let mut tx = self.application.begin(Identity::system()).await?;
+        let now = tx.begin_timestamp();
+        let jobs = SchedulerModel::new(&mut tx, TableNamespace::root_component())
+            .list()
+            .await?;
+        Ok(jobs.iter().any(|job| match job.state {
+            ScheduledJobState::Pending => job.next_ts.map_or(false, |ts| ts <= *now),
+            ScheduledJobState::InProgress => true,
+            _ => false,
+        }))
+    }
+}
+
+fn root_function_path(path: &str) -> anyhow::Result<ComponentFunctionPath> {
+    Ok(ComponentFunctionPath {
+        component: ComponentPath::root(),
+        udf_path: path.parse()?,
+    })
+}
+
+fn read_modules(root: &Path, dir: &Path, modules: &mut Vec<ModuleConfig>) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            read_modules(root, &path, modules)?;
+            continue;
+        }
+        if path.extension().map_or(true, |extension| extension != "js") {
+            continue;
+        }
+        let source = fs::read_to_string(&path)?;
+        let source_map_path = path.with_extension("js.map");
+        let source_map = source_map_path
+            .exists()
+            .then(|| fs::read_to_string(&source_map_path))
+            .transpose()?;
+        let environment = if is_node_module(&source) {
+            ModuleEnvironment::Node
+        } else {
+            ModuleEnvironment::Isolate
+        };
+        let relative_path = path.strip_prefix(root)?;
+        let module_path: ModulePath = relative_path
+            .to_str()
+            .with_context(|| format!("Invalid module path {}", relative_path.display()))?
+            .parse()?;
+        modules.push(ModuleConfig {
+            path: module_path,
+            source,
+            source_map,
+            environment,
+        });
+    }
+    Ok(())
+}
+
+fn is_node_module(source: &str) -> bool {
+    let source = source.trim_start();
+    source.starts_with("\"use node\"") || source.starts_with("'use node'")
+}
@@ -55,7 +55,10 @@ use keybroker::{
 };
 use model::{
     config::{
-        types::ConfigMetadata,
+        types::{
+            ConfigMetadata,
+            ModuleConfig,
+        },
         ConfigModel,
     },
     cron_jobs::types::CronJob,
@@ -124,6 +127,8 @@ pub trait ApplicationTestExt<RT: Runtime> {
     /// Load the modules from npm-packages/udf-tests
     async fn load_udf_tests_modules(&self) -> anyhow::Result<()>;
     async fn load_udf_tests_modules_with_node(&self) -> anyhow::Result<()>;
+    /// Push `modules` as the root component's modules, with an empty schema.
+    async fn load_test_modules(&self, modules: Vec<ModuleConfig>) -> anyhow::Result<()>;
     async fn test_one_off_cron_job_executor_run(
         &self,
         job: CronJob,
@@ -202,6 +207,7 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
                 InstanceStorage {
                     files_storage: files_storage.clone(),
                     modules_storage: modules_storage.clone(),
+                    file_scanner: None,
                 },
                 database.clone(),
                 fetch_client.clone(),
@@ -288,49 +294,28 @@ impl<RT: Runtime> ApplicationTestExt<RT> for Application<RT> {
     }
 
     async fn load_udf_tests_modules(&self) -> anyhow::Result<()> {
-        self.load_udf_tests_modules_inner(false).await
+        self.load_test_modules(TEST_SOURCE_ISOLATE_ONLY.clone())
+            .await
     }
 
     async fn load_udf_tests_modules_with_node(&self) -> anyhow::Result<()> {
-        self.load_udf_tests_modules_inner(true).await
+        self.load_test_modules(TEST_SOURCE.clone()).await
     }
 
-    fn validate_user_defined_index_fields(
-        &self,
-        fields: IndexedFields,
-    ) -> anyhow::Result<IndexedFields> {
-        self._validate_user_defined_index_fields(fields)
-    }
-
-    fn database(&self) -> &Database<RT> {
-        &self.database
-    }
-}
-
-impl<RT: Runtime> Application<RT> {
-    async fn load_udf_tests_modules_inner(&self, include_node: bool) -> anyhow::Result<()> {
-        let test_source = if include_node {
-            TEST_SOURCE.clone()
-        } else {
-            TEST_SOURCE_ISOLATE_ONLY.clone()
-        };
+    async fn load_test_modules(&self, modules: Vec<ModuleConfig>) -> anyhow::Result<()> {
         let mut tx = self.begin(Identity::system()).await?;
         let udf_config = UdfConfig::new_for_test(&self.runtime(), "1000.0.0".parse()?);
         // TODO(rakeeb): add external packages to udf test modules
-        let source_package = self.upload_package(&test_source, None).await?;
+        let source_package = self.upload_package(&modules, None).await?;
         let analyze_results = self
-            .analyze(
-                udf_config.clone(),
-                test_source.clone(),
-                source_package.clone(),
-            )
+            .analyze(udf_config.clone(), modules.clone(), source_package.clone())
             .await??;
         let schema_id = insert_validated_schema(&mut tx).await?;
 
         ConfigModel::new(&mut tx)
             .apply(
                 ConfigMetadata::new(),
-                test_source.clone(),
+                modules,
                 udf_config,
                 Some(source_package),
                 analyze_results,
@@ -340,6 +325,17 @@ impl<RT: Runtime> Application<RT> {
         self.commit_test(tx).await?;
         Ok(())
     }
+
+    fn validate_user_defined_index_fields(
+        &self,
+        fields: IndexedFields,
+    ) -> anyhow::Result<IndexedFields> {
+        self._validate_user_defined_index_fields(fields)
+    }
+
+    fn database(&self) -> &Database<RT> {
+        &self.database
+    }
 }
 
 // The contents of the schema are irrelevant for the modules, but we need one to
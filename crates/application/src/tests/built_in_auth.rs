@@ -0,0 +1,111 @@
+use std::time::Duration;
+
+use common::runtime::Runtime;
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::Identity;
+use model::built_in_auth::{
+    types::AuthCodeKind,
+    BuiltInAuthModel,
+};
+use runtime::testing::TestRuntime;
+use sync_types::AuthenticationToken;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_sign_up_and_sign_in(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let session = application
+        .sign_up("Ada@example.com", "correct horse battery staple")
+        .await?;
+    let identity = application
+        .authenticate(AuthenticationToken::User(session.token), rt.system_time())
+        .await?;
+    let Identity::User(user) = &identity else {
+        panic!("Expected a user identity");
+    };
+    assert_eq!(user.attributes.email.as_deref(), Some("ada@example.com"));
+    assert_eq!(user.attributes.email_verified, Some(false));
+
+    let session = application
+        .sign_in("ada@example.com", "correct horse battery staple")
+        .await?;
+    let refreshed = application.refresh_session(&identity).await?;
+    assert!(refreshed.expires_at >= session.expires_at);
+
+    let err = application
+        .sign_in("ada@example.com", "Tr0ub4dor&3")
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidCredentials");
+    // Email addresses without accounts fail the same way.
+    let err = application
+        .sign_in("grace@example.com", "correct horse battery staple")
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidCredentials");
+    let err = application
+        .sign_up("ada@example.com", "another long password")
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "AccountExists");
+    let err = application
+        .sign_up("grace@example.com", "short")
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "PasswordTooShort");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_auth_codes(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    // Magic links need the app to be able to send emails.
+    let err = application
+        .send_magic_link("ada@example.com")
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "BuiltInAuthEmailNotConfigured");
+
+    application
+        .sign_up("ada@example.com", "correct horse battery staple")
+        .await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = BuiltInAuthModel::new(&mut tx);
+    let account = model
+        .get_account_by_email(&"ada@example.com".parse()?)
+        .await?
+        .unwrap();
+    let code = model
+        .create_code(
+            account.id().into(),
+            AuthCodeKind::VerifyEmail,
+            Duration::from_secs(60),
+        )
+        .await?;
+    application.commit_test(tx).await?;
+
+    // Codes only work for their own kind.
+    let err = application
+        .sign_in_with_magic_link(&code)
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidAuthCode");
+    let session = application.verify_email(&code).await?;
+    let identity = application
+        .authenticate(AuthenticationToken::User(session.token), rt.system_time())
+        .await?;
+    let Identity::User(user) = identity else {
+        panic!("Expected a user identity");
+    };
+    assert_eq!(user.attributes.email_verified, Some(true));
+
+    // Codes can only be redeemed once.
+    let err = application.verify_email(&code).await.unwrap_err();
+    assert!(err.is_unauthenticated());
+    assert_eq!(err.short_msg(), "InvalidAuthCode");
+    Ok(())
+}
@@ -46,6 +46,7 @@ use model::{
     modules::{
         function_validators::{
             ArgsValidator,
+            ErrorsValidator,
             ReturnsValidator,
         },
         module_versions::{
@@ -118,6 +119,9 @@ bar.invokeAction = async (requestId, argsStr) => {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: None,
             }]
             .into(),
             http_routes: None,
@@ -161,6 +165,7 @@ bar.invokeAction = async (requestId, argsStr) => {
         let component = ComponentMetadata {
             definition_id: definition_id.internal_id(),
             component_type: ComponentType::App,
+            quotas: Default::default(),
         };
         let component_id = SystemMetadataModel::new_global(&mut tx)
             .insert(&COMPONENTS_TABLE, component.try_into()?)
@@ -197,6 +202,7 @@ bar.invokeAction = async (requestId, argsStr) => {
                     "maxLength".parse()? => Resource::Value(ConvexValue::Float64(10.)),
                 },
             },
+            quotas: Default::default(),
         };
         SystemMetadataModel::new_global(&mut tx)
             .insert(&COMPONENTS_TABLE, component.try_into()?)
@@ -34,8 +34,11 @@ use model::{
     },
     cron_jobs::{
         types::{
+            CronCatchUpPolicy,
             CronIdentifier,
             CronJob,
+            CronJobLog,
+            CronJobStatus,
             CronSchedule,
             CronSpec,
         },
@@ -62,6 +65,7 @@ fn test_cron_identifier() -> CronIdentifier {
 
 async fn create_cron_job(
     tx: &mut Transaction<TestRuntime>,
+    catch_up: CronCatchUpPolicy,
 ) -> anyhow::Result<(
     BTreeMap<CronIdentifier, ParsedDocument<CronJob>>,
     CronModel<TestRuntime>,
@@ -80,6 +84,9 @@ async fn create_cron_job(
         udf_path: path.as_root_udf_path()?.clone().canonicalize(),
         udf_args: parse_udf_args(&path, vec![JsonValue::Object(map)])?,
         cron_schedule: CronSchedule::Interval { seconds: 60 },
+        timezone: None,
+        jitter_seconds: None,
+        catch_up,
     };
     let original_jobs = cron_model.list().await?;
     let name = test_cron_identifier();
@@ -113,7 +120,7 @@ pub(crate) async fn test_cron_jobs_success(rt: TestRuntime) -> anyhow::Result<()
 
     let mut tx = application.begin(Identity::system()).await?;
 
-    let (original_jobs, mut cron_model) = create_cron_job(&mut tx).await?;
+    let (original_jobs, mut cron_model) = create_cron_job(&mut tx, CronCatchUpPolicy::Skip).await?;
 
     let jobs = cron_model.list().await?;
     assert_eq!(jobs.len(), original_jobs.len() + 1);
@@ -148,7 +155,7 @@ pub(crate) async fn test_cron_jobs_race_condition(rt: TestRuntime) -> anyhow::Re
     application.load_udf_tests_modules().await?;
 
     let mut tx = application.begin(Identity::system()).await?;
-    let (original_jobs, mut model) = create_cron_job(&mut tx).await?;
+    let (original_jobs, mut model) = create_cron_job(&mut tx, CronCatchUpPolicy::Skip).await?;
 
     let jobs = model.list().await?;
     assert_eq!(jobs.len(), original_jobs.len() + 1);
@@ -171,6 +178,73 @@ pub(crate) async fn test_cron_jobs_race_condition(rt: TestRuntime) -> anyhow::Re
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_cron_jobs_catch_up_run_once(rt: TestRuntime) -> anyhow::Result<()> {
+    test_cron_jobs_catch_up_run_once_helper(rt, CronCatchUpPolicy::RunOnce).await
+}
+
+// Crons that don't set a policy still run once after downtime, like they did
+// before catch-up policies existed.
+#[convex_macro::test_runtime]
+async fn test_cron_jobs_catch_up_default(rt: TestRuntime) -> anyhow::Result<()> {
+    test_cron_jobs_catch_up_run_once_helper(rt, CronCatchUpPolicy::default()).await
+}
+
+async fn test_cron_jobs_catch_up_run_once_helper(
+    rt: TestRuntime,
+    catch_up: CronCatchUpPolicy,
+) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    // Pause the backend so only the one-off executor below runs the job.
+    let mut tx = application.begin(Identity::system()).await?;
+    BackendStateModel::new(&mut tx)
+        .toggle_backend_state(BackendState::Paused)
+        .await?;
+    application.commit_test(tx).await?;
+
+    // Pretend the backend was down for the last ten runs of the job.
+    let mut tx = application.begin(Identity::system()).await?;
+    let (_, mut model) = create_cron_job(&mut tx, catch_up).await?;
+    let jobs = model.list().await?;
+    let (job_id, mut job) = jobs
+        .get(&test_cron_identifier())
+        .unwrap()
+        .clone()
+        .into_id_and_value();
+    job.next_ts = rt.generate_timestamp()?.sub(Duration::from_secs(600))?;
+    model.update_job_state(job_id, job.clone()).await?;
+    application.commit_test(tx).await?;
+
+    application
+        .test_one_off_cron_job_executor_run(job.clone(), job_id)
+        .await?;
+
+    // Every run but the most recent missed one is skipped.
+    let mut tx = application.begin(Identity::system()).await?;
+    let jobs = CronModel::new(&mut tx, ComponentId::test_user())
+        .list()
+        .await?;
+    let updated_job = jobs.get(&test_cron_identifier()).unwrap();
+    assert!(updated_job.next_ts > job.next_ts);
+    assert!(updated_job.next_ts <= rt.generate_timestamp()?);
+    let mut logs_query = cron_log_query(&mut tx, ComponentId::test_user())?;
+    let log = CronJobLog::try_from(
+        logs_query
+            .next(&mut tx, None)
+            .await?
+            .unwrap()
+            .into_value()
+            .0,
+    )?;
+    assert!(matches!(
+        log.status,
+        CronJobStatus::Canceled { num_canceled } if num_canceled >= 9
+    ));
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_paused_cron_jobs(rt: TestRuntime) -> anyhow::Result<()> {
     test_cron_jobs_helper(rt, BackendState::Paused).await?;
@@ -199,7 +273,7 @@ async fn test_cron_jobs_helper(rt: TestRuntime, backend_state: BackendState) ->
     application.commit_test(tx).await?;
 
     let mut tx = application.begin(Identity::system()).await?;
-    let (original_jobs, mut cron_model) = create_cron_job(&mut tx).await?;
+    let (original_jobs, mut cron_model) = create_cron_job(&mut tx, CronCatchUpPolicy::Skip).await?;
     let jobs = cron_model.list().await?;
     assert_eq!(jobs.len(), original_jobs.len() + 1);
     let mut table_model = TableModel::new(&mut tx);
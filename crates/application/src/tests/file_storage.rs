@@ -0,0 +1,271 @@
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use bytes::Bytes;
+use common::{
+    components::ComponentId,
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::UserFacingModel;
+use errors::ErrorMetadataAnyhowExt;
+use futures::StreamExt;
+use headers::ContentType;
+use keybroker::Identity;
+use model::{
+    file_lifecycle_rules::types::{
+        FileLifecycleRule,
+        LifecycleAction,
+        LifecycleRunReport,
+    },
+    file_storage::{
+        types::FileStorageFilter,
+        FileStorageId,
+    },
+};
+use runtime::testing::TestRuntime;
+use value::{
+    id_v6::DeveloperDocumentId,
+    obj,
+    TableNamespace,
+};
+
+use crate::{
+    file_lifecycle::FileLifecycleWorker,
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+async fn store_file(
+    application: &Application<TestRuntime>,
+    content_type: ContentType,
+    contents: &'static [u8],
+) -> anyhow::Result<DeveloperDocumentId> {
+    application
+        .store_file(
+            ComponentId::Root,
+            None,
+            Some(content_type),
+            None,
+            futures::stream::iter(vec![Ok(Bytes::from_static(contents))]).boxed(),
+        )
+        .await
+}
+
+async fn set_file_metadata(
+    application: &Application<TestRuntime>,
+    storage_id: DeveloperDocumentId,
+    tags: Option<BTreeSet<String>>,
+    metadata: Option<BTreeMap<String, String>>,
+) -> anyhow::Result<()> {
+    let mut tx = application.begin(Identity::system()).await?;
+    application
+        .file_storage
+        .transactional_file_storage
+        .set_file_metadata(
+            &mut tx,
+            TableNamespace::root_component(),
+            FileStorageId::DocumentId(storage_id),
+            tags,
+            metadata,
+        )
+        .await?;
+    application.commit_test(tx).await?;
+    Ok(())
+}
+
+async fn list_files(
+    application: &Application<TestRuntime>,
+    filter: FileStorageFilter,
+) -> anyhow::Result<Vec<DeveloperDocumentId>> {
+    let page = application
+        .list_files(Identity::system(), filter, None, 100)
+        .await?;
+    assert!(page.cursor.is_none());
+    Ok(page.files.into_iter().map(|(id, _)| id).collect())
+}
+
+#[convex_macro::test_runtime]
+async fn test_list_files_with_filters(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let png = store_file(&application, ContentType::png(), b"png").await?;
+    let jpeg = store_file(&application, ContentType::jpeg(), b"a larger jpeg").await?;
+    let text = store_file(&application, ContentType::text(), b"some text").await?;
+
+    set_file_metadata(
+        &application,
+        png,
+        Some(BTreeSet::from(["thumbnail".to_string()])),
+        Some(BTreeMap::from([("owner".to_string(), "alice".to_string())])),
+    )
+    .await?;
+    // Setting only the tags leaves the metadata unchanged.
+    set_file_metadata(
+        &application,
+        png,
+        Some(BTreeSet::from(["old".to_string(), "thumbnail".to_string()])),
+        None,
+    )
+    .await?;
+
+    assert_eq!(
+        list_files(&application, FileStorageFilter::default()).await?,
+        vec![png, jpeg, text]
+    );
+    let images = FileStorageFilter {
+        content_type: Some("image/".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(list_files(&application, images).await?, vec![png, jpeg]);
+    let large = FileStorageFilter {
+        min_size: Some(5),
+        ..Default::default()
+    };
+    assert_eq!(list_files(&application, large).await?, vec![jpeg, text]);
+    let old = FileStorageFilter {
+        tag: Some("old".to_string()),
+        ..Default::default()
+    };
+    let page = application
+        .list_files(Identity::system(), old, None, 100)
+        .await?;
+    let [(id, file)] = &page.files[..] else {
+        panic!("Expected one file, got {}", page.files.len());
+    };
+    assert_eq!(*id, png);
+    assert_eq!(file.tags.len(), 2);
+    assert_eq!(file.metadata["owner"], "alice");
+
+    // Page through the files one at a time.
+    let page = application
+        .list_files(Identity::system(), FileStorageFilter::default(), None, 1)
+        .await?;
+    assert_eq!(page.files[0].0, png);
+    let page = application
+        .list_files(
+            Identity::system(),
+            FileStorageFilter::default(),
+            page.cursor,
+            1,
+        )
+        .await?;
+    assert_eq!(page.files[0].0, jpeg);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_invalid_file_metadata(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let storage_id = store_file(&application, ContentType::text(), b"text").await?;
+    let err = set_file_metadata(
+        &application,
+        storage_id,
+        None,
+        Some(BTreeMap::from([(
+            "not a field".to_string(),
+            "value".to_string(),
+        )])),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidFileMetadata");
+    let err = set_file_metadata(
+        &application,
+        storage_id,
+        Some(BTreeSet::from(["x".repeat(65)])),
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.short_msg(), "InvalidFileMetadata");
+    Ok(())
+}
+
+async fn apply_lifecycle_rule(
+    worker: &FileLifecycleWorker<TestRuntime>,
+    rule: &FileLifecycleRule,
+    now: SystemTime,
+) -> anyhow::Result<LifecycleRunReport> {
+    let mut report = LifecycleRunReport {
+        started_ts: Timestamp::MIN,
+        finished_ts: Timestamp::MIN,
+        dry_run: rule.dry_run,
+        files_matched: 0,
+        bytes_matched: 0,
+        files_applied: 0,
+        sample_storage_ids: vec![],
+        error: None,
+    };
+    worker.apply_rule(rule, now, &mut report).await?;
+    Ok(report)
+}
+
+#[convex_macro::test_runtime]
+async fn test_file_lifecycle_rules(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let orphan = store_file(&application, ContentType::text(), b"orphan").await?;
+    let referenced = store_file(&application, ContentType::text(), b"referenced").await?;
+    let mut tx = application.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            "messages".parse()?,
+            obj!("attachment" => obj!("storageId" => referenced.encode())?)?,
+        )
+        .await?;
+    application.commit_test(tx).await?;
+
+    let worker = FileLifecycleWorker::new(
+        rt.clone(),
+        application.database().clone(),
+        application.files_storage(),
+    );
+    let mut rule = FileLifecycleRule {
+        name: "orphans".to_string(),
+        min_age_days: 1,
+        only_orphaned: true,
+        action: LifecycleAction::Delete,
+        dry_run: true,
+        last_run: None,
+    };
+    // The files aren't old enough yet.
+    let report = apply_lifecycle_rule(&worker, &rule, rt.system_time()).await?;
+    assert_eq!(report.files_matched, 0);
+
+    // A dry run reports the orphaned file without deleting it.
+    let in_two_days = rt.system_time() + Duration::from_secs(2 * 24 * 60 * 60);
+    let report = apply_lifecycle_rule(&worker, &rule, in_two_days).await?;
+    assert_eq!(report.files_matched, 1);
+    assert_eq!(report.bytes_matched, 6);
+    assert_eq!(report.files_applied, 0);
+    assert_eq!(report.sample_storage_ids, vec![orphan.encode()]);
+    assert_eq!(
+        list_files(&application, FileStorageFilter::default()).await?,
+        vec![orphan, referenced]
+    );
+
+    rule.dry_run = false;
+    let report = apply_lifecycle_rule(&worker, &rule, in_two_days).await?;
+    assert_eq!(report.files_applied, 1);
+    assert_eq!(
+        list_files(&application, FileStorageFilter::default()).await?,
+        vec![referenced]
+    );
+
+    // Local storage has no storage classes to move files to.
+    rule.only_orphaned = false;
+    rule.action = LifecycleAction::Transition {
+        storage_class: "COLD".to_string(),
+    };
+    assert!(apply_lifecycle_rule(&worker, &rule, in_two_days)
+        .await
+        .is_err());
+    Ok(())
+}
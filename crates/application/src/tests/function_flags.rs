@@ -0,0 +1,90 @@
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentFunctionPath,
+        ComponentPath,
+    },
+    types::FunctionCaller,
+    RequestId,
+};
+use keybroker::{
+    testing::TestUserIdentity,
+    Identity,
+    UserIdentity,
+};
+use model::function_flags::{
+    types::{
+        FunctionFlag,
+        FunctionFlagMode,
+    },
+    FunctionFlagsModel,
+};
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use value::ConvexValue;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+    RedactedQueryReturn,
+};
+
+async fn count_objects(
+    application: &Application<TestRuntime>,
+    identity: Identity,
+) -> anyhow::Result<RedactedQueryReturn> {
+    application
+        .read_only_udf(
+            RequestId::new(),
+            ComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "query_cache:countObjects".parse()?,
+            },
+            vec![json!({})],
+            identity,
+            FunctionCaller::HttpEndpoint,
+        )
+        .await
+}
+
+#[convex_macro::test_runtime]
+async fn test_disabled_query(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let user = Identity::user(UserIdentity::test());
+
+    // Cache a result first, so the flag has to apply to cache hits too.
+    let result = count_objects(&application, user.clone()).await?;
+    assert_eq!(result.result?, ConvexValue::from(0.));
+
+    let path = CanonicalizedComponentFunctionPath {
+        component: ComponentPath::root(),
+        udf_path: "query_cache.js:countObjects".parse()?,
+    };
+    let mut tx = application.begin(Identity::system()).await?;
+    FunctionFlagsModel::new(&mut tx)
+        .set(FunctionFlag {
+            path: path.clone(),
+            mode: FunctionFlagMode::Disabled,
+            reason: Some("under maintenance".to_string()),
+        })
+        .await?;
+    application.commit_test(tx).await?;
+
+    let err = count_objects(&application, user.clone())
+        .await?
+        .result
+        .unwrap_err();
+    assert!(err.to_string().contains("under maintenance"), "{err}");
+
+    // The deployment itself can still call the function.
+    let result = count_objects(&application, Identity::system()).await?;
+    assert_eq!(result.result?, ConvexValue::from(0.));
+
+    let mut tx = application.begin(Identity::system()).await?;
+    FunctionFlagsModel::new(&mut tx).delete(&path).await?;
+    application.commit_test(tx).await?;
+    let result = count_objects(&application, user).await?;
+    assert_eq!(result.result?, ConvexValue::from(0.));
+    Ok(())
+}
@@ -1,14 +1,21 @@
 mod analyze;
 mod auth_config;
+mod built_in_auth;
 mod components;
 mod cron_jobs;
 mod environment_variables;
+mod file_storage;
+mod function_flags;
 mod mutation;
 mod occ_retries;
+mod query_cache;
+mod queues;
 mod returns_validation;
+mod revoked_sessions;
 mod scheduled_jobs;
 mod schema;
 mod source_package;
+mod test_harness;
 
 const NODE_SOURCE: &str = r#"
 var nodeFunction = () => {};
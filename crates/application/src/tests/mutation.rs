@@ -14,6 +14,7 @@ use common::{
 };
 use errors::ErrorMetadataAnyhowExt;
 use keybroker::Identity;
+use model::idempotency_keys::types::MutationIdentifier;
 use runtime::testing::TestRuntime;
 use serde_json::{
     json,
@@ -42,6 +43,7 @@ async fn insert_object(
             None,
             FunctionCaller::Action {
                 parent_scheduled_job: None,
+                trace_parent: None,
             },
             pause_client,
         )
@@ -66,6 +68,7 @@ async fn insert_and_count(
             None,
             FunctionCaller::Action {
                 parent_scheduled_job: None,
+                trace_parent: None,
             },
             pause_client,
         )
@@ -75,6 +78,33 @@ async fn insert_and_count(
         .context("Expected f64 result")? as usize)
 }
 
+async fn insert_and_count_with_key(
+    application: &Application<TestRuntime>,
+    idempotency_key: &str,
+    obj: JsonValue,
+) -> anyhow::Result<usize> {
+    let result = application
+        .mutation_udf(
+            RequestId::new(),
+            ComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "basic:insertAndCount".parse()?,
+            },
+            vec![obj],
+            Identity::system(),
+            Some(MutationIdentifier::IdempotencyKey(idempotency_key.parse()?)),
+            FunctionCaller::Action {
+                parent_scheduled_job: None,
+                trace_parent: None,
+            },
+            PauseClient::new(),
+        )
+        .await??;
+    Ok(JsonValue::from(result.value)
+        .as_f64()
+        .context("Expected f64 result")? as usize)
+}
+
 #[convex_macro::test_runtime]
 async fn test_mutation(rt: TestRuntime) -> anyhow::Result<()> {
     let application = Application::new_for_tests(&rt).await?;
@@ -175,3 +205,27 @@ async fn test_multiple_inserts_dont_occ(rt: TestRuntime) -> anyhow::Result<()> {
     assert_eq!(result["an"], "object");
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_mutation_idempotency_key(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let obj = json!({"an": "object"});
+    let count = insert_and_count_with_key(&application, "key-1", obj.clone()).await?;
+    assert_eq!(count, 1);
+
+    // Retrying with the same key returns the first result without inserting.
+    let count = insert_and_count_with_key(&application, "key-1", obj.clone()).await?;
+    assert_eq!(count, 1);
+
+    let count = insert_and_count_with_key(&application, "key-2", obj).await?;
+    assert_eq!(count, 2);
+
+    // Reusing a key with different arguments is an error.
+    let err = insert_and_count_with_key(&application, "key-1", json!({"another": "object"}))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "IdempotencyKeyReused");
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use common::{
+    components::{
+        ComponentFunctionPath,
+        ComponentPath,
+    },
+    pause::PauseClient,
+    types::FunctionCaller,
+    RequestId,
+};
+use keybroker::{
+    testing::TestUserIdentity,
+    Identity,
+    UserIdentity,
+};
+use runtime::testing::TestRuntime;
+use serde_json::json;
+use value::ConvexValue;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+    RedactedQueryReturn,
+};
+
+async fn count_objects(
+    application: &Application<TestRuntime>,
+) -> anyhow::Result<RedactedQueryReturn> {
+    application
+        .read_only_udf(
+            RequestId::new(),
+            ComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "query_cache:countObjects".parse()?,
+            },
+            vec![json!({})],
+            Identity::user(UserIdentity::test()),
+            FunctionCaller::HttpEndpoint,
+        )
+        .await
+}
+
+async fn insert_object(application: &Application<TestRuntime>) -> anyhow::Result<()> {
+    application
+        .mutation_udf(
+            RequestId::new(),
+            ComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: "basic:insertObject".parse()?,
+            },
+            vec![json!({"an": "object"})],
+            Identity::system(),
+            None,
+            FunctionCaller::Action {
+                parent_scheduled_job: None,
+                trace_parent: None,
+            },
+            PauseClient::new(),
+        )
+        .await??;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_cache_ttl(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let result = count_objects(&application).await?;
+    assert_eq!(result.result?, ConvexValue::from(0.));
+    assert!(result.cache_expiry.is_none());
+
+    // The cached result is served within the TTL even though it's outdated.
+    insert_object(&application).await?;
+    let result = count_objects(&application).await?;
+    assert_eq!(result.result?, ConvexValue::from(0.));
+    let expiry = result.cache_expiry.expect("Outdated result should expire");
+    assert!(expiry.expires_in.as_secs() <= 60);
+
+    // Invalidating the cache recomputes the result.
+    assert!(application
+        .invalidate_query_cache(Identity::user(UserIdentity::test()), None)
+        .is_err());
+    assert_eq!(
+        application.invalidate_query_cache(Identity::system(), None)?,
+        1
+    );
+    let result = count_objects(&application).await?;
+    assert_eq!(result.result?, ConvexValue::from(1.));
+    assert!(result.cache_expiry.is_none());
+    Ok(())
+}
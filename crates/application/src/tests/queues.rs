@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use common::{
+    document::ParsedDocument,
+    execution_context::ExecutionContext,
+    runtime::Runtime,
+};
+use database::TableModel;
+use keybroker::Identity;
+use model::queues::{
+    types::{
+        QueueConsumer,
+        QueueDeliveryOutcome,
+        QueueMessage,
+    },
+    QueueModel,
+};
+use runtime::testing::TestRuntime;
+use sync_types::UdfPath;
+use value::{
+    ConvexValue,
+    TableNamespace,
+};
+
+use crate::{
+    test_helpers::{
+        ApplicationTestExt,
+        OBJECTS_TABLE,
+        OBJECTS_TABLE_COMPONENT,
+    },
+    Application,
+};
+
+fn consumer(queue: &str, group: &str) -> anyhow::Result<QueueConsumer> {
+    Ok(QueueConsumer {
+        queue: queue.to_string(),
+        group: group.to_string(),
+        handler: "basic:insertObject".parse::<UdfPath>()?.canonicalize(),
+        visibility_timeout: Duration::from_secs(10),
+        max_attempts: 3,
+        max_concurrency: None,
+    })
+}
+
+#[convex_macro::test_runtime]
+async fn test_queue_delivery(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = QueueModel::new(&mut tx, TableNamespace::test_user());
+    model
+        .register_consumer(consumer("emails", "sender")?)
+        .await?;
+    let num_groups = model
+        .enqueue(
+            "emails",
+            ConvexValue::try_from("hello".to_string())?,
+            Duration::ZERO,
+        )
+        .await?;
+    assert_eq!(num_groups, 1);
+    let err = model
+        .enqueue(
+            "unknown",
+            ConvexValue::try_from("hello".to_string())?,
+            Duration::ZERO,
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err}").contains("has no consumer groups"));
+    application.commit_test(tx).await?;
+
+    // The dispatcher schedules the handler, and deletes the message once its
+    // visibility timeout passes since the handler succeeded.
+    rt.wait(Duration::from_secs(100)).await;
+    let mut tx = application.begin(Identity::system()).await?;
+    assert!(
+        !TableModel::new(&mut tx)
+            .table_is_empty(OBJECTS_TABLE_COMPONENT.into(), &OBJECTS_TABLE)
+            .await?
+    );
+    let now = rt.generate_timestamp()?;
+    let (messages, next_visible_ts) = QueueModel::new(&mut tx, TableNamespace::test_user())
+        .visible_messages(now, 10)
+        .await?;
+    assert!(messages.is_empty());
+    assert_eq!(next_visible_ts, None);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_queue_ack(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let mut model = QueueModel::new(&mut tx, TableNamespace::test_user());
+    model
+        .register_consumer(consumer("jobs", "workers")?)
+        .await?;
+    model
+        .enqueue("jobs", ConvexValue::Int64(1017), Duration::ZERO)
+        .await?;
+    let (mut messages, _) = model.visible_messages(rt.generate_timestamp()?, 10).await?;
+    assert_eq!(messages.len(), 1);
+    let message = messages.pop().unwrap();
+    let message_id = message.id();
+    let outcome = model
+        .deliver(message, ExecutionContext::new_for_test())
+        .await?;
+    assert_eq!(outcome, QueueDeliveryOutcome::Delivered);
+
+    // The message is hidden until its visibility timeout passes.
+    let (messages, next_visible_ts) = model.visible_messages(rt.generate_timestamp()?, 10).await?;
+    assert!(messages.is_empty());
+    assert!(next_visible_ts.is_some());
+
+    let message: ParsedDocument<QueueMessage> = tx.get(message_id).await?.unwrap().try_into()?;
+    assert_eq!(message.attempts, 1);
+    let receipt = message.delivery.clone().unwrap().receipt;
+    let mut model = QueueModel::new(&mut tx, TableNamespace::test_user());
+    let err = model
+        .ack(message_id.into(), "not-a-receipt")
+        .await
+        .unwrap_err();
+    assert!(format!("{err}").contains("delivered again"));
+    model.ack(message_id.into(), &receipt).await?;
+    assert!(tx.get(message_id).await?.is_none());
+    Ok(())
+}
@@ -14,6 +14,7 @@ use keybroker::{
 };
 use runtime::testing::TestRuntime;
 use serde_json::json;
+use value::assert_obj;
 
 use crate::{
     test_helpers::ApplicationTestExt,
@@ -170,3 +171,33 @@ async fn test_action_output(rt: TestRuntime) -> anyhow::Result<()> {
     assert!(format!("{}", result.unwrap().value).contains("hello"));
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_query_typed_error(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    let result = run_zero_arg_query(&application, "returns_validation:throwsDeclaredError").await?;
+    let error = result.result.unwrap_err();
+    assert_eq!(error.error_code(), Some("NotFound"));
+    assert_eq!(
+        error.custom_data_if_any(),
+        Some(assert_obj!("code" => "NotFound", "data" => assert_obj!("id" => "abc")).into())
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_invalid_typed_error(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+    for name in [
+        "returns_validation:throwsUndeclaredErrorCode",
+        "returns_validation:throwsInvalidErrorData",
+    ] {
+        let result = run_zero_arg_query(&application, name).await?;
+        let error = result.result.unwrap_err();
+        assert!(format!("{error}").contains("ErrorValidationError"));
+        assert_eq!(error.error_code(), None);
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use errors::ErrorMetadataAnyhowExt;
+use keybroker::{
+    testing::TestUserIdentity,
+    Identity,
+    UserIdentity,
+};
+use model::revoked_sessions::RevokedSessionsModel;
+use runtime::testing::TestRuntime;
+
+use crate::{
+    test_helpers::ApplicationTestExt,
+    Application,
+};
+
+#[convex_macro::test_runtime]
+async fn test_revoked_sessions(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    let user = UserIdentity::test();
+    let identity = Identity::user(user.clone());
+    application.ensure_session_not_revoked(&identity).await?;
+
+    // Revoking another of the subject's tokens doesn't affect this session.
+    let mut tx = application.begin(Identity::system()).await?;
+    RevokedSessionsModel::new(&mut tx)
+        .revoke(user.subject.clone(), None, Some("other-token".to_string()))
+        .await?;
+    application.commit_test(tx).await?;
+    application.ensure_session_not_revoked(&identity).await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    RevokedSessionsModel::new(&mut tx)
+        .revoke(user.subject.clone(), Some(user.issuer.clone()), None)
+        .await?;
+    application.commit_test(tx).await?;
+    let err = application
+        .ensure_session_not_revoked(&identity)
+        .await
+        .unwrap_err();
+    assert!(err.is_unauthenticated());
+    assert_eq!(err.short_msg(), "SessionRevoked");
+
+    // Only user sessions can be revoked.
+    application
+        .ensure_session_not_revoked(&Identity::system())
+        .await?;
+    Ok(())
+}
@@ -24,13 +24,21 @@ use database::{
 };
 use isolate::parse_udf_args;
 use keybroker::Identity;
+use maplit::btreemap;
 use model::{
     backend_state::{
         types::BackendState,
         BackendStateModel,
     },
+    scheduled_job_batches::ScheduledJobBatchModel,
     scheduled_jobs::{
-        types::ScheduledJobState,
+        types::{
+            ScheduleOptions,
+            ScheduledJobFilter,
+            ScheduledJobPriority,
+            ScheduledJobState,
+            ScheduledJobStateKind,
+        },
         SchedulerModel,
     },
 };
@@ -79,6 +87,7 @@ async fn create_scheduled_job<'a>(
             path.udf_path.clone(),
             parse_udf_args(&path, vec![JsonValue::Object(map)])?,
             rt.unix_timestamp(),
+            ScheduleOptions::default(),
             ExecutionContext::new_for_test(),
         )
         .await?;
@@ -126,6 +135,183 @@ async fn test_scheduled_jobs_success(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_schedule_options(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let path = function_path();
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    model
+        .schedule(
+            path.udf_path.clone(),
+            parse_udf_args(&path, vec![])?,
+            rt.unix_timestamp(),
+            ScheduleOptions {
+                priority: ScheduledJobPriority::Low,
+                max_concurrency: Some(2),
+            },
+            ExecutionContext::new_for_test(),
+        )
+        .await?;
+    let jobs = model.list().await?;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].priority, ScheduledJobPriority::Low);
+    assert_eq!(jobs[0].max_concurrency, Some(2));
+
+    let err = model
+        .schedule(
+            path.udf_path.clone(),
+            parse_udf_args(&path, vec![])?,
+            rt.unix_timestamp(),
+            ScheduleOptions {
+                priority: ScheduledJobPriority::Normal,
+                max_concurrency: Some(0),
+            },
+            ExecutionContext::new_for_test(),
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err}").contains("maxConcurrency must be greater than 0"));
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_schedule_batch(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let path = function_path();
+    let jobs = (0..3)
+        .map(|_| {
+            Ok((
+                path.udf_path.clone(),
+                parse_udf_args(&path, vec![])?,
+                rt.unix_timestamp(),
+                ScheduleOptions::default(),
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let batch_id = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .schedule_batch(jobs, ExecutionContext::new_for_test())
+        .await?;
+    // The jobs aren't in the scheduled jobs table until the batch is expanded.
+    assert!(SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .list()
+        .await?
+        .is_empty());
+
+    let chunk = ScheduledJobBatchModel::new(&mut tx, TableNamespace::test_user())
+        .next_chunk()
+        .await?
+        .unwrap();
+    assert_eq!(chunk.batch_id, batch_id);
+    let num_jobs = SchedulerModel::new(&mut tx, TableNamespace::test_user())
+        .expand_batch_chunk(chunk)
+        .await?;
+    assert_eq!(num_jobs, 3);
+    assert!(
+        ScheduledJobBatchModel::new(&mut tx, TableNamespace::test_user())
+            .next_chunk()
+            .await?
+            .is_none()
+    );
+
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    let jobs = model.list().await?;
+    assert_eq!(jobs.len(), 3);
+    for job in &jobs {
+        assert_eq!(job.batch_id.as_deref(), Some(batch_id.as_str()));
+        assert_eq!(job.state, ScheduledJobState::Pending);
+    }
+
+    // Large batches are canceled a page at a time.
+    assert_eq!(model.cancel_batch(&batch_id, 2).await?, (2, true));
+    assert_eq!(model.cancel_batch(&batch_id, 2).await?, (1, false));
+    for job in model.list().await? {
+        assert_eq!(job.state, ScheduledJobState::Canceled);
+    }
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_bulk_manage_scheduled_jobs(rt: TestRuntime) -> anyhow::Result<()> {
+    let application = Application::new_for_tests(&rt).await?;
+    application.load_udf_tests_modules().await?;
+
+    let mut tx = application.begin(Identity::system()).await?;
+    let path = function_path();
+    let mut model = SchedulerModel::new(&mut tx, TableNamespace::test_user());
+    let mut job_ids = vec![];
+    for _ in 0..3 {
+        let job_id = model
+            .schedule(
+                path.udf_path.clone(),
+                parse_udf_args(&path, vec![])?,
+                rt.unix_timestamp(),
+                ScheduleOptions::default(),
+                ExecutionContext::new_for_test(),
+            )
+            .await?;
+        job_ids.push(job_id);
+    }
+    model
+        .complete(job_ids[0], ScheduledJobState::Failed("error".to_string()))
+        .await?;
+
+    // Page through the pending jobs one at a time.
+    let pending = ScheduledJobFilter {
+        states: Some(vec![ScheduledJobStateKind::Pending]),
+        ..Default::default()
+    };
+    let page = model.list_page(&pending, None, 1).await?;
+    assert_eq!(page.jobs.len(), 1);
+    assert_eq!(page.jobs[0].id(), job_ids[1]);
+    assert!(page.cursor.is_some());
+    let page = model.list_page(&pending, page.cursor, 1).await?;
+    assert_eq!(page.jobs.len(), 1);
+    assert_eq!(page.jobs[0].id(), job_ids[2]);
+    let page = model.list_page(&pending, page.cursor, 1).await?;
+    assert!(page.jobs.is_empty());
+    assert!(page.cursor.is_none());
+
+    let all = ScheduledJobFilter::default();
+    let (counts, cursor) = model.count_page(&all, None).await?;
+    assert_eq!(
+        counts,
+        btreemap! {
+            ScheduledJobStateKind::Pending => 2,
+            ScheduledJobStateKind::Failed => 1,
+        }
+    );
+    assert!(cursor.is_none());
+
+    // Only the pending jobs can be canceled.
+    let (num_canceled, _) = model.cancel_page(&all, None, 10).await?;
+    assert_eq!(num_canceled, 2);
+
+    // Re-running the failed job schedules a new pending job and keeps the
+    // failed one.
+    let failed = ScheduledJobFilter {
+        states: Some(vec![ScheduledJobStateKind::Failed]),
+        ..Default::default()
+    };
+    let (num_rerun, _) = model.rerun_page(&failed, None, 10).await?;
+    assert_eq!(num_rerun, 1);
+    let (counts, _) = model.count_page(&all, None).await?;
+    assert_eq!(
+        counts,
+        btreemap! {
+            ScheduledJobStateKind::Pending => 1,
+            ScheduledJobStateKind::Failed => 1,
+            ScheduledJobStateKind::Canceled => 2,
+        }
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_scheduled_jobs_canceled(rt: TestRuntime) -> anyhow::Result<()> {
     let application = Application::new_for_tests(&rt).await?;
@@ -307,6 +493,7 @@ async fn test_cancel_recursively_scheduled_job(rt: TestRuntime) -> anyhow::Resul
             None,
             FunctionCaller::Action {
                 parent_scheduled_job,
+                trace_parent: None,
             },
             PauseClient::new(),
         )
@@ -324,6 +511,7 @@ async fn test_cancel_recursively_scheduled_job(rt: TestRuntime) -> anyhow::Resul
             Identity::system(),
             FunctionCaller::Action {
                 parent_scheduled_job,
+                trace_parent: None,
             },
         )
         .await??;
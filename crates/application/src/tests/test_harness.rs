@@ -0,0 +1,56 @@
+use std::{
+    fs,
+    time::Duration,
+};
+
+use common::assert_obj;
+use isolate::test_helpers::TEST_SOURCE_ISOLATE_ONLY;
+use runtime::testing::TestRuntime;
+use serde_json::Value as JsonValue;
+
+use crate::test_harness::TestBackend;
+
+async fn scheduled_job_states(backend: &TestBackend) -> anyhow::Result<Vec<String>> {
+    let jobs = backend
+        .query("scheduler:getScheduledJobs", assert_obj!())
+        .await??;
+    let JsonValue::Array(jobs) = JsonValue::from(jobs) else {
+        anyhow::bail!("getScheduledJobs didn't return an array");
+    };
+    Ok(jobs
+        .iter()
+        .map(|job| {
+            job["state"]["kind"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect())
+}
+
+#[convex_macro::test_runtime]
+async fn test_backend_runs_scheduled_functions(rt: TestRuntime) -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    for module in TEST_SOURCE_ISOLATE_ONLY.iter() {
+        let path = dir.path().join(module.path.as_str());
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, &module.source)?;
+        if let Some(source_map) = &module.source_map {
+            fs::write(path.with_extension("js.map"), source_map)?;
+        }
+    }
+    let backend = TestBackend::new(&rt).await?;
+    backend.push_modules_from_dir(dir.path()).await?;
+
+    backend
+        .mutation("scheduler:scheduleAfter", assert_obj!("delayMs" => 60000.0))
+        .await??;
+    assert_eq!(scheduled_job_states(&backend).await?, vec!["pending"]);
+
+    backend.advance_time(Duration::from_secs(30)).await?;
+    assert_eq!(scheduled_job_states(&backend).await?, vec!["pending"]);
+
+    backend.advance_time(Duration::from_secs(30)).await?;
+    assert_eq!(scheduled_job_states(&backend).await?, vec!["success"]);
+    Ok(())
+}
@@ -0,0 +1,406 @@
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    components::ComponentFunctionPath,
+    errors::report_error,
+    http::{
+        fetch::FetchClient,
+        HttpRequest,
+    },
+    knobs::{
+        TRAFFIC_SHADOWING_LATENCY_DIVERGENCE_RATIO,
+        TRAFFIC_SHADOWING_MAX_IN_FLIGHT,
+        TRAFFIC_SHADOWING_TIMEOUT,
+    },
+    runtime::{
+        Runtime,
+        RuntimeInstant,
+        UnixTimestamp,
+    },
+    types::{
+        FunctionCaller,
+        UdfType,
+    },
+};
+use database::Database;
+use futures::{
+    select_biased,
+    Future,
+    FutureExt,
+};
+use http::{
+    header::{
+        AUTHORIZATION,
+        CONTENT_TYPE,
+    },
+    HeaderMap,
+    HeaderValue,
+    Method,
+};
+use keybroker::Identity;
+use model::traffic_shadowing::{
+    types::TrafficShadowingConfig,
+    TrafficShadowingModel,
+};
+use parking_lot::{
+    Mutex,
+    RwLock,
+};
+use rand::Rng;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use tokio::sync::Semaphore;
+
+use crate::metrics::log_traffic_shadowing_call;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Number of divergences kept in the report.
+const MAX_RECENT_DIVERGENCES: usize = 100;
+
+/// Latency differences smaller than this are noise, however large they are
+/// relative to the production execution time.
+const MIN_LATENCY_DIVERGENCE: Duration = Duration::from_millis(10);
+
+/// A call shadowed to the target, as it's sent to `/api/shadow_function`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowFunctionRequest {
+    /// "query" or "mutation".
+    pub udf_type: String,
+    pub path: String,
+    /// Empty for the root component.
+    pub component_path: String,
+    pub args: Vec<JsonValue>,
+    /// The user's identity attributes, or None if the call was
+    /// unauthenticated.
+    pub identity: Option<JsonValue>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowFunctionResponse {
+    pub result: ShadowFunctionResult,
+    pub execution_time_ms: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ShadowFunctionResult {
+    Success { value: JsonValue },
+    Error { message: String },
+}
+
+impl From<Result<JsonValue, String>> for ShadowFunctionResult {
+    fn from(result: Result<JsonValue, String>) -> Self {
+        match result {
+            Ok(value) => Self::Success { value },
+            Err(message) => Self::Error { message },
+        }
+    }
+}
+
+impl From<ShadowFunctionResult> for Result<JsonValue, String> {
+    fn from(result: ShadowFunctionResult) -> Self {
+        match result {
+            ShadowFunctionResult::Success { value } => Ok(value),
+            ShadowFunctionResult::Error { message } => Err(message),
+        }
+    }
+}
+
+/// How the calls shadowed to the current target compare to production.
+#[derive(Clone, Debug, Default)]
+pub struct TrafficShadowingReport {
+    /// Calls the target ran. Each of these either matched production's
+    /// result or diverged from it.
+    pub num_shadowed: u64,
+    pub num_result_divergences: u64,
+    pub num_latency_divergences: u64,
+    /// Calls the target didn't respond to in time or failed to run.
+    pub num_failed: u64,
+    /// Sampled calls that weren't shadowed because too many were in flight.
+    pub num_dropped: u64,
+    pub total_production_execution_time: Duration,
+    pub total_shadow_execution_time: Duration,
+    /// The most recent divergences, oldest first.
+    pub recent_divergences: VecDeque<TrafficShadowingDivergence>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TrafficShadowingDivergence {
+    pub ts: UnixTimestamp,
+    pub udf_type: UdfType,
+    pub path: String,
+    pub component_path: String,
+    pub result_diverged: bool,
+    pub latency_diverged: bool,
+    pub production_result: Result<JsonValue, String>,
+    pub shadow_result: Result<JsonValue, String>,
+    pub production_execution_time: Duration,
+    pub shadow_execution_time: Duration,
+}
+
+/// A call that's been sampled for shadowing, which is sent to the target once
+/// it's finished running in production.
+pub struct ShadowedCall<RT: Runtime> {
+    udf_type: UdfType,
+    request: ShadowFunctionRequest,
+    start: RT::Instant,
+}
+
+/// Replays a sample of this deployment's queries and mutations against the
+/// deployment configured with `TrafficShadowingModel`, e.g. a staging
+/// deployment running a risky backend upgrade, and reports where its results
+/// or latencies diverge from production's. The target runs mutations without
+/// committing them.
+///
+/// Shadowed calls are sent in the background once the production call has
+/// finished, so they never delay or fail it.
+#[derive(Clone)]
+pub struct TrafficShadower<RT: Runtime> {
+    runtime: RT,
+    fetch_client: Arc<dyn FetchClient>,
+    config: Arc<RwLock<Option<TrafficShadowingConfig>>>,
+    in_flight: Arc<Semaphore>,
+    report: Arc<Mutex<TrafficShadowingReport>>,
+}
+
+impl<RT: Runtime> TrafficShadower<RT> {
+    pub fn new(runtime: RT, fetch_client: Arc<dyn FetchClient>) -> Self {
+        Self {
+            runtime,
+            fetch_client,
+            config: Arc::new(RwLock::new(None)),
+            in_flight: Arc::new(Semaphore::new(*TRAFFIC_SHADOWING_MAX_IN_FLIGHT)),
+            report: Arc::new(Mutex::new(TrafficShadowingReport::default())),
+        }
+    }
+
+    /// Keeps the shadower's config up to date with `_traffic_shadowing`.
+    pub fn start_config_worker(self, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        async move {
+            tracing::info!("Starting traffic shadowing config worker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(mut e) = self.watch_config(&database, &mut backoff).await {
+                let delay = self.runtime.with_rng(|rng| backoff.fail(rng));
+                tracing::error!("Traffic shadowing config worker failed, sleeping {delay:?}");
+                report_error(&mut e);
+                self.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn watch_config(
+        &self,
+        database: &Database<RT>,
+        backoff: &mut Backoff,
+    ) -> anyhow::Result<()> {
+        loop {
+            let mut tx = database.begin(Identity::system()).await?;
+            let config = TrafficShadowingModel::new(&mut tx).get().await?;
+            self.set_config(config);
+            let token = tx.into_token()?;
+            let subscription = database.subscribe(token).await?;
+            backoff.reset();
+            subscription.wait_for_invalidation().await;
+        }
+    }
+
+    fn set_config(&self, config: Option<TrafficShadowingConfig>) {
+        let mut current = self.config.write();
+        let target_url = |config: &Option<TrafficShadowingConfig>| {
+            config.as_ref().map(|config| config.target_url.clone())
+        };
+        // Results from different targets aren't comparable.
+        if target_url(&current) != target_url(&config) {
+            *self.report.lock() = TrafficShadowingReport::default();
+        }
+        *current = config;
+    }
+
+    pub fn report(&self) -> TrafficShadowingReport {
+        self.report.lock().clone()
+    }
+
+    /// Decides whether to shadow a call before it runs. Only calls from
+    /// clients are shadowed, since they're what production traffic consists
+    /// of, and calls that admins make aren't replayable as users.
+    pub fn sample(
+        &self,
+        udf_type: UdfType,
+        path: &ComponentFunctionPath,
+        args: &[JsonValue],
+        identity: &Identity,
+        caller: &FunctionCaller,
+    ) -> Option<ShadowedCall<RT>> {
+        let sample_percentage = self.config.read().as_ref()?.sample_percentage;
+        if !matches!(
+            caller,
+            FunctionCaller::SyncWorker(_) | FunctionCaller::HttpApi(_)
+        ) {
+            return None;
+        }
+        let identity = match identity {
+            Identity::User(user) => match JsonValue::try_from(user.attributes.clone()) {
+                Ok(attributes) => Some(attributes),
+                Err(mut e) => {
+                    report_error(&mut e);
+                    return None;
+                },
+            },
+            Identity::Unknown => None,
+            _ => return None,
+        };
+        if self.runtime.with_rng(|rng| rng.gen_range(0.0..100.0)) >= sample_percentage {
+            return None;
+        }
+        Some(ShadowedCall {
+            udf_type,
+            request: ShadowFunctionRequest {
+                udf_type: udf_type.to_lowercase_string().to_string(),
+                path: path.udf_path.to_string(),
+                component_path: String::from(path.component.clone()),
+                args: args.to_vec(),
+                identity,
+            },
+            start: self.runtime.monotonic_now(),
+        })
+    }
+
+    /// Sends a sampled call to the target in the background and compares the
+    /// target's result with `production_result`.
+    pub fn shadow(&self, call: ShadowedCall<RT>, production_result: Result<JsonValue, String>) {
+        let production_execution_time = call.start.elapsed();
+        let Some(config) = self.config.read().clone() else {
+            return;
+        };
+        let Ok(permit) = self.in_flight.clone().try_acquire_owned() else {
+            self.report.lock().num_dropped += 1;
+            log_traffic_shadowing_call(call.udf_type, "dropped");
+            return;
+        };
+        let shadower = self.clone();
+        self.runtime.spawn("traffic_shadowing", async move {
+            let _permit = permit;
+            let response = select_biased! {
+                response = shadower.send(&config, &call.request).fuse() => response,
+                _ = shadower.runtime.wait(*TRAFFIC_SHADOWING_TIMEOUT).fuse() => {
+                    Err(anyhow::anyhow!("Timed out after {:?}", *TRAFFIC_SHADOWING_TIMEOUT))
+                },
+            };
+            match response {
+                Ok(response) => {
+                    shadower.compare(call, production_result, production_execution_time, response)
+                },
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to shadow {} to {}: {e:#}",
+                        call.request.path,
+                        config.target_url
+                    );
+                    shadower.report.lock().num_failed += 1;
+                    log_traffic_shadowing_call(call.udf_type, "failed");
+                },
+            }
+        });
+    }
+
+    async fn send(
+        &self,
+        config: &TrafficShadowingConfig,
+        request: &ShadowFunctionRequest,
+    ) -> anyhow::Result<ShadowFunctionResponse> {
+        let url = format!(
+            "{}/api/shadow_function",
+            config.target_url.trim_end_matches('/')
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            HeaderValue::from_str(&format!("Convex {}", config.target_admin_key))?,
+        );
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let request = HttpRequest {
+            headers,
+            url: url.parse()?,
+            method: Method::POST,
+            body: Some(serde_json::to_vec(request)?),
+        };
+        let response = self
+            .fetch_client
+            .fetch(request.into())
+            .await?
+            .into_http_response()
+            .await?;
+        anyhow::ensure!(
+            response.status.is_success(),
+            "The target responded with {}",
+            response.status
+        );
+        Ok(serde_json::from_slice(&response.body.unwrap_or_default())?)
+    }
+
+    fn compare(
+        &self,
+        call: ShadowedCall<RT>,
+        production_result: Result<JsonValue, String>,
+        production_execution_time: Duration,
+        response: ShadowFunctionResponse,
+    ) {
+        let shadow_result: Result<JsonValue, String> = response.result.into();
+        let shadow_execution_time = Duration::from_secs_f64(response.execution_time_ms / 1000.0);
+        // Error messages can differ between versions, and are redacted for
+        // some callers, so any two errors match.
+        let result_diverged = match (&production_result, &shadow_result) {
+            (Ok(production), Ok(shadow)) => production != shadow,
+            (Err(_), Err(_)) => false,
+            _ => true,
+        };
+        let latency_diverged = shadow_execution_time
+            > production_execution_time.mul_f64(*TRAFFIC_SHADOWING_LATENCY_DIVERGENCE_RATIO)
+            && shadow_execution_time.saturating_sub(production_execution_time)
+                > MIN_LATENCY_DIVERGENCE;
+        let outcome = match (result_diverged, latency_diverged) {
+            (true, _) => "result_divergence",
+            (false, true) => "latency_divergence",
+            (false, false) => "matched",
+        };
+        log_traffic_shadowing_call(call.udf_type, outcome);
+
+        let mut report = self.report.lock();
+        report.num_shadowed += 1;
+        report.num_result_divergences += result_diverged as u64;
+        report.num_latency_divergences += latency_diverged as u64;
+        report.total_production_execution_time += production_execution_time;
+        report.total_shadow_execution_time += shadow_execution_time;
+        if result_diverged || latency_diverged {
+            if report.recent_divergences.len() == MAX_RECENT_DIVERGENCES {
+                report.recent_divergences.pop_front();
+            }
+            report
+                .recent_divergences
+                .push_back(TrafficShadowingDivergence {
+                    ts: self.runtime.unix_timestamp(),
+                    udf_type: call.udf_type,
+                    path: call.request.path,
+                    component_path: call.request.component_path,
+                    result_diverged,
+                    latency_diverged,
+                    production_result,
+                    shadow_result,
+                    production_execution_time,
+                    shadow_execution_time,
+                });
+        }
+    }
+}
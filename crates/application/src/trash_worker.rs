@@ -0,0 +1,96 @@
+use std::{
+    collections::BTreeSet,
+    time::Duration,
+};
+
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    knobs::{
+        TRASH_PURGE_BATCH_SIZE,
+        TRASH_PURGE_INTERVAL,
+    },
+    runtime::Runtime,
+};
+use database::{
+    trash::{
+        TrashModel,
+        TRASH_TABLE,
+    },
+    Database,
+};
+use futures::Future;
+use keybroker::Identity;
+use value::TableNamespace;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(900);
+
+/// Deletes soft-deleted documents from the `_trash` table of every component
+/// once their retention window has ended.
+pub struct TrashWorker<RT: Runtime> {
+    runtime: RT,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> TrashWorker<RT> {
+    pub fn start(runtime: RT, database: Database<RT>) -> impl Future<Output = ()> + Send {
+        let worker = Self { runtime, database };
+        async move {
+            tracing::info!("Starting TrashWorker");
+            let mut backoff = Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF);
+            while let Err(e) = worker.run(&mut backoff).await {
+                let delay = worker.runtime.with_rng(|rng| backoff.fail(rng));
+                report_error(&mut e.context("TrashWorker died"));
+                tracing::error!("Trash worker failed, sleeping {delay:?}");
+                worker.runtime.wait(delay).await;
+            }
+        }
+    }
+
+    async fn run(&self, backoff: &mut Backoff) -> anyhow::Result<()> {
+        loop {
+            self.purge_expired().await?;
+            backoff.reset();
+            self.runtime.wait(*TRASH_PURGE_INTERVAL).await;
+        }
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<()> {
+        let namespaces: BTreeSet<TableNamespace> = {
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let table_mapping = tx.table_mapping();
+            table_mapping
+                .iter()
+                .filter(|(tablet_id, .., table_name)| {
+                    **table_name == *TRASH_TABLE && table_mapping.is_active(*tablet_id)
+                })
+                .map(|(_, namespace, ..)| namespace)
+                .collect()
+        };
+        let now = self.runtime.unix_timestamp();
+        for namespace in namespaces {
+            loop {
+                let mut tx = self.database.begin(Identity::system()).await?;
+                let expired = TrashModel::new(&mut tx, namespace)
+                    .expired(now, *TRASH_PURGE_BATCH_SIZE)
+                    .await?;
+                if expired.is_empty() {
+                    break;
+                }
+                let num_expired = expired.len();
+                tracing::debug!("Purging {num_expired} expired documents from the trash");
+                for id in expired {
+                    TrashModel::new(&mut tx, namespace).purge(id).await?;
+                }
+                self.database
+                    .commit_with_write_source(tx, "trash_purge")
+                    .await?;
+                if num_expired < *TRASH_PURGE_BATCH_SIZE {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -121,6 +121,8 @@ impl<Value: SizedValue, RT: Runtime> SizedValue for CacheResult<Value, RT> {
 
 struct Inner<RT: Runtime, Key, Value> {
     cache: LruCache<Key, CacheResult<Value, RT>>,
+    // Values that are kept even if they're evicted from `cache`.
+    pinned: HashMap<Key, Arc<Value>>,
     current_size: u64,
     max_size: u64,
     label: &'static str,
@@ -136,6 +138,7 @@ impl<RT: Runtime, Key, Value> Inner<RT, Key, Value> {
     ) -> Arc<Mutex<Self>> {
         Arc::new(Mutex::new(Self {
             cache,
+            pinned: HashMap::new(),
             current_size: 0,
             max_size,
             label,
@@ -340,6 +343,41 @@ impl<
         inner.current_size
     }
 
+    /// Loads the value for `key` like `get` and keeps it until `unpin` is
+    /// called. Pinned values count toward `max_size` while they're in the LRU,
+    /// but stay available after it evicts them.
+    pub async fn pin(
+        &self,
+        key: Key,
+        value_generator: SingleValueGenerator<Value>,
+    ) -> anyhow::Result<Arc<Value>> {
+        let value = self.get(key.clone(), value_generator).await?;
+        self.inner.lock().pinned.insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Returns whether `key` was pinned.
+    pub fn unpin(&self, key: &Key) -> bool {
+        self.inner.lock().pinned.remove(key).is_some()
+    }
+
+    pub fn is_pinned(&self, key: &Key) -> bool {
+        self.inner.lock().pinned.contains_key(key)
+    }
+
+    /// The value for `key` if it's available without generating it. Unlike
+    /// `get`, this doesn't count as a use of the value.
+    pub fn peek(&self, key: &Key) -> Option<Arc<Value>> {
+        let inner = self.inner.lock();
+        if let Some(value) = inner.pinned.get(key) {
+            return Some(value.clone());
+        }
+        match inner.cache.peek(key) {
+            Some(CacheResult::Ready { value, .. }) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
     pub async fn get_and_prepopulate(
         &self,
         key: Key,
@@ -407,6 +445,10 @@ impl<
     ) -> anyhow::Result<Status<Value>> {
         let mut inner = self.inner.lock();
         log_async_lru_size(inner.cache.len(), inner.current_size, self.label);
+        if let Some(value) = inner.pinned.get(key) {
+            log_async_lru_cache_hit(self.label);
+            return Ok(Status::Ready(value.clone()));
+        }
         match inner.cache.get(key) {
             Some(CacheResult::Ready { value, .. }) => {
                 log_async_lru_cache_hit(self.label);
@@ -670,6 +712,35 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn pinned_values_survive_eviction(rt: TestRuntime) -> anyhow::Result<()> {
+        let cache = AsyncLru::new(rt, 1, 1, "label");
+        assert!(cache.peek(&"key").is_none());
+        let first = cache
+            .pin("key", GenerateRandomValue::generate_value("key").boxed())
+            .await?;
+        assert_eq!(cache.peek(&"key"), Some(first.clone()));
+        cache
+            .get(
+                "other_key",
+                GenerateRandomValue::generate_value("other_key").boxed(),
+            )
+            .await?;
+        assert!(cache.is_pinned(&"key"));
+        let second = cache
+            .get("key", GenerateRandomValue::generate_value("key").boxed())
+            .await?;
+        assert_eq!(first, second);
+
+        assert!(cache.unpin(&"key"));
+        assert!(cache.peek(&"key").is_none());
+        let third = cache
+            .get("key", GenerateRandomValue::generate_value("key").boxed())
+            .await?;
+        assert_ne!(first, third);
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn get_with_failure_propagates_error(rt: TestRuntime) -> anyhow::Result<()> {
         let cache = AsyncLru::new(rt, 1, 1, "label");
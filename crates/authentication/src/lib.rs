@@ -16,8 +16,17 @@ use biscuit::{
     JWT,
 };
 use chrono::TimeZone;
-use common::auth::AuthInfo;
-use errors::ErrorMetadata;
+use common::{
+    auth::{
+        AuthInfo,
+        SessionClaims,
+    },
+    runtime::UnixTimestamp,
+};
+use errors::{
+    ErrorMetadata,
+    ErrorMetadataAnyhowExt,
+};
 use futures::Future;
 use http::{
     header::ACCEPT,
@@ -38,6 +47,7 @@ use openidconnect::{
     },
     ClaimsVerificationError,
     ClientId,
+    IssuerUrl,
 };
 use serde::{
     Deserialize,
@@ -46,8 +56,18 @@ use serde::{
 use sync_types::AuthenticationToken;
 use url::Url;
 
+use crate::{
+    metrics::{
+        log_id_token_validation,
+        UNKNOWN_ISSUER,
+    },
+    provider_metadata_cache::ProviderMetadataCache,
+};
+
 pub mod access_token_auth;
 pub mod application_auth;
+mod metrics;
+pub mod provider_metadata_cache;
 
 /// Issuer for API access tokens
 pub static CONVEX_AUTH_URL: LazyLock<Url> =
@@ -101,14 +121,70 @@ pub async fn validate_id_token<F, E>(
     token_str: Auth0IdToken,
     // The http client is injected here so we can unit test this filter without needing to actually
     // serve an HTTP response from an identity provider.
-    http_client: impl Fn(HttpRequest) -> F + 'static,
+    http_client: impl Fn(HttpRequest) -> F + Clone + 'static,
     auth_infos: Vec<AuthInfo>,
+    provider_metadata_cache: &ProviderMetadataCache,
     system_time: SystemTime,
 ) -> anyhow::Result<UserIdentity>
 where
     F: Future<Output = Result<HttpResponse, E>>,
     E: std::error::Error + 'static + Send + Sync,
 {
+    let (token, application_id, issuer) = match find_auth_provider(token_str, auth_infos) {
+        Ok(provider) => provider,
+        Err(e) => {
+            log_id_token_validation(UNKNOWN_ISSUER, validation_outcome(&e));
+            return Err(e);
+        },
+    };
+    let result = verify_id_token(
+        token,
+        application_id,
+        &issuer,
+        http_client,
+        provider_metadata_cache,
+        system_time,
+    )
+    .await;
+    let outcome = match &result {
+        Ok(_) => "success",
+        Err(e) => validation_outcome(e),
+    };
+    log_id_token_validation(issuer.as_str(), outcome);
+    result
+}
+
+/// Read the claims identifying the session of a user's ID token, which was
+/// verified when the identity was created.
+pub fn session_claims(user: &UserIdentity) -> anyhow::Result<SessionClaims> {
+    #[derive(Deserialize)]
+    struct RawSessionClaims {
+        iat: Option<f64>,
+        jti: Option<String>,
+    }
+    let token = user.original_token.to_string();
+    let payload = token
+        .split('.')
+        .nth(1)
+        .context("ID token is missing its payload")?;
+    let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD)?;
+    let claims: RawSessionClaims = serde_json::from_slice(&payload)?;
+    Ok(SessionClaims {
+        issued_at: claims
+            .iat
+            .filter(|iat| iat.is_finite() && *iat >= 0.0)
+            .map(UnixTimestamp::from_secs_f64),
+        token_id: claims.jti,
+    })
+}
+
+/// Parse an ID token without verifying it and find the auth provider it
+/// claims to be from, returning the token, the provider's application ID and
+/// the token's issuer.
+fn find_auth_provider(
+    token_str: Auth0IdToken,
+    auth_infos: Vec<AuthInfo>,
+) -> anyhow::Result<(CoreIdToken, String, IssuerUrl)> {
     let token = CoreIdToken::from_str(&token_str.0).context(ErrorMetadata::unauthenticated(
         "InvalidAuthHeader",
         "Could not parse as id token",
@@ -129,7 +205,7 @@ where
                 .iter()
                 .map(|aud| aud.to_string())
                 .collect::<Vec<_>>(),
-            claims.issuer(),
+            claims.issuer().clone(),
         )
     };
     // Find the provider matching this token
@@ -142,24 +218,78 @@ where
             // Discovery response will contain, but the value entered in the instance config
             // may or may not have the slash.
             audiences.contains(&info.application_id)
-                && info.domain.trim_end_matches('/') == issuer.trim_end_matches('/')
+                && info
+                    .issuers()
+                    .any(|domain| domain.trim_end_matches('/') == issuer.trim_end_matches('/'))
         })
         .context(ErrorMetadata::unauthenticated(
             "NoAuthProvider",
             "No auth provider found matching the given token",
         ))?;
-    // Use the OpenID Connect Discovery protocol to get the public keys for this
-    // provider.
-    // TODO(CX-606): Add an caching layer that respects the HTTP cache headers
-    // in the response.
-    let metadata = CoreProviderMetadata::discover_async(issuer.clone(), http_client).await?;
+    Ok((token, auth_info.application_id, issuer))
+}
+
+/// Verify an ID token against the signing keys of the provider at `issuer`.
+async fn verify_id_token<F, E>(
+    token: CoreIdToken,
+    application_id: String,
+    issuer: &IssuerUrl,
+    http_client: impl Fn(HttpRequest) -> F + Clone + 'static,
+    provider_metadata_cache: &ProviderMetadataCache,
+    system_time: SystemTime,
+) -> anyhow::Result<UserIdentity>
+where
+    F: Future<Output = Result<HttpResponse, E>>,
+    E: std::error::Error + 'static + Send + Sync,
+{
+    let metadata = provider_metadata_cache
+        .get(issuer, http_client.clone(), system_time)
+        .await?;
+    let result = match verify_with_metadata(
+        token.clone(),
+        application_id.clone(),
+        &metadata,
+        system_time,
+    ) {
+        Err(e)
+            if matches!(
+                e.downcast_ref::<ClaimsVerificationError>(),
+                Some(ClaimsVerificationError::SignatureVerification(_))
+            ) =>
+        {
+            // The token may be signed by a key the provider rotated in since we
+            // fetched its key set, so refetch and try again.
+            match provider_metadata_cache
+                .refetch_for_unknown_key(issuer, http_client, system_time)
+                .await?
+            {
+                Some(metadata) => {
+                    verify_with_metadata(token, application_id, &metadata, system_time)
+                },
+                None => Err(e),
+            }
+        },
+        result => result,
+    };
+    result.context(ErrorMetadata::unauthenticated(
+        "Unauthenticated",
+        "Could not verify token claim",
+    ))
+}
+
+fn verify_with_metadata(
+    token: CoreIdToken,
+    application_id: String,
+    metadata: &CoreProviderMetadata,
+    system_time: SystemTime,
+) -> anyhow::Result<UserIdentity> {
     // Create a verifier for the provider using this metadata. Set the verifier
     // to enforce that the issuer and audience match.
     // Note for posterity: this verifier will reject tokens containing multiple
     // audiences. It's very uncommon for an identity provider to create a token with
     // multiple valid audiences, so we don't handle that case yet.
     let verifier = CoreIdTokenVerifier::new_public_client(
-        ClientId::new(auth_info.application_id),
+        ClientId::new(application_id),
         metadata.issuer().clone(),
         metadata.jwks().clone(),
     )
@@ -176,10 +306,18 @@ where
             )
             .unwrap()
     });
-    UserIdentity::from_token(token, verifier).context(ErrorMetadata::unauthenticated(
-        "Unauthenticated",
-        "Could not verify token claim",
-    ))
+    UserIdentity::from_token(token, verifier)
+}
+
+/// Label for the outcome of a failed ID token validation.
+fn validation_outcome(e: &anyhow::Error) -> &'static str {
+    match e.short_msg() {
+        "InvalidAuthHeader" => "malformed",
+        "IdTokenExpired" => "expired",
+        "NoAuthProvider" => "no_provider",
+        "Unauthenticated" => "invalid",
+        _ => "error",
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -372,7 +510,11 @@ mod tests {
         Duration,
         Utc,
     };
-    use common::auth::AuthInfo;
+    use common::{
+        auth::AuthInfo,
+        knobs::AUTH_PROVIDER_METADATA_MIN_REFRESH_INTERVAL,
+    };
+    use errors::ErrorMetadataAnyhowExt;
     use futures::{
         Future,
         FutureExt,
@@ -385,6 +527,7 @@ mod tests {
             CoreGenderClaim,
             CoreIdToken,
             CoreIdTokenClaims,
+            CoreJsonWebKey,
             CoreJsonWebKeySet,
             CoreJsonWebKeyType,
             CoreJweContentEncryptionAlgorithm,
@@ -418,6 +561,8 @@ mod tests {
     };
 
     use crate::{
+        provider_metadata_cache::ProviderMetadataCache,
+        session_claims,
         validate_access_token,
         validate_id_token,
         Auth0AccessToken,
@@ -429,7 +574,7 @@ mod tests {
     fn fake_http_client(
         metadata: String,
         jwks: String,
-    ) -> impl Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<HttpResponse, Infallible>>>>
+    ) -> impl Fn(HttpRequest) -> Pin<Box<dyn Future<Output = Result<HttpResponse, Infallible>>>> + Clone
     {
         move |request: HttpRequest| {
             let metadata_ = metadata.clone();
@@ -455,17 +600,15 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_id_token_auth() -> anyhow::Result<()> {
-        let issuer_url = IssuerUrl::new("https://dev-1sfr-rpl.us.auth0.com".to_string()).unwrap();
-        let audience = Audience::new("client-id-123".to_string());
-        let provider_metadata = serde_json::to_string(
+    fn provider_metadata(issuer_url: &IssuerUrl) -> String {
+        serde_json::to_string(
             &CoreProviderMetadata::new(
                 issuer_url.clone(),
                 None,
-                JsonWebKeySetUrl::new(
-                    "https://dev-1sfr-rpl.us.auth0.com/.well-known/jwks.json".to_string(),
-                )
+                JsonWebKeySetUrl::new(format!(
+                    "{}/.well-known/jwks.json",
+                    issuer_url.trim_end_matches('/')
+                ))
                 .unwrap(),
                 vec![
                     ResponseTypes::new(vec![CoreResponseType::Code]),
@@ -499,12 +642,15 @@ mod tests {
                 CoreClaimName::new("picture".to_string()),
             ])),
         )
-        .unwrap();
-        let jwks = serde_json::to_string(&CoreJsonWebKeySet::new(vec![
-            TEST_SIGNING_KEY.as_verification_key()
-        ]))
-        .unwrap();
-        let id_token = CoreIdToken::new(
+        .unwrap()
+    }
+
+    fn jwks(keys: Vec<CoreJsonWebKey>) -> String {
+        serde_json::to_string(&CoreJsonWebKeySet::new(keys)).unwrap()
+    }
+
+    fn id_token(issuer_url: &IssuerUrl, audience: &Audience) -> String {
+        CoreIdToken::new(
             CoreIdTokenClaims::new(
                 issuer_url.clone(),
                 vec![audience.clone()],
@@ -520,14 +666,25 @@ mod tests {
             None,
         )
         .unwrap()
-        .to_string();
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_id_token_auth() -> anyhow::Result<()> {
+        let issuer_url = IssuerUrl::new("https://dev-1sfr-rpl.us.auth0.com".to_string()).unwrap();
+        let audience = Audience::new("client-id-123".to_string());
         validate_id_token(
-            Auth0IdToken(id_token),
-            fake_http_client(provider_metadata, jwks),
+            Auth0IdToken(id_token(&issuer_url, &audience)),
+            fake_http_client(
+                provider_metadata(&issuer_url),
+                jwks(vec![TEST_SIGNING_KEY.as_verification_key()]),
+            ),
             vec![AuthInfo {
                 application_id: (*audience).clone(),
                 domain: issuer_url,
+                additional_issuers: vec![],
             }],
+            &ProviderMetadataCache::default(),
             SystemTime::now(),
         )
         .await
@@ -535,6 +692,156 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_id_token_auth_additional_issuer() -> anyhow::Result<()> {
+        let issuer_url = IssuerUrl::new("https://dev-1sfr-rpl.us.auth0.com".to_string()).unwrap();
+        let other_issuer_url = IssuerUrl::new("https://eu.auth0.com".to_string()).unwrap();
+        let audience = Audience::new("client-id-123".to_string());
+        let auth_infos = vec![AuthInfo {
+            application_id: (*audience).clone(),
+            domain: issuer_url.clone(),
+            additional_issuers: vec![other_issuer_url.clone()],
+        }];
+        // Tokens from the additional issuer are verified against its own keys.
+        let user = validate_id_token(
+            Auth0IdToken(id_token(&other_issuer_url, &audience)),
+            fake_http_client(
+                provider_metadata(&other_issuer_url),
+                jwks(vec![TEST_SIGNING_KEY.as_verification_key()]),
+            ),
+            auth_infos.clone(),
+            &ProviderMetadataCache::default(),
+            SystemTime::now(),
+        )
+        .await?;
+        assert_eq!(user.issuer, other_issuer_url.as_str());
+
+        let unknown_issuer_url = IssuerUrl::new("https://unknown.auth0.com".to_string()).unwrap();
+        let err = validate_id_token(
+            Auth0IdToken(id_token(&unknown_issuer_url, &audience)),
+            fake_http_client(
+                provider_metadata(&unknown_issuer_url),
+                jwks(vec![TEST_SIGNING_KEY.as_verification_key()]),
+            ),
+            auth_infos,
+            &ProviderMetadataCache::default(),
+            SystemTime::now(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.short_msg(), "NoAuthProvider");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_session_claims() -> anyhow::Result<()> {
+        let issuer_url = IssuerUrl::new("https://dev-1sfr-rpl.us.auth0.com".to_string()).unwrap();
+        let audience = Audience::new("client-id-123".to_string());
+        #[derive(Debug, Deserialize, Serialize, Clone)]
+        struct SessionIdClaims {
+            jti: String,
+        }
+        impl AdditionalClaims for SessionIdClaims {}
+        let issued_at = Utc::now();
+        let id_token = IdToken::<
+            SessionIdClaims,
+            CoreGenderClaim,
+            CoreJweContentEncryptionAlgorithm,
+            CoreJwsSigningAlgorithm,
+            CoreJsonWebKeyType,
+        >::new(
+            IdTokenClaims::new(
+                issuer_url.clone(),
+                vec![audience.clone()],
+                issued_at + Duration::seconds(120),
+                issued_at,
+                StandardClaims::new(SubjectIdentifier::new("1234-abcd".to_string())),
+                SessionIdClaims {
+                    jti: "session-1".to_string(),
+                },
+            ),
+            &*TEST_SIGNING_KEY,
+            CoreJwsSigningAlgorithm::RsaSsaPkcs1V15Sha256,
+            None,
+            None,
+        )
+        .unwrap()
+        .to_string();
+        let user = validate_id_token(
+            Auth0IdToken(id_token),
+            fake_http_client(
+                provider_metadata(&issuer_url),
+                jwks(vec![TEST_SIGNING_KEY.as_verification_key()]),
+            ),
+            vec![AuthInfo {
+                application_id: (*audience).clone(),
+                domain: issuer_url,
+                additional_issuers: vec![],
+            }],
+            &ProviderMetadataCache::default(),
+            SystemTime::now(),
+        )
+        .await?;
+        let claims = session_claims(&user)?;
+        assert_eq!(claims.token_id.as_deref(), Some("session-1"));
+        assert_eq!(
+            claims.issued_at.map(|iat| iat.as_secs()),
+            Some(issued_at.timestamp() as u64)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_id_token_auth_key_rotation() -> anyhow::Result<()> {
+        let issuer_url = IssuerUrl::new("https://dev-1sfr-rpl.us.auth0.com".to_string()).unwrap();
+        let audience = Audience::new("client-id-123".to_string());
+        let auth_infos = vec![AuthInfo {
+            application_id: (*audience).clone(),
+            domain: issuer_url.clone(),
+            additional_issuers: vec![],
+        }];
+        let token = id_token(&issuer_url, &audience);
+        let before_rotation = fake_http_client(provider_metadata(&issuer_url), jwks(vec![]));
+        let after_rotation = fake_http_client(
+            provider_metadata(&issuer_url),
+            jwks(vec![TEST_SIGNING_KEY.as_verification_key()]),
+        );
+        let cache = ProviderMetadataCache::default();
+        let now = SystemTime::now();
+
+        // The token is signed by a key that isn't published yet.
+        validate_id_token(
+            Auth0IdToken(token.clone()),
+            before_rotation,
+            auth_infos.clone(),
+            &cache,
+            now,
+        )
+        .await
+        .unwrap_err();
+        // The key is published, but the cached keys were fetched too recently to
+        // refetch.
+        validate_id_token(
+            Auth0IdToken(token.clone()),
+            after_rotation.clone(),
+            auth_infos.clone(),
+            &cache,
+            now,
+        )
+        .await
+        .unwrap_err();
+        // Once they've aged, the unknown key causes a refetch.
+        validate_id_token(
+            Auth0IdToken(token),
+            after_rotation,
+            auth_infos,
+            &cache,
+            now + *AUTH_PROVIDER_METADATA_MIN_REFRESH_INTERVAL,
+        )
+        .await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_access_token_auth() -> anyhow::Result<()> {
         let issuer_url = IssuerUrl::from_url(CONVEX_AUTH_URL.clone());
@@ -0,0 +1,44 @@
+use metrics::{
+    log_counter_with_labels,
+    register_convex_counter,
+    MetricLabel,
+};
+
+register_convex_counter!(
+    AUTH_ID_TOKEN_VALIDATIONS_TOTAL,
+    "Count of ID token validations, by the auth provider issuer that the token matched",
+    &["issuer", "outcome"]
+);
+
+/// Issuer label for tokens that didn't match a configured auth provider. The
+/// token's own issuer isn't used since it's chosen by whoever made the token.
+pub const UNKNOWN_ISSUER: &str = "unknown";
+
+pub fn log_id_token_validation(issuer: &str, outcome: &'static str) {
+    log_counter_with_labels(
+        &AUTH_ID_TOKEN_VALIDATIONS_TOTAL,
+        1,
+        vec![
+            MetricLabel::new("issuer", issuer.to_string()),
+            MetricLabel::new("outcome", outcome),
+        ],
+    )
+}
+
+register_convex_counter!(
+    AUTH_PROVIDER_METADATA_FETCHES_TOTAL,
+    "Count of fetches of an auth provider's metadata and signing keys",
+    &["issuer", "reason", "status"]
+);
+
+pub fn log_provider_metadata_fetch(issuer: &str, reason: &'static str, is_ok: bool) {
+    log_counter_with_labels(
+        &AUTH_PROVIDER_METADATA_FETCHES_TOTAL,
+        1,
+        vec![
+            MetricLabel::new("issuer", issuer.to_string()),
+            MetricLabel::new("reason", reason),
+            MetricLabel::status(is_ok),
+        ],
+    )
+}
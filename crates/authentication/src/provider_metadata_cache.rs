@@ -0,0 +1,198 @@
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use common::knobs::{
+    AUTH_PROVIDER_METADATA_MAX_STALENESS,
+    AUTH_PROVIDER_METADATA_MIN_REFRESH_INTERVAL,
+    AUTH_PROVIDER_METADATA_REFRESH_INTERVAL,
+};
+use futures::Future;
+use oauth2::{
+    HttpRequest,
+    HttpResponse,
+};
+use openidconnect::{
+    core::CoreProviderMetadata,
+    IssuerUrl,
+};
+use parking_lot::Mutex;
+
+use crate::metrics::log_provider_metadata_fetch;
+
+/// In-memory cache of the OpenID Connect discovery documents and signing keys
+/// of the auth providers that tokens have been validated against, keyed by
+/// issuer.
+///
+/// Entries are refreshed in the background by `refresh_stale` so validation
+/// rarely waits on a fetch, and are refetched early when a token is signed
+/// by a key we don't have yet, which is how providers rotate keys.
+#[derive(Default)]
+pub struct ProviderMetadataCache {
+    providers: Mutex<BTreeMap<String, CachedProviderMetadata>>,
+}
+
+struct CachedProviderMetadata {
+    issuer: IssuerUrl,
+    metadata: Arc<CoreProviderMetadata>,
+    fetched_at: SystemTime,
+    last_used: SystemTime,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum FetchReason {
+    /// Nothing usable was cached.
+    Miss,
+    /// A token was signed by a key that wasn't in the cached key set.
+    UnknownKey,
+    /// The cached entry was due for a background refresh.
+    Refresh,
+}
+
+impl FetchReason {
+    fn as_label(&self) -> &'static str {
+        match self {
+            FetchReason::Miss => "miss",
+            FetchReason::UnknownKey => "unknown_key",
+            FetchReason::Refresh => "refresh",
+        }
+    }
+}
+
+fn age(since: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(since).unwrap_or(Duration::ZERO)
+}
+
+impl ProviderMetadataCache {
+    /// Get the metadata for `issuer`, fetching it if it isn't cached or the
+    /// cached copy is too old to trust.
+    pub async fn get<F, E>(
+        &self,
+        issuer: &IssuerUrl,
+        http_client: impl Fn(HttpRequest) -> F + 'static,
+        now: SystemTime,
+    ) -> anyhow::Result<Arc<CoreProviderMetadata>>
+    where
+        F: Future<Output = Result<HttpResponse, E>>,
+        E: std::error::Error + 'static + Send + Sync,
+    {
+        {
+            let mut providers = self.providers.lock();
+            if let Some(cached) = providers.get_mut(issuer.as_str()) {
+                if age(cached.fetched_at, now) < *AUTH_PROVIDER_METADATA_MAX_STALENESS {
+                    cached.last_used = now;
+                    return Ok(cached.metadata.clone());
+                }
+            }
+        }
+        self.fetch(issuer, http_client, now, FetchReason::Miss)
+            .await
+    }
+
+    /// Refetch the metadata for `issuer` after a token failed to verify
+    /// against the cached signing keys. Returns `None` without fetching if the
+    /// cached copy is too recent to refetch, so that tokens signed by unknown
+    /// keys can't make us hammer the provider.
+    pub async fn refetch_for_unknown_key<F, E>(
+        &self,
+        issuer: &IssuerUrl,
+        http_client: impl Fn(HttpRequest) -> F + 'static,
+        now: SystemTime,
+    ) -> anyhow::Result<Option<Arc<CoreProviderMetadata>>>
+    where
+        F: Future<Output = Result<HttpResponse, E>>,
+        E: std::error::Error + 'static + Send + Sync,
+    {
+        let recently_fetched = self
+            .providers
+            .lock()
+            .get(issuer.as_str())
+            .is_some_and(|cached| {
+                age(cached.fetched_at, now) < *AUTH_PROVIDER_METADATA_MIN_REFRESH_INTERVAL
+            });
+        if recently_fetched {
+            return Ok(None);
+        }
+        let metadata = self
+            .fetch(issuer, http_client, now, FetchReason::UnknownKey)
+            .await?;
+        Ok(Some(metadata))
+    }
+
+    /// Refetch every entry older than `AUTH_PROVIDER_METADATA_REFRESH_INTERVAL`
+    /// and drop entries that haven't been used within
+    /// `AUTH_PROVIDER_METADATA_MAX_STALENESS`, e.g. for removed providers.
+    /// Entries that fail to refresh are kept, so validation continues with
+    /// the previous keys until they're too stale.
+    pub async fn refresh_stale<F, E>(
+        &self,
+        http_client: impl Fn(HttpRequest) -> F + Clone + 'static,
+        now: SystemTime,
+    ) where
+        F: Future<Output = Result<HttpResponse, E>>,
+        E: std::error::Error + 'static + Send + Sync,
+    {
+        let stale: Vec<_> = {
+            let mut providers = self.providers.lock();
+            providers.retain(|_, cached| {
+                age(cached.last_used, now) < *AUTH_PROVIDER_METADATA_MAX_STALENESS
+            });
+            providers
+                .iter()
+                .filter(|(_, cached)| {
+                    age(cached.fetched_at, now) >= *AUTH_PROVIDER_METADATA_REFRESH_INTERVAL
+                })
+                .map(|(_, cached)| cached.issuer.clone())
+                .collect()
+        };
+        for issuer in stale {
+            if let Err(e) = self
+                .fetch(&issuer, http_client.clone(), now, FetchReason::Refresh)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to refresh auth provider metadata for {}: {e:#}",
+                    issuer.as_str()
+                );
+            }
+        }
+    }
+
+    async fn fetch<F, E>(
+        &self,
+        issuer: &IssuerUrl,
+        http_client: impl Fn(HttpRequest) -> F + 'static,
+        now: SystemTime,
+        reason: FetchReason,
+    ) -> anyhow::Result<Arc<CoreProviderMetadata>>
+    where
+        F: Future<Output = Result<HttpResponse, E>>,
+        E: std::error::Error + 'static + Send + Sync,
+    {
+        // Use the OpenID Connect Discovery protocol to get the public keys for
+        // this provider.
+        let result = CoreProviderMetadata::discover_async(issuer.clone(), http_client).await;
+        log_provider_metadata_fetch(issuer.as_str(), reason.as_label(), result.is_ok());
+        let metadata = Arc::new(result?);
+        let mut providers = self.providers.lock();
+        let last_used = providers
+            .get(issuer.as_str())
+            .map(|cached| cached.last_used)
+            .unwrap_or(now);
+        providers.insert(
+            issuer.as_str().to_owned(),
+            CachedProviderMetadata {
+                issuer: issuer.clone(),
+                metadata: metadata.clone(),
+                fetched_at: now,
+                last_used,
+            },
+        );
+        Ok(metadata)
+    }
+}
@@ -1,4 +1,8 @@
-use std::sync::LazyLock;
+use std::{
+    collections::BTreeSet,
+    iter,
+    sync::LazyLock,
+};
 
 use openidconnect::IssuerUrl;
 use regex::Regex;
@@ -7,6 +11,13 @@ use serde::{
     Deserializer,
     Serialize,
 };
+use sync_types::CanonicalizedUdfPath;
+
+use crate::{
+    components::CanonicalizedComponentFunctionPath,
+    runtime::UnixTimestamp,
+    types::UdfType,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, Ord, PartialOrd)]
 #[serde(rename_all = "camelCase")]
@@ -15,6 +26,16 @@ pub struct AuthInfo {
     pub application_id: String,
     #[serde(deserialize_with = "deserialize_url_default_to_https")]
     pub domain: IssuerUrl,
+    /// Other issuers whose tokens are accepted for this application, for
+    /// providers that issue tokens from more than one URL (e.g. per region or
+    /// per API version). Each must serve its own OpenID Connect discovery
+    /// document.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_urls_default_to_https"
+    )]
+    pub additional_issuers: Vec<IssuerUrl>,
 }
 
 static PROTOCOL_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\w+://").unwrap());
@@ -23,7 +44,20 @@ fn deserialize_url_default_to_https<'de, D>(deserializer: D) -> Result<IssuerUrl
 where
     D: Deserializer<'de>,
 {
-    let url = String::deserialize(deserializer)?;
+    parse_url_default_to_https(String::deserialize(deserializer)?)
+}
+
+fn deserialize_urls_default_to_https<'de, D>(deserializer: D) -> Result<Vec<IssuerUrl>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .map(parse_url_default_to_https)
+        .collect()
+}
+
+fn parse_url_default_to_https<E: serde::de::Error>(url: String) -> Result<IssuerUrl, E> {
     let url = if PROTOCOL_REGEX.is_match(&url) {
         url
     } else {
@@ -34,17 +68,22 @@ where
         .ok_or(serde::de::Error::custom("must use HTTPS"))
         .and_then(|url| serde_json::to_string(&url))
         .and_then(|json| serde_json::from_str(&json))
-        .map_err(|error| {
-            serde::de::Error::custom(format!("Invalid provider domain URL \"{url}\": {error}"))
-        })
+        .map_err(|error| E::custom(format!("Invalid provider domain URL \"{url}\": {error}")))
 }
 
 impl AuthInfo {
+    /// The issuers whose tokens are accepted: `domain` followed by
+    /// `additional_issuers`.
+    pub fn issuers(&self) -> impl Iterator<Item = &IssuerUrl> {
+        iter::once(&self.domain).chain(self.additional_issuers.iter())
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub fn test_example() -> Self {
         Self {
             application_id: "12345".to_string(),
             domain: IssuerUrl::new("https://convex.dev".to_string()).unwrap(),
+            additional_issuers: vec![],
         }
     }
 }
@@ -64,6 +103,7 @@ impl proptest::arbitrary::Arbitrary for AuthInfo {
                     .map(|domain| Self {
                         application_id: s,
                         domain,
+                        additional_issuers: vec![],
                     })
                     .ok()
             },
@@ -71,9 +111,64 @@ impl proptest::arbitrary::Arbitrary for AuthInfo {
     }
 }
 
+/// What a deployment API key may be used for. Keys can run internal as well
+/// as public functions, so a key that should only reach a few functions
+/// should list them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ApiKeyScope {
+    /// Only allow running queries.
+    pub read_only: bool,
+    /// Only allow running these functions in the root component, or any
+    /// function if `None`.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::option::of(proptest::collection::btree_set(proptest::prelude::any::<CanonicalizedUdfPath>(), 0..4))"
+        )
+    )]
+    pub functions: Option<BTreeSet<CanonicalizedUdfPath>>,
+}
+
+impl ApiKeyScope {
+    pub fn allows_udf_type(&self, udf_type: UdfType) -> bool {
+        !self.read_only || udf_type == UdfType::Query
+    }
+
+    pub fn allows_function(&self, path: &CanonicalizedComponentFunctionPath) -> bool {
+        match &self.functions {
+            None => true,
+            Some(functions) => path.component.is_root() && functions.contains(&path.udf_path),
+        }
+    }
+}
+
+/// Claims of a verified ID token that aren't part of the user's identity but
+/// identify the session the token belongs to, for checking whether it has been
+/// revoked.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SessionClaims {
+    /// When the token was issued (`iat`).
+    pub issued_at: Option<UnixTimestamp>,
+    /// The token's unique identifier (`jti`), if its provider sets one.
+    pub token_id: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::auth::AuthInfo;
+    use maplit::btreeset;
+
+    use crate::{
+        auth::{
+            ApiKeyScope,
+            AuthInfo,
+        },
+        components::{
+            CanonicalizedComponentFunctionPath,
+            ComponentPath,
+        },
+        types::UdfType,
+    };
 
     #[test]
     fn test_auth_info_https_prefix() {
@@ -82,6 +177,22 @@ mod tests {
         assert_eq!(info.domain.to_string(), "https://example.com");
     }
 
+    #[test]
+    fn test_auth_info_additional_issuers() {
+        let info: AuthInfo = serde_json::from_str(
+            r#"{"applicationID": "123", "domain": "example.com", "additionalIssuers": ["eu.example.com"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            info.additional_issuers[0].to_string(),
+            "https://eu.example.com"
+        );
+        serde_json::from_str::<AuthInfo>(
+            r#"{"applicationID": "123", "domain": "example.com", "additionalIssuers": ["http://eu.example.com"]}"#,
+        )
+        .unwrap_err();
+    }
+
     #[test]
     fn test_auth_info_http_fails() {
         serde_json::from_str::<AuthInfo>(
@@ -89,4 +200,24 @@ mod tests {
         )
         .unwrap_err();
     }
+
+    #[test]
+    fn test_api_key_scope() -> anyhow::Result<()> {
+        let path = |p: &str| -> anyhow::Result<_> {
+            Ok(CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: p.parse()?,
+            })
+        };
+        let scope = ApiKeyScope {
+            read_only: true,
+            functions: Some(btreeset! { "messages.js:list".parse()? }),
+        };
+        assert!(scope.allows_udf_type(UdfType::Query));
+        assert!(!scope.allows_udf_type(UdfType::Mutation));
+        assert!(scope.allows_function(&path("messages.js:list")?));
+        assert!(!scope.allows_function(&path("messages.js:send")?));
+        assert!(ApiKeyScope::default().allows_function(&path("messages.js:send")?));
+        Ok(())
+    }
 }
@@ -23,6 +23,9 @@ use crate::components::{
 pub struct ComponentMetadata {
     pub definition_id: InternalId,
     pub component_type: ComponentType,
+    // Set by admins rather than pushes, so they're kept when the component is
+    // updated by a push.
+    pub quotas: ComponentQuotas,
 }
 
 impl ComponentMetadata {
@@ -51,6 +54,90 @@ impl ComponentType {
     }
 }
 
+/// Limits on the resources a component can use, so a misbehaving component
+/// can't use up the whole deployment's. Limits that aren't set aren't
+/// enforced.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ComponentQuotas {
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_tables: Option<u64>,
+    // Jobs that are pending or in progress.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_scheduled_jobs: Option<u64>,
+    // Total size of the component's stored files.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_storage_bytes: Option<u64>,
+    // Calls to the component's functions from clients, actions and the
+    // scheduler. Calls from the component's parent within a query or mutation
+    // run in the parent's transaction, so they count as the parent's call.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_function_calls_per_minute: Option<u64>,
+}
+
+impl ComponentQuotas {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedComponentQuotas {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tables: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_scheduled_jobs: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_storage_bytes: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_function_calls_per_minute: Option<i64>,
+}
+
+impl TryFrom<ComponentQuotas> for SerializedComponentQuotas {
+    type Error = anyhow::Error;
+
+    fn try_from(quotas: ComponentQuotas) -> anyhow::Result<Self> {
+        Ok(Self {
+            max_tables: quotas.max_tables.map(i64::try_from).transpose()?,
+            max_scheduled_jobs: quotas.max_scheduled_jobs.map(i64::try_from).transpose()?,
+            max_storage_bytes: quotas.max_storage_bytes.map(i64::try_from).transpose()?,
+            max_function_calls_per_minute: quotas
+                .max_function_calls_per_minute
+                .map(i64::try_from)
+                .transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedComponentQuotas> for ComponentQuotas {
+    type Error = anyhow::Error;
+
+    fn try_from(quotas: SerializedComponentQuotas) -> anyhow::Result<Self> {
+        Ok(Self {
+            max_tables: quotas.max_tables.map(u64::try_from).transpose()?,
+            max_scheduled_jobs: quotas.max_scheduled_jobs.map(u64::try_from).transpose()?,
+            max_storage_bytes: quotas.max_storage_bytes.map(u64::try_from).transpose()?,
+            max_function_calls_per_minute: quotas
+                .max_function_calls_per_minute
+                .map(u64::try_from)
+                .transpose()?,
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SerializedComponentMetadata {
@@ -58,6 +145,8 @@ struct SerializedComponentMetadata {
     pub parent: Option<String>,
     pub name: Option<String>,
     pub args: Option<Vec<(String, SerializedResource)>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quotas: Option<SerializedComponentQuotas>,
 }
 
 impl TryFrom<ComponentMetadata> for SerializedComponentMetadata {
@@ -76,11 +165,17 @@ impl TryFrom<ComponentMetadata> for SerializedComponentMetadata {
                 ),
             ),
         };
+        let quotas = if m.quotas.is_empty() {
+            None
+        } else {
+            Some(m.quotas.try_into()?)
+        };
         Ok(Self {
             definition_id: m.definition_id.to_string(),
             parent,
             name,
             args,
+            quotas,
         })
     }
 }
@@ -104,6 +199,11 @@ impl TryFrom<SerializedComponentMetadata> for ComponentMetadata {
         Ok(Self {
             definition_id: m.definition_id.parse()?,
             component_type,
+            quotas: m
+                .quotas
+                .map(TryFrom::try_from)
+                .transpose()?
+                .unwrap_or_default(),
         })
     }
 }
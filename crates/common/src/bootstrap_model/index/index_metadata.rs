@@ -1,4 +1,7 @@
-use std::collections::BTreeSet;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
 
 use serde::{
     Deserialize,
@@ -87,6 +90,8 @@ impl<T: IndexTableIdentifier> IndexMetadata<T> {
             name,
             DeveloperTextIndexConfig {
                 search_field,
+                additional_search_fields: BTreeSet::new(),
+                search_field_boosts: BTreeMap::new(),
                 filter_fields,
             },
             TextIndexState::Backfilling(TextIndexBackfillState::new()),
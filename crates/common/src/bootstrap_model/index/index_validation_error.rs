@@ -3,7 +3,11 @@ use value::TableName;
 
 use crate::{
     paths::FieldPath,
-    schemas::IndexSchema,
+    schemas::{
+        IndexSchema,
+        MAX_SKETCHES_PER_TABLE,
+        MAX_SOFT_DELETE_RETENTION_DAYS,
+    },
     types::{
         IndexDescriptor,
         IndexTableIdentifier,
@@ -68,8 +72,8 @@ pub fn search_field_not_unique(
         "SearchIndexFieldNotUnique",
         format!(
             "In table \"{table_name}\" search index \"{index1}\" and search index \"{index2}\" \
-             have the same `searchField`. Search index fields must be unique within a table. You \
-             should combine the
+             have the same `searchField` and `additionalSearchFields`. Search index fields must \
+             be unique within a table. You should combine the
              indexes with the same `searchField` into one index containing all `filterField`s and \
              then use different subsets of the `filterField`s at query time."
         ),
@@ -126,6 +130,85 @@ pub fn invalid_index_field(descriptor: &IndexDescriptor, field: &str) -> ErrorMe
     )
 }
 
+pub fn encrypted_field_indexed(
+    table_name: &TableName,
+    index: &IndexDescriptor,
+    field: &FieldPath,
+) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "EncryptedFieldIndexed",
+        format!(
+            "In table \"{table_name}\" index \"{index}\" uses the encrypted field \"{field}\". \
+             Encrypted fields can't be indexed."
+        ),
+    )
+}
+
+pub fn invalid_encrypted_field(table_name: &TableName, field: &str) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidEncryptedField",
+        format!(
+            "In table \"{table_name}\": Invalid encrypted field \"{field}\". Only top-level, \
+             non-system fields can be encrypted."
+        ),
+    )
+}
+
+pub fn invalid_trigger(table_name: &TableName, function: &str) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidTrigger",
+        format!(
+            "In table \"{table_name}\": Invalid trigger function \"{function}\". Triggers must be \
+             mutations, referenced as \"path/to/module:functionName\"."
+        ),
+    )
+}
+
+pub fn invalid_trigger_timing(table_name: &TableName, timing: &str) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidTrigger",
+        format!(
+            "In table \"{table_name}\": Invalid trigger timing \"{timing}\". Expected \"before\" \
+             or \"after\"."
+        ),
+    )
+}
+
+pub fn invalid_soft_delete_retention(table_name: &TableName, retention_days: u32) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidSoftDelete",
+        format!(
+            "In table \"{table_name}\": Soft delete retention must be between 1 and \
+             {MAX_SOFT_DELETE_RETENTION_DAYS} days, not {retention_days}."
+        ),
+    )
+}
+
+pub fn invalid_external_id_field(
+    table_name: &TableName,
+    field: &str,
+    reason: &str,
+) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidIdStrategy",
+        format!("In table \"{table_name}\": External ID field {field:?} {reason}."),
+    )
+}
+
+pub fn invalid_sketch(table_name: &TableName, sketch: &str, reason: &str) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidSketch",
+        format!("In table \"{table_name}\": Sketch {sketch:?} {reason}."),
+    )
+}
+
+pub fn too_many_sketches(table_name: &TableName) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "TooManySketches",
+        format!("Table \"{table_name}\" cannot have more than {MAX_SKETCHES_PER_TABLE} sketches."),
+    )
+}
+
 // TODO - move elsewhere (near table names) - it's not indexing related
 pub fn invalid_table_name(table_name: &str) -> ErrorMetadata {
     ErrorMetadata::bad_request(
@@ -154,6 +237,22 @@ pub fn too_many_filter_fields(num_fields: usize) -> ErrorMetadata {
         format!("Search indexes may have up to {num_fields} filter fields."),
     )
 }
+pub fn too_many_search_fields(num_fields: usize) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "IndexTooManySearchFields",
+        format!("Search indexes may have up to {num_fields} search fields."),
+    )
+}
+pub fn invalid_search_field_boost(
+    index: &IndexDescriptor,
+    field: &FieldPath,
+    reason: &str,
+) -> ErrorMetadata {
+    ErrorMetadata::bad_request(
+        "InvalidSearchFieldBoost",
+        format!("In search index \"{index}\": The boost for {field} {reason}."),
+    )
+}
 pub fn too_many_indexes(table_name: &TableName, num_indexes: usize) -> ErrorMetadata {
     ErrorMetadata::bad_request(
         "TooManyIndexes",
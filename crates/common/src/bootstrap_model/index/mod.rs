@@ -36,4 +36,8 @@ pub static TABLE_ID_FIELD_PATH: LazyLock<FieldPath> =
 
 pub const MAX_INDEX_FIELDS_SIZE: usize = 16;
 pub const MAX_SEARCH_INDEX_FILTER_FIELDS_SIZE: usize = 16;
+pub const MAX_SEARCH_INDEX_SEARCH_FIELDS_SIZE: usize = 8;
+/// Boosts are applied at index time by indexing a field's text once per unit
+/// of boost, so they're kept small.
+pub const MAX_SEARCH_FIELD_BOOST: u32 = 10;
 pub const MAX_VECTOR_INDEX_FILTER_FIELDS_SIZE: usize = 16;
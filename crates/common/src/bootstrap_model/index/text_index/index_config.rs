@@ -1,4 +1,7 @@
-use std::collections::BTreeSet;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
 
 use serde::{
     Deserialize,
@@ -14,6 +17,15 @@ pub struct DeveloperTextIndexConfig {
     /// The field to index for full text search.
     pub search_field: FieldPath,
 
+    /// More fields to index for full text search. Queries against the index
+    /// match text in any of its search fields.
+    pub additional_search_fields: BTreeSet<FieldPath>,
+
+    /// How many times more a match in a search field counts towards a
+    /// document's score than a match in an unboosted one. Search fields
+    /// without a boost have a boost of 1.
+    pub search_field_boosts: BTreeMap<FieldPath, u32>,
+
     /// Other fields to index for equality filtering.
     pub filter_fields: BTreeSet<FieldPath>,
 }
@@ -22,15 +34,39 @@ pub struct DeveloperTextIndexConfig {
 #[serde(rename_all = "camelCase")]
 pub struct SerializedDeveloperTextIndexConfig {
     search_field: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    additional_search_fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    search_field_boosts: Vec<SerializedSearchFieldBoost>,
     filter_fields: Vec<String>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSearchFieldBoost {
+    field_path: String,
+    boost: i64,
+}
+
 impl TryFrom<DeveloperTextIndexConfig> for SerializedDeveloperTextIndexConfig {
     type Error = anyhow::Error;
 
     fn try_from(config: DeveloperTextIndexConfig) -> anyhow::Result<Self> {
         Ok(Self {
             search_field: config.search_field.into(),
+            additional_search_fields: config
+                .additional_search_fields
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            search_field_boosts: config
+                .search_field_boosts
+                .into_iter()
+                .map(|(field_path, boost)| SerializedSearchFieldBoost {
+                    field_path: field_path.into(),
+                    boost: boost.into(),
+                })
+                .collect(),
             filter_fields: config.filter_fields.into_iter().map(String::from).collect(),
         })
     }
@@ -42,6 +78,16 @@ impl TryFrom<SerializedDeveloperTextIndexConfig> for DeveloperTextIndexConfig {
     fn try_from(config: SerializedDeveloperTextIndexConfig) -> anyhow::Result<Self> {
         Ok(Self {
             search_field: config.search_field.parse()?,
+            additional_search_fields: config
+                .additional_search_fields
+                .into_iter()
+                .map(|p| p.parse())
+                .collect::<anyhow::Result<BTreeSet<FieldPath>>>()?,
+            search_field_boosts: config
+                .search_field_boosts
+                .into_iter()
+                .map(|b| anyhow::Ok((b.field_path.parse()?, b.boost.try_into()?)))
+                .collect::<anyhow::Result<BTreeMap<FieldPath, u32>>>()?,
             filter_fields: config
                 .filter_fields
                 .into_iter()
@@ -62,6 +108,22 @@ impl TryFrom<pb::searchlight::SearchIndexConfig> for DeveloperTextIndexConfig {
                 .search_field_path
                 .ok_or_else(|| anyhow::format_err!("Missing search_field_path"))?
                 .try_into()?,
+            additional_search_fields: proto
+                .additional_search_fields
+                .into_iter()
+                .map(|i| i.try_into())
+                .collect::<anyhow::Result<_>>()?,
+            search_field_boosts: proto
+                .search_field_boosts
+                .into_iter()
+                .map(|b| {
+                    let field_path = b
+                        .path
+                        .ok_or_else(|| anyhow::format_err!("Missing search field boost path"))?
+                        .try_into()?;
+                    anyhow::Ok((field_path, b.boost))
+                })
+                .collect::<anyhow::Result<_>>()?,
             filter_fields: proto
                 .filter_fields
                 .into_iter()
@@ -77,6 +139,19 @@ impl From<DeveloperTextIndexConfig> for pb::searchlight::SearchIndexConfig {
     fn from(config: DeveloperTextIndexConfig) -> Self {
         pb::searchlight::SearchIndexConfig {
             search_field_path: Some(config.search_field.into()),
+            additional_search_fields: config
+                .additional_search_fields
+                .into_iter()
+                .map(|f| f.into())
+                .collect::<Vec<_>>(),
+            search_field_boosts: config
+                .search_field_boosts
+                .into_iter()
+                .map(|(path, boost)| pb::searchlight::SearchFieldBoost {
+                    path: Some(path.into()),
+                    boost,
+                })
+                .collect::<Vec<_>>(),
             filter_fields: config
                 .filter_fields
                 .into_iter()
@@ -6,7 +6,9 @@ use value::codegen_convex_serialization;
 
 use super::{
     index_snapshot::SerializedTextIndexSnapshot,
+    FragmentedTextSegment,
     TextIndexSnapshot,
+    TextIndexSnapshotData,
 };
 use crate::bootstrap_model::index::text_index::backfill_state::{
     SerializedTextIndexBackfillState,
@@ -27,6 +29,20 @@ pub enum TextIndexState {
     SnapshottedAt(TextIndexSnapshot),
 }
 
+impl TextIndexState {
+    pub fn segments(&self) -> anyhow::Result<&Vec<FragmentedTextSegment>> {
+        match self {
+            TextIndexState::Backfilling(backfill_state) => Ok(&backfill_state.segments),
+            TextIndexState::Backfilled(snapshot) | TextIndexState::SnapshottedAt(snapshot) => {
+                match snapshot.data {
+                    TextIndexSnapshotData::MultiSegment(ref segments) => Ok(segments),
+                    TextIndexSnapshotData::Unknown(_) => anyhow::bail!("Unknown snapshot data!"),
+                }
+            },
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(tag = "state", rename_all = "camelCase")]
 pub enum SerializedTextIndexState {
@@ -41,6 +41,11 @@ pub enum TableState {
     /// Deleting -- for now. Eventually we may want to clean up Deleting tables
     /// and delete the _tables rows or create a new Deleted state.
     Deleting,
+    /// The table was archived by a developer. Like a Hidden table, it appears
+    /// in only one direction of TableMapping, so its name can be reused and
+    /// its documents can't be read by name, but they're retained until the
+    /// table is restored to Active.
+    Archived,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -108,6 +113,7 @@ impl TryFrom<TableMetadata> for SerializedTableMetadata {
                 TableState::Active => "active".to_owned(),
                 TableState::Deleting => "deleting".to_owned(),
                 TableState::Hidden => "hidden".to_owned(),
+                TableState::Archived => "archived".to_owned(),
             },
             namespace: table_namespace_to_serialized(m.namespace)?,
         })
@@ -125,6 +131,7 @@ impl TryFrom<SerializedTableMetadata> for TableMetadata {
                 "active" => TableState::Active,
                 "deleting" => TableState::Deleting,
                 "hidden" => TableState::Hidden,
+                "archived" => TableState::Archived,
                 s => anyhow::bail!("invalid table state {s}"),
             },
             namespace: table_namespace_from_serialized(m.namespace)?,
@@ -84,6 +84,18 @@ pub static CREATION_TIME_FIELD: LazyLock<IdentifierFieldName> =
 pub static CREATION_TIME_FIELD_PATH: LazyLock<FieldPath> =
     LazyLock::new(|| FieldPath::new(vec![CREATION_TIME_FIELD.clone()]).unwrap());
 
+/// In tables whose schema tracks updates, the database sets the time of the
+/// latest write to each document in the "_updatedTime" field. Like
+/// "_creationTime", it's a Float64 of milliseconds since the Unix epoch.
+pub static UPDATED_TIME_FIELD: LazyLock<IdentifierFieldName> =
+    LazyLock::new(|| "_updatedTime".parse().unwrap());
+
+/// In tables whose schema tracks updates, the database sets the token
+/// identifier of the user who made the latest write to each document in the
+/// "_updatedBy" field, or null if the write wasn't made by a user.
+pub static UPDATED_BY_FIELD: LazyLock<IdentifierFieldName> =
+    LazyLock::new(|| "_updatedBy".parse().unwrap());
+
 // The current Unix timestamp (as of 2022-08-02) in milliseconds is
 //
 //     1659481438151.257
@@ -432,8 +444,8 @@ impl ResolvedDocument {
         Ok(())
     }
 
-    /// Checks system fields _id and _creationTime and confirms
-    /// those are the only system fields.
+    /// Checks system fields _id and _creationTime, and _updatedTime and
+    /// _updatedBy if present, and confirms those are the only system fields.
     /// Returns vec of violations, which may be displayed to clients.
     pub fn validate(&self) -> Vec<DocumentValidationError> {
         fn validate_inner_value(
@@ -471,6 +483,8 @@ impl ResolvedDocument {
                 | ConvexValue::Null
                 | ConvexValue::Int64(_)
                 | ConvexValue::Float64(_)
+                | ConvexValue::Decimal(_)
+                | ConvexValue::GeoPoint(_)
                 | ConvexValue::String(_) => {},
             }
         }
@@ -524,6 +538,18 @@ impl ResolvedDocument {
             {
                 continue;
             }
+            if field == &(*UPDATED_TIME_FIELD).clone().into() {
+                if !matches!(value, ConvexValue::Float64(_)) {
+                    violations.push(DocumentValidationError::UpdatedTimeBadType(value.clone()));
+                }
+                continue;
+            }
+            if field == &(*UPDATED_BY_FIELD).clone().into() {
+                if !matches!(value, ConvexValue::String(_) | ConvexValue::Null) {
+                    violations.push(DocumentValidationError::UpdatedByBadType(value.clone()));
+                }
+                continue;
+            }
             if field.is_system() {
                 violations.push(DocumentValidationError::SystemField(field.clone()));
             }
@@ -863,6 +889,10 @@ pub enum DocumentValidationError {
     CreationTimeBadType(ConvexValue),
     #[error("The '_creationTime' field is missing")]
     CreationTimeMissing,
+    #[error("The '_updatedTime' field should be a float, is {0}")]
+    UpdatedTimeBadType(ConvexValue),
+    #[error("The '_updatedBy' field should be a string or null, is {0}")]
+    UpdatedByBadType(ConvexValue),
     #[error(
         "Document is too nested (nested {0} levels deep > maximum nesting {MAX_DOCUMENT_NESTING})"
     )]
@@ -881,6 +911,8 @@ impl DocumentValidationError {
             DocumentValidationError::CreationTimeInvalidFloat(_) => "_creationTime invalid float",
             DocumentValidationError::CreationTimeBadType(_) => "_creationTime wrong type",
             DocumentValidationError::CreationTimeMissing => "_creationTime missing",
+            DocumentValidationError::UpdatedTimeBadType(_) => "_updatedTime wrong type",
+            DocumentValidationError::UpdatedByBadType(_) => "_updatedBy wrong type",
             DocumentValidationError::TooNested(_) => "too nested",
         }
     }
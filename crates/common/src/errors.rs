@@ -18,6 +18,7 @@ pub use errors::{
 };
 use metrics::log_counter;
 use pb::common::{
+    CustomDataSource as CustomDataSourceProto,
     FrameData as FrameDataProto,
     JsError as JsErrorProto,
     JsFrames as JsFramesProto,
@@ -362,6 +363,51 @@ impl fmt::Display for FrameData {
     }
 }
 
+/// A typed application error thrown by a function. See
+/// [`JsError::typed_error`].
+#[derive(Debug, PartialEq)]
+pub struct TypedError<'a> {
+    pub code: &'a str,
+    pub data: Option<&'a ConvexValue>,
+}
+
+/// Where the `custom_data` of a [`JsError`] came from. This decides whether
+/// clients can see it when errors are redacted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum CustomDataSource {
+    /// The function threw a `ConvexError` with this data.
+    #[default]
+    Thrown,
+    /// Convex attached this data to describe why a value failed validation.
+    /// It can reveal the function's validators, so it's redacted along with
+    /// the message.
+    Validation,
+    /// The function threw a typed error with a code it declares, and the
+    /// data matched the code's validator.
+    DeclaredTypedError,
+}
+
+impl From<CustomDataSource> for CustomDataSourceProto {
+    fn from(source: CustomDataSource) -> Self {
+        match source {
+            CustomDataSource::Thrown => CustomDataSourceProto::Thrown,
+            CustomDataSource::Validation => CustomDataSourceProto::Validation,
+            CustomDataSource::DeclaredTypedError => CustomDataSourceProto::DeclaredTypedError,
+        }
+    }
+}
+
+impl From<CustomDataSourceProto> for CustomDataSource {
+    fn from(source: CustomDataSourceProto) -> Self {
+        match source {
+            CustomDataSourceProto::Thrown => CustomDataSource::Thrown,
+            CustomDataSourceProto::Validation => CustomDataSource::Validation,
+            CustomDataSourceProto::DeclaredTypedError => CustomDataSource::DeclaredTypedError,
+        }
+    }
+}
+
 /// An Error emitted from a Convex Function execution.
 #[derive(Clone)]
 #[cfg_attr(
@@ -371,6 +417,7 @@ impl fmt::Display for FrameData {
 pub struct JsError {
     pub message: String,
     pub custom_data: Option<ConvexValue>,
+    pub custom_data_source: CustomDataSource,
     pub frames: Option<JsFrames>,
 }
 
@@ -388,6 +435,7 @@ impl TryFrom<JsError> for JsErrorProto {
         JsError {
             message,
             custom_data,
+            custom_data_source,
             frames,
         }: JsError,
     ) -> anyhow::Result<Self> {
@@ -400,6 +448,7 @@ impl TryFrom<JsError> for JsErrorProto {
                 })
                 .transpose()?,
             frames: frames.map(JsFramesProto::from),
+            custom_data_source: Some(CustomDataSourceProto::from(custom_data_source).into()),
         })
     }
 }
@@ -412,6 +461,7 @@ impl TryFrom<JsErrorProto> for JsError {
             message,
             custom_data,
             frames,
+            custom_data_source,
         }: JsErrorProto,
     ) -> anyhow::Result<Self> {
         Ok(Self {
@@ -423,6 +473,11 @@ impl TryFrom<JsErrorProto> for JsError {
                     anyhow::Ok::<ConvexValue>(developer_value)
                 })
                 .transpose()?,
+            custom_data_source: custom_data_source
+                .map(CustomDataSourceProto::try_from)
+                .transpose()?
+                .map(CustomDataSource::from)
+                .unwrap_or_default(),
             frames: frames.map(JsFrames::try_from).transpose()?,
         })
     }
@@ -484,6 +539,7 @@ impl JsError {
         Self {
             message,
             custom_data: None,
+            custom_data_source: CustomDataSource::Thrown,
             frames: None,
         }
     }
@@ -492,10 +548,44 @@ impl JsError {
         Self {
             message,
             custom_data: Some(data),
+            custom_data_source: CustomDataSource::Thrown,
             frames: None,
         }
     }
 
+    /// An error for a value that failed validation, with `data` describing
+    /// where and why.
+    pub fn validation_error(message: String, data: ConvexValue) -> Self {
+        Self {
+            message,
+            custom_data: Some(data),
+            custom_data_source: CustomDataSource::Validation,
+            frames: None,
+        }
+    }
+
+    /// The code and payload of a typed application error, i.e. a
+    /// `ConvexError` whose data is `{ code, data }`, as thrown by e.g.
+    /// `throw new ConvexError({ code: "NotFound", data: { id } })`.
+    pub fn typed_error(&self) -> Option<TypedError<'_>> {
+        let Some(ConvexValue::Object(object)) = &self.custom_data else {
+            return None;
+        };
+        let Some(ConvexValue::String(code)) = object.get("code") else {
+            return None;
+        };
+        if object
+            .keys()
+            .any(|field| !matches!(&field[..], "code" | "data"))
+        {
+            return None;
+        }
+        Some(TypedError {
+            code,
+            data: object.get("data"),
+        })
+    }
+
     pub fn from_frames(
         message: String,
         frame_data: Vec<FrameData>,
@@ -568,6 +658,7 @@ impl JsError {
         JsError {
             message,
             custom_data,
+            custom_data_source: CustomDataSource::Thrown,
             frames: Some(JsFrames(mapped_frames.into())),
         }
     }
@@ -7,6 +7,7 @@ use std::{
 };
 
 use anyhow::Context;
+use http::HeaderMap;
 use rand::Rng;
 use serde_json::{
     json,
@@ -37,6 +38,9 @@ pub struct ExecutionContext {
     /// version of this would be something like parent_execution_id:
     /// Option<ExecutionId>
     is_root: bool,
+    /// The `traceparent` of the request or function that ran this one, if it
+    /// was part of a distributed trace.
+    pub trace_parent: Option<TraceParent>,
 }
 
 impl ExecutionContext {
@@ -46,6 +50,7 @@ impl ExecutionContext {
             execution_id: ExecutionId::new(),
             parent_scheduled_job: caller.parent_scheduled_job(),
             is_root: caller.is_root(),
+            trace_parent: caller.trace_parent(),
         }
     }
 
@@ -54,12 +59,14 @@ impl ExecutionContext {
         execution_id: ExecutionId,
         parent_scheduled_job: Option<DeveloperDocumentId>,
         is_root: bool,
+        trace_parent: Option<TraceParent>,
     ) -> Self {
         Self {
             request_id,
             execution_id,
             parent_scheduled_job,
             is_root,
+            trace_parent,
         }
     }
 
@@ -67,6 +74,27 @@ impl ExecutionContext {
         self.is_root
     }
 
+    /// The `traceparent` to send with requests this function makes. It
+    /// continues the caller's trace if there is one, and otherwise starts a
+    /// trace whose id is the `ExecutionId`. Either way, the function's span id
+    /// comes from its `ExecutionId`.
+    pub fn traceparent(&self) -> TraceParent {
+        let execution_id = self.execution_id.0.as_bytes();
+        let mut parent_id = [0; 8];
+        parent_id.copy_from_slice(&execution_id[8..]);
+        match self.trace_parent {
+            Some(trace_parent) => TraceParent {
+                parent_id,
+                ..trace_parent
+            },
+            None => TraceParent {
+                trace_id: *execution_id,
+                parent_id,
+                flags: TraceParent::SAMPLED,
+            },
+        }
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub fn new_for_test() -> Self {
         Self {
@@ -74,6 +102,7 @@ impl ExecutionContext {
             execution_id: ExecutionId::new(),
             parent_scheduled_job: None,
             is_root: true,
+            trace_parent: None,
         }
     }
 }
@@ -84,6 +113,7 @@ impl HeapSize for ExecutionContext {
             + self.execution_id.heap_size()
             + self.parent_scheduled_job.heap_size()
             + self.is_root.heap_size()
+            + self.trace_parent.heap_size()
     }
 }
 
@@ -203,6 +233,117 @@ impl HeapSize for ExecutionId {
     }
 }
 
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// A W3C Trace Context `traceparent`, which lets a request join the
+/// distributed trace of the request that caused it, e.g.
+/// `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TraceParent {
+    trace_id: [u8; 16],
+    /// The id of the caller's span.
+    parent_id: [u8; 8],
+    flags: u8,
+}
+
+impl TraceParent {
+    const SAMPLED: u8 = 0x01;
+
+    /// The `traceparent` header of an incoming request. A missing or invalid
+    /// header starts a new trace, as the spec requires.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        headers.get(TRACEPARENT_HEADER)?.to_str().ok()?.parse().ok()
+    }
+
+    pub fn trace_id(&self) -> String {
+        hex::encode(self.trace_id)
+    }
+
+    pub fn parent_id(&self) -> String {
+        hex::encode(self.parent_id)
+    }
+}
+
+fn parse_lowercase_hex(field: &str, out: &mut [u8]) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        field.len() == out.len() * 2
+            && field
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)),
+        "Invalid traceparent field {field}"
+    );
+    hex::decode_to_slice(field, out)?;
+    Ok(())
+}
+
+impl FromStr for TraceParent {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.trim().split('-');
+        let (Some(version), Some(trace_id_field), Some(parent_id_field), Some(flags_field)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            anyhow::bail!("Invalid traceparent {s}");
+        };
+        let mut version_byte = [0];
+        parse_lowercase_hex(version, &mut version_byte)?;
+        anyhow::ensure!(version_byte[0] != 0xff, "Invalid traceparent version");
+        // Later versions may add fields, which we ignore.
+        anyhow::ensure!(
+            version_byte[0] != 0 || fields.next().is_none(),
+            "Invalid traceparent {s}"
+        );
+        let mut trace_id = [0; 16];
+        parse_lowercase_hex(trace_id_field, &mut trace_id)?;
+        let mut parent_id = [0; 8];
+        parse_lowercase_hex(parent_id_field, &mut parent_id)?;
+        let mut flags = [0];
+        parse_lowercase_hex(flags_field, &mut flags)?;
+        anyhow::ensure!(
+            trace_id != [0; 16] && parent_id != [0; 8],
+            "Invalid traceparent {s}"
+        );
+        Ok(Self {
+            trace_id,
+            parent_id,
+            flags: flags[0],
+        })
+    }
+}
+
+impl Display for TraceParent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "00-{}-{}-{:02x}",
+            self.trace_id(),
+            self.parent_id(),
+            self.flags
+        )
+    }
+}
+
+impl HeapSize for TraceParent {
+    fn heap_size(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for TraceParent {
+    type Parameters = ();
+
+    type Strategy = impl proptest::strategy::Strategy<Value = Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        "00-[a-f0-9]{32}-[a-f0-9]{16}-0[01]"
+            .prop_filter_map("Invalid traceparent", |s| s.parse().ok())
+            .boxed()
+    }
+}
+
 impl From<ExecutionContext> for pb::common::ExecutionContext {
     fn from(value: ExecutionContext) -> Self {
         pb::common::ExecutionContext {
@@ -210,6 +351,7 @@ impl From<ExecutionContext> for pb::common::ExecutionContext {
             execution_id: Some(value.execution_id.to_string()),
             parent_scheduled_job: value.parent_scheduled_job.map(|id| id.into()),
             is_root: Some(value.is_root),
+            trace_parent: value.trace_parent.map(|t| t.to_string()),
         }
     }
 }
@@ -226,17 +368,89 @@ impl TryFrom<pb::common::ExecutionContext> for ExecutionContext {
             },
             parent_scheduled_job: value.parent_scheduled_job.map(|s| s.parse()).transpose()?,
             is_root: value.is_root.unwrap_or_default(),
+            trace_parent: value.trace_parent.map(|t| t.parse()).transpose()?,
         })
     }
 }
 
 impl From<ExecutionContext> for JsonValue {
     fn from(value: ExecutionContext) -> Self {
+        let traceparent = value.traceparent();
         json!({
             "requestId": String::from(value.request_id),
             "executionId": value.execution_id.to_string(),
             "isRoot": value.is_root,
             "parentScheduledJob": value.parent_scheduled_job.map(|id| id.to_string()),
+            "traceparent": traceparent.to_string(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use sync_types::testing::assert_roundtrips;
+
+    use super::{
+        ExecutionContext,
+        TraceParent,
+    };
+
+    #[test]
+    fn test_parse_traceparent() -> anyhow::Result<()> {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let trace_parent: TraceParent = header.parse()?;
+        assert_eq!(trace_parent.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(trace_parent.parent_id(), "00f067aa0ba902b7");
+        assert_eq!(trace_parent.to_string(), header);
+
+        // Later versions can add fields.
+        let trace_parent: TraceParent =
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra".parse()?;
+        assert_eq!(trace_parent.to_string(), header);
+
+        for invalid in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01",
+        ] {
+            assert!(invalid.parse::<TraceParent>().is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_traceparent_continues_trace() -> anyhow::Result<()> {
+        let mut context = ExecutionContext::new_for_test();
+        let trace_parent = context.traceparent();
+        assert_eq!(
+            trace_parent.trace_id(),
+            context.execution_id.to_string().replace('-', "")
+        );
+
+        let incoming: TraceParent =
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00".parse()?;
+        context.trace_parent = Some(incoming);
+        let outgoing = context.traceparent();
+        assert_eq!(outgoing.trace_id(), incoming.trace_id());
+        assert_eq!(outgoing.parent_id(), trace_parent.parent_id());
+        assert!(outgoing.to_string().ends_with("-00"));
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_execution_context_roundtrips(u in any::<ExecutionContext>()) {
+            assert_roundtrips::<ExecutionContext, pb::common::ExecutionContext>(u);
+        }
+    }
+}
@@ -4,6 +4,7 @@ use std::collections::{
 };
 
 use async_trait::async_trait;
+use errors::ErrorMetadata;
 use futures::{
     future::BoxFuture,
     StreamExt,
@@ -39,17 +40,39 @@ pub trait FetchClient: Send + Sync {
         request: HttpRequestStream,
         purpose: InternalFetchPurpose,
     ) -> anyhow::Result<HttpResponseStream>;
+
+    /// Fetch through a proxy chosen by the deployment's egress policy rather
+    /// than by the backend.
+    async fn fetch_through_proxy(
+        &self,
+        _request: HttpRequestStream,
+        _proxy: &FetchProxy,
+    ) -> anyhow::Result<HttpResponseStream> {
+        anyhow::bail!("This fetch client doesn't support egress proxies")
+    }
+}
+
+/// An HTTP proxy that all of a deployment's requests must go through.
+#[derive(Clone, Debug)]
+pub struct FetchProxy {
+    pub url: Url,
+    pub username: Option<String>,
+    pub password: Option<String>,
 }
 
 #[derive(Clone)]
 pub struct ProxiedFetchClient {
     http_client: reqwest::Client,
     internal_http_client: reqwest::Client,
+    // Whether requests go through the backend's own proxy, which egress
+    // proxies would bypass.
+    has_backend_proxy: bool,
 }
 
 impl ProxiedFetchClient {
     pub fn new(proxy_url: Option<Url>, client_id: String) -> Self {
         let mut builder = reqwest::Client::builder().redirect(redirect::Policy::none());
+        let has_backend_proxy = proxy_url.is_some();
         // It's okay to panic on these errors, as they indicate a serious programming
         // error -- building the reqwest client is expected to be infallible.
         if let Some(proxy_url) = proxy_url {
@@ -65,8 +88,31 @@ impl ProxiedFetchClient {
         Self {
             http_client: builder.build().expect("Failed to build reqwest client"),
             internal_http_client: reqwest::Client::new(),
+            has_backend_proxy,
         }
     }
+
+    async fn execute(
+        http_client: &reqwest::Client,
+        request: HttpRequestStream,
+    ) -> anyhow::Result<HttpResponseStream> {
+        let mut request_builder = http_client.request(request.method, request.url.as_str());
+        let body = Body::wrap_stream(request.body);
+        request_builder = request_builder.body(body);
+        for (name, value) in &request.headers {
+            request_builder = request_builder.header(name.as_str(), value.as_bytes());
+        }
+        let raw_request = request_builder.build()?;
+        let raw_response = http_client.execute(raw_request).await?;
+        let status = raw_response.status();
+        let headers = raw_response.headers().to_owned();
+        Ok(HttpResponseStream {
+            status,
+            headers,
+            url: Some(request.url),
+            body: Some(raw_response.bytes_stream().map_err(|e| e.into()).boxed()),
+        })
+    }
 }
 
 #[async_trait]
@@ -104,25 +150,34 @@ impl FetchClient for ProxiedFetchClient {
         request: HttpRequestStream,
         _purpose: InternalFetchPurpose,
     ) -> anyhow::Result<HttpResponseStream> {
-        let mut request_builder = self
-            .internal_http_client
-            .request(request.method, request.url.as_str());
-        let body = Body::wrap_stream(request.body);
-        request_builder = request_builder.body(body);
-        for (name, value) in &request.headers {
-            request_builder = request_builder.header(name.as_str(), value.as_bytes());
+        Self::execute(&self.internal_http_client, request).await
+    }
+
+    async fn fetch_through_proxy(
+        &self,
+        request: HttpRequestStream,
+        proxy: &FetchProxy,
+    ) -> anyhow::Result<HttpResponseStream> {
+        // The backend's proxy blocks requests to non-public addresses, and
+        // reqwest can't chain proxies, so only allow egress proxies when there
+        // isn't one.
+        anyhow::ensure!(
+            !self.has_backend_proxy,
+            ErrorMetadata::bad_request(
+                "EgressProxyUnsupported",
+                "Egress proxies aren't supported on this deployment",
+            )
+        );
+        let mut egress_proxy = Proxy::all(proxy.url.clone())?;
+        if let Some(username) = &proxy.username {
+            egress_proxy =
+                egress_proxy.basic_auth(username, proxy.password.as_deref().unwrap_or(""));
         }
-        let raw_request = request_builder.build()?;
-        let raw_response = self.internal_http_client.execute(raw_request).await?;
-        let status = raw_response.status();
-        let headers = raw_response.headers().to_owned();
-        let response = HttpResponseStream {
-            status,
-            headers,
-            url: Some(request.url),
-            body: Some(raw_response.bytes_stream().map_err(|e| e.into()).boxed()),
-        };
-        Ok(response)
+        let http_client = reqwest::Client::builder()
+            .redirect(redirect::Policy::none())
+            .proxy(egress_proxy)
+            .build()?;
+        Self::execute(&http_client, request).await
     }
 }
 
@@ -185,6 +240,10 @@ impl FetchClient for StaticFetchClient {
 
 pub enum InternalFetchPurpose {
     AccessTokenAuth,
+    /// Fetching secrets that environment variables refer to, which can be on
+    /// the internal network and must not go through a deployment's egress
+    /// proxy.
+    SecretManager,
 }
 
 #[cfg(test)]
@@ -578,9 +578,8 @@ impl ConvexHttpService {
                     }))
                     .layer(TimeoutLayer::new(request_timeout)),
             )
-            // Middleware needn't apply to these routes
+            // Middleware needn't apply to this route
             .route("/version", get(move || async move { version }))
-            .route("/metrics", get(metrics))
             .layer(sentry_layer);
 
         Self { router }
@@ -597,6 +596,12 @@ impl ConvexHttpService {
         serve_http(make_svc, addr, shutdown).await
     }
 
+    /// The service's router with its middleware, for serving it on a listener
+    /// other than the one `serve` binds, e.g. one that terminates TLS.
+    pub fn into_router(self) -> Router<(), Body> {
+        self.router
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub fn new_for_test(router: Router<(), Body>) -> Self {
         Self { router }
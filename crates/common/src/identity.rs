@@ -33,6 +33,8 @@ pub enum InertIdentity {
     Unknown,
     User(UserIdentifier),
     ActingUser(MemberId, UserIdentifier),
+    /// Deployment API key, by name.
+    ApiKey(String),
 }
 
 impl InertIdentity {
@@ -54,6 +56,7 @@ impl HeapSize for InertIdentity {
             InertIdentity::Unknown => 0,
             InertIdentity::User(u) => u.0.heap_size(),
             InertIdentity::ActingUser(_m, u) => u.0.heap_size(),
+            InertIdentity::ApiKey(name) => name.heap_size(),
         }
     }
 }
@@ -72,6 +75,8 @@ pub enum IdentityCacheKey {
     Unknown,
     User(UserIdentityAttributes),
     ActingUser(MemberId, UserIdentityAttributes),
+    /// Deployment API key, by ID.
+    ApiKey(String),
 }
 
 impl HeapSize for IdentityCacheKey {
@@ -82,6 +87,7 @@ impl HeapSize for IdentityCacheKey {
             IdentityCacheKey::Unknown => 0,
             IdentityCacheKey::User(u) => u.heap_size(),
             IdentityCacheKey::ActingUser(_m, u) => u.heap_size(),
+            IdentityCacheKey::ApiKey(id) => id.heap_size(),
         }
     }
 }
@@ -105,6 +111,7 @@ impl Arbitrary for InertIdentity {
             (any::<UserIdentifier>()).prop_map(InertIdentity::User),
             (any::<MemberId>(), any::<UserIdentifier>())
                 .prop_map(|(a, b)| InertIdentity::ActingUser(a, b)),
+            "[a-zA-Z0-9_-]{1,16}".prop_map(InertIdentity::ApiKey),
         ]
     }
 }
@@ -123,6 +130,7 @@ impl FromStr for InertIdentity {
         match (parts.next(), parts.next()) {
             (Some("admin"), Some(s)) => Ok(InertIdentity::InstanceAdmin(s.to_string())),
             (Some("user"), Some(s)) => Ok(InertIdentity::User(UserIdentifier(s.to_string()))),
+            (Some("api_key"), Some(s)) => Ok(InertIdentity::ApiKey(s.to_string())),
             (Some("impersonated_user"), Some(admin_id_and_user_id)) => {
                 let mut parts = admin_id_and_user_id.splitn(2, ':');
                 if let (Some(admin_id), Some(user_id)) = (parts.next(), parts.next()) {
@@ -150,6 +158,7 @@ impl Display for InertIdentity {
             InertIdentity::ActingUser(admin_id, id) => {
                 write!(f, "impersonated_user:{}:{}", admin_id, id.deref())
             },
+            InertIdentity::ApiKey(name) => write!(f, "api_key:{}", name),
         }
     }
 }
@@ -172,5 +181,6 @@ mod tests {
         assert_identity_string_roundtrips("unknown".to_string());
         assert_identity_string_roundtrips("admin:AdminIdentifier".to_string());
         assert_identity_string_roundtrips("user:UserIdentifier".to_string());
+        assert_identity_string_roundtrips("api_key:ApiKeyName".to_string());
     }
 }
@@ -23,6 +23,11 @@ use std::ops::{
     RangeBounds,
 };
 
+use pb::common::{
+    interval::End as EndProto,
+    Interval as IntervalProto,
+};
+
 pub use self::{
     bounds::{
         End,
@@ -170,6 +175,34 @@ impl RangeBounds<[u8]> for &Interval {
     }
 }
 
+impl From<Interval> for IntervalProto {
+    fn from(Interval { start, end }: Interval) -> Self {
+        let Start::Included(start) = start;
+        let end = match end {
+            End::Unbounded => EndProto::AfterAll(()),
+            End::Excluded(e) => EndProto::Exclusive(e.to_vec()),
+        };
+        IntervalProto {
+            start_inclusive: start.to_vec(),
+            end: Some(end),
+        }
+    }
+}
+
+impl TryFrom<IntervalProto> for Interval {
+    type Error = anyhow::Error;
+
+    fn try_from(interval: IntervalProto) -> anyhow::Result<Self> {
+        let start = Start::Included(interval.start_inclusive.into());
+        let end = match interval.end {
+            None => anyhow::bail!("Interval missing end"),
+            Some(EndProto::AfterAll(())) => End::Unbounded,
+            Some(EndProto::Exclusive(end)) => End::Excluded(end.into()),
+        };
+        Ok(Interval { start, end })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeSet;
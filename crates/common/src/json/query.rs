@@ -6,6 +6,11 @@ use serde::{
     Serialize,
 };
 use serde_json::Value as JsonValue;
+use value::{
+    geo::GeoRegion,
+    ConvexValue,
+    GeoPoint,
+};
 
 use crate::{
     json::expression::JsonExpression,
@@ -13,6 +18,7 @@ use crate::{
     query::{
         Expression,
         FullTableScan,
+        GeoQuery,
         IndexRange,
         IndexRangeExpression,
         Order,
@@ -52,6 +58,7 @@ enum JsonQuerySource {
     FullTableScan(JsonFullTableScan),
     IndexRange(JsonQueryIndexRange),
     Search(JsonSearch),
+    Geo(JsonGeoQuery),
 }
 
 #[derive(Deserialize, Serialize)]
@@ -204,6 +211,74 @@ impl From<SearchFilterExpression> for JsonSearchFilterExpression {
     }
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonGeoQuery {
+    index_name: String,
+    region: JsonGeoRegion,
+}
+
+/// Points are encoded as geo point values, e.g.
+/// `{"$geoPoint": {"latitude": 51.5, "longitude": -0.12}}`.
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type")]
+enum JsonGeoRegion {
+    Radius {
+        center: JsonValue,
+        meters: f64,
+    },
+    #[serde(rename_all = "camelCase")]
+    BoundingBox {
+        south_west: JsonValue,
+        north_east: JsonValue,
+    },
+}
+
+fn geo_point_from_json(value: JsonValue) -> anyhow::Result<GeoPoint> {
+    GeoPoint::try_from(ConvexValue::try_from(value)?)
+}
+
+fn geo_point_to_json(point: GeoPoint) -> JsonValue {
+    JsonValue::from(ConvexValue::from(point))
+}
+
+impl TryFrom<JsonGeoRegion> for GeoRegion {
+    type Error = anyhow::Error;
+
+    fn try_from(json_region: JsonGeoRegion) -> Result<Self> {
+        match json_region {
+            JsonGeoRegion::Radius { center, meters } => {
+                GeoRegion::radius(geo_point_from_json(center)?, meters)
+            },
+            JsonGeoRegion::BoundingBox {
+                south_west,
+                north_east,
+            } => GeoRegion::bounding_box(
+                geo_point_from_json(south_west)?,
+                geo_point_from_json(north_east)?,
+            ),
+        }
+    }
+}
+
+impl From<GeoRegion> for JsonGeoRegion {
+    fn from(region: GeoRegion) -> Self {
+        match region {
+            GeoRegion::Radius { center, meters } => JsonGeoRegion::Radius {
+                center: geo_point_to_json(center),
+                meters,
+            },
+            GeoRegion::BoundingBox {
+                south_west,
+                north_east,
+            } => JsonGeoRegion::BoundingBox {
+                south_west: geo_point_to_json(south_west),
+                north_east: geo_point_to_json(north_east),
+            },
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct JsonQuery {
@@ -256,6 +331,10 @@ impl TryFrom<JsonQuerySource> for QuerySource {
                     filters: filter_expressions,
                 })
             },
+            JsonQuerySource::Geo(json_geo_query) => QuerySource::Geo(GeoQuery {
+                index_name: IndexName::from_str(&json_geo_query.index_name)?,
+                region: json_geo_query.region.try_into()?,
+            }),
         })
     }
 }
@@ -289,6 +368,12 @@ impl From<QuerySource> for JsonQuerySource {
                 index_name: index_name.to_string(),
                 filters: filters.into_iter().map(|filter| filter.into()).collect(),
             }),
+            QuerySource::Geo(GeoQuery { index_name, region }) => {
+                JsonQuerySource::Geo(JsonGeoQuery {
+                    index_name: index_name.to_string(),
+                    region: region.into(),
+                })
+            },
         }
     }
 }
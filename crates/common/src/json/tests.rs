@@ -81,6 +81,30 @@ fn test_parse_query() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_parse_geo_query() -> anyhow::Result<()> {
+    let center = json!({ "$geoPoint": { "latitude": 51.5, "longitude": -0.125 } });
+    Query::try_from(json!({
+        "source": {
+            "type": "Geo",
+            "indexName": "places.by_location",
+            "region": { "type": "Radius", "center": center, "meters": 1000.0 },
+        },
+        "operators": [],
+    }))?;
+    // Radii can't be negative.
+    assert!(Query::try_from(json!({
+        "source": {
+            "type": "Geo",
+            "indexName": "places.by_location",
+            "region": { "type": "Radius", "center": center, "meters": -1.0 },
+        },
+        "operators": [],
+    }))
+    .is_err());
+    Ok(())
+}
+
 proptest! {
     #![proptest_config(
             ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
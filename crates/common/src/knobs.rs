@@ -91,6 +91,17 @@ pub static ACTION_USER_TIMEOUT: LazyLock<Duration> =
 pub static DOCUMENT_DELTAS_LIMIT: LazyLock<usize> =
     LazyLock::new(|| env_config("DOCUMENT_DELTAS_LIMIT", 128));
 
+/// Max number of rows of the documents log we will read for one page of a
+/// table's changes requested from an action. Rows from other tables count
+/// towards this limit, so a page may be short even though more changes exist.
+pub static TABLE_CHANGES_ROWS_READ_LIMIT: LazyLock<usize> =
+    LazyLock::new(|| env_config("TABLE_CHANGES_ROWS_READ_LIMIT", 8192));
+
+/// Max number of changes returned in one page of a table's changes requested
+/// from an action.
+pub static TABLE_CHANGES_MAX_PAGE_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("TABLE_CHANGES_MAX_PAGE_SIZE", 1024));
+
 /// Max number of rows we will read when calculating snapshot pages.
 /// Each document can be up to `::value::MAX_USER_SIZE`
 /// Note that this is a pro feature, so we can afford more memory.
@@ -220,10 +231,91 @@ pub static TRANSACTION_MAX_SYSTEM_WRITE_SIZE_BYTES: LazyLock<usize> = LazyLock::
 pub static TRANSACTION_MAX_NUM_SCHEDULED: LazyLock<usize> =
     LazyLock::new(|| env_config("TRANSACTION_MAX_NUM_SCHEDULED", 1000));
 
+/// Maximum number of jobs that can be scheduled in a single batch.
+pub static SCHEDULED_JOB_BATCH_MAX_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULED_JOB_BATCH_MAX_SIZE", 5000));
+
+/// Maximum number of jobs in each chunk of a scheduled job batch. The scheduler
+/// expands one chunk into scheduled jobs per transaction.
+pub static SCHEDULED_JOB_BATCH_CHUNK_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULED_JOB_BATCH_CHUNK_SIZE", 100));
+
+/// Maximum total size of the arguments in each chunk of a scheduled job batch.
+pub static SCHEDULED_JOB_BATCH_CHUNK_MAX_SIZE_BYTES: LazyLock<usize> = LazyLock::new(|| {
+    env_config("SCHEDULED_JOB_BATCH_CHUNK_MAX_SIZE_BYTES", 1 << 19) // 512 KiB
+});
+
 /// Maximum number of scheduled jobs to cancel in a single transaction.
 pub static MAX_JOBS_CANCEL_BATCH: LazyLock<usize> =
     LazyLock::new(|| env_config("MAX_JOBS_CANCEL_BATCH", 1000));
 
+/// Maximum number of scheduled jobs read in a single transaction when listing,
+/// counting, canceling or re-running jobs in bulk. Pages of jobs matching a
+/// sparse filter can be shorter than requested so each transaction stays small.
+pub static MAX_SCHEDULED_JOBS_SCANNED_PER_PAGE: LazyLock<usize> =
+    LazyLock::new(|| env_config("MAX_SCHEDULED_JOBS_SCANNED_PER_PAGE", 4096));
+
+/// Maximum number of files read in a single transaction when listing files
+/// with a filter. Pages of files matching a sparse filter can be shorter than
+/// requested so each transaction stays small.
+pub static MAX_FILES_SCANNED_PER_PAGE: LazyLock<usize> =
+    LazyLock::new(|| env_config("MAX_FILES_SCANNED_PER_PAGE", 4096));
+
+/// How often each file lifecycle rule is run.
+pub static FILE_LIFECYCLE_RULE_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "FILE_LIFECYCLE_RULE_INTERVAL_SECS",
+        60 * 60 * 6, // 6 hours
+    ))
+});
+
+/// Maximum number of files deleted or transitioned by a file lifecycle rule
+/// in a single transaction.
+pub static FILE_LIFECYCLE_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("FILE_LIFECYCLE_BATCH_SIZE", 100));
+
+/// How often soft-deleted documents whose retention window has ended are
+/// deleted from `_trash`.
+pub static TRASH_PURGE_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("TRASH_PURGE_INTERVAL_SECS", 60 * 60)));
+
+/// Maximum number of expired soft-deleted documents deleted in a single
+/// transaction.
+pub static TRASH_PURGE_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("TRASH_PURGE_BATCH_SIZE", 500));
+
+/// How often the segments of pinned text and vector indexes are loaded into
+/// the searcher. Indexes get new segments as they're flushed and compacted, so
+/// this bounds how long a pinned index can have segments that aren't loaded.
+pub static SEARCH_INDEX_PIN_REFRESH_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SEARCH_INDEX_PIN_REFRESH_INTERVAL_SECS", 30)));
+
+/// How often the table storage snapshot worker checks whether today's
+/// per-table storage breakdown has been computed yet. Snapshots are only
+/// computed once per UTC day.
+pub static TABLE_STORAGE_SNAPSHOT_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("TABLE_STORAGE_SNAPSHOT_INTERVAL_SECS", 60 * 60))
+});
+
+/// Number of days of per-table storage snapshots kept in
+/// `_table_storage_snapshots`.
+pub static TABLE_STORAGE_SNAPSHOT_RETENTION_DAYS: LazyLock<u64> =
+    LazyLock::new(|| env_config("TABLE_STORAGE_SNAPSHOT_RETENTION_DAYS", 90));
+
+/// Maximum number of document log rows read per second when measuring the
+/// historical size of a table for its storage snapshot.
+pub static TABLE_STORAGE_SNAPSHOT_ROWS_PER_SECOND: LazyLock<NonZeroU32> = LazyLock::new(|| {
+    env_config(
+        "TABLE_STORAGE_SNAPSHOT_ROWS_PER_SECOND",
+        NonZeroU32::new(10000).unwrap(),
+    )
+});
+
+/// How long the file scanner may take to scan an uploaded file before the
+/// upload fails.
+pub static FILE_SCAN_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("FILE_SCAN_TIMEOUT_SECS", 300)));
+
 /// Maximum size of the arguments to a scheduled function.
 pub static TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES: LazyLock<usize> =
     LazyLock::new(|| {
@@ -240,6 +332,89 @@ pub static TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES: LazyLock<usize>
 pub static SCHEDULED_JOB_EXECUTION_PARALLELISM: LazyLock<usize> =
     LazyLock::new(|| env_config("SCHEDULED_JOB_EXECUTION_PARALLELISM", 10));
 
+/// Number of SCHEDULED_JOB_EXECUTION_PARALLELISM slots that only high priority
+/// scheduled jobs can use, so that they can start even when the scheduler is
+/// busy with normal and low priority jobs.
+pub static SCHEDULED_JOB_HIGH_PRIORITY_RESERVED_PARALLELISM: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULED_JOB_HIGH_PRIORITY_RESERVED_PARALLELISM", 2));
+
+/// Maximum number of low priority scheduled jobs that can execute in parallel.
+pub static SCHEDULED_JOB_LOW_PRIORITY_MAX_PARALLELISM: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULED_JOB_LOW_PRIORITY_MAX_PARALLELISM", 5));
+
+/// Maximum number of ready scheduled jobs the executor will pass over because
+/// their lane or function is at its concurrency limit before it stops looking
+/// for other jobs to start.
+pub static SCHEDULED_JOB_MAX_THROTTLED_PER_POLL: LazyLock<usize> =
+    LazyLock::new(|| env_config("SCHEDULED_JOB_MAX_THROTTLED_PER_POLL", 1000));
+
+/// Maximum number of missed runs a cron with the `runAll` catch-up policy will
+/// execute. Older missed runs beyond this are skipped.
+pub static CRON_JOB_MAX_CATCH_UP_RUNS: LazyLock<usize> =
+    LazyLock::new(|| env_config("CRON_JOB_MAX_CATCH_UP_RUNS", 100));
+
+/// How long a queue message stays invisible to other consumers after it is
+/// delivered, unless the consumer group sets its own visibility timeout.
+pub static QUEUE_DEFAULT_VISIBILITY_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("QUEUE_DEFAULT_VISIBILITY_TIMEOUT_SECONDS", 30))
+});
+
+/// Number of times a queue message is delivered before it is dead-lettered,
+/// unless the consumer group sets its own limit.
+pub static QUEUE_DEFAULT_MAX_ATTEMPTS: LazyLock<u32> =
+    LazyLock::new(|| env_config("QUEUE_DEFAULT_MAX_ATTEMPTS", 5));
+
+/// Maximum number of queue messages delivered in a single transaction.
+pub static QUEUE_DISPATCH_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("QUEUE_DISPATCH_BATCH_SIZE", 100));
+
+/// Maximum number of outbox records delivered at the same time.
+pub static OUTBOX_DELIVERY_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("OUTBOX_DELIVERY_BATCH_SIZE", 32));
+
+/// How long a delivery of an outbox record can take before it's abandoned
+/// and retried.
+pub static OUTBOX_DELIVERY_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("OUTBOX_DELIVERY_TIMEOUT_SECS", 30)));
+
+/// Number of failed deliveries after which an outbox record is marked as
+/// failed and no longer retried.
+pub static OUTBOX_MAX_ATTEMPTS: LazyLock<u32> =
+    LazyLock::new(|| env_config("OUTBOX_MAX_ATTEMPTS", 10));
+
+/// Backoff after the first failed delivery of an outbox record. Doubles
+/// with each failed attempt, up to `OUTBOX_MAX_BACKOFF`.
+pub static OUTBOX_INITIAL_BACKOFF: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("OUTBOX_INITIAL_BACKOFF_SECS", 1)));
+
+/// Maximum backoff between deliveries of an outbox record.
+pub static OUTBOX_MAX_BACKOFF: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("OUTBOX_MAX_BACKOFF_SECS", 60 * 60)));
+
+/// How long delivered outbox records are kept before they're deleted.
+pub static OUTBOX_DELIVERED_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "OUTBOX_DELIVERED_RETENTION_SECS",
+        7 * 24 * 60 * 60,
+    ))
+});
+
+/// Maximum number of shadowed calls in flight to the traffic shadowing
+/// target at the same time. Calls sampled beyond this are dropped rather
+/// than queued, so a slow target can't build up a backlog.
+pub static TRAFFIC_SHADOWING_MAX_IN_FLIGHT: LazyLock<usize> =
+    LazyLock::new(|| env_config("TRAFFIC_SHADOWING_MAX_IN_FLIGHT", 32));
+
+/// How long a shadowed call can take before it's counted as failed.
+pub static TRAFFIC_SHADOWING_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("TRAFFIC_SHADOWING_TIMEOUT_SECS", 30)));
+
+/// A shadowed call whose execution takes more than this many times as long
+/// on the target as it did in production is reported as a latency
+/// divergence.
+pub static TRAFFIC_SHADOWING_LATENCY_DIVERGENCE_RATIO: LazyLock<f64> =
+    LazyLock::new(|| env_config("TRAFFIC_SHADOWING_LATENCY_DIVERGENCE_RATIO", 2.0));
+
 /// Initial backoff in milliseconds on a system error from a scheduled job.
 pub static SCHEDULED_JOB_INITIAL_BACKOFF: LazyLock<Duration> =
     LazyLock::new(|| Duration::from_millis(env_config("SCHEDULED_JOB_INITIAL_BACKOFF_MS", 10)));
@@ -268,6 +443,32 @@ pub static SCHEDULED_JOB_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
 pub static SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("SCHEDULED_JOB_GARBAGE_COLLECTION_BATCH_SIZE", 1000));
 
+/// How long the result of a mutation called with an `Idempotency-Key` is kept,
+/// and so how long retries with the same key return it instead of running the
+/// mutation again.
+pub static MUTATION_IDEMPOTENCY_KEY_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "MUTATION_IDEMPOTENCY_KEY_RETENTION_SECS",
+        60 * 60 * 24, // 1 day
+    ))
+});
+
+/// Maximum number of expired idempotency keys to delete in a single
+/// transaction.
+pub static IDEMPOTENCY_KEY_GARBAGE_COLLECTION_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("IDEMPOTENCY_KEY_GARBAGE_COLLECTION_BATCH_SIZE", 1000));
+
+/// Maximum number of times a workflow (a scheduled action with checkpointed
+/// steps) is restarted from its last completed step after failing or being
+/// interrupted, before it is marked as failed.
+pub static WORKFLOW_MAX_ATTEMPTS: LazyLock<u32> =
+    LazyLock::new(|| env_config("WORKFLOW_MAX_ATTEMPTS", 5));
+
+/// Initial delay in seconds before restarting a failed workflow. The delay
+/// doubles with every attempt, up to SCHEDULED_JOB_MAX_BACKOFF.
+pub static WORKFLOW_RESTART_INITIAL_BACKOFF: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("WORKFLOW_RESTART_INITIAL_BACKOFF_SECS", 1)));
+
 /// Maximum number of syscalls that can run in a batch together when
 /// awaited in parallel. Higher values improve latency, while lower ones
 /// protect one isolate from hogging database connections.
@@ -375,6 +576,17 @@ pub static INDEX_BACKFILL_CHUNK_RATE: LazyLock<usize> =
 pub static INDEX_BACKFILL_CHUNK_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("INDEX_BACKFILL_CHUNK_SIZE", 256));
 
+/// Backfills slow down while the average latency of commits is above this,
+/// so they don't starve foreground writes of persistence.
+pub static INDEX_BACKFILL_TARGET_COMMIT_LATENCY: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_millis(env_config("INDEX_BACKFILL_TARGET_COMMIT_LATENCY_MS", 100))
+});
+
+/// The longest a throttled backfill waits between chunks.
+pub static INDEX_BACKFILL_MAX_THROTTLE_DELAY: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_millis(env_config("INDEX_BACKFILL_MAX_THROTTLE_DELAY_MS", 5000))
+});
+
 /// Chunk size of index entries when reading from persistence.
 pub static RETENTION_READ_CHUNK: LazyLock<usize> =
     LazyLock::new(|| env_config("RETENTION_READ_CHUNK", 128));
@@ -481,6 +693,18 @@ pub static WRITE_LOG_MAX_RETENTION_SECS: LazyLock<Duration> =
 pub static WRITE_LOG_SOFT_MAX_SIZE_BYTES: LazyLock<usize> =
     LazyLock::new(|| env_config("WRITE_LOG_SOFT_MAX_SIZE_BYTES", 50 * 1024 * 1024));
 
+/// Number of shards the subscriptions worker splits subscribers into. Each
+/// shard is matched against new writes on its own thread when there are at
+/// least SUBSCRIPTIONS_PARALLEL_INVALIDATION_MIN_SUBSCRIBERS subscribers.
+pub static SUBSCRIPTIONS_SHARDS: LazyLock<usize> =
+    LazyLock::new(|| env_config("SUBSCRIPTIONS_SHARDS", 4));
+
+/// Minimum number of subscribers before the subscriptions worker matches
+/// writes against its shards in parallel. Below this, spawning threads costs
+/// more than it saves.
+pub static SUBSCRIPTIONS_PARALLEL_INVALIDATION_MIN_SUBSCRIBERS: LazyLock<usize> =
+    LazyLock::new(|| env_config("SUBSCRIPTIONS_PARALLEL_INVALIDATION_MIN_SUBSCRIBERS", 5000));
+
 /// How frequently system tables are cleaned up.
 pub static SYSTEM_TABLE_CLEANUP_FREQUENCY: LazyLock<Duration> = LazyLock::new(|| {
     Duration::from_secs(env_config(
@@ -772,12 +996,175 @@ pub static MYSQL_CHUNK_SIZE: LazyLock<usize> =
 pub static MAX_CONCURRENT_ACTION_OPS: LazyLock<usize> =
     LazyLock::new(|| env_config("MAX_CONCURRENT_ACTION_OPS", 8));
 
+/// How many bytes of an HTTP action's request body can be read from the
+/// client ahead of the JavaScript reading them. Reading the body pauses once
+/// this many bytes are buffered, so large uploads can be streamed through an
+/// HTTP action without holding the whole body in memory.
+pub static HTTP_ACTION_REQUEST_BODY_MAX_BUFFERED_BYTES: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_ACTION_REQUEST_BODY_MAX_BUFFERED_BYTES", 4 << 20));
+
+/// Maximum size of the cache of HTTP action responses, which serves repeated
+/// requests from responses the action marked as cacheable with
+/// `Cache-Control` instead of running the action again. The cache is disabled
+/// unless this is set to a nonzero size.
+pub static HTTP_ACTION_RESPONSE_CACHE_MAX_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_ACTION_RESPONSE_CACHE_MAX_SIZE", 0));
+
+/// Responses with larger bodies than this aren't stored in the HTTP action
+/// response cache.
+pub static HTTP_ACTION_RESPONSE_CACHE_MAX_ENTRY_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("HTTP_ACTION_RESPONSE_CACHE_MAX_ENTRY_SIZE", 1 << 20));
+
+/// Maximum size of the cache of images transformed for file storage GETs with
+/// `width`, `height`, `format` or `quality` parameters.
+pub static IMAGE_TRANSFORM_CACHE_MAX_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("IMAGE_TRANSFORM_CACHE_MAX_SIZE", 128 << 20));
+
+/// Stored files larger than this can't be transformed, since the whole file
+/// is decoded in memory.
+pub static IMAGE_TRANSFORM_MAX_SOURCE_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("IMAGE_TRANSFORM_MAX_SOURCE_SIZE", 32 << 20));
+
+/// Number of threads images are transformed on.
+pub static IMAGE_TRANSFORM_THREADS: LazyLock<usize> =
+    LazyLock::new(|| env_config("IMAGE_TRANSFORM_THREADS", 4));
+
+/// Rate limit on calls to the public function API (queries, mutations and
+/// actions), like `100/s,burst=200,key=ip`. See `RateLimitConfig` in
+/// `local_backend` for the format. Disabled if empty.
+pub static PUBLIC_API_RATE_LIMIT: LazyLock<Option<String>> = LazyLock::new(|| {
+    let result = env_config("PUBLIC_API_RATE_LIMIT", "".to_string());
+    if !result.is_empty() {
+        Some(result)
+    } else {
+        None
+    }
+});
+
+/// Rate limit on requests to HTTP actions, in the same format as
+/// `PUBLIC_API_RATE_LIMIT`. Disabled if empty.
+pub static HTTP_ACTION_RATE_LIMIT: LazyLock<Option<String>> = LazyLock::new(|| {
+    let result = env_config("HTTP_ACTION_RATE_LIMIT", "".to_string());
+    if !result.is_empty() {
+        Some(result)
+    } else {
+        None
+    }
+});
+
+/// How long an HTTP action can go without sending any data on a streamed
+/// response body before the response is ended.
+pub static HTTP_ACTION_RESPONSE_IDLE_TIMEOUT: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("HTTP_ACTION_RESPONSE_IDLE_TIMEOUT_SECS", 300))
+});
+
+/// How often to send a heartbeat comment on a server-sent event stream from an
+/// HTTP action that isn't otherwise sending data, so proxies and clients don't
+/// close the connection as idle.
+pub static HTTP_ACTION_SSE_HEARTBEAT_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config("HTTP_ACTION_SSE_HEARTBEAT_INTERVAL_SECS", 15))
+});
+
+/// Serve a GraphQL endpoint at `/api/graphql` that exposes the deployment's
+/// public queries and mutations, with subscriptions to queries over
+/// WebSockets.
+pub static GRAPHQL_ENDPOINT_ENABLED: LazyLock<bool> =
+    LazyLock::new(|| env_config("GRAPHQL_ENDPOINT_ENABLED", false));
+
+/// Maximum nesting depth of a GraphQL query.
+pub static GRAPHQL_MAX_DEPTH: LazyLock<usize> =
+    LazyLock::new(|| env_config("GRAPHQL_MAX_DEPTH", 10));
+
+/// Maximum complexity of a GraphQL query, where every field selected,
+/// including aliases of the same function, counts as one.
+pub static GRAPHQL_MAX_COMPLEXITY: LazyLock<usize> =
+    LazyLock::new(|| env_config("GRAPHQL_MAX_COMPLEXITY", 100));
+
 /// Maximum count of transitions within the web socket server message buffer.
 /// When this limit is reached, the web socket worker will temporary stop
 /// computing and sending transition messages to the client.
 pub static SYNC_MAX_SEND_TRANSITION_COUNT: LazyLock<usize> =
     LazyLock::new(|| env_config("SYNC_MAX_SEND_TRANSITION_COUNT", 2));
 
+/// Minimum size in bytes of a query result for the sync worker to send later
+/// changes to it as a patch rather than in full, to clients that support it.
+/// The worker keeps a copy of each result at least this large, so lowering
+/// this trades memory for egress.
+pub static SYNC_QUERY_PATCH_MIN_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("SYNC_QUERY_PATCH_MIN_SIZE", 1 << 14));
+
+/// Minimum time between updates the sync worker sends for a single query.
+/// Invalidations of a query within this interval of its last update are
+/// coalesced into one update with the latest result once the interval has
+/// passed. Zero disables throttling.
+pub static SYNC_QUERY_MIN_UPDATE_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("SYNC_QUERY_MIN_UPDATE_INTERVAL_MS", 0)));
+
+/// How long the sync worker keeps a connection's query subscriptions after
+/// the client's last message. Once it passes, the worker drops them, which
+/// frees their read sets, and tells the client, which gets them rerun with
+/// its next message. Subscriptions left open by backgrounded tabs otherwise
+/// use memory for as long as the tab stays open. Only clients that speak
+/// `SUBSCRIPTION_EXPIRY_SYNC_PROTOCOL_VERSION` are affected. Zero disables
+/// expiry.
+pub static SYNC_IDLE_SUBSCRIPTION_TIMEOUT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SYNC_IDLE_SUBSCRIPTION_TIMEOUT_SECS", 0)));
+
+/// How often the sync worker checks whether the session of a connection
+/// authenticated as a user has been revoked. This bounds how long a revoked
+/// session stays connected.
+pub static SYNC_SESSION_REVOCATION_CHECK_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "SYNC_SESSION_REVOCATION_CHECK_INTERVAL_SECS",
+        60,
+    ))
+});
+
+/// Whether to serve the built-in auth endpoints under `/api/auth`, which let
+/// apps sign users in with an email and password or a magic link without a
+/// third-party auth provider.
+pub static BUILT_IN_AUTH_ENABLED: LazyLock<bool> =
+    LazyLock::new(|| env_config("BUILT_IN_AUTH_ENABLED", false));
+
+/// How long the session tokens issued by built-in auth are valid for. Clients
+/// refresh them before they expire.
+pub static BUILT_IN_AUTH_TOKEN_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("BUILT_IN_AUTH_TOKEN_TTL_SECS", 24 * 60 * 60)));
+
+/// How long the email verification codes and magic links sent by built-in
+/// auth are valid for.
+pub static BUILT_IN_AUTH_CODE_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("BUILT_IN_AUTH_CODE_TTL_SECS", 15 * 60)));
+
+/// Number of threads that hash and check built-in auth passwords. Each hash
+/// takes about 64MB of memory, so this also bounds the memory used by
+/// sign-ups and sign-ins.
+pub static BUILT_IN_AUTH_PASSWORD_THREADS: LazyLock<usize> =
+    LazyLock::new(|| env_config("BUILT_IN_AUTH_PASSWORD_THREADS", 2));
+
+/// Rate limit on built-in auth sign-ins and magic links from each client, in
+/// the same format as `PUBLIC_API_RATE_LIMIT`. Disabled if empty.
+pub static BUILT_IN_AUTH_CLIENT_RATE_LIMIT: LazyLock<Option<String>> = LazyLock::new(|| {
+    let result = env_config("BUILT_IN_AUTH_CLIENT_RATE_LIMIT", "30/m".to_string());
+    if !result.is_empty() {
+        Some(result)
+    } else {
+        None
+    }
+});
+
+/// Rate limit on built-in auth sign-ins and magic links for each email
+/// address, in the same format as `PUBLIC_API_RATE_LIMIT`, where the `key`
+/// option has no effect. Disabled if empty.
+pub static BUILT_IN_AUTH_EMAIL_RATE_LIMIT: LazyLock<Option<String>> = LazyLock::new(|| {
+    let result = env_config("BUILT_IN_AUTH_EMAIL_RATE_LIMIT", "5/m".to_string());
+    if !result.is_empty() {
+        Some(result)
+    } else {
+        None
+    }
+});
+
 /// Max Axiom sink attributes. This is a knob just in case a user actually hits
 /// the limit but has an Enterprise Axiom plan that lets them use more than the
 /// limit we've configured.
@@ -804,6 +1191,12 @@ pub static MIN_COMPACTION_SEGMENTS: LazyLock<u64> =
 /// This number must be between 0 and 1.
 pub static MAX_SEGMENT_DELETED_PERCENTAGE: LazyLock<f64> =
     LazyLock::new(|| env_config("MAX_SEGMENT_DELETED_PERCENTAGE", 0.2));
+/// The maximum percentage of a vector index that can be deleted before we
+/// recompact every segment with deleted vectors, including small segments that
+/// MAX_SEGMENT_DELETED_PERCENTAGE doesn't apply to.
+/// This number must be between 0 and 1.
+pub static MAX_VECTOR_INDEX_DELETED_PERCENTAGE: LazyLock<f64> =
+    LazyLock::new(|| env_config("MAX_VECTOR_INDEX_DELETED_PERCENTAGE", 0.1));
 
 /// Whether to run queries, mutations, and v8 actions in Funrun (true) or
 /// InProcessFunctionRunner (false).
@@ -991,6 +1384,24 @@ pub static MAX_BACKEND_RPC_RESPONSE_SIZE: LazyLock<usize> =
 pub static MAX_BACKEND_RPC_HTTP_CHUNK_SIZE: LazyLock<usize> =
     LazyLock::new(|| env_config("MAX_BACKEND_RPC_RESPONSE_SIZE", 1 << 23)); // 8 MiB
 
+/// Node actions send results longer than this many characters to the action
+/// callback endpoint in chunks of this size instead of in the executor
+/// response, whose size is limited (to 6 MiB on AWS Lambda). Must be smaller
+/// than `MAX_BACKEND_RPC_REQUEST_SIZE`.
+pub static NODE_ACTION_RESULT_CHUNK_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("NODE_ACTION_RESULT_CHUNK_SIZE", 1 << 20)); // 1 MiB
+
+/// The maximum size of a Node action result sent in chunks. This is larger
+/// than `FUNCTION_MAX_RESULT_SIZE` since the result is JSON encoded, and the
+/// decoded value is still checked against that limit.
+pub static NODE_ACTION_MAX_STREAMED_RESULT_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("NODE_ACTION_MAX_STREAMED_RESULT_SIZE", 1 << 24)); // 16 MiB
+
+/// The maximum total size of Node action results being reassembled from
+/// chunks at once, across all running actions.
+pub static NODE_ACTION_MAX_BUFFERED_RESULT_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("NODE_ACTION_MAX_BUFFERED_RESULT_SIZE", 1 << 28)); // 256 MiB
+
 /// The maximum size for requests to the backend public API. Must be at least 8
 /// MiB for function arguments.
 pub static MAX_BACKEND_PUBLIC_API_REQUEST_SIZE: LazyLock<usize> =
@@ -1044,3 +1455,119 @@ pub static REQUEST_TRACE_SAMPLE_CONFIG: LazyLock<SamplingConfig> =
 /// the "backend_startup" domain keyed by db cluster name.
 pub static STARTUP_RATE_LIMIT_ENABLED: LazyLock<bool> =
     LazyLock::new(|| env_config("STARTUP_RATE_LIMIT_ENABLED", false));
+
+/// How often the signing keys and other metadata of an auth provider are
+/// refetched in the background.
+pub static AUTH_PROVIDER_METADATA_REFRESH_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "AUTH_PROVIDER_METADATA_REFRESH_INTERVAL_SECONDS",
+        15 * 60,
+    ))
+});
+
+/// Cached auth provider metadata older than this isn't used to validate tokens,
+/// so a provider that stays unreachable eventually stops authenticating users.
+pub static AUTH_PROVIDER_METADATA_MAX_STALENESS: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "AUTH_PROVIDER_METADATA_MAX_STALENESS_SECONDS",
+        24 * 60 * 60,
+    ))
+});
+
+/// Minimum time between refetches of an auth provider's metadata triggered by
+/// a token signed with a key that isn't in its cached key set.
+pub static AUTH_PROVIDER_METADATA_MIN_REFRESH_INTERVAL: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "AUTH_PROVIDER_METADATA_MIN_REFRESH_INTERVAL_SECONDS",
+        30,
+    ))
+});
+
+/// Number of log event batches buffered for each log sink. Logs are dropped
+/// when a sink falls further behind than this.
+pub static LOG_SINK_CHANNEL_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("LOG_SINK_CHANNEL_SIZE", 1024));
+
+/// Maximum number of log events delivered to a log sink in one request.
+pub static LOG_SINK_MAX_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("LOG_SINK_MAX_BATCH_SIZE", 500));
+
+/// How long a log sink waits for a batch to fill up before delivering it.
+pub static LOG_SINK_BATCH_INTERVAL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("LOG_SINK_BATCH_INTERVAL_MS", 1000)));
+
+/// Number of times delivery of a batch to a log sink is retried before the
+/// batch is dropped.
+pub static LOG_SINK_MAX_RETRIES: LazyLock<u32> =
+    LazyLock::new(|| env_config("LOG_SINK_MAX_RETRIES", 5));
+
+/// How long completed function executions are kept in `_function_executions`
+/// and so can be queried from the dashboard.
+pub static FUNCTION_EXECUTION_LOG_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "FUNCTION_EXECUTION_LOG_RETENTION_SECS",
+        60 * 60 * 24 * 7, // 7 days
+    ))
+});
+
+/// Number of function executions buffered before they are persisted.
+/// Executions are dropped from the persisted log (but not the in-memory one)
+/// when persisting falls further behind than this.
+pub static FUNCTION_EXECUTION_LOG_CHANNEL_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("FUNCTION_EXECUTION_LOG_CHANNEL_SIZE", 10000));
+
+/// Maximum number of function executions persisted, or deleted after they
+/// expire, in a single transaction.
+pub static FUNCTION_EXECUTION_LOG_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("FUNCTION_EXECUTION_LOG_BATCH_SIZE", 500));
+
+/// How long finished scheduled job and cron runs are kept in
+/// `_job_executions`, and so how far back their history and summaries go.
+pub static JOB_EXECUTION_HISTORY_RETENTION: LazyLock<Duration> = LazyLock::new(|| {
+    Duration::from_secs(env_config(
+        "JOB_EXECUTION_HISTORY_RETENTION_SECS",
+        60 * 60 * 24 * 30, // 30 days
+    ))
+});
+
+/// Maximum number of affected functions recorded for each group of function
+/// errors in `_error_groups`.
+pub static ERROR_GROUP_MAX_AFFECTED_FUNCTIONS: LazyLock<usize> =
+    LazyLock::new(|| env_config("ERROR_GROUP_MAX_AFFECTED_FUNCTIONS", 50));
+
+/// Executions that take longer than this have the rows and index ranges they
+/// read and their syscall timings captured in `_slow_queries`, which keeps
+/// them for `FUNCTION_EXECUTION_LOG_RETENTION`.
+pub static SLOW_QUERY_THRESHOLD: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_millis(env_config("SLOW_QUERY_THRESHOLD_MS", 1000)));
+
+/// Maximum number of documents read in each mutation of a bulk edit from the
+/// dashboard.
+pub static BULK_EDIT_BATCH_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("BULK_EDIT_BATCH_SIZE", 1000));
+
+/// Bulk edits are paced to edit at most this many documents per second, so
+/// cleaning up a large table doesn't starve the deployment's own functions.
+pub static BULK_EDIT_DOCUMENTS_PER_SECOND: LazyLock<u64> =
+    LazyLock::new(|| env_config("BULK_EDIT_DOCUMENTS_PER_SECOND", 2000));
+
+/// How long a single bulk edit request runs before returning a cursor to
+/// continue from.
+pub static BULK_EDIT_REQUEST_TIME_LIMIT: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("BULK_EDIT_REQUEST_TIME_LIMIT_SECS", 30)));
+
+/// Number of pushed versions of a deployment's functions kept in
+/// `_deployment_versions` for rolling back to.
+pub static DEPLOYMENT_VERSION_HISTORY_SIZE: LazyLock<usize> =
+    LazyLock::new(|| env_config("DEPLOYMENT_VERSION_HISTORY_SIZE", 10));
+
+/// How long a secret that an environment variable refers to is cached before
+/// it's fetched from its secret manager again, so rotated secrets are picked
+/// up.
+pub static SECRET_CACHE_TTL: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SECRET_CACHE_TTL_SECS", 5 * 60)));
+
+/// A cached secret is used for up to this long while its secret manager can't
+/// be reached, so an outage doesn't fail every action that uses it.
+pub static SECRET_MAX_STALENESS: LazyLock<Duration> =
+    LazyLock::new(|| Duration::from_secs(env_config("SECRET_MAX_STALENESS_SECS", 60 * 60)));
@@ -91,6 +91,9 @@ pub enum StructuredLogEvent {
     DeploymentAuditLog {
         action: String,
         metadata: serde_json::Map<String, JsonValue>,
+        /// Who performed the action, e.g. `{"type": "admin", "member_id": "1",
+        /// "team_id": null, "role": "admin"}`.
+        actor: serde_json::Map<String, JsonValue>,
     },
     // User-specified topics -- not yet implemented.
     // See here for more details: https://www.notion.so/Log-Streaming-in-Convex-19a1dfadd6924c33b29b2796b0f5b2e2
@@ -242,12 +245,17 @@ impl LogEvent {
                         "userIdentifier": user_identifier,
                     })
                 },
-                StructuredLogEvent::DeploymentAuditLog { action, metadata } => {
+                StructuredLogEvent::DeploymentAuditLog {
+                    action,
+                    metadata,
+                    actor,
+                } => {
                     json!({
                         "_timestamp": ms,
                         "_topic":  "_audit_log",
                         "action": action,
-                        "actionMetadata": metadata
+                        "actionMetadata": metadata,
+                        "actor": actor
                     })
                 },
             },
@@ -290,10 +298,11 @@ impl LogEvent {
                     usage_stats,
                 } => {
                     let function_source = source.to_json_map();
-                    let (status, error_message) = match error {
+                    let (status, error_message) = match &error {
                         Some(error) => ("failure", Some(error.to_string())),
                         None => ("success", None),
                     };
+                    let typed_error = error.as_ref().and_then(|error| error.typed_error());
                     json!({
                         "timestamp": ms,
                         "topic": "function_execution",
@@ -301,6 +310,10 @@ impl LogEvent {
                         "execution_time_ms": execution_time.as_millis(),
                         "status": status,
                         "error_message": error_message,
+                        "error_code": typed_error.as_ref().map(|e| e.code),
+                        "error_data": typed_error
+                            .and_then(|e| e.data)
+                            .map(|data| JsonValue::from(data.clone())),
                         "usage": {
                             "database_read_bytes": usage_stats.database_read_bytes,
                             "database_write_bytes": usage_stats.database_write_bytes,
@@ -335,13 +348,18 @@ impl LogEvent {
                         "userIdentifier": user_identifier,
                     })
                 },
-                StructuredLogEvent::DeploymentAuditLog { action, metadata } => {
+                StructuredLogEvent::DeploymentAuditLog {
+                    action,
+                    metadata,
+                    actor,
+                } => {
                     json!({
                         "timestamp": ms,
                         "topic": "audit_log",
                         "audit_log_action": action,
                         // stringified JSON to avoid
-                        "audit_log_metadata": serde_json::to_string(&JsonValue::Object(metadata))?
+                        "audit_log_metadata": serde_json::to_string(&JsonValue::Object(metadata))?,
+                        "audit_log_actor": actor
                     })
                 },
             },
@@ -436,6 +454,8 @@ impl FunctionEventSource {
             "type": udf_type,
             "cached": self.cached,
             "request_id": self.context.request_id.to_string(),
+            // Lets the logs be correlated with the function's distributed trace.
+            "traceparent": self.context.traceparent().to_string(),
         }) else {
             unreachable!()
         };
@@ -480,6 +500,7 @@ mod tests {
         let timestamp = UnixTimestamp::from_millis(1000);
         let context = ExecutionContext::new_for_test();
         let request_id = context.request_id.clone();
+        let traceparent = context.traceparent();
         let event = LogEvent {
             timestamp,
             event: StructuredLogEvent::Console {
@@ -511,7 +532,8 @@ mod tests {
                     "path": "test:test",
                     "type": "query",
                     "cached": true,
-                    "request_id": request_id.to_string()
+                    "request_id": request_id.to_string(),
+                    "traceparent": traceparent.to_string(),
                 }),
                 "log_level": "LOG",
                 "message": "my test log",
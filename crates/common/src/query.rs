@@ -23,11 +23,13 @@ use sha2::{
     Sha256,
 };
 use value::{
+    geo::GeoRegion,
     heap_size::HeapSize,
     id_v6::{
         DeveloperDocumentId,
         VirtualTableNumberMap,
     },
+    sorting::geo_cell_range_to_bytes,
     utils::display_sequence,
     val,
     ConvexObject,
@@ -621,6 +623,53 @@ impl InternalSearch {
     }
 }
 
+/// A query for the documents whose geo point falls within a region, using a
+/// database index whose first field holds the points.
+///
+/// Results are returned in index order, which follows a Z-order curve over
+/// latitude and longitude, rather than by distance.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct GeoQuery {
+    /// The index being scanned.
+    pub index_name: IndexName,
+    /// The region that the points must fall within.
+    pub region: GeoRegion,
+}
+
+impl GeoQuery {
+    /// Returns the indexed field holding the points, along with the ascending,
+    /// disjoint intervals of the index that may contain points in the region.
+    pub fn compile(
+        &self,
+        indexed_fields: &IndexedFields,
+    ) -> anyhow::Result<(FieldPath, Vec<Interval>)> {
+        let Some(field_path) = indexed_fields.first() else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidGeoQuery",
+                format!(
+                    "Tried to run a geo query against {} but it doesn't index any fields. Geo \
+                     queries need an index whose first field holds geo points.",
+                    self.index_name
+                ),
+            ));
+        };
+        let intervals = self
+            .region
+            .cell_ranges()
+            .iter()
+            .map(|cells| {
+                let (start, end) = geo_cell_range_to_bytes(cells);
+                Interval {
+                    start: Start::Included(BinaryKey::from(start)),
+                    end: End::Excluded(BinaryKey::from(end)),
+                }
+            })
+            .collect();
+        Ok((field_path.clone(), intervals))
+    }
+}
+
 /// Filter field values under this size are stored as bytes. Otherwise
 /// we hash them down to 32 bytes.
 const MAX_FILTER_FIELD_LENGTH: usize = 32;
@@ -676,6 +725,8 @@ pub enum QuerySource {
     IndexRange(IndexRange),
     /// Perform a full text search.
     Search(Search),
+    /// Find the points within a region using an index on geo points.
+    Geo(GeoQuery),
 }
 
 /// An Expression evaluates to a Value.
@@ -727,6 +778,7 @@ mod proptest {
 
     use super::{
         Expression,
+        GeoQuery,
         IndexRange,
         MaybeValue,
         Query,
@@ -883,6 +935,7 @@ mod proptest {
                 any::<FullTableScan>().prop_map(QuerySource::FullTableScan),
                 any::<IndexRange>().prop_map(QuerySource::IndexRange),
                 any::<Search>().prop_map(QuerySource::Search),
+                any::<GeoQuery>().prop_map(QuerySource::Geo),
             ]
         }
     }
@@ -1134,6 +1187,13 @@ impl Query {
         }
     }
 
+    pub fn geo(geo_query: GeoQuery) -> Self {
+        Self {
+            source: QuerySource::Geo(geo_query),
+            operators: vec![],
+        }
+    }
+
     /// Add a filter predicate to a query.
     pub fn filter(mut self, expression: Expression) -> Self {
         self.operators.push(QueryOperator::Filter(expression));
@@ -1174,12 +1234,15 @@ mod tests {
     use proptest::prelude::*;
     use sync_types::testing::assert_roundtrips;
     use value::{
+        geo::GeoRegion,
         val,
         ConvexValue,
+        GeoPoint,
     };
 
     use super::{
         Expression,
+        GeoQuery,
         Order,
         Query,
     };
@@ -1193,6 +1256,7 @@ mod tests {
             IndexRangeExpression,
             MaybeValue,
         },
+        value::values_to_bytes,
     };
     #[test]
     fn test_expr_eval() -> anyhow::Result<()> {
@@ -1358,6 +1422,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_geo_query_compile() -> anyhow::Result<()> {
+        let point = GeoPoint::new(51.5, -0.125)?;
+        let geo_query = GeoQuery {
+            index_name: "places.by_location".parse()?,
+            region: GeoRegion::radius(point, 1_000.0)?,
+        };
+        let indexed_fields = vec!["location".parse()?, "name".parse()?].try_into()?;
+        let (field_path, intervals) = geo_query.compile(&indexed_fields)?;
+        assert_eq!(field_path, "location".parse()?);
+        assert!(!intervals.is_empty());
+        // The point's index keys are within exactly one of the intervals.
+        let key = values_to_bytes(&[Some(ConvexValue::from(point)), Some(val!("Big Ben"))]);
+        assert_eq!(
+            intervals
+                .iter()
+                .filter(|interval| interval.contains(&key))
+                .count(),
+            1
+        );
+
+        assert!(geo_query.compile(&IndexedFields::by_id()).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_query_fingerprint_stability() -> anyhow::Result<()> {
         /*
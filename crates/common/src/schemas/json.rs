@@ -16,6 +16,7 @@ use serde::{
     Serialize,
 };
 use serde_json::Value as JsonValue;
+use sync_types::UdfPath;
 use value::{
     ConvexValue,
     FieldPath,
@@ -32,8 +33,15 @@ use super::{
     },
     DatabaseSchema,
     DocumentSchema,
+    IdStrategy,
     IndexSchema,
+    SketchKind,
+    SketchSchema,
+    SoftDeleteSchema,
+    TriggerSchema,
+    TriggerTiming,
     VectorIndexSchema,
+    MAX_SOFT_DELETE_RETENTION_DAYS,
 };
 use crate::{
     bootstrap_model::index::{
@@ -51,6 +59,7 @@ use crate::{
         SearchIndexSchema,
         TableDefinition,
         MAX_INDEXES_PER_TABLE,
+        MAX_SKETCHES_PER_TABLE,
     },
     types::{
         IndexDescriptor,
@@ -134,6 +143,121 @@ struct TableDefinitionJson {
     search_indexes: Option<Vec<JsonValue>>,
     vector_indexes: Option<Vec<JsonValue>>,
     document_type: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encrypted_fields: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    triggers: Option<Vec<TriggerSchemaJson>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    track_updates: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    soft_delete: Option<SoftDeleteSchemaJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id_strategy: Option<IdStrategyJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sketches: Option<Vec<SketchSchemaJson>>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SketchSchemaJson {
+    name: String,
+    index: String,
+    field: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SoftDeleteSchemaJson {
+    retention_days: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum IdStrategyJson {
+    Random,
+    Ulid,
+    External { field: String },
+}
+
+impl IdStrategy {
+    fn from_json(table_name: &TableName, j: IdStrategyJson) -> anyhow::Result<Self> {
+        match j {
+            IdStrategyJson::Random => Ok(IdStrategy::Random),
+            IdStrategyJson::Ulid => Ok(IdStrategy::Ulid),
+            IdStrategyJson::External { field } => {
+                let parsed = field.parse::<IdentifierFieldName>().with_context(|| {
+                    index_validation_error::invalid_external_id_field(
+                        table_name,
+                        &field,
+                        "isn't a valid field name",
+                    )
+                })?;
+                anyhow::ensure!(
+                    !field.starts_with('_'),
+                    index_validation_error::invalid_external_id_field(
+                        table_name,
+                        &field,
+                        "can't be a system field",
+                    )
+                );
+                Ok(IdStrategy::External { field: parsed })
+            },
+        }
+    }
+}
+
+impl From<IdStrategy> for IdStrategyJson {
+    fn from(strategy: IdStrategy) -> Self {
+        match strategy {
+            IdStrategy::Random => IdStrategyJson::Random,
+            IdStrategy::Ulid => IdStrategyJson::Ulid,
+            IdStrategy::External { field } => IdStrategyJson::External {
+                field: field.into(),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TriggerSchemaJson {
+    timing: String,
+    function: String,
+}
+
+impl TriggerSchema {
+    fn from_json(table_name: &TableName, j: TriggerSchemaJson) -> anyhow::Result<Self> {
+        let timing = match &j.timing[..] {
+            "before" => TriggerTiming::Before,
+            "after" => TriggerTiming::After,
+            _ => anyhow::bail!(index_validation_error::invalid_trigger_timing(
+                table_name, &j.timing
+            )),
+        };
+        let function: UdfPath = j
+            .function
+            .parse()
+            .with_context(|| index_validation_error::invalid_trigger(table_name, &j.function))?;
+        Ok(Self {
+            timing,
+            function: function.canonicalize(),
+        })
+    }
+}
+
+impl From<TriggerSchema> for TriggerSchemaJson {
+    fn from(TriggerSchema { timing, function }: TriggerSchema) -> Self {
+        let timing = match timing {
+            TriggerTiming::Before => "before",
+            TriggerTiming::After => "after",
+        };
+        Self {
+            timing: timing.to_string(),
+            function: String::from(function),
+        }
+    }
 }
 
 // Collect the index names separately from the deduplicating map so that we can
@@ -219,7 +343,12 @@ impl TryFrom<JsonValue> for TableDefinition {
             })?;
         validate_unique_index_fields(
             &search_indexes,
-            |idx| idx.search_field.clone(),
+            |idx| {
+                (
+                    idx.search_field.clone(),
+                    idx.additional_search_fields.clone(),
+                )
+            },
             |index1, index2| search_field_not_unique(&table_name, index1, index2),
         )?;
 
@@ -252,13 +381,134 @@ impl TryFrom<JsonValue> for TableDefinition {
             }
         }
 
-        Ok(Self {
+        let encrypted_fields = j
+            .encrypted_fields
+            .unwrap_or_default()
+            .into_iter()
+            .map(|field| {
+                let parsed = field.parse::<IdentifierFieldName>().with_context(|| {
+                    index_validation_error::invalid_encrypted_field(&table_name, &field)
+                })?;
+                anyhow::ensure!(
+                    !field.starts_with('_'),
+                    index_validation_error::invalid_encrypted_field(&table_name, &field)
+                );
+                Ok(parsed)
+            })
+            .collect::<anyhow::Result<BTreeSet<_>>>()?;
+
+        let triggers = j
+            .triggers
+            .unwrap_or_default()
+            .into_iter()
+            .map(|trigger| TriggerSchema::from_json(&table_name, trigger))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let soft_delete = j
+            .soft_delete
+            .map(|SoftDeleteSchemaJson { retention_days }| {
+                anyhow::ensure!(
+                    (1..=MAX_SOFT_DELETE_RETENTION_DAYS).contains(&retention_days),
+                    index_validation_error::invalid_soft_delete_retention(
+                        &table_name,
+                        retention_days
+                    )
+                );
+                Ok(SoftDeleteSchema { retention_days })
+            })
+            .transpose()?;
+
+        let id_strategy = j
+            .id_strategy
+            .map(|strategy| IdStrategy::from_json(&table_name, strategy))
+            .transpose()?
+            .unwrap_or_default();
+
+        let sketches_json = j.sketches.unwrap_or_default();
+        anyhow::ensure!(
+            sketches_json.len() <= MAX_SKETCHES_PER_TABLE,
+            index_validation_error::too_many_sketches(&table_name)
+        );
+        let mut sketches = BTreeMap::new();
+        for SketchSchemaJson {
+            name,
+            index,
+            field,
+            kind,
+        } in sketches_json
+        {
+            let invalid =
+                |reason: &str| index_validation_error::invalid_sketch(&table_name, &name, reason);
+            let parsed_name = name
+                .parse::<IdentifierFieldName>()
+                .with_context(|| invalid("isn't a valid name"))?;
+            let index = index
+                .parse::<IndexDescriptor>()
+                .ok()
+                .filter(|index| indexes.contains_key(index))
+                .with_context(|| {
+                    invalid(&format!(
+                        "uses {index:?}, which isn't an index on the table"
+                    ))
+                })?;
+            let field: FieldPath = field
+                .parse()
+                .with_context(|| invalid(&format!("has an invalid field {field:?}")))?;
+            anyhow::ensure!(
+                !field
+                    .fields()
+                    .first()
+                    .is_some_and(|field| encrypted_fields.contains(field)),
+                invalid("can't be over an encrypted field")
+            );
+            let kind = kind.parse::<SketchKind>().with_context(|| {
+                invalid(&format!(
+                    "has an invalid type {kind:?}. Expected \"distinct\" or \"quantile\""
+                ))
+            })?;
+            let sketch = SketchSchema { index, field, kind };
+            if sketches.insert(parsed_name, sketch).is_some() {
+                anyhow::bail!(invalid("is declared more than once"));
+            }
+        }
+
+        let table_definition = Self {
             table_name,
             indexes,
             search_indexes,
             vector_indexes,
             document_type,
-        })
+            encrypted_fields,
+            triggers,
+            track_updates: j.track_updates.unwrap_or(false),
+            soft_delete,
+            id_strategy,
+            sketches,
+        };
+        // Ciphertext is randomized, so indexing it would be useless.
+        for (index_descriptor, field_path) in table_definition.fields_referenced_in_indexes() {
+            if let Some(field) = field_path.fields().first()
+                && table_definition.encrypted_fields.contains(field)
+            {
+                anyhow::bail!(index_validation_error::encrypted_field_indexed(
+                    &table_definition.table_name,
+                    index_descriptor,
+                    field_path,
+                ));
+            }
+        }
+        // Documents are looked up by the plaintext key when they're inserted.
+        if let IdStrategy::External { field } = &table_definition.id_strategy {
+            anyhow::ensure!(
+                !table_definition.encrypted_fields.contains(field),
+                index_validation_error::invalid_external_id_field(
+                    &table_definition.table_name,
+                    field,
+                    "can't be encrypted",
+                )
+            );
+        }
+        Ok(table_definition)
     }
 }
 
@@ -272,6 +522,12 @@ impl TryFrom<TableDefinition> for JsonValue {
             search_indexes,
             vector_indexes,
             document_type,
+            encrypted_fields,
+            triggers,
+            track_updates,
+            soft_delete,
+            id_strategy,
+            sketches,
         }: TableDefinition,
     ) -> anyhow::Result<Self> {
         let table_name = String::from(table_name);
@@ -298,6 +554,25 @@ impl TryFrom<TableDefinition> for JsonValue {
             search_indexes,
             vector_indexes,
             document_type,
+            encrypted_fields: (!encrypted_fields.is_empty())
+                .then(|| encrypted_fields.into_iter().map(String::from).collect()),
+            triggers: (!triggers.is_empty())
+                .then(|| triggers.into_iter().map(TriggerSchemaJson::from).collect()),
+            track_updates: track_updates.then_some(true),
+            soft_delete: soft_delete
+                .map(|SoftDeleteSchema { retention_days }| SoftDeleteSchemaJson { retention_days }),
+            id_strategy: (id_strategy != IdStrategy::Random).then(|| id_strategy.into()),
+            sketches: (!sketches.is_empty()).then(|| {
+                sketches
+                    .into_iter()
+                    .map(|(name, sketch)| SketchSchemaJson {
+                        name: name.into(),
+                        index: sketch.index.to_string(),
+                        field: String::from(sketch.field),
+                        kind: sketch.kind.to_string(),
+                    })
+                    .collect()
+            }),
         })?)
     }
 }
@@ -426,6 +701,10 @@ impl TryFrom<VectorIndexSchema> for JsonValue {
 struct SearchIndexSchemaJson {
     index_descriptor: String,
     search_field: String,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    additional_search_fields: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    search_field_boosts: BTreeMap<String, u32>,
     filter_fields: BTreeSet<String>,
 }
 
@@ -438,6 +717,20 @@ impl TryFrom<JsonValue> for SearchIndexSchema {
         let search_field = j.search_field.parse().with_context(|| {
             index_validation_error::invalid_index_field(&index_descriptor, &j.search_field)
         })?;
+        let parse_field = |f: &String| {
+            f.parse()
+                .with_context(|| index_validation_error::invalid_index_field(&index_descriptor, f))
+        };
+        let additional_search_fields = j
+            .additional_search_fields
+            .iter()
+            .map(parse_field)
+            .collect::<anyhow::Result<BTreeSet<_>>>()?;
+        let search_field_boosts = j
+            .search_field_boosts
+            .iter()
+            .map(|(f, boost)| anyhow::Ok((parse_field(f)?, *boost)))
+            .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
         let filter_fields = j
             .filter_fields
             .into_iter()
@@ -448,7 +741,13 @@ impl TryFrom<JsonValue> for SearchIndexSchema {
             })
             .collect::<anyhow::Result<BTreeSet<_>>>()?;
 
-        Self::new(index_descriptor, search_field, filter_fields)
+        Self::new_multi_field(
+            index_descriptor,
+            search_field,
+            additional_search_fields,
+            search_field_boosts,
+            filter_fields,
+        )
     }
 }
 
@@ -459,6 +758,8 @@ impl TryFrom<SearchIndexSchema> for JsonValue {
         SearchIndexSchema {
             index_descriptor,
             search_field,
+            additional_search_fields,
+            search_field_boosts,
             filter_fields,
             ..
         }: SearchIndexSchema,
@@ -466,6 +767,14 @@ impl TryFrom<SearchIndexSchema> for JsonValue {
         let search_index_json = SearchIndexSchemaJson {
             index_descriptor: index_descriptor.to_string(),
             search_field: String::from(search_field),
+            additional_search_fields: additional_search_fields
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            search_field_boosts: search_field_boosts
+                .into_iter()
+                .map(|(f, boost)| (String::from(f), boost))
+                .collect(),
             filter_fields: filter_fields
                 .into_iter()
                 .map(String::from)
@@ -7,6 +7,7 @@ use std::{
     fmt::Display,
     iter,
     marker::PhantomData,
+    time::Duration,
 };
 
 use errors::ErrorMetadata;
@@ -21,6 +22,7 @@ use shape_inference::{
     ShapeConfig,
     ShapeCounter,
 };
+use sync_types::CanonicalizedUdfPath;
 #[cfg(any(test, feature = "testing"))]
 use value::TableType;
 use value::{
@@ -42,8 +44,11 @@ use crate::{
     bootstrap_model::index::{
         database_index::IndexedFields,
         index_validation_error,
+        text_index::DeveloperTextIndexConfig,
         vector_index::VectorDimensions,
+        MAX_SEARCH_FIELD_BOOST,
         MAX_SEARCH_INDEX_FILTER_FIELDS_SIZE,
+        MAX_SEARCH_INDEX_SEARCH_FIELDS_SIZE,
         MAX_VECTOR_INDEX_FILTER_FIELDS_SIZE,
     },
     document::ResolvedDocument,
@@ -173,6 +178,12 @@ macro_rules! db_schema {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: Some($document_schema),
+                        encrypted_fields: Default::default(),
+                        triggers: vec![],
+                        track_updates: false,
+                        soft_delete: None,
+                        id_strategy: Default::default(),
+                        sketches: Default::default(),
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -204,6 +215,12 @@ macro_rules! db_schema_not_validated {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: Some($document_schema),
+                        encrypted_fields: Default::default(),
+                        triggers: vec![],
+                        track_updates: false,
+                        soft_delete: None,
+                        id_strategy: Default::default(),
+                        sketches: Default::default(),
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -257,6 +274,12 @@ macro_rules! db_schema_with_vector_indexes {
                         search_indexes: Default::default(),
                         vector_indexes,
                         document_type: Some($document_schema),
+                        encrypted_fields: Default::default(),
+                        triggers: vec![],
+                        track_updates: false,
+                        soft_delete: None,
+                        id_strategy: Default::default(),
+                        sketches: Default::default(),
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -534,6 +557,108 @@ pub struct TableDefinition {
     pub search_indexes: BTreeMap<IndexDescriptor, SearchIndexSchema>,
     pub vector_indexes: BTreeMap<IndexDescriptor, VectorIndexSchema>,
     pub document_type: Option<DocumentSchema>,
+    /// Top-level fields whose values are encrypted before they're persisted
+    /// and decrypted when functions read them. They can't be indexed.
+    pub encrypted_fields: BTreeSet<IdentifierFieldName>,
+    /// Mutations that run inside the committing transaction for each write to
+    /// the table, in the order they're declared.
+    pub triggers: Vec<TriggerSchema>,
+    /// Whether the database maintains the `_updatedTime` and `_updatedBy`
+    /// fields of the table's documents.
+    pub track_updates: bool,
+    /// Set if deleting a document moves it to the trash, where it can be
+    /// restored until the retention window ends, rather than deleting it.
+    pub soft_delete: Option<SoftDeleteSchema>,
+    /// How the IDs of documents inserted into the table are generated.
+    pub id_strategy: IdStrategy,
+    /// Approximate aggregates the database maintains as documents are
+    /// written, keyed by name.
+    pub sketches: BTreeMap<IdentifierFieldName, SketchSchema>,
+}
+
+/// The most sketches a table can have. Each one is updated by every write to
+/// the table.
+pub const MAX_SKETCHES_PER_TABLE: usize = 8;
+
+/// An approximate aggregate over one field of a table's documents, e.g. the
+/// number of distinct users who sent messages or the 99th percentile request
+/// latency. It's kept per value of the first field of `index`, so it can be
+/// read for a range of that field without scanning the documents in it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SketchSchema {
+    pub index: IndexDescriptor,
+    pub field: FieldPath,
+    pub kind: SketchKind,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, strum::EnumString, strum::Display)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[strum(serialize_all = "camelCase")]
+pub enum SketchKind {
+    /// Estimates how many distinct values `field` has, with a HyperLogLog.
+    Distinct,
+    /// Estimates quantiles of `field`'s numeric values, with a DDSketch.
+    Quantile,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum IdStrategy {
+    /// IDs are random, apart from the day they were generated on.
+    #[default]
+    Random,
+    /// IDs start with the millisecond they were generated in, like ULIDs, so
+    /// ordering by `_id` orders documents by when they were inserted. IDs
+    /// generated by one transaction increase in the order they're inserted.
+    Ulid,
+    /// IDs are derived from the string an app stores in `field`, e.g. a key
+    /// from an external system, so every insert with the same key gets the
+    /// same ID. Inserting a second document with a key already in the table
+    /// fails, and the field can't be changed once it's set. Like other IDs,
+    /// the key of a deleted document can't be reused.
+    External { field: IdentifierFieldName },
+}
+
+/// The longest a soft-deleted document can be kept in the trash.
+pub const MAX_SOFT_DELETE_RETENTION_DAYS: u32 = 365;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SoftDeleteSchema {
+    /// How many days a deleted document is kept in the trash before it's
+    /// deleted for good.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "1..=MAX_SOFT_DELETE_RETENTION_DAYS")
+    )]
+    pub retention_days: u32,
+}
+
+impl SoftDeleteSchema {
+    pub fn retention(&self) -> Duration {
+        Duration::from_secs(u64::from(self.retention_days) * 24 * 60 * 60)
+    }
+}
+
+/// When a trigger runs relative to the other triggers for the same writes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum TriggerTiming {
+    /// Runs first, e.g. to validate a write and throw to reject it.
+    Before,
+    /// Runs once the `Before` triggers have accepted the writes, e.g. to
+    /// update denormalized data.
+    After,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TriggerSchema {
+    pub timing: TriggerTiming,
+    /// A mutation in the same component as the table. It's called with the
+    /// document's ID and its old and new values.
+    pub function: CanonicalizedUdfPath,
 }
 
 impl TableDefinition {
@@ -553,8 +678,10 @@ impl TableDefinition {
         let search_index_fields =
             self.search_indexes
                 .iter()
-                .map(|(index_descriptor, search_index_schema)| {
-                    (index_descriptor, (&search_index_schema.search_field))
+                .flat_map(|(index_descriptor, search_index_schema)| {
+                    std::iter::once(&search_index_schema.search_field)
+                        .chain(&search_index_schema.additional_search_fields)
+                        .map(move |field_path| (index_descriptor, field_path))
                 });
 
         let search_index_filter_fields =
@@ -629,6 +756,12 @@ impl proptest::arbitrary::Arbitrary for TableDefinition {
                                 .map(|i| (i.index_descriptor.clone(), i))
                                 .collect(),
                             document_type,
+                            encrypted_fields: BTreeSet::new(),
+                            triggers: vec![],
+                            track_updates: false,
+                            soft_delete: None,
+                            id_strategy: Default::default(),
+                            sketches: Default::default(),
                         })
                     } else {
                         None
@@ -656,6 +789,13 @@ impl Display for IndexSchema {
 pub struct SearchIndexSchema {
     pub index_descriptor: IndexDescriptor,
     pub search_field: FieldPath,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "prop::collection::btree_set(any::<FieldPath>(), 0..2)")
+    )]
+    pub additional_search_fields: BTreeSet<FieldPath>,
+    #[cfg_attr(any(test, feature = "testing"), proptest(value = "BTreeMap::new()"))]
+    pub search_field_boosts: BTreeMap<FieldPath, u32>,
     #[cfg_attr(
         any(test, feature = "testing"),
         proptest(strategy = "prop::collection::btree_set(any::<FieldPath>(), 0..8)")
@@ -671,19 +811,72 @@ impl SearchIndexSchema {
         index_descriptor: IndexDescriptor,
         search_field: FieldPath,
         filter_fields: BTreeSet<FieldPath>,
+    ) -> anyhow::Result<Self> {
+        Self::new_multi_field(
+            index_descriptor,
+            search_field,
+            BTreeSet::new(),
+            BTreeMap::new(),
+            filter_fields,
+        )
+    }
+
+    pub fn new_multi_field(
+        index_descriptor: IndexDescriptor,
+        search_field: FieldPath,
+        additional_search_fields: BTreeSet<FieldPath>,
+        search_field_boosts: BTreeMap<FieldPath, u32>,
+        filter_fields: BTreeSet<FieldPath>,
     ) -> anyhow::Result<Self> {
         if filter_fields.len() > MAX_SEARCH_INDEX_FILTER_FIELDS_SIZE {
             anyhow::bail!(index_validation_error::too_many_filter_fields(
                 MAX_SEARCH_INDEX_FILTER_FIELDS_SIZE
             ));
         }
+        if additional_search_fields.len() + 1 > MAX_SEARCH_INDEX_SEARCH_FIELDS_SIZE {
+            anyhow::bail!(index_validation_error::too_many_search_fields(
+                MAX_SEARCH_INDEX_SEARCH_FIELDS_SIZE
+            ));
+        }
+        if additional_search_fields.contains(&search_field) {
+            anyhow::bail!(index_validation_error::fields_not_unique_within_index(
+                &search_field
+            ));
+        }
+        for (field_path, boost) in &search_field_boosts {
+            if *field_path != search_field && !additional_search_fields.contains(field_path) {
+                anyhow::bail!(index_validation_error::invalid_search_field_boost(
+                    &index_descriptor,
+                    field_path,
+                    "is for a field that isn't a search field",
+                ));
+            }
+            if !(1..=MAX_SEARCH_FIELD_BOOST).contains(boost) {
+                anyhow::bail!(index_validation_error::invalid_search_field_boost(
+                    &index_descriptor,
+                    field_path,
+                    &format!("must be between 1 and {MAX_SEARCH_FIELD_BOOST}, not {boost}"),
+                ));
+            }
+        }
         Ok(Self {
             index_descriptor,
             search_field,
+            additional_search_fields,
+            search_field_boosts,
             filter_fields,
             _pd: PhantomData,
         })
     }
+
+    pub fn developer_config(&self) -> DeveloperTextIndexConfig {
+        DeveloperTextIndexConfig {
+            search_field: self.search_field.clone(),
+            additional_search_fields: self.additional_search_fields.clone(),
+            search_field_boosts: self.search_field_boosts.clone(),
+            filter_fields: self.filter_fields.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -746,7 +939,7 @@ pub enum DocumentSchema {
 }
 
 impl DocumentSchema {
-    fn check_value(
+    pub fn check_value(
         &self,
         value: &ConvexObject,
         table_mapping: &NamespacedTableMapping,
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use cmd_util::env::env_config;
 use proptest::prelude::*;
 use serde_json::{
@@ -26,6 +28,10 @@ use crate::{
         },
         DatabaseSchema,
         DocumentSchema,
+        IdStrategy,
+        SketchKind,
+        SketchSchema,
+        TriggerTiming,
         Validator,
     },
     testing::assert_roundtrips,
@@ -657,6 +663,96 @@ fn test_json_backwards_compatibility() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_encrypted_fields() -> anyhow::Result<()> {
+    let table = |encrypted_fields: JsonValue, index_fields: JsonValue| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "users",
+                    "indexes": [
+                        {
+                            "indexDescriptor": "by_field",
+                            "fields": index_fields,
+                        },
+                    ],
+                    "searchIndexes": [],
+                    "encryptedFields": encrypted_fields,
+                },
+            ],
+            "schemaValidation": true
+        })
+    };
+    let schema = DatabaseSchema::try_from(table(json!(["ssn"]), json!(["name"])))?;
+    let users = &schema.tables[&"users".parse::<value::TableName>()?];
+    assert_eq!(
+        users
+            .encrypted_fields
+            .iter()
+            .map(|f| &f[..])
+            .collect::<Vec<_>>(),
+        vec!["ssn"]
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    let error = DatabaseSchema::try_from(table(json!(["ssn"]), json!(["ssn.last4"])))
+        .expect_err("Successfully created invalid schema");
+    assert!(error
+        .to_string()
+        .contains("Encrypted fields can't be indexed"));
+
+    let error = DatabaseSchema::try_from(table(json!(["_creationTime"]), json!(["name"])))
+        .expect_err("Successfully created invalid schema");
+    assert!(error.to_string().contains("Invalid encrypted field"));
+    Ok(())
+}
+
+#[test]
+fn test_triggers() -> anyhow::Result<()> {
+    let table = |triggers: JsonValue| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "messages",
+                    "indexes": [],
+                    "triggers": triggers,
+                },
+            ],
+            "schemaValidation": true
+        })
+    };
+    let schema = DatabaseSchema::try_from(table(json!([
+        { "timing": "before", "function": "triggers:validateMessage" },
+        { "timing": "after", "function": "triggers.js:countMessages" },
+    ])))?;
+    let messages = &schema.tables[&"messages".parse::<value::TableName>()?];
+    assert_eq!(
+        messages
+            .triggers
+            .iter()
+            .map(|t| (t.timing, String::from(t.function.clone())))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                TriggerTiming::Before,
+                "triggers.js:validateMessage".to_string()
+            ),
+            (
+                TriggerTiming::After,
+                "triggers.js:countMessages".to_string()
+            ),
+        ]
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    let error = DatabaseSchema::try_from(table(json!([
+        { "timing": "during", "function": "triggers:validateMessage" },
+    ])))
+    .expect_err("Successfully created invalid schema");
+    assert!(error.to_string().contains("Invalid trigger timing"));
+    Ok(())
+}
+
 fn empty_table_mapping() -> NamespacedTableMapping {
     TableMapping::new().namespace(TableNamespace::test_user())
 }
@@ -1621,3 +1717,164 @@ mod can_contain_field {
         Ok(())
     }
 }
+
+#[test]
+fn test_track_updates() -> anyhow::Result<()> {
+    let schema_json = json!({
+        "tables": [
+            {
+                "tableName": "messages",
+                "indexes": [
+                    {
+                        "indexDescriptor": "by_updated_time",
+                        "fields": ["_updatedTime"],
+                    },
+                ],
+                "trackUpdates": true,
+            },
+            {
+                "tableName": "users",
+                "indexes": [],
+            },
+        ],
+        "schemaValidation": true
+    });
+    let schema = DatabaseSchema::try_from(schema_json)?;
+    assert!(schema.tables[&"messages".parse::<value::TableName>()?].track_updates);
+    assert!(!schema.tables[&"users".parse::<value::TableName>()?].track_updates);
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+    Ok(())
+}
+
+#[test]
+fn test_soft_delete() -> anyhow::Result<()> {
+    let table = |soft_delete: JsonValue| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "messages",
+                    "indexes": [],
+                    "softDelete": soft_delete,
+                },
+            ],
+            "schemaValidation": true
+        })
+    };
+    let schema = DatabaseSchema::try_from(table(json!({ "retentionDays": 30 })))?;
+    let messages = &schema.tables[&"messages".parse::<value::TableName>()?];
+    assert_eq!(
+        messages
+            .soft_delete
+            .map(|soft_delete| soft_delete.retention()),
+        Some(Duration::from_secs(30 * 24 * 60 * 60))
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    let error = DatabaseSchema::try_from(table(json!({ "retentionDays": 0 })))
+        .expect_err("Successfully created invalid schema");
+    assert!(error
+        .to_string()
+        .contains("Soft delete retention must be between 1 and 365 days"));
+    Ok(())
+}
+
+#[test]
+fn test_id_strategy() -> anyhow::Result<()> {
+    let table = |id_strategy: JsonValue| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "orders",
+                    "indexes": [],
+                    "encryptedFields": ["address"],
+                    "idStrategy": id_strategy,
+                },
+            ],
+            "schemaValidation": true
+        })
+    };
+    let orders: value::TableName = "orders".parse()?;
+    let schema = DatabaseSchema::try_from(table(json!({ "type": "ulid" })))?;
+    assert_eq!(schema.tables[&orders].id_strategy, IdStrategy::Ulid);
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    let schema = DatabaseSchema::try_from(table(json!({
+        "type": "external",
+        "field": "orderNumber",
+    })))?;
+    assert_eq!(
+        schema.tables[&orders].id_strategy,
+        IdStrategy::External {
+            field: "orderNumber".parse()?
+        }
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    for field in ["_id", "address"] {
+        let error = DatabaseSchema::try_from(table(json!({
+            "type": "external",
+            "field": field,
+        })))
+        .expect_err("Successfully created invalid schema");
+        assert!(error.to_string().contains("External ID field"), "{error}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sketches() -> anyhow::Result<()> {
+    let table = |sketches: JsonValue| {
+        json!({
+            "tables": [
+                {
+                    "tableName": "requests",
+                    "indexes": [
+                        { "indexDescriptor": "by_route", "fields": ["route"] },
+                    ],
+                    "encryptedFields": ["ip"],
+                    "sketches": sketches,
+                },
+            ],
+            "schemaValidation": true
+        })
+    };
+    let schema = DatabaseSchema::try_from(table(json!([
+        { "name": "users", "index": "by_route", "field": "userId", "type": "distinct" },
+        { "name": "latency", "index": "by_route", "field": "latencyMs", "type": "quantile" },
+    ])))?;
+    let requests = &schema.tables[&"requests".parse::<value::TableName>()?];
+    assert_eq!(
+        requests.sketches[&"latency".parse()?],
+        SketchSchema {
+            index: "by_route".parse()?,
+            field: "latencyMs".parse()?,
+            kind: SketchKind::Quantile,
+        }
+    );
+    assert_roundtrips::<DatabaseSchema, JsonValue>(schema);
+
+    for (sketch, expected) in [
+        (
+            json!({ "name": "users", "index": "by_user", "field": "userId", "type": "distinct" }),
+            "isn't an index on the table",
+        ),
+        (
+            json!({ "name": "ips", "index": "by_route", "field": "ip", "type": "distinct" }),
+            "encrypted field",
+        ),
+        (
+            json!({ "name": "users", "index": "by_route", "field": "userId", "type": "sum" }),
+            "invalid type",
+        ),
+    ] {
+        let error = DatabaseSchema::try_from(table(json!([sketch])))
+            .expect_err("Successfully created invalid schema");
+        assert!(error.to_string().contains(expected), "{error}");
+    }
+    let sketch =
+        json!({ "name": "users", "index": "by_route", "field": "userId", "type": "distinct" });
+    let error = DatabaseSchema::try_from(table(json!([sketch.clone(), sketch])))
+        .expect_err("Successfully created invalid schema");
+    assert!(error.to_string().contains("more than once"), "{error}");
+    Ok(())
+}
@@ -342,6 +342,10 @@ impl Validator {
             ShapeEnum::NegativeZero => Self::Float64,
             ShapeEnum::NaN => Self::Float64,
             ShapeEnum::NormalFloat64 => Self::Float64,
+            // There's no decimal validator, so fall back to `v.any()`.
+            ShapeEnum::Decimal => Self::Any,
+            // Likewise for geo points.
+            ShapeEnum::GeoPoint => Self::Any,
             ShapeEnum::Boolean => Self::Boolean,
             ShapeEnum::StringLiteral(s) => {
                 Self::Literal(LiteralValidator::String(s.literal.clone()))
@@ -804,6 +808,12 @@ impl ValidationContext {
             None => Self(Some(new_context)),
         }
     }
+
+    /// The path from the validated value to where validation failed, e.g.
+    /// `.messages[0].author`, or empty for the value itself.
+    pub fn path(&self) -> &str {
+        self.0.as_deref().unwrap_or_default()
+    }
 }
 
 impl Display for ValidationContext {
@@ -1002,6 +1012,10 @@ impl FieldValidator {
         &self.validator
     }
 
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+
     pub fn required_field_type(validator: Validator) -> Self {
         Self {
             validator,
@@ -1095,6 +1109,74 @@ Validator: {validator}")]
     },
 }
 
+impl ValidationError {
+    /// The path to the value that failed validation. For missing and extra
+    /// fields, this is the path to the field.
+    pub fn path(&self) -> String {
+        match self {
+            ValidationError::TableNamesDoNotMatch { context, .. }
+            | ValidationError::SystemTableReference { context, .. }
+            | ValidationError::LiteralValuesDoNotMatch { context, .. }
+            | ValidationError::NoMatch { context, .. } => context.path().to_string(),
+            ValidationError::MissingRequiredField {
+                field_name,
+                context,
+                ..
+            } => format!("{}.{field_name}", context.path()),
+            ValidationError::ExtraField {
+                field_name,
+                context,
+                ..
+            } => format!("{}.{field_name}", context.path()),
+        }
+    }
+
+    /// The validator the value at `path()` had to match, e.g. `v.string()`.
+    pub fn expected(&self) -> String {
+        match self {
+            ValidationError::TableNamesDoNotMatch {
+                validator_table, ..
+            }
+            | ValidationError::SystemTableReference {
+                validator_table, ..
+            } => Validator::Id(validator_table.clone()).to_string(),
+            ValidationError::LiteralValuesDoNotMatch {
+                literal_validator, ..
+            } => Validator::Literal(literal_validator.clone()).to_string(),
+            ValidationError::MissingRequiredField {
+                field_name,
+                object_validator,
+                ..
+            } => match object_validator.0.get::<str>(field_name.borrow()) {
+                Some(field_validator) => field_validator.to_string(),
+                None => Validator::Any.to_string(),
+            },
+            ValidationError::ExtraField { .. } => "undefined".to_string(),
+            ValidationError::NoMatch { validator, .. } => validator.to_string(),
+        }
+    }
+
+    /// What was found at `path()`: the value's type, e.g. `String`, the
+    /// value itself for literals, or `undefined` for a missing field.
+    pub fn actual(&self) -> String {
+        match self {
+            ValidationError::TableNamesDoNotMatch {
+                found_table_name, ..
+            } => format!("Id(\"{found_table_name}\")"),
+            ValidationError::SystemTableReference { .. } => "Id(system table)".to_string(),
+            ValidationError::LiteralValuesDoNotMatch { value, .. } => value.to_string(),
+            ValidationError::MissingRequiredField { .. } => "undefined".to_string(),
+            ValidationError::ExtraField {
+                object, field_name, ..
+            } => object
+                .get(field_name)
+                .map_or("undefined", |value| value.type_name())
+                .to_string(),
+            ValidationError::NoMatch { value, .. } => value.type_name().to_string(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -1567,6 +1649,41 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_validation_error_details() -> anyhow::Result<()> {
+        let validator = Validator::Object(object_validator!(
+            "author" => FieldValidator::required_field_type(Validator::String),
+            "tags" => FieldValidator::required_field_type(
+                Validator::Array(Box::new(Validator::String))
+            ),
+        ));
+        let check = |value: ConvexObject| {
+            validator
+                .check_value(
+                    &value.into(),
+                    &empty_table_mapping(),
+                    &empty_virtual_table_mapping(),
+                )
+                .unwrap_err()
+        };
+
+        let err = check(assert_obj!("author" => "sarah", "tags" => ["a", 1.0]));
+        assert_eq!(err.path(), ".tags[1]");
+        assert_eq!(err.expected(), "v.string()");
+        assert_eq!(err.actual(), "Float64");
+
+        let err = check(assert_obj!("tags" => ["a"]));
+        assert_eq!(err.path(), ".author");
+        assert_eq!(err.expected(), "v.string()");
+        assert_eq!(err.actual(), "undefined");
+
+        let err = check(assert_obj!("author" => "sarah", "tags" => ["a"], "extra" => true));
+        assert_eq!(err.path(), ".extra");
+        assert_eq!(err.expected(), "undefined");
+        assert_eq!(err.actual(), "Boolean");
+        Ok(())
+    }
+
     #[test]
     fn test_id_match() -> anyhow::Result<()> {
         let table1: TableName = "table1".parse()?;
@@ -36,6 +36,8 @@ pub fn dashboard_shape_json(
         ReducedShape::Float64(range) => {
             json!({"type": "Float64", "float64Range": JsonValue::from(range)})
         },
+        ReducedShape::Decimal => json!({"type": "Decimal"}),
+        ReducedShape::GeoPoint => json!({"type": "GeoPoint"}),
         ReducedShape::Boolean => json!({"type": "Boolean"}),
         ReducedShape::String => json!({"type": "String"}),
         ReducedShape::Bytes => json!({"type": "Bytes"}),
@@ -150,6 +152,8 @@ mod tests {
             Float64 {
                 float64_range: JsonValue,
             },
+            Decimal,
+            GeoPoint,
             Boolean,
             String,
             Bytes,
@@ -190,6 +194,8 @@ mod tests {
             ShapeEnumJson::Float64 { float64_range } => {
                 ReducedShape::Float64(ReducedFloatRange::try_from(float64_range)?)
             },
+            ShapeEnumJson::Decimal => ReducedShape::Decimal,
+            ShapeEnumJson::GeoPoint => ReducedShape::GeoPoint,
             ShapeEnumJson::Boolean => ReducedShape::Boolean,
             ShapeEnumJson::String => ReducedShape::String,
             ShapeEnumJson::Bytes => ReducedShape::Bytes,
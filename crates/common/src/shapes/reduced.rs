@@ -36,6 +36,8 @@ pub enum ReducedShape {
     Null,
     Int64,
     Float64(ReducedFloatRange),
+    Decimal,
+    GeoPoint,
     Boolean,
     String,
     Bytes,
@@ -88,6 +90,8 @@ impl ReducedShape {
             ShapeEnum::NormalFloat64 => ReducedShape::Float64(ReducedFloatRange {
                 has_special_values: false,
             }),
+            ShapeEnum::Decimal => ReducedShape::Decimal,
+            ShapeEnum::GeoPoint => ReducedShape::GeoPoint,
             ShapeEnum::Boolean => ReducedShape::Boolean,
             ShapeEnum::StringLiteral(ref s) => {
                 if let Ok(id) = DeveloperDocumentId::decode(s)
@@ -0,0 +1,168 @@
+use std::{
+    collections::BTreeSet,
+    future::Future,
+    sync::{
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use rand::Rng;
+use serde_json::Value as JsonValue;
+use value::InternalDocumentId;
+
+use crate::{
+    document::ResolvedDocument,
+    index::IndexEntry,
+    persistence::{
+        ConflictStrategy,
+        Persistence,
+        PersistenceGlobalKey,
+        PersistenceReader,
+    },
+    runtime::Runtime,
+    types::{
+        DatabaseIndexUpdate,
+        Timestamp,
+    },
+};
+
+/// How a [`FaultInjectingPersistence`] disrupts writes. Reads are never
+/// disrupted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PersistenceFaults {
+    /// Probability that a write fails without being applied.
+    pub fail_before_write: f64,
+    /// Probability that a write is applied but fails anyway, like a write
+    /// whose acknowledgement was lost.
+    pub fail_after_write: f64,
+    /// Each write is delayed by a random duration up to this long, which
+    /// changes how it interleaves with everything else.
+    pub max_write_delay: Duration,
+}
+
+/// Wraps a persistence to inject faults into its writes, drawing from the
+/// runtime's RNG so that a `TestRuntime` with the same seed injects the same
+/// faults.
+pub struct FaultInjectingPersistence<RT: Runtime> {
+    inner: Arc<dyn Persistence>,
+    runtime: RT,
+    faults: Mutex<PersistenceFaults>,
+    num_faults: AtomicUsize,
+}
+
+impl<RT: Runtime> FaultInjectingPersistence<RT> {
+    pub fn new(inner: Arc<dyn Persistence>, runtime: RT, faults: PersistenceFaults) -> Self {
+        Self {
+            inner,
+            runtime,
+            faults: Mutex::new(faults),
+            num_faults: AtomicUsize::new(0),
+        }
+    }
+
+    /// Change the faults injected into subsequent writes, e.g. to stop
+    /// injecting them while recovering.
+    pub fn set_faults(&self, faults: PersistenceFaults) {
+        *self.faults.lock() = faults;
+    }
+
+    /// Number of writes that have failed so far.
+    pub fn num_faults(&self) -> usize {
+        self.num_faults.load(Ordering::SeqCst)
+    }
+
+    async fn inject<T: Send>(
+        &self,
+        write: impl Future<Output = anyhow::Result<T>> + Send,
+    ) -> anyhow::Result<T> {
+        let faults = *self.faults.lock();
+        let (delay, fail_before_write, fail_after_write) = self.runtime.with_rng(|rng| {
+            (
+                rng.gen_range(Duration::ZERO..=faults.max_write_delay),
+                rng.gen_bool(faults.fail_before_write),
+                rng.gen_bool(faults.fail_after_write),
+            )
+        });
+        if !delay.is_zero() {
+            self.runtime.wait(delay).await;
+        }
+        if fail_before_write {
+            self.num_faults.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("Injected persistence fault before the write");
+        }
+        let result = write.await?;
+        if fail_after_write {
+            self.num_faults.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("Injected persistence fault after the write");
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> Persistence for FaultInjectingPersistence<RT> {
+    fn is_fresh(&self) -> bool {
+        self.inner.is_fresh()
+    }
+
+    fn reader(&self) -> Arc<dyn PersistenceReader> {
+        self.inner.reader()
+    }
+
+    fn set_ratelimiter_enabled(&self, enabled: bool) {
+        self.inner.set_ratelimiter_enabled(enabled)
+    }
+
+    async fn write(
+        &self,
+        documents: Vec<(Timestamp, InternalDocumentId, Option<ResolvedDocument>)>,
+        indexes: BTreeSet<(Timestamp, DatabaseIndexUpdate)>,
+        conflict_strategy: ConflictStrategy,
+    ) -> anyhow::Result<()> {
+        self.inject(self.inner.write(documents, indexes, conflict_strategy))
+            .await
+    }
+
+    async fn set_read_only(&self, read_only: bool) -> anyhow::Result<()> {
+        self.inner.set_read_only(read_only).await
+    }
+
+    async fn write_persistence_global(
+        &self,
+        key: PersistenceGlobalKey,
+        value: JsonValue,
+    ) -> anyhow::Result<()> {
+        self.inject(self.inner.write_persistence_global(key, value))
+            .await
+    }
+
+    async fn load_index_chunk(
+        &self,
+        cursor: Option<IndexEntry>,
+        chunk_size: usize,
+    ) -> anyhow::Result<Vec<IndexEntry>> {
+        self.inner.load_index_chunk(cursor, chunk_size).await
+    }
+
+    async fn delete_index_entries(&self, entries: Vec<IndexEntry>) -> anyhow::Result<usize> {
+        self.inject(self.inner.delete_index_entries(entries)).await
+    }
+
+    async fn delete(
+        &self,
+        documents: Vec<(Timestamp, InternalDocumentId)>,
+    ) -> anyhow::Result<usize> {
+        self.inject(self.inner.delete(documents)).await
+    }
+
+    async fn shutdown(&self) -> anyhow::Result<()> {
+        self.inner.shutdown().await
+    }
+}
@@ -1,10 +1,16 @@
 //! Test helpers for types defined in this crate
+mod fault_injection;
 #[cfg(test)]
 mod schema;
+mod simulation;
 mod test_id_generator;
 mod test_persistence;
 
 pub use cmd_util::env::config_test as init_test_logging;
+pub use fault_injection::{
+    FaultInjectingPersistence,
+    PersistenceFaults,
+};
 use proptest::{
     arbitrary::{
         any,
@@ -20,6 +26,7 @@ use proptest::{
         TestRunner,
     },
 };
+pub use simulation::run_simulation;
 pub use sync_types::testing::assert_roundtrips;
 pub use test_id_generator::TestIdGenerator;
 pub use test_persistence::TestPersistence;
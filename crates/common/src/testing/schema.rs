@@ -814,6 +814,62 @@ fn test_too_many_search_filter_fields() {
     );
 }
 
+#[test]
+fn test_invalid_search_field_boosts() {
+    for (boosts, additional_search_fields) in [
+        (json!({ "title": 0 }), json!(["body"])),
+        (json!({ "title": 11 }), json!(["body"])),
+        (json!({ "author": 2 }), json!(["body"])),
+        (json!({ "body": 2 }), json!([])),
+    ] {
+        let value = json!({
+            "tables": [
+                {
+                    "tableName": "test",
+                    "indexes": [],
+                    "searchIndexes": [{
+                        "indexDescriptor": "search_index",
+                        "searchField": "title",
+                        "additionalSearchFields": additional_search_fields,
+                        "searchFieldBoosts": boosts,
+                        "filterFields": [],
+                    }]
+                },
+            ],
+            "schemaValidation": true,
+        });
+        let err = index_validation_test(value);
+        assert_eq!(
+            err.short_msg, "InvalidSearchFieldBoost",
+            "<{err}> does not match expected error type"
+        );
+    }
+}
+
+#[test]
+fn test_duplicate_search_fields() {
+    let value = json!({
+        "tables": [
+            {
+                "tableName": "test",
+                "indexes": [],
+                "searchIndexes": [{
+                    "indexDescriptor": "search_index",
+                    "searchField": "title",
+                    "additionalSearchFields": ["title"],
+                    "filterFields": [],
+                }]
+            },
+        ],
+        "schemaValidation": true,
+    });
+    let err = index_validation_test(value);
+    assert_eq!(
+        err.short_msg, "FieldsNotUniqueWithinIndex",
+        "<{err}> does not match expected error type"
+    );
+}
+
 #[test]
 fn test_too_many_indexes() {
     let value = json!({
@@ -0,0 +1,48 @@
+//! Deterministic simulation testing.
+//!
+//! A simulation runs a randomized workload on a `TestRuntime`, which has a
+//! virtual clock, a seeded RNG and a single-threaded scheduler seeded with the
+//! same seed. So a seed determines the interleaving of every task and every
+//! fault injected with the runtime's RNG, and a failing seed can be rerun on
+//! its own to debug it.
+
+use std::{
+    env,
+    future::Future,
+};
+
+use anyhow::Context;
+use cmd_util::env::{
+    config_test,
+    env_config,
+};
+
+use crate::runtime::testing::{
+    TestDriver,
+    TestRuntime,
+};
+
+/// Run `simulation` once for each seed. By default those are the first
+/// `num_seeds` seeds, or the first `CONVEX_SIMULATION_SEEDS` if it's set.
+/// `CONVEX_SIMULATION_SEED` runs just that seed.
+pub fn run_simulation<F, Fut>(num_seeds: u64, simulation: F) -> anyhow::Result<()>
+where
+    F: Fn(TestRuntime) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    config_test();
+    let seeds: Vec<u64> = match env::var("CONVEX_SIMULATION_SEED") {
+        Ok(seed) => vec![seed.parse().context("Invalid CONVEX_SIMULATION_SEED")?],
+        Err(_) => (0..env_config("CONVEX_SIMULATION_SEEDS", num_seeds)).collect(),
+    };
+    for seed in seeds {
+        let driver = TestDriver::new_with_seed(seed);
+        let rt = driver.rt();
+        driver.run_until(simulation(rt)).with_context(|| {
+            format!(
+                "Simulation failed with seed {seed}. Rerun it with CONVEX_SIMULATION_SEED={seed}"
+            )
+        })?;
+    }
+    Ok(())
+}
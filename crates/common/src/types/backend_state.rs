@@ -5,10 +5,17 @@ pub enum BackendState {
     Disabled,
     Paused,
     Running,
+    /// Queries and subscriptions run as usual, but mutations, actions and any
+    /// other writes to user tables are rejected.
+    ReadOnly,
 }
 
 impl BackendState {
     pub fn is_stopped(&self) -> bool {
         matches!(self, BackendState::Disabled | BackendState::Paused)
     }
+
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, BackendState::ReadOnly)
+    }
 }
@@ -20,7 +20,10 @@ use value::{
 };
 
 use super::HttpActionRoute;
-use crate::version::ClientVersion;
+use crate::{
+    execution_context::TraceParent,
+    version::ClientVersion,
+};
 
 #[derive(Serialize, Copy, Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
 #[serde(rename_all = "camelCase")]
@@ -140,6 +143,8 @@ pub enum FunctionCaller {
     },
     Action {
         parent_scheduled_job: Option<DeveloperDocumentId>,
+        /// The calling action's `traceparent`.
+        trace_parent: Option<TraceParent>,
     },
 }
 
@@ -167,10 +172,23 @@ impl FunctionCaller {
             FunctionCaller::Scheduler { job_id } => Some(*job_id),
             FunctionCaller::Action {
                 parent_scheduled_job,
+                ..
             } => *parent_scheduled_job,
         }
     }
 
+    pub fn trace_parent(&self) -> Option<TraceParent> {
+        match self {
+            FunctionCaller::SyncWorker(_)
+            | FunctionCaller::HttpApi(_)
+            | FunctionCaller::Tester(_)
+            | FunctionCaller::HttpEndpoint
+            | FunctionCaller::Cron
+            | FunctionCaller::Scheduler { .. } => None,
+            FunctionCaller::Action { trace_parent, .. } => *trace_parent,
+        }
+    }
+
     pub fn is_root(&self) -> bool {
         match self {
             FunctionCaller::SyncWorker(_)
@@ -252,9 +270,11 @@ impl From<FunctionCaller> for pb::common::FunctionCaller {
             },
             FunctionCaller::Action {
                 parent_scheduled_job,
+                trace_parent,
             } => {
                 let caller = pb::common::ActionFunctionCaller {
                     parent_scheduled_job: parent_scheduled_job.map(|job_id| job_id.into()),
+                    trace_parent: trace_parent.map(|t| t.to_string()),
                 };
                 pb::common::function_caller::Caller::Action(caller)
             },
@@ -291,12 +311,15 @@ impl TryFrom<pb::common::FunctionCaller> for FunctionCaller {
             Some(pb::common::function_caller::Caller::Action(caller)) => {
                 let pb::common::ActionFunctionCaller {
                     parent_scheduled_job,
+                    trace_parent,
                 } = caller;
                 let parent_scheduled_job = parent_scheduled_job
                     .map(|job_id| job_id.try_into())
                     .transpose()?;
+                let trace_parent = trace_parent.map(|t| t.parse()).transpose()?;
                 FunctionCaller::Action {
                     parent_scheduled_job,
+                    trace_parent,
                 }
             },
             None => anyhow::bail!("Missing `caller` field"),
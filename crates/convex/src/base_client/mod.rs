@@ -13,6 +13,7 @@ use std::{
 };
 
 use convex_sync_types::{
+    patch::apply_patch,
     AuthenticationToken,
     CanonicalizedUdfPath,
     ClientMessage,
@@ -26,7 +27,10 @@ use convex_sync_types::{
     Timestamp,
     UdfPath,
 };
-use serde_json::json;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
 use tokio::sync::oneshot;
 
 #[cfg(doc)]
@@ -321,6 +325,30 @@ impl RemoteQuerySet {
                     };
                     self.remote_query_set.insert(query_id, function_result);
                 },
+                StateModification::QueryPatched {
+                    query_id,
+                    patch,
+                    log_lines,
+                    journal: _,
+                } => {
+                    for log_line in log_lines.0 {
+                        convex_logs!("{}", log_line);
+                    }
+                    let Some(FunctionResult::Value(value)) = self.remote_query_set.get(&query_id)
+                    else {
+                        tracing::error!(
+                            "INTERNAL BUG: Protocol Error patch for query {query_id:?} without a \
+                             previous value"
+                        );
+                        return Err("QueryPatchedWithoutValue".into());
+                    };
+                    let mut json = JsonValue::from(value.clone());
+                    let value = apply_patch(&mut json, &patch)
+                        .and_then(|()| Value::try_from(json))
+                        .map_err(|e| format!("InvalidQueryPatch: {e}"))?;
+                    self.remote_query_set
+                        .insert(query_id, FunctionResult::Value(value));
+                },
                 StateModification::QueryRemoved { query_id } => {
                     self.remote_query_set.remove(&query_id);
                 },
@@ -399,8 +427,10 @@ impl OptimisticQueryResults {
 /// To watch for consistent changes in query values, you can add the following
 /// code to the background thread:
 /// ```no_run
-/// use convex::base_client::BaseConvexClient;
-/// use convex::Value;
+/// use convex::{
+///     base_client::BaseConvexClient,
+///     Value,
+/// };
 /// use convex_sync_types::ServerMessage;
 ///
 /// fn on_receive_server_message(mut base_client: BaseConvexClient, msg: ServerMessage<Value>) {
@@ -653,6 +683,11 @@ impl BaseConvexClient {
             ServerMessage::Ping => {
                 // Do nothing
             },
+            ServerMessage::SubscriptionsExpired => {
+                // The server reruns our queries when we next send it a
+                // message, so there's nothing to do until then.
+                tracing::debug!("Server expired query subscriptions");
+            },
         }
         Ok(None)
     }
@@ -407,6 +407,7 @@ pub mod tests {
         StateVersion,
         UdfPath,
         UserIdentityAttributes,
+        SYNC_PROTOCOL_VERSION,
     };
     use futures::{
         channel::mpsc,
@@ -659,6 +660,7 @@ pub mod tests {
                     connection_count: 0,
                     last_close_reason: "InitialConnect".to_string(),
                     max_observed_timestamp: None,
+                    protocol_version: SYNC_PROTOCOL_VERSION,
                 },
                 ClientMessage::ModifyQuerySet {
                     base_version: 0,
@@ -791,6 +793,7 @@ pub mod tests {
                     connection_count: 0,
                     last_close_reason: "InitialConnect".to_string(),
                     max_observed_timestamp: None,
+                    protocol_version: SYNC_PROTOCOL_VERSION,
                 },
                 ClientMessage::ModifyQuerySet {
                     base_version: 0,
@@ -848,6 +851,7 @@ pub mod tests {
                     connection_count: 0,
                     last_close_reason: "InitialConnect".to_string(),
                     max_observed_timestamp: None,
+                    protocol_version: SYNC_PROTOCOL_VERSION,
                 },
                 ClientMessage::ModifyQuerySet {
                     base_version: 0,
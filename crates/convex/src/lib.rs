@@ -19,11 +19,18 @@
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
 //!     let mut client = ConvexClient::new("https://cool-music-123.convex.cloud").await?;
-//!     client.mutation("sendMessage", maplit::btreemap!{
-//!         "body".into() => "Let it be.".into(),
-//!         "author".into() => "The Beatles".into(),
-//!     }).await?;
-//!     let mut sub = client.subscribe("listMessages", maplit::btreemap!{}).await?;
+//!     client
+//!         .mutation(
+//!             "sendMessage",
+//!             maplit::btreemap! {
+//!                 "body".into() => "Let it be.".into(),
+//!                 "author".into() => "The Beatles".into(),
+//!             },
+//!         )
+//!         .await?;
+//!     let mut sub = client
+//!         .subscribe("listMessages", maplit::btreemap! {})
+//!         .await?;
 //!     while let Some(result) = sub.next().await {
 //!         println!("{result:?}");
 //!     }
@@ -46,6 +53,8 @@ mod value;
 pub use value::export::roundtrip::ExportContext;
 pub use value::{
     ConvexError,
+    Decimal,
+    GeoPoint,
     Value,
 };
 
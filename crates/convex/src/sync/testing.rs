@@ -8,6 +8,7 @@ use async_trait::async_trait;
 use convex_sync_types::{
     ClientMessage,
     SessionId,
+    SYNC_PROTOCOL_VERSION,
 };
 use futures::{
     channel::mpsc,
@@ -81,6 +82,7 @@ impl SyncProtocol for TestProtocolManager {
                 connection_count,
                 last_close_reason: "InitialConnect".to_string(),
                 max_observed_timestamp: None,
+                protocol_version: SYNC_PROTOCOL_VERSION,
             })
             .await?;
 
@@ -11,6 +11,7 @@ use convex_sync_types::{
     ClientMessage,
     SessionId,
     Timestamp,
+    SYNC_PROTOCOL_VERSION,
 };
 use futures::{
     channel::{
@@ -314,6 +315,7 @@ impl WebSocketInternal {
             connection_count,
             last_close_reason,
             max_observed_timestamp,
+            protocol_version: SYNC_PROTOCOL_VERSION,
         };
         let msg = Message::Text(
             serde_json::Value::try_from(message)
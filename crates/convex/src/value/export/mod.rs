@@ -47,6 +47,10 @@ impl Value {
                     value.into()
                 }
             },
+            Value::Decimal(value) => JsonValue::String(value.to_string()),
+            Value::GeoPoint(value) => {
+                json!({ "latitude": value.latitude(), "longitude": value.longitude() })
+            },
             Value::Boolean(value) => JsonValue::Bool(value),
             Value::String(value) => JsonValue::String(value),
             Value::Bytes(value) => JsonValue::String(base64::encode(value)),
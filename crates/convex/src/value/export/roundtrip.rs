@@ -3,7 +3,11 @@ use std::collections::BTreeMap;
 use anyhow::Context;
 use serde_json::Value as JsonValue;
 
-use crate::Value;
+use crate::{
+    value::json::geo_point_from_json,
+    Decimal,
+    Value,
+};
 
 /// Type hint associated with a Convex value. This allows us to uniquely convert
 /// the exported value back to the original Convex value.
@@ -16,6 +20,8 @@ pub enum ExportContext {
         // assumes a single NaN value. This ensures that we can fully roundtrip values.
         nan_value: Option<f64>,
     },
+    Decimal,
+    GeoPoint,
     Boolean,
     String,
     Bytes,
@@ -34,6 +40,8 @@ impl ExportContext {
             Value::Float64(f) => ExportContext::Float64 {
                 nan_value: f.is_nan().then_some(*f),
             },
+            Value::Decimal(_) => ExportContext::Decimal,
+            Value::GeoPoint(_) => ExportContext::GeoPoint,
             Value::Boolean(_) => ExportContext::Boolean,
             Value::String(_) => ExportContext::String,
             Value::Bytes(_) => ExportContext::Bytes,
@@ -90,6 +98,14 @@ impl TryFrom<(JsonValue, &ExportContext)> for Value {
                     .context("Unexpected number for i64"),
                 _ => anyhow::bail!("Unexpected value for f64"),
             },
+            ExportContext::Decimal => match exported_value {
+                JsonValue::String(str) => str
+                    .parse::<Decimal>()
+                    .map(Value::from)
+                    .context("Unexpected string for decimal"),
+                _ => anyhow::bail!("Unexpected value for decimal"),
+            },
+            ExportContext::GeoPoint => geo_point_from_json(exported_value).map(Value::from),
             ExportContext::Boolean => match exported_value {
                 JsonValue::Bool(value) => Ok(value.into()),
                 _ => anyhow::bail!("Unexpected value for boolean"),
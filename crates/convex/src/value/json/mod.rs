@@ -10,7 +10,10 @@ use serde_json::{
     Value as JsonValue,
 };
 
-use crate::value::Value;
+use crate::value::{
+    GeoPoint,
+    Value,
+};
 
 mod bytes;
 mod float;
@@ -21,6 +24,27 @@ fn is_negative_zero(n: f64) -> bool {
     matches!(n.total_cmp(&-0.0), Ordering::Equal)
 }
 
+/// Parse a geo point from an object with its latitude and longitude in
+/// degrees.
+pub(crate) fn geo_point_from_json(value: JsonValue) -> anyhow::Result<GeoPoint> {
+    let JsonValue::Object(mut fields) = value else {
+        anyhow::bail!("Geo point must be an object, got {value}");
+    };
+    let mut coordinate = |name: &str| {
+        fields
+            .remove(name)
+            .and_then(|v| v.as_f64())
+            .with_context(|| format!("Geo point must have a numeric {name}"))
+    };
+    let latitude = coordinate("latitude")?;
+    let longitude = coordinate("longitude")?;
+    anyhow::ensure!(
+        fields.is_empty(),
+        "Geo point must only have a latitude and longitude"
+    );
+    GeoPoint::new(latitude, longitude)
+}
+
 impl From<Value> for JsonValue {
     fn from(value: Value) -> JsonValue {
         match value {
@@ -38,6 +62,10 @@ impl From<Value> for JsonValue {
                     json!(n)
                 }
             },
+            Value::Decimal(d) => json!({ "$decimal": d.to_string() }),
+            Value::GeoPoint(p) => json!({
+                "$geoPoint": { "latitude": p.latitude(), "longitude": p.longitude() },
+            }),
             Value::Boolean(b) => json!(b),
             Value::String(s) => json!(s),
             Value::Bytes(b) => json!({ "$bytes": bytes::JsonBytes::encode(&b) }),
@@ -93,6 +121,11 @@ impl TryFrom<JsonValue> for Value {
                             }
                             Self::from(n)
                         },
+                        "$decimal" => {
+                            let d: String = serde_json::from_value(value)?;
+                            Self::Decimal(d.parse()?)
+                        },
+                        "$geoPoint" => Self::GeoPoint(geo_point_from_json(value)?),
                         "$set" => {
                             anyhow::bail!(
                                 "Received a Set which is no longer supported as a Convex type, \
@@ -1,5 +1,10 @@
 use std::collections::BTreeMap;
 
+pub use convex_sync_types::{
+    Decimal,
+    GeoPoint,
+};
+
 pub mod export;
 mod json;
 mod sorting;
@@ -13,6 +18,8 @@ pub enum Value {
     Null,
     Int64(i64),
     Float64(f64),
+    Decimal(Decimal),
+    GeoPoint(GeoPoint),
     Boolean(bool),
     String(String),
     Bytes(Vec<u8>),
@@ -38,6 +45,18 @@ impl From<f64> for Value {
     }
 }
 
+impl From<Decimal> for Value {
+    fn from(v: Decimal) -> Value {
+        Value::Decimal(v)
+    }
+}
+
+impl From<GeoPoint> for Value {
+    fn from(v: GeoPoint) -> Value {
+        Value::GeoPoint(v)
+    }
+}
+
 impl From<bool> for Value {
     fn from(v: bool) -> Value {
         Value::Boolean(v)
@@ -72,7 +91,11 @@ impl From<Vec<Value>> for Value {
 mod proptest {
     use proptest::prelude::*;
 
-    use super::Value;
+    use super::{
+        Decimal,
+        GeoPoint,
+        Value,
+    };
 
     impl Arbitrary for Value {
         type Parameters = ();
@@ -93,6 +116,8 @@ mod proptest {
             1 => Just(Value::Null),
             1 => any::<i64>().prop_map(Value::from),
             1 => (prop::num::f64::ANY | prop::num::f64::SIGNALING_NAN).prop_map(Value::from),
+            1 => any::<Decimal>().prop_map(Value::from),
+            1 => any::<GeoPoint>().prop_map(Value::from),
             1 => any::<bool>().prop_map(Value::from),
             1 => any::<String>().prop_map(Value::String),
             1 => any::<Vec<u8>>().prop_map(Value::Bytes),
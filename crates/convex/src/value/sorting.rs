@@ -6,7 +6,11 @@ use std::{
     collections::BTreeMap,
 };
 
-use crate::value::Value;
+use crate::value::{
+    Decimal,
+    GeoPoint,
+    Value,
+};
 
 #[derive(Eq, PartialEq, Ord, PartialOrd)]
 enum OrdValue<'a> {
@@ -18,6 +22,9 @@ enum OrdValue<'a> {
     Bytes(&'a Vec<u8>),
     Array(&'a Vec<Value>),
     Object(&'a BTreeMap<String, Value>),
+    // Decimals were added after the other types, so they sort after objects.
+    Decimal(&'a Decimal),
+    GeoPoint(&'a GeoPoint),
 }
 
 impl<'a> From<&'a Value> for OrdValue<'a> {
@@ -31,6 +38,8 @@ impl<'a> From<&'a Value> for OrdValue<'a> {
             Value::Bytes(x) => OrdValue::Bytes(x),
             Value::Array(x) => OrdValue::Array(x),
             Value::Object(x) => OrdValue::Object(x),
+            Value::Decimal(x) => OrdValue::Decimal(x),
+            Value::GeoPoint(x) => OrdValue::GeoPoint(x),
         }
     }
 }
@@ -0,0 +1,323 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    str::FromStr,
+};
+
+/// Most significant digits a [`Decimal`] can have.
+pub const MAX_DECIMAL_DIGITS: usize = 1000;
+
+/// Largest magnitude of a [`Decimal`]'s exponent, so the largest and smallest
+/// nonzero decimals are about `1e10000` and `1e-10000`.
+pub const MAX_DECIMAL_EXPONENT: i32 = 10000;
+
+/// Arbitrary-precision decimal number, for values like monetary amounts that
+/// can't be represented exactly as a float64.
+///
+/// Decimals are normalized, so `1.50` and `1.5` are the same decimal, and
+/// there's no negative zero. They're written in the same format as JavaScript
+/// numbers, e.g. `-12.5`, `0.000001` and `1e+21`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    negative: bool,
+    /// Significant digits from 0 to 9, with no leading or trailing zeros.
+    /// Empty for zero.
+    digits: Vec<u8>,
+    /// The decimal is `0.<digits> * 10^exponent`. Zero for zero.
+    exponent: i32,
+}
+
+impl Decimal {
+    pub const ZERO: Self = Self {
+        negative: false,
+        digits: Vec::new(),
+        exponent: 0,
+    };
+
+    /// Build a decimal from its normalized parts, see [`Self::digits`] and
+    /// [`Self::exponent`].
+    pub fn from_parts(negative: bool, digits: Vec<u8>, exponent: i32) -> anyhow::Result<Self> {
+        if digits.is_empty() {
+            anyhow::ensure!(!negative && exponent == 0, "Zero must be positive");
+            return Ok(Self::ZERO);
+        }
+        anyhow::ensure!(
+            digits.iter().all(|&d| d <= 9),
+            "Decimal digits must be from 0 to 9"
+        );
+        anyhow::ensure!(
+            digits[0] != 0 && digits[digits.len() - 1] != 0,
+            "Decimal digits must not have leading or trailing zeros"
+        );
+        anyhow::ensure!(
+            digits.len() <= MAX_DECIMAL_DIGITS,
+            "Decimals can have at most {MAX_DECIMAL_DIGITS} significant digits"
+        );
+        anyhow::ensure!(
+            (-MAX_DECIMAL_EXPONENT..=MAX_DECIMAL_EXPONENT).contains(&exponent),
+            "Decimal exponent must be at most {MAX_DECIMAL_EXPONENT}"
+        );
+        Ok(Self {
+            negative,
+            digits,
+            exponent,
+        })
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Significant digits from 0 to 9, with no leading or trailing zeros.
+    pub fn digits(&self) -> &[u8] {
+        &self.digits
+    }
+
+    /// Exponent such that the decimal is `0.<digits> * 10^exponent`.
+    pub fn exponent(&self) -> i32 {
+        self.exponent
+    }
+
+    /// The nearest float64, which may lose precision or be infinite.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string()
+            .parse()
+            .expect("Decimals are valid float literals")
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let invalid = || anyhow::anyhow!("Invalid decimal {s:?}");
+        let out_of_range = || anyhow::anyhow!("Decimal {s:?} is out of range");
+        let (negative, unsigned) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+            Some(i) => {
+                let exponent = &unsigned[i + 1..];
+                let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                let exponent: i64 = exponent.parse().map_err(|_| out_of_range())?;
+                (&unsigned[..i], exponent)
+            },
+            None => (unsigned, 0),
+        };
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !integer
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let all_digits = integer.bytes().chain(fraction.bytes()).map(|b| b - b'0');
+        let leading_zeros = all_digits.clone().take_while(|&d| d == 0).count();
+        let mut digits: Vec<u8> = all_digits.skip(leading_zeros).collect();
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            return Ok(Self::ZERO);
+        }
+        anyhow::ensure!(
+            digits.len() <= MAX_DECIMAL_DIGITS,
+            "Decimal {s:?} has more than {MAX_DECIMAL_DIGITS} significant digits"
+        );
+        let exponent = (integer.len() as i64 - leading_zeros as i64).saturating_add(exponent);
+        if exponent.abs() > MAX_DECIMAL_EXPONENT as i64 {
+            return Err(out_of_range());
+        }
+        Ok(Self {
+            negative,
+            digits,
+            exponent: exponent as i32,
+        })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let digits: String = self.digits.iter().map(|d| char::from(b'0' + d)).collect();
+        let num_digits = digits.len() as i32;
+        match self.exponent {
+            exponent @ -5..=0 => write!(f, "0.{}{digits}", "0".repeat(-exponent as usize)),
+            exponent @ 1..=21 if exponent >= num_digits => {
+                write!(
+                    f,
+                    "{digits}{}",
+                    "0".repeat((exponent - num_digits) as usize)
+                )
+            },
+            exponent @ 1..=21 => {
+                let (integer, fraction) = digits.split_at(exponent as usize);
+                write!(f, "{integer}.{fraction}")
+            },
+            exponent => {
+                let (first, rest) = digits.split_at(1);
+                let point = if rest.is_empty() { "" } else { "." };
+                let exponent = exponent - 1;
+                let sign = if exponent > 0 { "+" } else { "" };
+                write!(f, "{first}{point}{rest}e{sign}{exponent}")
+            },
+        }
+    }
+}
+
+impl fmt::Debug for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Decimal({self})")
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn sign(d: &Decimal) -> i8 {
+            if d.negative {
+                -1
+            } else if d.is_zero() {
+                0
+            } else {
+                1
+            }
+        }
+        let sign_cmp = sign(self).cmp(&sign(other));
+        if !sign_cmp.is_eq() || self.is_zero() {
+            return sign_cmp;
+        }
+        // Digits have no leading zeros, so a larger exponent means a larger
+        // magnitude, and a longer run of digits with the same prefix does too.
+        let magnitude_cmp = self
+            .exponent
+            .cmp(&other.exponent)
+            .then_with(|| self.digits.cmp(&other.digits));
+        if self.negative {
+            magnitude_cmp.reverse()
+        } else {
+            magnitude_cmp
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(n: i64) -> Self {
+        n.to_string().parse().expect("Integers are valid decimals")
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for Decimal {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        ("-?[0-9]{1,24}(\\.[0-9]{1,24})?", -40i32..40)
+            .prop_map(|(mantissa, exponent)| {
+                format!("{mantissa}e{exponent}")
+                    .parse()
+                    .expect("Generated an invalid decimal")
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::Decimal;
+
+    #[test]
+    fn test_decimal_normalization() -> anyhow::Result<()> {
+        let cases = [
+            ("0", "0"),
+            ("-0.000", "0"),
+            ("1.50", "1.5"),
+            ("+001.0", "1"),
+            ("123.456", "123.456"),
+            ("-0.001", "-0.001"),
+            (".5", "0.5"),
+            ("5.", "5"),
+            ("0.000001", "0.000001"),
+            ("0.0000001", "1e-7"),
+            ("1e20", "100000000000000000000"),
+            ("1e21", "1e+21"),
+            ("12345e30", "1.2345e+34"),
+            ("12.5E-1", "1.25"),
+            ("99999999999999999999.99", "99999999999999999999.99"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(input.parse::<Decimal>()?.to_string(), expected, "{input}");
+        }
+        for invalid in [
+            "", "-", ".", "e5", "1e", "1.2.3", "NaN", "Infinity", " 1", "1e+",
+        ] {
+            assert!(invalid.parse::<Decimal>().is_err(), "{invalid}");
+        }
+        assert!("1e10001".parse::<Decimal>().is_err());
+        assert!("1".repeat(1001).parse::<Decimal>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal_ordering() -> anyhow::Result<()> {
+        let ordered = [
+            "-1e+30", "-100", "-12.5", "-12", "-0.1234", "-0.123", "0", "0.000001", "0.1", "0.12",
+            "0.123", "1", "1.01", "10", "1e+30",
+        ];
+        let decimals = ordered
+            .iter()
+            .map(|d| d.parse())
+            .collect::<anyhow::Result<Vec<Decimal>>>()?;
+        for window in decimals.windows(2) {
+            assert!(window[0] < window[1], "{:?} < {:?}", window[0], window[1]);
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_decimal_string_roundtrips(d in any::<Decimal>()) {
+            assert_eq!(d.to_string().parse::<Decimal>().unwrap(), d);
+        }
+
+        #[test]
+        fn test_decimal_parts_roundtrip(d in any::<Decimal>()) {
+            let parts = Decimal::from_parts(d.is_negative(), d.digits().to_vec(), d.exponent());
+            assert_eq!(parts.unwrap(), d);
+        }
+
+        #[test]
+        fn test_decimal_ordering_matches_integers(l in any::<i64>(), r in any::<i64>()) {
+            assert_eq!(Decimal::from(l).cmp(&Decimal::from(r)), l.cmp(&r));
+        }
+    }
+}
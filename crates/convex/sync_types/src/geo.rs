@@ -0,0 +1,204 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{
+        Hash,
+        Hasher,
+    },
+};
+
+/// Mean radius of the Earth, used for distances between points.
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// A point on the Earth's surface, in degrees.
+///
+/// Points are ordered by their [`GeoPoint::cell`] first, so points that are
+/// close together tend to be close together in an index.
+#[derive(Clone, Copy)]
+pub struct GeoPoint {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            latitude.is_finite() && (-90.0..=90.0).contains(&latitude),
+            "Latitude must be between -90 and 90, got {latitude}"
+        );
+        anyhow::ensure!(
+            longitude.is_finite() && (-180.0..=180.0).contains(&longitude),
+            "Longitude must be between -180 and 180, got {longitude}"
+        );
+        // Adding zero turns negative zero into zero, so equal points have equal
+        // bits.
+        Ok(Self {
+            latitude: latitude + 0.0,
+            longitude: longitude + 0.0,
+        })
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+
+    /// Position of the point along a Z-order curve over the latitude and
+    /// longitude, with 32 bits of precision for each. Every cell of the
+    /// quadtree over latitude and longitude is a contiguous range of these.
+    pub fn cell(&self) -> u64 {
+        interleave(
+            quantize(self.longitude, -180.0, 360.0),
+            quantize(self.latitude, -90.0, 180.0),
+        )
+    }
+
+    /// Great-circle distance to another point.
+    pub fn distance_meters(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let dlat = lat2 - lat1;
+        let dlng = (other.longitude - self.longitude).to_radians();
+        let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlng / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * h.sqrt().min(1.0).asin()
+    }
+}
+
+/// Map a coordinate in `[min, min + extent]` onto the full range of a `u32`.
+pub fn quantize(value: f64, min: f64, extent: f64) -> u32 {
+    let scaled = (value - min) / extent * (u32::MAX as f64 + 1.0);
+    scaled.clamp(0.0, u32::MAX as f64) as u32
+}
+
+/// Interleave the bits of two coordinates, starting with `x`'s most
+/// significant bit.
+pub fn interleave(x: u32, y: u32) -> u64 {
+    (spread(x) << 1) | spread(y)
+}
+
+/// Move the bits of `v` to the even bits of a `u64`.
+fn spread(v: u32) -> u64 {
+    let mut v = v as u64;
+    v = (v | (v << 16)) & 0x0000_FFFF_0000_FFFF;
+    v = (v | (v << 8)) & 0x00FF_00FF_00FF_00FF;
+    v = (v | (v << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+    v = (v | (v << 1)) & 0x5555_5555_5555_5555;
+    v
+}
+
+impl PartialEq for GeoPoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.latitude.to_bits() == other.latitude.to_bits()
+            && self.longitude.to_bits() == other.longitude.to_bits()
+    }
+}
+
+impl Eq for GeoPoint {}
+
+impl Hash for GeoPoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.latitude.to_bits().hash(state);
+        self.longitude.to_bits().hash(state);
+    }
+}
+
+impl Ord for GeoPoint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cell()
+            .cmp(&other.cell())
+            .then_with(|| self.latitude.total_cmp(&other.latitude))
+            .then_with(|| self.longitude.total_cmp(&other.longitude))
+    }
+}
+
+impl PartialOrd for GeoPoint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({:?}, {:?})", self.latitude, self.longitude)
+    }
+}
+
+impl fmt::Debug for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GeoPoint{self}")
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for GeoPoint {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        (-90.0..=90.0f64, -180.0..=180.0f64)
+            .prop_map(|(latitude, longitude)| {
+                GeoPoint::new(latitude, longitude).expect("Generated an invalid point")
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{
+        interleave,
+        GeoPoint,
+    };
+
+    #[test]
+    fn test_geo_point_validation() {
+        assert!(GeoPoint::new(90.0, 180.0).is_ok());
+        assert!(GeoPoint::new(-90.0, -180.0).is_ok());
+        assert!(GeoPoint::new(90.5, 0.0).is_err());
+        assert!(GeoPoint::new(0.0, -180.5).is_err());
+        assert!(GeoPoint::new(f64::NAN, 0.0).is_err());
+        assert_eq!(
+            GeoPoint::new(-0.0, -0.0).unwrap(),
+            GeoPoint::new(0.0, 0.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_geo_point_cells() {
+        assert_eq!(interleave(0b10, 0b01), 0b1001);
+        assert_eq!(GeoPoint::new(-90.0, -180.0).unwrap().cell(), 0);
+        assert_eq!(GeoPoint::new(90.0, 180.0).unwrap().cell(), u64::MAX);
+        // The north-east quadrant comes after the other three.
+        let north_east = GeoPoint::new(1.0, 1.0).unwrap().cell();
+        for (latitude, longitude) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0)] {
+            assert!(GeoPoint::new(latitude, longitude).unwrap().cell() < north_east);
+        }
+    }
+
+    #[test]
+    fn test_geo_point_distance() {
+        let london = GeoPoint::new(51.5074, -0.1278).unwrap();
+        let paris = GeoPoint::new(48.8566, 2.3522).unwrap();
+        let distance = london.distance_meters(&paris);
+        assert!((343_000.0..345_000.0).contains(&distance), "{distance}");
+        assert_eq!(london.distance_meters(&london), 0.0);
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_geo_point_ordering_is_consistent(l in any::<GeoPoint>(), r in any::<GeoPoint>()) {
+            assert_eq!(l.cmp(&r).is_eq(), l == r);
+            assert_eq!(l.cmp(&r), r.cmp(&l).reverse());
+        }
+    }
+}
@@ -18,6 +18,7 @@ use crate::{
     ClientMessage,
     IdentityVersion,
     LogLinesMessage,
+    PatchOperation,
     Query,
     QueryId,
     QuerySetModification,
@@ -29,6 +30,7 @@ use crate::{
     Timestamp,
     UserIdentifier,
     UserIdentityAttributes,
+    INITIAL_SYNC_PROTOCOL_VERSION,
 };
 
 /// We implement custom deserialize and serialize to deliver u64s to
@@ -53,6 +55,11 @@ fn string_to_u64(s: &str) -> anyhow::Result<u64> {
     Ok(u64::from_le_bytes(bytes))
 }
 
+/// Clients that predate protocol versioning don't send one when connecting.
+fn default_protocol_version() -> u32 {
+    INITIAL_SYNC_PROTOCOL_VERSION
+}
+
 /// A custom deserializer for optional fields.
 /// The outer `Option` represents the field being missing and the inner
 /// `Option` represents null.
@@ -163,6 +170,9 @@ enum ClientMessageJson {
         #[serde(default)]
         #[serde(skip_serializing_if = "Option::is_none")]
         max_observed_timestamp: Option<String>,
+
+        #[serde(default = "default_protocol_version")]
+        protocol_version: u32,
     },
     #[serde(rename_all = "camelCase")]
     ModifyQuerySet {
@@ -205,11 +215,13 @@ impl TryFrom<ClientMessage> for JsonValue {
                 connection_count,
                 last_close_reason,
                 max_observed_timestamp,
+                protocol_version,
             } => ClientMessageJson::Connect {
                 session_id: format!("{}", session_id.as_hyphenated()),
                 connection_count,
                 last_close_reason: Some(last_close_reason),
                 max_observed_timestamp: max_observed_timestamp.map(|ts| u64_to_string(ts.into())),
+                protocol_version,
             },
             ClientMessage::ModifyQuerySet {
                 base_version,
@@ -285,6 +297,7 @@ impl TryFrom<JsonValue> for ClientMessage {
                 connection_count,
                 last_close_reason,
                 max_observed_timestamp,
+                protocol_version,
             } => ClientMessage::Connect {
                 session_id: session_id.parse()?,
                 connection_count,
@@ -294,6 +307,7 @@ impl TryFrom<JsonValue> for ClientMessage {
                     .transpose()?
                     .map(Timestamp::try_from)
                     .transpose()?,
+                protocol_version,
             },
             ClientMessageJson::ModifyQuerySet {
                 base_version,
@@ -423,6 +437,18 @@ impl<V: Into<JsonValue>> From<StateModification<V>> for JsonValue {
                 }
                 response
             },
+            StateModification::QueryPatched {
+                query_id,
+                patch,
+                log_lines,
+                journal,
+            } => json!({
+                "type": "QueryPatched",
+                "queryId": query_id,
+                "patch": patch,
+                "logLines": log_lines,
+                "journal": journal
+            }),
             StateModification::QueryRemoved { query_id } => json!({
                 "type": "QueryRemoved",
                 "queryId": query_id,
@@ -456,6 +482,13 @@ impl<V: TryFrom<JsonValue, Error = anyhow::Error>> TryFrom<JsonValue> for StateM
                 error_data: Option<JsonValue>,
             },
             #[serde(rename_all = "camelCase")]
+            QueryPatched {
+                query_id: QueryId,
+                patch: Vec<PatchOperation>,
+                log_lines: LogLinesMessage,
+                journal: SerializedQueryJournal,
+            },
+            #[serde(rename_all = "camelCase")]
             QueryRemoved { query_id: QueryId },
         }
         let s: StateModificationJson = serde_json::from_value(value)?;
@@ -486,6 +519,17 @@ impl<V: TryFrom<JsonValue, Error = anyhow::Error>> TryFrom<JsonValue> for StateM
                     .map(|error_data| error_data.try_into())
                     .transpose()?,
             },
+            StateModificationJson::QueryPatched {
+                query_id,
+                patch,
+                log_lines,
+                journal,
+            } => StateModification::QueryPatched {
+                query_id,
+                patch,
+                log_lines,
+                journal,
+            },
             StateModificationJson::QueryRemoved { query_id } => {
                 StateModification::QueryRemoved { query_id }
             },
@@ -588,6 +632,9 @@ impl<V: Into<JsonValue>> From<ServerMessage<V>> for JsonValue {
             ServerMessage::Ping {} => json!({
                 "type": "Ping"
             }),
+            ServerMessage::SubscriptionsExpired => json!({
+                "type": "SubscriptionsExpired"
+            }),
         }
     }
 }
@@ -633,6 +680,8 @@ impl<V: TryFrom<JsonValue, Error = anyhow::Error>> TryFrom<JsonValue> for Server
             },
             #[serde(rename_all = "camelCase")]
             Ping {},
+            #[serde(rename_all = "camelCase")]
+            SubscriptionsExpired {},
         }
         let s: ServerMessageJson = serde_json::from_value(value)?;
         let result = match s {
@@ -717,6 +766,7 @@ impl<V: TryFrom<JsonValue, Error = anyhow::Error>> TryFrom<JsonValue> for Server
                 base_version,
             },
             ServerMessageJson::Ping {} => ServerMessage::Ping {},
+            ServerMessageJson::SubscriptionsExpired {} => ServerMessage::SubscriptionsExpired,
         };
         Ok(result)
     }
@@ -927,6 +977,22 @@ mod tests {
         assert_roundtrips::<JsonValue, ClientMessage>(old_user_auth_message);
     }
 
+    #[test]
+    fn connect_without_protocol_version_backwards_compatibility() {
+        let old_connect_message = json!({
+            "type": "Connect",
+            "sessionId": "c9cbf8a5-2a05-4a3c-9d4e-9e5b9b5e5f21",
+            "connectionCount": 0,
+        });
+        let ClientMessage::Connect {
+            protocol_version, ..
+        } = ClientMessage::try_from(old_connect_message).unwrap()
+        else {
+            panic!("Expected a Connect message");
+        };
+        assert_eq!(protocol_version, crate::INITIAL_SYNC_PROTOCOL_VERSION);
+    }
+
     #[test]
     fn user_identity_attributes_deserialize_token_identifier_given() {
         let serialized = "{\"tokenIdentifier\":\"fake_identifier\"}";
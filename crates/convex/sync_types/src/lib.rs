@@ -1,9 +1,12 @@
 pub mod backoff;
+pub mod decimal;
 pub mod function_name;
+pub mod geo;
 pub mod headers;
 pub mod identifier;
 pub mod json;
 pub mod module_path;
+pub mod patch;
 pub mod path;
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
@@ -12,11 +15,14 @@ pub mod types;
 pub mod udf_path;
 
 pub use crate::{
+    decimal::Decimal,
     function_name::FunctionName,
+    geo::GeoPoint,
     module_path::{
         CanonicalizedModulePath,
         ModulePath,
     },
+    patch::PatchOperation,
     timestamp::Timestamp,
     types::{
         AuthenticationToken,
@@ -36,6 +42,10 @@ pub use crate::{
         StateVersion,
         UserIdentifier,
         UserIdentityAttributes,
+        INITIAL_SYNC_PROTOCOL_VERSION,
+        QUERY_PATCH_SYNC_PROTOCOL_VERSION,
+        SUBSCRIPTION_EXPIRY_SYNC_PROTOCOL_VERSION,
+        SYNC_PROTOCOL_VERSION,
     },
     udf_path::{
         CanonicalizedUdfPath,
@@ -0,0 +1,288 @@
+//! Patches between two JSON values, used to send a query result as a change
+//! against the result the client already has rather than in full.
+//!
+//! Patches are a subset of JSON Patch (RFC 6902) using only the `add`,
+//! `remove` and `replace` operations, with locations given as JSON Pointers
+//! (RFC 6901).
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum PatchOperation {
+    /// Insert a field into an object, or an element into an array before the
+    /// element currently at the index.
+    Add {
+        path: String,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_json()")
+        )]
+        value: JsonValue,
+    },
+    /// Remove a field from an object or an element from an array.
+    Remove { path: String },
+    /// Replace the value at the location, which must exist.
+    Replace {
+        path: String,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "crate::testing::arb_json()")
+        )]
+        value: JsonValue,
+    },
+}
+
+/// Compute a patch that turns `old` into `new`.
+///
+/// Arrays are compared by trimming their common prefix and suffix and then
+/// pairing up the remaining elements, so inserting or removing elements at
+/// one place in a list of documents produces a small patch.
+pub fn diff(old: &JsonValue, new: &JsonValue) -> Vec<PatchOperation> {
+    let mut patch = vec![];
+    diff_into(old, new, &mut String::new(), &mut patch);
+    patch
+}
+
+fn diff_into(old: &JsonValue, new: &JsonValue, path: &mut String, patch: &mut Vec<PatchOperation>) {
+    if old == new {
+        return;
+    }
+    match (old, new) {
+        (JsonValue::Object(old), JsonValue::Object(new)) => {
+            for key in old.keys() {
+                if !new.contains_key(key) {
+                    patch.push(PatchOperation::Remove {
+                        path: child_path(path, key),
+                    });
+                }
+            }
+            for (key, new_value) in new {
+                match old.get(key) {
+                    Some(old_value) => {
+                        let len = path.len();
+                        push_token(path, key);
+                        diff_into(old_value, new_value, path, patch);
+                        path.truncate(len);
+                    },
+                    None => patch.push(PatchOperation::Add {
+                        path: child_path(path, key),
+                        value: new_value.clone(),
+                    }),
+                }
+            }
+        },
+        (JsonValue::Array(old), JsonValue::Array(new)) => {
+            let prefix = old
+                .iter()
+                .zip(new.iter())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let suffix = old[prefix..]
+                .iter()
+                .rev()
+                .zip(new[prefix..].iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let old_middle = &old[prefix..old.len() - suffix];
+            let new_middle = &new[prefix..new.len() - suffix];
+            let paired = old_middle.len().min(new_middle.len());
+            for i in 0..paired {
+                let len = path.len();
+                push_token(path, &(prefix + i).to_string());
+                diff_into(&old_middle[i], &new_middle[i], path, patch);
+                path.truncate(len);
+            }
+            // Removing an element shifts the rest down, so all of the extra
+            // elements are removed from the same index.
+            for _ in paired..old_middle.len() {
+                patch.push(PatchOperation::Remove {
+                    path: child_path(path, &(prefix + paired).to_string()),
+                });
+            }
+            for (i, value) in new_middle.iter().enumerate().skip(paired) {
+                patch.push(PatchOperation::Add {
+                    path: child_path(path, &(prefix + i).to_string()),
+                    value: value.clone(),
+                });
+            }
+        },
+        _ => patch.push(PatchOperation::Replace {
+            path: path.clone(),
+            value: new.clone(),
+        }),
+    }
+}
+
+fn push_token(path: &mut String, token: &str) {
+    path.push('/');
+    path.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+fn child_path(path: &str, token: &str) -> String {
+    let mut path = path.to_string();
+    push_token(&mut path, token);
+    path
+}
+
+/// Apply a patch produced by [`diff`] to `value` in place.
+pub fn apply_patch(value: &mut JsonValue, patch: &[PatchOperation]) -> anyhow::Result<()> {
+    for operation in patch {
+        match operation {
+            PatchOperation::Add { path, value: new } => {
+                let (parent, token) = parent_mut(value, path)?;
+                match parent {
+                    JsonValue::Object(object) => {
+                        object.insert(token, new.clone());
+                    },
+                    JsonValue::Array(array) => {
+                        let index = if token == "-" {
+                            array.len()
+                        } else {
+                            parse_index(&token, array.len() + 1)?
+                        };
+                        array.insert(index, new.clone());
+                    },
+                    _ => anyhow::bail!("Can't add {path}: parent isn't an object or array"),
+                }
+            },
+            PatchOperation::Remove { path } => {
+                let (parent, token) = parent_mut(value, path)?;
+                match parent {
+                    JsonValue::Object(object) => {
+                        anyhow::ensure!(
+                            object.remove(&token).is_some(),
+                            "Can't remove {path}: no such field"
+                        );
+                    },
+                    JsonValue::Array(array) => {
+                        let index = parse_index(&token, array.len())?;
+                        array.remove(index);
+                    },
+                    _ => anyhow::bail!("Can't remove {path}: parent isn't an object or array"),
+                }
+            },
+            PatchOperation::Replace { path, value: new } => {
+                let target = value
+                    .pointer_mut(path)
+                    .ok_or_else(|| anyhow::anyhow!("Can't replace {path}: no such location"))?;
+                *target = new.clone();
+            },
+        }
+    }
+    Ok(())
+}
+
+fn parent_mut<'a>(
+    value: &'a mut JsonValue,
+    path: &str,
+) -> anyhow::Result<(&'a mut JsonValue, String)> {
+    let Some((parent_path, token)) = path.rsplit_once('/') else {
+        anyhow::bail!("Invalid patch path {path:?}");
+    };
+    let parent = value
+        .pointer_mut(parent_path)
+        .ok_or_else(|| anyhow::anyhow!("Invalid patch path {path:?}: no such location"))?;
+    Ok((parent, token.replace("~1", "/").replace("~0", "~")))
+}
+
+fn parse_index(token: &str, len: usize) -> anyhow::Result<usize> {
+    let index: usize = token
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid array index {token:?}"))?;
+    anyhow::ensure!(index < len, "Array index {index} out of bounds");
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use super::{
+        apply_patch,
+        diff,
+        PatchOperation,
+    };
+    use crate::testing::arb_json;
+
+    fn assert_diff_applies(old: JsonValue, new: JsonValue) {
+        let patch = diff(&old, &new);
+        let mut patched = old;
+        apply_patch(&mut patched, &patch).unwrap();
+        assert_eq!(patched, new);
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn proptest_diff_applies(old in arb_json(), new in arb_json()) {
+            assert_diff_applies(old, new);
+        }
+
+        #[test]
+        fn proptest_patch_operation_roundtrips(op in any::<PatchOperation>()) {
+            let json = serde_json::to_value(&op).unwrap();
+            assert_eq!(serde_json::from_value::<PatchOperation>(json).unwrap(), op);
+        }
+    }
+
+    #[test]
+    fn test_diff_equal_is_empty() {
+        let value = json!({"a": [1, 2, {"b": "c"}]});
+        assert!(diff(&value, &value).is_empty());
+    }
+
+    #[test]
+    fn test_diff_array_insert() {
+        let old = json!([{"_id": "b"}, {"_id": "c"}]);
+        let new = json!([{"_id": "a"}, {"_id": "b"}, {"_id": "c"}]);
+        assert_eq!(
+            diff(&old, &new),
+            vec![PatchOperation::Add {
+                path: "/0".to_string(),
+                value: json!({"_id": "a"}),
+            }]
+        );
+        assert_diff_applies(old, new);
+    }
+
+    #[test]
+    fn test_diff_escapes_keys() {
+        let old = json!({"a/b": {"c~d": 1}});
+        let new = json!({"a/b": {"c~d": 2}});
+        assert_eq!(
+            diff(&old, &new),
+            vec![PatchOperation::Replace {
+                path: "/a~1b/c~0d".to_string(),
+                value: json!(2),
+            }]
+        );
+        assert_diff_applies(old, new);
+    }
+
+    #[test]
+    fn test_patch_operation_json() {
+        let op: PatchOperation =
+            serde_json::from_value(json!({"op": "replace", "path": "/0/text", "value": "hi"}))
+                .unwrap();
+        assert_eq!(
+            op,
+            PatchOperation::Replace {
+                path: "/0/text".to_string(),
+                value: json!("hi"),
+            }
+        );
+    }
+}
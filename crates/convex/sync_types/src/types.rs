@@ -17,10 +17,26 @@ use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
 use crate::{
+    PatchOperation,
     Timestamp,
     UdfPath,
 };
 
+/// The version of the sync protocol spoken by clients that don't send one when
+/// they connect.
+pub const INITIAL_SYNC_PROTOCOL_VERSION: u32 = 1;
+
+/// The first version of the sync protocol where the server may send
+/// [`StateModification::QueryPatched`].
+pub const QUERY_PATCH_SYNC_PROTOCOL_VERSION: u32 = 2;
+
+/// The first version of the sync protocol where the server may send
+/// [`ServerMessage::SubscriptionsExpired`].
+pub const SUBSCRIPTION_EXPIRY_SYNC_PROTOCOL_VERSION: u32 = 3;
+
+/// The latest version of the sync protocol.
+pub const SYNC_PROTOCOL_VERSION: u32 = SUBSCRIPTION_EXPIRY_SYNC_PROTOCOL_VERSION;
+
 #[derive(
     Copy, Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Hash,
 )]
@@ -91,6 +107,8 @@ pub enum ClientMessage {
         connection_count: u32,
         last_close_reason: String,
         max_observed_timestamp: Option<Timestamp>,
+        /// The newest version of the sync protocol the client understands.
+        protocol_version: u32,
     },
     ModifyQuerySet {
         base_version: QuerySetVersion,
@@ -244,6 +262,20 @@ pub enum StateModification<V> {
         journal: SerializedQueryJournal,
         error_data: Option<V>,
     },
+    /// The query's new result, as a patch against the JSON encoding of the
+    /// result from its last `QueryUpdated` or `QueryPatched`. Only sent to
+    /// clients that connect with at least
+    /// [`QUERY_PATCH_SYNC_PROTOCOL_VERSION`].
+    QueryPatched {
+        query_id: QueryId,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "prop::collection::vec(any::<PatchOperation>(), 0..4)")
+        )]
+        patch: Vec<PatchOperation>,
+        log_lines: LogLinesMessage,
+        journal: SerializedQueryJournal,
+    },
     QueryRemoved {
         query_id: QueryId,
     },
@@ -298,6 +330,11 @@ pub enum ServerMessage<V: 'static> {
         error_message: String,
     },
     Ping,
+    /// The server stopped updating the client's queries because it hadn't
+    /// heard from the client in a while. Their results stay as they were
+    /// until the client sends its next message, at which point the server
+    /// reruns them and sends a transition with any changes.
+    SubscriptionsExpired,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
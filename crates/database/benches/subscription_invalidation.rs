@@ -0,0 +1,115 @@
+//! Benchmarks matching writes to a single hot table against 50k
+//! subscriptions to ranges of one of its indexes, with the subscriptions split
+//! across different numbers of shards.
+
+use std::{
+    collections::BTreeSet,
+    str::FromStr,
+};
+
+use common::{
+    assert_obj,
+    document::{
+        CreationTime,
+        PackedDocument,
+        ResolvedDocument,
+    },
+    interval::{
+        BinaryKey,
+        Interval,
+    },
+    testing::TestIdGenerator,
+    types::{
+        IndexDescriptor,
+        TabletIndexName,
+        Timestamp,
+    },
+};
+use criterion::{
+    criterion_group,
+    criterion_main,
+    BenchmarkId,
+    Criterion,
+};
+use database::{
+    subscription::SubscriptionManager,
+    Token,
+    TransactionReadSet,
+};
+use value::{
+    values_to_bytes,
+    ConvexValue,
+    DeveloperDocumentId,
+    FieldPath,
+    ResolvedDocumentId,
+};
+
+const TOTAL_SUBSCRIPTIONS: i64 = 50_000;
+const WRITES_PER_COMMIT: i64 = 100;
+
+fn create_subscriptions(index_name: &TabletIndexName, num_shards: usize) -> SubscriptionManager {
+    let mut subscription_manager = SubscriptionManager::new_for_testing_with_shards(num_shards);
+    for key in 0..TOTAL_SUBSCRIPTIONS {
+        let mut reads = TransactionReadSet::new();
+        reads
+            .record_indexed_directly(
+                index_name.clone(),
+                vec![FieldPath::from_str("k").unwrap()].try_into().unwrap(),
+                Interval::prefix(BinaryKey::from(values_to_bytes(&[Some(
+                    ConvexValue::from(key),
+                )]))),
+            )
+            .unwrap();
+        let token = Token::new_for_testing(reads.into_read_set(), Timestamp::MIN);
+        subscription_manager.subscribe(token).unwrap();
+    }
+    subscription_manager
+}
+
+fn bench_invalidation(c: &mut Criterion) {
+    let mut id_generator = TestIdGenerator::new();
+    let table_name = id_generator.generate_table_name();
+    let table_id = id_generator.user_table_id(&table_name);
+    let index_name = TabletIndexName::new(
+        table_id.tablet_id,
+        IndexDescriptor::from_str("by_k").unwrap(),
+    )
+    .unwrap();
+
+    let documents: Vec<PackedDocument> = (0..WRITES_PER_COMMIT)
+        .map(|i| {
+            let id = ResolvedDocumentId::new(
+                table_id.tablet_id,
+                DeveloperDocumentId::new(table_id.table_number, id_generator.generate_internal()),
+            );
+            let key = i * (TOTAL_SUBSCRIPTIONS / WRITES_PER_COMMIT);
+            let document =
+                ResolvedDocument::new(id, CreationTime::ONE, assert_obj!("k" => key)).unwrap();
+            PackedDocument::pack(document)
+        })
+        .collect();
+    let documents: Vec<&PackedDocument> = documents.iter().collect();
+
+    let mut group = c.benchmark_group("subscription_invalidation");
+    group.throughput(criterion::Throughput::Elements(documents.len() as u64));
+    group.sample_size(10);
+    for num_shards in [1, 2, 4, 8] {
+        let subscription_manager = create_subscriptions(&index_name, num_shards);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{TOTAL_SUBSCRIPTIONS}/{num_shards}_shards")),
+            &documents,
+            |b, documents| {
+                b.iter(|| {
+                    let mut to_notify = BTreeSet::new();
+                    subscription_manager
+                        .overlapping_documents_for_testing(documents, &mut to_notify);
+                    assert_eq!(to_notify.len(), documents.len());
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_invalidation);
+criterion_main!(benches);
@@ -0,0 +1,98 @@
+//! The committer's side of read-only mode. The `_backend_state` table itself
+//! is managed by `BackendStateModel` in the model crate, but the committer
+//! needs to check it so that no write to a user table slips through while
+//! the deployment is read-only, whichever API it came from. The committer
+//! loads the state once and then keeps it up to date as it commits writes to
+//! the table, so checking it doesn't cost a read per commit.
+
+use std::sync::LazyLock;
+
+use common::{
+    document::ResolvedDocument,
+    persistence::PersistenceSnapshot,
+    runtime::Runtime,
+    types::BackendState,
+};
+use errors::ErrorMetadata;
+use indexing::index_registry::IndexRegistry;
+use value::{
+    ConvexValue,
+    TableMapping,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    database::DatabaseSnapshot,
+    transaction::FinalTransaction,
+};
+
+pub static BACKEND_STATE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_backend_state"
+        .parse()
+        .expect("Invalid built-in backend_state table")
+});
+
+pub const READ_ONLY_ERROR_MESSAGE: &str = "This deployment is in read-only mode, so mutations and \
+                                           actions can't run and data can't be changed. Queries \
+                                           and subscriptions still work. An admin can turn off \
+                                           read-only mode to allow writes again.";
+
+pub fn read_only_error() -> ErrorMetadata {
+    ErrorMetadata::bad_request("DeploymentReadOnly", READ_ONLY_ERROR_MESSAGE)
+}
+
+/// Loads the backend state at `persistence_snapshot`, or `None` if the
+/// `_backend_state` table doesn't exist yet.
+pub(crate) async fn load_backend_state<RT: Runtime>(
+    persistence_snapshot: &PersistenceSnapshot,
+    table_mapping: &TableMapping,
+    index_registry: &IndexRegistry,
+) -> anyhow::Result<Option<BackendState>> {
+    let Some(tablet_id) = table_mapping
+        .namespace(TableNamespace::Global)
+        .id_if_exists(&BACKEND_STATE_TABLE)
+    else {
+        return Ok(None);
+    };
+    let by_id = index_registry.must_get_by_id(tablet_id)?.id();
+    let documents =
+        DatabaseSnapshot::<RT>::load_raw_table_documents(persistence_snapshot, by_id, tablet_id)
+            .await?;
+    let Some((_, document)) = documents.values().next() else {
+        return Ok(None);
+    };
+    Ok(Some(parse_backend_state(document)?))
+}
+
+pub(crate) fn is_backend_state_tablet(table_mapping: &TableMapping, tablet_id: TabletId) -> bool {
+    table_mapping
+        .namespace(TableNamespace::Global)
+        .id_if_exists(&BACKEND_STATE_TABLE)
+        == Some(tablet_id)
+}
+
+pub(crate) fn parse_backend_state(document: &ResolvedDocument) -> anyhow::Result<BackendState> {
+    match document.value().get("state") {
+        Some(ConvexValue::String(state)) => Ok(state.parse()?),
+        _ => anyhow::bail!("Missing state field for BackendState: {document:?}"),
+    }
+}
+
+/// Fails if the transaction writes to a user table while the deployment is
+/// read-only. The committer checks transactions in commit order, so no write
+/// to a user table commits after the commit that turns on read-only mode.
+pub(crate) fn fail_user_writes_while_read_only(
+    backend_state: Option<&BackendState>,
+    transaction: &FinalTransaction,
+) -> anyhow::Result<()> {
+    if transaction.writes.user_size().num_writes == 0 {
+        return Ok(());
+    }
+    anyhow::ensure!(
+        !backend_state.is_some_and(BackendState::is_read_only),
+        read_only_error()
+    );
+    Ok(())
+}
@@ -13,6 +13,7 @@ use common::{
             ComponentDefinitionType,
         },
         ComponentMetadata,
+        ComponentQuotas,
         ComponentType,
     },
     components::{
@@ -54,7 +55,9 @@ use crate::{
         SystemIndex,
         SystemTable,
     },
+    unauthorized_error,
     ResolvedQuery,
+    SystemMetadataModel,
     Transaction,
     COMPONENT_DEFINITIONS_TABLE,
 };
@@ -366,6 +369,53 @@ impl<'a, RT: Runtime> BootstrapComponentsModel<'a, RT> {
         }
     }
 
+    /// The quotas of the component whose tables are in `namespace`. The app
+    /// itself has no quotas.
+    pub async fn load_quotas(
+        &mut self,
+        namespace: TableNamespace,
+    ) -> anyhow::Result<ComponentQuotas> {
+        let TableNamespace::ByComponent(internal_id) = namespace else {
+            return Ok(ComponentQuotas::default());
+        };
+        let quotas = self
+            .load_component(ComponentId::Child(internal_id))
+            .await?
+            .map(|component| component.into_value().quotas)
+            .unwrap_or_default();
+        Ok(quotas)
+    }
+
+    pub async fn set_quotas(
+        &mut self,
+        path: ComponentPath,
+        quotas: ComponentQuotas,
+    ) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("set_component_quotas"));
+        }
+        anyhow::ensure!(
+            !path.is_root(),
+            ErrorMetadata::bad_request(
+                "InvalidComponentPath",
+                "Quotas can only be set on components, not on the app"
+            )
+        );
+        let Some(component) = self.resolve_path(path.clone()).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "ComponentNotFound",
+                format!("Component {:?} not found", String::from(path)),
+            ));
+        };
+        let id = component.id();
+        let mut metadata = component.into_value();
+        metadata.quotas = quotas;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, metadata.try_into()?)
+            .await?;
+        Ok(())
+    }
+
     pub async fn function_path_to_module(
         &mut self,
         path: CanonicalizedComponentFunctionPath,
@@ -390,6 +440,7 @@ mod tests {
                 ComponentInstantiation,
             },
             ComponentMetadata,
+            ComponentQuotas,
             ComponentType,
         },
         components::{
@@ -400,6 +451,10 @@ mod tests {
     };
     use keybroker::Identity;
     use runtime::testing::TestRuntime;
+    use value::{
+        TableName,
+        TableNamespace,
+    };
 
     use super::definition::COMPONENT_DEFINITIONS_TABLE;
     use crate::{
@@ -409,6 +464,7 @@ mod tests {
         },
         test_helpers::new_test_database,
         SystemMetadataModel,
+        TableModel,
     };
 
     #[convex_macro::test_runtime]
@@ -453,6 +509,7 @@ mod tests {
                 ComponentMetadata {
                     definition_id: root_definition_id.internal_id(),
                     component_type: ComponentType::App,
+                    quotas: Default::default(),
                 }
                 .try_into()?,
             )
@@ -467,6 +524,7 @@ mod tests {
                         name: "subcomponent_child".parse()?,
                         args: Default::default(),
                     },
+                    quotas: Default::default(),
                 }
                 .try_into()?,
             )
@@ -484,4 +542,90 @@ mod tests {
         );
         Ok(())
     }
+
+    #[convex_macro::test_runtime]
+    async fn test_table_quota(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = new_test_database(rt.clone()).await;
+        let mut tx = db.begin(Identity::system()).await?;
+        let definition_id = SystemMetadataModel::new_global(&mut tx)
+            .insert(
+                &COMPONENT_DEFINITIONS_TABLE,
+                ComponentDefinitionMetadata {
+                    path: "../app/child".parse()?,
+                    definition_type: ComponentDefinitionType::ChildComponent {
+                        name: "child".parse()?,
+                        args: BTreeMap::new(),
+                    },
+                    child_components: Vec::new(),
+                    exports: BTreeMap::new(),
+                }
+                .try_into()?,
+            )
+            .await?;
+        let root_id = SystemMetadataModel::new_global(&mut tx)
+            .insert(
+                &COMPONENTS_TABLE,
+                ComponentMetadata {
+                    definition_id: definition_id.internal_id(),
+                    component_type: ComponentType::App,
+                    quotas: Default::default(),
+                }
+                .try_into()?,
+            )
+            .await?;
+        let child_id = SystemMetadataModel::new_global(&mut tx)
+            .insert(
+                &COMPONENTS_TABLE,
+                ComponentMetadata {
+                    definition_id: definition_id.internal_id(),
+                    component_type: ComponentType::ChildComponent {
+                        parent: root_id.internal_id(),
+                        name: "child".parse()?,
+                        args: Default::default(),
+                    },
+                    quotas: Default::default(),
+                }
+                .try_into()?,
+            )
+            .await?;
+        let quotas = ComponentQuotas {
+            max_tables: Some(1),
+            ..Default::default()
+        };
+        BootstrapComponentsModel::new(&mut tx)
+            .set_quotas(ComponentPath::from(vec!["child".parse()?]), quotas.clone())
+            .await?;
+        assert!(BootstrapComponentsModel::new(&mut tx)
+            .set_quotas(ComponentPath::root(), quotas.clone())
+            .await
+            .is_err());
+        let namespace = TableNamespace::ByComponent(child_id.internal_id());
+        assert_eq!(
+            BootstrapComponentsModel::new(&mut tx)
+                .load_quotas(namespace)
+                .await?,
+            quotas
+        );
+
+        let first: TableName = "first".parse()?;
+        let second: TableName = "second".parse()?;
+        TableModel::new(&mut tx)
+            .insert_table_metadata(namespace, &first)
+            .await?;
+        // Recreating an existing table is fine, but not adding another one.
+        TableModel::new(&mut tx)
+            .insert_table_metadata(namespace, &first)
+            .await?;
+        assert!(TableModel::new(&mut tx)
+            .insert_table_metadata(namespace, &second)
+            .await
+            .is_err());
+        // The app isn't limited by its components' quotas.
+        for table in [&first, &second] {
+            TableModel::new(&mut tx)
+                .insert_table_metadata(TableNamespace::Global, table)
+                .await?;
+        }
+        Ok(())
+    }
 }
@@ -14,7 +14,7 @@ use common::{
         },
         index_validation_error,
         text_index::{
-            DeveloperTextIndexConfig,
+            TextIndexBackfillState,
             TextIndexState,
         },
         vector_index::{
@@ -385,10 +385,10 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
             // Collect the search indexes.
             for (index_descriptor, index_schema) in &table_schema.search_indexes {
                 let index_name = IndexName::new(table_name.clone(), index_descriptor.clone())?;
-                indexes_in_schema.push(IndexMetadata::new_backfilling_search_index(
+                indexes_in_schema.push(IndexMetadata::new_search_index(
                     index_name.clone(),
-                    index_schema.search_field.clone(),
-                    index_schema.filter_fields.clone(),
+                    index_schema.developer_config(),
+                    TextIndexState::Backfilling(TextIndexBackfillState::new()),
                 ))
             }
             for (index_descriptor, index_schema) in &table_schema.vector_indexes {
@@ -913,16 +913,11 @@ impl<'a, RT: Runtime> IndexModel<'a, RT> {
                     ..
                 } => IndexMetadata::new_backfilling(*self.tx.begin_timestamp(), index_name, fields),
                 IndexConfig::Search {
-                    developer_config:
-                        DeveloperTextIndexConfig {
-                            search_field,
-                            filter_fields,
-                        },
-                    ..
-                } => IndexMetadata::new_backfilling_search_index(
+                    developer_config, ..
+                } => IndexMetadata::new_search_index(
                     index_name,
-                    search_field,
-                    filter_fields,
+                    developer_config,
+                    TextIndexState::Backfilling(TextIndexBackfillState::new()),
                 ),
                 IndexConfig::Vector {
                     developer_config:
@@ -438,6 +438,24 @@ impl<'a, RT: Runtime> SchemaModel<'a, RT> {
         Ok(())
     }
 
+    /// Mark a submitted schema that won't be applied as overwritten, e.g.
+    /// when installing a component fails after its schema was submitted.
+    pub async fn abandon(&mut self, document_id: ResolvedDocumentId) -> anyhow::Result<()> {
+        let doc = self
+            .tx
+            .get(document_id)
+            .await?
+            .context("Schema to abandon must exist.")?;
+        let schema = SchemaMetadata::try_from(doc.into_value().into_value())?;
+        match schema.state {
+            SchemaState::Pending | SchemaState::Validated => {
+                self.mark_overwritten(document_id).await?;
+            },
+            SchemaState::Active | SchemaState::Failed { .. } | SchemaState::Overwritten => {},
+        }
+        Ok(())
+    }
+
     pub async fn overwrite_all(&mut self) -> anyhow::Result<bool> {
         let mut is_any_schema_overwritten = false;
         for state in [
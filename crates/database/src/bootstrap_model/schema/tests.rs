@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use std::{
+    collections::BTreeSet,
+    str::FromStr,
+};
 
 use common::{
     bootstrap_model::schema::{
@@ -17,6 +20,7 @@ use common::{
         },
         DatabaseSchema,
         DocumentSchema,
+        IdStrategy,
         SchemaValidationError,
     },
     value::{
@@ -24,6 +28,7 @@ use common::{
         ConvexValue,
         ResolvedDocumentId,
         TableName,
+        TableNamespace,
     },
 };
 use errors::ErrorMetadataAnyhowExt;
@@ -37,6 +42,7 @@ use crate::{
         new_test_database,
         new_tx,
     },
+    PatchValue,
     SchemaModel,
     Transaction,
     UserFacingModel,
@@ -335,6 +341,41 @@ async fn test_mark_schema_as_failed(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_abandon_schema(rt: TestRuntime) -> anyhow::Result<()> {
+    let db = new_test_database(rt.clone()).await;
+    let mut tx = db.begin(Identity::system()).await?;
+    let mut model = SchemaModel::new_root_for_test(&mut tx);
+
+    let db_schema = DatabaseSchema::default();
+    let (id, state) = model.submit_pending(db_schema.clone()).await?;
+    assert_eq!(state, SchemaState::Pending);
+    model.mark_validated(id).await?;
+    model.mark_active(id).await?;
+    // Abandoning the active schema leaves it in place.
+    model.abandon(id).await?;
+    assert_eq!(
+        model.get_by_state(SchemaState::Active).await?,
+        Some((id, db_schema))
+    );
+
+    let new_db_schema = db_schema!("table" => DocumentSchema::Any);
+    let (new_id, state) = model.submit_pending(new_db_schema).await?;
+    assert_eq!(state, SchemaState::Pending);
+    model.abandon(new_id).await?;
+    assert!(model.get_by_state(SchemaState::Pending).await?.is_none());
+    let SchemaMetadata { state, schema: _ } = tx
+        .get(new_id)
+        .await?
+        .unwrap()
+        .into_value()
+        .into_value()
+        .try_into()?;
+    assert_eq!(state, SchemaState::Overwritten);
+
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_schema_enforced_on_write(rt: TestRuntime) -> anyhow::Result<()> {
     let db = new_test_database(rt.clone()).await;
@@ -718,3 +759,224 @@ async fn failed_schema_deletes_old_failed_schema(rt: TestRuntime) -> anyhow::Res
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_encrypted_fields(rt: TestRuntime) -> anyhow::Result<()> {
+    let db = new_test_database(rt.clone()).await;
+    let mut tx = db.begin(Identity::system()).await?;
+    let table_name = TableName::from_str("people")?;
+    let mut db_schema = db_schema!("people" => DocumentSchema::Any);
+    db_schema
+        .tables
+        .get_mut(&table_name)
+        .unwrap()
+        .encrypted_fields = BTreeSet::from(["ssn".parse()?]);
+    let mut model = SchemaModel::new_root_for_test(&mut tx);
+    let (schema_id, _state) = model.submit_pending(db_schema).await?;
+    model.mark_validated(schema_id).await?;
+    model.mark_active(schema_id).await?;
+
+    let value = assert_obj!("name" => "Ada", "ssn" => "123-45-6789");
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name, value)
+        .await?;
+    db.commit(tx).await?;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    let resolved_id = id.to_resolved(
+        tx.table_mapping()
+            .namespace(TableNamespace::test_user())
+            .number_to_tablet(),
+    )?;
+    // The stored document only holds the ciphertext.
+    let stored = tx.get(resolved_id).await?.unwrap();
+    assert_eq!(
+        stored.value().get("name"),
+        Some(&ConvexValue::try_from("Ada")?)
+    );
+    assert!(matches!(
+        stored.value().get("ssn"),
+        Some(ConvexValue::Bytes(_))
+    ));
+
+    // Patching other fields keeps the field encrypted.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .patch(id, PatchValue::from(assert_obj!("name" => "Grace")))
+        .await?;
+    let stored = tx.get(resolved_id).await?.unwrap();
+    assert!(matches!(
+        stored.value().get("ssn"),
+        Some(ConvexValue::Bytes(_))
+    ));
+
+    // Functions read the plaintext.
+    let document = UserFacingModel::new_root_for_test(&mut tx)
+        .get(id, None)
+        .await?
+        .unwrap();
+    assert_eq!(
+        document.value().get("ssn"),
+        Some(&ConvexValue::try_from("123-45-6789")?)
+    );
+    assert_eq!(
+        document.value().get("name"),
+        Some(&ConvexValue::try_from("Grace")?)
+    );
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_track_updates(rt: TestRuntime) -> anyhow::Result<()> {
+    let db = new_test_database(rt.clone()).await;
+    let mut tx = db.begin(Identity::system()).await?;
+    let table_name = TableName::from_str("tracked")?;
+    let mut db_schema = db_schema!(
+        "tracked" => DocumentSchema::Any,
+        "untracked" => DocumentSchema::Any
+    );
+    db_schema.tables.get_mut(&table_name).unwrap().track_updates = true;
+    let mut model = SchemaModel::new_root_for_test(&mut tx);
+    let (schema_id, _state) = model.submit_pending(db_schema).await?;
+    model.mark_validated(schema_id).await?;
+    model.mark_active(schema_id).await?;
+
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(table_name, assert_obj!("name" => "Ada"))
+        .await?;
+    let resolved_id = id.to_resolved(
+        tx.table_mapping()
+            .namespace(TableNamespace::test_user())
+            .number_to_tablet(),
+    )?;
+    let inserted = tx.get(resolved_id).await?.unwrap();
+    let Some(ConvexValue::Float64(inserted_time)) = inserted.value().get("_updatedTime").cloned()
+    else {
+        anyhow::bail!("Missing _updatedTime on {inserted:?}");
+    };
+    assert_eq!(Some(inserted_time), inserted.creation_time().map(f64::from));
+    assert_eq!(inserted.value().get("_updatedBy"), Some(&ConvexValue::Null));
+
+    // Replacing the document advances `_updatedTime`.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("name" => "Grace"))
+        .await?;
+    let replaced = tx.get(resolved_id).await?.unwrap();
+    let Some(ConvexValue::Float64(replaced_time)) = replaced.value().get("_updatedTime").cloned()
+    else {
+        anyhow::bail!("Missing _updatedTime on {replaced:?}");
+    };
+    assert!(replaced_time > inserted_time);
+    assert_eq!(replaced.value().get("_updatedBy"), Some(&ConvexValue::Null));
+
+    // Tables that don't track updates don't keep the fields.
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert(
+            "untracked".parse()?,
+            assert_obj!("name" => "Ada", "_updatedTime" => 1.0),
+        )
+        .await?;
+    let resolved_id = id.to_resolved(
+        tx.table_mapping()
+            .namespace(TableNamespace::test_user())
+            .number_to_tablet(),
+    )?;
+    let untracked = tx.get(resolved_id).await?.unwrap();
+    assert_eq!(untracked.value().get("_updatedTime"), None);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_id_strategies(rt: TestRuntime) -> anyhow::Result<()> {
+    let db = new_test_database(rt.clone()).await;
+    let mut tx = db.begin(Identity::system()).await?;
+    let mut db_schema = db_schema!(
+        "events" => DocumentSchema::Any,
+        "orders" => DocumentSchema::Any
+    );
+    db_schema
+        .tables
+        .get_mut(&"events".parse::<TableName>()?)
+        .unwrap()
+        .id_strategy = IdStrategy::Ulid;
+    db_schema
+        .tables
+        .get_mut(&"orders".parse::<TableName>()?)
+        .unwrap()
+        .id_strategy = IdStrategy::External {
+        field: "orderNumber".parse()?,
+    };
+    let mut model = SchemaModel::new_root_for_test(&mut tx);
+    let (schema_id, _state) = model.submit_pending(db_schema).await?;
+    model.mark_validated(schema_id).await?;
+    model.mark_active(schema_id).await?;
+
+    // ULIDs sort in the order documents were inserted.
+    let mut event_ids = vec![];
+    for i in 0..5 {
+        event_ids.push(
+            UserFacingModel::new_root_for_test(&mut tx)
+                .insert("events".parse()?, assert_obj!("i" => i))
+                .await?,
+        );
+    }
+    assert!(event_ids
+        .windows(2)
+        .all(|pair| pair[0].internal_id() < pair[1].internal_id()));
+
+    // External IDs are derived from the key, which must be unique.
+    let id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert("orders".parse()?, assert_obj!("orderNumber" => "A-1"))
+        .await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert("orders".parse()?, assert_obj!("orderNumber" => "A-1"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "DuplicateExternalId");
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert("orders".parse()?, assert_obj!("total" => 10))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "MissingExternalId");
+    let other_id = UserFacingModel::new_root_for_test(&mut tx)
+        .insert("orders".parse()?, assert_obj!("orderNumber" => "A-2"))
+        .await?;
+    assert_ne!(id, other_id);
+
+    // The key can't change, but the rest of the document can.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .patch(id, PatchValue::from(assert_obj!("total" => 10)))
+        .await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .replace(id, assert_obj!("orderNumber" => "A-3"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "ExternalIdChanged");
+
+    // Keys of deleted documents can't be reused.
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(id)
+        .await?;
+    let err = UserFacingModel::new_root_for_test(&mut tx)
+        .insert("orders".parse()?, assert_obj!("orderNumber" => "A-1"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.short_msg(), "DocumentDeleted");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_active_schema_cached_until_schema_changes(rt: TestRuntime) -> anyhow::Result<()> {
+    let db = new_test_database(rt.clone()).await;
+    let mut tx = db.begin(Identity::system()).await?;
+    let namespace = TableNamespace::test_user();
+    assert!(tx.active_schema(namespace).await?.is_none());
+
+    let db_schema = db_schema!("table" => DocumentSchema::Any);
+    let mut model = SchemaModel::new(&mut tx, namespace);
+    let (id, _) = model.submit_pending(db_schema.clone()).await?;
+    model.mark_validated(id).await?;
+    model.mark_active(id).await?;
+    let active_schema = tx.active_schema(namespace).await?;
+    assert_eq!(active_schema.as_deref(), Some(&db_schema));
+    Ok(())
+}
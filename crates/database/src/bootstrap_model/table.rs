@@ -1,6 +1,7 @@
 use std::{
     collections::BTreeSet,
     sync::LazyLock,
+    time::Duration,
 };
 
 use anyhow::Context;
@@ -8,13 +9,20 @@ use async_recursion::async_recursion;
 use common::{
     bootstrap_model::{
         index::{
-            database_index::IndexedFields,
+            database_index::{
+                DatabaseIndexState,
+                IndexedFields,
+            },
             index_validation_error,
+            text_index::TextIndexState,
+            vector_index::VectorIndexState,
             DeveloperIndexMetadata,
+            IndexConfig,
             IndexMetadata,
             TabletIndexMetadata,
             INDEX_TABLE,
         },
+        schema::SchemaState,
         tables::{
             TableMetadata,
             TableState,
@@ -33,6 +41,7 @@ use common::{
     runtime::Runtime,
     types::{
         GenericIndexName,
+        IndexDescriptor,
         IndexName,
         TableName,
         TabletIndexName,
@@ -57,6 +66,8 @@ use crate::{
         SystemIndex,
         SystemTable,
     },
+    BootstrapComponentsModel,
+    IndexBackfillProgress,
     IndexModel,
     ResolvedQuery,
     SchemaModel,
@@ -101,6 +112,47 @@ impl SystemTable for TablesTable {
     }
 }
 
+/// Statistics about a table that are kept up to date as it's written to, so
+/// reading them doesn't scan the table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStatistics {
+    pub document_count: u64,
+    /// Total size of the table's documents.
+    pub total_bytes: u64,
+    /// Average writes per minute over the last few minutes, as of the start of
+    /// the transaction.
+    pub writes_per_minute: f64,
+    /// Developer-defined indexes on the table.
+    pub indexes: Vec<IndexStatistics>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexStatistics {
+    pub name: IndexDescriptor,
+    /// `database`, `search` or `vector`.
+    pub index_type: &'static str,
+    /// `backfilling`, `backfilled` or `enabled`.
+    pub state: &'static str,
+    /// Search and vector indexes are the size of their segments, as recorded
+    /// by the index workers. Database indexes are estimated to be the size of
+    /// the table's documents, as they are for usage.
+    pub size_bytes: u64,
+    /// How far along a backfilling database index is, if this backend is
+    /// backfilling it.
+    pub backfill_progress: Option<IndexBackfillProgress>,
+    pub backfill_eta: Option<Duration>,
+    /// Only set for vector indexes.
+    pub vector_counts: Option<VectorCounts>,
+}
+
+/// How many of a vector index's vectors are live, and how many are deleted
+/// but still take up space in its segments until they're compacted away.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VectorCounts {
+    pub live: u64,
+    pub deleted: u64,
+}
+
 pub struct TableModel<'a, RT: Runtime> {
     tx: &'a mut Transaction<RT>,
 }
@@ -155,6 +207,125 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
         }
     }
 
+    /// Returns statistics about the table at the start of the transaction,
+    /// taking a read dependency on the whole table.
+    pub async fn statistics(
+        &mut self,
+        namespace: TableNamespace,
+        table: &TableName,
+    ) -> anyhow::Result<TableStatistics> {
+        let Some(tablet_id) = self
+            .tx
+            .table_mapping()
+            .namespace(namespace)
+            .id_if_exists(table)
+        else {
+            return Ok(TableStatistics {
+                document_count: 0,
+                total_bytes: 0,
+                writes_per_minute: 0.0,
+                indexes: vec![],
+            });
+        };
+        let document_count = self.count_tablet(tablet_id).await?;
+        let total_bytes = self.tx.count_snapshot.total_size(tablet_id).await?;
+        let ts = *self.tx.begin_timestamp();
+        let writes_per_minute = self
+            .tx
+            .count_snapshot
+            .writes_per_minute(tablet_id, ts)
+            .await?;
+        let now = self.tx.runtime.system_time();
+        let mut indexes = vec![];
+        for index in IndexModel::new(self.tx)
+            .all_indexes_on_table(tablet_id)
+            .await?
+        {
+            if index.name.is_system_owned() {
+                continue;
+            }
+            let mut vector_counts = None;
+            let (index_type, state, size_bytes) = match &index.config {
+                IndexConfig::Database { on_disk_state, .. } => {
+                    let state = match on_disk_state {
+                        DatabaseIndexState::Backfilling(_) => "backfilling",
+                        DatabaseIndexState::Backfilled => "backfilled",
+                        DatabaseIndexState::Enabled => "enabled",
+                    };
+                    ("database", state, total_bytes)
+                },
+                IndexConfig::Search { on_disk_state, .. } => {
+                    let state = match on_disk_state {
+                        TextIndexState::Backfilling(_) => "backfilling",
+                        TextIndexState::Backfilled(_) => "backfilled",
+                        TextIndexState::SnapshottedAt(_) => "enabled",
+                    };
+                    // Indexes in an unknown format are counted as empty.
+                    let size_bytes = on_disk_state.segments().map_or(0, |segments| {
+                        segments
+                            .iter()
+                            .map(|segment| segment.size_bytes_total)
+                            .sum::<u64>()
+                    });
+                    ("search", state, size_bytes)
+                },
+                IndexConfig::Vector {
+                    on_disk_state,
+                    developer_config,
+                } => {
+                    let state = match on_disk_state {
+                        VectorIndexState::Backfilling(_) => "backfilling",
+                        VectorIndexState::Backfilled(_) => "backfilled",
+                        VectorIndexState::SnapshottedAt(_) => "enabled",
+                    };
+                    let size_bytes = match on_disk_state.segments() {
+                        Ok(segments) => {
+                            let mut counts = VectorCounts::default();
+                            for segment in segments {
+                                let live = segment.non_deleted_vectors()?;
+                                counts.live += live;
+                                counts.deleted += segment.num_vectors as u64 - live;
+                            }
+                            vector_counts = Some(counts);
+                            segments
+                                .iter()
+                                .map(|segment| {
+                                    segment.total_size_bytes(developer_config.dimensions)
+                                })
+                                .sum::<anyhow::Result<u64>>()?
+                        },
+                        Err(_) => 0,
+                    };
+                    ("vector", state, size_bytes)
+                },
+            };
+            let backfill_progress = match &index.config {
+                IndexConfig::Database { .. } => {
+                    self.tx.backfill_progress.get(index.id().internal_id())
+                },
+                _ => None,
+            };
+            let backfill_eta = backfill_progress
+                .as_ref()
+                .and_then(|progress| progress.eta(now));
+            indexes.push(IndexStatistics {
+                name: index.name.descriptor().clone(),
+                index_type,
+                state,
+                size_bytes,
+                backfill_progress,
+                backfill_eta,
+                vector_counts,
+            });
+        }
+        Ok(TableStatistics {
+            document_count,
+            total_bytes,
+            writes_per_minute,
+            indexes,
+        })
+    }
+
     pub(crate) fn doc_table_id_to_name(
         &mut self,
         doc: ParsedDocument<TabletIndexMetadata>,
@@ -173,6 +344,30 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
         self.tx.table_mapping().iter_active_user_tables().count()
     }
 
+    /// Check that the component owning `namespace` can have another table.
+    async fn check_table_quota(&mut self, namespace: TableNamespace) -> anyhow::Result<()> {
+        let quotas = BootstrapComponentsModel::new(self.tx)
+            .load_quotas(namespace)
+            .await?;
+        let Some(max_tables) = quotas.max_tables else {
+            return Ok(());
+        };
+        let num_tables = self
+            .tx
+            .table_mapping()
+            .namespace(namespace)
+            .iter_active_user_tables()
+            .count();
+        anyhow::ensure!(
+            (num_tables as u64) < max_tables,
+            ErrorMetadata::bad_request(
+                "ComponentQuotaExceeded",
+                format!("Component is over its quota of {max_tables} tables"),
+            )
+        );
+        Ok(())
+    }
+
     pub async fn delete_table(
         &mut self,
         namespace: TableNamespace,
@@ -200,6 +395,198 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
         self.delete_table_by_id(tablet_id).await
     }
 
+    /// Renames a user table in place. Its documents keep their IDs and the
+    /// table keeps its indexes, since both refer to the table by tablet and
+    /// number rather than by name.
+    pub async fn rename_table(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+        new_name: TableName,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !table_name.is_system() && !new_name.is_system(),
+            ErrorMetadata::bad_request("InvalidTableName", "System tables can't be renamed")
+        );
+        let tablet_id = self.active_user_table(namespace, table_name)?;
+        if *table_name == new_name {
+            return Ok(());
+        }
+        self.check_name_available(namespace, &new_name).await?;
+        SchemaModel::new(self.tx, namespace)
+            .enforce_table_deletion(table_name.clone())
+            .await?;
+        self.replace_table_metadata(tablet_id, new_name, TableState::Active)
+            .await
+    }
+
+    /// Hides a user table as if it were deleted, but keeps its documents and
+    /// indexes so it can be restored with `restore_table`.
+    pub async fn archive_table(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !table_name.is_system(),
+            ErrorMetadata::bad_request("InvalidTableName", "System tables can't be archived")
+        );
+        let tablet_id = self.active_user_table(namespace, table_name)?;
+        anyhow::ensure!(
+            self.archived_table(namespace, table_name).is_none(),
+            ErrorMetadata::bad_request(
+                "TableAlreadyArchived",
+                format!(
+                    "An earlier table named {table_name} is already archived. Restore it under a \
+                     different name first."
+                ),
+            )
+        );
+        SchemaModel::new(self.tx, namespace)
+            .enforce_table_deletion(table_name.clone())
+            .await?;
+        self.replace_table_metadata(tablet_id, table_name.clone(), TableState::Archived)
+            .await
+    }
+
+    /// Makes an archived table active again, under `new_name` if given.
+    /// Returns the table's name.
+    pub async fn restore_table(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+        new_name: Option<TableName>,
+    ) -> anyhow::Result<TableName> {
+        let Some(tablet_id) = self.archived_table(namespace, table_name) else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "ArchivedTableNotFound",
+                format!("There is no archived table named {table_name}"),
+            ));
+        };
+        let new_name = new_name.unwrap_or_else(|| table_name.clone());
+        anyhow::ensure!(
+            !new_name.is_system(),
+            ErrorMetadata::bad_request(
+                "InvalidTableName",
+                format!("Can't restore a table as system table {new_name}"),
+            )
+        );
+        self.check_name_available(namespace, &new_name).await?;
+        anyhow::ensure!(
+            self.count_user_tables() < MAX_USER_TABLES,
+            index_validation_error::too_many_tables(MAX_USER_TABLES)
+        );
+        self.check_table_quota(namespace).await?;
+        self.replace_table_metadata(tablet_id, new_name.clone(), TableState::Active)
+            .await?;
+        Ok(new_name)
+    }
+
+    /// The names of the archived tables in `namespace`.
+    pub fn archived_table_names(&mut self, namespace: TableNamespace) -> Vec<TableName> {
+        let archived = self.archived_tablets(namespace);
+        archived.into_iter().map(|(_, name)| name).collect()
+    }
+
+    fn archived_table(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> Option<TabletId> {
+        self.archived_tablets(namespace)
+            .into_iter()
+            .find(|(_, name)| name == table_name)
+            .map(|(tablet_id, _)| tablet_id)
+    }
+
+    fn archived_tablets(&mut self, namespace: TableNamespace) -> Vec<(TabletId, TableName)> {
+        self.tx.take_table_mapping_dep();
+        let registry = &self.tx.metadata;
+        registry
+            .table_mapping()
+            .iter()
+            .filter(|(tablet_id, table_namespace, ..)| {
+                *table_namespace == namespace
+                    && registry.table_state(*tablet_id) == Some(TableState::Archived)
+            })
+            .map(|(tablet_id, _, _, name)| (tablet_id, name.clone()))
+            .collect()
+    }
+
+    fn active_user_table(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<TabletId> {
+        self.tx
+            .table_mapping()
+            .namespace(namespace)
+            .id_if_exists(table_name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(ErrorMetadata::not_found(
+                    "TableNotFound",
+                    format!("Table {table_name} not found"),
+                ))
+            })
+    }
+
+    /// Checks that a table can be renamed or restored to `table_name`: no
+    /// other table has the name, and the active schema doesn't validate
+    /// documents in a table with that name, since the moved documents
+    /// haven't been validated against it.
+    async fn check_name_available(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.table_exists(namespace, table_name),
+            ErrorMetadata::bad_request(
+                "TableAlreadyExists",
+                format!("Table {table_name} already exists"),
+            )
+        );
+        if let Some((_, schema)) = SchemaModel::new(self.tx, namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+            && schema.schema_validation
+            && schema
+                .tables
+                .get(table_name)
+                .is_some_and(|table| table.document_type.is_some())
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TableInSchema",
+                format!(
+                    "Table {table_name} is validated by the schema. Remove it from the schema \
+                     first."
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn replace_table_metadata(
+        &mut self,
+        tablet_id: TabletId,
+        name: TableName,
+        state: TableState,
+    ) -> anyhow::Result<()> {
+        let (table_doc_id, table_metadata) = self
+            .get_table_metadata(tablet_id)
+            .await?
+            .into_id_and_value();
+        let updated_table_metadata = TableMetadata {
+            name,
+            state,
+            ..table_metadata
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .replace(table_doc_id, updated_table_metadata.try_into()?)
+            .await?;
+        Ok(())
+    }
+
     async fn delete_table_by_id(&mut self, tablet_id: TabletId) -> anyhow::Result<()> {
         for index in IndexModel::new(self.tx)
             .all_indexes_on_table(tablet_id)
@@ -478,6 +865,9 @@ impl<'a, RT: Runtime> TableModel<'a, RT> {
                 self.count_user_tables() < MAX_USER_TABLES,
                 index_validation_error::too_many_tables(MAX_USER_TABLES)
             );
+            if !self.table_exists(namespace, table) {
+                self.check_table_quota(namespace).await?;
+            }
             let table_number = if let Some(table_number) = table_number {
                 anyhow::ensure!(
                     state == TableState::Hidden
@@ -557,18 +947,25 @@ mod tests {
             DocumentSchema,
         },
     };
+    use errors::ErrorMetadataAnyhowExt;
+    use keybroker::Identity;
     use must_let::must_let;
     use runtime::testing::TestRuntime;
     use value::{
+        assert_obj,
         TableName,
         TableNamespace,
     };
 
     use crate::{
         bootstrap_model::table::NUM_RESERVED_SYSTEM_TABLE_NUMBERS,
-        test_helpers::new_tx,
+        test_helpers::{
+            new_tx,
+            DbFixtures,
+        },
         SchemaModel,
         TableModel,
+        TestFacingModel,
         Transaction,
     };
 
@@ -785,6 +1182,69 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_rename_archive_and_restore_table(rt: TestRuntime) -> anyhow::Result<()> {
+        let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+        let namespace = TableNamespace::test_user();
+        let messages: TableName = "messages".parse()?;
+        let chats: TableName = "chats".parse()?;
+        let mut tx = db.begin(Identity::system()).await?;
+        let id = TestFacingModel::new(&mut tx)
+            .insert(&messages, assert_obj!("body" => "hi"))
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        TableModel::new(&mut tx)
+            .rename_table(namespace, &messages, chats.clone())
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = TableModel::new(&mut tx);
+        assert!(!model.table_exists(namespace, &messages));
+        assert_eq!(model.count(namespace, &chats).await?, 1);
+        assert_eq!(
+            tx.table_mapping()
+                .namespace(namespace)
+                .id(&chats)?
+                .tablet_id,
+            id.tablet_id
+        );
+        assert!(tx.get(id).await?.is_some());
+        TableModel::new(&mut tx)
+            .archive_table(namespace, &chats)
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = TableModel::new(&mut tx);
+        assert!(!model.table_exists(namespace, &chats));
+        assert_eq!(model.archived_table_names(namespace), vec![chats.clone()]);
+        // The archived table's name can be reused.
+        TestFacingModel::new(&mut tx)
+            .insert(&chats, assert_obj!("body" => "new"))
+            .await?;
+        let err = TableModel::new(&mut tx)
+            .restore_table(namespace, &chats, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "TableAlreadyExists");
+        let restored = TableModel::new(&mut tx)
+            .restore_table(namespace, &chats, Some(messages.clone()))
+            .await?;
+        assert_eq!(restored, messages);
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = TableModel::new(&mut tx);
+        assert_eq!(model.count(namespace, &messages).await?, 1);
+        assert_eq!(model.count(namespace, &chats).await?, 1);
+        assert!(model.archived_table_names(namespace).is_empty());
+        assert!(tx.get(id).await?.is_some());
+        Ok(())
+    }
+
     async fn set_active_schema(
         tx: &mut Transaction<TestRuntime>,
         schema: DatabaseSchema,
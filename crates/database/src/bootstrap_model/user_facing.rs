@@ -10,9 +10,12 @@ use common::{
     },
     query::CursorPosition,
     runtime::Runtime,
+    schemas::IdStrategy,
     types::{
+        IndexDescriptor,
         StableIndexName,
         WriteTimestamp,
+        INDEX_BY_ID_DESCRIPTOR,
     },
     version::Version,
 };
@@ -24,7 +27,9 @@ use indexing::backend_in_memory_indexes::{
 use value::{
     check_user_size,
     ConvexObject,
+    ConvexValue,
     DeveloperDocumentId,
+    FieldName,
     ResolvedDocumentId,
     Size,
     TableName,
@@ -44,6 +49,8 @@ use crate::{
         IndexRangeRequest,
         MAX_PAGE_SIZE,
     },
+    transaction_id_generator::TransactionIdGenerator,
+    trash::TrashModel,
     unauthorized_error,
     virtual_tables::VirtualTable,
     PatchValue,
@@ -62,6 +69,8 @@ use crate::{
 //  4. We track user size limits for documents, which are more restrictive than
 //     the database's limits.
 //  5. We support branching on the `convex` NPM package's version.
+//  6. We decrypt the fields that the schema marks as encrypted.
+//  7. We generate IDs with the ID strategy the schema picks for the table.
 pub struct UserFacingModel<'a, RT: Runtime> {
     tx: &'a mut Transaction<RT>,
     namespace: TableNamespace,
@@ -118,6 +127,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
                     .name(document.id().table())?;
                 self.tx.reads.record_read_document(
                     table_name,
+                    &INDEX_BY_ID_DESCRIPTOR,
                     document.size(),
                     &self.tx.usage_tracker,
                     true,
@@ -141,7 +151,9 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             )?;
             let table_name = self.tx.table_mapping().tablet_name(id_.tablet_id)?;
             let result = self.tx.get_inner(id_, table_name).await?;
-            Ok(result.map(|(doc, ts)| (doc.to_developer(), ts)))
+            result
+                .map(|(doc, ts)| Ok((self.tx.decrypt_document(doc)?.to_developer(), ts)))
+                .transpose()
         }
     }
 
@@ -188,11 +200,54 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
         TableModel::new(self.tx)
             .insert_table_metadata(self.namespace, &table)
             .await?;
+        let id_strategy = self.tx.id_strategy(self.namespace, &table).await?;
         let table_id = self
             .tx
             .table_mapping()
             .namespace(self.namespace)
-            .name_to_id_user_input()(table)?;
+            .name_to_id_user_input()(table.clone())?;
+        let internal_id = match id_strategy {
+            IdStrategy::Random => internal_id,
+            IdStrategy::Ulid => self.tx.id_generator.generate_ulid_internal()?,
+            IdStrategy::External { field } => {
+                let field = FieldName::from(field);
+                let Some(ConvexValue::String(key)) = value.get(&field) else {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "MissingExternalId",
+                        format!(
+                            "Documents in {table} need a string {field} field, which their ID is \
+                             derived from"
+                        ),
+                    ));
+                };
+                let key: &str = key;
+                let internal_id =
+                    TransactionIdGenerator::external_internal_id(table_id.table_number, key);
+                let id = ResolvedDocumentId::new(
+                    table_id.tablet_id,
+                    DeveloperDocumentId::new(table_id.table_number, internal_id),
+                );
+                if self.tx.get(id).await?.is_some() {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "DuplicateExternalId",
+                        format!("A document in {table} already has {field} {key:?}"),
+                    ));
+                }
+                // IDs can't be reused, so neither can the keys of deleted
+                // documents. The committer checks for documents deleted
+                // before this transaction began.
+                if self.tx.writes().contains(&id) {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "DocumentDeleted",
+                        format!(
+                            "Cannot reuse {field} {key:?} in {table}, since the document with \
+                             that key was deleted"
+                        ),
+                    ));
+                }
+                internal_id
+            },
+        };
         let document = ResolvedDocument::new(
             ResolvedDocumentId::new(
                 table_id.tablet_id,
@@ -290,14 +345,15 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
                 .namespace(self.namespace)
                 .number_to_tablet(),
         )?;
-        let document = self.tx.delete_inner(id_).await?;
-        Ok(document.to_developer())
+        let document = TrashModel::new(self.tx, self.namespace).delete(id_).await?;
+        Ok(self.tx.decrypt_document(document)?.to_developer())
     }
 
     pub fn record_read_document(
         &mut self,
         document: &DeveloperDocument,
         table_name: &TableName,
+        index: &IndexDescriptor,
     ) -> anyhow::Result<()> {
         let is_virtual_table = self
             .tx
@@ -305,6 +361,7 @@ impl<'a, RT: Runtime> UserFacingModel<'a, RT> {
             .is_virtual_table(table_name);
         self.tx.reads.record_read_document(
             table_name.clone(),
+            index,
             document.size(),
             &self.tx.usage_tracker,
             is_virtual_table,
@@ -400,8 +457,10 @@ pub async fn index_range_batch<RT: Runtime>(
                     .try_collect()?,
                 None => page
                     .into_iter()
-                    .map(|(key, doc, ts)| (key, doc.to_developer(), ts))
-                    .collect(),
+                    .map(|(key, doc, ts)| {
+                        anyhow::Ok((key, tx.decrypt_document(doc)?.to_developer(), ts))
+                    })
+                    .try_collect()?,
             };
             anyhow::Ok(DeveloperIndexRangeResponse {
                 page: developer_results,
@@ -55,6 +55,7 @@ use common::{
         },
     },
     types::{
+        BackendState,
         DatabaseIndexUpdate,
         DatabaseIndexValue,
         RepeatableTimestamp,
@@ -96,6 +97,11 @@ use value::{
 use vector::DocInVectorIndex;
 
 use crate::{
+    backend_state::{
+        fail_user_writes_while_read_only,
+        is_backend_state_tablet,
+        parse_backend_state,
+    },
     bootstrap_model::defaults::BootstrapTableIds,
     database::{
         ConflictingReadWithWriteSource,
@@ -159,6 +165,9 @@ pub struct Committer<RT: Runtime> {
     persistence_writes: FuturesOrdered<BoxFuture<'static, anyhow::Result<PersistenceWrite>>>,
 
     retention_validator: Arc<dyn RetentionValidator>,
+
+    // The state in `_backend_state` as of the last validated commit.
+    backend_state: Option<BackendState>,
 }
 
 impl<RT: Runtime> Committer<RT> {
@@ -168,6 +177,7 @@ impl<RT: Runtime> Committer<RT> {
         persistence: Arc<dyn Persistence>,
         runtime: RT,
         retention_validator: Arc<dyn RetentionValidator>,
+        backend_state: Option<BackendState>,
         shutdown: ShutdownSignal,
     ) -> CommitterClient<RT> {
         let persistence_reader = persistence.reader();
@@ -184,6 +194,7 @@ impl<RT: Runtime> Committer<RT> {
             persistence_writes: FuturesOrdered::new(),
             shutdown,
             retention_validator: retention_validator.clone(),
+            backend_state,
         };
         let handle = runtime.spawn("committer", committer.go(rx));
         CommitterClient {
@@ -498,6 +509,7 @@ impl<RT: Runtime> Committer<RT> {
         transaction: FinalTransaction,
         write_source: WriteSource,
     ) -> anyhow::Result<ValidatedCommit> {
+        fail_user_writes_while_read_only(self.backend_state.as_ref(), &transaction)?;
         let commit_ts = self.next_commit_ts()?;
         let timer = metrics::commit_is_stale_timer();
         if let Some(conflicting_read) = self.commit_has_conflict(
@@ -523,6 +535,15 @@ impl<RT: Runtime> Committer<RT> {
         });
 
         let (document_writes, index_writes) = self.compute_writes(commit_ts, &ordered_updates)?;
+        for (id, update) in &ordered_updates {
+            if is_backend_state_tablet(&transaction.table_mapping, id.tablet_id) {
+                self.backend_state = update
+                    .new_document
+                    .as_ref()
+                    .map(parse_backend_state)
+                    .transpose()?;
+            }
+        }
 
         // Append the updates to pending_writes, so future conflicting commits
         // will fail the `commit_has_conflict` check above, even before
@@ -1027,7 +1048,8 @@ enum CommitterMessage<RT: Runtime> {
 // - table created before its indexes.
 // - indexes created before documents in the table.
 // - indexes deleted before other indexes created, in case of naming conflicts.
-// - tables deleted before other tables created, in case of naming conflicts.
+// - tables deleted or archived before other tables created, in case of naming
+//   conflicts.
 // - indexes on a table deleted before the table itself.
 pub fn table_dependency_sort_key(
     bootstrap_tables: BootstrapTableIds,
@@ -1055,7 +1077,7 @@ pub fn table_dependency_sort_key(
                     TableState::Hidden => 3,
                     // Deleting index must come before table deletion,
                     // so we can delete the table.by_id index while the table still exists.
-                    TableState::Deleting => 1,
+                    TableState::Deleting | TableState::Archived => 1,
                 }
             },
             // Legacy method of deleting _tables, supported here when walking the log.
@@ -53,7 +53,11 @@ use common::{
         ResolvedDocument,
     },
     interval::Interval,
-    knobs::DEFAULT_DOCUMENTS_PAGE_SIZE,
+    knobs::{
+        DEFAULT_DOCUMENTS_PAGE_SIZE,
+        RETENTION_READ_CHUNK,
+        TABLE_STORAGE_SNAPSHOT_ROWS_PER_SECOND,
+    },
     pause::PauseClient,
     persistence::{
         new_idle_repeatable_ts,
@@ -70,6 +74,7 @@ use common::{
     },
     query::Order,
     runtime::{
+        new_rate_limiter,
         RateLimiter,
         Runtime,
         RuntimeInstant,
@@ -78,6 +83,7 @@ use common::{
         new_split_rw_lock,
         Reader,
     },
+    try_chunks::TryChunksExt,
     types::{
         GenericIndexName,
         IndexId,
@@ -110,6 +116,7 @@ use futures::{
     StreamExt,
     TryStreamExt,
 };
+use governor::Quota;
 use imbl::OrdMap;
 use indexing::{
     backend_in_memory_indexes::{
@@ -148,6 +155,7 @@ use vector::{
 };
 
 use crate::{
+    backend_state::load_backend_state,
     bootstrap_model::{
         table::{
             NUM_RESERVED_LEGACY_TABLE_NUMBERS,
@@ -167,6 +175,11 @@ use crate::{
         SystemIndex,
         DEFAULT_BOOTSTRAP_TABLE_NUMBERS,
     },
+    field_encryption::FieldEncryptor,
+    index_backfill::{
+        BackfillProgressTracker,
+        CommitLatency,
+    },
     metrics::{
         self,
         load_indexes_into_memory_timer,
@@ -174,6 +187,11 @@ use crate::{
         vector::vector_search_with_retries_timer,
         verify_invariants_timer,
     },
+    replication::{
+        ReplicatedRevision,
+        ReplicationLogPage,
+        REPLICATED_PERSISTENCE_GLOBALS,
+    },
     retention::LeaderRetentionManager,
     search_and_vector_bootstrap::SearchAndVectorIndexBootstrapWorker,
     snapshot_manager::{
@@ -259,6 +277,10 @@ pub struct Database<RT: Runtime> {
     retention_manager: LeaderRetentionManager<RT>,
     pub searcher: Arc<dyn Searcher>,
     pub search_storage: Arc<OnceLock<Arc<dyn Storage>>>,
+    pub field_encryptor: Arc<OnceLock<Arc<dyn FieldEncryptor>>>,
+    /// Paces index backfills so they don't slow down foreground commits.
+    pub(crate) commit_latency: CommitLatency<RT>,
+    pub(crate) backfill_progress: BackfillProgressTracker,
     usage_counter: UsageCounter,
     virtual_system_mapping: VirtualSystemMapping,
     pub bootstrap_metadata: BootstrapMetadata,
@@ -319,6 +341,20 @@ pub struct DocumentDeltas {
     pub has_more: bool,
 }
 
+/// A page of the changes to a single table, for functions that process a
+/// table incrementally.
+#[derive(PartialEq, Eq, Debug)]
+pub struct TableChanges {
+    /// Changes in increasing (ts, id) order. The document is None if it was
+    /// deleted.
+    pub changes: Vec<(Timestamp, DeveloperDocumentId, Option<ResolvedDocument>)>,
+    /// Exclusive cursor timestamp to pass in to the next call to
+    /// table_changes.
+    pub cursor: Timestamp,
+    /// Continue calling table_changes while has_more is true.
+    pub has_more: bool,
+}
+
 #[derive(PartialEq, Eq, Debug)]
 pub struct SnapshotPage {
     pub documents: Vec<(Timestamp, TableName, ResolvedDocument)>,
@@ -394,7 +430,7 @@ impl<RT: Runtime> DatabaseSnapshot<RT> {
                     table_number,
                     table_metadata.name,
                 ),
-                TableState::Hidden => table_mapping.insert_tablet(
+                TableState::Hidden | TableState::Archived => table_mapping.insert_tablet(
                     tablet_id,
                     table_metadata.namespace,
                     table_number,
@@ -803,6 +839,12 @@ impl<RT: Runtime> Database<RT> {
             "race while loading DatabaseSnapshot: max ts {original_max_ts} at start, {max_ts} at \
              end",
         );
+        let backend_state = load_backend_state::<RT>(
+            &db_snapshot.persistence_snapshot,
+            db_snapshot.snapshot.table_registry.table_mapping(),
+            &db_snapshot.snapshot.index_registry,
+        )
+        .await?;
         let DatabaseSnapshot {
             bootstrap_metadata,
             persistence_snapshot: _,
@@ -847,6 +889,7 @@ impl<RT: Runtime> Database<RT> {
             persistence,
             runtime.clone(),
             Arc::new(retention_manager.clone()),
+            backend_state,
             shutdown,
         );
         let table_mapping_snapshot_cache =
@@ -857,6 +900,8 @@ impl<RT: Runtime> Database<RT> {
         let database = Self {
             committer,
             subscriptions,
+            commit_latency: CommitLatency::new(runtime.clone()),
+            backfill_progress: BackfillProgressTracker::default(),
             runtime,
             log: log_reader,
             retention_manager,
@@ -865,6 +910,7 @@ impl<RT: Runtime> Database<RT> {
             write_commits_since_load: Arc::new(AtomicUsize::new(0)),
             searcher,
             search_storage: Arc::new(OnceLock::new()),
+            field_encryptor: Arc::new(OnceLock::new()),
             usage_counter,
             virtual_system_mapping,
             bootstrap_metadata,
@@ -883,6 +929,14 @@ impl<RT: Runtime> Database<RT> {
         tracing::info!("Set search storage to {search_storage:?}");
     }
 
+    /// Sets the encryptor for the fields schemas mark as encrypted. Until
+    /// it's set, those fields are stored in plaintext.
+    pub fn set_field_encryptor(&self, field_encryptor: Arc<dyn FieldEncryptor>) {
+        if self.field_encryptor.set(field_encryptor).is_err() {
+            panic!("Tried to set field encryptor more than once");
+        }
+    }
+
     pub fn start_search_and_vector_bootstrap(&self, pause_client: PauseClient) -> RT::Handle {
         let worker = self.new_search_and_vector_bootstrap_worker(pause_client);
         self.runtime
@@ -959,6 +1013,49 @@ impl<RT: Runtime> Database<RT> {
             .boxed()
     }
 
+    /// The total size of the revisions in a table that have been overwritten
+    /// or deleted as of `snapshot_ts` but are still within retention, since
+    /// snapshot reads at earlier timestamps can see them.
+    pub async fn historical_table_size(
+        &self,
+        tablet_id: TabletId,
+        snapshot_ts: RepeatableTimestamp,
+    ) -> anyhow::Result<u64> {
+        let retention_validator = self.retention_validator();
+        let min_snapshot_ts = retention_validator.min_document_snapshot_ts().await?;
+        if min_snapshot_ts >= *snapshot_ts {
+            return Ok(0);
+        }
+        let reader =
+            RepeatablePersistence::new(self.reader.clone(), snapshot_ts, retention_validator);
+        // A revision written after the min snapshot replaced one that's still
+        // visible there, so retention hasn't deleted it yet.
+        let timestamp_range = TimestampRange::new((
+            Bound::Excluded(min_snapshot_ts),
+            Bound::Included(*snapshot_ts),
+        ))?;
+        let rate_limiter = new_rate_limiter(
+            self.runtime.clone(),
+            Quota::per_second(*TABLE_STORAGE_SNAPSHOT_ROWS_PER_SECOND),
+        );
+        let chunks = self
+            .load_documents_in_table(tablet_id, timestamp_range, Order::Asc, &rate_limiter)
+            .try_chunks2(*RETENTION_READ_CHUNK);
+        pin_mut!(chunks);
+        let mut historical_size = 0;
+        while let Some(chunk) = chunks.try_next().await? {
+            let ids = chunk.into_iter().map(|(ts, id, _)| (id, ts)).collect();
+            historical_size += reader
+                .previous_revisions(ids)
+                .await?
+                .into_values()
+                .filter_map(|(_, prev_rev)| prev_rev)
+                .map(|document| document.value().size() as u64)
+                .sum::<u64>();
+        }
+        Ok(historical_size)
+    }
+
     /// Allows iterating over tables at any repeatable timestamp,
     /// even if it's outside of retention.
     /// TableIterator will have to walk all documents between snapshot_ts
@@ -1466,7 +1563,7 @@ impl<RT: Runtime> Database<RT> {
             )),
         );
         let count_snapshot = Arc::new(snapshot.table_summaries);
-        let tx = Transaction::new(
+        let mut tx = Transaction::new(
             identity,
             id_generator,
             creation_time,
@@ -1478,6 +1575,10 @@ impl<RT: Runtime> Database<RT> {
             Arc::new(self.retention_manager.clone()),
             self.virtual_system_mapping.clone(),
         );
+        if let Some(field_encryptor) = self.field_encryptor.get() {
+            tx.set_field_encryptor(field_encryptor.clone());
+        }
+        tx.backfill_progress = self.backfill_progress.clone();
         Ok(tx)
     }
 
@@ -1490,6 +1591,32 @@ impl<RT: Runtime> Database<RT> {
         Ok(snapshot)
     }
 
+    /// Reads from persistence directly, bypassing the snapshot manager, to
+    /// check that it's reachable.
+    pub async fn check_persistence(&self) -> anyhow::Result<()> {
+        DatabaseSnapshot::<RT>::max_ts(&*self.reader).await?;
+        Ok(())
+    }
+
+    /// Checks that text and vector search indexes have finished loading and
+    /// that search storage has been set, i.e. that searches can be served.
+    pub fn check_search_ready(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.search_storage.get().is_some(),
+            "Search storage hasn't been set"
+        );
+        let snapshot = self.latest_snapshot()?;
+        anyhow::ensure!(
+            !snapshot.search_indexes.is_bootstrapping(),
+            "Text search indexes are still loading"
+        );
+        anyhow::ensure!(
+            !snapshot.vector_indexes.is_bootstrapping(),
+            "Vector search indexes are still loading"
+        );
+        Ok(())
+    }
+
     #[cfg(any(test, feature = "testing"))]
     pub async fn commit(&self, transaction: Transaction<RT>) -> anyhow::Result<Timestamp> {
         self.commit_with_write_source(transaction, WriteSource::unknown())
@@ -1503,12 +1630,14 @@ impl<RT: Runtime> Database<RT> {
         write_source: impl Into<WriteSource>,
     ) -> anyhow::Result<Timestamp> {
         let readonly = transaction.is_readonly();
+        let start = self.runtime.monotonic_now();
         let result = self
             .committer
             .commit(transaction, write_source.into())
             .await?;
         if !readonly {
             self.write_commits_since_load.fetch_add(1, Ordering::SeqCst);
+            self.commit_latency.record(start.elapsed());
         }
         Ok(result)
     }
@@ -1628,6 +1757,176 @@ impl<RT: Runtime> Database<RT> {
         })
     }
 
+    /// A page of the changes to `table` in `namespace` after `cursor`, or
+    /// from the start of retention if there's no cursor. Like
+    /// `document_deltas`, a page never splits a timestamp, so `cursor` can be
+    /// passed back in to resume without missing or repeating changes.
+    #[minitrace::trace]
+    pub async fn table_changes(
+        &self,
+        identity: Identity,
+        namespace: TableNamespace,
+        table: TableName,
+        cursor: Option<Timestamp>,
+        rows_read_limit: usize,
+        rows_returned_limit: usize,
+    ) -> anyhow::Result<TableChanges> {
+        anyhow::ensure!(rows_read_limit >= rows_returned_limit);
+        if table.is_system() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidTableForChanges",
+                format!("Can't read changes to system table {table}")
+            ));
+        }
+        let (upper_bound, table_mapping) = {
+            let mut tx = self.begin(identity).await?;
+            (tx.begin_timestamp(), tx.table_mapping().clone())
+        };
+        let Some(table_id) = table_mapping
+            .namespace(namespace)
+            .id_and_number_if_exists(&table)
+        else {
+            // A table that doesn't exist yet has no changes.
+            return Ok(TableChanges {
+                changes: vec![],
+                cursor: cursor.unwrap_or(*upper_bound),
+                has_more: false,
+            });
+        };
+        let retention_validator = self.retention_validator();
+        let range = match cursor {
+            Some(ts) => TimestampRange::new((Bound::Excluded(ts), Bound::Unbounded))?,
+            None => {
+                let min_ts = retention_validator.min_document_snapshot_ts().await?;
+                TimestampRange::new((Bound::Included(min_ts), Bound::Unbounded))?
+            },
+        };
+        let repeatable_persistence =
+            RepeatablePersistence::new(self.reader.clone(), upper_bound, retention_validator);
+        let mut document_stream = repeatable_persistence.load_documents(range, Order::Asc);
+        let mut changes = vec![];
+        // new_cursor is set once, when we know the final timestamp.
+        let mut new_cursor = None;
+        let mut has_more = false;
+        let mut rows_read = 0;
+        while let Some((ts, id, maybe_doc)) = match document_stream.try_next().await {
+            Ok::<_, Error>(doc) => doc,
+            Err(e) if e.is_out_of_retention() => {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "ChangesCursorOutOfRetention",
+                    format!(
+                        "Changes to {table} after timestamp {} are no longer retained",
+                        range.min_timestamp_inclusive()
+                    )
+                ))
+            },
+            Err(e) => anyhow::bail!(e),
+        } {
+            rows_read += 1;
+            if let Some(new_cursor) = new_cursor
+                && new_cursor < ts
+            {
+                has_more = true;
+                break;
+            }
+            if new_cursor.is_none() && rows_read >= rows_read_limit {
+                // Finish after the rest of the documents at this timestamp.
+                new_cursor = Some(ts);
+            }
+            if id.table() != table_id.tablet_id {
+                continue;
+            }
+            let id = DeveloperDocumentId::new(table_id.table_number, id.internal_id());
+            changes.push((ts, id, maybe_doc));
+            if new_cursor.is_none() && changes.len() >= rows_returned_limit {
+                new_cursor = Some(ts);
+            }
+        }
+        Ok(TableChanges {
+            changes,
+            cursor: new_cursor.unwrap_or(*upper_bound),
+            has_more,
+        })
+    }
+
+    /// A page of the documents log after `cursor`, for a standby to
+    /// replicate. Unlike `document_deltas`, it includes every table and the
+    /// retention bounds the standby needs. The page is at least
+    /// `rows_limit` revisions unless it reaches the end of the log, and
+    /// never splits a transaction.
+    #[minitrace::trace]
+    pub async fn replication_log(
+        &self,
+        identity: Identity,
+        cursor: Option<Timestamp>,
+        rows_limit: usize,
+    ) -> anyhow::Result<ReplicationLogPage> {
+        anyhow::ensure!(
+            identity.is_system() || identity.is_admin(),
+            unauthorized_error("replication_log")
+        );
+        let upper_bound = self.now_ts_for_reads();
+        if let Some(cursor) = cursor {
+            metrics::log_replication_lag(upper_bound.secs_since_f64(cursor));
+        }
+        let retention_validator = self.retention_validator();
+        let min_snapshot_ts = retention_validator.min_snapshot_ts().await?;
+        let min_document_snapshot_ts = retention_validator.min_document_snapshot_ts().await?;
+        let repeatable_persistence =
+            RepeatablePersistence::new(self.reader.clone(), upper_bound, retention_validator);
+        let range = match cursor {
+            Some(ts) => TimestampRange::new((Bound::Excluded(ts), Bound::Unbounded))?,
+            None => TimestampRange::all(),
+        };
+        let mut document_stream = repeatable_persistence.load_documents(range, Order::Asc);
+        let mut revisions: Vec<ReplicatedRevision> = vec![];
+        let mut has_more = false;
+        while let Some((ts, id, document)) = match document_stream.try_next().await {
+            Ok::<_, Error>(doc) => doc,
+            Err(e) if e.is_out_of_retention() => {
+                // The standby can't catch up from here since revisions it's missing may
+                // already be gone.
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "ReplicationCursorOutOfRetention",
+                    format!(
+                        "Timestamp {} is too old to replicate from. The standby must be restarted \
+                         with an empty database.",
+                        range.min_timestamp_inclusive()
+                    )
+                ))
+            },
+            Err(e) => anyhow::bail!(e),
+        } {
+            if revisions.len() >= rows_limit
+                && let Some(last) = revisions.last()
+                && last.ts < ts
+            {
+                has_more = true;
+                break;
+            }
+            revisions.push(ReplicatedRevision { ts, id, document });
+        }
+        let mut persistence_globals = vec![];
+        for key in REPLICATED_PERSISTENCE_GLOBALS {
+            if let Some(value) = self.reader.get_persistence_global(key).await? {
+                persistence_globals.push((key, value));
+            }
+        }
+        let cursor = match (has_more, revisions.last()) {
+            (true, Some(last)) => last.ts,
+            _ => *upper_bound,
+        };
+        Ok(ReplicationLogPage {
+            revisions,
+            cursor,
+            has_more,
+            primary_ts: *upper_bound,
+            min_snapshot_ts,
+            min_document_snapshot_ts,
+            persistence_globals,
+        })
+    }
+
     #[minitrace::trace]
     pub async fn list_snapshot(
         &self,
@@ -0,0 +1,165 @@
+//! Encryption of the fields a schema marks as `encryptedFields`.
+//!
+//! Each encrypted field is stored as a `Bytes` value holding
+//! [`CIPHERTEXT_PREFIX`] followed by the sealed JSON encoding of the original
+//! value. Since ciphertexts are self-describing, reads don't need to consult
+//! the schema: any top-level field holding a ciphertext is decrypted.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    sync::Arc,
+};
+
+use common::document::ResolvedDocument;
+use keybroker::Encryptor;
+use serde_json::Value as JsonValue;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+    IdentifierFieldName,
+};
+
+/// Marks a `Bytes` value as a field ciphertext rather than user bytes.
+const CIPHERTEXT_PREFIX: &[u8] = b"\x00convex-encrypted-field\x00";
+
+const FIELD_ENCRYPTION_VERSION: u8 = 1;
+
+/// Hook for the key management of encrypted fields. The default
+/// implementation uses a key derived from the instance secret, but
+/// deployments can plug in their own (e.g. one backed by a KMS) with
+/// `Database::set_field_encryptor`.
+pub trait FieldEncryptor: Send + Sync {
+    fn encrypt(&self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>>;
+    fn decrypt(&self, ciphertext: &[u8]) -> anyhow::Result<Vec<u8>>;
+}
+
+impl FieldEncryptor for Encryptor {
+    fn encrypt(&self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        Ok(self.seal(FIELD_ENCRYPTION_VERSION, plaintext))
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        self.open(FIELD_ENCRYPTION_VERSION, ciphertext)
+    }
+}
+
+fn decrypt_value(
+    encryptor: &Arc<dyn FieldEncryptor>,
+    value: &ConvexValue,
+) -> anyhow::Result<Option<ConvexValue>> {
+    let ConvexValue::Bytes(bytes) = value else {
+        return Ok(None);
+    };
+    let Some(ciphertext) = bytes.strip_prefix(CIPHERTEXT_PREFIX) else {
+        return Ok(None);
+    };
+    let Ok(plaintext) = encryptor.decrypt(ciphertext) else {
+        return Ok(None);
+    };
+    let json: JsonValue = serde_json::from_slice(&plaintext)?;
+    Ok(Some(ConvexValue::try_from(json)?))
+}
+
+/// Encrypt the values of `fields` in `object`. Values that are already
+/// ciphertexts, e.g. fields carried over from the previous version of a
+/// patched document, are left as they are.
+pub fn encrypt_fields(
+    encryptor: &Arc<dyn FieldEncryptor>,
+    fields: &BTreeSet<IdentifierFieldName>,
+    object: ConvexObject,
+) -> anyhow::Result<ConvexObject> {
+    let mut map: BTreeMap<FieldName, ConvexValue> = object.into();
+    for field in fields {
+        let Some(value) = map.get_mut(&FieldName::from(field.clone())) else {
+            continue;
+        };
+        if decrypt_value(encryptor, value)?.is_some() {
+            continue;
+        }
+        let plaintext = serde_json::to_vec(&JsonValue::from(value.clone()))?;
+        let mut ciphertext = CIPHERTEXT_PREFIX.to_vec();
+        ciphertext.extend(encryptor.encrypt(&plaintext)?);
+        *value = ConvexValue::try_from(ciphertext)?;
+    }
+    map.try_into()
+}
+
+/// Decrypt every top-level field of `object` that holds a ciphertext.
+pub fn decrypt_fields(
+    encryptor: &Arc<dyn FieldEncryptor>,
+    object: ConvexObject,
+) -> anyhow::Result<ConvexObject> {
+    let mut map: BTreeMap<FieldName, ConvexValue> = object.into();
+    for value in map.values_mut() {
+        if let Some(plaintext) = decrypt_value(encryptor, value)? {
+            *value = plaintext;
+        }
+    }
+    map.try_into()
+}
+
+/// Decrypt the encrypted fields of a document read from storage.
+pub fn decrypt_document(
+    encryptor: &Arc<dyn FieldEncryptor>,
+    document: ResolvedDocument,
+) -> anyhow::Result<ResolvedDocument> {
+    let value = decrypt_fields(encryptor, document.value().clone().into_value())?;
+    document.replace_value(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeSet,
+        sync::Arc,
+    };
+
+    use keybroker::{
+        Encryptor,
+        Secret,
+    };
+    use value::{
+        assert_obj,
+        ConvexValue,
+    };
+
+    use super::{
+        decrypt_fields,
+        encrypt_fields,
+        FieldEncryptor,
+    };
+
+    #[test]
+    fn test_encrypt_fields_roundtrip() -> anyhow::Result<()> {
+        let encryptor: Arc<dyn FieldEncryptor> = Arc::new(Encryptor::new(Secret::random())?);
+        let fields = BTreeSet::from(["ssn".parse()?, "address".parse()?]);
+        let object = assert_obj!(
+            "name" => "Ada",
+            "ssn" => "123-45-6789",
+            "address" => assert_obj!("city" => "London", "zip" => 12i64)
+        );
+
+        let encrypted = encrypt_fields(&encryptor, &fields, object.clone())?;
+        assert_eq!(encrypted.get("name"), object.get("name"));
+        assert!(matches!(encrypted.get("ssn"), Some(ConvexValue::Bytes(_))));
+        assert!(matches!(
+            encrypted.get("address"),
+            Some(ConvexValue::Bytes(_))
+        ));
+
+        // Encrypting again leaves existing ciphertexts alone.
+        let reencrypted = encrypt_fields(&encryptor, &fields, encrypted.clone())?;
+        assert_eq!(reencrypted, encrypted);
+
+        assert_eq!(decrypt_fields(&encryptor, encrypted.clone())?, object);
+
+        // Ciphertexts from another key are left as they are.
+        let other: Arc<dyn FieldEncryptor> = Arc::new(Encryptor::new(Secret::random())?);
+        assert_eq!(decrypt_fields(&other, encrypted.clone())?, encrypted);
+        Ok(())
+    }
+}
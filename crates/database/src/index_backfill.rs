@@ -0,0 +1,258 @@
+//! Throttling and progress tracking for database index backfills.
+//!
+//! Backfills write index entries straight to persistence, so they compete
+//! with foreground commits for it. The database keeps a running average of
+//! its commit latency, and backfills back off while it's above
+//! `INDEX_BACKFILL_TARGET_COMMIT_LATENCY`.
+//!
+//! Both the latency and the progress of backfills are only kept in memory.
+
+use std::{
+    cmp,
+    collections::BTreeMap,
+    sync::Arc,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use common::{
+    knobs::{
+        INDEX_BACKFILL_MAX_THROTTLE_DELAY,
+        INDEX_BACKFILL_TARGET_COMMIT_LATENCY,
+    },
+    runtime::{
+        Runtime,
+        RuntimeInstant,
+    },
+    types::IndexId,
+};
+use parking_lot::Mutex;
+
+/// Weight of the latest commit in the average commit latency.
+const COMMIT_LATENCY_SMOOTHING: f64 = 0.2;
+
+/// The average is forgotten if there haven't been any commits for this long,
+/// so a backfill doesn't stay throttled by a spike once writes stop.
+const COMMIT_LATENCY_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// The first delay of a backfill that starts being throttled. Delays double
+/// from here while commits are slow and halve back down once they aren't.
+const MIN_THROTTLE_DELAY: Duration = Duration::from_millis(10);
+
+/// Exponentially weighted average of how long write transactions take to
+/// commit.
+#[derive(Clone)]
+pub struct CommitLatency<RT: Runtime> {
+    runtime: RT,
+    average: Arc<Mutex<Option<(Duration, RT::Instant)>>>,
+}
+
+impl<RT: Runtime> CommitLatency<RT> {
+    pub fn new(runtime: RT) -> Self {
+        Self {
+            runtime,
+            average: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn record(&self, latency: Duration) {
+        let now = self.runtime.monotonic_now();
+        let mut average = self.average.lock();
+        let latency = match &*average {
+            Some((previous, updated))
+                if now.clone() - updated.clone() < COMMIT_LATENCY_STALE_AFTER =>
+            {
+                previous.mul_f64(1.0 - COMMIT_LATENCY_SMOOTHING)
+                    + latency.mul_f64(COMMIT_LATENCY_SMOOTHING)
+            },
+            _ => latency,
+        };
+        *average = Some((latency, now));
+    }
+
+    pub fn get(&self) -> Duration {
+        match &*self.average.lock() {
+            Some((average, updated)) if updated.elapsed() < COMMIT_LATENCY_STALE_AFTER => *average,
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Adaptive backoff for a single pass of a backfill, paced by the database's
+/// commit latency.
+pub struct BackfillThrottle<RT: Runtime> {
+    commit_latency: CommitLatency<RT>,
+    delay: Duration,
+}
+
+impl<RT: Runtime> BackfillThrottle<RT> {
+    pub fn new(commit_latency: CommitLatency<RT>) -> Self {
+        Self {
+            commit_latency,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// How long to wait before writing the next chunk.
+    pub fn next_delay(&mut self) -> Duration {
+        self.delay = if self.commit_latency.get() > *INDEX_BACKFILL_TARGET_COMMIT_LATENCY {
+            cmp::max(self.delay * 2, MIN_THROTTLE_DELAY).min(*INDEX_BACKFILL_MAX_THROTTLE_DELAY)
+        } else if self.delay > MIN_THROTTLE_DELAY {
+            self.delay / 2
+        } else {
+            Duration::ZERO
+        };
+        self.delay
+    }
+
+    pub async fn wait(&mut self, runtime: &RT) {
+        let delay = self.next_delay();
+        if !delay.is_zero() {
+            runtime.wait(delay).await;
+        }
+    }
+}
+
+/// How far the snapshot pass of a database index backfill has gotten. After
+/// the snapshot pass, the backfill walks the recent history of the table,
+/// which isn't tracked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexBackfillProgress {
+    pub num_docs_indexed: u64,
+    /// Documents in the table when the backfill started.
+    pub total_docs: u64,
+    pub started_at: SystemTime,
+}
+
+impl IndexBackfillProgress {
+    /// Estimated time until the snapshot pass is done, assuming it continues
+    /// at its average rate so far.
+    pub fn eta(&self, now: SystemTime) -> Option<Duration> {
+        if self.num_docs_indexed == 0 {
+            return None;
+        }
+        let elapsed = now.duration_since(self.started_at).ok()?;
+        let remaining = self.total_docs.saturating_sub(self.num_docs_indexed);
+        Some(elapsed.mul_f64(remaining as f64 / self.num_docs_indexed as f64))
+    }
+}
+
+/// Progress of the database index backfills in progress, by index.
+#[derive(Clone, Default)]
+pub struct BackfillProgressTracker {
+    progress: Arc<Mutex<BTreeMap<IndexId, IndexBackfillProgress>>>,
+}
+
+impl BackfillProgressTracker {
+    pub fn start(
+        &self,
+        index_ids: impl IntoIterator<Item = IndexId>,
+        total_docs: u64,
+        started_at: SystemTime,
+    ) {
+        let mut progress = self.progress.lock();
+        for index_id in index_ids {
+            progress.insert(
+                index_id,
+                IndexBackfillProgress {
+                    num_docs_indexed: 0,
+                    total_docs,
+                    started_at,
+                },
+            );
+        }
+    }
+
+    pub fn record_indexed(&self, index_ids: &[IndexId], num_docs: u64) {
+        let mut progress = self.progress.lock();
+        for index_id in index_ids {
+            if let Some(progress) = progress.get_mut(index_id) {
+                progress.num_docs_indexed += num_docs;
+            }
+        }
+    }
+
+    pub fn finish(&self, index_id: IndexId) {
+        self.progress.lock().remove(&index_id);
+    }
+
+    pub fn get(&self, index_id: IndexId) -> Option<IndexBackfillProgress> {
+        self.progress.lock().get(&index_id).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{
+        Duration,
+        SystemTime,
+    };
+
+    use common::knobs::{
+        INDEX_BACKFILL_MAX_THROTTLE_DELAY,
+        INDEX_BACKFILL_TARGET_COMMIT_LATENCY,
+    };
+    use runtime::testing::TestRuntime;
+
+    use super::{
+        BackfillThrottle,
+        CommitLatency,
+        IndexBackfillProgress,
+        COMMIT_LATENCY_STALE_AFTER,
+        MIN_THROTTLE_DELAY,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_backfill_throttle(rt: TestRuntime) -> anyhow::Result<()> {
+        let commit_latency = CommitLatency::new(rt.clone());
+        let mut throttle = BackfillThrottle::new(commit_latency.clone());
+        assert_eq!(throttle.next_delay(), Duration::ZERO);
+
+        // Slow commits back the backfill off exponentially, up to the max.
+        commit_latency.record(*INDEX_BACKFILL_TARGET_COMMIT_LATENCY * 10);
+        assert_eq!(throttle.next_delay(), MIN_THROTTLE_DELAY);
+        assert_eq!(throttle.next_delay(), MIN_THROTTLE_DELAY * 2);
+        for _ in 0..20 {
+            throttle.next_delay();
+        }
+        assert_eq!(throttle.next_delay(), *INDEX_BACKFILL_MAX_THROTTLE_DELAY);
+
+        // Once commits are fast again, it speeds back up.
+        for _ in 0..20 {
+            commit_latency.record(Duration::ZERO);
+        }
+        assert!(commit_latency.get() < *INDEX_BACKFILL_TARGET_COMMIT_LATENCY);
+        assert_eq!(
+            throttle.next_delay(),
+            *INDEX_BACKFILL_MAX_THROTTLE_DELAY / 2
+        );
+        for _ in 0..20 {
+            throttle.next_delay();
+        }
+        assert_eq!(throttle.next_delay(), Duration::ZERO);
+
+        // A spike is forgotten if there are no commits after it.
+        commit_latency.record(*INDEX_BACKFILL_TARGET_COMMIT_LATENCY * 10);
+        rt.wait(COMMIT_LATENCY_STALE_AFTER).await;
+        assert_eq!(commit_latency.get(), Duration::ZERO);
+        Ok(())
+    }
+
+    #[test]
+    fn test_backfill_eta() {
+        let started_at = SystemTime::UNIX_EPOCH;
+        let mut progress = IndexBackfillProgress {
+            num_docs_indexed: 0,
+            total_docs: 300,
+            started_at,
+        };
+        let now = started_at + Duration::from_secs(10);
+        assert_eq!(progress.eta(now), None);
+        progress.num_docs_indexed = 100;
+        assert_eq!(progress.eta(now), Some(Duration::from_secs(20)));
+        progress.num_docs_indexed = 400;
+        assert_eq!(progress.eta(now), Some(Duration::ZERO));
+    }
+}
@@ -98,6 +98,11 @@ use value::{
 };
 
 use crate::{
+    index_backfill::{
+        BackfillProgressTracker,
+        BackfillThrottle,
+        CommitLatency,
+    },
     metrics::{
         log_index_backfilled,
         log_num_indexes_to_backfill,
@@ -108,6 +113,7 @@ use crate::{
     ResolvedQuery,
     SystemMetadataModel,
     TableIterator,
+    TableModel,
 };
 
 const MAX_BACKOFF: Duration = Duration::from_secs(30);
@@ -139,6 +145,9 @@ pub struct IndexWriter<RT: Runtime> {
     reader: Arc<dyn PersistenceReader>,
     retention_validator: Arc<dyn RetentionValidator>,
     rate_limiter: Arc<RateLimiter<RT>>,
+    // Backfills back off while commits are slow.
+    commit_latency: CommitLatency<RT>,
+    backfill_progress: BackfillProgressTracker,
     runtime: RT,
 }
 
@@ -197,6 +206,14 @@ impl IndexSelector {
         tables.into_iter()
     }
 
+    fn index_ids(&self) -> Vec<IndexId> {
+        match self {
+            Self::All(_) => vec![],
+            Self::Index { id, .. } => vec![*id],
+            Self::ManyIndexes { indexes, .. } => indexes.keys().copied().collect(),
+        }
+    }
+
     fn filter_id(&self, id: InternalDocumentId) -> bool {
         match self {
             Self::All(_) => true,
@@ -217,10 +234,11 @@ impl<RT: Runtime> IndexWorker<RT> {
         let reader = persistence.reader();
         let persistence_version = reader.version();
         let mut worker = IndexWorker {
-            database,
             runtime: runtime.clone(),
             backoff: Backoff::new(*INDEX_WORKERS_INITIAL_BACKOFF, MAX_BACKOFF),
-            index_writer: IndexWriter::new(persistence, reader, retention_validator, runtime),
+            index_writer: IndexWriter::new(persistence, reader, retention_validator, runtime)
+                .with_database(&database),
+            database,
             #[cfg(any(test, feature = "testing"))]
             should_terminate: false,
             persistence_version,
@@ -260,10 +278,11 @@ impl<RT: Runtime> IndexWorker<RT> {
         let reader = persistence.reader();
         let persistence_version = reader.version();
         let mut worker = IndexWorker {
-            database,
             backoff: Backoff::new(*INDEX_WORKERS_INITIAL_BACKOFF, MAX_BACKOFF),
             runtime: runtime.clone(),
-            index_writer: IndexWriter::new(persistence, reader, retention_validator, runtime),
+            index_writer: IndexWriter::new(persistence, reader, retention_validator, runtime)
+                .with_database(&database),
+            database,
             should_terminate: true,
             persistence_version,
         };
@@ -402,6 +421,13 @@ impl<RT: Runtime> IndexWorker<RT> {
                 "Starting backfill of {} indexes for {table_name}: {needs_backfill:?}",
                 needs_backfill.len()
             );
+            let mut tx = self.database.begin(Identity::system()).await?;
+            let total_docs = TableModel::new(&mut tx).count_tablet(tablet_id).await?;
+            self.database.backfill_progress.start(
+                needs_backfill.keys().copied(),
+                total_docs,
+                self.runtime.system_time(),
+            );
             let index_selector = IndexSelector::ManyIndexes {
                 tablet_id,
                 indexes: needs_backfill,
@@ -591,6 +617,7 @@ impl<RT: Runtime> IndexWorker<RT> {
         self.database
             .commit_with_write_source(tx, "index_worker_finish_backfill")
             .await?;
+        self.database.backfill_progress.finish(index_id);
         log::info!("Finished backfill of index {}", name);
         log_index_backfilled();
         Ok(())
@@ -612,10 +639,20 @@ impl<RT: Runtime> IndexWriter<RT> {
                 runtime.clone(),
                 Quota::per_second(*ENTRIES_PER_SECOND),
             )),
+            commit_latency: CommitLatency::new(runtime.clone()),
+            backfill_progress: BackfillProgressTracker::default(),
             runtime,
         }
     }
 
+    /// Throttle backfills by `database`'s commit latency, and report their
+    /// progress to it.
+    pub fn with_database(mut self, database: &Database<RT>) -> Self {
+        self.commit_latency = database.commit_latency.clone();
+        self.backfill_progress = database.backfill_progress.clone();
+        self
+    }
+
     /// Backfill in two steps: first a snapshot at the current time, and then
     /// walking the log. After the current snapshot is backfilled, index
     /// snapshot reads at >=ts are valid. The subsequent walking of the log
@@ -646,7 +683,44 @@ impl<RT: Runtime> IndexWriter<RT> {
     ) -> anyhow::Result<()> {
         // Backfill in two steps: first create index entries for all latest documents,
         // then create index entries for all documents in the retention range.
+        self.backfill_snapshot(snapshot_ts, index_metadata, &index_selector)
+            .await?;
+
+        let mut min_backfilled_ts = snapshot_ts;
 
+        // Retry until min_snapshot_ts passes min_backfilled_ts, at which point we
+        // have backfilled the full range of snapshots within retention.
+        loop {
+            let min_snapshot_ts = self.retention_validator.min_snapshot_ts().await?;
+            if min_snapshot_ts >= *min_backfilled_ts {
+                break;
+            }
+            // NOTE: ordering Desc is important, to keep the range of valid snapshots
+            // contiguous. If we backfilled in order Asc, then we might see a
+            // document creation before its tombstone, and that document would be
+            // visible at snapshots where it should be deleted.
+            min_backfilled_ts = self
+                .backfill_backwards(
+                    min_backfilled_ts,
+                    min_snapshot_ts,
+                    index_metadata,
+                    &index_selector,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Backfills the index entries of the documents that were latest at
+    /// `snapshot_ts`, one table at a time in parallel. Reads of the selected
+    /// indexes are then valid at `snapshot_ts` and after, as long as entries
+    /// are written for later revisions.
+    pub async fn backfill_snapshot(
+        &self,
+        snapshot_ts: RepeatableTimestamp,
+        index_metadata: &IndexRegistry,
+        index_selector: &IndexSelector,
+    ) -> anyhow::Result<()> {
         let (tx, rx) = mpsc::unbounded();
         let handles: Vec<_> = index_selector
             .iterate_tables()
@@ -676,29 +750,6 @@ impl<RT: Runtime> IndexWriter<RT> {
         }
         tx.close_channel();
         let _: Vec<_> = rx.try_collect().await?;
-
-        let mut min_backfilled_ts = snapshot_ts;
-
-        // Retry until min_snapshot_ts passes min_backfilled_ts, at which point we
-        // have backfilled the full range of snapshots within retention.
-        loop {
-            let min_snapshot_ts = self.retention_validator.min_snapshot_ts().await?;
-            if min_snapshot_ts >= *min_backfilled_ts {
-                break;
-            }
-            // NOTE: ordering Desc is important, to keep the range of valid snapshots
-            // contiguous. If we backfilled in order Asc, then we might see a
-            // document creation before its tombstone, and that document would be
-            // visible at snapshots where it should be deleted.
-            min_backfilled_ts = self
-                .backfill_backwards(
-                    min_backfilled_ts,
-                    min_snapshot_ts,
-                    index_metadata,
-                    &index_selector,
-                )
-                .await?;
-        }
         Ok(())
     }
 
@@ -732,15 +783,19 @@ impl<RT: Runtime> IndexWriter<RT> {
             .stream_documents_in_table(tablet_id, by_id, None)
             .fuse();
         pin_mut!(stream);
+        let index_ids = index_selector.index_ids();
+        let mut throttle = BackfillThrottle::new(self.commit_latency.clone());
         let mut index_updates_written = 0;
         let mut last_logged = self.runtime.system_time();
         while !stream.is_done() {
             let mut chunk = BTreeSet::new();
+            let mut num_docs = 0;
             while chunk.len() < *INDEX_BACKFILL_CHUNK_SIZE {
                 let (document, ts) = match stream.try_next().await? {
                     Some(d) => d,
                     None => break,
                 };
+                num_docs += 1;
                 let index_updates = index_registry.index_updates(None, Some(&document));
                 chunk.extend(
                     index_updates
@@ -750,11 +805,13 @@ impl<RT: Runtime> IndexWriter<RT> {
                 );
             }
             if !chunk.is_empty() {
+                throttle.wait(&self.runtime).await;
                 index_updates_written += chunk.len();
                 self.persistence
                     .write(vec![], chunk, ConflictStrategy::Overwrite)
                     .await?;
             }
+            self.backfill_progress.record_indexed(&index_ids, num_docs);
             if last_logged.elapsed()? >= Duration::from_secs(60) {
                 tracing::info!(
                     "backfilled {index_updates_written} index rows for table {tablet_id} at \
@@ -949,6 +1006,7 @@ impl<RT: Runtime> IndexWriter<RT> {
 
         let mut last_logged = self.runtime.system_time();
         let mut num_entries_written = 0;
+        let mut throttle = BackfillThrottle::new(self.commit_latency.clone());
 
         while !updates.is_terminated() {
             // There are potentially more document revisions, so start a new chunk. First,
@@ -975,6 +1033,7 @@ impl<RT: Runtime> IndexWriter<RT> {
                 chunk.insert((ts, update));
             }
             if !chunk.is_empty() {
+                throttle.wait(&self.runtime).await;
                 num_entries_written += chunk.len();
                 self.persistence
                     .write(vec![], chunk, ConflictStrategy::Overwrite)
@@ -7,6 +7,7 @@ use anyhow::Context;
 use common::{
     knobs::{
         MAX_SEGMENT_DELETED_PERCENTAGE,
+        MAX_VECTOR_INDEX_DELETED_PERCENTAGE,
         MIN_COMPACTION_SEGMENTS,
         SEARCH_WORKER_PASSIVE_PAGES_PER_SECOND,
         SEGMENT_MAX_SIZE_BYTES,
@@ -41,6 +42,7 @@ use crate::{
         compaction_build_one_timer,
         finish_compaction_timer,
         log_compaction_compacted_segment_num_documents_total,
+        log_compaction_deleted_documents_removed,
         log_compaction_total_segments,
         CompactionReason,
     },
@@ -183,6 +185,13 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
         let total_compacted_segments = segments_to_compact.len();
         log_compaction_total_segments(total_compacted_segments, Self::search_type());
 
+        let deleted_documents_before = segments_to_compact
+            .iter()
+            .map(|segment| segment.statistics())
+            .reduce(SegmentStatistics::add)
+            .transpose()?
+            .unwrap_or_default()
+            .num_deleted_documents();
         let new_segment = self
             .compact(&job.developer_config, segments_to_compact.clone())
             .await?;
@@ -190,6 +199,12 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
 
         let total_documents = stats.num_documents();
         log_compaction_compacted_segment_num_documents_total(total_documents, Self::search_type());
+        // Documents deleted while the segments were being compacted are
+        // still in the new segment.
+        log_compaction_deleted_documents_removed(
+            deleted_documents_before.saturating_sub(stats.num_deleted_documents()),
+            Self::search_type(),
+        );
 
         self.writer
             .commit_compaction(
@@ -320,7 +335,7 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
             )));
         }
 
-        // Finally check to see if any individual segment has a large number of deleted
+        // Next check to see if any individual segment has a large number of deleted
         // documents and if so compact just that segment.
         let compact_deletes = large_segments
             .into_iter()
@@ -339,6 +354,42 @@ impl<RT: Runtime, T: SearchIndex> SearchIndexCompactor<RT, T> {
         if let Some(compact_deletes) = compact_deletes {
             return Ok(Some((to_owned(compact_deletes), CompactionReason::Deletes)));
         }
+
+        // Finally, if enough of the whole index is deleted, compact the segments that
+        // have deletes however small or few they are, so deleted documents
+        // don't linger until the index is rebuilt.
+        if let Some(max_index_deleted_percentage) = compaction_config.max_index_deleted_percentage {
+            let index_stats = segments
+                .iter()
+                .map(|segment| segment.statistics())
+                .reduce(SegmentStatistics::add)
+                .transpose()?
+                .unwrap_or_default();
+            if index_stats.num_deleted_documents() as f64 / index_stats.num_documents() as f64
+                > max_index_deleted_percentage
+            {
+                let mut segments_with_deletes = vec![];
+                for segment in segments {
+                    if segment.statistics()?.num_deleted_documents() > 0 {
+                        segments_with_deletes.push(segment);
+                    }
+                }
+                let compact_tombstones = Self::get_compactable_segments(
+                    segments_with_deletes,
+                    developer_config,
+                    &CompactionConfig {
+                        min_compaction_segments: 1,
+                        ..compaction_config.clone()
+                    },
+                )?;
+                if let Some(compact_tombstones) = compact_tombstones {
+                    return Ok(Some((
+                        to_owned(compact_tombstones),
+                        CompactionReason::Tombstones,
+                    )));
+                }
+            }
+        }
         tracing::trace!(
             "Found no segments to compact, segments: {:#?}",
             segments
@@ -414,6 +465,22 @@ pub struct CompactionConfig {
     // want to compact yet.
     pub min_compaction_segments: u64,
     pub max_segment_size_bytes: u64,
+    // Once more than this fraction of an index's documents are deleted, compact every segment
+    // with deletes, regardless of its size or how many segments there are. None to only
+    // compact away deletes in large segments over `max_deleted_percentage`.
+    pub max_index_deleted_percentage: Option<f64>,
+}
+
+impl CompactionConfig {
+    /// Vector indexes also compact away deletes across the whole index, since
+    /// deleted vectors still take up memory in the searcher and crowd out
+    /// results until they're removed.
+    pub fn vector() -> Self {
+        Self {
+            max_index_deleted_percentage: Some(*MAX_VECTOR_INDEX_DELETED_PERCENTAGE),
+            ..Self::default()
+        }
+    }
 }
 
 // TODO(sam): These defaults are reasonable for vector, but maybe not text.
@@ -428,6 +495,7 @@ impl Default for CompactionConfig {
             small_segment_threshold_bytes: *VECTOR_INDEX_SIZE_HARD_LIMIT as u64,
             min_compaction_segments: *MIN_COMPACTION_SEGMENTS,
             max_segment_size_bytes: *SEGMENT_MAX_SIZE_BYTES,
+            max_index_deleted_percentage: None,
         }
     }
 }
@@ -138,7 +138,7 @@ impl<RT: Runtime> SearchIndexWorkers<RT> {
                 database.clone(),
                 searcher.clone(),
                 search_storage.clone(),
-                CompactionConfig::default(),
+                CompactionConfig::vector(),
                 vector_index_metadata_writer.clone(),
             )),
         );
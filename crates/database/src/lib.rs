@@ -18,10 +18,13 @@
 #![feature(cow_is_borrowed)]
 #![feature(try_find)]
 
+mod backend_state;
 mod bootstrap_model;
 mod committer;
 mod database;
 mod execution_size;
+pub mod field_encryption;
+mod index_backfill;
 mod index_worker;
 mod index_workers;
 mod metrics;
@@ -30,17 +33,22 @@ pub mod persistence_helpers;
 mod preloaded;
 pub mod query;
 pub mod reads;
+mod replication;
 mod retention;
 mod search_and_vector_bootstrap;
+pub mod sketches;
 mod snapshot_manager;
 mod stack_traces;
 pub mod subscription;
 mod table_registry;
 pub mod table_summary;
+mod table_write_rate;
 mod token;
 mod transaction;
 mod transaction_id_generator;
 mod transaction_index;
+pub mod trash;
+pub mod triggers;
 pub mod vector_index_worker;
 mod virtual_tables;
 mod write_limits;
@@ -54,6 +62,7 @@ pub mod test_helpers;
 pub mod tests;
 pub mod text_index_worker;
 pub use execution_size::FunctionExecutionSize;
+pub use index_backfill::IndexBackfillProgress;
 pub use index_worker::IndexWorker;
 pub use index_workers::{
     fast_forward::FastForwardIndexWorker,
@@ -62,11 +71,18 @@ pub use index_workers::{
 pub use patch::PatchValue;
 pub use preloaded::PreloadedIndexRange;
 pub use reads::{
+    IndexReads,
+    ReadBudget,
     ReadSet,
     TransactionReadSet,
     TransactionReadSize,
     OVER_LIMIT_HELP,
 };
+pub use replication::{
+    ReplicaWriter,
+    ReplicatedRevision,
+    ReplicationLogPage,
+};
 pub use table_registry::TableRegistry;
 pub use token::{
     SerializedToken,
@@ -93,6 +109,11 @@ pub use writes::{
 };
 
 pub use self::{
+    backend_state::{
+        read_only_error,
+        BACKEND_STATE_TABLE,
+        READ_ONLY_ERROR_MESSAGE,
+    },
     bootstrap_model::{
         components::{
             definition::{
@@ -129,8 +150,11 @@ pub use self::{
         },
         system_metadata::SystemMetadataModel,
         table::{
+            IndexStatistics,
             TableModel,
+            TableStatistics,
             TablesTable,
+            VectorCounts,
             NUM_RESERVED_LEGACY_TABLE_NUMBERS,
             NUM_RESERVED_SYSTEM_TABLE_NUMBERS,
             TABLES_INDEX,
@@ -152,6 +176,7 @@ pub use self::{
         ShortBoxFuture,
         ShutdownSignal,
         SnapshotPage,
+        TableChanges,
         MAX_OCC_FAILURES,
     },
     index_worker::{
@@ -61,6 +61,14 @@ pub fn log_index_backfilled() {
     log_counter(&INDEXES_BACKFILLED_TOTAL, 1);
 }
 
+register_convex_gauge!(
+    REPLICATION_LAG_SECONDS,
+    "How far behind this backend a standby replicating from it is, as of its last request"
+);
+pub fn log_replication_lag(lag_seconds: f64) {
+    log_gauge(&REPLICATION_LAG_SECONDS, lag_seconds);
+}
+
 register_convex_histogram!(
     DATABASE_WRITE_TX_READ_INTERVALS_TOTAL,
     "Number of read intervals in a write transaction"
@@ -885,6 +893,7 @@ pub enum CompactionReason {
     SmallSegments,
     LargeSegments,
     Deletes,
+    Tombstones,
 }
 
 impl CompactionReason {
@@ -893,6 +902,7 @@ impl CompactionReason {
             CompactionReason::SmallSegments => "small",
             CompactionReason::LargeSegments => "large",
             CompactionReason::Deletes => "deletes",
+            CompactionReason::Tombstones => "tombstones",
         };
         StaticMetricLabel::new(COMPACTION_REASON_LABEL, label)
     }
@@ -946,6 +956,19 @@ pub fn log_compaction_compacted_segment_num_documents_total(
     );
 }
 
+register_convex_counter!(
+    COMPACTION_DELETED_DOCUMENTS_REMOVED_TOTAL,
+    "Number of deleted documents removed from search indexes by compaction",
+    &[SEARCH_TYPE_LABEL],
+);
+pub fn log_compaction_deleted_documents_removed(count: u64, search_type: SearchType) {
+    log_counter_with_labels(
+        &COMPACTION_DELETED_DOCUMENTS_REMOVED_TOTAL,
+        count,
+        vec![search_type.tag()],
+    );
+}
+
 register_convex_histogram!(
     DATABASE_SEARCH_INDEX_BUILD_ONE_SECONDS,
     "Time to build one (multisegment) search index",
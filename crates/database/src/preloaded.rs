@@ -52,7 +52,11 @@ impl PreloadedIndexRange {
         )?;
         let result = self.range.get(key);
         if let Some(document) = result {
-            tx.record_read_document(document, &self.table_name)?;
+            tx.record_read_document(
+                document,
+                &self.table_name,
+                self.tablet_index_name.descriptor(),
+            )?;
         }
         Ok(result)
     }
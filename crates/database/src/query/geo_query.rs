@@ -0,0 +1,206 @@
+use std::collections::VecDeque;
+
+use async_trait::async_trait;
+use common::{
+    bootstrap_model::index::database_index::IndexedFields,
+    interval::Interval,
+    paths::FieldPath,
+    query::{
+        CursorPosition,
+        Order,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        StableIndexName,
+        TabletIndexName,
+    },
+    version::Version,
+};
+use value::{
+    geo::GeoRegion,
+    ConvexValue,
+    TableNamespace,
+};
+
+use super::{
+    index_range::{
+        CursorInterval,
+        IndexRange,
+    },
+    DeveloperIndexRangeResponse,
+    QueryStream,
+    QueryStreamNext,
+};
+use crate::Transaction;
+
+/// A `QueryStream` that finds the documents whose geo point is within a
+/// region. It scans the intervals of the index that cover the region one after
+/// another, skipping documents whose point is outside of the region.
+pub struct GeoQuery {
+    namespace: TableNamespace,
+    stable_index_name: StableIndexName,
+    printable_index_name: IndexName,
+    indexed_fields: IndexedFields,
+    field_path: FieldPath,
+    region: GeoRegion,
+
+    /// The range being scanned, and the intervals left to scan after it, in
+    /// index order.
+    current: IndexRange,
+    remaining_intervals: VecDeque<Interval>,
+
+    /// The position of the last result across all of the intervals. Each
+    /// `IndexRange` moves its own cursor to the end of the query when it
+    /// finishes, so track the overall position here.
+    cursor_position: Option<CursorPosition>,
+    end_cursor: Option<CursorPosition>,
+
+    rows_read: usize,
+    bytes_read: usize,
+    maximum_rows_read: Option<usize>,
+    maximum_bytes_read: Option<usize>,
+    should_compute_split_cursor: bool,
+    version: Option<Version>,
+}
+
+impl GeoQuery {
+    pub fn new(
+        namespace: TableNamespace,
+        stable_index_name: StableIndexName,
+        printable_index_name: IndexName,
+        indexed_fields: IndexedFields,
+        field_path: FieldPath,
+        region: GeoRegion,
+        intervals: Vec<Interval>,
+        cursor_interval: CursorInterval,
+        maximum_rows_read: Option<usize>,
+        maximum_bytes_read: Option<usize>,
+        should_compute_split_cursor: bool,
+        version: Option<Version>,
+    ) -> Self {
+        let mut remaining_intervals = VecDeque::from(intervals);
+        let cursor_position = cursor_interval.curr_exclusive.clone();
+        let end_cursor = cursor_interval.end_inclusive.clone();
+        let current = IndexRange::new(
+            namespace,
+            stable_index_name.clone(),
+            printable_index_name.clone(),
+            remaining_intervals
+                .pop_front()
+                .unwrap_or_else(Interval::empty),
+            Order::Asc,
+            indexed_fields.clone(),
+            cursor_interval,
+            maximum_rows_read,
+            maximum_bytes_read,
+            should_compute_split_cursor,
+            version.clone(),
+        );
+        Self {
+            namespace,
+            stable_index_name,
+            printable_index_name,
+            indexed_fields,
+            field_path,
+            region,
+            current,
+            remaining_intervals,
+            cursor_position,
+            end_cursor,
+            rows_read: 0,
+            bytes_read: 0,
+            maximum_rows_read,
+            maximum_bytes_read,
+            should_compute_split_cursor,
+            version,
+        }
+    }
+
+    /// Move on to the next interval, sharing what's left of the data limits
+    /// with it. Returns false if there are no intervals left.
+    fn advance(&mut self) -> bool {
+        let Some(interval) = self.remaining_intervals.pop_front() else {
+            return false;
+        };
+        self.rows_read += self.current.rows_read();
+        self.bytes_read += self.current.returned_bytes();
+        // Intervals before the start cursor or after the end cursor are empty
+        // once they're intersected with the cursors.
+        let cursor_interval = CursorInterval {
+            curr_exclusive: self.cursor_position.clone(),
+            end_inclusive: self.end_cursor.clone(),
+        };
+        self.current = IndexRange::new(
+            self.namespace,
+            self.stable_index_name.clone(),
+            self.printable_index_name.clone(),
+            interval,
+            Order::Asc,
+            self.indexed_fields.clone(),
+            cursor_interval,
+            self.maximum_rows_read
+                .map(|maximum| maximum.saturating_sub(self.rows_read)),
+            self.maximum_bytes_read
+                .map(|maximum| maximum.saturating_sub(self.bytes_read)),
+            self.should_compute_split_cursor,
+            self.version.clone(),
+        );
+        true
+    }
+}
+
+#[async_trait]
+impl QueryStream for GeoQuery {
+    fn cursor_position(&self) -> &Option<CursorPosition> {
+        &self.cursor_position
+    }
+
+    fn split_cursor_position(&self) -> Option<&CursorPosition> {
+        self.current.split_cursor_position()
+    }
+
+    fn is_approaching_data_limit(&self) -> bool {
+        self.current.is_approaching_data_limit()
+    }
+
+    async fn next<RT: Runtime>(
+        &mut self,
+        tx: &mut Transaction<RT>,
+        prefetch_hint: Option<usize>,
+    ) -> anyhow::Result<QueryStreamNext> {
+        loop {
+            let (document, write_timestamp) = match self.current.next(tx, prefetch_hint).await? {
+                QueryStreamNext::Ready(Some(v)) => v,
+                QueryStreamNext::Ready(None) => {
+                    if self.advance() {
+                        continue;
+                    }
+                    self.cursor_position =
+                        Some(self.end_cursor.clone().unwrap_or(CursorPosition::End));
+                    return Ok(QueryStreamNext::Ready(None));
+                },
+                QueryStreamNext::WaitingOn(request) => {
+                    return Ok(QueryStreamNext::WaitingOn(request))
+                },
+            };
+            self.cursor_position = self.current.cursor_position().clone();
+            // Points in the covering cells may still be outside of the region.
+            let in_region = match document.value().0.get_path(&self.field_path) {
+                Some(ConvexValue::GeoPoint(point)) => self.region.contains(point),
+                _ => false,
+            };
+            if in_region {
+                return Ok(QueryStreamNext::Ready(Some((document, write_timestamp))));
+            }
+        }
+    }
+
+    fn feed(&mut self, index_range_response: DeveloperIndexRangeResponse) -> anyhow::Result<()> {
+        self.current.feed(index_range_response)
+    }
+
+    fn tablet_index_name(&self) -> Option<&TabletIndexName> {
+        self.current.tablet_index_name()
+    }
+}
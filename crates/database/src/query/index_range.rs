@@ -157,6 +157,16 @@ impl IndexRange {
         }
     }
 
+    /// Number of index entries fetched so far.
+    pub fn rows_read(&self) -> usize {
+        self.rows_read
+    }
+
+    /// Size of the documents returned so far.
+    pub fn returned_bytes(&self) -> usize {
+        self.returned_bytes
+    }
+
     fn start_next<RT: Runtime>(
         &mut self,
         tx: &mut Transaction<RT>,
@@ -208,8 +218,11 @@ impl IndexRange {
                 self.indexed_fields.clone(),
                 used_interval,
             )?;
-            UserFacingModel::new(tx, self.namespace)
-                .record_read_document(&v, self.printable_index_name.table())?;
+            UserFacingModel::new(tx, self.namespace).record_read_document(
+                &v,
+                self.printable_index_name.table(),
+                self.printable_index_name.descriptor(),
+            )?;
 
             // Database bandwidth for index reads
             tx.usage_tracker.track_database_egress_size(
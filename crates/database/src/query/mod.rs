@@ -44,6 +44,7 @@ use value::TableNamespace;
 
 use self::{
     filter::Filter,
+    geo_query::GeoQuery,
     index_range::{
         CursorInterval,
         IndexRange,
@@ -59,6 +60,7 @@ use crate::{
 };
 
 mod filter;
+mod geo_query;
 mod index_range;
 mod limit;
 mod search_query;
@@ -313,12 +315,13 @@ impl<RT: Runtime> DeveloperQuery<RT> {
             },
             QuerySource::IndexRange(ref index_range) => index_range.index_name.clone(),
             QuerySource::Search(ref search) => search.index_name.clone(),
+            QuerySource::Geo(ref geo_query) => geo_query.index_name.clone(),
         };
         let stable_index_name =
             IndexModel::new(tx).stable_index_name(namespace, &index_name, table_filter)?;
         let indexed_fields = match query.source {
             QuerySource::FullTableScan(_) => IndexedFields::creation_time(),
-            QuerySource::IndexRange(_) => {
+            QuerySource::IndexRange(_) | QuerySource::Geo(_) => {
                 IndexModel::new(tx).indexed_fields(&stable_index_name, &index_name)?
             },
             QuerySource::Search(_) => {
@@ -391,6 +394,23 @@ impl<RT: Runtime> DeveloperQuery<RT> {
                 cursor_interval,
                 version,
             )),
+            QuerySource::Geo(geo_query) => {
+                let (field_path, intervals) = geo_query.compile(&indexed_fields)?;
+                QueryNode::Geo(Box::new(GeoQuery::new(
+                    namespace,
+                    stable_index_name,
+                    index_name,
+                    indexed_fields,
+                    field_path,
+                    geo_query.region,
+                    intervals,
+                    cursor_interval,
+                    maximum_rows_read,
+                    maximum_bytes_read,
+                    should_compute_split_cursor,
+                    version,
+                )))
+            },
         };
         for operator in query.operators {
             let next_node = match operator {
@@ -622,6 +642,7 @@ pub async fn resolved_query_batch_next<RT: Runtime>(
 enum QueryNode {
     IndexRange(IndexRange),
     Search(SearchQuery),
+    Geo(Box<GeoQuery>),
     Filter(Box<Filter>),
     Limit(Box<Limit>),
 }
@@ -632,6 +653,7 @@ impl QueryStream for QueryNode {
         match self {
             QueryNode::IndexRange(r) => r.cursor_position(),
             QueryNode::Search(r) => r.cursor_position(),
+            QueryNode::Geo(r) => r.cursor_position(),
             QueryNode::Filter(r) => r.cursor_position(),
             QueryNode::Limit(r) => r.cursor_position(),
         }
@@ -641,6 +663,7 @@ impl QueryStream for QueryNode {
         match self {
             QueryNode::IndexRange(r) => r.split_cursor_position(),
             QueryNode::Search(r) => r.split_cursor_position(),
+            QueryNode::Geo(r) => r.split_cursor_position(),
             QueryNode::Filter(r) => r.split_cursor_position(),
             QueryNode::Limit(r) => r.split_cursor_position(),
         }
@@ -650,6 +673,7 @@ impl QueryStream for QueryNode {
         match self {
             Self::IndexRange(r) => r.is_approaching_data_limit(),
             Self::Search(r) => r.is_approaching_data_limit(),
+            Self::Geo(r) => r.is_approaching_data_limit(),
             Self::Filter(r) => r.is_approaching_data_limit(),
             Self::Limit(r) => r.is_approaching_data_limit(),
         }
@@ -663,6 +687,7 @@ impl QueryStream for QueryNode {
         match self {
             QueryNode::IndexRange(r) => r.next(tx, prefetch_hint).await,
             QueryNode::Search(r) => r.next(tx, prefetch_hint).await,
+            QueryNode::Geo(r) => r.next(tx, prefetch_hint).await,
             QueryNode::Filter(r) => r.next(tx, prefetch_hint).await,
             QueryNode::Limit(r) => r.next(tx, prefetch_hint).await,
         }
@@ -672,6 +697,7 @@ impl QueryStream for QueryNode {
         match self {
             QueryNode::IndexRange(r) => r.feed(index_range_response),
             QueryNode::Search(r) => r.feed(index_range_response),
+            QueryNode::Geo(r) => r.feed(index_range_response),
             QueryNode::Filter(r) => r.feed(index_range_response),
             QueryNode::Limit(r) => r.feed(index_range_response),
         }
@@ -681,6 +707,7 @@ impl QueryStream for QueryNode {
         match self {
             QueryNode::IndexRange(r) => r.tablet_index_name(),
             QueryNode::Search(r) => r.tablet_index_name(),
+            QueryNode::Geo(r) => r.tablet_index_name(),
             QueryNode::Filter(r) => r.tablet_index_name(),
             QueryNode::Limit(r) => r.tablet_index_name(),
         }
@@ -19,6 +19,7 @@ use common::{
     },
     static_span,
     types::{
+        IndexDescriptor,
         PersistenceVersion,
         TabletIndexName,
         Timestamp,
@@ -227,6 +228,8 @@ pub struct TransactionReadSet {
 
     user_tx_size: TransactionReadSize,
     system_tx_size: TransactionReadSize,
+
+    budget: ReadBudget,
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -248,6 +251,37 @@ pub struct TransactionReadSize {
     pub total_document_count: usize,
 }
 
+/// Limits on how much a single function can read from user tables, set per
+/// function by admins. They're checked as each document is read, like the
+/// transaction limits, but are usually much lower, so a function that scans
+/// more than expected fails early with an error naming the index it was
+/// reading.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ReadBudget {
+    pub max_documents: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+impl ReadBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_documents.is_none() && self.max_bytes.is_none()
+    }
+}
+
+fn read_budget_exceeded(
+    limit: String,
+    table_name: &TableName,
+    index: &IndexDescriptor,
+) -> ErrorMetadata {
+    ErrorMetadata::pagination_limit(
+        "ReadBudgetExceeded",
+        format!(
+            "Read budget exceeded: this function can read at most {limit}, and went over it \
+             reading index {table_name}.{index}. {OVER_LIMIT_HELP}"
+        ),
+    )
+}
+
 impl TransactionReadSet {
     /// Create a read-set at the given timestamp.
     pub fn new() -> Self {
@@ -256,9 +290,20 @@ impl TransactionReadSet {
             num_intervals: 0,
             user_tx_size: TransactionReadSize::default(),
             system_tx_size: TransactionReadSize::default(),
+            budget: ReadBudget::default(),
         }
     }
 
+    pub fn budget(&self) -> ReadBudget {
+        self.budget
+    }
+
+    /// Limit the reads from here on, on top of the transaction limits. Reads
+    /// that were already recorded count towards the budget.
+    pub fn set_budget(&mut self, budget: ReadBudget) {
+        self.budget = budget;
+    }
+
     pub fn into_read_set(self) -> ReadSet {
         self.read_set
     }
@@ -337,6 +382,7 @@ impl TransactionReadSet {
     pub fn record_read_document(
         &mut self,
         table_name: TableName,
+        index: &IndexDescriptor,
         document_size: usize,
         usage_tracker: &FunctionUsageTracker,
         is_virtual_table: bool,
@@ -384,6 +430,18 @@ impl TransactionReadSet {
                     )
                 ),
             );
+            if let Some(max_documents) = self.budget.max_documents {
+                anyhow::ensure!(
+                    tx_size.total_document_count <= max_documents,
+                    read_budget_exceeded(format!("{max_documents} documents"), &table_name, index)
+                );
+            }
+            if let Some(max_bytes) = self.budget.max_bytes {
+                anyhow::ensure!(
+                    tx_size.total_document_size <= max_bytes,
+                    read_budget_exceeded(format!("{max_bytes} bytes"), &table_name, index)
+                );
+            }
         }
         Ok(())
     }
@@ -497,6 +555,7 @@ mod tests {
         query::search_value_to_bytes,
         testing::TestIdGenerator,
         types::{
+            IndexDescriptor,
             PersistenceVersion,
             TabletIndexName,
         },
@@ -506,6 +565,7 @@ mod tests {
             ResolvedDocumentId,
         },
     };
+    use errors::ErrorMetadataAnyhowExt;
     use search::{
         query::{
             FuzzyDistance,
@@ -515,9 +575,13 @@ mod tests {
         QueryReads as SearchQueryReads,
         TextQueryTermRead,
     };
+    use usage_tracking::FunctionUsageTracker;
     use value::val;
 
-    use super::TransactionReadSet;
+    use super::{
+        ReadBudget,
+        TransactionReadSet,
+    };
     use crate::ReadSet;
 
     fn create_document_with_one_field(
@@ -966,4 +1030,36 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_read_budget() -> anyhow::Result<()> {
+        let mut reads = TransactionReadSet::new();
+        let usage_tracker = FunctionUsageTracker::new();
+        let index: IndexDescriptor = "by_author".parse()?;
+        reads.set_budget(ReadBudget {
+            max_documents: Some(2),
+            max_bytes: None,
+        });
+        for _ in 0..2 {
+            reads.record_read_document("messages".parse()?, &index, 10, &usage_tracker, false)?;
+        }
+        // System tables don't count towards the budget.
+        reads.record_read_document("_modules".parse()?, &index, 10, &usage_tracker, false)?;
+        let err = reads
+            .record_read_document("messages".parse()?, &index, 10, &usage_tracker, false)
+            .unwrap_err();
+        assert!(err.is_pagination_limit());
+        assert!(err.to_string().contains("messages.by_author"), "{err}");
+
+        let mut reads = TransactionReadSet::new();
+        reads.set_budget(ReadBudget {
+            max_documents: None,
+            max_bytes: Some(100),
+        });
+        reads.record_read_document("messages".parse()?, &index, 100, &usage_tracker, false)?;
+        assert!(reads
+            .record_read_document("messages".parse()?, &index, 1, &usage_tracker, false)
+            .is_err());
+        Ok(())
+    }
 }
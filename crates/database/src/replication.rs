@@ -0,0 +1,484 @@
+//! Asynchronous replication of the documents log to a standby backend.
+//!
+//! The primary serves its log in pages with [`Database::replication_log`],
+//! and the standby writes each page into its own persistence with
+//! [`ReplicaWriter`], deriving index entries from the documents the same way
+//! the committer and index worker do. The standby doesn't run a [`Database`]
+//! while it's replicating. Promoting it is loading one from its persistence,
+//! like any restart.
+//!
+//! Only persistence is replicated. Files and text and vector index segments
+//! live in blob storage, so the standby must share or separately replicate
+//! the primary's storage for them to be available after promotion.
+//!
+//! [`Database`]: crate::Database
+//! [`Database::replication_log`]: crate::Database::replication_log
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    sync::Arc,
+};
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::tables::TableMetadata,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    persistence::{
+        ConflictStrategy,
+        NoopRetentionValidator,
+        Persistence,
+        PersistenceGlobalKey,
+        RepeatablePersistence,
+    },
+    runtime::Runtime,
+    types::{
+        IndexId,
+        RepeatableReason,
+        RepeatableTimestamp,
+        TabletIndexName,
+        Timestamp,
+    },
+    value::TabletId,
+};
+use indexing::index_registry::IndexRegistry;
+use itertools::Itertools;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use value::{
+    ConvexValue,
+    InternalDocumentId,
+    InternalId,
+};
+
+use crate::{
+    database::BootstrapMetadata,
+    index_worker::{
+        IndexSelector,
+        IndexWriter,
+    },
+    DatabaseSnapshot,
+};
+
+/// Persistence globals the standby needs before it can load the database.
+pub const REPLICATED_PERSISTENCE_GLOBALS: [PersistenceGlobalKey; 4] = [
+    PersistenceGlobalKey::TablesByIdIndex,
+    PersistenceGlobalKey::TablesTabletId,
+    PersistenceGlobalKey::IndexByIdIndex,
+    PersistenceGlobalKey::IndexTabletId,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplicatedRevision {
+    pub ts: Timestamp,
+    pub id: InternalDocumentId,
+    /// `None` for a delete.
+    pub document: Option<ResolvedDocument>,
+}
+
+/// A page of the primary's documents log. Pages never split a transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplicationLogPage {
+    /// Revisions in increasing `(ts, id)` order.
+    pub revisions: Vec<ReplicatedRevision>,
+    /// After applying this page, the standby has every revision up to and
+    /// including this timestamp. Passed back as the next page's cursor.
+    pub cursor: Timestamp,
+    pub has_more: bool,
+    /// The primary's latest timestamp when it read this page, for measuring
+    /// replication lag.
+    pub primary_ts: Timestamp,
+    /// The primary's retention bounds. The standby can't serve reads before
+    /// them since the primary may have deleted the revisions it would need.
+    pub min_snapshot_ts: Timestamp,
+    pub min_document_snapshot_ts: Timestamp,
+    pub persistence_globals: Vec<(PersistenceGlobalKey, JsonValue)>,
+}
+
+impl TryFrom<ReplicationLogPage> for JsonValue {
+    type Error = anyhow::Error;
+
+    fn try_from(page: ReplicationLogPage) -> anyhow::Result<Self> {
+        let revisions: Vec<_> = page
+            .revisions
+            .into_iter()
+            .map(|revision| {
+                json!({
+                    "ts": JsonValue::from(revision.ts),
+                    "table": revision.id.table().to_string(),
+                    "id": revision.id.internal_id().to_string(),
+                    "document": revision
+                        .document
+                        .map(|document| JsonValue::from(document.value().0.clone())),
+                })
+            })
+            .collect();
+        let persistence_globals: serde_json::Map<_, _> = page
+            .persistence_globals
+            .into_iter()
+            .map(|(key, value)| (String::from(key), value))
+            .collect();
+        Ok(json!({
+            "revisions": revisions,
+            "cursor": JsonValue::from(page.cursor),
+            "hasMore": page.has_more,
+            "primaryTs": JsonValue::from(page.primary_ts),
+            "minSnapshotTs": JsonValue::from(page.min_snapshot_ts),
+            "minDocumentSnapshotTs": JsonValue::from(page.min_document_snapshot_ts),
+            "persistenceGlobals": persistence_globals,
+        }))
+    }
+}
+
+impl TryFrom<JsonValue> for ReplicationLogPage {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonValue) -> anyhow::Result<Self> {
+        let JsonValue::Object(mut fields) = value else {
+            anyhow::bail!("Replication log page must be an object");
+        };
+        let mut timestamp = |field: &str| -> anyhow::Result<Timestamp> {
+            Timestamp::try_from(
+                fields
+                    .remove(field)
+                    .with_context(|| format!("Replication log page is missing `{field}`"))?,
+            )
+        };
+        let cursor = timestamp("cursor")?;
+        let primary_ts = timestamp("primaryTs")?;
+        let min_snapshot_ts = timestamp("minSnapshotTs")?;
+        let min_document_snapshot_ts = timestamp("minDocumentSnapshotTs")?;
+        let has_more = fields
+            .remove("hasMore")
+            .and_then(|has_more| has_more.as_bool())
+            .context("Replication log page is missing `hasMore`")?;
+        let Some(JsonValue::Array(revisions)) = fields.remove("revisions") else {
+            anyhow::bail!("Replication log page is missing `revisions`");
+        };
+        let revisions = revisions
+            .into_iter()
+            .map(|revision| {
+                let ts = Timestamp::try_from(revision["ts"].clone())?;
+                let table: TabletId = revision["table"]
+                    .as_str()
+                    .context("Revision is missing `table`")?
+                    .parse()?;
+                let id: InternalId = revision["id"]
+                    .as_str()
+                    .context("Revision is missing `id`")?
+                    .parse()?;
+                let document = match &revision["document"] {
+                    JsonValue::Null => None,
+                    document => Some(ResolvedDocument::from_database(
+                        table,
+                        ConvexValue::try_from(document.clone())?,
+                    )?),
+                };
+                Ok(ReplicatedRevision {
+                    ts,
+                    id: InternalDocumentId::new(table, id),
+                    document,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let Some(JsonValue::Object(persistence_globals)) = fields.remove("persistenceGlobals")
+        else {
+            anyhow::bail!("Replication log page is missing `persistenceGlobals`");
+        };
+        let persistence_globals = persistence_globals
+            .into_iter()
+            .map(|(key, value)| Ok((key.parse()?, value)))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            revisions,
+            cursor,
+            has_more,
+            primary_ts,
+            min_snapshot_ts,
+            min_document_snapshot_ts,
+            persistence_globals,
+        })
+    }
+}
+
+/// Writes pages of the primary's log into a standby's persistence.
+///
+/// The standby's `max_repeatable_ts` persistence global doubles as the
+/// replication cursor, so a standby that restarts picks up where it left
+/// off. Pages are written with `ConflictStrategy::Overwrite`, so reapplying a
+/// partially applied page is harmless.
+pub struct ReplicaWriter<RT: Runtime> {
+    runtime: RT,
+    persistence: Arc<dyn Persistence>,
+    cursor: Option<Timestamp>,
+    bootstrap_metadata: Option<BootstrapMetadata>,
+    // Latest revisions of the `_tables` and `_index` documents, to derive the
+    // index registry from.
+    table_documents: BTreeMap<InternalDocumentId, ResolvedDocument>,
+    index_documents: BTreeMap<InternalDocumentId, ResolvedDocument>,
+    index_registry: Option<IndexRegistry>,
+}
+
+impl<RT: Runtime> ReplicaWriter<RT> {
+    pub async fn new(runtime: RT, persistence: Arc<dyn Persistence>) -> anyhow::Result<Self> {
+        let reader = persistence.reader();
+        let cursor = reader
+            .get_persistence_global(PersistenceGlobalKey::MaxRepeatableTimestamp)
+            .await?
+            .map(Timestamp::try_from)
+            .transpose()?;
+        let mut writer = Self {
+            runtime,
+            persistence: persistence.clone(),
+            cursor,
+            bootstrap_metadata: None,
+            table_documents: BTreeMap::new(),
+            index_documents: BTreeMap::new(),
+            index_registry: None,
+        };
+        let Some(cursor) = cursor else {
+            anyhow::ensure!(
+                persistence.is_fresh(),
+                "A standby must start replicating into an empty database"
+            );
+            return Ok(writer);
+        };
+        // Resume from the tables and indexes as of the cursor.
+        let bootstrap_metadata = DatabaseSnapshot::<RT>::get_meta_ids(reader.as_ref()).await?;
+        let persistence_snapshot = RepeatablePersistence::new(
+            reader,
+            RepeatableTimestamp::new_validated(cursor, RepeatableReason::IdleMaxTs),
+            Arc::new(NoopRetentionValidator),
+        )
+        .read_snapshot(RepeatableTimestamp::new_validated(
+            cursor,
+            RepeatableReason::IdleMaxTs,
+        ))?;
+        for (documents, index_id, tablet_id) in [
+            (
+                &mut writer.table_documents,
+                bootstrap_metadata.tables_by_id,
+                bootstrap_metadata.tables_tablet_id,
+            ),
+            (
+                &mut writer.index_documents,
+                bootstrap_metadata.index_by_id,
+                bootstrap_metadata.index_tablet_id,
+            ),
+        ] {
+            *documents = DatabaseSnapshot::<RT>::load_raw_table_documents(
+                &persistence_snapshot,
+                index_id,
+                tablet_id,
+            )
+            .await?
+            .into_values()
+            .map(|(_, document)| (document.id().into(), document))
+            .collect();
+        }
+        writer.bootstrap_metadata = Some(bootstrap_metadata);
+        writer.index_registry = Some(writer.derive_index_registry()?);
+        Ok(writer)
+    }
+
+    /// The cursor to request the next page with. `None` before the first
+    /// page, which starts from the beginning of the log.
+    pub fn cursor(&self) -> Option<Timestamp> {
+        self.cursor
+    }
+
+    pub async fn apply(&mut self, page: ReplicationLogPage) -> anyhow::Result<()> {
+        if let Some(cursor) = self.cursor {
+            anyhow::ensure!(
+                page.cursor >= cursor,
+                "Replication log page ends at {}, before the standby's cursor {cursor}",
+                page.cursor
+            );
+        }
+        if self.bootstrap_metadata.is_none() {
+            for (key, value) in page.persistence_globals {
+                self.persistence
+                    .write_persistence_global(key, value)
+                    .await?;
+            }
+            self.bootstrap_metadata = Some(
+                DatabaseSnapshot::<RT>::get_meta_ids(self.persistence.reader().as_ref()).await?,
+            );
+        }
+        for (ts, revisions) in &page.revisions.into_iter().group_by(|revision| revision.ts) {
+            self.apply_transaction(ts, revisions.collect()).await?;
+        }
+        self.advance_retention_bound(
+            PersistenceGlobalKey::RetentionMinSnapshotTimestamp,
+            page.min_snapshot_ts,
+        )
+        .await?;
+        self.advance_retention_bound(
+            PersistenceGlobalKey::DocumentRetentionMinSnapshotTimestamp,
+            page.min_document_snapshot_ts,
+        )
+        .await?;
+        self.persistence
+            .write_persistence_global(
+                PersistenceGlobalKey::MaxRepeatableTimestamp,
+                page.cursor.into(),
+            )
+            .await?;
+        self.cursor = Some(page.cursor);
+        Ok(())
+    }
+
+    async fn apply_transaction(
+        &mut self,
+        ts: Timestamp,
+        revisions: Vec<ReplicatedRevision>,
+    ) -> anyhow::Result<()> {
+        let bootstrap_metadata = self
+            .bootstrap_metadata
+            .clone()
+            .context("Missing bootstrap metadata")?;
+
+        // Tables and indexes created in this transaction may have documents
+        // written in it too, so update the registry first.
+        let mut metadata_changed = self.index_registry.is_none();
+        for revision in &revisions {
+            let documents = if revision.id.table() == bootstrap_metadata.tables_tablet_id {
+                &mut self.table_documents
+            } else if revision.id.table() == bootstrap_metadata.index_tablet_id {
+                &mut self.index_documents
+            } else {
+                continue;
+            };
+            match &revision.document {
+                Some(document) => documents.insert(revision.id, document.clone()),
+                None => documents.remove(&revision.id),
+            };
+            metadata_changed = true;
+        }
+        let mut new_indexes: BTreeMap<TabletId, BTreeMap<IndexId, TabletIndexName>> =
+            BTreeMap::new();
+        if metadata_changed {
+            let index_registry = self.derive_index_registry()?;
+            let previous_indexes = self
+                .index_registry
+                .as_ref()
+                .map(|registry| registry.all_database_index_configs())
+                .unwrap_or_default();
+            for (index_id, (name, _)) in index_registry.all_database_index_configs() {
+                if !previous_indexes.contains_key(&index_id) {
+                    new_indexes
+                        .entry(*name.table())
+                        .or_default()
+                        .insert(index_id, name);
+                }
+            }
+            self.index_registry = Some(index_registry);
+        }
+        let index_registry = self
+            .index_registry
+            .as_ref()
+            .context("Missing index registry")?;
+
+        let reader = self.persistence.reader();
+        let previous_revisions = reader
+            .previous_revisions(
+                revisions
+                    .iter()
+                    .map(|revision| (revision.id, ts))
+                    .collect::<BTreeSet<_>>(),
+                Arc::new(NoopRetentionValidator),
+            )
+            .await?;
+        let mut index_updates = BTreeSet::new();
+        for revision in &revisions {
+            let previous = previous_revisions
+                .get(&(revision.id, ts))
+                .and_then(|(_, document)| document.as_ref());
+            index_updates.extend(
+                index_registry
+                    .index_updates(previous, revision.document.as_ref())
+                    .into_iter()
+                    .map(|update| (ts, update)),
+            );
+        }
+        let documents = revisions
+            .into_iter()
+            .map(|revision| (revision.ts, revision.id, revision.document))
+            .collect();
+        self.persistence
+            .write(documents, index_updates, ConflictStrategy::Overwrite)
+            .await?;
+
+        // The primary backfills new indexes by writing entries for existing
+        // documents directly, without going through the log, so do the same.
+        // Their entries are only complete from `ts` on.
+        if !new_indexes.is_empty() {
+            let snapshot_ts = RepeatableTimestamp::new_validated(ts, RepeatableReason::IdleMaxTs);
+            let index_writer = IndexWriter::new(
+                self.persistence.clone(),
+                reader,
+                Arc::new(NoopRetentionValidator),
+                self.runtime.clone(),
+            );
+            for (tablet_id, indexes) in new_indexes {
+                index_writer
+                    .backfill_snapshot(
+                        snapshot_ts,
+                        index_registry,
+                        &IndexSelector::ManyIndexes { tablet_id, indexes },
+                    )
+                    .await?;
+            }
+            self.advance_retention_bound(PersistenceGlobalKey::RetentionMinSnapshotTimestamp, ts)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn derive_index_registry(&self) -> anyhow::Result<IndexRegistry> {
+        let table_documents: Vec<ParsedDocument<TableMetadata>> = self
+            .table_documents
+            .values()
+            .cloned()
+            .map(ParsedDocument::try_from)
+            .try_collect()?;
+        let (table_mapping, _) = DatabaseSnapshot::<RT>::table_mapping_and_states(table_documents);
+        IndexRegistry::bootstrap(
+            &table_mapping,
+            self.index_documents.values(),
+            self.persistence.reader().version(),
+        )
+    }
+
+    async fn advance_retention_bound(
+        &self,
+        key: PersistenceGlobalKey,
+        ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        let current = self
+            .persistence
+            .reader()
+            .get_persistence_global(key)
+            .await?
+            .map(ConvexValue::try_from)
+            .transpose()?;
+        let current = match current {
+            Some(ConvexValue::Int64(current)) => Timestamp::try_from(current)?,
+            None => Timestamp::MIN,
+            _ => anyhow::bail!("invalid retention snapshot {current:?}"),
+        };
+        if ts > current {
+            self.persistence
+                .write_persistence_global(key, ConvexValue::from(i64::from(ts)).into())
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Quantiles are within 1% of the true value.
+const RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Values closer to zero than this are counted as zero.
+const MIN_INDEXABLE_VALUE: f64 = 1e-9;
+
+/// A DDSketch, which estimates quantiles of the values inserted into it to
+/// within `RELATIVE_ACCURACY`. Values are counted in logarithmically sized
+/// bins, so unlike most quantile sketches they can also be removed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DdSketch {
+    /// Counts of positive values by bin. Bin `i` covers values in
+    /// `(gamma^(i-1), gamma^i]`.
+    positive: BTreeMap<i32, u64>,
+    /// Counts of negative values by the bin of their absolute value.
+    negative: BTreeMap<i32, u64>,
+    zero: u64,
+}
+
+fn gamma() -> f64 {
+    (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY)
+}
+
+fn bin(value: f64) -> i32 {
+    (value.ln() / gamma().ln()).ceil() as i32
+}
+
+/// The value a bin's values are estimated as, which is within
+/// `RELATIVE_ACCURACY` of every value in it.
+fn bin_value(bin: i32) -> f64 {
+    2.0 * gamma().powi(bin) / (gamma() + 1.0)
+}
+
+impl DdSketch {
+    fn count_mut(&mut self, value: f64) -> &mut u64 {
+        if value.abs() < MIN_INDEXABLE_VALUE {
+            &mut self.zero
+        } else if value > 0.0 {
+            self.positive.entry(bin(value)).or_default()
+        } else {
+            self.negative.entry(bin(-value)).or_default()
+        }
+    }
+
+    /// Counts `value`. Returns false if it isn't finite and wasn't counted.
+    pub fn insert(&mut self, value: f64) -> bool {
+        if !value.is_finite() {
+            return false;
+        }
+        *self.count_mut(value) += 1;
+        true
+    }
+
+    /// Stops counting a value previously passed to `insert`.
+    pub fn remove(&mut self, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        let count = self.count_mut(value);
+        *count = count.saturating_sub(1);
+        self.positive.retain(|_, count| *count > 0);
+        self.negative.retain(|_, count| *count > 0);
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (bin, count) in &other.positive {
+            *self.positive.entry(*bin).or_default() += count;
+        }
+        for (bin, count) in &other.negative {
+            *self.negative.entry(*bin).or_default() += count;
+        }
+        self.zero += other.zero;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.positive.values().sum::<u64>() + self.negative.values().sum::<u64>() + self.zero
+    }
+
+    /// The estimated value at `quantile`, from 0 to 1, or None if the sketch
+    /// is empty.
+    pub fn quantile(&self, quantile: f64) -> Option<f64> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = (quantile.clamp(0.0, 1.0) * (count - 1) as f64).floor() as u64;
+        // Walk the bins from the most negative value to the most positive.
+        let bins = self
+            .negative
+            .iter()
+            .rev()
+            .map(|(bin, count)| (-bin_value(*bin), *count))
+            .chain(std::iter::once((0.0, self.zero)))
+            .chain(
+                self.positive
+                    .iter()
+                    .map(|(bin, count)| (bin_value(*bin), *count)),
+            );
+        let mut seen = 0;
+        for (value, count) in bins {
+            seen += count;
+            if seen > rank {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    pub fn to_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let serialized = SerializedDdSketch {
+            positive: self.positive.clone().into_iter().collect(),
+            negative: self.negative.clone().into_iter().collect(),
+            zero: self.zero,
+        };
+        Ok(serde_json::to_vec(&serialized)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let serialized: SerializedDdSketch = serde_json::from_slice(bytes)?;
+        Ok(Self {
+            positive: serialized.positive.into_iter().collect(),
+            negative: serialized.negative.into_iter().collect(),
+            zero: serialized.zero,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedDdSketch {
+    positive: Vec<(i32, u64)>,
+    negative: Vec<(i32, u64)>,
+    zero: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DdSketch,
+        RELATIVE_ACCURACY,
+    };
+
+    fn assert_close(estimate: Option<f64>, expected: f64) {
+        let estimate = estimate.expect("Sketch is empty");
+        assert!(
+            // Values on a bin's boundary are off by exactly the accuracy.
+            (estimate - expected).abs() <= expected.abs() * RELATIVE_ACCURACY * (1.0 + 1e-9) + 1e-9,
+            "{estimate} isn't close to {expected}"
+        );
+    }
+
+    #[test]
+    fn test_ddsketch_quantiles() -> anyhow::Result<()> {
+        let mut sketch = DdSketch::default();
+        assert_eq!(sketch.quantile(0.5), None);
+        for i in 1..=1000 {
+            assert!(sketch.insert(f64::from(i)));
+        }
+        assert!(!sketch.insert(f64::NAN));
+        assert_eq!(sketch.count(), 1000);
+        assert_close(sketch.quantile(0.0), 1.0);
+        assert_close(sketch.quantile(0.5), 500.0);
+        assert_close(sketch.quantile(0.99), 990.0);
+        assert_close(sketch.quantile(1.0), 1000.0);
+
+        // Removing the top half moves the median down.
+        for i in 501..=1000 {
+            sketch.remove(f64::from(i));
+        }
+        assert_eq!(sketch.count(), 500);
+        assert_close(sketch.quantile(0.5), 250.0);
+
+        let mut other = DdSketch::default();
+        for i in -500..=0 {
+            other.insert(f64::from(i));
+        }
+        sketch.merge(&other);
+        assert_close(sketch.quantile(0.0), -500.0);
+        assert_close(sketch.quantile(0.5), 0.0);
+        assert_eq!(DdSketch::from_bytes(&sketch.to_bytes()?)?, sketch);
+        Ok(())
+    }
+}
@@ -0,0 +1,757 @@
+//! Sketches. A table's schema can declare approximate aggregates over one of
+//! its fields, like the number of distinct values or quantiles of numeric
+//! values. The database keeps a sketch per value of the first field of an
+//! index in `_sketches`, updating it in the same transaction as each write to
+//! the table, so a range of that field can be aggregated by merging its
+//! buckets' sketches instead of scanning its documents.
+//!
+//! Since each write to a table reads and writes its buckets' sketches,
+//! concurrent writes to the same bucket conflict. Sketches only count the
+//! documents written after they're added to the schema.
+
+use std::{
+    collections::BTreeMap,
+    ops::Bound,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    schemas::{
+        SketchKind,
+        SketchSchema,
+    },
+    types::{
+        IndexName,
+        MaybeValue,
+    },
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+    FieldPath,
+    IdentifierFieldName,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+    TabletId,
+};
+
+use crate::{
+    defaults::{
+        system_index,
+        SystemIndex,
+        SystemTable,
+    },
+    ResolvedQuery,
+    Transaction,
+};
+
+mod ddsketch;
+mod hyperloglog;
+
+pub use self::{
+    ddsketch::DdSketch,
+    hyperloglog::HyperLogLog,
+};
+
+pub static SKETCHES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_sketches"
+        .parse()
+        .expect("Invalid built-in sketches table")
+});
+
+static TABLET_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "tablet".parse().expect("Invalid built-in field"));
+
+static SKETCH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "sketch".parse().expect("Invalid built-in field"));
+
+static BUCKET_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "bucket".parse().expect("Invalid built-in field"));
+
+pub static SKETCHES_BY_BUCKET_INDEX: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SKETCHES_TABLE, "by_tablet_sketch_and_bucket"));
+
+pub struct SketchesTable;
+impl SystemTable for SketchesTable {
+    fn table_name(&self) -> &'static TableName {
+        &SKETCHES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: SKETCHES_BY_BUCKET_INDEX.clone(),
+            fields: vec![
+                TABLET_FIELD.clone(),
+                SKETCH_FIELD.clone(),
+                BUCKET_FIELD.clone(),
+            ]
+            .try_into()
+            .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<SketchBucket>::try_from(document).map(|_| ())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sketch {
+    Distinct(HyperLogLog),
+    Quantile(DdSketch),
+}
+
+impl Sketch {
+    fn new(kind: SketchKind) -> Self {
+        match kind {
+            SketchKind::Distinct => Self::Distinct(HyperLogLog::default()),
+            SketchKind::Quantile => Self::Quantile(DdSketch::default()),
+        }
+    }
+
+    fn kind(&self) -> SketchKind {
+        match self {
+            Self::Distinct(_) => SketchKind::Distinct,
+            Self::Quantile(_) => SketchKind::Quantile,
+        }
+    }
+
+    fn merge(&mut self, other: &Self) -> anyhow::Result<()> {
+        match (self, other) {
+            (Self::Distinct(hll), Self::Distinct(other)) => hll.merge(other),
+            (Self::Quantile(sketch), Self::Quantile(other)) => sketch.merge(other),
+            _ => anyhow::bail!("Can't merge sketches of different kinds"),
+        }
+        Ok(())
+    }
+}
+
+/// A table's sketch of the documents with one value of the sketch's index's
+/// first field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SketchBucket {
+    pub tablet_id: TabletId,
+    pub sketch_name: IdentifierFieldName,
+    /// None for documents without the field.
+    pub bucket: Option<ConvexValue>,
+    /// The field the sketch was kept by. If the schema changes the sketch's
+    /// definition, buckets kept by the old definition are ignored.
+    pub bucket_field: FieldPath,
+    pub field: FieldPath,
+    /// How many documents are counted in the sketch. The bucket is deleted
+    /// when this drops to zero.
+    pub count: u64,
+    pub sketch: Sketch,
+}
+
+impl SketchBucket {
+    fn matches(&self, schema: &SketchSchema, bucket_field: &FieldPath) -> bool {
+        self.sketch.kind() == schema.kind
+            && self.field == schema.field
+            && &self.bucket_field == bucket_field
+    }
+}
+
+impl TryFrom<SketchBucket> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(bucket: SketchBucket) -> anyhow::Result<Self> {
+        let (kind, state) = match &bucket.sketch {
+            Sketch::Distinct(hll) => (SketchKind::Distinct, hll.to_bytes()),
+            Sketch::Quantile(sketch) => (SketchKind::Quantile, sketch.to_bytes()?),
+        };
+        let mut obj: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
+        obj.insert(
+            "tablet".parse()?,
+            ConvexValue::try_from(bucket.tablet_id.to_string())?,
+        );
+        obj.insert(
+            "sketch".parse()?,
+            ConvexValue::try_from(String::from(bucket.sketch_name))?,
+        );
+        if let Some(value) = bucket.bucket {
+            obj.insert("bucket".parse()?, value);
+        }
+        obj.insert(
+            "bucketField".parse()?,
+            ConvexValue::try_from(String::from(bucket.bucket_field))?,
+        );
+        obj.insert(
+            "field".parse()?,
+            ConvexValue::try_from(String::from(bucket.field))?,
+        );
+        obj.insert(
+            "count".parse()?,
+            ConvexValue::Int64(bucket.count.try_into()?),
+        );
+        obj.insert("kind".parse()?, ConvexValue::try_from(kind.to_string())?);
+        obj.insert("state".parse()?, ConvexValue::try_from(state)?);
+        ConvexObject::try_from(obj)
+    }
+}
+
+impl TryFrom<ConvexObject> for SketchBucket {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields: BTreeMap<_, _> = object.into();
+        let mut string = |field: &str| -> anyhow::Result<String> {
+            match fields.remove(field) {
+                Some(ConvexValue::String(s)) => Ok(s.into()),
+                _ => anyhow::bail!("Missing or invalid `{field}` field for SketchBucket"),
+            }
+        };
+        let tablet_id = string("tablet")?.parse()?;
+        let sketch_name = string("sketch")?.parse()?;
+        let bucket_field = string("bucketField")?.parse()?;
+        let field = string("field")?.parse()?;
+        let kind: SketchKind = string("kind")?.parse()?;
+        let count = match fields.remove("count") {
+            Some(ConvexValue::Int64(count)) => count.try_into()?,
+            _ => anyhow::bail!("Missing or invalid `count` field for SketchBucket"),
+        };
+        let sketch = match fields.remove("state") {
+            Some(ConvexValue::Bytes(state)) => match kind {
+                SketchKind::Distinct => Sketch::Distinct(HyperLogLog::from_bytes(&state)?),
+                SketchKind::Quantile => Sketch::Quantile(DdSketch::from_bytes(&state)?),
+            },
+            _ => anyhow::bail!("Missing or invalid `state` field for SketchBucket"),
+        };
+        Ok(Self {
+            tablet_id,
+            sketch_name,
+            bucket: fields.remove("bucket"),
+            bucket_field,
+            field,
+            count,
+            sketch,
+        })
+    }
+}
+
+/// A range of values of the first field of a sketch's index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SketchRange {
+    pub lower: Bound<ConvexValue>,
+    pub upper: Bound<ConvexValue>,
+}
+
+impl SketchRange {
+    pub fn all() -> Self {
+        Self {
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+        }
+    }
+}
+
+/// Sketches only count values quantiles can be computed over.
+fn numeric_value(value: &ConvexValue) -> Option<f64> {
+    match value {
+        ConvexValue::Float64(f) => Some(*f),
+        ConvexValue::Int64(i) => Some(*i as f64),
+        _ => None,
+    }
+}
+
+pub struct SketchModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> SketchModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Updates the sketches of the table `id` is in for a write changing the
+    /// document from `old_document` to `new_document`.
+    pub(crate) async fn update(
+        &mut self,
+        id: ResolvedDocumentId,
+        old_document: Option<&ResolvedDocument>,
+        new_document: Option<&ResolvedDocument>,
+    ) -> anyhow::Result<()> {
+        if self.tx.table_mapping().is_system_tablet(id.tablet_id) {
+            return Ok(());
+        }
+        let table_name = self.tx.table_mapping().tablet_name(id.tablet_id)?;
+        let Some(schema) = self.tx.active_schema(self.namespace).await? else {
+            return Ok(());
+        };
+        let Some(table) = schema.tables.get(&table_name) else {
+            return Ok(());
+        };
+        for (sketch_name, sketch) in &table.sketches {
+            let Some(bucket_field) = table
+                .indexes
+                .get(&sketch.index)
+                .and_then(|index| index.fields.first())
+            else {
+                continue;
+            };
+            let entry = |document: Option<&ResolvedDocument>| {
+                document.map(|document| {
+                    (
+                        document.value().get_path(bucket_field).cloned(),
+                        document.value().get_path(&sketch.field).cloned(),
+                    )
+                })
+            };
+            let (old_entry, new_entry) = (entry(old_document), entry(new_document));
+            if old_entry == new_entry {
+                continue;
+            }
+            if let Some((bucket, Some(value))) = old_entry {
+                self.update_bucket(
+                    id.tablet_id,
+                    sketch_name,
+                    sketch,
+                    bucket_field,
+                    bucket,
+                    |sketch_bucket| match &mut sketch_bucket.sketch {
+                        Sketch::Distinct(_) => {
+                            sketch_bucket.count = sketch_bucket.count.saturating_sub(1)
+                        },
+                        Sketch::Quantile(sketch) => {
+                            if let Some(value) = numeric_value(&value) {
+                                sketch.remove(value);
+                            }
+                            sketch_bucket.count = sketch.count();
+                        },
+                    },
+                )
+                .await?;
+            }
+            if let Some((bucket, Some(value))) = new_entry {
+                self.update_bucket(
+                    id.tablet_id,
+                    sketch_name,
+                    sketch,
+                    bucket_field,
+                    bucket,
+                    |sketch_bucket| match &mut sketch_bucket.sketch {
+                        Sketch::Distinct(hll) => {
+                            hll.insert(&value);
+                            sketch_bucket.count += 1;
+                        },
+                        Sketch::Quantile(sketch) => {
+                            if let Some(value) = numeric_value(&value) {
+                                sketch.insert(value);
+                            }
+                            sketch_bucket.count = sketch.count();
+                        },
+                    },
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn bucket_query(
+        &mut self,
+        tablet_id: TabletId,
+        sketch_name: &IdentifierFieldName,
+        range: Vec<IndexRangeExpression>,
+    ) -> anyhow::Result<ResolvedQuery<RT>> {
+        let query = Query::index_range(IndexRange {
+            index_name: SKETCHES_BY_BUCKET_INDEX.clone(),
+            range: [
+                IndexRangeExpression::Eq(
+                    TABLET_FIELD.clone(),
+                    ConvexValue::try_from(tablet_id.to_string())?.into(),
+                ),
+                IndexRangeExpression::Eq(
+                    SKETCH_FIELD.clone(),
+                    ConvexValue::try_from(String::from(sketch_name.clone()))?.into(),
+                ),
+            ]
+            .into_iter()
+            .chain(range)
+            .collect(),
+            order: Order::Asc,
+        });
+        ResolvedQuery::new(self.tx, self.namespace, query)
+    }
+
+    async fn update_bucket(
+        &mut self,
+        tablet_id: TabletId,
+        sketch_name: &IdentifierFieldName,
+        sketch: &SketchSchema,
+        bucket_field: &FieldPath,
+        bucket: Option<ConvexValue>,
+        update: impl FnOnce(&mut SketchBucket),
+    ) -> anyhow::Result<()> {
+        let mut query_stream = self.bucket_query(
+            tablet_id,
+            sketch_name,
+            vec![IndexRangeExpression::Eq(
+                BUCKET_FIELD.clone(),
+                MaybeValue(bucket.clone()),
+            )],
+        )?;
+        let existing = query_stream.expect_at_most_one(self.tx).await?;
+        let mut sketch_bucket = match &existing {
+            Some(document) => Some(SketchBucket::try_from(
+                document.value().clone().into_value(),
+            )?),
+            None => None,
+        }
+        .filter(|sketch_bucket| sketch_bucket.matches(sketch, bucket_field))
+        .unwrap_or_else(|| SketchBucket {
+            tablet_id,
+            sketch_name: sketch_name.clone(),
+            bucket,
+            bucket_field: bucket_field.clone(),
+            field: sketch.field.clone(),
+            count: 0,
+            sketch: Sketch::new(sketch.kind),
+        });
+        update(&mut sketch_bucket);
+        // Sketches are written on behalf of whoever wrote the document, so
+        // this skips `SystemMetadataModel`'s authorization check.
+        match existing {
+            Some(document) if sketch_bucket.count == 0 => {
+                self.tx
+                    .apply_validated_write(document.id(), Some(document), None)?;
+            },
+            Some(document) => {
+                let new_document = document.replace_value(sketch_bucket.try_into()?)?;
+                self.tx
+                    .apply_validated_write(document.id(), Some(document), Some(new_document))?;
+            },
+            None if sketch_bucket.count == 0 => {},
+            None => {
+                let table_id = self
+                    .tx
+                    .table_mapping()
+                    .namespace(self.namespace)
+                    .id(&SKETCHES_TABLE)?;
+                let id = self.tx.id_generator.generate_resolved(table_id);
+                let creation_time = self.tx.next_creation_time.increment()?;
+                let document = ResolvedDocument::new(id, creation_time, sketch_bucket.try_into()?)?;
+                self.tx.apply_validated_write(id, None, Some(document))?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Merges the buckets of a sketch of `table_name` in `range`. Returns how
+    /// many documents they count and the merged sketch.
+    async fn merge(
+        &mut self,
+        table_name: &TableName,
+        sketch_name: &str,
+        range: SketchRange,
+    ) -> anyhow::Result<(u64, Sketch)> {
+        let not_found = || {
+            ErrorMetadata::bad_request(
+                "SketchNotFound",
+                format!("Table {table_name} has no sketch named {sketch_name:?}"),
+            )
+        };
+        let schema = self.tx.active_schema(self.namespace).await?;
+        let table = schema
+            .as_ref()
+            .and_then(|schema| schema.tables.get(table_name))
+            .ok_or_else(not_found)?;
+        let sketch_name: IdentifierFieldName = sketch_name.parse().map_err(|_| not_found())?;
+        let sketch = table.sketches.get(&sketch_name).ok_or_else(not_found)?;
+        let bucket_field = table
+            .indexes
+            .get(&sketch.index)
+            .and_then(|index| index.fields.first())
+            .ok_or_else(not_found)?;
+        let mut merged = Sketch::new(sketch.kind);
+        let mut count = 0;
+        let Ok(table_id) = self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .id(table_name)
+        else {
+            return Ok((count, merged));
+        };
+        let mut range_expressions = vec![];
+        match range.lower {
+            Bound::Included(value) => {
+                range_expressions.push(IndexRangeExpression::Gte(BUCKET_FIELD.clone(), value))
+            },
+            Bound::Excluded(value) => {
+                range_expressions.push(IndexRangeExpression::Gt(BUCKET_FIELD.clone(), value))
+            },
+            Bound::Unbounded => {},
+        }
+        match range.upper {
+            Bound::Included(value) => {
+                range_expressions.push(IndexRangeExpression::Lte(BUCKET_FIELD.clone(), value))
+            },
+            Bound::Excluded(value) => {
+                range_expressions.push(IndexRangeExpression::Lt(BUCKET_FIELD.clone(), value))
+            },
+            Bound::Unbounded => {},
+        }
+        let mut query_stream =
+            self.bucket_query(table_id.tablet_id, &sketch_name, range_expressions)?;
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            let sketch_bucket = SketchBucket::try_from(document.into_value().into_value())?;
+            if !sketch_bucket.matches(sketch, bucket_field) {
+                continue;
+            }
+            merged.merge(&sketch_bucket.sketch)?;
+            count += sketch_bucket.count;
+        }
+        Ok((count, merged))
+    }
+
+    /// The approximate number of distinct values of a distinct sketch's field
+    /// in the documents in `range`.
+    pub async fn distinct_count(
+        &mut self,
+        table_name: &TableName,
+        sketch_name: &str,
+        range: SketchRange,
+    ) -> anyhow::Result<u64> {
+        let (count, sketch) = self.merge(table_name, sketch_name, range).await?;
+        let Sketch::Distinct(hll) = sketch else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "WrongSketchType",
+                format!("Sketch {sketch_name:?} on {table_name} isn't a distinct count sketch"),
+            ));
+        };
+        // Deleted values stay in the HyperLogLog, but there can't be more
+        // distinct values than documents.
+        Ok((hll.estimate().round() as u64).min(count))
+    }
+
+    /// The approximate value at `quantile`, from 0 to 1, of a quantile
+    /// sketch's field in the documents in `range`. None if they have no
+    /// numeric values.
+    pub async fn quantile(
+        &mut self,
+        table_name: &TableName,
+        sketch_name: &str,
+        quantile: f64,
+        range: SketchRange,
+    ) -> anyhow::Result<Option<f64>> {
+        anyhow::ensure!(
+            (0.0..=1.0).contains(&quantile),
+            ErrorMetadata::bad_request(
+                "InvalidQuantile",
+                format!("Quantile must be between 0 and 1, not {quantile}"),
+            )
+        );
+        let (_, sketch) = self.merge(table_name, sketch_name, range).await?;
+        let Sketch::Quantile(sketch) = sketch else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "WrongSketchType",
+                format!("Sketch {sketch_name:?} on {table_name} isn't a quantile sketch"),
+            ));
+        };
+        Ok(sketch.quantile(quantile))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use common::{
+        bootstrap_model::index::IndexMetadata,
+        db_schema,
+        schemas::{
+            DocumentSchema,
+            IndexSchema,
+            SketchKind,
+            SketchSchema,
+        },
+        types::TableName,
+    };
+    use keybroker::Identity;
+    use runtime::testing::TestRuntime;
+    use value::{
+        assert_obj,
+        ConvexValue,
+        TableNamespace,
+    };
+
+    use super::{
+        SketchModel,
+        SketchRange,
+        SketchesTable,
+        SKETCHES_TABLE,
+    };
+    use crate::{
+        defaults::SystemTable,
+        test_helpers::new_test_database,
+        IndexModel,
+        SchemaModel,
+        UserFacingModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_sketches(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = new_test_database(rt.clone()).await;
+        let namespace = TableNamespace::test_user();
+        let mut tx = db.begin(Identity::system()).await?;
+        tx.create_system_table_testing(namespace, &SKETCHES_TABLE, None)
+            .await?;
+        for index in SketchesTable.indexes() {
+            IndexModel::new(&mut tx)
+                .add_system_index(
+                    namespace,
+                    IndexMetadata::new_enabled(index.name, index.fields),
+                )
+                .await?;
+        }
+        let requests: TableName = "requests".parse()?;
+        let mut db_schema = db_schema!("requests" => DocumentSchema::Any);
+        let table = db_schema.tables.get_mut(&requests).unwrap();
+        table.indexes.insert(
+            "by_route".parse()?,
+            IndexSchema {
+                index_descriptor: "by_route".parse()?,
+                fields: vec!["route".parse()?].try_into()?,
+            },
+        );
+        table.sketches.insert(
+            "users".parse()?,
+            SketchSchema {
+                index: "by_route".parse()?,
+                field: "userId".parse()?,
+                kind: SketchKind::Distinct,
+            },
+        );
+        table.sketches.insert(
+            "latency".parse()?,
+            SketchSchema {
+                index: "by_route".parse()?,
+                field: "latencyMs".parse()?,
+                kind: SketchKind::Quantile,
+            },
+        );
+        let mut model = SchemaModel::new_root_for_test(&mut tx);
+        let (schema_id, _state) = model.submit_pending(db_schema).await?;
+        model.mark_validated(schema_id).await?;
+        model.mark_active(schema_id).await?;
+
+        let mut slowest_a = None;
+        for i in 1..=100 {
+            let id = UserFacingModel::new_root_for_test(&mut tx)
+                .insert(
+                    requests.clone(),
+                    assert_obj!("route" => "a", "userId" => i % 10, "latencyMs" => i as f64),
+                )
+                .await?;
+            slowest_a = Some(id);
+        }
+        let mut b_ids = vec![];
+        for i in 0..5 {
+            b_ids.push(
+                UserFacingModel::new_root_for_test(&mut tx)
+                    .insert(
+                        requests.clone(),
+                        assert_obj!("route" => "b", "userId" => i + 100, "latencyMs" => 1000.0),
+                    )
+                    .await?,
+            );
+        }
+        db.commit(tx).await?;
+
+        let route = |route: &str| -> anyhow::Result<SketchRange> {
+            let value = ConvexValue::try_from(route)?;
+            Ok(SketchRange {
+                lower: Bound::Included(value.clone()),
+                upper: Bound::Included(value),
+            })
+        };
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = SketchModel::new(&mut tx, namespace);
+        assert_eq!(
+            model
+                .distinct_count(&requests, "users", SketchRange::all())
+                .await?,
+            15
+        );
+        assert_eq!(
+            model
+                .distinct_count(&requests, "users", route("a")?)
+                .await?,
+            10
+        );
+        let median = model
+            .quantile(&requests, "latency", 0.5, route("a")?)
+            .await?
+            .unwrap();
+        assert!((median - 50.0).abs() <= 0.5, "{median}");
+        let after_a = SketchRange {
+            lower: Bound::Excluded(ConvexValue::try_from("a")?),
+            upper: Bound::Unbounded,
+        };
+        let median = model
+            .quantile(&requests, "latency", 0.5, after_a)
+            .await?
+            .unwrap();
+        assert!((median - 1000.0).abs() <= 10.0, "{median}");
+        let error = model
+            .quantile(&requests, "users", 0.5, SketchRange::all())
+            .await
+            .unwrap_err();
+        assert!(format!("{error}").contains("isn't a quantile sketch"));
+        assert!(model
+            .distinct_count(&requests, "missing", SketchRange::all())
+            .await
+            .is_err());
+
+        // Updates move values between buckets, and deletes remove them.
+        UserFacingModel::new_root_for_test(&mut tx)
+            .patch(
+                slowest_a.unwrap(),
+                assert_obj!("latencyMs" => 10_000.0).into(),
+            )
+            .await?;
+        for id in b_ids {
+            UserFacingModel::new_root_for_test(&mut tx)
+                .delete(id)
+                .await?;
+        }
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut model = SketchModel::new(&mut tx, namespace);
+        let max = model
+            .quantile(&requests, "latency", 1.0, route("a")?)
+            .await?
+            .unwrap();
+        assert!((max - 10_000.0).abs() <= 100.0, "{max}");
+        assert_eq!(
+            model
+                .quantile(&requests, "latency", 0.5, route("b")?)
+                .await?,
+            None
+        );
+        assert_eq!(
+            model
+                .distinct_count(&requests, "users", route("b")?)
+                .await?,
+            0
+        );
+        Ok(())
+    }
+}
@@ -48,6 +48,7 @@ use crate::{
         TableUpdateMode,
     },
     table_summary::TableSummarySnapshot,
+    table_write_rate::TableWriteRate,
     transaction::TableCountSnapshot,
     TableRegistry,
     TableSummary,
@@ -68,11 +69,12 @@ pub struct SnapshotManager<RT: Runtime> {
 
 #[derive(Clone)]
 /// This is a wrapper on [TableSummarySnapshot] that is filtered to tables that
-/// exist and tracks the user document and size counts.
+/// exist and tracks the user document and size counts, and recent write rates.
 pub struct TableSummaries {
     pub tables: OrdMap<TabletId, TableSummary>,
     pub num_user_documents: usize,
     pub user_size: usize,
+    pub write_rates: OrdMap<TabletId, TableWriteRate>,
 }
 
 #[async_trait]
@@ -84,6 +86,22 @@ impl TableCountSnapshot for TableSummaries {
             .map_or(0, |summary| summary.num_values() as u64);
         Ok(count)
     }
+
+    async fn total_size(&self, table: TabletId) -> anyhow::Result<u64> {
+        let size = self
+            .tables
+            .get(&table)
+            .map_or(0, |summary| summary.total_size() as u64);
+        Ok(size)
+    }
+
+    async fn writes_per_minute(&self, table: TabletId, ts: Timestamp) -> anyhow::Result<f64> {
+        let rate = self
+            .write_rates
+            .get(&table)
+            .map_or(0.0, |rate| rate.writes_per_minute(ts));
+        Ok(rate)
+    }
 }
 
 impl TableSummaries {
@@ -110,6 +128,7 @@ impl TableSummaries {
             tables,
             num_user_documents,
             user_size,
+            write_rates: OrdMap::new(),
         }
     }
 
@@ -127,6 +146,7 @@ impl TableSummaries {
         new: Option<&ResolvedDocument>,
         table_update: Option<&TableUpdate>,
         table_mapping: &TableMapping,
+        commit_ts: Timestamp,
     ) -> anyhow::Result<()> {
         let mut table_summary = self
             .tables
@@ -157,11 +177,23 @@ impl TableSummaries {
                         .is_none());
                 },
                 TableUpdateMode::Activate => {},
+                // The table keeps its tablet, so its summary is unchanged.
+                TableUpdateMode::Rename | TableUpdateMode::Archive => {},
                 TableUpdateMode::Drop => {
                     self.tables.remove(&table_id_and_number.tablet_id);
+                    self.write_rates.remove(&table_id_and_number.tablet_id);
                 },
             }
         }
+        if old.is_some() || new.is_some() {
+            let mut write_rate = self
+                .write_rates
+                .get(&document_id.tablet_id)
+                .cloned()
+                .unwrap_or_default();
+            write_rate.record(commit_ts);
+            self.write_rates.insert(document_id.tablet_id, write_rate);
+        }
         let new_info_num_values = table_summary.num_values();
         let new_info_total_size = table_summary.total_size();
         match self.tables.insert(document_id.tablet_id, table_summary) {
@@ -214,6 +246,7 @@ impl<RT: Runtime> Snapshot<RT> {
                 insertion,
                 table_update.as_ref(),
                 self.table_registry.table_mapping(),
+                commit_ts,
             )
             .context("Table summaries update failed")?;
 
@@ -15,6 +15,10 @@ use common::{
     bootstrap_model::index::database_index::IndexedFields,
     document::PackedDocument,
     errors::report_error,
+    knobs::{
+        SUBSCRIPTIONS_PARALLEL_INVALIDATION_MIN_SUBSCRIBERS,
+        SUBSCRIPTIONS_SHARDS,
+    },
     runtime::{
         Runtime,
         SpawnHandle,
@@ -196,6 +200,10 @@ pub struct SubscriptionManager {
 
     sender: mpsc::Sender<SubscriptionRequest>,
     persistence_version: PersistenceVersion,
+
+    // Match writes against the shards of `subscriptions` on separate threads
+    // once there are at least this many subscribers.
+    parallel_min_subscribers: usize,
 }
 
 struct Subscriber {
@@ -215,6 +223,17 @@ impl SubscriptionManager {
         Self::new(tx, log_owner, PersistenceVersion::V5)
     }
 
+    /// A subscription manager with `num_shards` shards that always matches
+    /// writes against them in parallel.
+    #[allow(unused)]
+    #[cfg(any(test, feature = "testing"))]
+    pub fn new_for_testing_with_shards(num_shards: usize) -> Self {
+        let mut manager = Self::new_for_testing();
+        manager.subscriptions = SubscriptionMap::new(num_shards);
+        manager.parallel_min_subscribers = 0;
+        manager
+    }
+
     fn new(
         tx: mpsc::Sender<SubscriptionRequest>,
         log: LogOwner,
@@ -223,12 +242,13 @@ impl SubscriptionManager {
         let processed_ts = log.max_ts();
         Self {
             subscribers: Slab::new(),
-            subscriptions: SubscriptionMap::new(),
+            subscriptions: SubscriptionMap::new(*SUBSCRIPTIONS_SHARDS),
             next_seq: 0,
             log,
             processed_ts,
             sender: tx,
             persistence_version,
+            parallel_min_subscribers: *SUBSCRIPTIONS_PARALLEL_INVALIDATION_MIN_SUBSCRIBERS,
         }
     }
 
@@ -282,18 +302,20 @@ impl SubscriptionManager {
 
         let mut to_notify = BTreeSet::new();
         self.log.for_each(from_ts, next_ts, |_, writes| {
+            let mut documents = vec![];
             for (_, document_change) in writes {
                 // We're applying a mutation to the document so if it already exists
                 // we need to remove it before writing the new version.
                 if let Some(ref old_document) = document_change.old_document {
-                    self.overlapping(old_document, &mut to_notify, self.persistence_version);
+                    documents.push(old_document);
                 }
                 // If we're doing anything other than deleting the document then
                 // we'll also need to insert a new value.
                 if let Some(ref new_document) = document_change.new_document {
-                    self.overlapping(new_document, &mut to_notify, self.persistence_version);
+                    documents.push(new_document);
                 }
             }
+            self.overlapping_documents(&documents, &mut to_notify);
         })?;
 
         // First, do a pass where we advance all of the valid subscriptions.
@@ -331,21 +353,69 @@ impl SubscriptionManager {
         self.overlapping(document, to_notify, persistence_version);
     }
 
+    #[allow(unused)]
+    #[cfg(any(test, feature = "testing"))]
+    pub fn overlapping_documents_for_testing(
+        &self,
+        documents: &[&PackedDocument],
+        to_notify: &mut BTreeSet<SubscriberId>,
+    ) {
+        self.overlapping_documents(documents, to_notify);
+    }
+
     fn overlapping(
         &self,
         document: &PackedDocument,
         to_notify: &mut BTreeSet<SubscriberId>,
         persistence_version: PersistenceVersion,
     ) {
-        for (index, (fields, range_map)) in &self.subscriptions.indexed {
-            if *index.table() == document.id().tablet_id {
-                let index_key = document.index_key(fields, persistence_version);
-                for subscriber_id in range_map.query(index_key.into_bytes()) {
-                    to_notify.insert(subscriber_id);
-                }
+        for shard in &self.subscriptions.shards {
+            shard.overlapping(document, to_notify, persistence_version);
+        }
+    }
+
+    /// Find the subscribers whose reads overlap any of `documents`, matching
+    /// each shard on its own thread if there are enough subscribers for it to
+    /// be worth it.
+    fn overlapping_documents(
+        &self,
+        documents: &[&PackedDocument],
+        to_notify: &mut BTreeSet<SubscriberId>,
+    ) {
+        let shards = &self.subscriptions.shards;
+        let persistence_version = self.persistence_version;
+        let overlapping_shard = |shard: &SubscriptionShard, to_notify: &mut BTreeSet<_>| {
+            for document in documents {
+                shard.overlapping(document, to_notify, persistence_version);
+            }
+        };
+        if shards.len() == 1 || self.subscribers.len() < self.parallel_min_subscribers {
+            for shard in shards {
+                overlapping_shard(shard, to_notify);
             }
+            return;
         }
-        self.subscriptions.search.add_matches(document, to_notify);
+        let overlapping_shard = &overlapping_shard;
+        std::thread::scope(|scope| {
+            // Match the first shard on this thread while the others run.
+            let handles: Vec<_> = shards[1..]
+                .iter()
+                .map(|shard| {
+                    scope.spawn(move || {
+                        let mut shard_to_notify = BTreeSet::new();
+                        overlapping_shard(shard, &mut shard_to_notify);
+                        shard_to_notify
+                    })
+                })
+                .collect();
+            overlapping_shard(&shards[0], to_notify);
+            for handle in handles {
+                match handle.join() {
+                    Ok(shard_to_notify) => to_notify.extend(shard_to_notify),
+                    Err(e) => std::panic::resume_unwind(e),
+                }
+            }
+        });
     }
 
     fn get_subscriber(&self, key: SubscriptionKey) -> Option<&Subscriber> {
@@ -431,12 +501,45 @@ impl Drop for Subscription {
 }
 
 /// Tracks every subscriber for a given read-set.
+///
+/// Matching a write against an index checks every subscriber to that index, so
+/// a hot table with many subscribers would serialize invalidation on a single
+/// core. Instead, subscribers are split across shards by their ID, and each
+/// shard holds the index ranges and search queries for its subscribers, so the
+/// shards can be matched in parallel.
 struct SubscriptionMap {
+    shards: Vec<SubscriptionShard>,
+}
+
+impl SubscriptionMap {
+    fn new(num_shards: usize) -> Self {
+        Self {
+            shards: (0..num_shards.max(1))
+                .map(|_| SubscriptionShard::new())
+                .collect(),
+        }
+    }
+
+    fn shard_mut(&mut self, id: SubscriberId) -> &mut SubscriptionShard {
+        let num_shards = self.shards.len();
+        &mut self.shards[id % num_shards]
+    }
+
+    fn insert(&mut self, id: SubscriberId, reads: &ReadSet) {
+        self.shard_mut(id).insert(id, reads);
+    }
+
+    fn remove(&mut self, id: SubscriberId, reads: &ReadSet) {
+        self.shard_mut(id).remove(id, reads);
+    }
+}
+
+struct SubscriptionShard {
     indexed: BTreeMap<TabletIndexName, (IndexedFields, IntervalMap<SubscriberId>)>,
     search: TextSearchSubscriptions,
 }
 
-impl SubscriptionMap {
+impl SubscriptionShard {
     fn new() -> Self {
         Self {
             indexed: BTreeMap::new(),
@@ -444,6 +547,23 @@ impl SubscriptionMap {
         }
     }
 
+    fn overlapping(
+        &self,
+        document: &PackedDocument,
+        to_notify: &mut BTreeSet<SubscriberId>,
+        persistence_version: PersistenceVersion,
+    ) {
+        for (index, (fields, range_map)) in &self.indexed {
+            if *index.table() == document.id().tablet_id {
+                let index_key = document.index_key(fields, persistence_version);
+                for subscriber_id in range_map.query(index_key.into_bytes()) {
+                    to_notify.insert(subscriber_id);
+                }
+            }
+        }
+        self.search.add_matches(document, to_notify);
+    }
+
     fn insert(&mut self, id: SubscriberId, reads: &ReadSet) {
         for (index, index_reads) in reads.iter_indexed() {
             let (_, interval_map) = self
@@ -487,11 +607,16 @@ mod tests {
     };
 
     use common::{
+        assert_obj,
         document::{
             CreationTime,
             PackedDocument,
             ResolvedDocument,
         },
+        interval::{
+            BinaryKey,
+            Interval,
+        },
         testing::TestIdGenerator,
         types::{
             GenericIndexName,
@@ -523,6 +648,7 @@ mod tests {
     };
     use sync_types::Timestamp;
     use value::{
+        values_to_bytes,
         ConvexObject,
         ConvexString,
         ConvexValue,
@@ -539,6 +665,7 @@ mod tests {
         subscription::SubscriptionManager,
         ReadSet,
         Token,
+        TransactionReadSet,
     };
 
     fn tokens_only(
@@ -733,6 +860,66 @@ mod tests {
         ))
     }
 
+    fn create_indexed_token(index_name: TabletIndexName, key: i64) -> anyhow::Result<Token> {
+        let mut reads = TransactionReadSet::new();
+        reads.record_indexed_directly(
+            index_name,
+            vec![FieldPath::from_str("k")?].try_into()?,
+            Interval::prefix(BinaryKey::from(values_to_bytes(&[Some(
+                ConvexValue::from(key),
+            )]))),
+        )?;
+        Ok(Token::new_for_testing(
+            reads.into_read_set(),
+            Timestamp::MIN,
+        ))
+    }
+
+    #[test]
+    fn sharded_indexed_subscriptions_are_notified() -> anyhow::Result<()> {
+        let mut id_generator = TestIdGenerator::new();
+        let table_id = id_generator.user_table_id(&id_generator.generate_table_name());
+        let index_name =
+            TabletIndexName::new(table_id.tablet_id, IndexDescriptor::from_str("by_k")?)?;
+
+        let mut subscription_manager = SubscriptionManager::new_for_testing_with_shards(4);
+        let mut subscriptions = vec![];
+        for i in 0..20 {
+            let token = create_indexed_token(index_name.clone(), i % 10)?;
+            subscriptions.push((i % 10, subscription_manager.subscribe(token)?));
+        }
+
+        let documents = [3i64, 7]
+            .into_iter()
+            .map(|key| {
+                let id = ResolvedDocumentId::new(
+                    table_id.tablet_id,
+                    DeveloperDocumentId::new(
+                        table_id.table_number,
+                        id_generator.generate_internal(),
+                    ),
+                );
+                Ok(pack(ResolvedDocument::new(
+                    id,
+                    CreationTime::ONE,
+                    assert_obj!("k" => key),
+                )?))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut to_notify = BTreeSet::new();
+        subscription_manager
+            .overlapping_documents_for_testing(&documents.iter().collect_vec(), &mut to_notify);
+
+        let expected = subscriptions
+            .iter()
+            .filter(|(key, _)| *key == 3 || *key == 7)
+            .map(|(_, subscription)| *subscription.id())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(expected.len(), 4);
+        assert_eq!(to_notify, expected);
+        Ok(())
+    }
+
     #[test]
     fn add_remove_two_identical_search_subscriptions_different_subscribers() -> anyhow::Result<()> {
         let mut id_generator = TestIdGenerator::new();
@@ -129,9 +129,16 @@ impl TableRegistry {
                         tablet_id,
                         table_number: old_metadata.number,
                     };
+                    let renamed = old_metadata.name != new_metadata.name;
                     anyhow::ensure!(
-                        old_metadata.name == new_metadata.name,
-                        "Table renames currently unsupported: {old_metadata:?} => {new_metadata:?}"
+                        !renamed
+                            || (new_metadata.is_active()
+                                && matches!(
+                                    old_metadata.state,
+                                    TableState::Active | TableState::Archived
+                                )),
+                        "Tables can only be renamed while active or when restored from archive: \
+                         {old_metadata:?} => {new_metadata:?}"
                     );
                     anyhow::ensure!(
                         old_metadata.number == new_metadata.number,
@@ -168,6 +175,53 @@ impl TableRegistry {
                             state: new_metadata.state,
                             mode: TableUpdateMode::Activate,
                         })
+                    } else if matches!(old_metadata.state, TableState::Archived)
+                        && new_metadata.is_active()
+                    {
+                        // Table restored from archive, possibly under a new name.
+                        if self.table_exists(new_metadata.namespace, &new_metadata.name) {
+                            anyhow::bail!("Tried to restore duplicate table {new_value}");
+                        }
+                        self.validate_table_number(new_metadata.namespace, new_metadata.number)?;
+                        Some(TableUpdate {
+                            namespace: old_metadata.namespace,
+                            table_id_and_number: old_table_id_and_number,
+                            table_name: new_metadata.name,
+                            state: new_metadata.state,
+                            mode: TableUpdateMode::Activate,
+                        })
+                    } else if old_metadata.is_active()
+                        && matches!(new_metadata.state, TableState::Archived)
+                    {
+                        anyhow::ensure!(
+                            !old_metadata.name.is_system(),
+                            "cannot archive system table {}",
+                            old_metadata.name
+                        );
+                        Some(TableUpdate {
+                            namespace: old_metadata.namespace,
+                            table_id_and_number: old_table_id_and_number,
+                            table_name: old_metadata.name,
+                            state: new_metadata.state,
+                            mode: TableUpdateMode::Archive,
+                        })
+                    } else if renamed {
+                        anyhow::ensure!(
+                            !old_metadata.name.is_system() && !new_metadata.name.is_system(),
+                            "cannot rename system table {} to {}",
+                            old_metadata.name,
+                            new_metadata.name
+                        );
+                        if self.table_exists(new_metadata.namespace, &new_metadata.name) {
+                            anyhow::bail!("Tried to rename to duplicate table {new_value}");
+                        }
+                        Some(TableUpdate {
+                            namespace: old_metadata.namespace,
+                            table_id_and_number: old_table_id_and_number,
+                            table_name: new_metadata.name,
+                            state: new_metadata.state,
+                            mode: TableUpdateMode::Rename,
+                        })
                     } else {
                         // Allow updating other fields on TableMetadata.
                         None
@@ -317,6 +371,8 @@ pub(crate) enum TableUpdateMode {
     Create,
     Activate,
     Drop,
+    Rename,
+    Archive,
 }
 
 pub(crate) struct Update<'a> {
@@ -358,6 +414,30 @@ impl<'a> Update<'a> {
                         .table_mapping
                         .remove(table_id_and_number.tablet_id);
                 },
+                TableUpdateMode::Rename => {
+                    self.metadata
+                        .table_mapping
+                        .remove(table_id_and_number.tablet_id);
+                    self.metadata.table_mapping.insert(
+                        table_id_and_number.tablet_id,
+                        *namespace,
+                        table_id_and_number.table_number,
+                        table_name.clone(),
+                    );
+                },
+                TableUpdateMode::Archive => {
+                    // Keep the tablet's mapping so its documents and indexes
+                    // can still be resolved, but free up its name.
+                    self.metadata
+                        .table_mapping
+                        .remove(table_id_and_number.tablet_id);
+                    self.metadata.table_mapping.insert_tablet(
+                        table_id_and_number.tablet_id,
+                        *namespace,
+                        table_id_and_number.table_number,
+                        table_name.clone(),
+                    );
+                },
             }
             self.metadata
                 .tablet_states
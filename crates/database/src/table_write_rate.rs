@@ -0,0 +1,96 @@
+//! Write rates of tables, counted by the committer as it applies writes to
+//! the in-memory snapshot.
+//!
+//! Rates are only kept in memory, so they start over from zero when the
+//! backend restarts.
+
+use std::collections::VecDeque;
+
+use common::types::Timestamp;
+
+/// How far back write rates are averaged over, in minutes.
+pub const WRITE_RATE_WINDOW_MINUTES: u64 = 10;
+
+const NANOS_PER_MINUTE: u64 = 60 * 1_000_000_000;
+
+/// Recent writes to a table, counted in one-minute buckets by commit
+/// timestamp.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableWriteRate {
+    // (minute since the epoch, writes committed in that minute), oldest first.
+    buckets: VecDeque<(u64, u64)>,
+}
+
+impl TableWriteRate {
+    pub fn record(&mut self, commit_ts: Timestamp) {
+        let minute = minute_of(commit_ts);
+        match self.buckets.back_mut() {
+            // Commit timestamps only increase, so this is the current minute.
+            Some((last_minute, writes)) if *last_minute >= minute => *writes += 1,
+            _ => self.buckets.push_back((minute, 1)),
+        }
+        while let Some((oldest_minute, _)) = self.buckets.front()
+            && oldest_minute + WRITE_RATE_WINDOW_MINUTES <= minute
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Average writes per minute over the `WRITE_RATE_WINDOW_MINUTES` minutes
+    /// up to `ts`.
+    pub fn writes_per_minute(&self, ts: Timestamp) -> f64 {
+        let minute = minute_of(ts);
+        let writes: u64 = self
+            .buckets
+            .iter()
+            .filter(|(bucket_minute, _)| {
+                *bucket_minute <= minute && bucket_minute + WRITE_RATE_WINDOW_MINUTES > minute
+            })
+            .map(|(_, writes)| writes)
+            .sum();
+        writes as f64 / WRITE_RATE_WINDOW_MINUTES as f64
+    }
+}
+
+fn minute_of(ts: Timestamp) -> u64 {
+    u64::from(ts) / NANOS_PER_MINUTE
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::types::Timestamp;
+
+    use super::{
+        TableWriteRate,
+        WRITE_RATE_WINDOW_MINUTES,
+    };
+
+    #[test]
+    fn test_writes_per_minute() -> anyhow::Result<()> {
+        let start = Timestamp::try_from(Duration::from_secs(3600).as_nanos() as u64)?;
+        let mut rate = TableWriteRate::default();
+        assert_eq!(rate.writes_per_minute(start), 0.0);
+        for _ in 0..20 {
+            rate.record(start);
+        }
+        let later = start.add(Duration::from_secs(90))?;
+        for _ in 0..10 {
+            rate.record(later);
+        }
+        assert_eq!(
+            rate.writes_per_minute(later),
+            30.0 / WRITE_RATE_WINDOW_MINUTES as f64
+        );
+        // The first writes fall out of the window before the later ones.
+        let after_window = start.add(Duration::from_secs(60 * WRITE_RATE_WINDOW_MINUTES))?;
+        assert_eq!(
+            rate.writes_per_minute(after_window),
+            10.0 / WRITE_RATE_WINDOW_MINUTES as f64
+        );
+        let much_later = start.add(Duration::from_secs(3600))?;
+        assert_eq!(rate.writes_per_minute(much_later), 0.0);
+        Ok(())
+    }
+}
@@ -10,6 +10,7 @@ use common::{
     testing::TestPersistence,
 };
 use events::testing::TestUsageEventLogger;
+use keybroker::KeyBroker;
 use search::{
     searcher::{
         InProcessSearcher,
@@ -91,6 +92,7 @@ impl<RT: Runtime> DbFixtures<RT> {
         )
         .await?;
         db.set_search_storage(search_storage.clone());
+        db.set_field_encryptor(Arc::new(KeyBroker::dev().field_encryptor()));
         if bootstrap_search_and_vector_indexes {
             db.start_search_and_vector_bootstrap(PauseClient::new())
                 .into_join_future()
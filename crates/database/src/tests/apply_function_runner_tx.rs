@@ -267,6 +267,45 @@ async fn test_apply_function_runner_tx_merge_existing_writes(
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_apply_function_runner_tx_rewrite_existing_writes(
+    rt: TestRuntime,
+) -> anyhow::Result<()> {
+    let db = new_test_database(rt).await;
+    let mut backend_tx = db.begin_system().await?;
+    // Make writes before initializing funrun transaction
+    let id = UserFacingModel::new_root_for_test(&mut backend_tx)
+        .insert("table".parse()?, obj!("field" => "value")?)
+        .await?;
+    let begin_timestamp = backend_tx.begin_timestamp();
+
+    // Create a new tx as though it were in funrun
+    let mut function_runner_tx = db
+        .begin_with_ts(
+            Identity::system(),
+            *begin_timestamp,
+            FunctionUsageTracker::new(),
+        )
+        .await?;
+    let (updates, generated_ids) = backend_tx.writes().clone().into_updates_and_generated_ids();
+    function_runner_tx.merge_writes(updates, generated_ids)?;
+
+    // Write to the same document again as if in funrun
+    UserFacingModel::new_root_for_test(&mut function_runner_tx)
+        .replace(id, obj!("field" => "value2")?)
+        .await?;
+
+    // Apply the writes to the backend_tx
+    let (updates, generated_ids) = function_runner_tx
+        .writes
+        .clone()
+        .into_updates_and_generated_ids();
+    backend_tx.merge_writes(updates, generated_ids)?;
+
+    assert_transaction_writes_match(&backend_tx, &function_runner_tx)?;
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_apply_function_runner_tx_merge_existing_writes_bad(
     rt: TestRuntime,
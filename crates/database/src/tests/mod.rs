@@ -36,6 +36,7 @@ use common::{
     query::{
         Expression,
         FullTableScan,
+        GeoQuery,
         IndexRange,
         IndexRangeExpression,
         Order,
@@ -55,6 +56,7 @@ use common::{
         TableDefinition,
         MAX_INDEXES_PER_TABLE,
     },
+    testing::TestPersistence,
     types::{
         unchecked_repeatable_ts,
         IndexDescriptor,
@@ -78,13 +80,16 @@ use must_let::must_let;
 use pretty_assertions::assert_eq;
 use proptest::prelude::*;
 use runtime::testing::TestRuntime;
+use serde_json::Value as JsonValue;
 use sync_types::backoff::Backoff;
 use value::{
     array,
     assert_val,
+    geo::GeoRegion,
     id_v6::DeveloperDocumentId,
     val,
     FieldPath,
+    GeoPoint,
     ResolvedDocumentId,
     TableMapping,
     TableNamespace,
@@ -92,6 +97,7 @@ use value::{
 };
 
 use crate::{
+    backend_state::BACKEND_STATE_TABLE,
     index_worker::{
         IndexSelector,
         IndexWriter,
@@ -116,6 +122,8 @@ use crate::{
     ImportFacingModel,
     IndexModel,
     IndexWorker,
+    ReplicaWriter,
+    ReplicationLogPage,
     SchemaModel,
     SystemMetadataModel,
     TableModel,
@@ -125,6 +133,7 @@ use crate::{
 };
 
 mod randomized_search_tests;
+mod simulation;
 mod streaming_export_tests;
 mod usage_tracking;
 mod vector_tests;
@@ -160,6 +169,74 @@ async fn test_load_database(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn replicate<RT: Runtime>(
+    db: &Database<RT>,
+    replica: &mut ReplicaWriter<RT>,
+) -> anyhow::Result<()> {
+    loop {
+        let page = db
+            .replication_log(Identity::system(), replica.cursor(), 1)
+            .await?;
+        let has_more = page.has_more;
+        assert_eq!(
+            ReplicationLogPage::try_from(JsonValue::try_from(page.clone())?)?,
+            page
+        );
+        replica.apply(page).await?;
+        if !has_more {
+            return Ok(());
+        }
+    }
+}
+
+#[convex_macro::test_runtime]
+async fn test_replicate_to_standby(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let id1 = TestFacingModel::new(&mut tx)
+        .insert(&"table1".parse()?, assert_obj!("key" => 1))
+        .await?;
+    let id2 = TestFacingModel::new(&mut tx)
+        .insert(&"table1".parse()?, assert_obj!("key" => 2))
+        .await?;
+    db.commit(tx).await?;
+
+    let standby_tp = Arc::new(TestPersistence::new());
+    let mut replica = ReplicaWriter::new(rt.clone(), standby_tp.clone()).await?;
+    replicate(&db, &mut replica).await?;
+
+    // Changes after the first sync, including a new table, are picked up by a
+    // standby that restarted in between.
+    let mut tx = db.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(id1.into())
+        .await?;
+    let id3 = TestFacingModel::new(&mut tx)
+        .insert(&"table2".parse()?, assert_obj!("key" => 3))
+        .await?;
+    db.commit(tx).await?;
+    let mut replica = ReplicaWriter::new(rt.clone(), standby_tp.clone()).await?;
+    replicate(&db, &mut replica).await?;
+
+    // Promote the standby.
+    let DbFixtures { db: standby, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(standby_tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let mut tx = standby.begin(Identity::system()).await?;
+    assert!(tx.get(id1).await?.is_none());
+    assert_eq!(
+        tx.get(id2).await?.unwrap().value().0.get("key"),
+        Some(&val!(2))
+    );
+    assert!(tx.get(id3).await?.is_some());
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_load_from_table_summary_snapshot(rt: TestRuntime) -> anyhow::Result<()> {
     let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
@@ -230,6 +307,73 @@ async fn test_load_from_table_summary_snapshot(rt: TestRuntime) -> anyhow::Resul
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_table_statistics(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let table: TableName = "table".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    for i in 0..3 {
+        TestFacingModel::new(&mut tx)
+            .insert(&table, assert_obj!("key" => i))
+            .await?;
+    }
+    db.commit(tx).await?;
+    let total_bytes = db.latest_snapshot()?.table_summary(&table).total_size() as u64;
+
+    let mut tx = db.begin(Identity::system()).await?;
+    let stats = TableModel::new(&mut tx)
+        .statistics(TableNamespace::test_user(), &table)
+        .await?;
+    assert_eq!(stats.document_count, 3);
+    assert_eq!(stats.total_bytes, total_bytes);
+    assert!(stats.writes_per_minute > 0.0);
+    assert!(stats.indexes.is_empty());
+
+    let missing = TableModel::new(&mut tx)
+        .statistics(TableNamespace::test_user(), &"missing".parse()?)
+        .await?;
+    assert_eq!(missing.document_count, 0);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_historical_table_size(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let table: TableName = "table".parse()?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let replaced = TestFacingModel::new(&mut tx)
+        .insert_and_get(table.clone(), assert_obj!("key" => 0))
+        .await?;
+    let deleted = TestFacingModel::new(&mut tx)
+        .insert_and_get(table.clone(), assert_obj!("key" => 1))
+        .await?;
+    TestFacingModel::new(&mut tx)
+        .insert(&table, assert_obj!("key" => 2))
+        .await?;
+    db.commit(tx).await?;
+    let tablet_id = replaced.id().tablet_id;
+    assert_eq!(
+        db.historical_table_size(tablet_id, db.now_ts_for_reads())
+            .await?,
+        0
+    );
+
+    let mut tx = db.begin(Identity::system()).await?;
+    TestFacingModel::new(&mut tx)
+        .replace(replaced.id(), assert_obj!("key" => 10))
+        .await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(deleted.id().into())
+        .await?;
+    db.commit(tx).await?;
+    assert_eq!(
+        db.historical_table_size(tablet_id, db.now_ts_for_reads())
+            .await?,
+        (replaced.value().size() + deleted.value().size()) as u64
+    );
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
     let database = new_test_database(rt).await;
@@ -268,6 +412,12 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
             document_type: None,
+            encrypted_fields: Default::default(),
+            triggers: Default::default(),
+            track_updates: false,
+            soft_delete: None,
+            id_strategy: Default::default(),
+            sketches: Default::default(),
         },
     );
     let schema = DatabaseSchema {
@@ -324,6 +474,12 @@ async fn test_build_indexes(rt: TestRuntime) -> anyhow::Result<()> {
             search_indexes: BTreeMap::new(),
             vector_indexes: BTreeMap::new(),
             document_type: None,
+            encrypted_fields: Default::default(),
+            triggers: Default::default(),
+            track_updates: false,
+            soft_delete: None,
+            id_strategy: Default::default(),
+            sketches: Default::default(),
         },
     );
     let schema = DatabaseSchema {
@@ -856,6 +1012,69 @@ async fn test_query_index_range_half_bounded(rt: TestRuntime) -> anyhow::Result<
     .await
 }
 
+#[convex_macro::test_runtime]
+async fn test_query_geo(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures {
+        db: database, tp, ..
+    } = DbFixtures::new(&rt).await?;
+    let table_name: TableName = str::parse("places")?;
+    let namespace = TableNamespace::test_user();
+    let index_name = IndexName::new(table_name.clone(), "by_location".parse()?)?;
+    add_and_enable_index(
+        rt,
+        &database,
+        tp,
+        namespace,
+        &index_name,
+        vec![str::parse("location")?].try_into()?,
+    )
+    .await?;
+
+    // A point every ten degrees, plus a document that isn't a point.
+    let mut tx = database.begin(Identity::system()).await?;
+    let mut documents = vec![];
+    for latitude in -8..=8 {
+        for longitude in -17..=18 {
+            let point = GeoPoint::new(latitude as f64 * 10.0, longitude as f64 * 10.0)?;
+            let document = TestFacingModel::new(&mut tx)
+                .insert_and_get(table_name.clone(), assert_obj!("location" => point))
+                .await?;
+            documents.push(document);
+        }
+    }
+    TestFacingModel::new(&mut tx)
+        .insert(&table_name, assert_obj!("location" => "nowhere"))
+        .await?;
+    database.commit(tx).await?;
+
+    let regions = [
+        GeoRegion::radius(GeoPoint::new(20.0, 30.0)?, 2_500_000.0)?,
+        // Crosses the antimeridian.
+        GeoRegion::bounding_box(GeoPoint::new(-25.0, 165.0)?, GeoPoint::new(15.0, -155.0)?)?,
+    ];
+    for region in regions {
+        let expected: BTreeSet<_> = documents
+            .iter()
+            .filter(|document| {
+                let location = document.value().get("location").unwrap();
+                must_let!(let ConvexValue::GeoPoint(point) = location);
+                region.contains(point)
+            })
+            .map(|document| document.id())
+            .collect();
+        assert!(!expected.is_empty());
+        let query = Query::geo(GeoQuery {
+            index_name: index_name.clone(),
+            region,
+        });
+        let results = run_query(database.clone(), namespace, query).await?;
+        assert_eq!(results.len(), expected.len());
+        let actual: BTreeSet<_> = results.iter().map(|document| document.id()).collect();
+        assert_eq!(actual, expected);
+    }
+    Ok(())
+}
+
 proptest! {
     #![proptest_config(
             ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
@@ -2305,3 +2524,46 @@ async fn test_query_readset_empty_query(rt: TestRuntime) -> anyhow::Result<()> {
         .is_some());
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_user_writes_fail_while_read_only(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, tp, .. } = DbFixtures::new(&rt).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    tx.create_system_table_testing(TableNamespace::Global, &BACKEND_STATE_TABLE, None)
+        .await?;
+    let backend_state_id = SystemMetadataModel::new_global(&mut tx)
+        .insert(&BACKEND_STATE_TABLE, assert_obj!("state" => "read_only"))
+        .await?;
+    db.commit(tx).await?;
+
+    let insert_user_document = async |db: &Database<TestRuntime>| {
+        let mut tx = db.begin(Identity::system()).await?;
+        TestFacingModel::new(&mut tx)
+            .insert(&"table".parse()?, assert_obj!())
+            .await?;
+        db.commit(tx).await
+    };
+    let err = insert_user_document(&db).await.unwrap_err();
+    assert_eq!(err.short_msg(), "DeploymentReadOnly");
+
+    // The committer picks up the backend state when the database loads.
+    let DbFixtures { db, .. } = DbFixtures::new_with_args(
+        &rt,
+        DbFixturesArgs {
+            tp: Some(tp),
+            ..Default::default()
+        },
+    )
+    .await?;
+    let err = insert_user_document(&db).await.unwrap_err();
+    assert_eq!(err.short_msg(), "DeploymentReadOnly");
+
+    // System writes still go through, including turning off read-only mode.
+    let mut tx = db.begin(Identity::system()).await?;
+    SystemMetadataModel::new_global(&mut tx)
+        .replace(backend_state_id, assert_obj!("state" => "running"))
+        .await?;
+    db.commit(tx).await?;
+    insert_user_document(&db).await?;
+    Ok(())
+}
@@ -0,0 +1,440 @@
+//! Simulations of concurrent commits, subscriptions and snapshot reads, with
+//! the database crashing and restarting when persistence writes fail.
+//!
+//! Writers increment counters in read-modify-write transactions, so they
+//! conflict with each other, and record the timestamp of each increment that
+//! commits. An increment whose commit fails with anything but an OCC error
+//! may or may not have been written, so it's only known to be "ambiguous".
+//! Readers check the database against that history:
+//! - A subscription is never valid past a commit that wrote one of its reads,
+//!   and is invalidated soon after one.
+//! - Refreshing a token never skips over such a commit.
+//! - A snapshot read returns the counters as of its timestamp, or fails.
+//! - After a restart, every committed increment is there, plus some of the
+//!   ambiguous ones.
+
+use std::{
+    sync::Arc,
+    time::Duration,
+};
+
+use ::usage_tracking::FunctionUsageTracker;
+use anyhow::Context;
+use common::{
+    assert_obj,
+    persistence::Persistence,
+    runtime::Runtime,
+    testing::{
+        run_simulation,
+        FaultInjectingPersistence,
+        PersistenceFaults,
+        TestPersistence,
+    },
+    types::Timestamp,
+    value::ConvexValue,
+};
+use errors::ErrorMetadataAnyhowExt;
+use events::usage::NoOpUsageEventLogger;
+use futures::{
+    future,
+    FutureExt,
+    StreamExt,
+};
+use keybroker::Identity;
+use parking_lot::Mutex;
+use rand::Rng;
+use runtime::testing::TestRuntime;
+use search::searcher::SearcherStub;
+use value::ResolvedDocumentId;
+
+use crate::{
+    Database,
+    ShutdownSignal,
+    TestFacingModel,
+    Transaction,
+    UserFacingModel,
+};
+
+const NUM_SEEDS: u64 = 8;
+const NUM_COUNTERS: usize = 4;
+const NUM_WRITERS: usize = 4;
+const NUM_READERS: usize = 3;
+const OPS_PER_WORKER: usize = 25;
+
+/// Upper bound on how long a worker pauses between and within operations.
+/// Pausing while holding a transaction open is what makes them conflict.
+const MAX_PAUSE: Duration = Duration::from_millis(50);
+
+/// How long a subscription may take to be invalidated by a conflicting
+/// commit.
+const INVALIDATION_DEADLINE: Duration = Duration::from_secs(10);
+
+/// The database stops injecting faults after restarting this many times, so
+/// every simulation makes progress.
+const MAX_RESTARTS: usize = 3;
+
+#[derive(Default)]
+struct History {
+    // Timestamp and counter of each increment that committed.
+    committed: Vec<(Timestamp, usize)>,
+    // Number of increments of each counter that may or may not have committed.
+    ambiguous: [u64; NUM_COUNTERS],
+}
+
+impl History {
+    fn committed_between(
+        &self,
+        counters: &[usize],
+        after: Timestamp,
+        until: Timestamp,
+    ) -> Option<(Timestamp, usize)> {
+        self.committed
+            .iter()
+            .find(|(ts, counter)| *ts > after && *ts <= until && counters.contains(counter))
+            .copied()
+    }
+
+    fn committed_until(&self, counter: usize, until: Timestamp) -> u64 {
+        self.committed
+            .iter()
+            .filter(|(ts, c)| *c == counter && *ts <= until)
+            .count() as u64
+    }
+
+    /// Check the value of `counter` at `ts` against the history.
+    fn check(&self, counter: usize, ts: Timestamp, value: u64) -> anyhow::Result<()> {
+        let committed = self.committed_until(counter, ts);
+        anyhow::ensure!(
+            committed <= value && value <= committed + self.ambiguous[counter],
+            "Counter {counter} is {value} at {ts}, but {committed} increments committed and {} \
+             are ambiguous",
+            self.ambiguous[counter]
+        );
+        Ok(())
+    }
+}
+
+struct Simulation {
+    rt: TestRuntime,
+    tp: Arc<FaultInjectingPersistence<TestRuntime>>,
+    faults: PersistenceFaults,
+    db: Mutex<Database<TestRuntime>>,
+    // Incremented on every restart, so readers can tell if one happened while
+    // they were waiting on a subscription.
+    epoch: Mutex<usize>,
+    counters: Vec<ResolvedDocumentId>,
+    history: Mutex<History>,
+}
+
+impl Simulation {
+    async fn new(rt: TestRuntime, faults: PersistenceFaults) -> anyhow::Result<(Self, ShutdownRx)> {
+        let tp = Arc::new(FaultInjectingPersistence::new(
+            Arc::new(TestPersistence::new()),
+            rt.clone(),
+            PersistenceFaults::default(),
+        ));
+        let (shutdown_tx, shutdown_rx) = async_broadcast::broadcast(1);
+        let db = load_database(&rt, tp.clone(), ShutdownSignal::new(shutdown_tx)).await?;
+        let mut tx = db.begin(Identity::system()).await?;
+        let mut counters = vec![];
+        for _ in 0..NUM_COUNTERS {
+            counters.push(
+                TestFacingModel::new(&mut tx)
+                    .insert(&"counters".parse()?, assert_obj!("value" => 0))
+                    .await?,
+            );
+        }
+        db.commit(tx).await?;
+        tp.set_faults(faults);
+        let simulation = Self {
+            rt,
+            tp,
+            faults,
+            db: Mutex::new(db),
+            epoch: Mutex::new(0),
+            counters,
+            history: Mutex::new(History::default()),
+        };
+        Ok((simulation, shutdown_rx))
+    }
+
+    fn db(&self) -> Database<TestRuntime> {
+        self.db.lock().clone()
+    }
+
+    async fn pause(&self) {
+        let pause = self
+            .rt
+            .with_rng(|rng| rng.gen_range(Duration::ZERO..=MAX_PAUSE));
+        self.rt.wait(pause).await;
+    }
+
+    fn random_counter(&self) -> usize {
+        self.rt.with_rng(|rng| rng.gen_range(0..NUM_COUNTERS))
+    }
+
+    async fn read_counter(
+        &self,
+        tx: &mut Transaction<TestRuntime>,
+        counter: usize,
+    ) -> anyhow::Result<u64> {
+        let document = tx
+            .get(self.counters[counter])
+            .await?
+            .context("Counter is missing")?;
+        match document.value().get("value") {
+            Some(ConvexValue::Int64(value)) => Ok(*value as u64),
+            value => anyhow::bail!("Invalid counter value {value:?}"),
+        }
+    }
+
+    async fn increment(&self) -> anyhow::Result<()> {
+        let counter = self.random_counter();
+        let db = self.db();
+        let Ok(mut tx) = db.begin(Identity::system()).await else {
+            // The database is shutting down.
+            return Ok(());
+        };
+        let value = self.read_counter(&mut tx, counter).await?;
+        self.pause().await;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .replace(
+                self.counters[counter].into(),
+                assert_obj!("value" => (value + 1) as i64),
+            )
+            .await?;
+        match db.commit(tx).await {
+            Ok(ts) => self.history.lock().committed.push((ts, counter)),
+            Err(e) if e.is_occ() => (),
+            Err(_) => self.history.lock().ambiguous[counter] += 1,
+        }
+        Ok(())
+    }
+
+    async fn check_subscription(&self) -> anyhow::Result<()> {
+        let counters: Vec<usize> = (0..2).map(|_| self.random_counter()).collect();
+        let db = self.db();
+        let epoch = *self.epoch.lock();
+        let Ok(mut tx) = db.begin(Identity::system()).await else {
+            return Ok(());
+        };
+        for counter in &counters {
+            let value = self.read_counter(&mut tx, *counter).await?;
+            self.history
+                .lock()
+                .check(*counter, *tx.begin_timestamp(), value)?;
+        }
+        let token = tx.into_token()?;
+        let token_ts = token.ts();
+        // Like `begin`, subscribing fails if the database has shut down.
+        let Ok(subscription) = db.subscribe(token.clone()).await else {
+            return Ok(());
+        };
+        self.pause().await;
+
+        if let Some(valid_ts) = subscription.current_ts()
+            && let Some((ts, counter)) = self
+                .history
+                .lock()
+                .committed_between(&counters, token_ts, valid_ts)
+        {
+            anyhow::bail!(
+                "Subscription at {token_ts} is valid at {valid_ts}, after counter {counter} was \
+                 incremented at {ts}"
+            );
+        }
+        let now = *db.now_ts_for_reads();
+        if let Ok(Some(refreshed)) = db.refresh_token(token, now).await
+            && let Some((ts, counter)) =
+                self.history
+                    .lock()
+                    .committed_between(&counters, token_ts, refreshed.ts())
+        {
+            anyhow::bail!(
+                "Token at {token_ts} was refreshed to {}, past counter {counter} being \
+                 incremented at {ts}",
+                refreshed.ts()
+            );
+        }
+
+        let conflict = self
+            .history
+            .lock()
+            .committed_between(&counters, token_ts, Timestamp::MAX);
+        if let Some((ts, counter)) = conflict {
+            let invalidated = futures::select_biased! {
+                _ = subscription.wait_for_invalidation().fuse() => true,
+                _ = self.rt.wait(INVALIDATION_DEADLINE) => false,
+            };
+            anyhow::ensure!(
+                invalidated || *self.epoch.lock() != epoch,
+                "Subscription at {token_ts} wasn't invalidated by counter {counter} being \
+                 incremented at {ts}"
+            );
+        }
+        Ok(())
+    }
+
+    async fn check_snapshot_read(&self) -> anyhow::Result<()> {
+        let db = self.db();
+        let Some(ts) = ({
+            let history = self.history.lock();
+            if history.committed.is_empty() {
+                None
+            } else {
+                let i = self
+                    .rt
+                    .with_rng(|rng| rng.gen_range(0..history.committed.len()));
+                Some(history.committed[i].0)
+            }
+        }) else {
+            return Ok(());
+        };
+        // Reads before the database's in-memory snapshots, or its latest restart,
+        // fail rather than returning the wrong data.
+        let Ok(mut tx) = db
+            .begin_with_ts(Identity::system(), ts, FunctionUsageTracker::new())
+            .await
+        else {
+            return Ok(());
+        };
+        let read_ts = *tx.begin_timestamp();
+        for counter in 0..NUM_COUNTERS {
+            let value = self.read_counter(&mut tx, counter).await?;
+            self.history.lock().check(counter, read_ts, value)?;
+        }
+        Ok(())
+    }
+
+    async fn run_writer(&self) -> anyhow::Result<()> {
+        for _ in 0..OPS_PER_WORKER {
+            self.increment().await?;
+            self.pause().await;
+        }
+        Ok(())
+    }
+
+    async fn run_reader(&self) -> anyhow::Result<()> {
+        for _ in 0..OPS_PER_WORKER {
+            if self.rt.with_rng(|rng| rng.gen_bool(0.5)) {
+                self.check_subscription().await?;
+            } else {
+                self.check_snapshot_read().await?;
+            }
+            self.pause().await;
+        }
+        Ok(())
+    }
+
+    /// Restart the database whenever it shuts down after a failed write.
+    async fn run_supervisor(&self, mut shutdown_rx: ShutdownRx) -> anyhow::Result<()> {
+        let mut restarts = 0;
+        while let Some(error) = shutdown_rx.next().await {
+            tracing::info!("Restarting database after: {error:#}");
+            restarts += 1;
+            self.tp.set_faults(PersistenceFaults::default());
+            let old_db = self.db();
+            old_db.shutdown().await?;
+            let (shutdown_tx, new_shutdown_rx) = async_broadcast::broadcast(1);
+            let db =
+                load_database(&self.rt, self.tp.clone(), ShutdownSignal::new(shutdown_tx)).await?;
+            shutdown_rx = new_shutdown_rx;
+
+            // The new database must have every committed increment.
+            let mut tx = db.begin(Identity::system()).await?;
+            for counter in 0..NUM_COUNTERS {
+                let value = self.read_counter(&mut tx, counter).await?;
+                self.history
+                    .lock()
+                    .check(counter, *tx.begin_timestamp(), value)?;
+            }
+            *self.db.lock() = db;
+            *self.epoch.lock() += 1;
+            if restarts < MAX_RESTARTS {
+                self.tp.set_faults(self.faults);
+            }
+        }
+        Ok(())
+    }
+
+    /// Final check once the workload is done.
+    async fn check_final_state(&self) -> anyhow::Result<()> {
+        let db = self.db();
+        let mut tx = db.begin(Identity::system()).await?;
+        let ts = *tx.begin_timestamp();
+        for counter in 0..NUM_COUNTERS {
+            let value = self.read_counter(&mut tx, counter).await?;
+            self.history.lock().check(counter, ts, value)?;
+        }
+        tracing::info!(
+            "Simulation done with {} commits and {} faults",
+            self.history.lock().committed.len(),
+            self.tp.num_faults()
+        );
+        Ok(())
+    }
+}
+
+type ShutdownRx = async_broadcast::Receiver<Arc<anyhow::Error>>;
+
+async fn load_database(
+    rt: &TestRuntime,
+    tp: Arc<dyn Persistence>,
+    shutdown: ShutdownSignal,
+) -> anyhow::Result<Database<TestRuntime>> {
+    Database::load(
+        tp,
+        rt.clone(),
+        Arc::new(SearcherStub {}),
+        shutdown,
+        Default::default(),
+        Arc::new(NoOpUsageEventLogger),
+    )
+    .await
+}
+
+async fn simulate(rt: TestRuntime, faults: PersistenceFaults) -> anyhow::Result<()> {
+    let (simulation, shutdown_rx) = Simulation::new(rt, faults).await?;
+    let workers = future::try_join_all(
+        (0..NUM_WRITERS)
+            .map(|_| simulation.run_writer().boxed_local())
+            .chain((0..NUM_READERS).map(|_| simulation.run_reader().boxed_local())),
+    );
+    futures::select! {
+        result = workers.fuse() => {
+            result?;
+        },
+        result = simulation.run_supervisor(shutdown_rx).fuse() => {
+            result?;
+            anyhow::bail!("Database shut down for good");
+        },
+    }
+    simulation.check_final_state().await
+}
+
+#[test]
+fn test_simulate_commits_and_subscriptions() -> anyhow::Result<()> {
+    run_simulation(NUM_SEEDS, |rt| {
+        simulate(
+            rt,
+            PersistenceFaults {
+                max_write_delay: Duration::from_millis(20),
+                ..Default::default()
+            },
+        )
+    })
+}
+
+#[test]
+fn test_simulate_persistence_faults() -> anyhow::Result<()> {
+    run_simulation(NUM_SEEDS, |rt| {
+        simulate(
+            rt,
+            PersistenceFaults {
+                fail_before_write: 0.01,
+                fail_after_write: 0.01,
+                max_write_delay: Duration::from_millis(20),
+            },
+        )
+    })
+}
@@ -15,6 +15,7 @@ use crate::{
     test_helpers::DbFixtures,
     DocumentDeltas,
     SnapshotPage,
+    TableChanges,
     TableModel,
     TestFacingModel,
     UserFacingModel,
@@ -219,6 +220,99 @@ async fn document_deltas_should_not_ignore_rows_from_tables_that_were_not_delete
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_table_changes(rt: TestRuntime) -> anyhow::Result<()> {
+    let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let doc1 = TestFacingModel::new(&mut tx)
+        .insert_and_get("table1".parse()?, assert_obj!("f" => 1))
+        .await?;
+    let ts1 = db.commit(tx).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    let doc2 = TestFacingModel::new(&mut tx)
+        .insert_and_get("table1".parse()?, assert_obj!("f" => 2))
+        .await?;
+    TestFacingModel::new(&mut tx)
+        .insert_and_get("table2".parse()?, assert_obj!("f" => 3))
+        .await?;
+    let ts2 = db.commit(tx).await?;
+    let mut tx = db.begin(Identity::system()).await?;
+    UserFacingModel::new_root_for_test(&mut tx)
+        .delete(doc1.developer_id())
+        .await?;
+    let ts3 = db.commit(tx).await?;
+
+    // Without a cursor, changes are read from the start of retention.
+    let page = db
+        .table_changes(
+            Identity::system(),
+            TableNamespace::test_user(),
+            "table1".parse()?,
+            None,
+            200,
+            1,
+        )
+        .await?;
+    assert_eq!(
+        page,
+        TableChanges {
+            changes: vec![(ts1, doc1.developer_id(), Some(doc1.clone()))],
+            cursor: ts1,
+            has_more: true,
+        }
+    );
+
+    // Changes to other tables are skipped, and deletes have no document.
+    let page = db
+        .table_changes(
+            Identity::system(),
+            TableNamespace::test_user(),
+            "table1".parse()?,
+            Some(page.cursor),
+            200,
+            10,
+        )
+        .await?;
+    assert_eq!(
+        page,
+        TableChanges {
+            changes: vec![
+                (ts2, doc2.developer_id(), Some(doc2)),
+                (ts3, doc1.developer_id(), None),
+            ],
+            cursor: ts3,
+            has_more: false,
+        }
+    );
+
+    // A table that doesn't exist has no changes.
+    let page = db
+        .table_changes(
+            Identity::system(),
+            TableNamespace::test_user(),
+            "missing".parse()?,
+            Some(ts1),
+            200,
+            10,
+        )
+        .await?;
+    assert!(page.changes.is_empty());
+    assert_eq!(page.cursor, ts1);
+
+    let system_table = db
+        .table_changes(
+            Identity::system(),
+            TableNamespace::test_user(),
+            "_tables".parse()?,
+            None,
+            200,
+            10,
+        )
+        .await;
+    assert!(system_table.is_err());
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_snapshot_list(rt: TestRuntime) -> anyhow::Result<()> {
     let DbFixtures { db, .. } = DbFixtures::new(&rt).await?;
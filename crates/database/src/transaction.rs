@@ -19,6 +19,7 @@ use common::{
             IndexMetadata,
             INDEX_TABLE,
         },
+        schema::SchemaState,
         tables::{
             TableMetadata,
             TABLES_TABLE,
@@ -28,6 +29,8 @@ use common::{
         CreationTime,
         DocumentUpdate,
         ResolvedDocument,
+        UPDATED_BY_FIELD,
+        UPDATED_TIME_FIELD,
     },
     identity::InertIdentity,
     index::{
@@ -47,9 +50,14 @@ use common::{
         SearchVersion,
     },
     runtime::Runtime,
+    schemas::{
+        DatabaseSchema,
+        IdStrategy,
+    },
     sync::split_rw_lock::Reader,
     types::{
         GenericIndexName,
+        IndexDescriptor,
         IndexId,
         IndexName,
         PersistenceVersion,
@@ -59,6 +67,7 @@ use common::{
         TableStats,
         TabletIndexName,
         WriteTimestamp,
+        INDEX_BY_ID_DESCRIPTOR,
     },
     value::{
         id_v6::DeveloperDocumentId,
@@ -84,6 +93,8 @@ use sync_types::{
 };
 use usage_tracking::FunctionUsageTracker;
 use value::{
+    ConvexValue,
+    FieldName,
     TableNamespace,
     TableNumber,
     TabletId,
@@ -92,6 +103,7 @@ use value::{
 use crate::{
     bootstrap_model::{
         defaults::BootstrapTableIds,
+        schema::SCHEMAS_TABLE,
         table::{
             NUM_RESERVED_LEGACY_TABLE_NUMBERS,
             NUM_RESERVED_SYSTEM_TABLE_NUMBERS,
@@ -99,6 +111,12 @@ use crate::{
     },
     committer::table_dependency_sort_key,
     execution_size::FunctionExecutionSize,
+    field_encryption::{
+        decrypt_document,
+        encrypt_fields,
+        FieldEncryptor,
+    },
+    index_backfill::BackfillProgressTracker,
     metrics,
     patch::PatchValue,
     preloaded::PreloadedIndexRange,
@@ -106,7 +124,11 @@ use crate::{
         IndexRangeResponse,
         TableFilter,
     },
-    reads::TransactionReadSet,
+    reads::{
+        ReadBudget,
+        TransactionReadSet,
+    },
+    sketches::SketchModel,
     snapshot_manager::{
         Snapshot,
         SnapshotManager,
@@ -164,6 +186,22 @@ pub struct Transaction<RT: Runtime> {
     pub usage_tracker: FunctionUsageTracker,
     pub(crate) virtual_system_mapping: VirtualSystemMapping,
 
+    /// Encrypts the fields of user documents that the schema marks as
+    /// encrypted. If unset, they're stored in plaintext.
+    pub(crate) field_encryptor: Option<Arc<dyn FieldEncryptor>>,
+
+    /// Writes to user tables that haven't been passed to triggers yet, if the
+    /// transaction runs triggers. See `crate::triggers`.
+    pub(crate) trigger_changes: Option<Vec<DocumentUpdate>>,
+
+    /// The active schema of each namespace this transaction has looked up,
+    /// read at most once until the transaction writes to `_schemas`. See
+    /// `Transaction::active_schema`.
+    active_schemas: BTreeMap<TableNamespace, Option<Arc<DatabaseSchema>>>,
+
+    /// Progress of the database's index backfills, for reporting it.
+    pub(crate) backfill_progress: BackfillProgressTracker,
+
     #[cfg(any(test, feature = "testing"))]
     index_size_override: Option<usize>,
 }
@@ -180,6 +218,14 @@ pub trait TableCountSnapshot: Send + Sync + 'static {
     /// Returns the number of documents in the table at the timestamp of the
     /// snapshot.
     async fn count(&self, table: TabletId) -> anyhow::Result<u64>;
+
+    /// Returns the total size in bytes of the table's documents at the
+    /// timestamp of the snapshot.
+    async fn total_size(&self, table: TabletId) -> anyhow::Result<u64>;
+
+    /// Returns the average number of writes per minute to the table over the
+    /// few minutes before `ts`, as of the snapshot.
+    async fn writes_per_minute(&self, table: TabletId, ts: Timestamp) -> anyhow::Result<f64>;
 }
 
 impl<RT: Runtime> Transaction<RT> {
@@ -211,11 +257,34 @@ impl<RT: Runtime> Transaction<RT> {
             retention_validator,
             usage_tracker,
             virtual_system_mapping,
+            field_encryptor: None,
+            trigger_changes: None,
+            active_schemas: BTreeMap::new(),
+            backfill_progress: BackfillProgressTracker::default(),
             #[cfg(any(test, feature = "testing"))]
             index_size_override: None,
         }
     }
 
+    pub fn set_field_encryptor(&mut self, field_encryptor: Arc<dyn FieldEncryptor>) {
+        self.field_encryptor = Some(field_encryptor);
+    }
+
+    /// Start keeping the writes to user tables so that `run_triggers` can pass
+    /// them to the tables' triggers before the transaction commits.
+    pub fn record_writes_for_triggers(&mut self) {
+        if self.trigger_changes.is_none() {
+            self.trigger_changes = Some(vec![]);
+        }
+    }
+
+    pub(crate) fn take_trigger_changes(&mut self) -> Vec<DocumentUpdate> {
+        match &mut self.trigger_changes {
+            Some(changes) => mem::take(changes),
+            None => vec![],
+        }
+    }
+
     pub fn persistence_version(&self) -> PersistenceVersion {
         self.index.index_registry().persistence_version()
     }
@@ -311,6 +380,20 @@ impl<RT: Runtime> Transaction<RT> {
         &self.writes
     }
 
+    pub fn reads(&self) -> &TransactionReadSet {
+        &self.reads
+    }
+
+    pub fn read_budget(&self) -> ReadBudget {
+        self.reads.budget()
+    }
+
+    /// Fail reads from user tables once the transaction has read more than
+    /// `budget`.
+    pub fn set_read_budget(&mut self, budget: ReadBudget) {
+        self.reads.set_budget(budget);
+    }
+
     pub fn into_reads_and_writes(self) -> (TransactionReadSet, Writes) {
         (self.reads, self.writes)
     }
@@ -387,8 +470,10 @@ impl<RT: Runtime> Transaction<RT> {
 
     // Checks that if this transaction already has some writes, they are included
     // in the given `updates`. This means the passed in `updates` are a superset
-    // of the existing `updates` on this transaction, and that the merged-in
-    // writes cannot modify documents already written to in this transaction.
+    // of the existing `updates` on this transaction. The merged-in writes may
+    // write to documents already written to in this transaction again, e.g. when
+    // a trigger updates a document its mutation wrote, but they must start from
+    // the same previous version of the document.
     // In most scenarios this transaction will have no writes.
     pub fn merge_writes(
         &mut self,
@@ -409,6 +494,13 @@ impl<RT: Runtime> Transaction<RT> {
             );
         }
 
+        for id in existing_updates.keys() {
+            anyhow::ensure!(
+                updates.contains_key(id),
+                "Existing write to document {id} was not preserved"
+            );
+        }
+
         let mut updates = updates.into_iter().collect::<Vec<_>>();
         updates.sort_by_key(|(id, update)| {
             table_dependency_sort_key(
@@ -420,15 +512,21 @@ impl<RT: Runtime> Transaction<RT> {
 
         let mut preserved_update_count = 0;
         for (id, update) in updates {
-            // Ensure that the existing update matches, and that
-            // that the merged-in writes didn't otherwise modify documents
-            // already written to in this transaction.
+            // Ensure that the merged-in update builds on the existing one, and
+            // apply any later write to the document on top of it.
             if let Some(existing_update) = existing_updates.get(&id) {
                 anyhow::ensure!(
-                    existing_update == &update,
+                    existing_update.old_document == update.old_document,
                     "Conflicting updates for document {id}"
                 );
                 preserved_update_count += 1;
+                if existing_update.new_document != update.new_document {
+                    self.apply_validated_write(
+                        id,
+                        existing_update.new_document.clone(),
+                        update.new_document,
+                    )?;
+                }
                 continue;
             }
 
@@ -495,16 +593,25 @@ impl<RT: Runtime> Transaction<RT> {
                 ))?;
 
         let new_document = {
-            let patched_value = value
-                .clone()
-                .apply(old_document.value().clone().into_value())?;
+            let patched_value = value.clone().apply(
+                self.decrypt_document(old_document.clone())?
+                    .into_value()
+                    .into_value(),
+            )?;
             old_document.replace_value(patched_value)?
         };
+        self.check_external_id_unchanged(namespace, &old_document, &new_document)
+            .await?;
+        let new_document = self.track_update(namespace, new_document, None).await?;
         SchemaModel::new(self, namespace)
             .enforce(&new_document)
             .await?;
 
-        self.apply_validated_write(id, Some(old_document), Some(new_document.clone()))?;
+        let stored_document = self.encrypt_document(namespace, &new_document).await?;
+        self.apply_validated_write(id, Some(old_document.clone()), Some(stored_document))?;
+        SketchModel::new(self, namespace)
+            .update(id, Some(&old_document), Some(&new_document))
+            .await?;
         Ok(new_document)
     }
 
@@ -539,16 +646,23 @@ impl<RT: Runtime> Transaction<RT> {
 
         // Replace document.
         let new_document = old_document.replace_value(value)?;
+        self.check_external_id_unchanged(namespace, &old_document, &new_document)
+            .await?;
+        let new_document = self.track_update(namespace, new_document, None).await?;
 
         SchemaModel::new(self, namespace)
             .enforce(&new_document)
             .await?;
 
+        let stored_document = self.encrypt_document(namespace, &new_document).await?;
         self.apply_validated_write(
             new_document.id(),
-            Some(old_document),
-            Some(new_document.clone()),
+            Some(old_document.clone()),
+            Some(stored_document),
         )?;
+        SketchModel::new(self, namespace)
+            .update(id, Some(&old_document), Some(&new_document))
+            .await?;
         Ok(new_document)
     }
 
@@ -558,6 +672,7 @@ impl<RT: Runtime> Transaction<RT> {
         id: ResolvedDocumentId,
     ) -> anyhow::Result<ResolvedDocument> {
         let table_name = self.table_mapping().tablet_name(id.tablet_id)?;
+        let namespace = self.table_mapping().tablet_namespace(id.tablet_id)?;
         let (document, _) =
             self.get_inner(id, table_name)
                 .await?
@@ -567,6 +682,9 @@ impl<RT: Runtime> Transaction<RT> {
                 ))?;
 
         self.apply_validated_write(document.id(), Some(document.clone()), None)?;
+        SketchModel::new(self, namespace)
+            .update(id, Some(&document), None)
+            .await?;
         Ok(document)
     }
 
@@ -614,7 +732,7 @@ impl<RT: Runtime> Transaction<RT> {
         &self.stats
     }
 
-    fn take_table_mapping_dep(&mut self) {
+    pub(crate) fn take_table_mapping_dep(&mut self) {
         let tables_by_id = TabletIndexName::by_id(
             self.metadata
                 .table_mapping()
@@ -813,6 +931,7 @@ impl<RT: Runtime> Transaction<RT> {
                     .name_exists(&table_name);
                 self.reads.record_read_document(
                     table_name,
+                    &INDEX_BY_ID_DESCRIPTOR,
                     doc.size(),
                     &self.usage_tracker,
                     is_virtual_table,
@@ -871,6 +990,12 @@ impl<RT: Runtime> Transaction<RT> {
                 }
             },
         };
+        let trigger_change =
+            (!is_system_document && self.trigger_changes.is_some()).then(|| DocumentUpdate {
+                id,
+                old_document: old_document.clone(),
+                new_document: new_document.clone(),
+            });
         // NB: Writes::update is fallible, so be sure to call it before applying the
         // index and metadata updates.
         self.writes.update(
@@ -890,6 +1015,17 @@ impl<RT: Runtime> Transaction<RT> {
         metadata_update.apply();
 
         *self.table_count_deltas.entry(id.tablet_id).or_default() += delta;
+        if self
+            .table_mapping()
+            .tablet_matches_name(id.tablet_id, &SCHEMAS_TABLE)
+        {
+            self.active_schemas.clear();
+        }
+        if let Some(change) = trigger_change
+            && let Some(changes) = &mut self.trigger_changes
+        {
+            changes.push(change);
+        }
         Ok(())
     }
 
@@ -901,11 +1037,177 @@ impl<RT: Runtime> Transaction<RT> {
         let namespace = self
             .table_mapping()
             .tablet_namespace(document_id.tablet_id)?;
+        let creation_time = document.creation_time();
+        let document = self
+            .track_update(namespace, document, creation_time)
+            .await?;
         SchemaModel::new(self, namespace).enforce(&document).await?;
-        self.apply_validated_write(document_id, None, Some(document))?;
+        let stored_document = self.encrypt_document(namespace, &document).await?;
+        self.apply_validated_write(document_id, None, Some(stored_document))?;
+        SketchModel::new(self, namespace)
+            .update(document_id, None, Some(&document))
+            .await?;
         Ok(document_id)
     }
 
+    /// The active schema of `namespace`, which determines how writes to its
+    /// tables are handled. It's only read and parsed on the first call, so
+    /// writes don't each pay for it.
+    pub(crate) async fn active_schema(
+        &mut self,
+        namespace: TableNamespace,
+    ) -> anyhow::Result<Option<Arc<DatabaseSchema>>> {
+        if let Some(schema) = self.active_schemas.get(&namespace) {
+            return Ok(schema.clone());
+        }
+        let schema = SchemaModel::new(self, namespace)
+            .get_by_state(SchemaState::Active)
+            .await?
+            .map(|(_, schema)| Arc::new(schema));
+        self.active_schemas.insert(namespace, schema.clone());
+        Ok(schema)
+    }
+
+    /// How the IDs of documents inserted into `table_name` are generated,
+    /// according to the active schema.
+    pub(crate) async fn id_strategy(
+        &mut self,
+        namespace: TableNamespace,
+        table_name: &TableName,
+    ) -> anyhow::Result<IdStrategy> {
+        Ok(self
+            .active_schema(namespace)
+            .await?
+            .and_then(|schema| {
+                schema
+                    .tables
+                    .get(table_name)
+                    .map(|table| table.id_strategy.clone())
+            })
+            .unwrap_or_default())
+    }
+
+    /// In tables whose IDs are derived from an external key, the key can't
+    /// change, or the document's ID would no longer match it.
+    async fn check_external_id_unchanged(
+        &mut self,
+        namespace: TableNamespace,
+        old_document: &ResolvedDocument,
+        new_document: &ResolvedDocument,
+    ) -> anyhow::Result<()> {
+        let tablet_id = new_document.id().tablet_id;
+        if self.table_mapping().is_system_tablet(tablet_id) {
+            return Ok(());
+        }
+        let table_name = self.table_mapping().tablet_name(tablet_id)?;
+        let IdStrategy::External { field } = self.id_strategy(namespace, &table_name).await? else {
+            return Ok(());
+        };
+        let field = FieldName::from(field);
+        if old_document.value().get(&field) != new_document.value().get(&field) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ExternalIdChanged",
+                format!(
+                    "Can't change the {field} field of a document in {table_name}, since its ID \
+                     is derived from it"
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Sets the `_updatedTime` and `_updatedBy` fields of a document written to
+    /// a table whose schema tracks updates. In other tables the fields are
+    /// removed, so they can't be set by hand. `updated_time` defaults to the
+    /// transaction's next creation time.
+    async fn track_update(
+        &mut self,
+        namespace: TableNamespace,
+        document: ResolvedDocument,
+        updated_time: Option<CreationTime>,
+    ) -> anyhow::Result<ResolvedDocument> {
+        let tablet_id = document.id().tablet_id;
+        if self.table_mapping().is_system_tablet(tablet_id) {
+            return Ok(document);
+        }
+        let table_name = self.table_mapping().tablet_name(tablet_id)?;
+        let track_updates = self.active_schema(namespace).await?.is_some_and(|schema| {
+            schema
+                .tables
+                .get(&table_name)
+                .is_some_and(|table| table.track_updates)
+        });
+        let updated_time_field = FieldName::from(UPDATED_TIME_FIELD.clone());
+        let updated_by_field = FieldName::from(UPDATED_BY_FIELD.clone());
+        let mut fields: BTreeMap<FieldName, ConvexValue> =
+            document.value().clone().into_value().into();
+        if track_updates {
+            let updated_time = match updated_time {
+                Some(updated_time) => updated_time,
+                None => self.next_creation_time.increment()?,
+            };
+            let updated_by = match self.user_identity() {
+                Some(identity) => ConvexValue::try_from(identity.token_identifier.0)?,
+                None => ConvexValue::Null,
+            };
+            fields.insert(
+                updated_time_field,
+                ConvexValue::from(f64::from(updated_time)),
+            );
+            fields.insert(updated_by_field, updated_by);
+        } else {
+            let removed_time = fields.remove(&updated_time_field);
+            let removed_by = fields.remove(&updated_by_field);
+            if removed_time.is_none() && removed_by.is_none() {
+                return Ok(document);
+            }
+        }
+        document.replace_value(fields.try_into()?)
+    }
+
+    /// Encrypts the fields of `document` that the active schema marks as
+    /// encrypted, returning the document to persist.
+    async fn encrypt_document(
+        &mut self,
+        namespace: TableNamespace,
+        document: &ResolvedDocument,
+    ) -> anyhow::Result<ResolvedDocument> {
+        let Some(field_encryptor) = self.field_encryptor.clone() else {
+            return Ok(document.clone());
+        };
+        let tablet_id = document.id().tablet_id;
+        if self.table_mapping().is_system_tablet(tablet_id) {
+            return Ok(document.clone());
+        }
+        let table_name = self.table_mapping().tablet_name(tablet_id)?;
+        let Some(schema) = self.active_schema(namespace).await? else {
+            return Ok(document.clone());
+        };
+        let Some(table) = schema.tables.get(&table_name) else {
+            return Ok(document.clone());
+        };
+        if table.encrypted_fields.is_empty() {
+            return Ok(document.clone());
+        }
+        let value = encrypt_fields(
+            &field_encryptor,
+            &table.encrypted_fields,
+            document.value().clone().into_value(),
+        )?;
+        document.replace_value(value)
+    }
+
+    /// Decrypts any encrypted fields of a document read from a user table.
+    pub(crate) fn decrypt_document(
+        &self,
+        document: ResolvedDocument,
+    ) -> anyhow::Result<ResolvedDocument> {
+        match &self.field_encryptor {
+            Some(field_encryptor) => decrypt_document(field_encryptor, document),
+            None => Ok(document),
+        }
+    }
+
     pub async fn search(
         &mut self,
         stable_index_name: &StableIndexName,
@@ -929,10 +1231,12 @@ impl<RT: Runtime> Transaction<RT> {
         &mut self,
         document: &ResolvedDocument,
         table_name: &TableName,
+        index: &IndexDescriptor,
     ) -> anyhow::Result<()> {
         let is_virtual_table = self.virtual_system_mapping().is_virtual_table(table_name);
         self.reads.record_read_document(
             table_name.clone(),
+            index,
             document.size(),
             &self.usage_tracker,
             is_virtual_table,
@@ -3,6 +3,7 @@ use std::time::SystemTime;
 use common::{
     document::InternalId,
     runtime::Runtime,
+    sha256::Sha256,
 };
 use rand::{
     Rng,
@@ -26,6 +27,8 @@ use value::{
 pub struct TransactionIdGenerator {
     rng: ChaCha12Rng,
     day_bytes: [u8; 2],
+    unix_ms: u64,
+    last_ulid: Option<u128>,
 }
 
 impl TransactionIdGenerator {
@@ -45,6 +48,8 @@ impl TransactionIdGenerator {
         Ok(Self {
             rng,
             day_bytes: day_bytes[6..].try_into()?,
+            unix_ms: duration.as_millis().try_into()?,
+            last_ulid: None,
         })
     }
 
@@ -55,6 +60,38 @@ impl TransactionIdGenerator {
         InternalId(id_bytes)
     }
 
+    /// Creates an InternalId laid out like a ULID: 6 bytes of milliseconds
+    /// since the Unix epoch followed by 10 bytes of randomness. IDs from the
+    /// same generator increase, so documents inserted by one transaction sort
+    /// in the order they were inserted.
+    pub fn generate_ulid_internal(&mut self) -> anyhow::Result<InternalId> {
+        let ulid = match self.last_ulid {
+            Some(last_ulid) => last_ulid
+                .checked_add(1)
+                .ok_or_else(|| anyhow::anyhow!("ULID overflow"))?,
+            None => {
+                let mut random_bytes = [0u8; 16];
+                self.rng.fill_bytes(&mut random_bytes[6..]);
+                (u128::from(self.unix_ms) << 80) | u128::from_be_bytes(random_bytes)
+            },
+        };
+        self.last_ulid = Some(ulid);
+        Ok(InternalId(ulid.to_be_bytes()))
+    }
+
+    /// The InternalId for a document whose ID is derived from an app-provided
+    /// key. The same key always maps to the same ID within a table.
+    pub fn external_internal_id(table_number: TableNumber, key: &str) -> InternalId {
+        let mut hasher = Sha256::new();
+        hasher.update(&u32::from(table_number).to_be_bytes());
+        hasher.update(b"|");
+        hasher.update(key.as_bytes());
+        let digest = hasher.finalize();
+        let mut id_bytes = [0u8; 16];
+        id_bytes.copy_from_slice(&digest[..16]);
+        InternalId(id_bytes)
+    }
+
     pub fn generate(&mut self, table_number: TableNumber) -> DeveloperDocumentId {
         DeveloperDocumentId::new(table_number, self.generate_internal())
     }
@@ -107,4 +144,43 @@ mod tests {
         assert_eq!(day_from_id, day_from_system_time);
         Ok(())
     }
+
+    #[test]
+    fn ulids_are_increasing_and_start_with_time() -> anyhow::Result<()> {
+        let td = TestDriver::new();
+        let mut id_generator = TransactionIdGenerator::new(&td.rt())?;
+        let ids = (0..10)
+            .map(|_| id_generator.generate_ulid_internal())
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut ms_bytes = [0u8; 8];
+        ms_bytes[2..].copy_from_slice(&ids[0][..6]);
+        let duration = td
+            .rt()
+            .system_time()
+            .duration_since(SystemTime::UNIX_EPOCH)?;
+        assert_eq!(u64::from_be_bytes(ms_bytes) as u128, duration.as_millis());
+        Ok(())
+    }
+
+    #[test]
+    fn external_ids_are_deterministic() -> anyhow::Result<()> {
+        let table: TableNumber = <TableNumber as TableIdentifier>::min();
+        let other_table: TableNumber = 10001.try_into()?;
+        let id = TransactionIdGenerator::external_internal_id(table, "order-1");
+        assert_eq!(
+            id,
+            TransactionIdGenerator::external_internal_id(table, "order-1")
+        );
+        assert_ne!(
+            id,
+            TransactionIdGenerator::external_internal_id(table, "order-2")
+        );
+        assert_ne!(
+            id,
+            TransactionIdGenerator::external_internal_id(other_table, "order-1")
+        );
+        Ok(())
+    }
 }
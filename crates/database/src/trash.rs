@@ -0,0 +1,505 @@
+//! Soft delete. Deleting a document from a table whose schema enables soft
+//! delete moves it to `_trash` in the table's namespace, so it no longer
+//! shows up in queries or indexes, and it can be restored with its original
+//! ID until its retention window ends.
+
+use std::{
+    collections::BTreeMap,
+    sync::LazyLock,
+};
+
+use common::{
+    document::{
+        CreationTime,
+        ParsedDocument,
+        ResolvedDocument,
+        CREATION_TIME_FIELD,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::IndexName,
+};
+use errors::ErrorMetadata;
+use serde_json::Value as JsonValue;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexObject,
+    ConvexValue,
+    FieldName,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    defaults::{
+        system_index,
+        SystemIndex,
+        SystemTable,
+    },
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+
+pub static TRASH_TABLE: LazyLock<TableName> =
+    LazyLock::new(|| "_trash".parse().expect("Invalid built-in trash table"));
+
+static DOCUMENT_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "documentId".parse().expect("Invalid built-in field"));
+
+static TABLE_NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "tableName".parse().expect("Invalid built-in field"));
+
+static DELETED_AT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "deletedAt".parse().expect("Invalid built-in field"));
+
+static EXPIRES_AT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "expiresAt".parse().expect("Invalid built-in field"));
+
+pub static TRASH_BY_DOCUMENT_ID_INDEX: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&TRASH_TABLE, "by_document_id"));
+
+pub static TRASH_BY_TABLE_NAME_INDEX: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&TRASH_TABLE, "by_table_name_and_deleted_at"));
+
+pub static TRASH_BY_EXPIRES_AT_INDEX: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&TRASH_TABLE, "by_expires_at"));
+
+pub struct TrashTable;
+impl SystemTable for TrashTable {
+    fn table_name(&self) -> &'static TableName {
+        &TRASH_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: TRASH_BY_DOCUMENT_ID_INDEX.clone(),
+                fields: vec![DOCUMENT_ID_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: TRASH_BY_TABLE_NAME_INDEX.clone(),
+                fields: vec![TABLE_NAME_FIELD.clone(), DELETED_AT_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+            SystemIndex {
+                name: TRASH_BY_EXPIRES_AT_INDEX.clone(),
+                fields: vec![EXPIRES_AT_FIELD.clone()].try_into().unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<TrashedDocument>::try_from(document).map(|_| ())
+    }
+}
+
+/// A soft-deleted document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrashedDocument {
+    pub table_name: TableName,
+    pub document_id: DeveloperDocumentId,
+    /// The document as it was stored, including its system fields. Encrypted
+    /// fields stay encrypted.
+    pub document: ConvexObject,
+    pub deleted_at: UnixTimestamp,
+    /// When the document is deleted for good.
+    pub expires_at: UnixTimestamp,
+}
+
+fn timestamp_value(timestamp: UnixTimestamp) -> anyhow::Result<ConvexValue> {
+    Ok(ConvexValue::Int64(timestamp.as_nanos().try_into()?))
+}
+
+impl TryFrom<TrashedDocument> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(trashed: TrashedDocument) -> anyhow::Result<Self> {
+        // Serialize the document as binary since we restrict what field names
+        // can be used in a `Document`'s top-level object.
+        let document_bytes = serde_json::to_vec(&JsonValue::from(trashed.document))?;
+        let mut obj: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
+        obj.insert(
+            "tableName".parse()?,
+            ConvexValue::try_from(String::from(trashed.table_name))?,
+        );
+        obj.insert(
+            "documentId".parse()?,
+            ConvexValue::try_from(trashed.document_id.encode())?,
+        );
+        obj.insert("document".parse()?, ConvexValue::try_from(document_bytes)?);
+        obj.insert("deletedAt".parse()?, timestamp_value(trashed.deleted_at)?);
+        obj.insert("expiresAt".parse()?, timestamp_value(trashed.expires_at)?);
+        ConvexObject::try_from(obj)
+    }
+}
+
+impl TryFrom<ConvexObject> for TrashedDocument {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields: BTreeMap<_, _> = object.into();
+        let table_name = match fields.remove("tableName") {
+            Some(ConvexValue::String(s)) => s.parse()?,
+            _ => anyhow::bail!("Missing or invalid `tableName` field for TrashedDocument"),
+        };
+        let document_id = match fields.remove("documentId") {
+            Some(ConvexValue::String(s)) => DeveloperDocumentId::decode(&s)?,
+            _ => anyhow::bail!("Missing or invalid `documentId` field for TrashedDocument"),
+        };
+        let document = match fields.remove("document") {
+            Some(ConvexValue::Bytes(b)) => {
+                let document_json: JsonValue = serde_json::from_slice(&b)?;
+                document_json.try_into()?
+            },
+            _ => anyhow::bail!("Missing or invalid `document` field for TrashedDocument"),
+        };
+        let mut timestamp = |field: &str| -> anyhow::Result<UnixTimestamp> {
+            match fields.remove(field) {
+                Some(ConvexValue::Int64(nanos)) => Ok(UnixTimestamp::from_nanos(nanos.try_into()?)),
+                _ => anyhow::bail!("Missing or invalid `{field}` field for TrashedDocument"),
+            }
+        };
+        let deleted_at = timestamp("deletedAt")?;
+        let expires_at = timestamp("expiresAt")?;
+        Ok(Self {
+            table_name,
+            document_id,
+            document,
+            deleted_at,
+            expires_at,
+        })
+    }
+}
+
+pub struct TrashModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> TrashModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Deletes the document at `id`, moving it to the trash if its table's
+    /// schema enables soft delete. Returns the document as it was stored.
+    pub(crate) async fn delete(
+        &mut self,
+        id: ResolvedDocumentId,
+    ) -> anyhow::Result<ResolvedDocument> {
+        let table_name = self.tx.table_mapping().tablet_name(id.tablet_id)?;
+        let soft_delete = match self.tx.active_schema(self.namespace).await? {
+            Some(schema) => schema
+                .tables
+                .get(&table_name)
+                .and_then(|table| table.soft_delete),
+            None => None,
+        };
+        let document = self.tx.delete_inner(id).await?;
+        let Some(soft_delete) = soft_delete else {
+            return Ok(document);
+        };
+        let deleted_at = self.tx.runtime().unix_timestamp();
+        let trashed = TrashedDocument {
+            table_name,
+            document_id: id.into(),
+            document: document.value().clone().into_value(),
+            deleted_at,
+            expires_at: deleted_at + soft_delete.retention(),
+        };
+        // The trash is written on behalf of whoever deleted the document, so
+        // this skips `SystemMetadataModel`'s authorization check.
+        let table_id = self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .id(&TRASH_TABLE)?;
+        let trash_id = self.tx.id_generator.generate_resolved(table_id);
+        let creation_time = self.tx.next_creation_time.increment()?;
+        let trash_document = ResolvedDocument::new(trash_id, creation_time, trashed.try_into()?)?;
+        self.tx.insert_document(trash_document).await?;
+        Ok(document)
+    }
+
+    async fn get(
+        &mut self,
+        document_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<TrashedDocument>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: TRASH_BY_DOCUMENT_ID_INDEX.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                DOCUMENT_ID_FIELD.clone(),
+                ConvexValue::try_from(document_id.encode())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The documents deleted from `table_name` that are still in the trash,
+    /// most recently deleted first.
+    pub async fn list(
+        &mut self,
+        table_name: &TableName,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<TrashedDocument>>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("list_trash"));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: TRASH_BY_TABLE_NAME_INDEX.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                TABLE_NAME_FIELD.clone(),
+                ConvexValue::try_from(String::from(table_name.clone()))?.into(),
+            )],
+            order: Order::Desc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut trashed = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            trashed.push(document.try_into()?);
+        }
+        Ok(trashed)
+    }
+
+    /// Moves a soft-deleted document back into its table with its original ID
+    /// and creation time.
+    pub async fn restore(
+        &mut self,
+        document_id: DeveloperDocumentId,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("restore_document"));
+        }
+        let Some(trashed) = self.get(document_id).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "DocumentNotInTrash",
+                format!("Document {document_id} isn't in the trash. It may have expired."),
+            ));
+        };
+        let table_mapping = self.tx.table_mapping().namespace(self.namespace);
+        let Ok(table_id) = table_mapping.id(&trashed.table_name) else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "TableNotFound",
+                format!(
+                    "Can't restore document {document_id} because its table {} no longer exists",
+                    trashed.table_name
+                ),
+            ));
+        };
+        anyhow::ensure!(
+            table_id.table_number == document_id.table(),
+            ErrorMetadata::bad_request(
+                "TableNotFound",
+                format!(
+                    "Can't restore document {document_id} because its table {} has been replaced",
+                    trashed.table_name
+                ),
+            )
+        );
+        let id = ResolvedDocumentId::new(table_id.tablet_id, document_id);
+        let creation_time = match trashed
+            .document
+            .get(&FieldName::from(CREATION_TIME_FIELD.clone()))
+        {
+            Some(ConvexValue::Float64(ts)) => CreationTime::try_from(*ts)?,
+            _ => anyhow::bail!("Trashed document {document_id} is missing its creation time"),
+        };
+        let stored = ResolvedDocument::new(id, creation_time, trashed.document.clone())?;
+        let document = self.tx.decrypt_document(stored)?;
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(trashed.id())
+            .await?;
+        self.tx.insert_document(document).await
+    }
+
+    /// IDs of the trash entries that expired before `now`, oldest first.
+    pub async fn expired(
+        &mut self,
+        now: UnixTimestamp,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        let query = Query::index_range(IndexRange {
+            index_name: TRASH_BY_EXPIRES_AT_INDEX.clone(),
+            range: vec![IndexRangeExpression::Lt(
+                EXPIRES_AT_FIELD.clone(),
+                timestamp_value(now)?.into(),
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut ids = vec![];
+        while let Some(document) = query_stream.next(self.tx, None).await? {
+            ids.push(document.id());
+        }
+        Ok(ids)
+    }
+
+    /// Deletes an expired trash entry for good.
+    pub async fn purge(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("purge_trash"));
+        }
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(id)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::{
+        bootstrap_model::index::IndexMetadata,
+        db_schema,
+        schemas::{
+            DocumentSchema,
+            SoftDeleteSchema,
+        },
+        types::TableName,
+    };
+    use keybroker::Identity;
+    use runtime::testing::TestRuntime;
+    use value::{
+        assert_obj,
+        TableNamespace,
+    };
+
+    use super::{
+        TrashModel,
+        TrashTable,
+        TRASH_TABLE,
+    };
+    use crate::{
+        defaults::SystemTable,
+        test_helpers::new_test_database,
+        IndexModel,
+        SchemaModel,
+        UserFacingModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_soft_delete_and_restore(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = new_test_database(rt.clone()).await;
+        let namespace = TableNamespace::test_user();
+        let mut tx = db.begin(Identity::system()).await?;
+        tx.create_system_table_testing(namespace, &TRASH_TABLE, None)
+            .await?;
+        for index in TrashTable.indexes() {
+            IndexModel::new(&mut tx)
+                .add_system_index(
+                    namespace,
+                    IndexMetadata::new_enabled(index.name, index.fields),
+                )
+                .await?;
+        }
+        let messages: TableName = "messages".parse()?;
+        let mut db_schema = db_schema!(
+            "messages" => DocumentSchema::Any,
+            "logs" => DocumentSchema::Any
+        );
+        db_schema.tables.get_mut(&messages).unwrap().soft_delete =
+            Some(SoftDeleteSchema { retention_days: 7 });
+        let mut model = SchemaModel::new_root_for_test(&mut tx);
+        let (schema_id, _state) = model.submit_pending(db_schema).await?;
+        model.mark_validated(schema_id).await?;
+        model.mark_active(schema_id).await?;
+
+        let message_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert(messages.clone(), assert_obj!("body" => "hello"))
+            .await?;
+        let log_id = UserFacingModel::new_root_for_test(&mut tx)
+            .insert("logs".parse()?, assert_obj!("line" => "hello"))
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::system()).await?;
+        let inserted = UserFacingModel::new_root_for_test(&mut tx)
+            .get(message_id, None)
+            .await?
+            .unwrap();
+        UserFacingModel::new_root_for_test(&mut tx)
+            .delete(message_id)
+            .await?;
+        UserFacingModel::new_root_for_test(&mut tx)
+            .delete(log_id)
+            .await?;
+        db.commit(tx).await?;
+
+        // The deleted message is only in the trash, and the log, whose table
+        // doesn't enable soft delete, is gone.
+        let mut tx = db.begin(Identity::system()).await?;
+        assert!(UserFacingModel::new_root_for_test(&mut tx)
+            .get(message_id, None)
+            .await?
+            .is_none());
+        let trashed = TrashModel::new(&mut tx, namespace)
+            .list(&messages, 10)
+            .await?;
+        assert_eq!(trashed.len(), 1);
+        assert_eq!(trashed[0].document_id, message_id);
+        assert_eq!(
+            trashed[0].expires_at - trashed[0].deleted_at,
+            Duration::from_secs(7 * 24 * 60 * 60)
+        );
+        assert!(TrashModel::new(&mut tx, namespace)
+            .list(&"logs".parse()?, 10)
+            .await?
+            .is_empty());
+        let expires_at = trashed[0].expires_at;
+        assert!(TrashModel::new(&mut tx, namespace)
+            .expired(trashed[0].deleted_at, 10)
+            .await?
+            .is_empty());
+        assert_eq!(
+            TrashModel::new(&mut tx, namespace)
+                .expired(expires_at + Duration::from_secs(1), 10)
+                .await?,
+            vec![trashed[0].id()]
+        );
+
+        // Restoring puts the document back with its ID and creation time.
+        TrashModel::new(&mut tx, namespace)
+            .restore(message_id)
+            .await?;
+        db.commit(tx).await?;
+        let mut tx = db.begin(Identity::system()).await?;
+        let restored = UserFacingModel::new_root_for_test(&mut tx)
+            .get(message_id, None)
+            .await?
+            .unwrap();
+        assert_eq!(restored, inserted);
+        assert!(TrashModel::new(&mut tx, namespace)
+            .list(&messages, 10)
+            .await?
+            .is_empty());
+        let error = TrashModel::new(&mut tx, namespace)
+            .restore(message_id)
+            .await
+            .unwrap_err();
+        assert!(format!("{error}").contains("isn't in the trash"));
+        Ok(())
+    }
+}
@@ -0,0 +1,489 @@
+//! Triggers are mutations that a table's schema declares to run whenever one
+//! of its documents is written. They run inside the writing transaction just
+//! before it commits, so a trigger that throws rejects the whole transaction
+//! and the writes a trigger makes commit atomically with the writes that
+//! caused them.
+//!
+//! Triggers run in rounds. Each round passes the writes made since the
+//! previous round to the `Before` triggers of their tables and then to the
+//! `After` triggers. Writes made by a trigger are attributed to it, and run
+//! triggers in the next round. A trigger that would run again because of its
+//! own writes, directly or through other triggers, is a cycle and fails the
+//! transaction, as does a chain of more than [`MAX_TRIGGER_DEPTH`] triggers.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use common::{
+    document::DocumentUpdate,
+    errors::JsError,
+    runtime::Runtime,
+    schemas::{
+        TriggerSchema,
+        TriggerTiming,
+    },
+    types::TableName,
+};
+use sync_types::CanonicalizedUdfPath;
+use value::{
+    ResolvedDocumentId,
+    TableNamespace,
+};
+
+use crate::Transaction;
+
+/// The longest chain of triggers that a write can cause, counting the
+/// trigger that handles the original write.
+pub const MAX_TRIGGER_DEPTH: usize = 16;
+
+/// Executes trigger functions. The database doesn't run JavaScript, so the
+/// application provides this to run each trigger's mutation in the
+/// transaction.
+#[async_trait]
+pub trait TriggerRunner<RT: Runtime>: Send + Sync {
+    /// Run `trigger` for a single write, returning the transaction with the
+    /// trigger's reads and writes applied, or the error it threw.
+    async fn run_trigger(
+        &self,
+        tx: Transaction<RT>,
+        trigger: &TriggerSchema,
+        change: DocumentUpdate,
+    ) -> anyhow::Result<(Transaction<RT>, Result<(), JsError>)>;
+}
+
+/// A write waiting for its table's triggers, with the triggers that caused
+/// it, outermost first.
+struct PendingChange {
+    table_name: TableName,
+    change: DocumentUpdate,
+    caused_by: Vec<CanonicalizedUdfPath>,
+}
+
+/// Run the triggers of the tables in `namespace` for the writes `tx` has
+/// recorded since `Transaction::record_writes_for_triggers`, and for the
+/// writes those triggers make in turn. Returns the first error a trigger
+/// throws, in which case the transaction shouldn't be committed.
+pub async fn run_triggers<RT: Runtime>(
+    mut tx: Transaction<RT>,
+    namespace: TableNamespace,
+    runner: &dyn TriggerRunner<RT>,
+) -> anyhow::Result<(Transaction<RT>, Result<(), JsError>)> {
+    // Skip reading the schema if there's nothing to do.
+    if tx.trigger_changes.as_ref().map_or(true, Vec::is_empty) {
+        return Ok((tx, Ok(())));
+    }
+    let Some(schema) = tx.active_schema(namespace).await? else {
+        tx.take_trigger_changes();
+        return Ok((tx, Ok(())));
+    };
+    let triggers: BTreeMap<TableName, Vec<TriggerSchema>> = schema
+        .tables
+        .iter()
+        .filter(|(_, table)| !table.triggers.is_empty())
+        .map(|(table_name, table)| (table_name.clone(), table.triggers.clone()))
+        .collect();
+
+    let mut round = pending_changes(&mut tx, namespace, &triggers, vec![])?;
+    while !round.is_empty() {
+        let mut next_round = vec![];
+        for timing in [TriggerTiming::Before, TriggerTiming::After] {
+            for pending in &round {
+                let table_triggers = triggers[&pending.table_name]
+                    .iter()
+                    .filter(|trigger| trigger.timing == timing);
+                for trigger in table_triggers {
+                    if pending.caused_by.contains(&trigger.function) {
+                        let error = trigger_cycle_error(&trigger.function, &pending.caused_by);
+                        return Ok((tx, Err(error)));
+                    }
+                    if pending.caused_by.len() >= MAX_TRIGGER_DEPTH {
+                        let error = trigger_depth_error(&trigger.function, &pending.caused_by);
+                        return Ok((tx, Err(error)));
+                    }
+                    let change = DocumentUpdate {
+                        id: pending.change.id,
+                        old_document: pending
+                            .change
+                            .old_document
+                            .clone()
+                            .map(|document| tx.decrypt_document(document))
+                            .transpose()?,
+                        new_document: pending
+                            .change
+                            .new_document
+                            .clone()
+                            .map(|document| tx.decrypt_document(document))
+                            .transpose()?,
+                    };
+                    let result;
+                    (tx, result) = runner.run_trigger(tx, trigger, change).await?;
+                    if let Err(error) = result {
+                        return Ok((tx, Err(error)));
+                    }
+                    let mut caused_by = pending.caused_by.clone();
+                    caused_by.push(trigger.function.clone());
+                    next_round.extend(pending_changes(&mut tx, namespace, &triggers, caused_by)?);
+                }
+            }
+        }
+        round = next_round;
+    }
+    Ok((tx, Ok(())))
+}
+
+/// Take the writes `tx` has recorded, keeping those to tables with triggers.
+/// Several writes to the same document are combined into one change from
+/// its first old value to its last new value.
+fn pending_changes<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    namespace: TableNamespace,
+    triggers: &BTreeMap<TableName, Vec<TriggerSchema>>,
+    caused_by: Vec<CanonicalizedUdfPath>,
+) -> anyhow::Result<Vec<PendingChange>> {
+    let mut pending: Vec<PendingChange> = vec![];
+    let mut positions: BTreeMap<ResolvedDocumentId, usize> = BTreeMap::new();
+    for change in tx.take_trigger_changes() {
+        if tx.table_mapping().tablet_namespace(change.id.tablet_id)? != namespace {
+            continue;
+        }
+        let table_name = tx.table_mapping().tablet_name(change.id.tablet_id)?;
+        if !triggers.contains_key(&table_name) {
+            continue;
+        }
+        match positions.get(&change.id) {
+            Some(&position) => pending[position].change.new_document = change.new_document,
+            None => {
+                positions.insert(change.id, pending.len());
+                pending.push(PendingChange {
+                    table_name,
+                    change,
+                    caused_by: caused_by.clone(),
+                });
+            },
+        }
+    }
+    // A document that was inserted and then deleted doesn't need triggers.
+    pending.retain(|pending| {
+        pending.change.old_document.is_some() || pending.change.new_document.is_some()
+    });
+    Ok(pending)
+}
+
+fn trigger_chain(function: &CanonicalizedUdfPath, caused_by: &[CanonicalizedUdfPath]) -> String {
+    caused_by
+        .iter()
+        .chain([function])
+        .map(|function| function.to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+fn trigger_cycle_error(
+    function: &CanonicalizedUdfPath,
+    caused_by: &[CanonicalizedUdfPath],
+) -> JsError {
+    JsError::from_message(format!(
+        "Trigger {function} would run again because of its own writes: {}. Triggers can't cause \
+         themselves to run.",
+        trigger_chain(function, caused_by)
+    ))
+}
+
+fn trigger_depth_error(
+    function: &CanonicalizedUdfPath,
+    caused_by: &[CanonicalizedUdfPath],
+) -> JsError {
+    JsError::from_message(format!(
+        "Too many nested triggers: {}. A write can cause at most {MAX_TRIGGER_DEPTH} triggers to \
+         run in a chain.",
+        trigger_chain(function, caused_by)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use common::{
+        document::DocumentUpdate,
+        errors::JsError,
+        runtime::Runtime,
+        schemas::{
+            DatabaseSchema,
+            TableDefinition,
+            TriggerSchema,
+            TriggerTiming,
+        },
+        types::TableName,
+    };
+    use keybroker::Identity;
+    use parking_lot::Mutex;
+    use runtime::testing::TestRuntime;
+    use sync_types::CanonicalizedUdfPath;
+    use value::{
+        assert_obj,
+        ConvexValue,
+        TableNamespace,
+    };
+
+    use super::{
+        run_triggers,
+        TriggerRunner,
+        MAX_TRIGGER_DEPTH,
+    };
+    use crate::{
+        test_helpers::new_test_database,
+        Database,
+        SchemaModel,
+        SystemMetadataModel,
+        TestFacingModel,
+        Transaction,
+    };
+
+    /// Runs triggers by name: "reject" throws, "count" counts messages in the
+    /// `counts` table, and "echo" writes to the `echoes` table.
+    #[derive(Default)]
+    struct TestRunner {
+        calls: Mutex<Vec<(String, TriggerTiming)>>,
+    }
+
+    #[async_trait]
+    impl<RT: Runtime> TriggerRunner<RT> for TestRunner {
+        async fn run_trigger(
+            &self,
+            mut tx: Transaction<RT>,
+            trigger: &TriggerSchema,
+            change: DocumentUpdate,
+        ) -> anyhow::Result<(Transaction<RT>, Result<(), JsError>)> {
+            let name = trigger.function.function_name().to_string();
+            self.calls.lock().push((name.clone(), trigger.timing));
+            match &name[..] {
+                "reject" => {
+                    let error = JsError::from_message("rejected".to_string());
+                    return Ok((tx, Err(error)));
+                },
+                "count" => {
+                    let is_insert = change.old_document.is_none();
+                    TestFacingModel::new(&mut tx)
+                        .insert(&"counts".parse()?, assert_obj!("insert" => is_insert))
+                        .await?;
+                },
+                "echo" => {
+                    TestFacingModel::new(&mut tx)
+                        .insert(&"echoes".parse()?, assert_obj!())
+                        .await?;
+                },
+                _ => {},
+            }
+            Ok((tx, Ok(())))
+        }
+    }
+
+    fn trigger(timing: TriggerTiming, function: &str) -> anyhow::Result<TriggerSchema> {
+        Ok(TriggerSchema {
+            timing,
+            function: format!("triggers.js:{function}").parse::<CanonicalizedUdfPath>()?,
+        })
+    }
+
+    async fn set_triggers(
+        database: &Database<TestRuntime>,
+        tables: Vec<(&str, Vec<TriggerSchema>)>,
+    ) -> anyhow::Result<()> {
+        let mut tx = database.begin(Identity::system()).await?;
+        let schema = DatabaseSchema {
+            tables: tables
+                .into_iter()
+                .map(|(table_name, triggers)| {
+                    let table_name: TableName = table_name.parse()?;
+                    let table = TableDefinition {
+                        table_name: table_name.clone(),
+                        indexes: Default::default(),
+                        search_indexes: Default::default(),
+                        vector_indexes: Default::default(),
+                        document_type: None,
+                        encrypted_fields: Default::default(),
+                        triggers,
+                        track_updates: false,
+                        soft_delete: None,
+                        id_strategy: Default::default(),
+                        sketches: Default::default(),
+                    };
+                    Ok((table_name, table))
+                })
+                .collect::<anyhow::Result<_>>()?,
+            schema_validation: false,
+        };
+        let namespace = TableNamespace::test_user();
+        let mut model = SchemaModel::new(&mut tx, namespace);
+        let (schema_id, _) = model.submit_pending(schema).await?;
+        model.mark_validated(schema_id).await?;
+        model.mark_active(schema_id).await?;
+        database.commit(tx).await?;
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_triggers_run_in_order(rt: TestRuntime) -> anyhow::Result<()> {
+        let database = new_test_database(rt).await;
+        set_triggers(
+            &database,
+            vec![(
+                "messages",
+                vec![
+                    trigger(TriggerTiming::After, "count")?,
+                    trigger(TriggerTiming::Before, "validate")?,
+                ],
+            )],
+        )
+        .await?;
+        let namespace = TableNamespace::test_user();
+        let runner = TestRunner::default();
+
+        let mut tx = database.begin(Identity::system()).await?;
+        tx.record_writes_for_triggers();
+        let id = TestFacingModel::new(&mut tx)
+            .insert(&"messages".parse()?, assert_obj!("body" => "hi"))
+            .await?;
+        // Writes to the same document are combined.
+        TestFacingModel::new(&mut tx)
+            .replace(id, assert_obj!("body" => "hello"))
+            .await?;
+        // Documents inserted and then deleted don't run triggers.
+        let deleted = TestFacingModel::new(&mut tx)
+            .insert(&"messages".parse()?, assert_obj!("body" => "bye"))
+            .await?;
+        SystemMetadataModel::new(&mut tx, namespace)
+            .delete(deleted)
+            .await?;
+        let (mut tx, result) = run_triggers(tx, namespace, &runner).await?;
+        result?;
+        assert_eq!(
+            *runner.calls.lock(),
+            vec![
+                ("validate".to_string(), TriggerTiming::Before),
+                ("count".to_string(), TriggerTiming::After),
+            ]
+        );
+        assert_eq!(tx.count(namespace, &"counts".parse()?).await?, 1);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_trigger_rejects_write(rt: TestRuntime) -> anyhow::Result<()> {
+        let database = new_test_database(rt).await;
+        set_triggers(
+            &database,
+            vec![(
+                "messages",
+                vec![
+                    trigger(TriggerTiming::Before, "reject")?,
+                    trigger(TriggerTiming::After, "count")?,
+                ],
+            )],
+        )
+        .await?;
+        let runner = TestRunner::default();
+        let mut tx = database.begin(Identity::system()).await?;
+        tx.record_writes_for_triggers();
+        TestFacingModel::new(&mut tx)
+            .insert(&"messages".parse()?, assert_obj!())
+            .await?;
+        let (_, result) = run_triggers(tx, TableNamespace::test_user(), &runner).await?;
+        assert_eq!(result.unwrap_err().message, "rejected");
+        // The after trigger didn't run.
+        assert_eq!(runner.calls.lock().len(), 1);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_trigger_cycle(rt: TestRuntime) -> anyhow::Result<()> {
+        let database = new_test_database(rt).await;
+        // "echo" writes to `echoes`, whose trigger writes to `echoes` again.
+        set_triggers(
+            &database,
+            vec![
+                ("messages", vec![trigger(TriggerTiming::After, "echo")?]),
+                ("echoes", vec![trigger(TriggerTiming::After, "echo")?]),
+            ],
+        )
+        .await?;
+        let runner = TestRunner::default();
+        let mut tx = database.begin(Identity::system()).await?;
+        tx.record_writes_for_triggers();
+        TestFacingModel::new(&mut tx)
+            .insert(&"messages".parse()?, assert_obj!())
+            .await?;
+        let (_, result) = run_triggers(tx, TableNamespace::test_user(), &runner).await?;
+        let error = result.unwrap_err();
+        assert!(
+            error
+                .message
+                .contains("triggers.js:echo -> triggers.js:echo"),
+            "{error}"
+        );
+        assert_eq!(runner.calls.lock().len(), 1);
+        Ok(())
+    }
+
+    /// Counts down through a chain of tables `t<n>`, `t<n - 1>`, ..., `t0`,
+    /// with each table's trigger writing to the next.
+    struct ChainRunner;
+
+    #[async_trait]
+    impl<RT: Runtime> TriggerRunner<RT> for ChainRunner {
+        async fn run_trigger(
+            &self,
+            mut tx: Transaction<RT>,
+            _trigger: &TriggerSchema,
+            change: DocumentUpdate,
+        ) -> anyhow::Result<(Transaction<RT>, Result<(), JsError>)> {
+            let document = change.new_document.unwrap();
+            let ConvexValue::Int64(n) = document.value().get("n").unwrap() else {
+                anyhow::bail!("Missing n");
+            };
+            if *n > 0 {
+                let table_name: TableName = format!("t{}", n - 1).parse()?;
+                TestFacingModel::new(&mut tx)
+                    .insert(&table_name, assert_obj!("n" => n - 1))
+                    .await?;
+            }
+            Ok((tx, Ok(())))
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_trigger_depth(rt: TestRuntime) -> anyhow::Result<()> {
+        let database = new_test_database(rt).await;
+        let tables: Vec<_> = (0..=MAX_TRIGGER_DEPTH).map(|n| format!("t{n}")).collect();
+        set_triggers(
+            &database,
+            tables
+                .iter()
+                .map(|table| Ok((&table[..], vec![trigger(TriggerTiming::After, table)?])))
+                .collect::<anyhow::Result<_>>()?,
+        )
+        .await?;
+        let namespace = TableNamespace::test_user();
+
+        let max_depth = MAX_TRIGGER_DEPTH as i64;
+        for (n, succeeds) in [(max_depth - 1, true), (max_depth, false)] {
+            let mut tx = database.begin(Identity::system()).await?;
+            tx.record_writes_for_triggers();
+            TestFacingModel::new(&mut tx)
+                .insert(&format!("t{n}").parse()?, assert_obj!("n" => n))
+                .await?;
+            let (_, result) = run_triggers(tx, namespace, &ChainRunner).await?;
+            match result {
+                Ok(()) => assert!(succeeds),
+                Err(error) => {
+                    assert!(!succeeds);
+                    assert!(
+                        error.message.contains("Too many nested triggers"),
+                        "{error}"
+                    );
+                },
+            }
+        }
+        Ok(())
+    }
+}
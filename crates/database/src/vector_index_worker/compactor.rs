@@ -78,6 +78,8 @@ pub async fn compact_vector_indexes_in_test<RT: Runtime>(
 #[cfg(test)]
 mod tests {
 
+    use anyhow::Context;
+    use common::types::IndexName;
     use itertools::Itertools;
     use keybroker::Identity;
     use maplit::{
@@ -85,6 +87,7 @@ mod tests {
         btreeset,
     };
     use runtime::testing::TestRuntime;
+    use value::TableNamespace;
     use vector::VectorSearch;
 
     use crate::{
@@ -93,7 +96,9 @@ mod tests {
             VECTOR_SIZE_BYTES,
         },
         vector_index_worker::compactor::CompactionConfig,
+        TableModel,
         UserFacingModel,
+        VectorCounts,
     };
 
     #[convex_macro::test_runtime]
@@ -493,6 +498,82 @@ mod tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn compact_with_index_over_delete_threshold_compacts_away_deletes(
+        rt: TestRuntime,
+    ) -> anyhow::Result<()> {
+        let fixtures =
+            VectorFixtures::new_with_config(rt.clone(), CompactionConfig::vector()).await?;
+        let index_data = fixtures.enabled_vector_index().await?;
+
+        // Create a segment that's well under the default small segment threshold size.
+        let mut ids = vec![];
+        for _ in 0..3 {
+            ids.push(
+                fixtures
+                    .add_document_vec_array(index_data.index_name.table(), [3f64, 4f64])
+                    .await?,
+            );
+        }
+        fixtures.backfill().await?;
+
+        // Delete all but 1 vector.
+        let mut tx = fixtures.db.begin_system().await?;
+        for id in &ids[0..ids.len() - 1] {
+            UserFacingModel::new_root_for_test(&mut tx)
+                .delete((*id).into())
+                .await?;
+        }
+        fixtures.db.commit(tx).await?;
+        fixtures.backfill().await?;
+        assert_eq!(
+            vector_counts(&fixtures, &index_data.index_name).await?,
+            VectorCounts {
+                live: 1,
+                deleted: 2
+            }
+        );
+
+        // Most of the index is deleted, so the small segment is recompacted.
+        let compactor = fixtures.new_compactor().await?;
+        let (metrics, _) = compactor.step().await?;
+        assert_eq!(metrics, btreemap! { index_data.resolved_index_name => 1 });
+
+        let segments = fixtures
+            .get_segments_metadata(index_data.index_name.clone())
+            .await?;
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].num_deleted, 0);
+        assert_eq!(
+            vector_counts(&fixtures, &index_data.index_name).await?,
+            VectorCounts {
+                live: 1,
+                deleted: 0
+            }
+        );
+
+        // Without deletes there's nothing left to compact.
+        let (metrics, _) = compactor.step().await?;
+        assert!(metrics.is_empty());
+        Ok(())
+    }
+
+    async fn vector_counts(
+        fixtures: &VectorFixtures,
+        index_name: &IndexName,
+    ) -> anyhow::Result<VectorCounts> {
+        let mut tx = fixtures.db.begin_system().await?;
+        let stats = TableModel::new(&mut tx)
+            .statistics(TableNamespace::test_user(), index_name.table())
+            .await?;
+        stats
+            .indexes
+            .into_iter()
+            .find(|index| &index.name == index_name.descriptor())
+            .and_then(|index| index.vector_counts)
+            .context("vector index missing from statistics")
+    }
+
     #[convex_macro::test_runtime]
     async fn compact_with_large_segments_over_delete_threshold_compacts_away_deletes(
         rt: TestRuntime,
@@ -93,6 +93,11 @@ impl Writes {
         self.updates.is_empty()
     }
 
+    /// Has the active transaction written to `document_id`?
+    pub fn contains(&self, document_id: &ResolvedDocumentId) -> bool {
+        self.updates.contains_key(document_id)
+    }
+
     pub fn update(
         &mut self,
         bootstrap_tables: BootstrapTableIds,
@@ -54,6 +54,7 @@ pub enum ErrorCode {
     Forbidden,
     NotFound,
     ClientDisconnect,
+    RateLimited,
 
     Overloaded,
     RejectedBeforeExecution,
@@ -178,6 +179,23 @@ impl ErrorMetadata {
         }
     }
 
+    /// The client has sent too many requests and should retry later. Maps to
+    /// 429 in HTTP.
+    ///
+    /// The short_msg should be a CapitalCamelCased describing the error (eg
+    /// TooManyRequests). The msg should be a descriptive message targeted
+    /// toward the developer.
+    pub fn rate_limited(
+        short_msg: impl Into<Cow<'static, str>>,
+        msg: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            code: ErrorCode::RateLimited,
+            short_msg: short_msg.into(),
+            msg: msg.into(),
+        }
+    }
+
     // This is similar to `overloaded` but also guarantees the request was
     // rejected before it has been started. You should generally prefer to use
     // `overloaded`` instead of this error code and decide if an operation is safe
@@ -303,6 +321,7 @@ impl ErrorMetadata {
             | ErrorCode::Forbidden => true,
             ErrorCode::OperationalInternalServerError
             | ErrorCode::ClientDisconnect
+            | ErrorCode::RateLimited
             | ErrorCode::OCC
             | ErrorCode::OutOfRetention
             | ErrorCode::Overloaded
@@ -312,7 +331,9 @@ impl ErrorMetadata {
 
     pub fn should_report_to_sentry(&self) -> Option<(sentry::Level, Option<f64>)> {
         match self.code {
-            ErrorCode::ClientDisconnect => None,
+            // Rate limited clients can send many requests, so these are only
+            // tracked with a metric.
+            ErrorCode::ClientDisconnect | ErrorCode::RateLimited => None,
             ErrorCode::BadRequest
             | ErrorCode::NotFound
             | ErrorCode::PaginationLimit
@@ -336,7 +357,8 @@ impl ErrorMetadata {
             | ErrorCode::PaginationLimit
             | ErrorCode::Unauthenticated
             | ErrorCode::Forbidden
-            | ErrorCode::ClientDisconnect => None,
+            | ErrorCode::ClientDisconnect
+            | ErrorCode::RateLimited => None,
             ErrorCode::OCC => Some("occ"),
             ErrorCode::OutOfRetention => Some("out_of_retention"),
             ErrorCode::Overloaded => Some("overloaded"),
@@ -356,6 +378,7 @@ impl ErrorMetadata {
             ErrorCode::ClientDisconnect => Some(&crate::metrics::CLIENT_DISCONNECT_ERROR_TOTAL),
             ErrorCode::Unauthenticated => Some(&crate::metrics::SYNC_AUTH_ERROR_TOTAL),
             ErrorCode::Forbidden => Some(&crate::metrics::FORBIDDEN_ERROR_TOTAL),
+            ErrorCode::RateLimited => Some(&crate::metrics::RATE_LIMITED_ERROR_TOTAL),
             ErrorCode::OCC => Some(&crate::metrics::COMMIT_RACE_TOTAL),
             ErrorCode::NotFound => None,
             ErrorCode::PaginationLimit => None,
@@ -375,6 +398,7 @@ impl ErrorMetadata {
             ErrorCode::OCC
             | ErrorCode::OutOfRetention
             | ErrorCode::Overloaded
+            | ErrorCode::RateLimited
             | ErrorCode::RejectedBeforeExecution => Some(CloseCode::Again),
             ErrorCode::OperationalInternalServerError => Some(CloseCode::Error),
             // These ones are client errors - so no close code - the client
@@ -410,6 +434,7 @@ impl ErrorCode {
             | ErrorCode::Overloaded
             | ErrorCode::RejectedBeforeExecution => StatusCode::SERVICE_UNAVAILABLE,
             ErrorCode::ClientDisconnect => StatusCode::REQUEST_TIMEOUT,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
         }
     }
 
@@ -420,7 +445,7 @@ impl ErrorCode {
             ErrorCode::Forbidden => tonic::Code::FailedPrecondition,
             ErrorCode::NotFound => tonic::Code::NotFound,
             ErrorCode::ClientDisconnect => tonic::Code::Aborted,
-            ErrorCode::Overloaded | ErrorCode::RejectedBeforeExecution => {
+            ErrorCode::Overloaded | ErrorCode::RejectedBeforeExecution | ErrorCode::RateLimited => {
                 tonic::Code::ResourceExhausted
             },
             ErrorCode::OCC => tonic::Code::ResourceExhausted,
@@ -435,6 +460,7 @@ impl ErrorCode {
             StatusCode::UNAUTHORIZED => Some(ErrorCode::Unauthenticated),
             StatusCode::FORBIDDEN => Some(ErrorCode::Forbidden),
             StatusCode::NOT_FOUND => Some(ErrorCode::NotFound),
+            StatusCode::TOO_MANY_REQUESTS => Some(ErrorCode::RateLimited),
             // Tries to categorize in one of the above more specific 4xx codes first,
             // otherwise categorizes as a general 4xx via BadRequest
             v if v.is_client_error() => Some(ErrorCode::BadRequest),
@@ -729,6 +755,7 @@ mod proptest {
                     ErrorMetadata::operational_internal_server_error()
                 },
                 ErrorCode::ClientDisconnect => ErrorMetadata::client_disconnect(),
+                ErrorCode::RateLimited => ErrorMetadata::rate_limited("rate", "limited"),
             })
         }
     }
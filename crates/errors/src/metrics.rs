@@ -4,4 +4,5 @@ register_convex_counter!(pub BAD_REQUEST_ERROR_TOTAL, "Count of bad request erro
 register_convex_counter!(pub CLIENT_DISCONNECT_ERROR_TOTAL, "Count of client disconnect errors");
 register_convex_counter!(pub SYNC_AUTH_ERROR_TOTAL, "Count of sync auth errors");
 register_convex_counter!(pub FORBIDDEN_ERROR_TOTAL, "Count of forbidden errors");
+register_convex_counter!(pub RATE_LIMITED_ERROR_TOTAL, "Count of rate limited errors");
 register_convex_counter!(pub COMMIT_RACE_TOTAL, "Total count of commit race errors");
@@ -170,6 +170,7 @@ impl UsageCounterState {
             UsageEvent::CurrentDatabaseStorage { tables: _ } => todo!(),
             UsageEvent::CurrentFileStorage { total_size: _ } => todo!(),
             UsageEvent::CurrentDocumentCounts { tables: _ } => todo!(),
+            UsageEvent::TableStorageSnapshot { tables: _ } => todo!(),
         }
     }
 }
@@ -98,6 +98,11 @@ pub enum UsageEvent {
     CurrentDocumentCounts {
         tables: Vec<TableDocumentCount>,
     },
+    /// The daily snapshot of each table's storage, broken down by what it's
+    /// used for. Like the Current* events, it replaces the previous snapshot.
+    TableStorageSnapshot {
+        tables: Vec<TableStorageBreakdown>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -115,6 +120,17 @@ pub struct TableDatabaseStorage {
     pub total_index_size: u64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableStorageBreakdown {
+    pub table_name: String,
+    /// The size of the table's current documents.
+    pub document_size: u64,
+    pub index_size: u64,
+    /// The size of overwritten and deleted revisions that are still retained.
+    pub historical_size: u64,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub struct TableVectorStorage {
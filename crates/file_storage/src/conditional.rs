@@ -0,0 +1,82 @@
+//! Conditional requests for stored files, so that clients with a cached copy
+//! of a file don't download it again.
+
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use common::{
+    document::CreationTime,
+    sha256::Sha256Digest,
+};
+use headers::{
+    ETag,
+    IfModifiedSince,
+    IfNoneMatch,
+    LastModified,
+};
+
+/// Identify the version of a file a client has cached. Stored files never
+/// change, so the ETag is the file's hash and the modification time is when
+/// it was stored.
+#[derive(Clone, Debug)]
+pub struct FileValidators {
+    pub sha256: Sha256Digest,
+    pub etag: ETag,
+    pub last_modified: LastModified,
+}
+
+impl FileValidators {
+    pub fn new(sha256: &Sha256Digest, creation_time: CreationTime) -> anyhow::Result<Self> {
+        let etag = format!("\"{}\"", sha256.as_base64()).parse()?;
+        let creation_time_ms: f64 = creation_time.into();
+        let last_modified =
+            SystemTime::UNIX_EPOCH + Duration::from_secs_f64(creation_time_ms / 1000.);
+        Ok(Self {
+            sha256: sha256.clone(),
+            etag,
+            last_modified: last_modified.into(),
+        })
+    }
+
+    /// The validators of a representation derived from the file, e.g. a
+    /// resized image, named by `variant`.
+    pub fn for_variant(&self, variant: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            sha256: self.sha256.clone(),
+            etag: format!("\"{}-{variant}\"", self.sha256.as_base64()).parse()?,
+            last_modified: self.last_modified,
+        })
+    }
+}
+
+/// The conditional headers of a request for a file.
+#[derive(Clone, Debug, Default)]
+pub struct FileConditions {
+    pub if_none_match: Option<IfNoneMatch>,
+    pub if_modified_since: Option<IfModifiedSince>,
+}
+
+impl FileConditions {
+    /// Whether the client's cached copy of the file is current, so a 304 can
+    /// be returned instead of the file.
+    pub fn is_not_modified(&self, validators: &FileValidators) -> bool {
+        // If-Modified-Since is ignored when If-None-Match is present, see
+        // https://www.rfc-editor.org/rfc/rfc9110#section-13.1.3.
+        if let Some(if_none_match) = &self.if_none_match {
+            return !if_none_match.precondition_passes(&validators.etag);
+        }
+        if let Some(if_modified_since) = &self.if_modified_since {
+            return !if_modified_since.is_modified(validators.last_modified.into());
+        }
+        false
+    }
+}
+
+/// A file, unless the client's cached copy is current.
+pub enum ConditionalFile<T> {
+    /// The file wasn't read from storage.
+    NotModified(FileValidators),
+    Modified(FileValidators, T),
+}
@@ -1,5 +1,8 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     ops::Bound,
     sync::Arc,
 };
@@ -7,12 +10,18 @@ use std::{
 use anyhow::Context;
 use bytes::Bytes;
 use common::{
+    document::CreationTime,
+    knobs::FILE_SCAN_TIMEOUT,
     runtime::{
         Runtime,
         UnixTimestamp,
+        WithTimeout,
     },
     sha256::Sha256Digest,
-    types::ConvexOrigin,
+    types::{
+        ConvexOrigin,
+        ObjectKey,
+    },
 };
 use database::Transaction;
 use errors::ErrorMetadata;
@@ -32,17 +41,22 @@ use headers::{
 use keybroker::{
     Identity,
     KeyBroker,
+    UploadConstraints,
 };
 use maplit::btreemap;
 use mime::Mime;
 use model::file_storage::{
     types::{
+        validate_tags_and_metadata,
+        FileScanStatus,
         FileStorageEntry,
+        FileStorageFilter,
         StorageUuid,
     },
     BatchKey,
     FileStorageId,
     FileStorageModel,
+    FileStoragePage,
 };
 use storage::{
     Storage,
@@ -66,8 +80,11 @@ use crate::{
         GetFileType,
     },
     FileRangeStream,
+    FileScanner,
     FileStorage,
     FileStream,
+    FileValidators,
+    ScanVerdict,
     TransactionalFileStorage,
 };
 
@@ -79,15 +96,24 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             rt,
             storage,
             convex_origin,
+            scanner: None,
         }
     }
 
+    /// Scan uploaded files with `scanner` before storing them.
+    pub fn with_file_scanner(mut self, scanner: Option<Arc<dyn FileScanner>>) -> Self {
+        self.scanner = scanner;
+        self
+    }
+
+    /// A URL a file satisfying `constraints` can be uploaded to.
     pub fn generate_upload_url(
         &self,
         key_broker: &KeyBroker,
         issued_ts: UnixTimestamp,
+        constraints: &UploadConstraints,
     ) -> anyhow::Result<String> {
-        let token = key_broker.issue_store_file_authorization(&self.rt, issued_ts)?;
+        let token = key_broker.issue_store_file_authorization(&self.rt, issued_ts, constraints)?;
         let origin = &self.convex_origin;
 
         Ok(format!("{origin}/api/storage/upload?token={token}"))
@@ -142,6 +168,47 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
         Ok(())
     }
 
+    /// Replace the tags and metadata of a file. Fields that are None are left
+    /// unchanged.
+    pub async fn set_file_metadata(
+        &self,
+        tx: &mut Transaction<RT>,
+        namespace: TableNamespace,
+        storage_id: FileStorageId,
+        tags: Option<BTreeSet<String>>,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<()> {
+        validate_tags_and_metadata(
+            tags.as_ref().unwrap_or(&BTreeSet::new()),
+            metadata.as_ref().unwrap_or(&BTreeMap::new()),
+        )?;
+        let success = FileStorageModel::new(tx, namespace)
+            .set_tags_and_metadata(storage_id.clone(), tags, metadata)
+            .await?;
+        if !success {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "StorageIdNotFound",
+                format!("storage id {storage_id} not found"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns a page of the files matching `filter`, oldest first. See
+    /// [`FileStorageModel::list_page`].
+    pub async fn list_files(
+        &self,
+        tx: &mut Transaction<RT>,
+        namespace: TableNamespace,
+        filter: &FileStorageFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<FileStoragePage> {
+        FileStorageModel::new(tx, namespace)
+            .list_page(filter, cursor, limit)
+            .await
+    }
+
     pub async fn get_file_entry(
         &self,
         tx: &mut Transaction<RT>,
@@ -154,6 +221,28 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             .context("batch_key missing")?
     }
 
+    /// Like `get_file_entry`, along with the file's validators for
+    /// conditional requests.
+    pub async fn get_file_entry_with_validators(
+        &self,
+        tx: &mut Transaction<RT>,
+        namespace: TableNamespace,
+        storage_id: FileStorageId,
+    ) -> anyhow::Result<Option<(FileStorageEntry, FileValidators)>> {
+        let Some(doc) = FileStorageModel::new(tx, namespace)
+            .get_file(storage_id)
+            .await?
+        else {
+            return Ok(None);
+        };
+        let creation_time = doc
+            .creation_time()
+            .context("File storage entry missing creation time")?;
+        let entry = doc.into_value();
+        let validators = FileValidators::new(&entry.sha256, creation_time)?;
+        Ok(Some((entry, validators)))
+    }
+
     pub async fn get_file_entry_batch(
         &self,
         tx: &mut Transaction<RT>,
@@ -209,12 +298,25 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
         usage_tracker: impl StorageUsageTracker + Clone + 'static,
         get_file_type: GetFileType,
     ) -> anyhow::Result<FileRangeStream> {
+        if file.is_blocked() {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "FileQuarantined",
+                format!(
+                    "File {} was quarantined by the file scanner and can't be read",
+                    file.storage_id
+                ),
+            ));
+        }
         let FileStorageEntry {
             storage_id: _,
             storage_key,
             sha256: _,
             size,
             content_type,
+            tags: _,
+            metadata: _,
+            storage_class: _,
+            scan_status: _,
         } = file;
 
         let content_type = match content_type {
@@ -331,6 +433,7 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
         tracing::info!(
             "Wrote file {size} to {storage_key:?}. Total:{elapsed:?} ContentType:{content_type:?}",
         );
+        let scan_status = self.scan_file(&storage_key).await?;
 
         let entry = FileStorageEntry {
             storage_id,
@@ -338,11 +441,49 @@ impl<RT: Runtime> TransactionalFileStorage<RT> {
             sha256: actual_sha256,
             size: size.try_into()?,
             content_type: content_type.map(|ct| ct.to_string()),
+            tags: BTreeSet::new(),
+            metadata: BTreeMap::new(),
+            storage_class: None,
+            scan_status,
         };
 
         Ok(entry)
     }
 
+    /// Run a file that was just uploaded to `storage_key` through the file
+    /// scanner, if one is configured. The upload fails if the file can't be
+    /// scanned, so no file is served without being scanned.
+    pub(crate) async fn scan_file(
+        &self,
+        storage_key: &ObjectKey,
+    ) -> anyhow::Result<Option<FileScanStatus>> {
+        let Some(scanner) = &self.scanner else {
+            return Ok(None);
+        };
+        let timer = metrics::scan_file_timer();
+        let stream = self
+            .storage
+            .get(storage_key)
+            .await?
+            .with_context(|| format!("Uploaded object {storage_key:?} not found"))?
+            .stream;
+        let verdict = self
+            .rt
+            .with_timeout("scan_file", *FILE_SCAN_TIMEOUT, scanner.scan(stream))
+            .await
+            .context("Failed to scan uploaded file")?;
+        timer.finish();
+        let scan_status = match verdict {
+            ScanVerdict::Clean => FileScanStatus::Clean,
+            ScanVerdict::Infected(signature) => {
+                tracing::warn!("Quarantining file {storage_key:?} flagged as {signature}");
+                metrics::log_file_scan_blocked();
+                FileScanStatus::Blocked { signature }
+            },
+        };
+        Ok(Some(scan_status))
+    }
+
     /// Stores a file entry generated by upload_file(). The caller is
     /// responsible to track usage. If you are outside of the
     /// isolate environment, it is recommended to use FileStorage::store_file
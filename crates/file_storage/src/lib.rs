@@ -17,10 +17,32 @@ use headers::{
 use model::file_storage::types::StorageUuid;
 use storage::Storage;
 
+mod conditional;
 mod core;
 mod metrics;
+mod scanner;
 #[cfg(test)]
 mod tests;
+mod upload_sessions;
+
+pub use conditional::{
+    ConditionalFile,
+    FileConditions,
+    FileValidators,
+};
+pub use scanner::{
+    FileScanner,
+    FileScannerConfig,
+    ScanVerdict,
+};
+pub use upload_sessions::{
+    UploadSessionStatus,
+    UploadedPart,
+    MAX_UPLOAD_PARTS,
+    MAX_UPLOAD_PART_SIZE,
+    MIN_UPLOAD_PART_SIZE,
+    UPLOAD_SESSION_VALIDITY,
+};
 
 pub struct FileStream {
     pub sha256: Sha256Digest,
@@ -47,6 +69,7 @@ pub struct TransactionalFileStorage<RT: Runtime> {
     rt: RT,
     storage: Arc<dyn Storage>,
     convex_origin: ConvexOrigin,
+    scanner: Option<Arc<dyn FileScanner>>,
 }
 
 pub struct FileMetadata {
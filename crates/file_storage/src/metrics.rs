@@ -1,5 +1,7 @@
 use metrics::{
+    log_counter,
     log_distribution_with_labels,
+    register_convex_counter,
     register_convex_histogram,
     StaticMetricLabel,
     StatusTimer,
@@ -15,6 +17,23 @@ pub fn store_file_timer() -> StatusTimer {
     StatusTimer::new(&STORE_FILE_TOTAL_SECONDS)
 }
 
+register_convex_histogram!(
+    SCAN_FILE_TOTAL_SECONDS,
+    "Duration of scanning an uploaded file for malware",
+    &STATUS_LABEL
+);
+pub fn scan_file_timer() -> StatusTimer {
+    StatusTimer::new(&SCAN_FILE_TOTAL_SECONDS)
+}
+
+register_convex_counter!(
+    FILE_SCAN_BLOCKED_TOTAL,
+    "Number of uploaded files quarantined by the file scanner"
+);
+pub fn log_file_scan_blocked() {
+    log_counter(&FILE_SCAN_BLOCKED_TOTAL, 1);
+}
+
 const GET_FILE_TYPE_LABEL: &str = "type";
 
 #[derive(Clone, Copy)]
@@ -0,0 +1,297 @@
+use std::{
+    fmt,
+    path::PathBuf,
+    str::FromStr,
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::{
+    channel::mpsc,
+    stream::BoxStream,
+    SinkExt,
+    StreamExt,
+    TryStreamExt,
+};
+use reqwest::{
+    header::CONTENT_TYPE,
+    Body,
+    Url,
+};
+use serde::Deserialize;
+use tokio::{
+    io::{
+        AsyncRead,
+        AsyncReadExt,
+        AsyncWrite,
+        AsyncWriteExt,
+    },
+    net::{
+        TcpStream,
+        UnixStream,
+    },
+};
+
+/// What a [`FileScanner`] found in a file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScanVerdict {
+    Clean,
+    // The name of the signature the file matched, e.g. `Eicar-Signature`.
+    Infected(String),
+}
+
+/// Checks uploaded files for malware before they can be served. Files the
+/// scanner flags are kept in file storage, but quarantined.
+#[async_trait]
+pub trait FileScanner: Send + Sync + fmt::Debug {
+    async fn scan(
+        &self,
+        file: BoxStream<'static, futures::io::Result<Bytes>>,
+    ) -> anyhow::Result<ScanVerdict>;
+}
+
+/// Where to send uploaded files to be scanned, given on the command line as
+/// a URL:
+/// - `clamav://<host>:<port>` for a clamd daemon listening on TCP,
+/// - `clamav+unix://<path>` for a clamd daemon listening on a unix socket,
+/// - `http://...` or `https://...` for an HTTP scanning service, see
+///   [`HttpScanner`].
+#[derive(Clone, PartialEq)]
+pub enum FileScannerConfig {
+    ClamAvTcp(String),
+    ClamAvUnix(PathBuf),
+    Http(Url),
+}
+
+impl FromStr for FileScannerConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if let Some(address) = s.strip_prefix("clamav://") {
+            anyhow::ensure!(
+                address
+                    .rsplit_once(':')
+                    .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok()),
+                "Expected `clamav://<host>:<port>`, got {s:?}"
+            );
+            return Ok(Self::ClamAvTcp(address.to_string()));
+        }
+        if let Some(path) = s.strip_prefix("clamav+unix://") {
+            anyhow::ensure!(
+                !path.is_empty(),
+                "Expected `clamav+unix://<socket path>`, got {s:?}"
+            );
+            return Ok(Self::ClamAvUnix(path.into()));
+        }
+        let url: Url = s.parse()?;
+        anyhow::ensure!(
+            matches!(url.scheme(), "http" | "https"),
+            "Unsupported file scanner {s:?}, expected a `clamav://`, `clamav+unix://`, \
+             `http://` or `https://` URL"
+        );
+        Ok(Self::Http(url))
+    }
+}
+
+// The HTTP scanner's URL may contain credentials, so only its origin is shown.
+impl fmt::Debug for FileScannerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ClamAvTcp(address) => write!(f, "clamav://{address}"),
+            Self::ClamAvUnix(path) => write!(f, "clamav+unix://{}", path.display()),
+            Self::Http(url) => write!(f, "{}", url.origin().ascii_serialization()),
+        }
+    }
+}
+
+impl FileScannerConfig {
+    pub fn scanner(&self) -> Arc<dyn FileScanner> {
+        match self {
+            Self::ClamAvTcp(address) => Arc::new(ClamAvScanner {
+                address: ClamAvAddress::Tcp(address.clone()),
+            }),
+            Self::ClamAvUnix(path) => Arc::new(ClamAvScanner {
+                address: ClamAvAddress::Unix(path.clone()),
+            }),
+            Self::Http(url) => Arc::new(HttpScanner {
+                client: reqwest::Client::new(),
+                url: url.clone(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum ClamAvAddress {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+/// Scans files with a clamd daemon, streaming them with its `INSTREAM`
+/// command.
+#[derive(Debug)]
+pub struct ClamAvScanner {
+    address: ClamAvAddress,
+}
+
+// clamd reads at most this many bytes per chunk by default.
+const CLAMAV_CHUNK_SIZE: usize = 64 * 1024;
+
+#[async_trait]
+impl FileScanner for ClamAvScanner {
+    async fn scan(
+        &self,
+        file: BoxStream<'static, futures::io::Result<Bytes>>,
+    ) -> anyhow::Result<ScanVerdict> {
+        match &self.address {
+            ClamAvAddress::Tcp(address) => {
+                clamav_instream(TcpStream::connect(address).await?, file).await
+            },
+            ClamAvAddress::Unix(path) => {
+                clamav_instream(UnixStream::connect(path).await?, file).await
+            },
+        }
+    }
+}
+
+async fn clamav_instream(
+    mut connection: impl AsyncRead + AsyncWrite + Unpin,
+    mut file: BoxStream<'static, futures::io::Result<Bytes>>,
+) -> anyhow::Result<ScanVerdict> {
+    connection.write_all(b"zINSTREAM\0").await?;
+    while let Some(bytes) = file.try_next().await? {
+        for chunk in bytes.chunks(CLAMAV_CHUNK_SIZE) {
+            connection
+                .write_all(&(chunk.len() as u32).to_be_bytes())
+                .await?;
+            connection.write_all(chunk).await?;
+        }
+    }
+    // A chunk of length zero ends the stream.
+    connection.write_all(&0u32.to_be_bytes()).await?;
+    connection.flush().await?;
+    let mut reply = vec![];
+    connection.read_to_end(&mut reply).await?;
+    parse_clamav_reply(&String::from_utf8_lossy(&reply))
+}
+
+/// Parse clamd's reply to `INSTREAM`, e.g. `stream: OK` or
+/// `stream: Eicar-Signature FOUND`.
+fn parse_clamav_reply(reply: &str) -> anyhow::Result<ScanVerdict> {
+    let reply = reply.trim_end_matches(['\0', '\n']);
+    if let Some(result) = reply.strip_prefix("stream: ") {
+        if result == "OK" {
+            return Ok(ScanVerdict::Clean);
+        }
+        if let Some(signature) = result.strip_suffix(" FOUND") {
+            return Ok(ScanVerdict::Infected(signature.to_string()));
+        }
+    }
+    anyhow::bail!("clamd failed to scan file: {reply}")
+}
+
+/// Scans files by POSTing them to an HTTP service, which responds with
+/// `{"infected": false}`, or `{"infected": true, "signature": "..."}` if the
+/// file should be quarantined.
+pub struct HttpScanner {
+    client: reqwest::Client,
+    url: Url,
+}
+
+impl fmt::Debug for HttpScanner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpScanner")
+            .field("origin", &self.url.origin().ascii_serialization())
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct HttpScanResponse {
+    infected: bool,
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+#[async_trait]
+impl FileScanner for HttpScanner {
+    async fn scan(
+        &self,
+        mut file: BoxStream<'static, futures::io::Result<Bytes>>,
+    ) -> anyhow::Result<ScanVerdict> {
+        // reqwest needs a `Sync` body, so forward the stream through a channel.
+        let (mut sender, receiver) = mpsc::channel(1);
+        let forward = async move {
+            while let Some(chunk) = file.next().await {
+                if sender.send(chunk).await.is_err() {
+                    // The request failed, which is reported below.
+                    break;
+                }
+            }
+        };
+        let request = self
+            .client
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(Body::wrap_stream(receiver))
+            .send();
+        let ((), response) = futures::join!(forward, request);
+        let response = response?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!(
+                "File scanner responded with {status}: {}",
+                body.chars().take(1000).collect::<String>()
+            );
+        }
+        let response: HttpScanResponse = response.json().await?;
+        Ok(if response.infected {
+            ScanVerdict::Infected(response.signature.unwrap_or_else(|| "unknown".to_string()))
+        } else {
+            ScanVerdict::Clean
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_clamav_reply,
+        FileScannerConfig,
+        ScanVerdict,
+    };
+
+    #[test]
+    fn test_parse_config() -> anyhow::Result<()> {
+        assert_eq!(
+            "clamav://localhost:3310".parse::<FileScannerConfig>()?,
+            FileScannerConfig::ClamAvTcp("localhost:3310".to_string())
+        );
+        assert_eq!(
+            "clamav+unix:///run/clamav/clamd.ctl".parse::<FileScannerConfig>()?,
+            FileScannerConfig::ClamAvUnix("/run/clamav/clamd.ctl".into())
+        );
+        assert_eq!(
+            "https://scanner.example.com/scan".parse::<FileScannerConfig>()?,
+            FileScannerConfig::Http("https://scanner.example.com/scan".parse()?)
+        );
+        assert!("clamav://localhost".parse::<FileScannerConfig>().is_err());
+        assert!("ftp://scanner.example.com"
+            .parse::<FileScannerConfig>()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_clamav_reply() -> anyhow::Result<()> {
+        assert_eq!(parse_clamav_reply("stream: OK\0")?, ScanVerdict::Clean);
+        assert_eq!(
+            parse_clamav_reply("stream: Win.Test.EICAR_HDB-1 FOUND\0")?,
+            ScanVerdict::Infected("Win.Test.EICAR_HDB-1".to_string())
+        );
+        assert!(parse_clamav_reply("INSTREAM size limit exceeded. ERROR\0").is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use async_trait::async_trait;
+use bytes::Bytes;
 use common::{
     runtime::Runtime,
     sha256::Sha256,
@@ -16,10 +18,23 @@ use errors::{
     ErrorMetadata,
 };
 use events::usage::NoOpUsageEventLogger;
-use futures::stream;
-use keybroker::Identity;
+use futures::{
+    stream::{
+        self,
+        BoxStream,
+    },
+    TryStreamExt,
+};
+use headers::ContentType;
+use keybroker::{
+    Identity,
+    UploadConstraints,
+};
 use model::{
-    file_storage::FileStorageId,
+    file_storage::{
+        types::FileScanStatus,
+        FileStorageId,
+    },
     test_helpers::DbFixturesWithModel,
 };
 use runtime::testing::TestRuntime;
@@ -28,7 +43,13 @@ use usage_tracking::UsageCounter;
 use value::TableNamespace;
 
 use super::FileStorage;
-use crate::TransactionalFileStorage;
+use crate::{
+    FileScanner,
+    ScanVerdict,
+    TransactionalFileStorage,
+    UploadedPart,
+    MIN_UPLOAD_PART_SIZE,
+};
 
 fn setup_file_storage(
     rt: TestRuntime,
@@ -84,3 +105,240 @@ async fn test_store_file_sha_mismatch(rt: TestRuntime) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_upload_session(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = DbFixtures::new(&rt).await?.with_model().await?.db;
+    let file_storage = setup_file_storage(rt, &database)?;
+    let usage_tracker = UsageCounter::new(Arc::new(NoOpUsageEventLogger));
+
+    let first_part = vec![1; MIN_UPLOAD_PART_SIZE as usize];
+    let last_part = vec![2; 10];
+    let contents = [first_part.clone(), last_part.clone()].concat();
+    let upload_id = file_storage
+        .create_upload_session(
+            Some(ContentType::octet_stream()),
+            Some(Sha256::hash(&contents)),
+            &UploadConstraints::default(),
+        )
+        .await?;
+    // Parts can arrive in any order and be retried.
+    file_storage
+        .upload_session_part(&upload_id, 2, last_part.into())
+        .await?;
+    file_storage
+        .upload_session_part(&upload_id, 1, vec![3; 10].into())
+        .await?;
+    let status = file_storage.upload_session_status(&upload_id).await?;
+    assert_eq!(
+        status.parts,
+        vec![
+            UploadedPart {
+                part_number: 1,
+                size: 10
+            },
+            UploadedPart {
+                part_number: 2,
+                size: 10
+            },
+        ]
+    );
+    assert!(status.storage_id.is_none());
+
+    let err: ErrorMetadata = file_storage
+        .finish_upload_session(TableNamespace::test_user(), &upload_id, &usage_tracker)
+        .await
+        .unwrap_err()
+        .downcast()?;
+    assert_eq!(err.short_msg, "InvalidPartSize");
+    file_storage
+        .upload_session_part(&upload_id, 1, first_part.into())
+        .await?;
+
+    let storage_id = file_storage
+        .finish_upload_session(TableNamespace::test_user(), &upload_id, &usage_tracker)
+        .await?;
+    // Finishing again, e.g. after the response was lost, returns the same file.
+    assert_eq!(
+        file_storage
+            .finish_upload_session(TableNamespace::test_user(), &upload_id, &usage_tracker)
+            .await?,
+        storage_id
+    );
+    let status = file_storage.upload_session_status(&upload_id).await?;
+    assert_eq!(status.storage_id, Some(storage_id));
+
+    let mut tx = database.begin(Identity::system()).await?;
+    let entry = file_storage
+        .transactional_file_storage
+        .get_file_entry(
+            &mut tx,
+            TableNamespace::test_user(),
+            FileStorageId::DocumentId(storage_id),
+        )
+        .await?
+        .unwrap();
+    assert_eq!(entry.size, contents.len() as i64);
+    assert_eq!(entry.sha256, Sha256::hash(&contents));
+    assert_eq!(
+        entry.content_type.as_deref(),
+        Some("application/octet-stream")
+    );
+
+    let err: ErrorMetadata = file_storage
+        .upload_session_part(&upload_id, 3, vec![4; 10].into())
+        .await
+        .unwrap_err()
+        .downcast()?;
+    assert_eq!(err.short_msg, "UploadSessionFinished");
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_upload_session_missing_part(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = DbFixtures::new(&rt).await?.with_model().await?.db;
+    let file_storage = setup_file_storage(rt, &database)?;
+
+    let upload_id = file_storage
+        .create_upload_session(None, None, &UploadConstraints::default())
+        .await?;
+    file_storage
+        .upload_session_part(&upload_id, 2, vec![1; 10].into())
+        .await?;
+    let err: ErrorMetadata = file_storage
+        .finish_upload_session(
+            TableNamespace::test_user(),
+            &upload_id,
+            &UsageCounter::new(Arc::new(NoOpUsageEventLogger)),
+        )
+        .await
+        .unwrap_err()
+        .downcast()?;
+    assert_eq!(err.code, ErrorCode::BadRequest);
+    assert_eq!(err.short_msg, "MissingUploadParts");
+
+    let err: ErrorMetadata = file_storage
+        .upload_session_status("not-an-upload")
+        .await
+        .unwrap_err()
+        .downcast()?;
+    assert_eq!(err.code, ErrorCode::NotFound);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_upload_session_constraints(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = DbFixtures::new(&rt).await?.with_model().await?.db;
+    let file_storage = setup_file_storage(rt, &database)?;
+    let constraints = UploadConstraints {
+        max_bytes: Some(15),
+        allowed_content_types: vec!["image/*".to_string()],
+        expires_in: None,
+    };
+
+    let err: ErrorMetadata = file_storage
+        .create_upload_session(Some(ContentType::text()), None, &constraints)
+        .await
+        .unwrap_err()
+        .downcast()?;
+    assert_eq!(err.short_msg, "ContentTypeNotAllowed");
+
+    let upload_id = file_storage
+        .create_upload_session(Some(ContentType::png()), None, &constraints)
+        .await?;
+    file_storage
+        .upload_session_part(&upload_id, 1, vec![1; 10].into())
+        .await?;
+    let err: ErrorMetadata = file_storage
+        .upload_session_part(&upload_id, 2, vec![2; 10].into())
+        .await
+        .unwrap_err()
+        .downcast()?;
+    assert_eq!(err.code, ErrorCode::BadRequest);
+    assert_eq!(err.short_msg, "FileTooLarge");
+    // Replacing a part only counts its new size.
+    file_storage
+        .upload_session_part(&upload_id, 1, vec![1; 15].into())
+        .await?;
+    Ok(())
+}
+
+/// Flags files containing `EICAR`.
+#[derive(Debug)]
+struct EicarScanner;
+
+#[async_trait]
+impl FileScanner for EicarScanner {
+    async fn scan(
+        &self,
+        file: BoxStream<'static, futures::io::Result<Bytes>>,
+    ) -> anyhow::Result<ScanVerdict> {
+        let chunks: Vec<Bytes> = file.try_collect().await?;
+        Ok(if chunks.concat().windows(5).any(|w| w == b"EICAR") {
+            ScanVerdict::Infected("Eicar-Test-Signature".to_string())
+        } else {
+            ScanVerdict::Clean
+        })
+    }
+}
+
+#[convex_macro::test_runtime]
+async fn test_quarantine_flagged_files(rt: TestRuntime) -> anyhow::Result<()> {
+    let database = DbFixtures::new(&rt).await?.with_model().await?.db;
+    let mut file_storage = setup_file_storage(rt, &database)?;
+    file_storage.transactional_file_storage = file_storage
+        .transactional_file_storage
+        .with_file_scanner(Some(Arc::new(EicarScanner)));
+    let usage_tracker = UsageCounter::new(Arc::new(NoOpUsageEventLogger));
+
+    let mut entries = vec![];
+    for contents in [&b"harmless"[..], &b"X5O!P%@AP EICAR test file"[..]] {
+        let storage_id = file_storage
+            .store_file(
+                TableNamespace::test_user(),
+                None,
+                None,
+                stream::iter([Ok(contents.to_vec())]),
+                None,
+                &usage_tracker,
+            )
+            .await?;
+        let mut tx = database.begin(Identity::system()).await?;
+        let entry = file_storage
+            .transactional_file_storage
+            .get_file_entry(
+                &mut tx,
+                TableNamespace::test_user(),
+                FileStorageId::DocumentId(storage_id),
+            )
+            .await?
+            .unwrap();
+        entries.push(entry);
+    }
+    let [clean, blocked] = &entries[..] else {
+        unreachable!();
+    };
+    assert_eq!(clean.scan_status, Some(FileScanStatus::Clean));
+    assert_eq!(
+        blocked.scan_status,
+        Some(FileScanStatus::Blocked {
+            signature: "Eicar-Test-Signature".to_string()
+        })
+    );
+
+    // Quarantined files are kept, but can't be read.
+    file_storage
+        .transactional_file_storage
+        .get_file_stream(clean.clone(), usage_tracker.clone())
+        .await?;
+    let err: ErrorMetadata = file_storage
+        .transactional_file_storage
+        .get_file_stream(blocked.clone(), usage_tracker)
+        .await
+        .err()
+        .unwrap()
+        .downcast()?;
+    assert_eq!(err.code, ErrorCode::Forbidden);
+    assert_eq!(err.short_msg, "FileQuarantined");
+    Ok(())
+}
@@ -0,0 +1,411 @@
+//! Resumable uploads, where a file is uploaded in parts that can each be
+//! retried, so that a dropped connection doesn't restart the whole upload.
+//! The parts are assembled into a file when the session is finished.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    time::Duration,
+};
+
+use bytes::Bytes;
+use common::{
+    document::ParsedDocument,
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    sha256::{
+        Sha256,
+        Sha256Digest,
+    },
+    types::Timestamp,
+};
+use database::Transaction;
+use errors::ErrorMetadata;
+use futures::TryStreamExt;
+use headers::ContentType;
+use keybroker::{
+    Identity,
+    KeyBroker,
+    UploadConstraints,
+};
+use model::{
+    file_storage::types::{
+        FileStorageEntry,
+        StorageUuid,
+    },
+    file_upload_sessions::{
+        types::{
+            FileUploadPart,
+            FileUploadSession,
+            FileUploadSessionState,
+        },
+        FileUploadSessionsModel,
+    },
+};
+use storage::{
+    ClientDrivenUploadPartToken,
+    ClientDrivenUploadToken,
+    StorageExt,
+};
+use usage_tracking::{
+    StorageCallTracker,
+    StorageUsageTracker,
+};
+use value::{
+    id_v6::DeveloperDocumentId,
+    TableNamespace,
+};
+
+use crate::{
+    FileStorage,
+    TransactionalFileStorage,
+};
+
+/// How long parts can be uploaded to a session after it's created.
+pub const UPLOAD_SESSION_VALIDITY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Parts are held in memory while they're uploaded to storage.
+pub const MAX_UPLOAD_PART_SIZE: usize = 64 * (1 << 20);
+
+/// Every part but the last must be at least this large, which is the
+/// smallest part S3 accepts.
+pub const MIN_UPLOAD_PART_SIZE: u64 = 5 * (1 << 20);
+
+pub const MAX_UPLOAD_PARTS: u16 = 10000;
+
+/// Expired sessions are deleted as new sessions are created, a few at a time.
+const EXPIRED_SESSIONS_PER_CREATE: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadedPart {
+    pub part_number: u16,
+    pub size: u64,
+}
+
+pub struct UploadSessionStatus {
+    /// Ordered by part number. Empty once the session is finished.
+    pub parts: Vec<UploadedPart>,
+    pub expires_ts: Timestamp,
+    /// Set once the session is finished.
+    pub storage_id: Option<DeveloperDocumentId>,
+}
+
+impl<RT: Runtime> TransactionalFileStorage<RT> {
+    /// A URL that resumable upload sessions can be created at, authorized
+    /// by the same token as `generate_upload_url`.
+    pub fn generate_resumable_upload_url(
+        &self,
+        key_broker: &KeyBroker,
+        issued_ts: UnixTimestamp,
+        constraints: &UploadConstraints,
+    ) -> anyhow::Result<String> {
+        let token = key_broker.issue_store_file_authorization(&self.rt, issued_ts, constraints)?;
+        let origin = &self.convex_origin;
+
+        Ok(format!("{origin}/api/storage/upload_session?token={token}"))
+    }
+}
+
+impl<RT: Runtime> FileStorage<RT> {
+    /// Start a resumable upload of a file, which must satisfy the
+    /// `constraints` of the URL the session was created at. Returns the
+    /// session's upload id, which authorizes uploading to the session.
+    pub async fn create_upload_session(
+        &self,
+        content_type: Option<ContentType>,
+        expected_sha256: Option<Sha256Digest>,
+        constraints: &UploadConstraints,
+    ) -> anyhow::Result<String> {
+        let content_type = content_type.map(|ct| ct.to_string());
+        constraints.check_content_type(content_type.as_deref())?;
+        let upload_token = self
+            .transactional_file_storage
+            .storage
+            .start_client_driven_upload()
+            .await?;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let now = *tx.begin_timestamp();
+        let mut model = FileUploadSessionsModel::new(&mut tx);
+        model
+            .delete_expired(now, EXPIRED_SESSIONS_PER_CREATE)
+            .await?;
+        let upload_id = model
+            .create(
+                upload_token.0,
+                content_type,
+                expected_sha256,
+                constraints.max_bytes,
+                UPLOAD_SESSION_VALIDITY,
+            )
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "file_storage_create_upload_session")
+            .await?;
+        Ok(upload_id)
+    }
+
+    /// Upload part `part_number` of a session, replacing any previous upload
+    /// of the part.
+    pub async fn upload_session_part(
+        &self,
+        upload_id: &str,
+        part_number: u16,
+        part: Bytes,
+    ) -> anyhow::Result<()> {
+        if part_number == 0 || part_number > MAX_UPLOAD_PARTS {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidPartNumber",
+                format!("Part numbers must be between 1 and {MAX_UPLOAD_PARTS}, not {part_number}"),
+            ));
+        }
+        if part.is_empty() || part.len() > MAX_UPLOAD_PART_SIZE {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidPartSize",
+                format!(
+                    "Parts must be between 1 and {MAX_UPLOAD_PART_SIZE} bytes, not {}",
+                    part.len()
+                ),
+            ));
+        }
+        let size = part.len() as u64;
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let session = uploading_session(&mut tx, upload_id).await?;
+        let other_parts_size: u64 = FileUploadSessionsModel::new(&mut tx)
+            .parts(upload_id)
+            .await?
+            .iter()
+            .filter(|part| part.part_number != part_number)
+            .map(|part| part.size)
+            .sum();
+        size_constraint(&session).check_size(other_parts_size + size)?;
+        // Upload outside of a transaction, since it can be slow.
+        drop(tx);
+        let ClientDrivenUploadPartToken(part_token) = self
+            .transactional_file_storage
+            .storage
+            .upload_part(
+                ClientDrivenUploadToken(session.upload_token.clone()),
+                part_number,
+                part,
+            )
+            .await?;
+
+        let mut tx = self.database.begin(Identity::system()).await?;
+        // Fails if the session was finished while the part was uploading.
+        uploading_session(&mut tx, upload_id).await?;
+        FileUploadSessionsModel::new(&mut tx)
+            .put_part(FileUploadPart {
+                upload_id: upload_id.to_string(),
+                part_number,
+                part_token,
+                size,
+            })
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "file_storage_upload_session_part")
+            .await?;
+        Ok(())
+    }
+
+    /// The parts uploaded to a session so far, so that an interrupted client
+    /// knows which parts to upload again.
+    pub async fn upload_session_status(
+        &self,
+        upload_id: &str,
+    ) -> anyhow::Result<UploadSessionStatus> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let session = session(&mut tx, upload_id).await?;
+        let parts = FileUploadSessionsModel::new(&mut tx)
+            .parts(upload_id)
+            .await?
+            .into_iter()
+            .map(|part| UploadedPart {
+                part_number: part.part_number,
+                size: part.size,
+            })
+            .collect();
+        let storage_id = match &session.state {
+            FileUploadSessionState::Uploading => None,
+            FileUploadSessionState::Completed { storage_id } => Some(storage_id.parse()?),
+        };
+        Ok(UploadSessionStatus {
+            parts,
+            expires_ts: session.expires_ts,
+            storage_id,
+        })
+    }
+
+    /// Assemble the parts uploaded to a session, which must be numbered from
+    /// 1 without gaps, into a file. Finishing a session again returns the
+    /// same file.
+    pub async fn finish_upload_session(
+        &self,
+        namespace: TableNamespace,
+        upload_id: &str,
+        usage_tracker: &dyn StorageUsageTracker,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let session = session(&mut tx, upload_id).await?;
+        if let FileUploadSessionState::Completed { storage_id } = &session.state {
+            return Ok(storage_id.parse()?);
+        }
+        ensure_not_expired(&session, *tx.begin_timestamp())?;
+        let parts = FileUploadSessionsModel::new(&mut tx)
+            .parts(upload_id)
+            .await?;
+        drop(tx);
+        let Some(last) = parts.last() else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MissingUploadParts",
+                "No parts were uploaded to the session",
+            ));
+        };
+        if let Some(missing) = (1..=last.part_number)
+            .find(|part_number| !parts.iter().any(|part| part.part_number == *part_number))
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "MissingUploadParts",
+                format!("Part {missing} was not uploaded"),
+            ));
+        }
+        if let Some(small) = parts[..parts.len() - 1]
+            .iter()
+            .find(|part| part.size < MIN_UPLOAD_PART_SIZE)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidPartSize",
+                format!(
+                    "Part {} is {} bytes, but every part but the last must be at least \
+                     {MIN_UPLOAD_PART_SIZE} bytes",
+                    small.part_number, small.size
+                ),
+            ));
+        }
+
+        let storage = &self.transactional_file_storage.storage;
+        let storage_key = storage
+            .finish_client_driven_upload(
+                ClientDrivenUploadToken(session.upload_token.clone()),
+                parts
+                    .iter()
+                    .map(|part| ClientDrivenUploadPartToken(part.part_token.clone()))
+                    .collect(),
+            )
+            .await?;
+        // The parts could have been uploaded in any order, so the file's hash
+        // can only be computed once it's assembled.
+        let mut stream = storage
+            .get(&storage_key)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Finished upload {storage_key:?} not found"))?
+            .stream;
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+        while let Some(chunk) = stream.try_next().await? {
+            hasher.update(&chunk);
+            size += chunk.len();
+        }
+        let sha256 = hasher.finalize();
+        // Parts uploaded concurrently can each pass the check of the total
+        // size as they're uploaded, so check the assembled file too.
+        size_constraint(&session).check_size(size as u64)?;
+        if let Some(expected_sha256) = &session.sha256
+            && *expected_sha256 != sha256
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "Sha256Mismatch",
+                format!(
+                    "Sha256 mismatch. Expected: {} Actual: {}",
+                    expected_sha256.as_base64(),
+                    sha256.as_base64()
+                ),
+            ));
+        }
+        let scan_status = self
+            .transactional_file_storage
+            .scan_file(&storage_key)
+            .await?;
+        let entry = FileStorageEntry {
+            storage_id: StorageUuid::from(self.transactional_file_storage.rt.new_uuid_v4()),
+            storage_key,
+            sha256,
+            size: size.try_into()?,
+            content_type: session.content_type.clone(),
+            tags: BTreeSet::new(),
+            metadata: BTreeMap::new(),
+            storage_class: None,
+            scan_status,
+        };
+
+        let mut tx = self.database.begin(Identity::system()).await?;
+        let virtual_id = self
+            .transactional_file_storage
+            .store_file_entry(&mut tx, namespace, entry)
+            .await?;
+        FileUploadSessionsModel::new(&mut tx)
+            .complete(upload_id, virtual_id.encode())
+            .await?;
+        self.database
+            .commit_with_write_source(tx, "file_storage_finish_upload_session")
+            .await?;
+
+        usage_tracker
+            .track_storage_call("store")
+            .track_storage_ingress_size(size as u64);
+        Ok(virtual_id)
+    }
+}
+
+async fn session<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    upload_id: &str,
+) -> anyhow::Result<ParsedDocument<FileUploadSession>> {
+    FileUploadSessionsModel::new(tx)
+        .get(upload_id)
+        .await?
+        .ok_or_else(|| {
+            anyhow::anyhow!(ErrorMetadata::not_found(
+                "UploadSessionNotFound",
+                format!("Upload session {upload_id} not found"),
+            ))
+        })
+}
+
+async fn uploading_session<RT: Runtime>(
+    tx: &mut Transaction<RT>,
+    upload_id: &str,
+) -> anyhow::Result<ParsedDocument<FileUploadSession>> {
+    let session = session(tx, upload_id).await?;
+    if let FileUploadSessionState::Completed { .. } = session.state {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "UploadSessionFinished",
+            format!("Upload session {upload_id} is already finished"),
+        ));
+    }
+    ensure_not_expired(&session, *tx.begin_timestamp())?;
+    Ok(session)
+}
+
+fn size_constraint(session: &FileUploadSession) -> UploadConstraints {
+    UploadConstraints {
+        max_bytes: session.max_bytes,
+        ..Default::default()
+    }
+}
+
+fn ensure_not_expired(session: &FileUploadSession, now: Timestamp) -> anyhow::Result<()> {
+    if session.expires_ts < now {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "UploadSessionExpired",
+            format!(
+                "Upload session {} expired. Start a new upload session",
+                session.upload_id
+            ),
+        ));
+    }
+    Ok(())
+}
@@ -387,6 +387,12 @@ mod tests {
             )])),
             search_indexes: Default::default(),
             vector_indexes: Default::default(),
+            encrypted_fields: Default::default(),
+            triggers: Default::default(),
+            track_updates: false,
+            soft_delete: None,
+            id_strategy: Default::default(),
+            sketches: Default::default(),
         };
 
         assert_eq!(
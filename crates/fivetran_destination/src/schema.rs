@@ -212,6 +212,12 @@ impl FivetranTableSchema {
             indexes,
             search_indexes: Default::default(),
             vector_indexes: Default::default(),
+            encrypted_fields: Default::default(),
+            triggers: Default::default(),
+            track_updates: false,
+            soft_delete: None,
+            id_strategy: Default::default(),
+            sketches: Default::default(),
         })
     }
 
@@ -1007,6 +1013,12 @@ mod tests {
                     .collect(),
             )])),
             indexes: convex_indexes(indexes),
+            encrypted_fields: Default::default(),
+            triggers: Default::default(),
+            track_updates: false,
+            soft_delete: None,
+            id_strategy: Default::default(),
+            sketches: Default::default(),
         }
     }
 
@@ -1885,6 +1897,12 @@ mod tests {
                 )])),
                 search_indexes: Default::default(),
                 vector_indexes: Default::default(),
+                encrypted_fields: Default::default(),
+                triggers: Default::default(),
+                track_updates: false,
+                soft_delete: None,
+                id_strategy: Default::default(),
+                sketches: Default::default(),
             },
         );
         Ok(())
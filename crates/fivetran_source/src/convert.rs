@@ -23,10 +23,11 @@ fn to_fivetran_value(value: ConvexValue) -> FivetranValue {
         ConvexValue::Null => FivetranValue::Null(true),
         ConvexValue::Int64(value) => FivetranValue::Long(value),
         ConvexValue::Float64(value) => FivetranValue::Double(value),
+        ConvexValue::Decimal(value) => FivetranValue::Decimal(value.to_string()),
         ConvexValue::Boolean(value) => FivetranValue::Bool(value),
         ConvexValue::String(value) => FivetranValue::String(value),
         ConvexValue::Bytes(value) => FivetranValue::Binary(value),
-        ConvexValue::Array(_) | ConvexValue::Object(_) => {
+        ConvexValue::GeoPoint(_) | ConvexValue::Array(_) | ConvexValue::Object(_) => {
             FivetranValue::Json(value.export().to_string())
         },
     }
@@ -45,6 +46,7 @@ fn roundtrip_fivetran_value(
         FivetranValue::Bool(value) => ConvexValue::Boolean(value),
         FivetranValue::Long(value) => ConvexValue::Int64(value),
         FivetranValue::Double(value) => ConvexValue::Float64(value),
+        FivetranValue::Decimal(value) => ConvexValue::Decimal(value.parse()?),
         FivetranValue::Binary(value) => ConvexValue::Bytes(value),
         FivetranValue::String(value) => ConvexValue::String(value),
         FivetranValue::Json(value) => {
@@ -59,7 +61,6 @@ fn roundtrip_fivetran_value(
         | FivetranValue::NaiveTime(_)
         | FivetranValue::NaiveDate(_)
         | FivetranValue::NaiveDatetime(_)
-        | FivetranValue::Decimal(_)
         | FivetranValue::Xml(_) => anyhow::bail!("Unsupported Fivetran value: {:?}", value),
     })
 }
@@ -128,6 +128,7 @@ impl<RT: Runtime> IsolateWorker<RT> for FunctionRunnerIsolateWorker<RT> {
                 queue_timer,
                 action_callbacks,
                 fetch_client,
+                secret_resolver,
                 log_line_sender,
             } => {
                 drop(queue_timer);
@@ -143,6 +144,7 @@ impl<RT: Runtime> IsolateWorker<RT> for FunctionRunnerIsolateWorker<RT> {
                     request.transaction,
                     action_callbacks,
                     fetch_client,
+                    secret_resolver,
                     log_line_sender,
                     None,
                     heap_stats.clone(),
@@ -3,6 +3,7 @@
 #![feature(impl_trait_in_assoc_type)]
 #![feature(try_blocks)]
 #![feature(lint_reasons)]
+#![feature(coroutines)]
 use std::{
     collections::{
         BTreeMap,
@@ -55,6 +56,10 @@ mod in_memory_indexes;
 mod isolate_worker;
 mod metrics;
 mod module_cache;
+pub mod persistence_proxy;
+pub mod process_pool;
+pub mod process_worker;
+mod proto;
 pub mod server;
 
 #[async_trait]
@@ -0,0 +1,301 @@
+//! Function runner processes don't open the database themselves. They read it
+//! through the backend that started them, which serves its persistence reader
+//! as the `PersistenceReaderProxy` gRPC service.
+
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    future::Future,
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use common::{
+    document::ResolvedDocument,
+    index::IndexKeyBytes,
+    interval::Interval,
+    persistence::{
+        DocumentStream,
+        IndexStream,
+        NoopRetentionValidator,
+        PersistenceGlobalKey,
+        PersistenceReader,
+        RetentionValidator,
+        TimestampRange,
+    },
+    query::Order,
+    types::{
+        IndexId,
+        PersistenceVersion,
+        Timestamp,
+    },
+};
+use futures::{
+    stream::{
+        self,
+        BoxStream,
+    },
+    StreamExt,
+    TryStreamExt,
+};
+use futures_async_stream::try_stream;
+use pb::{
+    error_metadata::ErrorMetadataStatusExt,
+    funrun::{
+        persistence_reader_proxy_client::PersistenceReaderProxyClient,
+        persistence_reader_proxy_server::{
+            PersistenceReaderProxy,
+            PersistenceReaderProxyServer,
+        },
+        GetPersistenceGlobalRequest,
+        GetPersistenceGlobalResponse,
+        IndexScanRequest,
+        IndexScanResponse,
+    },
+};
+use serde_json::Value as JsonValue;
+use tokio::net::TcpListener;
+use tonic::{
+    transport::Channel,
+    Request,
+    Response,
+    Status,
+};
+use value::{
+    InternalDocumentId,
+    TabletId,
+};
+
+use crate::{
+    process_worker::MAX_MESSAGE_SIZE,
+    proto::internal_id_from_proto,
+};
+
+/// Serves the backend's persistence reader to its function runner processes.
+#[derive(Clone)]
+pub struct PersistenceReaderProxyService {
+    reader: Arc<dyn PersistenceReader>,
+}
+
+impl PersistenceReaderProxyService {
+    pub fn new(reader: Arc<dyn PersistenceReader>) -> Self {
+        Self { reader }
+    }
+
+    async fn get_persistence_global(
+        &self,
+        request: GetPersistenceGlobalRequest,
+    ) -> anyhow::Result<GetPersistenceGlobalResponse> {
+        let key: PersistenceGlobalKey = request.key.context("Missing key")?.parse()?;
+        let value = self.reader.get_persistence_global(key).await?;
+        Ok(GetPersistenceGlobalResponse {
+            json_value: value.map(|value| value.to_string()),
+        })
+    }
+}
+
+#[try_stream(ok = IndexScanResponse, error = anyhow::Error, boxed)]
+async fn serve_index_scan(reader: Arc<dyn PersistenceReader>, request: IndexScanRequest) {
+    let IndexScanRequest {
+        index_id,
+        tablet_id,
+        read_timestamp,
+        range,
+        descending,
+        size_hint,
+    } = request;
+    let index_id = internal_id_from_proto(index_id, "index_id")?;
+    let tablet_id = TabletId(internal_id_from_proto(tablet_id, "tablet_id")?);
+    let read_timestamp: Timestamp = read_timestamp
+        .context("Missing read_timestamp")?
+        .try_into()?;
+    let range: Interval = range.context("Missing range")?.try_into()?;
+    let order = if descending.unwrap_or_default() {
+        Order::Desc
+    } else {
+        Order::Asc
+    };
+    // Function runner processes only run queries and mutations, whose
+    // retention the backend checks after they finish.
+    let mut stream = reader.index_scan(
+        index_id,
+        tablet_id,
+        read_timestamp,
+        &range,
+        order,
+        size_hint.unwrap_or_default() as usize,
+        Arc::new(NoopRetentionValidator),
+    );
+    while let Some((key, ts, document)) = stream.try_next().await? {
+        yield IndexScanResponse {
+            key: Some(key.0),
+            ts: Some(ts.into()),
+            document: Some(document.try_into()?),
+        };
+    }
+}
+
+#[tonic::async_trait]
+impl PersistenceReaderProxy for PersistenceReaderProxyService {
+    type IndexScanStream = BoxStream<'static, Result<IndexScanResponse, Status>>;
+
+    async fn index_scan(
+        &self,
+        request: Request<IndexScanRequest>,
+    ) -> Result<Response<Self::IndexScanStream>, Status> {
+        let stream = serve_index_scan(self.reader.clone(), request.into_inner())
+            .map(|result| result.map_err(Status::from_anyhow));
+        Ok(Response::new(stream.boxed()))
+    }
+
+    async fn get_persistence_global(
+        &self,
+        request: Request<GetPersistenceGlobalRequest>,
+    ) -> Result<Response<GetPersistenceGlobalResponse>, Status> {
+        self.get_persistence_global(request.into_inner())
+            .await
+            .map(Response::new)
+            .map_err(Status::from_anyhow)
+    }
+}
+
+/// Serve `reader` to function runner processes on a local port, returning the
+/// URL they should connect to and the server, which runs until dropped.
+pub async fn start_persistence_reader_proxy(
+    reader: Arc<dyn PersistenceReader>,
+) -> anyhow::Result<(String, impl Future<Output = anyhow::Result<()>>)> {
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+    let url = format!("http://127.0.0.1:{}", listener.local_addr()?.port());
+    let incoming = stream::unfold(listener, |listener| async move {
+        let stream = listener.accept().await.map(|(stream, _)| stream);
+        Some((stream, listener))
+    });
+    let server = async move {
+        tonic::transport::Server::builder()
+            .add_service(
+                PersistenceReaderProxyServer::new(PersistenceReaderProxyService::new(reader))
+                    .max_decoding_message_size(MAX_MESSAGE_SIZE)
+                    .max_encoding_message_size(MAX_MESSAGE_SIZE),
+            )
+            .serve_with_incoming(incoming)
+            .await
+            .context("Persistence reader proxy failed")
+    };
+    Ok((url, server))
+}
+
+/// Persistence reader of a function runner process, which reads through the
+/// backend's `PersistenceReaderProxy`. It only supports the reads that queries
+/// and mutations make.
+#[derive(Clone)]
+pub struct RemotePersistenceReader {
+    client: PersistenceReaderProxyClient<Channel>,
+}
+
+impl RemotePersistenceReader {
+    pub async fn connect(url: String) -> anyhow::Result<Self> {
+        let client = PersistenceReaderProxyClient::connect(url.clone())
+            .await
+            .with_context(|| format!("Failed to connect to the backend at {url}"))?
+            .max_decoding_message_size(MAX_MESSAGE_SIZE)
+            .max_encoding_message_size(MAX_MESSAGE_SIZE);
+        Ok(Self { client })
+    }
+}
+
+fn unsupported(method: &str) -> anyhow::Error {
+    anyhow::anyhow!("{method} isn't supported in a function runner process")
+}
+
+#[try_stream(ok = (IndexKeyBytes, Timestamp, ResolvedDocument), error = anyhow::Error, boxed)]
+async fn remote_index_scan(
+    mut client: PersistenceReaderProxyClient<Channel>,
+    request: IndexScanRequest,
+) {
+    let mut stream = client
+        .index_scan(request)
+        .await
+        .map_err(Status::into_anyhow)?
+        .into_inner();
+    while let Some(response) = stream.message().await.map_err(Status::into_anyhow)? {
+        let IndexScanResponse { key, ts, document } = response;
+        yield (
+            IndexKeyBytes(key.context("Missing key")?),
+            ts.context("Missing ts")?.try_into()?,
+            document.context("Missing document")?.try_into()?,
+        );
+    }
+}
+
+#[async_trait]
+impl PersistenceReader for RemotePersistenceReader {
+    fn load_documents(
+        &self,
+        _range: TimestampRange,
+        _order: Order,
+        _page_size: u32,
+        _retention_validator: Arc<dyn RetentionValidator>,
+    ) -> DocumentStream<'_> {
+        stream::once(async { Err(unsupported("load_documents")) }).boxed()
+    }
+
+    async fn previous_revisions(
+        &self,
+        _ids: BTreeSet<(InternalDocumentId, Timestamp)>,
+        _retention_validator: Arc<dyn RetentionValidator>,
+    ) -> anyhow::Result<
+        BTreeMap<(InternalDocumentId, Timestamp), (Timestamp, Option<ResolvedDocument>)>,
+    > {
+        Err(unsupported("previous_revisions"))
+    }
+
+    fn index_scan(
+        &self,
+        index_id: IndexId,
+        tablet_id: TabletId,
+        read_timestamp: Timestamp,
+        range: &Interval,
+        order: Order,
+        size_hint: usize,
+        // The backend checks retention after the function finishes.
+        _retention_validator: Arc<dyn RetentionValidator>,
+    ) -> IndexStream<'_> {
+        let request = IndexScanRequest {
+            index_id: Some(index_id.to_vec()),
+            tablet_id: Some(tablet_id.0.to_vec()),
+            read_timestamp: Some(read_timestamp.into()),
+            range: Some(range.clone().into()),
+            descending: Some(order == Order::Desc),
+            size_hint: Some(size_hint as u64),
+        };
+        remote_index_scan(self.client.clone(), request)
+    }
+
+    async fn get_persistence_global(
+        &self,
+        key: PersistenceGlobalKey,
+    ) -> anyhow::Result<Option<JsonValue>> {
+        let response = self
+            .client
+            .clone()
+            .get_persistence_global(GetPersistenceGlobalRequest {
+                key: Some(key.into()),
+            })
+            .await
+            .map_err(Status::into_anyhow)?
+            .into_inner();
+        response
+            .json_value
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    fn version(&self) -> PersistenceVersion {
+        PersistenceVersion::V5
+    }
+}
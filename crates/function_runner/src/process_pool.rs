@@ -0,0 +1,436 @@
+//! Runs queries and mutations in a pool of function runner processes, so a
+//! V8 out-of-memory error or a crash in native code can't take down the
+//! committer and sync workers. Each process is supervised and restarted with
+//! backoff when it exits. Actions, HTTP actions and functions that need table
+//! counts or search still run in the backend.
+
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use common::{
+    backoff::Backoff,
+    errors::report_error,
+    execution_context::ExecutionContext,
+    log_lines::LogLine,
+    query_journal::QueryJournal,
+    runtime::Runtime,
+    types::{
+        IndexId,
+        RepeatableTimestamp,
+        UdfType,
+    },
+};
+use database::Database;
+use errors::ErrorMetadata;
+use futures::{
+    channel::mpsc,
+    select_biased,
+    FutureExt,
+};
+use isolate::{
+    ActionCallbacks,
+    FunctionOutcome,
+    ValidatedPathAndArgs,
+};
+use keybroker::Identity;
+use model::environment_variables::types::{
+    EnvVarName,
+    EnvVarValue,
+};
+use pb::{
+    error_metadata::ErrorMetadataStatusExt,
+    funrun::{
+        function_runner_process_client::FunctionRunnerProcessClient,
+        run_function_response::Result as RunFunctionResultProto,
+        CompletedFunction,
+        IndexLastModified,
+        NeedsBackend,
+        RunFunctionRequest,
+    },
+};
+use sync_types::Timestamp;
+use tokio::{
+    io::{
+        AsyncBufReadExt,
+        BufReader,
+    },
+    process::{
+        Child,
+        ChildStdin,
+        Command,
+    },
+    sync::watch,
+};
+use tonic::{
+    transport::{
+        Channel,
+        Endpoint,
+    },
+    Code,
+};
+use usage_tracking::FunctionUsageStats;
+
+use crate::{
+    process_worker::{
+        LISTENING_PREFIX,
+        MAX_MESSAGE_SIZE,
+    },
+    proto::bootstrap_metadata_to_proto,
+    server::{
+        validate_run_function_result,
+        InProcessFunctionRunner,
+    },
+    FunctionFinalTransaction,
+    FunctionRunner,
+    FunctionWrites,
+};
+
+/// How long a function runner process has to start before the backend gives
+/// up on it.
+const PROCESS_START_TIMEOUT: Duration = Duration::from_secs(60);
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(30);
+/// A process that ran for this long before exiting is restarted without
+/// backing off.
+const HEALTHY_PROCESS_UPTIME: Duration = Duration::from_secs(60);
+
+/// How to start function runner processes.
+#[derive(Clone, Debug)]
+pub struct FunctionRunnerProcessConfig {
+    /// Program to run, usually the backend's own executable.
+    pub program: PathBuf,
+    /// Arguments that make `program` run a function runner process for this
+    /// backend.
+    pub args: Vec<OsString>,
+    pub num_processes: usize,
+}
+
+/// A running function runner process.
+struct FunctionRunnerProcess<RT: Runtime> {
+    child: Child,
+    // The process exits once this is dropped.
+    _stdin: ChildStdin,
+    _output_forwarder: RT::Handle,
+    client: FunctionRunnerProcessClient<Channel>,
+}
+
+impl<RT: Runtime> FunctionRunnerProcess<RT> {
+    async fn start(rt: &RT, config: &FunctionRunnerProcessConfig) -> anyhow::Result<Self> {
+        let mut child = Command::new(&config.program)
+            .args(&config.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", config.program.display()))?;
+        let pid = child.id().context("Missing process id")?;
+        let stdin = child.stdin.take().context("Missing stdin")?;
+        let mut stdout = BufReader::new(child.stdout.take().context("Missing stdout")?).lines();
+        let port = {
+            let read_port = async {
+                while let Some(line) = stdout.next_line().await? {
+                    match line.strip_prefix(LISTENING_PREFIX) {
+                        Some(port) => return anyhow::Ok(port.trim().parse::<u16>()?),
+                        None => tracing::info!(pid, "{line}"),
+                    }
+                }
+                anyhow::bail!("Function runner process exited before it was ready")
+            };
+            select_biased! {
+                port = read_port.fuse() => port?,
+                _ = rt.wait(PROCESS_START_TIMEOUT) => anyhow::bail!(
+                    "Function runner process didn't start within {PROCESS_START_TIMEOUT:?}"
+                ),
+            }
+        };
+        // Log the rest of the process's output, tagged with the process it came
+        // from.
+        let output_forwarder = rt.spawn("function_runner_process_output", async move {
+            while let Ok(Some(line)) = stdout.next_line().await {
+                tracing::info!(pid, "{line}");
+            }
+        });
+        let channel = Endpoint::from_shared(format!("http://127.0.0.1:{port}"))?
+            .connect()
+            .await?;
+        let client = FunctionRunnerProcessClient::new(channel)
+            .max_decoding_message_size(MAX_MESSAGE_SIZE)
+            .max_encoding_message_size(MAX_MESSAGE_SIZE);
+        Ok(Self {
+            child,
+            _stdin: stdin,
+            _output_forwarder: output_forwarder,
+            client,
+        })
+    }
+}
+
+/// Wait for the `index`th process to exit and restart it, forever.
+async fn supervise_process<RT: Runtime>(
+    rt: RT,
+    config: FunctionRunnerProcessConfig,
+    index: usize,
+    mut process: FunctionRunnerProcess<RT>,
+    client_tx: watch::Sender<Option<FunctionRunnerProcessClient<Channel>>>,
+) {
+    let mut backoff = Backoff::new(INITIAL_RESTART_BACKOFF, MAX_RESTART_BACKOFF);
+    loop {
+        let started = rt.monotonic_now();
+        let status = process.child.wait().await;
+        client_tx.send_replace(None);
+        let mut e = anyhow::anyhow!("Function runner process {index} exited with {status:?}");
+        report_error(&mut e);
+        if rt.monotonic_now() - started >= HEALTHY_PROCESS_UPTIME {
+            backoff.reset();
+        }
+        process = loop {
+            let delay = rt.with_rng(|rng| backoff.fail(rng));
+            rt.wait(delay).await;
+            match FunctionRunnerProcess::start(&rt, &config).await {
+                Ok(process) => break process,
+                Err(mut e) => report_error(&mut e),
+            }
+        };
+        tracing::info!("Restarted function runner process {index}");
+        client_tx.send_replace(Some(process.client.clone()));
+    }
+}
+
+/// Supervised function runner processes that functions are spread across.
+struct FunctionRunnerProcessPool<RT: Runtime> {
+    clients: Vec<watch::Receiver<Option<FunctionRunnerProcessClient<Channel>>>>,
+    next: AtomicUsize,
+    // The processes are killed if their supervisors stop, and exit on their
+    // own if the backend does.
+    _supervisors: Vec<RT::Handle>,
+}
+
+impl<RT: Runtime> FunctionRunnerProcessPool<RT> {
+    async fn start(rt: RT, config: FunctionRunnerProcessConfig) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            config.num_processes > 0,
+            "At least one function runner process is required"
+        );
+        let mut clients = vec![];
+        let mut supervisors = vec![];
+        for index in 0..config.num_processes {
+            let process = FunctionRunnerProcess::start(&rt, &config)
+                .await
+                .with_context(|| format!("Failed to start function runner process {index}"))?;
+            let (client_tx, client_rx) = watch::channel(Some(process.client.clone()));
+            supervisors.push(rt.spawn(
+                "function_runner_process_supervisor",
+                supervise_process(rt.clone(), config.clone(), index, process, client_tx),
+            ));
+            clients.push(client_rx);
+        }
+        tracing::info!("Started {} function runner processes", clients.len());
+        Ok(Self {
+            clients,
+            next: AtomicUsize::new(0),
+            _supervisors: supervisors,
+        })
+    }
+
+    /// A client for the next running process, if any are running.
+    fn client(&self) -> Option<FunctionRunnerProcessClient<Channel>> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..self.clients.len()).find_map(|i| {
+            let client = self.clients[(start + i) % self.clients.len()].borrow();
+            client.clone()
+        })
+    }
+}
+
+enum ProcessRunResult {
+    Completed(
+        Option<FunctionFinalTransaction>,
+        FunctionOutcome,
+        FunctionUsageStats,
+    ),
+    NeedsBackend(String),
+}
+
+/// Function runner that runs queries and mutations in separate processes and
+/// everything else in-process.
+pub struct ProcessFunctionRunner<RT: Runtime> {
+    pool: FunctionRunnerProcessPool<RT>,
+    in_process: InProcessFunctionRunner<RT>,
+    database: Database<RT>,
+}
+
+impl<RT: Runtime> ProcessFunctionRunner<RT> {
+    /// Start the function runner processes, which read through a
+    /// `PersistenceReaderProxy` that the caller serves.
+    pub async fn new(
+        rt: RT,
+        config: FunctionRunnerProcessConfig,
+        in_process: InProcessFunctionRunner<RT>,
+        database: Database<RT>,
+    ) -> anyhow::Result<Self> {
+        let pool = FunctionRunnerProcessPool::start(rt, config).await?;
+        Ok(Self {
+            pool,
+            in_process,
+            database,
+        })
+    }
+
+    async fn run_in_process_pool(
+        &self,
+        path_and_args: &ValidatedPathAndArgs,
+        udf_type: UdfType,
+        identity: &Identity,
+        ts: RepeatableTimestamp,
+        existing_writes: &FunctionWrites,
+        journal: &QueryJournal,
+        system_env_vars: &BTreeMap<EnvVarName, EnvVarValue>,
+        in_memory_index_last_modified: &BTreeMap<IndexId, Timestamp>,
+        context: &ExecutionContext,
+    ) -> anyhow::Result<ProcessRunResult> {
+        let mut client = self.pool.client().ok_or_else(|| {
+            anyhow::anyhow!(ErrorMetadata::overloaded(
+                "FunctionRunnerRestarting",
+                "All function runner processes are restarting. Try again soon.",
+            ))
+        })?;
+        let request = RunFunctionRequest {
+            path_and_args: Some(path_and_args.clone().try_into()?),
+            udf_type: pb::common::UdfType::from(udf_type).into(),
+            identity: Some(identity.clone().into()),
+            ts: Some(ts.into()),
+            existing_writes: Some(existing_writes.clone().try_into()?),
+            journal: Some(journal.clone().into()),
+            system_env_vars: system_env_vars
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            in_memory_index_last_modified: in_memory_index_last_modified
+                .iter()
+                .map(|(index_id, ts)| IndexLastModified {
+                    index_id: Some(index_id.to_vec()),
+                    ts: Some((*ts).into()),
+                })
+                .collect(),
+            context: Some(context.clone().into()),
+            bootstrap_metadata: Some(bootstrap_metadata_to_proto(
+                self.database.bootstrap_metadata.clone(),
+            )),
+        };
+        let response = match client.run_function(request).await {
+            Ok(response) => response.into_inner(),
+            // The connection to the process broke, most likely because it
+            // crashed while running this function.
+            Err(status) if matches!(status.code(), Code::Unavailable | Code::Unknown) => {
+                return Err(status.into_anyhow().context(
+                    "The function runner process crashed while running this function, e.g. \
+                     because it ran out of memory",
+                ));
+            },
+            Err(status) => return Err(status.into_anyhow()),
+        };
+        match response.result.context("Missing result")? {
+            RunFunctionResultProto::Completed(CompletedFunction {
+                transaction,
+                outcome,
+                usage,
+            }) => {
+                let outcome = FunctionOutcome::from_proto(
+                    outcome.context("Missing outcome")?,
+                    path_and_args.clone(),
+                    identity.clone().into(),
+                )?;
+                Ok(ProcessRunResult::Completed(
+                    transaction.map(TryInto::try_into).transpose()?,
+                    outcome,
+                    usage.context("Missing usage")?.try_into()?,
+                ))
+            },
+            RunFunctionResultProto::NeedsBackend(NeedsBackend { reason }) => {
+                Ok(ProcessRunResult::NeedsBackend(reason.unwrap_or_default()))
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl<RT: Runtime> FunctionRunner<RT> for ProcessFunctionRunner<RT> {
+    #[minitrace::trace]
+    async fn run_function(
+        &self,
+        path_and_args: ValidatedPathAndArgs,
+        udf_type: UdfType,
+        identity: Identity,
+        ts: RepeatableTimestamp,
+        existing_writes: FunctionWrites,
+        journal: QueryJournal,
+        log_line_sender: Option<mpsc::UnboundedSender<LogLine>>,
+        system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        in_memory_index_last_modified: BTreeMap<IndexId, Timestamp>,
+        context: ExecutionContext,
+    ) -> anyhow::Result<(
+        Option<FunctionFinalTransaction>,
+        FunctionOutcome,
+        FunctionUsageStats,
+    )> {
+        if matches!(udf_type, UdfType::Query | UdfType::Mutation) {
+            let result = self
+                .run_in_process_pool(
+                    &path_and_args,
+                    udf_type,
+                    &identity,
+                    ts,
+                    &existing_writes,
+                    &journal,
+                    &system_env_vars,
+                    &in_memory_index_last_modified,
+                    &context,
+                )
+                .await;
+            // Like `InProcessFunctionRunner`, check retention before surfacing
+            // any results or errors.
+            validate_run_function_result(udf_type, *ts, self.database.retention_validator())
+                .await?;
+            match result? {
+                ProcessRunResult::Completed(transaction, outcome, usage) => {
+                    return Ok((transaction, outcome, usage));
+                },
+                ProcessRunResult::NeedsBackend(reason) => {
+                    tracing::debug!("Running {udf_type} in the backend: {reason}");
+                },
+            }
+        }
+        self.in_process
+            .run_function(
+                path_and_args,
+                udf_type,
+                identity,
+                ts,
+                existing_writes,
+                journal,
+                log_line_sender,
+                system_env_vars,
+                in_memory_index_last_modified,
+                context,
+            )
+            .await
+    }
+
+    fn set_action_callbacks(&self, action_callbacks: Arc<dyn ActionCallbacks>) {
+        self.in_process.set_action_callbacks(action_callbacks);
+    }
+}
@@ -0,0 +1,284 @@
+//! The function runner process, which runs queries and mutations for the
+//! backend that started it, so a V8 out-of-memory error or a crash in native
+//! code only takes down this process. It reads the database through the
+//! backend (see [`crate::persistence_proxy`]) and exits when its stdin closes.
+
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use common::{
+    document::DocumentUpdate,
+    execution_context::ExecutionContext,
+    persistence::PersistenceReader,
+    query::{
+        InternalSearch,
+        SearchVersion,
+    },
+    query_journal::QueryJournal,
+    runtime::Runtime,
+    types::{
+        ConvexOrigin,
+        RepeatableTimestamp,
+        UdfType,
+    },
+};
+use database::{
+    TableCountSnapshot,
+    TransactionSearchSnapshot,
+};
+use futures::stream;
+use indexing::index_registry::Index;
+use isolate::ValidatedPathAndArgs;
+use keybroker::{
+    Identity,
+    InstanceSecret,
+};
+use parking_lot::Mutex;
+use pb::{
+    common::UdfType as UdfTypeProto,
+    error_metadata::ErrorMetadataStatusExt,
+    funrun::{
+        function_runner_process_server::{
+            FunctionRunnerProcess,
+            FunctionRunnerProcessServer,
+        },
+        run_function_response::Result as RunFunctionResultProto,
+        CompletedFunction,
+        IndexLastModified,
+        NeedsBackend,
+        RunFunctionRequest,
+        RunFunctionResponse,
+    },
+};
+use search::QueryResults;
+use sync_types::Timestamp;
+use tokio::{
+    io::AsyncReadExt,
+    net::TcpListener,
+};
+use tonic::{
+    Request,
+    Response,
+    Status,
+};
+use value::TabletId;
+
+use crate::{
+    persistence_proxy::RemotePersistenceReader,
+    proto::{
+        bootstrap_metadata_from_proto,
+        internal_id_from_proto,
+    },
+    server::{
+        FunctionRunnerCore,
+        InstanceStorage,
+    },
+    FunctionWrites,
+};
+
+/// What a function runner process prints once it's ready, followed by the
+/// port it serves `FunctionRunnerProcess` on.
+pub(crate) const LISTENING_PREFIX: &str = "Function runner process listening on port ";
+
+/// Largest gRPC message between the backend and its function runner
+/// processes. Function arguments, results, reads and writes are limited far
+/// below this.
+pub(crate) const MAX_MESSAGE_SIZE: usize = 1 << 30;
+
+/// Table counts and search indexes are only in the backend, so a function that
+/// uses them makes the backend run it instead.
+#[derive(Default)]
+struct BackendOnlySnapshot {
+    needed_for: Mutex<Option<&'static str>>,
+}
+
+impl BackendOnlySnapshot {
+    fn needs_backend(&self, needed_for: &'static str) -> anyhow::Error {
+        *self.needed_for.lock() = Some(needed_for);
+        anyhow::anyhow!("{needed_for} isn't available in a function runner process")
+    }
+}
+
+#[async_trait]
+impl TableCountSnapshot for BackendOnlySnapshot {
+    async fn count(&self, _table: TabletId) -> anyhow::Result<u64> {
+        Err(self.needs_backend("Table counts"))
+    }
+
+    async fn total_size(&self, _table: TabletId) -> anyhow::Result<u64> {
+        Err(self.needs_backend("Table sizes"))
+    }
+
+    async fn writes_per_minute(&self, _table: TabletId, _ts: Timestamp) -> anyhow::Result<f64> {
+        Err(self.needs_backend("Table write rates"))
+    }
+}
+
+#[async_trait]
+impl TransactionSearchSnapshot for BackendOnlySnapshot {
+    async fn search(
+        &self,
+        _index: &Index,
+        _search: &InternalSearch,
+        _version: SearchVersion,
+        _pending_updates: &Vec<DocumentUpdate>,
+    ) -> anyhow::Result<QueryResults> {
+        Err(self.needs_backend("Search"))
+    }
+}
+
+/// Implements the `FunctionRunnerProcess` gRPC service.
+pub struct FunctionRunnerProcessService<RT: Runtime> {
+    core: FunctionRunnerCore<RT, InstanceStorage>,
+    reader: Arc<dyn PersistenceReader>,
+    instance_name: String,
+    instance_secret: InstanceSecret,
+    convex_origin: ConvexOrigin,
+}
+
+impl<RT: Runtime> FunctionRunnerProcessService<RT> {
+    async fn run_function(
+        &self,
+        request: RunFunctionRequest,
+    ) -> anyhow::Result<RunFunctionResponse> {
+        let RunFunctionRequest {
+            path_and_args,
+            udf_type,
+            identity,
+            ts,
+            existing_writes,
+            journal,
+            system_env_vars,
+            in_memory_index_last_modified,
+            context,
+            bootstrap_metadata,
+        } = request;
+        let path_and_args =
+            ValidatedPathAndArgs::from_proto(path_and_args.context("Missing path_and_args")?)?;
+        let udf_type = UdfType::from(UdfTypeProto::try_from(udf_type)?);
+        let identity = Identity::from_proto_unchecked(identity.context("Missing identity")?)?;
+        let ts = RepeatableTimestamp::try_from(ts.context("Missing ts")?)?;
+        let existing_writes = existing_writes
+            .map(FunctionWrites::try_from)
+            .transpose()?
+            .unwrap_or_default();
+        let journal = QueryJournal::try_from(journal.context("Missing journal")?)?;
+        let system_env_vars: BTreeMap<_, _> = system_env_vars
+            .into_iter()
+            .map(|(name, value)| anyhow::Ok((name.parse()?, value.parse()?)))
+            .try_collect()?;
+        let mut index_last_modified = BTreeMap::new();
+        for IndexLastModified { index_id, ts } in in_memory_index_last_modified {
+            let index_id = internal_id_from_proto(index_id, "index_id")?;
+            index_last_modified.insert(index_id, ts.context("Missing ts")?.try_into()?);
+        }
+        let context = ExecutionContext::try_from(context.context("Missing context")?)?;
+        let bootstrap_metadata = bootstrap_metadata_from_proto(
+            bootstrap_metadata.context("Missing bootstrap_metadata")?,
+        )?;
+
+        let backend_only = Arc::new(BackendOnlySnapshot::default());
+        let result = self
+            .core
+            .run_udf_no_retention_check(
+                self.instance_name.clone(),
+                self.instance_secret,
+                self.reader.clone(),
+                self.convex_origin.clone(),
+                bootstrap_metadata,
+                backend_only.clone(),
+                backend_only.clone(),
+                path_and_args,
+                udf_type,
+                identity,
+                ts,
+                existing_writes,
+                journal,
+                system_env_vars,
+                index_last_modified,
+                context,
+            )
+            .await;
+        // The function's result doesn't matter if it needed the backend, since
+        // the backend runs it again.
+        if let Some(needed_for) = *backend_only.needed_for.lock() {
+            return Ok(RunFunctionResponse {
+                result: Some(RunFunctionResultProto::NeedsBackend(NeedsBackend {
+                    reason: Some(format!("{needed_for} isn't available")),
+                })),
+            });
+        }
+        let (transaction, outcome, usage) = result?;
+        Ok(RunFunctionResponse {
+            result: Some(RunFunctionResultProto::Completed(CompletedFunction {
+                transaction: transaction.map(TryInto::try_into).transpose()?,
+                outcome: Some(outcome.try_into()?),
+                usage: Some(usage.into()),
+            })),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl<RT: Runtime> FunctionRunnerProcess for FunctionRunnerProcessService<RT> {
+    async fn run_function(
+        &self,
+        request: Request<RunFunctionRequest>,
+    ) -> Result<Response<RunFunctionResponse>, Status> {
+        self.run_function(request.into_inner())
+            .await
+            .map(Response::new)
+            .map_err(Status::from_anyhow)
+    }
+}
+
+/// Run a function runner process for the backend whose
+/// `PersistenceReaderProxy` is at `backend_url`, until stdin closes.
+pub async fn serve_function_runner_process<RT: Runtime>(
+    rt: RT,
+    storage: InstanceStorage,
+    instance_name: String,
+    instance_secret: InstanceSecret,
+    convex_origin: ConvexOrigin,
+    backend_url: String,
+) -> anyhow::Result<()> {
+    let reader = Arc::new(RemotePersistenceReader::connect(backend_url).await?);
+    // Each process serves a single backend, so it can use its full capacity.
+    let core = FunctionRunnerCore::new(rt, storage, 100).await?;
+    let service = FunctionRunnerProcessService {
+        core: core.clone(),
+        reader,
+        instance_name,
+        instance_secret,
+        convex_origin,
+    };
+
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+    let port = listener.local_addr()?.port();
+    let incoming = stream::unfold(listener, |listener| async move {
+        let stream = listener.accept().await.map(|(stream, _)| stream);
+        Some((stream, listener))
+    });
+    // The backend closes our stdin when it stops or restarts us, and it's also
+    // closed if the backend exits without doing so.
+    let stdin_closed = async {
+        let mut buf = vec![];
+        let _ = tokio::io::stdin().read_to_end(&mut buf).await;
+    };
+    println!("{LISTENING_PREFIX}{port}");
+    tonic::transport::Server::builder()
+        .add_service(
+            FunctionRunnerProcessServer::new(service)
+                .max_decoding_message_size(MAX_MESSAGE_SIZE)
+                .max_encoding_message_size(MAX_MESSAGE_SIZE),
+        )
+        .serve_with_incoming_shutdown(incoming, stdin_closed)
+        .await
+        .context("Function runner process server failed")?;
+    core.shutdown().await
+}
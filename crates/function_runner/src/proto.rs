@@ -0,0 +1,335 @@
+//! Conversions for sending function runs to and from a function runner
+//! process. See `funrun.proto`.
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use common::{
+    bootstrap_model::index::database_index::IndexedFields,
+    document::DocumentUpdate,
+    types::{
+        IndexDescriptor,
+        TabletIndexName,
+    },
+};
+use database::{
+    BootstrapMetadata,
+    IndexReads,
+    ReadSet,
+    TransactionReadSize,
+};
+use pb::funrun::{
+    BootstrapMetadata as BootstrapMetadataProto,
+    FunctionFinalTransaction as FunctionFinalTransactionProto,
+    FunctionReads as FunctionReadsProto,
+    FunctionWrites as FunctionWritesProto,
+    IndexReads as IndexReadsProto,
+    TabletRowsRead as TabletRowsReadProto,
+    TransactionReadSize as TransactionReadSizeProto,
+};
+use value::{
+    FieldPath,
+    InternalId,
+    TabletId,
+};
+
+use crate::{
+    FunctionFinalTransaction,
+    FunctionReads,
+    FunctionWrites,
+};
+
+pub(crate) fn internal_id_from_proto(
+    bytes: Option<Vec<u8>>,
+    field: &'static str,
+) -> anyhow::Result<InternalId> {
+    bytes
+        .with_context(|| format!("Missing {field}"))?
+        .try_into()
+}
+
+impl TryFrom<FunctionWrites> for FunctionWritesProto {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionWrites {
+            updates,
+            generated_ids,
+        }: FunctionWrites,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            updates: updates
+                .into_values()
+                .map(|update| update.try_into())
+                .try_collect()?,
+            generated_ids: generated_ids.into_iter().map(Into::into).collect(),
+        })
+    }
+}
+
+impl TryFrom<FunctionWritesProto> for FunctionWrites {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionWritesProto {
+            updates,
+            generated_ids,
+        }: FunctionWritesProto,
+    ) -> anyhow::Result<Self> {
+        let mut writes = FunctionWrites::default();
+        for update in updates {
+            let update: DocumentUpdate = update.try_into()?;
+            writes.updates.insert(update.id, update);
+        }
+        for id in generated_ids {
+            writes.generated_ids.insert(id.try_into()?);
+        }
+        Ok(writes)
+    }
+}
+
+impl TryFrom<FunctionReads> for FunctionReadsProto {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionReads {
+            reads,
+            num_intervals,
+            user_tx_size,
+            system_tx_size,
+        }: FunctionReads,
+    ) -> anyhow::Result<Self> {
+        let (indexed, mut search) = reads.consume();
+        // Function runner processes don't have search indexes, so functions
+        // that search run in the backend instead.
+        anyhow::ensure!(
+            search.next().is_none(),
+            "Search reads can't be sent from a function runner process"
+        );
+        let indexed = indexed
+            .map(
+                |(
+                    index_name,
+                    IndexReads {
+                        fields, intervals, ..
+                    },
+                )| {
+                    IndexReadsProto {
+                        tablet_id: Some(index_name.table().0.to_vec()),
+                        index_descriptor: Some(index_name.descriptor().to_string()),
+                        fields: fields.into(),
+                        intervals: intervals.into(),
+                    }
+                },
+            )
+            .collect();
+        Ok(Self {
+            indexed,
+            num_intervals: Some(num_intervals as u64),
+            user_tx_size: Some(read_size_to_proto(user_tx_size)),
+            system_tx_size: Some(read_size_to_proto(system_tx_size)),
+        })
+    }
+}
+
+impl TryFrom<FunctionReadsProto> for FunctionReads {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionReadsProto {
+            indexed,
+            num_intervals,
+            user_tx_size,
+            system_tx_size,
+        }: FunctionReadsProto,
+    ) -> anyhow::Result<Self> {
+        let mut indexed_reads = BTreeMap::new();
+        for IndexReadsProto {
+            tablet_id,
+            index_descriptor,
+            fields,
+            intervals,
+        } in indexed
+        {
+            let tablet_id = TabletId(internal_id_from_proto(tablet_id, "tablet_id")?);
+            let descriptor: IndexDescriptor = index_descriptor
+                .context("Missing index_descriptor")?
+                .parse()?;
+            let index_name = if descriptor.is_reserved() {
+                TabletIndexName::new_reserved(tablet_id, descriptor)?
+            } else {
+                TabletIndexName::new(tablet_id, descriptor)?
+            };
+            let fields: Vec<FieldPath> =
+                fields.into_iter().map(FieldPath::try_from).try_collect()?;
+            let reads = IndexReads {
+                fields: IndexedFields::try_from(fields)?,
+                intervals: intervals.try_into()?,
+                stack_traces: None,
+            };
+            indexed_reads.insert(index_name, reads);
+        }
+        Ok(Self {
+            reads: ReadSet::new(indexed_reads, BTreeMap::new()),
+            num_intervals: num_intervals.context("Missing num_intervals")? as usize,
+            user_tx_size: read_size_from_proto(user_tx_size.context("Missing user_tx_size")?),
+            system_tx_size: read_size_from_proto(system_tx_size.context("Missing system_tx_size")?),
+        })
+    }
+}
+
+fn read_size_to_proto(
+    TransactionReadSize {
+        total_document_size,
+        total_document_count,
+    }: TransactionReadSize,
+) -> TransactionReadSizeProto {
+    TransactionReadSizeProto {
+        total_document_size: Some(total_document_size as u64),
+        total_document_count: Some(total_document_count as u64),
+    }
+}
+
+fn read_size_from_proto(
+    TransactionReadSizeProto {
+        total_document_size,
+        total_document_count,
+    }: TransactionReadSizeProto,
+) -> TransactionReadSize {
+    TransactionReadSize {
+        total_document_size: total_document_size.unwrap_or_default() as usize,
+        total_document_count: total_document_count.unwrap_or_default() as usize,
+    }
+}
+
+impl TryFrom<FunctionFinalTransaction> for FunctionFinalTransactionProto {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionFinalTransaction {
+            begin_timestamp,
+            reads,
+            writes,
+            rows_read_by_tablet,
+        }: FunctionFinalTransaction,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            begin_timestamp: Some(begin_timestamp.into()),
+            reads: Some(reads.try_into()?),
+            writes: Some(writes.try_into()?),
+            rows_read_by_tablet: rows_read_by_tablet
+                .into_iter()
+                .map(|(tablet_id, rows_read)| TabletRowsReadProto {
+                    tablet_id: Some(tablet_id.0.to_vec()),
+                    rows_read: Some(rows_read),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<FunctionFinalTransactionProto> for FunctionFinalTransaction {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        FunctionFinalTransactionProto {
+            begin_timestamp,
+            reads,
+            writes,
+            rows_read_by_tablet,
+        }: FunctionFinalTransactionProto,
+    ) -> anyhow::Result<Self> {
+        let mut rows_read = BTreeMap::new();
+        for TabletRowsReadProto {
+            tablet_id,
+            rows_read: count,
+        } in rows_read_by_tablet
+        {
+            let tablet_id = TabletId(internal_id_from_proto(tablet_id, "tablet_id")?);
+            rows_read.insert(tablet_id, count.unwrap_or_default());
+        }
+        Ok(Self {
+            begin_timestamp: begin_timestamp
+                .context("Missing begin_timestamp")?
+                .try_into()?,
+            reads: reads.context("Missing reads")?.try_into()?,
+            writes: writes.context("Missing writes")?.try_into()?,
+            rows_read_by_tablet: rows_read,
+        })
+    }
+}
+
+pub(crate) fn bootstrap_metadata_to_proto(
+    BootstrapMetadata {
+        tables_by_id,
+        index_by_id,
+        tables_tablet_id,
+        index_tablet_id,
+    }: BootstrapMetadata,
+) -> BootstrapMetadataProto {
+    BootstrapMetadataProto {
+        tables_by_id: Some(tables_by_id.to_vec()),
+        index_by_id: Some(index_by_id.to_vec()),
+        tables_tablet_id: Some(tables_tablet_id.0.to_vec()),
+        index_tablet_id: Some(index_tablet_id.0.to_vec()),
+    }
+}
+
+pub(crate) fn bootstrap_metadata_from_proto(
+    BootstrapMetadataProto {
+        tables_by_id,
+        index_by_id,
+        tables_tablet_id,
+        index_tablet_id,
+    }: BootstrapMetadataProto,
+) -> anyhow::Result<BootstrapMetadata> {
+    Ok(BootstrapMetadata {
+        tables_by_id: internal_id_from_proto(tables_by_id, "tables_by_id")?,
+        index_by_id: internal_id_from_proto(index_by_id, "index_by_id")?,
+        tables_tablet_id: TabletId(internal_id_from_proto(
+            tables_tablet_id,
+            "tables_tablet_id",
+        )?),
+        index_tablet_id: TabletId(internal_id_from_proto(index_tablet_id, "index_tablet_id")?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use database::ReadSet;
+    use pb::funrun::{
+        FunctionFinalTransaction as FunctionFinalTransactionProto,
+        FunctionWrites as FunctionWritesProto,
+    };
+    use proptest::prelude::*;
+
+    use crate::{
+        FunctionFinalTransaction,
+        FunctionWrites,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { cases: 64, failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_function_writes_roundtrips(writes in any::<FunctionWrites>()) {
+            let proto = FunctionWritesProto::try_from(writes.clone()).unwrap();
+            assert_eq!(FunctionWrites::try_from(proto).unwrap(), writes);
+        }
+
+        #[test]
+        fn test_function_final_transaction_roundtrips(
+            mut transaction in any::<FunctionFinalTransaction>()
+        ) {
+            // Function runner processes don't send search reads.
+            let (indexed, _) = transaction.reads.reads.consume();
+            transaction.reads.reads = ReadSet::new(indexed.collect(), BTreeMap::new());
+            let proto = FunctionFinalTransactionProto::try_from(transaction.clone()).unwrap();
+            assert_eq!(FunctionFinalTransaction::try_from(proto).unwrap(), transaction);
+        }
+    }
+}
@@ -51,7 +51,10 @@ use database::{
     Transaction,
     TransactionSearchSnapshot,
 };
-use file_storage::TransactionalFileStorage;
+use file_storage::{
+    FileScanner,
+    TransactionalFileStorage,
+};
 use futures::channel::{
     mpsc,
     oneshot,
@@ -70,6 +73,7 @@ use isolate::{
         execute_full_error,
         queue_timer,
     },
+    secrets::SecretResolver,
     ActionCallbacks,
     ActionRequest,
     ActionRequestParams,
@@ -131,12 +135,18 @@ pub trait StorageForInstance<RT: Runtime>: Debug + Clone + Send + Sync + 'static
         transaction: &mut Transaction<RT>,
         use_case: StorageUseCase,
     ) -> anyhow::Result<Arc<dyn Storage>>;
+
+    /// Scanner that files stored by the instance's functions go through.
+    fn file_scanner(&self) -> Option<Arc<dyn FileScanner>> {
+        None
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct InstanceStorage {
     pub files_storage: Arc<dyn Storage>,
     pub modules_storage: Arc<dyn Storage>,
+    pub file_scanner: Option<Arc<dyn FileScanner>>,
 }
 
 #[async_trait]
@@ -152,6 +162,10 @@ impl<RT: Runtime> StorageForInstance<RT> for InstanceStorage {
             _ => anyhow::bail!("function runner storage does not support {use_case}"),
         }
     }
+
+    fn file_scanner(&self) -> Option<Arc<dyn FileScanner>> {
+        self.file_scanner.clone()
+    }
 }
 
 pub struct FunctionRunnerCore<RT: Runtime, S: StorageForInstance<RT>> {
@@ -163,6 +177,7 @@ pub struct FunctionRunnerCore<RT: Runtime, S: StorageForInstance<RT>> {
     storage: S,
     index_cache: InMemoryIndexCache<RT>,
     module_cache: ModuleCache<RT>,
+    secret_resolver: Arc<SecretResolver>,
 }
 
 impl<RT: Runtime, S: StorageForInstance<RT>> Clone for FunctionRunnerCore<RT, S> {
@@ -176,6 +191,7 @@ impl<RT: Runtime, S: StorageForInstance<RT>> Clone for FunctionRunnerCore<RT, S>
             storage: self.storage.clone(),
             index_cache: self.index_cache.clone(),
             module_cache: self.module_cache.clone(),
+            secret_resolver: self.secret_resolver.clone(),
         }
     }
 }
@@ -257,6 +273,7 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
             storage,
             index_cache,
             module_cache,
+            secret_resolver: Arc::new(SecretResolver::from_env()),
         })
     }
 
@@ -361,81 +378,50 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
         FunctionOutcome,
         FunctionUsageStats,
     )> {
-        let usage_tracker = FunctionUsageTracker::new();
-        let retention_validator: Arc<dyn RetentionValidator> = match udf_type {
-            // Since queries and mutations are ready only, we can check the retention
-            // in at end in `validate_function_runner_result`.
-            UdfType::Query | UdfType::Mutation => Arc::new(NoopRetentionValidator {}),
-            // For actions, we have to do it inline since they have side effects.
-            UdfType::Action | UdfType::HttpAction => {
-                Arc::new(FollowerRetentionManager::new(self.rt.clone(), reader.clone()).await?)
-            },
-        };
-        let transaction_ingredients = self
-            .index_cache
-            .begin_tx(
-                identity.clone(),
-                ts,
-                existing_writes,
-                reader,
-                instance_name.clone(),
-                in_memory_index_last_modified,
-                bootstrap_metadata,
-                table_count_snapshot,
-                search_index_snapshot,
-                usage_tracker.clone(),
-                retention_validator,
-            )
-            .await?;
-        let mut transaction = transaction_ingredients.try_into()?;
-        let storage = self
-            .storage
-            .storage_for_instance(&mut transaction, StorageUseCase::Files)
-            .await?;
-        let file_storage = TransactionalFileStorage::new(self.rt.clone(), storage, convex_origin);
-        let modules_storage = self
-            .storage
-            .storage_for_instance(&mut transaction, StorageUseCase::Modules)
-            .await?;
-
-        let key_broker = KeyBroker::new(&instance_name, instance_secret)?;
-        let environment_data = EnvironmentData {
-            key_broker,
-            system_env_vars,
-            file_storage,
-            module_loader: Arc::new(FunctionRunnerModuleLoader {
-                instance_name: instance_name.clone(),
-                cache: self.module_cache.clone(),
-                modules_storage,
-            }),
-        };
-
         match udf_type {
             UdfType::Query | UdfType::Mutation => {
-                let (tx, rx) = oneshot::channel();
-                let request = IsolateRequest::new(
+                self.run_udf_no_retention_check(
                     instance_name,
-                    IsolateRequestType::Udf {
-                        request: UdfRequest {
-                            path_and_args,
-                            udf_type,
-                            identity: identity.into(),
-                            transaction,
-                            journal,
-                            context,
-                        },
-                        environment_data,
-                        response: tx,
-                        queue_timer: queue_timer(),
-                        udf_callback: Box::new(self.clone()),
-                    },
-                    EncodedSpan::from_parent(),
-                );
-                self.send_request(request)?;
-                let (tx, outcome) = Self::receive_response(rx).await??;
-                Ok((Some(tx.into()), outcome, usage_tracker.gather_user_stats()))
+                    instance_secret,
+                    reader,
+                    convex_origin,
+                    bootstrap_metadata,
+                    table_count_snapshot,
+                    search_index_snapshot,
+                    path_and_args,
+                    udf_type,
+                    identity,
+                    ts,
+                    existing_writes,
+                    journal,
+                    system_env_vars,
+                    in_memory_index_last_modified,
+                    context,
+                )
+                .await
             },
             UdfType::Action => {
+                // For actions, we have to check retention inline since they
+                // have side effects.
+                let retention_validator =
+                    Arc::new(FollowerRetentionManager::new(self.rt.clone(), reader.clone()).await?);
+                let (transaction, environment_data, usage_tracker) = self
+                    .begin_function(
+                        instance_name.clone(),
+                        instance_secret,
+                        reader,
+                        convex_origin,
+                        bootstrap_metadata,
+                        table_count_snapshot,
+                        search_index_snapshot,
+                        identity.clone(),
+                        ts,
+                        existing_writes,
+                        system_env_vars,
+                        in_memory_index_last_modified,
+                        retention_validator,
+                    )
+                    .await?;
                 let (tx, rx) = oneshot::channel();
                 let log_line_sender =
                     log_line_sender.context("Missing log line sender for action")?;
@@ -453,6 +439,7 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
                         queue_timer: queue_timer(),
                         action_callbacks,
                         fetch_client,
+                        secret_resolver: self.secret_resolver.clone(),
                         log_line_sender,
                     },
                     EncodedSpan::from_parent(),
@@ -470,6 +457,141 @@ impl<RT: Runtime, S: StorageForInstance<RT>> FunctionRunnerCore<RT, S> {
             },
         }
     }
+
+    /// Like `run_function_no_retention_check` but only for queries and
+    /// mutations, which don't need action callbacks or a fetch client.
+    #[minitrace::trace]
+    pub async fn run_udf_no_retention_check(
+        &self,
+        instance_name: String,
+        instance_secret: InstanceSecret,
+        reader: Arc<dyn PersistenceReader>,
+        convex_origin: ConvexOrigin,
+        bootstrap_metadata: BootstrapMetadata,
+        table_count_snapshot: Arc<dyn TableCountSnapshot>,
+        search_index_snapshot: Arc<dyn TransactionSearchSnapshot>,
+        path_and_args: ValidatedPathAndArgs,
+        udf_type: UdfType,
+        identity: Identity,
+        ts: RepeatableTimestamp,
+        existing_writes: FunctionWrites,
+        journal: QueryJournal,
+        system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        in_memory_index_last_modified: BTreeMap<IndexId, Timestamp>,
+        context: ExecutionContext,
+    ) -> anyhow::Result<(
+        Option<FunctionFinalTransaction>,
+        FunctionOutcome,
+        FunctionUsageStats,
+    )> {
+        anyhow::ensure!(
+            matches!(udf_type, UdfType::Query | UdfType::Mutation),
+            "Expected a query or mutation, got {udf_type}"
+        );
+        // Since queries and mutations are ready only, we can check the retention
+        // in at end in `validate_function_runner_result`.
+        let (transaction, environment_data, usage_tracker) = self
+            .begin_function(
+                instance_name.clone(),
+                instance_secret,
+                reader,
+                convex_origin,
+                bootstrap_metadata,
+                table_count_snapshot,
+                search_index_snapshot,
+                identity.clone(),
+                ts,
+                existing_writes,
+                system_env_vars,
+                in_memory_index_last_modified,
+                Arc::new(NoopRetentionValidator {}),
+            )
+            .await?;
+        let (tx, rx) = oneshot::channel();
+        let request = IsolateRequest::new(
+            instance_name,
+            IsolateRequestType::Udf {
+                request: UdfRequest {
+                    path_and_args,
+                    udf_type,
+                    identity: identity.into(),
+                    transaction,
+                    journal,
+                    context,
+                },
+                environment_data,
+                response: tx,
+                queue_timer: queue_timer(),
+                udf_callback: Box::new(self.clone()),
+            },
+            EncodedSpan::from_parent(),
+        );
+        self.send_request(request)?;
+        let (tx, outcome) = Self::receive_response(rx).await??;
+        Ok((Some(tx.into()), outcome, usage_tracker.gather_user_stats()))
+    }
+
+    /// Begin the transaction a function runs in and gather what its
+    /// environment needs.
+    async fn begin_function(
+        &self,
+        instance_name: String,
+        instance_secret: InstanceSecret,
+        reader: Arc<dyn PersistenceReader>,
+        convex_origin: ConvexOrigin,
+        bootstrap_metadata: BootstrapMetadata,
+        table_count_snapshot: Arc<dyn TableCountSnapshot>,
+        search_index_snapshot: Arc<dyn TransactionSearchSnapshot>,
+        identity: Identity,
+        ts: RepeatableTimestamp,
+        existing_writes: FunctionWrites,
+        system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        in_memory_index_last_modified: BTreeMap<IndexId, Timestamp>,
+        retention_validator: Arc<dyn RetentionValidator>,
+    ) -> anyhow::Result<(Transaction<RT>, EnvironmentData<RT>, FunctionUsageTracker)> {
+        let usage_tracker = FunctionUsageTracker::new();
+        let transaction_ingredients = self
+            .index_cache
+            .begin_tx(
+                identity,
+                ts,
+                existing_writes,
+                reader,
+                instance_name.clone(),
+                in_memory_index_last_modified,
+                bootstrap_metadata,
+                table_count_snapshot,
+                search_index_snapshot,
+                usage_tracker.clone(),
+                retention_validator,
+            )
+            .await?;
+        let mut transaction = transaction_ingredients.try_into()?;
+        let storage = self
+            .storage
+            .storage_for_instance(&mut transaction, StorageUseCase::Files)
+            .await?;
+        let file_storage = TransactionalFileStorage::new(self.rt.clone(), storage, convex_origin)
+            .with_file_scanner(self.storage.file_scanner());
+        let modules_storage = self
+            .storage
+            .storage_for_instance(&mut transaction, StorageUseCase::Modules)
+            .await?;
+
+        let key_broker = KeyBroker::new(&instance_name, instance_secret)?;
+        transaction.set_field_encryptor(Arc::new(key_broker.field_encryptor()));
+        let environment_data = EnvironmentData {
+            key_broker,
+            system_env_vars,
+            file_storage,
+            module_loader: Arc::new(FunctionRunnerModuleLoader {
+                instance_name,
+                cache: self.module_cache.clone(),
+                modules_storage,
+            }),
+        };
+        Ok((transaction, environment_data, usage_tracker))
+    }
 }
 
 #[async_trait]
@@ -656,6 +778,7 @@ mod tests {
         let storage = InstanceStorage {
             files_storage: Arc::new(LocalDirStorage::new(rt.clone())?),
             modules_storage: Arc::new(LocalDirStorage::new(rt.clone())?),
+            file_scanner: None,
         };
         let function_runner_core = FunctionRunnerCore::_new(rt.clone(), storage, 100, 1).await?;
         let (mut pause1, pause_client1) = PauseController::new([PAUSE_REQUEST]);
@@ -686,6 +809,7 @@ mod tests {
         let storage = InstanceStorage {
             files_storage: Arc::new(LocalDirStorage::new(rt.clone())?),
             modules_storage: Arc::new(LocalDirStorage::new(rt.clone())?),
+            file_scanner: None,
         };
         let function_runner_core = FunctionRunnerCore::_new(rt.clone(), storage, 50, 2).await?;
         let (mut pause1, pause_client1) = PauseController::new([PAUSE_REQUEST]);
@@ -712,6 +836,7 @@ mod tests {
         let storage = InstanceStorage {
             files_storage: Arc::new(LocalDirStorage::new(rt.clone())?),
             modules_storage: Arc::new(LocalDirStorage::new(rt.clone())?),
+            file_scanner: None,
         };
         let function_runner_core = FunctionRunnerCore::_new(rt.clone(), storage, 50, 2).await?;
         let (mut pause1, pause_client1) = PauseController::new([PAUSE_REQUEST]);
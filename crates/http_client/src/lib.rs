@@ -37,6 +37,8 @@ static HTTP_CLIENT: LazyLock<reqwest_middleware::ClientWithMiddleware> = LazyLoc
         }))
         .build()
 });
+static UNCACHED_HTTP_CLIENT: LazyLock<reqwest_middleware::ClientWithMiddleware> =
+    LazyLock::new(|| ClientBuilder::new(Client::new()).build());
 
 /// Just for metrics labeling
 #[derive(Copy, Clone, Eq, PartialEq, Debug, strum::IntoStaticStr)]
@@ -52,12 +54,30 @@ pub fn cached_http_client_for(
     move |request: HttpRequest| cached_http_client_inner(request, purpose)
 }
 
+/// Like `cached_http_client_for`, but always makes the request. For callers
+/// that cache what they parse from the response themselves and need to be
+/// able to refetch before the response's `Cache-Control` would allow.
+pub fn http_client_for(
+    purpose: ClientPurpose,
+) -> impl Fn(HttpRequest) -> (impl Future<Output = Result<HttpResponse, AsStdError>> + 'static) + Clone
+{
+    move |request: HttpRequest| http_client_inner(&UNCACHED_HTTP_CLIENT, request, purpose)
+}
+
 /// HTTP fetch function that caches responses in memory based on the
 /// `Cache-Control` headers in the response.
 /// Uses a static `reqwest` client so connections can be reused.
 async fn cached_http_client_inner(
     request: HttpRequest,
     purpose: ClientPurpose,
+) -> Result<HttpResponse, AsStdError> {
+    http_client_inner(&HTTP_CLIENT, request, purpose).await
+}
+
+async fn http_client_inner(
+    client: &'static reqwest_middleware::ClientWithMiddleware,
+    request: HttpRequest,
+    purpose: ClientPurpose,
 ) -> Result<HttpResponse, AsStdError> {
     // Error handling shenanigans because `anyhow::Error` doesn't implement
     // `std::error::Error` (required by openidconnect), but the function body
@@ -65,7 +85,7 @@ async fn cached_http_client_inner(
     // `anyhow::Error`. We can collect the result as an `anyhow::Error`, then
     // convert it to a `AsStdError` which does implement `std::error::Error
     let res: Result<HttpResponse, anyhow::Error> = try {
-        let mut request_builder = HTTP_CLIENT
+        let mut request_builder = client
             .request(request.method, request.url.as_str())
             .body(request.body);
         for (name, value) in &request.headers {
@@ -73,7 +93,7 @@ async fn cached_http_client_inner(
         }
         let request = request_builder.build()?;
 
-        let response = HTTP_CLIENT.execute(request).await?;
+        let response = client.execute(request).await?;
 
         let cache_hit = response
             .headers()
@@ -66,6 +66,7 @@ use common::{
 };
 use database::{
     shutdown_error,
+    TableChanges,
     Transaction,
 };
 use deno_core::{
@@ -119,6 +120,7 @@ use model::{
         ModuleSource,
         SourceMap,
     },
+    scheduled_jobs::types::ScheduleOptions,
     udf_config::types::UdfConfig,
 };
 use parking_lot::Mutex;
@@ -131,11 +133,13 @@ use serde_json::Value as JsonValue;
 use sync_types::{
     CanonicalizedModulePath,
     CanonicalizedUdfPath,
+    Timestamp,
 };
 use usage_tracking::FunctionUsageStats;
 use value::{
     id_v6::DeveloperDocumentId,
     ConvexValue,
+    TableName,
 };
 use vector::PublicVectorSearchQueryResult;
 
@@ -177,6 +181,7 @@ use crate::{
         queue_timer,
         RequestStatus,
     },
+    secrets::SecretResolver,
     ActionOutcome,
     FunctionOutcome,
     HttpActionOutcome,
@@ -318,6 +323,15 @@ pub trait ActionCallbacks: Send + Sync {
         storage_id: FileStorageId,
     ) -> anyhow::Result<()>;
 
+    async fn storage_set_metadata(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        tags: Option<BTreeSet<String>>,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<()>;
+
     // Used to get a file content from an action running in v8.
     async fn storage_get_file_entry(
         &self,
@@ -341,6 +355,7 @@ pub trait ActionCallbacks: Send + Sync {
         path: ComponentFunctionPath,
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId>;
 
@@ -356,6 +371,34 @@ pub trait ActionCallbacks: Send + Sync {
         identity: Identity,
         query: JsonValue,
     ) -> anyhow::Result<(Vec<PublicVectorSearchQueryResult>, FunctionUsageStats)>;
+
+    // Workflows
+    async fn load_workflow_step(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        workflow_id: DeveloperDocumentId,
+        step_name: String,
+    ) -> anyhow::Result<Option<ConvexValue>>;
+
+    async fn checkpoint_workflow_step(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        workflow_id: DeveloperDocumentId,
+        step_name: String,
+        result: ConvexValue,
+    ) -> anyhow::Result<()>;
+
+    // Change feeds
+    async fn table_changes(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        table: TableName,
+        cursor: Option<Timestamp>,
+        limit: usize,
+    ) -> anyhow::Result<TableChanges>;
 }
 
 pub struct UdfRequest<RT: Runtime> {
@@ -431,6 +474,7 @@ pub enum RequestType<RT: Runtime> {
         queue_timer: Timer<VMHistogram>,
         action_callbacks: Arc<dyn ActionCallbacks>,
         fetch_client: Arc<dyn FetchClient>,
+        secret_resolver: Arc<SecretResolver>,
         log_line_sender: mpsc::UnboundedSender<LogLine>,
     },
     HttpAction {
@@ -440,6 +484,7 @@ pub enum RequestType<RT: Runtime> {
         queue_timer: Timer<VMHistogram>,
         action_callbacks: Arc<dyn ActionCallbacks>,
         fetch_client: Arc<dyn FetchClient>,
+        secret_resolver: Arc<SecretResolver>,
         log_line_sender: mpsc::UnboundedSender<LogLine>,
         http_response_streamer: HttpActionResponseStreamer,
     },
@@ -564,6 +609,7 @@ pub struct IsolateClient<RT: Runtime> {
     instance_secret: InstanceSecret,
     file_storage: TransactionalFileStorage<RT>,
     module_loader: Arc<dyn ModuleLoader<RT>>,
+    secret_resolver: Arc<SecretResolver>,
 }
 
 impl<RT: Runtime> Clone for IsolateClient<RT> {
@@ -579,6 +625,7 @@ impl<RT: Runtime> Clone for IsolateClient<RT> {
             instance_secret: self.instance_secret,
             file_storage: self.file_storage.clone(),
             module_loader: self.module_loader.clone(),
+            secret_resolver: self.secret_resolver.clone(),
         }
     }
 }
@@ -681,6 +728,7 @@ impl<RT: Runtime> IsolateClient<RT> {
             instance_secret,
             file_storage,
             module_loader,
+            secret_resolver: Arc::new(SecretResolver::from_env()),
         }
     }
 
@@ -771,6 +819,7 @@ impl<RT: Runtime> IsolateClient<RT> {
             queue_timer: queue_timer(),
             action_callbacks,
             fetch_client,
+            secret_resolver: self.secret_resolver.clone(),
             log_line_sender,
             http_response_streamer,
             environment_data: EnvironmentData {
@@ -826,6 +875,7 @@ impl<RT: Runtime> IsolateClient<RT> {
             queue_timer: queue_timer(),
             action_callbacks,
             fetch_client,
+            secret_resolver: self.secret_resolver.clone(),
             log_line_sender,
             environment_data: EnvironmentData {
                 key_broker,
@@ -1682,6 +1732,7 @@ impl<RT: Runtime> IsolateWorker<RT> for BackendIsolateWorker<RT> {
                 queue_timer,
                 action_callbacks,
                 fetch_client,
+                secret_resolver,
                 log_line_sender,
                 http_response_streamer,
             } => {
@@ -1696,6 +1747,7 @@ impl<RT: Runtime> IsolateWorker<RT> for BackendIsolateWorker<RT> {
                     request.transaction,
                     action_callbacks,
                     fetch_client,
+                    secret_resolver,
                     log_line_sender,
                     Some(http_response_streamer),
                     heap_stats.clone(),
@@ -1730,6 +1782,7 @@ impl<RT: Runtime> IsolateWorker<RT> for BackendIsolateWorker<RT> {
                 queue_timer,
                 action_callbacks,
                 fetch_client,
+                secret_resolver,
                 log_line_sender,
             } => {
                 drop(queue_timer);
@@ -1743,6 +1796,7 @@ impl<RT: Runtime> IsolateWorker<RT> for BackendIsolateWorker<RT> {
                     request.transaction,
                     action_callbacks,
                     fetch_client,
+                    secret_resolver,
                     log_line_sender,
                     None,
                     heap_stats.clone(),
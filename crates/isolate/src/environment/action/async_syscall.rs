@@ -1,21 +1,32 @@
 #![allow(non_snake_case)]
 
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    time::Duration,
+};
+
 use anyhow::Context;
 use common::{
     components::{
         ComponentId,
         Reference,
     },
+    knobs::TABLE_CHANGES_MAX_PAGE_SIZE,
     runtime::{
         Runtime,
         RuntimeInstant,
         UnixTimestamp,
     },
 };
+use database::TableChanges;
 use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
+use keybroker::UploadConstraints;
 use model::file_storage::{
     types::FileStorageEntry,
     FileStorageId,
@@ -28,12 +39,18 @@ use serde_json::{
     json,
     Value as JsonValue,
 };
-use value::id_v6::DeveloperDocumentId;
+use sync_types::Timestamp;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    TableName,
+};
 use vector::VectorSearchRequest;
 
 use super::task_executor::TaskExecutor;
 use crate::{
     environment::helpers::{
+        parse_schedule_options,
         with_argument_error,
         ArgName,
     },
@@ -63,10 +80,16 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 "1.0/getUserIdentity" => self.async_syscall_getUserIdentity(args).await?,
                 "1.0/storageDelete" => self.async_syscall_storageDelete(args).await?,
                 "1.0/storageGetMetadata" => self.async_syscall_storageGetMetadata(args).await?,
+                "1.0/storageSetMetadata" => self.async_syscall_storageSetMetadata(args).await?,
                 "1.0/storageGenerateUploadUrl" => {
                     self.async_syscall_storageGenerateUploadUrl(args).await?
                 },
                 "1.0/storageGetUrl" => self.async_syscall_storageGetUrl(args).await?,
+                "1.0/actions/workflow/getStep" => self.async_syscall_workflow_getStep(args).await?,
+                "1.0/actions/workflow/checkpointStep" => {
+                    self.async_syscall_workflow_checkpointStep(args).await?
+                },
+                "1.0/actions/changes" => self.async_syscall_changes(args).await?,
                 _ => {
                     anyhow::bail!(ErrorMetadata::bad_request(
                         "UnknownAsyncOperation",
@@ -194,17 +217,22 @@ impl<RT: Runtime> TaskExecutor<RT> {
             reference: Option<String>,
             ts: f64,
             args: UdfArgsJson,
+            priority: Option<String>,
+            max_concurrency: Option<u32>,
         }
 
-        let (reference, ts, args) = with_argument_error("scheduler", || {
+        let (reference, ts, args, options) = with_argument_error("scheduler", || {
             let ScheduleArgs {
                 name,
                 reference,
                 ts,
                 args,
+                priority,
+                max_concurrency,
             } = serde_json::from_value(args)?;
             let reference = parse_name_or_reference(name, reference)?;
-            Ok((reference, ts, args))
+            let options = parse_schedule_options(priority, max_concurrency)?;
+            Ok((reference, ts, args, options))
         })?;
         let path = self.resolve_function(&reference)?;
         let scheduled_ts = UnixTimestamp::from_secs_f64(ts);
@@ -215,6 +243,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 path,
                 args.into_arg_vec(),
                 scheduled_ts,
+                options,
                 self.context.clone(),
             )
             .await?;
@@ -260,12 +289,42 @@ impl<RT: Runtime> TaskExecutor<RT> {
 
     async fn async_syscall_storageGenerateUploadUrl(
         &self,
-        _args: JsonValue,
+        args: JsonValue,
     ) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GenerateUploadUrlArgs {
+            #[serde(default)]
+            resumable: bool,
+            max_bytes: Option<u64>,
+            #[serde(default)]
+            allowed_content_types: Vec<String>,
+            expires_in_ms: Option<u64>,
+        }
+        let GenerateUploadUrlArgs {
+            resumable,
+            max_bytes,
+            allowed_content_types,
+            expires_in_ms,
+        } = with_argument_error("storage.generateUploadUrl", || {
+            Ok(serde_json::from_value(args)?)
+        })?;
+        let constraints = UploadConstraints {
+            max_bytes,
+            allowed_content_types,
+            expires_in: expires_in_ms.map(Duration::from_millis),
+        };
         let issued_ts = self.rt.unix_timestamp();
-        let postUrl = self
-            .file_storage
-            .generate_upload_url(&self.key_broker, issued_ts)?;
+        let postUrl = if resumable {
+            self.file_storage.generate_resumable_upload_url(
+                &self.key_broker,
+                issued_ts,
+                &constraints,
+            )?
+        } else {
+            self.file_storage
+                .generate_upload_url(&self.key_broker, issued_ts, &constraints)?
+        };
         Ok(serde_json::to_value(postUrl)?)
     }
 
@@ -306,6 +365,42 @@ impl<RT: Runtime> TaskExecutor<RT> {
         Ok(JsonValue::Null)
     }
 
+    #[convex_macro::instrument_future]
+    async fn async_syscall_storageSetMetadata(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct StorageSetMetadataArgs {
+            storage_id: String,
+            tags: Option<BTreeSet<String>>,
+            metadata: Option<BTreeMap<String, String>>,
+        }
+        let (storage_id, tags, metadata): (FileStorageId, _, _) =
+            with_argument_error("storage.setMetadata", || {
+                let StorageSetMetadataArgs {
+                    storage_id,
+                    tags,
+                    metadata,
+                } = serde_json::from_value(args)?;
+                Ok((
+                    storage_id.parse().context(ArgName("storageId"))?,
+                    tags,
+                    metadata,
+                ))
+            })?;
+
+        self.action_callbacks
+            .storage_set_metadata(
+                self.identity.clone(),
+                self.component_id()?,
+                storage_id,
+                tags,
+                metadata,
+            )
+            .await?;
+
+        Ok(JsonValue::Null)
+    }
+
     #[convex_macro::instrument_future]
     async fn async_syscall_storageGetMetadata(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
         #[derive(Deserialize)]
@@ -325,6 +420,8 @@ impl<RT: Runtime> TaskExecutor<RT> {
             sha256: String,
             size: i64,
             content_type: Option<String>,
+            tags: BTreeSet<String>,
+            metadata: BTreeMap<String, String>,
         }
         let file_metadata = self
             .action_callbacks
@@ -337,6 +434,10 @@ impl<RT: Runtime> TaskExecutor<RT> {
                      sha256,
                      size,
                      content_type,
+                     tags,
+                     metadata,
+                     storage_class: _,
+                     scan_status: _,
                  }| {
                     FileMetadataJson {
                         storage_id: storage_id.to_string(),
@@ -344,11 +445,144 @@ impl<RT: Runtime> TaskExecutor<RT> {
                         sha256: sha256.as_hex(),
                         size,
                         content_type,
+                        tags,
+                        metadata,
                     }
                 },
             );
         Ok(serde_json::to_value(file_metadata)?)
     }
+
+    /// Workflows are actions run by the scheduler, and are identified by the
+    /// id of their scheduled job.
+    fn workflow_id(&self) -> anyhow::Result<DeveloperDocumentId> {
+        match self.context.parent_scheduled_job {
+            Some(job_id) if self.context.is_root() => Ok(job_id),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "WorkflowNotScheduled",
+                "Workflow steps can only be used from actions run by the scheduler"
+            )),
+        }
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_workflow_getStep(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GetStepArgs {
+            name: String,
+        }
+        let step_name = with_argument_error("workflow.step", || {
+            let GetStepArgs { name } = serde_json::from_value(args)?;
+            Ok(name)
+        })?;
+        let workflow_id = self.workflow_id()?;
+        let result = self
+            .action_callbacks
+            .load_workflow_step(
+                self.identity.clone(),
+                self.component_id()?,
+                workflow_id,
+                step_name,
+            )
+            .await?;
+        // Distinguish between steps that haven't completed and steps that
+        // completed with a `null` result.
+        Ok(match result {
+            Some(result) => json!({ "completed": true, "result": JsonValue::from(result) }),
+            None => json!({ "completed": false }),
+        })
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_workflow_checkpointStep(
+        &self,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CheckpointStepArgs {
+            name: String,
+            result: JsonValue,
+        }
+        let (step_name, result) = with_argument_error("workflow.step", || {
+            let CheckpointStepArgs { name, result } = serde_json::from_value(args)?;
+            let result = ConvexValue::try_from(result).context(ArgName("result"))?;
+            Ok((name, result))
+        })?;
+        let workflow_id = self.workflow_id()?;
+        self.action_callbacks
+            .checkpoint_workflow_step(
+                self.identity.clone(),
+                self.component_id()?,
+                workflow_id,
+                step_name,
+                result,
+            )
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn async_syscall_changes(&self, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ChangesArgs {
+            table: String,
+            cursor: Option<String>,
+            num_items: Option<usize>,
+        }
+        let (table, cursor, limit) = with_argument_error("changes", || {
+            let ChangesArgs {
+                table,
+                cursor,
+                num_items,
+            } = serde_json::from_value(args)?;
+            let table: TableName = table.parse().context(ArgName("table"))?;
+            let cursor = cursor
+                .map(|cursor| -> anyhow::Result<_> { Timestamp::try_from(cursor.parse::<u64>()?) })
+                .transpose()
+                .context(ArgName("cursor"))?;
+            let max_page_size = *TABLE_CHANGES_MAX_PAGE_SIZE;
+            let limit = num_items.unwrap_or(max_page_size);
+            anyhow::ensure!(
+                limit > 0 && limit <= max_page_size,
+                "numItems must be between 1 and {max_page_size}"
+            );
+            Ok((table, cursor, limit))
+        })?;
+        let TableChanges {
+            changes,
+            cursor,
+            has_more,
+        } = self
+            .action_callbacks
+            .table_changes(
+                self.identity.clone(),
+                self.component_id()?,
+                table,
+                cursor,
+                limit,
+            )
+            .await?;
+        let changes: Vec<_> = changes
+            .into_iter()
+            .map(|(ts, id, document)| {
+                json!({
+                    "id": id.encode(),
+                    // Milliseconds since the epoch, like `_creationTime`.
+                    "ts": ts.export_lossy_as_f64() / 1_000_000.0,
+                    "document": document
+                        .map(|document| JsonValue::from(document.to_developer().into_value().0)),
+                })
+            })
+            .collect();
+        Ok(json!({
+            "changes": changes,
+            "cursor": cursor.to_string(),
+            "hasMore": has_more,
+        }))
+    }
 }
 
 fn parse_name_or_reference(
@@ -2,7 +2,9 @@ use std::time::Duration;
 
 use ::metrics::StatusTimer;
 use common::{
+    execution_context::TRACEPARENT_HEADER,
     http::{
+        fetch::FetchProxy,
         HttpRequestStream,
         HttpResponseStream,
     },
@@ -60,16 +62,37 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 variant: Ok(TaskResponseEnum::Fetch(response)),
             });
         // After sending status and headers, send the body one chunk at a time.
-        let stream_result = self.send_stream(stream_id, body).await;
+        let stream_result = self.send_stream(stream_id, body, None).await;
         Self::log_fetch_request(t, origin, stream_result, initial_response_time);
     }
 
     #[convex_macro::instrument_future]
     async fn run_fetch_inner(
         &self,
-        request: HttpRequestStream,
+        mut request: HttpRequestStream,
     ) -> anyhow::Result<HttpResponseStream> {
-        self.fetch_client.fetch(request).await
+        // Let the service join the trace, unless the developer set a
+        // `traceparent` of their own.
+        if !request.headers.contains_key(TRACEPARENT_HEADER) {
+            request.headers.insert(
+                TRACEPARENT_HEADER,
+                self.context.traceparent().to_string().parse()?,
+            );
+        }
+        let egress_policy = self.egress_policy.lock().clone();
+        let host = request.url.host_str().unwrap_or_default();
+        egress_policy.check_host(host)?;
+        match egress_policy.proxy {
+            Some(proxy) => {
+                let proxy = FetchProxy {
+                    url: proxy.url.parse()?,
+                    username: proxy.username,
+                    password: proxy.password,
+                };
+                self.fetch_client.fetch_through_proxy(request, &proxy).await
+            },
+            None => self.fetch_client.fetch(request).await,
+        }
     }
 
     fn log_fetch_request(
@@ -28,6 +28,9 @@ use common::{
         ACTION_USER_TIMEOUT,
         FUNCTION_MAX_ARGS_SIZE,
         FUNCTION_MAX_RESULT_SIZE,
+        HTTP_ACTION_REQUEST_BODY_MAX_BUFFERED_BYTES,
+        HTTP_ACTION_RESPONSE_IDLE_TIMEOUT,
+        HTTP_ACTION_SSE_HEARTBEAT_INTERVAL,
         V8_ACTION_SYSTEM_TIMEOUT,
     },
     log_lines::{
@@ -67,6 +70,7 @@ use humansize::{
 use itertools::Itertools;
 use keybroker::Identity;
 use model::{
+    egress_policy::types::EgressPolicy,
     environment_variables::types::{
         EnvVarName,
         EnvVarValue,
@@ -143,6 +147,7 @@ use crate::{
         HttpResponseV8,
     },
     http_action::{
+        with_idle_policy,
         HttpActionRequest,
         HttpActionResponseHead,
         HttpActionResponsePart,
@@ -160,8 +165,10 @@ use crate::{
     ops::OpProvider,
     request_scope::{
         RequestScope,
+        StreamFlowControl,
         StreamListener,
     },
+    secrets::SecretResolver,
     strings,
     termination::{
         IsolateHandle,
@@ -241,6 +248,7 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         transaction: Transaction<RT>,
         action_callbacks: Arc<dyn ActionCallbacks>,
         fetch_client: Arc<dyn FetchClient>,
+        secret_resolver: Arc<SecretResolver>,
         log_line_sender: mpsc::UnboundedSender<LogLine>,
         http_response_streamer: Option<HttpActionResponseStreamer>,
         heap_stats: SharedIsolateHeapStats,
@@ -250,13 +258,14 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         let (task_retval_sender, task_responses) = mpsc::unbounded();
         let resources = Arc::new(Mutex::new(BTreeMap::new()));
         let component_id = Arc::new(Mutex::new(None));
+        let egress_policy = Arc::new(Mutex::new(EgressPolicy::default()));
         let task_executor = TaskExecutor {
             rt: rt.clone(),
             identity: identity.clone(),
             file_storage,
             syscall_trace: syscall_trace.clone(),
             action_callbacks,
-            fetch_client,
+            fetch_client: fetch_client.clone(),
             _module_loader: module_loader.clone(),
             key_broker,
             task_order: Default::default(),
@@ -265,6 +274,7 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             context,
             resources: resources.clone(),
             component_id: component_id.clone(),
+            egress_policy: egress_policy.clone(),
         };
         let (pending_task_sender, pending_task_receiver) = mpsc::unbounded();
         let running_tasks = rt.spawn("task_executor", task_executor.go(pending_task_receiver));
@@ -286,8 +296,11 @@ impl<RT: Runtime> ActionEnvironment<RT> {
                 transaction,
                 module_loader,
                 system_env_vars,
+                fetch_client,
+                secret_resolver,
                 resources,
                 component_id,
+                egress_policy,
             ),
             syscall_trace,
             heap_stats,
@@ -391,7 +404,7 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             state
                 .environment
                 .phase
-                .initialize(&mut state.timeout, &mut state.permit)
+                .initialize(&mut state.timeout, &mut state.permit, None)
                 .await?;
         }
 
@@ -460,11 +473,15 @@ impl<RT: Runtime> ActionEnvironment<RT> {
 
         let stream_id = match http_request.body {
             Some(body) => {
-                let stream_id = scope.state_mut()?.create_stream()?;
-                scope
-                    .state_mut()?
+                // Only read the body from the client as fast as the action
+                // consumes it, so large uploads aren't buffered in memory.
+                let flow_control =
+                    StreamFlowControl::new(*HTTP_ACTION_REQUEST_BODY_MAX_BUFFERED_BYTES);
+                let state = scope.state_mut()?;
+                let stream_id = state.create_stream_with_flow_control(flow_control.clone())?;
+                state
                     .environment
-                    .send_stream(stream_id, Some(body));
+                    .send_stream(stream_id, Some(body), Some(flow_control));
                 Some(stream_id)
             },
             None => None,
@@ -511,14 +528,28 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             },
             None => body_sender.close_channel(),
         };
-        let head = futures::stream::once(async move {
-            Ok(Ok(HttpActionResponsePart::Head(HttpActionResponseHead {
-                status: raw_response.status,
-                headers: raw_response.headers,
-            })))
-        });
-
-        Ok(head.chain(body_receiver.map_ok(|b| Ok(HttpActionResponsePart::BodyChunk(b)))))
+        let mut head = HttpActionResponseHead {
+            status: raw_response.status,
+            headers: raw_response.headers,
+        };
+        // Server-sent event streams are long-lived, so keep the connection
+        // alive while the action is quiet.
+        let heartbeat_interval = if head.is_event_stream() {
+            head.prepare_event_stream();
+            Some(*HTTP_ACTION_SSE_HEARTBEAT_INTERVAL)
+        } else {
+            None
+        };
+        let rt = scope.state()?.environment.rt.clone();
+        let body = with_idle_policy(
+            rt,
+            body_receiver,
+            *HTTP_ACTION_RESPONSE_IDLE_TIMEOUT,
+            heartbeat_interval,
+        );
+        let head = futures::stream::once(async move { Ok(Ok(HttpActionResponsePart::Head(head))) });
+
+        Ok(head.chain(body.map_ok(|b| b.map(HttpActionResponsePart::BodyChunk))))
     }
 
     fn handle_http_streamed_part(
@@ -569,12 +600,17 @@ impl<RT: Runtime> ActionEnvironment<RT> {
         &mut self,
         stream_id: uuid::Uuid,
         stream: Option<BoxStream<'static, anyhow::Result<bytes::Bytes>>>,
+        flow_control: Option<StreamFlowControl>,
     ) {
         let task_id = self.next_task_id.increment();
         self.pending_task_sender
             .unbounded_send(TaskRequest {
                 task_id,
-                variant: TaskRequestEnum::AsyncOp(AsyncOpRequest::SendStream { stream, stream_id }),
+                variant: TaskRequestEnum::AsyncOp(AsyncOpRequest::SendStream {
+                    stream,
+                    stream_id,
+                    flow_control,
+                }),
                 parent_trace: EncodedSpan::from_parent(),
             })
             .expect("TaskExecutor went away?");
@@ -668,7 +704,11 @@ impl<RT: Runtime> ActionEnvironment<RT> {
             state
                 .environment
                 .phase
-                .initialize(&mut state.timeout, &mut state.permit)
+                .initialize(
+                    &mut state.timeout,
+                    &mut state.permit,
+                    request_params.path_and_args.source_package_id(),
+                )
                 .await?;
         }
         let (path, arguments, _) = request_params.path_and_args.consume();
@@ -12,6 +12,7 @@ use common::{
         Reference,
         Resource,
     },
+    http::fetch::FetchClient,
     runtime::{
         Runtime,
         UnixTimestamp,
@@ -26,6 +27,10 @@ use errors::ErrorMetadata;
 use model::{
     components::ComponentsModel,
     config::module_loader::ModuleLoader,
+    egress_policy::{
+        types::EgressPolicy,
+        EgressPolicyModel,
+    },
     environment_variables::{
         types::{
             EnvVarName,
@@ -38,7 +43,10 @@ use model::{
         types::ModuleMetadata,
         ModuleModel,
     },
-    source_packages::SourcePackageModel,
+    source_packages::{
+        types::SourcePackageId,
+        SourcePackageModel,
+    },
     udf_config::UdfConfigModel,
 };
 use parking_lot::Mutex;
@@ -61,6 +69,7 @@ use crate::{
             Phase,
         },
     },
+    secrets::SecretResolver,
     timeout::Timeout,
 };
 
@@ -83,8 +92,11 @@ enum ActionPreloaded<RT: Runtime> {
         tx: Transaction<RT>,
         module_loader: Arc<dyn ModuleLoader<RT>>,
         system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        fetch_client: Arc<dyn FetchClient>,
+        secret_resolver: Arc<SecretResolver>,
         resources: Arc<Mutex<BTreeMap<Reference, Resource>>>,
         component_id: Arc<Mutex<Option<ComponentId>>>,
+        egress_policy: Arc<Mutex<EgressPolicy>>,
     },
     Preloading,
     Ready {
@@ -102,8 +114,11 @@ impl<RT: Runtime> ActionPhase<RT> {
         tx: Transaction<RT>,
         module_loader: Arc<dyn ModuleLoader<RT>>,
         system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
+        fetch_client: Arc<dyn FetchClient>,
+        secret_resolver: Arc<SecretResolver>,
         resources: Arc<Mutex<BTreeMap<Reference, Resource>>>,
         component_id: Arc<Mutex<Option<ComponentId>>>,
+        egress_policy: Arc<Mutex<EgressPolicy>>,
     ) -> Self {
         Self {
             component,
@@ -113,17 +128,23 @@ impl<RT: Runtime> ActionPhase<RT> {
                 tx,
                 module_loader,
                 system_env_vars,
+                fetch_client,
+                secret_resolver,
                 resources,
                 component_id,
+                egress_policy,
             },
         }
     }
 
+    /// Load the component's modules, from `source_package_id` if the action
+    /// is running a canary.
     #[minitrace::trace]
     pub async fn initialize(
         &mut self,
         timeout: &mut Timeout<RT>,
         permit_slot: &mut Option<ConcurrencyPermit>,
+        source_package_id: Option<SourcePackageId>,
     ) -> anyhow::Result<()> {
         anyhow::ensure!(self.phase == Phase::Importing);
 
@@ -132,8 +153,11 @@ impl<RT: Runtime> ActionPhase<RT> {
             mut tx,
             module_loader,
             system_env_vars,
+            fetch_client,
+            secret_resolver,
             resources,
             component_id,
+            egress_policy,
         } = preloaded
         else {
             anyhow::bail!("ActionPhase initialized twice");
@@ -149,6 +173,11 @@ impl<RT: Runtime> ActionPhase<RT> {
         })
         .await?;
 
+        let policy =
+            with_release_permit(timeout, permit_slot, EgressPolicyModel::new(&mut tx).get())
+                .await?;
+        *egress_policy.lock() = policy;
+
         let udf_config = with_release_permit(
             timeout,
             permit_slot,
@@ -165,9 +194,11 @@ impl<RT: Runtime> ActionPhase<RT> {
             let module_metadata = ModuleModel::new(&mut tx)
                 .get_all_metadata(component_id)
                 .await?;
-            let source_package = SourcePackageModel::new(&mut tx, component_id.into())
-                .get_latest()
-                .await?;
+            let mut source_packages = SourcePackageModel::new(&mut tx, component_id.into());
+            let source_package = match source_package_id {
+                Some(source_package_id) => Some(source_packages.get(source_package_id).await?),
+                None => source_packages.get_latest().await?,
+            };
             let loaded_resources = ComponentsModel::new(&mut tx)
                 .preload_resources(component_id)
                 .await?;
@@ -181,10 +212,13 @@ impl<RT: Runtime> ActionPhase<RT> {
 
         let modules = with_release_permit(timeout, permit_slot, async {
             let mut modules = BTreeMap::new();
-            for metadata in module_metadata {
+            for mut metadata in module_metadata {
                 if metadata.path.is_system() {
                     continue;
                 }
+                if let Some(source_package_id) = source_package_id {
+                    metadata.source_package_id = source_package_id;
+                }
                 let path = metadata.path.clone();
                 let module = module_loader
                     .get_module_with_metadata(
@@ -199,12 +233,21 @@ impl<RT: Runtime> ActionPhase<RT> {
         .await?;
 
         let mut env_vars = system_env_vars;
-        let user_env_vars = with_release_permit(
+        let mut user_env_vars = with_release_permit(
             timeout,
             permit_slot,
             EnvironmentVariablesModel::new(&mut tx).get_all(),
         )
         .await?;
+        // Environment variables can refer to secrets in a secret manager,
+        // which are fetched over the network.
+        let now = self.rt.system_time();
+        with_release_permit(
+            timeout,
+            permit_slot,
+            secret_resolver.resolve(&*fetch_client, &mut user_env_vars, now),
+        )
+        .await?;
         env_vars.extend(user_env_vars);
 
         self.preloaded = ActionPreloaded::Ready {
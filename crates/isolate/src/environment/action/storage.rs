@@ -115,7 +115,7 @@ impl<RT: Runtime> TaskExecutor<RT> {
                         task_id,
                         variant: Ok(TaskResponseEnum::StorageGet(Some(result))),
                     });
-                let _ = self.send_stream(stream_id, Some(stream)).await;
+                let _ = self.send_stream(stream_id, Some(stream), None).await;
             },
         }
     }
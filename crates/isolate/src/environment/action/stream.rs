@@ -9,20 +9,25 @@ use futures::{
 use http::Request;
 
 use super::task_executor::TaskExecutor;
-use crate::environment::action::task::{
-    FormPart,
-    FormPartFile,
-    TaskResponse,
+use crate::{
+    environment::action::task::{
+        FormPart,
+        FormPartFile,
+        TaskResponse,
+    },
+    request_scope::StreamFlowControl,
 };
 
 impl<RT: Runtime> TaskExecutor<RT> {
     // Sends a stream to javascript by sending TaskResponse::StreamExtend
     // repeatedly. Any errors are sent with StreamExtend, and the number of bytes
-    // sent are returned on success.
+    // sent are returned on success. With `flow_control`, each chunk waits until
+    // javascript has read enough of the chunks before it.
     pub async fn send_stream(
         &self,
         stream_id: uuid::Uuid,
         stream: Option<BoxStream<'static, anyhow::Result<bytes::Bytes>>>,
+        flow_control: Option<StreamFlowControl>,
     ) -> Result<usize, ()> {
         let mut size = 0;
         if let Some(mut stream) = stream {
@@ -42,6 +47,11 @@ impl<RT: Runtime> TaskExecutor<RT> {
                         return Err(());
                     },
                     Ok(chunk) => {
+                        if let Some(flow_control) = &flow_control
+                            && flow_control.reserve(chunk.len()).await.is_err()
+                        {
+                            return Err(());
+                        }
                         size += chunk.len();
                         _ = self
                             .task_retval_sender
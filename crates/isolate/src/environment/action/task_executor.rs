@@ -36,7 +36,10 @@ use keybroker::{
     KeyBroker,
 };
 use minitrace::future::FutureExt;
-use model::config::module_loader::ModuleLoader;
+use model::{
+    config::module_loader::ModuleLoader,
+    egress_policy::types::EgressPolicy,
+};
 use parking_lot::Mutex;
 use serde_json::Value as JsonValue;
 use usage_tracking::FunctionUsageTracker;
@@ -78,6 +81,8 @@ pub struct TaskExecutor<RT: Runtime> {
     pub context: ExecutionContext,
     pub resources: Arc<Mutex<BTreeMap<Reference, Resource>>>,
     pub component_id: Arc<Mutex<Option<ComponentId>>>,
+    // Loaded when the action starts, before it can make any requests.
+    pub egress_policy: Arc<Mutex<EgressPolicy>>,
 }
 
 impl<RT: Runtime> TaskExecutor<RT> {
@@ -125,8 +130,12 @@ impl<RT: Runtime> TaskExecutor<RT> {
                 .run_async_syscall(name, args)
                 .await
                 .map(TaskResponseEnum::Syscall),
-            TaskRequestEnum::AsyncOp(AsyncOpRequest::SendStream { stream, stream_id }) => {
-                let _ = self.send_stream(stream_id, stream).await;
+            TaskRequestEnum::AsyncOp(AsyncOpRequest::SendStream {
+                stream,
+                stream_id,
+                flow_control,
+            }) => {
+                let _ = self.send_stream(stream_id, stream, flow_control).await;
                 return task_id;
             },
             TaskRequestEnum::AsyncOp(AsyncOpRequest::Fetch {
@@ -51,8 +51,10 @@ use model::{
         EnvVarValue,
     },
     modules::{
+        function_dependencies::FunctionDependencies,
         function_validators::{
             ArgsValidator,
+            ErrorsValidator,
             ReturnsValidator,
         },
         module_versions::{
@@ -63,6 +65,7 @@ use model::{
             AnalyzedSourcePosition,
             FullModuleSource,
             MappedModule,
+            QueryCachePolicy,
             Visibility,
         },
         user_error::ModuleNotFoundError,
@@ -360,6 +363,7 @@ impl AnalyzeEnvironment {
                                     e.message
                                 ),
                                 custom_data: None,
+                                custom_data_source: Default::default(),
                                 frames: e.frames,
                             }))
                         },
@@ -582,6 +586,88 @@ fn udf_analyze<RT: Runtime>(
             ReturnsValidator::Unvalidated
         };
 
+        // Call `exportErrors` to get the typed errors the function declares.
+        let export_errors = strings::exportErrors.create(scope)?;
+        let errors = match function.get(scope, export_errors.into()) {
+            Some(export_errors_value) if export_errors_value.is_function() => {
+                let export_errors_function: v8::Local<v8::Function> =
+                    export_errors_value.try_into()?;
+                let result_v8: v8::Local<v8::String> = scope
+                    .with_try_catch(|s| export_errors_function.call(s, function.into(), &[]))??
+                    .context("Missing return value from successful function call")?
+                    .try_into()?;
+                let result_str = helpers::to_rust_string(scope, &result_v8)?;
+                let errors_json = match serde_json::from_str::<JsonValue>(&result_str) {
+                    Ok(errors_json) => errors_json,
+                    Err(parse_error) => {
+                        let message =
+                            format!("Unable to parse JSON from `exportErrors`: {parse_error}");
+                        return Ok(Err(JsError::from_message(message)));
+                    },
+                };
+                match ErrorsValidator::try_from(errors_json) {
+                    Ok(validator) => validator,
+                    Err(parse_error) => {
+                        let message = format!("Invalid errors for {property_name}: {parse_error}");
+                        return Ok(Err(JsError::from_message(message)));
+                    },
+                }
+            },
+            // `exportErrors` is undefined for old npm versions.
+            _ => ErrorsValidator::Unvalidated,
+        };
+
+        // Call `exportCache` to get the query's result caching, if it opted in.
+        let export_cache = strings::exportCache.create(scope)?;
+        let cache = match function.get(scope, export_cache.into()) {
+            Some(export_cache_value) if export_cache_value.is_function() => {
+                let export_cache_function: v8::Local<v8::Function> =
+                    export_cache_value.try_into()?;
+                let result_v8: v8::Local<v8::String> = scope
+                    .with_try_catch(|s| export_cache_function.call(s, function.into(), &[]))??
+                    .context("Missing return value from successful function call")?
+                    .try_into()?;
+                let result_str = helpers::to_rust_string(scope, &result_v8)?;
+                let cache_json = match serde_json::from_str::<JsonValue>(&result_str) {
+                    Ok(cache_json) => cache_json,
+                    Err(parse_error) => {
+                        let message =
+                            format!("Unable to parse JSON from `exportCache`: {parse_error}");
+                        return Ok(Err(JsError::from_message(message)));
+                    },
+                };
+                if cache_json.is_null() {
+                    None
+                } else if udf_type != UdfType::Query {
+                    let message = format!(
+                        "{property_name} declares result caching, but only queries can be cached"
+                    );
+                    return Ok(Err(JsError::from_message(message)));
+                } else {
+                    match QueryCachePolicy::try_from(cache_json) {
+                        Ok(policy) => Some(policy),
+                        Err(parse_error) => {
+                            let message = format!(
+                                "Invalid result caching for {property_name}: {parse_error}"
+                            );
+                            return Ok(Err(JsError::from_message(message)));
+                        },
+                    }
+                }
+            },
+            // `exportCache` is undefined for functions that don't opt in and
+            // for old npm versions.
+            _ => None,
+        };
+
+        // The registered function is the handler itself, so its source is the
+        // handler's source.
+        let source = function
+            .to_string(scope)
+            .ok_or_else(|| anyhow!("Failed to get source of {property_name}"))?;
+        let source = helpers::to_rust_string(scope, &source)?;
+        let dependencies = FunctionDependencies::from_source(&source);
+
         let visibility = match (is_public, is_internal) {
             (true, false) => Some(Visibility::Public),
             (false, true) => Some(Visibility::Internal),
@@ -640,6 +726,9 @@ fn udf_analyze<RT: Runtime>(
                 visibility: visibility.clone(),
                 args: args.clone(),
                 returns: returns.clone(),
+                errors: errors.clone(),
+                cache,
+                dependencies: Some(dependencies),
             });
         } else {
             // If there is no valid source map, push a function without a position
@@ -650,6 +739,9 @@ fn udf_analyze<RT: Runtime>(
                 visibility: visibility.clone(),
                 args: args.clone(),
                 returns: returns.clone(),
+                errors: errors.clone(),
+                cache,
+                dependencies: Some(dependencies),
             });
 
             // Log reason for fallback
@@ -9,6 +9,8 @@ use futures::{
     stream::BoxStream,
 };
 
+use crate::request_scope::StreamFlowControl;
+
 pub enum AsyncOpRequest {
     Fetch {
         request: HttpRequestStream,
@@ -35,6 +37,7 @@ pub enum AsyncOpRequest {
     SendStream {
         stream: Option<BoxStream<'static, anyhow::Result<bytes::Bytes>>>,
         stream_id: uuid::Uuid,
+        flow_control: Option<StreamFlowControl>,
     },
 }
 
@@ -9,11 +9,13 @@ mod syscall_trace;
 pub mod validation;
 mod version;
 
+use anyhow::Context;
 use deno_core::{
     serde_v8,
     v8,
 };
 use errors::ErrorMetadata;
+use model::scheduled_jobs::types::ScheduleOptions;
 use serde_json::Value as JsonValue;
 
 pub use self::{
@@ -54,6 +56,22 @@ pub fn with_argument_error<T>(
     })
 }
 
+/// Parses the optional `priority` and `maxConcurrency` arguments to the
+/// scheduler syscalls.
+pub fn parse_schedule_options(
+    priority: Option<String>,
+    max_concurrency: Option<u32>,
+) -> anyhow::Result<ScheduleOptions> {
+    let priority = match priority {
+        Some(priority) => priority.parse().context(ArgName("priority"))?,
+        None => Default::default(),
+    };
+    Ok(ScheduleOptions {
+        priority,
+        max_concurrency,
+    })
+}
+
 #[derive(Eq, PartialEq, Debug)]
 pub enum Phase {
     Importing,
@@ -24,6 +24,7 @@ use common::{
     },
 };
 use database::{
+    read_only_error,
     unauthorized_error,
     BootstrapComponentsModel,
     Transaction,
@@ -38,13 +39,17 @@ use model::{
         PAUSED_ERROR_MESSAGE,
     },
     modules::{
-        function_validators::ReturnsValidator,
+        function_validators::{
+            ErrorsValidator,
+            ReturnsValidator,
+        },
         module_versions::{
             AnalyzedFunction,
             Visibility,
         },
         ModuleModel,
     },
+    source_packages::types::SourcePackageId,
     udf_config::UdfConfigModel,
 };
 #[cfg(any(test, feature = "testing"))]
@@ -57,6 +62,7 @@ use value::{
     heap_size::HeapSize,
     ConvexArray,
     ConvexValue,
+    DeveloperDocumentId,
     NamespacedTableMapping,
     NamespacedVirtualTableMapping,
 };
@@ -204,6 +210,9 @@ pub struct ValidatedPathAndArgs {
     args: ConvexArray,
     // Not set for system modules.
     npm_version: Option<Version>,
+    // Set when the call runs a canary, whose modules are loaded from this
+    // source package rather than the active one.
+    source_package_id: Option<SourcePackageId>,
 }
 
 #[cfg(any(test, feature = "testing"))]
@@ -223,6 +232,7 @@ impl Arbitrary for ValidatedPathAndArgs {
                 },
                 args,
                 npm_version: None,
+                source_package_id: None,
             }
         })
     }
@@ -255,6 +265,7 @@ impl ValidatedPathAndArgs {
                     path,
                     args,
                     npm_version: None,
+                    source_package_id: None,
                 })
             } else {
                 Err(JsError::from_message(
@@ -280,6 +291,11 @@ impl ValidatedPathAndArgs {
                     DISABLED_ERROR_MESSAGE.to_string(),
                 )));
             },
+            BackendState::ReadOnly => {
+                if expected_udf_type != UdfType::Query {
+                    anyhow::bail!(read_only_error());
+                }
+            },
         }
 
         let udf_version = match udf_version(&path, component, tx).await? {
@@ -306,15 +322,16 @@ impl ValidatedPathAndArgs {
         )
     }
 
-    /// Do argument validation and get returns validator without retrieving
-    /// the analyze result twice.
+    /// Do argument validation and get the returns and errors validators
+    /// without retrieving the analyze result twice.
     pub async fn new_with_returns_validator<RT: Runtime>(
         allowed_visibility: AllowedVisibility,
         tx: &mut Transaction<RT>,
         path: CanonicalizedComponentFunctionPath,
         args: ConvexArray,
         expected_udf_type: UdfType,
-    ) -> anyhow::Result<Result<(ValidatedPathAndArgs, ReturnsValidator), JsError>> {
+    ) -> anyhow::Result<Result<(ValidatedPathAndArgs, ReturnsValidator, ErrorsValidator), JsError>>
+    {
         if path.udf_path.is_system() {
             // We don't analyze system modules, so we don't validate anything
             // except the identity for them.
@@ -324,8 +341,10 @@ impl ValidatedPathAndArgs {
                         path,
                         args,
                         npm_version: None,
+                        source_package_id: None,
                     },
                     ReturnsValidator::Unvalidated,
+                    ErrorsValidator::Unvalidated,
                 ))
             } else {
                 Err(JsError::from_message(
@@ -346,6 +365,11 @@ impl ValidatedPathAndArgs {
                     DISABLED_ERROR_MESSAGE.to_string(),
                 )));
             },
+            BackendState::ReadOnly => {
+                if expected_udf_type != UdfType::Query {
+                    anyhow::bail!(read_only_error());
+                }
+            },
         }
 
         let (_, component) = BootstrapComponentsModel::new(tx)
@@ -366,10 +390,13 @@ impl ValidatedPathAndArgs {
             )?)));
         };
 
-        let returns_validator = if path.udf_path.is_system() {
-            ReturnsValidator::Unvalidated
+        let (returns_validator, errors_validator) = if path.udf_path.is_system() {
+            (ReturnsValidator::Unvalidated, ErrorsValidator::Unvalidated)
         } else {
-            analyzed_function.returns.clone()
+            (
+                analyzed_function.returns.clone(),
+                analyzed_function.errors.clone(),
+            )
         };
 
         match ValidatedPathAndArgs::new_inner(
@@ -382,9 +409,11 @@ impl ValidatedPathAndArgs {
             analyzed_function,
             udf_version,
         )? {
-            Ok(validated_udf_path_and_args) => {
-                Ok(Ok((validated_udf_path_and_args, returns_validator)))
-            },
+            Ok(validated_udf_path_and_args) => Ok(Ok((
+                validated_udf_path_and_args,
+                returns_validator,
+                errors_validator,
+            ))),
             Err(js_err) => Ok(Err(js_err)),
         }
     }
@@ -403,6 +432,23 @@ impl ValidatedPathAndArgs {
         match identity {
             // This is an admin, so allow calling all functions
             Identity::InstanceAdmin(_) | Identity::ActingUser(..) => (),
+            // API keys can call internal functions too, but calls made with them
+            // directly are limited to the functions in the key's scope.
+            Identity::ApiKey(api_key) => match allowed_visibility {
+                AllowedVisibility::All => (),
+                AllowedVisibility::PublicOnly => {
+                    if !api_key.scope().allows_function(&path) {
+                        anyhow::bail!(ErrorMetadata::forbidden(
+                            "Unauthorized",
+                            format!(
+                                "API key {} does not have permission to run {}.",
+                                api_key.name(),
+                                path.udf_path
+                            ),
+                        ));
+                    }
+                },
+            },
             _ => match allowed_visibility {
                 AllowedVisibility::All => (),
                 AllowedVisibility::PublicOnly => match analyzed_function.visibility {
@@ -446,15 +492,18 @@ impl ValidatedPathAndArgs {
                 .check_args(&args, table_mapping, virtual_table_mapping)?;
 
         if let Some(error) = args_validation_error {
-            return Ok(Err(JsError::from_message(format!(
-                "ArgumentValidationError: {error}",
-            ))));
+            // Keep the error's data, which says where the args didn't match.
+            return Ok(Err(JsError {
+                message: format!("ArgumentValidationError: {error}"),
+                ..error
+            }));
         }
 
         Ok(Ok(ValidatedPathAndArgs {
             path,
             args,
             npm_version: Some(version),
+            source_package_id: None,
         }))
     }
 
@@ -468,6 +517,7 @@ impl ValidatedPathAndArgs {
             path,
             args,
             npm_version,
+            source_package_id: None,
         }
     }
 
@@ -475,6 +525,18 @@ impl ValidatedPathAndArgs {
         &self.path
     }
 
+    /// Run the function's canary, whose modules are in `source_package_id`.
+    pub fn with_source_package(self, source_package_id: SourcePackageId) -> Self {
+        Self {
+            source_package_id: Some(source_package_id),
+            ..self
+        }
+    }
+
+    pub fn source_package_id(&self) -> Option<SourcePackageId> {
+        self.source_package_id
+    }
+
     pub fn consume(
         self,
     ) -> (
@@ -495,6 +557,7 @@ impl ValidatedPathAndArgs {
             args,
             npm_version,
             component_path,
+            source_package_id,
         }: pb::common::ValidatedPathAndArgs,
     ) -> anyhow::Result<Self> {
         let args_json: JsonValue =
@@ -512,6 +575,9 @@ impl ValidatedPathAndArgs {
             },
             args,
             npm_version: npm_version.map(|v| Version::parse(&v)).transpose()?,
+            source_package_id: source_package_id
+                .map(|id| anyhow::Ok(DeveloperDocumentId::decode(&id)?.into()))
+                .transpose()?,
         })
     }
 }
@@ -524,6 +590,7 @@ impl TryFrom<ValidatedPathAndArgs> for pb::common::ValidatedPathAndArgs {
             path,
             args,
             npm_version,
+            source_package_id,
         }: ValidatedPathAndArgs,
     ) -> anyhow::Result<Self> {
         let args_json = JsonValue::from(args);
@@ -534,6 +601,7 @@ impl TryFrom<ValidatedPathAndArgs> for pb::common::ValidatedPathAndArgs {
             args: Some(args),
             npm_version: npm_version.map(|v| v.to_string()),
             component_path,
+            source_package_id: source_package_id.map(|id| DeveloperDocumentId::from(id).encode()),
         })
     }
 }
@@ -555,9 +623,9 @@ impl ValidatedHttpPath {
         npm_version: Option<Version>,
     ) -> anyhow::Result<Self> {
         if !udf_path.is_system() {
-            BackendStateModel::new(tx)
-                .fail_while_paused_or_disabled()
-                .await?;
+            let mut backend_state_model = BackendStateModel::new(tx);
+            backend_state_model.fail_while_paused_or_disabled().await?;
+            backend_state_model.fail_while_read_only().await?;
         }
         Ok(Self {
             path: CanonicalizedComponentFunctionPath {
@@ -579,9 +647,9 @@ impl ValidatedHttpPath {
             path.udf_path,
         );
         if !path.udf_path.is_system() {
-            BackendStateModel::new(tx)
-                .fail_while_paused_or_disabled()
-                .await?;
+            let mut backend_state_model = BackendStateModel::new(tx);
+            backend_state_model.fail_while_paused_or_disabled().await?;
+            backend_state_model.fail_while_read_only().await?;
         }
         let (_, component) = BootstrapComponentsModel::new(tx)
             .component_path_to_ids(path.component.clone())
@@ -692,6 +760,7 @@ impl ValidatedUdfOutcome {
     pub fn new(
         outcome: UdfOutcome,
         returns_validator: ReturnsValidator,
+        errors_validator: ErrorsValidator,
         table_mapping: &NamespacedTableMapping,
         virtual_table_mapping: &NamespacedVirtualTableMapping,
     ) -> Self {
@@ -713,7 +782,14 @@ impl ValidatedUdfOutcome {
         // TODO(CX-6318) Don't pack json value until it's been validated.
         let returns: ConvexValue = match &validated.result {
             Ok(json_packed_value) => json_packed_value.unpack(),
-            Err(_) => return validated,
+            Err(js_err) => {
+                if let Some(js_err) =
+                    errors_validator.check_error(js_err, table_mapping, virtual_table_mapping)
+                {
+                    validated.result = Err(js_err);
+                }
+                return validated;
+            },
         };
 
         if let Some(js_err) =
@@ -744,6 +820,7 @@ impl ValidatedActionOutcome {
     pub fn new(
         outcome: ActionOutcome,
         returns_validator: ReturnsValidator,
+        errors_validator: ErrorsValidator,
         table_mapping: &NamespacedTableMapping,
         virtual_table_mapping: &NamespacedVirtualTableMapping,
     ) -> Self {
@@ -757,13 +834,22 @@ impl ValidatedActionOutcome {
             udf_server_version: outcome.udf_server_version,
         };
 
-        if let Ok(ref json_packed_value) = &validated.result {
-            let output = json_packed_value.unpack();
-            if let Some(js_err) =
-                returns_validator.check_output(&output, table_mapping, virtual_table_mapping)
-            {
-                validated.result = Err(js_err);
-            }
+        match &validated.result {
+            Ok(json_packed_value) => {
+                let output = json_packed_value.unpack();
+                if let Some(js_err) =
+                    returns_validator.check_output(&output, table_mapping, virtual_table_mapping)
+                {
+                    validated.result = Err(js_err);
+                }
+            },
+            Err(js_err) => {
+                if let Some(js_err) =
+                    errors_validator.check_error(js_err, table_mapping, virtual_table_mapping)
+                {
+                    validated.result = Err(js_err);
+                }
+            },
         }
 
         validated
@@ -1,8 +1,15 @@
 #![allow(non_snake_case)]
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     marker::PhantomData,
-    time::Duration,
+    ops::Bound,
+    time::{
+        Duration,
+        SystemTime,
+    },
 };
 
 use anyhow::Context;
@@ -15,7 +22,12 @@ use common::{
     },
     document::DeveloperDocument,
     execution_context::ExecutionContext,
-    knobs::MAX_SYSCALL_BATCH_SIZE,
+    knobs::{
+        MAX_JOBS_CANCEL_BATCH,
+        MAX_SYSCALL_BATCH_SIZE,
+        QUEUE_DEFAULT_MAX_ATTEMPTS,
+        QUEUE_DEFAULT_VISIBILITY_TIMEOUT,
+    },
     query::{
         Cursor,
         CursorPosition,
@@ -40,10 +52,15 @@ use database::{
         query_batch_next,
         TableFilter,
     },
+    sketches::{
+        SketchModel,
+        SketchRange,
+    },
     soft_data_limit,
     BootstrapComponentsModel,
     DeveloperQuery,
     PatchValue,
+    TableModel,
     Transaction,
     UserFacingModel,
 };
@@ -53,7 +70,10 @@ use errors::{
     ErrorMetadataAnyhowExt,
 };
 use itertools::Itertools;
-use keybroker::KeyBroker;
+use keybroker::{
+    KeyBroker,
+    UploadConstraints,
+};
 use model::{
     components::ComponentsModel,
     file_storage::{
@@ -61,7 +81,15 @@ use model::{
         BatchKey,
         FileStorageId,
     },
-    scheduled_jobs::VirtualSchedulerModel,
+    outbox::OutboxModel,
+    queues::{
+        types::QueueConsumer,
+        QueueModel,
+    },
+    scheduled_jobs::{
+        SchedulerModel,
+        VirtualSchedulerModel,
+    },
 };
 use serde::{
     Deserialize,
@@ -71,6 +99,7 @@ use serde_json::{
     json,
     Value as JsonValue,
 };
+use sync_types::UdfPath;
 use value::{
     heap_size::HeapSize,
     id_v6::DeveloperDocumentId,
@@ -83,6 +112,7 @@ use super::DatabaseUdfEnvironment;
 use crate::{
     client::EnvironmentData,
     environment::helpers::{
+        parse_schedule_options,
         parse_version,
         syscall_error::clone_error_for_batch,
         validation::validate_schedule_args,
@@ -278,12 +308,22 @@ pub trait AsyncSyscallProvider<RT: Runtime> {
         scheduled_ts: UnixTimestamp,
     ) -> anyhow::Result<(ComponentFunctionPath, ConvexArray)>;
 
-    fn file_storage_generate_upload_url(&self) -> anyhow::Result<String>;
+    fn file_storage_generate_upload_url(
+        &self,
+        resumable: bool,
+        constraints: &UploadConstraints,
+    ) -> anyhow::Result<String>;
     async fn file_storage_get_url_batch(
         &mut self,
         storage_ids: BTreeMap<BatchKey, FileStorageId>,
     ) -> BTreeMap<BatchKey, anyhow::Result<Option<String>>>;
     async fn file_storage_delete(&mut self, storage_id: FileStorageId) -> anyhow::Result<()>;
+    async fn file_storage_set_metadata(
+        &mut self,
+        storage_id: FileStorageId,
+        tags: Option<BTreeSet<String>>,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<()>;
     async fn file_storage_get_entry(
         &mut self,
         storage_id: FileStorageId,
@@ -377,11 +417,22 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
         .await
     }
 
-    fn file_storage_generate_upload_url(&self) -> anyhow::Result<String> {
+    fn file_storage_generate_upload_url(
+        &self,
+        resumable: bool,
+        constraints: &UploadConstraints,
+    ) -> anyhow::Result<String> {
         let issued_ts = self.phase.unix_timestamp()?;
-        let post_url = self
-            .file_storage
-            .generate_upload_url(&self.key_broker, issued_ts)?;
+        let post_url = if resumable {
+            self.file_storage.generate_resumable_upload_url(
+                &self.key_broker,
+                issued_ts,
+                constraints,
+            )?
+        } else {
+            self.file_storage
+                .generate_upload_url(&self.key_broker, issued_ts, constraints)?
+        };
         Ok(post_url)
     }
 
@@ -419,6 +470,24 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
             .await
     }
 
+    async fn file_storage_set_metadata(
+        &mut self,
+        storage_id: FileStorageId,
+        tags: Option<BTreeSet<String>>,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<()> {
+        let component = self.component()?;
+        self.file_storage
+            .set_file_metadata(
+                self.phase.tx()?,
+                component.into(),
+                storage_id,
+                tags,
+                metadata,
+            )
+            .await
+    }
+
     async fn file_storage_get_entry(
         &mut self,
         storage_id: FileStorageId,
@@ -477,7 +546,7 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
             udf_type,
         )
         .await?;
-        let (path_and_args, returns_validator) = match path_and_args_result {
+        let (path_and_args, returns_validator, errors_validator) = match path_and_args_result {
             Ok(r) => r,
             Err(e) => {
                 // TODO: Propagate this JsError to user space correctly.
@@ -522,6 +591,9 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
         let result = match outcome.result {
             Ok(r) => r.unpack(),
             Err(e) => {
+                let e = errors_validator
+                    .check_error(&e, &table_mapping, &virtual_table_mapping)
+                    .unwrap_or(e);
                 // TODO: How do we want to propagate stack traces between component calls?
                 // TODO: Using `ErrorMetadata::bad_request` here is a hack.
                 // TODO: Also, propagate ConvexError correctly.
@@ -537,6 +609,47 @@ impl<RT: Runtime> AsyncSyscallProvider<RT> for DatabaseUdfEnvironment<RT> {
     }
 }
 
+/// A range of values of the first field of a sketch's index, as passed to
+/// `db.approximateDistinctCount` and `db.approximateQuantile`.
+#[derive(Deserialize)]
+struct SketchRangeJson {
+    eq: Option<JsonValue>,
+    gt: Option<JsonValue>,
+    gte: Option<JsonValue>,
+    lt: Option<JsonValue>,
+    lte: Option<JsonValue>,
+}
+
+fn parse_sketch_range(range: Option<SketchRangeJson>) -> anyhow::Result<SketchRange> {
+    let Some(range) = range else {
+        return Ok(SketchRange::all());
+    };
+    let value = |value: Option<JsonValue>| value.map(ConvexValue::try_from).transpose();
+    if let Some(eq) = value(range.eq)? {
+        anyhow::ensure!(
+            range.gt.is_none() && range.gte.is_none() && range.lt.is_none() && range.lte.is_none(),
+            "`eq` can't be combined with other bounds"
+        );
+        return Ok(SketchRange {
+            lower: Bound::Included(eq.clone()),
+            upper: Bound::Included(eq),
+        });
+    }
+    let lower = match (value(range.gt)?, value(range.gte)?) {
+        (Some(_), Some(_)) => anyhow::bail!("Only one of `gt` and `gte` can be set"),
+        (Some(gt), None) => Bound::Excluded(gt),
+        (None, Some(gte)) => Bound::Included(gte),
+        (None, None) => Bound::Unbounded,
+    };
+    let upper = match (value(range.lt)?, value(range.lte)?) {
+        (Some(_), Some(_)) => anyhow::bail!("Only one of `lt` and `lte` can be set"),
+        (Some(lt), None) => Bound::Excluded(lt),
+        (None, Some(lte)) => Bound::Included(lte),
+        (None, None) => Bound::Unbounded,
+    };
+    Ok(SketchRange { lower, upper })
+}
+
 /// These are syscalls that exist on `db` in `convex/server` for npm versions >=
 /// 0.16.0. They expect DocumentIdv6 strings (as opposed to ID classes).
 ///
@@ -574,6 +687,13 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                     "1.0/replace" => Box::pin(Self::replace(provider, args)).await,
                     "1.0/remove" => Box::pin(Self::remove(provider, args)).await,
                     "1.0/queryPage" => Box::pin(Self::query_page(provider, args)).await,
+                    "1.0/tableStats" => Box::pin(Self::table_stats(provider, args)).await,
+                    "1.0/db/approximateDistinctCount" => {
+                        Box::pin(Self::approximate_distinct_count(provider, args)).await
+                    },
+                    "1.0/db/approximateQuantile" => {
+                        Box::pin(Self::approximate_quantile(provider, args)).await
+                    },
                     // Auth
                     "1.0/getUserIdentity" => {
                         Box::pin(Self::get_user_identity(provider, args)).await
@@ -583,12 +703,28 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                     "1.0/storageGetMetadata" => {
                         Box::pin(Self::storage_get_metadata(provider, args)).await
                     },
+                    "1.0/storageSetMetadata" => {
+                        Box::pin(Self::storage_set_metadata(provider, args)).await
+                    },
                     "1.0/storageGenerateUploadUrl" => {
                         Box::pin(Self::storage_generate_upload_url(provider, args)).await
                     },
                     // Scheduling
                     "1.0/schedule" => Box::pin(Self::schedule(provider, args)).await,
                     "1.0/cancel_job" => Box::pin(Self::cancel_job(provider, args)).await,
+                    "1.0/scheduleBatch" => Box::pin(Self::schedule_batch(provider, args)).await,
+                    "1.0/cancelBatch" => Box::pin(Self::cancel_batch(provider, args)).await,
+                    // Queues
+                    "1.0/queue/enqueue" => Box::pin(Self::queue_enqueue(provider, args)).await,
+                    "1.0/queue/registerConsumer" => {
+                        Box::pin(Self::queue_register_consumer(provider, args)).await
+                    },
+                    "1.0/queue/unregisterConsumer" => {
+                        Box::pin(Self::queue_unregister_consumer(provider, args)).await
+                    },
+                    "1.0/queue/ack" => Box::pin(Self::queue_ack(provider, args)).await,
+                    // Outbox
+                    "1.0/outbox/enqueue" => Box::pin(Self::outbox_enqueue(provider, args)).await,
 
                     // Components
                     "1.0/runUdf" => Box::pin(Self::run_udf(provider, args)).await,
@@ -646,6 +782,129 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(ConvexValue::from(result).into())
     }
 
+    /// Statistics about a table, read from what the database maintains
+    /// instead of scanning the table. Only system UDFs may call this.
+    #[convex_macro::instrument_future]
+    async fn table_stats(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct TableStatsArgs {
+            table: String,
+        }
+        anyhow::ensure!(
+            matches!(
+                provider.table_filter(),
+                TableFilter::IncludePrivateSystemTables
+            ),
+            ErrorMetadata::bad_request(
+                "UnknownAsyncOperation",
+                "Unknown async operation 1.0/tableStats"
+            )
+        );
+        let table: TableName = with_argument_error("tableStats", || {
+            let args: TableStatsArgs = serde_json::from_value(args)?;
+            args.table.parse().context(ArgName("table"))
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let stats = TableModel::new(tx)
+            .statistics(component.into(), &table)
+            .await?;
+        let indexes: Vec<_> = stats
+            .indexes
+            .into_iter()
+            .map(|index| {
+                let backfill = index.backfill_progress.map(|progress| {
+                    let started_at = progress
+                        .started_at
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .unwrap_or_default();
+                    json!({
+                        "numDocsIndexed": progress.num_docs_indexed,
+                        "totalDocs": progress.total_docs,
+                        "startedAt": started_at.as_millis() as f64,
+                        "etaMs": index.backfill_eta.map(|eta| eta.as_millis() as f64),
+                    })
+                });
+                json!({
+                    "name": index.name.to_string(),
+                    "type": index.index_type,
+                    "state": index.state,
+                    "sizeBytes": index.size_bytes,
+                    "backfill": backfill,
+                    "vectors": index.vector_counts.map(|counts| json!({
+                        "live": counts.live,
+                        "deleted": counts.deleted,
+                    })),
+                })
+            })
+            .collect();
+        Ok(json!({
+            "documentCount": stats.document_count,
+            "totalBytes": stats.total_bytes,
+            "writesPerMinute": stats.writes_per_minute,
+            "indexes": indexes,
+        }))
+    }
+
+    /// The approximate number of distinct values of a field, from a sketch the
+    /// table's schema declares.
+    #[convex_macro::instrument_future]
+    async fn approximate_distinct_count(
+        provider: &mut P,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ApproximateDistinctCountArgs {
+            table: String,
+            sketch: String,
+            range: Option<SketchRangeJson>,
+        }
+        let (table, sketch, range) = with_argument_error("db.approximateDistinctCount", || {
+            let args: ApproximateDistinctCountArgs = serde_json::from_value(args)?;
+            let table: TableName = args.table.parse().context(ArgName("table"))?;
+            let range = parse_sketch_range(args.range).context(ArgName("range"))?;
+            Ok((table, args.sketch, range))
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let count = SketchModel::new(tx, component.into())
+            .distinct_count(&table, &sketch, range)
+            .await?;
+        Ok(ConvexValue::from(count as f64).into())
+    }
+
+    /// The approximate value of a field at a quantile, from a sketch the
+    /// table's schema declares.
+    #[convex_macro::instrument_future]
+    async fn approximate_quantile(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ApproximateQuantileArgs {
+            table: String,
+            sketch: String,
+            quantile: f64,
+            range: Option<SketchRangeJson>,
+        }
+        let (table, sketch, quantile, range) =
+            with_argument_error("db.approximateQuantile", || {
+                let args: ApproximateQuantileArgs = serde_json::from_value(args)?;
+                let table: TableName = args.table.parse().context(ArgName("table"))?;
+                let range = parse_sketch_range(args.range).context(ArgName("range"))?;
+                Ok((table, args.sketch, args.quantile, range))
+            })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let value = SketchModel::new(tx, component.into())
+            .quantile(&table, &sketch, quantile, range)
+            .await?;
+        Ok(match value {
+            Some(value) => ConvexValue::from(value).into(),
+            None => JsonValue::Null,
+        })
+    }
+
     #[convex_macro::instrument_future]
     async fn get_user_identity(provider: &mut P, _args: JsonValue) -> anyhow::Result<JsonValue> {
         // TODO: Somehow make the Transaction aware of the dependency on the user.
@@ -661,9 +920,32 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
     #[convex_macro::instrument_future]
     async fn storage_generate_upload_url(
         provider: &mut P,
-        _args: JsonValue,
+        args: JsonValue,
     ) -> anyhow::Result<JsonValue> {
-        let post_url = provider.file_storage_generate_upload_url()?;
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GenerateUploadUrlArgs {
+            #[serde(default)]
+            resumable: bool,
+            max_bytes: Option<u64>,
+            #[serde(default)]
+            allowed_content_types: Vec<String>,
+            expires_in_ms: Option<u64>,
+        }
+        let GenerateUploadUrlArgs {
+            resumable,
+            max_bytes,
+            allowed_content_types,
+            expires_in_ms,
+        } = with_argument_error("storage.generateUploadUrl", || {
+            Ok(serde_json::from_value(args)?)
+        })?;
+        let constraints = UploadConstraints {
+            max_bytes,
+            allowed_content_types,
+            expires_in: expires_in_ms.map(Duration::from_millis),
+        };
+        let post_url = provider.file_storage_generate_upload_url(resumable, &constraints)?;
         Ok(serde_json::to_value(post_url)?)
     }
 
@@ -722,6 +1004,36 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(JsonValue::Null)
     }
 
+    #[convex_macro::instrument_future]
+    async fn storage_set_metadata(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct StorageSetMetadataArgs {
+            storage_id: String,
+            tags: Option<BTreeSet<String>>,
+            metadata: Option<BTreeMap<String, String>>,
+        }
+        let (storage_id, tags, metadata): (FileStorageId, _, _) =
+            with_argument_error("storage.setMetadata", || {
+                let StorageSetMetadataArgs {
+                    storage_id,
+                    tags,
+                    metadata,
+                } = serde_json::from_value(args)?;
+                Ok((
+                    storage_id.parse().context(ArgName("storageId"))?,
+                    tags,
+                    metadata,
+                ))
+            })?;
+
+        provider
+            .file_storage_set_metadata(storage_id, tags, metadata)
+            .await?;
+
+        Ok(JsonValue::Null)
+    }
+
     #[convex_macro::instrument_future]
     async fn storage_get_metadata(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
         #[derive(Deserialize)]
@@ -741,6 +1053,8 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             sha256: String,
             size: i64,
             content_type: Option<String>,
+            tags: BTreeSet<String>,
+            metadata: BTreeMap<String, String>,
         }
         let file_metadata = provider.file_storage_get_entry(storage_id).await?.map(
             |FileStorageEntry {
@@ -749,6 +1063,10 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                  sha256,
                  size,
                  content_type,
+                 tags,
+                 metadata,
+                 storage_class: _,
+                 scan_status: _,
              }| {
                 FileMetadataJson {
                     storage_id: storage_id.to_string(),
@@ -756,6 +1074,8 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
                     sha256: sha256.as_hex(),
                     size,
                     content_type,
+                    tags,
+                    metadata,
                 }
             },
         );
@@ -770,11 +1090,21 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             name: String,
             ts: f64,
             args: UdfArgsJson,
+            priority: Option<String>,
+            max_concurrency: Option<u32>,
         }
 
-        let ScheduleArgs { name, ts, args }: ScheduleArgs =
-            with_argument_error("scheduler", || Ok(serde_json::from_value(args)?))?;
+        let ScheduleArgs {
+            name,
+            ts,
+            args,
+            priority,
+            max_concurrency,
+        }: ScheduleArgs = with_argument_error("scheduler", || Ok(serde_json::from_value(args)?))?;
         let udf_path = with_argument_error("scheduler", || name.parse().context(ArgName("name")))?;
+        let options = with_argument_error("scheduler", || {
+            parse_schedule_options(priority, max_concurrency)
+        })?;
 
         // TODO(lee) allow scheduling functions in other components.
         let component_id = provider.component()?;
@@ -797,7 +1127,7 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
             .component_path_to_ids(path.component)
             .await?;
         let virtual_id = VirtualSchedulerModel::new(tx, component_id.into())
-            .schedule(path.udf_path, udf_args, scheduled_ts, context)
+            .schedule(path.udf_path, udf_args, scheduled_ts, options, context)
             .await?;
 
         Ok(JsonValue::from(virtual_id))
@@ -826,6 +1156,216 @@ impl<RT: Runtime, P: AsyncSyscallProvider<RT>> DatabaseSyscallsV1<RT, P> {
         Ok(JsonValue::Null)
     }
 
+    #[convex_macro::instrument_future]
+    async fn schedule_batch(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ScheduleBatchJob {
+            name: String,
+            ts: f64,
+            args: UdfArgsJson,
+            priority: Option<String>,
+            max_concurrency: Option<u32>,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ScheduleBatchArgs {
+            jobs: Vec<ScheduleBatchJob>,
+        }
+
+        let ScheduleBatchArgs { jobs }: ScheduleBatchArgs =
+            with_argument_error("scheduler.runBatch", || Ok(serde_json::from_value(args)?))?;
+
+        let component_id = provider.component()?;
+        let component_path = BootstrapComponentsModel::new(provider.tx()?)
+            .get_component_path(component_id)
+            .await?;
+        let mut batch = Vec::with_capacity(jobs.len());
+        for ScheduleBatchJob {
+            name,
+            ts,
+            args,
+            priority,
+            max_concurrency,
+        } in jobs
+        {
+            let udf_path = with_argument_error("scheduler.runBatch", || {
+                name.parse().context(ArgName("name"))
+            })?;
+            let options = with_argument_error("scheduler.runBatch", || {
+                parse_schedule_options(priority, max_concurrency)
+            })?;
+            let path = ComponentFunctionPath {
+                component: component_path.clone(),
+                udf_path,
+            };
+            let scheduled_ts = UnixTimestamp::from_secs_f64(ts);
+            let (path, udf_args) = provider
+                .validate_schedule_args(path, args.into_arg_vec(), scheduled_ts)
+                .await?;
+            batch.push((path.udf_path, udf_args, scheduled_ts, options));
+        }
+
+        let context = provider.context().clone();
+        let tx = provider.tx()?;
+        let batch_id = SchedulerModel::new(tx, component_id.into())
+            .schedule_batch(batch, context)
+            .await?;
+
+        Ok(json!({ "batchId": batch_id }))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn cancel_batch(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CancelBatchArgs {
+            batch_id: String,
+        }
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+
+        let CancelBatchArgs { batch_id } = with_argument_error("scheduler.cancelBatch", || {
+            Ok(serde_json::from_value(args)?)
+        })?;
+
+        let (num_canceled, has_more) = SchedulerModel::new(tx, component.into())
+            .cancel_batch(&batch_id, *MAX_JOBS_CANCEL_BATCH)
+            .await?;
+
+        Ok(json!({ "numCanceled": num_canceled, "hasMore": has_more }))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_enqueue(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct EnqueueArgs {
+            queue: String,
+            payload: JsonValue,
+            delay_ms: Option<f64>,
+        }
+        let (queue, payload, delay) = with_argument_error("queue.enqueue", || {
+            let args: EnqueueArgs = serde_json::from_value(args)?;
+            let payload = ConvexValue::try_from(args.payload).context(ArgName("payload"))?;
+            let delay = match args.delay_ms {
+                Some(delay_ms) if !delay_ms.is_finite() || delay_ms < 0.0 => {
+                    anyhow::bail!("delayMs must be a non-negative number")
+                },
+                Some(delay_ms) => Duration::from_secs_f64(delay_ms / 1000.0),
+                None => Duration::ZERO,
+            };
+            Ok((args.queue, payload, delay))
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let num_groups = QueueModel::new(tx, component.into())
+            .enqueue(&queue, payload, delay)
+            .await?;
+        Ok(json!({ "numGroups": num_groups }))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_register_consumer(
+        provider: &mut P,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct RegisterConsumerArgs {
+            queue: String,
+            group: String,
+            handler: String,
+            visibility_timeout_ms: Option<u64>,
+            max_attempts: Option<u32>,
+            max_concurrency: Option<u32>,
+        }
+        let consumer = with_argument_error("queue.registerConsumer", || {
+            let args: RegisterConsumerArgs = serde_json::from_value(args)?;
+            let handler: UdfPath = args.handler.parse().context(ArgName("handler"))?;
+            Ok(QueueConsumer {
+                queue: args.queue,
+                group: args.group,
+                handler: handler.canonicalize(),
+                visibility_timeout: args
+                    .visibility_timeout_ms
+                    .map(Duration::from_millis)
+                    .unwrap_or(*QUEUE_DEFAULT_VISIBILITY_TIMEOUT),
+                max_attempts: args.max_attempts.unwrap_or(*QUEUE_DEFAULT_MAX_ATTEMPTS),
+                max_concurrency: args.max_concurrency,
+            })
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        QueueModel::new(tx, component.into())
+            .register_consumer(consumer)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_unregister_consumer(
+        provider: &mut P,
+        args: JsonValue,
+    ) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct UnregisterConsumerArgs {
+            queue: String,
+            group: String,
+        }
+        let UnregisterConsumerArgs { queue, group } =
+            with_argument_error("queue.unregisterConsumer", || {
+                Ok(serde_json::from_value(args)?)
+            })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        let num_deleted = QueueModel::new(tx, component.into())
+            .unregister_consumer(&queue, &group)
+            .await?;
+        Ok(json!({ "numDeleted": num_deleted }))
+    }
+
+    #[convex_macro::instrument_future]
+    async fn queue_ack(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AckArgs {
+            message_id: String,
+            receipt: String,
+        }
+        let (message_id, receipt) = with_argument_error("queue.ack", || {
+            let args: AckArgs = serde_json::from_value(args)?;
+            let message_id =
+                DeveloperDocumentId::decode(&args.message_id).context(ArgName("messageId"))?;
+            Ok((message_id, args.receipt))
+        })?;
+        let component = provider.component()?;
+        let tx = provider.tx()?;
+        QueueModel::new(tx, component.into())
+            .ack(message_id, &receipt)
+            .await?;
+        Ok(JsonValue::Null)
+    }
+
+    #[convex_macro::instrument_future]
+    async fn outbox_enqueue(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct EnqueueArgs {
+            target: String,
+            payload: JsonValue,
+        }
+        let (target, payload) = with_argument_error("outbox.enqueue", || {
+            let args: EnqueueArgs = serde_json::from_value(args)?;
+            let payload = ConvexValue::try_from(args.payload).context(ArgName("payload"))?;
+            Ok((args.target, payload))
+        })?;
+        let tx = provider.tx()?;
+        let record_id = OutboxModel::new(tx).enqueue(&target, payload).await?;
+        Ok(json!({ "recordId": DeveloperDocumentId::from(record_id).encode() }))
+    }
+
     #[minitrace::trace]
     #[convex_macro::instrument_future]
     async fn insert(provider: &mut P, args: JsonValue) -> anyhow::Result<JsonValue> {
@@ -335,12 +335,12 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
         udf_callback: Box<dyn UdfCallback<RT>>,
     ) -> Self {
         let persistence_version = transaction.persistence_version();
+        let source_package_id = path_and_args.source_package_id();
         let (path, arguments, udf_server_version) = path_and_args.consume();
-        let component_path = path.component.clone();
         Self {
             rt: rt.clone(),
             udf_type,
-            path,
+            path: path.clone(),
             arguments,
             identity,
             udf_server_version,
@@ -350,7 +350,8 @@ impl<RT: Runtime> DatabaseUdfEnvironment<RT> {
                 rt,
                 module_loader.clone(),
                 system_env_vars,
-                component_path,
+                path,
+                source_package_id,
             ),
             file_storage,
 
@@ -12,9 +12,9 @@ use std::{
 use anyhow::Context;
 use common::{
     components::{
+        CanonicalizedComponentFunctionPath,
         CanonicalizedComponentModulePath,
         ComponentId,
-        ComponentPath,
     },
     runtime::{
         Runtime,
@@ -39,10 +39,12 @@ use model::{
         EnvironmentVariablesModel,
         PreloadedEnvironmentVariables,
     },
+    function_read_budgets::FunctionReadBudgetsModel,
     modules::{
         module_versions::FullModuleSource,
         ModuleModel,
     },
+    source_packages::types::SourcePackageId,
     udf_config::UdfConfigModel,
 };
 use rand::SeedableRng;
@@ -79,7 +81,9 @@ pub struct UdfPhase<RT: Runtime> {
     module_loader: Arc<dyn ModuleLoader<RT>>,
     system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
     preloaded: UdfPreloaded,
-    component_path: ComponentPath,
+    path: CanonicalizedComponentFunctionPath,
+    // Set when running a canary, whose modules are in this source package.
+    source_package_id: Option<SourcePackageId>,
 }
 
 enum UdfPreloaded {
@@ -100,7 +104,8 @@ impl<RT: Runtime> UdfPhase<RT> {
         rt: RT,
         module_loader: Arc<dyn ModuleLoader<RT>>,
         system_env_vars: BTreeMap<EnvVarName, EnvVarValue>,
-        component_path: ComponentPath,
+        path: CanonicalizedComponentFunctionPath,
+        source_package_id: Option<SourcePackageId>,
     ) -> Self {
         Self {
             phase: Phase::Importing,
@@ -109,7 +114,8 @@ impl<RT: Runtime> UdfPhase<RT> {
             module_loader,
             system_env_vars,
             preloaded: UdfPreloaded::Created,
-            component_path,
+            path,
+            source_package_id,
         }
     }
 
@@ -123,7 +129,7 @@ impl<RT: Runtime> UdfPhase<RT> {
             anyhow::bail!("UdfPhase initialized twice");
         };
 
-        let component_path = self.component_path.clone();
+        let component_path = self.path.component.clone();
         let (_, component) = with_release_permit(
             timeout,
             permit_slot,
@@ -150,6 +156,19 @@ impl<RT: Runtime> UdfPhase<RT> {
         )
         .await?;
 
+        // Functions called from other components share their caller's
+        // transaction, and stay within the caller's budget.
+        if self.tx_ref()?.read_budget().is_unlimited() {
+            let path = self.path.clone();
+            let read_budget = with_release_permit(
+                timeout,
+                permit_slot,
+                FunctionReadBudgetsModel::new(self.tx_mut()?).read_budget_for(&path),
+            )
+            .await?;
+            self.tx_mut()?.set_read_budget(read_budget);
+        }
+
         self.preloaded = UdfPreloaded::Ready {
             rng,
             observed_rng_during_execution: false,
@@ -195,11 +214,18 @@ impl<RT: Runtime> UdfPhase<RT> {
         .await?;
 
         let module_loader = self.module_loader.clone();
-        let module_version = with_release_permit(
-            timeout,
-            permit_slot,
-            module_loader.get_module(self.tx_mut()?, path),
-        )
+        let source_package_id = self.source_package_id;
+        let tx = self.tx_mut()?;
+        let module_version = with_release_permit(timeout, permit_slot, async {
+            match source_package_id {
+                Some(source_package_id) => {
+                    module_loader
+                        .get_module_from_package(tx, path, source_package_id)
+                        .await
+                },
+                None => module_loader.get_module(tx, path).await,
+            }
+        })
         .await?;
 
         if let Some(module) = module.as_ref() {
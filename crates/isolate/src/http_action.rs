@@ -1,20 +1,44 @@
 use core::fmt;
+use std::time::Duration;
 
 use bytes::Bytes;
-use common::types::{
-    HttpActionRoute,
-    RoutableMethod,
+use common::{
+    errors::JsError,
+    runtime::{
+        Runtime,
+        RuntimeInstant,
+    },
+    types::{
+        HttpActionRoute,
+        RoutableMethod,
+    },
 };
 use futures::{
     channel::mpsc,
-    stream::BoxStream,
+    future::{
+        self,
+        FusedFuture,
+    },
+    select_biased,
+    stream::{
+        self,
+        BoxStream,
+    },
+    FutureExt,
+    Stream,
+    StreamExt,
 };
 use headers::{
     HeaderMap,
+    HeaderName,
     HeaderValue,
 };
 use http::{
-    header::CONTENT_TYPE,
+    header::{
+        CACHE_CONTROL,
+        CONTENT_LENGTH,
+        CONTENT_TYPE,
+    },
     Method,
     StatusCode,
 };
@@ -186,6 +210,82 @@ pub struct HttpActionResponseHead {
     pub headers: HeaderMap,
 }
 
+impl HttpActionResponseHead {
+    pub fn is_event_stream(&self) -> bool {
+        self.headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<mime::Mime>().ok())
+            .is_some_and(|mime| mime.essence_str() == mime::TEXT_EVENT_STREAM.essence_str())
+    }
+
+    /// Server-sent events need to reach the client as soon as they're sent, so
+    /// ask caches and reverse proxies not to buffer or store the response.
+    pub fn prepare_event_stream(&mut self) {
+        self.headers.remove(CONTENT_LENGTH);
+        self.headers
+            .entry(CACHE_CONTROL)
+            .or_insert(HeaderValue::from_static("no-cache"));
+        self.headers.insert(
+            HeaderName::from_static("x-accel-buffering"),
+            HeaderValue::from_static("no"),
+        );
+    }
+}
+
+/// A comment line, which clients ignore, sent on quiet server-sent event
+/// streams.
+const SSE_HEARTBEAT: &[u8] = b":\n\n";
+
+/// Applies the streaming policy to an HTTP action's response body: the body
+/// ends with an error once the action hasn't sent any data for `idle_timeout`,
+/// and if `heartbeat_interval` is set a heartbeat is sent whenever the body
+/// has been quiet for that long. Heartbeats don't count as activity for the
+/// idle timeout.
+pub fn with_idle_policy<RT: Runtime>(
+    rt: RT,
+    body: impl Stream<Item = anyhow::Result<Bytes>> + Send + 'static,
+    idle_timeout: Duration,
+    heartbeat_interval: Option<Duration>,
+) -> impl Stream<Item = anyhow::Result<Result<Bytes, JsError>>> + Send + 'static {
+    let last_data = rt.monotonic_now();
+    stream::unfold(
+        (body.boxed(), last_data, false),
+        move |(mut body, last_data, done)| {
+            let rt = rt.clone();
+            async move {
+                if done {
+                    return None;
+                }
+                let mut idle = rt.wait(idle_timeout.saturating_sub(last_data.elapsed()));
+                let mut heartbeat: std::pin::Pin<Box<dyn FusedFuture<Output = ()> + Send>> =
+                    match heartbeat_interval {
+                        Some(interval) => rt.wait(interval),
+                        None => Box::pin(future::pending().fuse()),
+                    };
+                select_biased! {
+                    chunk = body.next().fuse() => match chunk {
+                        Some(Ok(chunk)) => Some((Ok(Ok(chunk)), (body, rt.monotonic_now(), false))),
+                        Some(Err(e)) => Some((Err(e), (body, last_data, true))),
+                        None => None,
+                    },
+                    _ = idle => {
+                        let e = JsError::from_message(format!(
+                            "HttpResponseIdleTimeout: The HTTP action didn't send any data on \
+                             its response for {idle_timeout:?}, so the response was ended"
+                        ));
+                        Some((Ok(Err(e)), (body, last_data, true)))
+                    },
+                    _ = heartbeat => {
+                        let heartbeat = Bytes::from_static(SSE_HEARTBEAT);
+                        Some((Ok(Ok(heartbeat)), (body, last_data, false)))
+                    },
+                }
+            }
+        },
+    )
+}
+
 #[derive(Debug, Clone)]
 pub struct HttpActionResponseStreamer {
     head: Option<HttpActionResponseHead>,
@@ -305,6 +305,7 @@ impl<RT: Runtime> Isolate<RT> {
             stream_listeners: WithHeapSize::default(),
             console_timers: WithHeapSize::default(),
             text_decoders: BTreeMap::new(),
+            multi_part_readers: BTreeMap::new(),
         };
         Ok((self.handle.clone(), state))
     }
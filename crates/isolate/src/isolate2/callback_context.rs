@@ -460,6 +460,7 @@ mod op_provider {
         isolate2::client::PendingAsyncOp,
         ops::OpProvider,
         request_scope::{
+            MultiPartReaderResource,
             StreamListener,
             TextDecoderResource,
         },
@@ -587,5 +588,26 @@ mod op_provider {
         fn remove_text_decoder(&mut self, uuid: &Uuid) -> anyhow::Result<TextDecoderResource> {
             self.context_state()?.remove_text_decoder(uuid)
         }
+
+        fn create_multi_part_reader(
+            &mut self,
+            reader: MultiPartReaderResource,
+        ) -> anyhow::Result<Uuid> {
+            self.context_state()?.create_multi_part_reader(reader)
+        }
+
+        fn get_multi_part_reader(
+            &mut self,
+            uuid: &Uuid,
+        ) -> anyhow::Result<&mut MultiPartReaderResource> {
+            self.context_state()?.get_multi_part_reader(uuid)
+        }
+
+        fn remove_multi_part_reader(
+            &mut self,
+            uuid: &Uuid,
+        ) -> anyhow::Result<MultiPartReaderResource> {
+            self.context_state()?.remove_multi_part_reader(uuid)
+        }
     }
 }
@@ -32,6 +32,7 @@ use super::{
 use crate::{
     ops::CryptoOps,
     request_scope::{
+        MultiPartReaderResource,
         ReadableStream,
         StreamListener,
         TextDecoderResource,
@@ -59,6 +60,7 @@ pub struct ContextState {
     // This is not wrapped in `WithHeapSize` so we can return `&mut TextDecoderStream`.
     // Additionally, `TextDecoderResource` should have a fairly small heap size.
     pub text_decoders: BTreeMap<uuid::Uuid, TextDecoderResource>,
+    pub multi_part_readers: BTreeMap<uuid::Uuid, MultiPartReaderResource>,
 
     pub environment: Box<dyn Environment>,
 
@@ -86,6 +88,7 @@ impl ContextState {
             console_timers: BTreeMap::new().into(),
 
             text_decoders: BTreeMap::new(),
+            multi_part_readers: BTreeMap::new(),
 
             environment,
 
@@ -144,7 +147,7 @@ impl ContextState {
             None => None,
         };
         self.streams.mutate(&id, |stream| -> anyhow::Result<()> {
-            let Some(Ok(ReadableStream { parts, done })) = stream else {
+            let Some(Ok(ReadableStream { parts, done, .. })) = stream else {
                 anyhow::bail!("unrecognized stream id {id}");
             };
             if *done {
@@ -199,6 +202,33 @@ impl ContextState {
             .ok_or_else(|| anyhow::anyhow!("Text decoder resource not found"))?;
         Ok(decoder)
     }
+
+    pub fn create_multi_part_reader(
+        &mut self,
+        reader: MultiPartReaderResource,
+    ) -> anyhow::Result<Uuid> {
+        let id = CryptoOps::random_uuid(self.environment.rng()?)?;
+        self.multi_part_readers.insert(id, reader);
+        Ok(id)
+    }
+
+    pub fn get_multi_part_reader(
+        &mut self,
+        reader_id: &uuid::Uuid,
+    ) -> anyhow::Result<&mut MultiPartReaderResource> {
+        self.multi_part_readers
+            .get_mut(reader_id)
+            .ok_or_else(|| anyhow::anyhow!("Multipart reader resource not found"))
+    }
+
+    pub fn remove_multi_part_reader(
+        &mut self,
+        reader_id: &uuid::Uuid,
+    ) -> anyhow::Result<MultiPartReaderResource> {
+        self.multi_part_readers
+            .remove(reader_id)
+            .ok_or_else(|| anyhow::anyhow!("Multipart reader resource not found"))
+    }
 }
 
 pub enum ContextFailure {
@@ -1,6 +1,9 @@
 use std::{
     cmp::Ordering,
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     sync::Arc,
     time::Duration,
 };
@@ -46,7 +49,10 @@ use futures::{
     FutureExt,
     StreamExt,
 };
-use keybroker::KeyBroker;
+use keybroker::{
+    KeyBroker,
+    UploadConstraints,
+};
 use model::{
     config::module_loader::ModuleLoader,
     environment_variables::{
@@ -897,7 +903,11 @@ impl<'a, RT: Runtime> AsyncSyscallProvider<RT> for Isolate2SyscallProvider<'a, R
         validate_schedule_args(path, args, scheduled_ts, self.unix_timestamp, self.tx).await
     }
 
-    fn file_storage_generate_upload_url(&self) -> anyhow::Result<String> {
+    fn file_storage_generate_upload_url(
+        &self,
+        _resumable: bool,
+        _constraints: &UploadConstraints,
+    ) -> anyhow::Result<String> {
         todo!()
     }
 
@@ -912,6 +922,15 @@ impl<'a, RT: Runtime> AsyncSyscallProvider<RT> for Isolate2SyscallProvider<'a, R
         todo!()
     }
 
+    async fn file_storage_set_metadata(
+        &mut self,
+        _storage_id: FileStorageId,
+        _tags: Option<BTreeSet<String>>,
+        _metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<()> {
+        todo!()
+    }
+
     async fn file_storage_get_entry(
         &mut self,
         _storage_id: FileStorageId,
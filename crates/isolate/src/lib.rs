@@ -29,6 +29,7 @@ pub mod metrics;
 pub mod module_map;
 mod ops;
 mod request_scope;
+pub mod secrets;
 pub mod strings;
 mod termination;
 #[cfg(test)]
@@ -542,6 +542,22 @@ pub fn finish_udf_fetch_timer(t: StatusTimer, success: Result<usize, ()>) {
     log_counter_with_labels(&UDF_FETCH_TOTAL, 1, vec![status_label]);
 }
 
+register_convex_counter!(
+    SECRET_FETCHES_TOTAL,
+    "Count of fetches of secrets referred to by environment variables",
+    &["secret_manager", "status"]
+);
+pub fn log_secret_fetch(secret_manager: &'static str, is_ok: bool) {
+    log_counter_with_labels(
+        &SECRET_FETCHES_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("secret_manager", secret_manager),
+            StaticMetricLabel::status(is_ok),
+        ],
+    );
+}
+
 // Analyze counters
 register_convex_counter!(
     SOURCE_MAP_MISSING_TOTAL,
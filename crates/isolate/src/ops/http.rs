@@ -6,14 +6,19 @@ use deno_core::{
     v8::{
         self,
     },
+    ToJsBuffer,
 };
 use errors::ErrorMetadata;
-use futures::channel::mpsc;
+use futures::{
+    channel::mpsc,
+    FutureExt,
+};
 use headers::HeaderName;
 use serde::{
     Deserialize,
     Serialize,
 };
+use serde_bytes::ByteBuf;
 use serde_json::{
     json,
     Value as JsonValue,
@@ -31,7 +36,10 @@ use crate::{
         AsyncOpRequest,
     },
     http::HttpRequestV8,
-    request_scope::StreamListener,
+    request_scope::{
+        MultiPartReaderResource,
+        StreamListener,
+    },
 };
 
 pub fn async_op_fetch<'b, P: OpProvider<'b>>(
@@ -74,6 +82,109 @@ pub fn async_op_parse_multi_part<'b, P: OpProvider<'b>>(
     )
 }
 
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MultiPartEvent {
+    #[serde(rename_all = "camelCase")]
+    Field {
+        name: String,
+        file_name: Option<String>,
+        content_type: Option<String>,
+    },
+    Chunk {
+        value: ToJsBuffer,
+    },
+    Done,
+}
+
+#[convex_macro::v8_op]
+pub fn op_form_new_multi_part_reader<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    content_type: String,
+) -> anyhow::Result<uuid::Uuid> {
+    let boundary = multer::parse_boundary(content_type)
+        .map_err(|e| ErrorMetadata::bad_request("InvalidMultiPartForm", e.to_string()))?;
+    provider.create_multi_part_reader(MultiPartReaderResource::new(boundary))
+}
+
+/// Feeds the next chunk of the body to a multipart reader, or signals the end
+/// of the body if `bytes` is `None`, and returns the events that the reader
+/// can parse so far. The returned events end with `Done` once the whole form
+/// has been read.
+#[convex_macro::v8_op]
+pub fn op_form_feed_multi_part_reader<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    reader_id: uuid::Uuid,
+    bytes: Option<ByteBuf>,
+) -> anyhow::Result<Vec<MultiPartEvent>> {
+    let reader = provider.get_multi_part_reader(&reader_id)?;
+    match bytes {
+        Some(bytes) => {
+            let _ = reader
+                .body_sender
+                .unbounded_send(Ok(bytes.into_vec().into()));
+        },
+        None => reader.body_sender.close_channel(),
+    }
+    let mut events = vec![];
+    while let Some(event) = poll_multi_part_event(reader)
+        .map_err(|e| ErrorMetadata::bad_request("InvalidMultiPartForm", e.to_string()))?
+    {
+        let done = matches!(event, MultiPartEvent::Done);
+        events.push(event);
+        if done {
+            break;
+        }
+    }
+    Ok(events)
+}
+
+#[convex_macro::v8_op]
+pub fn op_form_cleanup_multi_part_reader<'b, P: OpProvider<'b>>(
+    provider: &mut P,
+    reader_id: uuid::Uuid,
+) -> anyhow::Result<()> {
+    provider.remove_multi_part_reader(&reader_id)?;
+    Ok(())
+}
+
+/// Returns the next event the reader can parse from the body fed to it so
+/// far, or `None` if it needs more of the body. The body is only ever fed
+/// through the reader's channel, so a parse that can't complete immediately
+/// is waiting for the next chunk.
+fn poll_multi_part_event(
+    reader: &mut MultiPartReaderResource,
+) -> anyhow::Result<Option<MultiPartEvent>> {
+    if let Some(field) = &mut reader.field {
+        match field.chunk().now_or_never().transpose()? {
+            None => return Ok(None),
+            Some(Some(chunk)) => {
+                return Ok(Some(MultiPartEvent::Chunk {
+                    value: chunk.to_vec().into(),
+                }))
+            },
+            Some(None) => reader.field = None,
+        }
+    }
+    let Some(field) = reader.multipart.next_field().now_or_never().transpose()? else {
+        return Ok(None);
+    };
+    let Some(field) = field else {
+        return Ok(Some(MultiPartEvent::Done));
+    };
+    let name = field
+        .name()
+        .context("multi-part form entry missing 'name'")?
+        .to_string();
+    let event = MultiPartEvent::Field {
+        name,
+        file_name: field.file_name().map(|f| f.to_string()),
+        content_type: field.content_type().map(|c| c.to_string()),
+    };
+    reader.field = Some(field);
+    Ok(Some(event))
+}
+
 #[convex_macro::v8_op]
 pub fn op_url_get_url_info<'b, P: OpProvider<'b>>(
     provider: &mut P,
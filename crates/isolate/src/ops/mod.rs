@@ -95,6 +95,9 @@ use self::{
     http::{
         async_op_fetch,
         async_op_parse_multi_part,
+        op_form_cleanup_multi_part_reader,
+        op_form_feed_multi_part_reader,
+        op_form_new_multi_part_reader,
         op_headers_get_mime_type,
         op_headers_normalize_name,
         op_url_get_url_info,
@@ -141,6 +144,7 @@ use crate::{
     helpers::to_rust_string,
     metrics,
     request_scope::{
+        MultiPartReaderResource,
         ReadableStream,
         StreamListener,
         TextDecoderResource,
@@ -187,6 +191,14 @@ pub trait OpProvider<'b> {
     fn get_text_decoder(&mut self, uuid: &Uuid) -> anyhow::Result<&mut TextDecoderResource>;
     fn remove_text_decoder(&mut self, uuid: &Uuid) -> anyhow::Result<TextDecoderResource>;
 
+    fn create_multi_part_reader(&mut self, reader: MultiPartReaderResource)
+        -> anyhow::Result<Uuid>;
+    fn get_multi_part_reader(
+        &mut self,
+        uuid: &Uuid,
+    ) -> anyhow::Result<&mut MultiPartReaderResource>;
+    fn remove_multi_part_reader(&mut self, uuid: &Uuid) -> anyhow::Result<MultiPartReaderResource>;
+
     fn get_environment_variable(&mut self, name: EnvVarName)
         -> anyhow::Result<Option<EnvVarValue>>;
 
@@ -273,7 +285,7 @@ impl<'a, 'b: 'a, RT: Runtime, E: IsolateEnvironment<RT>> OpProvider<'b>
             None => None,
         };
         state.streams.mutate(&id, |stream| -> anyhow::Result<()> {
-            let Some(Ok(ReadableStream { parts, done })) = stream else {
+            let Some(Ok(ReadableStream { parts, done, .. })) = stream else {
                 anyhow::bail!("unrecognized stream id {id}");
             };
             if *done {
@@ -319,6 +331,24 @@ impl<'a, 'b: 'a, RT: Runtime, E: IsolateEnvironment<RT>> OpProvider<'b>
         self.state_mut()?.remove_text_decoder(uuid)
     }
 
+    fn create_multi_part_reader(
+        &mut self,
+        reader: MultiPartReaderResource,
+    ) -> anyhow::Result<Uuid> {
+        self.state_mut()?.create_multi_part_reader(reader)
+    }
+
+    fn get_multi_part_reader(
+        &mut self,
+        uuid: &Uuid,
+    ) -> anyhow::Result<&mut MultiPartReaderResource> {
+        self.state_mut()?.get_multi_part_reader(uuid)
+    }
+
+    fn remove_multi_part_reader(&mut self, uuid: &Uuid) -> anyhow::Result<MultiPartReaderResource> {
+        self.state_mut()?.remove_multi_part_reader(uuid)
+    }
+
     fn get_environment_variable(
         &mut self,
         name: EnvVarName,
@@ -374,6 +404,9 @@ pub fn run_op<'b, P: OpProvider<'b>>(
         "blob/readPart" => op_blob_read_part(provider, args, rv)?,
         "headers/getMimeType" => op_headers_get_mime_type(provider, args, rv)?,
         "headers/normalizeName" => op_headers_normalize_name(provider, args, rv)?,
+        "form/newMultiPartReader" => op_form_new_multi_part_reader(provider, args, rv)?,
+        "form/feedMultiPartReader" => op_form_feed_multi_part_reader(provider, args, rv)?,
+        "form/cleanupMultiPartReader" => op_form_cleanup_multi_part_reader(provider, args, rv)?,
         "stream/create" => op_stream_create(provider, args, rv)?,
         "stream/extend" => op_stream_extend(provider, args, rv)?,
         "textEncoder/encode" => op_text_encoder_encode(provider, args, rv)?,
@@ -20,7 +20,10 @@ use crate::{
         IsolateEnvironment,
     },
     execution_scope::ExecutionScope,
-    request_scope::StreamListener,
+    request_scope::{
+        StreamFlowControl,
+        StreamListener,
+    },
 };
 
 pub fn async_op_stream_read_part<'b, P: OpProvider<'b>>(
@@ -63,17 +66,24 @@ impl<'a, 'b: 'a, RT: Runtime, E: IsolateEnvironment<RT>> ExecutionScope<'a, 'b,
             done: bool,
             value: Option<ToJsBuffer>,
         }
+        // The next chunk of a stream, whether the stream is done, and its flow
+        // control.
+        type StreamPart = (Option<Uuid>, bool, Option<StreamFlowControl>);
         loop {
             let state = self.state_mut()?;
             let mut ready = BTreeMap::new();
             for stream_id in state.stream_listeners.keys() {
                 let chunk = state.streams.mutate(
                     stream_id,
-                    |stream| -> anyhow::Result<Result<(Option<Uuid>, bool), ()>> {
+                    |stream| -> anyhow::Result<Result<StreamPart, ()>> {
                         let stream = stream
                             .ok_or_else(|| anyhow::anyhow!("listening on nonexistent stream"))?;
                         let result = match stream {
-                            Ok(stream) => Ok((stream.parts.pop_front(), stream.done)),
+                            Ok(stream) => Ok((
+                                stream.parts.pop_front(),
+                                stream.done,
+                                stream.flow_control.clone(),
+                            )),
                             Err(_) => Err(()),
                         };
                         Ok(result)
@@ -86,12 +96,17 @@ impl<'a, 'b: 'a, RT: Runtime, E: IsolateEnvironment<RT>> ExecutionScope<'a, 'b,
                             Err(state.streams.remove(stream_id).unwrap().unwrap_err()),
                         );
                     },
-                    Ok((chunk, stream_done)) => {
+                    Ok((chunk, stream_done, flow_control)) => {
                         if let Some(chunk) = chunk {
                             let ready_chunk = state
                                 .blob_parts
                                 .remove(&chunk)
                                 .ok_or_else(|| anyhow::anyhow!("stream chunk missing"))?;
+                            // Let the producer add more to the stream now that
+                            // this chunk has been read.
+                            if let Some(flow_control) = flow_control {
+                                flow_control.release(ready_chunk.len());
+                            }
                             ready.insert(*stream_id, Ok(Some(ready_chunk)));
                         } else if stream_done {
                             ready.insert(*stream_id, Ok(None));
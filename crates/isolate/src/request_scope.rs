@@ -3,7 +3,9 @@ use std::{
         BTreeMap,
         VecDeque,
     },
+    convert::Infallible,
     marker::PhantomData,
+    sync::Arc,
 };
 
 use anyhow::anyhow;
@@ -25,6 +27,7 @@ use errors::{
     ErrorMetadataAnyhowExt,
 };
 use futures::channel::mpsc;
+use tokio::sync::Semaphore;
 use value::heap_size::{
     HeapSize,
     WithHeapSize,
@@ -99,6 +102,9 @@ pub struct RequestState<RT: Runtime, E: IsolateEnvironment<RT>> {
     // This is not wrapped in `WithHeapSize` so we can return `&mut TextDecoderStream`.
     // Additionally, `TextDecoderResource` should have a fairly small heap size.
     pub text_decoders: BTreeMap<uuid::Uuid, TextDecoderResource>,
+    // Like `text_decoders`, this is not wrapped in `WithHeapSize`. A reader only
+    // buffers the parts of the body that it has been fed but not yet parsed.
+    pub multi_part_readers: BTreeMap<uuid::Uuid, MultiPartReaderResource>,
 }
 
 pub struct TextDecoderResource {
@@ -106,10 +112,68 @@ pub struct TextDecoderResource {
     pub fatal: bool,
 }
 
+/// Incrementally parses a multipart/form-data body that JavaScript feeds to
+/// it chunk by chunk, so fields can be read without buffering the whole body.
+pub struct MultiPartReaderResource {
+    pub body_sender: mpsc::UnboundedSender<Result<bytes::Bytes, Infallible>>,
+    pub multipart: multer::Multipart<'static>,
+    // The field whose data is currently being read.
+    pub field: Option<multer::Field<'static>>,
+}
+
+impl MultiPartReaderResource {
+    pub fn new(boundary: String) -> Self {
+        let (body_sender, body_receiver) = mpsc::unbounded();
+        Self {
+            body_sender,
+            multipart: multer::Multipart::new(body_receiver, boundary),
+            field: None,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct ReadableStream {
     pub parts: WithHeapSize<VecDeque<uuid::Uuid>>,
     pub done: bool,
+    pub flow_control: Option<StreamFlowControl>,
+}
+
+/// Bounds how many bytes of a stream produced in Rust can be waiting for
+/// JavaScript to read them. The producer reserves each chunk before adding it
+/// to the stream, and the reservation is released when the chunk is read.
+#[derive(Clone, Debug)]
+pub struct StreamFlowControl {
+    buffered_bytes: Arc<Semaphore>,
+    max_buffered_bytes: usize,
+}
+
+impl StreamFlowControl {
+    pub fn new(max_buffered_bytes: usize) -> Self {
+        let max_buffered_bytes = max_buffered_bytes.clamp(1, u32::MAX as usize);
+        Self {
+            buffered_bytes: Arc::new(Semaphore::new(max_buffered_bytes)),
+            max_buffered_bytes,
+        }
+    }
+
+    // A chunk larger than the whole budget reserves the whole budget, so it is
+    // only added once every chunk before it has been read.
+    fn permits(&self, len: usize) -> u32 {
+        len.min(self.max_buffered_bytes) as u32
+    }
+
+    pub async fn reserve(&self, len: usize) -> anyhow::Result<()> {
+        self.buffered_bytes
+            .acquire_many(self.permits(len))
+            .await?
+            .forget();
+        Ok(())
+    }
+
+    pub fn release(&self, len: usize) {
+        self.buffered_bytes.add_permits(self.permits(len) as usize);
+    }
 }
 
 impl HeapSize for ReadableStream {
@@ -148,6 +212,22 @@ impl<RT: Runtime, E: IsolateEnvironment<RT>> RequestState<RT, E> {
         Ok(uuid)
     }
 
+    pub fn create_stream_with_flow_control(
+        &mut self,
+        flow_control: StreamFlowControl,
+    ) -> anyhow::Result<uuid::Uuid> {
+        let rng = self.environment.rng()?;
+        let uuid = CryptoOps::random_uuid(rng)?;
+        self.streams.insert(
+            uuid,
+            Ok(ReadableStream {
+                flow_control: Some(flow_control),
+                ..Default::default()
+            }),
+        );
+        Ok(uuid)
+    }
+
     pub fn create_text_decoder(
         &mut self,
         decoder: TextDecoderResource,
@@ -180,6 +260,34 @@ impl<RT: Runtime, E: IsolateEnvironment<RT>> RequestState<RT, E> {
         Ok(decoder)
     }
 
+    pub fn create_multi_part_reader(
+        &mut self,
+        reader: MultiPartReaderResource,
+    ) -> anyhow::Result<uuid::Uuid> {
+        let rng = self.environment.rng()?;
+        let uuid = CryptoOps::random_uuid(rng)?;
+        self.multi_part_readers.insert(uuid, reader);
+        Ok(uuid)
+    }
+
+    pub fn get_multi_part_reader(
+        &mut self,
+        reader_id: &uuid::Uuid,
+    ) -> anyhow::Result<&mut MultiPartReaderResource> {
+        self.multi_part_readers
+            .get_mut(reader_id)
+            .ok_or_else(|| anyhow::anyhow!("Multipart reader resource not found"))
+    }
+
+    pub fn remove_multi_part_reader(
+        &mut self,
+        reader_id: &uuid::Uuid,
+    ) -> anyhow::Result<MultiPartReaderResource> {
+        self.multi_part_readers
+            .remove(reader_id)
+            .ok_or_else(|| anyhow::anyhow!("Multipart reader resource not found"))
+    }
+
     #[allow(unused)]
     pub fn read_part(&self, id: uuid::Uuid) -> anyhow::Result<bytes::Bytes> {
         self.blob_parts
@@ -0,0 +1,335 @@
+//! Resolves environment variables that refer to secrets in AWS Secrets
+//! Manager or HashiCorp Vault (see `SecretReference`), so actions can use
+//! credentials that are never stored in the deployment. The secret managers'
+//! own credentials are read from the environment when the function runner
+//! starts, from the variables their CLIs use.
+
+use std::{
+    collections::BTreeMap,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use anyhow::Context;
+use common::{
+    http::{
+        fetch::{
+            FetchClient,
+            InternalFetchPurpose,
+        },
+        HttpRequest,
+    },
+    knobs::{
+        SECRET_CACHE_TTL,
+        SECRET_MAX_STALENESS,
+    },
+};
+use errors::ErrorMetadata;
+use http::{
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    Method,
+};
+use model::environment_variables::types::{
+    EnvVarName,
+    EnvVarValue,
+    SecretReference,
+};
+use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
+use storage::sigv4::SigV4;
+use url::Url;
+
+use crate::metrics::log_secret_fetch;
+
+struct AwsSecretsManagerConfig {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    region: String,
+}
+
+struct VaultConfig {
+    address: Url,
+    token: String,
+    /// Set for Vault Enterprise namespaces.
+    namespace: Option<String>,
+}
+
+struct CachedSecret {
+    value: EnvVarValue,
+    fetched_at: SystemTime,
+}
+
+/// Fetches and caches the secrets that environment variables refer to.
+///
+/// A cached secret is fetched again once it's older than `SECRET_CACHE_TTL`,
+/// which is how rotated secrets are picked up. If that fetch fails, the
+/// cached value keeps being used until it's older than
+/// `SECRET_MAX_STALENESS`.
+#[derive(Default)]
+pub struct SecretResolver {
+    aws: Option<AwsSecretsManagerConfig>,
+    vault: Option<VaultConfig>,
+    cache: Mutex<BTreeMap<SecretReference, CachedSecret>>,
+}
+
+fn age(since: SystemTime, now: SystemTime) -> Duration {
+    now.duration_since(since).unwrap_or(Duration::ZERO)
+}
+
+impl SecretResolver {
+    /// Secrets Manager is configured by `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN` and `AWS_REGION`, and
+    /// Vault by `VAULT_ADDR`, `VAULT_TOKEN` and `VAULT_NAMESPACE`.
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        let aws = match (var("AWS_ACCESS_KEY_ID"), var("AWS_SECRET_ACCESS_KEY")) {
+            (Some(access_key_id), Some(secret_access_key)) => Some(AwsSecretsManagerConfig {
+                access_key_id,
+                secret_access_key,
+                session_token: var("AWS_SESSION_TOKEN"),
+                region: var("AWS_REGION")
+                    .or_else(|| var("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|| "us-east-1".to_string()),
+            }),
+            _ => None,
+        };
+        let vault = match (var("VAULT_ADDR"), var("VAULT_TOKEN")) {
+            (Some(address), Some(token)) => match address.parse() {
+                Ok(address) => Some(VaultConfig {
+                    address,
+                    token,
+                    namespace: var("VAULT_NAMESPACE"),
+                }),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid VAULT_ADDR {address:?}: {e}");
+                    None
+                },
+            },
+            _ => None,
+        };
+        Self {
+            aws,
+            vault,
+            cache: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Replace each value in `env_vars` that refers to a secret with the
+    /// secret's value.
+    pub async fn resolve(
+        &self,
+        fetch_client: &dyn FetchClient,
+        env_vars: &mut BTreeMap<EnvVarName, EnvVarValue>,
+        now: SystemTime,
+    ) -> anyhow::Result<()> {
+        for (name, value) in env_vars.iter_mut() {
+            let Some(reference) = SecretReference::parse(value)? else {
+                continue;
+            };
+            *value = self.get(fetch_client, &reference, now).await.map_err(|e| {
+                anyhow::anyhow!(ErrorMetadata::bad_request(
+                    "SecretResolutionFailed",
+                    format!("Failed to resolve the secret in environment variable {name}: {e:#}"),
+                ))
+            })?;
+        }
+        Ok(())
+    }
+
+    async fn get(
+        &self,
+        fetch_client: &dyn FetchClient,
+        reference: &SecretReference,
+        now: SystemTime,
+    ) -> anyhow::Result<EnvVarValue> {
+        let stale = match self.cache.lock().get(reference) {
+            Some(cached) if age(cached.fetched_at, now) < *SECRET_CACHE_TTL => {
+                return Ok(cached.value.clone());
+            },
+            Some(cached) if age(cached.fetched_at, now) < *SECRET_MAX_STALENESS => {
+                Some(cached.value.clone())
+            },
+            _ => None,
+        };
+        let result = self.fetch(fetch_client, reference, now).await;
+        match (result, stale) {
+            (Ok(value), _) => {
+                self.cache.lock().insert(
+                    reference.clone(),
+                    CachedSecret {
+                        value: value.clone(),
+                        fetched_at: now,
+                    },
+                );
+                Ok(value)
+            },
+            (Err(e), Some(stale)) => {
+                tracing::warn!("Failed to refetch secret {reference:?}, using cached value: {e:#}");
+                Ok(stale)
+            },
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    async fn fetch(
+        &self,
+        fetch_client: &dyn FetchClient,
+        reference: &SecretReference,
+        now: SystemTime,
+    ) -> anyhow::Result<EnvVarValue> {
+        let (secret_manager, result) = match reference {
+            SecretReference::AwsSecretsManager { secret_id, key } => (
+                "aws_secrets_manager",
+                self.fetch_from_aws(fetch_client, secret_id, key.as_deref(), now)
+                    .await,
+            ),
+            SecretReference::Vault { mount, path, key } => (
+                "vault",
+                self.fetch_from_vault(fetch_client, mount, path, key).await,
+            ),
+        };
+        log_secret_fetch(secret_manager, result.is_ok());
+        result?.parse()
+    }
+
+    async fn fetch_from_aws(
+        &self,
+        fetch_client: &dyn FetchClient,
+        secret_id: &str,
+        key: Option<&str>,
+        now: SystemTime,
+    ) -> anyhow::Result<String> {
+        let aws = self.aws.as_ref().context(
+            "AWS Secrets Manager isn't configured, set AWS_ACCESS_KEY_ID and \
+             AWS_SECRET_ACCESS_KEY for the backend",
+        )?;
+        // ARNs name the secret's region, e.g.
+        // `arn:aws:secretsmanager:us-west-2:123456789012:secret:name`.
+        let region = secret_id
+            .strip_prefix("arn:")
+            .and_then(|arn| arn.split(':').nth(2))
+            .unwrap_or(&aws.region);
+        let url: Url = format!("https://secretsmanager.{region}.amazonaws.com/").parse()?;
+        let body = serde_json::to_vec(&serde_json::json!({ "SecretId": secret_id }))?;
+        let sigv4 = SigV4 {
+            access_key_id: &aws.access_key_id,
+            secret_access_key: &aws.secret_access_key,
+            session_token: aws.session_token.as_deref(),
+            region,
+        };
+        let headers = sigv4.sign_with_body(
+            "secretsmanager",
+            "POST",
+            &url,
+            vec![
+                ("content-type", "application/x-amz-json-1.1".to_string()),
+                ("x-amz-target", "secretsmanager.GetSecretValue".to_string()),
+            ],
+            &body,
+            now.into(),
+        )?;
+        let response = send(fetch_client, Method::POST, url, headers, Some(body)).await?;
+        let secret = response
+            .get("SecretString")
+            .and_then(JsonValue::as_str)
+            .context("The secret has no SecretString, and binary secrets aren't supported")?;
+        match key {
+            None => Ok(secret.to_string()),
+            Some(key) => {
+                let fields: JsonValue =
+                    serde_json::from_str(secret).context("The secret isn't a JSON object")?;
+                field(&fields, key)
+            },
+        }
+    }
+
+    async fn fetch_from_vault(
+        &self,
+        fetch_client: &dyn FetchClient,
+        mount: &str,
+        path: &str,
+        key: &str,
+    ) -> anyhow::Result<String> {
+        let vault = self
+            .vault
+            .as_ref()
+            .context("Vault isn't configured, set VAULT_ADDR and VAULT_TOKEN for the backend")?;
+        let url = vault.address.join(&format!("v1/{mount}/data/{path}"))?;
+        let mut headers = vec![("x-vault-token", vault.token.clone())];
+        if let Some(namespace) = &vault.namespace {
+            headers.push(("x-vault-namespace", namespace.clone()));
+        }
+        let response = send(fetch_client, Method::GET, url, headers, None).await?;
+        let fields = response
+            .pointer("/data/data")
+            .context("Vault's response has no secret data")?;
+        field(fields, key)
+    }
+}
+
+/// The `key` field of a secret's fields. Fields that aren't strings are
+/// passed to functions as JSON.
+fn field(fields: &JsonValue, key: &str) -> anyhow::Result<String> {
+    match fields.get(key) {
+        Some(JsonValue::String(value)) => Ok(value.clone()),
+        Some(value) => Ok(value.to_string()),
+        None => anyhow::bail!("The secret has no field {key:?}"),
+    }
+}
+
+async fn send(
+    fetch_client: &dyn FetchClient,
+    method: Method,
+    url: Url,
+    headers: Vec<(&'static str, String)>,
+    body: Option<Vec<u8>>,
+) -> anyhow::Result<JsonValue> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        header_map.insert(
+            HeaderName::from_static(name),
+            HeaderValue::from_str(&value)?,
+        );
+    }
+    let request = HttpRequest {
+        headers: header_map,
+        url: url.clone(),
+        method,
+        body,
+    };
+    let response = fetch_client
+        .internal_fetch(request.into(), InternalFetchPurpose::SecretManager)
+        .await?
+        .into_http_response()
+        .await?;
+    let body = response.body.unwrap_or_default();
+    anyhow::ensure!(
+        response.status.is_success(),
+        "{} responded with {}: {}",
+        url.host_str().unwrap_or_default(),
+        response.status,
+        String::from_utf8_lossy(&body)
+    );
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::field;
+
+    #[test]
+    fn test_field() -> anyhow::Result<()> {
+        let fields = json!({ "apiKey": "sk_live_1234", "port": 5432 });
+        assert_eq!(field(&fields, "apiKey")?, "sk_live_1234");
+        assert_eq!(field(&fields, "port")?, "5432");
+        assert!(field(&fields, "password").is_err());
+        Ok(())
+    }
+}
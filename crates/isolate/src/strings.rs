@@ -64,6 +64,8 @@ declare_strings!(
     empty => "",
     export,
     exportArgs,
+    exportCache,
+    exportErrors,
     exportReturns,
     import_meta_unsupported => "import.meta unsupported",
     internal_error => "Convex encountered an internal error",
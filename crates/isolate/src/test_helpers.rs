@@ -1,5 +1,8 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     fs::File,
     io::Read,
     sync::{
@@ -25,6 +28,7 @@ use common::{
     errors::JsError,
     execution_context::ExecutionContext,
     http::fetch::ProxiedFetchClient,
+    knobs::TABLE_CHANGES_ROWS_READ_LIMIT,
     log_lines::{
         LogLine,
         LogLines,
@@ -63,6 +67,7 @@ use database::{
     FollowerRetentionManager,
     IndexModel,
     IndexWorker,
+    TableChanges,
     Transaction,
 };
 use file_storage::TransactionalFileStorage;
@@ -100,7 +105,10 @@ use model::{
         types::FileStorageEntry,
         FileStorageId,
     },
-    scheduled_jobs::VirtualSchedulerModel,
+    scheduled_jobs::{
+        types::ScheduleOptions,
+        VirtualSchedulerModel,
+    },
     source_packages::{
         types::SourcePackage,
         upload_download::upload_package,
@@ -111,6 +119,7 @@ use model::{
         UdfConfigModel,
     },
     virtual_system_mapping,
+    workflows::WorkflowModel,
 };
 use rand::Rng;
 use search::searcher::InProcessSearcher;
@@ -120,12 +129,16 @@ use storage::{
     LocalDirStorage,
     Storage,
 };
-use sync_types::UdfPath;
+use sync_types::{
+    Timestamp,
+    UdfPath,
+};
 use usage_tracking::FunctionUsageStats;
 use value::{
     id_v6::DeveloperDocumentId,
     ConvexArray,
     ConvexObject,
+    ConvexValue,
     TableName,
     TableNamespace,
 };
@@ -1040,6 +1053,19 @@ impl<RT: Runtime, P: Persistence + Clone> UdfTest<RT, P> {
         udf_path: &str,
         args: Vec<ConvexValue>,
         identity: Identity,
+    ) -> anyhow::Result<(ActionOutcome, LogLines)> {
+        self.raw_action_with_context(udf_path, args, identity, ExecutionContext::new_for_test())
+            .await
+    }
+
+    /// Run an action with the given `context`, e.g. as if it were run by the
+    /// scheduler.
+    pub async fn raw_action_with_context(
+        &self,
+        udf_path: &str,
+        args: Vec<ConvexValue>,
+        identity: Identity,
+        context: ExecutionContext,
     ) -> anyhow::Result<(ActionOutcome, LogLines)> {
         let mut tx = self.database.begin(identity.clone()).await?;
         let path = ComponentFunctionPath {
@@ -1084,7 +1110,7 @@ impl<RT: Runtime, P: Persistence + Clone> UdfTest<RT, P> {
                 Arc::new(self.clone()),
                 fetch_client,
                 log_line_sender,
-                ExecutionContext::new_for_test(),
+                context,
             )
             .await?;
         let log_lines: Vec<LogLine> = log_line_receiver.collect().await;
@@ -1324,12 +1350,29 @@ impl<RT: Runtime, P: Persistence + Clone> ActionCallbacks for UdfTest<RT, P> {
         Ok(())
     }
 
+    async fn storage_set_metadata(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        storage_id: FileStorageId,
+        tags: Option<BTreeSet<String>>,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(identity).await?;
+        self.file_storage
+            .set_file_metadata(&mut tx, component.into(), storage_id, tags, metadata)
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(())
+    }
+
     async fn schedule_job(
         &self,
         identity: Identity,
         path: ComponentFunctionPath,
         udf_args: Vec<JsonValue>,
         scheduled_ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let mut tx: database::Transaction<RT> = self.database.begin(identity).await?;
@@ -1348,7 +1391,7 @@ impl<RT: Runtime, P: Persistence + Clone> ActionCallbacks for UdfTest<RT, P> {
             .await?;
 
         let virtual_id = VirtualSchedulerModel::new(&mut tx, component_id.into())
-            .schedule(path.udf_path, udf_args, scheduled_ts, context)
+            .schedule(path.udf_path, udf_args, scheduled_ts, options, context)
             .await?;
         self.database.commit(tx).await?;
 
@@ -1376,6 +1419,57 @@ impl<RT: Runtime, P: Persistence + Clone> ActionCallbacks for UdfTest<RT, P> {
         let query = VectorSearch::try_from(query)?;
         self.database.vector_search(identity, query).await
     }
+
+    async fn load_workflow_step(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        workflow_id: DeveloperDocumentId,
+        step_name: String,
+    ) -> anyhow::Result<Option<ConvexValue>> {
+        let mut tx = self.database.begin(identity).await?;
+        let mut model = WorkflowModel::new(&mut tx, component.into());
+        model.declare(workflow_id).await?;
+        let step = model.get_step(workflow_id, &step_name).await?;
+        self.database.commit(tx).await?;
+        Ok(step.map(|step| step.into_value().result))
+    }
+
+    async fn checkpoint_workflow_step(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        workflow_id: DeveloperDocumentId,
+        step_name: String,
+        result: ConvexValue,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.database.begin(identity).await?;
+        WorkflowModel::new(&mut tx, component.into())
+            .checkpoint_step(workflow_id, step_name, result)
+            .await?;
+        self.database.commit(tx).await?;
+        Ok(())
+    }
+
+    async fn table_changes(
+        &self,
+        identity: Identity,
+        component: ComponentId,
+        table: TableName,
+        cursor: Option<Timestamp>,
+        limit: usize,
+    ) -> anyhow::Result<TableChanges> {
+        self.database
+            .table_changes(
+                identity,
+                component.into(),
+                table,
+                cursor,
+                *TABLE_CHANGES_ROWS_READ_LIMIT,
+                limit,
+            )
+            .await
+    }
 }
 
 /// Create a bogus UDF request for testing. Should only be used for tests
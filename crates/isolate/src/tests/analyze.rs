@@ -1,5 +1,8 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     str::FromStr,
 };
 
@@ -16,13 +19,16 @@ use maplit::btreemap;
 use model::{
     config::types::ModuleConfig,
     cron_jobs::types::{
+        CronCatchUpPolicy,
         CronIdentifier,
         CronSchedule,
         CronSpec,
     },
     modules::{
+        function_dependencies::FunctionDependencies,
         function_validators::{
             ArgsValidator,
+            ErrorsValidator,
             ReturnsValidator,
         },
         module_versions::{
@@ -36,10 +42,15 @@ use model::{
 };
 use pretty_assertions::assert_eq;
 use runtime::testing::TestRuntime;
+use sync_types::{
+    CanonicalizedModulePath,
+    CanonicalizedUdfPath,
+};
 use value::{
     assert_obj,
     ConvexArray,
     ConvexValue,
+    TableName,
 };
 
 use crate::test_helpers::UdfTest;
@@ -171,15 +182,24 @@ async fn test_analyze_module(rt: TestRuntime) -> anyhow::Result<()> {
         CronIdentifier::from_str("weekly re-engagement email")? => CronSpec {
             udf_path: "crons.js:addOne".parse()?,
             udf_args: args.clone(),
-            cron_schedule: CronSchedule::Weekly { day_of_week: 2, hour_utc: 17, minute_utc: 30 }},
+            cron_schedule: CronSchedule::Weekly { day_of_week: 2, hour_utc: 17, minute_utc: 30 },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::RunOnce },
         CronIdentifier::from_str("add one every hour")? => CronSpec {
             udf_path: "crons.js:addOne".parse()?,
             udf_args: args.clone(),
-            cron_schedule: CronSchedule::Interval{ seconds: 3600 * 24 * 7 } },
+            cron_schedule: CronSchedule::Interval{ seconds: 3600 * 24 * 7 },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::RunOnce },
         CronIdentifier::from_str("clear presence data")? => CronSpec {
             udf_path: "crons.js:addOne".parse()?,
             udf_args: args,
-            cron_schedule: CronSchedule::Interval{ seconds: 300} },
+            cron_schedule: CronSchedule::Interval{ seconds: 300},
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::RunOnce },
         ).into()),
     );
 
@@ -278,6 +298,9 @@ async fn test_analyze_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "throwsErrorInDep".parse()?,
@@ -294,6 +317,9 @@ async fn test_analyze_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
         ],
     );
@@ -312,6 +338,9 @@ async fn test_analyze_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "throwsErrorInDep".parse()?,
@@ -324,6 +353,9 @@ async fn test_analyze_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
         ],
     );
@@ -360,6 +392,9 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Internal),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "publicQuery".parse()?,
@@ -370,6 +405,9 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "myInternalMutation".parse()?,
@@ -380,6 +418,9 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Internal),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "publicMutation".parse()?,
@@ -390,6 +431,9 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
         ],
     );
@@ -408,6 +452,9 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Internal),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "publicQuery".parse()?,
@@ -420,6 +467,9 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "myInternalMutation".parse()?,
@@ -432,6 +482,9 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Internal),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
             AnalyzedFunction {
                 name: "publicMutation".parse()?,
@@ -444,12 +497,62 @@ async fn test_analyze_internal_function(rt: TestRuntime) -> anyhow::Result<()> {
                 visibility: Some(Visibility::Public),
                 args: ArgsValidator::Unvalidated,
                 returns: ReturnsValidator::Unvalidated,
+                errors: ErrorsValidator::Unvalidated,
+                cache: None,
+                dependencies: Some(FunctionDependencies::default()),
             },
         ],
     );
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_analyze_function_dependencies(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    let modules = {
+        let mut tx = t.database.begin(Identity::system()).await?;
+        ModuleModel::new(&mut tx)
+            .get_application_modules(ComponentId::test_user(), t.module_loader.as_ref())
+            .await?
+    };
+
+    let udf_config = UdfConfig::new_for_test(&t.rt, "1000.0.0".parse()?);
+    let result = t
+        .isolate
+        .analyze(udf_config, modules, BTreeMap::new())
+        .await??;
+    let dependencies = |module: &str, function: &str| -> anyhow::Result<FunctionDependencies> {
+        let module_path: CanonicalizedModulePath = module.parse()?;
+        let function = result[&module_path]
+            .functions
+            .iter()
+            .find(|f| f.name.to_string() == function)
+            .unwrap();
+        Ok(function.dependencies.clone().unwrap())
+    };
+
+    let insert_and_count = dependencies("basic.js", "insertAndCount")?;
+    assert_eq!(
+        insert_and_count.reads_tables,
+        BTreeSet::from(["objects".parse::<TableName>()?])
+    );
+    assert_eq!(
+        insert_and_count.writes_tables,
+        BTreeSet::from(["objects".parse::<TableName>()?])
+    );
+    assert!(!insert_and_count.reads_unknown_tables);
+    assert!(!insert_and_count.writes_unknown_tables);
+
+    let schedule_after = dependencies("scheduler.js", "scheduleAfter")?;
+    assert_eq!(
+        schedule_after.schedules,
+        BTreeSet::from(["basic.js:insertObject".parse::<CanonicalizedUdfPath>()?])
+    );
+    assert!(schedule_after.reads_tables.is_empty());
+    assert!(!schedule_after.references_unknown_functions);
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_analyze_developer_errors(rt: TestRuntime) -> anyhow::Result<()> {
     let cases = [
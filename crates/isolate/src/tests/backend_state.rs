@@ -10,7 +10,10 @@ use model::backend_state::{
     PAUSED_ERROR_MESSAGE,
 };
 use runtime::testing::TestRuntime;
-use value::assert_obj;
+use value::{
+    assert_obj,
+    ConvexValue,
+};
 
 use crate::{
     test_helpers::UdfTest,
@@ -64,6 +67,51 @@ async fn test_http_action_while_disabled(rt: TestRuntime) -> anyhow::Result<()>
     test_http_action_helper(rt, BackendState::Disabled).await
 }
 
+#[convex_macro::test_runtime]
+async fn test_query_while_read_only(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    toggle_backend_state(&t.database, BackendState::ReadOnly).await?;
+    t.query("basic:count", assert_obj!()).await?;
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_mutation_while_read_only(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    toggle_backend_state(&t.database, BackendState::ReadOnly).await?;
+    let error = t
+        .raw_mutation(
+            "basic:addOneInt",
+            vec![ConvexValue::Object(assert_obj!("x" => 1))],
+            Identity::system(),
+        )
+        .await
+        .unwrap_err();
+    assert_error(error, BackendState::ReadOnly);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_action_while_read_only(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = UdfTest::default(rt).await?;
+    toggle_backend_state(&t.database, BackendState::ReadOnly).await?;
+    let error = t
+        .raw_action(
+            "action:getCloudUrl",
+            vec![ConvexValue::Object(assert_obj!())],
+            Identity::system(),
+        )
+        .await
+        .unwrap_err();
+    assert_error(error, BackendState::ReadOnly);
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_http_action_while_read_only(rt: TestRuntime) -> anyhow::Result<()> {
+    test_http_action_helper(rt, BackendState::ReadOnly).await
+}
+
 async fn test_query_helper(
     rt: TestRuntime,
     backend_state: BackendState,
@@ -128,6 +176,7 @@ fn assert_error(error: anyhow::Error, backend_state: BackendState) {
     let error_message = match backend_state {
         BackendState::Paused => "NoRunWhilePaused",
         BackendState::Disabled => "NoRunWhileDisabled",
+        BackendState::ReadOnly => "DeploymentReadOnly",
         BackendState::Running => return,
     };
     let error_metadata = error.downcast_ref::<ErrorMetadata>().unwrap();
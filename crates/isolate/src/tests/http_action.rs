@@ -194,6 +194,24 @@ async fn test_http_echo(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_http_path_params(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = http_action_udf_test(rt).await?;
+
+    let response = t
+        .http_action(
+            "http_action",
+            http_request("users/a%20b/posts/2024/01"),
+            Identity::system(),
+        )
+        .await?;
+
+    must_let!(let Some(value) = response.body().clone());
+    let actual: JsonValue = serde_json::from_slice(&value)?;
+    assert_eq!(actual, json!({ "id": "a b", "rest": "2024/01" }));
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_http_scheduler(rt: TestRuntime) -> anyhow::Result<()> {
     let t = http_action_udf_test(rt).await?;
@@ -424,3 +442,45 @@ async fn test_http_streaming(rt: TestRuntime) -> anyhow::Result<()> {
     assert_contains(&last_line.to_pretty_string(), "Hit error while streaming");
     Ok(())
 }
+
+#[convex_macro::test_runtime]
+async fn test_http_server_sent_events(rt: TestRuntime) -> anyhow::Result<()> {
+    let t = http_action_udf_test(rt).await?;
+
+    // Quiet event streams get heartbeats.
+    let response = t
+        .http_action(
+            "http_action",
+            http_post_request(
+                "serverSentEvents",
+                "{ \"pauseMs\": 20000 }".as_bytes().to_vec(),
+            ),
+            Identity::system(),
+        )
+        .await?;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.headers.get("cache-control").unwrap(), "no-cache");
+    assert_eq!(response.headers.get("x-accel-buffering").unwrap(), "no");
+    must_let!(let Some(value) = response.body().clone());
+    assert_eq!(String::from_utf8(value)?, "data: 1\n\n:\n\ndata: 2\n\n");
+
+    // Streams that are idle for too long are ended.
+    let (response, log_lines) = t
+        .http_action_with_log_lines(
+            "http_action",
+            http_post_request(
+                "serverSentEvents",
+                "{ \"pauseMs\": 400000 }".as_bytes().to_vec(),
+            ),
+            Identity::system(),
+        )
+        .await?;
+    must_let!(let Some(value) = response.body().clone());
+    let body = String::from_utf8(value)?;
+    assert!(body.starts_with("data: 1\n\n:\n\n"), "{body}");
+    assert!(!body.contains("data: 2"), "{body}");
+    assert!(log_lines
+        .iter()
+        .any(|line| line.to_pretty_string().contains("HttpResponseIdleTimeout")));
+    Ok(())
+}
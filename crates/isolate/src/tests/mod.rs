@@ -34,6 +34,7 @@ mod unicode;
 mod user_error;
 mod values;
 mod vector_search;
+mod workflow;
 
 pub fn assert_contains(error: &impl Display, expected: &str) {
     assert!(
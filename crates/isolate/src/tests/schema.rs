@@ -263,7 +263,13 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                     "union" => FieldValidator::required_field_type(Validator::Union(vec![Validator::String, Validator::Float64])),
                     "object" => FieldValidator::required_field_type(Validator::Object(object_validator!("a" => FieldValidator::optional_field_type(Validator::Any))))
                   )
-                ]))
+                ])),
+                encrypted_fields: Default::default(),
+                triggers: Default::default(),
+                track_updates: false,
+                soft_delete: None,
+                id_strategy: Default::default(),
+                sketches: Default::default(),
             },
             name2.clone() => TableDefinition {
                 table_name: name2,
@@ -280,6 +286,12 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                 search_indexes: btreemap!(),
                 vector_indexes: btreemap!(),
                 document_type: None,
+                encrypted_fields: Default::default(),
+                triggers: Default::default(),
+                track_updates: false,
+                soft_delete: None,
+                id_strategy: Default::default(),
+                sketches: Default::default(),
             },
             name3.clone() => TableDefinition {
               table_name: name3,
@@ -293,6 +305,12 @@ async fn test_eval_schema(rt: TestRuntime) -> anyhow::Result<()> {
                },
                vector_indexes: btreemap!(),
                document_type: None,
+               encrypted_fields: Default::default(),
+               triggers: Default::default(),
+               track_updates: false,
+               soft_delete: None,
+               id_strategy: Default::default(),
+               sketches: Default::default(),
           }
         ),
         schema_validation: true,
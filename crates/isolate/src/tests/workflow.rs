@@ -0,0 +1,78 @@
+use common::{
+    assert_obj,
+    execution_context::ExecutionContext,
+    types::FunctionCaller,
+    value::ConvexValue,
+    RequestId,
+};
+use keybroker::Identity;
+use runtime::testing::TestRuntime;
+use value::{
+    DeveloperDocumentId,
+    InternalId,
+};
+
+use super::assert_contains;
+use crate::test_helpers::{
+    UdfTest,
+    UdfTestType,
+};
+
+fn scheduled_context(job_id: DeveloperDocumentId) -> ExecutionContext {
+    ExecutionContext::new(RequestId::new(), &FunctionCaller::Scheduler { job_id })
+}
+
+#[convex_macro::test_runtime]
+async fn test_workflow_step_resumes_from_checkpoint(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        let job_id = DeveloperDocumentId::new(10001.try_into()?, InternalId::MIN);
+        let (outcome, _) = t
+            .raw_action_with_context(
+                "workflow:runSteps",
+                vec![ConvexValue::Object(
+                    assert_obj!("value" => "first attempt", "failSecondStep" => true),
+                )],
+                Identity::system(),
+                scheduled_context(job_id),
+            )
+            .await?;
+        assert_contains(&outcome.result.unwrap_err(), "Failed the second step");
+
+        // The retry skips the first step, which already completed, and runs
+        // the second.
+        let (outcome, _) = t
+            .raw_action_with_context(
+                "workflow:runSteps",
+                vec![ConvexValue::Object(
+                    assert_obj!("value" => "retry", "failSecondStep" => false),
+                )],
+                Identity::system(),
+                scheduled_context(job_id),
+            )
+            .await?;
+        assert_eq!(
+            outcome.result?.unpack(),
+            ConvexValue::Array(vec!["first attempt".try_into()?, "retry".try_into()?].try_into()?)
+        );
+        Ok(())
+    })
+    .await
+}
+
+#[convex_macro::test_runtime]
+async fn test_workflow_step_requires_scheduler(rt: TestRuntime) -> anyhow::Result<()> {
+    UdfTest::run_test_with_isolate2(rt, async move |t: UdfTestType| {
+        let error = t
+            .action_js_error(
+                "workflow:runSteps",
+                assert_obj!("value" => "value", "failSecondStep" => false),
+            )
+            .await?;
+        assert_contains(
+            &error,
+            "Workflow steps can only be used from actions run by the scheduler",
+        );
+        Ok(())
+    })
+    .await
+}
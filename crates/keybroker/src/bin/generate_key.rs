@@ -2,11 +2,13 @@ use std::env;
 
 use common::types::MemberId;
 use keybroker::{
+    AdminRole,
     InstanceSecret,
     KeyBroker,
 };
 
-const USAGE: &str = "USAGE: ./generate_key <instance_name> <instance_secret> [member_id]";
+const USAGE: &str =
+    "USAGE: ./generate_key <instance_name> <instance_secret> [member_id] [admin|deploy|read_only]";
 
 fn main() -> anyhow::Result<()> {
     let instance_name = env::args().nth(1).ok_or_else(|| anyhow::anyhow!(USAGE))?;
@@ -16,10 +18,15 @@ fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|| "0".to_owned())
         .parse::<u64>()
         .map_err(|_| anyhow::anyhow!(USAGE))?;
+    let role = env::args()
+        .nth(4)
+        .map(|role| role.parse::<AdminRole>())
+        .transpose()?
+        .unwrap_or(AdminRole::Admin);
     let instance_secret = InstanceSecret::try_from(&instance_secret_s[..])?;
 
     let broker = KeyBroker::new(&instance_name[..], instance_secret)?;
-    let admin_key = broker.issue_admin_key(MemberId(member_id));
+    let admin_key = broker.issue_admin_key_with_role(MemberId(member_id), role);
     println!("{}", admin_key);
     let system_key = broker.issue_system_key();
     println!("{}", system_key);
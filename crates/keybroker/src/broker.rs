@@ -1,6 +1,7 @@
 use core::panic;
 use std::{
     fmt,
+    str::FromStr,
     time::{
         Duration,
         SystemTime,
@@ -10,6 +11,7 @@ use std::{
 use anyhow::Context;
 pub use common::types::SystemKey;
 use common::{
+    auth::ApiKeyScope,
     identity::{
         IdentityCacheKey,
         InertIdentity,
@@ -40,10 +42,22 @@ use common::{
 use errors::ErrorMetadata;
 use openidconnect::{
     core::{
+        CoreHmacKey,
         CoreIdToken,
+        CoreIdTokenClaims,
         CoreIdTokenVerifier,
+        CoreJsonWebKeySet,
+        CoreJwsSigningAlgorithm,
     },
+    Audience,
+    ClientId,
+    ClientSecret,
+    EmptyAdditionalClaims,
+    EndUserEmail,
+    IssuerUrl,
     Nonce,
+    StandardClaims,
+    SubjectIdentifier,
 };
 use pb::{
     convex_actions::ActionCallbackToken as ActionCallbackTokenProto,
@@ -55,6 +69,7 @@ use pb::{
     convex_identity::{
         unchecked_identity::Identity as UncheckedIdentityProto,
         ActingUser,
+        AdminRole as AdminRoleProto,
     },
     convex_keys::{
         admin_key::Identity as AdminIdentityProto,
@@ -76,6 +91,7 @@ use serde::{
     Deserialize,
     Serialize,
 };
+use sodiumoxide::crypto::auth::hmacsha256;
 use sync_types::{
     AuthenticationToken,
     SerializedQueryJournal,
@@ -91,7 +107,11 @@ use crate::{
         log_actions_token_expired,
         log_store_file_auth_expired,
     },
-    secret::InstanceSecret,
+    secret::{
+        InstanceSecret,
+        Secret,
+    },
+    upload_constraints::UploadConstraints,
 };
 
 const ACTION_KEY_VERSION: u8 = 1;
@@ -103,10 +123,24 @@ const QUERY_JOURNAL_VERSION: u8 = 7;
 // Max delay from transaction start time -> key being issued that is tolerable.
 const MAX_TS_DELAY: Duration = Duration::from_secs(15);
 
+/// Context for deriving the key that signs user tokens from the instance
+/// secret, so that it differs from the key used for encryption.
+const USER_TOKEN_KEY_CONTEXT: &[u8] = b"convex built-in auth user tokens";
+
+/// Context for deriving the key that encrypts the fields schemas mark as
+/// encrypted, so that it differs from the key used for tokens and cursors.
+const FIELD_ENCRYPTION_KEY_CONTEXT: &[u8] = b"convex encrypted document fields";
+
+/// Audience of the user tokens issued by the deployment's built-in auth.
+pub const USER_TOKEN_AUDIENCE: &str = "convex";
+
 #[derive(Clone)]
 pub struct KeyBroker {
     instance_name: String,
     encryptor: Encryptor,
+    // HMAC key for the user tokens issued by the deployment's built-in auth.
+    user_token_key: ClientSecret,
+    field_encryptor: Encryptor,
 }
 
 // This enum encodes a successful authentication decision, and its nontrivial
@@ -123,6 +157,7 @@ pub enum Identity {
     // ActingUser keeps track of the ID of the admin acting as a user,
     // and that user's fake attributes
     ActingUser(AdminIdentity, UserIdentityAttributes),
+    ApiKey(ApiKeyIdentity),
     Unknown,
 }
 
@@ -136,6 +171,8 @@ impl From<Identity> for AuthenticationToken {
                 AuthenticationToken::Admin(identity.key, Some(user))
             },
             Identity::InstanceAdmin(identity) => AuthenticationToken::Admin(identity.key, None),
+            // API keys are sent as bearer tokens.
+            Identity::ApiKey(identity) => AuthenticationToken::User(identity.key),
             _ => AuthenticationToken::None,
         }
     }
@@ -157,6 +194,9 @@ impl From<Identity> for pb::convex_identity::UncheckedIdentity {
                     attributes: Some(attributes.into()),
                 })
             },
+            Identity::ApiKey(api_key_identity) => {
+                UncheckedIdentityProto::ApiKey(api_key_identity.into())
+            },
             Identity::Unknown => UncheckedIdentityProto::Unknown(()),
         };
         Self {
@@ -191,24 +231,40 @@ impl Identity {
                     attributes.ok_or_else(|| anyhow::anyhow!("Missing user attributes"))?;
                 Ok(Identity::ActingUser(admin_identity, attributes.try_into()?))
             },
+            UncheckedIdentityProto::ApiKey(api_key_identity) => Ok(Identity::ApiKey(
+                ApiKeyIdentity::from_proto_unchecked(api_key_identity)?,
+            )),
             UncheckedIdentityProto::Unknown(()) => Ok(Identity::Unknown),
         }
     }
 
     pub fn ensure_can_run_function(&self, udf_type: UdfType) -> anyhow::Result<()> {
-        // Everyone can run queries.
-        if udf_type == UdfType::Query {
-            return Ok(());
-        }
         match self {
             Identity::InstanceAdmin(admin_identity) | Identity::ActingUser(admin_identity, _) => {
-                if admin_identity.is_read_only() {
+                let operation = match udf_type {
+                    UdfType::Query => AdminOperation::ReadData,
+                    UdfType::Mutation | UdfType::Action | UdfType::HttpAction => {
+                        AdminOperation::WriteData
+                    },
+                };
+                if !admin_identity.role().allows(operation) {
                     anyhow::bail!(ErrorMetadata::forbidden(
                         "Unauthorized",
                         format!("You do not have permission to run {udf_type} functions.")
                     ));
                 }
             },
+            Identity::ApiKey(api_key_identity) => {
+                if !api_key_identity.scope.allows_udf_type(udf_type) {
+                    anyhow::bail!(ErrorMetadata::forbidden(
+                        "Unauthorized",
+                        format!(
+                            "API key {} does not have permission to run {udf_type} functions.",
+                            api_key_identity.name
+                        )
+                    ));
+                }
+            },
             _ => {},
         }
         Ok(())
@@ -230,6 +286,7 @@ impl From<Identity> for InertIdentity {
                     panic!("Impresonating user for team access token is not supported")
                 },
             },
+            Identity::ApiKey(identity) => InertIdentity::ApiKey(identity.name),
         }
     }
 }
@@ -247,11 +304,13 @@ impl PartialEq for Identity {
                 Self::ActingUser(l_admin_identity, l_attributes),
                 Self::ActingUser(r_admin_identity, r_attributes),
             ) => l_admin_identity == r_admin_identity && l_attributes == r_attributes,
+            (Self::ApiKey(l), Self::ApiKey(r)) => l.id == r.id,
             (Self::InstanceAdmin(_), _)
             | (Self::System(_), _)
             | (Self::User(_), _)
             | (Self::Unknown, _)
-            | (Self::ActingUser(..), _) => false,
+            | (Self::ActingUser(..), _)
+            | (Self::ApiKey(_), _) => false,
         }
     }
 }
@@ -273,6 +332,7 @@ impl Identity {
                     panic!("Impresonating user for team access token is not supported")
                 },
             },
+            Identity::ApiKey(identity) => IdentityCacheKey::ApiKey(identity.id),
         }
     }
 
@@ -294,6 +354,15 @@ impl Identity {
         matches!(self, Identity::InstanceAdmin(..))
     }
 
+    /// Check that an admin's role allows `operation`. Other identities are
+    /// left to the operation's own checks.
+    pub fn ensure_admin_role_allows(&self, operation: AdminOperation) -> anyhow::Result<()> {
+        if let Identity::InstanceAdmin(admin_identity) = self {
+            admin_identity.role().ensure_allows(operation)?;
+        }
+        Ok(())
+    }
+
     pub fn is_user(&self) -> bool {
         matches!(self, Identity::User(..))
     }
@@ -494,6 +563,106 @@ pub enum AdminIdentityPrincipal {
     Team(TeamId),
 }
 
+/// What an instance admin may do with the deployment, which is fixed when
+/// their admin key is issued.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum AdminRole {
+    /// Full access to the deployment.
+    Admin,
+    /// Push code, schemas and environment variables, e.g. from CI, but not
+    /// read or write data.
+    Deploy,
+    /// Read data and logs and run queries, but not change anything.
+    ReadOnly,
+}
+
+/// The kinds of deployment management operations, which are allowed or not
+/// depending on the admin's [`AdminRole`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdminOperation {
+    /// Read the deployment's code, configuration and metadata.
+    ViewConfig,
+    /// Read data and logs, and run queries.
+    ReadData,
+    /// Push code, schemas and environment variables.
+    Deploy,
+    /// Write data, run mutations and actions, and everything else.
+    WriteData,
+}
+
+impl AdminRole {
+    pub fn allows(&self, operation: AdminOperation) -> bool {
+        match (self, operation) {
+            (AdminRole::Admin, _) => true,
+            (_, AdminOperation::ViewConfig) => true,
+            (AdminRole::Deploy, AdminOperation::Deploy) => true,
+            (AdminRole::ReadOnly, AdminOperation::ReadData) => true,
+            (AdminRole::Deploy, _) | (AdminRole::ReadOnly, _) => false,
+        }
+    }
+
+    pub fn ensure_allows(&self, operation: AdminOperation) -> anyhow::Result<()> {
+        if self.allows(operation) {
+            return Ok(());
+        }
+        let error = match self {
+            AdminRole::Deploy => ErrorMetadata::forbidden(
+                "DeployOnlyAdminKey",
+                "Deploy keys can only be used to push code and configuration.",
+            ),
+            AdminRole::Admin | AdminRole::ReadOnly => ErrorMetadata::forbidden(
+                "ReadOnlyAdminKey",
+                "You do not have permission to perform this operation.",
+            ),
+        };
+        Err(error.into())
+    }
+
+    fn from_proto(role: i32, is_read_only: bool) -> anyhow::Result<Self> {
+        Ok(match AdminRoleProto::try_from(role)? {
+            AdminRoleProto::Unspecified if is_read_only => AdminRole::ReadOnly,
+            AdminRoleProto::Unspecified | AdminRoleProto::Admin => AdminRole::Admin,
+            AdminRoleProto::Deploy => AdminRole::Deploy,
+            AdminRoleProto::ReadOnly => AdminRole::ReadOnly,
+        })
+    }
+}
+
+impl From<AdminRole> for AdminRoleProto {
+    fn from(role: AdminRole) -> Self {
+        match role {
+            AdminRole::Admin => AdminRoleProto::Admin,
+            AdminRole::Deploy => AdminRoleProto::Deploy,
+            AdminRole::ReadOnly => AdminRoleProto::ReadOnly,
+        }
+    }
+}
+
+impl FromStr for AdminRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "admin" => Ok(AdminRole::Admin),
+            "deploy" => Ok(AdminRole::Deploy),
+            "read_only" => Ok(AdminRole::ReadOnly),
+            _ => anyhow::bail!("Unknown admin role {s}, expected admin, deploy or read_only"),
+        }
+    }
+}
+
+impl fmt::Display for AdminRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let role = match self {
+            AdminRole::Admin => "admin",
+            AdminRole::Deploy => "deploy",
+            AdminRole::ReadOnly => "read_only",
+        };
+        write!(f, "{role}")
+    }
+}
+
 // Token indicating the possessor has authenticated as the admin for an
 // instance.
 #[derive(Clone, PartialEq, Eq, Hash)]
@@ -501,11 +670,7 @@ pub struct AdminIdentity {
     instance_name: String,
     principal: AdminIdentityPrincipal,
     key: String,
-    // is_read_only being true implies that this identity should not be able to write data.
-    // At the function level, read only admins are allowed to run queries but not mutations and
-    // actions. At the database level, they are allowed to read data from user and system tables
-    // but not write to them.
-    is_read_only: bool,
+    role: AdminRole,
 }
 
 impl From<AdminIdentity> for pb::convex_identity::AdminIdentity {
@@ -514,7 +679,7 @@ impl From<AdminIdentity> for pb::convex_identity::AdminIdentity {
             instance_name,
             principal,
             key,
-            is_read_only,
+            role,
         }: AdminIdentity,
     ) -> Self {
         Self {
@@ -528,7 +693,8 @@ impl From<AdminIdentity> for pb::convex_identity::AdminIdentity {
                 ),
             },
             key: Some(key),
-            is_read_only,
+            is_read_only: role == AdminRole::ReadOnly,
+            role: AdminRoleProto::from(role).into(),
         }
     }
 }
@@ -548,12 +714,12 @@ impl AdminIdentity {
             None => anyhow::bail!("Missing principal"),
         };
         let key = msg.key.ok_or_else(|| anyhow::anyhow!("Missing key"))?;
-        let is_read_only: bool = msg.is_read_only;
+        let role = AdminRole::from_proto(msg.role, msg.is_read_only)?;
         Ok(Self {
             instance_name,
             principal,
             key,
-            is_read_only,
+            role,
         })
     }
 
@@ -561,13 +727,13 @@ impl AdminIdentity {
         instance_name: String,
         principal: AdminIdentityPrincipal,
         access_token: String,
-        is_read_only: bool,
+        role: AdminRole,
     ) -> Self {
         Self {
             instance_name,
             principal,
             key: access_token,
-            is_read_only,
+            role,
         }
     }
 
@@ -575,12 +741,14 @@ impl AdminIdentity {
         &self.principal
     }
 
-    // is_read_only being true implies that this identity should not be able to
-    // write data. At the function level, read only admins are allowed to run
-    // queries but not mutations and actions. At the database level, they are
-    // allowed to read data from user and system tables but not write to them.
+    pub fn role(&self) -> AdminRole {
+        self.role
+    }
+
+    // Read only admins are allowed to run queries but not mutations and
+    // actions, and can read data but not write it.
     pub fn is_read_only(&self) -> bool {
-        self.is_read_only
+        self.role == AdminRole::ReadOnly
     }
 }
 
@@ -592,11 +760,13 @@ impl Arbitrary for AdminIdentity {
 
     fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
         use proptest::prelude::*;
-        any::<(AdminIdentityPrincipal, String)>().prop_map(|(principal, key)| AdminIdentity {
-            instance_name: "fake-instance-name".to_string(),
-            principal,
-            key,
-            is_read_only: false,
+        any::<(AdminIdentityPrincipal, String, AdminRole)>().prop_map(|(principal, key, role)| {
+            AdminIdentity {
+                instance_name: "fake-instance-name".to_string(),
+                principal,
+                key,
+                role,
+            }
         })
     }
 }
@@ -608,7 +778,7 @@ impl AdminIdentity {
             instance_name,
             principal: AdminIdentityPrincipal::Member(member_id),
             key: "chocolate-charlies-cupcake".to_string(),
-            is_read_only: false,
+            role: AdminRole::Admin,
         }
     }
 
@@ -636,6 +806,96 @@ impl fmt::Debug for SystemIdentity {
     }
 }
 
+// Token indicating the possessor has authenticated with one of the
+// deployment's API keys.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ApiKeyIdentity {
+    // ID of the key's document in `_api_keys`.
+    id: String,
+    name: String,
+    key: String,
+    scope: ApiKeyScope,
+}
+
+impl ApiKeyIdentity {
+    /// Only call this after checking `key` against the stored keys.
+    pub fn new(id: String, name: String, key: String, scope: ApiKeyScope) -> Self {
+        Self {
+            id,
+            name,
+            key,
+            scope,
+        }
+    }
+
+    pub fn from_proto_unchecked(msg: pb::convex_identity::ApiKeyIdentity) -> anyhow::Result<Self> {
+        let id = msg.id.ok_or_else(|| anyhow::anyhow!("Missing id"))?;
+        let name = msg.name.ok_or_else(|| anyhow::anyhow!("Missing name"))?;
+        let key = msg.key.ok_or_else(|| anyhow::anyhow!("Missing key"))?;
+        let functions = msg
+            .functions
+            .map(|functions| {
+                functions
+                    .paths
+                    .into_iter()
+                    .map(|path| path.parse())
+                    .collect::<anyhow::Result<_>>()
+            })
+            .transpose()?;
+        Ok(Self {
+            id,
+            name,
+            key,
+            scope: ApiKeyScope {
+                read_only: msg.read_only,
+                functions,
+            },
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scope(&self) -> &ApiKeyScope {
+        &self.scope
+    }
+}
+
+impl From<ApiKeyIdentity> for pb::convex_identity::ApiKeyIdentity {
+    fn from(
+        ApiKeyIdentity {
+            id,
+            name,
+            key,
+            scope,
+        }: ApiKeyIdentity,
+    ) -> Self {
+        Self {
+            id: Some(id),
+            name: Some(name),
+            key: Some(key),
+            read_only: scope.read_only,
+            functions: scope
+                .functions
+                .map(|functions| pb::convex_identity::ApiKeyFunctions {
+                    paths: functions.into_iter().map(String::from).collect(),
+                }),
+        }
+    }
+}
+
+impl fmt::Debug for ApiKeyIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ApiKey/{}/{}", self.id, self.name)
+    }
+}
+
 /// Encrypted authorization to store a file
 #[derive(Debug, derive_more::Display)]
 pub struct StoreFileAuthorization(String);
@@ -649,12 +909,27 @@ pub fn cursor_parse_error() -> ErrorMetadata {
 
 impl KeyBroker {
     pub fn new(instance_name: &str, instance_secret: InstanceSecret) -> anyhow::Result<Self> {
+        let user_token_key = hmacsha256::authenticate(
+            USER_TOKEN_KEY_CONTEXT,
+            &hmacsha256::Key(*instance_secret.as_bytes()),
+        );
+        let field_encryption_key = hmacsha256::authenticate(
+            FIELD_ENCRYPTION_KEY_CONTEXT,
+            &hmacsha256::Key(*instance_secret.as_bytes()),
+        );
         Ok(Self {
             instance_name: instance_name.to_owned(),
             encryptor: Encryptor::new(instance_secret)?,
+            user_token_key: ClientSecret::new(hex::encode(user_token_key.0)),
+            field_encryptor: Encryptor::new(Secret::try_from(field_encryption_key.0.to_vec())?)?,
         })
     }
 
+    /// Encryptor for the document fields that schemas mark as encrypted.
+    pub fn field_encryptor(&self) -> Encryptor {
+        self.field_encryptor.clone()
+    }
+
     pub fn dev() -> Self {
         Self::new(
             crate::DEV_INSTANCE_NAME,
@@ -664,32 +939,46 @@ impl KeyBroker {
     }
 
     pub fn issue_admin_key(&self, member_id: MemberId) -> AdminKey {
-        AdminKey::new(self.issue_key(Some(member_id), false))
+        self.issue_admin_key_with_role(member_id, AdminRole::Admin)
     }
 
     pub fn issue_read_only_admin_key(&self, member_id: MemberId) -> AdminKey {
-        AdminKey::new(self.issue_key(Some(member_id), true))
+        self.issue_admin_key_with_role(member_id, AdminRole::ReadOnly)
+    }
+
+    pub fn issue_admin_key_with_role(&self, member_id: MemberId, role: AdminRole) -> AdminKey {
+        AdminKey::new(self.issue_key(Some(member_id), role))
     }
 
     pub fn issue_system_key(&self) -> SystemKey {
-        SystemKey::new(self.issue_key(None, false))
+        SystemKey::new(self.issue_key(None, AdminRole::Admin))
     }
 
+    /// Authorize storing a file that satisfies `constraints`.
     pub fn issue_store_file_authorization<RT: Runtime>(
         &self,
         rt: &RT,
         issued: UnixTimestamp,
+        constraints: &UploadConstraints,
     ) -> anyhow::Result<StoreFileAuthorization> {
         let now = rt.unix_timestamp();
         if (now - issued) > MAX_TS_DELAY {
             anyhow::bail!("Could not issue authorization. Issued TS too far in past.");
         }
+        constraints.validate()?;
+        let store_file = StoreFileProto {
+            max_bytes: constraints.max_bytes,
+            allowed_content_types: constraints.allowed_content_types.clone(),
+            expires_s: constraints
+                .expires_in
+                .map(|expires_in| (issued + expires_in).as_secs()),
+        };
         Ok(StoreFileAuthorization(self.encryptor.encode_proto(
             STORE_FILE_AUTHZ_VERSION,
             StorageTokenProto {
                 instance_name: self.instance_name.clone(),
                 issued_s: issued.as_secs(),
-                authorization_type: Some(AuthorizationTypeProto::StoreFile(StoreFileProto {})),
+                authorization_type: Some(AuthorizationTypeProto::StoreFile(store_file)),
             },
         )))
     }
@@ -697,7 +986,7 @@ impl KeyBroker {
     /// Private helper method to generate an admin key.
     /// If `member_id` is None, it generates a system key, otherwise
     /// an admin key for the given user.
-    fn issue_key(&self, member_id: Option<MemberId>, is_read_only: bool) -> String {
+    fn issue_key(&self, member_id: Option<MemberId>, role: AdminRole) -> String {
         let now = SystemTime::now();
         let since_epoch = now
             .duration_since(SystemTime::UNIX_EPOCH)
@@ -711,7 +1000,8 @@ impl KeyBroker {
             instance_name: None,
             issued_s: since_epoch.as_secs(),
             identity: Some(identity),
-            is_read_only,
+            is_read_only: role == AdminRole::ReadOnly,
+            role: AdminRoleProto::from(role).into(),
         };
         format_admin_key(
             &self.instance_name,
@@ -738,6 +1028,7 @@ impl KeyBroker {
             issued_s,
             identity,
             is_read_only,
+            role,
         } = self
             .encryptor
             .decode_proto(ADMIN_KEY_VERSION, encrypted_part)
@@ -759,18 +1050,21 @@ impl KeyBroker {
                 instance_name: self.instance_name.clone(),
                 principal: AdminIdentityPrincipal::Member(MemberId(member_id)),
                 key: key.to_string(),
-                is_read_only,
+                role: AdminRole::from_proto(role, is_read_only)?,
             }),
             AdminIdentityProto::System(()) => Identity::system(),
         })
     }
 
+    /// Check an authorization to store a file, returning the constraints the
+    /// file must satisfy. Authorizations without their own expiry are valid
+    /// for `validity`.
     pub fn check_store_file_authorization<RT: Runtime>(
         &self,
         rt: &RT,
         store_file_authorization: &str,
         validity: Duration,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<UploadConstraints> {
         let StorageTokenProto {
             instance_name,
             issued_s,
@@ -791,8 +1085,21 @@ impl KeyBroker {
         }
 
         anyhow::ensure!(issued_s != 0, "Proto missing issued_s");
+        let Some(AuthorizationTypeProto::StoreFile(StoreFileProto {
+            max_bytes,
+            allowed_content_types,
+            expires_s,
+        })) = authorization_type
+        else {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "InvalidStorageToken",
+                "Storage token is for invalid instance {instance_name}"
+            ));
+        };
+
+        let expires_s = expires_s.unwrap_or(issued_s + validity.as_secs());
         let now = rt.unix_timestamp().as_secs();
-        if issued_s + validity.as_secs() <= now {
+        if expires_s <= now {
             log_store_file_auth_expired();
             anyhow::bail!(ErrorMetadata::unauthenticated(
                 "StorageTokenExpired",
@@ -800,14 +1107,11 @@ impl KeyBroker {
             ));
         }
 
-        let Some(AuthorizationTypeProto::StoreFile(StoreFileProto {})) = authorization_type else {
-            anyhow::bail!(ErrorMetadata::unauthenticated(
-                "InvalidStorageToken",
-                "Storage token is for invalid instance {instance_name}"
-            ));
-        };
-
-        Ok(())
+        Ok(UploadConstraints {
+            max_bytes,
+            allowed_content_types,
+            expires_in: Some(Duration::from_secs(expires_s.saturating_sub(issued_s))),
+        })
     }
 
     fn cursor_to_proto(&self, cursor: &Cursor) -> InstanceCursorProto {
@@ -910,6 +1214,73 @@ impl KeyBroker {
         }
     }
 
+    /// Issue an ID token for a user of the deployment's built-in auth, signed
+    /// with a key derived from the instance secret.
+    pub fn issue_user_token(
+        &self,
+        issuer: &str,
+        subject: String,
+        email: String,
+        email_verified: bool,
+        issued_at: SystemTime,
+        expiration: SystemTime,
+    ) -> anyhow::Result<String> {
+        let claims = CoreIdTokenClaims::new(
+            IssuerUrl::new(issuer.to_string())?,
+            vec![Audience::new(USER_TOKEN_AUDIENCE.to_string())],
+            expiration.into(),
+            issued_at.into(),
+            StandardClaims::new(SubjectIdentifier::new(subject))
+                .set_email(Some(EndUserEmail::new(email)))
+                .set_email_verified(Some(email_verified)),
+            EmptyAdditionalClaims {},
+        );
+        let token = CoreIdToken::new(
+            claims,
+            &CoreHmacKey::new(self.user_token_key.secret().as_bytes()),
+            CoreJwsSigningAlgorithm::HmacSha256,
+            None,
+            None,
+        )?;
+        Ok(token.to_string())
+    }
+
+    /// Check a user token issued by `issue_user_token`. Returns `None` if the
+    /// token isn't from `issuer`, so it should be checked against the
+    /// deployment's other auth providers instead.
+    pub fn check_user_token(
+        &self,
+        issuer: &str,
+        token: &str,
+        current_time: SystemTime,
+    ) -> anyhow::Result<Option<UserIdentity>> {
+        let issuer = IssuerUrl::new(issuer.to_string())?;
+        let Ok(token) = CoreIdToken::from_str(token) else {
+            return Ok(None);
+        };
+        let is_user_token = token
+            .claims(
+                &CoreIdTokenVerifier::new_insecure_without_verification(),
+                |_: Option<&Nonce>| Ok(()),
+            )
+            .is_ok_and(|claims| *claims.issuer() == issuer);
+        if !is_user_token {
+            return Ok(None);
+        }
+        let verifier = CoreIdTokenVerifier::new_confidential_client(
+            ClientId::new(USER_TOKEN_AUDIENCE.to_string()),
+            self.user_token_key.clone(),
+            issuer,
+            CoreJsonWebKeySet::new(vec![]),
+        )
+        .set_allowed_algs(vec![CoreJwsSigningAlgorithm::HmacSha256])
+        .set_time_fn(move || current_time.into());
+        let identity = UserIdentity::from_token(token, verifier).context(
+            ErrorMetadata::unauthenticated("InvalidUserToken", "The user token was invalid"),
+        )?;
+        Ok(Some(identity))
+    }
+
     pub fn issue_action_token(&self) -> ActionCallbackToken {
         let now = SystemTime::now();
         let since_epoch = now
@@ -978,12 +1349,17 @@ mod tests {
             MemberId,
             PersistenceVersion,
             TableName,
+            UdfType,
         },
         value::DeveloperDocumentId,
     };
-    use pb::convex_keys::{
-        admin_key::Identity as AdminIdentityProto,
-        AdminKey as AdminKeyProto,
+    use errors::ErrorMetadataAnyhowExt;
+    use pb::{
+        convex_identity::AdminRole as AdminRoleProto,
+        convex_keys::{
+            admin_key::Identity as AdminIdentityProto,
+            AdminKey as AdminKeyProto,
+        },
     };
     use pretty_assertions::assert_eq;
     use proptest::prelude::*;
@@ -996,7 +1372,11 @@ mod tests {
     };
     use crate::{
         AdminIdentity,
+        AdminOperation,
+        AdminRole,
         Identity,
+        InstanceSecret,
+        UploadConstraints,
     };
 
     #[test]
@@ -1009,6 +1389,74 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_admin_key_roles() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        for role in [AdminRole::Admin, AdminRole::Deploy, AdminRole::ReadOnly] {
+            let key = kb.issue_admin_key_with_role(MemberId(0), role);
+            let Identity::InstanceAdmin(admin) = kb.check_admin_key(&key.to_string())? else {
+                anyhow::bail!("Expected an admin identity");
+            };
+            assert_eq!(admin.role(), role);
+        }
+
+        let key = kb.issue_admin_key_with_role(MemberId(0), AdminRole::Deploy);
+        let deploy = kb.check_admin_key(&key.to_string())?;
+        deploy.ensure_admin_role_allows(AdminOperation::Deploy)?;
+        deploy
+            .ensure_admin_role_allows(AdminOperation::ReadData)
+            .unwrap_err();
+        deploy.ensure_can_run_function(UdfType::Query).unwrap_err();
+
+        let key = kb.issue_read_only_admin_key(MemberId(0));
+        let read_only = kb.check_admin_key(&key.to_string())?;
+        read_only.ensure_admin_role_allows(AdminOperation::ReadData)?;
+        read_only
+            .ensure_admin_role_allows(AdminOperation::Deploy)
+            .unwrap_err();
+        read_only.ensure_can_run_function(UdfType::Query)?;
+        read_only
+            .ensure_can_run_function(UdfType::Mutation)
+            .unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_user_tokens() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let issuer = "https://example.convex.site";
+        let now = SystemTime::now();
+        let token = kb.issue_user_token(
+            issuer,
+            "user1".to_string(),
+            "user1@example.com".to_string(),
+            true,
+            now,
+            now + Duration::from_secs(60),
+        )?;
+        let identity = kb
+            .check_user_token(issuer, &token, now)?
+            .expect("token should be from issuer");
+        assert_eq!(identity.subject, "user1");
+        assert_eq!(
+            identity.attributes.email.as_deref(),
+            Some("user1@example.com")
+        );
+        assert_eq!(identity.attributes.email_verified, Some(true));
+
+        // Tokens for other issuers are left to other auth providers.
+        assert!(kb
+            .check_user_token("https://other.convex.site", &token, now)?
+            .is_none());
+        assert!(kb.check_user_token(issuer, "not a token", now)?.is_none());
+
+        // Tokens signed by other deployments are rejected.
+        let other_kb = KeyBroker::new("other", InstanceSecret::random())?;
+        let err = other_kb.check_user_token(issuer, &token, now).unwrap_err();
+        assert_eq!(err.short_msg(), "InvalidUserToken");
+        Ok(())
+    }
+
     #[test]
     fn test_system_keys() -> anyhow::Result<()> {
         let kb = KeyBroker::dev();
@@ -1045,6 +1493,7 @@ mod tests {
             issued_s: since_epoch.as_secs(),
             identity: Some(identity),
             is_read_only: false,
+            role: AdminRoleProto::Unspecified.into(),
         };
         kb.encryptor.encode_proto(ADMIN_KEY_VERSION, proto)
     }
@@ -1062,8 +1511,41 @@ mod tests {
         let kb = KeyBroker::dev();
         let td = TestDriver::new();
         let now = td.rt().unix_timestamp();
-        let key = kb.issue_store_file_authorization(&td.rt(), now)?;
-        kb.check_store_file_authorization(&td.rt(), &key.to_string(), Duration::from_secs(60))?;
+        let key = kb.issue_store_file_authorization(&td.rt(), now, &Default::default())?;
+        let constraints =
+            kb.check_store_file_authorization(&td.rt(), &key.to_string(), Duration::from_secs(60))?;
+        assert_eq!(constraints.max_bytes, None);
+        assert!(constraints.allowed_content_types.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_file_authorization_constraints() -> anyhow::Result<()> {
+        let kb = KeyBroker::dev();
+        let td = TestDriver::new();
+        let now = td.rt().unix_timestamp();
+        let constraints = UploadConstraints {
+            max_bytes: Some(1024),
+            allowed_content_types: vec!["image/*".to_string()],
+            expires_in: Some(Duration::from_secs(120)),
+        };
+        let key = kb.issue_store_file_authorization(&td.rt(), now, &constraints)?;
+        // The token's own expiry overrides the default validity.
+        assert_eq!(
+            kb.check_store_file_authorization(&td.rt(), &key.to_string(), Duration::from_secs(60))?,
+            constraints
+        );
+
+        let expired = UploadConstraints {
+            expires_in: Some(Duration::from_secs(1)),
+            ..Default::default()
+        };
+        let key =
+            kb.issue_store_file_authorization(&td.rt(), now - Duration::from_secs(5), &expired)?;
+        let err = kb
+            .check_store_file_authorization(&td.rt(), &key.to_string(), Duration::from_secs(60))
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "StorageTokenExpired");
         Ok(())
     }
 
@@ -1072,7 +1554,7 @@ mod tests {
         let kb = KeyBroker::dev();
         let td = TestDriver::new();
         let hour_ago = td.rt().unix_timestamp() - Duration::from_secs(3600);
-        kb.issue_store_file_authorization(&td.rt(), hour_ago)
+        kb.issue_store_file_authorization(&td.rt(), hour_ago, &Default::default())
             .unwrap_err();
         Ok(())
     }
@@ -19,15 +19,7 @@ impl Encryptor {
     }
 
     pub fn encode_proto(&self, version: u8, message: impl Message) -> String {
-        let nonce = secretbox::gen_nonce();
-        let plaintext = message.encode_to_vec();
-        let ciphertext = secretbox::seal(&plaintext, &nonce, &self.secret);
-
-        let mut buffer = Vec::with_capacity(1 + nonce.0.len() + ciphertext.len());
-        buffer.push(version);
-        buffer.extend_from_slice(&nonce.0);
-        buffer.extend_from_slice(&ciphertext);
-        hex::encode(buffer)
+        hex::encode(self.seal(version, &message.encode_to_vec()))
     }
 
     pub fn decode_proto<M: Default + Message>(
@@ -36,7 +28,26 @@ impl Encryptor {
         encoded: &str,
     ) -> anyhow::Result<M> {
         let bytes = hex::decode(encoded)?;
-        let mut reader = &bytes[..];
+        let plaintext = self.open(version, &bytes)?;
+        Ok(M::decode(&*plaintext)?)
+    }
+
+    /// Encrypt `plaintext`, prefixing the ciphertext with `version` and a
+    /// freshly generated nonce.
+    pub fn seal(&self, version: u8, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = secretbox::gen_nonce();
+        let ciphertext = secretbox::seal(plaintext, &nonce, &self.secret);
+
+        let mut buffer = Vec::with_capacity(1 + nonce.0.len() + ciphertext.len());
+        buffer.push(version);
+        buffer.extend_from_slice(&nonce.0);
+        buffer.extend_from_slice(&ciphertext);
+        buffer
+    }
+
+    /// Decrypt bytes produced by [`Encryptor::seal`] with the same `version`.
+    pub fn open(&self, version: u8, bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut reader = bytes;
 
         let message_version = reader.read_u8()?;
         if message_version != version {
@@ -50,8 +61,7 @@ impl Encryptor {
         let mut ciphertext = Vec::with_capacity(bytes.len() - 1 - secretbox::NONCEBYTES);
         reader.read_to_end(&mut ciphertext)?;
 
-        let plaintext = secretbox::open(&ciphertext, &nonce, &self.secret)
-            .map_err(|_| anyhow::anyhow!("Failed to decrypt ciphertext"))?;
-        Ok(M::decode(&*plaintext)?)
+        secretbox::open(&ciphertext, &nonce, &self.secret)
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt ciphertext"))
     }
 }
@@ -2,13 +2,16 @@
 #![feature(lazy_cell)]
 #![feature(type_alias_impl_trait)]
 #![feature(impl_trait_in_assoc_type)]
+#![feature(let_chains)]
 
 mod broker;
 mod encryptor;
 mod metrics;
+pub mod password;
 mod secret;
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
+mod upload_constraints;
 
 pub use sync_types::UserIdentityAttributes;
 
@@ -16,6 +19,9 @@ pub use self::{
     broker::{
         AdminIdentity,
         AdminIdentityPrincipal,
+        AdminOperation,
+        AdminRole,
+        ApiKeyIdentity,
         GetFileAuthorization,
         Identity,
         KeyBroker,
@@ -28,6 +34,10 @@ pub use self::{
         InstanceSecret,
         Secret,
     },
+    upload_constraints::{
+        UploadConstraints,
+        MAX_UPLOAD_URL_EXPIRY,
+    },
 };
 
 pub const DEV_INSTANCE_NAME: &str = include_str!("../dev/instance_name.txt");
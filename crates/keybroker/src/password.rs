@@ -0,0 +1,46 @@
+use sodiumoxide::crypto::pwhash::argon2id13;
+
+/// Hash a password with Argon2id for storing. The hash includes its salt and
+/// parameters.
+pub fn hash_password(password: &str) -> anyhow::Result<String> {
+    let hash = argon2id13::pwhash(
+        password.as_bytes(),
+        argon2id13::OPSLIMIT_INTERACTIVE,
+        argon2id13::MEMLIMIT_INTERACTIVE,
+    )
+    .map_err(|()| anyhow::anyhow!("Failed to hash password"))?;
+    // The hash is a NUL-padded string.
+    let hash = hash.0.split(|b| *b == 0).next().unwrap_or_default();
+    Ok(String::from_utf8(hash.to_vec())?)
+}
+
+/// Check a password against a hash from `hash_password`.
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    let mut padded = [0; argon2id13::HASHEDPASSWORDBYTES];
+    if hash.len() >= padded.len() {
+        return false;
+    }
+    padded[..hash.len()].copy_from_slice(hash.as_bytes());
+    argon2id13::pwhash_verify(&argon2id13::HashedPassword(padded), password.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        hash_password,
+        verify_password,
+    };
+
+    #[test]
+    fn test_password_hash() -> anyhow::Result<()> {
+        let hash = hash_password("correct horse battery staple")?;
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password(&hash, "correct horse battery staple"));
+        assert!(!verify_password(&hash, "Tr0ub4dor&3"));
+        assert!(!verify_password(
+            "not a hash",
+            "correct horse battery staple"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use errors::ErrorMetadata;
+
+/// Upload URLs can be valid for at most a day.
+pub const MAX_UPLOAD_URL_EXPIRY: Duration = Duration::from_secs(60 * 60 * 24);
+pub const MAX_ALLOWED_CONTENT_TYPES: usize = 32;
+
+/// Restrictions an app puts on the file uploaded to an upload URL. They're
+/// part of the URL's token, so clients can't remove them, and are checked
+/// when the upload arrives.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UploadConstraints {
+    pub max_bytes: Option<u64>,
+    // Content types like `image/png`, or `image/*` for all subtypes. Any
+    // content type is allowed if empty.
+    pub allowed_content_types: Vec<String>,
+    // How long the URL is valid for, instead of the default.
+    pub expires_in: Option<Duration>,
+}
+
+impl UploadConstraints {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid = |msg: String| {
+            anyhow::anyhow!(ErrorMetadata::bad_request("InvalidUploadConstraints", msg))
+        };
+        if self.max_bytes == Some(0) {
+            return Err(invalid("maxBytes must be positive".to_string()));
+        }
+        if self.allowed_content_types.len() > MAX_ALLOWED_CONTENT_TYPES {
+            return Err(invalid(format!(
+                "At most {MAX_ALLOWED_CONTENT_TYPES} content types can be allowed"
+            )));
+        }
+        if let Some(content_type) = self
+            .allowed_content_types
+            .iter()
+            .find(|content_type| !is_content_type_pattern(content_type))
+        {
+            return Err(invalid(format!(
+                "Invalid content type {content_type:?}, expected e.g. \"image/png\" or \"image/*\""
+            )));
+        }
+        if let Some(expires_in) = self.expires_in
+            && !(Duration::from_secs(1)..=MAX_UPLOAD_URL_EXPIRY).contains(&expires_in)
+        {
+            return Err(invalid(format!(
+                "Upload URLs must expire between 1 second and {} hours from now",
+                MAX_UPLOAD_URL_EXPIRY.as_secs() / 3600
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check the Content-Type of an upload, ignoring parameters like
+    /// `charset`.
+    pub fn check_content_type(&self, content_type: Option<&str>) -> anyhow::Result<()> {
+        if self.allowed_content_types.is_empty() {
+            return Ok(());
+        }
+        let essence = content_type
+            .map(|content_type| {
+                content_type
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_ascii_lowercase()
+            })
+            .unwrap_or_default();
+        let allowed = self.allowed_content_types.iter().any(|pattern| {
+            let pattern = pattern.to_ascii_lowercase();
+            match pattern.strip_suffix('*') {
+                Some(prefix) => essence.starts_with(prefix),
+                None => essence == pattern,
+            }
+        });
+        if !allowed {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ContentTypeNotAllowed",
+                format!(
+                    "Content-Type {content_type:?} is not allowed by the upload URL, expected one \
+                     of {:?}",
+                    self.allowed_content_types
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check the size of an upload, or of the part of it received so far.
+    pub fn check_size(&self, size: u64) -> anyhow::Result<()> {
+        if let Some(max_bytes) = self.max_bytes
+            && size > max_bytes
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "FileTooLarge",
+                format!("The upload URL only allows files of up to {max_bytes} bytes"),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_content_type_pattern(s: &str) -> bool {
+    let is_token = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    match s.split_once('/') {
+        Some((type_, "*")) => is_token(type_),
+        Some((type_, subtype)) => is_token(type_) && is_token(subtype),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::UploadConstraints;
+
+    #[test]
+    fn test_validate() {
+        let images = UploadConstraints {
+            max_bytes: Some(1024),
+            allowed_content_types: vec!["image/*".to_string(), "application/pdf".to_string()],
+            expires_in: Some(Duration::from_secs(60)),
+        };
+        assert!(images.validate().is_ok());
+        assert!(UploadConstraints::default().validate().is_ok());
+        for invalid in [
+            UploadConstraints {
+                max_bytes: Some(0),
+                ..Default::default()
+            },
+            UploadConstraints {
+                allowed_content_types: vec!["image".to_string()],
+                ..Default::default()
+            },
+            UploadConstraints {
+                allowed_content_types: vec!["*/*; charset=utf-8".to_string()],
+                ..Default::default()
+            },
+            UploadConstraints {
+                expires_in: Some(Duration::from_secs(60 * 60 * 48)),
+                ..Default::default()
+            },
+        ] {
+            assert!(invalid.validate().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_upload() {
+        let constraints = UploadConstraints {
+            max_bytes: Some(10),
+            allowed_content_types: vec!["image/*".to_string(), "text/plain".to_string()],
+            expires_in: None,
+        };
+        assert!(constraints.check_content_type(Some("image/png")).is_ok());
+        assert!(constraints
+            .check_content_type(Some("Text/Plain; charset=utf-8"))
+            .is_ok());
+        assert!(constraints.check_content_type(Some("text/html")).is_err());
+        assert!(constraints.check_content_type(None).is_err());
+        assert!(constraints.check_size(10).is_ok());
+        assert!(constraints.check_size(11).is_err());
+
+        let unconstrained = UploadConstraints::default();
+        assert!(unconstrained.check_content_type(None).is_ok());
+        assert!(unconstrained.check_size(u64::MAX).is_ok());
+    }
+}
@@ -4,6 +4,7 @@ use common::types::MemberId;
 use errors::ErrorMetadata;
 use keybroker::{
     AdminIdentityPrincipal,
+    AdminOperation,
     Identity,
     KeyBroker,
 };
@@ -24,7 +25,13 @@ pub async fn must_be_admin_from_key_with_write_access(
     instance_name: String,
     admin_key: String,
 ) -> anyhow::Result<Identity> {
-    must_be_admin_from_key_internal(app_auth, instance_name, admin_key, true).await
+    must_be_admin_from_key_for(
+        app_auth,
+        instance_name,
+        admin_key,
+        AdminOperation::WriteData,
+    )
+    .await
 }
 
 pub async fn must_be_admin_from_key(
@@ -32,21 +39,28 @@ pub async fn must_be_admin_from_key(
     instance_name: String,
     admin_key: String,
 ) -> anyhow::Result<Identity> {
-    must_be_admin_from_key_internal(app_auth, instance_name, admin_key, false).await
+    must_be_admin_from_key_for(
+        app_auth,
+        instance_name,
+        admin_key,
+        AdminOperation::ViewConfig,
+    )
+    .await
 }
 
-async fn must_be_admin_from_key_internal(
+/// Check an admin key, and that its role allows `operation`.
+pub async fn must_be_admin_from_key_for(
     app_auth: &ApplicationAuth,
     instance_name: String,
     admin_key_or_access_token: String,
-    needs_write_access: bool,
+    operation: AdminOperation,
 ) -> anyhow::Result<Identity> {
     let identity = app_auth
         .check_key(admin_key_or_access_token, instance_name.clone())
         .await
         .context(bad_admin_key_error(Some(instance_name)))?;
-    if needs_write_access {
-        must_be_admin_with_write_access(&identity)?;
+    if operation != AdminOperation::ViewConfig {
+        must_be_admin_for(&identity, operation)?;
     }
     Ok(identity)
 }
@@ -54,21 +68,20 @@ async fn must_be_admin_from_key_internal(
 pub fn must_be_admin_with_write_access(
     identity: &Identity,
 ) -> anyhow::Result<AdminIdentityPrincipal> {
-    must_be_admin_internal(identity, true)
+    must_be_admin_for(identity, AdminOperation::WriteData)
 }
 
 pub fn must_be_admin(identity: &Identity) -> anyhow::Result<AdminIdentityPrincipal> {
-    must_be_admin_internal(identity, false)
+    must_be_admin_for(identity, AdminOperation::ViewConfig)
 }
 
-fn must_be_admin_internal(
+/// Check that `identity` is an admin whose role allows `operation`.
+pub fn must_be_admin_for(
     identity: &Identity,
-    needs_write_access: bool,
+    operation: AdminOperation,
 ) -> anyhow::Result<AdminIdentityPrincipal> {
     if let Identity::InstanceAdmin(admin_identity) = identity {
-        if needs_write_access && admin_identity.is_read_only() {
-            return Err(read_only_admin_key_error().into());
-        }
+        admin_identity.role().ensure_allows(operation)?;
         Ok(admin_identity.principal().clone())
     } else {
         Err(bad_admin_key_error(identity.instance_name()).into())
@@ -76,22 +89,22 @@ fn must_be_admin_internal(
 }
 
 pub fn must_be_admin_member_with_write_access(identity: &Identity) -> anyhow::Result<MemberId> {
-    must_be_admin_member_internal(identity, true)
+    must_be_admin_member_for(identity, AdminOperation::WriteData)
 }
 
 pub fn must_be_admin_member(identity: &Identity) -> anyhow::Result<MemberId> {
-    must_be_admin_member_internal(identity, false)
+    must_be_admin_member_for(identity, AdminOperation::ViewConfig)
 }
 
-fn must_be_admin_member_internal(
+/// Check that `identity` is a team member's admin key whose role allows
+/// `operation`.
+pub fn must_be_admin_member_for(
     identity: &Identity,
-    needs_write_access: bool,
+    operation: AdminOperation,
 ) -> anyhow::Result<MemberId> {
     if let Identity::InstanceAdmin(admin_identity) = identity {
         if let AdminIdentityPrincipal::Member(member_id) = admin_identity.principal() {
-            if needs_write_access && admin_identity.is_read_only() {
-                return Err(read_only_admin_key_error().into());
-            }
+            admin_identity.role().ensure_allows(operation)?;
             Ok(*member_id)
         } else {
             Err(bad_admin_key_error(identity.instance_name()).into())
@@ -114,10 +127,3 @@ pub fn bad_admin_key_error(instance_name: Option<String>) -> ErrorMetadata {
     };
     ErrorMetadata::forbidden("BadDeployKey", msg)
 }
-
-pub fn read_only_admin_key_error() -> ErrorMetadata {
-    ErrorMetadata::forbidden(
-        "ReadOnlyAdminKey",
-        "You do not have permission to perform this operation.",
-    )
-}
@@ -0,0 +1,364 @@
+//! Restrictions on where the admin API can be called from, for deployments
+//! that must not expose it on shared networks. Admin requests can be limited
+//! to clients on allowlisted networks and, when the backend terminates TLS, to
+//! clients presenting a certificate signed by a configured CA. Admin keys
+//! can't be used to call functions through the public API from anywhere else,
+//! whether as an admin or acting as a user. The rest of the API is unaffected.
+//!
+//! Clients are identified by the address of their connection, not by
+//! forwarding headers, so the allowlist should name the proxy's network when
+//! the backend is behind one.
+
+use std::{
+    net::{
+        IpAddr,
+        SocketAddr,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use axum::{
+    extract::{
+        ConnectInfo,
+        State,
+    },
+    middleware::Next,
+    response::{
+        IntoResponse,
+        Response,
+    },
+    Extension,
+    RequestPartsExt,
+    Router,
+};
+use common::http::HttpResponseError;
+use errors::ErrorMetadata;
+use futures::FutureExt;
+use http::Request;
+use hyper::{
+    server::conn::Http,
+    service::service_fn,
+    Body,
+};
+use ipnet::IpNet;
+use rustls::{
+    server::{
+        AllowAnyAnonymousOrAuthenticatedClient,
+        ClientHello,
+        ResolvesServerCert,
+    },
+    sign::CertifiedKey,
+    RootCertStore,
+    ServerConfig,
+};
+use sync_types::AuthenticationToken;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tower::Service;
+
+use crate::{
+    authentication::ExtractAuthenticationToken,
+    custom_domains::{
+        load_certified_key,
+        open_pem,
+    },
+    LocalAppState,
+};
+
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Who may call the admin API. The default allows everyone.
+#[derive(Clone, Debug, Default)]
+pub struct AdminAccessPolicy {
+    /// Networks admin requests may come from, or any network if empty.
+    pub allowed_networks: Vec<IpNet>,
+    /// Whether admin requests must come over a connection authenticated with
+    /// a client certificate.
+    pub require_client_cert: bool,
+}
+
+impl AdminAccessPolicy {
+    fn check(&self, remote_ip: Option<IpAddr>, client_cert: bool) -> anyhow::Result<()> {
+        if !self.allowed_networks.is_empty()
+            && !remote_ip.is_some_and(|ip| {
+                self.allowed_networks
+                    .iter()
+                    .any(|network| network.contains(&ip))
+            })
+        {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "AdminNetworkNotAllowed",
+                "The admin API can't be called from this network",
+            ));
+        }
+        if self.require_client_cert && !client_cert {
+            anyhow::bail!(ErrorMetadata::forbidden(
+                "ClientCertificateRequired",
+                "The admin API requires a TLS client certificate",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Marks requests on a TLS connection whose client presented a certificate
+/// signed by the admin client CA.
+#[derive(Clone, Copy, Debug)]
+pub struct VerifiedClientCert;
+
+/// Rejects admin requests that the deployment's [`AdminAccessPolicy`] doesn't
+/// allow.
+pub async fn admin_access<B: Send>(
+    State(st): State<LocalAppState>,
+    remote_addr: Option<ConnectInfo<SocketAddr>>,
+    client_cert: Option<Extension<VerifiedClientCert>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let remote_ip = remote_addr.map(|ConnectInfo(addr)| addr.ip());
+    if let Err(e) = st.admin_access.check(remote_ip, client_cert.is_some()) {
+        return HttpResponseError::from(e).into_response();
+    }
+    next.run(req).await
+}
+
+/// Marks public API requests whose connection the deployment's
+/// [`AdminAccessPolicy`] doesn't allow, so a sync client can't authenticate
+/// with an admin key after connecting.
+#[derive(Clone, Copy, Debug)]
+pub struct AdminAuthNotAllowed;
+
+/// Rejects public API requests authenticated with an admin key, including to
+/// act as a user, that the deployment's [`AdminAccessPolicy`] doesn't allow.
+/// Requests that authenticate some other way are unaffected.
+pub async fn admin_auth_access<B: Send>(
+    State(policy): State<Arc<AdminAccessPolicy>>,
+    remote_addr: Option<ConnectInfo<SocketAddr>>,
+    client_cert: Option<Extension<VerifiedClientCert>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let remote_ip = remote_addr.map(|ConnectInfo(addr)| addr.ip());
+    let Err(e) = policy.check(remote_ip, client_cert.is_some()) else {
+        return next.run(req).await;
+    };
+    let (mut parts, body) = req.into_parts();
+    if let Ok(ExtractAuthenticationToken(AuthenticationToken::Admin(..))) =
+        parts.extract::<ExtractAuthenticationToken>().await
+    {
+        return HttpResponseError::from(e).into_response();
+    }
+    parts.extensions.insert(AdminAuthNotAllowed);
+    next.run(Request::from_parts(parts, body)).await
+}
+
+/// Certificate and private key to serve the backend's API over TLS with, and
+/// optionally the CA that signs admin client certificates.
+#[derive(Clone, Debug)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    pub client_ca_path: Option<PathBuf>,
+}
+
+struct ServerCert(Arc<CertifiedKey>);
+
+impl ResolvesServerCert for ServerCert {
+    fn resolve(&self, _: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.clone())
+    }
+}
+
+fn load_client_ca(path: &Path) -> anyhow::Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    let certs = rustls_pemfile::certs(&mut open_pem(path)?)?;
+    anyhow::ensure!(!certs.is_empty(), "No certificates in {}", path.display());
+    let (_, ignored) = roots.add_parsable_certificates(&certs);
+    anyhow::ensure!(ignored == 0, "Invalid certificate in {}", path.display());
+    Ok(roots)
+}
+
+fn tls_acceptor(config: &TlsConfig) -> anyhow::Result<TlsAcceptor> {
+    let key = load_certified_key(&config.cert_path, &config.key_path)
+        .context("Failed to load TLS certificate")?;
+    let builder = ServerConfig::builder().with_safe_defaults();
+    // Client certificates are optional at the TLS layer, since only the admin
+    // API requires them. Connections that present an invalid one fail the
+    // handshake.
+    let builder = match &config.client_ca_path {
+        Some(path) => {
+            let roots = load_client_ca(path).context("Failed to load client CA")?;
+            builder.with_client_cert_verifier(
+                AllowAnyAnonymousOrAuthenticatedClient::new(roots).boxed(),
+            )
+        },
+        None => builder.with_no_client_auth(),
+    };
+    let mut tls_config = builder.with_cert_resolver(Arc::new(ServerCert(Arc::new(key))));
+    tls_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(tls_config)))
+}
+
+/// Serves `router` over TLS. Requests on connections authenticated with a
+/// client certificate are marked with [`VerifiedClientCert`].
+pub async fn serve_tls(
+    router: Router,
+    bind_addr: SocketAddr,
+    config: TlsConfig,
+    mut shutdown_rx: async_broadcast::Receiver<()>,
+) -> anyhow::Result<()> {
+    let acceptor = tls_acceptor(&config)?;
+    let listener = TcpListener::bind(bind_addr).await?;
+    tracing::info!("Listening on https://{bind_addr}");
+    let http = Http::new();
+    loop {
+        let (stream, remote_addr) = futures::select_biased! {
+            _ = shutdown_rx.recv().fuse() => break,
+            r = listener.accept().fuse() => match r {
+                Ok(r) => r,
+                Err(e) => {
+                    tracing::warn!("Failed to accept connection: {e}");
+                    continue;
+                },
+            },
+        };
+        let _ = stream.set_nodelay(true);
+        let acceptor = acceptor.clone();
+        let router = router.clone();
+        let http = http.clone();
+        tokio::spawn(async move {
+            let stream =
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        tracing::debug!("TLS handshake with {remote_addr} failed: {e}");
+                        return;
+                    },
+                    Err(_) => {
+                        tracing::debug!("TLS handshake with {remote_addr} timed out");
+                        return;
+                    },
+                };
+            let client_cert = stream.get_ref().1.peer_certificates().is_some();
+            let service = service_fn(move |mut req: Request<Body>| {
+                req.extensions_mut().insert(ConnectInfo(remote_addr));
+                if client_cert {
+                    req.extensions_mut().insert(VerifiedClientCert);
+                }
+                router.clone().call(req)
+            });
+            if let Err(e) = http.serve_connection(stream, service).with_upgrades().await {
+                tracing::debug!("Error serving connection from {remote_addr}: {e}");
+            }
+        });
+    }
+    tracing::info!("HTTPS server shutdown complete");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        routing::get,
+        Extension,
+        Router,
+    };
+    use errors::ErrorMetadataAnyhowExt;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use tower::ServiceExt;
+
+    use super::{
+        admin_auth_access,
+        AdminAccessPolicy,
+        AdminAuthNotAllowed,
+    };
+
+    #[test]
+    fn test_admin_access_policy() -> anyhow::Result<()> {
+        let open = AdminAccessPolicy::default();
+        open.check(None, false)?;
+
+        let policy = AdminAccessPolicy {
+            allowed_networks: vec!["10.0.0.0/8".parse()?, "::1/128".parse()?],
+            require_client_cert: false,
+        };
+        policy.check(Some("10.1.2.3".parse()?), false)?;
+        policy.check(Some("::1".parse()?), false)?;
+        let err = policy
+            .check(Some("192.168.1.1".parse()?), false)
+            .unwrap_err();
+        assert!(err.is_forbidden());
+        assert_eq!(err.short_msg(), "AdminNetworkNotAllowed");
+        // Requests without a known address are rejected.
+        assert!(policy.check(None, false).is_err());
+
+        let policy = AdminAccessPolicy {
+            allowed_networks: vec![],
+            require_client_cert: true,
+        };
+        policy.check(None, true)?;
+        let err = policy.check(None, false).unwrap_err();
+        assert_eq!(err.short_msg(), "ClientCertificateRequired");
+        Ok(())
+    }
+    #[tokio::test]
+    async fn test_admin_auth_access() -> anyhow::Result<()> {
+        let policy = AdminAccessPolicy {
+            allowed_networks: vec!["10.0.0.0/8".parse()?],
+            require_client_cert: false,
+        };
+        let router = Router::new()
+            .route(
+                "/query",
+                get(
+                    |marker: Option<Extension<AdminAuthNotAllowed>>| async move {
+                        if marker.is_some() {
+                            "not allowed"
+                        } else {
+                            "allowed"
+                        }
+                    },
+                ),
+            )
+            .route_layer(axum::middleware::from_fn_with_state(
+                Arc::new(policy),
+                admin_auth_access,
+            ));
+        let send = |authorization: Option<&str>| {
+            let mut req = Request::get("/query");
+            if let Some(authorization) = authorization {
+                req = req.header(http::header::AUTHORIZATION, authorization);
+            }
+            router.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+
+        // Admins and admins acting as users are rejected.
+        for authorization in [
+            "Convex admin-key",
+            "Convex admin-key:eyJ0b2tlbklkZW50aWZpZXIiOiJ1c2VyIn0=",
+        ] {
+            let response = send(Some(authorization)).await?;
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+        // Other requests go through, but can't authenticate as an admin later.
+        for authorization in [None, Some("Bearer token")] {
+            let response = send(authorization).await?;
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await?;
+            assert_eq!(&body[..], b"not allowed");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! Admin API for managing deployment API keys, which other servers can use to
+//! call the deployment's functions with a limited set of permissions. Keys are
+//! sent as `Authorization: Bearer <key>`.
+
+use std::collections::BTreeSet;
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    auth::ApiKeyScope,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use http::StatusCode;
+use model::{
+    api_keys::{
+        types::ApiKeyName,
+        ApiKeyModel,
+    },
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    parse::parse_udf_path,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKeyRequest {
+    name: String,
+    /// Only allow the key to run queries.
+    #[serde(default)]
+    read_only: bool,
+    /// Paths of the functions the key may call, or all functions if omitted.
+    functions: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKeyResponse {
+    id: String,
+    name: String,
+    key: String,
+}
+
+pub async fn create_api_key(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CreateApiKeyRequest {
+        name,
+        read_only,
+        functions,
+    }): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let name: ApiKeyName = name.parse()?;
+    let functions = functions
+        .map(|functions| {
+            functions
+                .iter()
+                .map(|path| Ok(parse_udf_path(path)?.canonicalize()))
+                .collect::<anyhow::Result<BTreeSet<_>>>()
+        })
+        .transpose()?;
+    let scope = ApiKeyScope {
+        read_only,
+        functions,
+    };
+
+    let mut tx = st.application.begin(identity).await?;
+    let (id, key) = ApiKeyModel::new(&mut tx)
+        .create(name.clone(), scope)
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::CreateApiKey { name: name.clone() }],
+            "create_api_key",
+        )
+        .await?;
+    Ok(Json(CreateApiKeyResponse {
+        id: id.to_string(),
+        name: name.into(),
+        key,
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyResponse {
+    id: String,
+    name: String,
+    read_only: bool,
+    functions: Option<Vec<String>>,
+    creation_time: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListApiKeysResponse {
+    api_keys: Vec<ApiKeyResponse>,
+}
+
+pub async fn list_api_keys(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let api_keys = ApiKeyModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|doc| {
+            let id = doc.id().to_string();
+            let creation_time = doc.creation_time().map(f64::from);
+            let metadata = doc.into_value();
+            ApiKeyResponse {
+                id,
+                name: metadata.name.into(),
+                read_only: metadata.scope.read_only,
+                functions: metadata
+                    .scope
+                    .functions
+                    .map(|functions| functions.into_iter().map(String::from).collect()),
+                creation_time,
+            }
+        })
+        .collect();
+    Ok(Json(ListApiKeysResponse { api_keys }))
+}
+
+#[derive(Deserialize)]
+pub struct DeleteApiKeyRequest {
+    name: String,
+}
+
+pub async fn delete_api_key(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteApiKeyRequest { name }): Json<DeleteApiKeyRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let name: ApiKeyName = name.parse()?;
+    let mut tx = st.application.begin(identity).await?;
+    ApiKeyModel::new(&mut tx).delete(&name).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteApiKey { name }],
+            "delete_api_key",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use application::test_helpers::ApplicationTestExt;
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        TestLocalBackend,
+    };
+
+    async fn create_api_key(backend: &TestLocalBackend, body: JsonValue) -> anyhow::Result<String> {
+        let req = Request::builder()
+            .uri("/api/create_api_key")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(Body::from(serde_json::to_vec(&body)?))?;
+        let response: JsonValue = backend.expect_success(req).await?;
+        Ok(response["key"].as_str().unwrap().to_string())
+    }
+
+    fn function_request(
+        endpoint: &str,
+        key: &str,
+        path: &str,
+        args: JsonValue,
+    ) -> anyhow::Result<Request<Body>> {
+        let body = json!({ "path": path, "args": args, "format": "json" });
+        Ok(Request::builder()
+            .uri(endpoint)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Host", "localhost")
+            .header("Authorization", format!("Bearer {key}"))
+            .body(Body::from(serde_json::to_vec(&body)?))?)
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_api_key_scope(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let key = create_api_key(
+            &backend,
+            json!({
+                "name": "balances",
+                "readOnly": true,
+                "functions": ["sync:accountBalance", "internal:myInternalQuery"],
+            }),
+        )
+        .await?;
+
+        let args = json!({ "name": "orinoco" });
+        let result: JsonValue = backend
+            .expect_success(function_request(
+                "/api/query",
+                &key,
+                "sync:accountBalance",
+                args.clone(),
+            )?)
+            .await?;
+        assert_eq!(result["status"], "success");
+        // Keys can call internal functions in their scope.
+        let result: JsonValue = backend
+            .expect_success(function_request(
+                "/api/query",
+                &key,
+                "internal:myInternalQuery",
+                json!({}),
+            )?)
+            .await?;
+        assert_eq!(result["status"], "success");
+
+        backend
+            .expect_error(
+                function_request("/api/query", &key, "values:intQuery", json!({}))?,
+                StatusCode::FORBIDDEN,
+                "Unauthorized",
+            )
+            .await?;
+        backend
+            .expect_error(
+                function_request(
+                    "/api/mutation",
+                    &key,
+                    "sync:initialize",
+                    json!({ "name": "orinoco", "balance": 100.0 }),
+                )?,
+                StatusCode::FORBIDDEN,
+                "Unauthorized",
+            )
+            .await?;
+        backend
+            .expect_error(
+                function_request(
+                    "/api/query",
+                    "convex_api_key_invalid",
+                    "sync:accountBalance",
+                    args,
+                )?,
+                StatusCode::UNAUTHORIZED,
+                "InvalidApiKey",
+            )
+            .await?;
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_delete_api_key(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let key = create_api_key(&backend, json!({ "name": "everything" })).await?;
+        let result: JsonValue = backend
+            .expect_success(function_request(
+                "/api/mutation",
+                &key,
+                "values:intMutation",
+                json!({}),
+            )?)
+            .await?;
+        assert_eq!(result["status"], "success");
+
+        let req = Request::builder()
+            .uri("/api/delete_api_key")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(Body::from(serde_json::to_vec(
+                &json!({ "name": "everything" }),
+            )?))?;
+        let _: JsonValue = backend.expect_success(req).await?;
+        backend
+            .expect_error(
+                function_request("/api/mutation", &key, "values:intMutation", json!({}))?,
+                StatusCode::UNAUTHORIZED,
+                "InvalidApiKey",
+            )
+            .await?;
+        Ok(())
+    }
+}
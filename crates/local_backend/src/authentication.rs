@@ -52,31 +52,7 @@ impl<T> FromRequestParts<T> for ExtractAuthenticationToken {
                 "HeaderParseFailure",
                 format!("Failed to parse header {h:?}"),
             ))?;
-            let is_admin_key = h_str
-                .get(..7)
-                .ok_or_else(|| anyhow!("Invalid Header"))
-                .context(ErrorMetadata::bad_request(
-                    "InvalidHeaderFailure",
-                    format!("Invalid authentication header"),
-                ))?
-                .eq_ignore_ascii_case("convex ");
-
-            return if is_admin_key {
-                // This is an admin key, not an OIDC bearer token. These are sent from the
-                // dashboard in lieu of our old cookie-based auth.
-                Ok(Self(extract_admin_key(h_str)?))
-            } else {
-                let auth: String = extract_bearer_token(Some(h_str.to_string()))
-                    .await
-                    .map_err(|_| {
-                        anyhow::anyhow!(ErrorMetadata::bad_request(
-                            "InvalidAdminKey",
-                            "Invalid admin key",
-                        ))
-                    })?
-                    .unwrap();
-                Ok(Self(AuthenticationToken::User(auth)))
-            };
+            return Ok(Self(parse_authorization_header(h_str).await?));
         }
 
         // If no header is provided, also allow extracting admin key from query param.
@@ -96,6 +72,36 @@ impl<T> FromRequestParts<T> for ExtractAuthenticationToken {
     }
 }
 
+/// Parse the value of an `Authorization` header, which is either a
+/// `Convex <admin key>` or a `Bearer <token>`.
+pub async fn parse_authorization_header(h_str: &str) -> anyhow::Result<AuthenticationToken> {
+    let is_admin_key = h_str
+        .get(..7)
+        .ok_or_else(|| anyhow!("Invalid Header"))
+        .context(ErrorMetadata::bad_request(
+            "InvalidHeaderFailure",
+            format!("Invalid authentication header"),
+        ))?
+        .eq_ignore_ascii_case("convex ");
+
+    if is_admin_key {
+        // This is an admin key, not an OIDC bearer token. These are sent from the
+        // dashboard in lieu of our old cookie-based auth.
+        extract_admin_key(h_str)
+    } else {
+        let auth: String = extract_bearer_token(Some(h_str.to_string()))
+            .await
+            .map_err(|_| {
+                anyhow::anyhow!(ErrorMetadata::bad_request(
+                    "InvalidAdminKey",
+                    "Invalid admin key",
+                ))
+            })?
+            .unwrap();
+        Ok(AuthenticationToken::User(auth))
+    }
+}
+
 impl From<ExtractAuthenticationToken> for AuthenticationToken {
     fn from(token: ExtractAuthenticationToken) -> Self {
         token.0
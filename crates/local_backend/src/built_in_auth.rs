@@ -0,0 +1,222 @@
+//! Public API of the deployment's built-in auth, for apps to sign users in
+//! with an email address and password or with a magic link. Each endpoint
+//! that signs a user in returns a token to use as the client's auth token.
+//!
+//! Signing in with a password and sending magic links are rate limited for
+//! each client and for each email address, to slow down password guessing and
+//! stop the deployment from being used to flood an inbox.
+
+use std::net::SocketAddr;
+
+use application::built_in_auth::BuiltInAuthSession;
+use axum::{
+    extract::{
+        ConnectInfo,
+        State,
+    },
+    response::{
+        IntoResponse,
+        Response,
+    },
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    knobs::{
+        BUILT_IN_AUTH_CLIENT_RATE_LIMIT,
+        BUILT_IN_AUTH_EMAIL_RATE_LIMIT,
+    },
+};
+use http::{
+    HeaderMap,
+    StatusCode,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    authentication::ExtractIdentity,
+    rate_limiter::{
+        too_many_requests,
+        RateLimiter,
+    },
+    LocalAppState,
+};
+
+/// Rate limits on the built-in auth endpoints that check passwords or send
+/// emails.
+pub struct BuiltInAuthRateLimiter {
+    pub by_client: RateLimiter,
+    pub by_email: RateLimiter,
+}
+
+impl BuiltInAuthRateLimiter {
+    pub fn from_knobs() -> Self {
+        Self {
+            by_client: RateLimiter::from_knob(
+                "built_in_auth_client",
+                "BUILT_IN_AUTH_CLIENT_RATE_LIMIT",
+                &BUILT_IN_AUTH_CLIENT_RATE_LIMIT,
+            ),
+            by_email: RateLimiter::from_knob(
+                "built_in_auth_email",
+                "BUILT_IN_AUTH_EMAIL_RATE_LIMIT",
+                &BUILT_IN_AUTH_EMAIL_RATE_LIMIT,
+            ),
+        }
+    }
+
+    /// Take a token for the client that sent a request and for the email
+    /// address in it, returning a 429 response if either is over its limit.
+    fn check(
+        &self,
+        headers: &HeaderMap,
+        remote_addr: Option<ConnectInfo<SocketAddr>>,
+        email: &str,
+    ) -> Result<(), Response> {
+        let remote_addr = remote_addr.map(|connect_info| connect_info.0);
+        self.by_client
+            .check(headers, remote_addr, None)
+            .map_err(too_many_requests)?;
+        self.by_email
+            .check_key(&format!("email:{}", email.trim().to_lowercase()))
+            .map_err(too_many_requests)?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PasswordRequest {
+    email: String,
+    password: String,
+}
+
+#[derive(Deserialize)]
+pub struct EmailRequest {
+    email: String,
+}
+
+#[derive(Deserialize)]
+pub struct CodeRequest {
+    code: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResponse {
+    token: String,
+    /// When the token expires, in milliseconds since the epoch.
+    expires_at: u64,
+}
+
+impl TryFrom<BuiltInAuthSession> for SessionResponse {
+    type Error = anyhow::Error;
+
+    fn try_from(session: BuiltInAuthSession) -> anyhow::Result<Self> {
+        Ok(Self {
+            token: session.token,
+            expires_at: session.expires_at.as_ms_since_epoch()?,
+        })
+    }
+}
+
+pub async fn sign_up(
+    State(st): State<LocalAppState>,
+    Json(PasswordRequest { email, password }): Json<PasswordRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let session = st.application.sign_up(&email, &password).await?;
+    Ok(Json(SessionResponse::try_from(session)?))
+}
+
+pub async fn sign_in(
+    State(st): State<LocalAppState>,
+    remote_addr: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(PasswordRequest { email, password }): Json<PasswordRequest>,
+) -> Result<Response, HttpResponseError> {
+    if let Err(response) = st
+        .built_in_auth_rate_limiter
+        .check(&headers, remote_addr, &email)
+    {
+        return Ok(response);
+    }
+    let session = st.application.sign_in(&email, &password).await?;
+    Ok(Json(SessionResponse::try_from(session)?).into_response())
+}
+
+pub async fn send_magic_link(
+    State(st): State<LocalAppState>,
+    remote_addr: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(EmailRequest { email }): Json<EmailRequest>,
+) -> Result<Response, HttpResponseError> {
+    if let Err(response) = st
+        .built_in_auth_rate_limiter
+        .check(&headers, remote_addr, &email)
+    {
+        return Ok(response);
+    }
+    st.application.send_magic_link(&email).await?;
+    Ok(StatusCode::OK.into_response())
+}
+
+pub async fn verify_email(
+    State(st): State<LocalAppState>,
+    Json(CodeRequest { code }): Json<CodeRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let session = st.application.verify_email(&code).await?;
+    Ok(Json(SessionResponse::try_from(session)?))
+}
+
+pub async fn sign_in_with_magic_link(
+    State(st): State<LocalAppState>,
+    Json(CodeRequest { code }): Json<CodeRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let session = st.application.sign_in_with_magic_link(&code).await?;
+    Ok(Json(SessionResponse::try_from(session)?))
+}
+
+/// Exchange a built-in auth token that hasn't expired yet for a new one.
+pub async fn refresh_session(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let session = st.application.refresh_session(&identity).await?;
+    Ok(Json(SessionResponse::try_from(session)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{
+        HeaderMap,
+        StatusCode,
+    };
+
+    use super::BuiltInAuthRateLimiter;
+    use crate::rate_limiter::RateLimiter;
+
+    #[test]
+    fn test_rate_limit_by_client_and_email() -> anyhow::Result<()> {
+        let headers = HeaderMap::new();
+        let limiter = BuiltInAuthRateLimiter {
+            by_client: RateLimiter::new("client", "2/h".parse()?),
+            by_email: RateLimiter::new("email", "1/h".parse()?),
+        };
+        limiter.check(&headers, None, "ada@example.com").unwrap();
+        // Email addresses are case insensitive.
+        let response = limiter
+            .check(&headers, None, " Ada@Example.com")
+            .unwrap_err();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        // The client is now out of requests for any email address.
+        let response = limiter
+            .check(&headers, None, "grace@example.com")
+            .unwrap_err();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::{
+        ComponentFunctionPath,
+        ComponentPath,
+    },
+    http::{
+        extract::Json,
+        ExtractClientVersion,
+        ExtractRequestId,
+        HttpResponseError,
+    },
+    knobs::{
+        BULK_EDIT_BATCH_SIZE,
+        BULK_EDIT_DOCUMENTS_PER_SECOND,
+        BULK_EDIT_REQUEST_TIME_LIMIT,
+    },
+    pause::PauseClient,
+    runtime::{
+        Runtime,
+        RuntimeInstant,
+    },
+    types::FunctionCaller,
+};
+use errors::ErrorMetadata;
+use model::deployment_audit_log::types::DeploymentAuditLogEvent;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use sync_types::UdfPath;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+const BULK_EDIT_MUTATION: &str = "_system/frontend/bulkEditDocuments.js";
+const BULK_EDIT_PREVIEW_QUERY: &str = "_system/frontend/bulkEditDocuments.js:preview";
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BulkEditOperation {
+    Delete,
+    /// `fields` are Convex values in JSON format. Fields set to the dashboard's
+    /// undefined placeholder are removed.
+    Patch {
+        fields: JsonValue,
+    },
+}
+
+impl BulkEditOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            BulkEditOperation::Delete => "delete",
+            BulkEditOperation::Patch { .. } => "patch",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkEditRequest {
+    table_name: String,
+    /// A b64-encoded JSON filter expression, in the format the dashboard's
+    /// table browser uses. Every document in the table matches if unset.
+    filters: Option<String>,
+    operation: BulkEditOperation,
+    /// The `continueCursor` of an earlier response, to carry on where it
+    /// stopped.
+    cursor: Option<String>,
+    /// Only count the matching documents, without editing them.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkEditResponse {
+    /// Documents that matched the filters, and were edited unless this was a
+    /// dry run.
+    matched: u64,
+    scanned: u64,
+    /// Set if the request stopped before reaching the end of the table.
+    continue_cursor: Option<String>,
+}
+
+/// The result of `_system/frontend/bulkEditDocuments` for one batch. Numbers
+/// come back as floats.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchResult {
+    scanned: f64,
+    matched: f64,
+    continue_cursor: String,
+    is_done: bool,
+}
+
+/// Deletes or patches the documents in a table that match a filter, so
+/// operators can clean up data without writing a one-off mutation. With
+/// `dryRun` it only counts them, as a preview.
+///
+/// Documents are edited in batches of `BULK_EDIT_BATCH_SIZE`, each in its own
+/// system mutation, so the usage of a bulk edit is tracked like any other
+/// dashboard edit. Batches are paced to `BULK_EDIT_DOCUMENTS_PER_SECOND`, and
+/// a request returns a `continueCursor` after `BULK_EDIT_REQUEST_TIME_LIMIT`.
+/// Batches that committed stay committed if a later batch fails.
+pub async fn bulk_edit_documents(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractClientVersion(client_version): ExtractClientVersion,
+    Json(req): Json<BulkEditRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    if req.dry_run {
+        must_be_admin(&identity)?;
+    } else {
+        must_be_admin_with_write_access(&identity)?;
+    }
+    let udf_path: UdfPath = if req.dry_run {
+        BULK_EDIT_PREVIEW_QUERY
+    } else {
+        BULK_EDIT_MUTATION
+    }
+    .parse()?;
+    let rt = st.application.runtime();
+    let start = rt.monotonic_now();
+    let mut cursor = req.cursor.clone();
+    let mut matched = 0;
+    let mut scanned = 0;
+    let mut is_done = false;
+    let result: anyhow::Result<()> = try {
+        while !is_done && start.elapsed() < *BULK_EDIT_REQUEST_TIME_LIMIT {
+            let mut args = json!({
+                "table": req.table_name,
+                "filters": req.filters,
+                "cursor": cursor,
+                "batchSize": *BULK_EDIT_BATCH_SIZE as f64,
+            });
+            if !req.dry_run {
+                args["operation"] = serde_json::to_value(&req.operation)?;
+            }
+            let path = ComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: udf_path.clone(),
+            };
+            let value = if req.dry_run {
+                st.application
+                    .read_only_udf(
+                        request_id.clone(),
+                        path,
+                        vec![args],
+                        identity.clone(),
+                        FunctionCaller::HttpApi(client_version.clone()),
+                    )
+                    .await?
+                    .result
+                    .map_err(|e| {
+                        anyhow::anyhow!(ErrorMetadata::bad_request("BulkEditFailed", e.to_string()))
+                    })?
+            } else {
+                st.application
+                    .mutation_udf(
+                        request_id.clone(),
+                        path,
+                        vec![args],
+                        identity.clone(),
+                        None,
+                        FunctionCaller::HttpApi(client_version.clone()),
+                        PauseClient::new(),
+                    )
+                    .await?
+                    .map_err(|e| {
+                        anyhow::anyhow!(ErrorMetadata::bad_request(
+                            "BulkEditFailed",
+                            e.error.to_string()
+                        ))
+                    })?
+                    .value
+            };
+            let batch: BatchResult = serde_json::from_value(JsonValue::from(value))?;
+            matched += batch.matched as u64;
+            scanned += batch.scanned as u64;
+            is_done = batch.is_done;
+            cursor = Some(batch.continue_cursor);
+            if !req.dry_run && !is_done {
+                // Wait until the documents edited so far are within the rate.
+                let target = Duration::from_secs_f64(
+                    matched as f64 / *BULK_EDIT_DOCUMENTS_PER_SECOND as f64,
+                );
+                let elapsed = start.elapsed();
+                if target > elapsed {
+                    rt.wait(target - elapsed).await;
+                }
+            }
+        }
+    };
+    if !req.dry_run && matched > 0 {
+        let event = DeploymentAuditLogEvent::BulkEditDocuments {
+            table_name: req.table_name.clone(),
+            operation: req.operation.name().to_string(),
+            document_count: matched,
+        };
+        let tx = st.application.begin(identity).await?;
+        st.application
+            .commit_with_audit_log_events(tx, vec![event], "bulk_edit_documents")
+            .await?;
+    }
+    result?;
+    Ok(Json(BulkEditResponse {
+        matched,
+        scanned,
+        continue_cursor: if is_done { None } else { cursor },
+    }))
+}
@@ -0,0 +1,136 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    bootstrap_model::components::ComponentQuotas,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use database::{
+    BootstrapComponentsModel,
+    WriteSource,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// A component's quotas. Quotas that aren't set aren't enforced.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentQuotasJson {
+    pub max_tables: Option<u64>,
+    /// Pending and in-progress scheduled functions.
+    pub max_scheduled_jobs: Option<u64>,
+    /// Total size of the component's stored files.
+    pub max_storage_bytes: Option<u64>,
+    pub max_function_calls_per_minute: Option<u64>,
+}
+
+impl From<ComponentQuotasJson> for ComponentQuotas {
+    fn from(quotas: ComponentQuotasJson) -> Self {
+        Self {
+            max_tables: quotas.max_tables,
+            max_scheduled_jobs: quotas.max_scheduled_jobs,
+            max_storage_bytes: quotas.max_storage_bytes,
+            max_function_calls_per_minute: quotas.max_function_calls_per_minute,
+        }
+    }
+}
+
+impl From<ComponentQuotas> for ComponentQuotasJson {
+    fn from(quotas: ComponentQuotas) -> Self {
+        Self {
+            max_tables: quotas.max_tables,
+            max_scheduled_jobs: quotas.max_scheduled_jobs,
+            max_storage_bytes: quotas.max_storage_bytes,
+            max_function_calls_per_minute: quotas.max_function_calls_per_minute,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetComponentQuotasRequest {
+    component_path: String,
+    quotas: ComponentQuotasJson,
+}
+
+/// Replace the quotas of a component. Pushes keep the quotas, but they're
+/// dropped if the component is removed.
+pub async fn set_component_quotas(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetComponentQuotasRequest {
+        component_path,
+        quotas,
+    }): Json<SetComponentQuotasRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let component_path: ComponentPath = component_path.parse().map_err(|e: anyhow::Error| {
+        ErrorMetadata::bad_request("InvalidComponentPath", e.to_string())
+    })?;
+    let mut tx = st.application.begin(identity).await?;
+    BootstrapComponentsModel::new(&mut tx)
+        .set_quotas(component_path, quotas.into())
+        .await?;
+    st.application
+        .commit(tx, WriteSource::new("set_component_quotas"))
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentQuotasEntryJson {
+    component_path: String,
+    quotas: ComponentQuotasJson,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListComponentQuotasResponse {
+    components: Vec<ComponentQuotasEntryJson>,
+}
+
+/// Lists the components that have quotas.
+pub async fn list_component_quotas(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let mut model = BootstrapComponentsModel::new(&mut tx);
+    let mut components = vec![];
+    for component in model.load_all_components().await? {
+        if component.quotas.is_empty() {
+            continue;
+        }
+        let component_path = model
+            .get_component_path(ComponentId::Child(component.id().internal_id()))
+            .await?;
+        components.push(ComponentQuotasEntryJson {
+            component_path: String::from(component_path),
+            quotas: component.into_value().quotas.into(),
+        });
+    }
+    Ok(Json(ListComponentQuotasResponse { components }))
+}
@@ -1,13 +1,20 @@
 use std::{
     fmt,
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::Duration,
 };
 
+use anyhow::Context;
 use clap::Parser;
 use common::types::{
     ConvexOrigin,
     ConvexSite,
 };
+use file_storage::FileScannerConfig;
+use ipnet::IpNet;
 use keybroker::{
     InstanceSecret,
     KeyBroker,
@@ -15,8 +22,22 @@ use keybroker::{
     DEV_SECRET,
 };
 use metrics::SERVER_VERSION_STR;
+use storage::{
+    CredentialsProvider,
+    RemoteStorageConfig,
+    StorageProvider,
+};
 use url::Url;
 
+use crate::{
+    admin_access::{
+        AdminAccessPolicy,
+        TlsConfig,
+    },
+    custom_domains::CustomDomainConfig,
+    deployments::DeploymentConfig,
+};
+
 #[derive(Parser, Clone)]
 #[clap(version = &**SERVER_VERSION_STR, author = "Convex, Inc. <no-reply@convex.dev>")]
 pub struct LocalConfig {
@@ -36,6 +57,63 @@ pub struct LocalConfig {
     #[clap(long, default_value = "3211")]
     site_proxy_port: u16,
 
+    /// Host port to bind for serving Convex HTTP Actions over TLS on custom
+    /// domains
+    #[clap(long, default_value = "443")]
+    custom_domain_port: u16,
+
+    /// Host port to bind for the gRPC API for running functions. The API is
+    /// disabled if no port is given.
+    #[clap(long)]
+    grpc_port: Option<u16>,
+
+    /// Custom domain to serve Convex HTTP Actions on, with the PEM files of its
+    /// certificate chain and private key, as `<domain>=<cert path>,<key path>`.
+    /// The domain may be a wildcard like `*.example.com`. Can be repeated.
+    #[clap(long = "custom-domain")]
+    pub custom_domains: Vec<CustomDomainConfig>,
+
+    /// Directory an ACME client (e.g. certbot in webroot mode) writes HTTP-01
+    /// challenge responses to. They're served on the HTTP Actions port under
+    /// `/.well-known/acme-challenge/`.
+    #[clap(long)]
+    pub acme_challenge_dir: Option<PathBuf>,
+
+    /// PEM file of the certificate chain to serve the backend's API over TLS
+    /// with, rather than plain HTTP.
+    #[clap(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// PEM file of the private key for `--tls-cert`.
+    #[clap(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// PEM file of the CA certificates that sign admin client certificates.
+    /// If given, the admin API only accepts requests over connections
+    /// authenticated with a client certificate signed by one of them.
+    #[clap(long, requires = "tls_cert")]
+    admin_client_ca: Option<PathBuf>,
+
+    /// Network the admin API may be called from, in CIDR notation (e.g.
+    /// `10.0.0.0/8`). Can be repeated. The admin API may be called from any
+    /// network if none are given.
+    #[clap(long = "admin-allowed-network")]
+    admin_allowed_networks: Vec<IpNet>,
+
+    /// Token that scrapers must send as `Authorization: Bearer <token>` to
+    /// read the backend's Prometheus metrics from `/metrics`. The endpoint is
+    /// disabled if no token is given.
+    #[clap(long)]
+    pub metrics_token: Option<String>,
+
+    /// JSON file with backend settings, in the format of the
+    /// `/api/update_backend_settings` admin API, like
+    /// `{"maxConcurrentQueries": 32, "publicApiRateLimit": "100/s"}`. The
+    /// settings are applied at startup and again whenever the file changes,
+    /// without restarting the backend.
+    #[clap(long)]
+    pub settings_file: Option<PathBuf>,
+
     /// Origin of the Convex server
     convex_origin: Option<ConvexOrigin>,
 
@@ -54,6 +132,94 @@ pub struct LocalConfig {
     /// Which directory should local storage use
     #[clap(long, default_value = "convex_local_storage")]
     local_storage: String,
+
+    /// Store files and search indexes in `s3` (or an S3-compatible store like
+    /// MinIO or R2, with `--storage-endpoint`), `gcs` or `azure` rather than
+    /// in `--local-storage`.
+    #[clap(long, requires = "storage_bucket")]
+    storage_provider: Option<StorageProvider>,
+
+    /// Bucket, or Azure container, for `--storage-provider`.
+    #[clap(long, requires = "storage_provider")]
+    storage_bucket: Option<String>,
+
+    /// Prefix of the keys of objects in `--storage-bucket`.
+    #[clap(long, default_value = "")]
+    storage_prefix: String,
+
+    /// Region of an S3 bucket, `auto` for R2.
+    #[clap(long, default_value = "us-east-1")]
+    storage_region: String,
+
+    /// Storage account of an Azure container.
+    #[clap(long)]
+    storage_azure_account: Option<String>,
+
+    /// Endpoint of an S3-compatible store like MinIO or R2, or of Azurite.
+    #[clap(long, requires = "storage_provider")]
+    storage_endpoint: Option<String>,
+
+    /// JSON file with the credentials for `--storage-provider`: either
+    /// `{"accessKeyId": ..., "secretAccessKey": ...}` with an optional
+    /// `"sessionToken"`, for S3 access keys or GCS HMAC keys, or
+    /// `{"accountKey": ...}` for Azure. The file is read again whenever it
+    /// changes, so the credentials can be rotated without a restart.
+    #[clap(long, requires = "storage_provider")]
+    storage_credentials_file: Option<PathBuf>,
+
+    /// Scan uploaded files for malware before they can be served, with a
+    /// clamd daemon (`clamav://<host>:<port>` or `clamav+unix://<socket
+    /// path>`) or an HTTP service (`https://...`) that responds to a POST of
+    /// the file with `{"infected": <bool>, "signature": <string>}`. Flagged
+    /// files are quarantined.
+    #[clap(long)]
+    pub file_scanner: Option<FileScannerConfig>,
+
+    /// URL of a primary backend to replicate into this one's database, which
+    /// must start out empty. The backend doesn't serve requests while it's a
+    /// standby. It's promoted to a primary when restarted without this flag,
+    /// or automatically with `--promote-after-secs`.
+    #[clap(long, requires = "replication_admin_key")]
+    pub replicate_from: Option<String>,
+
+    /// Admin key of the primary in `--replicate-from`.
+    #[clap(long, requires = "replicate_from")]
+    pub replication_admin_key: Option<String>,
+
+    /// Promote the standby once the primary has been unreachable for this
+    /// many seconds. The old primary must be fenced off before then so it
+    /// can't accept writes the standby won't see.
+    #[clap(long, requires = "replicate_from")]
+    promote_after_secs: Option<u64>,
+
+    /// Run queries and mutations in this many separate function runner
+    /// processes, so one that runs V8 out of memory or crashes can't take
+    /// down the backend. They run in the backend itself if this is 0.
+    #[clap(long, default_value = "0")]
+    pub function_runner_processes: usize,
+
+    /// Run as a function runner process for the backend serving its database
+    /// at this URL, rather than as a backend. The backend passes this to the
+    /// processes it starts for `--function-runner-processes`.
+    #[clap(long, hide = true)]
+    pub function_runner_backend_url: Option<String>,
+
+    /// Serve a deployment with this name, as `<name>` or `<name>=<instance
+    /// secret>` (defaults to `--instance-secret`). Can be repeated to serve
+    /// several isolated deployments, each with its own database, storage and
+    /// admin keys, instead of a single one. The deployment's name is its
+    /// instance name. Requests are routed by the path prefix `/d/<name>` or
+    /// by a `Host` of the form `<name>.<domain>`.
+    #[clap(
+        long = "deployment",
+        conflicts_with_all = [
+            "grpc_port",
+            "custom_domains",
+            "replicate_from",
+            "function_runner_processes",
+        ]
+    )]
+    pub deployments: Vec<DeploymentConfig>,
 }
 
 impl fmt::Debug for LocalConfig {
@@ -62,6 +228,22 @@ impl fmt::Debug for LocalConfig {
             .field("convex_origin", &self.convex_origin)
             .field("convex_site", &self.convex_site)
             .field("instance_name", &self.instance_name)
+            .field("custom_domains", &self.custom_domains)
+            .field("tls_cert", &self.tls_cert)
+            .field("admin_client_ca", &self.admin_client_ca)
+            .field("admin_allowed_networks", &self.admin_allowed_networks)
+            .field("storage_provider", &self.storage_provider)
+            .field("storage_bucket", &self.storage_bucket)
+            .field("file_scanner", &self.file_scanner)
+            .field("replicate_from", &self.replicate_from)
+            .field("promote_after_secs", &self.promote_after_secs)
+            .field("deployments", &self.deployments)
+            .field("settings_file", &self.settings_file)
+            .field("function_runner_processes", &self.function_runner_processes)
+            .field(
+                "function_runner_backend_url",
+                &self.function_runner_backend_url,
+            )
             .finish()
     }
 }
@@ -75,6 +257,29 @@ impl LocalConfig {
         Some((self.interface.octets(), self.site_proxy_port))
     }
 
+    pub fn custom_domain_bind_address(&self) -> ([u8; 4], u16) {
+        (self.interface.octets(), self.custom_domain_port)
+    }
+
+    pub fn grpc_bind_address(&self) -> Option<([u8; 4], u16)> {
+        self.grpc_port.map(|port| (self.interface.octets(), port))
+    }
+
+    pub fn tls_config(&self) -> Option<TlsConfig> {
+        Some(TlsConfig {
+            cert_path: self.tls_cert.clone()?,
+            key_path: self.tls_key.clone()?,
+            client_ca_path: self.admin_client_ca.clone(),
+        })
+    }
+
+    pub fn admin_access_policy(&self) -> AdminAccessPolicy {
+        AdminAccessPolicy {
+            allowed_networks: self.admin_allowed_networks.clone(),
+            require_client_cert: self.admin_client_ca.is_some(),
+        }
+    }
+
     pub fn convex_origin_url(&self) -> ConvexOrigin {
         self.convex_origin
             .clone()
@@ -112,10 +317,69 @@ impl LocalConfig {
         self.local_storage.clone().into()
     }
 
+    /// Where files and search indexes are stored if not in `storage_dir`, with
+    /// the credentials to access it.
+    pub fn remote_storage(
+        &self,
+    ) -> anyhow::Result<Option<(RemoteStorageConfig, CredentialsProvider)>> {
+        let Some(provider) = self.storage_provider else {
+            return Ok(None);
+        };
+        let config = RemoteStorageConfig {
+            provider,
+            bucket: self
+                .storage_bucket
+                .clone()
+                .context("--storage-bucket is required")?,
+            prefix: self.storage_prefix.clone(),
+            region: self.storage_region.clone(),
+            azure_account: self.storage_azure_account.clone(),
+            endpoint: self.storage_endpoint.clone(),
+        };
+        config.validate()?;
+        let credentials = CredentialsProvider::from_file(
+            self.storage_credentials_file
+                .clone()
+                .context("--storage-credentials-file is required")?,
+        )?;
+        Ok(Some((config, credentials)))
+    }
+
+    pub fn promote_after(&self) -> Option<Duration> {
+        self.promote_after_secs.map(Duration::from_secs)
+    }
+
+    /// The config of one of the deployments in `--deployment`. Its database
+    /// is next to `db_spec`, its files are in a directory named after it in
+    /// `--local-storage` or under `--storage-prefix`, and it's served under
+    /// `/d/<name>` of the backend's origins.
+    pub fn for_deployment(&self, deployment: &DeploymentConfig) -> Self {
+        let name = &deployment.name;
+        let db_spec = match self.db_spec.strip_suffix(".sqlite3") {
+            Some(stem) => format!("{stem}-{name}.sqlite3"),
+            None => format!("{}-{name}", self.db_spec),
+        };
+        Self {
+            db_spec,
+            local_storage: Path::new(&self.local_storage)
+                .join(name)
+                .to_string_lossy()
+                .into_owned(),
+            storage_prefix: format!("{}{name}/", self.storage_prefix),
+            convex_origin: Some(format!("{}/d/{name}", self.convex_origin_url()).into()),
+            convex_site: Some(format!("{}/d/{name}", self.convex_site_url()).into()),
+            instance_name: Some(name.clone()),
+            instance_secret: deployment
+                .instance_secret
+                .clone()
+                .or_else(|| self.instance_secret.clone()),
+            deployments: vec![],
+            ..self.clone()
+        }
+    }
+
     #[cfg(test)]
     pub fn new_for_test() -> anyhow::Result<Self> {
-        use anyhow::Context;
-
         let tempdir_handle = tempfile::tempdir()?;
         let db_path = tempdir_handle.path().join("convex_local_backend.sqlite3");
         // Easiest way to get a config object with defaults is to parse from cmd line
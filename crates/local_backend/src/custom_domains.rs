@@ -0,0 +1,388 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::BufReader,
+    net::SocketAddr,
+    path::{
+        Path,
+        PathBuf,
+    },
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use axum::{
+    extract::{
+        Path as PathParam,
+        State,
+    },
+    response::{
+        IntoResponse,
+        Response,
+    },
+    routing::get,
+    Router,
+};
+use common::{
+    http::HttpResponseError,
+    types::ConvexOrigin,
+};
+use futures::FutureExt;
+use http::{
+    header::{
+        CONTENT_TYPE,
+        HOST,
+    },
+    Request,
+    StatusCode,
+};
+use hyper::{
+    server::conn::Http,
+    Body,
+};
+use parking_lot::RwLock;
+use rustls::{
+    server::{
+        ClientHello,
+        ResolvesServerCert,
+    },
+    sign::CertifiedKey,
+    Certificate,
+    PrivateKey,
+    ServerConfig,
+};
+use rustls_pemfile::Item;
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+use crate::proxy::proxy_to_http_actions;
+
+/// How often certificates are reloaded from disk, so renewed certificates are
+/// picked up without restarting the backend.
+const CERT_RELOAD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A domain to serve HTTP actions on, with the PEM files of the certificate
+/// chain and private key to terminate TLS with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomDomainConfig {
+    /// Lowercase domain name. May start with `*.` to match any single label.
+    pub domain: String,
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl FromStr for CustomDomainConfig {
+    type Err = anyhow::Error;
+
+    /// Parses `<domain>=<cert path>,<key path>`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (domain, paths) = s
+            .split_once('=')
+            .context("Expected a custom domain of the form <domain>=<cert path>,<key path>")?;
+        let (cert_path, key_path) = paths
+            .split_once(',')
+            .context("Expected a custom domain of the form <domain>=<cert path>,<key path>")?;
+        let domain = domain.trim().to_ascii_lowercase();
+        anyhow::ensure!(is_valid_domain(&domain), "Invalid custom domain {domain:?}");
+        Ok(Self {
+            domain,
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+        })
+    }
+}
+
+fn is_valid_domain(domain: &str) -> bool {
+    let domain = domain.strip_prefix("*.").unwrap_or(domain);
+    domain.split('.').count() >= 2
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Whether `host` is the configured `domain` or, for a wildcard domain, is a
+/// single label under it.
+fn matches_domain(domain: &str, host: &str) -> bool {
+    match domain.strip_prefix("*.") {
+        Some(suffix) => host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+        None => domain == host,
+    }
+}
+
+/// The certificates of the configured custom domains. Each TLS connection is
+/// given the certificate of the domain it asks for with SNI.
+pub struct CustomDomainCerts {
+    configs: Vec<CustomDomainConfig>,
+    certs: RwLock<BTreeMap<String, Arc<CertifiedKey>>>,
+}
+
+impl CustomDomainCerts {
+    pub fn load(configs: Vec<CustomDomainConfig>) -> anyhow::Result<Self> {
+        let mut certs = BTreeMap::new();
+        for config in &configs {
+            let key = load_certified_key(&config.cert_path, &config.key_path)
+                .with_context(|| format!("Failed to load certificate for {}", config.domain))?;
+            certs.insert(config.domain.clone(), Arc::new(key));
+        }
+        Ok(Self {
+            configs,
+            certs: RwLock::new(certs),
+        })
+    }
+
+    /// Reloads all certificates from disk. Keeps serving the previous
+    /// certificate of a domain if its files fail to load.
+    pub fn reload(&self) {
+        for config in &self.configs {
+            match load_certified_key(&config.cert_path, &config.key_path) {
+                Ok(key) => {
+                    self.certs
+                        .write()
+                        .insert(config.domain.clone(), Arc::new(key));
+                },
+                Err(e) => {
+                    tracing::error!("Failed to reload certificate for {}: {e:#}", config.domain)
+                },
+            }
+        }
+    }
+
+    /// Returns the certificate for `host`, preferring an exact match over a
+    /// wildcard domain.
+    pub fn lookup(&self, host: &str) -> Option<Arc<CertifiedKey>> {
+        let host = host.to_ascii_lowercase();
+        let certs = self.certs.read();
+        if let Some(key) = certs.get(&host) {
+            return Some(key.clone());
+        }
+        certs
+            .iter()
+            .find(|(domain, _)| matches_domain(domain, &host))
+            .map(|(_, key)| key.clone())
+    }
+}
+
+impl ResolvesServerCert for CustomDomainCerts {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        self.lookup(client_hello.server_name()?)
+    }
+}
+
+/// Loads a certificate chain and its private key from PEM files.
+pub fn load_certified_key(cert_path: &Path, key_path: &Path) -> anyhow::Result<CertifiedKey> {
+    let certs = rustls_pemfile::certs(&mut open_pem(cert_path)?)?;
+    anyhow::ensure!(
+        !certs.is_empty(),
+        "No certificates in {}",
+        cert_path.display()
+    );
+    let mut key_reader = open_pem(key_path)?;
+    let key = loop {
+        match rustls_pemfile::read_one(&mut key_reader)? {
+            Some(Item::RSAKey(key) | Item::PKCS8Key(key) | Item::ECKey(key)) => break key,
+            Some(_) => continue,
+            None => anyhow::bail!("No private key in {}", key_path.display()),
+        }
+    };
+    let key = rustls::sign::any_supported_type(&PrivateKey(key))
+        .map_err(|_| anyhow::anyhow!("Unsupported private key in {}", key_path.display()))?;
+    Ok(CertifiedKey::new(
+        certs.into_iter().map(Certificate).collect(),
+        key,
+    ))
+}
+
+pub fn open_pem(path: &Path) -> anyhow::Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+#[derive(Clone)]
+struct CustomDomainState {
+    origin: ConvexOrigin,
+    certs: Arc<CustomDomainCerts>,
+}
+
+async fn custom_domain_proxy_method(
+    State(st): State<CustomDomainState>,
+    request: Request<Body>,
+) -> Result<Response, HttpResponseError> {
+    // Clients may reuse a connection for any domain its certificate is valid
+    // for, so check each request is for a custom domain too.
+    let host = request.uri().host().map(str::to_owned).or_else(|| {
+        let host = request.headers().get(HOST)?.to_str().ok()?;
+        Some(host.split(':').next()?.to_owned())
+    });
+    if !host.is_some_and(|host| st.certs.lookup(&host).is_some()) {
+        return Ok(StatusCode::MISDIRECTED_REQUEST.into_response());
+    }
+    let resp = proxy_to_http_actions(&st.origin, request).await?;
+    Ok(resp.into_response())
+}
+
+/// Serves HTTP actions over TLS on the configured custom domains by proxying
+/// requests to the main webserver.
+pub async fn custom_domain_proxy(
+    bind_addr: ([u8; 4], u16),
+    configs: Vec<CustomDomainConfig>,
+    origin: ConvexOrigin,
+    mut shutdown_rx: async_broadcast::Receiver<()>,
+) -> anyhow::Result<()> {
+    if configs.is_empty() {
+        return Ok(());
+    }
+    let certs = Arc::new(CustomDomainCerts::load(configs)?);
+    let mut tls_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(certs.clone());
+    tls_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+
+    let addr = SocketAddr::from(bind_addr);
+    let listener = TcpListener::bind(addr).await?;
+    tracing::info!("Serving custom domains at https://{addr}...");
+
+    let router = Router::new()
+        .fallback(custom_domain_proxy_method)
+        .with_state(CustomDomainState {
+            origin,
+            certs: certs.clone(),
+        });
+    let http = Http::new();
+    let mut reload_ticker = tokio::time::interval(CERT_RELOAD_INTERVAL);
+    // The first tick completes immediately, and the certificates were just
+    // loaded.
+    reload_ticker.tick().await;
+    loop {
+        let (stream, remote_addr) = futures::select_biased! {
+            _ = shutdown_rx.recv().fuse() => break,
+            _ = reload_ticker.tick().fuse() => {
+                certs.reload();
+                continue;
+            },
+            r = listener.accept().fuse() => match r {
+                Ok(r) => r,
+                Err(e) => {
+                    tracing::warn!("Failed to accept connection: {e}");
+                    continue;
+                },
+            },
+        };
+        let acceptor = acceptor.clone();
+        let router = router.clone();
+        let http = http.clone();
+        tokio::spawn(async move {
+            let stream =
+                match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        tracing::debug!("TLS handshake with {remote_addr} failed: {e}");
+                        return;
+                    },
+                    Err(_) => {
+                        tracing::debug!("TLS handshake with {remote_addr} timed out");
+                        return;
+                    },
+                };
+            if let Err(e) = http.serve_connection(stream, router).with_upgrades().await {
+                tracing::debug!("Error serving connection from {remote_addr}: {e}");
+            }
+        });
+    }
+    tracing::info!("Shut down custom domain proxy");
+    Ok(())
+}
+
+/// Serves the HTTP-01 challenge responses an ACME client (e.g. certbot in
+/// webroot mode) writes to `dir`, so certificates for custom domains can be
+/// issued and renewed while the backend is running.
+pub fn acme_challenge_routes(dir: PathBuf) -> Router {
+    async fn acme_challenge(
+        State(dir): State<Arc<PathBuf>>,
+        PathParam(token): PathParam<String>,
+    ) -> Result<Response, HttpResponseError> {
+        // Tokens are base64url, so they can't escape the challenge directory.
+        if token.is_empty()
+            || !token
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        }
+        match tokio::fs::read(dir.join(token)).await {
+            Ok(key_authorization) => {
+                Ok(([(CONTENT_TYPE, "text/plain")], key_authorization).into_response())
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(StatusCode::NOT_FOUND.into_response())
+            },
+            Err(e) => Err(anyhow::Error::new(e).into()),
+        }
+    }
+
+    Router::new()
+        .route("/.well-known/acme-challenge/:token", get(acme_challenge))
+        .with_state(Arc::new(dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        is_valid_domain,
+        matches_domain,
+        CustomDomainConfig,
+    };
+
+    #[test]
+    fn test_parse_custom_domain() -> anyhow::Result<()> {
+        let config: CustomDomainConfig = "API.example.com=/certs/api.pem,/certs/api.key".parse()?;
+        assert_eq!(
+            config,
+            CustomDomainConfig {
+                domain: "api.example.com".to_string(),
+                cert_path: "/certs/api.pem".into(),
+                key_path: "/certs/api.key".into(),
+            }
+        );
+        assert!("api.example.com".parse::<CustomDomainConfig>().is_err());
+        assert!("api.example.com=/certs/api.pem"
+            .parse::<CustomDomainConfig>()
+            .is_err());
+        assert!("api_example.com=/certs/api.pem,/certs/api.key"
+            .parse::<CustomDomainConfig>()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_valid_domain() {
+        assert!(is_valid_domain("example.com"));
+        assert!(is_valid_domain("*.example.com"));
+        assert!(is_valid_domain("my-api.example.com"));
+        assert!(!is_valid_domain("localhost"));
+        assert!(!is_valid_domain("*.com"));
+        assert!(!is_valid_domain("-api.example.com"));
+        assert!(!is_valid_domain("api..example.com"));
+        assert!(!is_valid_domain("api.*.example.com"));
+    }
+
+    #[test]
+    fn test_matches_domain() {
+        assert!(matches_domain("api.example.com", "api.example.com"));
+        assert!(!matches_domain("api.example.com", "www.example.com"));
+        assert!(matches_domain("*.example.com", "api.example.com"));
+        assert!(!matches_domain("*.example.com", "example.com"));
+        assert!(!matches_domain("*.example.com", "v1.api.example.com"));
+    }
+}
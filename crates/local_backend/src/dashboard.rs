@@ -5,6 +5,10 @@ use axum::{
     response::IntoResponse,
 };
 use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
     http::{
         extract::{
             Json,
@@ -17,8 +21,13 @@ use common::{
         reduced::ReducedShape,
     },
 };
-use database::IndexModel;
+use database::{
+    IndexModel,
+    TableModel,
+};
+use errors::ErrorMetadata;
 use http::StatusCode;
+use model::deployment_audit_log::types::DeploymentAuditLogEvent;
 use serde::{
     Deserialize,
     Serialize,
@@ -34,6 +43,7 @@ use crate::{
         must_be_admin_member_with_write_access,
     },
     authentication::ExtractIdentity,
+    parse::parse_udf_path,
     schema::IndexMetadataResponse,
     LocalAppState,
 };
@@ -89,6 +99,137 @@ pub async fn delete_tables(
     Ok(StatusCode::OK)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameTableArgs {
+    table_name: String,
+    new_name: String,
+}
+
+/// Renames a table in place, keeping its documents, their IDs and its
+/// indexes.
+#[debug_handler]
+pub async fn rename_table(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RenameTableArgs {
+        table_name,
+        new_name,
+    }): Json<RenameTableArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let old_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let renamed = new_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let mut tx = st.application.begin(identity).await?;
+    TableModel::new(&mut tx)
+        .rename_table(TableNamespace::by_component_TODO(), &old_name, renamed)
+        .await?;
+    let event = DeploymentAuditLogEvent::RenameTable {
+        table_name,
+        new_name,
+    };
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "rename_table")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveTableArgs {
+    table_name: String,
+}
+
+/// Hides a table as if it were deleted, keeping its data until it's restored.
+#[debug_handler]
+pub async fn archive_table(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ArchiveTableArgs { table_name }): Json<ArchiveTableArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let parsed = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let mut tx = st.application.begin(identity).await?;
+    TableModel::new(&mut tx)
+        .archive_table(TableNamespace::by_component_TODO(), &parsed)
+        .await?;
+    let event = DeploymentAuditLogEvent::ArchiveTable { table_name };
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "archive_table")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreTableArgs {
+    table_name: String,
+    /// Restores the table under a different name, e.g. if a new table has
+    /// taken its name since it was archived.
+    new_name: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreTableResponse {
+    table_name: String,
+}
+
+#[debug_handler]
+pub async fn restore_table(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RestoreTableArgs {
+        table_name,
+        new_name,
+    }): Json<RestoreTableArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let archived_name = table_name.parse::<ValidIdentifier<TableName>>()?.0;
+    let new_name = new_name
+        .map(|name| anyhow::Ok(name.parse::<ValidIdentifier<TableName>>()?.0))
+        .transpose()?;
+    let mut tx = st.application.begin(identity).await?;
+    let restored = TableModel::new(&mut tx)
+        .restore_table(
+            TableNamespace::by_component_TODO(),
+            &archived_name,
+            new_name,
+        )
+        .await?;
+    let event = DeploymentAuditLogEvent::RestoreTable {
+        table_name,
+        new_name: restored.to_string(),
+    };
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "restore_table")
+        .await?;
+    Ok(Json(RestoreTableResponse {
+        table_name: restored.to_string(),
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListArchivedTablesResponse {
+    table_names: Vec<String>,
+}
+
+#[debug_handler]
+pub async fn list_archived_tables(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let table_names = TableModel::new(&mut tx)
+        .archived_table_names(TableNamespace::by_component_TODO())
+        .into_iter()
+        .map(String::from)
+        .collect();
+    Ok(Json(ListArchivedTablesResponse { table_names }))
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GetIndexesResponse {
@@ -132,3 +273,100 @@ pub async fn get_source_code(
         .await?;
     Ok(Json(source_code))
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListActiveSubscriptionsArgs {
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ActiveSubscriptionResponse {
+    udf_path: String,
+    args_hash: String,
+    num_connections: usize,
+    last_update_ms: Option<u64>,
+    bytes_pushed: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListActiveSubscriptionsResponse {
+    subscriptions: Vec<ActiveSubscriptionResponse>,
+}
+
+/// Queries that sync clients are currently subscribed to, aggregated by
+/// function and arguments, with the most bytes pushed first.
+#[debug_handler]
+pub async fn list_active_subscriptions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListActiveSubscriptionsArgs { limit }): Query<ListActiveSubscriptionsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let subscriptions = st
+        .active_subscriptions
+        .list()
+        .into_iter()
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|subscription| {
+            Ok(ActiveSubscriptionResponse {
+                udf_path: subscription.udf_path.to_string(),
+                args_hash: subscription.args_hash,
+                num_connections: subscription.num_connections,
+                last_update_ms: subscription
+                    .last_update
+                    .map(|ts| ts.as_ms_since_epoch())
+                    .transpose()?,
+                bytes_pushed: subscription.bytes_pushed,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Json(ListActiveSubscriptionsResponse { subscriptions }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidateQueryCacheArgs {
+    // Only invalidate results of this query, e.g. `stats:summary`.
+    udf_path: Option<String>,
+    // The component `udf_path` is in, the root component by default.
+    component_path: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InvalidateQueryCacheResponse {
+    num_invalidated: usize,
+}
+
+/// Drop cached results of queries that declared a TTL, so they're recomputed
+/// now instead of when the TTL lapses.
+#[debug_handler]
+pub async fn invalidate_query_cache(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(InvalidateQueryCacheArgs {
+        udf_path,
+        component_path,
+    }): Json<InvalidateQueryCacheArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let component = match component_path {
+        Some(component_path) => component_path.parse().map_err(|e: anyhow::Error| {
+            ErrorMetadata::bad_request("InvalidComponentPath", e.to_string())
+        })?,
+        None => ComponentPath::root(),
+    };
+    let path = udf_path
+        .map(|udf_path| {
+            anyhow::Ok(CanonicalizedComponentFunctionPath {
+                component,
+                udf_path: parse_udf_path(&udf_path)?.canonicalize(),
+            })
+        })
+        .transpose()?;
+    let num_invalidated = st.application.invalidate_query_cache(identity, path)?;
+    Ok(Json(InvalidateQueryCacheResponse { num_invalidated }))
+}
@@ -35,7 +35,10 @@ use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
-use keybroker::Identity;
+use keybroker::{
+    AdminOperation,
+    Identity,
+};
 use model::{
     config::{
         types::{
@@ -70,8 +73,8 @@ use value::ConvexObject;
 
 use crate::{
     admin::{
+        must_be_admin_for,
         must_be_admin_from_key,
-        must_be_admin_with_write_access,
     },
     parse::parse_module_path,
     EmptyResponse,
@@ -384,7 +387,7 @@ pub async fn push_config_handler(
         .await
         .context("bad admin key error")?;
 
-    must_be_admin_with_write_access(&identity)?;
+    must_be_admin_for(&identity, AdminOperation::Deploy)?;
 
     let udf_server_version = Version::parse(&config.udf_server_version).context(
         ErrorMetadata::bad_request("InvalidVersion", "The function version is invalid"),
@@ -23,9 +23,12 @@ use common::{
         },
     },
     components::{
+        CanonicalizedComponentFunctionPath,
         ComponentDefinitionPath,
+        ComponentFunctionPath,
         ComponentId,
         ComponentPath,
+        Resource,
     },
     http::{
         extract::Json,
@@ -35,18 +38,29 @@ use common::{
         Runtime,
         UnixTimestamp,
     },
-    types::NodeDependency,
+    types::{
+        FunctionCaller,
+        ModuleEnvironment,
+        NodeDependency,
+    },
+    version::ClientVersion,
+    RequestId,
 };
 use database::{
     BootstrapComponentsModel,
     IndexModel,
+    SchemaModel,
+    Transaction,
     WriteSource,
 };
 use errors::{
     ErrorMetadata,
     ErrorMetadataAnyhowExt,
 };
-use keybroker::Identity;
+use keybroker::{
+    AdminOperation,
+    Identity,
+};
 use model::{
     auth::{
         types::AuthDiff,
@@ -59,6 +73,7 @@ use model::{
             ComponentDefinitionDiff,
             ComponentDiff,
             SchemaChange,
+            SchemaPlan,
             SerializedComponentDefinitionDiff,
             SerializedComponentDiff,
             SerializedSchemaChange,
@@ -80,11 +95,24 @@ use model::{
     config::types::{
         ConfigFile,
         ConfigMetadata,
+        ModuleConfig,
+    },
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    deployment_versions::{
+        types::DeploymentVersion,
+        DeploymentVersionModel,
     },
     external_packages::types::ExternalDepsPackageId,
-    modules::module_versions::{
-        AnalyzedModule,
-        SerializedAnalyzedModule,
+    function_canaries::{
+        types::FunctionCanary,
+        FunctionCanariesModel,
+    },
+    modules::{
+        module_versions::{
+            AnalyzedModule,
+            SerializedAnalyzedModule,
+        },
+        ModuleModel,
     },
     source_packages::{
         types::{
@@ -92,15 +120,21 @@ use model::{
             SourcePackage,
         },
         upload_download::download_package,
+        SourcePackageModel,
+    },
+    udf_config::{
+        types::UdfConfig,
+        UdfConfigModel,
     },
-    udf_config::types::UdfConfig,
 };
 use rand::Rng;
+use runtime::prod::ProdRuntime;
 use serde::{
     Deserialize,
     Serialize,
 };
 use serde_json::Value as JsonValue;
+use sync_types::CanonicalizedModulePath;
 use value::{
     ConvexObject,
     DeveloperDocumentId,
@@ -111,13 +145,19 @@ use value::{
 use crate::{
     admin::{
         must_be_admin_from_key,
-        must_be_admin_from_key_with_write_access,
+        must_be_admin_from_key_for,
     },
     deploy_config::{
         analyze_modules,
         ModuleJson,
         NodeDependencyJson,
     },
+    deployment_versions::{
+        check_schemas_compatible,
+        upload_push,
+    },
+    function_flags::parse_function_path,
+    parse::parse_udf_path,
     LocalAppState,
 };
 
@@ -231,18 +271,18 @@ impl TryFrom<ComponentDefinitionConfigJson> for ComponentDefinitionConfig {
     }
 }
 
-struct StartPushResponse {
+pub(crate) struct StartPushResponse {
     udf_config: UdfConfig,
 
     external_deps_id: Option<ExternalDepsPackageId>,
     component_definition_packages: BTreeMap<ComponentDefinitionPath, SourcePackage>,
 
     app_auth: Vec<AuthInfo>,
-    analysis: BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
+    pub(crate) analysis: BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
 
-    app: CheckedComponent,
+    pub(crate) app: CheckedComponent,
 
-    schema_change: SchemaChange,
+    pub(crate) schema_change: SchemaChange,
 }
 
 impl TryFrom<StartPushResponse> for SerializedStartPushResponse {
@@ -309,7 +349,7 @@ impl TryFrom<SerializedStartPushResponse> for StartPushResponse {
 
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct SerializedStartPushResponse {
+pub(crate) struct SerializedStartPushResponse {
     // Global evaluation
     udf_config: JsonValue,
 
@@ -359,10 +399,11 @@ pub async fn start_push_handler(
     st: &LocalAppState,
     request: StartPushRequest,
 ) -> anyhow::Result<StartPushResponse> {
-    let _identity = must_be_admin_from_key_with_write_access(
+    let _identity = must_be_admin_from_key_for(
         st.application.app_auth(),
         st.instance_name.clone(),
         request.admin_key.clone(),
+        AdminOperation::Deploy,
     )
     .await?;
     let identity = Identity::system();
@@ -568,7 +609,7 @@ pub async fn start_push_handler(
 
     let schema_change = {
         let mut tx = st.application.begin(identity.clone()).await?;
-        let schema_change = ComponentConfigModel::new(&mut tx)
+        let (schema_change, _) = ComponentConfigModel::new(&mut tx)
             .start_component_schema_changes(&app, &evaluated_components)
             .await?;
         if !dry_run {
@@ -618,7 +659,28 @@ async fn wait_for_schema_handler(
     )
     .await?;
     let schema_change: SchemaChange = req.schema_change.try_into()?;
+    let result = wait_for_schema_change(st, identity, &schema_change).await;
+    if let Err(ref e) = result
+        && e.short_msg() == "SchemaFailed"
+    {
+        // The push won't be finished, so don't leave its schemas pending.
+        let event = DeploymentAuditLogEvent::RollbackPush {
+            reason: e.user_facing_message(),
+        };
+        if let Err(e) = abandon_schema_change(st, &schema_change, vec![event]).await {
+            tracing::error!("Failed to abandon schemas of failed push: {e:?}");
+        }
+    }
+    result
+}
 
+/// Wait for the schemas of a push or component install to be validated and
+/// their indexes to be backfilled.
+async fn wait_for_schema_change(
+    st: &LocalAppState,
+    identity: Identity,
+    schema_change: &SchemaChange,
+) -> anyhow::Result<()> {
     loop {
         let mut tx = st.application.begin(identity.clone()).await?;
         let mut waiting = BTreeSet::new();
@@ -656,20 +718,8 @@ async fn wait_for_schema_handler(
                 waiting.insert(component_path.clone());
             }
 
-            let component_id = if component_path.is_root() {
-                ComponentId::Root
-            } else {
-                let existing = BootstrapComponentsModel::new(&mut tx)
-                    .resolve_path(component_path.clone())
-                    .await?;
-                let allocated = schema_change.allocated_component_ids.get(component_path);
-                let internal_id = match (existing, allocated) {
-                    (None, Some(id)) => *id,
-                    (Some(doc), None) => doc.id().internal_id(),
-                    r => anyhow::bail!("Invalid existing component state: {r:?}"),
-                };
-                ComponentId::Child(internal_id)
-            };
+            let component_id =
+                schema_change_component_id(&mut tx, schema_change, component_path).await?;
             let namespace = TableNamespace::from(component_id);
             for index in IndexModel::new(&mut tx)
                 .get_application_indexes(namespace)
@@ -695,12 +745,39 @@ async fn wait_for_schema_handler(
     Ok(())
 }
 
+/// The component a schema change at `component_path` is for, which may have
+/// been allocated by the schema change.
+async fn schema_change_component_id(
+    tx: &mut Transaction<ProdRuntime>,
+    schema_change: &SchemaChange,
+    component_path: &ComponentPath,
+) -> anyhow::Result<ComponentId> {
+    if component_path.is_root() {
+        return Ok(ComponentId::Root);
+    }
+    let existing = BootstrapComponentsModel::new(tx)
+        .resolve_path(component_path.clone())
+        .await?;
+    let allocated = schema_change.allocated_component_ids.get(component_path);
+    let internal_id = match (existing, allocated) {
+        (None, Some(id)) => *id,
+        (Some(doc), None) => doc.id().internal_id(),
+        r => anyhow::bail!("Invalid existing component state: {r:?}"),
+    };
+    Ok(ComponentId::Child(internal_id))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FinishPushRequest {
     admin_key: String,
     start_push: SerializedStartPushResponse,
     dry_run: bool,
+    // A function run with no arguments once the push is applied, e.g.
+    // `smoke:check`. If it fails, the code and configuration deployed before
+    // the push are restored.
+    #[serde(default)]
+    smoke_test_function: Option<String>,
 }
 
 #[debug_handler]
@@ -718,32 +795,42 @@ async fn finish_push_handler(
     st: &LocalAppState,
     req: FinishPushRequest,
 ) -> anyhow::Result<FinishPushDiff> {
+    let start_push_json = serde_json::to_vec(&req.start_push)?;
     let start_push = StartPushResponse::try_from(req.start_push)?;
-    let _identity = must_be_admin_from_key_with_write_access(
+    let identity = must_be_admin_from_key_for(
         st.application.app_auth(),
         st.instance_name.clone(),
         req.admin_key.clone(),
+        AdminOperation::Deploy,
     )
     .await?;
 
     // TODO: Verify that hash matches (env variables, schema, component tree).
 
-    // Download all source packages. We can remove this once we don't store source
-    // in the database.
-    let mut downloaded_source_packages = BTreeMap::new();
-    for (definition_path, source_package) in &start_push.component_definition_packages {
-        let package = download_package(
-            st.application.modules_storage().clone(),
-            source_package.storage_key.clone(),
-            source_package.sha256.clone(),
-        )
-        .await?;
-        downloaded_source_packages.insert(definition_path.clone(), package);
-    }
+    let downloaded_source_packages =
+        download_source_packages(st, &start_push.component_definition_packages).await?;
+    let version = if !req.dry_run {
+        Some(DeploymentVersion {
+            storage_key: upload_push(st, start_push_json).await?,
+            udf_server_version: start_push.udf_config.server_version.to_string(),
+        })
+    } else {
+        None
+    };
 
     // TODO: We require system identity for creating system tables.
     let mut tx = st.application.begin(Identity::system()).await?;
 
+    let smoke_test = match req.smoke_test_function {
+        Some(ref function) if !req.dry_run => {
+            let udf_path = parse_udf_path(function).map_err(|e| {
+                ErrorMetadata::bad_request("InvalidSmokeTestFunction", e.to_string())
+            })?;
+            Some((udf_path, load_deployed_push(&mut tx).await?))
+        },
+        _ => None,
+    };
+
     // Update app state: auth info and UDF server version.
     let auth_diff = AuthInfoModel::new(&mut tx).put(start_push.app_auth).await?;
 
@@ -765,6 +852,11 @@ async fn finish_push_handler(
             modules_by_definition,
         )
         .await?;
+    if let Some(version) = version {
+        DeploymentVersionModel::new(&mut tx).record(version).await?;
+    }
+    // Canaries run against the replaced modules' metadata.
+    FunctionCanariesModel::new(&mut tx).delete_all().await?;
 
     if !req.dry_run {
         st.application
@@ -774,6 +866,37 @@ async fn finish_push_handler(
         drop(tx);
     }
 
+    if let Some((udf_path, previous)) = smoke_test {
+        let path = ComponentFunctionPath {
+            component: ComponentPath::root(),
+            udf_path,
+        };
+        let result = st
+            .application
+            .any_udf(
+                RequestId::new(),
+                path,
+                vec![serde_json::json!({})],
+                identity,
+                FunctionCaller::HttpApi(ClientVersion::unknown()),
+            )
+            .await?;
+        if let Err(e) = result {
+            let reason = format!("Smoke test failed: {}", e.error);
+            let message = match previous {
+                Some(previous) => {
+                    let event = DeploymentAuditLogEvent::RollbackPush {
+                        reason: reason.clone(),
+                    };
+                    restore_push(st, previous, event).await?;
+                    format!("{reason}\nThe previously deployed functions were restored.")
+                },
+                None => format!("{reason}\nThere was no previous push to restore."),
+            };
+            anyhow::bail!(ErrorMetadata::bad_request("SmokeTestFailed", message));
+        }
+    }
+
     let diff = FinishPushDiff {
         auth_diff,
         definition_diffs,
@@ -782,6 +905,168 @@ async fn finish_push_handler(
     Ok(diff)
 }
 
+/// The deployment's code and configuration in the form a push applies them,
+/// so they can be restored if a push fails its smoke test. Schemas are left
+/// out, since rolling back leaves the pushed schemas and indexes in place.
+/// None if the deployment hasn't been pushed with components yet.
+async fn load_deployed_push(
+    tx: &mut Transaction<ProdRuntime>,
+) -> anyhow::Result<Option<StartPushResponse>> {
+    let Some(udf_config) = UdfConfigModel::new(tx, TableNamespace::Global)
+        .get()
+        .await?
+    else {
+        return Ok(None);
+    };
+    let app_auth = AuthInfoModel::new(tx)
+        .get()
+        .await?
+        .into_iter()
+        .map(|doc| doc.into_value())
+        .collect();
+    let definitions = BootstrapComponentsModel::new(tx)
+        .load_all_definitions()
+        .await?;
+    let definition_paths = definitions
+        .iter()
+        .map(|(path, definition)| (definition.id().internal_id(), path.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let components = BootstrapComponentsModel::new(tx)
+        .load_all_components()
+        .await?;
+    if !components
+        .iter()
+        .any(|component| component.component_type.is_root())
+    {
+        return Ok(None);
+    }
+
+    let mut analysis = BTreeMap::new();
+    let mut component_definition_packages = BTreeMap::new();
+    let mut allocated_component_ids = BTreeMap::new();
+    let mut schema_ids = BTreeMap::new();
+    for component in components {
+        let internal_id = component.id().internal_id();
+        let (component_id, definition_path) = if component.component_type.is_root() {
+            (ComponentId::Root, ComponentDefinitionPath::root())
+        } else {
+            let definition_path = definition_paths
+                .get(&component.definition_id)
+                .context("Missing definition for component")?;
+            (ComponentId::Child(internal_id), definition_path.clone())
+        };
+        // Components deleted by the push are recreated with their IDs, so
+        // they get their tables back.
+        let path = BootstrapComponentsModel::new(tx)
+            .get_component_path(component_id)
+            .await?;
+        allocated_component_ids.insert(path.clone(), internal_id);
+        schema_ids.insert(path, None);
+
+        if analysis.contains_key(&definition_path) {
+            continue;
+        }
+        let definition = match definitions.get(&definition_path) {
+            Some(definition) => definition.clone().into_value(),
+            None => ComponentDefinitionMetadata::default_root(),
+        };
+        let functions = ModuleModel::new(tx)
+            .get_all_metadata(component_id)
+            .await?
+            .into_iter()
+            .map(|module| {
+                let module = module.into_value();
+                (module.path, module.analyze_result.unwrap_or_default())
+            })
+            .collect();
+        let source_package = SourcePackageModel::new(tx, component_id.into())
+            .get_latest()
+            .await?
+            .context("Missing source package for component")?
+            .into_value();
+        analysis.insert(
+            definition_path.clone(),
+            EvaluatedComponentDefinition {
+                definition,
+                schema: None,
+                functions,
+            },
+        );
+        component_definition_packages.insert(definition_path, source_package);
+    }
+    let app = TypecheckContext::new(&analysis)
+        .instantiate_root()
+        .map_err(|e| anyhow::anyhow!("Failed to typecheck deployed components: {e}"))?;
+    Ok(Some(StartPushResponse {
+        udf_config: udf_config.into_value(),
+        external_deps_id: None,
+        component_definition_packages,
+        app_auth,
+        analysis,
+        app,
+        schema_change: SchemaChange {
+            allocated_component_ids,
+            schema_ids,
+        },
+    }))
+}
+
+/// Restore the code and configuration of an earlier push, leaving the
+/// current schemas and indexes in place. Fails if the earlier push's code
+/// declared a schema the current one isn't compatible with.
+pub(crate) async fn restore_push(
+    st: &LocalAppState,
+    previous: StartPushResponse,
+    event: DeploymentAuditLogEvent,
+) -> anyhow::Result<()> {
+    let downloaded_source_packages =
+        download_source_packages(st, &previous.component_definition_packages).await?;
+    let mut tx = st.application.begin(Identity::system()).await?;
+    check_schemas_compatible(&mut tx, &previous).await?;
+    AuthInfoModel::new(&mut tx).put(previous.app_auth).await?;
+    let (_, modules_by_definition) = ComponentDefinitionConfigModel::new(&mut tx)
+        .apply_component_definitions_diff(
+            &previous.analysis,
+            &previous.component_definition_packages,
+            &downloaded_source_packages,
+        )
+        .await?;
+    ComponentConfigModel::new(&mut tx)
+        .apply_component_tree_diff_without_schemas(
+            &previous.app,
+            &previous.udf_config,
+            &previous.schema_change,
+            modules_by_definition,
+        )
+        .await?;
+    FunctionCanariesModel::new(&mut tx).delete_all().await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "restore_push")
+        .await?;
+    Ok(())
+}
+
+/// Download all source packages. We can remove this once we don't store source
+/// in the database.
+async fn download_source_packages(
+    st: &LocalAppState,
+    component_definition_packages: &BTreeMap<ComponentDefinitionPath, SourcePackage>,
+) -> anyhow::Result<
+    BTreeMap<ComponentDefinitionPath, BTreeMap<CanonicalizedModulePath, ModuleConfig>>,
+> {
+    let mut downloaded_source_packages = BTreeMap::new();
+    for (definition_path, source_package) in component_definition_packages {
+        let package = download_package(
+            st.application.modules_storage().clone(),
+            source_package.storage_key.clone(),
+            source_package.sha256.clone(),
+        )
+        .await?;
+        downloaded_source_packages.insert(definition_path.clone(), package);
+    }
+    Ok(downloaded_source_packages)
+}
+
 struct FinishPushDiff {
     auth_diff: AuthDiff,
     definition_diffs: BTreeMap<ComponentDefinitionPath, ComponentDefinitionDiff>,
@@ -815,3 +1100,614 @@ impl TryFrom<FinishPushDiff> for SerializedFinishPushDiff {
         })
     }
 }
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComponentPlan {
+    #[serde(flatten)]
+    diff: SerializedComponentDiff,
+    index_diff: SerializedIndexDiff,
+    tables_to_validate: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PushPlan {
+    /// Whether the push deletes components, modules or crons, or drops
+    /// indexes.
+    destructive: bool,
+    auth_diff: AuthDiff,
+    definition_diffs: BTreeMap<String, SerializedComponentDefinitionDiff>,
+    components: BTreeMap<String, ComponentPlan>,
+}
+
+/// Report what pushing the request's config would change without applying
+/// any of it, so a push can be reviewed before it's run.
+#[debug_handler]
+pub async fn plan_push(
+    State(st): State<LocalAppState>,
+    Json(req): Json<StartPushRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let plan = plan_push_handler(&st, req)
+        .await
+        .map_err(|e| e.wrap_error_message(|msg| format!("Hit an error while planning:\n{msg}")))?;
+    Ok(Json(plan))
+}
+
+async fn plan_push_handler(
+    st: &LocalAppState,
+    mut request: StartPushRequest,
+) -> anyhow::Result<PushPlan> {
+    request.dry_run = true;
+    let start_push = start_push_handler(st, request).await?;
+    let downloaded_source_packages =
+        download_source_packages(st, &start_push.component_definition_packages).await?;
+
+    // Start the schema changes and diff the components in one transaction,
+    // which is never committed.
+    let mut tx = st.application.begin(Identity::system()).await?;
+    let (schema_change, mut schema_plans) = ComponentConfigModel::new(&mut tx)
+        .start_component_schema_changes(&start_push.app, &start_push.analysis)
+        .await?;
+    let auth_diff = AuthInfoModel::new(&mut tx).put(start_push.app_auth).await?;
+    let (definition_diffs, modules_by_definition) = ComponentDefinitionConfigModel::new(&mut tx)
+        .apply_component_definitions_diff(
+            &start_push.analysis,
+            &start_push.component_definition_packages,
+            &downloaded_source_packages,
+        )
+        .await?;
+    let component_diffs = ComponentConfigModel::new(&mut tx)
+        .apply_component_tree_diff_without_schemas(
+            &start_push.app,
+            &start_push.udf_config,
+            &schema_change,
+            modules_by_definition,
+        )
+        .await?;
+    drop(tx);
+
+    let mut destructive = false;
+    let mut components = BTreeMap::new();
+    for (path, diff) in component_diffs {
+        let schema_plan = schema_plans.remove(&path);
+        destructive |=
+            diff.is_destructive() || schema_plan.as_ref().is_some_and(|p| p.is_destructive());
+        let (index_diff, tables_to_validate) = match schema_plan {
+            Some(SchemaPlan {
+                index_diff,
+                tables_to_validate,
+            }) => (
+                SerializedIndexDiff {
+                    added: index_diff
+                        .added
+                        .iter()
+                        .map(|i| i.name.to_string())
+                        .collect(),
+                    removed: index_diff
+                        .dropped
+                        .iter()
+                        .map(|i| i.name.to_string())
+                        .collect(),
+                },
+                tables_to_validate.iter().map(|t| t.to_string()).collect(),
+            ),
+            None => (
+                SerializedIndexDiff {
+                    added: vec![],
+                    removed: vec![],
+                },
+                vec![],
+            ),
+        };
+        components.insert(
+            String::from(path),
+            ComponentPlan {
+                diff: diff.try_into()?,
+                index_diff,
+                tables_to_validate,
+            },
+        );
+    }
+    Ok(PushPlan {
+        destructive,
+        auth_diff,
+        definition_diffs: definition_diffs
+            .into_iter()
+            .map(|(k, v)| Ok((String::from(k), v.try_into()?)))
+            .collect::<anyhow::Result<_>>()?,
+        components,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanaryFunctionJson {
+    /// The component the function is in. Defaults to the app itself.
+    component_path: Option<String>,
+    /// e.g. "messages:send".
+    udf_path: String,
+    /// The percentage of users whose calls run the canary, from 0 to 100.
+    rollout_percentage: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartCanaryRequest {
+    admin_key: String,
+    start_push: SerializedStartPushResponse,
+    functions: Vec<CanaryFunctionJson>,
+}
+
+/// Run the code from `start_push` for some of the callers of `functions`,
+/// alongside the active version, instead of pushing it. The canary can only
+/// change modules that are already deployed, and only functions that run in
+/// V8. The next push or restored version ends every canary.
+#[debug_handler]
+pub async fn start_canary(
+    State(st): State<LocalAppState>,
+    Json(req): Json<StartCanaryRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    start_canary_handler(&st, req).await.map_err(|e| {
+        e.wrap_error_message(|msg| format!("Hit an error while starting a canary:\n{msg}"))
+    })?;
+    Ok(Json(()))
+}
+
+async fn start_canary_handler(st: &LocalAppState, req: StartCanaryRequest) -> anyhow::Result<()> {
+    let start_push = StartPushResponse::try_from(req.start_push)?;
+    let identity = must_be_admin_from_key_for(
+        st.application.app_auth(),
+        st.instance_name.clone(),
+        req.admin_key,
+        AdminOperation::Deploy,
+    )
+    .await?;
+    let mut tx = st.application.begin(identity).await?;
+    let mut source_package_ids = BTreeMap::new();
+    let mut events = vec![];
+    for function in req.functions {
+        let path = parse_function_path(function.component_path, &function.udf_path)?;
+        let Some(component) = find_checked_component(&start_push.app, &path.component) else {
+            anyhow::bail!(invalid_canary(format!(
+                "Component {:?} isn't in the pushed code",
+                String::from(path.component.clone())
+            )));
+        };
+        let component_id = if path.component.is_root() {
+            ComponentId::Root
+        } else {
+            let Some(doc) = BootstrapComponentsModel::new(&mut tx)
+                .resolve_path(path.component.clone())
+                .await?
+            else {
+                anyhow::bail!(invalid_canary(format!(
+                    "Component {:?} hasn't been pushed yet",
+                    String::from(path.component.clone())
+                )));
+            };
+            ComponentId::Child(doc.id().internal_id())
+        };
+        let analysis = start_push
+            .analysis
+            .get(&component.definition_path)
+            .context("Missing analysis for component")?;
+        check_canary_modules(&mut tx, component_id, analysis, &path).await?;
+
+        let source_package_id = match source_package_ids.get(&component_id) {
+            Some(source_package_id) => *source_package_id,
+            None => {
+                let package = start_push
+                    .component_definition_packages
+                    .get(&component.definition_path)
+                    .context("Missing source package for component")?;
+                let source_package_id = SourcePackageModel::new(&mut tx, component_id.into())
+                    .put(package.clone())
+                    .await?;
+                source_package_ids.insert(component_id, source_package_id);
+                source_package_id
+            },
+        };
+        events.push(DeploymentAuditLogEvent::SetFunctionCanary {
+            component_path: path.component.clone().into(),
+            udf_path: path.udf_path.clone().into(),
+            rollout_percentage: function.rollout_percentage,
+        });
+        FunctionCanariesModel::new(&mut tx)
+            .set(FunctionCanary {
+                path,
+                source_package_id,
+                rollout_percentage: function.rollout_percentage,
+            })
+            .await?;
+    }
+    st.application
+        .commit_with_audit_log_events(tx, events, "start_canary")
+        .await?;
+    Ok(())
+}
+
+fn find_checked_component<'a>(
+    app: &'a CheckedComponent,
+    component_path: &ComponentPath,
+) -> Option<&'a CheckedComponent> {
+    let mut components = vec![app];
+    while let Some(component) = components.pop() {
+        if component.component_path == *component_path {
+            return Some(component);
+        }
+        components.extend(component.child_components.values());
+    }
+    None
+}
+
+fn invalid_canary(msg: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidFunctionCanary", msg)
+}
+
+/// A canary runs its own source against the active version's module
+/// metadata, so it can't add modules or move a function out of V8, and the
+/// function has to exist in both versions.
+async fn check_canary_modules(
+    tx: &mut Transaction<ProdRuntime>,
+    component_id: ComponentId,
+    analysis: &EvaluatedComponentDefinition,
+    path: &CanonicalizedComponentFunctionPath,
+) -> anyhow::Result<()> {
+    let active: BTreeMap<_, _> = ModuleModel::new(tx)
+        .get_all_metadata(component_id)
+        .await?
+        .into_iter()
+        .map(|module| {
+            let module = module.into_value();
+            (module.path.clone(), module)
+        })
+        .collect();
+    if let Some(added) = analysis.functions.keys().find(|p| !active.contains_key(*p)) {
+        anyhow::bail!(invalid_canary(format!(
+            "The canary adds module {:?}, but can only change deployed modules",
+            added.as_str()
+        )));
+    }
+    let udf_path = String::from(path.udf_path.clone());
+    let module_path = path.udf_path.module();
+    match active.get(module_path) {
+        Some(module) if module.environment == ModuleEnvironment::Isolate => (),
+        Some(_) => anyhow::bail!(invalid_canary(format!(
+            "Function {udf_path:?} runs in Node, which canaries don't support"
+        ))),
+        None => anyhow::bail!(invalid_canary(format!(
+            "Function {udf_path:?} isn't deployed"
+        ))),
+    }
+    let in_canary = analysis.functions.get(module_path).is_some_and(|module| {
+        module
+            .functions
+            .iter()
+            .any(|function| function.name == *path.udf_path.function_name())
+    });
+    if !in_canary {
+        anyhow::bail!(invalid_canary(format!(
+            "Function {udf_path:?} isn't in the canary's code"
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallComponentRequest {
+    admin_key: String,
+    // Where to install the component, e.g. `ratelimiter` for a child of the
+    // app.
+    component_path: String,
+    // The installed component's definition, one of `component_definitions`.
+    definition_path: String,
+    #[serde(default)]
+    args: BTreeMap<String, JsonValue>,
+    udf_server_version: String,
+    // The installed component's definition and the definitions it depends on.
+    component_definitions: Vec<ComponentDefinitionConfigJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UninstallComponentRequest {
+    admin_key: String,
+    component_path: String,
+}
+
+struct ComponentChangeDiff {
+    definition_diffs: BTreeMap<ComponentDefinitionPath, ComponentDefinitionDiff>,
+    component_diffs: BTreeMap<ComponentPath, ComponentDiff>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedComponentChangeDiff {
+    definition_diffs: BTreeMap<String, SerializedComponentDefinitionDiff>,
+    component_diffs: BTreeMap<String, SerializedComponentDiff>,
+}
+
+impl TryFrom<ComponentChangeDiff> for SerializedComponentChangeDiff {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ComponentChangeDiff) -> Result<Self, Self::Error> {
+        Ok(Self {
+            definition_diffs: value
+                .definition_diffs
+                .into_iter()
+                .map(|(k, v)| Ok((String::from(k), v.try_into()?)))
+                .collect::<anyhow::Result<_>>()?,
+            component_diffs: value
+                .component_diffs
+                .into_iter()
+                .map(|(k, v)| Ok((String::from(k), v.try_into()?)))
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+/// Install a component, with its modules, schema and crons, without pushing
+/// the whole app. Components installed this way aren't in the app's
+/// definition, so the next push of the app uninstalls them.
+#[debug_handler]
+pub async fn install_component(
+    State(st): State<LocalAppState>,
+    Json(req): Json<InstallComponentRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let diff = install_component_handler(&st, req, false)
+        .await
+        .map_err(|e| {
+            e.wrap_error_message(|msg| format!("Hit an error while installing component:\n{msg}"))
+        })?;
+    Ok(Json(SerializedComponentChangeDiff::try_from(diff)?))
+}
+
+/// Replace an installed component with a new version, updating the
+/// components in its subtree.
+#[debug_handler]
+pub async fn upgrade_component(
+    State(st): State<LocalAppState>,
+    Json(req): Json<InstallComponentRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let diff = install_component_handler(&st, req, true)
+        .await
+        .map_err(|e| {
+            e.wrap_error_message(|msg| format!("Hit an error while upgrading component:\n{msg}"))
+        })?;
+    Ok(Json(SerializedComponentChangeDiff::try_from(diff)?))
+}
+
+async fn install_component_handler(
+    st: &LocalAppState,
+    req: InstallComponentRequest,
+    upgrade: bool,
+) -> anyhow::Result<ComponentChangeDiff> {
+    let identity = must_be_admin_from_key_for(
+        st.application.app_auth(),
+        st.instance_name.clone(),
+        req.admin_key.clone(),
+        AdminOperation::Deploy,
+    )
+    .await?;
+    let rt = st.application.runtime();
+    let udf_config = UdfConfig {
+        server_version: req.udf_server_version.parse()?,
+        import_phase_rng_seed: rt.with_rng(|rng| rng.gen()),
+        import_phase_unix_timestamp: rt.unix_timestamp(),
+    };
+    let invalid_config =
+        |e: anyhow::Error| ErrorMetadata::bad_request("InvalidConfig", e.to_string());
+    let component_path: ComponentPath = req.component_path.parse().map_err(invalid_config)?;
+    let definition_path: ComponentDefinitionPath =
+        req.definition_path.parse().map_err(invalid_config)?;
+    let component_definitions = req
+        .component_definitions
+        .into_iter()
+        .map(ComponentDefinitionConfig::try_from)
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(invalid_config)?;
+    let args = req
+        .args
+        .into_iter()
+        .map(|(name, value)| Ok((name.parse()?, Resource::Value(value.try_into()?))))
+        .collect::<anyhow::Result<BTreeMap<_, _>>>()
+        .map_err(invalid_config)?;
+
+    // Upload and analyze each definition's modules, like a push.
+    let mut total_size = PackageSize::default();
+    let mut packages = BTreeMap::new();
+    let mut definition_modules = BTreeMap::new();
+    let mut dependency_graph = BTreeSet::new();
+    let mut analysis_by_def_path = BTreeMap::new();
+    let mut schema_by_def_path = BTreeMap::new();
+    for component_def in &component_definitions {
+        anyhow::ensure!(
+            !component_def.definition_path.is_root(),
+            ErrorMetadata::bad_request(
+                "InvalidConfig",
+                "A component's definition can't be the app's definition"
+            )
+        );
+        let modules = component_def.modules().cloned().collect();
+        let package = st.application.upload_package(&modules, None).await?;
+        total_size += package.package_size;
+        let analysis = analyze_modules(
+            &st.application,
+            udf_config.clone(),
+            component_def.functions.clone(),
+            package.clone(),
+        )
+        .await?;
+        analysis_by_def_path.insert(component_def.definition_path.clone(), analysis);
+        if let Some(schema_module) = &component_def.schema {
+            let schema = st
+                .application
+                .evaluate_schema(schema_module.clone())
+                .await?;
+            schema_by_def_path.insert(component_def.definition_path.clone(), schema);
+        }
+        for dep in &component_def.dependencies {
+            dependency_graph.insert((component_def.definition_path.clone(), dep.clone()));
+        }
+        definition_modules.insert(
+            component_def.definition_path.clone(),
+            component_def.definition.clone(),
+        );
+        anyhow::ensure!(packages
+            .insert(component_def.definition_path.clone(), package)
+            .is_none());
+    }
+    total_size.verify_size()?;
+
+    let evaluated_definitions = st
+        .application
+        .evaluate_component_definitions(
+            definition_path.clone(),
+            definition_modules,
+            dependency_graph,
+        )
+        .await?;
+    let mut evaluated_components = BTreeMap::new();
+    for (path, definition) in evaluated_definitions {
+        let mut evaluated = EvaluatedComponentDefinition {
+            definition,
+            schema: schema_by_def_path.get(&path).cloned(),
+            functions: analysis_by_def_path
+                .get(&path)
+                .context("Missing analysis for component?")?
+                .clone(),
+        };
+        add_file_based_routing(&mut evaluated)?;
+        evaluated_components.insert(path, evaluated);
+    }
+    let component = TypecheckContext::new(&evaluated_components)
+        .instantiate(definition_path, component_path, args)
+        .map_err(|e| ErrorMetadata::bad_request("TypecheckError", e.to_string()))?;
+
+    let schema_change = {
+        let mut tx = st.application.begin(Identity::system()).await?;
+        let schema_change = ComponentConfigModel::new(&mut tx)
+            .start_install_component(&component, &evaluated_components, upgrade)
+            .await?;
+        st.application
+            .commit(tx, WriteSource::new("start_install_component"))
+            .await?;
+        schema_change
+    };
+
+    let result = async {
+        wait_for_schema_change(st, identity, &schema_change).await?;
+        let downloaded_source_packages = download_source_packages(st, &packages).await?;
+
+        let mut tx = st.application.begin(Identity::system()).await?;
+        let (mut definition_diffs, modules_by_definition) =
+            ComponentDefinitionConfigModel::new(&mut tx)
+                .put_component_definitions(
+                    &evaluated_components,
+                    &packages,
+                    &downloaded_source_packages,
+                )
+                .await?;
+        let component_diffs = ComponentConfigModel::new(&mut tx)
+            .install_component(
+                &component,
+                &udf_config,
+                &schema_change,
+                modules_by_definition,
+            )
+            .await?;
+        // Upgrading can remove components from the subtree.
+        definition_diffs.extend(
+            ComponentDefinitionConfigModel::new(&mut tx)
+                .delete_unused_component_definitions()
+                .await?,
+        );
+        st.application
+            .commit(tx, WriteSource::new("install_component"))
+            .await?;
+        anyhow::Ok(ComponentChangeDiff {
+            definition_diffs,
+            component_diffs,
+        })
+    }
+    .await;
+    if result.is_err() {
+        // Nothing was installed, so don't leave the submitted schemas pending.
+        if let Err(e) = abandon_schema_change(st, &schema_change, vec![]).await {
+            tracing::error!("Failed to abandon schemas of component install: {e:?}");
+        }
+    }
+    result
+}
+
+async fn abandon_schema_change(
+    st: &LocalAppState,
+    schema_change: &SchemaChange,
+    events: Vec<DeploymentAuditLogEvent>,
+) -> anyhow::Result<()> {
+    let mut tx = st.application.begin(Identity::system()).await?;
+    for (component_path, schema_id) in &schema_change.schema_ids {
+        let Some(schema_id) = schema_id else {
+            continue;
+        };
+        let schema_table_number = tx.table_mapping().tablet_number(schema_id.table())?;
+        let schema_id = ResolvedDocumentId::new(
+            schema_id.table(),
+            DeveloperDocumentId::new(schema_table_number, schema_id.internal_id()),
+        );
+        let component_id =
+            schema_change_component_id(&mut tx, schema_change, component_path).await?;
+        SchemaModel::new(&mut tx, component_id.into())
+            .abandon(schema_id)
+            .await?;
+    }
+    st.application
+        .commit_with_audit_log_events(tx, events, "abandon_schema_change")
+        .await?;
+    Ok(())
+}
+
+/// Uninstall a component installed with `install_component`, and the
+/// components in its subtree.
+#[debug_handler]
+pub async fn uninstall_component(
+    State(st): State<LocalAppState>,
+    Json(req): Json<UninstallComponentRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let diff = uninstall_component_handler(&st, req).await?;
+    Ok(Json(SerializedComponentChangeDiff::try_from(diff)?))
+}
+
+async fn uninstall_component_handler(
+    st: &LocalAppState,
+    req: UninstallComponentRequest,
+) -> anyhow::Result<ComponentChangeDiff> {
+    must_be_admin_from_key_for(
+        st.application.app_auth(),
+        st.instance_name.clone(),
+        req.admin_key,
+        AdminOperation::Deploy,
+    )
+    .await?;
+    let component_path: ComponentPath = req
+        .component_path
+        .parse()
+        .map_err(|e: anyhow::Error| ErrorMetadata::bad_request("InvalidConfig", e.to_string()))?;
+
+    let mut tx = st.application.begin(Identity::system()).await?;
+    let component_diffs = ComponentConfigModel::new(&mut tx)
+        .uninstall_component(&component_path)
+        .await?;
+    let definition_diffs = ComponentDefinitionConfigModel::new(&mut tx)
+        .delete_unused_component_definitions()
+        .await?;
+    st.application
+        .commit(tx, WriteSource::new("uninstall_component"))
+        .await?;
+    Ok(ComponentChangeDiff {
+        definition_diffs,
+        component_diffs,
+    })
+}
@@ -0,0 +1,241 @@
+//! Admin API for cloning this deployment's data, file storage, code,
+//! environment variables and pending scheduled jobs into another deployment.
+//! The target's admin key is never returned.
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    deployment_clones::{
+        types::DeploymentCloneState,
+        DeploymentClonesModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneDeploymentRequest {
+    /// URL of the deployment to clone into. Its tables, file storage, code and
+    /// the environment variables set here are replaced.
+    target_url: String,
+    target_admin_key: String,
+    /// Whether to copy file storage. Defaults to true.
+    include_storage: Option<bool>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneDeploymentResponse {
+    clone_id: String,
+}
+
+pub async fn clone_deployment(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(CloneDeploymentRequest {
+        target_url,
+        target_admin_key,
+        include_storage,
+    }): Json<CloneDeploymentRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let clone_id = DeploymentClonesModel::new(&mut tx)
+        .start(
+            target_url.clone(),
+            target_admin_key,
+            include_storage.unwrap_or(true),
+        )
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::CloneDeployment { target_url }],
+            "clone_deployment",
+        )
+        .await?;
+    Ok(Json(CloneDeploymentResponse {
+        clone_id: clone_id.developer_id.encode(),
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentCloneResponse {
+    id: String,
+    target_url: String,
+    include_storage: bool,
+    /// "pending", "copyingCode", "copyingEnvironmentVariables", "exporting",
+    /// "importing", "copyingScheduledJobs", "completed" or "failed".
+    state: &'static str,
+    /// Timestamps are nanoseconds since the Unix epoch.
+    started_ts: i64,
+    snapshot_ts: Option<i64>,
+    finished_ts: Option<i64>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeploymentClonesResponse {
+    /// Newest first.
+    deployment_clones: Vec<DeploymentCloneResponse>,
+}
+
+pub async fn list_deployment_clones(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let deployment_clones = DeploymentClonesModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .rev()
+        .map(|clone| {
+            let id = clone.developer_id().encode();
+            let clone = clone.into_value();
+            let mut response = DeploymentCloneResponse {
+                id,
+                target_url: clone.target_url,
+                include_storage: clone.include_storage,
+                state: clone.state.as_str(),
+                started_ts: clone.started_ts.into(),
+                snapshot_ts: None,
+                finished_ts: None,
+                error: None,
+            };
+            match clone.state {
+                DeploymentCloneState::Pending
+                | DeploymentCloneState::CopyingCode
+                | DeploymentCloneState::CopyingEnvironmentVariables
+                | DeploymentCloneState::Exporting => {},
+                DeploymentCloneState::Importing { snapshot_ts }
+                | DeploymentCloneState::CopyingScheduledJobs { snapshot_ts } => {
+                    response.snapshot_ts = Some(snapshot_ts.into());
+                },
+                DeploymentCloneState::Completed {
+                    snapshot_ts,
+                    completed_ts,
+                } => {
+                    response.snapshot_ts = Some(snapshot_ts.into());
+                    response.finished_ts = Some(completed_ts.into());
+                },
+                DeploymentCloneState::Failed { error, failed_ts } => {
+                    response.error = Some(error);
+                    response.finished_ts = Some(failed_ts.into());
+                },
+            }
+            response
+        })
+        .collect();
+    Ok(Json(ListDeploymentClonesResponse { deployment_clones }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        TestLocalBackend,
+    };
+
+    fn admin_request(
+        backend: &TestLocalBackend,
+        uri: &str,
+        body: Option<JsonValue>,
+    ) -> anyhow::Result<Request<Body>> {
+        let builder = Request::builder()
+            .uri(uri)
+            .header("Authorization", backend.admin_auth_header.0.encode());
+        Ok(match body {
+            Some(body) => builder
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body)?))?,
+            None => builder.method("GET").body(Body::empty())?,
+        })
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_clone_deployment(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend
+            .expect_error(
+                admin_request(
+                    &backend,
+                    "/api/clone_deployment",
+                    Some(json!({
+                        "targetUrl": "staging.example.com",
+                        "targetAdminKey": "target-admin-key",
+                    })),
+                )?,
+                StatusCode::BAD_REQUEST,
+                "InvalidDeploymentClone",
+            )
+            .await?;
+
+        let response: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/clone_deployment",
+                Some(json!({
+                    // Nothing listens here, so the clone fails once it starts.
+                    "targetUrl": "http://127.0.0.1:1/",
+                    "targetAdminKey": "target-admin-key",
+                    "includeStorage": false,
+                })),
+            )?)
+            .await?;
+        let clone_id = response["cloneId"].as_str().unwrap().to_string();
+
+        let response: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/list_deployment_clones",
+                None,
+            )?)
+            .await?;
+        let deployment_clones = response["deploymentClones"].as_array().unwrap();
+        assert_eq!(deployment_clones.len(), 1);
+        assert_eq!(deployment_clones[0]["id"], json!(clone_id));
+        assert_eq!(
+            deployment_clones[0]["targetUrl"],
+            json!("http://127.0.0.1:1")
+        );
+        assert_eq!(deployment_clones[0]["includeStorage"], json!(false));
+        assert!(!response.to_string().contains("target-admin-key"));
+        Ok(())
+    }
+}
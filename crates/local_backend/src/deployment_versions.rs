@@ -0,0 +1,319 @@
+use anyhow::Context;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    bootstrap_model::schema::SchemaState,
+    components::{
+        ComponentId,
+        ComponentPath,
+    },
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    schemas::DatabaseSchema,
+    types::ObjectKey,
+};
+use database::{
+    BootstrapComponentsModel,
+    SchemaModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use futures::TryStreamExt;
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    deployment_versions::DeploymentVersionModel,
+};
+use runtime::prod::ProdRuntime;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use storage::{
+    StorageExt,
+    Upload,
+};
+use value::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    deploy_config2::{
+        restore_push,
+        SerializedStartPushResponse,
+        StartPushResponse,
+    },
+    LocalAppState,
+};
+
+/// Store a push's `StartPushResponse` in module storage, so the version it
+/// deploys can be restored without pushing it again.
+pub(crate) async fn upload_push(
+    st: &LocalAppState,
+    start_push_json: Vec<u8>,
+) -> anyhow::Result<ObjectKey> {
+    let mut upload = st.application.modules_storage().start_upload().await?;
+    upload.write(start_push_json.into()).await?;
+    upload.complete().await
+}
+
+async fn download_push(
+    st: &LocalAppState,
+    storage_key: &ObjectKey,
+) -> anyhow::Result<StartPushResponse> {
+    let chunks: Vec<_> = st
+        .application
+        .modules_storage()
+        .get(storage_key)
+        .await?
+        .with_context(|| format!("Deployment version {storage_key:?} missing from storage"))?
+        .stream
+        .try_collect()
+        .await?;
+    let serialized: SerializedStartPushResponse = serde_json::from_slice(&chunks.concat())?;
+    serialized.try_into()
+}
+
+/// The ways `active` would break functions pushed with the schema `previous`:
+/// tables they declared that are gone or now hold a different document type,
+/// and indexes they declared that are gone or now index different fields.
+fn schema_incompatibilities(
+    previous: &DatabaseSchema,
+    active: Option<&DatabaseSchema>,
+) -> Vec<String> {
+    let mut problems = vec![];
+    for (table_name, table) in &previous.tables {
+        let Some(active_table) = active.and_then(|active| active.tables.get(table_name)) else {
+            problems.push(format!("table {table_name} is no longer in the schema"));
+            continue;
+        };
+        if table.document_type != active_table.document_type {
+            problems.push(format!("table {table_name} has a different document type"));
+        }
+        let changed_indexes = table
+            .indexes
+            .iter()
+            .filter(|(name, index)| active_table.indexes.get(*name) != Some(*index))
+            .map(|(name, _)| name)
+            .chain(
+                table
+                    .search_indexes
+                    .iter()
+                    .filter(|(name, index)| active_table.search_indexes.get(*name) != Some(*index))
+                    .map(|(name, _)| name),
+            )
+            .chain(
+                table
+                    .vector_indexes
+                    .iter()
+                    .filter(|(name, index)| active_table.vector_indexes.get(*name) != Some(*index))
+                    .map(|(name, _)| name),
+            );
+        for name in changed_indexes {
+            problems.push(format!("index {table_name}.{name} was removed or changed"));
+        }
+    }
+    problems
+}
+
+/// Check that every component of `previous` can run against the current
+/// schemas, since restoring a version leaves schemas and indexes as they are.
+pub(crate) async fn check_schemas_compatible(
+    tx: &mut Transaction<ProdRuntime>,
+    previous: &StartPushResponse,
+) -> anyhow::Result<()> {
+    let mut problems = vec![];
+    let mut components = vec![&previous.app];
+    while let Some(component) = components.pop() {
+        components.extend(component.child_components.values());
+        let component_path = &component.component_path;
+        let name = if component_path.is_root() {
+            "app".to_string()
+        } else {
+            format!("component {:?}", String::from(component_path.clone()))
+        };
+        let Some(component_id) = component_id(tx, previous, component_path).await? else {
+            problems.push(format!("{name} has been deleted"));
+            continue;
+        };
+        let Some(schema) = previous
+            .analysis
+            .get(&component.definition_path)
+            .and_then(|definition| definition.schema.as_ref())
+        else {
+            continue;
+        };
+        let active = SchemaModel::new(tx, component_id.into())
+            .get_by_state(SchemaState::Active)
+            .await?
+            .map(|(_, schema)| schema);
+        for problem in schema_incompatibilities(schema, active.as_ref()) {
+            problems.push(format!("{name}: {problem}"));
+        }
+    }
+    if !problems.is_empty() {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "IncompatibleDeploymentVersion",
+            format!(
+                "The current schema isn't compatible with this version's functions:\n{}",
+                problems.join("\n")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// The component at `component_path`, which may have been allocated by
+/// `previous` and deleted since.
+async fn component_id(
+    tx: &mut Transaction<ProdRuntime>,
+    previous: &StartPushResponse,
+    component_path: &ComponentPath,
+) -> anyhow::Result<Option<ComponentId>> {
+    if component_path.is_root() {
+        return Ok(Some(ComponentId::Root));
+    }
+    let existing = BootstrapComponentsModel::new(tx)
+        .resolve_path(component_path.clone())
+        .await?;
+    Ok(existing
+        .map(|doc| doc.id().internal_id())
+        .or_else(|| {
+            previous
+                .schema_change
+                .allocated_component_ids
+                .get(component_path)
+                .copied()
+        })
+        .map(ComponentId::Child))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentVersionJson {
+    id: String,
+    /// When the version was pushed, in milliseconds since the epoch.
+    pushed_at: Option<f64>,
+    udf_server_version: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListDeploymentVersionsResponse {
+    versions: Vec<DeploymentVersionJson>,
+}
+
+/// The versions that can be restored, most recent first. The first is the
+/// version currently deployed, unless a version has been restored since.
+pub async fn list_deployment_versions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let versions = DeploymentVersionModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|version| DeploymentVersionJson {
+            id: version.developer_id().encode(),
+            pushed_at: version.creation_time().map(f64::from),
+            udf_server_version: version.udf_server_version.clone(),
+        })
+        .collect();
+    Ok(Json(ListDeploymentVersionsResponse { versions }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreDeploymentVersionRequest {
+    version_id: String,
+}
+
+/// Switches the deployment's functions, components, auth and crons back to a
+/// recent version in one transaction, without pushing it again.
+pub async fn restore_deployment_version(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RestoreDeploymentVersionRequest { version_id }): Json<RestoreDeploymentVersionRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let id = DeveloperDocumentId::decode(&version_id).map_err(|_| {
+        ErrorMetadata::bad_request(
+            "InvalidDeploymentVersion",
+            format!("Invalid deployment version ID {version_id:?}"),
+        )
+    })?;
+    let mut tx = st.application.begin(identity).await?;
+    let Some(version) = DeploymentVersionModel::new(&mut tx).get(id).await? else {
+        return Err(anyhow::anyhow!(ErrorMetadata::not_found(
+            "DeploymentVersionNotFound",
+            format!("There is no deployment version {version_id:?}"),
+        ))
+        .into());
+    };
+    let previous = download_push(&st, &version.storage_key).await?;
+    restore_push(
+        &st,
+        previous,
+        DeploymentAuditLogEvent::RestoreDeploymentVersion { version_id },
+    )
+    .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        db_schema,
+        schemas::{
+            DocumentSchema,
+            IndexSchema,
+        },
+    };
+
+    use super::schema_incompatibilities;
+
+    #[test]
+    fn test_schema_incompatibilities() -> anyhow::Result<()> {
+        let mut previous = db_schema!("messages" => DocumentSchema::Any);
+        let messages = previous
+            .tables
+            .get_mut(&"messages".parse()?)
+            .expect("messages table");
+        let by_author = IndexSchema {
+            index_descriptor: "by_author".parse()?,
+            fields: vec!["author".parse()?].try_into()?,
+        };
+        messages
+            .indexes
+            .insert(by_author.index_descriptor.clone(), by_author);
+        assert!(schema_incompatibilities(&previous, Some(&previous)).is_empty());
+
+        // Adding tables and indexes doesn't break older functions.
+        let mut active = previous.clone();
+        active
+            .tables
+            .extend(db_schema!("users" => DocumentSchema::Any).tables);
+        assert!(schema_incompatibilities(&previous, Some(&active)).is_empty());
+
+        // Removing an index or changing a table's document type does.
+        let mut active = previous.clone();
+        let messages = active
+            .tables
+            .get_mut(&"messages".parse()?)
+            .expect("messages table");
+        messages.indexes.clear();
+        messages.document_type = Some(DocumentSchema::Union(vec![]));
+        assert_eq!(schema_incompatibilities(&previous, Some(&active)).len(), 2);
+        assert_eq!(schema_incompatibilities(&previous, None).len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    fmt,
+    str::FromStr,
+    sync::Arc,
+};
+
+use axum::{
+    extract::State,
+    response::{
+        IntoResponse,
+        Response,
+    },
+    Router,
+};
+use http::{
+    header::HOST,
+    Request,
+    StatusCode,
+};
+use hyper::Body;
+use tower::ServiceExt;
+
+/// Path prefix that selects a deployment, as in `/d/<name>/api/sync`.
+const DEPLOYMENT_PATH_PREFIX: &str = "/d/";
+
+/// Path prefix of HTTP actions forwarded by the site proxy from
+/// `<site>/d/<name>/...`.
+const HTTP_ACTION_DEPLOYMENT_PATH_PREFIX: &str = "/http/d/";
+
+/// One of several deployments served by the same backend, each with its own
+/// database, storage and instance secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DeploymentConfig {
+    /// Lowercase name, which is also the deployment's instance name.
+    pub name: String,
+    /// Instance secret, if not the backend's `--instance-secret`.
+    pub instance_secret: Option<String>,
+}
+
+impl fmt::Debug for DeploymentConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DeploymentConfig")
+            .field("name", &self.name)
+            .field("has_instance_secret", &self.instance_secret.is_some())
+            .finish()
+    }
+}
+
+impl FromStr for DeploymentConfig {
+    type Err = anyhow::Error;
+
+    /// Parses `<name>` or `<name>=<instance secret>`.
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (name, instance_secret) = match s.split_once('=') {
+            Some((name, secret)) => (name, Some(secret.trim().to_string())),
+            None => (s, None),
+        };
+        let name = name.trim().to_string();
+        anyhow::ensure!(
+            is_valid_deployment_name(&name),
+            "Invalid deployment name {name:?}. Names must be lowercase letters, digits and \
+             dashes, like `staging`."
+        );
+        Ok(Self {
+            name,
+            instance_secret,
+        })
+    }
+}
+
+fn is_valid_deployment_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 63
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Routes each request to the router of the deployment it's for, which is
+/// picked by the path prefix `/d/<name>` (stripped before routing) or else by
+/// the first label of the `Host` header, as in `<name>.example.com`.
+pub fn multi_deployment_router(routers: BTreeMap<String, Router>) -> Router {
+    Router::new()
+        .fallback(route_to_deployment)
+        .with_state(Arc::new(routers))
+}
+
+async fn route_to_deployment(
+    State(routers): State<Arc<BTreeMap<String, Router>>>,
+    mut request: Request<Body>,
+) -> Response {
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path_and_query| path_and_query.as_str());
+    let host = request
+        .headers()
+        .get(HOST)
+        .and_then(|host| host.to_str().ok());
+    let Some((name, path_and_query)) =
+        select_deployment(|name| routers.contains_key(name), path_and_query, host)
+    else {
+        return (
+            StatusCode::NOT_FOUND,
+            "No deployment found for this request. Use the path prefix /d/<deployment name>.",
+        )
+            .into_response();
+    };
+    match path_and_query.parse() {
+        Ok(uri) => *request.uri_mut() = uri,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+    let router = routers[&name].clone();
+    router.oneshot(request).await.unwrap_or_else(|e| match e {})
+}
+
+/// Returns the deployment a request with `path_and_query` and `host` is for,
+/// and the path and query to route within it.
+fn select_deployment(
+    is_deployment: impl Fn(&str) -> bool,
+    path_and_query: &str,
+    host: Option<&str>,
+) -> Option<(String, String)> {
+    for (prefix, new_prefix) in [
+        (DEPLOYMENT_PATH_PREFIX, ""),
+        (HTTP_ACTION_DEPLOYMENT_PATH_PREFIX, "/http"),
+    ] {
+        let Some(rest) = path_and_query.strip_prefix(prefix) else {
+            continue;
+        };
+        let (name, rest) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+        if is_deployment(name) {
+            let rest = if rest.starts_with('/') {
+                rest.to_string()
+            } else {
+                format!("/{rest}")
+            };
+            return Some((name.to_string(), format!("{new_prefix}{rest}")));
+        }
+    }
+    let host = host?.split(':').next()?;
+    let (label, _) = host.split_once('.')?;
+    let label = label.to_ascii_lowercase();
+    is_deployment(&label).then(|| (label, path_and_query.to_string()))
+}
+
+/// Checks that no two deployments have the same name.
+pub fn validate_deployments(deployments: &[DeploymentConfig]) -> anyhow::Result<()> {
+    let mut names = BTreeSet::new();
+    for deployment in deployments {
+        anyhow::ensure!(
+            names.insert(deployment.name.as_str()),
+            "Deployment {} is configured more than once",
+            deployment.name
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        select_deployment,
+        DeploymentConfig,
+    };
+
+    #[test]
+    fn test_parse_deployment() -> anyhow::Result<()> {
+        assert_eq!(
+            "staging".parse::<DeploymentConfig>()?,
+            DeploymentConfig {
+                name: "staging".to_string(),
+                instance_secret: None,
+            }
+        );
+        assert_eq!(
+            "prod=4361726e697461732c206c69746572616c6c79".parse::<DeploymentConfig>()?,
+            DeploymentConfig {
+                name: "prod".to_string(),
+                instance_secret: Some("4361726e697461732c206c69746572616c6c79".to_string()),
+            }
+        );
+        assert!("".parse::<DeploymentConfig>().is_err());
+        assert!("Prod".parse::<DeploymentConfig>().is_err());
+        assert!("my_app".parse::<DeploymentConfig>().is_err());
+        assert!("-dev".parse::<DeploymentConfig>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_deployment() {
+        let is_deployment = |name: &str| name == "dev" || name == "prod";
+        let select = |path: &str, host: Option<&str>| select_deployment(is_deployment, path, host);
+        assert_eq!(
+            select("/d/dev/api/sync", None),
+            Some(("dev".to_string(), "/api/sync".to_string()))
+        );
+        assert_eq!(
+            select("/d/prod?a=b", None),
+            Some(("prod".to_string(), "/?a=b".to_string()))
+        );
+        assert_eq!(
+            select("/http/d/dev/hello?a=b", None),
+            Some(("dev".to_string(), "/http/hello?a=b".to_string()))
+        );
+        assert_eq!(
+            select("/api/sync", Some("Prod.example.com:3210")),
+            Some(("prod".to_string(), "/api/sync".to_string()))
+        );
+        // The path prefix takes precedence over the host.
+        assert_eq!(
+            select("/d/dev/api/sync", Some("prod.localhost")),
+            Some(("dev".to_string(), "/api/sync".to_string()))
+        );
+        assert_eq!(select("/d/staging/api/sync", None), None);
+        assert_eq!(select("/api/sync", Some("127.0.0.1:3210")), None);
+        assert_eq!(select("/api/sync", Some("dev")), None);
+    }
+}
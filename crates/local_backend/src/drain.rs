@@ -0,0 +1,212 @@
+//! Draining lets a backend be replaced without aborting user functions, e.g.
+//! in a rolling update. Once `/api/drain` is called, the backend:
+//!
+//! 1. Rejects new WebSocket sessions, function calls and HTTP actions with 503,
+//!    and fails `/readyz`, so load balancers move traffic elsewhere. Clients
+//!    retry those on another backend.
+//! 2. Stops reading messages from existing WebSocket sessions, finishes the
+//!    mutations and actions they've started and then closes them, after which
+//!    clients reconnect elsewhere.
+//! 3. Stops starting scheduled and cron jobs and waits for running functions
+//!    and jobs to finish, up to the drain timeout.
+//! 4. Shuts down like it does on Ctrl-C: it finishes in-flight HTTP requests,
+//!    flushes usage events, stops committing, and exits.
+
+use std::{
+    sync::atomic::Ordering,
+    time::Duration,
+};
+
+use axum::{
+    extract::State,
+    middleware::Next,
+    response::{
+        IntoResponse,
+        Response,
+    },
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use errors::ErrorMetadata;
+use http::Request;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use tokio::sync::watch;
+
+use crate::{
+    admin::must_be_admin_with_write_access,
+    authentication::ExtractIdentity,
+    LocalAppState,
+    RouterState,
+};
+
+/// How long to wait for running functions and scheduled jobs to finish if the
+/// drain request doesn't say.
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Whether the backend is draining, shared by the admin API that starts it,
+/// the routes that stop accepting requests and the server that shuts down.
+pub struct Drain {
+    // The drain timeout once draining has started.
+    timeout_tx: watch::Sender<Option<Duration>>,
+}
+
+impl Drain {
+    pub fn new() -> Self {
+        let (timeout_tx, _) = watch::channel(None);
+        Self { timeout_tx }
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.timeout_tx.borrow().is_some()
+    }
+
+    /// Start draining, returning false if the backend is already draining.
+    pub fn start(&self, timeout: Duration) -> bool {
+        self.timeout_tx.send_if_modified(|current| {
+            if current.is_some() {
+                return false;
+            }
+            *current = Some(timeout);
+            true
+        })
+    }
+
+    /// Wait until draining starts, returning the drain timeout.
+    pub async fn started(&self) -> Duration {
+        let mut timeout_rx = self.timeout_tx.subscribe();
+        let timeout = timeout_rx
+            .wait_for(Option::is_some)
+            .await
+            .expect("Drain's sender was dropped while it's borrowed");
+        timeout.expect("Waited for the drain timeout to be set")
+    }
+}
+
+impl Default for Drain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn draining_error() -> anyhow::Error {
+    anyhow::anyhow!(ErrorMetadata::rejected_before_execution(
+        "BackendDraining",
+        "This backend is shutting down. Retry the request.",
+    ))
+}
+
+/// Reject requests once the backend is draining. Requests that have already
+/// started aren't affected.
+pub async fn reject_while_draining<B: Send>(
+    State(st): State<RouterState>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if st.drain.is_draining() {
+        return HttpResponseError::from(draining_error()).into_response();
+    }
+    next.run(req).await
+}
+
+/// Whether the backend is draining, for `/readyz`.
+pub fn check_not_draining(st: &LocalAppState) -> anyhow::Result<()> {
+    anyhow::ensure!(!st.drain.is_draining(), "The backend is draining");
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainRequest {
+    /// How long to wait for running functions and scheduled jobs to finish
+    /// before shutting down anyway.
+    timeout_secs: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DrainResponse {
+    pub running_functions: usize,
+    pub running_scheduled_jobs: usize,
+    pub live_web_sockets: u64,
+}
+
+/// Start draining the backend, after which it exits. Returns what's still
+/// running, and can be called again to check on that.
+pub async fn drain(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(req): Json<DrainRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let timeout = req
+        .timeout_secs
+        .map_or(DEFAULT_DRAIN_TIMEOUT, Duration::from_secs);
+    if st.drain.start(timeout) {
+        tracing::info!("Draining backend with a timeout of {timeout:?}");
+    }
+    Ok(Json(DrainResponse {
+        running_functions: st.application.runner().num_running_functions(),
+        running_scheduled_jobs: st.application.num_running_scheduled_jobs(),
+        live_web_sockets: st.live_ws_count.load(Ordering::SeqCst),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use super::{
+        Drain,
+        DrainResponse,
+    };
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[tokio::test]
+    async fn test_drain_starts_once() {
+        let drain = Drain::new();
+        assert!(!drain.is_draining());
+        assert!(drain.start(Duration::from_secs(5)));
+        assert!(!drain.start(Duration::from_secs(10)));
+        assert!(drain.is_draining());
+        assert_eq!(drain.started().await, Duration::from_secs(5));
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_drain_rejects_new_requests(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/api/drain")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(Body::from(serde_json::to_vec(&json!({"timeoutSecs": 5}))?))?;
+        let response: DrainResponse = backend.expect_success(req).await?;
+        assert_eq!(response.running_functions, 0);
+        assert!(backend.st.drain.is_draining());
+
+        let req = Request::builder()
+            .uri("/api/query")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(
+                &json!({"path": "getCounter", "args": {}}),
+            )?))?;
+        backend
+            .expect_error(req, StatusCode::SERVICE_UNAVAILABLE, "BackendDraining")
+            .await?;
+        Ok(())
+    }
+}
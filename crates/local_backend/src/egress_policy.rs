@@ -0,0 +1,142 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    egress_policy::{
+        types::{
+            EgressPolicy,
+            EgressProxy,
+        },
+        EgressPolicyModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EgressProxyRequest {
+    /// e.g. "http://proxy.example.com:3128".
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetEgressPolicyRequest {
+    /// If nonempty, actions can only make requests to these domains and their
+    /// subdomains.
+    #[serde(default)]
+    allowed_domains: Vec<String>,
+    /// Actions can't make requests to these domains or their subdomains.
+    #[serde(default)]
+    denied_domains: Vec<String>,
+    /// If set, all of the requests actions make go through this proxy.
+    proxy: Option<EgressProxyRequest>,
+}
+
+/// Restricts the requests actions can make, in both the isolate and Node.
+/// Requests the policy blocks fail with an `EgressDenied` error.
+pub async fn set_egress_policy(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetEgressPolicyRequest {
+        allowed_domains,
+        denied_domains,
+        proxy,
+    }): Json<SetEgressPolicyRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let policy = EgressPolicy {
+        allowed_domains,
+        denied_domains,
+        proxy: proxy.map(|proxy| EgressProxy {
+            url: proxy.url,
+            username: proxy.username,
+            password: proxy.password,
+        }),
+    };
+    let event = DeploymentAuditLogEvent::UpdateEgressPolicy {
+        allowed_domains: policy.allowed_domains.clone(),
+        denied_domains: policy.denied_domains.clone(),
+        proxy_url: policy.proxy.as_ref().map(|proxy| proxy.url.clone()),
+    };
+    let mut tx = st.application.begin(identity).await?;
+    EgressPolicyModel::new(&mut tx).set(policy).await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "set_egress_policy")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+/// Removes the egress policy, so actions can make requests anywhere again.
+pub async fn delete_egress_policy(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    EgressPolicyModel::new(&mut tx).clear().await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteEgressPolicy],
+            "delete_egress_policy",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EgressProxyJson {
+    url: String,
+    username: Option<String>,
+    // The password itself is never returned.
+    has_password: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EgressPolicyResponse {
+    allowed_domains: Vec<String>,
+    denied_domains: Vec<String>,
+    proxy: Option<EgressProxyJson>,
+}
+
+pub async fn get_egress_policy(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let policy = EgressPolicyModel::new(&mut tx).get().await?;
+    Ok(Json(EgressPolicyResponse {
+        allowed_domains: policy.allowed_domains,
+        denied_domains: policy.denied_domains,
+        proxy: policy.proxy.map(|proxy| EgressProxyJson {
+            url: proxy.url,
+            username: proxy.username,
+            has_password: proxy.password.is_some(),
+        }),
+    }))
+}
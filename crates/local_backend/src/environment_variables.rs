@@ -8,15 +8,17 @@ use common::http::{
     HttpResponseError,
 };
 use http::StatusCode;
+use keybroker::AdminOperation;
 use model::environment_variables::types::{
     EnvVarName,
     EnvVarValue,
     EnvironmentVariable,
+    SecretReference,
 };
 use serde::Deserialize;
 
 use crate::{
-    admin::must_be_admin_with_write_access,
+    admin::must_be_admin_for,
     authentication::ExtractIdentity,
     LocalAppState,
 };
@@ -56,7 +58,7 @@ pub async fn update_environment_variables(
     ExtractIdentity(identity): ExtractIdentity,
     Json(UpdateEnvVarsRequest { changes }): Json<UpdateEnvVarsRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    must_be_admin_with_write_access(&identity)?;
+    must_be_admin_for(&identity, AdminOperation::Deploy)?;
 
     let mut env_var_changes = vec![];
     for change in changes {
@@ -80,6 +82,8 @@ pub async fn update_environment_variables(
 fn validate_env_var(name: &String, value: &String) -> anyhow::Result<EnvironmentVariable> {
     let name: EnvVarName = name.parse()?;
     let value: EnvVarValue = value.parse()?;
+    // Reject malformed secret references now rather than when an action runs.
+    SecretReference::parse(&value)?;
     Ok(EnvironmentVariable::new(name, value))
 }
 
@@ -87,14 +91,23 @@ fn validate_env_var(name: &String, value: &String) -> anyhow::Result<Environment
 mod tests {
     use std::collections::BTreeMap;
 
+    use application::test_helpers::ApplicationTestExt;
     use axum::headers::authorization::Credentials;
     use common::types::{
         EnvVarName,
         EnvVarValue,
+        MemberId,
+    };
+    use http::{
+        HeaderValue,
+        Request,
+        StatusCode,
     };
-    use http::Request;
     use hyper::Body;
-    use keybroker::Identity;
+    use keybroker::{
+        AdminRole,
+        Identity,
+    };
     use maplit::btreemap;
     use model::environment_variables::EnvironmentVariablesModel;
     use runtime::prod::ProdRuntime;
@@ -109,16 +122,23 @@ mod tests {
         backend: &TestLocalBackend,
         changes: serde_json::Value,
     ) -> anyhow::Result<()> {
+        let req =
+            update_environment_variables_request(backend.admin_auth_header.0.encode(), changes)?;
+        backend.expect_success(req).await?;
+        Ok(())
+    }
+
+    fn update_environment_variables_request(
+        auth_header: HeaderValue,
+        changes: serde_json::Value,
+    ) -> anyhow::Result<Request<Body>> {
         let json_body = json!({"changes": changes});
-        let body = Body::from(serde_json::to_vec(&json_body)?);
-        let req = Request::builder()
+        Ok(Request::builder()
             .uri("/api/update_environment_variables")
             .method("POST")
             .header("Content-Type", "application/json")
-            .header("Authorization", backend.admin_auth_header.0.encode())
-            .body(body)?;
-        backend.expect_success(req).await?;
-        Ok(())
+            .header("Authorization", auth_header)
+            .body(Body::from(serde_json::to_vec(&json_body)?))?)
     }
 
     async fn list_environment_variables(
@@ -207,4 +227,54 @@ mod tests {
         );
         Ok(())
     }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_env_vars_admin_roles(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let key_broker = backend.st.application.key_broker();
+        let deploy_header = key_broker
+            .issue_admin_key_with_role(MemberId(2), AdminRole::Deploy)
+            .as_header()?
+            .0
+            .encode();
+        let read_only_header = key_broker
+            .issue_read_only_admin_key(MemberId(2))
+            .as_header()?
+            .0
+            .encode();
+
+        // Deploy keys can change environment variables but can't read data.
+        let req = update_environment_variables_request(
+            deploy_header.clone(),
+            json!([{"name": "name1", "value": "value1"}]),
+        )?;
+        let _: serde_json::Value = backend.expect_success(req).await?;
+        let query_body = json!({"path": "values:intQuery", "args": {}, "format": "json"});
+        let req = Request::builder()
+            .uri("/api/query")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", deploy_header)
+            .body(Body::from(serde_json::to_vec(&query_body)?))?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "Unauthorized")
+            .await?;
+
+        // Read-only keys can't change anything.
+        let req = update_environment_variables_request(
+            read_only_header,
+            json!([{"name": "name2", "value": "value2"}]),
+        )?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "ReadOnlyAdminKey")
+            .await?;
+        assert_eq!(
+            list_environment_variables(&backend).await?,
+            btreemap! {
+                "name1".parse()? => "value1".parse()?,
+            }
+        );
+        Ok(())
+    }
 }
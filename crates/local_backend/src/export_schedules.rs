@@ -0,0 +1,368 @@
+//! Admin API for recurring exports of the deployment's tables to an S3 or GCS
+//! bucket. Bucket credentials are never returned once configured.
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    types::Timestamp,
+};
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    scheduled_exports::{
+        types::{
+            parse_timezone,
+            BucketProvider,
+            ExportDestination,
+            ExportRunState,
+            ExportSchedule,
+        },
+        ScheduledExportsModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// How many of each schedule's latest runs are listed.
+const NUM_LISTED_RUNS: usize = 10;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportDestinationJson {
+    /// "s3" or "gcs".
+    provider: String,
+    bucket: String,
+    #[serde(default)]
+    prefix: String,
+    /// Defaults to "us-east-1" for S3 and "auto" for GCS.
+    region: Option<String>,
+    /// An S3-compatible endpoint to use instead of the provider's.
+    endpoint: Option<String>,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddExportScheduleRequest {
+    name: String,
+    /// Five field cron expression, e.g. "0 3 * * *" for 3am every day.
+    cron: String,
+    /// IANA timezone of the cron expression. Defaults to UTC.
+    timezone: Option<String>,
+    #[serde(default)]
+    include_storage: bool,
+    destination: ExportDestinationJson,
+    /// How many completed exports to keep in the bucket.
+    retention_count: u64,
+}
+
+pub async fn add_export_schedule(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(AddExportScheduleRequest {
+        name,
+        cron,
+        timezone,
+        include_storage,
+        destination,
+        retention_count,
+    }): Json<AddExportScheduleRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let provider = destination.provider.parse()?;
+    let region = destination.region.unwrap_or_else(|| match provider {
+        BucketProvider::S3 => "us-east-1".to_string(),
+        BucketProvider::Gcs => "auto".to_string(),
+    });
+    let schedule = ExportSchedule {
+        name: name.clone(),
+        cron,
+        timezone: timezone.map(|tz| parse_timezone(&tz)).transpose()?,
+        include_storage,
+        destination: ExportDestination {
+            provider,
+            bucket: destination.bucket,
+            prefix: destination.prefix,
+            region,
+            endpoint: destination.endpoint,
+            access_key_id: destination.access_key_id,
+            secret_access_key: destination.secret_access_key,
+        },
+        retention_count,
+        // Computed from the cron expression when the schedule is saved.
+        next_ts: Timestamp::MIN,
+    };
+    let mut tx = st.application.begin(identity).await?;
+    ScheduledExportsModel::new(&mut tx)
+        .add_or_update(schedule)
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::CreateExportSchedule { name }],
+            "add_export_schedule",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteExportScheduleRequest {
+    name: String,
+}
+
+pub async fn delete_export_schedule(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteExportScheduleRequest { name }): Json<DeleteExportScheduleRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    ScheduledExportsModel::new(&mut tx).delete(&name).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteExportSchedule { name }],
+            "delete_export_schedule",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportRunResponse {
+    /// "exporting", "uploading", "completed" or "failed".
+    state: &'static str,
+    /// Timestamps are nanoseconds since the Unix epoch.
+    started_ts: i64,
+    snapshot_ts: Option<i64>,
+    object_key: Option<String>,
+    size_bytes: Option<u64>,
+    finished_ts: Option<i64>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportScheduleResponse {
+    name: String,
+    cron: String,
+    timezone: Option<String>,
+    include_storage: bool,
+    provider: String,
+    bucket: String,
+    prefix: String,
+    region: String,
+    endpoint: Option<String>,
+    retention_count: u64,
+    next_ts: i64,
+    /// The schedule's latest runs, newest first.
+    runs: Vec<ExportRunResponse>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListExportSchedulesResponse {
+    export_schedules: Vec<ExportScheduleResponse>,
+}
+
+pub async fn list_export_schedules(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let mut model = ScheduledExportsModel::new(&mut tx);
+    let mut export_schedules = vec![];
+    for schedule in model.list().await? {
+        let runs = model
+            .latest_runs(&schedule.name, NUM_LISTED_RUNS)
+            .await?
+            .into_iter()
+            .map(|run| {
+                let run = run.into_value();
+                let mut response = ExportRunResponse {
+                    state: "exporting",
+                    started_ts: run.started_ts.into(),
+                    snapshot_ts: None,
+                    object_key: None,
+                    size_bytes: None,
+                    finished_ts: None,
+                    error: None,
+                };
+                match run.state {
+                    ExportRunState::Exporting => {},
+                    ExportRunState::Uploading { snapshot_ts } => {
+                        response.state = "uploading";
+                        response.snapshot_ts = Some(snapshot_ts.into());
+                    },
+                    ExportRunState::Completed {
+                        snapshot_ts,
+                        object_key,
+                        size_bytes,
+                        completed_ts,
+                    } => {
+                        response.state = "completed";
+                        response.snapshot_ts = Some(snapshot_ts.into());
+                        response.object_key = Some(object_key);
+                        response.size_bytes = Some(size_bytes);
+                        response.finished_ts = Some(completed_ts.into());
+                    },
+                    ExportRunState::Failed { error, failed_ts } => {
+                        response.state = "failed";
+                        response.error = Some(error);
+                        response.finished_ts = Some(failed_ts.into());
+                    },
+                }
+                response
+            })
+            .collect();
+        let schedule = schedule.into_value();
+        export_schedules.push(ExportScheduleResponse {
+            name: schedule.name,
+            cron: schedule.cron,
+            timezone: schedule.timezone.map(|tz| tz.name().to_string()),
+            include_storage: schedule.include_storage,
+            provider: schedule.destination.provider.to_string(),
+            bucket: schedule.destination.bucket,
+            prefix: schedule.destination.prefix,
+            region: schedule.destination.region,
+            endpoint: schedule.destination.endpoint,
+            retention_count: schedule.retention_count,
+            next_ts: schedule.next_ts.into(),
+            runs,
+        });
+    }
+    Ok(Json(ListExportSchedulesResponse { export_schedules }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        TestLocalBackend,
+    };
+
+    fn admin_request(
+        backend: &TestLocalBackend,
+        uri: &str,
+        body: Option<JsonValue>,
+    ) -> anyhow::Result<Request<Body>> {
+        let builder = Request::builder()
+            .uri(uri)
+            .header("Authorization", backend.admin_auth_header.0.encode());
+        Ok(match body {
+            Some(body) => builder
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body)?))?,
+            None => builder.method("GET").body(Body::empty())?,
+        })
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_configure_export_schedules(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let schedule = json!({
+            "name": "nightly",
+            "cron": "0 3 * * *",
+            "timezone": "Europe/Berlin",
+            "destination": {
+                "provider": "s3",
+                "bucket": "backups",
+                "prefix": "convex/",
+                "accessKeyId": "AKIDEXAMPLE",
+                "secretAccessKey": "secret-access-key",
+            },
+            "retentionCount": 7,
+        });
+        let _: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/add_export_schedule",
+                Some(schedule.clone()),
+            )?)
+            .await?;
+        let mut invalid = schedule.clone();
+        invalid["cron"] = json!("every night");
+        backend
+            .expect_error(
+                admin_request(&backend, "/api/add_export_schedule", Some(invalid))?,
+                StatusCode::BAD_REQUEST,
+                "InvalidExportSchedule",
+            )
+            .await?;
+        let mut invalid = schedule.clone();
+        invalid["destination"]["provider"] = json!("azure");
+        backend
+            .expect_error(
+                admin_request(&backend, "/api/add_export_schedule", Some(invalid))?,
+                StatusCode::BAD_REQUEST,
+                "InvalidBucketProvider",
+            )
+            .await?;
+
+        let response: JsonValue = backend
+            .expect_success(admin_request(&backend, "/api/list_export_schedules", None)?)
+            .await?;
+        let export_schedules = response["exportSchedules"].as_array().unwrap();
+        assert_eq!(export_schedules.len(), 1);
+        assert_eq!(export_schedules[0]["timezone"], json!("Europe/Berlin"));
+        assert_eq!(export_schedules[0]["region"], json!("us-east-1"));
+        assert_eq!(export_schedules[0]["retentionCount"], json!(7));
+        assert_eq!(export_schedules[0]["runs"], json!([]));
+        assert!(!response.to_string().contains("secret-access-key"));
+
+        let _: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/delete_export_schedule",
+                Some(json!({ "name": "nightly" })),
+            )?)
+            .await?;
+        backend
+            .expect_error(
+                admin_request(
+                    &backend,
+                    "/api/delete_export_schedule",
+                    Some(json!({ "name": "nightly" })),
+                )?,
+                StatusCode::NOT_FOUND,
+                "ExportScheduleNotFound",
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+//! Canaries are started by `deploy2/start_canary`, which uploads the new
+//! version's code. Calls are routed between the versions when they're run,
+//! so cached query results computed before a rollout change may still be
+//! served from the other version until the data they read changes.
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    function_canaries::FunctionCanariesModel,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    function_flags::parse_function_path,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFunctionCanaryRolloutRequest {
+    /// The component the function is in. Defaults to the app itself.
+    component_path: Option<String>,
+    /// e.g. "messages:send".
+    udf_path: String,
+    /// The percentage of users whose calls run the canary, from 0 to 100.
+    rollout_percentage: u8,
+}
+
+/// Moves more or fewer of a function's callers to its canary. Users already
+/// on the canary stay on it when the percentage is raised.
+pub async fn set_function_canary_rollout(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetFunctionCanaryRolloutRequest {
+        component_path,
+        udf_path,
+        rollout_percentage,
+    }): Json<SetFunctionCanaryRolloutRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let path = parse_function_path(component_path, &udf_path)?;
+    let mut tx = st.application.begin(identity).await?;
+    FunctionCanariesModel::new(&mut tx)
+        .set_rollout(&path, rollout_percentage)
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::SetFunctionCanary {
+                component_path: path.component.into(),
+                udf_path: path.udf_path.into(),
+                rollout_percentage,
+            }],
+            "set_function_canary_rollout",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFunctionCanaryRequest {
+    component_path: Option<String>,
+    udf_path: String,
+}
+
+/// Ends a function's canary, so every call runs the active version again.
+pub async fn delete_function_canary(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteFunctionCanaryRequest {
+        component_path,
+        udf_path,
+    }): Json<DeleteFunctionCanaryRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let path = parse_function_path(component_path, &udf_path)?;
+    let mut tx = st.application.begin(identity).await?;
+    FunctionCanariesModel::new(&mut tx).delete(&path).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteFunctionCanary {
+                component_path: path.component.into(),
+                udf_path: path.udf_path.into(),
+            }],
+            "delete_function_canary",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCanaryJson {
+    component_path: String,
+    udf_path: String,
+    source_package_id: String,
+    rollout_percentage: u8,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFunctionCanariesResponse {
+    canaries: Vec<FunctionCanaryJson>,
+}
+
+pub async fn list_function_canaries(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let canaries = FunctionCanariesModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|canary| {
+            let canary = canary.into_value();
+            FunctionCanaryJson {
+                component_path: canary.path.component.into(),
+                udf_path: canary.path.udf_path.into(),
+                source_package_id: DeveloperDocumentId::from(canary.source_package_id).encode(),
+                rollout_percentage: canary.rollout_percentage,
+            }
+        })
+        .collect();
+    Ok(Json(ListFunctionCanariesResponse { canaries }))
+}
@@ -0,0 +1,193 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    function_flags::{
+        types::{
+            FunctionFlag,
+            FunctionFlagMode,
+        },
+        FunctionFlagsModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    parse::parse_udf_path,
+    LocalAppState,
+};
+
+pub(crate) fn parse_function_path(
+    component_path: Option<String>,
+    udf_path: &str,
+) -> anyhow::Result<CanonicalizedComponentFunctionPath> {
+    let component = match component_path {
+        Some(component_path) => component_path.parse().map_err(|e: anyhow::Error| {
+            ErrorMetadata::bad_request("InvalidComponentPath", e.to_string())
+        })?,
+        None => ComponentPath::root(),
+    };
+    Ok(CanonicalizedComponentFunctionPath {
+        component,
+        udf_path: parse_udf_path(udf_path)?.canonicalize(),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFunctionFlagRequest {
+    /// The component the function is in. Defaults to the app itself.
+    component_path: Option<String>,
+    /// e.g. "messages:send".
+    udf_path: String,
+    /// "disabled" or "canary".
+    mode: String,
+    /// The percentage of users a "canary" function is rolled out to.
+    rollout_percentage: Option<u8>,
+    /// Included in the error callers get when the flag rejects their call.
+    reason: Option<String>,
+}
+
+fn parse_mode(mode: &str, rollout_percentage: Option<u8>) -> anyhow::Result<FunctionFlagMode> {
+    match (mode, rollout_percentage) {
+        ("disabled", None) => Ok(FunctionFlagMode::Disabled),
+        ("canary", Some(rollout_percentage)) => Ok(FunctionFlagMode::Canary { rollout_percentage }),
+        _ => anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidFunctionFlag",
+            "mode must be \"disabled\", or \"canary\" with a rolloutPercentage",
+        )),
+    }
+}
+
+/// Disables a function, or rolls it out to a percentage of users, without
+/// pushing code. Callers the flag rejects get a `FunctionDisabled` error.
+pub async fn set_function_flag(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetFunctionFlagRequest {
+        component_path,
+        udf_path,
+        mode,
+        rollout_percentage,
+        reason,
+    }): Json<SetFunctionFlagRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let flag = FunctionFlag {
+        path: parse_function_path(component_path, &udf_path)?,
+        mode: parse_mode(&mode, rollout_percentage)?,
+        reason,
+    };
+    let event = DeploymentAuditLogEvent::SetFunctionFlag {
+        component_path: flag.path.component.clone().into(),
+        udf_path: flag.path.udf_path.clone().into(),
+        mode: flag.mode.to_string(),
+    };
+    let mut tx = st.application.begin(identity).await?;
+    FunctionFlagsModel::new(&mut tx).set(flag).await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "set_function_flag")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFunctionFlagRequest {
+    component_path: Option<String>,
+    udf_path: String,
+}
+
+/// Removes a function's flag, so every caller can call it again.
+pub async fn delete_function_flag(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteFunctionFlagRequest {
+        component_path,
+        udf_path,
+    }): Json<DeleteFunctionFlagRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let path = parse_function_path(component_path, &udf_path)?;
+    let mut tx = st.application.begin(identity).await?;
+    FunctionFlagsModel::new(&mut tx).delete(&path).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteFunctionFlag {
+                component_path: path.component.into(),
+                udf_path: path.udf_path.into(),
+            }],
+            "delete_function_flag",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionFlagJson {
+    component_path: String,
+    udf_path: String,
+    mode: &'static str,
+    rollout_percentage: Option<u8>,
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFunctionFlagsResponse {
+    flags: Vec<FunctionFlagJson>,
+}
+
+pub async fn list_function_flags(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let flags = FunctionFlagsModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|flag| {
+            let flag = flag.into_value();
+            let (mode, rollout_percentage) = match flag.mode {
+                FunctionFlagMode::Disabled => ("disabled", None),
+                FunctionFlagMode::Canary { rollout_percentage } => {
+                    ("canary", Some(rollout_percentage))
+                },
+            };
+            FunctionFlagJson {
+                component_path: flag.path.component.into(),
+                udf_path: flag.path.udf_path.into(),
+                mode,
+                rollout_percentage,
+                reason: flag.reason,
+            }
+        })
+        .collect();
+    Ok(Json(ListFunctionFlagsResponse { flags }))
+}
@@ -0,0 +1,145 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    function_read_budgets::{
+        types::FunctionReadBudget,
+        FunctionReadBudgetsModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    function_flags::parse_function_path,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetFunctionReadBudgetRequest {
+    /// The component the function is in. Defaults to the app itself.
+    component_path: Option<String>,
+    /// e.g. "messages:list".
+    udf_path: String,
+    /// The most documents a call can read from user tables.
+    max_documents: Option<u64>,
+    /// The most bytes of documents a call can read from user tables.
+    max_bytes: Option<u64>,
+}
+
+/// Limits how much a query or mutation can read. Calls that go over the
+/// budget fail with a `ReadBudgetExceeded` error naming the index they were
+/// reading, instead of running until they hit the transaction limits.
+pub async fn set_function_read_budget(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetFunctionReadBudgetRequest {
+        component_path,
+        udf_path,
+        max_documents,
+        max_bytes,
+    }): Json<SetFunctionReadBudgetRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let budget = FunctionReadBudget {
+        path: parse_function_path(component_path, &udf_path)?,
+        max_documents,
+        max_bytes,
+    };
+    let event = DeploymentAuditLogEvent::SetFunctionReadBudget {
+        component_path: budget.path.component.clone().into(),
+        udf_path: budget.path.udf_path.clone().into(),
+    };
+    let mut tx = st.application.begin(identity).await?;
+    FunctionReadBudgetsModel::new(&mut tx).set(budget).await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "set_function_read_budget")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFunctionReadBudgetRequest {
+    component_path: Option<String>,
+    udf_path: String,
+}
+
+/// Removes a function's read budget, so only the transaction limits apply.
+pub async fn delete_function_read_budget(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteFunctionReadBudgetRequest {
+        component_path,
+        udf_path,
+    }): Json<DeleteFunctionReadBudgetRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let path = parse_function_path(component_path, &udf_path)?;
+    let mut tx = st.application.begin(identity).await?;
+    FunctionReadBudgetsModel::new(&mut tx).delete(&path).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteFunctionReadBudget {
+                component_path: path.component.into(),
+                udf_path: path.udf_path.into(),
+            }],
+            "delete_function_read_budget",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionReadBudgetJson {
+    component_path: String,
+    udf_path: String,
+    max_documents: Option<u64>,
+    max_bytes: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFunctionReadBudgetsResponse {
+    budgets: Vec<FunctionReadBudgetJson>,
+}
+
+pub async fn list_function_read_budgets(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let budgets = FunctionReadBudgetsModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|budget| {
+            let budget = budget.into_value();
+            FunctionReadBudgetJson {
+                component_path: budget.path.component.into(),
+                udf_path: budget.path.udf_path.into(),
+                max_documents: budget.max_documents,
+                max_bytes: budget.max_bytes,
+            }
+        })
+        .collect();
+    Ok(Json(ListFunctionReadBudgetsResponse { budgets }))
+}
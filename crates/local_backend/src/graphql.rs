@@ -0,0 +1,656 @@
+//! GraphQL gateway over the deployment's public functions.
+//!
+//! The schema is generated from the pushed modules: public queries become
+//! fields on `Query` and `Subscription`, and public mutations become fields on
+//! `Mutation`. Functions with argument validators get one GraphQL argument per
+//! field, and functions without them take their arguments as a single `args`
+//! object. Values that don't map onto a GraphQL scalar, including all return
+//! values, use the `JSON` scalar with the same encoding as the HTTP API's
+//! `json` format. The schema is cached until new functions are pushed.
+
+use std::{
+    collections::BTreeSet,
+    sync::Arc,
+};
+
+use application::{
+    api::{
+        ApplicationApi,
+        ExecuteQueryTimestamp,
+    },
+    redaction::RedactedJsError,
+};
+use async_graphql::{
+    dynamic::{
+        Field,
+        FieldFuture,
+        FieldValue,
+        InputValue,
+        Object,
+        ResolverContext,
+        Scalar,
+        Schema,
+        Subscription,
+        SubscriptionField,
+        SubscriptionFieldFuture,
+        TypeRef,
+    },
+    Data,
+    Value as GraphQLValue,
+};
+use async_graphql_axum::{
+    GraphQLProtocol,
+    GraphQLRequest,
+    GraphQLResponse,
+    GraphQLWebSocket,
+};
+use axum::{
+    extract::{
+        ws::WebSocketUpgrade,
+        Host,
+        State,
+    },
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    http::{
+        ExtractClientVersion,
+        ExtractRequestId,
+        HttpResponseError,
+    },
+    knobs::{
+        GRAPHQL_MAX_COMPLEXITY,
+        GRAPHQL_MAX_DEPTH,
+    },
+    schemas::validator::Validator,
+    types::{
+        FunctionCaller,
+        UdfType,
+    },
+    version::ClientVersion,
+    RequestId,
+};
+use futures::StreamExt;
+use futures_async_stream::try_stream;
+use keybroker::Identity;
+use model::{
+    modules::{
+        function_validators::ArgsValidator,
+        module_versions::{
+            AnalyzedFunction,
+            Visibility,
+        },
+        ModuleModel,
+    },
+    source_packages::types::SourcePackageId,
+};
+use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
+use sync_types::{
+    CanonicalizedUdfPath,
+    UdfPath,
+};
+use value::{
+    export::ValueFormat,
+    ConvexValue,
+};
+
+use crate::{
+    authentication::ExtractAuthenticationToken,
+    LocalAppState,
+};
+
+/// Scalar for arbitrary Convex values.
+const JSON_SCALAR: &str = "JSON";
+
+/// Argument holding all of the arguments to a function without validators.
+const UNVALIDATED_ARGS: &str = "args";
+
+/// Everything a resolver needs to run a function on behalf of the caller.
+#[derive(Clone)]
+struct FunctionContext {
+    api: Arc<dyn ApplicationApi>,
+    host: String,
+    identity: Identity,
+    client_version: ClientVersion,
+}
+
+impl FunctionContext {
+    async fn run_query(
+        &self,
+        path: UdfPath,
+        args: Vec<JsonValue>,
+    ) -> anyhow::Result<Result<ConvexValue, RedactedJsError>> {
+        let query_return = self
+            .api
+            .execute_public_query(
+                &self.host,
+                RequestId::new(),
+                self.identity.clone(),
+                path.into(),
+                args,
+                FunctionCaller::HttpApi(self.client_version.clone()),
+                ExecuteQueryTimestamp::Latest,
+                None,
+            )
+            .await?;
+        Ok(query_return.result)
+    }
+
+    async fn run_mutation(
+        &self,
+        path: UdfPath,
+        args: Vec<JsonValue>,
+    ) -> anyhow::Result<Result<ConvexValue, RedactedJsError>> {
+        let mutation_return = self
+            .api
+            .execute_public_mutation(
+                &self.host,
+                RequestId::new(),
+                self.identity.clone(),
+                path.into(),
+                args,
+                FunctionCaller::HttpApi(self.client_version.clone()),
+                None,
+            )
+            .await?;
+        Ok(mutation_return
+            .map(|mutation_return| mutation_return.value)
+            .map_err(|mutation_error| mutation_error.error))
+    }
+}
+
+/// Run a query and then rerun it whenever its result may have changed,
+/// yielding each distinct result.
+#[try_stream(ok = Result<ConvexValue, RedactedJsError>, error = anyhow::Error, boxed)]
+async fn subscribe_query(ctx: FunctionContext, path: UdfPath, args: Vec<JsonValue>) {
+    let mut last_value = None;
+    loop {
+        let query_return = ctx
+            .api
+            .execute_public_query(
+                &ctx.host,
+                RequestId::new(),
+                ctx.identity.clone(),
+                path.clone().into(),
+                args.clone(),
+                FunctionCaller::HttpApi(ctx.client_version.clone()),
+                ExecuteQueryTimestamp::Latest,
+                None,
+            )
+            .await?;
+        let subscription = ctx.api.subscribe(query_return.token).await?;
+        match query_return.result {
+            Ok(value) => {
+                if last_value.as_ref() != Some(&value) {
+                    last_value = Some(value.clone());
+                    yield Ok(value);
+                }
+            },
+            Err(error) => {
+                last_value = None;
+                yield Err(error);
+            },
+        }
+        subscription.wait_for_invalidation().await?;
+    }
+}
+
+/// A public function exposed as a GraphQL field.
+struct GraphQLFunction {
+    field_name: String,
+    path: UdfPath,
+    args: ArgsValidator,
+}
+
+impl GraphQLFunction {
+    fn new(path: CanonicalizedUdfPath, args: ArgsValidator) -> Self {
+        let path = path.strip();
+        let mut field_name: String = String::from(path.clone())
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if field_name.starts_with(|c: char| c.is_ascii_digit()) {
+            field_name.insert(0, '_');
+        }
+        Self {
+            field_name,
+            path,
+            args,
+        }
+    }
+
+    fn arguments(&self) -> Vec<InputValue> {
+        match &self.args {
+            ArgsValidator::Unvalidated => vec![InputValue::new(
+                UNVALIDATED_ARGS,
+                TypeRef::named(JSON_SCALAR),
+            )],
+            ArgsValidator::Validated(object_validator) => object_validator
+                .0
+                .iter()
+                .map(|(field_name, field_validator)| {
+                    let type_name = graphql_type_name(field_validator.validator());
+                    let type_ref = if field_validator.is_optional() {
+                        TypeRef::named(type_name)
+                    } else {
+                        TypeRef::named_nn(type_name)
+                    };
+                    InputValue::new(field_name.to_string(), type_ref)
+                })
+                .collect(),
+        }
+    }
+
+    fn query_field(&self) -> Field {
+        let path = self.path.clone();
+        let is_validated = matches!(self.args, ArgsValidator::Validated(_));
+        let field = Field::new(
+            self.field_name.clone(),
+            TypeRef::named(JSON_SCALAR),
+            move |ctx| {
+                let path = path.clone();
+                FieldFuture::new(async move {
+                    let function_ctx = ctx.data::<FunctionContext>()?;
+                    let args = function_args(&ctx, is_validated)?;
+                    let value = function_ctx.run_query(path, args).await??;
+                    Ok(Some(FieldValue::value(graphql_value(value)?)))
+                })
+            },
+        );
+        self.arguments()
+            .into_iter()
+            .fold(field, |field, argument| field.argument(argument))
+    }
+
+    fn mutation_field(&self) -> Field {
+        let path = self.path.clone();
+        let is_validated = matches!(self.args, ArgsValidator::Validated(_));
+        let field = Field::new(
+            self.field_name.clone(),
+            TypeRef::named(JSON_SCALAR),
+            move |ctx| {
+                let path = path.clone();
+                FieldFuture::new(async move {
+                    let function_ctx = ctx.data::<FunctionContext>()?;
+                    let args = function_args(&ctx, is_validated)?;
+                    let value = function_ctx.run_mutation(path, args).await??;
+                    Ok(Some(FieldValue::value(graphql_value(value)?)))
+                })
+            },
+        );
+        self.arguments()
+            .into_iter()
+            .fold(field, |field, argument| field.argument(argument))
+    }
+
+    fn subscription_field(&self) -> SubscriptionField {
+        let path = self.path.clone();
+        let is_validated = matches!(self.args, ArgsValidator::Validated(_));
+        let field = SubscriptionField::new(
+            self.field_name.clone(),
+            TypeRef::named(JSON_SCALAR),
+            move |ctx| {
+                let path = path.clone();
+                SubscriptionFieldFuture::new(async move {
+                    let function_ctx = ctx.data::<FunctionContext>()?.clone();
+                    let args = function_args(&ctx, is_validated)?;
+                    let stream = subscribe_query(function_ctx, path, args).map(
+                        |result| -> async_graphql::Result<_> {
+                            let value = result??;
+                            Ok(FieldValue::value(graphql_value(value)?))
+                        },
+                    );
+                    Ok(stream)
+                })
+            },
+        );
+        self.arguments()
+            .into_iter()
+            .fold(field, |field, argument| field.argument(argument))
+    }
+}
+
+fn graphql_type_name(validator: &Validator) -> &'static str {
+    match validator {
+        Validator::Id(_) => TypeRef::ID,
+        Validator::Float64 => TypeRef::FLOAT,
+        Validator::Boolean => TypeRef::BOOLEAN,
+        Validator::String => TypeRef::STRING,
+        _ => JSON_SCALAR,
+    }
+}
+
+/// Collect a field's GraphQL arguments into the function's arguments.
+fn function_args(ctx: &ResolverContext<'_>, is_validated: bool) -> anyhow::Result<Vec<JsonValue>> {
+    let args = if is_validated {
+        let mut args = serde_json::Map::new();
+        for (name, value) in ctx.args.as_index_map() {
+            // Optional arguments passed as `null` are omitted, since Convex
+            // validators distinguish missing fields from null values.
+            if *value != GraphQLValue::Null {
+                args.insert(name.to_string(), value.clone().into_json()?);
+            }
+        }
+        JsonValue::Object(args)
+    } else {
+        match ctx.args.get(UNVALIDATED_ARGS) {
+            Some(args) => args.as_value().clone().into_json()?,
+            None => JsonValue::Object(serde_json::Map::new()),
+        }
+    };
+    Ok(vec![args])
+}
+
+fn graphql_value(value: ConvexValue) -> anyhow::Result<GraphQLValue> {
+    Ok(GraphQLValue::from_json(
+        value.export(ValueFormat::ConvexCleanJSON),
+    )?)
+}
+
+/// Generate the GraphQL schema for a deployment's functions.
+fn build_schema(
+    functions: impl IntoIterator<Item = (CanonicalizedUdfPath, AnalyzedFunction)>,
+) -> anyhow::Result<Schema> {
+    let mut query = Object::new("Query");
+    let mut mutation = Object::new("Mutation");
+    let mut subscription = Subscription::new("Subscription");
+    let (mut has_queries, mut has_mutations) = (false, false);
+    let mut field_names = BTreeSet::new();
+    for (path, function) in functions {
+        if function.visibility != Some(Visibility::Public) {
+            continue;
+        }
+        let graphql_function = GraphQLFunction::new(path, function.args);
+        if !field_names.insert(graphql_function.field_name.clone()) {
+            tracing::warn!(
+                "Skipping {:?} in the GraphQL schema: field name {} is already used",
+                graphql_function.path,
+                graphql_function.field_name
+            );
+            continue;
+        }
+        match function.udf_type {
+            UdfType::Query => {
+                query = query.field(graphql_function.query_field());
+                subscription = subscription.field(graphql_function.subscription_field());
+                has_queries = true;
+            },
+            UdfType::Mutation => {
+                mutation = mutation.field(graphql_function.mutation_field());
+                has_mutations = true;
+            },
+            UdfType::Action | UdfType::HttpAction => (),
+        }
+    }
+    // GraphQL requires the query type to have at least one field.
+    if !has_queries {
+        query = query.field(Field::new(
+            "_empty",
+            TypeRef::named(TypeRef::BOOLEAN),
+            |_| FieldFuture::new(async { Ok(None::<FieldValue>) }),
+        ));
+    }
+    let mut builder = Schema::build(
+        "Query",
+        has_mutations.then_some("Mutation"),
+        has_queries.then_some("Subscription"),
+    )
+    .register(Scalar::new(JSON_SCALAR))
+    .register(query)
+    .limit_depth(*GRAPHQL_MAX_DEPTH)
+    .limit_complexity(*GRAPHQL_MAX_COMPLEXITY);
+    if has_mutations {
+        builder = builder.register(mutation);
+    }
+    if has_queries {
+        builder = builder.register(subscription);
+    }
+    builder
+        .finish()
+        .map_err(|e| anyhow::anyhow!("Failed to build GraphQL schema: {}", e.0))
+}
+
+/// The GraphQL schema along with the source packages of the modules it was
+/// generated from.
+#[derive(Default)]
+pub struct GraphQLSchemaCache {
+    schema: Mutex<Option<(BTreeSet<SourcePackageId>, Schema)>>,
+}
+
+async fn load_schema(st: &LocalAppState) -> anyhow::Result<Schema> {
+    let mut tx = st.application.begin(Identity::system()).await?;
+    let modules = ModuleModel::new(&mut tx)
+        .get_application_metadata(ComponentId::TODO())
+        .await?;
+    // Every push writes its modules with a new source package, so the schema
+    // only needs to be rebuilt when the set of source packages changes.
+    let source_package_ids: BTreeSet<_> = modules
+        .iter()
+        .map(|module| module.source_package_id)
+        .collect();
+    if let Some((cached_ids, schema)) = &*st.graphql_schema_cache.schema.lock() {
+        if *cached_ids == source_package_ids {
+            return Ok(schema.clone());
+        }
+    }
+    let functions = modules.into_iter().flat_map(|module| {
+        let module = module.into_value();
+        let path = module.path;
+        module
+            .analyze_result
+            .into_iter()
+            .flat_map(|analyze_result| analyze_result.functions.into_iter())
+            .map(move |function| {
+                (
+                    CanonicalizedUdfPath::new(path.clone(), function.name.clone()),
+                    function,
+                )
+            })
+    });
+    let schema = build_schema(functions)?;
+    *st.graphql_schema_cache.schema.lock() = Some((source_package_ids, schema.clone()));
+    Ok(schema)
+}
+
+async fn function_context(
+    st: &LocalAppState,
+    host: String,
+    request_id: RequestId,
+    auth_token: ExtractAuthenticationToken,
+    client_version: ClientVersion,
+) -> anyhow::Result<FunctionContext> {
+    let api: Arc<dyn ApplicationApi> = Arc::new(st.application.clone());
+    let identity = api
+        .authenticate(host.as_str(), request_id, auth_token.into())
+        .await?;
+    Ok(FunctionContext {
+        api,
+        host,
+        identity,
+        client_version,
+    })
+}
+
+pub async fn graphql_post(
+    State(st): State<LocalAppState>,
+    Host(host): Host,
+    ExtractRequestId(request_id): ExtractRequestId,
+    auth_token: ExtractAuthenticationToken,
+    ExtractClientVersion(client_version): ExtractClientVersion,
+    request: GraphQLRequest,
+) -> Result<GraphQLResponse, HttpResponseError> {
+    let function_ctx = function_context(&st, host, request_id, auth_token, client_version).await?;
+    let schema = load_schema(&st).await?;
+    let response = schema
+        .execute(request.into_inner().data(function_ctx))
+        .await;
+    Ok(response.into())
+}
+
+/// Serve GraphQL subscriptions over a WebSocket. The connection is
+/// authenticated with the `Authorization` header of the upgrade request.
+pub async fn graphql_ws(
+    State(st): State<LocalAppState>,
+    Host(host): Host,
+    ExtractRequestId(request_id): ExtractRequestId,
+    auth_token: ExtractAuthenticationToken,
+    ExtractClientVersion(client_version): ExtractClientVersion,
+    protocol: GraphQLProtocol,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let function_ctx = function_context(&st, host, request_id, auth_token, client_version).await?;
+    let schema = load_schema(&st).await?;
+    let mut data = Data::default();
+    data.insert(function_ctx);
+    Ok(ws
+        .protocols(async_graphql::http::ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |socket| {
+            GraphQLWebSocket::new(socket, schema, protocol)
+                .with_data(data)
+                .serve()
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use application::{
+        api::ApplicationApi,
+        test_helpers::ApplicationTestExt,
+    };
+    use async_graphql::Request;
+    use common::{
+        knobs::GRAPHQL_MAX_COMPLEXITY,
+        version::ClientVersion,
+    };
+    use futures::StreamExt;
+    use keybroker::Identity;
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use super::{
+        load_schema,
+        FunctionContext,
+    };
+    use crate::test_helpers::setup_backend_for_test;
+
+    fn function_context(backend_api: Arc<dyn ApplicationApi>) -> FunctionContext {
+        FunctionContext {
+            api: backend_api,
+            host: "localhost".to_string(),
+            identity: Identity::Unknown,
+            client_version: ClientVersion::unknown(),
+        }
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_graphql_schema(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let schema = load_schema(&backend.st).await?;
+        let sdl = schema.sdl();
+        assert!(
+            sdl.contains("args_validation_stringArg(arg: String!): JSON"),
+            "{sdl}"
+        );
+        assert!(sdl.contains("sync_initialize(args: JSON): JSON"), "{sdl}");
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_graphql_complexity_limit(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let schema = load_schema(&backend.st).await?;
+        let ctx = function_context(Arc::new(backend.st.application.clone()));
+
+        // Aliasing the same function many times is rejected before any of the
+        // calls run.
+        let fields: Vec<_> = (0..=*GRAPHQL_MAX_COMPLEXITY)
+            .map(|i| format!(r#"f{i}: args_validation_stringArg(arg: "hello")"#))
+            .collect();
+        let response = schema
+            .execute(Request::new(format!("{{ {} }}", fields.join(" "))).data(ctx))
+            .await;
+        assert!(
+            response.errors[0].message.contains("too complex"),
+            "{:?}",
+            response.errors
+        );
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_graphql_query_and_mutation(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let schema = load_schema(&backend.st).await?;
+        let ctx = function_context(Arc::new(backend.st.application.clone()));
+
+        let response = schema
+            .execute(
+                Request::new(r#"{ args_validation_stringArg(arg: "hello") }"#).data(ctx.clone()),
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json()?,
+            json!({"args_validation_stringArg": "hello"})
+        );
+
+        let response = schema
+            .execute(
+                Request::new(
+                    r#"mutation { sync_initialize(args: {name: "orinoco", balance: 5}) }"#,
+                )
+                .data(ctx.clone()),
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let response = schema
+            .execute(Request::new(r#"{ sync_accountBalance(args: {name: "orinoco"}) }"#).data(ctx))
+            .await;
+        assert_eq!(
+            response.data.into_json()?,
+            json!({"sync_accountBalance": 5.0})
+        );
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_graphql_subscription(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let schema = load_schema(&backend.st).await?;
+        let ctx = function_context(Arc::new(backend.st.application.clone()));
+
+        let mut stream = schema.execute_stream(
+            Request::new(r#"subscription { sync_accountBalance(args: {name: "orinoco"}) }"#)
+                .data(ctx.clone()),
+        );
+        let response = stream.next().await.unwrap();
+        assert_eq!(
+            response.data.into_json()?,
+            json!({"sync_accountBalance": 0.0})
+        );
+
+        let response = schema
+            .execute(
+                Request::new(
+                    r#"mutation { sync_initialize(args: {name: "orinoco", balance: 5}) }"#,
+                )
+                .data(ctx),
+            )
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        let response = stream.next().await.unwrap();
+        assert_eq!(
+            response.data.into_json()?,
+            json!({"sync_accountBalance": 5.0})
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+//! gRPC API for running public functions, for backend-to-backend callers that
+//! want typed values instead of JSON. See `convex_functions.proto`.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use application::{
+    api::{
+        ApplicationApi,
+        ExecuteQueryTimestamp,
+    },
+    redaction::{
+        RedactedJsError,
+        RedactedLogLines,
+    },
+};
+use common::{
+    types::FunctionCaller,
+    version::ClientVersion,
+    RequestId,
+};
+use errors::ErrorMetadata;
+use futures::{
+    stream::BoxStream,
+    StreamExt,
+};
+use futures_async_stream::try_stream;
+use keybroker::Identity;
+use pb::{
+    convex_functions::{
+        convex_functions_server::ConvexFunctions,
+        function_response::Result as FunctionResultProto,
+        FunctionError as FunctionErrorProto,
+        FunctionRequest,
+        FunctionResponse,
+    },
+    error_metadata::ErrorMetadataStatusExt,
+};
+use serde_json::Value as JsonValue;
+use sync_types::{
+    AuthenticationToken,
+    UdfPath,
+};
+use tonic::{
+    Request,
+    Response,
+    Status,
+};
+use value::{
+    ConvexObject,
+    ConvexValue,
+};
+
+use crate::{
+    authentication::parse_authorization_header,
+    parse::parse_udf_path,
+};
+
+/// Implements the `ConvexFunctions` gRPC service.
+#[derive(Clone)]
+pub struct ConvexFunctionsService {
+    api: Arc<dyn ApplicationApi>,
+    host: String,
+}
+
+impl ConvexFunctionsService {
+    pub fn new(api: Arc<dyn ApplicationApi>, host: String) -> Self {
+        Self { api, host }
+    }
+
+    /// Authenticate the caller and parse the function and arguments of their
+    /// request.
+    async fn parse_request(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> anyhow::Result<(Identity, UdfPath, Vec<JsonValue>)> {
+        let auth_token = match request.metadata().get(http::header::AUTHORIZATION.as_str()) {
+            Some(header) => {
+                let header = header.to_str().context(ErrorMetadata::bad_request(
+                    "HeaderParseFailure",
+                    "Failed to parse `authorization` metadata",
+                ))?;
+                parse_authorization_header(header).await?
+            },
+            None => AuthenticationToken::None,
+        };
+        let identity = self
+            .api
+            .authenticate(&self.host, RequestId::new(), auth_token)
+            .await?;
+        let FunctionRequest { path, args } = request.into_inner();
+        let udf_path = parse_udf_path(&path)?;
+        let args = match args {
+            Some(args) => ConvexObject::try_from(args).map_err(|e| {
+                let msg = format!("Invalid arguments for {path}: {e}");
+                e.context(ErrorMetadata::bad_request("InvalidFunctionArgs", msg))
+            })?,
+            None => ConvexObject::empty(),
+        };
+        Ok((
+            identity,
+            udf_path,
+            vec![JsonValue::from(ConvexValue::Object(args))],
+        ))
+    }
+
+    async fn run_query(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> anyhow::Result<FunctionResponse> {
+        let (identity, udf_path, args) = self.parse_request(request).await?;
+        let query_return = self
+            .api
+            .execute_public_query(
+                &self.host,
+                RequestId::new(),
+                identity,
+                udf_path.into(),
+                args,
+                FunctionCaller::HttpApi(ClientVersion::unknown()),
+                ExecuteQueryTimestamp::Latest,
+                None,
+            )
+            .await?;
+        Ok(function_response(
+            query_return.result,
+            query_return.log_lines,
+        ))
+    }
+
+    async fn run_mutation(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> anyhow::Result<FunctionResponse> {
+        let (identity, udf_path, args) = self.parse_request(request).await?;
+        let response = match self
+            .api
+            .execute_public_mutation(
+                &self.host,
+                RequestId::new(),
+                identity,
+                udf_path.into(),
+                args,
+                FunctionCaller::HttpApi(ClientVersion::unknown()),
+                None,
+            )
+            .await?
+        {
+            Ok(mutation_return) => {
+                function_response(Ok(mutation_return.value), mutation_return.log_lines)
+            },
+            Err(mutation_error) => {
+                function_response(Err(mutation_error.error), mutation_error.log_lines)
+            },
+        };
+        Ok(response)
+    }
+
+    async fn run_action(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> anyhow::Result<FunctionResponse> {
+        let (identity, udf_path, args) = self.parse_request(request).await?;
+        let response = match self
+            .api
+            .execute_public_action(
+                &self.host,
+                RequestId::new(),
+                identity,
+                udf_path.into(),
+                args,
+                FunctionCaller::HttpApi(ClientVersion::unknown()),
+            )
+            .await?
+        {
+            Ok(action_return) => {
+                function_response(Ok(action_return.value), action_return.log_lines)
+            },
+            Err(action_error) => function_response(Err(action_error.error), action_error.log_lines),
+        };
+        Ok(response)
+    }
+}
+
+fn function_response(
+    result: Result<ConvexValue, RedactedJsError>,
+    log_lines: RedactedLogLines,
+) -> FunctionResponse {
+    let result = match result {
+        Ok(value) => FunctionResultProto::Value(value.into()),
+        Err(error) => FunctionResultProto::Error(FunctionErrorProto {
+            message: error.to_string(),
+            data: error.custom_data_if_any().map(Into::into),
+        }),
+    };
+    FunctionResponse {
+        result: Some(result),
+        log_lines: log_lines.iter().cloned().collect(),
+    }
+}
+
+/// Run a query and then rerun it whenever its result may have changed,
+/// yielding a response each time the result is different.
+#[try_stream(ok = FunctionResponse, error = anyhow::Error, boxed)]
+async fn subscribe_query(
+    api: Arc<dyn ApplicationApi>,
+    host: String,
+    identity: Identity,
+    udf_path: UdfPath,
+    args: Vec<JsonValue>,
+) {
+    let mut last_value = None;
+    loop {
+        let query_return = api
+            .execute_public_query(
+                &host,
+                RequestId::new(),
+                identity.clone(),
+                udf_path.clone().into(),
+                args.clone(),
+                FunctionCaller::HttpApi(ClientVersion::unknown()),
+                ExecuteQueryTimestamp::Latest,
+                None,
+            )
+            .await?;
+        let subscription = api.subscribe(query_return.token).await?;
+        match query_return.result {
+            Ok(value) => {
+                if last_value.as_ref() != Some(&value) {
+                    last_value = Some(value.clone());
+                    yield function_response(Ok(value), query_return.log_lines);
+                }
+            },
+            Err(error) => {
+                last_value = None;
+                yield function_response(Err(error), query_return.log_lines);
+            },
+        }
+        subscription.wait_for_invalidation().await?;
+    }
+}
+
+#[tonic::async_trait]
+impl ConvexFunctions for ConvexFunctionsService {
+    type SubscribeStream = BoxStream<'static, Result<FunctionResponse, Status>>;
+
+    async fn query(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> Result<Response<FunctionResponse>, Status> {
+        self.run_query(request)
+            .await
+            .map(Response::new)
+            .map_err(Status::from_anyhow)
+    }
+
+    async fn mutation(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> Result<Response<FunctionResponse>, Status> {
+        self.run_mutation(request)
+            .await
+            .map(Response::new)
+            .map_err(Status::from_anyhow)
+    }
+
+    async fn action(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> Result<Response<FunctionResponse>, Status> {
+        self.run_action(request)
+            .await
+            .map(Response::new)
+            .map_err(Status::from_anyhow)
+    }
+
+    async fn subscribe(
+        &self,
+        request: Request<FunctionRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let (identity, udf_path, args) = self
+            .parse_request(request)
+            .await
+            .map_err(Status::from_anyhow)?;
+        let stream = subscribe_query(
+            self.api.clone(),
+            self.host.clone(),
+            identity,
+            udf_path,
+            args,
+        )
+        .map(|result| result.map_err(Status::from_anyhow));
+        Ok(Response::new(stream.boxed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use application::test_helpers::ApplicationTestExt;
+    use futures::StreamExt;
+    use maplit::btreemap;
+    use pb::convex_functions::{
+        convex_functions_server::ConvexFunctions,
+        function_response::Result as FunctionResultProto,
+        FunctionRequest,
+        FunctionResponse,
+    };
+    use runtime::prod::ProdRuntime;
+    use tonic::Request;
+    use value::{
+        ConvexObject,
+        ConvexValue,
+    };
+
+    use super::ConvexFunctionsService;
+    use crate::test_helpers::setup_backend_for_test;
+
+    fn request(path: &str, args: ConvexObject) -> Request<FunctionRequest> {
+        Request::new(FunctionRequest {
+            path: path.to_string(),
+            args: Some(args.into()),
+        })
+    }
+
+    fn value(response: FunctionResponse) -> anyhow::Result<ConvexValue> {
+        match response.result {
+            Some(FunctionResultProto::Value(value)) => value.try_into(),
+            result => anyhow::bail!("Unexpected result {result:?}"),
+        }
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_grpc_functions(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let service = ConvexFunctionsService::new(
+            Arc::new(backend.st.application.clone()),
+            "localhost".to_string(),
+        );
+        let name = ConvexObject::try_from(btreemap! {
+            "name".parse()? => ConvexValue::try_from("orinoco")?,
+        })?;
+
+        let mut subscription = service
+            .subscribe(request("sync:accountBalance", name.clone()))
+            .await?
+            .into_inner();
+        let first = subscription.next().await.unwrap()?;
+        assert_eq!(value(first)?, ConvexValue::from(0.0));
+
+        let initialize_args = ConvexObject::try_from(btreemap! {
+            "name".parse()? => ConvexValue::try_from("orinoco")?,
+            "balance".parse()? => ConvexValue::from(100.0),
+        })?;
+        service
+            .mutation(request("sync:initialize", initialize_args))
+            .await?;
+        let balance = service
+            .query(request("sync:accountBalance", name))
+            .await?
+            .into_inner();
+        assert_eq!(value(balance)?, ConvexValue::from(100.0));
+        let second = subscription.next().await.unwrap()?;
+        assert_eq!(value(second)?, ConvexValue::from(100.0));
+
+        let args = ConvexObject::try_from(btreemap! {
+            "i".parse()? => ConvexValue::from(1.0),
+        })?;
+        let failure = service
+            .query(request("sync:fail", args))
+            .await?
+            .into_inner();
+        match failure.result {
+            Some(FunctionResultProto::Error(error)) => {
+                assert!(error.message.contains("No can do."), "{}", error.message)
+            },
+            result => anyhow::bail!("Unexpected result {result:?}"),
+        }
+
+        let status = service
+            .query(request("sync:not a function!", ConvexObject::empty()))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+//! Health checks for load balancers and orchestrators like Kubernetes.
+//!
+//! `/healthz` is a liveness probe: it fails when the backend can't recover
+//! without being restarted, i.e. it has lost its lease (or hit another fatal
+//! error) and is shutting down, or it can't reach persistence. `/readyz` is a
+//! readiness probe that also checks that search indexes have loaded and that
+//! the scheduled job executor is running and that the backend isn't draining,
+//! so requests are only routed to a backend that can serve all of them.
+//!
+//! Both return 200 if every check passes and 503 otherwise, with a body like
+//! `{"status": "error", "checks": {"persistence": {"status": "ok"},
+//! "searcher": {"status": "error", "error": "..."}}}`.
+
+use std::{
+    collections::BTreeMap,
+    time::Duration,
+};
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::extract::Json,
+    runtime::Runtime,
+};
+use futures::{
+    select_biased,
+    FutureExt,
+};
+use http::StatusCode;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    drain::check_not_draining,
+    LocalAppState,
+};
+
+/// How long to wait for persistence before reporting it as unavailable.
+const PERSISTENCE_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthStatus {
+    Ok,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CheckResult {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HealthResponse {
+    pub status: HealthStatus,
+    pub checks: BTreeMap<String, CheckResult>,
+}
+
+impl HealthResponse {
+    fn new(checks: Vec<(&str, anyhow::Result<()>)>) -> Self {
+        let mut status = HealthStatus::Ok;
+        let checks = checks
+            .into_iter()
+            .map(|(name, result)| {
+                let result = match result {
+                    Ok(()) => CheckResult {
+                        status: HealthStatus::Ok,
+                        error: None,
+                    },
+                    Err(e) => {
+                        tracing::warn!("Health check {name} failed: {e:#}");
+                        status = HealthStatus::Error;
+                        CheckResult {
+                            status: HealthStatus::Error,
+                            error: Some(format!("{e:#}")),
+                        }
+                    },
+                };
+                (name.to_string(), result)
+            })
+            .collect();
+        Self { status, checks }
+    }
+}
+
+impl IntoResponse for HealthResponse {
+    fn into_response(self) -> axum::response::Response {
+        let status_code = match self.status {
+            HealthStatus::Ok => StatusCode::OK,
+            HealthStatus::Error => StatusCode::SERVICE_UNAVAILABLE,
+        };
+        (status_code, Json(self)).into_response()
+    }
+}
+
+fn check_lease(st: &LocalAppState) -> anyhow::Result<()> {
+    // The committer sends the shutdown signal when a write fails, e.g. because
+    // another backend has taken over the persistence lease. Only the clone
+    // reads it, so the original still sees the signal.
+    anyhow::ensure!(
+        st.zombify_rx.clone().try_recv().is_err(),
+        "The backend has lost its lease or hit a fatal error and is shutting down"
+    );
+    Ok(())
+}
+
+async fn check_persistence(st: &LocalAppState) -> anyhow::Result<()> {
+    let rt = st.application.runtime();
+    select_biased! {
+        result = st.application.check_persistence().fuse() => result,
+        _ = rt.wait(PERSISTENCE_CHECK_TIMEOUT) => anyhow::bail!(
+            "Persistence didn't respond within {PERSISTENCE_CHECK_TIMEOUT:?}"
+        ),
+    }
+}
+
+pub async fn healthz(State(st): State<LocalAppState>) -> HealthResponse {
+    HealthResponse::new(vec![
+        ("lease", check_lease(&st)),
+        ("persistence", check_persistence(&st).await),
+    ])
+}
+
+pub async fn readyz(State(st): State<LocalAppState>) -> HealthResponse {
+    HealthResponse::new(vec![
+        ("lease", check_lease(&st)),
+        ("persistence", check_persistence(&st).await),
+        ("searcher", st.application.check_search_ready()),
+        ("scheduler", st.application.check_scheduler_live()),
+        ("drain", check_not_draining(&st)),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::response::IntoResponse;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+
+    use super::{
+        HealthResponse,
+        HealthStatus,
+    };
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[test]
+    fn test_health_response() {
+        let response = HealthResponse::new(vec![
+            ("persistence", Ok(())),
+            ("searcher", Err(anyhow::anyhow!("Still loading"))),
+        ]);
+        assert_eq!(response.status, HealthStatus::Error);
+        assert_eq!(response.checks["persistence"].status, HealthStatus::Ok);
+        assert_eq!(
+            response.checks["searcher"].error.as_deref(),
+            Some("Still loading")
+        );
+        assert_eq!(
+            response.into_response().status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_healthz(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/healthz")
+            .method("GET")
+            .body(Body::empty())?;
+        let response: HealthResponse = backend.expect_success(req).await?;
+        assert_eq!(response.status, HealthStatus::Ok);
+        assert_eq!(response.checks["lease"].status, HealthStatus::Ok);
+        assert_eq!(response.checks["persistence"].status, HealthStatus::Ok);
+        Ok(())
+    }
+}
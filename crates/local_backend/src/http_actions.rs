@@ -59,6 +59,7 @@ use url::Url;
 
 use crate::{
     authentication::TryExtractIdentity,
+    http_response_cache::CacheLookup,
     RouterState,
 };
 
@@ -146,6 +147,11 @@ pub async fn http_any_method(
     Host(host): Host,
     ExtractHttpRequestMetadata(http_request_metadata): ExtractHttpRequestMetadata,
 ) -> Result<impl IntoResponse, HttpResponseError> {
+    let cache_recorder = match st.http_response_cache.lookup(&http_request_metadata.head) {
+        CacheLookup::Hit(response) => return Ok(response),
+        CacheLookup::Miss(recorder) => Some(recorder),
+        CacheLookup::Bypass => None,
+    };
     // All HTTP actions run the default export of the http.js path.
     let path = "http.js".parse()?;
     let mut http_response_stream = stream_http_response(
@@ -160,18 +166,24 @@ pub async fn http_any_method(
     let Some(HttpActionResponsePart::Head(response_head)) = head else {
         return Err(anyhow::anyhow!("Did not receive HTTP response head first").into());
     };
-    let body = http_response_stream.map(|p| match p {
-        Ok(HttpActionResponsePart::BodyChunk(bytes)) => Ok(bytes),
-        Err(e) => Err(e),
-        _ => Err(anyhow::anyhow!(
-            "Unexpected element in HTTP response stream"
-        )),
-    });
+    let body = http_response_stream
+        .map(|p| match p {
+            Ok(HttpActionResponsePart::BodyChunk(bytes)) => Ok(bytes),
+            Err(e) => Err(e),
+            _ => Err(anyhow::anyhow!(
+                "Unexpected element in HTTP response stream"
+            )),
+        })
+        .boxed();
+    let body = match cache_recorder {
+        Some(recorder) => recorder.record(&response_head, body),
+        None => body,
+    };
 
     Ok(HttpActionResponse {
         status: response_head.status,
         headers: response_head.headers,
-        body: Box::pin(body),
+        body,
     })
 }
 
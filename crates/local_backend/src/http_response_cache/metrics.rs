@@ -0,0 +1,71 @@
+use metrics::{
+    log_counter,
+    log_counter_with_labels,
+    log_gauge,
+    register_convex_counter,
+    register_convex_gauge,
+    StaticMetricLabel,
+};
+
+pub enum CacheStatus {
+    /// Served from the cache.
+    Hit,
+    /// Could have been served from the cache, but ran the action.
+    Miss,
+    /// The request can't be served from the cache, e.g. it isn't a GET.
+    Bypass,
+}
+
+impl CacheStatus {
+    fn tag(&self) -> StaticMetricLabel {
+        let value = match self {
+            CacheStatus::Hit => "hit",
+            CacheStatus::Miss => "miss",
+            CacheStatus::Bypass => "bypass",
+        };
+        StaticMetricLabel::new("cache_status", value)
+    }
+}
+
+register_convex_counter!(
+    HTTP_ACTION_RESPONSE_CACHE_LOOKUPS_TOTAL,
+    "Number of HTTP action requests looked up in the response cache. The hit rate is the number \
+     of hits over the number of hits and misses.",
+    &["cache_status"]
+);
+pub fn log_lookup(status: CacheStatus) {
+    log_counter_with_labels(
+        &HTTP_ACTION_RESPONSE_CACHE_LOOKUPS_TOTAL,
+        1,
+        vec![status.tag()],
+    );
+}
+
+register_convex_counter!(
+    HTTP_ACTION_RESPONSE_CACHE_STORED_TOTAL,
+    "Number of HTTP action responses stored in the response cache"
+);
+pub fn log_stored() {
+    log_counter(&HTTP_ACTION_RESPONSE_CACHE_STORED_TOTAL, 1);
+}
+
+register_convex_counter!(
+    HTTP_ACTION_RESPONSE_CACHE_UNCACHEABLE_TOTAL,
+    "Number of HTTP action responses to cache misses that couldn't be stored",
+    &["reason"]
+);
+pub fn log_uncacheable(reason: &'static str) {
+    log_counter_with_labels(
+        &HTTP_ACTION_RESPONSE_CACHE_UNCACHEABLE_TOTAL,
+        1,
+        vec![StaticMetricLabel::new("reason", reason)],
+    );
+}
+
+register_convex_gauge!(
+    HTTP_ACTION_RESPONSE_CACHE_SIZE_BYTES,
+    "Approximate size of the HTTP action response cache"
+);
+pub fn log_cache_size(size: usize) {
+    log_gauge(&HTTP_ACTION_RESPONSE_CACHE_SIZE_BYTES, size as f64);
+}
@@ -0,0 +1,689 @@
+//! An opt-in cache in front of HTTP actions. A response is only stored when
+//! the action marks it as cacheable by a shared cache with `Cache-Control`, and
+//! it's served to matching requests until it goes stale, so read-heavy public
+//! endpoints don't run the action for every request.
+//!
+//! Entries are keyed by the request's method and URL, and by the values of the
+//! request headers the response lists in `Vary`. Only GET requests are cached.
+
+use std::{
+    mem,
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use axum::body::Bytes;
+use futures::{
+    stream::{
+        self,
+        BoxStream,
+    },
+    StreamExt,
+};
+use futures_async_stream::try_stream;
+use http::{
+    header::{
+        AGE,
+        AUTHORIZATION,
+        CACHE_CONTROL,
+        CONTENT_LENGTH,
+        ETAG,
+        IF_NONE_MATCH,
+        SET_COOKIE,
+        VARY,
+    },
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    Method,
+    StatusCode,
+};
+use isolate::{
+    HttpActionRequestHead,
+    HttpActionResponseHead,
+};
+use lru::LruCache;
+use parking_lot::Mutex;
+use url::Position;
+
+use self::metrics::{
+    log_cache_size,
+    log_lookup,
+    log_stored,
+    log_uncacheable,
+    CacheStatus,
+};
+use crate::http_actions::HttpActionResponse;
+
+mod metrics;
+
+/// Statuses that a shared cache may store when the response has explicit
+/// freshness information.
+const CACHEABLE_STATUSES: [StatusCode; 6] = [
+    StatusCode::OK,
+    StatusCode::NON_AUTHORITATIVE_INFORMATION,
+    StatusCode::NO_CONTENT,
+    StatusCode::MOVED_PERMANENTLY,
+    StatusCode::NOT_FOUND,
+    StatusCode::GONE,
+];
+
+pub struct HttpResponseCache {
+    inner: Mutex<Inner>,
+    max_size: usize,
+    max_entry_size: usize,
+}
+
+pub enum CacheLookup {
+    Hit(HttpActionResponse),
+    /// The action has to run. Its response can be stored with the recorder.
+    Miss(CacheRecorder),
+    Bypass,
+}
+
+impl HttpResponseCache {
+    /// The cache is disabled if `max_size` is zero.
+    pub fn new(max_size: usize, max_entry_size: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                cache: LruCache::unbounded(),
+                size: 0,
+            }),
+            max_size,
+            max_entry_size,
+        }
+    }
+
+    pub fn lookup(self: &Arc<Self>, request: &HttpActionRequestHead) -> CacheLookup {
+        let lookup = self.lookup_at(request, Instant::now());
+        log_lookup(match lookup {
+            CacheLookup::Hit(_) => CacheStatus::Hit,
+            CacheLookup::Miss(_) => CacheStatus::Miss,
+            CacheLookup::Bypass => CacheStatus::Bypass,
+        });
+        lookup
+    }
+
+    fn lookup_at(self: &Arc<Self>, request: &HttpActionRequestHead, now: Instant) -> CacheLookup {
+        if self.max_size == 0 || request.method != Method::GET {
+            return CacheLookup::Bypass;
+        }
+        let directives = CacheControl::parse(&request.headers);
+        if directives.no_store {
+            return CacheLookup::Bypass;
+        }
+        let key = CacheKey::new(request);
+        // The client can ask for a response from the origin with `no-cache` or
+        // `max-age=0`, which we still store for later requests.
+        if !directives.no_cache && directives.max_age != Some(Duration::ZERO) {
+            let mut inner = self.inner.lock();
+            if let Some(response) = inner.get(&key, &request.headers, now) {
+                return CacheLookup::Hit(response.to_response(&request.headers, now));
+            }
+        }
+        CacheLookup::Miss(CacheRecorder {
+            cache: self.clone(),
+            key,
+            request_headers: request.headers.clone(),
+        })
+    }
+
+    fn insert(&self, key: CacheKey, response: CachedResponse) {
+        if response.size() > self.max_entry_size {
+            log_uncacheable("too_large");
+            return;
+        }
+        let mut inner = self.inner.lock();
+        inner.insert(key, response);
+        inner.enforce_size_limit(self.max_size);
+        log_stored();
+    }
+}
+
+/// Stores the response to a cache miss if the action made it cacheable.
+pub struct CacheRecorder {
+    cache: Arc<HttpResponseCache>,
+    key: CacheKey,
+    request_headers: HeaderMap,
+}
+
+impl CacheRecorder {
+    /// Pass the response body through, storing the response once the whole
+    /// body has been sent.
+    pub fn record(
+        self,
+        head: &HttpActionResponseHead,
+        body: BoxStream<'static, anyhow::Result<Bytes>>,
+    ) -> BoxStream<'static, anyhow::Result<Bytes>> {
+        let lifetime = match freshness_lifetime(head, &self.request_headers) {
+            Ok(lifetime) => lifetime,
+            Err(reason) => {
+                log_uncacheable(reason);
+                return body;
+            },
+        };
+        let vary = match vary_headers(&head.headers, &self.request_headers) {
+            Some(vary) => vary,
+            None => {
+                log_uncacheable("vary_all");
+                return body;
+            },
+        };
+        record_body(self, head.clone(), vary, lifetime, body)
+    }
+}
+
+#[try_stream(ok = Bytes, error = anyhow::Error, boxed)]
+async fn record_body(
+    recorder: CacheRecorder,
+    head: HttpActionResponseHead,
+    vary: Vec<(HeaderName, Vec<HeaderValue>)>,
+    lifetime: Duration,
+    mut body: BoxStream<'static, anyhow::Result<Bytes>>,
+) {
+    let max_entry_size = recorder.cache.max_entry_size;
+    let mut buffered = Some(vec![]);
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        if let Some(buf) = &mut buffered {
+            if buf.len() + chunk.len() > max_entry_size {
+                log_uncacheable("too_large");
+                buffered = None;
+            } else {
+                buf.extend_from_slice(&chunk);
+            }
+        }
+        yield chunk;
+    }
+    if let Some(buf) = buffered {
+        let stored_at = Instant::now();
+        recorder.cache.insert(
+            recorder.key,
+            CachedResponse {
+                status: head.status,
+                headers: head.headers,
+                body: buf.into(),
+                vary,
+                stored_at,
+                expires_at: stored_at + lifetime,
+            },
+        );
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct CacheKey {
+    method: Method,
+    // The URL without its fragment.
+    url: String,
+}
+
+impl CacheKey {
+    fn new(request: &HttpActionRequestHead) -> Self {
+        Self {
+            method: request.method.clone(),
+            url: request.url[..Position::AfterQuery].to_string(),
+        }
+    }
+
+    fn size(&self) -> usize {
+        mem::size_of::<Self>() + self.url.len()
+    }
+}
+
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    // The request headers named by the response's `Vary` header, with the
+    // values they had in the request the response was generated for.
+    vary: Vec<(HeaderName, Vec<HeaderValue>)>,
+    stored_at: Instant,
+    expires_at: Instant,
+}
+
+impl CachedResponse {
+    /// Approximate size in-memory of the response.
+    fn size(&self) -> usize {
+        mem::size_of::<Self>()
+            + self.body.len()
+            + self
+                .headers
+                .iter()
+                .map(|(name, value)| name.as_str().len() + value.len())
+                .sum::<usize>()
+    }
+
+    fn matches(&self, request_headers: &HeaderMap) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, values)| request_headers.get_all(name).iter().eq(values.iter()))
+    }
+
+    fn to_response(&self, request_headers: &HeaderMap, now: Instant) -> HttpActionResponse {
+        let mut headers = self.headers.clone();
+        headers.insert(
+            AGE,
+            HeaderValue::from(now.duration_since(self.stored_at).as_secs()),
+        );
+        if let Some(etag) = self.headers.get(ETAG)
+            && if_none_match(request_headers, etag)
+        {
+            headers.remove(CONTENT_LENGTH);
+            return HttpActionResponse {
+                status: StatusCode::NOT_MODIFIED,
+                headers,
+                body: stream::empty().boxed(),
+            };
+        }
+        HttpActionResponse {
+            status: self.status,
+            headers,
+            body: stream::once(futures::future::ready(Ok(self.body.clone()))).boxed(),
+        }
+    }
+}
+
+struct Inner {
+    // Each URL can have a response for each combination of the values of the
+    // headers the response varies on.
+    cache: LruCache<CacheKey, Vec<CachedResponse>>,
+    size: usize,
+}
+
+impl Inner {
+    fn get(
+        &mut self,
+        key: &CacheKey,
+        request_headers: &HeaderMap,
+        now: Instant,
+    ) -> Option<&CachedResponse> {
+        let responses = self.cache.get_mut(key)?;
+        let mut removed_size = 0;
+        responses.retain(|response| {
+            let fresh = response.expires_at > now;
+            if !fresh {
+                removed_size += response.size();
+            }
+            fresh
+        });
+        let is_empty = responses.is_empty();
+        self.size -= removed_size;
+        if is_empty {
+            self.cache.pop(key);
+            self.size -= key.size();
+            log_cache_size(self.size);
+            return None;
+        }
+        log_cache_size(self.size);
+        self.cache
+            .peek(key)?
+            .iter()
+            .find(|response| response.matches(request_headers))
+    }
+
+    fn insert(&mut self, key: CacheKey, response: CachedResponse) {
+        if !self.cache.contains(&key) {
+            self.size += key.size();
+            self.cache.put(key.clone(), vec![]);
+        }
+        let responses = self
+            .cache
+            .get_mut(&key)
+            .expect("responses for key were just inserted");
+        // Replace the response for the same values of the vary headers.
+        if let Some(i) = responses
+            .iter()
+            .position(|existing| existing.vary == response.vary)
+        {
+            self.size -= responses.remove(i).size();
+        }
+        self.size += response.size();
+        responses.push(response);
+    }
+
+    /// Pop responses until the cache is under the given size.
+    fn enforce_size_limit(&mut self, max_size: usize) {
+        while self.size > max_size {
+            let Some((popped_key, popped_responses)) = self.cache.pop_lru() else {
+                break;
+            };
+            self.size -= popped_key.size()
+                + popped_responses
+                    .iter()
+                    .map(CachedResponse::size)
+                    .sum::<usize>();
+        }
+        log_cache_size(self.size);
+    }
+}
+
+#[derive(Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    public: bool,
+    max_age: Option<Duration>,
+    s_maxage: Option<Duration>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> Self {
+        let mut directives = Self::default();
+        let values = headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+        for directive in values.flat_map(|value| value.split(',')) {
+            let (name, argument) = match directive.split_once('=') {
+                Some((name, argument)) => (name, Some(argument.trim().trim_matches('"'))),
+                None => (directive, None),
+            };
+            let seconds = || {
+                argument
+                    .and_then(|a| a.parse().ok())
+                    .map(Duration::from_secs)
+            };
+            match &name.trim().to_ascii_lowercase()[..] {
+                "no-store" => directives.no_store = true,
+                "no-cache" => directives.no_cache = true,
+                "private" => directives.private = true,
+                "public" => directives.public = true,
+                "max-age" => directives.max_age = seconds(),
+                "s-maxage" => directives.s_maxage = seconds(),
+                _ => {},
+            }
+        }
+        directives
+    }
+}
+
+/// How long a shared cache may serve the response for, or the reason it can't
+/// be stored. We only store responses with explicit freshness information.
+fn freshness_lifetime(
+    head: &HttpActionResponseHead,
+    request_headers: &HeaderMap,
+) -> Result<Duration, &'static str> {
+    if !CACHEABLE_STATUSES.contains(&head.status) {
+        return Err("status");
+    }
+    if head.headers.contains_key(SET_COOKIE) {
+        return Err("set_cookie");
+    }
+    let directives = CacheControl::parse(&head.headers);
+    if directives.no_store || directives.no_cache || directives.private {
+        return Err("cache_control");
+    }
+    // A response to an authorized request is only shared if the response
+    // explicitly allows it.
+    if request_headers.contains_key(AUTHORIZATION)
+        && !directives.public
+        && directives.s_maxage.is_none()
+    {
+        return Err("authorization");
+    }
+    match directives.s_maxage.or(directives.max_age) {
+        Some(lifetime) if !lifetime.is_zero() => Ok(lifetime),
+        _ => Err("no_max_age"),
+    }
+}
+
+/// The request headers the response varies on, with their values in the
+/// request. Returns `None` if the response varies on everything.
+fn vary_headers(
+    response_headers: &HeaderMap,
+    request_headers: &HeaderMap,
+) -> Option<Vec<(HeaderName, Vec<HeaderValue>)>> {
+    let mut names: Vec<HeaderName> = vec![];
+    let values = response_headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok());
+    for name in values.flat_map(|value| value.split(',')) {
+        let name = name.trim();
+        if name == "*" {
+            return None;
+        }
+        if let Ok(name) = HeaderName::from_bytes(name.as_bytes())
+            && !names.contains(&name)
+        {
+            names.push(name);
+        }
+    }
+    names.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    Some(
+        names
+            .into_iter()
+            .map(|name| {
+                let values = request_headers.get_all(&name).iter().cloned().collect();
+                (name, values)
+            })
+            .collect(),
+    )
+}
+
+/// Whether the request's `If-None-Match` matches the entity tag, using the weak
+/// comparison.
+fn if_none_match(request_headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let Ok(etag) = etag.to_str() else {
+        return false;
+    };
+    let etag = etag.trim_start_matches("W/");
+    request_headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|candidate| {
+            let candidate = candidate.trim();
+            candidate == "*" || candidate.trim_start_matches("W/") == etag
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::Arc,
+        time::{
+            Duration,
+            Instant,
+        },
+    };
+
+    use axum::body::Bytes;
+    use futures::TryStreamExt;
+    use http::{
+        header::CACHE_CONTROL,
+        HeaderMap,
+        Method,
+        StatusCode,
+    };
+    use isolate::{
+        HttpActionRequestHead,
+        HttpActionResponseHead,
+    };
+
+    use super::{
+        freshness_lifetime,
+        vary_headers,
+        CacheKey,
+        CacheLookup,
+        CachedResponse,
+        HttpResponseCache,
+    };
+
+    fn request(headers: &[(&'static str, &'static str)]) -> HttpActionRequestHead {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            header_map.append(*name, value.parse().unwrap());
+        }
+        HttpActionRequestHead {
+            headers: header_map,
+            url: "https://example.com/posts?page=1".parse().unwrap(),
+            method: Method::GET,
+        }
+    }
+
+    fn response(headers: &[(&'static str, &'static str)]) -> HttpActionResponseHead {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            header_map.append(*name, value.parse().unwrap());
+        }
+        HttpActionResponseHead {
+            status: StatusCode::OK,
+            headers: header_map,
+        }
+    }
+
+    fn store(
+        cache: &HttpResponseCache,
+        request: &HttpActionRequestHead,
+        response: &HttpActionResponseHead,
+        now: Instant,
+    ) {
+        let lifetime = freshness_lifetime(response, &request.headers).unwrap();
+        cache.insert(
+            CacheKey::new(request),
+            CachedResponse {
+                status: response.status,
+                headers: response.headers.clone(),
+                body: Bytes::from_static(b"hello"),
+                vary: vary_headers(&response.headers, &request.headers).unwrap(),
+                stored_at: now,
+                expires_at: now + lifetime,
+            },
+        );
+    }
+
+    #[test]
+    fn test_freshness_lifetime() {
+        let anonymous = request(&[]);
+        let authorized = request(&[("authorization", "Bearer token")]);
+        assert_eq!(
+            freshness_lifetime(
+                &response(&[("cache-control", "public, max-age=60")]),
+                &anonymous.headers
+            ),
+            Ok(Duration::from_secs(60))
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &response(&[("cache-control", "max-age=60, s-maxage=10")]),
+                &anonymous.headers
+            ),
+            Ok(Duration::from_secs(10))
+        );
+        assert_eq!(
+            freshness_lifetime(&response(&[]), &anonymous.headers),
+            Err("no_max_age")
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &response(&[("cache-control", "private, max-age=60")]),
+                &anonymous.headers
+            ),
+            Err("cache_control")
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &response(&[("cache-control", "max-age=60"), ("set-cookie", "a=b")]),
+                &anonymous.headers
+            ),
+            Err("set_cookie")
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &response(&[("cache-control", "max-age=60")]),
+                &authorized.headers
+            ),
+            Err("authorization")
+        );
+        assert_eq!(
+            freshness_lifetime(
+                &response(&[("cache-control", "public, max-age=60")]),
+                &authorized.headers
+            ),
+            Ok(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn test_lookup_honors_freshness_and_vary() {
+        let cache = Arc::new(HttpResponseCache::new(1 << 20, 1 << 10));
+        let now = Instant::now();
+        let english = request(&[("accept-language", "en")]);
+        let french = request(&[("accept-language", "fr")]);
+        let cacheable = response(&[("cache-control", "max-age=60"), ("vary", "Accept-Language")]);
+        store(&cache, &english, &cacheable, now);
+
+        assert!(matches!(
+            cache.lookup_at(&english, now + Duration::from_secs(30)),
+            CacheLookup::Hit(_)
+        ));
+        assert!(matches!(
+            cache.lookup_at(&french, now + Duration::from_secs(30)),
+            CacheLookup::Miss(_)
+        ));
+        assert!(matches!(
+            cache.lookup_at(&english, now + Duration::from_secs(61)),
+            CacheLookup::Miss(_)
+        ));
+        // Stale responses are evicted when they're looked up.
+        assert_eq!(cache.inner.lock().size, 0);
+
+        // The client can skip the cache, and non-GET requests always do.
+        store(&cache, &english, &cacheable, now);
+        let mut no_cache = english.clone();
+        no_cache
+            .headers
+            .insert(CACHE_CONTROL, "no-cache".parse().unwrap());
+        assert!(matches!(
+            cache.lookup_at(&no_cache, now),
+            CacheLookup::Miss(_)
+        ));
+        let mut post = english.clone();
+        post.method = Method::POST;
+        assert!(matches!(cache.lookup_at(&post, now), CacheLookup::Bypass));
+    }
+
+    #[tokio::test]
+    async fn test_hit_revalidates_etag() -> anyhow::Result<()> {
+        let cache = Arc::new(HttpResponseCache::new(1 << 20, 1 << 10));
+        let now = Instant::now();
+        let cacheable = response(&[("cache-control", "max-age=60"), ("etag", "\"v1\"")]);
+        store(&cache, &request(&[]), &cacheable, now);
+
+        let CacheLookup::Hit(hit) = cache.lookup_at(&request(&[]), now) else {
+            panic!("expected a cache hit");
+        };
+        assert_eq!(hit.status, StatusCode::OK);
+        let body: Vec<Bytes> = hit.body.try_collect().await?;
+        assert_eq!(body, vec![Bytes::from_static(b"hello")]);
+
+        let conditional = request(&[("if-none-match", "W/\"v1\"")]);
+        let CacheLookup::Hit(not_modified) = cache.lookup_at(&conditional, now) else {
+            panic!("expected a cache hit");
+        };
+        assert_eq!(not_modified.status, StatusCode::NOT_MODIFIED);
+        let body: Vec<Bytes> = not_modified.body.try_collect().await?;
+        assert!(body.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_enforces_size_limit() {
+        let cache = Arc::new(HttpResponseCache::new(1, 1 << 10));
+        let now = Instant::now();
+        let cacheable = response(&[("cache-control", "max-age=60")]);
+        store(&cache, &request(&[]), &cacheable, now);
+        assert!(matches!(
+            cache.lookup_at(&request(&[]), now),
+            CacheLookup::Miss(_)
+        ));
+        assert_eq!(cache.inner.lock().size, 0);
+    }
+}
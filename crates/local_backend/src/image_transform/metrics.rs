@@ -0,0 +1,43 @@
+use metrics::{
+    log_counter_with_labels,
+    log_gauge,
+    register_convex_counter,
+    register_convex_gauge,
+    register_convex_histogram,
+    StaticMetricLabel,
+    StatusTimer,
+    STATUS_LABEL,
+};
+
+register_convex_counter!(
+    IMAGE_TRANSFORM_CACHE_LOOKUPS_TOTAL,
+    "Number of transformed images looked up in the image transform cache",
+    &["cache_status"]
+);
+pub fn log_lookup(hit: bool) {
+    log_counter_with_labels(
+        &IMAGE_TRANSFORM_CACHE_LOOKUPS_TOTAL,
+        1,
+        vec![StaticMetricLabel::new(
+            "cache_status",
+            if hit { "hit" } else { "miss" },
+        )],
+    );
+}
+
+register_convex_histogram!(
+    IMAGE_TRANSFORM_SECONDS,
+    "Duration of decoding, resizing and encoding an image",
+    &STATUS_LABEL
+);
+pub fn transform_timer() -> StatusTimer {
+    StatusTimer::new(&IMAGE_TRANSFORM_SECONDS)
+}
+
+register_convex_gauge!(
+    IMAGE_TRANSFORM_CACHE_SIZE_BYTES,
+    "Approximate size of the image transform cache"
+);
+pub fn log_cache_size(size: usize) {
+    log_gauge(&IMAGE_TRANSFORM_CACHE_SIZE_BYTES, size as f64);
+}
@@ -0,0 +1,450 @@
+//! Resizing and format conversion of images served from file storage, so apps
+//! can request e.g. a thumbnail with `?width=200&format=webp` instead of
+//! generating one ahead of time or proxying through another service.
+//!
+//! Transformed images are kept in an in-memory LRU cache keyed by the source
+//! file's hash and the transformation. Stored files never change, so cached
+//! images never go stale.
+
+use std::{
+    fmt,
+    io::Cursor,
+    mem,
+};
+
+use axum::body::Bytes;
+use common::{
+    bounded_thread_pool::BoundedThreadPool,
+    sha256::Sha256Digest,
+};
+use errors::ErrorMetadata;
+use image::{
+    codecs::jpeg::JpegEncoder,
+    imageops::FilterType,
+    DynamicImage,
+    ImageFormat,
+    ImageReader,
+    Limits,
+};
+use lru::LruCache;
+use parking_lot::Mutex;
+use runtime::prod::ProdRuntime;
+use serde::Deserialize;
+
+use self::metrics::{
+    log_cache_size,
+    log_lookup,
+    transform_timer,
+};
+
+mod metrics;
+
+/// Images can't be resized to be wider or taller than this.
+pub const MAX_IMAGE_DIMENSION: u32 = 4096;
+
+/// Larger images aren't decoded, so a small file can't make us allocate an
+/// enormous image.
+const MAX_SOURCE_DIMENSION: u32 = 16384;
+
+const DEFAULT_JPEG_QUALITY: u8 = 80;
+
+const THREAD_POOL_QUEUE_SIZE: usize = 1000;
+
+/// The query parameters of a file storage GET that transform an image.
+#[derive(Deserialize, Default)]
+pub struct ImageTransformParams {
+    width: Option<u32>,
+    height: Option<u32>,
+    format: Option<String>,
+    quality: Option<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl OutputFormat {
+    fn from_image_format(format: ImageFormat) -> Option<Self> {
+        match format {
+            ImageFormat::Png => Some(OutputFormat::Png),
+            ImageFormat::Jpeg => Some(OutputFormat::Jpeg),
+            ImageFormat::WebP => Some(OutputFormat::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "image/png",
+            OutputFormat::Jpeg => "image/jpeg",
+            OutputFormat::Webp => "image/webp",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg => "jpeg",
+            OutputFormat::Webp => "webp",
+        }
+    }
+}
+
+/// How to transform an image. Images are scaled down to fit within `width`
+/// and `height`, keeping their aspect ratio, and are never scaled up.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ImageTransform {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Defaults to the source image's format, or PNG if it can't be encoded.
+    pub format: Option<OutputFormat>,
+    /// JPEG quality from 1 to 100. WebP images are encoded losslessly, so
+    /// it's ignored for other formats.
+    pub quality: Option<u8>,
+}
+
+impl ImageTransform {
+    /// `None` if the request doesn't ask for the image to be transformed.
+    pub fn from_params(params: ImageTransformParams) -> anyhow::Result<Option<Self>> {
+        let ImageTransformParams {
+            width,
+            height,
+            format,
+            quality,
+        } = params;
+        if width.is_none() && height.is_none() && format.is_none() && quality.is_none() {
+            return Ok(None);
+        }
+        for (name, dimension) in [("width", width), ("height", height)] {
+            if let Some(dimension) = dimension
+                && !(1..=MAX_IMAGE_DIMENSION).contains(&dimension)
+            {
+                anyhow::bail!(invalid_transform(format!(
+                    "{name} must be between 1 and {MAX_IMAGE_DIMENSION}, not {dimension}"
+                )));
+            }
+        }
+        if let Some(quality) = quality
+            && !(1..=100).contains(&quality)
+        {
+            anyhow::bail!(invalid_transform(format!(
+                "quality must be between 1 and 100, not {quality}"
+            )));
+        }
+        let format = format
+            .map(|format| match &format.to_ascii_lowercase()[..] {
+                "png" => Ok(OutputFormat::Png),
+                "jpeg" | "jpg" => Ok(OutputFormat::Jpeg),
+                "webp" => Ok(OutputFormat::Webp),
+                _ => Err(invalid_transform(format!(
+                    "format must be one of png, jpeg or webp, not {format}"
+                ))),
+            })
+            .transpose()?;
+        Ok(Some(Self {
+            width,
+            height,
+            format,
+            quality,
+        }))
+    }
+}
+
+/// Names the transformation in the transformed image's ETag.
+impl fmt::Display for ImageTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if let Some(width) = self.width {
+            parts.push(format!("w{width}"));
+        }
+        if let Some(height) = self.height {
+            parts.push(format!("h{height}"));
+        }
+        if let Some(format) = self.format {
+            parts.push(format.name().to_string());
+        }
+        if let Some(quality) = self.quality {
+            parts.push(format!("q{quality}"));
+        }
+        write!(f, "{}", parts.join("-"))
+    }
+}
+
+fn invalid_transform(msg: String) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidImageTransform", msg)
+}
+
+#[derive(Clone, Debug)]
+pub struct TransformedImage {
+    pub body: Bytes,
+    pub format: OutputFormat,
+}
+
+type CacheKey = (Sha256Digest, ImageTransform);
+
+pub struct ImageTransformer {
+    thread_pool: BoundedThreadPool<ProdRuntime>,
+    cache: Mutex<Cache>,
+    max_cache_size: usize,
+    /// Larger files aren't read to be transformed.
+    pub max_source_size: usize,
+}
+
+impl ImageTransformer {
+    pub fn new(
+        rt: ProdRuntime,
+        num_threads: usize,
+        max_cache_size: usize,
+        max_source_size: usize,
+    ) -> Self {
+        Self {
+            thread_pool: BoundedThreadPool::new(
+                rt,
+                THREAD_POOL_QUEUE_SIZE,
+                num_threads,
+                "image_transform",
+            ),
+            cache: Mutex::new(Cache {
+                images: LruCache::unbounded(),
+                size: 0,
+            }),
+            max_cache_size,
+            max_source_size,
+        }
+    }
+
+    /// A previous transformation of the file with hash `sha256`.
+    pub fn cached(
+        &self,
+        sha256: &Sha256Digest,
+        transform: &ImageTransform,
+    ) -> Option<TransformedImage> {
+        let image = self
+            .cache
+            .lock()
+            .images
+            .get(&(sha256.clone(), transform.clone()))
+            .cloned();
+        log_lookup(image.is_some());
+        image
+    }
+
+    /// Transform `source`, the contents of the file with hash `sha256`, and
+    /// cache the result.
+    pub async fn transform(
+        &self,
+        sha256: Sha256Digest,
+        source: Vec<u8>,
+        transform: ImageTransform,
+    ) -> anyhow::Result<TransformedImage> {
+        let key = (sha256, transform.clone());
+        // Decoding and encoding images is CPU intensive, so it's done on a
+        // separate thread pool.
+        let image = self
+            .thread_pool
+            .execute(move || transform_image(&source, &transform))
+            .await??;
+        let mut cache = self.cache.lock();
+        cache.insert(key, image.clone());
+        cache.enforce_size_limit(self.max_cache_size);
+        Ok(image)
+    }
+}
+
+struct Cache {
+    images: LruCache<CacheKey, TransformedImage>,
+    size: usize,
+}
+
+impl Cache {
+    fn entry_size(image: &TransformedImage) -> usize {
+        mem::size_of::<CacheKey>() + mem::size_of::<TransformedImage>() + image.body.len()
+    }
+
+    fn insert(&mut self, key: CacheKey, image: TransformedImage) {
+        self.size += Self::entry_size(&image);
+        if let Some(replaced) = self.images.put(key, image) {
+            self.size -= Self::entry_size(&replaced);
+        }
+    }
+
+    /// Pop images until the cache is under the given size.
+    fn enforce_size_limit(&mut self, max_size: usize) {
+        while self.size > max_size {
+            let Some((_, popped)) = self.images.pop_lru() else {
+                break;
+            };
+            self.size -= Self::entry_size(&popped);
+        }
+        log_cache_size(self.size);
+    }
+}
+
+fn transform_image(source: &[u8], transform: &ImageTransform) -> anyhow::Result<TransformedImage> {
+    let timer = transform_timer();
+    let mut reader = ImageReader::new(Cursor::new(source)).with_guessed_format()?;
+    let source_format = reader.format();
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| {
+        ErrorMetadata::bad_request(
+            "InvalidImage",
+            format!("The file can't be transformed because it isn't a supported image: {e}"),
+        )
+    })?;
+    let format = transform
+        .format
+        .or_else(|| source_format.and_then(OutputFormat::from_image_format))
+        .unwrap_or(OutputFormat::Png);
+
+    let image = resize(image, transform.width, transform.height);
+    let mut body = Vec::new();
+    match format {
+        OutputFormat::Png => image.write_to(&mut Cursor::new(&mut body), ImageFormat::Png)?,
+        OutputFormat::Jpeg => {
+            // JPEG doesn't support transparency.
+            let encoder = JpegEncoder::new_with_quality(
+                &mut body,
+                transform.quality.unwrap_or(DEFAULT_JPEG_QUALITY),
+            );
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?;
+        },
+        OutputFormat::Webp => {
+            // The WebP encoder only supports 8-bit RGB(A).
+            DynamicImage::ImageRgba8(image.to_rgba8())
+                .write_to(&mut Cursor::new(&mut body), ImageFormat::WebP)?
+        },
+    }
+    timer.finish();
+    Ok(TransformedImage {
+        body: body.into(),
+        format,
+    })
+}
+
+fn resize(image: DynamicImage, width: Option<u32>, height: Option<u32>) -> DynamicImage {
+    let width = width.map_or(image.width(), |width| width.min(image.width()));
+    let height = height.map_or(image.height(), |height| height.min(image.height()));
+    if (width, height) == (image.width(), image.height()) {
+        return image;
+    }
+    image.resize(width, height, FilterType::CatmullRom)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{
+        DynamicImage,
+        ImageFormat,
+        RgbaImage,
+    };
+
+    use super::{
+        transform_image,
+        ImageTransform,
+        ImageTransformParams,
+        OutputFormat,
+    };
+
+    fn png(width: u32, height: u32) -> anyhow::Result<Vec<u8>> {
+        let mut body = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut body), ImageFormat::Png)?;
+        Ok(body)
+    }
+
+    #[test]
+    fn test_parse_transform() -> anyhow::Result<()> {
+        assert_eq!(
+            ImageTransform::from_params(ImageTransformParams::default())?,
+            None
+        );
+        assert_eq!(
+            ImageTransform::from_params(ImageTransformParams {
+                width: Some(200),
+                format: Some("JPG".to_string()),
+                quality: Some(60),
+                ..Default::default()
+            })?,
+            Some(ImageTransform {
+                width: Some(200),
+                height: None,
+                format: Some(OutputFormat::Jpeg),
+                quality: Some(60),
+            })
+        );
+        for params in [
+            ImageTransformParams {
+                width: Some(0),
+                ..Default::default()
+            },
+            ImageTransformParams {
+                height: Some(10000),
+                ..Default::default()
+            },
+            ImageTransformParams {
+                quality: Some(101),
+                ..Default::default()
+            },
+            ImageTransformParams {
+                format: Some("tiff".to_string()),
+                ..Default::default()
+            },
+        ] {
+            assert!(ImageTransform::from_params(params).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_image() -> anyhow::Result<()> {
+        let source = png(400, 200)?;
+
+        // Resizing keeps the aspect ratio and the source's format.
+        let resized = transform_image(
+            &source,
+            &ImageTransform {
+                width: Some(100),
+                height: None,
+                format: None,
+                quality: None,
+            },
+        )?;
+        assert_eq!(resized.format, OutputFormat::Png);
+        let image = image::load_from_memory(&resized.body)?;
+        assert_eq!((image.width(), image.height()), (100, 50));
+
+        // Images aren't scaled up.
+        let converted = transform_image(
+            &source,
+            &ImageTransform {
+                width: Some(1000),
+                height: Some(1000),
+                format: Some(OutputFormat::Jpeg),
+                quality: Some(50),
+            },
+        )?;
+        assert_eq!(converted.format, OutputFormat::Jpeg);
+        let image = image::load_from_memory_with_format(&converted.body, ImageFormat::Jpeg)?;
+        assert_eq!((image.width(), image.height()), (400, 200));
+
+        assert!(transform_image(
+            b"pinna park",
+            &ImageTransform {
+                width: Some(100),
+                height: None,
+                format: None,
+                quality: None,
+            },
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use std::str::FromStr;
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+};
 
 use anyhow::Context;
 use application::snapshot_import::{
@@ -27,6 +30,7 @@ use futures::{
     TryStreamExt,
 };
 use model::snapshot_imports::types::{
+    CsvColumnType,
     ImportFormat,
     ImportMode,
 };
@@ -56,6 +60,17 @@ pub struct ImportQueryArgs {
     format: ImportFormatArg,
     #[serde(default)]
     mode: ImportMode,
+    /// Types of CSV columns whose values shouldn't be inferred, like
+    /// `count:int64,done:boolean`.
+    column_types: Option<String>,
+    /// Validate every row before waiting for confirmation, so the import can
+    /// be checked without writing anything.
+    #[serde(default)]
+    dry_run: bool,
+    /// Give imported documents new ids, and rewrite the references to them in
+    /// `v.id` fields of the schema.
+    #[serde(default)]
+    remap_ids: bool,
 }
 
 #[derive(Deserialize)]
@@ -88,10 +103,32 @@ struct ImportResponse {
     num_written: u64,
 }
 
+fn parse_column_types_arg(column_types: &str) -> anyhow::Result<BTreeMap<String, CsvColumnType>> {
+    column_types
+        .split(',')
+        .map(|column_type| {
+            let (column, column_type) = column_type.split_once(':').with_context(|| {
+                ErrorMetadata::bad_request(
+                    "InvalidCsvColumnType",
+                    format!("CSV column type {column_type:?} should look like \"column:type\""),
+                )
+            })?;
+            Ok((column.trim().to_string(), column_type.trim().parse()?))
+        })
+        .collect()
+}
+
 fn parse_format_arg(
     table_name: Option<String>,
     format: ImportFormatArg,
+    column_types: Option<String>,
 ) -> anyhow::Result<ImportFormat> {
+    if column_types.is_some() && format != ImportFormatArg::Csv {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidCsvColumnType",
+            "Column types can only be given for CSV imports",
+        ));
+    }
     let table_name = table_name
         .map(|table_name| {
             TableName::from_str(&table_name).map_err(|e| {
@@ -112,9 +149,16 @@ fn parse_format_arg(
             }
             ImportFormat::Zip
         },
-        ImportFormatArg::Csv => ImportFormat::Csv(table_name.context(
-            ErrorMetadata::bad_request("InvalidName", "CSV import requires table name"),
-        )?),
+        ImportFormatArg::Csv => ImportFormat::Csv(
+            table_name.context(ErrorMetadata::bad_request(
+                "InvalidName",
+                "CSV import requires table name",
+            ))?,
+            column_types
+                .map(|column_types| parse_column_types_arg(&column_types))
+                .transpose()?
+                .unwrap_or_default(),
+        ),
         ImportFormatArg::JsonArray => ImportFormat::JsonArray(table_name.context(
             ErrorMetadata::bad_request("InvalidName", "JSON import requires table name"),
         )?),
@@ -132,11 +176,28 @@ pub async fn import(
         table_name,
         format,
         mode,
+        column_types,
+        dry_run,
+        remap_ids,
     }): Query<ImportQueryArgs>,
     stream: BodyStream,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
-    let format = parse_format_arg(table_name, format)?;
+    if dry_run {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidImport",
+            "Dry runs aren't supported by this endpoint, use /api/prepare_import instead",
+        ))
+        .into());
+    }
+    if remap_ids {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidImport",
+            "Id remapping isn't supported by this endpoint, use /api/prepare_import instead",
+        ))
+        .into());
+    }
+    let format = parse_format_arg(table_name, format, column_types)?;
     let body_stream = stream.map_err(anyhow::Error::from).boxed();
     let num_written = do_import(&st.application, identity, format, mode, body_stream).await?;
     Ok(Json(ImportResponse { num_written }))
@@ -202,19 +263,24 @@ pub async fn import_finish_upload(
                 table_name,
                 format,
                 mode,
+                column_types,
+                dry_run,
+                remap_ids,
             },
         upload_token,
         part_tokens,
     }): Json<ImportFinishUploadArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
-    let format = parse_format_arg(table_name, format)?;
+    let format = parse_format_arg(table_name, format, column_types)?;
     let import_id = st
         .application
         .import_finish_upload(
             identity,
             format,
             mode,
+            dry_run,
+            remap_ids,
             ClientDrivenUploadToken(upload_token),
             part_tokens
                 .into_iter()
@@ -240,14 +306,25 @@ pub async fn prepare_import(
         table_name,
         format,
         mode,
+        column_types,
+        dry_run,
+        remap_ids,
     }): Query<ImportQueryArgs>,
     stream: BodyStream,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
-    let format = parse_format_arg(table_name, format)?;
+    let format = parse_format_arg(table_name, format, column_types)?;
     let body_stream = stream.map_err(anyhow::Error::from).boxed();
-    let import_id =
-        upload_import_file(&st.application, identity, format, mode, body_stream).await?;
+    let import_id = upload_import_file(
+        &st.application,
+        identity,
+        format,
+        mode,
+        dry_run,
+        remap_ids,
+        body_stream,
+    )
+    .await?;
     Ok(Json(PrepareImportResponse {
         import_id: import_id.encode(),
     }))
@@ -7,6 +7,7 @@
 #![feature(lint_reasons)]
 
 use std::{
+    ffi::OsString,
     sync::{
         atomic::AtomicU64,
         Arc,
@@ -19,21 +20,32 @@ use ::authentication::{
     application_auth::ApplicationAuth,
 };
 use ::storage::{
+    CredentialsProvider,
     LocalDirStorage,
+    RemoteStorage,
+    RemoteStorageConfig,
+    Storage,
     StorageUseCase,
 };
+use admin_access::AdminAccessPolicy;
 use application::{
     api::ApplicationApi,
+    log_streaming::LogManager,
     log_visibility::AllowLogging,
     Application,
 };
+use built_in_auth::BuiltInAuthRateLimiter;
 use common::{
+    errors::report_error,
     http::{
         fetch::ProxiedFetchClient,
         RouteMapper,
     },
-    knobs::ACTION_USER_TIMEOUT,
-    log_streaming::NoopLogSender,
+    knobs::{
+        ACTION_USER_TIMEOUT,
+        HTTP_ACTION_RATE_LIMIT,
+        PUBLIC_API_RATE_LIMIT,
+    },
     pause::PauseClient,
     persistence::Persistence,
     types::{
@@ -46,18 +58,28 @@ use database::{
     Database,
     ShutdownSignal,
 };
+use drain::Drain;
 use events::usage::NoOpUsageEventLogger;
 use file_storage::{
+    FileScannerConfig,
     FileStorage,
     TransactionalFileStorage,
 };
 use function_runner::{
+    persistence_proxy::start_persistence_reader_proxy,
+    process_pool::{
+        FunctionRunnerProcessConfig,
+        ProcessFunctionRunner,
+    },
     server::{
         InProcessFunctionRunner,
         InstanceStorage,
     },
     FunctionRunner,
 };
+use graphql::GraphQLSchemaCache;
+use http_response_cache::HttpResponseCache;
+use image_transform::ImageTransformer;
 use model::{
     initialize_application_system_tables,
     virtual_system_mapping,
@@ -66,6 +88,7 @@ use node_executor::{
     local::LocalNodeExecutor,
     Actions,
 };
+use rate_limiter::RateLimiter;
 use runtime::prod::ProdRuntime;
 use search::{
     searcher::InProcessSearcher,
@@ -73,28 +96,64 @@ use search::{
     SegmentTermMetadataFetcher,
 };
 use serde::Serialize;
+use settings::BackendSettings;
+use sync::ActiveSubscriptions;
 
 pub mod admin;
+pub mod admin_access;
+pub mod api_keys;
 pub mod authentication;
+pub mod built_in_auth;
+pub mod bulk_edit;
+pub mod component_quotas;
 pub mod config;
+pub mod custom_domains;
 pub mod custom_headers;
 pub mod dashboard;
 pub mod deploy_config;
 pub mod deploy_config2;
+pub mod deployment_clones;
+pub mod deployment_versions;
+pub mod deployments;
+pub mod drain;
+pub mod egress_policy;
 pub mod environment_variables;
+pub mod export_schedules;
+pub mod function_canaries;
+pub mod function_flags;
+pub mod function_read_budgets;
+pub mod graphql;
+pub mod grpc;
+pub mod health;
 pub mod http_actions;
+pub mod http_response_cache;
+pub mod image_transform;
 pub mod import;
+pub mod log_settings;
+pub mod log_sinks;
 pub mod logs;
+pub mod metrics_endpoint;
 pub mod node_action_callbacks;
+pub mod openapi;
+pub mod outbox;
 pub mod parse;
 pub mod proxy;
 pub mod public_api;
+pub mod rate_limiter;
+pub mod read_only;
+pub mod replication;
+pub mod revoked_sessions;
 pub mod router;
 pub mod scheduling;
 pub mod schema;
+pub mod search_index_cache;
+pub mod settings;
 pub mod snapshot_export;
 pub mod storage;
+pub mod storage_admin;
 pub mod subs;
+pub mod traffic_shadowing;
+pub mod trash;
 
 #[cfg(test)]
 mod test_helpers;
@@ -111,7 +170,23 @@ pub struct LocalAppState {
     pub application: Application<ProdRuntime>,
     // Number of sync protocol workers.
     pub live_ws_count: Arc<AtomicU64>,
+    // Queries subscribed to by sync protocol workers.
+    pub active_subscriptions: Arc<ActiveSubscriptions>,
     pub zombify_rx: async_broadcast::Receiver<()>,
+    // Who may call the admin API.
+    pub admin_access: Arc<AdminAccessPolicy>,
+    // Token that must be sent to read `/metrics`, which is disabled without one.
+    pub metrics_token: Option<String>,
+    // Rate limits that can be changed while the backend is running.
+    pub public_api_rate_limiter: Arc<RateLimiter>,
+    pub http_action_rate_limiter: Arc<RateLimiter>,
+    // Current values of the settings that can be changed without a restart.
+    pub settings: Arc<tokio::sync::Mutex<BackendSettings>>,
+    // Set once the backend starts draining before it shuts down.
+    pub drain: Arc<Drain>,
+    // GraphQL schema for the most recently pushed functions.
+    pub graphql_schema_cache: Arc<GraphQLSchemaCache>,
+    pub built_in_auth_rate_limiter: Arc<BuiltInAuthRateLimiter>,
 }
 
 impl LocalAppState {
@@ -130,7 +205,16 @@ impl Clone for LocalAppState {
             instance_name: self.instance_name.clone(),
             application: self.application.clone(),
             live_ws_count: self.live_ws_count.clone(),
+            active_subscriptions: self.active_subscriptions.clone(),
             zombify_rx: self.zombify_rx.clone(),
+            admin_access: self.admin_access.clone(),
+            metrics_token: self.metrics_token.clone(),
+            public_api_rate_limiter: self.public_api_rate_limiter.clone(),
+            http_action_rate_limiter: self.http_action_rate_limiter.clone(),
+            settings: self.settings.clone(),
+            drain: self.drain.clone(),
+            graphql_schema_cache: self.graphql_schema_cache.clone(),
+            built_in_auth_rate_limiter: self.built_in_auth_rate_limiter.clone(),
         }
     }
 }
@@ -145,6 +229,18 @@ pub struct RouterState {
 
     // Number of sync protocol workers.
     pub live_ws_count: Arc<AtomicU64>,
+    // Queries subscribed to by sync protocol workers.
+    pub active_subscriptions: Arc<ActiveSubscriptions>,
+
+    pub http_response_cache: Arc<HttpResponseCache>,
+    pub image_transformer: Arc<ImageTransformer>,
+
+    // Rate limits on the public function API and HTTP actions, which may be
+    // disabled.
+    pub public_api_rate_limiter: Arc<RateLimiter>,
+    pub http_action_rate_limiter: Arc<RateLimiter>,
+
+    pub drain: Arc<Drain>,
 }
 
 #[derive(Serialize)]
@@ -173,23 +269,21 @@ pub async fn make_app(
     )
     .await?;
     initialize_application_system_tables(&database).await?;
-    let files_storage = Arc::new(LocalDirStorage::for_use_case(
-        runtime.clone(),
-        &config.storage_dir().to_string_lossy(),
-        StorageUseCase::Files,
-    )?);
+    let remote_storage = config
+        .remote_storage()?
+        .map(|(remote_config, credentials)| (remote_config, Arc::new(credentials)));
+    let remote_or_local_storage =
+        |use_case| remote_or_local_storage(&runtime, &config, remote_storage.as_ref(), use_case);
+    let files_storage = remote_or_local_storage(StorageUseCase::Files)?;
     let modules_storage = Arc::new(LocalDirStorage::for_use_case(
         runtime.clone(),
         &config.storage_dir().to_string_lossy(),
         StorageUseCase::Modules,
     )?);
-    let search_storage = Arc::new(LocalDirStorage::for_use_case(
-        runtime.clone(),
-        &config.storage_dir().to_string_lossy(),
-        StorageUseCase::SearchIndexes,
-    )?);
+    let search_storage = remote_or_local_storage(StorageUseCase::SearchIndexes)?;
     // Search storage needs to be set for Database to be fully initialized
     database.set_search_storage(search_storage.clone());
+    database.set_field_encryptor(Arc::new(key_broker.field_encryptor()));
     let exports_storage = Arc::new(LocalDirStorage::for_use_case(
         runtime.clone(),
         &config.storage_dir().to_string_lossy(),
@@ -201,12 +295,14 @@ pub async fn make_app(
         StorageUseCase::SnapshotImports,
     )?);
 
+    let file_scanner = config.file_scanner.as_ref().map(FileScannerConfig::scanner);
     let file_storage = FileStorage {
         transactional_file_storage: TransactionalFileStorage::new(
             runtime.clone(),
             files_storage.clone(),
             config.convex_origin_url(),
-        ),
+        )
+        .with_file_scanner(file_scanner.clone()),
         database: database.clone(),
     };
 
@@ -228,22 +324,52 @@ pub async fn make_app(
         config.convex_http_proxy.clone(),
         config.name(),
     ));
-    let function_runner: Arc<dyn FunctionRunner<ProdRuntime>> = Arc::new(
-        InProcessFunctionRunner::new(
-            config.name().clone(),
-            config.secret()?,
-            config.convex_origin_url(),
-            runtime.clone(),
-            persistence.reader(),
-            InstanceStorage {
-                files_storage: files_storage.clone(),
-                modules_storage: modules_storage.clone(),
-            },
-            database.clone(),
-            fetch_client.clone(),
-        )
-        .await?,
-    );
+    let in_process_function_runner = InProcessFunctionRunner::new(
+        config.name().clone(),
+        config.secret()?,
+        config.convex_origin_url(),
+        runtime.clone(),
+        persistence.reader(),
+        InstanceStorage {
+            files_storage: files_storage.clone(),
+            modules_storage: modules_storage.clone(),
+            file_scanner,
+        },
+        database.clone(),
+        fetch_client.clone(),
+    )
+    .await?;
+    let function_runner: Arc<dyn FunctionRunner<ProdRuntime>> =
+        if config.function_runner_processes > 0 {
+            let (backend_url, proxy) = start_persistence_reader_proxy(persistence.reader()).await?;
+            // The proxy serves the processes for as long as the backend runs.
+            runtime.spawn("persistence_reader_proxy", async move {
+                if let Err(mut e) = proxy.await {
+                    report_error(&mut e);
+                }
+            });
+            // The processes are started like this backend, but in function
+            // runner mode.
+            let mut args: Vec<OsString> = std::env::args_os().skip(1).collect();
+            args.push("--function-runner-backend-url".into());
+            args.push(backend_url.into());
+            let process_config = FunctionRunnerProcessConfig {
+                program: std::env::current_exe()?,
+                args,
+                num_processes: config.function_runner_processes,
+            };
+            Arc::new(
+                ProcessFunctionRunner::new(
+                    runtime.clone(),
+                    process_config,
+                    in_process_function_runner,
+                    database.clone(),
+                )
+                .await?,
+            )
+        } else {
+            Arc::new(in_process_function_runner)
+        };
     let application = Application::new(
         runtime.clone(),
         database.clone(),
@@ -265,7 +391,7 @@ pub async fn make_app(
         persistence,
         actions,
         fetch_client,
-        Arc::new(NoopLogSender),
+        LogManager::start(runtime.clone(), database.clone(), config.name())?,
         Arc::new(AllowLogging),
         PauseClient::new(),
         PauseClient::new(),
@@ -278,6 +404,7 @@ pub async fn make_app(
 
     let origin = config.convex_origin_url();
     let instance_name = config.name().clone();
+    let settings = BackendSettings::new(application.runner().concurrency_limits());
 
     let app_state = LocalAppState {
         origin,
@@ -285,12 +412,77 @@ pub async fn make_app(
         instance_name,
         application,
         live_ws_count: Arc::new(AtomicU64::new(0)),
+        active_subscriptions: Arc::new(ActiveSubscriptions::new()),
         zombify_rx,
+        admin_access: Arc::new(config.admin_access_policy()),
+        metrics_token: config.metrics_token.clone(),
+        public_api_rate_limiter: Arc::new(RateLimiter::from_knob(
+            "public_api",
+            "PUBLIC_API_RATE_LIMIT",
+            &PUBLIC_API_RATE_LIMIT,
+        )),
+        http_action_rate_limiter: Arc::new(RateLimiter::from_knob(
+            "http_action",
+            "HTTP_ACTION_RATE_LIMIT",
+            &HTTP_ACTION_RATE_LIMIT,
+        )),
+        settings: Arc::new(tokio::sync::Mutex::new(settings)),
+        drain: Arc::new(Drain::new()),
+        graphql_schema_cache: Arc::new(GraphQLSchemaCache::default()),
+        built_in_auth_rate_limiter: Arc::new(BuiltInAuthRateLimiter::from_knobs()),
     };
 
     Ok(app_state)
 }
 
+/// Files and search indexes may be stored remotely, the rest is always stored
+/// locally.
+fn remote_or_local_storage(
+    runtime: &ProdRuntime,
+    config: &LocalConfig,
+    remote_storage: Option<&(RemoteStorageConfig, Arc<CredentialsProvider>)>,
+    use_case: StorageUseCase,
+) -> anyhow::Result<Arc<dyn Storage>> {
+    Ok(match remote_storage {
+        Some((remote_config, credentials)) => Arc::new(RemoteStorage::for_use_case(
+            runtime.clone(),
+            remote_config.clone(),
+            credentials.clone(),
+            use_case,
+        )?),
+        None => Arc::new(LocalDirStorage::for_use_case(
+            runtime.clone(),
+            &config.storage_dir().to_string_lossy(),
+            use_case,
+        )?),
+    })
+}
+
+/// Storage for a function runner process started by the backend with
+/// `config`, which reads the same modules and files as the backend.
+pub fn function_runner_process_storage(
+    runtime: &ProdRuntime,
+    config: &LocalConfig,
+) -> anyhow::Result<InstanceStorage> {
+    let remote_storage = config
+        .remote_storage()?
+        .map(|(remote_config, credentials)| (remote_config, Arc::new(credentials)));
+    Ok(InstanceStorage {
+        files_storage: remote_or_local_storage(
+            runtime,
+            config,
+            remote_storage.as_ref(),
+            StorageUseCase::Files,
+        )?,
+        modules_storage: Arc::new(LocalDirStorage::for_use_case(
+            runtime.clone(),
+            &config.storage_dir().to_string_lossy(),
+            StorageUseCase::Modules,
+        )?),
+        file_scanner: config.file_scanner.as_ref().map(FileScannerConfig::scanner),
+    })
+}
+
 #[derive(Clone)]
 pub struct HttpActionRouteMapper;
 
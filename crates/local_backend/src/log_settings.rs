@@ -0,0 +1,161 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    log_lines::LogLevel,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    log_settings::{
+        types::LogSetting,
+        LogSettingsModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sync_types::CanonicalizedUdfPath;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    parse::parse_udf_path,
+    LocalAppState,
+};
+
+fn parse_optional_udf_path(
+    udf_path: Option<String>,
+) -> anyhow::Result<Option<CanonicalizedUdfPath>> {
+    udf_path
+        .map(|udf_path| Ok(parse_udf_path(&udf_path)?.canonicalize()))
+        .transpose()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetLogSettingRequest {
+    /// e.g. "messages:list". Omit to configure every function that doesn't
+    /// have its own setting.
+    udf_path: Option<String>,
+    /// "DEBUG", "LOG", "INFO", "WARN" or "ERROR". Lines below this level are
+    /// dropped.
+    min_level: Option<String>,
+    /// The fraction of executions whose logs are kept, from 0 to 1.
+    sample_rate: Option<f64>,
+}
+
+/// Reduces how much a function logs without redeploying it. Errors are
+/// always kept.
+pub async fn set_log_setting(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetLogSettingRequest {
+        udf_path,
+        min_level,
+        sample_rate,
+    }): Json<SetLogSettingRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let min_level = min_level
+        .map(|level| {
+            level.parse::<LogLevel>().map_err(|_| {
+                ErrorMetadata::bad_request(
+                    "InvalidLogSetting",
+                    format!("Unknown log level {level:?}"),
+                )
+            })
+        })
+        .transpose()?;
+    let setting = LogSetting {
+        udf_path: parse_optional_udf_path(udf_path)?,
+        min_level,
+        sample_rate,
+    };
+    let event = DeploymentAuditLogEvent::SetLogSetting {
+        udf_path: setting.udf_path.clone().map(String::from),
+    };
+    let mut tx = st.application.begin(identity).await?;
+    LogSettingsModel::new(&mut tx).set(setting).await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "set_log_setting")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteLogSettingRequest {
+    udf_path: Option<String>,
+}
+
+/// Removes a log setting, so the function's lines are kept again unless the
+/// deployment-wide setting applies.
+pub async fn delete_log_setting(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteLogSettingRequest { udf_path }): Json<DeleteLogSettingRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let udf_path = parse_optional_udf_path(udf_path)?;
+    let mut tx = st.application.begin(identity).await?;
+    LogSettingsModel::new(&mut tx)
+        .delete(udf_path.as_ref())
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteLogSetting {
+                udf_path: udf_path.map(String::from),
+            }],
+            "delete_log_setting",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSettingJson {
+    udf_path: Option<String>,
+    min_level: Option<String>,
+    sample_rate: Option<f64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLogSettingsResponse {
+    settings: Vec<LogSettingJson>,
+}
+
+pub async fn list_log_settings(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let settings = LogSettingsModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|setting| {
+            let setting = setting.into_value();
+            LogSettingJson {
+                udf_path: setting.udf_path.map(String::from),
+                min_level: setting.min_level.map(|level| level.to_string()),
+                sample_rate: setting.sample_rate,
+            }
+        })
+        .collect();
+    Ok(Json(ListLogSettingsResponse { settings }))
+}
@@ -0,0 +1,489 @@
+//! Admin API for configuring the services the deployment's logs are streamed
+//! to, and which logs each of them receives. Secrets like API keys and tokens
+//! are never returned once configured.
+
+use application::log_streaming::validate_sink_config;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    log_streaming::LogEventFormatVersion,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    log_sinks::{
+        types::{
+            DatadogConfig,
+            LogRoutingRules,
+            SentryConfig,
+            SinkConfig,
+            SinkState,
+            SinkType,
+            SplunkConfig,
+        },
+        LogSinksModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddDatadogSinkRequest {
+    site_location: String,
+    dd_api_key: String,
+    #[serde(default)]
+    dd_tags: Vec<String>,
+    service: Option<String>,
+    /// Format of the logs, "1" or "2". Defaults to "2".
+    version: Option<String>,
+    #[serde(default)]
+    rules: LogRoutingRulesJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddSplunkSinkRequest {
+    hec_endpoint: String,
+    token: String,
+    index: Option<String>,
+    source_type: Option<String>,
+    /// Format of the logs, "1" or "2". Defaults to "2".
+    version: Option<String>,
+    #[serde(default)]
+    rules: LogRoutingRulesJson,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddSentrySinkRequest {
+    dsn: String,
+    #[serde(default)]
+    rules: LogRoutingRulesJson,
+}
+
+/// Which logs a sink receives, e.g. `{"logLevels": ["ERROR"]}` for only
+/// errors. Omitted lists match every log.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRoutingRulesJson {
+    /// "console", "function_execution", "exception" or "audit_log".
+    #[serde(default)]
+    event_types: Vec<String>,
+    /// Function paths, or path prefixes ending in `*`.
+    #[serde(default)]
+    function_paths: Vec<String>,
+    /// "DEBUG", "INFO", "LOG", "WARN" or "ERROR".
+    #[serde(default)]
+    log_levels: Vec<String>,
+}
+
+impl TryFrom<LogRoutingRulesJson> for LogRoutingRules {
+    type Error = anyhow::Error;
+
+    fn try_from(rules: LogRoutingRulesJson) -> anyhow::Result<Self> {
+        Ok(Self {
+            event_types: rules
+                .event_types
+                .iter()
+                .map(|event_type| event_type.parse())
+                .collect::<anyhow::Result<_>>()?,
+            function_paths: rules.function_paths,
+            log_levels: rules
+                .log_levels
+                .iter()
+                .map(|level| {
+                    level.parse().map_err(|_| {
+                        anyhow::anyhow!(ErrorMetadata::bad_request(
+                            "InvalidLogLevel",
+                            format!("Unknown log level {level}"),
+                        ))
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+impl From<LogRoutingRules> for LogRoutingRulesJson {
+    fn from(rules: LogRoutingRules) -> Self {
+        Self {
+            event_types: rules
+                .event_types
+                .iter()
+                .map(|event_type| event_type.to_string())
+                .collect(),
+            function_paths: rules.function_paths,
+            log_levels: rules
+                .log_levels
+                .iter()
+                .map(|level| level.to_string())
+                .collect(),
+        }
+    }
+}
+
+fn parse_version(version: Option<String>) -> anyhow::Result<LogEventFormatVersion> {
+    match version {
+        Some(version) => version.parse(),
+        None => Ok(LogEventFormatVersion::V2),
+    }
+}
+
+async fn add_sink(
+    st: &LocalAppState,
+    identity: keybroker::Identity,
+    config: SinkConfig,
+    rules: LogRoutingRules,
+) -> anyhow::Result<()> {
+    validate_sink_config(&config)?;
+    let sink_type = config.sink_type();
+    let mut tx = st.application.begin(identity).await?;
+    LogSinksModel::new(&mut tx)
+        .add_or_update(config, rules)
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::CreateLogSink { sink_type }],
+            "add_log_sink",
+        )
+        .await?;
+    Ok(())
+}
+
+pub async fn add_datadog_sink(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(AddDatadogSinkRequest {
+        site_location,
+        dd_api_key,
+        dd_tags,
+        service,
+        version,
+        rules,
+    }): Json<AddDatadogSinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let config = SinkConfig::Datadog(DatadogConfig {
+        site_location: site_location.parse()?,
+        dd_api_key,
+        dd_tags,
+        service,
+        version: parse_version(version)?,
+    });
+    add_sink(&st, identity, config, rules.try_into()?).await?;
+    Ok(StatusCode::OK)
+}
+
+pub async fn add_splunk_sink(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(AddSplunkSinkRequest {
+        hec_endpoint,
+        token,
+        index,
+        source_type,
+        version,
+        rules,
+    }): Json<AddSplunkSinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let config = SinkConfig::Splunk(SplunkConfig {
+        hec_endpoint,
+        token,
+        index,
+        source_type,
+        version: parse_version(version)?,
+    });
+    add_sink(&st, identity, config, rules.try_into()?).await?;
+    Ok(StatusCode::OK)
+}
+
+/// Only exceptions are reported to Sentry, whatever the sink's rules.
+pub async fn add_sentry_sink(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(AddSentrySinkRequest { dsn, rules }): Json<AddSentrySinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let config = SinkConfig::Sentry(SentryConfig { dsn });
+    add_sink(&st, identity, config, rules.try_into()?).await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLogSinkRulesRequest {
+    sink_type: String,
+    rules: LogRoutingRulesJson,
+}
+
+pub async fn update_log_sink_rules(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(UpdateLogSinkRulesRequest { sink_type, rules }): Json<UpdateLogSinkRulesRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let sink_type: SinkType = sink_type.parse()?;
+    let rules: LogRoutingRules = rules.try_into()?;
+    let mut tx = st.application.begin(identity).await?;
+    LogSinksModel::new(&mut tx)
+        .set_rules(sink_type, rules)
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::UpdateLogSinkRules { sink_type }],
+            "update_log_sink_rules",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteLogSinkRequest {
+    sink_type: String,
+}
+
+pub async fn delete_log_sink(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteLogSinkRequest { sink_type }): Json<DeleteLogSinkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let sink_type: SinkType = sink_type.parse()?;
+    let mut tx = st.application.begin(identity).await?;
+    LogSinksModel::new(&mut tx).delete(sink_type).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteLogSink { sink_type }],
+            "delete_log_sink",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogSinkResponse {
+    sink_type: String,
+    /// "pending", "active" or "failed".
+    status: &'static str,
+    failure_reason: Option<String>,
+    /// Format of the logs, for sinks that have one.
+    version: Option<String>,
+    /// Where logs are sent: the Datadog site, the Splunk HEC endpoint or the
+    /// Sentry DSN without its key.
+    destination: String,
+    rules: LogRoutingRulesJson,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListLogSinksResponse {
+    log_sinks: Vec<LogSinkResponse>,
+}
+
+pub async fn list_log_sinks(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let log_sinks = LogSinksModel::new(&mut tx)
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|doc| {
+            let row = doc.into_value();
+            let (status, failure_reason) = match row.status {
+                SinkState::Pending => ("pending", None),
+                SinkState::Active => ("active", None),
+                SinkState::Failed { reason } => ("failed", Some(reason)),
+            };
+            let (version, destination) = match &row.config {
+                SinkConfig::Datadog(config) => {
+                    (Some(config.version), config.site_location.to_string())
+                },
+                SinkConfig::Splunk(config) => (Some(config.version), config.hec_endpoint.clone()),
+                SinkConfig::Sentry(config) => (None, redact_dsn(&config.dsn)),
+            };
+            LogSinkResponse {
+                sink_type: row.config.sink_type().to_string(),
+                status,
+                failure_reason,
+                version: version.map(|version| version.to_string()),
+                destination,
+                rules: row.rules.into(),
+            }
+        })
+        .collect();
+    Ok(Json(ListLogSinksResponse { log_sinks }))
+}
+
+/// Strips the public key from a Sentry DSN.
+fn redact_dsn(dsn: &str) -> String {
+    let Ok(mut url) = dsn.parse::<url::Url>() else {
+        return String::new();
+    };
+    if url.set_username("").is_err() {
+        return String::new();
+    }
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        TestLocalBackend,
+    };
+
+    fn admin_request(
+        backend: &TestLocalBackend,
+        uri: &str,
+        body: Option<JsonValue>,
+    ) -> anyhow::Result<Request<Body>> {
+        let builder = Request::builder()
+            .uri(uri)
+            .header("Authorization", backend.admin_auth_header.0.encode());
+        Ok(match body {
+            Some(body) => builder
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body)?))?,
+            None => builder.method("GET").body(Body::empty())?,
+        })
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_configure_log_sinks(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let _: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/add_splunk_sink",
+                Some(json!({
+                    "hecEndpoint": "https://splunk.example.com:8088/services/collector/event",
+                    "token": "secret-token",
+                    "index": "convex",
+                })),
+            )?)
+            .await?;
+        backend
+            .expect_error(
+                admin_request(
+                    &backend,
+                    "/api/add_splunk_sink",
+                    Some(json!({ "hecEndpoint": "not a url", "token": "secret-token" })),
+                )?,
+                StatusCode::BAD_REQUEST,
+                "InvalidSplunkEndpoint",
+            )
+            .await?;
+
+        let response: JsonValue = backend
+            .expect_success(admin_request(&backend, "/api/list_log_sinks", None)?)
+            .await?;
+        let log_sinks = response["logSinks"].as_array().unwrap();
+        assert_eq!(log_sinks.len(), 1);
+        assert_eq!(log_sinks[0]["sinkType"], json!("splunk"));
+        assert_eq!(log_sinks[0]["version"], json!("2"));
+        assert!(!response.to_string().contains("secret-token"));
+        assert_eq!(log_sinks[0]["rules"]["logLevels"], json!([]));
+
+        let _: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/update_log_sink_rules",
+                Some(json!({
+                    "sinkType": "splunk",
+                    "rules": { "logLevels": ["ERROR"], "functionPaths": ["admin/*"] },
+                })),
+            )?)
+            .await?;
+        backend
+            .expect_error(
+                admin_request(
+                    &backend,
+                    "/api/update_log_sink_rules",
+                    Some(json!({ "sinkType": "splunk", "rules": { "eventTypes": ["metrics"] } })),
+                )?,
+                StatusCode::BAD_REQUEST,
+                "InvalidLogEventType",
+            )
+            .await?;
+        let response: JsonValue = backend
+            .expect_success(admin_request(&backend, "/api/list_log_sinks", None)?)
+            .await?;
+        assert_eq!(
+            response["logSinks"][0]["rules"],
+            json!({ "eventTypes": [], "functionPaths": ["admin/*"], "logLevels": ["ERROR"] })
+        );
+
+        backend
+            .expect_error(
+                admin_request(
+                    &backend,
+                    "/api/add_sentry_sink",
+                    Some(json!({ "dsn": "https://o42.ingest.sentry.io/4506" })),
+                )?,
+                StatusCode::BAD_REQUEST,
+                "InvalidSentryDsn",
+            )
+            .await?;
+
+        let _: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/delete_log_sink",
+                Some(json!({ "sinkType": "splunk" })),
+            )?)
+            .await?;
+        backend
+            .expect_error(
+                admin_request(
+                    &backend,
+                    "/api/delete_log_sink",
+                    Some(json!({ "sinkType": "splunk" })),
+                )?,
+                StatusCode::NOT_FOUND,
+                "LogSinkNotFound",
+            )
+            .await?;
+        Ok(())
+    }
+}
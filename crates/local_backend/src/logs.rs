@@ -19,11 +19,28 @@ use common::{
         ExtractClientVersion,
         HttpResponseError,
     },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
     version::ClientType,
     RequestId,
 };
 use errors::ErrorMetadata;
 use futures::FutureExt;
+use model::{
+    function_executions::types::{
+        FunctionExecutionFilter,
+        FunctionExecutionRecord,
+        FunctionExecutionStatus,
+    },
+    job_executions::types::{
+        CronExecutionSummary,
+        JobExecutionFilter,
+        JobExecutionRecord,
+        JobKind,
+    },
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -275,3 +292,275 @@ fn execution_to_json(
     };
     Ok(json)
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryFunctionExecutionsArgs {
+    function: Option<String>,
+    status: Option<String>,
+    // Milliseconds since the Unix epoch, inclusive.
+    start_ms: Option<u64>,
+    // Milliseconds since the Unix epoch, exclusive.
+    end_ms: Option<u64>,
+    min_duration_ms: Option<u64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionExecutionRecordJson {
+    timestamp: f64,
+    udf_type: String,
+    identifier: String,
+    status: String,
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_data: Option<JsonValue>,
+    execution_time: f64,
+    cached_result: bool,
+    request_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<String>,
+    database_read_bytes: u64,
+    database_write_bytes: u64,
+    storage_read_bytes: u64,
+    storage_write_bytes: u64,
+    action_memory_used_mb: Option<u64>,
+    log_lines: Vec<String>,
+}
+
+impl From<FunctionExecutionRecord> for FunctionExecutionRecordJson {
+    fn from(record: FunctionExecutionRecord) -> Self {
+        Self {
+            timestamp: record.timestamp.as_secs_f64(),
+            udf_type: record.udf_type.to_string(),
+            identifier: record.function,
+            status: record.status.to_string(),
+            error: record.error,
+            error_code: record.error_code,
+            error_data: record
+                .error_data
+                .and_then(|data| serde_json::from_str(&data).ok()),
+            execution_time: record.execution_time.as_secs_f64(),
+            cached_result: record.cached,
+            request_id: record.request_id,
+            api_key: record.api_key,
+            database_read_bytes: record.usage.database_read_bytes,
+            database_write_bytes: record.usage.database_write_bytes,
+            storage_read_bytes: record.usage.storage_read_bytes,
+            storage_write_bytes: record.usage.storage_write_bytes,
+            action_memory_used_mb: record.usage.action_memory_used_mb,
+            log_lines: record.log_lines,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryFunctionExecutionsResponse {
+    entries: Vec<FunctionExecutionRecordJson>,
+}
+
+const DEFAULT_FUNCTION_EXECUTIONS_LIMIT: usize = 100;
+const MAX_FUNCTION_EXECUTIONS_LIMIT: usize = 1000;
+
+/// Queries the function executions retained in `_function_executions`, newest
+/// first. Unlike `stream_udf_execution`, this includes executions from before
+/// the backend last restarted.
+pub async fn query_function_executions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(query_args): Query<QueryFunctionExecutionsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let status = query_args
+        .status
+        .map(|status| status.parse::<FunctionExecutionStatus>())
+        .transpose()?;
+    let limit = query_args
+        .limit
+        .unwrap_or(DEFAULT_FUNCTION_EXECUTIONS_LIMIT);
+    if limit > MAX_FUNCTION_EXECUTIONS_LIMIT {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "FunctionExecutionsLimitTooLarge",
+            format!("limit must be at most {MAX_FUNCTION_EXECUTIONS_LIMIT}, got {limit}"),
+        ))
+        .into());
+    }
+    let filter = FunctionExecutionFilter {
+        function: query_args.function,
+        status,
+        start: query_args.start_ms.map(UnixTimestamp::from_millis),
+        end: query_args.end_ms.map(UnixTimestamp::from_millis),
+        min_execution_time: query_args.min_duration_ms.map(Duration::from_millis),
+        limit,
+    };
+    let entries = st
+        .application
+        .query_function_executions(identity, filter)
+        .await?
+        .into_iter()
+        .map(FunctionExecutionRecordJson::from)
+        .collect();
+    Ok(Json(QueryFunctionExecutionsResponse { entries }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryJobExecutionsArgs {
+    // "scheduled" or "cron".
+    kind: Option<String>,
+    // A scheduled job's ID or a cron's name. Requires `kind`.
+    job: Option<String>,
+    // Milliseconds since the Unix epoch, inclusive.
+    since_ms: Option<u64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobExecutionRecordJson {
+    kind: String,
+    job: String,
+    component_path: String,
+    identifier: String,
+    start: f64,
+    end: f64,
+    execution_time: f64,
+    status: String,
+    error: Option<String>,
+    attempts: u32,
+    database_read_bytes: u64,
+    database_write_bytes: u64,
+    storage_read_bytes: u64,
+    storage_write_bytes: u64,
+    action_memory_used_mb: Option<u64>,
+}
+
+impl From<JobExecutionRecord> for JobExecutionRecordJson {
+    fn from(record: JobExecutionRecord) -> Self {
+        Self {
+            kind: record.kind.to_string(),
+            execution_time: record.duration().as_secs_f64(),
+            job: record.job,
+            component_path: record.component.into(),
+            identifier: record.udf_path.into(),
+            start: record.start.as_secs_f64(),
+            end: record.end.as_secs_f64(),
+            status: record.status.to_string(),
+            error: record.error,
+            attempts: record.attempts,
+            database_read_bytes: record.usage.database_read_bytes,
+            database_write_bytes: record.usage.database_write_bytes,
+            storage_read_bytes: record.usage.storage_read_bytes,
+            storage_write_bytes: record.usage.storage_write_bytes,
+            action_memory_used_mb: record.usage.action_memory_used_mb,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryJobExecutionsResponse {
+    entries: Vec<JobExecutionRecordJson>,
+}
+
+/// Queries the scheduled job and cron runs retained in `_job_executions`,
+/// newest first.
+pub async fn query_job_executions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(query_args): Query<QueryJobExecutionsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let kind = query_args
+        .kind
+        .map(|kind| kind.parse::<JobKind>())
+        .transpose()?;
+    let limit = query_args
+        .limit
+        .unwrap_or(DEFAULT_FUNCTION_EXECUTIONS_LIMIT);
+    if limit > MAX_FUNCTION_EXECUTIONS_LIMIT {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "JobExecutionsLimitTooLarge",
+            format!("limit must be at most {MAX_FUNCTION_EXECUTIONS_LIMIT}, got {limit}"),
+        ))
+        .into());
+    }
+    let filter = JobExecutionFilter {
+        kind,
+        job: query_args.job,
+        since: query_args.since_ms.map(UnixTimestamp::from_millis),
+        limit,
+    };
+    let entries = st
+        .application
+        .query_job_executions(identity, filter)
+        .await?
+        .into_iter()
+        .map(JobExecutionRecordJson::from)
+        .collect();
+    Ok(Json(QueryJobExecutionsResponse { entries }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeCronExecutionsArgs {
+    // Milliseconds since the Unix epoch. Defaults to a day ago.
+    since_ms: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronExecutionSummaryJson {
+    component_path: String,
+    name: String,
+    num_runs: u64,
+    num_failures: u64,
+    success_rate: f64,
+    p95_execution_time: f64,
+    last_run: f64,
+}
+
+impl From<CronExecutionSummary> for CronExecutionSummaryJson {
+    fn from(summary: CronExecutionSummary) -> Self {
+        Self {
+            success_rate: summary.success_rate(),
+            component_path: summary.component.into(),
+            name: summary.name,
+            num_runs: summary.num_runs,
+            num_failures: summary.num_failures,
+            p95_execution_time: summary.p95_duration.as_secs_f64(),
+            last_run: summary.last_run.as_secs_f64(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeCronExecutionsResponse {
+    crons: Vec<CronExecutionSummaryJson>,
+}
+
+const DEFAULT_CRON_SUMMARY_WINDOW: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Summarizes how each cron's runs went: how often it ran and failed, and how
+/// long its slowest runs took.
+pub async fn summarize_cron_executions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(query_args): Query<SummarizeCronExecutionsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let since = match query_args.since_ms {
+        Some(since_ms) => UnixTimestamp::from_millis(since_ms),
+        None => st.application.runtime().unix_timestamp() - DEFAULT_CRON_SUMMARY_WINDOW,
+    };
+    let crons = st
+        .application
+        .summarize_cron_executions(identity, since)
+        .await?
+        .into_iter()
+        .map(CronExecutionSummaryJson::from)
+        .collect();
+    Ok(Json(SummarizeCronExecutionsResponse { crons }))
+}
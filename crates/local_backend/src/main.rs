@@ -1,20 +1,28 @@
 #![feature(let_chains)]
 
 use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
     sync::Arc,
     time::Duration,
 };
 
-use anyhow::anyhow;
+use anyhow::{
+    anyhow,
+    Context,
+};
+use application::standby::StandbyReplicator;
 use clap::Parser;
 use cmd_util::env::config_service;
 use common::{
     errors::MainError,
+    grpc::ConvexGrpcService,
     http::ConvexHttpService,
     runtime::Runtime,
     version::SERVER_VERSION_STR,
 };
 use database::ShutdownSignal;
+use function_runner::process_worker::serve_function_runner_process;
 use futures::{
     future::{
         self,
@@ -23,13 +31,23 @@ use futures::{
     FutureExt,
 };
 use local_backend::{
+    admin_access::serve_tls,
     config::LocalConfig,
+    custom_domains::custom_domain_proxy,
+    deployments::{
+        multi_deployment_router,
+        validate_deployments,
+    },
+    function_runner_process_storage,
+    grpc::ConvexFunctionsService,
     make_app,
     proxy::dev_site_proxy,
     router::router,
+    settings::watch_settings_file,
     HttpActionRouteMapper,
     MAX_CONCURRENT_REQUESTS,
 };
+use pb::convex_functions::convex_functions_server::ConvexFunctionsServer;
 use runtime::prod::ProdRuntime;
 use sqlite::SqlitePersistence;
 use tokio::signal::{
@@ -47,6 +65,15 @@ fn main() -> Result<(), MainError> {
     let tokio = ProdRuntime::init_tokio()?;
     let runtime = ProdRuntime::new(&tokio);
 
+    if let Some(backend_url) = config.function_runner_backend_url.clone() {
+        let runtime_ = runtime.clone();
+        let process_future = async {
+            run_function_runner_process(runtime_, config, backend_url).await?;
+            Ok(())
+        };
+        return runtime.block_on("function_runner_process", process_future);
+    }
+
     let runtime_ = runtime.clone();
     let server_future = async {
         run_server(runtime_, config).await?;
@@ -56,6 +83,23 @@ fn main() -> Result<(), MainError> {
     runtime.block_on("main", server_future)
 }
 
+async fn run_function_runner_process(
+    runtime: ProdRuntime,
+    config: LocalConfig,
+    backend_url: String,
+) -> anyhow::Result<()> {
+    let storage = function_runner_process_storage(&runtime, &config)?;
+    serve_function_runner_process(
+        runtime,
+        storage,
+        config.name(),
+        config.secret()?,
+        config.convex_origin_url(),
+        backend_url,
+    )
+    .await
+}
+
 async fn run_server(runtime: ProdRuntime, config: LocalConfig) -> anyhow::Result<()> {
     let serve_future = async move { run_server_inner(runtime, config).await }.fuse();
     futures::pin_mut!(serve_future);
@@ -75,16 +119,67 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
     let (preempt_tx, mut preempt_rx) = async_broadcast::broadcast(1);
     // Use to signal to the http service to stop.
     let (shutdown_tx, shutdown_rx) = async_broadcast::broadcast(1);
-    let persistence = SqlitePersistence::new(&config.db_spec, false)?;
-    let st = make_app(
-        runtime.clone(),
-        config.clone(),
-        Arc::new(persistence),
-        shutdown_rx.clone(),
-        ShutdownSignal::new(preempt_tx.clone()),
-    )
-    .await?;
-    let router = router(st.clone()).await;
+    if let Some(primary_url) = config.replicate_from.clone() {
+        let replicator = StandbyReplicator::new(
+            runtime.clone(),
+            Arc::new(SqlitePersistence::new(&config.db_spec, false)?),
+            primary_url,
+            config
+                .replication_admin_key
+                .clone()
+                .context("--replication-admin-key is required")?,
+            config.promote_after(),
+        )
+        .await?;
+        futures::select! {
+            r = replicator.run().fuse() => r?,
+            r = signal::ctrl_c().fuse() => {
+                tracing::info!("Received Ctrl-C signal while replicating!");
+                r?;
+                return Ok(());
+            },
+        }
+        // The standby's persistence is reopened below, which loads it like any
+        // other restart.
+        tracing::info!("Promoting standby to primary");
+    }
+    let (router, apps) = if config.deployments.is_empty() {
+        let persistence = SqlitePersistence::new(&config.db_spec, false)?;
+        let st = make_app(
+            runtime.clone(),
+            config.clone(),
+            Arc::new(persistence),
+            shutdown_rx.clone(),
+            ShutdownSignal::new(preempt_tx.clone()),
+        )
+        .await?;
+        (router(st.clone()).await, vec![st])
+    } else {
+        validate_deployments(&config.deployments)?;
+        let mut routers = BTreeMap::new();
+        let mut apps = vec![];
+        for deployment in &config.deployments {
+            let deployment_config = config.for_deployment(deployment);
+            tracing::info!(
+                "Starting deployment {} at {}",
+                deployment.name,
+                deployment_config.convex_origin_url()
+            );
+            let persistence = SqlitePersistence::new(&deployment_config.db_spec, false)?;
+            let st = make_app(
+                runtime.clone(),
+                deployment_config,
+                Arc::new(persistence),
+                shutdown_rx.clone(),
+                ShutdownSignal::new(preempt_tx.clone()),
+            )
+            .await
+            .with_context(|| format!("Failed to start deployment {}", deployment.name))?;
+            routers.insert(deployment.name.clone(), router(st.clone()).await);
+            apps.push(st);
+        }
+        (multi_deployment_router(routers), apps)
+    };
     let mut shutdown_rx_ = shutdown_rx.clone();
     let http_service = ConvexHttpService::new(
         router,
@@ -93,24 +188,99 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
         Duration::from_secs(125),
         HttpActionRouteMapper,
     );
-    let serve_http_future = http_service.serve(config.http_bind_address().into(), async move {
-        let _ = shutdown_rx_.recv().await;
-    });
+    let tls_config = config.tls_config();
+    let http_bind_address = SocketAddr::from(config.http_bind_address());
+    let serve_http_future = async move {
+        match tls_config {
+            Some(tls_config) => {
+                serve_tls(
+                    http_service.into_router(),
+                    http_bind_address,
+                    tls_config,
+                    shutdown_rx_,
+                )
+                .await
+            },
+            None => {
+                http_service
+                    .serve(http_bind_address, async move {
+                        let _ = shutdown_rx_.recv().await;
+                    })
+                    .await
+            },
+        }
+    };
     let proxy_future = dev_site_proxy(
         config.site_bind_address(),
         config.convex_origin_url(),
-        shutdown_rx,
+        config.acme_challenge_dir.clone(),
+        shutdown_rx.clone(),
+    );
+    let custom_domain_future = custom_domain_proxy(
+        config.custom_domain_bind_address(),
+        config.custom_domains.clone(),
+        config.convex_origin_url(),
+        shutdown_rx.clone(),
+    );
+    // `--grpc-port` can't be combined with `--deployment`, so there's a single
+    // application to serve.
+    let grpc_service = ConvexFunctionsService::new(
+        Arc::new(apps[0].application.clone()),
+        config.convex_origin_url().to_string(),
     );
+    let grpc_bind_address = config.grpc_bind_address();
+    let mut shutdown_rx_ = shutdown_rx.clone();
+    let grpc_future = async move {
+        let Some(grpc_bind_address) = grpc_bind_address else {
+            return Ok(());
+        };
+        ConvexGrpcService::new()
+            .add_service(ConvexFunctionsServer::new(grpc_service))
+            .serve(grpc_bind_address.into(), async move {
+                let _ = shutdown_rx_.recv().await;
+            })
+            .await
+    };
 
-    let serve_future = future::try_join(serve_http_future, proxy_future).fuse();
+    // The settings file applies to every deployment.
+    let mut settings_watchers = vec![];
+    if let Some(settings_file) = &config.settings_file {
+        for st in &apps {
+            settings_watchers.push(watch_settings_file(
+                st.clone(),
+                settings_file.clone(),
+                shutdown_rx.clone(),
+            ));
+        }
+    }
+    let settings_future = future::try_join_all(settings_watchers);
+
+    let serve_future = future::try_join5(
+        serve_http_future,
+        proxy_future,
+        custom_domain_future,
+        grpc_future,
+        settings_future,
+    )
+    .fuse();
     futures::pin_mut!(serve_future);
 
     let preempt_future = async move { preempt_rx.recv().await }.fuse();
     futures::pin_mut!(preempt_future);
 
+    // Draining any deployment drains the whole backend, since it's about to
+    // exit.
+    let drain_future = future::select_all(apps.iter().map(|st| {
+        let drain = st.drain.clone();
+        async move { drain.started().await }.boxed()
+    }))
+    .fuse();
+    futures::pin_mut!(drain_future);
+
     // Start shutdown when we get a manual shutdown signal or with the first
     // ctrl-c.
     let mut force_exit_duration = None;
+    let mut drain_timeout = None;
     futures::select! {
         r = serve_future => {
             r?;
@@ -127,16 +297,43 @@ async fn run_server_inner(runtime: ProdRuntime, config: LocalConfig) -> anyhow::
             r?;
             let _: Result<_, _> = shutdown_tx.broadcast(()).await;
         },
+        (timeout, ..) = drain_future => {
+            tracing::info!("Draining before shutting down");
+            drain_timeout = Some(timeout);
+        },
     }
 
     let shutdown = async move {
+        if let Some(timeout) = drain_timeout {
+            for st in &apps {
+                st.drain.start(timeout);
+            }
+            // Keep serving while functions and scheduled jobs finish, since
+            // some are running for requests that are still in flight.
+            let drain_apps =
+                future::try_join_all(apps.iter().map(|st| st.application.drain(timeout))).fuse();
+            futures::pin_mut!(drain_apps);
+            futures::select! {
+                r = serve_future => {
+                    r?;
+                    panic!("Serve future stopped unexpectedly!")
+                },
+                r = drain_apps => {
+                    r?;
+                },
+            }
+            let _: Result<_, _> = shutdown_tx.broadcast(()).await;
+        }
+
         // First, drain all in-progress requests;
         tracing::info!("Shutdown initiated, draining existing requests...");
         serve_future.await?;
 
         // Next, shutdown all of our asynchronous workers.
         tracing::info!("Shutting down application...");
-        st.shutdown().await?;
+        for st in apps {
+            st.shutdown().await?;
+        }
 
         Ok::<_, anyhow::Error>(())
     }
@@ -0,0 +1,96 @@
+//! Prometheus metrics for self-hosted deployments. Everything recorded in the
+//! backend's metrics registry, e.g. function latencies, commit rates,
+//! subscription counts and isolate pool stats, is served from `/metrics` in
+//! the Prometheus exposition format.
+//!
+//! The endpoint is only enabled when the backend is started with
+//! `--metrics-token`, and scrapers must send that token as
+//! `Authorization: Bearer <token>`.
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        metrics,
+        HttpResponseError,
+    },
+    sha256::Sha256,
+};
+use errors::ErrorMetadata;
+use http::{
+    header::AUTHORIZATION,
+    HeaderMap,
+};
+
+use crate::LocalAppState;
+
+pub async fn get_metrics(
+    State(st): State<LocalAppState>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let Some(metrics_token) = &st.metrics_token else {
+        return Err(anyhow::anyhow!(ErrorMetadata::not_found(
+            "MetricsDisabled",
+            "Metrics are disabled. Start the backend with --metrics-token to enable them.",
+        ))
+        .into());
+    };
+    let authorization = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    check_metrics_token(metrics_token, authorization)?;
+    metrics().await
+}
+
+fn check_metrics_token(metrics_token: &str, authorization: Option<&str>) -> anyhow::Result<()> {
+    let token = authorization.and_then(|value| value.strip_prefix("Bearer "));
+    // Compare digests so that the time taken doesn't depend on how much of the
+    // token was guessed correctly.
+    if !token.is_some_and(|token| {
+        Sha256::hash(token.as_bytes()) == Sha256::hash(metrics_token.as_bytes())
+    }) {
+        anyhow::bail!(ErrorMetadata::unauthenticated(
+            "InvalidMetricsToken",
+            "Metrics require the token the backend was started with, sent as `Authorization: \
+             Bearer <token>`",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+
+    use super::check_metrics_token;
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[test]
+    fn test_check_metrics_token() {
+        assert!(check_metrics_token("s3cret", Some("Bearer s3cret")).is_ok());
+        assert!(check_metrics_token("s3cret", Some("Bearer s3cre")).is_err());
+        assert!(check_metrics_token("s3cret", Some("s3cret")).is_err());
+        assert!(check_metrics_token("s3cret", None).is_err());
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_metrics_disabled_without_token(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = Request::builder()
+            .uri("/metrics")
+            .method("GET")
+            .header("Authorization", "Bearer s3cret")
+            .body(Body::empty())?;
+        backend
+            .expect_error(req, StatusCode::NOT_FOUND, "MetricsDisabled")
+            .await?;
+        Ok(())
+    }
+}
@@ -1,6 +1,13 @@
 use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     str::FromStr,
-    time::SystemTime,
+    time::{
+        Duration,
+        SystemTime,
+    },
 };
 
 use anyhow::Context;
@@ -24,6 +31,7 @@ use common::{
     execution_context::{
         ExecutionContext,
         ExecutionId,
+        TraceParent,
     },
     http::{
         extract::Json,
@@ -49,9 +57,15 @@ use isolate::{
     ActionCallbacks,
     UdfArgsJson,
 };
-use keybroker::Identity;
+use keybroker::{
+    Identity,
+    UploadConstraints,
+};
 use minitrace::future::FutureExt;
-use model::file_storage::types::FileStorageEntry;
+use model::{
+    file_storage::types::FileStorageEntry,
+    scheduled_jobs::types::ScheduleOptions,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -102,7 +116,7 @@ pub async fn internal_query_post(
     let udf_return = st
         .application
         .read_only_udf(
-            context.request_id,
+            context.request_id.clone(),
             ComponentFunctionPath {
                 component: ComponentPath::root(),
                 udf_path,
@@ -111,6 +125,7 @@ pub async fn internal_query_post(
             identity,
             FunctionCaller::Action {
                 parent_scheduled_job: context.parent_scheduled_job,
+                trace_parent: Some(context.traceparent()),
             },
         )
         .await?;
@@ -147,7 +162,7 @@ pub async fn internal_mutation_post(
     let udf_result = st
         .application
         .mutation_udf(
-            context.request_id,
+            context.request_id.clone(),
             ComponentFunctionPath {
                 component: ComponentPath::root(),
                 udf_path,
@@ -157,6 +172,7 @@ pub async fn internal_mutation_post(
             None,
             FunctionCaller::Action {
                 parent_scheduled_job: context.parent_scheduled_job,
+                trace_parent: Some(context.traceparent()),
             },
             PauseClient::new(),
         )
@@ -197,7 +213,7 @@ pub async fn internal_action_post(
     let udf_result = st
         .application
         .action_udf(
-            context.request_id,
+            context.request_id.clone(),
             ComponentFunctionPath {
                 component: ComponentPath::root(),
                 udf_path,
@@ -206,6 +222,7 @@ pub async fn internal_action_post(
             identity,
             FunctionCaller::Action {
                 parent_scheduled_job: context.parent_scheduled_job,
+                trace_parent: Some(context.traceparent()),
             },
         )
         .await?;
@@ -234,6 +251,10 @@ pub struct ScheduleJobRequest {
     udf_path: String,
     udf_args: UdfArgsJson,
     scheduled_ts: f64,
+    #[serde(default)]
+    priority: Option<String>,
+    #[serde(default)]
+    max_concurrency: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -255,6 +276,18 @@ pub async fn schedule_job(
         anyhow::anyhow!(ErrorMetadata::bad_request("InvalidUdfPath", e.to_string()))
     })?;
     let udf_args = req.udf_args.into_arg_vec();
+    let priority = req
+        .priority
+        .map(|priority| priority.parse())
+        .transpose()
+        .map_err(|e: anyhow::Error| {
+            anyhow::anyhow!(ErrorMetadata::bad_request("InvalidPriority", e.to_string()))
+        })?
+        .unwrap_or_default();
+    let options = ScheduleOptions {
+        priority,
+        max_concurrency: req.max_concurrency,
+    };
     let job_id = st
         .application
         .runner()
@@ -266,6 +299,7 @@ pub async fn schedule_job(
             },
             udf_args,
             scheduled_ts,
+            options,
             context,
         )
         .await?;
@@ -297,6 +331,27 @@ pub async fn cancel_developer_job(
     Ok(Json(json!(null)))
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultChunkRequest {
+    pub index: usize,
+    pub chunk: String,
+}
+
+/// Receives a chunk of an action's result when it's too large to be returned
+/// in the Node executor's response.
+#[debug_handler]
+pub async fn result_chunk(
+    State(st): State<LocalAppState>,
+    ExtractExecutionContext(context): ExtractExecutionContext,
+    Json(ResultChunkRequest { index, chunk }): Json<ResultChunkRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    st.application
+        .runner()
+        .append_action_result_chunk(&context.execution_id, index, chunk)?;
+    Ok(Json(json!(null)))
+}
+
 #[debug_handler]
 pub async fn vector_search(
     State(st): State<LocalAppState>,
@@ -336,11 +391,44 @@ pub async fn vector_search(
     Ok(Json(json!({ "results": results })))
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateUploadUrlRequest {
+    /// Whether to generate a URL for starting a resumable upload session
+    /// instead of uploading the file in one request.
+    #[serde(default)]
+    pub resumable: bool,
+    /// Constraints on the uploaded file, see `UploadConstraints`.
+    pub max_bytes: Option<u64>,
+    #[serde(default)]
+    pub allowed_content_types: Vec<String>,
+    pub expires_in_ms: Option<u64>,
+}
+
 #[debug_handler]
 pub async fn storage_generate_upload_url(
     State(st): State<LocalAppState>,
+    Json(GenerateUploadUrlRequest {
+        resumable,
+        max_bytes,
+        allowed_content_types,
+        expires_in_ms,
+    }): Json<GenerateUploadUrlRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    let url = st.application.storage_generate_upload_url().await?;
+    let constraints = UploadConstraints {
+        max_bytes,
+        allowed_content_types,
+        expires_in: expires_in_ms.map(Duration::from_millis),
+    };
+    let url = if resumable {
+        st.application
+            .storage_generate_resumable_upload_url(constraints)
+            .await?
+    } else {
+        st.application
+            .storage_generate_upload_url(constraints)
+            .await?
+    };
     Ok(Json(json!({ "url": url })))
 }
 
@@ -380,6 +468,8 @@ pub async fn storage_get_metadata(
         sha256: String,
         size: i64,
         content_type: Option<String>,
+        tags: BTreeSet<String>,
+        metadata: BTreeMap<String, String>,
     }
 
     let file_metadata = st
@@ -394,6 +484,10 @@ pub async fn storage_get_metadata(
                  sha256,
                  size,
                  content_type,
+                 tags,
+                 metadata,
+                 storage_class: _,
+                 scan_status: _,
              }| {
                 FileMetadataJson {
                     storage_id: storage_id.to_string(),
@@ -401,6 +495,8 @@ pub async fn storage_get_metadata(
                     sha256: sha256.as_hex(),
                     size,
                     content_type,
+                    tags,
+                    metadata,
                 }
             },
         );
@@ -422,6 +518,29 @@ pub async fn storage_delete(
     Ok(Json(json!(null)))
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetMetadataParams {
+    storage_id: String,
+    tags: Option<BTreeSet<String>>,
+    metadata: Option<BTreeMap<String, String>>,
+}
+
+#[debug_handler]
+pub async fn storage_set_metadata(
+    State(st): State<LocalAppState>,
+    ExtractActionIdentity(identity): ExtractActionIdentity,
+    Json(req): Json<SetMetadataParams>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let storage_id = req.storage_id.parse()?;
+    let component = ComponentId::TODO();
+    st.application
+        .runner()
+        .storage_set_metadata(identity, component, storage_id, req.tags, req.metadata)
+        .await?;
+    Ok(Json(json!(null)))
+}
+
 pub static CONVEX_ACTIONS_CALLBACK_TOKEN: &str = "Convex-Action-Callback-Token";
 
 async fn check_actions_token(
@@ -559,12 +678,16 @@ impl<T> FromRequestParts<T> for ExtractExecutionContext {
             .map(|s| s.parse())
             .transpose()
             .context("Invalid scheduled job id")?;
+        // This is the action's own `traceparent`, rather than its caller's, but
+        // `ExecutionContext::traceparent` only uses its trace id and flags.
+        let trace_parent = TraceParent::from_headers(&parts.headers);
 
         Ok(Self(ExecutionContext::new_from_parts(
             request_id,
             execution_id,
             parent_job_id,
             is_root,
+            trace_parent,
         )))
     }
 }
@@ -0,0 +1,384 @@
+//! OpenAPI description of the deployment's public functions and HTTP actions,
+//! for generating typed clients.
+//!
+//! The function endpoints take the function's path in the request body, so
+//! each of `/api/query`, `/api/mutation` and `/api/action` is one operation
+//! whose request body is a union of per-function schemas discriminated by
+//! `path`. Arguments use the encoding the endpoints accept, and results use the
+//! `json` format, which requests must ask for.
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentId,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    schemas::validator::AddTopLevelFields,
+    types::{
+        HttpActionRoute,
+        UdfType,
+    },
+};
+use keybroker::Identity;
+use model::modules::{
+    function_validators::{
+        ArgsValidator,
+        ReturnsValidator,
+    },
+    module_versions::{
+        AnalyzedFunction,
+        Visibility,
+    },
+    ModuleModel,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use sync_types::CanonicalizedUdfPath;
+use value::export::ValueFormat;
+
+use crate::LocalAppState;
+
+pub async fn openapi_spec_get(
+    State(st): State<LocalAppState>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    Ok(Json(load_spec(&st).await?))
+}
+
+async fn load_spec(st: &LocalAppState) -> anyhow::Result<JsonValue> {
+    let mut tx = st.application.begin(Identity::system()).await?;
+    let modules = ModuleModel::new(&mut tx)
+        .get_application_metadata(ComponentId::TODO())
+        .await?;
+    let mut functions = vec![];
+    let mut http_routes = vec![];
+    for module in modules {
+        let module = module.into_value();
+        let Some(analyze_result) = module.analyze_result else {
+            continue;
+        };
+        for function in analyze_result.functions.into_iter() {
+            functions.push((
+                CanonicalizedUdfPath::new(module.path.clone(), function.name.clone()),
+                function,
+            ));
+        }
+        if let Some(routes) = analyze_result.http_routes {
+            http_routes.extend(routes.into_iter().map(|route| route.route));
+        }
+    }
+    Ok(build_spec(
+        &st.instance_name,
+        st.origin.as_str(),
+        st.site_origin.as_str(),
+        functions,
+        http_routes,
+    ))
+}
+
+/// A public function's request and result schemas.
+struct FunctionSpec {
+    /// Name of the function's schemas under `components`.
+    name: String,
+    path: String,
+    request: JsonValue,
+    result: JsonValue,
+}
+
+impl FunctionSpec {
+    fn new(path: CanonicalizedUdfPath, function: AnalyzedFunction) -> Self {
+        let path = String::from(path.strip());
+        let name = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let args = match function.args {
+            ArgsValidator::Unvalidated => json!({ "type": "object" }),
+            ArgsValidator::Validated(validator) => {
+                validator.to_json_schema(AddTopLevelFields::False, ValueFormat::ConvexEncodedJSON)
+            },
+        };
+        let request = json!({
+            "type": "object",
+            "properties": {
+                "path": { "const": path },
+                "args": args,
+                "format": { "const": "json" },
+            },
+            "required": ["path", "args", "format"],
+        });
+        let result = match function.returns {
+            ReturnsValidator::Unvalidated => json!({}),
+            ReturnsValidator::Validated(validator) => {
+                validator.to_json_schema(ValueFormat::ConvexCleanJSON)
+            },
+        };
+        Self {
+            name,
+            path,
+            request,
+            result,
+        }
+    }
+}
+
+/// The operation for one of the function endpoints, or `None` if there are
+/// no functions of its type.
+fn function_operation(
+    operation_id: &str,
+    functions: &[FunctionSpec],
+    schemas: &mut serde_json::Map<String, JsonValue>,
+) -> Option<JsonValue> {
+    if functions.is_empty() {
+        return None;
+    }
+    let mut requests = vec![];
+    let mut results = vec![];
+    let mut mapping = serde_json::Map::new();
+    for function in functions {
+        let request_name = format!("{}_request", function.name);
+        let result_name = format!("{}_result", function.name);
+        schemas.insert(request_name.clone(), function.request.clone());
+        schemas.insert(result_name.clone(), function.result.clone());
+        let request_ref = format!("#/components/schemas/{request_name}");
+        mapping.insert(function.path.clone(), json!(request_ref));
+        requests.push(json!({ "$ref": request_ref }));
+        results.push(json!({ "$ref": format!("#/components/schemas/{result_name}") }));
+    }
+    Some(json!({
+        "post": {
+            "operationId": operation_id,
+            "requestBody": {
+                "required": true,
+                "content": {
+                    "application/json": {
+                        "schema": {
+                            "oneOf": requests,
+                            "discriminator": {
+                                "propertyName": "path",
+                                "mapping": mapping,
+                            },
+                        },
+                    },
+                },
+            },
+            "responses": {
+                "200": {
+                    "description": "The function's result, or the error it threw",
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "oneOf": [
+                                    {
+                                        "type": "object",
+                                        "properties": {
+                                            "status": { "const": "success" },
+                                            "value": { "anyOf": results },
+                                            "logLines": { "$ref": "#/components/schemas/LogLines" },
+                                        },
+                                        "required": ["status", "value"],
+                                    },
+                                    { "$ref": "#/components/schemas/FunctionError" },
+                                ],
+                            },
+                        },
+                    },
+                },
+                "default": {
+                    "description": "The request failed",
+                },
+            },
+        },
+    }))
+}
+
+/// Convert an HTTP action route into an OpenAPI path, returning the names of
+/// its path parameters. `:name` segments and the wildcard of a path prefix
+/// route become parameters, though a parameter for a wildcard will only match
+/// a single segment.
+fn http_route_path(path: &str) -> (String, Vec<String>) {
+    let mut parameters = vec![];
+    let path = path
+        .split('/')
+        .map(|segment| {
+            match segment
+                .strip_prefix(':')
+                .or_else(|| segment.strip_prefix('*'))
+            {
+                Some(name) => {
+                    let name = if name.is_empty() { "rest" } else { name };
+                    parameters.push(name.to_string());
+                    format!("{{{name}}}")
+                },
+                None => segment.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+    (path, parameters)
+}
+
+/// Generate the OpenAPI document for a deployment's functions and HTTP
+/// actions.
+fn build_spec(
+    instance_name: &str,
+    origin: &str,
+    site_origin: &str,
+    functions: impl IntoIterator<Item = (CanonicalizedUdfPath, AnalyzedFunction)>,
+    http_routes: impl IntoIterator<Item = HttpActionRoute>,
+) -> JsonValue {
+    let (mut queries, mut mutations, mut actions) = (vec![], vec![], vec![]);
+    for (path, function) in functions {
+        if function.visibility != Some(Visibility::Public) {
+            continue;
+        }
+        match function.udf_type {
+            UdfType::Query => queries.push(FunctionSpec::new(path, function)),
+            UdfType::Mutation => mutations.push(FunctionSpec::new(path, function)),
+            UdfType::Action => actions.push(FunctionSpec::new(path, function)),
+            UdfType::HttpAction => (),
+        }
+    }
+
+    let mut schemas = serde_json::Map::new();
+    schemas.insert(
+        "LogLines".to_string(),
+        json!({ "type": "array", "items": { "type": "string" } }),
+    );
+    schemas.insert(
+        "FunctionError".to_string(),
+        json!({
+            "type": "object",
+            "properties": {
+                "status": { "const": "error" },
+                "errorMessage": { "type": "string" },
+                "errorData": {},
+                "logLines": { "$ref": "#/components/schemas/LogLines" },
+            },
+            "required": ["status", "errorMessage"],
+        }),
+    );
+    let mut paths = serde_json::Map::new();
+    for (endpoint, operation_id, functions) in [
+        ("/api/query", "query", &queries),
+        ("/api/mutation", "mutation", &mutations),
+        ("/api/action", "action", &actions),
+    ] {
+        if let Some(operation) = function_operation(operation_id, functions, &mut schemas) {
+            paths.insert(endpoint.to_string(), operation);
+        }
+    }
+
+    for route in http_routes {
+        let (path, parameters) = http_route_path(&route.path);
+        let method = route.method.to_string();
+        let operation_id: String = format!("{method}{}", route.path)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let parameters: Vec<_> = parameters
+            .into_iter()
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect();
+        let path_item = paths
+            .entry(path)
+            .or_insert_with(|| json!({ "servers": [{ "url": site_origin }] }));
+        path_item[method.to_lowercase()] = json!({
+            "operationId": operation_id,
+            "summary": format!("HTTP action {route}"),
+            "parameters": parameters,
+            "responses": {
+                "default": {
+                    "description": "The HTTP action's response",
+                },
+            },
+        });
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": format!("Convex deployment {instance_name}"),
+            "version": "1",
+        },
+        "servers": [{ "url": origin }],
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "bearerAuth": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "A JWT from the deployment's auth provider",
+                },
+            },
+        },
+        "security": [{}, { "bearerAuth": [] }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use application::test_helpers::ApplicationTestExt;
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use super::{
+        http_route_path,
+        load_spec,
+    };
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_openapi_spec(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend.st.application.load_udf_tests_modules().await?;
+        let spec = load_spec(&backend.st).await?;
+
+        let mapping = &spec["paths"]["/api/query"]["post"]["requestBody"]["content"]
+            ["application/json"]["schema"]["discriminator"]["mapping"];
+        assert_eq!(
+            mapping["sync:accountBalance"],
+            json!("#/components/schemas/sync_accountBalance_request")
+        );
+        // Internal functions aren't part of the spec.
+        assert!(mapping.get("internal:myInternalQuery").is_none());
+
+        let schemas = &spec["components"]["schemas"];
+        assert_eq!(
+            schemas["returns_validation_stringOutputReturnsNumberMutation_result"],
+            json!({ "type": "string" })
+        );
+        assert_eq!(
+            schemas["returns_validation_extraOutputFields_request"]["properties"]["args"]
+                ["properties"],
+            json!({})
+        );
+        assert!(spec["paths"]["/api/action"]["post"].is_object());
+        Ok(())
+    }
+
+    #[test]
+    fn test_http_route_path() {
+        assert_eq!(http_route_path("/inline"), ("/inline".to_string(), vec![]));
+        assert_eq!(
+            http_route_path("/users/:id/posts/*"),
+            (
+                "/users/{id}/posts/{rest}".to_string(),
+                vec!["id".to_string(), "rest".to_string()]
+            )
+        );
+    }
+}
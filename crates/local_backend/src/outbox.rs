@@ -0,0 +1,279 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::{
+        Json,
+        Query,
+    },
+    HttpResponseError,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    outbox::{
+        types::{
+            OutboxHeader,
+            OutboxRecordState,
+            OutboxRecordStatus,
+            OutboxTarget,
+            OutboxTargetConfig,
+        },
+        OutboxModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use value::id_v6::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+const DEFAULT_LIST_LIMIT: usize = 100;
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OutboxTargetConfigRequest {
+    Http {
+        url: String,
+        #[serde(default)]
+        headers: Vec<OutboxHeader>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Kafka {
+        /// e.g. "https://kafka-rest.example.com:8082".
+        rest_proxy_url: String,
+        topic: String,
+        #[serde(default)]
+        headers: Vec<OutboxHeader>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetOutboxTargetRequest {
+    name: String,
+    config: OutboxTargetConfigRequest,
+}
+
+/// Creates or replaces a target that mutations can enqueue outbox records
+/// for with `outbox.enqueue`.
+pub async fn set_outbox_target(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetOutboxTargetRequest { name, config }): Json<SetOutboxTargetRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let config = match config {
+        OutboxTargetConfigRequest::Http { url, headers } => {
+            OutboxTargetConfig::Http { url, headers }
+        },
+        OutboxTargetConfigRequest::Kafka {
+            rest_proxy_url,
+            topic,
+            headers,
+        } => OutboxTargetConfig::Kafka {
+            rest_proxy_url,
+            topic,
+            headers,
+        },
+    };
+    let event = DeploymentAuditLogEvent::SetOutboxTarget {
+        name: name.clone(),
+        target_type: config.target_type().to_string(),
+    };
+    let mut tx = st.application.begin(identity).await?;
+    OutboxModel::new(&mut tx)
+        .set_target(OutboxTarget { name, config })
+        .await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "set_outbox_target")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteOutboxTargetRequest {
+    name: String,
+}
+
+/// Deletes a target. Records that haven't been delivered to it yet fail.
+pub async fn delete_outbox_target(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteOutboxTargetRequest { name }): Json<DeleteOutboxTargetRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    OutboxModel::new(&mut tx).delete_target(&name).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteOutboxTarget { name }],
+            "delete_outbox_target",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxTargetJson {
+    name: String,
+    r#type: &'static str,
+    url: String,
+    topic: Option<String>,
+    // Header values are never returned since they may contain credentials.
+    header_names: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListOutboxTargetsResponse {
+    targets: Vec<OutboxTargetJson>,
+}
+
+pub async fn list_outbox_targets(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let targets = OutboxModel::new(&mut tx)
+        .targets()
+        .await?
+        .into_iter()
+        .map(|target| {
+            let OutboxTarget { name, config } = target.into_value();
+            let r#type = config.target_type();
+            let header_names = config
+                .headers()
+                .iter()
+                .map(|header| header.name.clone())
+                .collect();
+            let (url, topic) = match config {
+                OutboxTargetConfig::Http { url, .. } => (url, None),
+                OutboxTargetConfig::Kafka {
+                    rest_proxy_url,
+                    topic,
+                    ..
+                } => (rest_proxy_url, Some(topic)),
+            };
+            OutboxTargetJson {
+                name,
+                r#type,
+                url,
+                topic,
+                header_names,
+            }
+        })
+        .collect();
+    Ok(Json(ListOutboxTargetsResponse { targets }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListOutboxRecordsArgs {
+    /// "pending", "delivered" or "failed".
+    state: String,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxRecordJson {
+    id: String,
+    target: String,
+    payload: JsonValue,
+    enqueued_ts: i64,
+    next_attempt_ts: Option<i64>,
+    attempts: u32,
+    state: String,
+    delivered_ts: Option<i64>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListOutboxRecordsResponse {
+    records: Vec<OutboxRecordJson>,
+}
+
+/// The oldest records in a state, e.g. to find the records that failed.
+pub async fn list_outbox_records(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ListOutboxRecordsArgs { state, limit }): Query<ListOutboxRecordsArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let status: OutboxRecordStatus = state.parse().map_err(|_| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidOutboxRecordState",
+            format!("{state:?} isn't one of \"pending\", \"delivered\" or \"failed\""),
+        ))
+    })?;
+    let mut tx = st.application.begin(identity).await?;
+    let records = OutboxModel::new(&mut tx)
+        .list(status, limit.unwrap_or(DEFAULT_LIST_LIMIT))
+        .await?
+        .into_iter()
+        .map(|record| {
+            let (id, record) = record.into_id_and_value();
+            let state = record.state.status().to_string();
+            let (delivered_ts, error) = match record.state {
+                OutboxRecordState::Pending { last_error } => (None, last_error),
+                OutboxRecordState::Delivered { delivered_ts } => (Some(delivered_ts.into()), None),
+                OutboxRecordState::Failed { error } => (None, Some(error)),
+            };
+            OutboxRecordJson {
+                id: DeveloperDocumentId::from(id).encode(),
+                target: record.target,
+                payload: record.payload.into(),
+                enqueued_ts: record.enqueued_ts.into(),
+                next_attempt_ts: record.next_attempt_ts.map(|ts| ts.into()),
+                attempts: record.attempts,
+                state,
+                delivered_ts,
+                error,
+            }
+        })
+        .collect();
+    Ok(Json(ListOutboxRecordsResponse { records }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryOutboxRecordRequest {
+    id: String,
+}
+
+/// Delivers a failed record again, e.g. after fixing its target.
+pub async fn retry_outbox_record(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RetryOutboxRecordRequest { id }): Json<RetryOutboxRecordRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let id = DeveloperDocumentId::decode(&id).map_err(|e| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidOutboxRecordId",
+            e.to_string()
+        ))
+    })?;
+    let mut tx = st.application.begin(identity).await?;
+    OutboxModel::new(&mut tx).retry(id).await?;
+    st.application.commit(tx, "retry_outbox_record").await?;
+    Ok(StatusCode::OK)
+}
@@ -1,5 +1,6 @@
 use std::{
     net::SocketAddr,
+    path::PathBuf,
     time::Duration,
 };
 
@@ -17,13 +18,38 @@ use common::{
     },
     types::ConvexOrigin,
 };
-use http::Request;
+use http::{
+    Request,
+    Response,
+    Version,
+};
 use hyper::Body;
 
+use crate::custom_domains::acme_challenge_routes;
+
+/// Forwards a request to the HTTP actions of the webserver at `origin`.
+pub(crate) async fn proxy_to_http_actions(
+    origin: &ConvexOrigin,
+    mut request: Request<Body>,
+) -> anyhow::Result<Response<Body>> {
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path_and_query| path_and_query.as_str());
+    let new_uri = format!("{}/http{}", origin, path_and_query);
+    *request.uri_mut() = new_uri.parse()?;
+    // The client only speaks HTTP/1 to the webserver, even if the request came
+    // in over HTTP/2.
+    *request.version_mut() = Version::HTTP_11;
+    let resp = hyper::Client::new().request(request).await?;
+    Ok(resp)
+}
+
 /// Routes HTTP actions to the main webserver
 pub async fn dev_site_proxy(
     site_bind_addr: Option<([u8; 4], u16)>,
     origin: ConvexOrigin,
+    acme_challenge_dir: Option<PathBuf>,
     mut shutdown_rx: async_broadcast::Receiver<()>,
 ) -> anyhow::Result<()> {
     let Some(addr) = site_bind_addr else {
@@ -33,14 +59,9 @@ pub async fn dev_site_proxy(
 
     async fn proxy_method(
         State(st): State<ConvexOrigin>,
-        mut request: Request<Body>,
+        request: Request<Body>,
     ) -> Result<impl IntoResponse, HttpResponseError> {
-        let new_uri = format!("{}/http{}", st, request.uri());
-        *request.uri_mut() = new_uri.parse().map_err(anyhow::Error::new)?;
-        let resp = hyper::Client::new()
-            .request(request)
-            .await
-            .map_err(anyhow::Error::new)?;
+        let resp = proxy_to_http_actions(&st, request).await?;
         Ok(resp)
     }
 
@@ -50,10 +71,13 @@ pub async fn dev_site_proxy(
         .patch(proxy_method)
         .put(proxy_method)
         .options(proxy_method);
-    let router = Router::new()
+    let mut router = Router::new()
         .route("/*rest", proxy_handler.clone())
         .route("/", proxy_handler)
         .with_state(origin);
+    if let Some(dir) = acme_challenge_dir {
+        router = acme_challenge_routes(dir).merge(router);
+    }
 
     let service = ConvexHttpService::new(
         Router::new().fallback_service(router),
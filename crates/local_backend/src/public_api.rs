@@ -6,8 +6,10 @@ use application::{
         RedactedLogLines,
     },
 };
+use async_trait::async_trait;
 use axum::{
     extract::{
+        FromRequestParts,
         Host,
         State,
     },
@@ -31,7 +33,12 @@ use common::{
     version::ClientVersion,
 };
 use errors::ErrorMetadata;
+use http::HeaderName;
 use isolate::UdfArgsJson;
+use model::idempotency_keys::types::{
+    IdempotencyKey,
+    MutationIdentifier,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -49,6 +56,42 @@ use crate::{
     RouterState,
 };
 
+#[allow(clippy::declare_interior_mutable_const)]
+pub const IDEMPOTENCY_KEY_HEADER: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// The optional `Idempotency-Key` header of a mutation request. Retrying a
+/// mutation with the same key returns the result of the first successful
+/// execution instead of running the mutation again.
+pub struct ExtractIdempotencyKey(pub Option<IdempotencyKey>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ExtractIdempotencyKey
+where
+    S: Send + Sync,
+{
+    type Rejection = HttpResponseError;
+
+    async fn from_request_parts(
+        parts: &mut axum::http::request::Parts,
+        _state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let Some(header) = parts.headers.get(IDEMPOTENCY_KEY_HEADER) else {
+            return Ok(Self(None));
+        };
+        let key = header
+            .to_str()
+            .map_err(|_| {
+                anyhow!(ErrorMetadata::bad_request(
+                    "InvalidIdempotencyKey",
+                    "Idempotency keys may only contain printable ASCII characters other than \
+                     spaces",
+                ))
+            })?
+            .parse()?;
+        Ok(Self(Some(key)))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct UdfPostRequest {
     pub path: String,
@@ -80,6 +123,11 @@ pub enum UdfResponse {
     Error {
         error_message: String,
 
+        /// The code of a typed application error, which is also the `code`
+        /// field of `error_data`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error_code: Option<String>,
+
         #[serde(skip_serializing_if = "Option::is_none")]
         error_data: Option<JsonValue>,
 
@@ -129,6 +177,7 @@ impl UdfResponse {
     ) -> anyhow::Result<Self> {
         Ok(Self::Error {
             error_message,
+            error_code: error.error_code().map(str::to_string),
             error_data: error
                 .custom_data_if_any()
                 .map(|value| export_value(value, value_format, client_version))
@@ -375,6 +424,7 @@ pub async fn public_mutation_post(
     ExtractRequestId(request_id): ExtractRequestId,
     ExtractAuthenticationToken(auth_token): ExtractAuthenticationToken,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    ExtractIdempotencyKey(idempotency_key): ExtractIdempotencyKey,
     Json(req): Json<UdfPostRequest>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     let udf_path = parse_udf_path(&req.path)?;
@@ -395,7 +445,7 @@ pub async fn public_mutation_post(
             udf_path.into(),
             req.args.into_arg_vec(),
             FunctionCaller::HttpApi(client_version.clone()),
-            None,
+            idempotency_key.map(MutationIdentifier::IdempotencyKey),
         )
         .await?;
     let value_format = req.format.as_ref().map(|f| f.parse()).transpose()?;
@@ -0,0 +1,22 @@
+use metrics::{
+    log_counter_with_labels,
+    register_convex_counter,
+    StaticMetricLabel,
+};
+
+register_convex_counter!(
+    RATE_LIMITER_CHECKS_TOTAL,
+    "Number of requests checked against a rate limit",
+    &["limiter", "status"]
+);
+pub fn log_rate_limit_check(limiter: &'static str, allowed: bool) {
+    let status = if allowed { "allowed" } else { "limited" };
+    log_counter_with_labels(
+        &RATE_LIMITER_CHECKS_TOTAL,
+        1,
+        vec![
+            StaticMetricLabel::new("limiter", limiter),
+            StaticMetricLabel::new("status", status),
+        ],
+    );
+}
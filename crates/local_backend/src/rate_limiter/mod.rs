@@ -0,0 +1,555 @@
+//! Token bucket rate limiting for the public function API and HTTP actions.
+//!
+//! Each client gets a bucket of `burst` tokens that refills at a steady rate,
+//! and every request takes a token. Requests that find their bucket empty are
+//! rejected with a 429 and a `Retry-After` header saying when a token will be
+//! available. Clients are identified by IP address, by the subject of their
+//! validated auth token, or by the value of a request header.
+//!
+//! Buckets are kept in memory, so each backend instance enforces the limit
+//! separately.
+
+use std::{
+    net::SocketAddr,
+    num::NonZeroUsize,
+    str::FromStr,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use anyhow::Context;
+use axum::{
+    extract::{
+        ConnectInfo,
+        FromRequestParts,
+        State,
+    },
+    middleware::Next,
+    response::{
+        IntoResponse,
+        Response,
+    },
+};
+use common::http::HttpResponseError;
+use errors::ErrorMetadata;
+use http::{
+    header::RETRY_AFTER,
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+    Request,
+};
+use keybroker::Identity;
+use lru::LruCache;
+use parking_lot::{
+    Mutex,
+    RwLock,
+};
+
+use self::metrics::log_rate_limit_check;
+use crate::{
+    authentication::TryExtractIdentity,
+    RouterState,
+};
+
+mod metrics;
+
+/// Maximum number of clients to track buckets for. When there are more, the
+/// least recently seen client's bucket is dropped, which refills it.
+const MAX_TRACKED_KEYS: usize = 100_000;
+
+/// How a rate limit is configured, parsed from strings like
+/// `100/s,burst=200,key=subject`.
+///
+/// The first part is the sustained rate, in requests per second (`s`), minute
+/// (`m`) or hour (`h`). The options are:
+/// - `burst`: how many requests a client can make at once. Defaults to the
+///   number of requests in the rate.
+/// - `key`: what identifies a client. One of `ip` (the default), `forwarded_ip`
+///   (the first address in `X-Forwarded-For`, for deployments behind a proxy),
+///   `subject` (the issuer and subject of the auth token, once it's been
+///   validated) or `header:<name>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimitConfig {
+    pub requests: u32,
+    pub per: Duration,
+    pub burst: u32,
+    pub key: RateLimitKey,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RateLimitKey {
+    Ip,
+    ForwardedIp,
+    Subject,
+    Header(HeaderName),
+}
+
+impl FromStr for RateLimitConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut parts = s.split(',').map(str::trim);
+        let rate = parts.next().context("Missing rate")?;
+        let (requests, unit) = rate
+            .split_once('/')
+            .with_context(|| format!("Invalid rate {rate:?}, expected e.g. 100/s"))?;
+        let requests: u32 = requests
+            .parse()
+            .with_context(|| format!("Invalid number of requests {requests:?}"))?;
+        anyhow::ensure!(requests > 0, "Rate must allow at least one request");
+        let per = match unit {
+            "s" => Duration::from_secs(1),
+            "m" => Duration::from_secs(60),
+            "h" => Duration::from_secs(60 * 60),
+            _ => anyhow::bail!("Invalid rate unit {unit:?}, expected s, m or h"),
+        };
+        let mut config = Self {
+            requests,
+            per,
+            burst: requests,
+            key: RateLimitKey::Ip,
+        };
+        for option in parts {
+            let (name, value) = option
+                .split_once('=')
+                .with_context(|| format!("Invalid option {option:?}"))?;
+            match name {
+                "burst" => {
+                    config.burst = value
+                        .parse()
+                        .with_context(|| format!("Invalid burst {value:?}"))?;
+                    anyhow::ensure!(config.burst > 0, "Burst must be at least one request");
+                },
+                "key" => {
+                    config.key = match value {
+                        "ip" => RateLimitKey::Ip,
+                        "forwarded_ip" => RateLimitKey::ForwardedIp,
+                        "subject" => RateLimitKey::Subject,
+                        _ => match value.strip_prefix("header:") {
+                            Some(header) => RateLimitKey::Header(
+                                header
+                                    .parse()
+                                    .with_context(|| format!("Invalid header {header:?}"))?,
+                            ),
+                            None => anyhow::bail!("Invalid key {value:?}"),
+                        },
+                    }
+                },
+                _ => anyhow::bail!("Unknown option {name:?}"),
+            }
+        }
+        Ok(config)
+    }
+}
+
+impl RateLimitConfig {
+    fn tokens_per_second(&self) -> f64 {
+        self.requests as f64 / self.per.as_secs_f64()
+    }
+}
+
+impl RateLimitKey {
+    /// The key identifying the client that sent the request. `subject` is the
+    /// issuer and subject of the request's auth token, if it was validated.
+    /// Falls back to the client's IP address when the request doesn't have the
+    /// configured key.
+    fn extract(
+        &self,
+        headers: &HeaderMap,
+        remote_addr: Option<SocketAddr>,
+        subject: Option<&str>,
+    ) -> String {
+        let key = match self {
+            RateLimitKey::Ip => None,
+            RateLimitKey::ForwardedIp => headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .map(|ip| format!("ip:{}", ip.trim())),
+            RateLimitKey::Subject => subject.map(|subject| format!("sub:{subject}")),
+            RateLimitKey::Header(name) => headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| format!("header:{value}")),
+        };
+        key.unwrap_or_else(|| match remote_addr {
+            Some(addr) => format!("ip:{}", addr.ip()),
+            None => "ip:unknown".to_string(),
+        })
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn try_acquire(&mut self, config: &RateLimitConfig, now: Instant) -> Result<(), Duration> {
+        let rate = config.tokens_per_second();
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(config.burst as f64);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rate))
+        }
+    }
+}
+
+pub struct RateLimiter {
+    /// Name of the API being limited, for metrics.
+    name: &'static str,
+    /// `None` if rate limiting is disabled. Can be changed while the backend
+    /// is running.
+    config: RwLock<Option<RateLimitConfig>>,
+    buckets: Mutex<LruCache<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(name: &'static str, config: RateLimitConfig) -> Self {
+        Self::with_config(name, Some(config))
+    }
+
+    pub fn disabled(name: &'static str) -> Self {
+        Self::with_config(name, None)
+    }
+
+    fn with_config(name: &'static str, config: Option<RateLimitConfig>) -> Self {
+        Self {
+            name,
+            config: RwLock::new(config),
+            buckets: Mutex::new(LruCache::new(
+                NonZeroUsize::new(MAX_TRACKED_KEYS).expect("MAX_TRACKED_KEYS is zero"),
+            )),
+        }
+    }
+
+    /// Parse the limiter's config from a knob. The limiter is disabled if the
+    /// knob is unset or invalid.
+    pub fn from_knob(name: &'static str, knob_name: &str, config: &Option<String>) -> Self {
+        let Some(config) = config else {
+            return Self::disabled(name);
+        };
+        match config.parse() {
+            Ok(config) => Self::new(name, config),
+            Err(e) => {
+                tracing::error!("Invalid {knob_name} {config:?}, not rate limiting {name}: {e:#}");
+                Self::disabled(name)
+            },
+        }
+    }
+
+    pub fn config(&self) -> Option<RateLimitConfig> {
+        self.config.read().clone()
+    }
+
+    /// Change the limit, or disable it with `None`. Every client starts over
+    /// with a full bucket.
+    pub fn set_config(&self, config: Option<RateLimitConfig>) {
+        *self.config.write() = config;
+        self.buckets.lock().clear();
+    }
+
+    /// Whether the limiter keys clients by the subject of their auth token,
+    /// which has to be validated before calling [`RateLimiter::check`].
+    fn keys_by_subject(&self) -> bool {
+        matches!(
+            *self.config.read(),
+            Some(RateLimitConfig {
+                key: RateLimitKey::Subject,
+                ..
+            })
+        )
+    }
+
+    /// Take a token for the client that sent a request. Returns how long the
+    /// client should wait before retrying if it's over the limit.
+    pub fn check(
+        &self,
+        headers: &HeaderMap,
+        remote_addr: Option<SocketAddr>,
+        subject: Option<&str>,
+    ) -> Result<(), Duration> {
+        let Some(config) = self.config() else {
+            return Ok(());
+        };
+        self.check_key(&config.key.extract(headers, remote_addr, subject))
+    }
+
+    /// Like [`RateLimiter::check`], but for a client identified by `key`
+    /// instead of the configured key.
+    pub fn check_key(&self, key: &str) -> Result<(), Duration> {
+        let result = self.check_at(key, Instant::now());
+        log_rate_limit_check(self.name, result.is_ok());
+        result
+    }
+
+    fn check_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let Some(config) = self.config() else {
+            return Ok(());
+        };
+        let mut buckets = self.buckets.lock();
+        if !buckets.contains(key) {
+            let bucket = TokenBucket {
+                tokens: config.burst as f64,
+                updated: now,
+            };
+            buckets.put(key.to_string(), bucket);
+        }
+        let bucket = buckets.get_mut(key).expect("Bucket was just inserted");
+        bucket.try_acquire(&config, now)
+    }
+}
+
+/// The issuer and subject of the request's auth token, if it's valid. A forged
+/// or expired token has no subject, so its requests are limited by IP address
+/// instead of getting a fresh bucket for every made-up subject.
+async fn validated_subject(
+    st: &RouterState,
+    parts: &mut axum::http::request::Parts,
+) -> Option<String> {
+    let TryExtractIdentity(identity) = TryExtractIdentity::from_request_parts(parts, st)
+        .await
+        .ok()?;
+    match identity.ok()? {
+        Identity::User(user) => Some(format!("{}|{}", user.issuer, user.subject)),
+        _ => None,
+    }
+}
+
+async fn rate_limit<B: Send>(
+    st: &RouterState,
+    limiter: &RateLimiter,
+    remote_addr: Option<ConnectInfo<SocketAddr>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let remote_addr = remote_addr.map(|connect_info| connect_info.0);
+    let (mut parts, body) = req.into_parts();
+    let subject = if limiter.keys_by_subject() {
+        validated_subject(st, &mut parts).await
+    } else {
+        None
+    };
+    let req = Request::from_parts(parts, body);
+    if let Err(retry_after) = limiter.check(req.headers(), remote_addr, subject.as_deref()) {
+        return too_many_requests(retry_after);
+    }
+    next.run(req).await
+}
+
+/// The response to a request that's over its rate limit.
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    let error = HttpResponseError::from(anyhow::anyhow!(ErrorMetadata::rate_limited(
+        "TooManyRequests",
+        "Too many requests. Retry after the time in the Retry-After header.",
+    )));
+    let mut response = error.into_response();
+    // Retry-After is in whole seconds, so round up.
+    let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    response
+}
+
+pub async fn public_api_rate_limit<B: Send>(
+    State(st): State<RouterState>,
+    remote_addr: Option<ConnectInfo<SocketAddr>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    rate_limit(&st, &st.public_api_rate_limiter, remote_addr, req, next).await
+}
+
+pub async fn http_action_rate_limit<B: Send>(
+    State(st): State<RouterState>,
+    remote_addr: Option<ConnectInfo<SocketAddr>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    rate_limit(&st, &st.http_action_rate_limiter, remote_addr, req, next).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::SocketAddr,
+        time::{
+            Duration,
+            Instant,
+        },
+    };
+
+    use http::{
+        header::AUTHORIZATION,
+        HeaderMap,
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use super::{
+        RateLimitConfig,
+        RateLimitKey,
+        RateLimiter,
+    };
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[test]
+    fn test_parse_config() -> anyhow::Result<()> {
+        assert_eq!(
+            "10/s".parse::<RateLimitConfig>()?,
+            RateLimitConfig {
+                requests: 10,
+                per: Duration::from_secs(1),
+                burst: 10,
+                key: RateLimitKey::Ip,
+            }
+        );
+        assert_eq!(
+            "600/m, burst=50, key=header:x-api-key".parse::<RateLimitConfig>()?,
+            RateLimitConfig {
+                requests: 600,
+                per: Duration::from_secs(60),
+                burst: 50,
+                key: RateLimitKey::Header("x-api-key".parse()?),
+            }
+        );
+        assert_eq!(
+            "5/h,key=subject".parse::<RateLimitConfig>()?.key,
+            RateLimitKey::Subject
+        );
+        for invalid in [
+            "",
+            "10",
+            "0/s",
+            "10/d",
+            "10/s,burst=0",
+            "10/s,key=cookie",
+            "10/s,foo=bar",
+        ] {
+            assert!(invalid.parse::<RateLimitConfig>().is_err(), "{invalid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_token_bucket() -> anyhow::Result<()> {
+        let limiter = RateLimiter::new("test", "2/s,burst=3".parse()?);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.check_at("a", start).unwrap();
+        }
+        assert_eq!(
+            limiter.check_at("a", start),
+            Err(Duration::from_millis(500))
+        );
+        // Other clients have their own buckets.
+        limiter.check_at("b", start).unwrap();
+
+        // Tokens refill at the configured rate.
+        let later = start + Duration::from_millis(750);
+        limiter.check_at("a", later).unwrap();
+        assert_eq!(
+            limiter.check_at("a", later),
+            Err(Duration::from_millis(250))
+        );
+
+        // But never past the burst size.
+        let much_later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            limiter.check_at("a", much_later).unwrap();
+        }
+        assert!(limiter.check_at("a", much_later).is_err());
+
+        // Changing the limit refills every bucket, and disabling it lets every
+        // request through.
+        limiter.set_config(Some("1/s".parse()?));
+        limiter.check_at("a", much_later).unwrap();
+        assert!(limiter.check_at("a", much_later).is_err());
+        limiter.set_config(None);
+        limiter.check_at("a", much_later).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_key() -> anyhow::Result<()> {
+        let remote_addr: SocketAddr = "1.2.3.4:5678".parse()?;
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "5.6.7.8, 1.2.3.4".parse()?);
+        headers.insert("x-api-key", "abc".parse()?);
+
+        assert_eq!(
+            RateLimitKey::Ip.extract(&headers, Some(remote_addr), None),
+            "ip:1.2.3.4"
+        );
+        assert_eq!(
+            RateLimitKey::ForwardedIp.extract(&headers, Some(remote_addr), None),
+            "ip:5.6.7.8"
+        );
+        assert_eq!(
+            RateLimitKey::Header("x-api-key".parse()?).extract(&headers, Some(remote_addr), None),
+            "header:abc"
+        );
+        // Fall back to the IP address without a validated token, even if the
+        // request has one.
+        headers.insert(AUTHORIZATION, "Bearer header.payload.sig".parse()?);
+        assert_eq!(
+            RateLimitKey::Subject.extract(&headers, Some(remote_addr), None),
+            "ip:1.2.3.4"
+        );
+        assert_eq!(
+            RateLimitKey::Subject.extract(
+                &headers,
+                Some(remote_addr),
+                Some("https://issuer.example.com|user1")
+            ),
+            "sub:https://issuer.example.com|user1"
+        );
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_forged_subjects_share_a_bucket(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        backend
+            .st
+            .public_api_rate_limiter
+            .set_config(Some("1/h,key=subject".parse()?));
+        let query = |subject: &str| -> anyhow::Result<Request<Body>> {
+            let payload = base64::encode_config(
+                json!({"iss": "https://issuer.example.com", "sub": subject}).to_string(),
+                base64::URL_SAFE_NO_PAD,
+            );
+            Ok(Request::builder()
+                .uri("/api/query")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header(AUTHORIZATION, format!("Bearer header.{payload}.sig"))
+                .body(Body::from(serde_json::to_vec(
+                    &json!({"path": "getCounter", "args": {}}),
+                )?))?)
+        };
+        // The unsigned tokens fail authentication, so they're limited by IP
+        // address instead of each getting their own bucket.
+        let response = backend.send(query("user1")?).await?;
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        backend
+            .expect_error(
+                query("user2")?,
+                StatusCode::TOO_MANY_REQUESTS,
+                "TooManyRequests",
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use model::{
+    backend_state::BackendStateModel,
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+};
+use serde::Deserialize;
+
+use crate::{
+    admin::must_be_admin_with_write_access,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateReadOnlyModeRequest {
+    read_only: bool,
+}
+
+/// Turns read-only mode on or off. While the deployment is read-only,
+/// queries and subscriptions keep working, but mutations and actions are
+/// rejected with a `DeploymentReadOnly` error, and so is any other write to
+/// a user table.
+pub async fn update_read_only_mode(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(UpdateReadOnlyModeRequest { read_only }): Json<UpdateReadOnlyModeRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let mut model = BackendStateModel::new(&mut tx);
+    let old_state = model.set_read_only(read_only).await?;
+    let new_state = model.get_backend_state().await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::ChangeDeploymentState {
+                old_state,
+                new_state,
+            }],
+            "update_read_only_mode",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
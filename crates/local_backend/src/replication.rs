@@ -0,0 +1,102 @@
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    types::Timestamp,
+};
+use keybroker::AdminOperation;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use crate::{
+    admin::must_be_admin_for,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// Default and maximum number of revisions in a page of the log.
+const MAX_REPLICATION_LOG_ROWS: usize = 1000;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplicationLogArgs {
+    /// The `cursor` of the previous page. Omitted to start from the
+    /// beginning of the log.
+    cursor: Option<u64>,
+    limit: Option<usize>,
+}
+
+/// A page of this backend's documents log, for a standby replicating from
+/// it.
+#[debug_handler]
+pub async fn replication_log(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(ReplicationLogArgs { cursor, limit }): Query<ReplicationLogArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    // The log has every revision of every document, so reading it needs a
+    // key that can read data, not just one that can deploy.
+    must_be_admin_for(&identity, AdminOperation::ReadData)?;
+    let cursor = cursor.map(Timestamp::try_from).transpose()?;
+    let limit = limit
+        .unwrap_or(MAX_REPLICATION_LOG_ROWS)
+        .min(MAX_REPLICATION_LOG_ROWS);
+    let page = st
+        .application
+        .replication_log(identity, cursor, limit)
+        .await?;
+    Ok(Json(JsonValue::try_from(page)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use common::types::MemberId;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use keybroker::AdminRole;
+    use runtime::prod::ProdRuntime;
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    fn replication_log_request(auth_header: String) -> anyhow::Result<Request<Body>> {
+        Ok(Request::builder()
+            .uri("/api/replication/log")
+            .method("GET")
+            .header("Authorization", auth_header)
+            .body(Body::empty())?)
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_replication_log_requires_read_access(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let req = replication_log_request(backend.admin_auth_header.0.encode())?;
+        let _: serde_json::Value = backend.expect_success(req).await?;
+
+        let deploy_header = backend
+            .st
+            .application
+            .key_broker()
+            .issue_admin_key_with_role(MemberId(2), AdminRole::Deploy)
+            .as_header()?
+            .0
+            .encode();
+        let req = replication_log_request(deploy_header)?;
+        backend
+            .expect_error(req, StatusCode::FORBIDDEN, "DeployOnlyAdminKey")
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Admin API for revoking user sessions, e.g. to cut off a compromised account
+//! before its tokens expire. Revoked tokens fail to authenticate, and
+//! WebSocket sessions authenticated with them are closed with an auth error.
+
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    HttpResponseError,
+};
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    revoked_sessions::RevokedSessionsModel,
+};
+use serde::Deserialize;
+
+use crate::{
+    admin::must_be_admin_with_write_access,
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeSessionsRequest {
+    /// The `sub` claim of the tokens to revoke.
+    subject: String,
+    /// Only revoke tokens from this issuer, rather than from every auth
+    /// provider.
+    issuer: Option<String>,
+    /// Only revoke the token with this `jti` claim, rather than every token
+    /// issued to the subject so far.
+    token_id: Option<String>,
+}
+
+pub async fn revoke_sessions(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RevokeSessionsRequest {
+        subject,
+        issuer,
+        token_id,
+    }): Json<RevokeSessionsRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    RevokedSessionsModel::new(&mut tx)
+        .revoke(subject.clone(), issuer.clone(), token_id.clone())
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::RevokeSessions {
+                subject,
+                issuer,
+                token_id,
+            }],
+            "revoke_sessions",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use errors::ErrorMetadataAnyhowExt;
+    use http::Request;
+    use hyper::Body;
+    use keybroker::{
+        testing::TestUserIdentity,
+        Identity,
+        UserIdentity,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_revoke_sessions(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let user = UserIdentity::test();
+        let identity = Identity::user(user.clone());
+        backend
+            .st
+            .application
+            .ensure_session_not_revoked(&identity)
+            .await?;
+
+        let req = Request::builder()
+            .uri("/api/revoke_sessions")
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(Body::from(serde_json::to_vec(
+                &json!({ "subject": user.subject }),
+            )?))?;
+        let _: JsonValue = backend.expect_success(req).await?;
+        let err = backend
+            .st
+            .application
+            .ensure_session_not_revoked(&identity)
+            .await
+            .unwrap_err();
+        assert_eq!(err.short_msg(), "SessionRevoked");
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@ use axum::{
     routing::{
         get,
         post,
+        put,
     },
     BoxError,
     Router,
@@ -19,6 +20,13 @@ use common::{
         CONVEX_CLIENT_HEADER,
     },
     knobs::{
+        BUILT_IN_AUTH_ENABLED,
+        GRAPHQL_ENDPOINT_ENABLED,
+        HTTP_ACTION_RESPONSE_CACHE_MAX_ENTRY_SIZE,
+        HTTP_ACTION_RESPONSE_CACHE_MAX_SIZE,
+        IMAGE_TRANSFORM_CACHE_MAX_SIZE,
+        IMAGE_TRANSFORM_MAX_SOURCE_SIZE,
+        IMAGE_TRANSFORM_THREADS,
         MAX_BACKEND_PUBLIC_API_REQUEST_SIZE,
         MAX_BACKEND_RPC_REQUEST_SIZE,
         MAX_PUSH_BYTES,
@@ -45,10 +53,38 @@ use tower_http::{
 };
 
 use crate::{
+    admin_access::{
+        admin_access,
+        admin_auth_access,
+    },
+    api_keys::{
+        create_api_key,
+        delete_api_key,
+        list_api_keys,
+    },
+    built_in_auth::{
+        refresh_session,
+        send_magic_link,
+        sign_in,
+        sign_in_with_magic_link,
+        sign_up,
+        verify_email,
+    },
+    bulk_edit::bulk_edit_documents,
+    component_quotas::{
+        list_component_quotas,
+        set_component_quotas,
+    },
     dashboard::{
+        archive_table,
         delete_tables,
         get_indexes,
         get_source_code,
+        invalidate_query_cache,
+        list_active_subscriptions,
+        list_archived_tables,
+        rename_table,
+        restore_table,
         shapes2,
     },
     deploy_config::{
@@ -57,8 +93,55 @@ use crate::{
         push_config,
     },
     deploy_config2,
+    deployment_clones::{
+        clone_deployment,
+        list_deployment_clones,
+    },
+    deployment_versions::{
+        list_deployment_versions,
+        restore_deployment_version,
+    },
+    drain::{
+        drain,
+        reject_while_draining,
+    },
+    egress_policy::{
+        delete_egress_policy,
+        get_egress_policy,
+        set_egress_policy,
+    },
     environment_variables::update_environment_variables,
+    export_schedules::{
+        add_export_schedule,
+        delete_export_schedule,
+        list_export_schedules,
+    },
+    function_canaries::{
+        delete_function_canary,
+        list_function_canaries,
+        set_function_canary_rollout,
+    },
+    function_flags::{
+        delete_function_flag,
+        list_function_flags,
+        set_function_flag,
+    },
+    function_read_budgets::{
+        delete_function_read_budget,
+        list_function_read_budgets,
+        set_function_read_budget,
+    },
+    graphql::{
+        graphql_post,
+        graphql_ws,
+    },
+    health::{
+        healthz,
+        readyz,
+    },
     http_actions::http_action_handler,
+    http_response_cache::HttpResponseCache,
+    image_transform::ImageTransformer,
     import::{
         cancel_import,
         import,
@@ -68,23 +151,50 @@ use crate::{
         perform_import,
         prepare_import,
     },
+    log_settings::{
+        delete_log_setting,
+        list_log_settings,
+        set_log_setting,
+    },
+    log_sinks::{
+        add_datadog_sink,
+        add_sentry_sink,
+        add_splunk_sink,
+        delete_log_sink,
+        list_log_sinks,
+        update_log_sink_rules,
+    },
     logs::{
+        query_function_executions,
+        query_job_executions,
         stream_function_logs,
         stream_udf_execution,
+        summarize_cron_executions,
     },
+    metrics_endpoint::get_metrics,
     node_action_callbacks::{
         action_callbacks_middleware,
         cancel_developer_job,
         internal_action_post,
         internal_mutation_post,
         internal_query_post,
+        result_chunk,
         schedule_job,
         storage_delete,
         storage_generate_upload_url,
         storage_get_metadata,
         storage_get_url,
+        storage_set_metadata,
         vector_search,
     },
+    openapi::openapi_spec_get,
+    outbox::{
+        delete_outbox_target,
+        list_outbox_records,
+        list_outbox_targets,
+        retry_outbox_record,
+        set_outbox_target,
+    },
     public_api::{
         public_action_post,
         public_function_post,
@@ -92,15 +202,37 @@ use crate::{
         public_query_batch_post,
         public_query_get,
         public_query_post,
+        IDEMPOTENCY_KEY_HEADER,
     },
+    rate_limiter::{
+        http_action_rate_limit,
+        public_api_rate_limit,
+    },
+    read_only::update_read_only_mode,
+    replication::replication_log,
+    revoked_sessions::revoke_sessions,
     scheduling::{
         cancel_all_jobs,
         cancel_job,
+        cancel_scheduled_jobs,
+        count_scheduled_jobs,
+        list_scheduled_jobs,
+        rerun_scheduled_jobs,
+        schedule_jobs,
     },
     schema::{
         prepare_schema,
         schema_state,
     },
+    search_index_cache::{
+        pin_search_index,
+        search_index_cache_status,
+        unpin_search_index,
+    },
+    settings::{
+        get_backend_settings,
+        update_backend_settings_handler,
+    },
     snapshot_export::{
         get_export,
         get_zip_export,
@@ -108,13 +240,33 @@ use crate::{
         request_zip_export,
     },
     storage::{
+        storage_create_upload_session,
+        storage_finish_upload_session,
         storage_get,
         storage_upload,
+        storage_upload_session_part,
+        storage_upload_session_status,
+    },
+    storage_admin::{
+        add_file_lifecycle_rule,
+        delete_file_lifecycle_rule,
+        list_file_lifecycle_rules,
+        list_files,
     },
     subs::{
         sync,
         sync_client_version_url,
     },
+    traffic_shadowing::{
+        delete_traffic_shadowing,
+        get_traffic_shadowing,
+        set_traffic_shadowing,
+        shadow_function,
+    },
+    trash::{
+        list_trash,
+        restore_document,
+    },
     LocalAppState,
     RouterState,
 };
@@ -129,22 +281,128 @@ pub async fn router(st: LocalAppState) -> Router {
         // Scheduled jobs routes
         .route("/cancel_all_jobs", post(cancel_all_jobs))
         .route("/cancel_job", post(cancel_job))
+        .route("/list_scheduled_jobs", post(list_scheduled_jobs))
+        .route("/count_scheduled_jobs", post(count_scheduled_jobs))
+        .route("/cancel_scheduled_jobs", post(cancel_scheduled_jobs))
+        .route("/rerun_scheduled_jobs", post(rerun_scheduled_jobs))
+        .route("/schedule_jobs", post(schedule_jobs))
+        // File storage routes
+        .route("/list_files", post(list_files))
+        .route("/add_file_lifecycle_rule", post(add_file_lifecycle_rule))
+        .route("/delete_file_lifecycle_rule", post(delete_file_lifecycle_rule))
+        .route("/list_file_lifecycle_rules", get(list_file_lifecycle_rules))
+        // Soft-deleted document routes
+        .route("/list_trash", post(list_trash))
+        .route("/restore_document", post(restore_document))
+        // Function flag routes
+        .route("/set_function_flag", post(set_function_flag))
+        .route("/delete_function_flag", post(delete_function_flag))
+        .route("/list_function_flags", get(list_function_flags))
+        // Function canary routes
+        .route("/set_function_canary_rollout", post(set_function_canary_rollout))
+        .route("/delete_function_canary", post(delete_function_canary))
+        .route("/list_function_canaries", get(list_function_canaries))
+        // Function read budget routes
+        .route("/set_function_read_budget", post(set_function_read_budget))
+        .route("/delete_function_read_budget", post(delete_function_read_budget))
+        .route("/list_function_read_budgets", get(list_function_read_budgets))
+        // Log setting routes
+        .route("/set_log_setting", post(set_log_setting))
+        .route("/delete_log_setting", post(delete_log_setting))
+        .route("/list_log_settings", get(list_log_settings))
+        // Deployment version routes
+        .route("/list_deployment_versions", get(list_deployment_versions))
+        .route("/restore_deployment_version", post(restore_deployment_version))
+        // Egress policy routes
+        .route("/set_egress_policy", post(set_egress_policy))
+        .route("/delete_egress_policy", post(delete_egress_policy))
+        .route("/get_egress_policy", get(get_egress_policy))
+        // Outbox routes
+        .route("/set_outbox_target", post(set_outbox_target))
+        .route("/delete_outbox_target", post(delete_outbox_target))
+        .route("/list_outbox_targets", get(list_outbox_targets))
+        .route("/list_outbox_records", get(list_outbox_records))
+        .route("/retry_outbox_record", post(retry_outbox_record))
+        // Traffic shadowing routes
+        .route("/set_traffic_shadowing", post(set_traffic_shadowing))
+        .route("/delete_traffic_shadowing", post(delete_traffic_shadowing))
+        .route("/get_traffic_shadowing", get(get_traffic_shadowing))
+        // Bulk edit routes
+        .route("/bulk_edit_documents", post(bulk_edit_documents))
+        // Search index cache routes
+        .route("/search_index_cache_status", get(search_index_cache_status))
+        .route("/pin_search_index", post(pin_search_index))
+        .route("/unpin_search_index", post(unpin_search_index))
+        // Read-only mode routes
+        .route("/update_read_only_mode", post(update_read_only_mode))
+        // Component routes
+        .route("/set_component_quotas", post(set_component_quotas))
+        .route("/list_component_quotas", get(list_component_quotas))
         // Environment variable routes
         .route("/update_environment_variables", post(update_environment_variables))
+        // API key routes
+        .route("/create_api_key", post(create_api_key))
+        .route("/list_api_keys", get(list_api_keys))
+        .route("/delete_api_key", post(delete_api_key))
+        .route("/revoke_sessions", post(revoke_sessions))
+        // Log streaming routes
+        .route("/add_datadog_sink", post(add_datadog_sink))
+        .route("/add_splunk_sink", post(add_splunk_sink))
+        .route("/add_sentry_sink", post(add_sentry_sink))
+        .route("/update_log_sink_rules", post(update_log_sink_rules))
+        .route("/delete_log_sink", post(delete_log_sink))
+        .route("/list_log_sinks", get(list_log_sinks))
+        // Scheduled export routes
+        .route("/add_export_schedule", post(add_export_schedule))
+        .route("/delete_export_schedule", post(delete_export_schedule))
+        .route("/list_export_schedules", get(list_export_schedules))
+        // Deployment clone routes
+        .route("/clone_deployment", post(clone_deployment))
+        .route("/list_deployment_clones", get(list_deployment_clones))
+        // Draining before a restart
+        .route("/drain", post(drain))
+        // Backend settings routes
+        .route("/backend_settings", get(get_backend_settings))
+        .route("/update_backend_settings", post(update_backend_settings_handler))
         // Administrative routes for the dashboard
         .route("/shapes2", get(shapes2))
         .route("/get_indexes", get(get_indexes))
         .route("/delete_tables", post(delete_tables))
+        .route("/rename_table", post(rename_table))
+        .route("/archive_table", post(archive_table))
+        .route("/restore_table", post(restore_table))
+        .route("/list_archived_tables", get(list_archived_tables))
         .route("/get_source_code", get(get_source_code))
+        .route("/active_subscriptions", get(list_active_subscriptions))
+        .route("/invalidate_query_cache", post(invalidate_query_cache))
         // Metrics routes
         .route("/app_metrics/stream_udf_execution", get(stream_udf_execution))
         .route("/app_metrics/stream_function_logs", get(stream_function_logs))
+        .route(
+            "/app_metrics/function_executions",
+            get(query_function_executions),
+        )
+        .route("/app_metrics/job_executions", get(query_job_executions))
+        .route(
+            "/app_metrics/cron_execution_summary",
+            get(summarize_cron_executions),
+        )
         .layer(ServiceBuilder::new());
 
     let cli_routes = Router::new()
         .route("/push_config", post(push_config))
         .route("/prepare_schema", post(prepare_schema))
         .route("/deploy2/start_push", post(deploy_config2::start_push))
+        .route("/deploy2/plan_push", post(deploy_config2::plan_push))
+        .route("/deploy2/start_canary", post(deploy_config2::start_canary))
+        .route(
+            "/components/install",
+            post(deploy_config2::install_component),
+        )
+        .route(
+            "/components/upgrade",
+            post(deploy_config2::upgrade_component),
+        )
         .layer(
             ServiceBuilder::new()
                 .layer(HandleErrorLayer::new(|_: BoxError| async {
@@ -158,6 +416,10 @@ pub async fn router(st: LocalAppState) -> Router {
             post(deploy_config2::wait_for_schema),
         )
         .route("/deploy2/finish_push", post(deploy_config2::finish_push))
+        .route(
+            "/components/uninstall",
+            post(deploy_config2::uninstall_component),
+        )
         .route("/get_config", post(get_config))
         .route("/get_config_hashes", post(get_config_hashes))
         .route("/schema_state/:schema_id", get(schema_state))
@@ -172,16 +434,89 @@ pub async fn router(st: LocalAppState) -> Router {
         .route("/request/zip", post(request_zip_export))
         .route("/zip/:snapshot_ts", get(get_zip_export));
 
-    let api_routes = Router::new()
+    // The admin API, which the deployment may only allow certain networks and
+    // clients to call.
+    let admin_routes = Router::new()
         .merge(cli_routes)
         .merge(dashboard_routes)
+        .nest("/export", snapshot_export_routes)
+        .route("/replication/log", get(replication_log))
+        .route("/shadow_function", post(shadow_function))
+        .route_layer(axum::middleware::from_fn_with_state(
+            st.clone(),
+            admin_access,
+        ));
+
+    let router_state = RouterState {
+        api: Arc::new(st.application.clone()),
+        runtime: st.application.runtime().clone(),
+        live_ws_count: st.live_ws_count.clone(),
+        active_subscriptions: st.active_subscriptions.clone(),
+        http_response_cache: Arc::new(HttpResponseCache::new(
+            *HTTP_ACTION_RESPONSE_CACHE_MAX_SIZE,
+            *HTTP_ACTION_RESPONSE_CACHE_MAX_ENTRY_SIZE,
+        )),
+        image_transformer: Arc::new(ImageTransformer::new(
+            st.application.runtime().clone(),
+            *IMAGE_TRANSFORM_THREADS,
+            *IMAGE_TRANSFORM_CACHE_MAX_SIZE,
+            *IMAGE_TRANSFORM_MAX_SOURCE_SIZE,
+        )),
+        public_api_rate_limiter: st.public_api_rate_limiter.clone(),
+        http_action_rate_limiter: st.http_action_rate_limiter.clone(),
+        drain: st.drain.clone(),
+    };
+
+    let mut api_routes = Router::new()
+        .merge(admin_routes)
         .nest("/actions", action_callback_routes(st.clone()))
-        .nest("/export", snapshot_export_routes);
+        .route("/openapi.json", get(openapi_spec_get));
+    if *GRAPHQL_ENDPOINT_ENABLED {
+        // GraphQL runs public functions, so it has the same limits as the
+        // public function API.
+        api_routes = api_routes.route(
+            "/graphql",
+            get(graphql_ws)
+                .post(graphql_post)
+                .route_layer(axum::middleware::from_fn_with_state(
+                    st.admin_access.clone(),
+                    admin_auth_access,
+                ))
+                .route_layer(axum::middleware::from_fn_with_state(
+                    router_state.clone(),
+                    public_api_rate_limit,
+                ))
+                .route_layer(axum::middleware::from_fn_with_state(
+                    router_state.clone(),
+                    reject_while_draining,
+                )),
+        );
+    }
+    if *BUILT_IN_AUTH_ENABLED {
+        api_routes = api_routes.nest("/auth", built_in_auth_routes());
+    }
 
     // Endpoints migrated to use the RouterState trait instead of application.
     let migrated_api_routes = Router::new()
-        .merge(browser_routes)
-        .merge(public_api_routes())
+        .merge(browser_routes.route_layer(axum::middleware::from_fn_with_state(
+            st.admin_access.clone(),
+            admin_auth_access,
+        )))
+        .merge(
+            public_api_routes()
+                .route_layer(axum::middleware::from_fn_with_state(
+                    st.admin_access.clone(),
+                    admin_auth_access,
+                ))
+                .route_layer(axum::middleware::from_fn_with_state(
+                    router_state.clone(),
+                    public_api_rate_limit,
+                ))
+                .route_layer(axum::middleware::from_fn_with_state(
+                    router_state.clone(),
+                    reject_while_draining,
+                )),
+        )
         .nest("/storage", storage_api_routes());
     let migrated = Router::new()
         .nest("/api", migrated_api_routes)
@@ -189,16 +524,26 @@ pub async fn router(st: LocalAppState) -> Router {
         // Order matters. Layers only apply to routes above them.
         // Notably, any layers added here won't apply to common routes
         // added inside `serve_http`
-        .nest("/http/", http_action_routes())
-        .with_state(RouterState {
-            api: Arc::new(st.application.clone()),
-            runtime: st.application.runtime().clone(),
-            live_ws_count: st.live_ws_count.clone(),
-        });
+        .nest(
+            "/http/",
+            http_action_routes()
+                .route_layer(axum::middleware::from_fn_with_state(
+                    router_state.clone(),
+                    http_action_rate_limit,
+                ))
+                .route_layer(axum::middleware::from_fn_with_state(
+                    router_state.clone(),
+                    reject_while_draining,
+                )),
+        )
+        .with_state(router_state);
 
     Router::new()
         .nest("/api", api_routes)
         .layer(cors().await)
+        .route("/metrics", get(get_metrics))
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
         .with_state(st)
         .merge(migrated)
 }
@@ -215,9 +560,28 @@ pub fn public_api_routes() -> Router<RouterState> {
         .layer(DefaultBodyLimit::max(*MAX_BACKEND_PUBLIC_API_REQUEST_SIZE))
 }
 
+pub fn built_in_auth_routes() -> Router<LocalAppState> {
+    Router::new()
+        .route("/sign_up", post(sign_up))
+        .route("/sign_in", post(sign_in))
+        .route("/send_magic_link", post(send_magic_link))
+        .route("/verify_email", post(verify_email))
+        .route("/sign_in_with_magic_link", post(sign_in_with_magic_link))
+        .route("/refresh", post(refresh_session))
+}
+
 pub fn storage_api_routes() -> Router<RouterState> {
     Router::new()
         .route("/upload", post(storage_upload))
+        .route("/upload_session", post(storage_create_upload_session))
+        .route(
+            "/upload_session/:upload_id",
+            get(storage_upload_session_status).post(storage_finish_upload_session),
+        )
+        .route(
+            "/upload_session/:upload_id/:part_number",
+            put(storage_upload_session_part),
+        )
         .route("/:storage_id", get(storage_get))
 }
 
@@ -229,11 +593,13 @@ pub fn action_callback_routes(st: LocalAppState) -> Router<LocalAppState> {
         .route("/schedule_job", post(schedule_job))
         .route("/vector_search", post(vector_search))
         .route("/cancel_job", post(cancel_developer_job))
+        .route("/result_chunk", post(result_chunk))
         // file storage endpoints
         .route("/storage_generate_upload_url", post(storage_generate_upload_url))
         .route("/storage_get_url", post(storage_get_url))
         .route("/storage_get_metadata", post(storage_get_metadata))
         .route("/storage_delete", post(storage_delete))
+        .route("/storage_set_metadata", post(storage_set_metadata))
         // All routes above this line get the increased limit
         .layer(DefaultBodyLimit::max(*MAX_BACKEND_RPC_REQUEST_SIZE))
         .layer(axum::middleware::from_fn_with_state(st.clone(), action_callbacks_middleware))
@@ -259,7 +625,7 @@ pub fn http_action_routes() -> Router<RouterState> {
 
 pub async fn cors() -> CorsLayer {
     CorsLayer::new()
-        .allow_headers(vec![CONTENT_TYPE, "sentry-trace".parse().unwrap(), "baggage".parse().unwrap(), CONVEX_CLIENT_HEADER, AUTHORIZATION])
+        .allow_headers(vec![CONTENT_TYPE, "sentry-trace".parse().unwrap(), "baggage".parse().unwrap(), CONVEX_CLIENT_HEADER, AUTHORIZATION, IDEMPOTENCY_KEY_HEADER])
         .allow_credentials(true)
         .allow_methods(vec![
             Method::GET,
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use anyhow::Context;
 use axum::{
     debug_handler,
@@ -9,14 +11,33 @@ use common::{
         CanonicalizedComponentFunctionPath,
         ComponentPath,
     },
+    document::{
+        timestamp_to_ms,
+        CreationTime,
+        ParsedDocument,
+    },
+    execution_context::ExecutionContext,
     http::{
         extract::Json,
+        ExtractClientVersion,
+        ExtractRequestId,
         HttpResponseError,
     },
+    runtime::UnixTimestamp,
+    types::{
+        FunctionCaller,
+        Timestamp,
+    },
 };
 use errors::ErrorMetadata;
 use http::StatusCode;
+use keybroker::AdminOperation;
 use model::scheduled_jobs::{
+    types::{
+        ScheduleOptions,
+        ScheduledJob,
+        ScheduledJobFilter,
+    },
     SchedulerModel,
     SCHEDULED_JOBS_TABLE,
 };
@@ -24,12 +45,24 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use value::TableNamespace;
+use serde_json::Value as JsonValue;
+use value::{
+    ConvexObject,
+    ConvexValue,
+    TableNamespace,
+};
 
 use crate::{
-    admin::must_be_admin_member_with_write_access,
+    admin::{
+        must_be_admin_member,
+        must_be_admin_member_for,
+        must_be_admin_member_with_write_access,
+    },
     authentication::ExtractIdentity,
-    parse::parse_document_id,
+    parse::{
+        parse_document_id,
+        parse_udf_path,
+    },
     LocalAppState,
 };
 
@@ -96,3 +129,293 @@ pub async fn cancel_job(
 
     Ok(StatusCode::OK)
 }
+
+/// Selects the scheduled jobs a bulk request applies to. All jobs match if no
+/// field is set.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledJobFilterArgs {
+    pub udf_path: Option<String>,
+    pub states: Option<Vec<String>>,
+    /// Only match jobs scheduled to run at or after this time.
+    pub scheduled_after_ms: Option<u64>,
+    /// Only match jobs scheduled to run before this time.
+    pub scheduled_before_ms: Option<u64>,
+}
+
+impl TryFrom<ScheduledJobFilterArgs> for ScheduledJobFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(args: ScheduledJobFilterArgs) -> anyhow::Result<Self> {
+        let udf_path =
+            args.udf_path
+                .map(|p| p.parse())
+                .transpose()
+                .context(ErrorMetadata::bad_request(
+                    "InvalidUdfPath",
+                    "udfPath must be a canonicalized UdfPath",
+                ))?;
+        let states = args
+            .states
+            .map(|states| {
+                states
+                    .iter()
+                    .map(|state| state.parse())
+                    .collect::<anyhow::Result<_>>()
+            })
+            .transpose()
+            .context(ErrorMetadata::bad_request(
+                "InvalidScheduledJobState",
+                "states must be \"pending\", \"inProgress\", \"success\", \"failed\" or \
+                 \"canceled\"",
+            ))?;
+        let to_ts = |ms: u64| -> anyhow::Result<Timestamp> {
+            UnixTimestamp::from_millis(ms).as_system_time().try_into()
+        };
+        Ok(Self {
+            udf_path,
+            states,
+            scheduled_after: args.scheduled_after_ms.map(to_ts).transpose()?,
+            scheduled_before: args.scheduled_before_ms.map(to_ts).transpose()?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledJobJson {
+    pub id: String,
+    pub udf_path: String,
+    pub args: JsonValue,
+    pub state: JsonValue,
+    pub scheduled_time_ms: f64,
+    pub completed_time_ms: Option<f64>,
+    pub attempts: u32,
+    pub priority: String,
+    pub batch_id: Option<String>,
+}
+
+impl TryFrom<ParsedDocument<ScheduledJob>> for ScheduledJobJson {
+    type Error = anyhow::Error;
+
+    fn try_from(job: ParsedDocument<ScheduledJob>) -> anyhow::Result<Self> {
+        let id = job.developer_id().encode();
+        let job = job.into_value();
+        Ok(Self {
+            id,
+            udf_path: String::from(job.udf_path),
+            args: JsonValue::from(job.udf_args),
+            state: JsonValue::from(ConvexObject::try_from(job.state)?),
+            scheduled_time_ms: timestamp_to_ms(job.original_scheduled_ts)?,
+            completed_time_ms: job.completed_ts.map(timestamp_to_ms).transpose()?,
+            attempts: job.attempts,
+            priority: job.priority.to_string(),
+            batch_id: job.batch_id,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListScheduledJobsRequest {
+    #[serde(default)]
+    pub filter: ScheduledJobFilterArgs,
+    pub cursor: Option<f64>,
+    pub num_items: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListScheduledJobsResponse {
+    pub jobs: Vec<ScheduledJobJson>,
+    pub cursor: Option<f64>,
+    pub is_done: bool,
+}
+
+const DEFAULT_LIST_SCHEDULED_JOBS_NUM_ITEMS: usize = 100;
+
+#[debug_handler]
+pub async fn list_scheduled_jobs(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ListScheduledJobsRequest {
+        filter,
+        cursor,
+        num_items,
+    }): Json<ListScheduledJobsRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    // Scheduled jobs' arguments are data.
+    must_be_admin_member_for(&identity, AdminOperation::ReadData)?;
+    let cursor =
+        cursor
+            .map(CreationTime::try_from)
+            .transpose()
+            .context(ErrorMetadata::bad_request(
+                "InvalidCursor",
+                "cursor must be a cursor returned by list_scheduled_jobs",
+            ))?;
+    let page = st
+        .application
+        .list_scheduled_jobs(
+            identity,
+            filter.try_into()?,
+            cursor,
+            num_items.unwrap_or(DEFAULT_LIST_SCHEDULED_JOBS_NUM_ITEMS),
+        )
+        .await?;
+    Ok(Json(ListScheduledJobsResponse {
+        jobs: page
+            .jobs
+            .into_iter()
+            .map(ScheduledJobJson::try_from)
+            .collect::<anyhow::Result<_>>()?,
+        cursor: page.cursor.map(f64::from),
+        is_done: page.cursor.is_none(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledJobsFilterRequest {
+    #[serde(default)]
+    pub filter: ScheduledJobFilterArgs,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountScheduledJobsResponse {
+    pub counts: BTreeMap<String, usize>,
+}
+
+#[debug_handler]
+pub async fn count_scheduled_jobs(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ScheduledJobsFilterRequest { filter }): Json<ScheduledJobsFilterRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member(&identity)?;
+    let counts = st
+        .application
+        .count_scheduled_jobs(identity, filter.try_into()?)
+        .await?;
+    Ok(Json(CountScheduledJobsResponse {
+        counts: counts
+            .into_iter()
+            .map(|(state, count)| (state.to_string(), count))
+            .collect(),
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelScheduledJobsResponse {
+    pub num_canceled: usize,
+}
+
+#[debug_handler]
+pub async fn cancel_scheduled_jobs(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ScheduledJobsFilterRequest { filter }): Json<ScheduledJobsFilterRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let num_canceled = st
+        .application
+        .cancel_scheduled_jobs(identity, filter.try_into()?)
+        .await?;
+    Ok(Json(CancelScheduledJobsResponse { num_canceled }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RerunScheduledJobsResponse {
+    pub num_rerun: usize,
+}
+
+#[debug_handler]
+pub async fn rerun_scheduled_jobs(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ScheduledJobsFilterRequest { filter }): Json<ScheduledJobsFilterRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let num_rerun = st
+        .application
+        .rerun_scheduled_jobs(identity, filter.try_into()?)
+        .await?;
+    Ok(Json(RerunScheduledJobsResponse { num_rerun }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleJobJson {
+    pub udf_path: String,
+    /// The function's arguments, as an array of Convex JSON values.
+    pub args: JsonValue,
+    pub scheduled_time_ms: u64,
+    pub priority: Option<String>,
+    pub max_concurrency: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleJobsRequest {
+    pub jobs: Vec<ScheduleJobJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleJobsResponse {
+    pub batch_id: String,
+}
+
+/// Schedule many functions atomically as one batch, e.g. to copy the pending
+/// jobs of the deployment this one was cloned from.
+#[debug_handler]
+pub async fn schedule_jobs(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    ExtractRequestId(request_id): ExtractRequestId,
+    ExtractClientVersion(client_version): ExtractClientVersion,
+    Json(ScheduleJobsRequest { jobs }): Json<ScheduleJobsRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_with_write_access(&identity)?;
+    let jobs = jobs
+        .into_iter()
+        .map(|job| {
+            let udf_path = parse_udf_path(&job.udf_path)?;
+            let ConvexValue::Array(args) = ConvexValue::try_from(job.args)? else {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "InvalidScheduledJobArgs",
+                    format!("The arguments of {} must be an array", job.udf_path),
+                ));
+            };
+            let priority = job
+                .priority
+                .map(|priority| priority.parse())
+                .transpose()
+                .context(ErrorMetadata::bad_request(
+                    "InvalidScheduledJobPriority",
+                    "priority must be \"high\", \"normal\" or \"low\"",
+                ))?
+                .unwrap_or_default();
+            let options = ScheduleOptions {
+                priority,
+                max_concurrency: job.max_concurrency,
+            };
+            Ok((
+                udf_path,
+                args,
+                UnixTimestamp::from_millis(job.scheduled_time_ms),
+                options,
+            ))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let context = ExecutionContext::new(request_id, &FunctionCaller::HttpApi(client_version));
+    let mut tx = st.application.begin(identity).await?;
+    let batch_id = SchedulerModel::new(&mut tx, TableNamespace::by_component_TODO())
+        .schedule_batch(jobs, context)
+        .await?;
+    st.application.commit(tx, "schedule_jobs").await?;
+    Ok(Json(ScheduleJobsResponse { batch_id }))
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use anyhow::Context;
 use axum::{
     debug_handler,
@@ -43,6 +45,7 @@ use database::{
     SchemaModel,
 };
 use errors::ErrorMetadata;
+use keybroker::AdminOperation;
 use serde::{
     Deserialize,
     Serialize,
@@ -61,7 +64,7 @@ use value::{
 use crate::{
     admin::{
         must_be_admin,
-        must_be_admin_from_key_with_write_access,
+        must_be_admin_from_key_for,
     },
     authentication::ExtractIdentity,
     deploy_config::ModuleJson,
@@ -107,7 +110,8 @@ pub struct IndexMetadataResponse {
     table: String,
     name: String,
     // Either an array of fields (`string[]`) for a database index or an object of
-    // `{ searchField: string, filterFields: string }` for a search index.
+    // `{ searchField: string, additionalSearchFields: string[], searchFieldBoosts:
+    // Record<string, number>, filterFields: string }` for a search index.
     fields: JsonValue,
     backfill: BackfillResponse,
 }
@@ -149,9 +153,19 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                 developer_config:
                     DeveloperTextIndexConfig {
                         search_field,
+                        additional_search_fields,
+                        search_field_boosts,
                         filter_fields,
                     },
             } => {
+                let additional_search_fields: Vec<_> = additional_search_fields
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                let search_field_boosts: BTreeMap<_, _> = search_field_boosts
+                    .into_iter()
+                    .map(|(field_path, boost)| (String::from(field_path), boost))
+                    .collect();
                 let backfill_state = match on_disk_state {
                     TextIndexState::Backfilling(_) => "in_progress".to_string(),
                     // TODO(CX-3851): The result of this is used to poll for state in the CLI and
@@ -167,6 +181,8 @@ impl TryFrom<IndexMetadata<TableName>> for IndexMetadataResponse {
                     name,
                     fields: json!({
                         "searchField":  String::from(search_field),
+                        "additionalSearchFields": additional_search_fields,
+                        "searchFieldBoosts": search_field_boosts,
                         "filterFields": filter_fields.into_iter().map(String::from).collect::<Vec<_>>()
                     }),
                     backfill: BackfillResponse {
@@ -256,10 +272,11 @@ pub async fn prepare_schema_handler(
     req: PrepareSchemaArgs,
 ) -> Result<(Json<PrepareSchemaResponse>, bool), HttpResponseError> {
     let bundle = req.bundle.try_into()?;
-    let identity = must_be_admin_from_key_with_write_access(
+    let identity = must_be_admin_from_key_for(
         st.application.app_auth(),
         st.instance_name.clone(),
         req.admin_key,
+        AdminOperation::Deploy,
     )
     .await?;
     let schema = match st.application.evaluate_schema(bundle).await {
@@ -0,0 +1,163 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    errors::report_error,
+    http::{
+        extract::{
+            Json,
+            Query,
+        },
+        HttpResponseError,
+    },
+    types::IndexName,
+};
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    search_index_pins::SearchIndexPinsModel,
+};
+use search::searcher::SegmentResidence;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::TableName;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexCacheStatusArgs {
+    table_name: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentCacheStatusJson {
+    /// "memory", "disk" or "remote".
+    residence: &'static str,
+    pinned: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexCacheStatusJson {
+    index_name: String,
+    pinned: bool,
+    segments: Vec<SegmentCacheStatusJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexCacheStatusResponse {
+    indexes: Vec<SearchIndexCacheStatusJson>,
+}
+
+/// Whether each segment of a table's text and vector indexes is loaded in
+/// the searcher's memory, on its disk, or only in search storage, where the
+/// next query has to fetch it from.
+pub async fn search_index_cache_status(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Query(SearchIndexCacheStatusArgs { table_name }): Query<SearchIndexCacheStatusArgs>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let table_name: TableName = table_name.parse()?;
+    let statuses = st
+        .application
+        .search_index_cache_status(identity, &table_name)
+        .await?;
+    let indexes = statuses
+        .into_iter()
+        .map(|status| SearchIndexCacheStatusJson {
+            index_name: status.index_name.descriptor().to_string(),
+            pinned: status.pinned,
+            segments: status
+                .segments
+                .into_iter()
+                .map(|segment| SegmentCacheStatusJson {
+                    residence: match segment.residence {
+                        SegmentResidence::Memory => "memory",
+                        SegmentResidence::Disk => "disk",
+                        SegmentResidence::Remote => "remote",
+                    },
+                    pinned: segment.pinned,
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(Json(SearchIndexCacheStatusResponse { indexes }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndexPinRequest {
+    table_name: String,
+    /// The index's name in the schema, e.g. "search_body".
+    index_name: String,
+}
+
+impl SearchIndexPinRequest {
+    fn index_name(&self) -> anyhow::Result<IndexName> {
+        IndexName::new(self.table_name.parse()?, self.index_name.parse()?)
+    }
+}
+
+/// Keeps a text or vector index's segments loaded in the searcher, e.g. for
+/// latency-critical tables. The pin lasts across deploys and restarts until
+/// the index is unpinned, and its segments are loaded before this returns.
+pub async fn pin_search_index(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(req): Json<SearchIndexPinRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let index_name = req.index_name()?;
+    let event = DeploymentAuditLogEvent::PinSearchIndex {
+        table_name: req.table_name,
+        index_name: req.index_name,
+    };
+    let mut tx = st.application.begin(identity).await?;
+    SearchIndexPinsModel::new(&mut tx)
+        .pin(index_name.clone())
+        .await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "pin_search_index")
+        .await?;
+    // The pin is saved, so the search index pin worker retries if this fails.
+    if let Err(e) = st.application.prewarm_search_index(&index_name).await {
+        report_error(&mut e.context(format!("Failed to prewarm search index {index_name}")));
+    }
+    Ok(StatusCode::OK)
+}
+
+/// Lets an index's segments be evicted like any others again.
+pub async fn unpin_search_index(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(req): Json<SearchIndexPinRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let index_name = req.index_name()?;
+    let event = DeploymentAuditLogEvent::UnpinSearchIndex {
+        table_name: req.table_name,
+        index_name: req.index_name,
+    };
+    let mut tx = st.application.begin(identity).await?;
+    SearchIndexPinsModel::new(&mut tx)
+        .unpin(&index_name)
+        .await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "unpin_search_index")
+        .await?;
+    Ok(StatusCode::OK)
+}
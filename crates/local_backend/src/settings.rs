@@ -0,0 +1,419 @@
+//! Settings that can be changed while the backend is running, through the
+//! admin API or a settings file, since restarting the backend drops every
+//! WebSocket session. An update is validated as a whole before any of it is
+//! applied, and each changed setting is recorded in the deployment audit log.
+//!
+//! Log sinks are already configured at runtime, through their own admin API.
+
+use std::{
+    fs,
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+use anyhow::Context;
+use application::application_function_runner::FunctionConcurrencyLimits;
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    errors::report_error,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+    knobs::{
+        HTTP_ACTION_RATE_LIMIT,
+        PUBLIC_API_RATE_LIMIT,
+    },
+    runtime::Runtime,
+};
+use errors::ErrorMetadata;
+use futures::FutureExt;
+use keybroker::Identity;
+use model::deployment_audit_log::types::DeploymentAuditLogEvent;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    rate_limiter::RateLimitConfig,
+    LocalAppState,
+};
+
+/// Upper bound on each of the function concurrency limits, so a typo can't
+/// make the backend run far more functions at once than it has memory for.
+const MAX_CONCURRENT_FUNCTIONS: usize = 1024;
+
+/// How often the settings file is checked for changes.
+const SETTINGS_FILE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The backend's current settings. They start out as the values of the knobs
+/// of the same names.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendSettings {
+    pub max_concurrent_queries: usize,
+    pub max_concurrent_mutations: usize,
+    pub max_concurrent_v8_actions: usize,
+    pub max_concurrent_node_actions: usize,
+    /// Rate limit in the format of `PUBLIC_API_RATE_LIMIT`, or empty if
+    /// disabled.
+    pub public_api_rate_limit: String,
+    pub http_action_rate_limit: String,
+}
+
+/// A change to some of the settings. Settings that are left out keep their
+/// current value.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BackendSettingsUpdate {
+    pub max_concurrent_queries: Option<usize>,
+    pub max_concurrent_mutations: Option<usize>,
+    pub max_concurrent_v8_actions: Option<usize>,
+    pub max_concurrent_node_actions: Option<usize>,
+    pub public_api_rate_limit: Option<String>,
+    pub http_action_rate_limit: Option<String>,
+}
+
+impl BackendSettings {
+    pub fn new(limits: FunctionConcurrencyLimits) -> Self {
+        Self {
+            max_concurrent_queries: limits.queries,
+            max_concurrent_mutations: limits.mutations,
+            max_concurrent_v8_actions: limits.v8_actions,
+            max_concurrent_node_actions: limits.node_actions,
+            public_api_rate_limit: PUBLIC_API_RATE_LIMIT.clone().unwrap_or_default(),
+            http_action_rate_limit: HTTP_ACTION_RATE_LIMIT.clone().unwrap_or_default(),
+        }
+    }
+
+    fn updated(&self, update: BackendSettingsUpdate) -> Self {
+        let current = self.clone();
+        Self {
+            max_concurrent_queries: update
+                .max_concurrent_queries
+                .unwrap_or(current.max_concurrent_queries),
+            max_concurrent_mutations: update
+                .max_concurrent_mutations
+                .unwrap_or(current.max_concurrent_mutations),
+            max_concurrent_v8_actions: update
+                .max_concurrent_v8_actions
+                .unwrap_or(current.max_concurrent_v8_actions),
+            max_concurrent_node_actions: update
+                .max_concurrent_node_actions
+                .unwrap_or(current.max_concurrent_node_actions),
+            public_api_rate_limit: update
+                .public_api_rate_limit
+                .map_or(current.public_api_rate_limit, |limit| {
+                    limit.trim().to_string()
+                }),
+            http_action_rate_limit: update
+                .http_action_rate_limit
+                .map_or(current.http_action_rate_limit, |limit| {
+                    limit.trim().to_string()
+                }),
+        }
+    }
+
+    fn concurrency_limits(&self) -> FunctionConcurrencyLimits {
+        FunctionConcurrencyLimits {
+            queries: self.max_concurrent_queries,
+            mutations: self.max_concurrent_mutations,
+            v8_actions: self.max_concurrent_v8_actions,
+            node_actions: self.max_concurrent_node_actions,
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let invalid = |msg: String| ErrorMetadata::bad_request("InvalidBackendSettings", msg);
+        for (name, limit) in [
+            ("maxConcurrentQueries", self.max_concurrent_queries),
+            ("maxConcurrentMutations", self.max_concurrent_mutations),
+            ("maxConcurrentV8Actions", self.max_concurrent_v8_actions),
+            ("maxConcurrentNodeActions", self.max_concurrent_node_actions),
+        ] {
+            if !(1..=MAX_CONCURRENT_FUNCTIONS).contains(&limit) {
+                anyhow::bail!(invalid(format!(
+                    "{name} must be between 1 and {MAX_CONCURRENT_FUNCTIONS}, not {limit}"
+                )));
+            }
+        }
+        for (name, limit) in [
+            ("publicApiRateLimit", &self.public_api_rate_limit),
+            ("httpActionRateLimit", &self.http_action_rate_limit),
+        ] {
+            if let Err(e) = parse_rate_limit(limit) {
+                anyhow::bail!(invalid(format!("Invalid {name} {limit:?}: {e:#}")));
+            }
+        }
+        Ok(())
+    }
+
+    /// An audit log event for each setting that's different in `new`.
+    fn changes(&self, new: &Self) -> anyhow::Result<Vec<DeploymentAuditLogEvent>> {
+        let (JsonValue::Object(previous), JsonValue::Object(new)) =
+            (serde_json::to_value(self)?, serde_json::to_value(new)?)
+        else {
+            anyhow::bail!("Settings didn't serialize to objects");
+        };
+        Ok(previous
+            .into_iter()
+            .zip(new)
+            .filter(|((_, previous_value), (_, value))| previous_value != value)
+            .map(|((name, previous_value), (_, value))| {
+                DeploymentAuditLogEvent::UpdateBackendSetting {
+                    name,
+                    previous_value: format_value(previous_value),
+                    value: format_value(value),
+                }
+            })
+            .collect())
+    }
+}
+
+fn format_value(value: JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s,
+        value => value.to_string(),
+    }
+}
+
+fn parse_rate_limit(limit: &str) -> anyhow::Result<Option<RateLimitConfig>> {
+    if limit.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(limit.parse()?))
+}
+
+/// Validate and apply `update`, returning the new settings. Updates are
+/// applied one at a time, so each one's audit log events describe the
+/// change from the settings before it.
+pub async fn update_backend_settings(
+    st: &LocalAppState,
+    identity: Identity,
+    update: BackendSettingsUpdate,
+) -> anyhow::Result<BackendSettings> {
+    let mut settings = st.settings.lock().await;
+    let new_settings = settings.updated(update);
+    new_settings.validate()?;
+    let events = settings.changes(&new_settings)?;
+    if events.is_empty() {
+        return Ok(new_settings);
+    }
+    let tx = st.application.begin(identity).await?;
+    st.application
+        .commit_with_audit_log_events(tx, events, "update_backend_settings")
+        .await?;
+
+    st.application
+        .runner()
+        .set_concurrency_limits(new_settings.concurrency_limits());
+    st.public_api_rate_limiter
+        .set_config(parse_rate_limit(&new_settings.public_api_rate_limit)?);
+    st.http_action_rate_limiter
+        .set_config(parse_rate_limit(&new_settings.http_action_rate_limit)?);
+    tracing::info!("Updated backend settings to {new_settings:?}");
+    *settings = new_settings.clone();
+    Ok(new_settings)
+}
+
+pub async fn get_backend_settings(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let settings = st.settings.lock().await.clone();
+    Ok(Json(settings))
+}
+
+pub async fn update_backend_settings_handler(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(update): Json<BackendSettingsUpdate>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let settings = update_backend_settings(&st, identity, update).await?;
+    Ok(Json(settings))
+}
+
+fn read_settings_file(path: &Path) -> anyhow::Result<BackendSettingsUpdate> {
+    let contents = fs::read(path)?;
+    Ok(serde_json::from_slice(&contents)?)
+}
+
+/// Apply the settings in the JSON file at `path`, in the format of the
+/// update endpoint, whenever it changes, until the backend shuts down. The
+/// file is applied before this returns the first time, and an invalid file
+/// then fails startup. Later invalid files are reported and ignored.
+pub async fn watch_settings_file(
+    st: LocalAppState,
+    path: PathBuf,
+    mut shutdown_rx: async_broadcast::Receiver<()>,
+) -> anyhow::Result<()> {
+    let modified = |path: &Path| -> Option<SystemTime> { fs::metadata(path).ok()?.modified().ok() };
+    let mut last_modified = modified(&path);
+    let update = read_settings_file(&path)
+        .with_context(|| format!("Failed to read backend settings from {path:?}"))?;
+    update_backend_settings(&st, Identity::system(), update).await?;
+    let runtime = st.application.runtime();
+    loop {
+        futures::select_biased! {
+            _ = shutdown_rx.recv().fuse() => return Ok(()),
+            _ = runtime.wait(SETTINGS_FILE_POLL_INTERVAL) => (),
+        }
+        let current_modified = modified(&path);
+        if current_modified == last_modified {
+            continue;
+        }
+        last_modified = current_modified;
+        let result: anyhow::Result<()> = try {
+            let update = read_settings_file(&path)?;
+            update_backend_settings(&st, Identity::system(), update).await?;
+            tracing::info!("Reloaded backend settings from {path:?}");
+        };
+        if let Err(e) = result {
+            report_error(
+                &mut e.context(format!("Failed to reload backend settings from {path:?}")),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use model::deployment_audit_log::types::DeploymentAuditLogEvent;
+    use runtime::prod::ProdRuntime;
+    use serde_json::json;
+
+    use super::{
+        BackendSettings,
+        BackendSettingsUpdate,
+    };
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[test]
+    fn test_settings_changes() -> anyhow::Result<()> {
+        let settings = BackendSettings {
+            max_concurrent_queries: 16,
+            max_concurrent_mutations: 16,
+            max_concurrent_v8_actions: 16,
+            max_concurrent_node_actions: 16,
+            public_api_rate_limit: "".to_string(),
+            http_action_rate_limit: "10/s".to_string(),
+        };
+        let updated = settings.updated(BackendSettingsUpdate {
+            max_concurrent_queries: Some(32),
+            public_api_rate_limit: Some(" 100/s ".to_string()),
+            http_action_rate_limit: Some("10/s".to_string()),
+            ..Default::default()
+        });
+        updated.validate()?;
+        assert_eq!(
+            settings.changes(&updated)?,
+            vec![
+                DeploymentAuditLogEvent::UpdateBackendSetting {
+                    name: "maxConcurrentQueries".to_string(),
+                    previous_value: "16".to_string(),
+                    value: "32".to_string(),
+                },
+                DeploymentAuditLogEvent::UpdateBackendSetting {
+                    name: "publicApiRateLimit".to_string(),
+                    previous_value: "".to_string(),
+                    value: "100/s".to_string(),
+                },
+            ]
+        );
+
+        for invalid in [
+            BackendSettingsUpdate {
+                max_concurrent_mutations: Some(0),
+                ..Default::default()
+            },
+            BackendSettingsUpdate {
+                max_concurrent_node_actions: Some(100_000),
+                ..Default::default()
+            },
+            BackendSettingsUpdate {
+                http_action_rate_limit: Some("10/d".to_string()),
+                ..Default::default()
+            },
+        ] {
+            assert!(settings.updated(invalid).validate().is_err());
+        }
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_update_backend_settings(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let update = |body: serde_json::Value| -> anyhow::Result<Request<Body>> {
+            Ok(Request::builder()
+                .uri("/api/update_backend_settings")
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header("Authorization", backend.admin_auth_header.0.encode())
+                .body(Body::from(serde_json::to_vec(&body)?))?)
+        };
+
+        let settings: BackendSettings = backend
+            .expect_success(update(json!({
+                "maxConcurrentQueries": 4,
+                "publicApiRateLimit": "100/s",
+            }))?)
+            .await?;
+        assert_eq!(settings.max_concurrent_queries, 4);
+        assert_eq!(
+            backend.st.application.runner().concurrency_limits().queries,
+            4
+        );
+        assert_eq!(
+            backend.st.public_api_rate_limiter.config(),
+            Some("100/s".parse()?)
+        );
+
+        // Nothing is applied if any setting is invalid.
+        backend
+            .expect_error(
+                update(json!({
+                    "maxConcurrentQueries": 8,
+                    "publicApiRateLimit": "100/d",
+                }))?,
+                StatusCode::BAD_REQUEST,
+                "InvalidBackendSettings",
+            )
+            .await?;
+        assert_eq!(
+            backend.st.application.runner().concurrency_limits().queries,
+            4
+        );
+
+        let req = Request::builder()
+            .uri("/api/backend_settings")
+            .method("GET")
+            .header("Authorization", backend.admin_auth_header.0.encode())
+            .body(Body::empty())?;
+        let current: BackendSettings = backend.expect_success(req).await?;
+        assert_eq!(current, settings);
+        Ok(())
+    }
+}
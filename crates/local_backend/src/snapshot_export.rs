@@ -1,4 +1,10 @@
-use std::time::Duration;
+use std::{
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
+    time::Duration,
+};
 
 use anyhow::Context;
 use axum::{
@@ -21,9 +27,18 @@ use common::http::{
 };
 use errors::ErrorMetadata;
 use http::StatusCode;
+use model::exports::types::{
+    ExportFormat,
+    ExportSelection,
+    FileStorageLayout,
+};
 use serde::Deserialize;
 use storage::StorageGetStream;
 use sync_types::Timestamp;
+use value::{
+    FieldName,
+    TableName,
+};
 
 use crate::{
     admin::must_be_admin_with_write_access,
@@ -35,13 +50,102 @@ use crate::{
 // Export GETs are immutable. Browser can cache for a long time.
 const MAX_CACHE_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
 
+const MANIFEST_FILE_NAME: &str = "_manifest.json";
+
+/// Narrows an export down from every field of every table, e.g.
+/// `?tables=users,messages&excludeFields=users.email,users.passwordHash`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSelectionArgs {
+    // Comma separated names of the tables to export.
+    tables: Option<String>,
+    // Comma separated `table.field` pairs to leave out of the export.
+    exclude_fields: Option<String>,
+}
+
+impl TryFrom<ExportSelectionArgs> for ExportSelection {
+    type Error = anyhow::Error;
+
+    fn try_from(args: ExportSelectionArgs) -> anyhow::Result<Self> {
+        let invalid = |msg: String| {
+            anyhow::anyhow!(ErrorMetadata::bad_request("InvalidExportSelection", msg))
+        };
+        let tables = args
+            .tables
+            .map(|tables| {
+                tables
+                    .split(',')
+                    .map(|table| {
+                        table
+                            .trim()
+                            .parse::<TableName>()
+                            .map_err(|e| invalid(format!("Invalid table name {table:?}: {e}")))
+                    })
+                    .collect::<anyhow::Result<BTreeSet<_>>>()
+            })
+            .transpose()?;
+        let mut excluded_fields: BTreeMap<TableName, BTreeSet<FieldName>> = BTreeMap::new();
+        for pair in args
+            .exclude_fields
+            .iter()
+            .flat_map(|pairs| pairs.split(','))
+        {
+            let pair = pair.trim();
+            let (table, field) = pair.split_once('.').ok_or_else(|| {
+                invalid(format!(
+                    "Invalid excluded field {pair:?}, expected `table.field`"
+                ))
+            })?;
+            let table = table
+                .parse()
+                .map_err(|e| invalid(format!("Invalid table name {table:?}: {e}")))?;
+            let field = field
+                .parse()
+                .map_err(|e| invalid(format!("Invalid field name {field:?}: {e}")))?;
+            excluded_fields.entry(table).or_default().insert(field);
+        }
+        Ok(ExportSelection {
+            tables,
+            excluded_fields,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RequestExport {
+    // `ndjson` or `parquet` for a file per table and a manifest of their
+    // columns. Otherwise each table is exported as JSON.
+    format: Option<String>,
+}
+
+fn parse_export_format(format: Option<String>) -> anyhow::Result<Option<ExportFormat>> {
+    let Some(format) = format else {
+        return Ok(None);
+    };
+    let format = match &format[..] {
+        "ndjson" => ExportFormat::Ndjson,
+        "parquet" => ExportFormat::Parquet,
+        _ => anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidExportFormat",
+            format!("Invalid export format {format:?}, expected `ndjson` or `parquet`"),
+        )),
+    };
+    Ok(Some(format))
+}
+
 pub async fn request_export(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
+    Query(RequestExport { format }): Query<RequestExport>,
+    Query(selection): Query<ExportSelectionArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
     st.application
-        .request_export(identity, false, false)
+        .request_export(
+            identity,
+            parse_export_format(format)?,
+            selection.try_into()?,
+        )
         .await?;
     Ok(StatusCode::OK)
 }
@@ -51,17 +155,42 @@ pub async fn request_export(
 pub struct RequestZipExport {
     #[serde(default)]
     include_storage: bool,
+    /// Where file contents go when `include_storage` is set: `in_zip` (the
+    /// default) or `separate_objects`.
+    storage_layout: Option<String>,
 }
 
 #[minitrace::trace]
 pub async fn request_zip_export(
     State(st): State<LocalAppState>,
     ExtractIdentity(identity): ExtractIdentity,
-    Query(RequestZipExport { include_storage }): Query<RequestZipExport>,
+    Query(RequestZipExport {
+        include_storage,
+        storage_layout,
+    }): Query<RequestZipExport>,
+    Query(selection): Query<ExportSelectionArgs>,
 ) -> Result<impl IntoResponse, HttpResponseError> {
     must_be_admin_with_write_access(&identity)?;
+    let storage_layout: FileStorageLayout = match storage_layout {
+        Some(storage_layout) => storage_layout.parse()?,
+        None => FileStorageLayout::default(),
+    };
+    if !include_storage && storage_layout != FileStorageLayout::InZip {
+        return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidFileStorageLayout",
+            "A file storage layout can only be set when the export includes file storage",
+        ))
+        .into());
+    }
     st.application
-        .request_export(identity, true, include_storage)
+        .request_export(
+            identity,
+            Some(ExportFormat::Zip {
+                include_storage,
+                storage_layout,
+            }),
+            selection.try_into()?,
+        )
         .await?;
     Ok(StatusCode::OK)
 }
@@ -70,7 +199,8 @@ pub async fn request_zip_export(
 pub struct ExportRequest {
     // Timestamp the snapshot export started at
     snapshot_ts: String,
-    // Table to get the export for
+    // Table to get the export for, or `_manifest.json` for the manifest of an
+    // NDJSON or Parquet export
     table_name: String,
 }
 
@@ -88,25 +218,23 @@ pub async fn get_export(
         "BadSnapshotTimestamp",
         "Snapshot timestamp did not parse to a timestamp.",
     ))?;
-    let extension = if file_name.ends_with(".json") {
-        Some(".json")
-    } else if file_name.ends_with(".jsonl") {
-        Some(".jsonl")
-    } else {
-        None
-    }
-    .context(ErrorMetadata::bad_request(
-        "BadSnapshotFilename",
-        "Snapshot filename must be {table}.json(l)",
-    ))?;
-    let table_name = file_name.strip_suffix(extension).unwrap();
     let StorageGetStream {
         content_length,
         stream,
-    } = st
-        .application
-        .get_export(identity, ts, table_name.parse()?)
-        .await?;
+    } = if file_name == MANIFEST_FILE_NAME {
+        st.application.get_export_manifest(identity, ts).await?
+    } else {
+        let table_name = [".json", ".jsonl", ".ndjson", ".parquet"]
+            .into_iter()
+            .find_map(|extension| file_name.strip_suffix(extension))
+            .context(ErrorMetadata::bad_request(
+                "BadSnapshotFilename",
+                "Snapshot filename must be {table}.json(l), {table}.ndjson or {table}.parquet",
+            ))?;
+        st.application
+            .get_export(identity, ts, table_name.parse()?)
+            .await?
+    };
     let content_length = ContentLength(content_length as u64);
     Ok((
         TypedHeader(content_length),
@@ -164,3 +292,59 @@ pub async fn get_zip_export(
         StreamBody::new(stream),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use maplit::{
+        btreemap,
+        btreeset,
+    };
+    use model::exports::types::{
+        ExportFormat,
+        ExportSelection,
+    };
+
+    use super::{
+        parse_export_format,
+        ExportSelectionArgs,
+    };
+
+    #[test]
+    fn test_parse_export_selection() -> anyhow::Result<()> {
+        let selection = ExportSelection::try_from(ExportSelectionArgs {
+            tables: Some("users, messages".to_string()),
+            exclude_fields: Some("users.email,users.passwordHash".to_string()),
+        })?;
+        assert_eq!(
+            selection,
+            ExportSelection {
+                tables: Some(btreeset! { "users".parse()?, "messages".parse()? }),
+                excluded_fields: btreemap! {
+                    "users".parse()? => btreeset! { "email".parse()?, "passwordHash".parse()? },
+                },
+            }
+        );
+        let selection = ExportSelection::try_from(ExportSelectionArgs {
+            tables: None,
+            exclude_fields: None,
+        })?;
+        assert_eq!(selection, ExportSelection::default());
+        assert!(ExportSelection::try_from(ExportSelectionArgs {
+            tables: None,
+            exclude_fields: Some("email".to_string()),
+        })
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_export_format() -> anyhow::Result<()> {
+        assert_eq!(parse_export_format(None)?, None);
+        assert_eq!(
+            parse_export_format(Some("parquet".to_string()))?,
+            Some(ExportFormat::Parquet)
+        );
+        assert!(parse_export_format(Some("csv".to_string())).is_err());
+        Ok(())
+    }
+}
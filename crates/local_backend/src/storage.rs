@@ -22,6 +22,8 @@ use axum::{
         ContentLength,
         ContentType,
         Header,
+        IfModifiedSince,
+        IfNoneMatch,
         Range,
     },
     response::{
@@ -42,21 +44,37 @@ use common::{
         HttpResponseError,
     },
     sha256::DigestHeader,
+    RequestId,
 };
 use errors::ErrorMetadata;
 use file_storage::{
+    ConditionalFile,
+    FileConditions,
     FileRangeStream,
     FileStream,
+    FileValidators,
+    UploadSessionStatus,
+    UploadedPart,
+    MAX_UPLOAD_PART_SIZE,
 };
 use futures::StreamExt;
-use http::StatusCode;
+use http::{
+    header,
+    StatusCode,
+};
 use model::file_storage::FileStorageId;
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::RouterState;
+use crate::{
+    image_transform::{
+        ImageTransform,
+        ImageTransformParams,
+    },
+    RouterState,
+};
 
 // Storage GETs are immutable. Browser can cache for a long time.
 const MAX_CACHE_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 30);
@@ -92,7 +110,8 @@ pub async fn storage_upload(
     ExtractRequestId(request_id): ExtractRequestId,
     body: BodyStream,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    st.api
+    let constraints = st
+        .api
         .check_store_file_authorization(
             &host,
             request_id.clone(),
@@ -104,7 +123,21 @@ pub async fn storage_upload(
     let content_length = map_header_err(content_length)?;
     let content_type = map_header_err(content_type)?;
     let sha256 = map_header_err(sha256)?.map(|dh| dh.0);
-    let body = body.map(|r| r.context("Error parsing body")).boxed();
+    constraints.check_content_type(content_type.as_ref().map(|ct| ct.to_string()).as_deref())?;
+    if let Some(ContentLength(content_length)) = content_length {
+        constraints.check_size(content_length)?;
+    }
+    // The Content-Length header is optional, so also count the bytes as they
+    // arrive.
+    let mut received = 0;
+    let body = body
+        .map(move |r| {
+            let chunk = r.context("Error parsing body")?;
+            received += chunk.len() as u64;
+            constraints.check_size(received)?;
+            Ok(chunk)
+        })
+        .boxed();
     let storage_id = st
         .api
         .store_file(
@@ -128,12 +161,147 @@ pub async fn storage_upload(
     }))
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadSessionResponse {
+    upload_id: String,
+}
+
+/// Start a resumable upload. The file's parts are uploaded with
+/// `storage_upload_session_part` and assembled by
+/// `storage_finish_upload_session`. The Content-Type and Digest headers
+/// describe the whole file.
+#[debug_handler]
+pub async fn storage_create_upload_session(
+    State(st): State<RouterState>,
+    Query(QueryParams { token }): Query<QueryParams>,
+    content_type: Result<TypedHeader<ContentType>, TypedHeaderRejection>,
+    sha256: Result<TypedHeader<DigestHeader>, TypedHeaderRejection>,
+    Host(host): Host,
+    ExtractRequestId(request_id): ExtractRequestId,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let constraints = st
+        .api
+        .check_store_file_authorization(
+            &host,
+            request_id.clone(),
+            &token,
+            STORE_FILE_AUTHORIZATION_VALIDITY,
+        )
+        .await?;
+    let content_type = map_header_err(content_type)?;
+    let sha256 = map_header_err(sha256)?.map(|dh| dh.0);
+    let upload_id = st
+        .api
+        .create_upload_session(&host, request_id, content_type, sha256, constraints)
+        .await?;
+    Ok(Json(UploadSessionResponse { upload_id }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedPartResponse {
+    part_number: u16,
+    size: u64,
+}
+
+/// Upload one part of a resumable upload. Uploading a part again replaces it.
+#[debug_handler]
+pub async fn storage_upload_session_part(
+    State(st): State<RouterState>,
+    Path((upload_id, part_number)): Path<(String, u16)>,
+    Host(host): Host,
+    ExtractRequestId(request_id): ExtractRequestId,
+    mut body: BodyStream,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let mut part = Vec::new();
+    while let Some(chunk) = body.next().await {
+        part.extend_from_slice(&chunk.context("Error parsing body")?);
+        if part.len() > MAX_UPLOAD_PART_SIZE {
+            return Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+                "InvalidPartSize",
+                format!("Parts can be at most {MAX_UPLOAD_PART_SIZE} bytes"),
+            ))
+            .into());
+        }
+    }
+    let size = part.len() as u64;
+    st.api
+        .upload_session_part(&host, request_id, &upload_id, part_number, part.into())
+        .await?;
+    Ok(Json(UploadedPartResponse { part_number, size }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadSessionStatusResponse {
+    /// The uploaded parts, ordered by part number. Parts missing from this
+    /// list need to be uploaded before the session is finished.
+    parts: Vec<UploadedPartResponse>,
+    /// Nanoseconds since the Unix epoch.
+    expires_ts: i64,
+    /// Set once the session is finished.
+    storage_id: Option<String>,
+}
+
+#[debug_handler]
+pub async fn storage_upload_session_status(
+    State(st): State<RouterState>,
+    Path(upload_id): Path<String>,
+    Host(host): Host,
+    ExtractRequestId(request_id): ExtractRequestId,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let UploadSessionStatus {
+        parts,
+        expires_ts,
+        storage_id,
+    } = st
+        .api
+        .upload_session_status(&host, request_id, &upload_id)
+        .await?;
+    Ok(Json(UploadSessionStatusResponse {
+        parts: parts
+            .into_iter()
+            .map(|UploadedPart { part_number, size }| UploadedPartResponse { part_number, size })
+            .collect(),
+        expires_ts: expires_ts.into(),
+        storage_id: storage_id.map(|id| id.to_string()),
+    }))
+}
+
+/// Assemble the parts of a resumable upload into a file.
+#[debug_handler]
+pub async fn storage_finish_upload_session(
+    State(st): State<RouterState>,
+    Path(upload_id): Path<String>,
+    Host(host): Host,
+    ExtractRequestId(request_id): ExtractRequestId,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    let component = ComponentId::TODO();
+    let storage_id = st
+        .api
+        .finish_upload_session(&host, request_id, component, &upload_id)
+        .await?;
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Response {
+        storage_id: String,
+    }
+    Ok(Json(Response {
+        storage_id: storage_id.to_string(),
+    }))
+}
+
 #[debug_handler]
 pub async fn storage_get(
     State(st): State<RouterState>,
     Path(uuid): Path<String>,
     // Query(QueryParams { token }): Query<QueryParams>,
+    Query(image_transform_params): Query<ImageTransformParams>,
     range: Result<TypedHeader<Range>, TypedHeaderRejection>,
+    if_none_match: Result<TypedHeader<IfNoneMatch>, TypedHeaderRejection>,
+    if_modified_since: Result<TypedHeader<IfModifiedSince>, TypedHeaderRejection>,
     Host(host): Host,
     ExtractRequestId(request_id): ExtractRequestId,
 ) -> Result<Response, HttpResponseError> {
@@ -143,9 +311,29 @@ pub async fn storage_get(
     ))?;
     let file_storage_id = FileStorageId::LegacyStorageId(storage_uuid);
     let component = ComponentId::TODO();
+    // Malformed conditions are ignored, which returns the whole file.
+    let conditions = FileConditions {
+        if_none_match: if_none_match.ok().map(|h| h.0),
+        if_modified_since: if_modified_since.ok().map(|h| h.0),
+    };
+    let cache_control = cache_control();
 
     // TODO(CX-3065) figure out deterministic repeatable tokens
 
+    // Transformed images are always returned whole, so the range is ignored.
+    if let Some(transform) = ImageTransform::from_params(image_transform_params)? {
+        return Ok(get_transformed_image(
+            &st,
+            &host,
+            request_id,
+            component,
+            file_storage_id,
+            transform,
+            conditions,
+        )
+        .await?);
+    }
+
     if let Ok(range_header) = range {
         let ranges: Vec<(Bound<u64>, Bound<u64>)> = range_header.iter().collect();
         // Convex only supports a single range because underlying AWS S3 only supports
@@ -157,52 +345,462 @@ pub async fn storage_get(
         }
         let range = ranges[0];
 
-        let FileRangeStream {
-            content_length,
-            content_range,
-            content_type,
-            stream,
-        } = st
+        let (
+            FileValidators {
+                etag,
+                last_modified,
+                ..
+            },
+            FileRangeStream {
+                content_length,
+                content_range,
+                content_type,
+                stream,
+            },
+        ) = match st
             .api
-            .get_file_range(&host, request_id, component, file_storage_id, range)
-            .await?;
+            .get_file_range(
+                &host,
+                request_id,
+                component,
+                file_storage_id,
+                range,
+                conditions,
+            )
+            .await?
+        {
+            ConditionalFile::NotModified(validators) => {
+                return Ok(not_modified(validators, cache_control));
+            },
+            ConditionalFile::Modified(validators, stream) => (validators, stream),
+        };
 
         return Ok((
             StatusCode::PARTIAL_CONTENT,
             content_type.map(TypedHeader),
             TypedHeader(content_range),
             TypedHeader(content_length),
-            TypedHeader(
-                CacheControl::new()
-                    .with_private()
-                    .with_max_age(MAX_CACHE_AGE),
-            ),
+            TypedHeader(etag),
+            TypedHeader(last_modified),
+            cache_control,
             TypedHeader(AcceptRanges::bytes()),
             StreamBody::new(stream),
         )
             .into_response());
     }
 
-    let FileStream {
-        sha256,
-        content_type,
-        content_length,
-        stream,
-    } = st
+    let (
+        FileValidators {
+            etag,
+            last_modified,
+            ..
+        },
+        FileStream {
+            sha256,
+            content_type,
+            content_length,
+            stream,
+        },
+    ) = match st
         .api
-        .get_file(&host, request_id, component, file_storage_id)
-        .await?;
+        .get_file(&host, request_id, component, file_storage_id, conditions)
+        .await?
+    {
+        ConditionalFile::NotModified(validators) => {
+            return Ok(not_modified(validators, cache_control));
+        },
+        ConditionalFile::Modified(validators, stream) => (validators, stream),
+    };
     Ok((
         TypedHeader(DigestHeader(sha256)),
         content_type.map(TypedHeader),
         TypedHeader(content_length),
-        TypedHeader(
-            CacheControl::new()
-                .with_private()
-                .with_max_age(MAX_CACHE_AGE),
-        ),
+        TypedHeader(etag),
+        TypedHeader(last_modified),
+        cache_control,
         TypedHeader(AcceptRanges::bytes()),
         StreamBody::new(stream),
     )
         .into_response())
 }
+
+async fn get_transformed_image(
+    st: &RouterState,
+    host: &str,
+    request_id: RequestId,
+    component: ComponentId,
+    file_storage_id: FileStorageId,
+    transform: ImageTransform,
+    conditions: FileConditions,
+) -> anyhow::Result<Response> {
+    // `If-None-Match: *` matches any file, so this looks up the file's
+    // validators without reading it from storage.
+    let validators = match st
+        .api
+        .get_file(
+            host,
+            request_id.clone(),
+            component,
+            file_storage_id.clone(),
+            FileConditions {
+                if_none_match: Some(IfNoneMatch::any()),
+                if_modified_since: None,
+            },
+        )
+        .await?
+    {
+        ConditionalFile::NotModified(validators) | ConditionalFile::Modified(validators, _) => {
+            validators
+        },
+    };
+    let validators = validators.for_variant(&transform.to_string())?;
+    if conditions.is_not_modified(&validators) {
+        return Ok(not_modified(validators, cache_control()));
+    }
+
+    let image_transformer = &st.image_transformer;
+    let image = match image_transformer.cached(&validators.sha256, &transform) {
+        Some(image) => image,
+        None => {
+            let ConditionalFile::Modified(_, file) = st
+                .api
+                .get_file(
+                    host,
+                    request_id,
+                    component,
+                    file_storage_id,
+                    FileConditions::default(),
+                )
+                .await?
+            else {
+                anyhow::bail!("File read without conditions was not modified");
+            };
+            if file.content_length.0 > image_transformer.max_source_size as u64 {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "ImageTooLarge",
+                    format!(
+                        "Images larger than {} bytes can't be transformed",
+                        image_transformer.max_source_size
+                    ),
+                ));
+            }
+            let mut source = Vec::with_capacity(file.content_length.0 as usize);
+            let mut stream = file.stream;
+            while let Some(chunk) = stream.next().await {
+                source.extend_from_slice(&chunk?);
+            }
+            image_transformer
+                .transform(validators.sha256.clone(), source, transform)
+                .await?
+        },
+    };
+    let FileValidators {
+        etag,
+        last_modified,
+        ..
+    } = validators;
+    Ok((
+        [(header::CONTENT_TYPE, image.format.content_type())],
+        TypedHeader(ContentLength(image.body.len() as u64)),
+        TypedHeader(etag),
+        TypedHeader(last_modified),
+        cache_control(),
+        image.body,
+    )
+        .into_response())
+}
+
+fn cache_control() -> TypedHeader<CacheControl> {
+    TypedHeader(
+        CacheControl::new()
+            .with_private()
+            .with_max_age(MAX_CACHE_AGE),
+    )
+}
+
+fn not_modified(
+    FileValidators {
+        etag,
+        last_modified,
+        ..
+    }: FileValidators,
+    cache_control: TypedHeader<CacheControl>,
+) -> Response {
+    (
+        StatusCode::NOT_MODIFIED,
+        TypedHeader(etag),
+        TypedHeader(last_modified),
+        cache_control,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use common::components::ComponentId;
+    use http::{
+        header,
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use keybroker::{
+        Identity,
+        UploadConstraints,
+    };
+    use model::file_storage::{
+        FileStorageId,
+        FileStorageModel,
+    };
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::setup_backend_for_test;
+
+    #[convex_macro::prod_rt_test]
+    async fn test_resumable_upload(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let url = backend
+            .st
+            .application
+            .storage_generate_resumable_upload_url(UploadConstraints::default())
+            .await?;
+        let uri = &url[url.find("/api/storage/").unwrap()..];
+        let response: JsonValue = backend
+            .expect_success(
+                Request::builder()
+                    .uri(uri)
+                    .method("POST")
+                    .header("Content-Type", "text/plain")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        let upload_id = response["uploadId"].as_str().unwrap().to_string();
+        let session_uri = format!("/api/storage/upload_session/{upload_id}");
+
+        let response: JsonValue = backend
+            .expect_success(
+                Request::builder()
+                    .uri(format!("{session_uri}/1"))
+                    .method("PUT")
+                    .body(Body::from("pinna park"))?,
+            )
+            .await?;
+        assert_eq!(response, json!({ "partNumber": 1, "size": 10 }));
+
+        let response: JsonValue = backend
+            .expect_success(
+                Request::builder()
+                    .uri(&session_uri)
+                    .method("GET")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response["parts"], json!([{ "partNumber": 1, "size": 10 }]));
+        assert_eq!(response["storageId"], JsonValue::Null);
+
+        let response: JsonValue = backend
+            .expect_success(
+                Request::builder()
+                    .uri(&session_uri)
+                    .method("POST")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert!(response["storageId"].is_string());
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_upload_constraints(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let url = backend
+            .st
+            .application
+            .storage_generate_upload_url(UploadConstraints {
+                max_bytes: Some(10),
+                allowed_content_types: vec!["text/*".to_string()],
+                expires_in: None,
+            })
+            .await?;
+        let uri = &url[url.find("/api/storage/").unwrap()..];
+        let upload = |content_type: &str, body: &'static str| {
+            Request::builder()
+                .uri(uri)
+                .method("POST")
+                .header("Content-Type", content_type)
+                .body(Body::from(body))
+        };
+        backend
+            .expect_error(
+                upload("image/png", "pinna park")?,
+                StatusCode::BAD_REQUEST,
+                "ContentTypeNotAllowed",
+            )
+            .await?;
+        backend
+            .expect_error(
+                upload("text/plain", "pinna park!")?,
+                StatusCode::BAD_REQUEST,
+                "FileTooLarge",
+            )
+            .await?;
+        let response: JsonValue = backend
+            .expect_success(upload("text/plain", "pinna park")?)
+            .await?;
+        assert!(response["storageId"].is_string());
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_conditional_get(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let url = backend
+            .st
+            .application
+            .storage_generate_upload_url(UploadConstraints::default())
+            .await?;
+        let response: JsonValue = backend
+            .expect_success(
+                Request::builder()
+                    .uri(&url[url.find("/api/storage/").unwrap()..])
+                    .method("POST")
+                    .body(Body::from("pinna park"))?,
+            )
+            .await?;
+        let storage_id = response["storageId"].as_str().unwrap().parse()?;
+        let mut tx = backend.st.application.begin(Identity::system()).await?;
+        let entry = FileStorageModel::new(&mut tx, ComponentId::TODO().into())
+            .get_file(FileStorageId::DocumentId(storage_id))
+            .await?
+            .unwrap();
+        let uri = format!("/api/storage/{}", entry.storage_id);
+        let get = |headers: Vec<(header::HeaderName, String)>| {
+            let mut request = Request::builder().uri(&uri).method("GET");
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            request.body(Body::empty())
+        };
+
+        let response = backend.send(get(vec![])?).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].to_str()?.to_string();
+        let last_modified = response.headers()[header::LAST_MODIFIED]
+            .to_str()?
+            .to_string();
+
+        let response = backend
+            .send(get(vec![(header::IF_NONE_MATCH, etag.clone())])?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert!(hyper::body::to_bytes(response.into_body())
+            .await?
+            .is_empty());
+
+        let response = backend
+            .send(get(vec![(
+                header::IF_MODIFIED_SINCE,
+                last_modified.clone(),
+            )])?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        // If-None-Match takes precedence over If-Modified-Since.
+        let response = backend
+            .send(get(vec![
+                (header::IF_NONE_MATCH, "\"stale\"".to_string()),
+                (header::IF_MODIFIED_SINCE, last_modified),
+            ])?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = backend
+            .send(get(vec![
+                (header::RANGE, "bytes=0-4".to_string()),
+                (header::IF_NONE_MATCH, "\"stale\"".to_string()),
+            ])?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert_eq!(
+            hyper::body::to_bytes(response.into_body()).await?,
+            "pinna".as_bytes()
+        );
+
+        let response = backend
+            .send(get(vec![
+                (header::RANGE, "bytes=0-4".to_string()),
+                (header::IF_NONE_MATCH, etag),
+            ])?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        Ok(())
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_transform_image(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let mut png = Vec::new();
+        image::DynamicImage::ImageRgba8(image::RgbaImage::new(40, 20))
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+        let url = backend
+            .st
+            .application
+            .storage_generate_upload_url(UploadConstraints::default())
+            .await?;
+        let response: JsonValue = backend
+            .expect_success(
+                Request::builder()
+                    .uri(&url[url.find("/api/storage/").unwrap()..])
+                    .method("POST")
+                    .header("Content-Type", "image/png")
+                    .body(Body::from(png))?,
+            )
+            .await?;
+        let storage_id = response["storageId"].as_str().unwrap().parse()?;
+        let mut tx = backend.st.application.begin(Identity::system()).await?;
+        let entry = FileStorageModel::new(&mut tx, ComponentId::TODO().into())
+            .get_file(FileStorageId::DocumentId(storage_id))
+            .await?
+            .unwrap();
+        let uri = format!("/api/storage/{}?width=10&format=jpeg", entry.storage_id);
+
+        let response = backend
+            .send(Request::builder().uri(&uri).body(Body::empty())?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/jpeg");
+        let etag = response.headers()[header::ETAG].to_str()?.to_string();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        let image = image::load_from_memory_with_format(&body, image::ImageFormat::Jpeg)?;
+        assert_eq!((image.width(), image.height()), (10, 5));
+
+        let response = backend
+            .send(
+                Request::builder()
+                    .uri(&uri)
+                    .header(header::IF_NONE_MATCH, etag)
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        backend
+            .expect_error(
+                Request::builder()
+                    .uri(format!("/api/storage/{}?width=0", entry.storage_id))
+                    .body(Body::empty())?,
+                StatusCode::BAD_REQUEST,
+                "InvalidImageTransform",
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use anyhow::Context;
+use axum::{
+    debug_handler,
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    document::{
+        CreationTime,
+        ParsedDocument,
+    },
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use keybroker::AdminOperation;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    file_lifecycle_rules::{
+        types::{
+            FileLifecycleRule,
+            LifecycleAction,
+            LifecycleRunReport,
+        },
+        FileLifecycleRulesModel,
+    },
+    file_storage::types::{
+        FileScanStatus,
+        FileStorageEntry,
+        FileStorageFilter,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::id_v6::DeveloperDocumentId;
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_member_for,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+/// Selects the files to list. All files match if no field is set.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FileStorageFilterArgs {
+    /// A content type like "image/png", or "image/" for all images.
+    pub content_type: Option<String>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    pub tag: Option<String>,
+    /// Only match files uploaded at or after this time.
+    pub uploaded_after_ms: Option<f64>,
+    /// Only match files uploaded before this time.
+    pub uploaded_before_ms: Option<f64>,
+}
+
+impl TryFrom<FileStorageFilterArgs> for FileStorageFilter {
+    type Error = anyhow::Error;
+
+    fn try_from(args: FileStorageFilterArgs) -> anyhow::Result<Self> {
+        let to_creation_time = |ms: f64| {
+            CreationTime::try_from(ms).context(ErrorMetadata::bad_request(
+                "InvalidUploadTime",
+                "uploadedAfterMs and uploadedBeforeMs must be timestamps in milliseconds",
+            ))
+        };
+        Ok(Self {
+            content_type: args.content_type,
+            min_size: args.min_size,
+            max_size: args.max_size,
+            tag: args.tag,
+            uploaded_after: args.uploaded_after_ms.map(to_creation_time).transpose()?,
+            uploaded_before: args.uploaded_before_ms.map(to_creation_time).transpose()?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileJson {
+    pub storage_id: String,
+    pub sha256: String,
+    pub size: i64,
+    pub content_type: Option<String>,
+    pub tags: BTreeSet<String>,
+    pub metadata: BTreeMap<String, String>,
+    /// Set once a lifecycle rule moved the file to another storage class.
+    pub storage_class: Option<String>,
+    /// `clean` or `blocked` if the file went through the file scanner.
+    pub scan_status: Option<&'static str>,
+    /// What the file scanner flagged a blocked file as.
+    pub scan_signature: Option<String>,
+    pub uploaded_at_ms: f64,
+}
+
+impl TryFrom<(DeveloperDocumentId, ParsedDocument<FileStorageEntry>)> for FileJson {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        (storage_id, file): (DeveloperDocumentId, ParsedDocument<FileStorageEntry>),
+    ) -> anyhow::Result<Self> {
+        let uploaded_at = file
+            .creation_time()
+            .context("File storage entry missing creation time")?;
+        let file = file.into_value();
+        Ok(Self {
+            storage_id: storage_id.encode(),
+            sha256: file.sha256.as_base64(),
+            size: file.size,
+            content_type: file.content_type,
+            tags: file.tags,
+            metadata: file.metadata,
+            storage_class: file.storage_class,
+            scan_status: file.scan_status.as_ref().map(FileScanStatus::as_str),
+            scan_signature: file
+                .scan_status
+                .as_ref()
+                .and_then(|status| status.signature().map(str::to_string)),
+            uploaded_at_ms: uploaded_at.into(),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFilesRequest {
+    #[serde(default)]
+    pub filter: FileStorageFilterArgs,
+    pub cursor: Option<f64>,
+    pub num_items: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFilesResponse {
+    pub files: Vec<FileJson>,
+    pub cursor: Option<f64>,
+    pub is_done: bool,
+}
+
+const DEFAULT_LIST_FILES_NUM_ITEMS: usize = 100;
+
+/// Lists stored files matching a filter, oldest first, e.g. to find large old
+/// files to delete. A page can have fewer than `numItems` files even if it
+/// isn't the last page.
+#[debug_handler]
+pub async fn list_files(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ListFilesRequest {
+        filter,
+        cursor,
+        num_items,
+    }): Json<ListFilesRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    // File metadata is data.
+    must_be_admin_member_for(&identity, AdminOperation::ReadData)?;
+    let cursor =
+        cursor
+            .map(CreationTime::try_from)
+            .transpose()
+            .context(ErrorMetadata::bad_request(
+                "InvalidCursor",
+                "cursor must be a cursor returned by list_files",
+            ))?;
+    let page = st
+        .application
+        .list_files(
+            identity,
+            filter.try_into()?,
+            cursor,
+            num_items.unwrap_or(DEFAULT_LIST_FILES_NUM_ITEMS),
+        )
+        .await?;
+    Ok(Json(ListFilesResponse {
+        files: page
+            .files
+            .into_iter()
+            .map(FileJson::try_from)
+            .collect::<anyhow::Result<_>>()?,
+        cursor: page.cursor.map(f64::from),
+        is_done: page.cursor.is_none(),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddFileLifecycleRuleRequest {
+    name: String,
+    /// Files uploaded at least this many days ago match the rule.
+    min_age_days: u64,
+    /// Only match files that no document references.
+    #[serde(default)]
+    only_orphaned: bool,
+    /// "delete" or "transition".
+    action: String,
+    /// The storage class files are moved to by a "transition" rule, e.g.
+    /// "GLACIER".
+    storage_class: Option<String>,
+    /// Only report the files the rule matches.
+    #[serde(default)]
+    dry_run: bool,
+}
+
+fn parse_lifecycle_action(
+    action: &str,
+    storage_class: Option<String>,
+) -> anyhow::Result<LifecycleAction> {
+    match (action, storage_class) {
+        ("delete", None) => Ok(LifecycleAction::Delete),
+        ("transition", Some(storage_class)) => Ok(LifecycleAction::Transition { storage_class }),
+        _ => anyhow::bail!(ErrorMetadata::bad_request(
+            "InvalidFileLifecycleRule",
+            "action must be \"delete\", or \"transition\" with a storageClass",
+        )),
+    }
+}
+
+pub async fn add_file_lifecycle_rule(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(AddFileLifecycleRuleRequest {
+        name,
+        min_age_days,
+        only_orphaned,
+        action,
+        storage_class,
+        dry_run,
+    }): Json<AddFileLifecycleRuleRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let rule = FileLifecycleRule {
+        name: name.clone(),
+        min_age_days,
+        only_orphaned,
+        action: parse_lifecycle_action(&action, storage_class)?,
+        dry_run,
+        last_run: None,
+    };
+    let mut tx = st.application.begin(identity).await?;
+    FileLifecycleRulesModel::new(&mut tx)
+        .add_or_update(rule)
+        .await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::CreateFileLifecycleRule { name }],
+            "add_file_lifecycle_rule",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFileLifecycleRuleRequest {
+    name: String,
+}
+
+pub async fn delete_file_lifecycle_rule(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(DeleteFileLifecycleRuleRequest { name }): Json<DeleteFileLifecycleRuleRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    FileLifecycleRulesModel::new(&mut tx).delete(&name).await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteFileLifecycleRule { name }],
+            "delete_file_lifecycle_rule",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LifecycleRunReportJson {
+    /// Timestamps are nanoseconds since the Unix epoch.
+    started_ts: i64,
+    finished_ts: i64,
+    dry_run: bool,
+    files_matched: u64,
+    bytes_matched: u64,
+    files_applied: u64,
+    sample_storage_ids: Vec<String>,
+    error: Option<String>,
+}
+
+impl From<LifecycleRunReport> for LifecycleRunReportJson {
+    fn from(report: LifecycleRunReport) -> Self {
+        Self {
+            started_ts: report.started_ts.into(),
+            finished_ts: report.finished_ts.into(),
+            dry_run: report.dry_run,
+            files_matched: report.files_matched,
+            bytes_matched: report.bytes_matched,
+            files_applied: report.files_applied,
+            sample_storage_ids: report.sample_storage_ids,
+            error: report.error,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileLifecycleRuleJson {
+    name: String,
+    min_age_days: u64,
+    only_orphaned: bool,
+    action: &'static str,
+    storage_class: Option<String>,
+    dry_run: bool,
+    last_run: Option<LifecycleRunReportJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListFileLifecycleRulesResponse {
+    rules: Vec<FileLifecycleRuleJson>,
+}
+
+/// Lists the lifecycle rules with the report of each one's latest run, which
+/// for a dry run shows what the rule would delete or transition.
+pub async fn list_file_lifecycle_rules(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let rules = FileLifecycleRulesModel::new(&mut tx)
+        .list()
+        .await?
+        .into_iter()
+        .map(|rule| {
+            let rule = rule.into_value();
+            let (action, storage_class) = match rule.action {
+                LifecycleAction::Delete => ("delete", None),
+                LifecycleAction::Transition { storage_class } => {
+                    ("transition", Some(storage_class))
+                },
+            };
+            FileLifecycleRuleJson {
+                name: rule.name,
+                min_age_days: rule.min_age_days,
+                only_orphaned: rule.only_orphaned,
+                action,
+                storage_class,
+                dry_run: rule.dry_run,
+                last_run: rule.last_run.map(LifecycleRunReportJson::from),
+            }
+        })
+        .collect();
+    Ok(Json(ListFileLifecycleRulesResponse { rules }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::headers::authorization::Credentials;
+    use http::{
+        Request,
+        StatusCode,
+    };
+    use hyper::Body;
+    use runtime::prod::ProdRuntime;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
+
+    use crate::test_helpers::{
+        setup_backend_for_test,
+        TestLocalBackend,
+    };
+
+    fn admin_request(
+        backend: &TestLocalBackend,
+        uri: &str,
+        body: Option<JsonValue>,
+    ) -> anyhow::Result<Request<Body>> {
+        let builder = Request::builder()
+            .uri(uri)
+            .header("Authorization", backend.admin_auth_header.0.encode());
+        Ok(match body {
+            Some(body) => builder
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_vec(&body)?))?,
+            None => builder.method("GET").body(Body::empty())?,
+        })
+    }
+
+    #[convex_macro::prod_rt_test]
+    async fn test_configure_file_lifecycle_rules(rt: ProdRuntime) -> anyhow::Result<()> {
+        let backend = setup_backend_for_test(rt).await?;
+        let rule = json!({
+            "name": "archive",
+            "minAgeDays": 90,
+            "action": "transition",
+            "storageClass": "GLACIER",
+            "dryRun": true,
+        });
+        let _: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/add_file_lifecycle_rule",
+                Some(rule.clone()),
+            )?)
+            .await?;
+        let mut invalid = rule.clone();
+        invalid["storageClass"] = JsonValue::Null;
+        backend
+            .expect_error(
+                admin_request(&backend, "/api/add_file_lifecycle_rule", Some(invalid))?,
+                StatusCode::BAD_REQUEST,
+                "InvalidFileLifecycleRule",
+            )
+            .await?;
+        let mut invalid = rule.clone();
+        invalid["minAgeDays"] = json!(0);
+        backend
+            .expect_error(
+                admin_request(&backend, "/api/add_file_lifecycle_rule", Some(invalid))?,
+                StatusCode::BAD_REQUEST,
+                "InvalidFileLifecycleRule",
+            )
+            .await?;
+
+        let response: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/list_file_lifecycle_rules",
+                None,
+            )?)
+            .await?;
+        let rules = response["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0]["action"], json!("transition"));
+        assert_eq!(rules[0]["storageClass"], json!("GLACIER"));
+        assert_eq!(rules[0]["onlyOrphaned"], json!(false));
+        assert_eq!(rules[0]["dryRun"], json!(true));
+
+        let _: JsonValue = backend
+            .expect_success(admin_request(
+                &backend,
+                "/api/delete_file_lifecycle_rule",
+                Some(json!({ "name": "archive" })),
+            )?)
+            .await?;
+        backend
+            .expect_error(
+                admin_request(
+                    &backend,
+                    "/api/delete_file_lifecycle_rule",
+                    Some(json!({ "name": "archive" })),
+                )?,
+                StatusCode::NOT_FOUND,
+                "FileLifecycleRuleNotFound",
+            )
+            .await?;
+        Ok(())
+    }
+}
@@ -73,6 +73,7 @@ pub fn log_websocket_message_out(message: &ServerMessage, delay: Duration) {
         ServerMessage::AuthError { .. } => "AuthError",
         ServerMessage::FatalError { .. } => "FatalError",
         ServerMessage::Ping { .. } => "Ping",
+        ServerMessage::SubscriptionsExpired => "SubscriptionsExpired",
     };
     let labels = vec![StaticMetricLabel::new("endpoint", endpoint)];
     log_distribution_with_labels(
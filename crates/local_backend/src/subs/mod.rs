@@ -20,6 +20,7 @@ use anyhow::Context as _;
 use axum::{
     extract::{
         ws::{
+            close_code,
             CloseFrame,
             Message,
             WebSocket,
@@ -29,6 +30,7 @@ use axum::{
         State,
     },
     response::IntoResponse,
+    Extension,
 };
 use common::{
     errors::{
@@ -81,7 +83,10 @@ use metrics::{
     websocket_upgrade_timer,
 };
 
-use crate::RouterState;
+use crate::{
+    admin_access::AdminAuthNotAllowed,
+    RouterState,
+};
 
 /// How often heartbeat pings are sent.
 const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
@@ -136,7 +141,16 @@ async fn run_sync_socket(
 
     let (client_tx, client_rx) = mpsc::unbounded();
     let receive_messages = async {
-        while let Some(message_r) = rx.next().await {
+        loop {
+            // Stop reading messages once the backend is draining, so the sync
+            // worker finishes what it's started and exits.
+            let message_r = select_biased! {
+                _ = st.drain.started().fuse() => break,
+                message_r = rx.next().fuse() => match message_r {
+                    Some(message_r) => message_r,
+                    None => break,
+                },
+            };
             let message = match message_r {
                 Ok(message) => message,
                 Err(e) if is_connection_closed_error(&e) => {
@@ -226,6 +240,7 @@ async fn run_sync_socket(
             st.runtime.clone(),
             host,
             config.clone(),
+            st.active_subscriptions.clone(),
             client_rx,
             server_tx,
         );
@@ -243,6 +258,11 @@ async fn run_sync_socket(
     let mut socket = tx.reunite(rx).expect("Mixed up WebSocket halves?");
 
     let close_msg = match result {
+        // Ask the client to reconnect, which it'll do to another backend.
+        Ok(..) if st.drain.is_draining() => Some(Message::Close(Some(CloseFrame {
+            code: close_code::RESTART,
+            reason: "BackendDraining".into(),
+        }))),
         Ok(..) => None,
         Err(err) => {
             let mut err = err.last_second_classification();
@@ -321,7 +341,10 @@ async fn run_sync_socket(
 
 fn new_sync_worker_config(client_version: ClientVersion) -> anyhow::Result<SyncWorkerConfig> {
     match client_version.client() {
-        ClientType::NPM => Ok(SyncWorkerConfig { client_version }),
+        ClientType::NPM => Ok(SyncWorkerConfig {
+            client_version,
+            ..SyncWorkerConfig::default()
+        }),
         ClientType::Rust | ClientType::Unrecognized(_) => Ok(SyncWorkerConfig::default()),
         ClientType::CLI
         | ClientType::Python
@@ -341,9 +364,11 @@ pub async fn sync_client_version_url(
     State(st): State<RouterState>,
     Host(host): Host,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    admin_auth_not_allowed: Option<Extension<AdminAuthNotAllowed>>,
     ws: WebSocketUpgrade,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    let config = new_sync_worker_config(client_version)?;
+    let mut config = new_sync_worker_config(client_version)?;
+    config.allow_admin_auth = admin_auth_not_allowed.is_none();
     // Make a copy of the Sentry scope, which contains the request metadata.
     let sentry_scope = sentry::configure_scope(move |s| s.clone());
 
@@ -358,9 +383,11 @@ pub async fn sync(
     State(st): State<RouterState>,
     Host(host): Host,
     ExtractClientVersion(client_version): ExtractClientVersion,
+    admin_auth_not_allowed: Option<Extension<AdminAuthNotAllowed>>,
     ws: WebSocketUpgrade,
 ) -> Result<impl IntoResponse, HttpResponseError> {
-    let config = new_sync_worker_config(client_version)?;
+    let mut config = new_sync_worker_config(client_version)?;
+    config.allow_admin_auth = admin_auth_not_allowed.is_none();
     // Make a copy of the Sentry scope, which contains the request metadata.
     let sentry_scope = sentry::configure_scope(move |s| s.clone());
 
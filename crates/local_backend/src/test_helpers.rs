@@ -4,7 +4,10 @@ use std::{
 };
 
 use anyhow::Context;
-use axum::headers::Authorization;
+use axum::{
+    headers::Authorization,
+    response::Response,
+};
 use common::{
     http::{
         ConvexHttpService,
@@ -88,6 +91,11 @@ impl TestLocalBackend {
             .context(format!("Couldn't deserialize as json: {bytes:?}"))
     }
 
+    pub async fn send(&self, req: Request<hyper::Body>) -> anyhow::Result<Response> {
+        tracing::info!("Sending req {req:?}");
+        Ok(self.app.router().clone().oneshot(req).await?)
+    }
+
     pub async fn expect_error(
         &self,
         req: Request<hyper::Body>,
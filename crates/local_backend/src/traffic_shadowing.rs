@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use application::traffic_shadowing::{
+    ShadowFunctionRequest,
+    ShadowFunctionResult,
+    TrafficShadowingDivergence,
+};
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::http::{
+    extract::Json,
+    ExtractRequestId,
+    HttpResponseError,
+};
+use http::StatusCode;
+use model::{
+    deployment_audit_log::types::DeploymentAuditLogEvent,
+    traffic_shadowing::{
+        types::TrafficShadowingConfig,
+        TrafficShadowingModel,
+    },
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::{
+    admin::{
+        must_be_admin,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTrafficShadowingRequest {
+    /// The deployment calls are shadowed to, e.g.
+    /// "https://happy-animal-123.convex.cloud".
+    target_url: String,
+    /// An admin key for the target deployment.
+    target_admin_key: String,
+    /// The percentage of queries and mutations that are shadowed, from 0 to
+    /// 100.
+    sample_percentage: f64,
+}
+
+/// Starts replaying a sample of this deployment's queries and mutations
+/// against another deployment, e.g. a staging deployment running a backend
+/// upgrade. The target runs mutations without committing them. Its results
+/// and latencies are compared with production's in
+/// `get_traffic_shadowing`.
+pub async fn set_traffic_shadowing(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(SetTrafficShadowingRequest {
+        target_url,
+        target_admin_key,
+        sample_percentage,
+    }): Json<SetTrafficShadowingRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let event = DeploymentAuditLogEvent::UpdateTrafficShadowing {
+        target_url: target_url.clone(),
+    };
+    let config = TrafficShadowingConfig {
+        target_url,
+        target_admin_key,
+        sample_percentage,
+    };
+    let mut tx = st.application.begin(identity).await?;
+    TrafficShadowingModel::new(&mut tx).set(config).await?;
+    st.application
+        .commit_with_audit_log_events(tx, vec![event], "set_traffic_shadowing")
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+/// Stops shadowing calls.
+pub async fn delete_traffic_shadowing(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    TrafficShadowingModel::new(&mut tx).clear().await?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::DeleteTrafficShadowing],
+            "delete_traffic_shadowing",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficShadowingConfigJson {
+    target_url: String,
+    sample_percentage: f64,
+    // The target's admin key is never returned.
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficShadowingDivergenceJson {
+    ts: f64,
+    udf_type: String,
+    path: String,
+    component_path: String,
+    result_diverged: bool,
+    latency_diverged: bool,
+    production_result: ShadowFunctionResult,
+    shadow_result: ShadowFunctionResult,
+    production_execution_time_ms: f64,
+    shadow_execution_time_ms: f64,
+}
+
+impl From<TrafficShadowingDivergence> for TrafficShadowingDivergenceJson {
+    fn from(divergence: TrafficShadowingDivergence) -> Self {
+        Self {
+            ts: divergence.ts.as_secs_f64() * 1000.0,
+            udf_type: divergence.udf_type.to_lowercase_string().to_string(),
+            path: divergence.path,
+            component_path: divergence.component_path,
+            result_diverged: divergence.result_diverged,
+            latency_diverged: divergence.latency_diverged,
+            production_result: divergence.production_result.into(),
+            shadow_result: divergence.shadow_result.into(),
+            production_execution_time_ms: as_ms(divergence.production_execution_time),
+            shadow_execution_time_ms: as_ms(divergence.shadow_execution_time),
+        }
+    }
+}
+
+fn as_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficShadowingReportJson {
+    num_shadowed: u64,
+    num_result_divergences: u64,
+    num_latency_divergences: u64,
+    num_failed: u64,
+    num_dropped: u64,
+    /// None until a call has been shadowed.
+    mean_production_execution_time_ms: Option<f64>,
+    mean_shadow_execution_time_ms: Option<f64>,
+    recent_divergences: Vec<TrafficShadowingDivergenceJson>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficShadowingResponse {
+    config: Option<TrafficShadowingConfigJson>,
+    report: TrafficShadowingReportJson,
+}
+
+/// The shadowing config, and how the calls this backend has shadowed to the
+/// current target compare to production. The report is kept in memory, so it
+/// starts over when the backend restarts or the target changes.
+pub async fn get_traffic_shadowing(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let mut tx = st.application.begin(identity).await?;
+    let config = TrafficShadowingModel::new(&mut tx).get().await?;
+    let report = st.application.traffic_shadowing_report();
+    let mean_ms = |total: Duration| {
+        (report.num_shadowed > 0).then(|| as_ms(total) / report.num_shadowed as f64)
+    };
+    Ok(Json(TrafficShadowingResponse {
+        config: config.map(|config| TrafficShadowingConfigJson {
+            target_url: config.target_url,
+            sample_percentage: config.sample_percentage,
+        }),
+        report: TrafficShadowingReportJson {
+            num_shadowed: report.num_shadowed,
+            num_result_divergences: report.num_result_divergences,
+            num_latency_divergences: report.num_latency_divergences,
+            num_failed: report.num_failed,
+            num_dropped: report.num_dropped,
+            mean_production_execution_time_ms: mean_ms(report.total_production_execution_time),
+            mean_shadow_execution_time_ms: mean_ms(report.total_shadow_execution_time),
+            recent_divergences: report
+                .recent_divergences
+                .into_iter()
+                .map(TrafficShadowingDivergenceJson::from)
+                .collect(),
+        },
+    }))
+}
+
+/// Runs a query or mutation that another deployment is shadowing to this
+/// one, as the user who called it there. Mutations aren't committed.
+pub async fn shadow_function(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    ExtractRequestId(request_id): ExtractRequestId,
+    Json(req): Json<ShadowFunctionRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin(&identity)?;
+    let response = st
+        .application
+        .execute_shadowed_function(request_id, identity, req)
+        .await?;
+    Ok(Json(response))
+}
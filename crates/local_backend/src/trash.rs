@@ -0,0 +1,170 @@
+use axum::{
+    extract::State,
+    response::IntoResponse,
+};
+use common::{
+    components::ComponentPath,
+    http::{
+        extract::Json,
+        HttpResponseError,
+    },
+};
+use database::{
+    trash::{
+        TrashModel,
+        TrashedDocument,
+    },
+    BootstrapComponentsModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use http::StatusCode;
+use keybroker::AdminOperation;
+use model::deployment_audit_log::types::DeploymentAuditLogEvent;
+use runtime::prod::ProdRuntime;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use value::{
+    id_v6::DeveloperDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use crate::{
+    admin::{
+        must_be_admin_member_for,
+        must_be_admin_with_write_access,
+    },
+    authentication::ExtractIdentity,
+    LocalAppState,
+};
+
+const DEFAULT_LIST_TRASH_NUM_ITEMS: usize = 100;
+
+async fn component_namespace(
+    tx: &mut Transaction<ProdRuntime>,
+    component_path: Option<String>,
+) -> anyhow::Result<TableNamespace> {
+    let component_path: ComponentPath = match component_path {
+        Some(component_path) => component_path.parse().map_err(|e: anyhow::Error| {
+            ErrorMetadata::bad_request("InvalidComponentPath", e.to_string())
+        })?,
+        None => ComponentPath::root(),
+    };
+    let (_, component_id) = BootstrapComponentsModel::new(tx)
+        .component_path_to_ids(component_path)
+        .await?;
+    Ok(component_id.into())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTrashRequest {
+    /// The component the table is in. Defaults to the app itself.
+    component_path: Option<String>,
+    table_name: String,
+    num_items: Option<usize>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedDocumentJson {
+    document_id: String,
+    table_name: String,
+    /// The document as it was deleted. Encrypted fields are left encrypted.
+    document: JsonValue,
+    deleted_at_ms: u64,
+    expires_at_ms: u64,
+}
+
+impl TryFrom<TrashedDocument> for TrashedDocumentJson {
+    type Error = anyhow::Error;
+
+    fn try_from(trashed: TrashedDocument) -> anyhow::Result<Self> {
+        Ok(Self {
+            document_id: trashed.document_id.encode(),
+            table_name: trashed.table_name.to_string(),
+            document: trashed.document.into(),
+            deleted_at_ms: trashed.deleted_at.as_ms_since_epoch()?,
+            expires_at_ms: trashed.expires_at.as_ms_since_epoch()?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListTrashResponse {
+    documents: Vec<TrashedDocumentJson>,
+}
+
+/// Lists the soft-deleted documents of a table that can still be restored,
+/// most recently deleted first.
+pub async fn list_trash(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(ListTrashRequest {
+        component_path,
+        table_name,
+        num_items,
+    }): Json<ListTrashRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_member_for(&identity, AdminOperation::ReadData)?;
+    let table_name: TableName = table_name.parse().map_err(|e: anyhow::Error| {
+        ErrorMetadata::bad_request("InvalidTableName", e.to_string())
+    })?;
+    let mut tx = st.application.begin(identity).await?;
+    let namespace = component_namespace(&mut tx, component_path).await?;
+    let documents = TrashModel::new(&mut tx, namespace)
+        .list(
+            &table_name,
+            num_items.unwrap_or(DEFAULT_LIST_TRASH_NUM_ITEMS),
+        )
+        .await?
+        .into_iter()
+        .map(|trashed| TrashedDocumentJson::try_from(trashed.into_value()))
+        .collect::<anyhow::Result<_>>()?;
+    Ok(Json(ListTrashResponse { documents }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreDocumentRequest {
+    /// The component the document's table is in. Defaults to the app itself.
+    component_path: Option<String>,
+    document_id: String,
+}
+
+/// Moves a soft-deleted document back into its table with its original ID.
+pub async fn restore_document(
+    State(st): State<LocalAppState>,
+    ExtractIdentity(identity): ExtractIdentity,
+    Json(RestoreDocumentRequest {
+        component_path,
+        document_id,
+    }): Json<RestoreDocumentRequest>,
+) -> Result<impl IntoResponse, HttpResponseError> {
+    must_be_admin_with_write_access(&identity)?;
+    let document_id = DeveloperDocumentId::decode(&document_id).map_err(|e| {
+        ErrorMetadata::bad_request("InvalidId", format!("Invalid document ID: {e}"))
+    })?;
+    let mut tx = st.application.begin(identity).await?;
+    let namespace = component_namespace(&mut tx, component_path).await?;
+    let id = TrashModel::new(&mut tx, namespace)
+        .restore(document_id)
+        .await?;
+    let table_name = tx.table_mapping().tablet_name(id.tablet_id)?;
+    st.application
+        .commit_with_audit_log_events(
+            tx,
+            vec![DeploymentAuditLogEvent::RestoreDocument {
+                table_name: table_name.to_string(),
+                document_id: document_id.encode(),
+            }],
+            "restore_document",
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
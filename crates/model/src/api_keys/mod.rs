@@ -0,0 +1,211 @@
+use std::sync::LazyLock;
+
+use common::{
+    auth::ApiKeyScope,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use rand::Rng;
+use value::{
+    sha256::Sha256,
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    ApiKeyMetadata,
+    ApiKeyName,
+    API_KEY_PREFIX,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Table of deployment API keys, which let other servers call the
+/// deployment's functions with a limited set of permissions.
+pub static API_KEYS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_api_keys"
+        .parse()
+        .expect("Invalid built-in api keys table")
+});
+
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("Invalid built-in field"));
+
+static KEY_HASH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "keyHash".parse().expect("Invalid built-in field"));
+
+pub static API_KEYS_INDEX_BY_NAME: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&API_KEYS_TABLE, "by_name"));
+
+pub static API_KEYS_INDEX_BY_KEY_HASH: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&API_KEYS_TABLE, "by_key_hash"));
+
+pub struct ApiKeysTable;
+impl SystemTable for ApiKeysTable {
+    fn table_name(&self) -> &'static TableName {
+        &API_KEYS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: API_KEYS_INDEX_BY_NAME.clone(),
+                fields: vec![NAME_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: API_KEYS_INDEX_BY_KEY_HASH.clone(),
+                fields: vec![KEY_HASH_FIELD.clone()].try_into().unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ApiKeyMetadata>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ApiKeyModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ApiKeyModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    /// Create an API key, returning its id and the key itself. The key isn't
+    /// stored, so this is the only time it's available.
+    pub async fn create(
+        &mut self,
+        name: ApiKeyName,
+        scope: ApiKeyScope,
+    ) -> anyhow::Result<(ResolvedDocumentId, String)> {
+        self.check_admin("create_api_key")?;
+        if self.get_by_name(&name).await?.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ApiKeyNameTaken",
+                format!("An API key named {name} already exists"),
+            ));
+        }
+        let secret: [u8; 32] = self.tx.runtime().with_rng(|rng| rng.gen());
+        let key = format!("{API_KEY_PREFIX}{}", hex::encode(secret));
+        let metadata = ApiKeyMetadata {
+            name,
+            key_hash: Sha256::hash(key.as_bytes()),
+            scope,
+        };
+        let id = SystemMetadataModel::new_global(self.tx)
+            .insert(&API_KEYS_TABLE, metadata.try_into()?)
+            .await?;
+        Ok((id, key))
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<ApiKeyMetadata>>> {
+        self.check_admin("list_api_keys")?;
+        let query = Query::index_range(IndexRange {
+            index_name: API_KEYS_INDEX_BY_NAME.clone(),
+            range: vec![],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut api_keys = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            api_keys.push(doc.try_into()?);
+        }
+        Ok(api_keys)
+    }
+
+    pub async fn delete(&mut self, name: &ApiKeyName) -> anyhow::Result<ApiKeyMetadata> {
+        self.check_admin("delete_api_key")?;
+        let Some(doc) = self.get_by_name(name).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "ApiKeyNotFound",
+                format!("There is no API key named {name}"),
+            ));
+        };
+        let document = SystemMetadataModel::new_global(self.tx)
+            .delete(doc.id())
+            .await?;
+        let api_key: ParsedDocument<ApiKeyMetadata> = document.try_into()?;
+        Ok(api_key.into_value())
+    }
+
+    /// Look up the API key that `key` was issued as, for authenticating a
+    /// request made with it.
+    pub async fn get_by_key(
+        &mut self,
+        key: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<ApiKeyMetadata>>> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("get_api_key"));
+        }
+        let key_hash = ConvexValue::try_from(Sha256::hash(key.as_bytes()))?;
+        self.query_one(
+            API_KEYS_INDEX_BY_KEY_HASH.clone(),
+            KEY_HASH_FIELD.clone(),
+            key_hash,
+        )
+        .await
+    }
+
+    async fn get_by_name(
+        &mut self,
+        name: &ApiKeyName,
+    ) -> anyhow::Result<Option<ParsedDocument<ApiKeyMetadata>>> {
+        let name = ConvexValue::try_from(name.to_string())?;
+        self.query_one(API_KEYS_INDEX_BY_NAME.clone(), NAME_FIELD.clone(), name)
+            .await
+    }
+
+    async fn query_one(
+        &mut self,
+        index_name: IndexName,
+        field: FieldPath,
+        value: ConvexValue,
+    ) -> anyhow::Result<Option<ParsedDocument<ApiKeyMetadata>>> {
+        let query = Query::index_range(IndexRange {
+            index_name,
+            range: vec![IndexRangeExpression::Eq(field, value.into())],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+};
+
+use common::{
+    auth::ApiKeyScope,
+    obj,
+    value::ConvexValue,
+};
+use errors::ErrorMetadata;
+use value::{
+    sha256::Sha256Digest,
+    ConvexObject,
+};
+
+/// Prefix of deployment API keys, which tells them apart from the JWTs that
+/// are also sent as bearer tokens.
+pub const API_KEY_PREFIX: &str = "convex_api_key_";
+
+/// Maximum length of an API key's name.
+pub const MAX_API_KEY_NAME_LENGTH: usize = 64;
+
+/// Unique name of an API key, which identifies it in the API key management
+/// endpoints and in usage metrics.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ApiKeyName(
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(regex = "[a-zA-Z0-9_-]{1,64}")
+    )]
+    String,
+);
+
+impl FromStr for ApiKeyName {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if s.is_empty() || s.len() > MAX_API_KEY_NAME_LENGTH {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidApiKeyName",
+                format!(
+                    "API key names must be between 1 and {MAX_API_KEY_NAME_LENGTH} characters long"
+                ),
+            ));
+        }
+        if !s
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidApiKeyName",
+                "API key names may only contain letters, numbers, underscores and dashes",
+            ));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for ApiKeyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<ApiKeyName> for String {
+    fn from(name: ApiKeyName) -> Self {
+        name.0
+    }
+}
+
+/// A deployment API key. Only the key's hash is stored, so the key itself is
+/// only available when it's created.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ApiKeyMetadata {
+    pub name: ApiKeyName,
+    pub key_hash: Sha256Digest,
+    pub scope: ApiKeyScope,
+}
+
+impl TryFrom<ApiKeyMetadata> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(metadata: ApiKeyMetadata) -> anyhow::Result<Self> {
+        let functions = match metadata.scope.functions {
+            Some(functions) => {
+                let functions: Vec<_> = functions
+                    .into_iter()
+                    .map(|path| ConvexValue::try_from(String::from(path)))
+                    .try_collect()?;
+                ConvexValue::Array(functions.try_into()?)
+            },
+            None => ConvexValue::Null,
+        };
+        obj!(
+            "name" => String::from(metadata.name),
+            "keyHash" => metadata.key_hash,
+            "readOnly" => metadata.scope.read_only,
+            "functions" => functions,
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for ApiKeyMetadata {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields: BTreeMap<_, _> = object.into();
+
+        let name = match fields.remove("name") {
+            Some(ConvexValue::String(s)) => s.parse()?,
+            v => anyhow::bail!("Invalid name field for ApiKeyMetadata: {:?}", v),
+        };
+        let key_hash = match fields.remove("keyHash") {
+            Some(v @ ConvexValue::Bytes(_)) => v.try_into()?,
+            v => anyhow::bail!("Invalid keyHash field for ApiKeyMetadata: {:?}", v),
+        };
+        let read_only = match fields.remove("readOnly") {
+            Some(ConvexValue::Boolean(b)) => b,
+            v => anyhow::bail!("Invalid readOnly field for ApiKeyMetadata: {:?}", v),
+        };
+        let functions = match fields.remove("functions") {
+            Some(ConvexValue::Null) => None,
+            Some(ConvexValue::Array(functions)) => Some(
+                functions
+                    .into_iter()
+                    .map(|v| match v {
+                        ConvexValue::String(s) => s.parse(),
+                        v => anyhow::bail!("Invalid function path for ApiKeyMetadata: {:?}", v),
+                    })
+                    .try_collect()?,
+            ),
+            v => anyhow::bail!("Invalid functions field for ApiKeyMetadata: {:?}", v),
+        };
+
+        Ok(ApiKeyMetadata {
+            name,
+            key_hash,
+            scope: ApiKeyScope {
+                read_only,
+                functions,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::testing::assert_roundtrips;
+    use proptest::prelude::*;
+    use value::ConvexObject;
+
+    use super::{
+        ApiKeyMetadata,
+        ApiKeyName,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_api_key_metadata_roundtrips(v in any::<ApiKeyMetadata>()) {
+            assert_roundtrips::<ApiKeyMetadata, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_parse_api_key_name() {
+        assert!("billing-service_2".parse::<ApiKeyName>().is_ok());
+        assert!("".parse::<ApiKeyName>().is_err());
+        assert!("has space".parse::<ApiKeyName>().is_err());
+        assert!("a".repeat(65).parse::<ApiKeyName>().is_err());
+    }
+}
@@ -11,9 +11,9 @@ use serde::{
 };
 use serde_json::Value as JsonValue;
 use value::{
-    obj,
     ConvexObject,
     ConvexValue,
+    FieldName,
 };
 
 /// Persisted version of AuthInfo that impls try_from to ConvexObject
@@ -35,9 +35,21 @@ impl TryFrom<ConvexObject> for AuthInfoPersisted {
             Some(ConvexValue::String(s)) => IssuerUrl::new(s.into())?,
             _ => anyhow::bail!("Missing or invalid domain field for AuthInfo"),
         };
+        let additional_issuers = match fields.remove("additionalIssuers") {
+            Some(ConvexValue::Array(issuers)) => issuers
+                .into_iter()
+                .map(|issuer| match issuer {
+                    ConvexValue::String(s) => Ok(IssuerUrl::new(s.into())?),
+                    _ => anyhow::bail!("Invalid additionalIssuers field for AuthInfo"),
+                })
+                .collect::<anyhow::Result<_>>()?,
+            None => vec![],
+            _ => anyhow::bail!("Invalid additionalIssuers field for AuthInfo"),
+        };
         Ok(Self(AuthInfo {
             application_id,
             domain,
+            additional_issuers,
         }))
     }
 }
@@ -46,10 +58,20 @@ impl TryFrom<AuthInfoPersisted> for ConvexObject {
     type Error = anyhow::Error;
 
     fn try_from(info: AuthInfoPersisted) -> Result<Self, Self::Error> {
-        obj!(
-            "applicationID" => info.0.application_id,
-            "domain" => info.0.domain.to_string(),
-        )
+        let mut fields: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
+        fields.insert("applicationID".parse()?, info.0.application_id.try_into()?);
+        fields.insert("domain".parse()?, info.0.domain.to_string().try_into()?);
+        // Omitted when empty so existing configs are unchanged.
+        if !info.0.additional_issuers.is_empty() {
+            let issuers = info
+                .0
+                .additional_issuers
+                .into_iter()
+                .map(|issuer| ConvexValue::try_from(issuer.to_string()))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            fields.insert("additionalIssuers".parse()?, issuers.try_into()?);
+        }
+        fields.try_into()
     }
 }
 
@@ -1,5 +1,3 @@
-use std::sync::LazyLock;
-
 use common::{
     document::{
         ParsedDocument,
@@ -12,10 +10,15 @@ use common::{
     runtime::Runtime,
 };
 use database::{
+    read_only_error,
     ResolvedQuery,
     SystemMetadataModel,
     Transaction,
 };
+pub use database::{
+    BACKEND_STATE_TABLE,
+    READ_ONLY_ERROR_MESSAGE,
+};
 use errors::ErrorMetadata;
 use value::{
     TableName,
@@ -41,12 +44,6 @@ pub const DISABLED_ERROR_MESSAGE: &str = "You have exceeded the free plan limits
                                           deployments have been disabled. Please upgrade to a Pro \
                                           plan or reach out to us at support@convex.dev for help.";
 
-pub static BACKEND_STATE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
-    "_backend_state"
-        .parse()
-        .expect("Invalid built-in backend_state table")
-});
-
 pub struct BackendStateTable;
 impl SystemTable for BackendStateTable {
     fn table_name(&self) -> &'static TableName {
@@ -107,7 +104,7 @@ impl<'a, RT: Runtime> BackendStateModel<'a, RT> {
     pub async fn fail_while_paused_or_disabled(&mut self) -> anyhow::Result<()> {
         let backend_state = self.get_backend_state().await?;
         match backend_state {
-            BackendState::Running => {},
+            BackendState::Running | BackendState::ReadOnly => {},
             BackendState::Paused => anyhow::bail!(ErrorMetadata::bad_request(
                 "NoRunWhilePaused",
                 PAUSED_ERROR_MESSAGE,
@@ -120,6 +117,36 @@ impl<'a, RT: Runtime> BackendStateModel<'a, RT> {
         Ok(())
     }
 
+    pub async fn fail_while_read_only(&mut self) -> anyhow::Result<()> {
+        let backend_state = self.get_backend_state().await?;
+        anyhow::ensure!(!backend_state.is_read_only(), read_only_error());
+        Ok(())
+    }
+
+    /// Turns read-only mode on or off, returning the previous state. Only a
+    /// running deployment can be made read-only, so this can't be used to
+    /// resume a paused or disabled deployment.
+    pub async fn set_read_only(&mut self, read_only: bool) -> anyhow::Result<BackendState> {
+        let current_state = self.get_backend_state().await?;
+        anyhow::ensure!(
+            matches!(
+                current_state,
+                BackendState::Running | BackendState::ReadOnly
+            ),
+            ErrorMetadata::bad_request(
+                "DeploymentNotRunning",
+                format!("Cannot change read-only mode while the deployment is {current_state}")
+            )
+        );
+        let new_state = if read_only {
+            BackendState::ReadOnly
+        } else {
+            BackendState::Running
+        };
+        self.toggle_backend_state(new_state).await?;
+        Ok(current_state)
+    }
+
     pub async fn toggle_backend_state(&mut self, new_state: BackendState) -> anyhow::Result<()> {
         let (id, current_state) = self.get_backend_state_inner().await?.into_id_and_value();
         anyhow::ensure!(
@@ -138,12 +165,16 @@ impl<'a, RT: Runtime> BackendStateModel<'a, RT> {
 
 #[cfg(test)]
 mod tests {
-    use database::test_helpers::DbFixtures;
+    use database::{
+        test_helpers::DbFixtures,
+        TestFacingModel,
+    };
     use errors::{
         ErrorCode,
         ErrorMetadata,
     };
     use runtime::testing::TestRuntime;
+    use value::assert_obj;
 
     use crate::{
         backend_state::{
@@ -174,4 +205,47 @@ mod tests {
         assert_eq!(err.code, ErrorCode::BadRequest);
         Ok(())
     }
+    #[convex_macro::test_runtime]
+    async fn test_read_only_rejects_user_writes(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let previous_state = BackendStateModel::new(&mut tx).set_read_only(true).await?;
+        assert_eq!(previous_state, BackendState::Running);
+        db.commit(tx).await?;
+
+        // Writes to user tables are rejected by the committer.
+        let mut tx = db.begin_system().await?;
+        TestFacingModel::new(&mut tx)
+            .insert(&"messages".parse()?, assert_obj!("body" => "hi"))
+            .await?;
+        let err = db.commit(tx).await.unwrap_err();
+        let err = err.downcast_ref::<ErrorMetadata>().unwrap();
+        assert_eq!(err.short_msg, "DeploymentReadOnly");
+
+        // System writes, like turning read-only mode off, still commit.
+        let mut tx = db.begin_system().await?;
+        let previous_state = BackendStateModel::new(&mut tx).set_read_only(false).await?;
+        assert_eq!(previous_state, BackendState::ReadOnly);
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        TestFacingModel::new(&mut tx)
+            .insert(&"messages".parse()?, assert_obj!("body" => "hi"))
+            .await?;
+        db.commit(tx).await?;
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_set_read_only_while_paused(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = BackendStateModel::new(&mut tx);
+        model.toggle_backend_state(BackendState::Paused).await?;
+        let err = model.set_read_only(false).await.unwrap_err();
+        let err = err.downcast_ref::<ErrorMetadata>().unwrap();
+        assert_eq!(err.short_msg, "DeploymentNotRunning");
+        assert_eq!(model.get_backend_state().await?, BackendState::Paused);
+        Ok(())
+    }
 }
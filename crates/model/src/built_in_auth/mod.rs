@@ -0,0 +1,279 @@
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
+
+use anyhow::Context;
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use rand::Rng;
+use value::{
+    id_v6::DeveloperDocumentId,
+    sha256::Sha256,
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    AuthAccount,
+    AuthCode,
+    AuthCodeKind,
+    AuthEmail,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Table of the accounts of the deployment's built-in auth.
+pub static AUTH_ACCOUNTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_auth_accounts"
+        .parse()
+        .expect("Invalid built-in auth accounts table")
+});
+
+/// Table of the one-time codes emailed to built-in auth accounts, for
+/// verifying their email addresses and for magic links.
+pub static AUTH_CODES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_auth_codes"
+        .parse()
+        .expect("Invalid built-in auth codes table")
+});
+
+static EMAIL_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "email".parse().expect("Invalid built-in field"));
+
+static CODE_HASH_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "codeHash".parse().expect("Invalid built-in field"));
+
+pub static AUTH_ACCOUNTS_INDEX_BY_EMAIL: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&AUTH_ACCOUNTS_TABLE, "by_email"));
+
+pub static AUTH_CODES_INDEX_BY_CODE_HASH: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&AUTH_CODES_TABLE, "by_code_hash"));
+
+pub struct AuthAccountsTable;
+impl SystemTable for AuthAccountsTable {
+    fn table_name(&self) -> &'static TableName {
+        &AUTH_ACCOUNTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: AUTH_ACCOUNTS_INDEX_BY_EMAIL.clone(),
+            fields: vec![EMAIL_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AuthAccount>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct AuthCodesTable;
+impl SystemTable for AuthCodesTable {
+    fn table_name(&self) -> &'static TableName {
+        &AUTH_CODES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: AUTH_CODES_INDEX_BY_CODE_HASH.clone(),
+            fields: vec![CODE_HASH_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<AuthCode>::try_from(document).map(|_| ())
+    }
+}
+
+/// Accounts and one-time codes of the deployment's built-in auth. Only the
+/// system identity may use this model, since the application checks
+/// passwords and codes before calling it.
+pub struct BuiltInAuthModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> BuiltInAuthModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_system(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn get_account_by_email(
+        &mut self,
+        email: &AuthEmail,
+    ) -> anyhow::Result<Option<ParsedDocument<AuthAccount>>> {
+        self.check_system("get_auth_account")?;
+        let query = Query::index_range(IndexRange {
+            index_name: AUTH_ACCOUNTS_INDEX_BY_EMAIL.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                EMAIL_FIELD.clone(),
+                ConvexValue::try_from(email.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()
+    }
+
+    pub async fn get_account(
+        &mut self,
+        account_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<AuthAccount>>> {
+        self.check_system("get_auth_account")?;
+        let id = self.resolve(account_id)?;
+        self.tx.get(id).await?.map(|doc| doc.try_into()).transpose()
+    }
+
+    /// Create an account, failing if there's already one with its email
+    /// address.
+    pub async fn create_account(
+        &mut self,
+        account: AuthAccount,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_system("create_auth_account")?;
+        if self.get_account_by_email(&account.email).await?.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "AccountExists",
+                format!("There is already an account for {}", account.email),
+            ));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&AUTH_ACCOUNTS_TABLE, account.try_into()?)
+            .await
+    }
+
+    pub async fn mark_email_verified(
+        &mut self,
+        account_id: DeveloperDocumentId,
+    ) -> anyhow::Result<()> {
+        self.check_system("verify_auth_account_email")?;
+        let id = self.resolve(account_id)?;
+        let doc = self
+            .tx
+            .get(id)
+            .await?
+            .context("Couldn't find auth account")?;
+        let mut account: ParsedDocument<AuthAccount> = doc.try_into()?;
+        if account.email_verified {
+            return Ok(());
+        }
+        account.email_verified = true;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, account.into_value().try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Create a one-time code of `kind` for the account, returning the code.
+    /// Only the code's hash is stored, so this is the only time it's
+    /// available.
+    pub async fn create_code(
+        &mut self,
+        account_id: DeveloperDocumentId,
+        kind: AuthCodeKind,
+        ttl: Duration,
+    ) -> anyhow::Result<String> {
+        self.check_system("create_auth_code")?;
+        let secret: [u8; 32] = self.tx.runtime().with_rng(|rng| rng.gen());
+        let code = hex::encode(secret);
+        let auth_code = AuthCode {
+            account_id,
+            code_hash: Sha256::hash(code.as_bytes()),
+            kind,
+            expires_at: self.tx.runtime().unix_timestamp() + ttl,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&AUTH_CODES_TABLE, auth_code.try_into()?)
+            .await?;
+        Ok(code)
+    }
+
+    /// Redeem a one-time code of `kind`, returning the id of the account it
+    /// was created for. Codes can only be redeemed once.
+    pub async fn redeem_code(
+        &mut self,
+        code: &str,
+        kind: AuthCodeKind,
+    ) -> anyhow::Result<DeveloperDocumentId> {
+        self.check_system("redeem_auth_code")?;
+        let query = Query::index_range(IndexRange {
+            index_name: AUTH_CODES_INDEX_BY_CODE_HASH.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                CODE_HASH_FIELD.clone(),
+                ConvexValue::try_from(Sha256::hash(code.as_bytes()))?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let auth_code: Option<ParsedDocument<AuthCode>> = query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()?;
+        let Some(auth_code) = auth_code.filter(|auth_code| auth_code.kind == kind) else {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "InvalidAuthCode",
+                "This code is invalid or has already been used",
+            ));
+        };
+        if auth_code.expires_at < self.tx.runtime().unix_timestamp() {
+            anyhow::bail!(ErrorMetadata::unauthenticated(
+                "InvalidAuthCode",
+                "This code has expired",
+            ));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .delete(auth_code.id())
+            .await?;
+        Ok(auth_code.account_id)
+    }
+
+    fn resolve(&mut self, id: DeveloperDocumentId) -> anyhow::Result<ResolvedDocumentId> {
+        id.to_resolved(
+            &self
+                .tx
+                .table_mapping()
+                .namespace(TableNamespace::Global)
+                .number_to_tablet(),
+        )
+    }
+}
@@ -0,0 +1,274 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+};
+
+use common::{
+    obj,
+    runtime::UnixTimestamp,
+    value::ConvexValue,
+};
+use errors::ErrorMetadata;
+use value::{
+    id_v6::DeveloperDocumentId,
+    sha256::Sha256Digest,
+    ConvexObject,
+};
+
+/// Maximum length of an email address, per RFC 5321.
+pub const MAX_EMAIL_LENGTH: usize = 254;
+
+/// Minimum length of a password.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Maximum length of a password, which bounds the work of hashing it.
+pub const MAX_PASSWORD_LENGTH: usize = 256;
+
+/// The email address of an account, normalized to lowercase so that it's
+/// unique regardless of how the user types it.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct AuthEmail(
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(regex = "[a-z0-9._+-]{1,32}@[a-z0-9-]{1,16}\\.[a-z]{2,4}")
+    )]
+    String,
+);
+
+impl FromStr for AuthEmail {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let email = s.trim().to_lowercase();
+        let is_valid = email.len() <= MAX_EMAIL_LENGTH
+            && !email.chars().any(char::is_whitespace)
+            && email
+                .split_once('@')
+                .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'));
+        if !is_valid {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidEmail",
+                format!("{s:?} isn't a valid email address"),
+            ));
+        }
+        Ok(Self(email))
+    }
+}
+
+impl fmt::Display for AuthEmail {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<AuthEmail> for String {
+    fn from(email: AuthEmail) -> Self {
+        email.0
+    }
+}
+
+/// Check that a new password is acceptable.
+pub fn validate_password(password: &str) -> anyhow::Result<()> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "PasswordTooShort",
+            format!("Passwords must be at least {MIN_PASSWORD_LENGTH} characters long"),
+        ));
+    }
+    if password.len() > MAX_PASSWORD_LENGTH {
+        anyhow::bail!(ErrorMetadata::bad_request(
+            "PasswordTooLong",
+            format!("Passwords must be at most {MAX_PASSWORD_LENGTH} bytes long"),
+        ));
+    }
+    Ok(())
+}
+
+/// A user account of the deployment's built-in auth.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct AuthAccount {
+    pub email: AuthEmail,
+    /// Argon2id hash of the account's password, or `None` if the account was
+    /// created by a magic link and can only sign in with one.
+    pub password_hash: Option<String>,
+    pub email_verified: bool,
+}
+
+impl TryFrom<AuthAccount> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(account: AuthAccount) -> anyhow::Result<Self> {
+        obj!(
+            "email" => String::from(account.email),
+            "passwordHash" => account.password_hash,
+            "emailVerified" => account.email_verified,
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for AuthAccount {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields: BTreeMap<_, _> = object.into();
+        let email = match fields.remove("email") {
+            Some(ConvexValue::String(s)) => s.parse()?,
+            v => anyhow::bail!("Invalid email field for AuthAccount: {:?}", v),
+        };
+        let password_hash = match fields.remove("passwordHash") {
+            Some(ConvexValue::String(s)) => Some(s.into()),
+            Some(ConvexValue::Null) => None,
+            v => anyhow::bail!("Invalid passwordHash field for AuthAccount: {:?}", v),
+        };
+        let email_verified = match fields.remove("emailVerified") {
+            Some(ConvexValue::Boolean(b)) => b,
+            v => anyhow::bail!("Invalid emailVerified field for AuthAccount: {:?}", v),
+        };
+        Ok(Self {
+            email,
+            password_hash,
+            email_verified,
+        })
+    }
+}
+
+/// What a one-time code emailed to a user lets them do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum AuthCodeKind {
+    /// Verify the account's email address.
+    VerifyEmail,
+    /// Sign in without a password.
+    MagicLink,
+}
+
+impl AuthCodeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthCodeKind::VerifyEmail => "verifyEmail",
+            AuthCodeKind::MagicLink => "magicLink",
+        }
+    }
+}
+
+impl FromStr for AuthCodeKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "verifyEmail" => Ok(AuthCodeKind::VerifyEmail),
+            "magicLink" => Ok(AuthCodeKind::MagicLink),
+            _ => anyhow::bail!("Invalid auth code kind {s}"),
+        }
+    }
+}
+
+/// A one-time code emailed to a user. Only the code's hash is stored, and
+/// it's deleted when it's redeemed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct AuthCode {
+    pub account_id: DeveloperDocumentId,
+    pub code_hash: Sha256Digest,
+    pub kind: AuthCodeKind,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub expires_at: UnixTimestamp,
+}
+
+impl TryFrom<AuthCode> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(code: AuthCode) -> anyhow::Result<Self> {
+        obj!(
+            "accountId" => code.account_id.encode(),
+            "codeHash" => code.code_hash,
+            "kind" => code.kind.as_str(),
+            "expiresAt" => ConvexValue::Int64(code.expires_at.as_nanos().try_into()?),
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for AuthCode {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields: BTreeMap<_, _> = object.into();
+        let account_id = match fields.remove("accountId") {
+            Some(ConvexValue::String(s)) => DeveloperDocumentId::decode(&s)?,
+            v => anyhow::bail!("Invalid accountId field for AuthCode: {:?}", v),
+        };
+        let code_hash = match fields.remove("codeHash") {
+            Some(v @ ConvexValue::Bytes(_)) => v.try_into()?,
+            v => anyhow::bail!("Invalid codeHash field for AuthCode: {:?}", v),
+        };
+        let kind = match fields.remove("kind") {
+            Some(ConvexValue::String(s)) => s.parse()?,
+            v => anyhow::bail!("Invalid kind field for AuthCode: {:?}", v),
+        };
+        let expires_at = match fields.remove("expiresAt") {
+            Some(ConvexValue::Int64(nanos)) => UnixTimestamp::from_nanos(nanos.try_into()?),
+            v => anyhow::bail!("Invalid expiresAt field for AuthCode: {:?}", v),
+        };
+        Ok(Self {
+            account_id,
+            code_hash,
+            kind,
+            expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::testing::assert_roundtrips;
+    use proptest::prelude::*;
+    use value::ConvexObject;
+
+    use super::{
+        validate_password,
+        AuthAccount,
+        AuthCode,
+        AuthEmail,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_auth_account_roundtrips(v in any::<AuthAccount>()) {
+            assert_roundtrips::<AuthAccount, ConvexObject>(v);
+        }
+
+        #[test]
+        fn test_auth_code_roundtrips(v in any::<AuthCode>()) {
+            assert_roundtrips::<AuthCode, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_parse_auth_email() -> anyhow::Result<()> {
+        let email: AuthEmail = " Ada@Example.com ".parse()?;
+        assert_eq!(email.to_string(), "ada@example.com");
+        assert!("ada".parse::<AuthEmail>().is_err());
+        assert!("@example.com".parse::<AuthEmail>().is_err());
+        assert!("ada lovelace@example.com".parse::<AuthEmail>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_password() {
+        assert!(validate_password("hunter2").is_err());
+        assert!(validate_password("correct horse battery staple").is_ok());
+        assert!(validate_password(&"a".repeat(257)).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use std::collections::BTreeMap;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
 
 use anyhow::Context;
 use common::{
@@ -11,13 +14,16 @@ use common::{
         schema::SchemaState,
     },
     components::{
+        ComponentDefinitionId,
         ComponentDefinitionPath,
         ComponentId,
         ComponentName,
         ComponentPath,
+        Resource,
     },
     document::ParsedDocument,
     runtime::Runtime,
+    types::IndexDiff,
 };
 use database::{
     BootstrapComponentsModel,
@@ -40,6 +46,7 @@ use value::{
     InternalDocumentId,
     InternalId,
     ResolvedDocumentId,
+    TableName,
     TableNamespace,
 };
 
@@ -109,6 +116,32 @@ impl<'a, RT: Runtime> ComponentDefinitionConfigModel<'a, RT> {
                 .await?;
             definition_diffs.insert(definition_path.clone(), diff);
         }
+        let (put_diffs, modules_by_definition) = self
+            .put_component_definitions(new_definitions, source_packages, downloaded_source_packages)
+            .await?;
+        definition_diffs.extend(put_diffs);
+
+        Ok((definition_diffs, modules_by_definition))
+    }
+
+    /// Create or update `new_definitions`, leaving other definitions in
+    /// place.
+    pub async fn put_component_definitions(
+        &mut self,
+        new_definitions: &BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
+        source_packages: &BTreeMap<ComponentDefinitionPath, SourcePackage>,
+        downloaded_source_packages: &BTreeMap<
+            ComponentDefinitionPath,
+            BTreeMap<CanonicalizedModulePath, ModuleConfig>,
+        >,
+    ) -> anyhow::Result<(
+        BTreeMap<ComponentDefinitionPath, ComponentDefinitionDiff>,
+        BTreeMap<InternalId, NewModules>,
+    )> {
+        let mut definition_diffs = BTreeMap::new();
+        let existing_definitions = BootstrapComponentsModel::new(self.tx)
+            .load_all_definitions()
+            .await?;
         let mut modules_by_definition = BTreeMap::new();
 
         for (definition_path, new_definition) in new_definitions {
@@ -167,6 +200,35 @@ impl<'a, RT: Runtime> ComponentDefinitionConfigModel<'a, RT> {
         Ok((definition_diffs, modules_by_definition))
     }
 
+    /// Delete the definitions no component is an instance of anymore, e.g.
+    /// after uninstalling a component.
+    pub async fn delete_unused_component_definitions(
+        &mut self,
+    ) -> anyhow::Result<BTreeMap<ComponentDefinitionPath, ComponentDefinitionDiff>> {
+        let used_definition_ids = BootstrapComponentsModel::new(self.tx)
+            .load_all_components()
+            .await?
+            .into_iter()
+            .map(|component| component.definition_id)
+            .collect::<BTreeSet<_>>();
+        let mut definition_diffs = BTreeMap::new();
+        for (definition_path, existing_definition) in BootstrapComponentsModel::new(self.tx)
+            .load_all_definitions()
+            .await?
+        {
+            if definition_path.is_root()
+                || used_definition_ids.contains(&existing_definition.id().internal_id())
+            {
+                continue;
+            }
+            let diff = self
+                .delete_component_definition(&existing_definition)
+                .await?;
+            definition_diffs.insert(definition_path, diff);
+        }
+        Ok(definition_diffs)
+    }
+
     pub async fn create_component_definition(
         &mut self,
         definition: ComponentDefinitionMetadata,
@@ -236,19 +298,32 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         &mut self,
         app: &CheckedComponent,
         new_definitions: &BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
-    ) -> anyhow::Result<SchemaChange> {
-        let existing_components_by_parent = BootstrapComponentsModel::new(self.tx)
-            .load_all_components()
-            .await?
-            .into_iter()
-            .map(|c| (c.parent_and_name(), c))
-            .collect::<BTreeMap<_, _>>();
+    ) -> anyhow::Result<(SchemaChange, BTreeMap<ComponentPath, SchemaPlan>)> {
+        let existing_components_by_parent = self.existing_components_by_parent().await?;
+        let existing_root = existing_components_by_parent.get(&None);
+        self.start_subtree_schema_changes(
+            &existing_components_by_parent,
+            ComponentPath::root(),
+            existing_root,
+            app,
+            new_definitions,
+        )
+        .await
+    }
 
+    async fn start_subtree_schema_changes(
+        &mut self,
+        existing_components_by_parent: &ComponentsByParent,
+        path: ComponentPath,
+        existing: Option<&ParsedDocument<ComponentMetadata>>,
+        new: &CheckedComponent,
+        new_definitions: &BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
+    ) -> anyhow::Result<(SchemaChange, BTreeMap<ComponentPath, SchemaPlan>)> {
         let mut allocated_component_ids = BTreeMap::new();
         let mut schema_ids = BTreeMap::new();
+        let mut schema_plans = BTreeMap::new();
 
-        let existing_root = existing_components_by_parent.get(&None);
-        let mut stack = vec![(ComponentPath::root(), existing_root, Some(app))];
+        let mut stack = vec![(path, existing, Some(new))];
         while let Some((path, existing_node, new_node)) = stack.pop() {
             // First, diff the schemas of the existing and new nodes.
             let internal_id = match (existing_node, new_node) {
@@ -284,13 +359,43 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                     .get(&new_node.definition_path)
                     .context("Missing definition for component")?;
                 let schema_id = if let Some(ref schema) = definition.schema {
+                    let active_schema = SchemaModel::new(self.tx, namespace)
+                        .get_by_state(SchemaState::Active)
+                        .await?
+                        .map(|(_, schema)| schema);
                     let index_diff = IndexModel::new(self.tx)
                         .prepare_new_and_mutated_indexes(namespace, schema)
                         .await?;
-
-                    // TODO: Return this to the client for presentation.
                     tracing::info!("Index diff for {path:?}: {index_diff:?}");
 
+                    // Documents are only validated if validation is on, and
+                    // only in tables whose document types changed.
+                    let tables_to_validate = if schema.schema_validation {
+                        schema
+                            .tables
+                            .iter()
+                            .filter(|(table_name, table)| {
+                                active_schema
+                                    .as_ref()
+                                    .filter(|active| active.schema_validation)
+                                    .and_then(|active| active.tables.get(*table_name))
+                                    .map_or(true, |active| {
+                                        active.document_type != table.document_type
+                                    })
+                            })
+                            .map(|(table_name, _)| table_name.clone())
+                            .collect()
+                    } else {
+                        vec![]
+                    };
+                    schema_plans.insert(
+                        path.clone(),
+                        SchemaPlan {
+                            index_diff,
+                            tables_to_validate,
+                        },
+                    );
+
                     let (schema_id, schema_state) = SchemaModel::new(self.tx, namespace)
                         .submit_pending(schema.clone())
                         .await?;
@@ -313,15 +418,16 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                 );
             }
             // Second, push children to traverse onto the stack.
-            for child in tree_diff_children(&existing_components_by_parent, new_node, internal_id) {
+            for child in tree_diff_children(existing_components_by_parent, new_node, internal_id) {
                 stack.push((path.join(child.name.clone()), child.existing, child.new));
             }
         }
 
-        Ok(SchemaChange {
+        let schema_change = SchemaChange {
             allocated_component_ids,
             schema_ids,
-        })
+        };
+        Ok((schema_change, schema_plans))
     }
 
     async fn initialize_component_namespace(
@@ -360,6 +466,64 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         udf_config: &UdfConfig,
         schema_change: &SchemaChange,
         modules_by_definition: BTreeMap<InternalId, NewModules>,
+    ) -> anyhow::Result<BTreeMap<ComponentPath, ComponentDiff>> {
+        let existing_components_by_parent = self.existing_components_by_parent().await?;
+        let existing_root = existing_components_by_parent.get(&None);
+        self.apply_subtree_diff(
+            &existing_components_by_parent,
+            SubtreeRoot {
+                path: ComponentPath::root(),
+                parent_and_name: None,
+                existing: existing_root,
+                new: Some(app),
+            },
+            Some(udf_config),
+            schema_change,
+            &modules_by_definition,
+            true,
+        )
+        .await
+    }
+
+    /// Diff the component tree like `apply_component_tree_diff`, but leave
+    /// each component's active schema and indexes as they are. Planning a
+    /// push uses this before its schemas are validated, and rolling back a
+    /// push uses it to restore only code and configuration.
+    pub async fn apply_component_tree_diff_without_schemas(
+        &mut self,
+        app: &CheckedComponent,
+        udf_config: &UdfConfig,
+        schema_change: &SchemaChange,
+        modules_by_definition: BTreeMap<InternalId, NewModules>,
+    ) -> anyhow::Result<BTreeMap<ComponentPath, ComponentDiff>> {
+        let existing_components_by_parent = self.existing_components_by_parent().await?;
+        let existing_root = existing_components_by_parent.get(&None);
+        self.apply_subtree_diff(
+            &existing_components_by_parent,
+            SubtreeRoot {
+                path: ComponentPath::root(),
+                parent_and_name: None,
+                existing: existing_root,
+                new: Some(app),
+            },
+            Some(udf_config),
+            schema_change,
+            &modules_by_definition,
+            false,
+        )
+        .await
+    }
+
+    /// Diff the subtree of components starting at `root`, creating, updating
+    /// and deleting components to match the new subtree.
+    async fn apply_subtree_diff(
+        &mut self,
+        existing_components_by_parent: &ComponentsByParent,
+        root: SubtreeRoot<'_>,
+        udf_config: Option<&UdfConfig>,
+        schema_change: &SchemaChange,
+        modules_by_definition: &BTreeMap<InternalId, NewModules>,
+        apply_schemas: bool,
     ) -> anyhow::Result<BTreeMap<ComponentPath, ComponentDiff>> {
         let definition_id_by_path = BootstrapComponentsModel::new(self.tx)
             .load_all_definitions()
@@ -368,15 +532,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
             .map(|(path, d)| (path, d.id().internal_id()))
             .collect::<BTreeMap<_, _>>();
 
-        let existing_components_by_parent = BootstrapComponentsModel::new(self.tx)
-            .load_all_components()
-            .await?
-            .into_iter()
-            .map(|c| (c.parent_and_name(), c))
-            .collect::<BTreeMap<_, _>>();
-
-        let existing_root = existing_components_by_parent.get(&None);
-        let mut stack = vec![(ComponentPath::root(), None, existing_root, Some(app))];
+        let mut stack = vec![(root.path, root.parent_and_name, root.existing, root.new)];
         let mut diffs = BTreeMap::new();
         while let Some((path, parent_and_name, existing_node, new_node)) = stack.pop() {
             let new_metadata = match new_node {
@@ -398,6 +554,9 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                     Some(ComponentMetadata {
                         definition_id,
                         component_type,
+                        quotas: existing_node
+                            .map(|existing| existing.quotas.clone())
+                            .unwrap_or_default(),
                     })
                 },
                 None => None,
@@ -414,8 +573,8 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                     self.create_component(
                         internal_id,
                         new_metadata,
-                        &modules_by_definition,
-                        udf_config.clone(),
+                        modules_by_definition,
+                        udf_config.context("Missing UDF config")?.clone(),
                     )
                     .await?
                 },
@@ -424,8 +583,8 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
                     self.modify_component(
                         existing_node,
                         new_metadata,
-                        &modules_by_definition,
-                        udf_config.clone(),
+                        modules_by_definition,
+                        udf_config.context("Missing UDF config")?.clone(),
                     )
                     .await?
                 },
@@ -442,7 +601,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
             };
 
             // Apply schema changes when we're not deleting the component.
-            if new_node.is_some() {
+            if apply_schemas && new_node.is_some() {
                 let schema_id = schema_change
                     .schema_ids
                     .get(&path)
@@ -469,7 +628,7 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
             }
 
             // After diffing the current node, push children to traverse onto the stack.
-            for child in tree_diff_children(&existing_components_by_parent, new_node, internal_id) {
+            for child in tree_diff_children(existing_components_by_parent, new_node, internal_id) {
                 stack.push((
                     path.join(child.name.clone()),
                     Some((internal_id, child.name)),
@@ -481,6 +640,243 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
         Ok(diffs)
     }
 
+    /// Start installing `component` at its path, or upgrading the component
+    /// already installed there, by submitting the schemas of its subtree.
+    /// Finish with `install_component` once the schemas are validated.
+    pub async fn start_install_component(
+        &mut self,
+        component: &CheckedComponent,
+        new_definitions: &BTreeMap<ComponentDefinitionPath, EvaluatedComponentDefinition>,
+        upgrade: bool,
+    ) -> anyhow::Result<SchemaChange> {
+        let existing_components_by_parent = self.existing_components_by_parent().await?;
+        let (_, existing) = self
+            .check_runtime_component(&existing_components_by_parent, &component.component_path)
+            .await?;
+        match (existing, upgrade) {
+            (Some(_), false) => anyhow::bail!(ErrorMetadata::bad_request(
+                "ComponentAlreadyInstalled",
+                format!(
+                    "Component {:?} is already installed, upgrade it instead",
+                    String::from(component.component_path.clone())
+                ),
+            )),
+            (None, true) => anyhow::bail!(component_not_found(&component.component_path)),
+            _ => (),
+        }
+        self.check_definitions_not_shared(&existing_components_by_parent, component)
+            .await?;
+        self.start_subtree_schema_changes(
+            &existing_components_by_parent,
+            component.component_path.clone(),
+            existing,
+            component,
+            new_definitions,
+        )
+        .await
+        .map(|(schema_change, _)| schema_change)
+    }
+
+    /// Create or update the components in `component`'s subtree, deleting
+    /// the components of an upgraded subtree that aren't in the new one.
+    pub async fn install_component(
+        &mut self,
+        component: &CheckedComponent,
+        udf_config: &UdfConfig,
+        schema_change: &SchemaChange,
+        modules_by_definition: BTreeMap<InternalId, NewModules>,
+    ) -> anyhow::Result<BTreeMap<ComponentPath, ComponentDiff>> {
+        let existing_components_by_parent = self.existing_components_by_parent().await?;
+        let (parent_and_name, existing) = self
+            .check_runtime_component(&existing_components_by_parent, &component.component_path)
+            .await?;
+        self.check_definitions_not_shared(&existing_components_by_parent, component)
+            .await?;
+        self.apply_subtree_diff(
+            &existing_components_by_parent,
+            SubtreeRoot {
+                path: component.component_path.clone(),
+                parent_and_name: Some(parent_and_name),
+                existing,
+                new: Some(component),
+            },
+            Some(udf_config),
+            schema_change,
+            &modules_by_definition,
+            true,
+        )
+        .await
+    }
+
+    /// Delete the component at `path` and its subtree. The components'
+    /// tables are left in place, like when a push deletes a component.
+    pub async fn uninstall_component(
+        &mut self,
+        path: &ComponentPath,
+    ) -> anyhow::Result<BTreeMap<ComponentPath, ComponentDiff>> {
+        let existing_components_by_parent = self.existing_components_by_parent().await?;
+        let (parent_and_name, existing) = self
+            .check_runtime_component(&existing_components_by_parent, path)
+            .await?;
+        let existing = existing.ok_or_else(|| component_not_found(path))?;
+
+        // Other components can't be left with arguments referencing the
+        // subtree's functions.
+        let paths = component_paths(&existing_components_by_parent)?;
+        for component in existing_components_by_parent.values() {
+            let component_path = &paths[&component.id().internal_id()];
+            if component_path.starts_with(path) {
+                continue;
+            }
+            let ComponentType::ChildComponent { ref args, .. } = component.component_type else {
+                continue;
+            };
+            for resource in args.values() {
+                if let Resource::Function(function) = resource
+                    && function.component.starts_with(path)
+                {
+                    anyhow::bail!(ErrorMetadata::bad_request(
+                        "ComponentInUse",
+                        format!(
+                            "Component {:?} can't be uninstalled, since {:?} is passed its \
+                             function {:?}",
+                            String::from(path.clone()),
+                            String::from(component_path.clone()),
+                            function.udf_path.to_string(),
+                        ),
+                    ));
+                }
+            }
+        }
+
+        let schema_change = SchemaChange {
+            allocated_component_ids: BTreeMap::new(),
+            schema_ids: BTreeMap::new(),
+        };
+        self.apply_subtree_diff(
+            &existing_components_by_parent,
+            SubtreeRoot {
+                path: path.clone(),
+                parent_and_name: Some(parent_and_name),
+                existing: Some(existing),
+                new: None,
+            },
+            None,
+            &schema_change,
+            &BTreeMap::new(),
+            true,
+        )
+        .await
+    }
+
+    async fn existing_components_by_parent(&mut self) -> anyhow::Result<ComponentsByParent> {
+        Ok(BootstrapComponentsModel::new(self.tx)
+            .load_all_components()
+            .await?
+            .into_iter()
+            .map(|c| (c.parent_and_name(), c))
+            .collect())
+    }
+
+    /// Components are installed at runtime under an existing component, and
+    /// can't be declared in its parent's definition, since pushes manage
+    /// those. Returns the component's parent and name, and the component if
+    /// it's installed.
+    async fn check_runtime_component<'b>(
+        &mut self,
+        existing_components_by_parent: &'b ComponentsByParent,
+        path: &ComponentPath,
+    ) -> anyhow::Result<(
+        (InternalId, ComponentName),
+        Option<&'b ParsedDocument<ComponentMetadata>>,
+    )> {
+        let Some((parent_path, name)) = path.parent() else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidComponentPath",
+                "The app can only be changed by pushing it",
+            ));
+        };
+        let parent = BootstrapComponentsModel::new(self.tx)
+            .resolve_path(parent_path.clone())
+            .await?
+            .ok_or_else(|| component_not_found(&parent_path))?;
+        let parent_definition_id = if parent_path.is_root() {
+            ComponentDefinitionId::Root
+        } else {
+            ComponentDefinitionId::Child(parent.definition_id)
+        };
+        let parent_definition = BootstrapComponentsModel::new(self.tx)
+            .load_definition_metadata(parent_definition_id)
+            .await?;
+        if parent_definition
+            .child_components
+            .iter()
+            .any(|instantiation| instantiation.name == name)
+        {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ComponentDeclaredInDefinition",
+                format!(
+                    "Component {:?} is declared in its parent's definition, change it by pushing \
+                     the app instead",
+                    String::from(path.clone())
+                ),
+            ));
+        }
+        let parent_and_name = (parent.id().internal_id(), name);
+        let existing = existing_components_by_parent.get(&Some(parent_and_name.clone()));
+        Ok((parent_and_name, existing))
+    }
+
+    /// Components share the modules of their definition, so a definition in
+    /// `component`'s subtree can't also be used by a component outside it,
+    /// which the install would leave with outdated modules.
+    async fn check_definitions_not_shared(
+        &mut self,
+        existing_components_by_parent: &ComponentsByParent,
+        component: &CheckedComponent,
+    ) -> anyhow::Result<()> {
+        let mut definition_paths = BTreeSet::new();
+        let mut stack = vec![component];
+        while let Some(node) = stack.pop() {
+            anyhow::ensure!(
+                !node.definition_path.is_root(),
+                ErrorMetadata::bad_request(
+                    "InvalidComponentDefinition",
+                    "The app's definition can't be installed as a component",
+                )
+            );
+            definition_paths.insert(node.definition_path.clone());
+            stack.extend(node.child_components.values());
+        }
+        let definition_path_by_id = BootstrapComponentsModel::new(self.tx)
+            .load_all_definitions()
+            .await?
+            .into_iter()
+            .map(|(path, d)| (d.id().internal_id(), path))
+            .collect::<BTreeMap<_, _>>();
+        let paths = component_paths(existing_components_by_parent)?;
+        for existing in existing_components_by_parent.values() {
+            let existing_path = &paths[&existing.id().internal_id()];
+            if existing_path.starts_with(&component.component_path) {
+                continue;
+            }
+            if let Some(definition_path) = definition_path_by_id.get(&existing.definition_id)
+                && definition_paths.contains(definition_path)
+            {
+                anyhow::bail!(ErrorMetadata::bad_request(
+                    "ComponentDefinitionInUse",
+                    format!(
+                        "Definition {:?} is also used by component {:?}, change it by pushing the \
+                         app instead",
+                        String::from(definition_path.clone()),
+                        String::from(existing_path.clone()),
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     async fn create_component(
         &mut self,
         id: InternalId,
@@ -609,11 +1005,55 @@ impl<'a, RT: Runtime> ComponentConfigModel<'a, RT> {
     }
 }
 
+fn component_not_found(path: &ComponentPath) -> ErrorMetadata {
+    ErrorMetadata::not_found(
+        "ComponentNotFound",
+        format!("Component {:?} not found", String::from(path.clone())),
+    )
+}
+
+/// The path of each component, by its id.
+fn component_paths(
+    components_by_parent: &ComponentsByParent,
+) -> anyhow::Result<BTreeMap<InternalId, ComponentPath>> {
+    let parent_and_name_by_id = components_by_parent
+        .iter()
+        .map(|(parent_and_name, c)| (c.id().internal_id(), parent_and_name))
+        .collect::<BTreeMap<_, _>>();
+    let mut paths = BTreeMap::new();
+    for &id in parent_and_name_by_id.keys() {
+        let mut names = vec![];
+        let mut current = id;
+        while let Some((parent, name)) = parent_and_name_by_id
+            .get(&current)
+            .context("Missing component")?
+        {
+            anyhow::ensure!(
+                names.len() < parent_and_name_by_id.len(),
+                "Cyclic component tree"
+            );
+            names.push(name.clone());
+            current = *parent;
+        }
+        names.reverse();
+        paths.insert(id, ComponentPath::from(names));
+    }
+    Ok(paths)
+}
+
+type ComponentsByParent =
+    BTreeMap<Option<(InternalId, ComponentName)>, ParsedDocument<ComponentMetadata>>;
+
+/// The component a subtree diff starts at.
+struct SubtreeRoot<'a> {
+    path: ComponentPath,
+    parent_and_name: Option<(InternalId, ComponentName)>,
+    existing: Option<&'a ParsedDocument<ComponentMetadata>>,
+    new: Option<&'a CheckedComponent>,
+}
+
 fn tree_diff_children<'a>(
-    existing_components_by_parent: &'a BTreeMap<
-        Option<(InternalId, ComponentName)>,
-        ParsedDocument<ComponentMetadata>,
-    >,
+    existing_components_by_parent: &'a ComponentsByParent,
     new_node: Option<&'a CheckedComponent>,
     internal_id: InternalId,
 ) -> impl Iterator<Item = TreeDiffChild<'a>> {
@@ -675,6 +1115,16 @@ pub struct ComponentDiff {
     cron_diff: CronDiff,
 }
 
+impl ComponentDiff {
+    /// Whether applying the diff deletes the component, removes modules or
+    /// deletes crons.
+    pub fn is_destructive(&self) -> bool {
+        matches!(self.diff_type, ComponentDiffType::Delete)
+            || !self.module_diff.removed.is_empty()
+            || !self.cron_diff.deleted.is_empty()
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase", tag = "type")]
 pub enum SerializedComponentDiffType {
@@ -717,6 +1167,30 @@ impl TryFrom<ComponentDiff> for SerializedComponentDiff {
     }
 }
 
+/// What starting a component's schema change does, for presenting a push
+/// before it's applied.
+#[derive(Debug)]
+pub struct SchemaPlan {
+    /// Indexes the new schema adds, which are backfilled, and drops.
+    pub index_diff: IndexDiff,
+    /// Tables whose documents are validated against the new schema.
+    pub tables_to_validate: Vec<TableName>,
+}
+
+impl SchemaPlan {
+    /// Whether the new schema drops an index without replacing it. Mutated
+    /// indexes are dropped and added again under the same name.
+    pub fn is_destructive(&self) -> bool {
+        self.index_diff.dropped.iter().any(|dropped| {
+            !self
+                .index_diff
+                .added
+                .iter()
+                .any(|added| added.name == dropped.name)
+        })
+    }
+}
+
 pub struct SchemaChange {
     pub allocated_component_ids: BTreeMap<ComponentPath, InternalId>,
     pub schema_ids: BTreeMap<ComponentPath, Option<InternalDocumentId>>,
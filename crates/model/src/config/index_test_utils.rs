@@ -102,6 +102,12 @@ macro_rules! db_schema_with_indexes {
                         search_indexes: Default::default(),
                         vector_indexes: Default::default(),
                         document_type: None,
+                        encrypted_fields: Default::default(),
+                        triggers: Default::default(),
+                        track_updates: false,
+                        soft_delete: None,
+                        id_strategy: Default::default(),
+                        sketches: Default::default(),
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -91,6 +91,12 @@ macro_rules! db_schema_with_search_indexes {
                         search_indexes,
                         vector_indexes: Default::default(),
                         document_type: None,
+                        encrypted_fields: Default::default(),
+                        triggers: Default::default(),
+                        track_updates: false,
+                        soft_delete: None,
+                        id_strategy: Default::default(),
+                        sketches: Default::default(),
                     };
                     tables.insert(table_name, table_def);
                 )*
@@ -15,7 +15,10 @@ use crate::{
         ModuleModel,
     },
     source_packages::{
-        types::SourcePackage,
+        types::{
+            SourcePackage,
+            SourcePackageId,
+        },
         SourcePackageModel,
     },
 };
@@ -45,6 +48,30 @@ pub trait ModuleLoader<RT: Runtime>: Sync + Send + 'static {
             .await
             .map(Some)
     }
+
+    /// Like `get_module`, but loads the module's source from
+    /// `source_package_id` rather than the package it was last pushed in,
+    /// e.g. to run a function's canary. Only modules that are active can be
+    /// loaded this way.
+    async fn get_module_from_package(
+        &self,
+        tx: &mut Transaction<RT>,
+        path: CanonicalizedComponentModulePath,
+        source_package_id: SourcePackageId,
+    ) -> anyhow::Result<Option<Arc<FullModuleSource>>> {
+        let component = path.component;
+        let mut module_metadata = match ModuleModel::new(tx).get_metadata(path).await? {
+            Some(r) => r,
+            None => return Ok(None),
+        };
+        let source_package = SourcePackageModel::new(tx, component.into())
+            .get(source_package_id)
+            .await?;
+        module_metadata.source_package_id = source_package_id;
+        self.get_module_with_metadata(module_metadata, source_package)
+            .await
+            .map(Some)
+    }
 }
 
 #[cfg(any(test, feature = "testing"))]
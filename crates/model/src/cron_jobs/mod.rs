@@ -196,9 +196,12 @@ impl<'a, RT: Runtime> CronModel<'a, RT> {
         cron_spec: CronSpec,
     ) -> anyhow::Result<()> {
         let now = self.runtime().generate_timestamp()?;
+        let next_ts = self
+            .runtime()
+            .with_rng(|rng| compute_next_ts(&cron_spec, None, now, rng))?;
         let cron = CronJob {
             name,
-            next_ts: compute_next_ts(&cron_spec, None, now)?,
+            next_ts,
             cron_spec,
             state: CronJobState::Pending,
             prev_ts: None,
@@ -215,9 +218,14 @@ impl<'a, RT: Runtime> CronModel<'a, RT> {
         new_cron_spec: CronSpec,
     ) -> anyhow::Result<()> {
         let (job_id, mut cron_job) = cron_job.into_id_and_value();
-        if new_cron_spec.cron_schedule != cron_job.cron_spec.cron_schedule {
+        if new_cron_spec.cron_schedule != cron_job.cron_spec.cron_schedule
+            || new_cron_spec.timezone != cron_job.cron_spec.timezone
+            || new_cron_spec.jitter_seconds != cron_job.cron_spec.jitter_seconds
+        {
             let now = self.runtime().generate_timestamp()?;
-            cron_job.next_ts = compute_next_ts(&new_cron_spec, cron_job.prev_ts, now)?;
+            cron_job.next_ts = self
+                .runtime()
+                .with_rng(|rng| compute_next_ts(&new_cron_spec, cron_job.prev_ts, now, rng))?;
         }
         cron_job.cron_spec = new_cron_spec;
         self.update_job_state(job_id, cron_job).await?;
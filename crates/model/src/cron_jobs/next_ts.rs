@@ -2,9 +2,16 @@ use std::time::Duration;
 
 use anyhow::Context;
 use chrono::{
+    DateTime,
+    LocalResult,
+    NaiveDateTime,
+    Offset,
+    TimeDelta,
     TimeZone,
     Utc,
 };
+use chrono_tz::Tz;
+use rand::Rng;
 use saffron::Cron;
 use sync_types::Timestamp;
 
@@ -13,16 +20,24 @@ use super::types::{
     CronSpec,
 };
 
+/// Compute the next time the cron should run after `prev_ts`, or after `now`
+/// if it has never run. The hours and minutes of the schedule are interpreted
+/// in the spec's timezone, and if the spec has a jitter window the result is
+/// delayed by a random amount within it.
 pub fn compute_next_ts(
     cron_spec: &CronSpec,
     prev_ts: Option<Timestamp>,
     now: Timestamp,
+    rng: &mut impl Rng,
 ) -> anyhow::Result<Timestamp> {
     let cron: Cron = match cron_spec.cron_schedule.clone() {
         CronSchedule::Interval { seconds } => {
+            let interval = Duration::from_secs(seconds as u64);
             let next_ts = match prev_ts {
-                Some(prev_ts) => prev_ts.add(Duration::from_secs(seconds as u64))?,
-                None => now,
+                Some(prev_ts) => prev_ts.add(interval)?,
+                // Intervals aren't anchored to the wall clock, so only jitter the first run.
+                // Every later run keeps the same offset.
+                None => now.add(jitter(cron_spec, interval, rng))?,
             };
             return Ok(next_ts);
         },
@@ -53,13 +68,20 @@ pub fn compute_next_ts(
             .parse()
             .context("Cron Schedule: Cron parsing from Saffron failed")?,
     };
+    let tz = cron_spec.timezone.unwrap_or(Tz::UTC);
     let prev_ts = prev_ts.unwrap_or(now);
     let prev_ts_nanos: i64 = prev_ts.into();
     let prev_ts_utc = Utc.timestamp_nanos(prev_ts_nanos);
-    let next_ts_utc = match cron.next_after(prev_ts_utc) {
-        Some(next_ts_utc) => next_ts_utc,
-        None => return Err(anyhow::anyhow!("Could not compute next timestamp for cron")),
-    };
+    let mut next_ts_utc = next_after_in_tz(&cron, tz, prev_ts_utc)?;
+    if cron_spec.jitter_seconds.is_some() {
+        // Keep the jittered run before the following scheduled run, so that
+        // computing the next run from it doesn't skip a run.
+        let following_ts_utc = next_after_in_tz(&cron, tz, next_ts_utc)?;
+        let gap = (following_ts_utc - next_ts_utc)
+            .to_std()
+            .context("Cron runs out of order")?;
+        next_ts_utc += TimeDelta::from_std(jitter(cron_spec, gap, rng))?;
+    }
     let next_ts_nanos = next_ts_utc
         .timestamp_nanos_opt()
         .context("Unable to get nanos from UTC")?;
@@ -67,9 +89,77 @@ pub fn compute_next_ts(
     Ok(next_ts)
 }
 
+/// The first time after `prev_ts` that `cron` matches, with its hours and
+/// minutes interpreted in `tz`.
+pub fn next_cron_ts(cron: &Cron, tz: Tz, prev_ts: Timestamp) -> anyhow::Result<Timestamp> {
+    let prev_ts_nanos: i64 = prev_ts.into();
+    let next_ts_utc = next_after_in_tz(cron, tz, Utc.timestamp_nanos(prev_ts_nanos))?;
+    let next_ts_nanos = next_ts_utc
+        .timestamp_nanos_opt()
+        .context("Unable to get nanos from UTC")?;
+    Ok(next_ts_nanos.try_into()?)
+}
+
+/// Saffron only understands UTC, so evaluate the cron against the local wall
+/// clock time as if it were UTC and then convert the result back.
+fn next_after_in_tz(cron: &Cron, tz: Tz, prev: DateTime<Utc>) -> anyhow::Result<DateTime<Utc>> {
+    let mut local = prev.with_timezone(&tz).naive_local();
+    // Each iteration moves `local` forward, and a wall clock time can only be
+    // before `prev` around a DST transition, so this terminates quickly.
+    for _ in 0..8 {
+        let Some(next_local) = cron.next_after(Utc.from_utc_datetime(&local)) else {
+            anyhow::bail!("Could not compute next timestamp for cron");
+        };
+        let next_local = next_local.naive_utc();
+        let next = match tz.from_local_datetime(&next_local) {
+            LocalResult::Single(next) => next.with_timezone(&Utc),
+            // The wall clock time happens twice when clocks are turned back. Run
+            // at the first one we haven't passed yet.
+            LocalResult::Ambiguous(earliest, latest) => {
+                if earliest.with_timezone(&Utc) > prev {
+                    earliest.with_timezone(&Utc)
+                } else {
+                    latest.with_timezone(&Utc)
+                }
+            },
+            // The wall clock time is skipped when clocks are turned forward. Run
+            // as if the clocks hadn't changed yet, which lands just after the gap.
+            LocalResult::None => skipped_local_to_utc(tz, next_local),
+        };
+        if next > prev {
+            return Ok(next);
+        }
+        local = next_local;
+    }
+    anyhow::bail!("Could not compute next timestamp for cron in {}", tz.name())
+}
+
+fn skipped_local_to_utc(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    let offset_before = tz
+        .offset_from_utc_datetime(&(local - TimeDelta::days(1)))
+        .fix();
+    let utc = local - TimeDelta::seconds(offset_before.local_minus_utc() as i64);
+    Utc.from_utc_datetime(&utc)
+}
+
+/// Pick a random delay within the spec's jitter window, capped at `max`.
+fn jitter(cron_spec: &CronSpec, max: Duration, rng: &mut impl Rng) -> Duration {
+    let Some(jitter_seconds) = cron_spec.jitter_seconds else {
+        return Duration::ZERO;
+    };
+    let window_seconds = jitter_seconds.min(max.as_secs());
+    if window_seconds == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs(rng.gen_range(0..window_seconds))
+}
+
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{
+        str::FromStr,
+        time::Duration,
+    };
 
     use sync_types::{
         Timestamp,
@@ -80,6 +170,7 @@ mod tests {
     use crate::cron_jobs::{
         next_ts::compute_next_ts,
         types::{
+            CronCatchUpPolicy,
             CronSchedule,
             CronSpec,
         },
@@ -92,16 +183,19 @@ mod tests {
             udf_path: UdfPath::from_str("test").unwrap().canonicalize(),
             udf_args: ConvexArray::try_from(vec![]).unwrap(),
             cron_schedule: CronSchedule::Interval { seconds: 60 },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
         };
 
         // Mar 01 2023 08:35:00 UTC
         let now = Timestamp::try_from(i64::pow(10, 9) * 1677659700).unwrap();
         let mut prev_ts = None;
-        let mut result = compute_next_ts(&cron_spec, prev_ts, now);
+        let mut result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         assert_eq!(result.unwrap(), now);
 
         prev_ts = Some(now);
-        result = compute_next_ts(&cron_spec, prev_ts, now);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // Mar 01 2023 08:36:00 UTC
         let expected = Timestamp::try_from(i64::pow(10, 9) * 1677659760).unwrap();
         assert_eq!(result.unwrap(), expected);
@@ -114,18 +208,21 @@ mod tests {
             udf_path: UdfPath::from_str("test").unwrap().canonicalize(),
             udf_args: ConvexArray::try_from(vec![]).unwrap(),
             cron_schedule: CronSchedule::Hourly { minute_utc: 5 },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
         };
 
         // Mar 01 2023 08:35:00 UTC
         let now = Timestamp::try_from(i64::pow(10, 9) * 1677659700).unwrap();
         let mut prev_ts = None;
-        let mut result = compute_next_ts(&cron_spec, prev_ts, now);
+        let mut result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // Mar 01 2023 09:05:00 UTC
         let mut expected = Timestamp::try_from(i64::pow(10, 9) * 1677661500).unwrap();
         assert_eq!(result.unwrap(), expected);
 
         prev_ts = Some(expected);
-        result = compute_next_ts(&cron_spec, prev_ts, now);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // Mar 01 2023 10:05:00 UTC
         expected = Timestamp::try_from(i64::pow(10, 9) * 1677665100).unwrap();
         assert_eq!(result.unwrap(), expected);
@@ -141,18 +238,21 @@ mod tests {
                 hour_utc: 8,
                 minute_utc: 30,
             },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
         };
 
         // Feb 28 2023 08:35:00 UTC
         let now = Timestamp::try_from(i64::pow(10, 9) * 1677573300).unwrap();
         let mut prev_ts = None;
-        let mut result = compute_next_ts(&cron_spec, prev_ts, now);
+        let mut result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // Mar 01 2023 8:30:00 UTC
         let mut expected = Timestamp::try_from(i64::pow(10, 9) * 1677659400).unwrap();
         assert_eq!(result.unwrap(), expected);
 
         prev_ts = Some(expected);
-        result = compute_next_ts(&cron_spec, prev_ts, now);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // Mar 02 2023 8:30:00 UTC
         expected = Timestamp::try_from(i64::pow(10, 9) * 1677745800).unwrap();
         assert_eq!(result.unwrap(), expected);
@@ -169,18 +269,21 @@ mod tests {
                 hour_utc: 12,
                 minute_utc: 30,
             },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
         };
 
         // Feb 28 2023 08:35:00 UTC
         let now = Timestamp::try_from(i64::pow(10, 9) * 1677573300).unwrap();
         let mut prev_ts = None;
-        let mut result = compute_next_ts(&cron_spec, prev_ts, now);
+        let mut result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // Feb 28 2023 12:30:00 UTC
         let mut expected = Timestamp::try_from(i64::pow(10, 9) * 1677587400).unwrap();
         assert_eq!(result.unwrap(), expected);
 
         prev_ts = Some(expected);
-        result = compute_next_ts(&cron_spec, prev_ts, now);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // Mar 07 2023 12:30:00 UTC
         expected = Timestamp::try_from(i64::pow(10, 9) * 1678192200).unwrap();
         assert_eq!(result.unwrap(), expected);
@@ -197,18 +300,21 @@ mod tests {
                 hour_utc: 12,
                 minute_utc: 30,
             },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
         };
 
         // Feb 28 2023 08:35:00 UTC
         let now = Timestamp::try_from(i64::pow(10, 9) * 1677573300).unwrap();
         let mut prev_ts = None;
-        let mut result = compute_next_ts(&cron_spec, prev_ts, now);
+        let mut result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // March 1 2023 12:30:00 UTC
         let mut expected = Timestamp::try_from(i64::pow(10, 9) * 1677673800).unwrap();
         assert_eq!(result.unwrap(), expected);
 
         prev_ts = Some(expected);
-        result = compute_next_ts(&cron_spec, prev_ts, now);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // April 1 2023 12:30:00 UTC
         // fun fact: this also tests that daylight savings was computed correctly
         expected = Timestamp::try_from(i64::pow(10, 9) * 1680352200).unwrap();
@@ -226,18 +332,21 @@ mod tests {
             cron_schedule: CronSchedule::Cron {
                 cron_expr: "0 12 * * 1,5".to_string(),
             },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
         };
 
         // Feb 28 2023 08:35:00 UTC
         let now = Timestamp::try_from(i64::pow(10, 9) * 1677573300).unwrap();
         let mut prev_ts = None;
-        let mut result = compute_next_ts(&cron_spec, prev_ts, now);
+        let mut result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // March 3 2023 18:00:00 UTC
         let mut expected = Timestamp::try_from(i64::pow(10, 9) * 1677844800).unwrap();
         assert_eq!(result.unwrap(), expected);
 
         prev_ts = Some(expected);
-        result = compute_next_ts(&cron_spec, prev_ts, now);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         // March 6 2023 18:00:00 UTC
         expected = Timestamp::try_from(i64::pow(10, 9) * 1678104000).unwrap();
         assert_eq!(result.unwrap(), expected);
@@ -249,10 +358,94 @@ mod tests {
             cron_schedule: CronSchedule::Cron {
                 cron_expr: "0 12 * * 7".to_string(),
             },
+            timezone: None,
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
         };
-        result = compute_next_ts(&cron_spec, prev_ts, now);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
         assert!(result.is_err());
         assert!(format!("{:?}", result.unwrap_err())
             .contains("Cron Schedule: Cron parsing from Saffron failed"));
     }
+
+    #[test]
+    fn test_compute_next_ts_timezone() {
+        // Every day at midnight in New York
+        let cron_spec = CronSpec {
+            udf_path: UdfPath::from_str("test").unwrap().canonicalize(),
+            udf_args: ConvexArray::try_from(vec![]).unwrap(),
+            cron_schedule: CronSchedule::Daily {
+                hour_utc: 0,
+                minute_utc: 0,
+            },
+            timezone: Some(chrono_tz::America::New_York),
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
+        };
+
+        // Mar 01 2023 08:35:00 UTC
+        let now = Timestamp::try_from(i64::pow(10, 9) * 1677659700).unwrap();
+        let result = compute_next_ts(&cron_spec, None, now, &mut rand::thread_rng());
+        // Mar 02 2023 05:00:00 UTC
+        let expected = Timestamp::try_from(i64::pow(10, 9) * 1677733200).unwrap();
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_compute_next_ts_timezone_dst() {
+        // Every day at 2:30 in New York, which doesn't exist on Mar 12 2023
+        let cron_spec = CronSpec {
+            udf_path: UdfPath::from_str("test").unwrap().canonicalize(),
+            udf_args: ConvexArray::try_from(vec![]).unwrap(),
+            cron_schedule: CronSchedule::Daily {
+                hour_utc: 2,
+                minute_utc: 30,
+            },
+            timezone: Some(chrono_tz::America::New_York),
+            jitter_seconds: None,
+            catch_up: CronCatchUpPolicy::Skip,
+        };
+
+        // Mar 11 2023 07:30:00 UTC
+        let now = Timestamp::try_from(i64::pow(10, 9) * 1678519800).unwrap();
+        let mut prev_ts = Some(now);
+        let mut result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
+        // Mar 12 2023 07:30:00 UTC, which is 3:30 in New York
+        let mut expected = Timestamp::try_from(i64::pow(10, 9) * 1678606200).unwrap();
+        assert_eq!(result.unwrap(), expected);
+
+        prev_ts = Some(expected);
+        result = compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng());
+        // Mar 13 2023 06:30:00 UTC
+        expected = Timestamp::try_from(i64::pow(10, 9) * 1678689000).unwrap();
+        assert_eq!(result.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_compute_next_ts_jitter() {
+        // Every hour on the hour, jittered by up to two hours
+        let cron_spec = CronSpec {
+            udf_path: UdfPath::from_str("test").unwrap().canonicalize(),
+            udf_args: ConvexArray::try_from(vec![]).unwrap(),
+            cron_schedule: CronSchedule::Hourly { minute_utc: 0 },
+            timezone: None,
+            jitter_seconds: Some(7200),
+            catch_up: CronCatchUpPolicy::Skip,
+        };
+
+        // Mar 01 2023 08:35:00 UTC
+        let now = Timestamp::try_from(i64::pow(10, 9) * 1677659700).unwrap();
+        // Mar 01 2023 09:00:00 UTC
+        let mut expected = Timestamp::try_from(i64::pow(10, 9) * 1677661200).unwrap();
+        let mut prev_ts = None;
+        for _ in 0..10 {
+            let result =
+                compute_next_ts(&cron_spec, prev_ts, now, &mut rand::thread_rng()).unwrap();
+            // The jitter is capped at the gap between runs so none are skipped.
+            assert!(result >= expected);
+            assert!(result < expected.add(Duration::from_secs(3600)).unwrap());
+            prev_ts = Some(result);
+            expected = expected.add(Duration::from_secs(3600)).unwrap();
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use std::{
     borrow::Borrow,
     collections::BTreeMap,
+    fmt,
     mem,
     ops::Deref,
     str::FromStr,
@@ -10,6 +11,7 @@ use anyhow::{
     bail,
     Context,
 };
+use chrono_tz::Tz;
 use common::{
     log_lines::RawLogLines,
     types::Timestamp,
@@ -43,6 +45,12 @@ pub enum CronValidationError {
     SecondsMinutesHours,
     #[error("Interval must be an integer greater than 0")]
     InvalidIntervalValue,
+    #[error("Unknown timezone {0:?}, expected an IANA timezone like \"America/New_York\"")]
+    InvalidTimezone(String),
+    #[error("Jitter must be an integer number of seconds greater than 0")]
+    InvalidJitterValue,
+    #[error("Unknown catch-up policy {0:?}, expected \"skip\", \"runOnce\" or \"runAll\"")]
+    InvalidCatchUpPolicy(String),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -172,6 +180,24 @@ pub struct CronSpec {
     )]
     pub udf_args: ConvexArray,
     pub cron_schedule: CronSchedule,
+    // IANA timezone that the hours and minutes of `cron_schedule` are in.
+    // Defaults to UTC.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(proptest::sample::select(&\
+                             chrono_tz::TZ_VARIANTS[..]))")
+    )]
+    pub timezone: Option<Tz>,
+    // Delay each run by a random amount of up to this many seconds, so that
+    // many crons on the same schedule don't all start in the same second.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(1..=86400u64)")
+    )]
+    pub jitter_seconds: Option<u64>,
+    // What to do with runs that were missed because the backend was down or
+    // the previous run was still executing.
+    pub catch_up: CronCatchUpPolicy,
 }
 
 impl HeapSize for CronSpec {
@@ -187,6 +213,12 @@ pub struct SerializedCronSpec {
     #[serde(with = "serde_bytes")]
     udf_args: Option<Vec<u8>>,
     cron_schedule: SerializedCronSchedule,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jitter_seconds: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    catch_up: Option<String>,
 }
 
 impl TryFrom<CronSpec> for SerializedCronSpec {
@@ -201,6 +233,10 @@ impl TryFrom<CronSpec> for SerializedCronSpec {
             udf_path: String::from(spec.udf_path),
             udf_args: Some(udf_args_bytes),
             cron_schedule: spec.cron_schedule.try_into()?,
+            timezone: spec.timezone.map(|tz| tz.name().to_string()),
+            jitter_seconds: spec.jitter_seconds.map(i64::try_from).transpose()?,
+            catch_up: (spec.catch_up != CronCatchUpPolicy::default())
+                .then(|| spec.catch_up.to_string()),
         })
     }
 }
@@ -218,14 +254,74 @@ impl TryFrom<SerializedCronSpec> for CronSpec {
             None => ConvexArray::try_from(vec![])?,
         };
         let cron_schedule = value.cron_schedule.try_into()?;
+        let timezone = value.timezone.map(|tz| parse_timezone(&tz)).transpose()?;
+        let jitter_seconds = value.jitter_seconds.map(u64::try_from).transpose()?;
+        let catch_up = value
+            .catch_up
+            .map(|catch_up| catch_up.parse())
+            .transpose()?
+            .unwrap_or_default();
         Ok(Self {
             udf_path,
             udf_args,
             cron_schedule,
+            timezone,
+            jitter_seconds,
+            catch_up,
         })
     }
 }
 
+fn parse_timezone(tz: &str) -> anyhow::Result<Tz> {
+    tz.parse::<Tz>()
+        .map_err(|_| CronValidationError::InvalidTimezone(tz.to_string()).into())
+}
+
+/// What a cron does with the runs it missed, either because the backend was
+/// down when they were due or because the previous run took longer than the
+/// interval between runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum CronCatchUpPolicy {
+    /// Skip every missed run and wait for the next scheduled time.
+    Skip,
+    /// Collapse the missed runs into a single run that happens immediately.
+    /// This is how crons have always handled downtime, so it's the default.
+    #[default]
+    RunOnce,
+    /// Run every missed run back to back, up to `CRON_JOB_MAX_CATCH_UP_RUNS`.
+    RunAll,
+}
+
+impl CronCatchUpPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::RunOnce => "runOnce",
+            Self::RunAll => "runAll",
+        }
+    }
+}
+
+impl fmt::Display for CronCatchUpPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for CronCatchUpPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "skip" => Ok(Self::Skip),
+            "runOnce" => Ok(Self::RunOnce),
+            "runAll" => Ok(Self::RunAll),
+            _ => anyhow::bail!(CronValidationError::InvalidCatchUpPolicy(s.to_string())),
+        }
+    }
+}
+
 mod codegen_cron_spec {
     use value::codegen_convex_serialization;
 
@@ -305,6 +401,9 @@ impl TryFrom<JsonValue> for CronSpec {
             name: String,
             args: JsonValue,
             schedule: ScheduleJson,
+            timezone: Option<String>,
+            jitter_seconds: Option<i64>,
+            catch_up: Option<String>,
         }
         let j: CronSpecJson = serde_json::from_value(value.clone())
             .with_context(|| CronValidationError::InvalidJson)?;
@@ -436,12 +535,29 @@ impl TryFrom<JsonValue> for CronSpec {
             },
         };
 
+        let timezone = j.timezone.map(|tz| parse_timezone(&tz)).transpose()?;
+        let jitter_seconds = match j.jitter_seconds {
+            Some(jitter_seconds) if jitter_seconds <= 0 => {
+                anyhow::bail!(CronValidationError::InvalidJitterValue)
+            },
+            Some(jitter_seconds) => Some(jitter_seconds as u64),
+            None => None,
+        };
+        let catch_up = j
+            .catch_up
+            .map(|catch_up| catch_up.parse())
+            .transpose()?
+            .unwrap_or_default();
+
         let udf_path: UdfPath = j.name.parse()?;
         let udf_path_canonicalized = udf_path.canonicalize();
         Ok(Self {
             udf_path: udf_path_canonicalized,
             udf_args: ConvexArray::try_from(j.args)?,
             cron_schedule: schedule,
+            timezone,
+            jitter_seconds,
+            catch_up,
         })
     }
 }
@@ -1035,11 +1151,13 @@ mod tests {
     };
 
     use crate::cron_jobs::types::{
+        CronCatchUpPolicy,
         CronJob,
         CronJobLog,
         CronJobLogLines,
         CronJobResult,
         CronJobStatus,
+        CronSpec,
     };
 
     proptest! {
@@ -1100,4 +1218,64 @@ mod tests {
         );
         assert_roundtrips::<_, CronJob>(cron_job_obj);
     }
+
+    #[test]
+    fn test_cron_spec_timezone_and_jitter_from_json() -> anyhow::Result<()> {
+        let spec = CronSpec::try_from(serde_json::json!({
+            "name": "crons.js:addOne",
+            "args": [{}],
+            "schedule": {"type": "daily", "hourUTC": 0, "minuteUTC": 0},
+            "timezone": "America/New_York",
+            "jitterSeconds": 60,
+        }))?;
+        assert_eq!(spec.timezone, Some(chrono_tz::America::New_York));
+        assert_eq!(spec.jitter_seconds, Some(60));
+
+        let err = CronSpec::try_from(serde_json::json!({
+            "name": "crons.js:addOne",
+            "args": [{}],
+            "schedule": {"type": "daily", "hourUTC": 0, "minuteUTC": 0},
+            "timezone": "Mars/Olympus_Mons",
+        }))
+        .unwrap_err();
+        assert!(format!("{err}").contains("Unknown timezone"));
+
+        let err = CronSpec::try_from(serde_json::json!({
+            "name": "crons.js:addOne",
+            "args": [{}],
+            "schedule": {"type": "daily", "hourUTC": 0, "minuteUTC": 0},
+            "jitterSeconds": 0,
+        }))
+        .unwrap_err();
+        assert!(format!("{err}").contains("Jitter must be"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cron_spec_catch_up_from_json() -> anyhow::Result<()> {
+        let spec = CronSpec::try_from(serde_json::json!({
+            "name": "crons.js:addOne",
+            "args": [{}],
+            "schedule": {"type": "hourly", "minuteUTC": 0},
+        }))?;
+        assert_eq!(spec.catch_up, CronCatchUpPolicy::RunOnce);
+
+        let spec = CronSpec::try_from(serde_json::json!({
+            "name": "crons.js:addOne",
+            "args": [{}],
+            "schedule": {"type": "hourly", "minuteUTC": 0},
+            "catchUp": "runAll",
+        }))?;
+        assert_eq!(spec.catch_up, CronCatchUpPolicy::RunAll);
+
+        let err = CronSpec::try_from(serde_json::json!({
+            "name": "crons.js:addOne",
+            "args": [{}],
+            "schedule": {"type": "hourly", "minuteUTC": 0},
+            "catchUp": "runTwice",
+        }))
+        .unwrap_err();
+        assert!(format!("{err}").contains("Unknown catch-up policy"));
+        Ok(())
+    }
 }
@@ -14,6 +14,10 @@ use database::{
     SystemMetadataModel,
     Transaction,
 };
+use keybroker::{
+    AdminIdentityPrincipal,
+    Identity,
+};
 use value::{
     ConvexObject,
     FieldPath,
@@ -54,6 +58,41 @@ impl SystemTable for DeploymentAuditLogsTable {
     }
 }
 
+/// Describes who performed an audited action: the team member or team whose
+/// admin key was used along with the key's role, or the system.
+/// `member_id_override` is for actions that the system performs on behalf of a
+/// member, like finishing an import they started.
+pub fn audit_log_actor(
+    identity: &Identity,
+    member_id_override: Option<MemberId>,
+) -> anyhow::Result<ConvexObject> {
+    if let Some(member_id) = member_id_override {
+        return obj!(
+            "type" => "admin",
+            "member_id" => i64::try_from(u64::from(member_id))?,
+            "team_id" => null,
+            "role" => null
+        );
+    }
+    let admin = match identity {
+        Identity::InstanceAdmin(admin) | Identity::ActingUser(admin, _) => admin,
+        Identity::System(_) => return obj!("type" => "system"),
+        Identity::User(_) | Identity::ApiKey(_) | Identity::Unknown => {
+            return obj!("type" => "unknown")
+        },
+    };
+    let (member_id, team_id) = match admin.principal() {
+        AdminIdentityPrincipal::Member(member_id) => (Some(u64::from(*member_id)), None),
+        AdminIdentityPrincipal::Team(team_id) => (None, Some(u64::from(*team_id))),
+    };
+    obj!(
+        "type" => "admin",
+        "member_id" => member_id.map(i64::try_from).transpose()?,
+        "team_id" => team_id.map(i64::try_from).transpose()?,
+        "role" => admin.role().to_string()
+    )
+}
+
 pub struct DeploymentAuditLogModel<'a, RT: Runtime> {
     tx: &'a mut Transaction<RT>,
 }
@@ -88,6 +127,7 @@ impl<'a, RT: Runtime> DeploymentAuditLogModel<'a, RT> {
                 i64::try_from(member_id_u64)
             })
             .transpose()?;
+        let actor = audit_log_actor(self.tx.identity(), member_id_override)?;
         let mut deployment_audit_log_ids = vec![];
         for event in events {
             let event_object: ConvexObject = event.try_into()?;
@@ -95,6 +135,8 @@ impl<'a, RT: Runtime> DeploymentAuditLogModel<'a, RT> {
                 Some(member_id) => event_object.shallow_merge(obj!("member_id" => member_id)?)?,
                 None => event_object.shallow_merge(obj!("member_id" => null)?)?,
             };
+            let event_object_with_member_id =
+                event_object_with_member_id.shallow_merge(obj!("actor" => actor.clone())?)?;
             let id = SystemMetadataModel::new_global(self.tx)
                 .insert_metadata(&DEPLOYMENT_AUDIT_LOG_TABLE, event_object_with_member_id)
                 .await?;
@@ -18,8 +18,10 @@ use database::LegacyIndexDiff;
 use proptest::prelude::*;
 use serde_json::Value as JsonValue;
 use value::{
+    export::ValueFormat,
     obj,
     remove_int64,
+    remove_nullable_string,
     remove_object,
     remove_string,
     remove_vec,
@@ -30,9 +32,12 @@ use value::{
 };
 
 use crate::{
+    api_keys::types::ApiKeyName,
     backend_state::types::BackendState,
     config::types::ConfigDiff,
     environment_variables::types::EnvVarName,
+    exports::types::ExportFormat,
+    log_sinks::types::SinkType,
     snapshot_imports::types::{
         ImportFormat,
         ImportMode,
@@ -94,6 +99,155 @@ pub enum DeploymentAuditLogEvent {
         import_mode: ImportMode,
         import_format: ImportFormat,
     },
+    CreateApiKey {
+        name: ApiKeyName,
+    },
+    DeleteApiKey {
+        name: ApiKeyName,
+    },
+    CreateLogSink {
+        sink_type: SinkType,
+    },
+    UpdateLogSinkRules {
+        sink_type: SinkType,
+    },
+    DeleteLogSink {
+        sink_type: SinkType,
+    },
+    RevokeSessions {
+        subject: String,
+        issuer: Option<String>,
+        token_id: Option<String>,
+    },
+    RequestExport {
+        format: ExportFormat,
+    },
+    CreateExportSchedule {
+        name: String,
+    },
+    DeleteExportSchedule {
+        name: String,
+    },
+    CloneDeployment {
+        target_url: String,
+    },
+    /// A successful call to one of the dashboard's `_system/frontend`
+    /// mutations, which is how admins edit data from the dashboard.
+    DashboardEdit {
+        function: String,
+        request_id: String,
+    },
+    CreateFileLifecycleRule {
+        name: String,
+    },
+    DeleteFileLifecycleRule {
+        name: String,
+    },
+    /// A change to one of the backend's settings that can be changed without
+    /// a restart, with the values formatted as in the settings API.
+    UpdateBackendSetting {
+        name: String,
+        previous_value: String,
+        value: String,
+    },
+    /// A soft-deleted document moved back from the trash into its table.
+    RestoreDocument {
+        table_name: String,
+        document_id: String,
+    },
+    /// `mode` describes the flag, e.g. "disabled" or "canary at 10%".
+    SetFunctionFlag {
+        component_path: String,
+        udf_path: String,
+        mode: String,
+    },
+    DeleteFunctionFlag {
+        component_path: String,
+        udf_path: String,
+    },
+    /// Proxy credentials are never logged.
+    UpdateEgressPolicy {
+        allowed_domains: Vec<String>,
+        denied_domains: Vec<String>,
+        proxy_url: Option<String>,
+    },
+    DeleteEgressPolicy,
+    /// `operation` is "delete" or "patch". Filters and patched values aren't
+    /// logged, since they can contain user data.
+    BulkEditDocuments {
+        table_name: String,
+        operation: String,
+        document_count: u64,
+    },
+    PinSearchIndex {
+        table_name: String,
+        index_name: String,
+    },
+    UnpinSearchIndex {
+        table_name: String,
+        index_name: String,
+    },
+    RenameTable {
+        table_name: String,
+        new_name: String,
+    },
+    ArchiveTable {
+        table_name: String,
+    },
+    /// `new_name` is the name the table was restored under, which is the same
+    /// as `table_name` unless it was restored under a different name.
+    RestoreTable {
+        table_name: String,
+        new_name: String,
+    },
+    /// Only the target's name and type are logged since its headers may
+    /// contain credentials.
+    SetOutboxTarget {
+        name: String,
+        target_type: String,
+    },
+    DeleteOutboxTarget {
+        name: String,
+    },
+    /// The target's admin key isn't logged.
+    UpdateTrafficShadowing {
+        target_url: String,
+    },
+    DeleteTrafficShadowing,
+    SetFunctionReadBudget {
+        component_path: String,
+        udf_path: String,
+    },
+    DeleteFunctionReadBudget {
+        component_path: String,
+        udf_path: String,
+    },
+    /// `udf_path` is None for the deployment-wide setting.
+    SetLogSetting {
+        udf_path: Option<String>,
+    },
+    DeleteLogSetting {
+        udf_path: Option<String>,
+    },
+    /// A push was undone because its schema failed validation or its smoke
+    /// test failed.
+    RollbackPush {
+        reason: String,
+    },
+    /// An admin switched the deployment back to one of its recent versions.
+    RestoreDeploymentVersion {
+        version_id: String,
+    },
+    /// Logged when a canary is started and when its rollout changes.
+    SetFunctionCanary {
+        component_path: String,
+        udf_path: String,
+        rollout_percentage: u8,
+    },
+    DeleteFunctionCanary {
+        component_path: String,
+        udf_path: String,
+    },
 }
 
 impl From<LegacyIndexDiff> for DeploymentAuditLogEvent {
@@ -142,6 +296,47 @@ impl DeploymentAuditLogEvent {
             DeploymentAuditLogEvent::ChangeDeploymentState { .. } => "change_deployment_state",
             DeploymentAuditLogEvent::SnapshotImport { .. } => "snapshot_import",
             DeploymentAuditLogEvent::ClearTables => "clear_tables",
+            DeploymentAuditLogEvent::CreateApiKey { .. } => "create_api_key",
+            DeploymentAuditLogEvent::DeleteApiKey { .. } => "delete_api_key",
+            DeploymentAuditLogEvent::CreateLogSink { .. } => "create_log_sink",
+            DeploymentAuditLogEvent::UpdateLogSinkRules { .. } => "update_log_sink_rules",
+            DeploymentAuditLogEvent::DeleteLogSink { .. } => "delete_log_sink",
+            DeploymentAuditLogEvent::RevokeSessions { .. } => "revoke_sessions",
+            DeploymentAuditLogEvent::RequestExport { .. } => "request_export",
+            DeploymentAuditLogEvent::CreateExportSchedule { .. } => "create_export_schedule",
+            DeploymentAuditLogEvent::DeleteExportSchedule { .. } => "delete_export_schedule",
+            DeploymentAuditLogEvent::CloneDeployment { .. } => "clone_deployment",
+            DeploymentAuditLogEvent::DashboardEdit { .. } => "dashboard_edit",
+            DeploymentAuditLogEvent::CreateFileLifecycleRule { .. } => "create_file_lifecycle_rule",
+            DeploymentAuditLogEvent::DeleteFileLifecycleRule { .. } => "delete_file_lifecycle_rule",
+            DeploymentAuditLogEvent::UpdateBackendSetting { .. } => "update_backend_setting",
+            DeploymentAuditLogEvent::RestoreDocument { .. } => "restore_document",
+            DeploymentAuditLogEvent::SetFunctionFlag { .. } => "set_function_flag",
+            DeploymentAuditLogEvent::DeleteFunctionFlag { .. } => "delete_function_flag",
+            DeploymentAuditLogEvent::UpdateEgressPolicy { .. } => "update_egress_policy",
+            DeploymentAuditLogEvent::DeleteEgressPolicy => "delete_egress_policy",
+            DeploymentAuditLogEvent::BulkEditDocuments { .. } => "bulk_edit_documents",
+            DeploymentAuditLogEvent::PinSearchIndex { .. } => "pin_search_index",
+            DeploymentAuditLogEvent::UnpinSearchIndex { .. } => "unpin_search_index",
+            DeploymentAuditLogEvent::RenameTable { .. } => "rename_table",
+            DeploymentAuditLogEvent::ArchiveTable { .. } => "archive_table",
+            DeploymentAuditLogEvent::RestoreTable { .. } => "restore_table",
+            DeploymentAuditLogEvent::SetOutboxTarget { .. } => "set_outbox_target",
+            DeploymentAuditLogEvent::DeleteOutboxTarget { .. } => "delete_outbox_target",
+            DeploymentAuditLogEvent::UpdateTrafficShadowing { .. } => "update_traffic_shadowing",
+            DeploymentAuditLogEvent::DeleteTrafficShadowing => "delete_traffic_shadowing",
+            DeploymentAuditLogEvent::SetFunctionReadBudget { .. } => "set_function_read_budget",
+            DeploymentAuditLogEvent::DeleteFunctionReadBudget { .. } => {
+                "delete_function_read_budget"
+            },
+            DeploymentAuditLogEvent::SetLogSetting { .. } => "set_log_setting",
+            DeploymentAuditLogEvent::DeleteLogSetting { .. } => "delete_log_setting",
+            DeploymentAuditLogEvent::RollbackPush { .. } => "rollback_push",
+            DeploymentAuditLogEvent::RestoreDeploymentVersion { .. } => {
+                "restore_deployment_version"
+            },
+            DeploymentAuditLogEvent::SetFunctionCanary { .. } => "set_function_canary",
+            DeploymentAuditLogEvent::DeleteFunctionCanary { .. } => "delete_function_canary",
         }
     }
 
@@ -226,22 +421,190 @@ impl DeploymentAuditLogEvent {
                 )
             },
             DeploymentAuditLogEvent::ClearTables => obj!(),
+            DeploymentAuditLogEvent::CreateApiKey { name }
+            | DeploymentAuditLogEvent::DeleteApiKey { name } => {
+                obj!("api_key_name" => String::from(name))
+            },
+            DeploymentAuditLogEvent::CreateLogSink { sink_type }
+            | DeploymentAuditLogEvent::UpdateLogSinkRules { sink_type }
+            | DeploymentAuditLogEvent::DeleteLogSink { sink_type } => {
+                obj!("sink_type" => sink_type.to_string())
+            },
+            DeploymentAuditLogEvent::RevokeSessions {
+                subject,
+                issuer,
+                token_id,
+            } => {
+                obj!("subject" => subject, "issuer" => issuer, "token_id" => token_id)
+            },
+            DeploymentAuditLogEvent::RequestExport { format } => {
+                obj!("format" => ConvexValue::try_from(format)?)
+            },
+            DeploymentAuditLogEvent::CreateExportSchedule { name }
+            | DeploymentAuditLogEvent::DeleteExportSchedule { name } => {
+                obj!("schedule_name" => name)
+            },
+            DeploymentAuditLogEvent::CloneDeployment { target_url } => {
+                obj!("target_url" => target_url)
+            },
+            DeploymentAuditLogEvent::DashboardEdit {
+                function,
+                request_id,
+            } => {
+                obj!("function" => function, "request_id" => request_id)
+            },
+            DeploymentAuditLogEvent::CreateFileLifecycleRule { name }
+            | DeploymentAuditLogEvent::DeleteFileLifecycleRule { name } => {
+                obj!("rule_name" => name)
+            },
+            DeploymentAuditLogEvent::UpdateBackendSetting {
+                name,
+                previous_value,
+                value,
+            } => {
+                obj!("setting_name" => name, "previous_value" => previous_value, "value" => value)
+            },
+            DeploymentAuditLogEvent::RestoreDocument {
+                table_name,
+                document_id,
+            } => {
+                obj!("table_name" => table_name, "document_id" => document_id)
+            },
+            DeploymentAuditLogEvent::SetFunctionFlag {
+                component_path,
+                udf_path,
+                mode,
+            } => {
+                obj!("component_path" => component_path, "udf_path" => udf_path, "mode" => mode)
+            },
+            DeploymentAuditLogEvent::DeleteFunctionFlag {
+                component_path,
+                udf_path,
+            } => {
+                obj!("component_path" => component_path, "udf_path" => udf_path)
+            },
+            DeploymentAuditLogEvent::UpdateEgressPolicy {
+                allowed_domains,
+                denied_domains,
+                proxy_url,
+            } => {
+                let to_values = |domains: Vec<String>| -> anyhow::Result<Vec<ConvexValue>> {
+                    domains
+                        .into_iter()
+                        .map(|domain| anyhow::Ok(ConvexValue::String(domain.try_into()?)))
+                        .try_collect()
+                };
+                obj!(
+                    "allowed_domains" => to_values(allowed_domains)?,
+                    "denied_domains" => to_values(denied_domains)?,
+                    "proxy_url" => proxy_url
+                )
+            },
+            DeploymentAuditLogEvent::DeleteEgressPolicy => obj!(),
+            DeploymentAuditLogEvent::BulkEditDocuments {
+                table_name,
+                operation,
+                document_count,
+            } => {
+                obj!(
+                    "table_name" => table_name,
+                    "operation" => operation,
+                    "document_count" => document_count as i64
+                )
+            },
+            DeploymentAuditLogEvent::PinSearchIndex {
+                table_name,
+                index_name,
+            }
+            | DeploymentAuditLogEvent::UnpinSearchIndex {
+                table_name,
+                index_name,
+            } => {
+                obj!("table_name" => table_name, "index_name" => index_name)
+            },
+            DeploymentAuditLogEvent::RenameTable {
+                table_name,
+                new_name,
+            }
+            | DeploymentAuditLogEvent::RestoreTable {
+                table_name,
+                new_name,
+            } => {
+                obj!("table_name" => table_name, "new_name" => new_name)
+            },
+            DeploymentAuditLogEvent::ArchiveTable { table_name } => {
+                obj!("table_name" => table_name)
+            },
+            DeploymentAuditLogEvent::SetOutboxTarget { name, target_type } => {
+                obj!("name" => name, "target_type" => target_type)
+            },
+            DeploymentAuditLogEvent::DeleteOutboxTarget { name } => {
+                obj!("name" => name)
+            },
+            DeploymentAuditLogEvent::UpdateTrafficShadowing { target_url } => {
+                obj!("target_url" => target_url)
+            },
+            DeploymentAuditLogEvent::DeleteTrafficShadowing => obj!(),
+            DeploymentAuditLogEvent::SetFunctionReadBudget {
+                component_path,
+                udf_path,
+            }
+            | DeploymentAuditLogEvent::DeleteFunctionReadBudget {
+                component_path,
+                udf_path,
+            } => {
+                obj!("component_path" => component_path, "udf_path" => udf_path)
+            },
+            DeploymentAuditLogEvent::SetLogSetting { udf_path }
+            | DeploymentAuditLogEvent::DeleteLogSetting { udf_path } => {
+                obj!("udf_path" => udf_path)
+            },
+            DeploymentAuditLogEvent::RollbackPush { reason } => {
+                obj!("reason" => reason)
+            },
+            DeploymentAuditLogEvent::RestoreDeploymentVersion { version_id } => {
+                obj!("version_id" => version_id)
+            },
+            DeploymentAuditLogEvent::SetFunctionCanary {
+                component_path,
+                udf_path,
+                rollout_percentage,
+            } => {
+                obj!(
+                    "component_path" => component_path,
+                    "udf_path" => udf_path,
+                    "rollout_percentage" => rollout_percentage as i64,
+                )
+            },
+            DeploymentAuditLogEvent::DeleteFunctionCanary {
+                component_path,
+                udf_path,
+            } => {
+                obj!("component_path" => component_path, "udf_path" => udf_path)
+            },
         }
     }
 
+    /// `actor` is the object produced by `audit_log_actor` for the identity
+    /// that performed the action.
     pub fn to_log_event(
         event: DeploymentAuditLogEvent,
+        actor: ConvexObject,
         timestamp: UnixTimestamp,
     ) -> anyhow::Result<LogEvent> {
         let action = event.action().to_string();
         let JsonValue::Object(metadata_fields) = event.metadata()?.into() else {
             anyhow::bail!("DeploymentAuditLogEvent metdata was not a JSON object")
         };
+        let JsonValue::Object(actor_fields) = actor.export(ValueFormat::ConvexCleanJSON) else {
+            anyhow::bail!("DeploymentAuditLogEvent actor was not a JSON object")
+        };
         Ok(LogEvent {
             timestamp,
             event: StructuredLogEvent::DeploymentAuditLog {
                 action,
                 metadata: metadata_fields,
+                actor: actor_fields,
             },
         })
     }
@@ -322,6 +685,141 @@ impl TryFrom<ConvexObject> for DeploymentAuditLogEvent {
                     import_format: remove_object(&mut fields, "import_format")?,
                 }
             },
+            "create_api_key" => DeploymentAuditLogEvent::CreateApiKey {
+                name: remove_string(&mut fields, "api_key_name")?.parse()?,
+            },
+            "delete_api_key" => DeploymentAuditLogEvent::DeleteApiKey {
+                name: remove_string(&mut fields, "api_key_name")?.parse()?,
+            },
+            "create_log_sink" => DeploymentAuditLogEvent::CreateLogSink {
+                sink_type: remove_string(&mut fields, "sink_type")?.parse()?,
+            },
+            "update_log_sink_rules" => DeploymentAuditLogEvent::UpdateLogSinkRules {
+                sink_type: remove_string(&mut fields, "sink_type")?.parse()?,
+            },
+            "delete_log_sink" => DeploymentAuditLogEvent::DeleteLogSink {
+                sink_type: remove_string(&mut fields, "sink_type")?.parse()?,
+            },
+            "revoke_sessions" => DeploymentAuditLogEvent::RevokeSessions {
+                subject: remove_string(&mut fields, "subject")?,
+                issuer: remove_nullable_string(&mut fields, "issuer")?,
+                token_id: remove_nullable_string(&mut fields, "token_id")?,
+            },
+            "request_export" => {
+                let Some(format) = fields.remove("format") else {
+                    anyhow::bail!("request_export is missing format");
+                };
+                DeploymentAuditLogEvent::RequestExport {
+                    format: ExportFormat::try_from(format)?,
+                }
+            },
+            "create_export_schedule" => DeploymentAuditLogEvent::CreateExportSchedule {
+                name: remove_string(&mut fields, "schedule_name")?,
+            },
+            "delete_export_schedule" => DeploymentAuditLogEvent::DeleteExportSchedule {
+                name: remove_string(&mut fields, "schedule_name")?,
+            },
+            "clone_deployment" => DeploymentAuditLogEvent::CloneDeployment {
+                target_url: remove_string(&mut fields, "target_url")?,
+            },
+            "dashboard_edit" => DeploymentAuditLogEvent::DashboardEdit {
+                function: remove_string(&mut fields, "function")?,
+                request_id: remove_string(&mut fields, "request_id")?,
+            },
+            "create_file_lifecycle_rule" => DeploymentAuditLogEvent::CreateFileLifecycleRule {
+                name: remove_string(&mut fields, "rule_name")?,
+            },
+            "delete_file_lifecycle_rule" => DeploymentAuditLogEvent::DeleteFileLifecycleRule {
+                name: remove_string(&mut fields, "rule_name")?,
+            },
+            "update_backend_setting" => DeploymentAuditLogEvent::UpdateBackendSetting {
+                name: remove_string(&mut fields, "setting_name")?,
+                previous_value: remove_string(&mut fields, "previous_value")?,
+                value: remove_string(&mut fields, "value")?,
+            },
+            "restore_document" => DeploymentAuditLogEvent::RestoreDocument {
+                table_name: remove_string(&mut fields, "table_name")?,
+                document_id: remove_string(&mut fields, "document_id")?,
+            },
+            "set_function_flag" => DeploymentAuditLogEvent::SetFunctionFlag {
+                component_path: remove_string(&mut fields, "component_path")?,
+                udf_path: remove_string(&mut fields, "udf_path")?,
+                mode: remove_string(&mut fields, "mode")?,
+            },
+            "delete_function_flag" => DeploymentAuditLogEvent::DeleteFunctionFlag {
+                component_path: remove_string(&mut fields, "component_path")?,
+                udf_path: remove_string(&mut fields, "udf_path")?,
+            },
+            "update_egress_policy" => DeploymentAuditLogEvent::UpdateEgressPolicy {
+                allowed_domains: remove_vec_of_strings(&mut fields, "allowed_domains")?,
+                denied_domains: remove_vec_of_strings(&mut fields, "denied_domains")?,
+                proxy_url: remove_nullable_string(&mut fields, "proxy_url")?,
+            },
+            "delete_egress_policy" => DeploymentAuditLogEvent::DeleteEgressPolicy,
+            "bulk_edit_documents" => DeploymentAuditLogEvent::BulkEditDocuments {
+                table_name: remove_string(&mut fields, "table_name")?,
+                operation: remove_string(&mut fields, "operation")?,
+                document_count: remove_int64(&mut fields, "document_count")? as u64,
+            },
+            "pin_search_index" => DeploymentAuditLogEvent::PinSearchIndex {
+                table_name: remove_string(&mut fields, "table_name")?,
+                index_name: remove_string(&mut fields, "index_name")?,
+            },
+            "unpin_search_index" => DeploymentAuditLogEvent::UnpinSearchIndex {
+                table_name: remove_string(&mut fields, "table_name")?,
+                index_name: remove_string(&mut fields, "index_name")?,
+            },
+            "rename_table" => DeploymentAuditLogEvent::RenameTable {
+                table_name: remove_string(&mut fields, "table_name")?,
+                new_name: remove_string(&mut fields, "new_name")?,
+            },
+            "archive_table" => DeploymentAuditLogEvent::ArchiveTable {
+                table_name: remove_string(&mut fields, "table_name")?,
+            },
+            "restore_table" => DeploymentAuditLogEvent::RestoreTable {
+                table_name: remove_string(&mut fields, "table_name")?,
+                new_name: remove_string(&mut fields, "new_name")?,
+            },
+            "set_outbox_target" => DeploymentAuditLogEvent::SetOutboxTarget {
+                name: remove_string(&mut fields, "name")?,
+                target_type: remove_string(&mut fields, "target_type")?,
+            },
+            "delete_outbox_target" => DeploymentAuditLogEvent::DeleteOutboxTarget {
+                name: remove_string(&mut fields, "name")?,
+            },
+            "update_traffic_shadowing" => DeploymentAuditLogEvent::UpdateTrafficShadowing {
+                target_url: remove_string(&mut fields, "target_url")?,
+            },
+            "delete_traffic_shadowing" => DeploymentAuditLogEvent::DeleteTrafficShadowing,
+            "set_function_read_budget" => DeploymentAuditLogEvent::SetFunctionReadBudget {
+                component_path: remove_string(&mut fields, "component_path")?,
+                udf_path: remove_string(&mut fields, "udf_path")?,
+            },
+            "delete_function_read_budget" => DeploymentAuditLogEvent::DeleteFunctionReadBudget {
+                component_path: remove_string(&mut fields, "component_path")?,
+                udf_path: remove_string(&mut fields, "udf_path")?,
+            },
+            "set_log_setting" => DeploymentAuditLogEvent::SetLogSetting {
+                udf_path: remove_nullable_string(&mut fields, "udf_path")?,
+            },
+            "delete_log_setting" => DeploymentAuditLogEvent::DeleteLogSetting {
+                udf_path: remove_nullable_string(&mut fields, "udf_path")?,
+            },
+            "rollback_push" => DeploymentAuditLogEvent::RollbackPush {
+                reason: remove_string(&mut fields, "reason")?,
+            },
+            "restore_deployment_version" => DeploymentAuditLogEvent::RestoreDeploymentVersion {
+                version_id: remove_string(&mut fields, "version_id")?,
+            },
+            "set_function_canary" => DeploymentAuditLogEvent::SetFunctionCanary {
+                component_path: remove_string(&mut fields, "component_path")?,
+                udf_path: remove_string(&mut fields, "udf_path")?,
+                rollout_percentage: remove_int64(&mut fields, "rollout_percentage")?.try_into()?,
+            },
+            "delete_function_canary" => DeploymentAuditLogEvent::DeleteFunctionCanary {
+                component_path: remove_string(&mut fields, "component_path")?,
+                udf_path: remove_string(&mut fields, "udf_path")?,
+            },
             _ => anyhow::bail!("action {action} unrecognized"),
         };
         Ok(event)
@@ -351,12 +849,21 @@ mod tests {
     use common::{
         log_streaming::LogEventFormatVersion,
         runtime::UnixTimestamp,
+        types::MemberId,
+    };
+    use keybroker::{
+        AdminIdentity,
+        Identity,
     };
     use proptest::prelude::*;
     use serde_json::json;
-    use value::ConvexObject;
+    use value::{
+        obj,
+        ConvexObject,
+    };
 
     use super::DeploymentAuditLogEvent;
+    use crate::deployment_audit_log::audit_log_actor;
 
     proptest! {
         #![proptest_config(
@@ -379,6 +886,7 @@ mod tests {
             DeploymentAuditLogEvent::CreateEnvironmentVariable {
                 name: "test_env_variable".parse()?,
             },
+            audit_log_actor(&Identity::system(), None)?,
             UnixTimestamp::from_millis(0),
         )?;
         let event_json = event.to_json_map(LogEventFormatVersion::default())?;
@@ -390,10 +898,30 @@ mod tests {
                 "timestamp": 0,
                 "audit_log_action": "create_environment_variable",
                 "audit_log_metadata": "{\"variable_name\":\"test_env_variable\"}",
+                "audit_log_actor": { "type": "system" },
             })
         );
         Ok(())
     }
+
+    #[test]
+    fn test_audit_log_actor() -> anyhow::Result<()> {
+        let admin = Identity::InstanceAdmin(AdminIdentity::new_for_test_only(
+            "carnitas".to_string(),
+            MemberId(7),
+        ));
+        assert_eq!(
+            audit_log_actor(&admin, None)?,
+            obj!("type" => "admin", "member_id" => 7i64, "team_id" => null, "role" => "admin")?,
+        );
+        // Imports run as the system but are attributed to the member who
+        // started them.
+        assert_eq!(
+            audit_log_actor(&Identity::system(), Some(MemberId(8)))?,
+            obj!("type" => "admin", "member_id" => 8i64, "team_id" => null, "role" => null)?,
+        );
+        Ok(())
+    }
 }
 
 #[cfg(test)]
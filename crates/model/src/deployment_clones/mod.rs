@@ -0,0 +1,224 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::Timestamp,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    DeploymentClone,
+    DeploymentCloneState,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Clones of this deployment into other deployments, and how far each got.
+pub static DEPLOYMENT_CLONES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_deployment_clones"
+        .parse()
+        .expect("Invalid built-in deployment clones table")
+});
+
+pub struct DeploymentClonesTable;
+impl SystemTable for DeploymentClonesTable {
+    fn table_name(&self) -> &'static TableName {
+        &DEPLOYMENT_CLONES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<DeploymentClone>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct DeploymentClonesModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> DeploymentClonesModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    fn check_system(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    /// Record a clone into `target_url`, which the deployment clone worker
+    /// starts once the clones before it have finished.
+    pub async fn start(
+        &mut self,
+        target_url: String,
+        target_admin_key: String,
+        include_storage: bool,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("clone_deployment")?;
+        let clone = DeploymentClone {
+            target_url: target_url.trim_end_matches('/').to_string(),
+            target_admin_key: Some(target_admin_key),
+            include_storage,
+            started_ts: self.tx.runtime().generate_timestamp()?,
+            state: DeploymentCloneState::Pending,
+        };
+        clone.validate()?;
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&DEPLOYMENT_CLONES_TABLE, clone.try_into()?)
+            .await
+    }
+
+    /// Every clone, oldest first.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<DeploymentClone>>> {
+        self.check_admin("list_deployment_clones")?;
+        let query = Query::full_table_scan(DEPLOYMENT_CLONES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut clones = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            clones.push(doc.try_into()?);
+        }
+        Ok(clones)
+    }
+
+    pub async fn get(
+        &mut self,
+        id: ResolvedDocumentId,
+    ) -> anyhow::Result<ParsedDocument<DeploymentClone>> {
+        self.check_admin("get_deployment_clone")?;
+        let Some(document) = self.tx.get(id).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "DeploymentCloneNotFound",
+                format!("Deployment clone {id} not found"),
+            ));
+        };
+        document.try_into()
+    }
+
+    /// Move the clone to `state`, forgetting the target's admin key once the
+    /// clone has finished.
+    pub async fn set_state(
+        &mut self,
+        id: ResolvedDocumentId,
+        state: DeploymentCloneState,
+    ) -> anyhow::Result<()> {
+        self.check_system("set_deployment_clone_state")?;
+        let mut clone = self.get(id).await?.into_value();
+        if state.is_finished() {
+            clone.target_admin_key = None;
+        }
+        clone.state = state;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, clone.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Fail the clone without touching the target, e.g. because it was
+    /// interrupted at a step that can't be safely repeated.
+    pub async fn fail(&mut self, id: ResolvedDocumentId, error: String) -> anyhow::Result<()> {
+        let failed_ts: Timestamp = self.tx.runtime().generate_timestamp()?;
+        self.set_state(id, DeploymentCloneState::Failed { error, failed_ts })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::types::Timestamp;
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        deployment_clones::{
+            types::DeploymentCloneState,
+            DeploymentClonesModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_deployment_clones(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = DeploymentClonesModel::new(&mut tx);
+        assert!(model
+            .start("staging.example.com".to_string(), "key".to_string(), true)
+            .await
+            .is_err());
+        let first = model
+            .start(
+                "https://staging.example.com/".to_string(),
+                "admin-key".to_string(),
+                true,
+            )
+            .await?;
+        let second = model
+            .start(
+                "https://preview.example.com".to_string(),
+                "admin-key".to_string(),
+                false,
+            )
+            .await?;
+        model
+            .set_state(
+                first,
+                DeploymentCloneState::Importing {
+                    snapshot_ts: Timestamp::must(1),
+                },
+            )
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = DeploymentClonesModel::new(&mut tx);
+        let clones = model.list().await?;
+        assert_eq!(clones.len(), 2);
+        assert_eq!(clones[0].id(), first);
+        assert_eq!(clones[0].target_url, "https://staging.example.com");
+        assert_eq!(clones[0].target_admin_key.as_deref(), Some("admin-key"));
+        assert_eq!(clones[1].state, DeploymentCloneState::Pending);
+
+        model.fail(second, "Target unreachable".to_string()).await?;
+        let failed = model.get(second).await?;
+        assert!(failed.state.is_finished());
+        // The admin key isn't needed once the clone has finished.
+        assert_eq!(failed.target_admin_key, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+use common::types::Timestamp;
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// How far cloning the deployment into its target has gotten. The steps run
+/// in the order of the variants below.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum DeploymentCloneState {
+    // Waiting for earlier clones to finish.
+    Pending,
+    // Pushing the schema and the current version of every module, which also
+    // registers the crons defined in them.
+    CopyingCode,
+    CopyingEnvironmentVariables,
+    // Writing the ZIP snapshot of the deployment's tables and file storage.
+    Exporting,
+    // Importing the snapshot into the target, replacing its tables.
+    Importing {
+        snapshot_ts: Timestamp,
+    },
+    // Scheduling the jobs that are pending in this deployment on the target.
+    CopyingScheduledJobs {
+        snapshot_ts: Timestamp,
+    },
+    Completed {
+        snapshot_ts: Timestamp,
+        completed_ts: Timestamp,
+    },
+    Failed {
+        error: String,
+        failed_ts: Timestamp,
+    },
+}
+
+impl DeploymentCloneState {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            DeploymentCloneState::Completed { .. } | DeploymentCloneState::Failed { .. }
+        )
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeploymentCloneState::Pending => "pending",
+            DeploymentCloneState::CopyingCode => "copyingCode",
+            DeploymentCloneState::CopyingEnvironmentVariables => "copyingEnvironmentVariables",
+            DeploymentCloneState::Exporting => "exporting",
+            DeploymentCloneState::Importing { .. } => "importing",
+            DeploymentCloneState::CopyingScheduledJobs { .. } => "copyingScheduledJobs",
+            DeploymentCloneState::Completed { .. } => "completed",
+            DeploymentCloneState::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// A copy of this deployment's data, file storage, code, environment
+/// variables and pending scheduled jobs into another deployment, driven
+/// through the target's admin API.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct DeploymentClone {
+    // The target deployment's URL, e.g. `https://happy-animal-123.convex.cloud`.
+    pub target_url: String,
+    // Admin key of the target deployment. Only kept until the clone finishes.
+    pub target_admin_key: Option<String>,
+    pub include_storage: bool,
+    pub started_ts: Timestamp,
+    pub state: DeploymentCloneState,
+}
+
+impl DeploymentClone {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid = |msg: String| {
+            anyhow::anyhow!(ErrorMetadata::bad_request("InvalidDeploymentClone", msg))
+        };
+        if !(self.target_url.starts_with("https://") || self.target_url.starts_with("http://")) {
+            return Err(invalid(format!(
+                "Target URL {:?} must be an http or https URL",
+                self.target_url
+            )));
+        }
+        if self
+            .target_admin_key
+            .as_ref()
+            .is_some_and(|key| key.is_empty())
+        {
+            return Err(invalid("targetAdminKey must not be empty".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedDeploymentCloneState {
+    Pending,
+    CopyingCode,
+    CopyingEnvironmentVariables,
+    Exporting,
+    #[serde(rename_all = "camelCase")]
+    Importing {
+        snapshot_ts: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    CopyingScheduledJobs {
+        snapshot_ts: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        snapshot_ts: i64,
+        completed_ts: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Failed {
+        error: String,
+        failed_ts: i64,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedDeploymentClone {
+    target_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target_admin_key: Option<String>,
+    include_storage: bool,
+    started_ts: i64,
+    state: SerializedDeploymentCloneState,
+}
+
+impl From<DeploymentCloneState> for SerializedDeploymentCloneState {
+    fn from(state: DeploymentCloneState) -> Self {
+        match state {
+            DeploymentCloneState::Pending => SerializedDeploymentCloneState::Pending,
+            DeploymentCloneState::CopyingCode => SerializedDeploymentCloneState::CopyingCode,
+            DeploymentCloneState::CopyingEnvironmentVariables => {
+                SerializedDeploymentCloneState::CopyingEnvironmentVariables
+            },
+            DeploymentCloneState::Exporting => SerializedDeploymentCloneState::Exporting,
+            DeploymentCloneState::Importing { snapshot_ts } => {
+                SerializedDeploymentCloneState::Importing {
+                    snapshot_ts: snapshot_ts.into(),
+                }
+            },
+            DeploymentCloneState::CopyingScheduledJobs { snapshot_ts } => {
+                SerializedDeploymentCloneState::CopyingScheduledJobs {
+                    snapshot_ts: snapshot_ts.into(),
+                }
+            },
+            DeploymentCloneState::Completed {
+                snapshot_ts,
+                completed_ts,
+            } => SerializedDeploymentCloneState::Completed {
+                snapshot_ts: snapshot_ts.into(),
+                completed_ts: completed_ts.into(),
+            },
+            DeploymentCloneState::Failed { error, failed_ts } => {
+                SerializedDeploymentCloneState::Failed {
+                    error,
+                    failed_ts: failed_ts.into(),
+                }
+            },
+        }
+    }
+}
+
+impl TryFrom<SerializedDeploymentCloneState> for DeploymentCloneState {
+    type Error = anyhow::Error;
+
+    fn try_from(state: SerializedDeploymentCloneState) -> anyhow::Result<Self> {
+        Ok(match state {
+            SerializedDeploymentCloneState::Pending => DeploymentCloneState::Pending,
+            SerializedDeploymentCloneState::CopyingCode => DeploymentCloneState::CopyingCode,
+            SerializedDeploymentCloneState::CopyingEnvironmentVariables => {
+                DeploymentCloneState::CopyingEnvironmentVariables
+            },
+            SerializedDeploymentCloneState::Exporting => DeploymentCloneState::Exporting,
+            SerializedDeploymentCloneState::Importing { snapshot_ts } => {
+                DeploymentCloneState::Importing {
+                    snapshot_ts: snapshot_ts.try_into()?,
+                }
+            },
+            SerializedDeploymentCloneState::CopyingScheduledJobs { snapshot_ts } => {
+                DeploymentCloneState::CopyingScheduledJobs {
+                    snapshot_ts: snapshot_ts.try_into()?,
+                }
+            },
+            SerializedDeploymentCloneState::Completed {
+                snapshot_ts,
+                completed_ts,
+            } => DeploymentCloneState::Completed {
+                snapshot_ts: snapshot_ts.try_into()?,
+                completed_ts: completed_ts.try_into()?,
+            },
+            SerializedDeploymentCloneState::Failed { error, failed_ts } => {
+                DeploymentCloneState::Failed {
+                    error,
+                    failed_ts: failed_ts.try_into()?,
+                }
+            },
+        })
+    }
+}
+
+impl TryFrom<DeploymentClone> for SerializedDeploymentClone {
+    type Error = anyhow::Error;
+
+    fn try_from(clone: DeploymentClone) -> anyhow::Result<Self> {
+        Ok(Self {
+            target_url: clone.target_url,
+            target_admin_key: clone.target_admin_key,
+            include_storage: clone.include_storage,
+            started_ts: clone.started_ts.into(),
+            state: clone.state.into(),
+        })
+    }
+}
+
+impl TryFrom<SerializedDeploymentClone> for DeploymentClone {
+    type Error = anyhow::Error;
+
+    fn try_from(clone: SerializedDeploymentClone) -> anyhow::Result<Self> {
+        Ok(Self {
+            target_url: clone.target_url,
+            target_admin_key: clone.target_admin_key,
+            include_storage: clone.include_storage,
+            started_ts: clone.started_ts.try_into()?,
+            state: clone.state.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(DeploymentClone, SerializedDeploymentClone);
@@ -0,0 +1,198 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    knobs::DEPLOYMENT_VERSION_HISTORY_SIZE,
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    DeveloperDocumentId,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::DeploymentVersion;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// The most recently pushed versions of the deployment's functions, for
+/// rolling back to.
+pub static DEPLOYMENT_VERSIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_deployment_versions"
+        .parse()
+        .expect("Invalid built-in deployment versions table")
+});
+
+pub struct DeploymentVersionsTable;
+impl SystemTable for DeploymentVersionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &DEPLOYMENT_VERSIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<DeploymentVersion>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct DeploymentVersionModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> DeploymentVersionModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    /// The recorded versions, most recent first.
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<DeploymentVersion>>> {
+        self.check_admin("list_deployment_versions")?;
+        let query = Query::full_table_scan(DEPLOYMENT_VERSIONS_TABLE.clone(), Order::Desc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut versions = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            versions.push(doc.try_into()?);
+        }
+        Ok(versions)
+    }
+
+    pub async fn get(
+        &mut self,
+        id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<DeploymentVersion>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|version| DeveloperDocumentId::from(version.id()) == id))
+    }
+
+    /// Record a newly pushed version, forgetting the oldest versions beyond
+    /// `DEPLOYMENT_VERSION_HISTORY_SIZE`.
+    pub async fn record(
+        &mut self,
+        version: DeploymentVersion,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("record_deployment_version")?;
+        let id = SystemMetadataModel::new_global(self.tx)
+            .insert(&DEPLOYMENT_VERSIONS_TABLE, version.try_into()?)
+            .await?;
+        let expired = self
+            .list()
+            .await?
+            .into_iter()
+            .skip(*DEPLOYMENT_VERSION_HISTORY_SIZE);
+        for version in expired {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(version.id())
+                .await?;
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::knobs::DEPLOYMENT_VERSION_HISTORY_SIZE;
+    use database::test_helpers::DbFixtures;
+    use keybroker::Identity;
+    use proptest::prelude::*;
+    use runtime::testing::TestRuntime;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+        DeveloperDocumentId,
+    };
+
+    use crate::{
+        deployment_versions::{
+            types::DeploymentVersion,
+            DeploymentVersionModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn version(i: usize) -> anyhow::Result<DeploymentVersion> {
+        Ok(DeploymentVersion {
+            storage_key: format!("push-{i}").try_into()?,
+            udf_server_version: "1.14.0".to_string(),
+        })
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_deployment_version_roundtrips(v in any::<DeploymentVersion>()) {
+            assert_roundtrips::<DeploymentVersion, ConvexObject>(v);
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_deployment_versions(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let history_size = *DEPLOYMENT_VERSION_HISTORY_SIZE;
+        let mut ids = vec![];
+        for i in 0..history_size + 2 {
+            let mut tx = db.begin_system().await?;
+            ids.push(
+                DeploymentVersionModel::new(&mut tx)
+                    .record(version(i)?)
+                    .await?,
+            );
+            db.commit(tx).await?;
+        }
+
+        // Only the most recent versions are kept, newest first.
+        let mut tx = db.begin_system().await?;
+        let mut model = DeploymentVersionModel::new(&mut tx);
+        let versions = model.list().await?;
+        assert_eq!(versions.len(), history_size);
+        assert_eq!(versions[0].clone().into_value(), version(history_size + 1)?);
+        assert!(model
+            .get(DeveloperDocumentId::from(ids[0]))
+            .await?
+            .is_none());
+        assert_eq!(
+            model
+                .get(DeveloperDocumentId::from(ids[2]))
+                .await?
+                .map(|v| v.into_value()),
+            Some(version(2)?)
+        );
+
+        let mut tx = db.begin(Identity::Unknown).await?;
+        assert!(DeploymentVersionModel::new(&mut tx).list().await.is_err());
+        Ok(())
+    }
+}
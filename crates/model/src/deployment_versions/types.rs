@@ -0,0 +1,48 @@
+use common::types::ObjectKey;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A version of the deployment's functions and configuration, recorded when
+/// a push finishes. The push itself is stored in module storage at
+/// `storage_key`, so restoring the version applies it again.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct DeploymentVersion {
+    pub storage_key: ObjectKey,
+    /// The npm package version the functions were pushed with.
+    pub udf_server_version: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedDeploymentVersion {
+    storage_key: String,
+    udf_server_version: String,
+}
+
+impl TryFrom<DeploymentVersion> for SerializedDeploymentVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(version: DeploymentVersion) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage_key: version.storage_key.into(),
+            udf_server_version: version.udf_server_version,
+        })
+    }
+}
+
+impl TryFrom<SerializedDeploymentVersion> for DeploymentVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(version: SerializedDeploymentVersion) -> anyhow::Result<Self> {
+        Ok(Self {
+            storage_key: version.storage_key.try_into()?,
+            udf_server_version: version.udf_server_version,
+        })
+    }
+}
+
+codegen_convex_serialization!(DeploymentVersion, SerializedDeploymentVersion);
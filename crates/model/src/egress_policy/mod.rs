@@ -0,0 +1,184 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::EgressPolicy;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// The deployment's egress policy, which has at most one document. Actions
+/// can make requests anywhere when it's empty.
+pub static EGRESS_POLICY_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_egress_policy"
+        .parse()
+        .expect("Invalid built-in egress policy table")
+});
+
+pub struct EgressPolicyTable;
+impl SystemTable for EgressPolicyTable {
+    fn table_name(&self) -> &'static TableName {
+        &EGRESS_POLICY_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<EgressPolicy>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct EgressPolicyModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> EgressPolicyModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    async fn get_document(&mut self) -> anyhow::Result<Option<ParsedDocument<EgressPolicy>>> {
+        let query = Query::full_table_scan(EGRESS_POLICY_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The policy actions run under. This isn't restricted to admins because
+    /// actions load it before they make any requests, whoever calls them.
+    pub async fn get(&mut self) -> anyhow::Result<EgressPolicy> {
+        Ok(self
+            .get_document()
+            .await?
+            .map(ParsedDocument::into_value)
+            .unwrap_or_default())
+    }
+
+    /// Replace the policy. Running actions keep the policy they started with.
+    pub async fn set(&mut self, policy: EgressPolicy) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("set_egress_policy")?;
+        policy.validate()?;
+        match self.get_document().await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), policy.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&EGRESS_POLICY_TABLE, policy.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Remove the policy, so actions can make requests anywhere again.
+    pub async fn clear(&mut self) -> anyhow::Result<()> {
+        self.check_admin("clear_egress_policy")?;
+        let Some(existing) = self.get_document().await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "EgressPolicyNotFound",
+                "This deployment doesn't have an egress policy",
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        egress_policy::{
+            types::{
+                EgressPolicy,
+                EgressProxy,
+            },
+            EgressPolicyModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_egress_policy(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = EgressPolicyModel::new(&mut tx);
+        assert_eq!(model.get().await?, EgressPolicy::default());
+
+        let policy = EgressPolicy {
+            allowed_domains: vec!["example.com".to_string()],
+            denied_domains: vec![],
+            proxy: Some(EgressProxy {
+                url: "http://proxy.internal:3128".to_string(),
+                username: Some("convex".to_string()),
+                password: Some("hunter2".to_string()),
+            }),
+        };
+        let id = model.set(policy.clone()).await?;
+        assert_eq!(model.get().await?, policy);
+
+        // There's only one policy, so setting it again replaces it.
+        let policy = EgressPolicy {
+            proxy: None,
+            ..policy
+        };
+        assert_eq!(model.set(policy.clone()).await?, id);
+        assert_eq!(model.get().await?, policy);
+
+        let invalid = EgressPolicy {
+            allowed_domains: vec!["Example.com".to_string()],
+            ..policy
+        };
+        assert!(model.set(invalid).await.is_err());
+
+        model.clear().await?;
+        assert_eq!(model.get().await?, EgressPolicy::default());
+        assert!(model.clear().await.is_err());
+        Ok(())
+    }
+}
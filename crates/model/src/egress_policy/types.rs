@@ -0,0 +1,211 @@
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Restrictions on the outbound requests actions can make, enforced by
+/// `fetch` in the isolate and by the network layer of the Node executor.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct EgressPolicy {
+    /// If nonempty, actions can only make requests to these domains and
+    /// their subdomains.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::collection::vec(\"[a-z]{1,8}\\\\.com\", 0..4)")
+    )]
+    pub allowed_domains: Vec<String>,
+    /// Actions can't make requests to these domains or their subdomains,
+    /// even if they're allowed.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::collection::vec(\"[a-z]{1,8}\\\\.com\", 0..4)")
+    )]
+    pub denied_domains: Vec<String>,
+    /// If set, all requests go through this proxy.
+    pub proxy: Option<EgressProxy>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct EgressProxy {
+    /// e.g. "http://proxy.example.com:3128".
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "\"https?://[a-z]{1,8}\\\\.com(:[0-9]{4})?\"")
+    )]
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+fn normalize_host(host: &str) -> String {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    match host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        Some(ip) => ip.to_string(),
+        None => host,
+    }
+}
+
+fn matches_domain(host: &str, domain: &str) -> bool {
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|subdomain| subdomain.ends_with('.'))
+}
+
+fn validate_domain(domain: &str) -> anyhow::Result<()> {
+    let valid = !domain.is_empty()
+        && !domain.starts_with(['.', '-'])
+        && !domain.ends_with(['.', '-'])
+        && !domain.contains("..")
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '-'));
+    anyhow::ensure!(
+        valid,
+        ErrorMetadata::bad_request(
+            "InvalidEgressPolicy",
+            format!(
+                "Invalid domain {domain:?}. Domains must be lowercase, like \"api.example.com\", \
+                 and match their subdomains too."
+            ),
+        )
+    );
+    Ok(())
+}
+
+impl EgressPolicy {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for domain in self.allowed_domains.iter().chain(&self.denied_domains) {
+            validate_domain(domain)?;
+        }
+        if let Some(proxy) = &self.proxy {
+            let invalid =
+                |msg: &str| ErrorMetadata::bad_request("InvalidEgressPolicy", msg.to_string());
+            anyhow::ensure!(
+                proxy.url.starts_with("http://") || proxy.url.starts_with("https://"),
+                invalid("The proxy URL must start with http:// or https://")
+            );
+            anyhow::ensure!(
+                proxy.password.is_none() || proxy.username.is_some(),
+                invalid("A proxy password requires a username")
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether actions may make requests to `host`, which may be a domain or
+    /// an IP address.
+    pub fn allows_host(&self, host: &str) -> bool {
+        let host = normalize_host(host);
+        if self
+            .denied_domains
+            .iter()
+            .any(|domain| matches_domain(&host, domain))
+        {
+            return false;
+        }
+        self.allowed_domains.is_empty()
+            || self
+                .allowed_domains
+                .iter()
+                .any(|domain| matches_domain(&host, domain))
+    }
+
+    pub fn check_host(&self, host: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.allows_host(host),
+            ErrorMetadata::bad_request(
+                "EgressDenied",
+                format!("Requests to {host} are blocked by this deployment's egress policy"),
+            )
+        );
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedEgressProxy {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedEgressPolicy {
+    allowed_domains: Vec<String>,
+    denied_domains: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    proxy: Option<SerializedEgressProxy>,
+}
+
+impl TryFrom<EgressPolicy> for SerializedEgressPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: EgressPolicy) -> anyhow::Result<Self> {
+        Ok(Self {
+            allowed_domains: policy.allowed_domains,
+            denied_domains: policy.denied_domains,
+            proxy: policy.proxy.map(|proxy| SerializedEgressProxy {
+                url: proxy.url,
+                username: proxy.username,
+                password: proxy.password,
+            }),
+        })
+    }
+}
+
+impl TryFrom<SerializedEgressPolicy> for EgressPolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: SerializedEgressPolicy) -> anyhow::Result<Self> {
+        Ok(Self {
+            allowed_domains: policy.allowed_domains,
+            denied_domains: policy.denied_domains,
+            proxy: policy.proxy.map(|proxy| EgressProxy {
+                url: proxy.url,
+                username: proxy.username,
+                password: proxy.password,
+            }),
+        })
+    }
+}
+
+codegen_convex_serialization!(EgressPolicy, SerializedEgressPolicy);
+
+#[cfg(test)]
+mod tests {
+    use super::EgressPolicy;
+
+    #[test]
+    fn test_allows_host() {
+        let policy = EgressPolicy {
+            allowed_domains: vec!["example.com".to_string(), "1.2.3.4".to_string()],
+            denied_domains: vec!["internal.example.com".to_string()],
+            proxy: None,
+        };
+        assert!(policy.allows_host("example.com"));
+        assert!(policy.allows_host("API.Example.com."));
+        assert!(policy.allows_host("1.2.3.4"));
+        assert!(!policy.allows_host("internal.example.com"));
+        assert!(!policy.allows_host("db.internal.example.com"));
+        assert!(!policy.allows_host("badexample.com"));
+        assert!(!policy.allows_host("example.com.evil.net"));
+
+        let policy = EgressPolicy {
+            allowed_domains: vec![],
+            denied_domains: vec!["evil.net".to_string()],
+            proxy: None,
+        };
+        assert!(policy.allows_host("example.com"));
+        assert!(!policy.allows_host("www.evil.net"));
+        assert!(EgressPolicy::default().allows_host("anything.com"));
+    }
+}
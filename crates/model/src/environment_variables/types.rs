@@ -5,6 +5,7 @@ pub use common::types::{
     EnvVarValue,
     EnvironmentVariable,
 };
+use errors::ErrorMetadata;
 use value::{
     obj,
     ConvexObject,
@@ -47,6 +48,71 @@ impl TryFrom<ConvexObject> for PersistedEnvironmentVariable {
     }
 }
 
+/// An environment variable value that refers to a secret in an external
+/// secret manager. Actions see the secret's value in place of the reference.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum SecretReference {
+    /// `aws-sm://<secret name or ARN>`, or `aws-sm://<secret>#<key>` for the
+    /// `key` field of a secret whose value is a JSON object.
+    AwsSecretsManager {
+        secret_id: String,
+        key: Option<String>,
+    },
+    /// `vault://<mount>/<path>#<key>`, the `key` field of a secret in a
+    /// HashiCorp Vault KV version 2 secrets engine.
+    Vault {
+        mount: String,
+        path: String,
+        key: String,
+    },
+}
+
+impl SecretReference {
+    /// None if `value` isn't a reference. Values with a secret manager's
+    /// scheme that aren't valid references are an error, so a typo isn't
+    /// passed to functions as the secret.
+    pub fn parse(value: &EnvVarValue) -> anyhow::Result<Option<Self>> {
+        let invalid = |expected: &str| {
+            anyhow::anyhow!(ErrorMetadata::bad_request(
+                "InvalidSecretReference",
+                format!(
+                    "Invalid secret reference {:?}, expected {expected}",
+                    value.0
+                ),
+            ))
+        };
+        let (reference, key) = match value.0.split_once('#') {
+            Some((reference, key)) => (reference, Some(key)),
+            None => (value.0.as_str(), None),
+        };
+        if let Some(secret_id) = reference.strip_prefix("aws-sm://") {
+            if secret_id.is_empty() || key == Some("") {
+                return Err(invalid("aws-sm://<secret>[#<key>]"));
+            }
+            return Ok(Some(SecretReference::AwsSecretsManager {
+                secret_id: secret_id.to_string(),
+                key: key.map(|key| key.to_string()),
+            }));
+        }
+        if let Some(path) = reference.strip_prefix("vault://") {
+            let expected = "vault://<mount>/<path>#<key>";
+            let Some((mount, path)) = path.split_once('/') else {
+                return Err(invalid(expected));
+            };
+            let key = key.unwrap_or_default();
+            if mount.is_empty() || path.is_empty() || key.is_empty() {
+                return Err(invalid(expected));
+            }
+            return Ok(Some(SecretReference::Vault {
+                mount: mount.to_string(),
+                path: path.to_string(),
+                key: key.to_string(),
+            }));
+        }
+        Ok(None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -56,7 +122,10 @@ mod tests {
         ConvexObject,
     };
 
-    use super::PersistedEnvironmentVariable;
+    use super::{
+        PersistedEnvironmentVariable,
+        SecretReference,
+    };
 
     proptest! {
         #![proptest_config(
@@ -67,4 +136,39 @@ mod tests {
             assert_roundtrips::<PersistedEnvironmentVariable, ConvexObject>(e);
         }
     }
+
+    #[test]
+    fn test_parse_secret_reference() -> anyhow::Result<()> {
+        let parse = |value: &str| SecretReference::parse(&value.parse()?);
+        assert_eq!(parse("sk_live_1234")?, None);
+        assert_eq!(parse("https://example.com/#anchor")?, None);
+        assert_eq!(
+            parse("aws-sm://prod/stripe")?,
+            Some(SecretReference::AwsSecretsManager {
+                secret_id: "prod/stripe".to_string(),
+                key: None,
+            })
+        );
+        assert_eq!(
+            parse("aws-sm://arn:aws:secretsmanager:us-west-2:123456789012:secret:stripe#apiKey")?,
+            Some(SecretReference::AwsSecretsManager {
+                secret_id: "arn:aws:secretsmanager:us-west-2:123456789012:secret:stripe"
+                    .to_string(),
+                key: Some("apiKey".to_string()),
+            })
+        );
+        assert_eq!(
+            parse("vault://secret/prod/stripe#apiKey")?,
+            Some(SecretReference::Vault {
+                mount: "secret".to_string(),
+                path: "prod/stripe".to_string(),
+                key: "apiKey".to_string(),
+            })
+        );
+        assert!(parse("aws-sm://").is_err());
+        assert!(parse("aws-sm://prod/stripe#").is_err());
+        assert!(parse("vault://secret/prod/stripe").is_err());
+        assert!(parse("vault://secret#apiKey").is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,235 @@
+use common::{
+    errors::JsError,
+    runtime::UnixTimestamp,
+    sha256::Sha256,
+};
+
+/// Maximum number of stack frames that contribute to a fingerprint. Frames
+/// deeper than this are usually framework code shared by unrelated errors.
+const MAX_FINGERPRINT_FRAMES: usize = 8;
+
+/// An exception thrown by a function, along with the fingerprint that groups
+/// it with other exceptions that have the same cause.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorOccurrence {
+    pub fingerprint: String,
+    pub message_template: String,
+    pub message: String,
+    pub stack: Vec<String>,
+    pub function: String,
+    pub timestamp: UnixTimestamp,
+}
+
+impl ErrorOccurrence {
+    pub fn new(error: &JsError, function: String, timestamp: UnixTimestamp) -> Self {
+        let message_template = message_template(&error.message);
+        let frames: Vec<_> = error
+            .frames
+            .iter()
+            .flat_map(|frames| frames.0.iter())
+            .collect();
+
+        // Line and column numbers are left out of the fingerprint so that
+        // unrelated edits to a module don't split its existing groups.
+        let mut hasher = Sha256::new();
+        hasher.update(message_template.as_bytes());
+        for frame in frames
+            .iter()
+            .filter(|frame| frame.file_name.is_some())
+            .take(MAX_FINGERPRINT_FRAMES)
+        {
+            hasher.update(b"\n");
+            hasher.update(frame.file_name.as_deref().unwrap_or_default().as_bytes());
+            hasher.update(b":");
+            hasher.update(
+                frame
+                    .function_name
+                    .as_deref()
+                    .unwrap_or_default()
+                    .as_bytes(),
+            );
+        }
+
+        let stack = frames
+            .iter()
+            .map(|frame| {
+                let function_name = frame.function_name.as_deref().unwrap_or("<anonymous>");
+                match (&frame.file_name, frame.line_number, frame.column_number) {
+                    (Some(file), Some(line), Some(column)) => {
+                        format!("{function_name} ({file}:{line}:{column})")
+                    },
+                    (Some(file), ..) => format!("{function_name} ({file})"),
+                    (None, ..) => function_name.to_string(),
+                }
+            })
+            .collect();
+        Self {
+            fingerprint: hasher.finalize().as_hex(),
+            message_template,
+            message: error.message.clone(),
+            stack,
+            function,
+            timestamp,
+        }
+    }
+}
+
+/// Replaces the parts of an error message that vary between occurrences of
+/// the same error (quoted strings, numbers and IDs) with placeholders, e.g.
+/// `Document "k57..." not found after 3 retries` becomes
+/// `Document <str> not found after <n> retries`.
+pub fn message_template(message: &str) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    let mut template = String::with_capacity(message.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let follows_word = i > 0 && chars[i - 1].is_alphanumeric();
+        if matches!(c, '"' | '\'' | '`')
+            && !follows_word
+            && let Some(len) = chars[i + 1..].iter().position(|&next| next == c)
+        {
+            template.push_str("<str>");
+            i += len + 2;
+            continue;
+        }
+        if is_word_char(&chars, i) {
+            let start = i;
+            while i < chars.len() && is_word_char(&chars, i) {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            template.push_str(&word_template(word));
+            continue;
+        }
+        template.push(c);
+        i += 1;
+    }
+    template
+}
+
+/// Words are runs of alphanumeric characters and underscores, plus decimal
+/// points so that `12.50` is a single number.
+fn is_word_char(chars: &[char], i: usize) -> bool {
+    let c = chars[i];
+    c.is_alphanumeric()
+        || c == '_'
+        || (c == '.' && chars.get(i + 1).is_some_and(|next| next.is_ascii_digit()))
+}
+
+fn word_template(word: String) -> String {
+    let has_digit = word.chars().any(|c| c.is_ascii_digit());
+    if has_digit && word.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return "<n>".to_string();
+    }
+    // Document IDs, request IDs, hashes and the like.
+    if has_digit && word.len() >= 16 {
+        return "<id>".to_string();
+    }
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        errors::{
+            FrameData,
+            JsError,
+            JsFrames,
+        },
+        runtime::UnixTimestamp,
+    };
+
+    use super::{
+        message_template,
+        ErrorOccurrence,
+    };
+
+    #[test]
+    fn test_message_template() {
+        assert_eq!(
+            message_template("Document \"k57a9rvnc6ft0bhcdp8qr0rtcn6z7w9s\" not found"),
+            "Document <str> not found"
+        );
+        assert_eq!(
+            message_template("Can't charge 12.50 to card ending 4242"),
+            "Can't charge <n> to card ending <n>"
+        );
+        assert_eq!(
+            message_template("Request a1b2c3d4e5f6a7b8c9 failed with status 503"),
+            "Request <id> failed with status <n>"
+        );
+        assert_eq!(
+            message_template("Uncaught Error: user2 is not allowed"),
+            "Uncaught Error: user2 is not allowed"
+        );
+    }
+
+    fn frame(function_name: &str, line_number: u32) -> FrameData {
+        FrameData {
+            type_name: None,
+            function_name: Some(function_name.to_string()),
+            method_name: None,
+            file_name: Some("../convex/messages.ts".to_string()),
+            line_number: Some(line_number),
+            column_number: Some(5),
+            eval_origin: None,
+            is_top_level: None,
+            is_eval: false,
+            is_native: false,
+            is_constructor: false,
+            is_async: true,
+            is_promise_all: false,
+            promise_index: None,
+        }
+    }
+
+    fn error(message: &str, frames: Vec<FrameData>) -> JsError {
+        JsError {
+            message: message.to_string(),
+            custom_data: None,
+            custom_data_source: Default::default(),
+            frames: Some(JsFrames(frames.into())),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let occurrence = |error: JsError| {
+            ErrorOccurrence::new(
+                &error,
+                "messages:send".to_string(),
+                UnixTimestamp::from_millis(1715000000000),
+            )
+        };
+        let first = occurrence(error(
+            "Message 1 is too long",
+            vec![frame("validate", 10), frame("handler", 20)],
+        ));
+        assert_eq!(
+            first.stack,
+            vec![
+                "validate (../convex/messages.ts:10:5)",
+                "handler (../convex/messages.ts:20:5)"
+            ]
+        );
+
+        // Same message template and stack, even after the code moved.
+        let second = occurrence(error(
+            "Message 2 is too long",
+            vec![frame("validate", 12), frame("handler", 22)],
+        ));
+        assert_eq!(first.fingerprint, second.fingerprint);
+
+        // Thrown from a different place.
+        let third = occurrence(error("Message 3 is too long", vec![frame("handler", 20)]));
+        assert_ne!(first.fingerprint, third.fingerprint);
+
+        // A different message.
+        let fourth = occurrence(error(
+            "Message 4 is empty",
+            vec![frame("validate", 10), frame("handler", 20)],
+        ));
+        assert_ne!(first.fingerprint, fourth.fingerprint);
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    knobs::ERROR_GROUP_MAX_AFFECTED_FUNCTIONS,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+pub mod fingerprint;
+pub mod types;
+
+use fingerprint::ErrorOccurrence;
+use types::ErrorGroup;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Exceptions thrown by functions, grouped by fingerprint. Read by the
+/// dashboard through system UDFs.
+pub static ERROR_GROUPS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_error_groups"
+        .parse()
+        .expect("Invalid built-in error groups table")
+});
+
+static FINGERPRINT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "fingerprint".parse().expect("Invalid built-in field"));
+
+static LAST_SEEN_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "lastSeen".parse().expect("Invalid built-in field"));
+
+pub static ERROR_GROUPS_INDEX_BY_FINGERPRINT: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&ERROR_GROUPS_TABLE, "by_fingerprint"));
+
+pub static ERROR_GROUPS_INDEX_BY_LAST_SEEN: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&ERROR_GROUPS_TABLE, "by_last_seen"));
+
+pub struct ErrorGroupsTable;
+impl SystemTable for ErrorGroupsTable {
+    fn table_name(&self) -> &'static TableName {
+        &ERROR_GROUPS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: ERROR_GROUPS_INDEX_BY_FINGERPRINT.clone(),
+                fields: vec![FINGERPRINT_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: ERROR_GROUPS_INDEX_BY_LAST_SEEN.clone(),
+                fields: vec![LAST_SEEN_FIELD.clone()].try_into().unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ErrorGroup>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ErrorGroupsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ErrorGroupsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn get(
+        &mut self,
+        fingerprint: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<ErrorGroup>>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("get_error_group"));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: ERROR_GROUPS_INDEX_BY_FINGERPRINT.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                FINGERPRINT_FIELD.clone(),
+                ConvexValue::try_from(fingerprint.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let Some(doc) = query_stream.next(self.tx, None).await? else {
+            return Ok(None);
+        };
+        anyhow::ensure!(
+            query_stream.next(self.tx, Some(1)).await?.is_none(),
+            "Expected at most one error group with fingerprint {fingerprint}"
+        );
+        Ok(Some(doc.try_into()?))
+    }
+
+    /// Adds `occurrence` to the group with its fingerprint, creating the group
+    /// if this is the first time the error has been seen.
+    pub async fn record(&mut self, occurrence: ErrorOccurrence) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("record_error_occurrence"));
+        }
+        match self.get(&occurrence.fingerprint).await? {
+            Some(existing) => {
+                let id = existing.id();
+                let mut group = existing.into_value();
+                group.count += 1;
+                // Occurrences can arrive slightly out of order, e.g. when a
+                // cached query result is logged.
+                if occurrence.timestamp >= group.last_seen {
+                    group.last_seen = occurrence.timestamp;
+                    group.last_message = occurrence.message;
+                    group.last_stack = occurrence.stack;
+                }
+                group.first_seen = group.first_seen.min(occurrence.timestamp);
+                if !group.functions.contains(&occurrence.function)
+                    && group.functions.len() < *ERROR_GROUP_MAX_AFFECTED_FUNCTIONS
+                {
+                    group.functions.push(occurrence.function);
+                }
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, group.try_into()?)
+                    .await?;
+            },
+            None => {
+                let group = ErrorGroup {
+                    fingerprint: occurrence.fingerprint,
+                    message_template: occurrence.message_template,
+                    last_message: occurrence.message,
+                    last_stack: occurrence.stack,
+                    first_seen: occurrence.timestamp,
+                    last_seen: occurrence.timestamp,
+                    count: 1,
+                    functions: vec![occurrence.function],
+                };
+                SystemMetadataModel::new_global(self.tx)
+                    .insert_metadata(&ERROR_GROUPS_TABLE, group.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Up to `limit` error groups, most recently seen first.
+    pub async fn list(&mut self, limit: usize) -> anyhow::Result<Vec<ParsedDocument<ErrorGroup>>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("list_error_groups"));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: ERROR_GROUPS_INDEX_BY_LAST_SEEN.clone(),
+            range: vec![],
+            order: Order::Desc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut groups = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            groups.push(doc.try_into()?);
+        }
+        Ok(groups)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        errors::JsError,
+        runtime::UnixTimestamp,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        error_groups::{
+            fingerprint::ErrorOccurrence,
+            ErrorGroupsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn occurrence(message: &str, function: &str, timestamp_ms: u64) -> ErrorOccurrence {
+        ErrorOccurrence::new(
+            &JsError::from_message(message.to_string()),
+            function.to_string(),
+            UnixTimestamp::from_millis(timestamp_ms),
+        )
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_record_error_occurrences(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = ErrorGroupsModel::new(&mut tx);
+        model
+            .record(occurrence("Message 1 not found", "messages:get", 2000))
+            .await?;
+        model
+            .record(occurrence("Message 2 not found", "messages:edit", 3000))
+            .await?;
+        model
+            .record(occurrence("Message 3 not found", "messages:get", 1000))
+            .await?;
+        model
+            .record(occurrence("Rate limited", "messages:send", 1500))
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let groups = ErrorGroupsModel::new(&mut tx).list(10).await?;
+        assert_eq!(groups.len(), 2);
+        let not_found = &groups[0];
+        assert_eq!(not_found.message_template, "Message <n> not found");
+        assert_eq!(not_found.last_message, "Message 2 not found");
+        assert_eq!(not_found.count, 3);
+        assert_eq!(not_found.first_seen, UnixTimestamp::from_millis(1000));
+        assert_eq!(not_found.last_seen, UnixTimestamp::from_millis(3000));
+        assert_eq!(not_found.functions, vec!["messages:get", "messages:edit"]);
+        assert_eq!(groups[1].message_template, "Rate limited");
+        assert_eq!(groups[1].count, 1);
+        Ok(())
+    }
+}
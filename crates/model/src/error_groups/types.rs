@@ -0,0 +1,92 @@
+use common::runtime::UnixTimestamp;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Exceptions thrown by functions that share a fingerprint, i.e. have the same
+/// message template and stack.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ErrorGroup {
+    pub fingerprint: String,
+    pub message_template: String,
+    // The message and stack of the most recent occurrence.
+    pub last_message: String,
+    pub last_stack: Vec<String>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub first_seen: UnixTimestamp,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub last_seen: UnixTimestamp,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub count: u64,
+    // Functions that have thrown this error, up to
+    // `ERROR_GROUP_MAX_AFFECTED_FUNCTIONS`.
+    pub functions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedErrorGroup {
+    fingerprint: String,
+    message_template: String,
+    last_message: String,
+    last_stack: Vec<String>,
+    // Nanoseconds since the Unix epoch.
+    first_seen: i64,
+    last_seen: i64,
+    count: i64,
+    functions: Vec<String>,
+}
+
+impl TryFrom<ErrorGroup> for SerializedErrorGroup {
+    type Error = anyhow::Error;
+
+    fn try_from(group: ErrorGroup) -> anyhow::Result<Self> {
+        Ok(Self {
+            fingerprint: group.fingerprint,
+            message_template: group.message_template,
+            last_message: group.last_message,
+            last_stack: group.last_stack,
+            first_seen: group.first_seen.as_nanos().try_into()?,
+            last_seen: group.last_seen.as_nanos().try_into()?,
+            count: group.count.try_into()?,
+            functions: group.functions,
+        })
+    }
+}
+
+impl TryFrom<SerializedErrorGroup> for ErrorGroup {
+    type Error = anyhow::Error;
+
+    fn try_from(group: SerializedErrorGroup) -> anyhow::Result<Self> {
+        Ok(Self {
+            fingerprint: group.fingerprint,
+            message_template: group.message_template,
+            last_message: group.last_message,
+            last_stack: group.last_stack,
+            first_seen: UnixTimestamp::from_nanos(group.first_seen.try_into()?),
+            last_seen: UnixTimestamp::from_nanos(group.last_seen.try_into()?),
+            count: group.count.try_into()?,
+            functions: group.functions,
+        })
+    }
+}
+
+codegen_convex_serialization!(ErrorGroup, SerializedErrorGroup);
@@ -1,23 +1,32 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     fmt,
     fmt::Display,
+    str::FromStr,
 };
 
 use anyhow::Context;
 use common::{
+    document::ResolvedDocument,
     obj,
     types::{
         ObjectKey,
         TableName,
     },
 };
+use errors::ErrorMetadata;
 use maplit::btreemap;
 use sync_types::Timestamp;
 use value::{
     val,
+    ConvexArray,
     ConvexObject,
     ConvexValue,
+    FieldName,
+    Namespace,
 };
 
 const EXPORT_RETENTION: u64 = 14 * 24 * 60 * 60 * 1000000000; // 14 days
@@ -27,12 +36,14 @@ const EXPORT_RETENTION: u64 = 14 * 24 * 60 * 60 * 1000000000; // 14 days
 pub enum Export {
     Requested {
         format: ExportFormat,
+        selection: ExportSelection,
     },
     InProgress {
         /// Timestamp when the first attempt
         /// at the Export started.
         start_ts: Timestamp,
         format: ExportFormat,
+        selection: ExportSelection,
     },
     Completed {
         /// Timestamp for the successful (final) attempt at Export.
@@ -45,6 +56,8 @@ pub enum Export {
         object_keys: ExportObjectKeys,
         /// Format of the export
         format: ExportFormat,
+        /// Tables and fields that were exported
+        selection: ExportSelection,
     },
     Failed {
         /// Timestamp for the failed (final) attempt at Export.
@@ -52,18 +65,28 @@ pub enum Export {
         /// Timestamp when the Export failed
         failed_ts: Timestamp,
         format: ExportFormat,
+        selection: ExportSelection,
     },
 }
 
 impl Export {
     pub fn format(&self) -> ExportFormat {
         match self {
-            Export::Requested { format }
+            Export::Requested { format, .. }
             | Export::InProgress { format, .. }
             | Export::Completed { format, .. }
             | Export::Failed { format, .. } => *format,
         }
     }
+
+    pub fn selection(&self) -> &ExportSelection {
+        match self {
+            Export::Requested { selection, .. }
+            | Export::InProgress { selection, .. }
+            | Export::Completed { selection, .. }
+            | Export::Failed { selection, .. } => selection,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -74,19 +97,136 @@ pub enum ExportFormat {
     /// jsonl format of clean export Json
     CleanJsonl,
     /// zip file containing a CleanJsonl for each table, and sidecar type info.
-    Zip { include_storage: bool },
+    Zip {
+        include_storage: bool,
+        storage_layout: FileStorageLayout,
+    },
+    /// Newline-delimited JSON file for each table, and a manifest of each
+    /// table's columns.
+    Ndjson,
+    /// Parquet file for each table, and a manifest of each table's columns.
+    Parquet,
+}
+
+/// Where a ZIP export that includes file storage puts the contents of each
+/// file. The `_storage/documents.jsonl` metadata is always in the ZIP.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum FileStorageLayout {
+    /// Each file is an entry in the ZIP, at `_storage/<id><extension>`.
+    #[default]
+    InZip,
+    /// Each file is written to export storage as its own object, and the ZIP
+    /// has a `_storage/manifest.jsonl` mapping storage ids to object keys.
+    /// This keeps the ZIP small when there are many large files, but only a
+    /// deployment that can read this deployment's export storage can import
+    /// it.
+    SeparateObjects,
+}
+
+impl FileStorageLayout {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::InZip => "in_zip",
+            Self::SeparateObjects => "separate_objects",
+        }
+    }
+}
+
+impl FromStr for FileStorageLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "in_zip" => Ok(Self::InZip),
+            "separate_objects" => Ok(Self::SeparateObjects),
+            _ => Err(anyhow::anyhow!(ErrorMetadata::bad_request(
+                "InvalidFileStorageLayout",
+                format!("Invalid file storage layout {s:?}, expected in_zip or separate_objects"),
+            ))),
+        }
+    }
+}
+
+/// Which user tables, and which of their fields, an export includes. The
+/// default exports every field of every user table.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ExportSelection {
+    /// Tables to export, or all user tables if None.
+    pub tables: Option<BTreeSet<TableName>>,
+    /// Top-level fields to leave out of each table's documents.
+    pub excluded_fields: BTreeMap<TableName, BTreeSet<FieldName>>,
+}
+
+impl ExportSelection {
+    pub fn includes_table(&self, table_name: &TableName) -> bool {
+        self.tables
+            .as_ref()
+            .map_or(true, |tables| tables.contains(table_name))
+    }
+
+    /// Checks that every selected table exists, and that excluded fields are
+    /// only for exported tables and don't include system fields, which are
+    /// needed to import the export.
+    pub fn validate(&self, user_table_names: &BTreeSet<&TableName>) -> anyhow::Result<()> {
+        let invalid = |msg: String| {
+            anyhow::anyhow!(ErrorMetadata::bad_request("InvalidExportSelection", msg))
+        };
+        if let Some(tables) = &self.tables {
+            if tables.is_empty() {
+                return Err(invalid("At least one table must be selected.".to_string()));
+            }
+            if let Some(table_name) = tables.iter().find(|t| !user_table_names.contains(t)) {
+                return Err(invalid(format!("Table {table_name} doesn't exist.")));
+            }
+        }
+        for (table_name, fields) in &self.excluded_fields {
+            if !user_table_names.contains(table_name) || !self.includes_table(table_name) {
+                return Err(invalid(format!(
+                    "Can't exclude fields from {table_name} because it isn't being exported."
+                )));
+            }
+            if let Some(field) = fields.iter().find(|f| f.is_system()) {
+                return Err(invalid(format!(
+                    "Can't exclude the system field {field} from {table_name}."
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the fields excluded from `table_name` from one of its
+    /// documents.
+    pub fn filter_document(
+        &self,
+        table_name: &TableName,
+        doc: ResolvedDocument,
+    ) -> anyhow::Result<ResolvedDocument> {
+        let Some(excluded_fields) = self.excluded_fields.get(table_name) else {
+            return Ok(doc);
+        };
+        let fields: BTreeMap<_, _> = doc
+            .value()
+            .iter()
+            .filter(|(field, _)| !excluded_fields.contains(*field))
+            .map(|(field, value)| (field.clone(), value.clone()))
+            .collect();
+        doc.replace_value(fields.try_into()?)
+    }
 }
 
 impl Export {
-    pub fn requested(format: ExportFormat) -> Self {
-        Self::Requested { format }
+    pub fn requested(format: ExportFormat, selection: ExportSelection) -> Self {
+        Self::Requested { format, selection }
     }
 
     pub fn in_progress(self, ts: Timestamp) -> anyhow::Result<Export> {
         match self {
-            Self::Requested { format } => Ok(Self::InProgress {
+            Self::Requested { format, selection } => Ok(Self::InProgress {
                 start_ts: ts,
                 format,
+                selection,
             }),
             Self::Completed { .. } | Self::InProgress { .. } | Self::Failed { .. } => Err(
                 anyhow::anyhow!("Can only begin an export that is requested"),
@@ -102,7 +242,9 @@ impl Export {
     ) -> anyhow::Result<Export> {
         let expiration_ts = Into::<u64>::into(complete_ts) + EXPORT_RETENTION;
         match self {
-            Self::InProgress { format, .. } => {
+            Self::InProgress {
+                format, selection, ..
+            } => {
                 anyhow::ensure!(snapshot_ts <= complete_ts);
                 Ok(Self::Completed {
                     start_ts: snapshot_ts,
@@ -110,20 +252,26 @@ impl Export {
                     expiration_ts,
                     object_keys,
                     format,
+                    selection,
                 })
             },
-            Self::Requested { format: _ }
+            Self::Requested {
+                format: _,
+                selection: _,
+            }
             | Self::Completed {
                 start_ts: _,
                 complete_ts: _,
                 expiration_ts: _,
                 object_keys: _,
                 format: _,
+                selection: _,
             }
             | Self::Failed {
                 start_ts: _,
                 failed_ts: _,
                 format: _,
+                selection: _,
             } => Err(anyhow::anyhow!(
                 "Can only complete an export that is in_progress"
             )),
@@ -132,26 +280,34 @@ impl Export {
 
     pub fn failed(self, snapshot_ts: Timestamp, failed_ts: Timestamp) -> anyhow::Result<Export> {
         match self {
-            Self::InProgress { format, .. } => {
+            Self::InProgress {
+                format, selection, ..
+            } => {
                 anyhow::ensure!(snapshot_ts <= failed_ts);
                 Ok(Self::Failed {
                     start_ts: snapshot_ts,
                     failed_ts,
                     format,
+                    selection,
                 })
             },
-            Self::Requested { format: _ }
+            Self::Requested {
+                format: _,
+                selection: _,
+            }
             | Self::Completed {
                 start_ts: _,
                 complete_ts: _,
                 expiration_ts: _,
                 object_keys: _,
                 format: _,
+                selection: _,
             }
             | Self::Failed {
                 start_ts: _,
                 failed_ts: _,
                 format: _,
+                selection: _,
             } => Err(anyhow::anyhow!(
                 "Can only fail an export that is in_progress"
             )),
@@ -162,10 +318,14 @@ impl Export {
 impl Display for Export {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::Requested { format: _ } => write!(f, "requested"),
+            Self::Requested {
+                format: _,
+                selection: _,
+            } => write!(f, "requested"),
             Self::InProgress {
                 start_ts: _,
                 format: _,
+                selection: _,
             } => write!(f, "in_progress"),
             Self::Completed {
                 start_ts: _,
@@ -173,11 +333,13 @@ impl Display for Export {
                 expiration_ts: _,
                 object_keys: _,
                 format: _,
+                selection: _,
             } => write!(f, "completed"),
             Self::Failed {
                 start_ts: _,
                 failed_ts: _,
                 format: _,
+                selection: _,
             } => write!(f, "failed"),
         }
     }
@@ -187,13 +349,15 @@ impl TryFrom<Export> for ConvexObject {
     type Error = anyhow::Error;
 
     fn try_from(e: Export) -> anyhow::Result<ConvexObject> {
-        match e {
+        let selection = ConvexObject::try_from(e.selection().clone())?;
+        let o = match e {
             Export::Completed {
                 start_ts,
                 complete_ts,
                 expiration_ts,
                 object_keys,
                 format,
+                selection: _,
             } => {
                 let mut o = btreemap! {
                     "start_ts".parse()? => val!(i64::from(start_ts)),
@@ -203,25 +367,37 @@ impl TryFrom<Export> for ConvexObject {
                     "format".parse()? => val!(format),
                 };
                 match object_keys {
-                    ExportObjectKeys::ByTable(tables) => o.insert("tables".parse()?, {
-                        let tables: Vec<_> = tables
-                            .into_iter()
-                            .map(|(t, o)| anyhow::Ok(val!([t.to_string(), o.to_string()])))
-                            .try_collect()?;
-                        ConvexValue::Array(tables.try_into()?)
-                    }),
-                    ExportObjectKeys::Zip(object_key) => o.insert(
-                        "zip_object_key".parse()?,
-                        ConvexValue::try_from(object_key.to_string())?,
-                    ),
+                    ExportObjectKeys::ByTable(tables) => {
+                        o.insert("tables".parse()?, table_object_keys_to_value(tables)?);
+                    },
+                    ExportObjectKeys::Zip(object_key) => {
+                        o.insert(
+                            "zip_object_key".parse()?,
+                            ConvexValue::try_from(object_key.to_string())?,
+                        );
+                    },
+                    ExportObjectKeys::ByTableWithManifest { tables, manifest } => {
+                        o.insert("tables".parse()?, table_object_keys_to_value(tables)?);
+                        o.insert(
+                            "manifest_object_key".parse()?,
+                            ConvexValue::try_from(manifest.to_string())?,
+                        );
+                    },
                 };
                 ConvexObject::try_from(o)
             },
-            Export::Requested { format } => obj!(
+            Export::Requested {
+                format,
+                selection: _,
+            } => obj!(
                 "state" => "requested",
                 "format" => format,
             ),
-            Export::InProgress { start_ts, format } => {
+            Export::InProgress {
+                start_ts,
+                format,
+                selection: _,
+            } => {
                 obj!(
                     "state" => "in_progress",
                     "start_ts" => i64::from(start_ts),
@@ -232,6 +408,7 @@ impl TryFrom<Export> for ConvexObject {
                 start_ts,
                 failed_ts,
                 format,
+                selection: _,
             } => {
                 obj!(
                     "state" => "failed",
@@ -240,7 +417,8 @@ impl TryFrom<Export> for ConvexObject {
                     "format" => format,
                 )
             },
-        }
+        }?;
+        o.shallow_merge(selection)
     }
 }
 
@@ -252,9 +430,10 @@ impl TryFrom<ConvexObject> for Export {
             Some(format) => ExportFormat::try_from(format.clone())?,
             _ => anyhow::bail!("invalid format: {:?}", o),
         };
+        let selection = ExportSelection::try_from(&o)?;
         match o.get("state") {
             Some(ConvexValue::String(s)) => match &s[..] {
-                "requested" => Ok(Export::Requested { format }),
+                "requested" => Ok(Export::Requested { format, selection }),
                 "in_progress" => {
                     if let Some(start_ts_value) = o.get("start_ts")
                         && let ConvexValue::Int64(start_ts) = start_ts_value
@@ -262,6 +441,7 @@ impl TryFrom<ConvexObject> for Export {
                         Ok(Export::InProgress {
                             start_ts: (*start_ts).try_into()?,
                             format,
+                            selection,
                         })
                     } else {
                         Err(anyhow::anyhow!("No start_ts found for in_progress export."))
@@ -280,37 +460,25 @@ impl TryFrom<ConvexObject> for Export {
                         Some(ConvexValue::Int64(t)) => *t as u64,
                         _ => anyhow::bail!("invalid expiration_ts: {:?}", o),
                     };
-                    let object_keys = match (o.get("tables"), o.get("zip_object_key")) {
-                        (Some(ConvexValue::Array(tables)), None) => ExportObjectKeys::ByTable(
-                            tables
-                                .iter()
-                                .map(|v| match v {
-                                    ConvexValue::Array(t) => {
-                                        let table_name = match t
-                                            .first()
-                                            .context("array must have table name")?
-                                        {
-                                            ConvexValue::String(t) => t.parse()?,
-                                            _ => anyhow::bail!("invalid table name"),
-                                        };
-                                        let object_key = match t
-                                            .get(1)
-                                            .context("array must have export object key")?
-                                        {
-                                            ConvexValue::String(o) => {
-                                                String::from(o.clone()).try_into()?
-                                            },
-                                            _ => anyhow::bail!("invalid export object key"),
-                                        };
-                                        anyhow::Ok((table_name, object_key))
-                                    },
-                                    _ => anyhow::bail!("Tables must be a [string, string]"),
-                                })
-                                .try_collect()?,
-                        ),
-                        (None, Some(ConvexValue::String(zip_object_key))) => {
+                    let object_keys = match (
+                        o.get("tables"),
+                        o.get("zip_object_key"),
+                        o.get("manifest_object_key"),
+                    ) {
+                        (Some(ConvexValue::Array(tables)), None, None) => {
+                            ExportObjectKeys::ByTable(table_object_keys_from_value(tables)?)
+                        },
+                        (None, Some(ConvexValue::String(zip_object_key)), None) => {
                             ExportObjectKeys::Zip(String::from(zip_object_key.clone()).try_into()?)
                         },
+                        (
+                            Some(ConvexValue::Array(tables)),
+                            None,
+                            Some(ConvexValue::String(manifest)),
+                        ) => ExportObjectKeys::ByTableWithManifest {
+                            tables: table_object_keys_from_value(tables)?,
+                            manifest: String::from(manifest.clone()).try_into()?,
+                        },
                         _ => anyhow::bail!("invalid object keys: {:?}", o),
                     };
                     Ok(Export::Completed {
@@ -319,6 +487,7 @@ impl TryFrom<ConvexObject> for Export {
                         complete_ts,
                         object_keys,
                         format,
+                        selection,
                     })
                 },
                 "failed" => {
@@ -334,6 +503,7 @@ impl TryFrom<ConvexObject> for Export {
                         start_ts,
                         failed_ts,
                         format,
+                        selection,
                     })
                 },
                 _ => Err(anyhow::anyhow!("Invalid export state {s}")),
@@ -350,9 +520,20 @@ impl TryFrom<ExportFormat> for ConvexValue {
         let v = match value {
             ExportFormat::InternalJson => val!("internal_json"),
             ExportFormat::CleanJsonl => val!("clean_jsonl"),
-            ExportFormat::Zip { include_storage } => {
-                val!({"format" => "zip", "include_storage" => include_storage})
-            },
+            ExportFormat::Zip {
+                include_storage,
+                storage_layout: FileStorageLayout::InZip,
+            } => val!({"format" => "zip", "include_storage" => include_storage}),
+            ExportFormat::Zip {
+                include_storage,
+                storage_layout,
+            } => val!({
+                "format" => "zip",
+                "include_storage" => include_storage,
+                "storage_layout" => storage_layout.as_str()
+            }),
+            ExportFormat::Ndjson => val!("ndjson"),
+            ExportFormat::Parquet => val!("parquet"),
         };
         Ok(v)
     }
@@ -368,16 +549,27 @@ impl TryFrom<ConvexValue> for ExportFormat {
                 "clean_jsonl" => Self::CleanJsonl,
                 "zip" => Self::Zip {
                     include_storage: false,
+                    storage_layout: FileStorageLayout::InZip,
                 },
+                "ndjson" => Self::Ndjson,
+                "parquet" => Self::Parquet,
                 _ => anyhow::bail!("invalid format {value:?}"),
             },
             ConvexValue::Object(o) => match o.get("format") {
                 Some(ConvexValue::String(format)) => match &**format {
-                    "zip" => match o.get("include_storage") {
-                        Some(ConvexValue::Boolean(include_storage)) => Self::Zip {
-                            include_storage: *include_storage,
-                        },
-                        _ => anyhow::bail!("invalid format {value:?}"),
+                    "zip" => {
+                        let storage_layout = match o.get("storage_layout") {
+                            None => FileStorageLayout::InZip,
+                            Some(ConvexValue::String(s)) => s.parse()?,
+                            _ => anyhow::bail!("invalid format {value:?}"),
+                        };
+                        match o.get("include_storage") {
+                            Some(ConvexValue::Boolean(include_storage)) => Self::Zip {
+                                include_storage: *include_storage,
+                                storage_layout,
+                            },
+                            _ => anyhow::bail!("invalid format {value:?}"),
+                        }
                     },
                     _ => anyhow::bail!("invalid format {value:?}"),
                 },
@@ -389,12 +581,138 @@ impl TryFrom<ConvexValue> for ExportFormat {
     }
 }
 
+/// Stored as an array of `[table_name, object_key]` pairs.
+fn table_object_keys_to_value(
+    tables: BTreeMap<TableName, ObjectKey>,
+) -> anyhow::Result<ConvexValue> {
+    let tables: Vec<_> = tables
+        .into_iter()
+        .map(|(t, o)| anyhow::Ok(val!([t.to_string(), o.to_string()])))
+        .try_collect()?;
+    Ok(ConvexValue::Array(tables.try_into()?))
+}
+
+fn table_object_keys_from_value(
+    tables: &ConvexArray,
+) -> anyhow::Result<BTreeMap<TableName, ObjectKey>> {
+    tables
+        .iter()
+        .map(|v| match v {
+            ConvexValue::Array(t) => {
+                let table_name = match t.first().context("array must have table name")? {
+                    ConvexValue::String(t) => t.parse()?,
+                    _ => anyhow::bail!("invalid table name"),
+                };
+                let object_key = match t.get(1).context("array must have export object key")? {
+                    ConvexValue::String(o) => String::from(o.clone()).try_into()?,
+                    _ => anyhow::bail!("invalid export object key"),
+                };
+                anyhow::Ok((table_name, object_key))
+            },
+            _ => anyhow::bail!("Tables must be a [string, string]"),
+        })
+        .try_collect()
+}
+
+/// Only stored when it differs from the default, as `selected_tables` and
+/// `excluded_fields` fields alongside the rest of the export.
+impl TryFrom<ExportSelection> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(selection: ExportSelection) -> anyhow::Result<ConvexObject> {
+        let mut o = BTreeMap::new();
+        if let Some(tables) = selection.tables {
+            let tables: Vec<_> = tables
+                .into_iter()
+                .map(|t| ConvexValue::try_from(t.to_string()))
+                .try_collect()?;
+            o.insert(
+                "selected_tables".parse()?,
+                ConvexValue::Array(tables.try_into()?),
+            );
+        }
+        if !selection.excluded_fields.is_empty() {
+            let excluded_fields: Vec<_> = selection
+                .excluded_fields
+                .into_iter()
+                .map(|(t, fields)| {
+                    let fields: Vec<_> = fields
+                        .into_iter()
+                        .map(|f| ConvexValue::try_from(f.to_string()))
+                        .try_collect()?;
+                    let entry = vec![
+                        ConvexValue::try_from(t.to_string())?,
+                        ConvexValue::Array(fields.try_into()?),
+                    ];
+                    anyhow::Ok(ConvexValue::Array(entry.try_into()?))
+                })
+                .try_collect()?;
+            o.insert(
+                "excluded_fields".parse()?,
+                ConvexValue::Array(excluded_fields.try_into()?),
+            );
+        }
+        ConvexObject::try_from(o)
+    }
+}
+
+impl TryFrom<&ConvexObject> for ExportSelection {
+    type Error = anyhow::Error;
+
+    fn try_from(o: &ConvexObject) -> anyhow::Result<ExportSelection> {
+        let tables = o.get("selected_tables").map(parse_names).transpose()?;
+        let excluded_fields = match o.get("excluded_fields") {
+            Some(ConvexValue::Array(entries)) => entries
+                .iter()
+                .map(|entry| match entry {
+                    ConvexValue::Array(entry) => {
+                        let table_name = match entry.first().context("missing table name")? {
+                            ConvexValue::String(t) => t.parse()?,
+                            _ => anyhow::bail!("invalid table name"),
+                        };
+                        let fields = parse_names(entry.get(1).context("missing fields")?)?;
+                        anyhow::Ok((table_name, fields))
+                    },
+                    _ => anyhow::bail!("Excluded fields must be a [string, string[]]"),
+                })
+                .try_collect()?,
+            Some(_) => anyhow::bail!("invalid excluded_fields: {:?}", o),
+            None => BTreeMap::new(),
+        };
+        Ok(ExportSelection {
+            tables,
+            excluded_fields,
+        })
+    }
+}
+
+fn parse_names<T: FromStr<Err = anyhow::Error> + Ord>(
+    v: &ConvexValue,
+) -> anyhow::Result<BTreeSet<T>> {
+    match v {
+        ConvexValue::Array(names) => names
+            .iter()
+            .map(|name| match name {
+                ConvexValue::String(name) => name.parse(),
+                _ => anyhow::bail!("invalid name {name:?}"),
+            })
+            .try_collect(),
+        _ => anyhow::bail!("invalid names {v:?}"),
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub enum ExportObjectKeys {
     /// Tables that were exported, and the object key in S3 for the export.
     ByTable(BTreeMap<TableName, ObjectKey>),
     Zip(ObjectKey),
+    /// A file for each table that was exported, and the manifest describing
+    /// their columns.
+    ByTableWithManifest {
+        tables: BTreeMap<TableName, ObjectKey>,
+        manifest: ObjectKey,
+    },
 }
 
 #[cfg(test)]
@@ -0,0 +1,255 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    FileLifecycleRule,
+    LifecycleRunReport,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Rules that delete stored files, or move them to a colder storage class,
+/// once they're old or no longer referenced.
+pub static FILE_LIFECYCLE_RULES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_file_lifecycle_rules"
+        .parse()
+        .expect("Invalid built-in file lifecycle rules table")
+});
+
+pub struct FileLifecycleRulesTable;
+impl SystemTable for FileLifecycleRulesTable {
+    fn table_name(&self) -> &'static TableName {
+        &FILE_LIFECYCLE_RULES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FileLifecycleRule>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FileLifecycleRulesModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FileLifecycleRulesModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<FileLifecycleRule>>> {
+        self.check_admin("list_file_lifecycle_rules")?;
+        let query = Query::full_table_scan(FILE_LIFECYCLE_RULES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut rules = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            rules.push(doc.try_into()?);
+        }
+        Ok(rules)
+    }
+
+    pub async fn get(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<FileLifecycleRule>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|rule| rule.name == name))
+    }
+
+    /// Configure a rule, replacing any existing rule with the same name. The
+    /// report of the replaced rule's last run is dropped, so the rule runs
+    /// again soon with its new settings.
+    pub async fn add_or_update(
+        &mut self,
+        mut rule: FileLifecycleRule,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("add_file_lifecycle_rule")?;
+        rule.validate()?;
+        rule.last_run = None;
+        match self.get(&rule.name).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), rule.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FILE_LIFECYCLE_RULES_TABLE, rule.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    pub async fn delete(&mut self, name: &str) -> anyhow::Result<()> {
+        self.check_admin("delete_file_lifecycle_rule")?;
+        let Some(existing) = self.get(name).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "FileLifecycleRuleNotFound",
+                format!("There is no file lifecycle rule named {name:?}"),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+
+    /// Record the report of a run of the rule `id`, unless the rule was
+    /// deleted during the run.
+    pub async fn set_last_run(
+        &mut self,
+        id: ResolvedDocumentId,
+        report: LifecycleRunReport,
+    ) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("set_file_lifecycle_rule_last_run"));
+        }
+        let Some(document) = self.tx.get(id).await? else {
+            return Ok(());
+        };
+        let mut rule: ParsedDocument<FileLifecycleRule> = document.try_into()?;
+        rule.last_run = Some(report);
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, rule.into_value().try_into()?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::types::Timestamp;
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        file_lifecycle_rules::{
+            types::{
+                FileLifecycleRule,
+                LifecycleAction,
+                LifecycleRunReport,
+            },
+            FileLifecycleRulesModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn rule(name: &str, action: LifecycleAction) -> FileLifecycleRule {
+        FileLifecycleRule {
+            name: name.to_string(),
+            min_age_days: 30,
+            only_orphaned: true,
+            action,
+            dry_run: false,
+            last_run: None,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(rule("orphans", LifecycleAction::Delete).validate().is_ok());
+        assert!(rule("a/b", LifecycleAction::Delete).validate().is_err());
+        let glacier = LifecycleAction::Transition {
+            storage_class: "GLACIER".to_string(),
+        };
+        assert!(rule("archive", glacier).validate().is_ok());
+        let invalid_class = LifecycleAction::Transition {
+            storage_class: "not a class".to_string(),
+        };
+        assert!(rule("archive", invalid_class).validate().is_err());
+        let mut invalid = rule("orphans", LifecycleAction::Delete);
+        invalid.min_age_days = 0;
+        assert!(invalid.validate().is_err());
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_rules(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FileLifecycleRulesModel::new(&mut tx);
+        let id = model
+            .add_or_update(rule("orphans", LifecycleAction::Delete))
+            .await?;
+        let report = LifecycleRunReport {
+            started_ts: Timestamp::must(1),
+            finished_ts: Timestamp::must(2),
+            dry_run: false,
+            files_matched: 2,
+            bytes_matched: 100,
+            files_applied: 2,
+            sample_storage_ids: vec![],
+            error: None,
+        };
+        model.set_last_run(id, report.clone()).await?;
+        assert_eq!(model.get("orphans").await?.unwrap().last_run, Some(report));
+
+        // Updating a rule drops the report of its last run.
+        let mut updated = rule("orphans", LifecycleAction::Delete);
+        updated.dry_run = true;
+        assert_eq!(model.add_or_update(updated).await?, id);
+        let rules = model.list().await?;
+        assert_eq!(rules.len(), 1);
+        assert!(rules[0].dry_run);
+        assert_eq!(rules[0].last_run, None);
+
+        model.delete("orphans").await?;
+        assert!(model.list().await?.is_empty());
+        assert!(model.delete("orphans").await.is_err());
+        // The report of a run of a deleted rule is dropped.
+        let report = LifecycleRunReport {
+            started_ts: Timestamp::must(3),
+            finished_ts: Timestamp::must(4),
+            dry_run: true,
+            files_matched: 0,
+            bytes_matched: 0,
+            files_applied: 0,
+            sample_storage_ids: vec![],
+            error: None,
+        };
+        model.set_last_run(id, report).await?;
+        assert!(model.list().await?.is_empty());
+        Ok(())
+    }
+}
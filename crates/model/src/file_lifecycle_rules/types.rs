@@ -0,0 +1,228 @@
+use common::types::Timestamp;
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Rules can match files up to a century old.
+pub const MAX_MIN_AGE_DAYS: u64 = 36500;
+
+/// How many of the matched files' ids a run report keeps, to check what a
+/// dry run would have changed.
+pub const MAX_REPORT_SAMPLE_IDS: usize = 10;
+
+/// What a lifecycle rule does to the files it matches.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum LifecycleAction {
+    Delete,
+    // Move the file's object to a colder storage class of the bucket, e.g.
+    // `GLACIER` on S3 or `Cool` on Azure.
+    Transition { storage_class: String },
+}
+
+/// A rule for the files in the deployment's file storage, e.g. "delete files
+/// no document references a week after they're uploaded". The rules are run
+/// periodically by a background worker.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FileLifecycleRule {
+    // Unique name of the rule.
+    pub name: String,
+    // Files uploaded at least this many days ago match.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "1..=MAX_MIN_AGE_DAYS")
+    )]
+    pub min_age_days: u64,
+    // Only match files that no document references.
+    pub only_orphaned: bool,
+    pub action: LifecycleAction,
+    // Report the files the rule matches without changing them.
+    pub dry_run: bool,
+    // What the rule's latest run matched and changed.
+    pub last_run: Option<LifecycleRunReport>,
+}
+
+impl FileLifecycleRule {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid = |msg: String| {
+            anyhow::anyhow!(ErrorMetadata::bad_request("InvalidFileLifecycleRule", msg))
+        };
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid(format!(
+                "Invalid rule name {:?}, expected letters, digits, `-` and `_`",
+                self.name
+            )));
+        }
+        if !(1..=MAX_MIN_AGE_DAYS).contains(&self.min_age_days) {
+            return Err(invalid(format!(
+                "minAgeDays must be between 1 and {MAX_MIN_AGE_DAYS}"
+            )));
+        }
+        if let LifecycleAction::Transition { storage_class } = &self.action
+            && (storage_class.is_empty()
+                || storage_class.len() > 64
+                || !storage_class
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        {
+            return Err(invalid(format!(
+                "Invalid storage class {storage_class:?}, expected a class of the bucket like \
+                 \"GLACIER\""
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of one run of a lifecycle rule.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct LifecycleRunReport {
+    pub started_ts: Timestamp,
+    pub finished_ts: Timestamp,
+    // Whether the matched files were left unchanged.
+    pub dry_run: bool,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub files_matched: u64,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub bytes_matched: u64,
+    // Files deleted or transitioned, which is fewer than matched if the run
+    // failed partway through.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub files_applied: u64,
+    // `_storage` ids of the first matched files.
+    pub sample_storage_ids: Vec<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedLifecycleAction {
+    Delete,
+    #[serde(rename_all = "camelCase")]
+    Transition {
+        storage_class: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedLifecycleRunReport {
+    started_ts: i64,
+    finished_ts: i64,
+    dry_run: bool,
+    files_matched: i64,
+    bytes_matched: i64,
+    files_applied: i64,
+    sample_storage_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFileLifecycleRule {
+    name: String,
+    min_age_days: i64,
+    only_orphaned: bool,
+    action: SerializedLifecycleAction,
+    dry_run: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_run: Option<SerializedLifecycleRunReport>,
+}
+
+impl TryFrom<LifecycleRunReport> for SerializedLifecycleRunReport {
+    type Error = anyhow::Error;
+
+    fn try_from(report: LifecycleRunReport) -> anyhow::Result<Self> {
+        Ok(Self {
+            started_ts: report.started_ts.into(),
+            finished_ts: report.finished_ts.into(),
+            dry_run: report.dry_run,
+            files_matched: report.files_matched.try_into()?,
+            bytes_matched: report.bytes_matched.try_into()?,
+            files_applied: report.files_applied.try_into()?,
+            sample_storage_ids: report.sample_storage_ids,
+            error: report.error,
+        })
+    }
+}
+
+impl TryFrom<SerializedLifecycleRunReport> for LifecycleRunReport {
+    type Error = anyhow::Error;
+
+    fn try_from(report: SerializedLifecycleRunReport) -> anyhow::Result<Self> {
+        Ok(Self {
+            started_ts: report.started_ts.try_into()?,
+            finished_ts: report.finished_ts.try_into()?,
+            dry_run: report.dry_run,
+            files_matched: report.files_matched.try_into()?,
+            bytes_matched: report.bytes_matched.try_into()?,
+            files_applied: report.files_applied.try_into()?,
+            sample_storage_ids: report.sample_storage_ids,
+            error: report.error,
+        })
+    }
+}
+
+impl TryFrom<FileLifecycleRule> for SerializedFileLifecycleRule {
+    type Error = anyhow::Error;
+
+    fn try_from(rule: FileLifecycleRule) -> anyhow::Result<Self> {
+        let action = match rule.action {
+            LifecycleAction::Delete => SerializedLifecycleAction::Delete,
+            LifecycleAction::Transition { storage_class } => {
+                SerializedLifecycleAction::Transition { storage_class }
+            },
+        };
+        Ok(Self {
+            name: rule.name,
+            min_age_days: rule.min_age_days.try_into()?,
+            only_orphaned: rule.only_orphaned,
+            action,
+            dry_run: rule.dry_run,
+            last_run: rule.last_run.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedFileLifecycleRule> for FileLifecycleRule {
+    type Error = anyhow::Error;
+
+    fn try_from(rule: SerializedFileLifecycleRule) -> anyhow::Result<Self> {
+        let action = match rule.action {
+            SerializedLifecycleAction::Delete => LifecycleAction::Delete,
+            SerializedLifecycleAction::Transition { storage_class } => {
+                LifecycleAction::Transition { storage_class }
+            },
+        };
+        Ok(Self {
+            name: rule.name,
+            min_age_days: rule.min_age_days.try_into()?,
+            only_orphaned: rule.only_orphaned,
+            action,
+            dry_run: rule.dry_run,
+            last_run: rule.last_run.map(TryInto::try_into).transpose()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(FileLifecycleRule, SerializedFileLifecycleRule);
@@ -1,5 +1,8 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     str::FromStr,
     sync::{
         Arc,
@@ -10,9 +13,12 @@ use std::{
 use anyhow::Context;
 use common::{
     document::{
+        CreationTime,
         ParsedDocument,
         ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
     },
+    knobs::MAX_FILES_SCANNED_PER_PAGE,
     query::{
         IndexRange,
         IndexRangeExpression,
@@ -32,6 +38,7 @@ use database::{
         TableFilter,
     },
     unauthorized_error,
+    BootstrapComponentsModel,
     ResolvedQuery,
     SystemMetadataModel,
     TableModel,
@@ -58,6 +65,7 @@ use self::virtual_table::FileStorageDocMapper;
 use crate::{
     file_storage::types::{
         FileStorageEntry,
+        FileStorageFilter,
         StorageUuid,
     },
     SystemIndex,
@@ -69,6 +77,16 @@ pub mod virtual_table;
 
 pub type BatchKey = usize;
 
+/// A page of files returned by [`FileStorageModel::list_page`].
+pub struct FileStoragePage {
+    /// Files paired with their ids in the `_storage` table, which is how apps
+    /// refer to them.
+    pub files: Vec<(DeveloperDocumentId, ParsedDocument<FileStorageEntry>)>,
+    /// Creation time of the last file scanned, to continue listing from. None
+    /// once all files have been scanned.
+    pub cursor: Option<CreationTime>,
+}
+
 pub static FILE_STORAGE_TABLE: LazyLock<TableName> = LazyLock::new(|| {
     "_file_storage"
         .parse()
@@ -205,6 +223,7 @@ impl<'a, RT: Runtime> FileStorageModel<'a, RT> {
         &mut self,
         entry: FileStorageEntry,
     ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_storage_quota(entry.size).await?;
         // Call insert_metadata rather than insert because we already
         // did access check on `identity` rather than `self.identity`
         SystemMetadataModel::new(self.tx, self.namespace)
@@ -331,6 +350,118 @@ impl<'a, RT: Runtime> FileStorageModel<'a, RT> {
         Ok(Some(entry.into_value()))
     }
 
+    /// Replace the tags and metadata the app set on a file. Returns false if
+    /// the file doesn't exist.
+    pub async fn set_tags_and_metadata(
+        &mut self,
+        storage_id: FileStorageId,
+        tags: Option<BTreeSet<String>>,
+        metadata: Option<BTreeMap<String, String>>,
+    ) -> anyhow::Result<bool> {
+        let Some(entry) = self.get_file(storage_id).await? else {
+            return Ok(false);
+        };
+        let (document_id, mut entry) = entry.into_id_and_value();
+        if let Some(tags) = tags {
+            entry.tags = tags;
+        }
+        if let Some(metadata) = metadata {
+            entry.metadata = metadata;
+        }
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .replace(document_id, entry.try_into()?)
+            .await?;
+        Ok(true)
+    }
+
+    /// Record that the object of the file `id` was moved to `storage_class`.
+    /// Does nothing if the file was deleted since.
+    pub async fn set_storage_class(
+        &mut self,
+        id: ResolvedDocumentId,
+        storage_class: String,
+    ) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("set_storage_class"))
+        }
+        let Some(document) = self.tx.get(id).await? else {
+            return Ok(());
+        };
+        let mut entry: ParsedDocument<FileStorageEntry> = document.try_into()?;
+        entry.storage_class = Some(storage_class);
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .replace(id, entry.into_value().try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns up to `limit` files matching the filter, oldest first, starting
+    /// after `cursor`. At most `MAX_FILES_SCANNED_PER_PAGE` files are read, so
+    /// a page can hold fewer than `limit` files even if more match. The page's
+    /// cursor is None once all files have been scanned.
+    pub async fn list_page(
+        &mut self,
+        filter: &FileStorageFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<FileStoragePage> {
+        let mut range = vec![];
+        match (cursor, filter.uploaded_after) {
+            (Some(cursor), _) => range.push(IndexRangeExpression::Gt(
+                CREATION_TIME_FIELD_PATH.clone(),
+                ConvexValue::Float64(cursor.into()),
+            )),
+            (None, Some(uploaded_after)) => range.push(IndexRangeExpression::Gte(
+                CREATION_TIME_FIELD_PATH.clone(),
+                ConvexValue::Float64(uploaded_after.into()),
+            )),
+            (None, None) => {},
+        }
+        if let Some(uploaded_before) = filter.uploaded_before {
+            range.push(IndexRangeExpression::Lt(
+                CREATION_TIME_FIELD_PATH.clone(),
+                ConvexValue::Float64(uploaded_before.into()),
+            ));
+        }
+        let index_query = Query::index_range(IndexRange {
+            index_name: FILE_STORAGE_INDEX_BY_CREATION_TIME.clone(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let table_mapping = self.tx.table_mapping().clone();
+        let virtual_table_mapping = self.tx.virtual_table_mapping().clone();
+        let mut files = Vec::new();
+        let mut last_creation_time = cursor;
+        let mut num_scanned = 0;
+        while files.len() < limit && num_scanned < *MAX_FILES_SCANNED_PER_PAGE {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                return Ok(FileStoragePage {
+                    files,
+                    cursor: None,
+                });
+            };
+            num_scanned += 1;
+            let file: ParsedDocument<FileStorageEntry> = doc.try_into()?;
+            last_creation_time = file.creation_time().or(last_creation_time);
+            if filter.matches(&file) {
+                let storage_id = self
+                    .tx
+                    .virtual_system_mapping()
+                    .system_resolved_id_to_virtual_developer_id(
+                        file.id(),
+                        &table_mapping,
+                        &virtual_table_mapping,
+                    )?;
+                files.push((storage_id, file));
+            }
+        }
+        Ok(FileStoragePage {
+            files,
+            cursor: last_creation_time,
+        })
+    }
+
     pub async fn get_total_storage_count(&mut self) -> anyhow::Result<u64> {
         TableModel::new(self.tx)
             .count(self.namespace, &FILE_STORAGE_TABLE.clone())
@@ -341,7 +472,31 @@ impl<'a, RT: Runtime> FileStorageModel<'a, RT> {
         if !self.tx.identity().is_system() {
             anyhow::bail!(unauthorized_error("get_total_storage_size"))
         }
+        self.total_storage_size().await
+    }
+
+    /// Check that the component can store another `size` bytes. This scans
+    /// all of the component's files, so it's only done for components with a
+    /// storage quota.
+    async fn check_storage_quota(&mut self, size: i64) -> anyhow::Result<()> {
+        let quotas = BootstrapComponentsModel::new(self.tx)
+            .load_quotas(self.namespace)
+            .await?;
+        let Some(max_storage_bytes) = quotas.max_storage_bytes else {
+            return Ok(());
+        };
+        let total_size = self.total_storage_size().await? + u64::try_from(size)?;
+        anyhow::ensure!(
+            total_size <= max_storage_bytes,
+            ErrorMetadata::bad_request(
+                "ComponentQuotaExceeded",
+                format!("Component is over its quota of {max_storage_bytes} bytes of file storage"),
+            )
+        );
+        Ok(())
+    }
 
+    async fn total_storage_size(&mut self) -> anyhow::Result<u64> {
         let query = Query::full_table_scan(FILE_STORAGE_TABLE.to_owned(), Order::Asc);
         let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
         let mut total_size = 0;
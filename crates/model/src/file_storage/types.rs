@@ -1,21 +1,37 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     str::FromStr,
 };
 
 use anyhow::Context;
 use common::{
+    document::{
+        CreationTime,
+        ParsedDocument,
+    },
     obj,
     types::ObjectKey,
 };
+use errors::ErrorMetadata;
 use pb::storage::FileStorageEntry as FileStorageEntryProto;
 use uuid::Uuid;
 use value::{
     sha256::Sha256Digest,
+    ConvexArray,
     ConvexObject,
     ConvexValue,
+    IdentifierFieldName,
 };
 
+/// Limits on the tags and metadata set on a file.
+pub const MAX_FILE_TAGS: usize = 32;
+pub const MAX_FILE_TAG_LENGTH: usize = 64;
+pub const MAX_FILE_METADATA_ENTRIES: usize = 32;
+pub const MAX_FILE_METADATA_VALUE_LENGTH: usize = 1024;
+
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 #[derive(Clone, Debug, PartialEq)]
 pub struct FileStorageEntry {
@@ -25,6 +41,65 @@ pub struct FileStorageEntry {
     pub sha256: Sha256Digest,   // Sha256 of contents
     pub size: i64,              // Size of file in storage
     pub content_type: Option<String>, // Optional ContentType header saved with file
+    pub tags: BTreeSet<String>, // Set by the app, e.g. to find files to clean up
+    // Set by the app. Keys are identifiers, so the metadata can be stored as an object.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::btree_map(\"[a-zA-Z][a-zA-Z0-9_]{0,8}\", \
+                        proptest::prelude::any::<String>(), 0..4)"
+        )
+    )]
+    pub metadata: BTreeMap<String, String>,
+    // Storage class the object was transitioned to by a lifecycle rule, e.g.
+    // `GLACIER`. None while it's in the bucket's default class.
+    pub storage_class: Option<String>,
+    // Verdict of the file scanner the file went through when it was uploaded.
+    // None if no scanner was configured.
+    pub scan_status: Option<FileScanStatus>,
+}
+
+impl FileStorageEntry {
+    /// Whether the file was quarantined by the file scanner, so it must not be
+    /// served.
+    pub fn is_blocked(&self) -> bool {
+        matches!(self.scan_status, Some(FileScanStatus::Blocked { .. }))
+    }
+}
+
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum FileScanStatus {
+    Clean,
+    // The scanner flagged the file, e.g. as `Eicar-Signature`.
+    Blocked { signature: String },
+}
+
+impl FileScanStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileScanStatus::Clean => "clean",
+            FileScanStatus::Blocked { .. } => "blocked",
+        }
+    }
+
+    pub fn signature(&self) -> Option<&str> {
+        match self {
+            FileScanStatus::Clean => None,
+            FileScanStatus::Blocked { signature } => Some(signature),
+        }
+    }
+
+    /// Parse the fields `as_str` and `signature` are stored in.
+    pub fn from_parts(status: &str, signature: Option<String>) -> anyhow::Result<Self> {
+        match (status, signature) {
+            ("clean", None) => Ok(FileScanStatus::Clean),
+            ("blocked", Some(signature)) => Ok(FileScanStatus::Blocked { signature }),
+            (status, signature) => {
+                anyhow::bail!("Invalid scan status {status:?} with signature {signature:?}")
+            },
+        }
+    }
 }
 
 impl TryFrom<FileStorageEntry> for ConvexObject {
@@ -37,10 +112,14 @@ impl TryFrom<FileStorageEntry> for ConvexObject {
             sha256,
             size,
             content_type,
+            tags,
+            metadata,
+            storage_class,
+            scan_status,
         }: FileStorageEntry,
     ) -> Result<Self, Self::Error> {
         let storage_key: String = storage_key.into();
-        obj!(
+        let object = obj!(
             "storageId" => storage_id.to_string(),
             "storageKey" => storage_key,
             "sha256" => sha256,
@@ -49,7 +128,26 @@ impl TryFrom<FileStorageEntry> for ConvexObject {
                 None => ConvexValue::Null,
                 Some(ct) => ct.try_into()?,
             },
-        )
+        )?;
+        // Tags and metadata are left out when they're empty, so entries
+        // stored before they existed are unchanged.
+        let mut fields: BTreeMap<_, _> = object.into();
+        if !tags.is_empty() {
+            fields.insert("tags".parse()?, tags_to_value(tags)?);
+        }
+        if !metadata.is_empty() {
+            fields.insert("metadata".parse()?, metadata_to_value(metadata)?);
+        }
+        if let Some(storage_class) = storage_class {
+            fields.insert("storageClass".parse()?, storage_class.try_into()?);
+        }
+        if let Some(scan_status) = scan_status {
+            fields.insert("scanStatus".parse()?, scan_status.as_str().try_into()?);
+            if let Some(signature) = scan_status.signature() {
+                fields.insert("scanSignature".parse()?, signature.try_into()?);
+            }
+        }
+        fields.try_into()
     }
 }
 
@@ -79,12 +177,55 @@ impl TryFrom<ConvexObject> for FileStorageEntry {
             Some(ConvexValue::String(ct)) => Some(String::from(ct)),
             _ => anyhow::bail!("Invalid 'content_type' in {object_fields:?}"),
         };
+        let tags = match object_fields.remove("tags") {
+            None => BTreeSet::new(),
+            Some(ConvexValue::Array(tags)) => tags
+                .into_iter()
+                .map(|tag| match tag {
+                    ConvexValue::String(tag) => Ok(String::from(tag)),
+                    _ => anyhow::bail!("Invalid tag {tag:?}"),
+                })
+                .collect::<anyhow::Result<_>>()?,
+            _ => anyhow::bail!("Invalid 'tags' in {object_fields:?}"),
+        };
+        let metadata = match object_fields.remove("metadata") {
+            None => BTreeMap::new(),
+            Some(ConvexValue::Object(metadata)) => BTreeMap::from(metadata)
+                .into_iter()
+                .map(|(key, value)| match value {
+                    ConvexValue::String(value) => Ok((String::from(key), String::from(value))),
+                    _ => anyhow::bail!("Invalid metadata value {value:?}"),
+                })
+                .collect::<anyhow::Result<_>>()?,
+            _ => anyhow::bail!("Invalid 'metadata' in {object_fields:?}"),
+        };
+        let storage_class = match object_fields.remove("storageClass") {
+            None => None,
+            Some(ConvexValue::String(storage_class)) => Some(String::from(storage_class)),
+            _ => anyhow::bail!("Invalid 'storageClass' in {object_fields:?}"),
+        };
+        let scan_signature = match object_fields.remove("scanSignature") {
+            None => None,
+            Some(ConvexValue::String(signature)) => Some(String::from(signature)),
+            _ => anyhow::bail!("Invalid 'scanSignature' in {object_fields:?}"),
+        };
+        let scan_status = match object_fields.remove("scanStatus") {
+            None => None,
+            Some(ConvexValue::String(status)) => {
+                Some(FileScanStatus::from_parts(&status, scan_signature)?)
+            },
+            _ => anyhow::bail!("Invalid 'scanStatus' in {object_fields:?}"),
+        };
         Ok(Self {
             storage_id,
             storage_key,
             sha256,
             size,
             content_type,
+            tags,
+            metadata,
+            storage_class,
+            scan_status,
         })
     }
 }
@@ -103,12 +244,20 @@ impl TryFrom<FileStorageEntryProto> for FileStorageEntry {
             .try_into()?;
         let sha256 = entry.sha256.context("Missing `sha256` field")?.try_into()?;
         let size = entry.size.context("Missing `size` field")?;
+        let scan_status = entry
+            .scan_status
+            .map(|status| FileScanStatus::from_parts(&status, entry.scan_signature))
+            .transpose()?;
         Ok(FileStorageEntry {
             storage_id,
             storage_key,
             sha256,
             size,
             content_type: entry.content_type,
+            tags: entry.tags.into_iter().collect(),
+            metadata: entry.metadata.into_iter().collect(),
+            storage_class: entry.storage_class,
+            scan_status,
         })
     }
 }
@@ -121,7 +270,145 @@ impl From<FileStorageEntry> for FileStorageEntryProto {
             sha256: Some(entry.sha256.to_vec()),
             size: Some(entry.size),
             content_type: entry.content_type,
+            tags: entry.tags.into_iter().collect(),
+            metadata: entry.metadata.into_iter().collect(),
+            storage_class: entry.storage_class,
+            scan_status: entry
+                .scan_status
+                .as_ref()
+                .map(|status| status.as_str().to_string()),
+            scan_signature: entry
+                .scan_status
+                .and_then(|status| status.signature().map(str::to_string)),
+        }
+    }
+}
+
+pub fn tags_to_value(tags: BTreeSet<String>) -> anyhow::Result<ConvexValue> {
+    Ok(ConvexValue::Array(ConvexArray::try_from(
+        tags.into_iter()
+            .map(ConvexValue::try_from)
+            .collect::<anyhow::Result<Vec<_>>>()?,
+    )?))
+}
+
+pub fn metadata_to_value(metadata: BTreeMap<String, String>) -> anyhow::Result<ConvexValue> {
+    let fields = metadata
+        .into_iter()
+        .map(|(key, value)| anyhow::Ok((key.parse()?, ConvexValue::try_from(value)?)))
+        .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+    Ok(ConvexValue::Object(fields.try_into()?))
+}
+
+/// Check the tags and metadata an app sets on a file.
+pub fn validate_tags_and_metadata(
+    tags: &BTreeSet<String>,
+    metadata: &BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    let invalid = |msg: String| ErrorMetadata::bad_request("InvalidFileMetadata", msg);
+    if tags.len() > MAX_FILE_TAGS {
+        anyhow::bail!(invalid(format!(
+            "Files can have at most {MAX_FILE_TAGS} tags, not {}",
+            tags.len()
+        )));
+    }
+    if let Some(tag) = tags
+        .iter()
+        .find(|tag| tag.is_empty() || tag.len() > MAX_FILE_TAG_LENGTH)
+    {
+        anyhow::bail!(invalid(format!(
+            "Tags must be between 1 and {MAX_FILE_TAG_LENGTH} bytes long, but {tag:?} isn't"
+        )));
+    }
+    if metadata.len() > MAX_FILE_METADATA_ENTRIES {
+        anyhow::bail!(invalid(format!(
+            "Files can have at most {MAX_FILE_METADATA_ENTRIES} metadata entries, not {}",
+            metadata.len()
+        )));
+    }
+    for (key, value) in metadata {
+        if key.parse::<IdentifierFieldName>().is_err() {
+            anyhow::bail!(invalid(format!(
+                "Metadata keys must be valid identifiers, but {key:?} isn't"
+            )));
+        }
+        if value.len() > MAX_FILE_METADATA_VALUE_LENGTH {
+            anyhow::bail!(invalid(format!(
+                "Metadata values can be at most {MAX_FILE_METADATA_VALUE_LENGTH} bytes long, but \
+                 {key:?}'s is {} bytes",
+                value.len()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Selects stored files for the file listing API. Every condition that is set
+/// must hold for a file to match.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileStorageFilter {
+    /// Either a full content type like "image/png", or a type followed by a
+    /// slash like "image/" to match all of its subtypes.
+    pub content_type: Option<String>,
+    /// Bounds on the file's size in bytes, inclusive.
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    pub tag: Option<String>,
+    /// Bounds on when the file was uploaded, inclusive of the start and
+    /// exclusive of the end.
+    pub uploaded_after: Option<CreationTime>,
+    pub uploaded_before: Option<CreationTime>,
+}
+
+impl FileStorageFilter {
+    pub fn matches(&self, file: &ParsedDocument<FileStorageEntry>) -> bool {
+        if let Some(ref content_type) = self.content_type {
+            let matches = match &file.content_type {
+                None => false,
+                Some(file_content_type) if content_type.ends_with('/') => {
+                    file_content_type.starts_with(&content_type[..])
+                },
+                // Ignore parameters like "; charset=utf-8".
+                Some(file_content_type) => {
+                    file_content_type
+                        .split(';')
+                        .next()
+                        .unwrap_or_default()
+                        .trim()
+                        == content_type
+                },
+            };
+            if !matches {
+                return false;
+            }
+        }
+        if let Some(min_size) = self.min_size
+            && file.size < min_size
+        {
+            return false;
+        }
+        if let Some(max_size) = self.max_size
+            && file.size > max_size
+        {
+            return false;
+        }
+        if let Some(ref tag) = self.tag
+            && !file.tags.contains(tag)
+        {
+            return false;
+        }
+        let creation_time = file.creation_time();
+        if let Some(uploaded_after) = self.uploaded_after
+            && creation_time.is_some_and(|t| t < uploaded_after)
+        {
+            return false;
+        }
+        if let Some(uploaded_before) = self.uploaded_before
+            && creation_time.is_some_and(|t| t >= uploaded_before)
+        {
+            return false;
         }
+        true
     }
 }
 
@@ -1,5 +1,8 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     sync::LazyLock,
 };
 
@@ -25,7 +28,12 @@ use value::{
 };
 
 use super::{
-    types::FileStorageEntry,
+    types::{
+        metadata_to_value,
+        tags_to_value,
+        FileScanStatus,
+        FileStorageEntry,
+    },
     FILE_STORAGE_TABLE,
 };
 
@@ -67,6 +75,9 @@ impl VirtualSystemDocMapper for FileStorageDocMapper {
             sha256,
             size: metadata.size as f64,
             content_type: metadata.content_type,
+            tags: metadata.tags,
+            metadata: metadata.metadata,
+            scan_status: metadata.scan_status,
         };
         let mut public_metadata_resolved: ConvexObject = public_metadata.try_into()?;
 
@@ -102,6 +113,16 @@ struct PublicFileMetadata {
     sha256: String,               // Hex-encoded Sha256 of contents
     size: f64,                    // Size of file in storage
     content_type: Option<String>, // Optional ContentType header saved with file
+    tags: BTreeSet<String>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::btree_map(\"[a-zA-Z][a-zA-Z0-9_]{0,8}\", \
+                        proptest::prelude::any::<String>(), 0..4)"
+        )
+    )]
+    metadata: BTreeMap<String, String>,
+    scan_status: Option<FileScanStatus>,
 }
 
 impl TryFrom<PublicFileMetadata> for ConvexObject {
@@ -112,6 +133,9 @@ impl TryFrom<PublicFileMetadata> for ConvexObject {
             sha256,
             size,
             content_type,
+            tags,
+            metadata,
+            scan_status,
         }: PublicFileMetadata,
     ) -> Result<Self, Self::Error> {
         let mut obj: BTreeMap<FieldName, ConvexValue> = BTreeMap::new();
@@ -124,6 +148,20 @@ impl TryFrom<PublicFileMetadata> for ConvexObject {
                 Some(ct) => val!(ct),
             },
         );
+        // Like in the system table, tags and metadata are left out when empty.
+        if !tags.is_empty() {
+            obj.insert("tags".parse()?, tags_to_value(tags)?);
+        }
+        if !metadata.is_empty() {
+            obj.insert("metadata".parse()?, metadata_to_value(metadata)?);
+        }
+        // Files are only scanned if a file scanner is configured.
+        if let Some(scan_status) = scan_status {
+            obj.insert("scanStatus".parse()?, scan_status.as_str().try_into()?);
+            if let Some(signature) = scan_status.signature() {
+                obj.insert("scanSignature".parse()?, signature.try_into()?);
+            }
+        }
         ConvexObject::try_from(obj)
     }
 }
@@ -0,0 +1,389 @@
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        Timestamp,
+    },
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    sha256::Sha256Digest,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    FileUploadPart,
+    FileUploadSession,
+    FileUploadSessionState,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Resumable uploads to file storage.
+pub static FILE_UPLOAD_SESSIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_file_upload_sessions"
+        .parse()
+        .expect("Invalid built-in file upload sessions table")
+});
+
+/// The parts uploaded to each resumable upload. They're kept apart from their
+/// session so that parts can be uploaded concurrently.
+pub static FILE_UPLOAD_PARTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_file_upload_parts"
+        .parse()
+        .expect("Invalid built-in file upload parts table")
+});
+
+static UPLOAD_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "uploadId".parse().expect("Invalid built-in field"));
+
+static EXPIRES_TS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "expiresTs".parse().expect("Invalid built-in field"));
+
+static PART_NUMBER_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "partNumber".parse().expect("Invalid built-in field"));
+
+pub static FILE_UPLOAD_SESSIONS_INDEX_BY_UPLOAD_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_UPLOAD_SESSIONS_TABLE, "by_upload_id"));
+pub static FILE_UPLOAD_SESSIONS_INDEX_BY_EXPIRES_TS: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_UPLOAD_SESSIONS_TABLE, "by_expires_ts"));
+pub static FILE_UPLOAD_PARTS_INDEX_BY_UPLOAD_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FILE_UPLOAD_PARTS_TABLE, "by_upload_id_and_part_number"));
+
+pub struct FileUploadSessionsTable;
+impl SystemTable for FileUploadSessionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FILE_UPLOAD_SESSIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: FILE_UPLOAD_SESSIONS_INDEX_BY_UPLOAD_ID.clone(),
+                fields: vec![UPLOAD_ID_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: FILE_UPLOAD_SESSIONS_INDEX_BY_EXPIRES_TS.clone(),
+                fields: vec![EXPIRES_TS_FIELD.clone()].try_into().unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FileUploadSession>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FileUploadPartsTable;
+impl SystemTable for FileUploadPartsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FILE_UPLOAD_PARTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: FILE_UPLOAD_PARTS_INDEX_BY_UPLOAD_ID.clone(),
+            fields: vec![UPLOAD_ID_FIELD.clone(), PART_NUMBER_FIELD.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FileUploadPart>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FileUploadSessionsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FileUploadSessionsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_system(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    /// Start a session for the storage upload `upload_token` that parts can
+    /// be uploaded to for `validity`. Returns the session's upload id.
+    pub async fn create(
+        &mut self,
+        upload_token: String,
+        content_type: Option<String>,
+        sha256: Option<Sha256Digest>,
+        max_bytes: Option<u64>,
+        validity: Duration,
+    ) -> anyhow::Result<String> {
+        self.check_system("create_file_upload_session")?;
+        let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+        let upload_id = self.tx.runtime().new_uuid_v4().to_string();
+        let session = FileUploadSession {
+            upload_id: upload_id.clone(),
+            upload_token,
+            content_type,
+            sha256,
+            max_bytes,
+            expires_ts: now.add(validity)?,
+            state: FileUploadSessionState::Uploading,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&FILE_UPLOAD_SESSIONS_TABLE, session.try_into()?)
+            .await?;
+        Ok(upload_id)
+    }
+
+    pub async fn get(
+        &mut self,
+        upload_id: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<FileUploadSession>>> {
+        self.check_system("get_file_upload_session")?;
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_UPLOAD_SESSIONS_INDEX_BY_UPLOAD_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                UPLOAD_ID_FIELD.clone(),
+                ConvexValue::try_from(upload_id.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()
+    }
+
+    /// The parts uploaded to a session, ordered by part number.
+    pub async fn parts(
+        &mut self,
+        upload_id: &str,
+    ) -> anyhow::Result<Vec<ParsedDocument<FileUploadPart>>> {
+        self.check_system("list_file_upload_parts")?;
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_UPLOAD_PARTS_INDEX_BY_UPLOAD_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                UPLOAD_ID_FIELD.clone(),
+                ConvexValue::try_from(upload_id.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut parts = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            parts.push(doc.try_into()?);
+        }
+        Ok(parts)
+    }
+
+    /// Record an uploaded part, replacing a previous upload of the same part.
+    pub async fn put_part(&mut self, part: FileUploadPart) -> anyhow::Result<()> {
+        self.check_system("put_file_upload_part")?;
+        // Only read this part, so that uploads of other parts don't conflict.
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_UPLOAD_PARTS_INDEX_BY_UPLOAD_ID.clone(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    UPLOAD_ID_FIELD.clone(),
+                    ConvexValue::try_from(part.upload_id.clone())?.into(),
+                ),
+                IndexRangeExpression::Eq(
+                    PART_NUMBER_FIELD.clone(),
+                    ConvexValue::from(i64::from(part.part_number)).into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let existing = query_stream.expect_at_most_one(self.tx).await?;
+        match existing {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), part.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FILE_UPLOAD_PARTS_TABLE, part.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Mark a session as assembled into the file `storage_id`. Its parts are
+    /// no longer needed.
+    pub async fn complete(&mut self, upload_id: &str, storage_id: String) -> anyhow::Result<()> {
+        self.check_system("complete_file_upload_session")?;
+        let Some(session) = self.get(upload_id).await? else {
+            anyhow::bail!("File upload session {upload_id} not found");
+        };
+        let (id, mut session) = session.into_id_and_value();
+        session.state = FileUploadSessionState::Completed { storage_id };
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, session.try_into()?)
+            .await?;
+        self.delete_parts(upload_id).await
+    }
+
+    /// Delete up to `limit` sessions that expired before `now`, along with
+    /// their parts. Returns the number of deleted sessions.
+    pub async fn delete_expired(&mut self, now: Timestamp, limit: usize) -> anyhow::Result<usize> {
+        self.check_system("delete_expired_file_upload_sessions")?;
+        let query = Query::index_range(IndexRange {
+            index_name: FILE_UPLOAD_SESSIONS_INDEX_BY_EXPIRES_TS.clone(),
+            range: vec![IndexRangeExpression::Lt(
+                EXPIRES_TS_FIELD.clone(),
+                ConvexValue::from(i64::from(now)).into(),
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut expired = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            let session: ParsedDocument<FileUploadSession> = doc.try_into()?;
+            expired.push(session);
+        }
+        for session in &expired {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(session.id())
+                .await?;
+            self.delete_parts(&session.upload_id).await?;
+        }
+        Ok(expired.len())
+    }
+
+    async fn delete_parts(&mut self, upload_id: &str) -> anyhow::Result<()> {
+        for part in self.parts(upload_id).await? {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(part.id())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::types::Timestamp;
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        file_upload_sessions::{
+            types::{
+                FileUploadPart,
+                FileUploadSessionState,
+            },
+            FileUploadSessionsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn part(upload_id: &str, part_number: u16, size: u64) -> FileUploadPart {
+        FileUploadPart {
+            upload_id: upload_id.to_string(),
+            part_number,
+            part_token: format!("token-{part_number}-{size}"),
+            size,
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_file_upload_sessions(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FileUploadSessionsModel::new(&mut tx);
+        let upload_id = model
+            .create(
+                "upload-token".to_string(),
+                Some("video/mp4".to_string()),
+                None,
+                None,
+                Duration::from_secs(60 * 60),
+            )
+            .await?;
+        let other_upload_id = model
+            .create(
+                "other-upload-token".to_string(),
+                None,
+                None,
+                None,
+                Duration::from_secs(60 * 60),
+            )
+            .await?;
+        model.put_part(part(&upload_id, 2, 3)).await?;
+        model.put_part(part(&upload_id, 1, 5)).await?;
+        // Retrying a part replaces it.
+        model.put_part(part(&upload_id, 2, 4)).await?;
+        model.put_part(part(&other_upload_id, 1, 7)).await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = FileUploadSessionsModel::new(&mut tx);
+        let session = model.get(&upload_id).await?.unwrap();
+        assert_eq!(session.upload_token, "upload-token");
+        assert_eq!(session.content_type.as_deref(), Some("video/mp4"));
+        assert_eq!(session.state, FileUploadSessionState::Uploading);
+        let parts: Vec<_> = model
+            .parts(&upload_id)
+            .await?
+            .into_iter()
+            .map(|part| part.into_value())
+            .collect();
+        assert_eq!(parts, vec![part(&upload_id, 1, 5), part(&upload_id, 2, 4)]);
+
+        model.complete(&upload_id, "storage-id".to_string()).await?;
+        assert_eq!(
+            model.get(&upload_id).await?.unwrap().state,
+            FileUploadSessionState::Completed {
+                storage_id: "storage-id".to_string()
+            }
+        );
+        assert!(model.parts(&upload_id).await?.is_empty());
+        assert_eq!(model.parts(&other_upload_id).await?.len(), 1);
+
+        assert_eq!(model.delete_expired(Timestamp::MIN, 10).await?, 0);
+        assert_eq!(model.delete_expired(Timestamp::MAX, 10).await?, 2);
+        assert!(model.get(&upload_id).await?.is_none());
+        assert!(model.parts(&other_upload_id).await?.is_empty());
+        Ok(())
+    }
+}
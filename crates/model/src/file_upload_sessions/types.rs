@@ -0,0 +1,180 @@
+use common::types::Timestamp;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    sha256::Sha256Digest,
+};
+
+/// How far a resumable upload session has gotten.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum FileUploadSessionState {
+    // Parts are being uploaded.
+    Uploading,
+    // The parts were assembled into a file. Kept until the session expires so
+    // that finishing again returns the same storage id.
+    Completed { storage_id: String },
+}
+
+/// A file being uploaded in parts, which can be uploaded and retried
+/// independently so that a dropped connection only loses the part in flight.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FileUploadSession {
+    // Random id the client refers to the session by. Knowing it is what
+    // authorizes uploading to the session, so it's only returned to the client
+    // that created the session.
+    pub upload_id: String,
+    // The storage's token for the multipart upload. It may reference storage
+    // internals, so it's never returned to clients.
+    pub upload_token: String,
+    pub content_type: Option<String>,
+    // Checked against the assembled file when the session is finished.
+    pub sha256: Option<Sha256Digest>,
+    // Limit on the size of the file, from the constraints of the upload URL
+    // the session was created with.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub max_bytes: Option<u64>,
+    // Parts can't be uploaded after this.
+    pub expires_ts: Timestamp,
+    pub state: FileUploadSessionState,
+}
+
+/// A part uploaded to a session. Uploading the same part again replaces it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FileUploadPart {
+    pub upload_id: String,
+    pub part_number: u16,
+    // The storage's token for the part, needed to finish the upload.
+    pub part_token: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedFileUploadSessionState {
+    Uploading,
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        storage_id: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFileUploadSession {
+    upload_id: String,
+    upload_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_bytes: Option<i64>,
+    expires_ts: i64,
+    state: SerializedFileUploadSessionState,
+}
+
+impl TryFrom<FileUploadSession> for SerializedFileUploadSession {
+    type Error = anyhow::Error;
+
+    fn try_from(session: FileUploadSession) -> anyhow::Result<Self> {
+        Ok(Self {
+            upload_id: session.upload_id,
+            upload_token: session.upload_token,
+            content_type: session.content_type,
+            sha256: session.sha256.map(|sha256| sha256.as_base64()),
+            max_bytes: session.max_bytes.map(i64::try_from).transpose()?,
+            expires_ts: session.expires_ts.into(),
+            state: match session.state {
+                FileUploadSessionState::Uploading => SerializedFileUploadSessionState::Uploading,
+                FileUploadSessionState::Completed { storage_id } => {
+                    SerializedFileUploadSessionState::Completed { storage_id }
+                },
+            },
+        })
+    }
+}
+
+impl TryFrom<SerializedFileUploadSession> for FileUploadSession {
+    type Error = anyhow::Error;
+
+    fn try_from(session: SerializedFileUploadSession) -> anyhow::Result<Self> {
+        Ok(Self {
+            upload_id: session.upload_id,
+            upload_token: session.upload_token,
+            content_type: session.content_type,
+            sha256: session
+                .sha256
+                .map(|sha256| Sha256Digest::from_base64(&sha256))
+                .transpose()?,
+            max_bytes: session.max_bytes.map(u64::try_from).transpose()?,
+            expires_ts: session.expires_ts.try_into()?,
+            state: match session.state {
+                SerializedFileUploadSessionState::Uploading => FileUploadSessionState::Uploading,
+                SerializedFileUploadSessionState::Completed { storage_id } => {
+                    FileUploadSessionState::Completed { storage_id }
+                },
+            },
+        })
+    }
+}
+
+codegen_convex_serialization!(FileUploadSession, SerializedFileUploadSession);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFileUploadPart {
+    upload_id: String,
+    part_number: i64,
+    part_token: String,
+    size: i64,
+}
+
+impl TryFrom<FileUploadPart> for SerializedFileUploadPart {
+    type Error = anyhow::Error;
+
+    fn try_from(part: FileUploadPart) -> anyhow::Result<Self> {
+        Ok(Self {
+            upload_id: part.upload_id,
+            part_number: part.part_number.into(),
+            part_token: part.part_token,
+            size: part.size.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedFileUploadPart> for FileUploadPart {
+    type Error = anyhow::Error;
+
+    fn try_from(part: SerializedFileUploadPart) -> anyhow::Result<Self> {
+        Ok(Self {
+            upload_id: part.upload_id,
+            part_number: part.part_number.try_into()?,
+            part_token: part.part_token,
+            size: part.size.try_into()?,
+        })
+    }
+}
+
+mod file_upload_part_serde {
+    use value::codegen_convex_serialization;
+
+    use super::{
+        FileUploadPart,
+        SerializedFileUploadPart,
+    };
+
+    codegen_convex_serialization!(FileUploadPart, SerializedFileUploadPart);
+}
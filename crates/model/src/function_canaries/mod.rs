@@ -0,0 +1,288 @@
+use std::sync::LazyLock;
+
+use common::{
+    components::CanonicalizedComponentFunctionPath,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::FunctionCanary;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Functions with a canary version running alongside the active one, routed
+/// by the function router.
+pub static FUNCTION_CANARIES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_canaries"
+        .parse()
+        .expect("Invalid built-in function canaries table")
+});
+
+pub struct FunctionCanariesTable;
+impl SystemTable for FunctionCanariesTable {
+    fn table_name(&self) -> &'static TableName {
+        &FUNCTION_CANARIES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FunctionCanary>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FunctionCanariesModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FunctionCanariesModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<FunctionCanary>>> {
+        self.check_admin("list_function_canaries")?;
+        let query = Query::full_table_scan(FUNCTION_CANARIES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut canaries = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            canaries.push(doc.try_into()?);
+        }
+        Ok(canaries)
+    }
+
+    pub async fn get(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<Option<ParsedDocument<FunctionCanary>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|canary| canary.path == *path))
+    }
+
+    /// Set the canary for a function, replacing any existing canary for it.
+    pub async fn set(&mut self, canary: FunctionCanary) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("set_function_canary")?;
+        canary.validate()?;
+        match self.get(&canary.path).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), canary.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FUNCTION_CANARIES_TABLE, canary.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Change the percentage of users a function's canary is rolled out to.
+    pub async fn set_rollout(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+        rollout_percentage: u8,
+    ) -> anyhow::Result<()> {
+        let Some(existing) = self.get(path).await? else {
+            anyhow::bail!(not_found(path));
+        };
+        self.set(FunctionCanary {
+            rollout_percentage,
+            ..existing.into_value()
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Remove a function's canary, so every call runs the active version.
+    pub async fn delete(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<()> {
+        self.check_admin("delete_function_canary")?;
+        let Some(existing) = self.get(path).await? else {
+            anyhow::bail!(not_found(path));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+
+    /// Remove every canary. Canaries load the active version's module
+    /// metadata, so they end whenever different code is deployed.
+    pub async fn delete_all(&mut self) -> anyhow::Result<()> {
+        for canary in self.list().await? {
+            SystemMetadataModel::new_global(self.tx)
+                .delete(canary.id())
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+fn not_found(path: &CanonicalizedComponentFunctionPath) -> ErrorMetadata {
+    ErrorMetadata::not_found(
+        "FunctionCanaryNotFound",
+        format!(
+            "There is no canary for function {:?}",
+            String::from(path.udf_path.clone())
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use common::components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    };
+    use database::test_helpers::DbFixtures;
+    use keybroker::{
+        testing::TestUserIdentity,
+        Identity,
+        UserIdentity,
+    };
+    use proptest::prelude::*;
+    use runtime::testing::TestRuntime;
+    use sync_types::UserIdentifier;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+        DeveloperDocumentId,
+        InternalId,
+    };
+
+    use crate::{
+        function_canaries::{
+            types::FunctionCanary,
+            FunctionCanariesModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn canary(udf_path: &str, rollout_percentage: u8) -> anyhow::Result<FunctionCanary> {
+        Ok(FunctionCanary {
+            path: CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: udf_path.parse()?,
+            },
+            source_package_id: DeveloperDocumentId::new(1.try_into()?, InternalId::MIN).into(),
+            rollout_percentage,
+        })
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_function_canary_roundtrips(v in any::<FunctionCanary>()) {
+            assert_roundtrips::<FunctionCanary, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_routes_to_canary() -> anyhow::Result<()> {
+        assert!(canary("_system/cli/tables.js", 10)?.validate().is_err());
+        assert!(canary("messages.js:send", 101)?.validate().is_err());
+
+        let user = UserIdentity::test();
+        let users: Vec<Identity> = (0..200)
+            .map(|i| {
+                let mut user = user.clone();
+                user.attributes.token_identifier = UserIdentifier(format!("issuer|user{i}"));
+                Identity::user(user)
+            })
+            .collect();
+        let none = canary("messages.js:send", 0)?;
+        assert!(users.iter().all(|user| !none.routes_to_canary(user)));
+        let all = canary("messages.js:send", 100)?;
+        assert!(users.iter().all(|user| all.routes_to_canary(user)));
+        assert!(all.routes_to_canary(&Identity::system()));
+
+        // Raising the percentage only moves more users to the canary.
+        let half = canary("messages.js:send", 50)?;
+        let routed: Vec<_> = users
+            .iter()
+            .map(|user| half.routes_to_canary(user))
+            .collect();
+        let num_routed = routed.iter().filter(|routed| **routed).count();
+        assert!((50..150).contains(&num_routed), "{num_routed}");
+        let most = canary("messages.js:send", 90)?;
+        for (user, routed) in users.iter().zip(routed) {
+            assert!(!routed || most.routes_to_canary(user));
+        }
+        assert!(!half.routes_to_canary(&Identity::Unknown));
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_function_canaries(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionCanariesModel::new(&mut tx);
+        let send = canary("messages.js:send", 10)?;
+        let id = model.set(send.clone()).await?;
+        model.set(canary("messages.js:list", 10)?).await?;
+
+        model.set_rollout(&send.path, 50).await?;
+        assert_eq!(
+            model.get(&send.path).await?.unwrap().into_value(),
+            FunctionCanary {
+                rollout_percentage: 50,
+                ..send.clone()
+            }
+        );
+        assert_eq!(model.set(send.clone()).await?, id);
+
+        model.delete(&send.path).await?;
+        assert!(model.delete(&send.path).await.is_err());
+        assert!(model.set_rollout(&send.path, 50).await.is_err());
+        assert_eq!(model.list().await?.len(), 1);
+        model.delete_all().await?;
+        assert!(model.list().await?.is_empty());
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::Unknown).await?;
+        assert!(FunctionCanariesModel::new(&mut tx).list().await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use common::components::{
+    CanonicalizedComponentFunctionPath,
+    ComponentPath,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::{
+    codegen_convex_serialization,
+    DeveloperDocumentId,
+};
+
+use crate::{
+    function_flags::types::rollout_bucket,
+    source_packages::types::SourcePackageId,
+};
+
+/// A new version of a function's code that runs alongside the active one for
+/// some of its callers. The version is a source package of the function's
+/// component that was uploaded but never pushed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionCanary {
+    pub path: CanonicalizedComponentFunctionPath,
+    pub source_package_id: SourcePackageId,
+    /// The percentage of users whose calls run the canary.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..=100u8"))]
+    pub rollout_percentage: u8,
+}
+
+impl FunctionCanary {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid =
+            |msg: String| anyhow::anyhow!(ErrorMetadata::bad_request("InvalidFunctionCanary", msg));
+        if self.path.udf_path.is_system() {
+            return Err(invalid(format!(
+                "Cannot canary system function {:?}",
+                String::from(self.path.udf_path.clone())
+            )));
+        }
+        if self.rollout_percentage > 100 {
+            return Err(invalid(
+                "rolloutPercentage must be between 0 and 100".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether a call by `identity` runs the canary. Each user is bucketed by
+    /// a hash of their identity and the canary's version, so they
+    /// consistently run the same version until the percentage changes.
+    /// Callers without a user identity only run a canary that's rolled out
+    /// to everyone.
+    pub fn routes_to_canary(&self, identity: &Identity) -> bool {
+        let token_identifier = match identity {
+            Identity::User(user) => &user.attributes.token_identifier.0,
+            Identity::ActingUser(_, attributes) => &attributes.token_identifier.0,
+            Identity::System(_)
+            | Identity::InstanceAdmin(_)
+            | Identity::ApiKey(_)
+            | Identity::Unknown => return self.rollout_percentage >= 100,
+        };
+        let version = DeveloperDocumentId::from(self.source_package_id).encode();
+        rollout_bucket(&self.path, &version, token_identifier) < self.rollout_percentage
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFunctionCanary {
+    component_path: String,
+    udf_path: String,
+    source_package_id: String,
+    rollout_percentage: i64,
+}
+
+impl TryFrom<FunctionCanary> for SerializedFunctionCanary {
+    type Error = anyhow::Error;
+
+    fn try_from(canary: FunctionCanary) -> anyhow::Result<Self> {
+        Ok(Self {
+            component_path: canary.path.component.into(),
+            udf_path: canary.path.udf_path.into(),
+            source_package_id: DeveloperDocumentId::from(canary.source_package_id).encode(),
+            rollout_percentage: canary.rollout_percentage.into(),
+        })
+    }
+}
+
+impl TryFrom<SerializedFunctionCanary> for FunctionCanary {
+    type Error = anyhow::Error;
+
+    fn try_from(canary: SerializedFunctionCanary) -> anyhow::Result<Self> {
+        let component: ComponentPath = canary.component_path.parse()?;
+        Ok(Self {
+            path: CanonicalizedComponentFunctionPath {
+                component,
+                udf_path: canary.udf_path.parse()?,
+            },
+            source_package_id: DeveloperDocumentId::decode(&canary.source_package_id)?.into(),
+            rollout_percentage: canary.rollout_percentage.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionCanary, SerializedFunctionCanary);
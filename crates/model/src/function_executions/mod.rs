@@ -0,0 +1,379 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    FunctionExecutionFilter,
+    FunctionExecutionRecord,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Table of completed function executions, which outlives the in-memory
+/// function log so that history survives restarts.
+pub static FUNCTION_EXECUTIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_executions"
+        .parse()
+        .expect("Invalid built-in function executions table")
+});
+
+static TIMESTAMP_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "timestamp".parse().expect("Invalid built-in field"));
+
+static FUNCTION_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "function".parse().expect("Invalid built-in field"));
+
+pub static FUNCTION_EXECUTIONS_INDEX_BY_TIMESTAMP: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FUNCTION_EXECUTIONS_TABLE, "by_timestamp"));
+
+pub static FUNCTION_EXECUTIONS_INDEX_BY_FUNCTION: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&FUNCTION_EXECUTIONS_TABLE, "by_function_and_timestamp"));
+
+pub struct FunctionExecutionsTable;
+impl SystemTable for FunctionExecutionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FUNCTION_EXECUTIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: FUNCTION_EXECUTIONS_INDEX_BY_TIMESTAMP.clone(),
+                fields: vec![TIMESTAMP_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: FUNCTION_EXECUTIONS_INDEX_BY_FUNCTION.clone(),
+                fields: vec![FUNCTION_FIELD.clone(), TIMESTAMP_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FunctionExecutionRecord>::try_from(document).map(|_| ())
+    }
+}
+
+fn timestamp_value(timestamp: UnixTimestamp) -> anyhow::Result<ConvexValue> {
+    Ok(ConvexValue::Int64(timestamp.as_nanos().try_into()?))
+}
+
+pub struct FunctionExecutionsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FunctionExecutionsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, record: FunctionExecutionRecord) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("insert_function_execution"));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert_metadata(&FUNCTION_EXECUTIONS_TABLE, record.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// The executions matching `filter`, newest first.
+    pub async fn query(
+        &mut self,
+        filter: FunctionExecutionFilter,
+    ) -> anyhow::Result<Vec<ParsedDocument<FunctionExecutionRecord>>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("query_function_executions"));
+        }
+        let mut range = vec![];
+        let index_name = match &filter.function {
+            Some(function) => {
+                range.push(IndexRangeExpression::Eq(
+                    FUNCTION_FIELD.clone(),
+                    ConvexValue::try_from(function.clone())?.into(),
+                ));
+                FUNCTION_EXECUTIONS_INDEX_BY_FUNCTION.clone()
+            },
+            None => FUNCTION_EXECUTIONS_INDEX_BY_TIMESTAMP.clone(),
+        };
+        if let Some(start) = filter.start {
+            range.push(IndexRangeExpression::Gte(
+                TIMESTAMP_FIELD.clone(),
+                timestamp_value(start)?,
+            ));
+        }
+        if let Some(end) = filter.end {
+            range.push(IndexRangeExpression::Lt(
+                TIMESTAMP_FIELD.clone(),
+                timestamp_value(end)?,
+            ));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name,
+            range,
+            order: Order::Desc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut executions = vec![];
+        while executions.len() < filter.limit
+            && let Some(doc) = query_stream.next(self.tx, None).await?
+        {
+            let execution: ParsedDocument<FunctionExecutionRecord> = doc.try_into()?;
+            if filter
+                .status
+                .is_some_and(|status| execution.status != status)
+            {
+                continue;
+            }
+            if filter
+                .min_execution_time
+                .is_some_and(|min| execution.execution_time < min)
+            {
+                continue;
+            }
+            executions.push(execution);
+        }
+        Ok(executions)
+    }
+
+    /// Up to `limit` executions that happened before `cutoff`, oldest first.
+    pub async fn expired(
+        &mut self,
+        cutoff: UnixTimestamp,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        let query = Query::index_range(IndexRange {
+            index_name: FUNCTION_EXECUTIONS_INDEX_BY_TIMESTAMP.clone(),
+            range: vec![IndexRangeExpression::Lt(
+                TIMESTAMP_FIELD.clone(),
+                timestamp_value(cutoff)?,
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut ids = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            ids.push(doc.id());
+        }
+        Ok(ids)
+    }
+
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("delete_function_execution"));
+        }
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::{
+        runtime::UnixTimestamp,
+        types::UdfType,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        function_executions::{
+            types::{
+                FunctionExecutionFilter,
+                FunctionExecutionRecord,
+                FunctionExecutionStatus,
+                FunctionExecutionUsage,
+            },
+            FunctionExecutionsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn record(
+        function: &str,
+        timestamp_ms: u64,
+        status: FunctionExecutionStatus,
+        execution_time_ms: u64,
+    ) -> FunctionExecutionRecord {
+        FunctionExecutionRecord {
+            timestamp: UnixTimestamp::from_millis(timestamp_ms),
+            function: function.to_string(),
+            udf_type: UdfType::Mutation,
+            status,
+            error: (status == FunctionExecutionStatus::Failure).then(|| "boom".to_string()),
+            error_code: None,
+            error_data: None,
+            execution_time: Duration::from_millis(execution_time_ms),
+            cached: false,
+            request_id: "a1b2c3".to_string(),
+            api_key: None,
+            usage: FunctionExecutionUsage::default(),
+            log_lines: vec!["[LOG] 'hello'".to_string()],
+        }
+    }
+
+    fn filter() -> FunctionExecutionFilter {
+        FunctionExecutionFilter {
+            function: None,
+            status: None,
+            start: None,
+            end: None,
+            min_execution_time: None,
+            limit: 100,
+        }
+    }
+
+    async fn query_timestamps(
+        model: &mut FunctionExecutionsModel<'_, TestRuntime>,
+        filter: FunctionExecutionFilter,
+    ) -> anyhow::Result<Vec<u64>> {
+        model
+            .query(filter)
+            .await?
+            .into_iter()
+            .map(|execution| execution.timestamp.as_ms_since_epoch())
+            .collect()
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_query_function_executions(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionExecutionsModel::new(&mut tx);
+        for record in [
+            record("messages:send", 1000, FunctionExecutionStatus::Success, 5),
+            record("messages:send", 2000, FunctionExecutionStatus::Failure, 50),
+            record("messages:list", 3000, FunctionExecutionStatus::Success, 500),
+            record("messages:send", 4000, FunctionExecutionStatus::Success, 100),
+        ] {
+            model.insert(record).await?;
+        }
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionExecutionsModel::new(&mut tx);
+        assert_eq!(
+            query_timestamps(&mut model, filter()).await?,
+            vec![4000, 3000, 2000, 1000]
+        );
+        assert_eq!(
+            query_timestamps(
+                &mut model,
+                FunctionExecutionFilter {
+                    function: Some("messages:send".to_string()),
+                    ..filter()
+                }
+            )
+            .await?,
+            vec![4000, 2000, 1000]
+        );
+        assert_eq!(
+            query_timestamps(
+                &mut model,
+                FunctionExecutionFilter {
+                    status: Some(FunctionExecutionStatus::Failure),
+                    ..filter()
+                }
+            )
+            .await?,
+            vec![2000]
+        );
+        assert_eq!(
+            query_timestamps(
+                &mut model,
+                FunctionExecutionFilter {
+                    function: Some("messages:send".to_string()),
+                    start: Some(UnixTimestamp::from_millis(2000)),
+                    end: Some(UnixTimestamp::from_millis(4000)),
+                    ..filter()
+                }
+            )
+            .await?,
+            vec![2000]
+        );
+        assert_eq!(
+            query_timestamps(
+                &mut model,
+                FunctionExecutionFilter {
+                    min_execution_time: Some(Duration::from_millis(50)),
+                    limit: 2,
+                    ..filter()
+                }
+            )
+            .await?,
+            vec![4000, 3000]
+        );
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_delete_expired_function_executions(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionExecutionsModel::new(&mut tx);
+        for timestamp_ms in [1000, 2000, 3000] {
+            model
+                .insert(record(
+                    "messages:send",
+                    timestamp_ms,
+                    FunctionExecutionStatus::Success,
+                    5,
+                ))
+                .await?;
+        }
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionExecutionsModel::new(&mut tx);
+        let expired = model.expired(UnixTimestamp::from_millis(3000), 100).await?;
+        assert_eq!(expired.len(), 2);
+        for id in expired {
+            model.delete(id).await?;
+        }
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionExecutionsModel::new(&mut tx);
+        assert_eq!(query_timestamps(&mut model, filter()).await?, vec![3000]);
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::Duration,
+};
+
+use common::{
+    runtime::UnixTimestamp,
+    types::UdfType,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum FunctionExecutionStatus {
+    Success,
+    Failure,
+}
+
+impl FunctionExecutionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FunctionExecutionStatus::Success => "success",
+            FunctionExecutionStatus::Failure => "failure",
+        }
+    }
+}
+
+impl FromStr for FunctionExecutionStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "success" => Ok(FunctionExecutionStatus::Success),
+            "failure" => Ok(FunctionExecutionStatus::Failure),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidFunctionExecutionStatus",
+                format!(
+                    "Unknown function execution status {s}, expected \"success\" or \"failure\""
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FunctionExecutionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Resources used by a function execution.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionExecutionUsage {
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub database_read_bytes: u64,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub database_write_bytes: u64,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub storage_read_bytes: u64,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub storage_write_bytes: u64,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0..=i64::MAX as u64)")
+    )]
+    pub action_memory_used_mb: Option<u64>,
+}
+
+/// A completed function execution, retained in `_function_executions` for
+/// `FUNCTION_EXECUTION_LOG_RETENTION` so that it can be queried after the
+/// backend restarts.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionExecutionRecord {
+    // When the function's result was returned.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub timestamp: UnixTimestamp,
+    // Path of the function, or the route of an HTTP action.
+    pub function: String,
+    pub udf_type: UdfType,
+    pub status: FunctionExecutionStatus,
+    pub error: Option<String>,
+    // The code of a typed application error the function threw, and its data
+    // as JSON, so failures can be filtered by code without parsing `error`.
+    pub error_code: Option<String>,
+    pub error_data: Option<String>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::strategy::Strategy::prop_map(0..86_400_000u64, \
+                             Duration::from_millis)")
+    )]
+    pub execution_time: Duration,
+    pub cached: bool,
+    pub request_id: String,
+    // Name of the deployment API key a top-level execution was called with.
+    pub api_key: Option<String>,
+    pub usage: FunctionExecutionUsage,
+    pub log_lines: Vec<String>,
+}
+
+/// Which function executions to return from a query. Executions are
+/// returned newest first.
+#[derive(Clone, Debug)]
+pub struct FunctionExecutionFilter {
+    pub function: Option<String>,
+    pub status: Option<FunctionExecutionStatus>,
+    /// Inclusive.
+    pub start: Option<UnixTimestamp>,
+    /// Exclusive.
+    pub end: Option<UnixTimestamp>,
+    pub min_execution_time: Option<Duration>,
+    pub limit: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SerializedFunctionExecutionUsage {
+    database_read_bytes: i64,
+    database_write_bytes: i64,
+    storage_read_bytes: i64,
+    storage_write_bytes: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    action_memory_used_mb: Option<i64>,
+}
+
+impl TryFrom<FunctionExecutionUsage> for SerializedFunctionExecutionUsage {
+    type Error = anyhow::Error;
+
+    fn try_from(usage: FunctionExecutionUsage) -> anyhow::Result<Self> {
+        Ok(Self {
+            database_read_bytes: usage.database_read_bytes.try_into()?,
+            database_write_bytes: usage.database_write_bytes.try_into()?,
+            storage_read_bytes: usage.storage_read_bytes.try_into()?,
+            storage_write_bytes: usage.storage_write_bytes.try_into()?,
+            action_memory_used_mb: usage.action_memory_used_mb.map(i64::try_from).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedFunctionExecutionUsage> for FunctionExecutionUsage {
+    type Error = anyhow::Error;
+
+    fn try_from(usage: SerializedFunctionExecutionUsage) -> anyhow::Result<Self> {
+        Ok(Self {
+            database_read_bytes: usage.database_read_bytes.try_into()?,
+            database_write_bytes: usage.database_write_bytes.try_into()?,
+            storage_read_bytes: usage.storage_read_bytes.try_into()?,
+            storage_write_bytes: usage.storage_write_bytes.try_into()?,
+            action_memory_used_mb: usage.action_memory_used_mb.map(u64::try_from).transpose()?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFunctionExecutionRecord {
+    // Nanoseconds since the Unix epoch.
+    timestamp: i64,
+    function: String,
+    udf_type: String,
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error_data: Option<String>,
+    execution_time_ms: i64,
+    cached: bool,
+    request_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    api_key: Option<String>,
+    usage: SerializedFunctionExecutionUsage,
+    log_lines: Vec<String>,
+}
+
+impl TryFrom<FunctionExecutionRecord> for SerializedFunctionExecutionRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: FunctionExecutionRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            timestamp: record.timestamp.as_nanos().try_into()?,
+            function: record.function,
+            udf_type: record.udf_type.to_string(),
+            status: record.status.to_string(),
+            error: record.error,
+            error_code: record.error_code,
+            error_data: record.error_data,
+            execution_time_ms: record.execution_time.as_millis().try_into()?,
+            cached: record.cached,
+            request_id: record.request_id,
+            api_key: record.api_key,
+            usage: record.usage.try_into()?,
+            log_lines: record.log_lines,
+        })
+    }
+}
+
+impl TryFrom<SerializedFunctionExecutionRecord> for FunctionExecutionRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: SerializedFunctionExecutionRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            timestamp: UnixTimestamp::from_nanos(record.timestamp.try_into()?),
+            function: record.function,
+            udf_type: record.udf_type.parse()?,
+            status: record.status.parse()?,
+            error: record.error,
+            error_code: record.error_code,
+            error_data: record.error_data,
+            execution_time: Duration::from_millis(record.execution_time_ms.try_into()?),
+            cached: record.cached,
+            request_id: record.request_id,
+            api_key: record.api_key,
+            usage: record.usage.try_into()?,
+            log_lines: record.log_lines,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionExecutionRecord, SerializedFunctionExecutionRecord);
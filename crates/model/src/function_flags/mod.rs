@@ -0,0 +1,269 @@
+use std::sync::LazyLock;
+
+use common::{
+    components::CanonicalizedComponentFunctionPath,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::FunctionFlag;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Kill switches and canaries for individual functions, checked by the
+/// function router before every call.
+pub static FUNCTION_FLAGS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_flags"
+        .parse()
+        .expect("Invalid built-in function flags table")
+});
+
+pub struct FunctionFlagsTable;
+impl SystemTable for FunctionFlagsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FUNCTION_FLAGS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FunctionFlag>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FunctionFlagsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FunctionFlagsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<FunctionFlag>>> {
+        self.check_admin("list_function_flags")?;
+        let query = Query::full_table_scan(FUNCTION_FLAGS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut flags = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            flags.push(doc.try_into()?);
+        }
+        Ok(flags)
+    }
+
+    pub async fn get(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<Option<ParsedDocument<FunctionFlag>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|flag| flag.path == *path))
+    }
+
+    /// Set the flag for a function, replacing any existing flag for it.
+    pub async fn set(&mut self, flag: FunctionFlag) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("set_function_flag")?;
+        flag.validate()?;
+        match self.get(&flag.path).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), flag.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FUNCTION_FLAGS_TABLE, flag.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Remove the flag for a function, so every caller can call it again.
+    pub async fn delete(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<()> {
+        self.check_admin("delete_function_flag")?;
+        let Some(existing) = self.get(path).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "FunctionFlagNotFound",
+                format!(
+                    "There is no flag for function {:?}",
+                    String::from(path.udf_path.clone())
+                ),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    };
+    use database::test_helpers::DbFixtures;
+    use keybroker::{
+        testing::TestUserIdentity,
+        Identity,
+        UserIdentity,
+    };
+    use runtime::testing::TestRuntime;
+    use sync_types::UserIdentifier;
+
+    use crate::{
+        function_flags::{
+            types::{
+                FunctionFlag,
+                FunctionFlagMode,
+            },
+            FunctionFlagsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn flag(udf_path: &str, mode: FunctionFlagMode) -> anyhow::Result<FunctionFlag> {
+        Ok(FunctionFlag {
+            path: CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: udf_path.parse()?,
+            },
+            mode,
+            reason: None,
+        })
+    }
+
+    #[test]
+    fn test_validate() -> anyhow::Result<()> {
+        assert!(flag("messages.js:send", FunctionFlagMode::Disabled)?
+            .validate()
+            .is_ok());
+        assert!(flag("_system/cli/tables.js", FunctionFlagMode::Disabled)?
+            .validate()
+            .is_err());
+        let canary = FunctionFlagMode::Canary {
+            rollout_percentage: 101,
+        };
+        assert!(flag("messages.js:send", canary)?.validate().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_allows() -> anyhow::Result<()> {
+        let disabled = flag("messages.js:send", FunctionFlagMode::Disabled)?;
+        assert!(disabled.allows(&Identity::system()));
+        assert!(!disabled.allows(&Identity::Unknown));
+        let err = disabled.check(&Identity::Unknown).unwrap_err();
+        assert!(err.to_string().contains("is disabled"), "{err}");
+
+        let user = UserIdentity::test();
+        let users: Vec<Identity> = (0..200)
+            .map(|i| {
+                let mut user = user.clone();
+                user.attributes.token_identifier = UserIdentifier(format!("issuer|user{i}"));
+                Identity::user(user)
+            })
+            .collect();
+        let canary = |rollout_percentage| {
+            flag(
+                "messages.js:send",
+                FunctionFlagMode::Canary { rollout_percentage },
+            )
+        };
+        let none = canary(0)?;
+        assert!(users.iter().all(|user| !none.allows(user)));
+        let all = canary(100)?;
+        assert!(users.iter().all(|user| all.allows(user)));
+        assert!(all.allows(&Identity::Unknown));
+
+        // Users are bucketed consistently, and raising the percentage only
+        // adds users to the rollout.
+        let half = canary(50)?;
+        let allowed: Vec<_> = users.iter().map(|user| half.allows(user)).collect();
+        assert_eq!(
+            allowed,
+            users
+                .iter()
+                .map(|user| half.allows(user))
+                .collect::<Vec<_>>()
+        );
+        let num_allowed = allowed.iter().filter(|allowed| **allowed).count();
+        assert!((50..150).contains(&num_allowed), "{num_allowed}");
+        let most = canary(90)?;
+        for (user, allowed) in users.iter().zip(allowed) {
+            assert!(!allowed || most.allows(user));
+        }
+        assert!(!half.allows(&Identity::Unknown));
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_flags(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionFlagsModel::new(&mut tx);
+        let disabled = flag("messages.js:send", FunctionFlagMode::Disabled)?;
+        let id = model.set(disabled.clone()).await?;
+        assert_eq!(
+            model.get(&disabled.path).await?.unwrap().into_value(),
+            disabled
+        );
+
+        // Setting a flag for the same function replaces it.
+        let canary = flag(
+            "messages.js:send",
+            FunctionFlagMode::Canary {
+                rollout_percentage: 10,
+            },
+        )?;
+        assert_eq!(model.set(canary.clone()).await?, id);
+        let flags = model.list().await?;
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].clone().into_value(), canary);
+
+        model.delete(&canary.path).await?;
+        assert!(model.list().await?.is_empty());
+        assert!(model.delete(&canary.path).await.is_err());
+        Ok(())
+    }
+}
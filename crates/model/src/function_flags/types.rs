@@ -0,0 +1,210 @@
+use std::fmt;
+
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    sha256::Sha256,
+};
+use errors::ErrorMetadata;
+use keybroker::Identity;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Reasons are shown to callers, so keep them short.
+pub const MAX_REASON_LEN: usize = 1000;
+
+/// What a flag does to calls of its function.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum FunctionFlagMode {
+    /// Every call fails, e.g. to stop a misbehaving function during an
+    /// incident.
+    Disabled,
+    /// Only a percentage of users can call the function, e.g. to roll out a
+    /// new one gradually. Each user is bucketed by a hash of their identity,
+    /// so they consistently get the same answer until the percentage changes.
+    Canary {
+        #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0..=100u8"))]
+        rollout_percentage: u8,
+    },
+}
+
+impl fmt::Display for FunctionFlagMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FunctionFlagMode::Disabled => write!(f, "disabled"),
+            FunctionFlagMode::Canary { rollout_percentage } => {
+                write!(f, "canary at {rollout_percentage}%")
+            },
+        }
+    }
+}
+
+/// An admin-managed override for calls of one function, which takes effect
+/// without pushing code.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionFlag {
+    pub path: CanonicalizedComponentFunctionPath,
+    pub mode: FunctionFlagMode,
+    // Included in the error callers get when the flag rejects their call.
+    pub reason: Option<String>,
+}
+
+impl FunctionFlag {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid =
+            |msg: String| anyhow::anyhow!(ErrorMetadata::bad_request("InvalidFunctionFlag", msg));
+        if self.path.udf_path.is_system() {
+            return Err(invalid(format!(
+                "Cannot set a flag on system function {:?}",
+                String::from(self.path.udf_path.clone())
+            )));
+        }
+        if let FunctionFlagMode::Canary { rollout_percentage } = self.mode
+            && rollout_percentage > 100
+        {
+            return Err(invalid(
+                "rolloutPercentage must be between 0 and 100".to_string(),
+            ));
+        }
+        if let Some(reason) = &self.reason
+            && reason.len() > MAX_REASON_LEN
+        {
+            return Err(invalid(format!(
+                "reason must be at most {MAX_REASON_LEN} characters"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether `identity` may call the function. The deployment itself and
+    /// admins are part of every canary, so they can try the function out,
+    /// but nothing other than the system can call a disabled function.
+    /// Callers without a user identity are only part of a full rollout.
+    pub fn allows(&self, identity: &Identity) -> bool {
+        if identity.is_system() {
+            return true;
+        }
+        let FunctionFlagMode::Canary { rollout_percentage } = self.mode else {
+            return false;
+        };
+        let token_identifier = match identity {
+            Identity::User(user) => &user.attributes.token_identifier.0,
+            Identity::ActingUser(_, attributes) => &attributes.token_identifier.0,
+            Identity::InstanceAdmin(_) => return true,
+            Identity::System(_) | Identity::ApiKey(_) | Identity::Unknown => {
+                return rollout_percentage >= 100;
+            },
+        };
+        rollout_bucket(&self.path, "", token_identifier) < rollout_percentage
+    }
+
+    pub fn check(&self, identity: &Identity) -> anyhow::Result<()> {
+        if self.allows(identity) {
+            return Ok(());
+        }
+        let udf_path = String::from(self.path.udf_path.clone());
+        let mut msg = match self.mode {
+            FunctionFlagMode::Disabled => format!("Function {udf_path:?} is disabled"),
+            FunctionFlagMode::Canary { .. } => {
+                format!("Function {udf_path:?} is not yet available to this caller")
+            },
+        };
+        if let Some(reason) = &self.reason {
+            msg.push_str(": ");
+            msg.push_str(reason);
+        }
+        anyhow::bail!(ErrorMetadata::bad_request("FunctionDisabled", msg))
+    }
+}
+
+/// Which of 100 buckets a user falls into for a gradual rollout of `path`.
+/// The path is hashed too, so each function's rollout gets a different
+/// sample of users, and `salt` can vary the sample between rollouts of the
+/// same function.
+pub fn rollout_bucket(
+    path: &CanonicalizedComponentFunctionPath,
+    salt: &str,
+    token_identifier: &str,
+) -> u8 {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(String::from(path.component.clone()).as_bytes());
+    hasher.update(b"|");
+    hasher.update(String::from(path.udf_path.clone()).as_bytes());
+    hasher.update(b"|");
+    hasher.update(token_identifier.as_bytes());
+    let digest = hasher.finalize();
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % 100) as u8
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedFunctionFlagMode {
+    Disabled,
+    #[serde(rename_all = "camelCase")]
+    Canary {
+        rollout_percentage: i64,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFunctionFlag {
+    component_path: String,
+    udf_path: String,
+    mode: SerializedFunctionFlagMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+impl TryFrom<FunctionFlag> for SerializedFunctionFlag {
+    type Error = anyhow::Error;
+
+    fn try_from(flag: FunctionFlag) -> anyhow::Result<Self> {
+        let mode = match flag.mode {
+            FunctionFlagMode::Disabled => SerializedFunctionFlagMode::Disabled,
+            FunctionFlagMode::Canary { rollout_percentage } => SerializedFunctionFlagMode::Canary {
+                rollout_percentage: rollout_percentage.into(),
+            },
+        };
+        Ok(Self {
+            component_path: flag.path.component.into(),
+            udf_path: flag.path.udf_path.into(),
+            mode,
+            reason: flag.reason,
+        })
+    }
+}
+
+impl TryFrom<SerializedFunctionFlag> for FunctionFlag {
+    type Error = anyhow::Error;
+
+    fn try_from(flag: SerializedFunctionFlag) -> anyhow::Result<Self> {
+        let mode = match flag.mode {
+            SerializedFunctionFlagMode::Disabled => FunctionFlagMode::Disabled,
+            SerializedFunctionFlagMode::Canary { rollout_percentage } => FunctionFlagMode::Canary {
+                rollout_percentage: rollout_percentage.try_into()?,
+            },
+        };
+        let component: ComponentPath = flag.component_path.parse()?;
+        Ok(Self {
+            path: CanonicalizedComponentFunctionPath {
+                component,
+                udf_path: flag.udf_path.parse()?,
+            },
+            mode,
+            reason: flag.reason,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionFlag, SerializedFunctionFlag);
@@ -0,0 +1,271 @@
+use std::sync::LazyLock;
+
+use common::{
+    components::CanonicalizedComponentFunctionPath,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ReadBudget,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::FunctionReadBudget;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Limits on how much individual functions can read, loaded by the isolate
+/// before each query and mutation runs.
+pub static FUNCTION_READ_BUDGETS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_function_read_budgets"
+        .parse()
+        .expect("Invalid built-in function read budgets table")
+});
+
+pub struct FunctionReadBudgetsTable;
+impl SystemTable for FunctionReadBudgetsTable {
+    fn table_name(&self) -> &'static TableName {
+        &FUNCTION_READ_BUDGETS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<FunctionReadBudget>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct FunctionReadBudgetsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> FunctionReadBudgetsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    async fn list_unchecked(&mut self) -> anyhow::Result<Vec<ParsedDocument<FunctionReadBudget>>> {
+        let query = Query::full_table_scan(FUNCTION_READ_BUDGETS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut budgets = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            budgets.push(doc.try_into()?);
+        }
+        Ok(budgets)
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<FunctionReadBudget>>> {
+        self.check_admin("list_function_read_budgets")?;
+        self.list_unchecked().await
+    }
+
+    pub async fn get(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<Option<ParsedDocument<FunctionReadBudget>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|budget| budget.path == *path))
+    }
+
+    /// The budget a call of `path` runs with. Unlike `get`, this is called
+    /// from within the function's own transaction, so it's available to
+    /// every caller.
+    pub async fn read_budget_for(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<ReadBudget> {
+        if path.udf_path.is_system() {
+            return Ok(ReadBudget::default());
+        }
+        Ok(self
+            .list_unchecked()
+            .await?
+            .into_iter()
+            .find(|budget| budget.path == *path)
+            .map(|budget| budget.read_budget())
+            .unwrap_or_default())
+    }
+
+    /// Set the budget for a function, replacing any existing budget for it.
+    pub async fn set(&mut self, budget: FunctionReadBudget) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("set_function_read_budget")?;
+        budget.validate()?;
+        match self.get(&budget.path).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), budget.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&FUNCTION_READ_BUDGETS_TABLE, budget.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Remove the budget for a function, so only the transaction limits
+    /// apply to it.
+    pub async fn delete(
+        &mut self,
+        path: &CanonicalizedComponentFunctionPath,
+    ) -> anyhow::Result<()> {
+        self.check_admin("delete_function_read_budget")?;
+        let Some(existing) = self.get(path).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "FunctionReadBudgetNotFound",
+                format!(
+                    "There is no read budget for function {:?}",
+                    String::from(path.udf_path.clone())
+                ),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    };
+    use database::{
+        test_helpers::DbFixtures,
+        ReadBudget,
+    };
+    use keybroker::Identity;
+    use proptest::prelude::*;
+    use runtime::testing::TestRuntime;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use crate::{
+        function_read_budgets::{
+            types::FunctionReadBudget,
+            FunctionReadBudgetsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn budget(
+        udf_path: &str,
+        max_documents: Option<u64>,
+        max_bytes: Option<u64>,
+    ) -> anyhow::Result<FunctionReadBudget> {
+        Ok(FunctionReadBudget {
+            path: CanonicalizedComponentFunctionPath {
+                component: ComponentPath::root(),
+                udf_path: udf_path.parse()?,
+            },
+            max_documents,
+            max_bytes,
+        })
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_function_read_budget_roundtrips(v in any::<FunctionReadBudget>()) {
+            assert_roundtrips::<FunctionReadBudget, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_validate() -> anyhow::Result<()> {
+        assert!(budget("messages.js:list", Some(100), None)?
+            .validate()
+            .is_ok());
+        assert!(budget("messages.js:list", None, None)?.validate().is_err());
+        assert!(budget("messages.js:list", Some(0), None)?
+            .validate()
+            .is_err());
+        assert!(budget("messages.js:list", None, Some(u64::MAX))?
+            .validate()
+            .is_err());
+        assert!(budget("_system/cli/tables.js", Some(100), None)?
+            .validate()
+            .is_err());
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_read_budgets(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionReadBudgetsModel::new(&mut tx);
+        let list = budget("messages.js:list", Some(100), None)?;
+        let id = model.set(list.clone()).await?;
+        assert_eq!(model.get(&list.path).await?.unwrap().into_value(), list);
+
+        // Setting a budget for the same function replaces it.
+        let list = budget("messages.js:list", Some(100), Some(1 << 20))?;
+        assert_eq!(model.set(list.clone()).await?, id);
+        let budgets = model.list().await?;
+        assert_eq!(budgets.len(), 1);
+        assert_eq!(budgets[0].clone().into_value(), list);
+        db.commit(tx).await?;
+
+        // Functions read their budget as whoever called them.
+        let mut tx = db.begin(Identity::Unknown).await?;
+        let mut model = FunctionReadBudgetsModel::new(&mut tx);
+        assert!(model.list().await.is_err());
+        assert_eq!(
+            model.read_budget_for(&list.path).await?,
+            ReadBudget {
+                max_documents: Some(100),
+                max_bytes: Some(1 << 20),
+            }
+        );
+        let other = budget("messages.js:send", Some(1), None)?;
+        assert!(model.read_budget_for(&other.path).await?.is_unlimited());
+
+        let mut tx = db.begin_system().await?;
+        let mut model = FunctionReadBudgetsModel::new(&mut tx);
+        model.delete(&list.path).await?;
+        assert!(model.list().await?.is_empty());
+        assert!(model.delete(&list.path).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use common::{
+    components::{
+        CanonicalizedComponentFunctionPath,
+        ComponentPath,
+    },
+    knobs::{
+        TRANSACTION_MAX_READ_SIZE_BYTES,
+        TRANSACTION_MAX_READ_SIZE_ROWS,
+    },
+};
+use database::ReadBudget;
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// How much one function may read from user tables before it fails with a
+/// `ReadBudgetExceeded` error. Budgets are below the transaction limits, so
+/// a function that unexpectedly scans a whole table fails early, and the
+/// error says which index it was scanning.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionReadBudget {
+    pub path: CanonicalizedComponentFunctionPath,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(1..=16384u64)")
+    )]
+    pub max_documents: Option<u64>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(1..=8388608u64)")
+    )]
+    pub max_bytes: Option<u64>,
+}
+
+impl FunctionReadBudget {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid = |msg: String| {
+            anyhow::anyhow!(ErrorMetadata::bad_request("InvalidFunctionReadBudget", msg))
+        };
+        if self.path.udf_path.is_system() {
+            return Err(invalid(format!(
+                "Cannot set a read budget on system function {:?}",
+                String::from(self.path.udf_path.clone())
+            )));
+        }
+        if self.max_documents.is_none() && self.max_bytes.is_none() {
+            return Err(invalid(
+                "A read budget needs maxDocuments, maxBytes or both".to_string(),
+            ));
+        }
+        for (name, limit, transaction_limit) in [
+            (
+                "maxDocuments",
+                self.max_documents,
+                *TRANSACTION_MAX_READ_SIZE_ROWS,
+            ),
+            ("maxBytes", self.max_bytes, *TRANSACTION_MAX_READ_SIZE_BYTES),
+        ] {
+            if let Some(limit) = limit
+                && (limit == 0 || limit > transaction_limit as u64)
+            {
+                return Err(invalid(format!(
+                    "{name} must be between 1 and {transaction_limit}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_budget(&self) -> ReadBudget {
+        ReadBudget {
+            max_documents: self.max_documents.map(|limit| limit as usize),
+            max_bytes: self.max_bytes.map(|limit| limit as usize),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedFunctionReadBudget {
+    component_path: String,
+    udf_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_documents: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_bytes: Option<i64>,
+}
+
+impl TryFrom<FunctionReadBudget> for SerializedFunctionReadBudget {
+    type Error = anyhow::Error;
+
+    fn try_from(budget: FunctionReadBudget) -> anyhow::Result<Self> {
+        Ok(Self {
+            component_path: budget.path.component.into(),
+            udf_path: budget.path.udf_path.into(),
+            max_documents: budget.max_documents.map(i64::try_from).transpose()?,
+            max_bytes: budget.max_bytes.map(i64::try_from).transpose()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedFunctionReadBudget> for FunctionReadBudget {
+    type Error = anyhow::Error;
+
+    fn try_from(budget: SerializedFunctionReadBudget) -> anyhow::Result<Self> {
+        let component: ComponentPath = budget.component_path.parse()?;
+        Ok(Self {
+            path: CanonicalizedComponentFunctionPath {
+                component,
+                udf_path: budget.udf_path.parse()?,
+            },
+            max_documents: budget.max_documents.map(u64::try_from).transpose()?,
+            max_bytes: budget.max_bytes.map(u64::try_from).transpose()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(FunctionReadBudget, SerializedFunctionReadBudget);
@@ -0,0 +1,176 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        WriteTimestamp,
+    },
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use keybroker::Identity;
+use sync_types::Timestamp;
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    IdempotencyKey,
+    IdempotencyKeyRecord,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Table of mutations called with an `Idempotency-Key` header, used to return
+/// the original result when a request is retried.
+pub static IDEMPOTENCY_KEYS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_idempotency_keys"
+        .parse()
+        .expect("Invalid built-in idempotency keys table")
+});
+
+static KEY_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "key".parse().expect("Invalid built-in field"));
+
+static CREATED_TS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "createdTs".parse().expect("Invalid built-in field"));
+
+pub static IDEMPOTENCY_KEYS_INDEX: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&IDEMPOTENCY_KEYS_TABLE, "by_key"));
+
+pub static IDEMPOTENCY_KEYS_INDEX_BY_CREATED_TS: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&IDEMPOTENCY_KEYS_TABLE, "by_created_ts"));
+
+pub struct IdempotencyKeysTable;
+impl SystemTable for IdempotencyKeysTable {
+    fn table_name(&self) -> &'static TableName {
+        &IDEMPOTENCY_KEYS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: IDEMPOTENCY_KEYS_INDEX.clone(),
+                fields: vec![KEY_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: IDEMPOTENCY_KEYS_INDEX_BY_CREATED_TS.clone(),
+                fields: vec![CREATED_TS_FIELD.clone()].try_into().unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<IdempotencyKeyRecord>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct IdempotencyKeyModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> IdempotencyKeyModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Look up the record for `key` along with the timestamp it was committed
+    /// at. The lookup is part of the transaction's read set, so two concurrent
+    /// mutations with the same key can't both commit.
+    pub async fn get_record(
+        &mut self,
+        key: &IdempotencyKey,
+        identity: Identity,
+    ) -> anyhow::Result<Option<(Timestamp, IdempotencyKeyRecord)>> {
+        if !identity.is_system() {
+            anyhow::bail!(unauthorized_error("get_idempotency_key_record"))
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: IDEMPOTENCY_KEYS_INDEX.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                KEY_FIELD.clone(),
+                ConvexValue::try_from(key.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let Some((doc, ts)) = query_stream.next_with_ts(self.tx, None).await? else {
+            return Ok(None);
+        };
+        anyhow::ensure!(
+            query_stream.next(self.tx, Some(1)).await?.is_none(),
+            "Expected at most one idempotency key record."
+        );
+        let WriteTimestamp::Committed(ts) = ts else {
+            anyhow::bail!(
+                "Wrote an idempotency key record in the same transaction as the get? Not \
+                 supported."
+            );
+        };
+        let record: ParsedDocument<IdempotencyKeyRecord> = doc.try_into()?;
+        Ok(Some((ts, record.into_value())))
+    }
+
+    pub async fn record(
+        &mut self,
+        record: IdempotencyKeyRecord,
+        identity: Identity,
+    ) -> anyhow::Result<()> {
+        if !identity.is_system() {
+            anyhow::bail!(unauthorized_error("record_idempotency_key"))
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert_metadata(&IDEMPOTENCY_KEYS_TABLE, record.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// The oldest records, in the order they were created.
+    pub async fn oldest(
+        &mut self,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<IdempotencyKeyRecord>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: IDEMPOTENCY_KEYS_INDEX_BY_CREATED_TS.clone(),
+            range: vec![],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut records = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            records.push(doc.try_into()?);
+        }
+        Ok(records)
+    }
+
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
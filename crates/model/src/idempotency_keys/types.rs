@@ -0,0 +1,183 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+};
+
+use common::{
+    identity::InertIdentity,
+    obj,
+    value::ConvexValue,
+};
+use errors::ErrorMetadata;
+use sync_types::Timestamp;
+use value::{
+    sha256::Sha256Digest,
+    ConvexObject,
+};
+
+use crate::session_requests::types::{
+    SessionRequestIdentifier,
+    SessionRequestOutcome,
+};
+
+/// Maximum length in bytes of a client-supplied idempotency key.
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Identifier used to make a mutation idempotent, so a retried request returns
+/// the result of the first execution instead of running the mutation again.
+#[derive(Clone, Debug)]
+pub enum MutationIdentifier {
+    /// A request on a sync protocol session.
+    Session(SessionRequestIdentifier),
+    /// A key supplied by the client with the `Idempotency-Key` header.
+    IdempotencyKey(IdempotencyKey),
+}
+
+/// A client-supplied key identifying a mutation request. Keys are between 1
+/// and [`MAX_IDEMPOTENCY_KEY_LENGTH`] printable ASCII characters, which
+/// leaves room for UUIDs and the like.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct IdempotencyKey(
+    #[cfg_attr(any(test, feature = "testing"), proptest(regex = "[!-~]{1,64}"))] String,
+);
+
+impl FromStr for IdempotencyKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if s.is_empty() || s.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidIdempotencyKey",
+                format!(
+                    "Idempotency keys must be between 1 and {MAX_IDEMPOTENCY_KEY_LENGTH} \
+                     characters long"
+                ),
+            ));
+        }
+        if !s.chars().all(|c| c.is_ascii_graphic()) {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidIdempotencyKey",
+                "Idempotency keys may only contain printable ASCII characters other than spaces",
+            ));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<IdempotencyKey> for String {
+    fn from(key: IdempotencyKey) -> Self {
+        key.0
+    }
+}
+
+/// The outcome of a committed mutation that was called with an idempotency
+/// key. Records are written atomically with the mutation and deleted after
+/// `MUTATION_IDEMPOTENCY_KEY_RETENTION`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct IdempotencyKeyRecord {
+    pub key: IdempotencyKey,
+    /// Hash of the function path and arguments, used to reject reusing a key
+    /// for a different request.
+    pub request_hash: Sha256Digest,
+    /// Non-permission-granting representation of the identity that called the
+    /// mutation. Only the same identity can reuse the key.
+    pub identity: InertIdentity,
+    pub outcome: SessionRequestOutcome,
+    pub created_ts: Timestamp,
+}
+
+impl TryFrom<IdempotencyKeyRecord> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(record: IdempotencyKeyRecord) -> anyhow::Result<Self> {
+        obj!(
+            "key" => String::from(record.key),
+            "requestHash" => record.request_hash,
+            "identity" => record.identity.to_string(),
+            "outcome" => ConvexValue::Object(record.outcome.try_into()?),
+            "createdTs" => i64::from(record.created_ts),
+        )
+    }
+}
+
+impl TryFrom<ConvexObject> for IdempotencyKeyRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(object: ConvexObject) -> anyhow::Result<Self> {
+        let mut fields: BTreeMap<_, _> = object.into();
+
+        let key = match fields.remove("key") {
+            Some(ConvexValue::String(s)) => s.parse()?,
+            v => anyhow::bail!("Invalid key field for IdempotencyKeyRecord: {:?}", v),
+        };
+        let request_hash = match fields.remove("requestHash") {
+            Some(v @ ConvexValue::Bytes(_)) => v.try_into()?,
+            v => anyhow::bail!(
+                "Invalid requestHash field for IdempotencyKeyRecord: {:?}",
+                v
+            ),
+        };
+        let identity = match fields.remove("identity") {
+            Some(ConvexValue::String(s)) => s.to_string().parse()?,
+            v => anyhow::bail!("Invalid identity field for IdempotencyKeyRecord: {:?}", v),
+        };
+        let outcome = match fields.remove("outcome") {
+            Some(ConvexValue::Object(o)) => o.try_into()?,
+            v => anyhow::bail!("Invalid outcome field for IdempotencyKeyRecord: {:?}", v),
+        };
+        let created_ts = match fields.remove("createdTs") {
+            Some(ConvexValue::Int64(ts)) => ts.try_into()?,
+            v => anyhow::bail!("Invalid createdTs field for IdempotencyKeyRecord: {:?}", v),
+        };
+
+        Ok(IdempotencyKeyRecord {
+            key,
+            request_hash,
+            identity,
+            outcome,
+            created_ts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::testing::assert_roundtrips;
+    use proptest::prelude::*;
+    use value::ConvexObject;
+
+    use super::{
+        IdempotencyKey,
+        IdempotencyKeyRecord,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_idempotency_key_record_roundtrips(v in any::<IdempotencyKeyRecord>()) {
+            assert_roundtrips::<IdempotencyKeyRecord, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_parse_idempotency_key() {
+        assert!("d8a3b2c1-5f4e-4a7b-9c0d-1e2f3a4b5c6d"
+            .parse::<IdempotencyKey>()
+            .is_ok());
+        assert!("".parse::<IdempotencyKey>().is_err());
+        assert!("has space".parse::<IdempotencyKey>().is_err());
+        assert!("é".parse::<IdempotencyKey>().is_err());
+        assert!("a".repeat(256).parse::<IdempotencyKey>().is_err());
+    }
+}
@@ -0,0 +1,468 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    CronExecutionSummary,
+    JobExecutionFilter,
+    JobExecutionRecord,
+    JobKind,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Table of finished scheduled job and cron runs, kept for
+/// `JOB_EXECUTION_HISTORY_RETENTION`.
+pub static JOB_EXECUTIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_job_executions"
+        .parse()
+        .expect("Invalid built-in job executions table")
+});
+
+static KIND_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "kind".parse().expect("Invalid built-in field"));
+
+static JOB_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "job".parse().expect("Invalid built-in field"));
+
+static END_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "end".parse().expect("Invalid built-in field"));
+
+pub static JOB_EXECUTIONS_INDEX_BY_END: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&JOB_EXECUTIONS_TABLE, "by_end"));
+
+pub static JOB_EXECUTIONS_INDEX_BY_KIND: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&JOB_EXECUTIONS_TABLE, "by_kind_and_end"));
+
+pub static JOB_EXECUTIONS_INDEX_BY_JOB: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&JOB_EXECUTIONS_TABLE, "by_kind_job_and_end"));
+
+pub struct JobExecutionsTable;
+impl SystemTable for JobExecutionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &JOB_EXECUTIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: JOB_EXECUTIONS_INDEX_BY_END.clone(),
+                fields: vec![END_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: JOB_EXECUTIONS_INDEX_BY_KIND.clone(),
+                fields: vec![KIND_FIELD.clone(), END_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+            SystemIndex {
+                name: JOB_EXECUTIONS_INDEX_BY_JOB.clone(),
+                fields: vec![KIND_FIELD.clone(), JOB_FIELD.clone(), END_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<JobExecutionRecord>::try_from(document).map(|_| ())
+    }
+}
+
+fn timestamp_value(timestamp: UnixTimestamp) -> anyhow::Result<ConvexValue> {
+    Ok(ConvexValue::Int64(timestamp.as_nanos().try_into()?))
+}
+
+pub struct JobExecutionsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> JobExecutionsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, record: JobExecutionRecord) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("insert_job_execution"));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert_metadata(&JOB_EXECUTIONS_TABLE, record.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// The executions matching `filter`, newest first.
+    pub async fn query(
+        &mut self,
+        filter: JobExecutionFilter,
+    ) -> anyhow::Result<Vec<ParsedDocument<JobExecutionRecord>>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("query_job_executions"));
+        }
+        let mut range = vec![];
+        let index_name = match (filter.kind, &filter.job) {
+            (Some(kind), Some(job)) => {
+                range.push(IndexRangeExpression::Eq(
+                    KIND_FIELD.clone(),
+                    ConvexValue::try_from(kind.to_string())?.into(),
+                ));
+                range.push(IndexRangeExpression::Eq(
+                    JOB_FIELD.clone(),
+                    ConvexValue::try_from(job.clone())?.into(),
+                ));
+                JOB_EXECUTIONS_INDEX_BY_JOB.clone()
+            },
+            (Some(kind), None) => {
+                range.push(IndexRangeExpression::Eq(
+                    KIND_FIELD.clone(),
+                    ConvexValue::try_from(kind.to_string())?.into(),
+                ));
+                JOB_EXECUTIONS_INDEX_BY_KIND.clone()
+            },
+            (None, None) => JOB_EXECUTIONS_INDEX_BY_END.clone(),
+            (None, Some(_)) => anyhow::bail!(ErrorMetadata::bad_request(
+                "JobKindRequired",
+                "kind must be set to query the executions of a single job",
+            )),
+        };
+        if let Some(since) = filter.since {
+            range.push(IndexRangeExpression::Gte(
+                END_FIELD.clone(),
+                timestamp_value(since)?,
+            ));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name,
+            range,
+            order: Order::Desc,
+        })
+        .limit(filter.limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut executions = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            executions.push(doc.try_into()?);
+        }
+        Ok(executions)
+    }
+
+    /// Summaries of every cron that ran at or after `since`. This reads all
+    /// of their runs in that time, which retention bounds.
+    pub async fn summarize_crons(
+        &mut self,
+        since: UnixTimestamp,
+    ) -> anyhow::Result<Vec<CronExecutionSummary>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("summarize_cron_executions"));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: JOB_EXECUTIONS_INDEX_BY_KIND.clone(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    KIND_FIELD.clone(),
+                    ConvexValue::try_from(JobKind::Cron.to_string())?.into(),
+                ),
+                IndexRangeExpression::Gte(END_FIELD.clone(), timestamp_value(since)?),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut executions = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            let execution: ParsedDocument<JobExecutionRecord> = doc.try_into()?;
+            executions.push(execution.into_value());
+        }
+        Ok(CronExecutionSummary::summarize(executions))
+    }
+
+    /// Up to `limit` executions that ended before `cutoff`, oldest first.
+    pub async fn expired(
+        &mut self,
+        cutoff: UnixTimestamp,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        let query = Query::index_range(IndexRange {
+            index_name: JOB_EXECUTIONS_INDEX_BY_END.clone(),
+            range: vec![IndexRangeExpression::Lt(
+                END_FIELD.clone(),
+                timestamp_value(cutoff)?,
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut ids = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            ids.push(doc.id());
+        }
+        Ok(ids)
+    }
+
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("delete_job_execution"));
+        }
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::{
+        components::ComponentPath,
+        runtime::UnixTimestamp,
+    };
+    use database::test_helpers::DbFixtures;
+    use keybroker::Identity;
+    use proptest::prelude::*;
+    use runtime::testing::TestRuntime;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use crate::{
+        function_executions::types::{
+            FunctionExecutionStatus,
+            FunctionExecutionUsage,
+        },
+        job_executions::{
+            types::{
+                CronExecutionSummary,
+                JobExecutionFilter,
+                JobExecutionRecord,
+                JobKind,
+            },
+            JobExecutionsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn record(
+        kind: JobKind,
+        job: &str,
+        end_ms: u64,
+        duration_ms: u64,
+        status: FunctionExecutionStatus,
+    ) -> anyhow::Result<JobExecutionRecord> {
+        Ok(JobExecutionRecord {
+            kind,
+            job: job.to_string(),
+            component: ComponentPath::root(),
+            udf_path: "crons.js:cleanup".parse()?,
+            start: UnixTimestamp::from_millis(end_ms - duration_ms),
+            end: UnixTimestamp::from_millis(end_ms),
+            status,
+            error: (status == FunctionExecutionStatus::Failure).then(|| "boom".to_string()),
+            attempts: 0,
+            usage: FunctionExecutionUsage::default(),
+        })
+    }
+
+    fn filter() -> JobExecutionFilter {
+        JobExecutionFilter {
+            kind: None,
+            job: None,
+            since: None,
+            limit: 100,
+        }
+    }
+
+    async fn query_ends(
+        model: &mut JobExecutionsModel<'_, TestRuntime>,
+        filter: JobExecutionFilter,
+    ) -> anyhow::Result<Vec<u64>> {
+        model
+            .query(filter)
+            .await?
+            .into_iter()
+            .map(|execution| execution.end.as_ms_since_epoch())
+            .collect()
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_job_execution_roundtrips(v in any::<JobExecutionRecord>()) {
+            assert_roundtrips::<JobExecutionRecord, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_summarize() -> anyhow::Result<()> {
+        let mut runs = vec![];
+        for i in 1..=20 {
+            let status = if i % 10 == 0 {
+                FunctionExecutionStatus::Failure
+            } else {
+                FunctionExecutionStatus::Success
+            };
+            runs.push(record(JobKind::Cron, "cleanup", 1000 * i, 10 * i, status)?);
+        }
+        runs.push(record(
+            JobKind::Cron,
+            "digest",
+            5000,
+            7,
+            FunctionExecutionStatus::Success,
+        )?);
+        runs.push(record(
+            JobKind::Scheduled,
+            "kg2h4sq1xh4x0gm6",
+            5000,
+            7,
+            FunctionExecutionStatus::Failure,
+        )?);
+        let summaries = CronExecutionSummary::summarize(runs);
+        assert_eq!(summaries.len(), 2);
+        let cleanup = &summaries[0];
+        assert_eq!(cleanup.name, "cleanup");
+        assert_eq!(cleanup.num_runs, 20);
+        assert_eq!(cleanup.num_failures, 2);
+        assert_eq!(cleanup.success_rate(), 0.9);
+        assert_eq!(cleanup.p95_duration, Duration::from_millis(190));
+        assert_eq!(cleanup.last_run, UnixTimestamp::from_millis(20_000));
+        assert_eq!(summaries[1].p95_duration, Duration::from_millis(7));
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_job_executions(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = JobExecutionsModel::new(&mut tx);
+        for record in [
+            record(
+                JobKind::Cron,
+                "cleanup",
+                1000,
+                5,
+                FunctionExecutionStatus::Success,
+            )?,
+            record(
+                JobKind::Scheduled,
+                "job1",
+                2000,
+                5,
+                FunctionExecutionStatus::Failure,
+            )?,
+            record(
+                JobKind::Cron,
+                "digest",
+                3000,
+                5,
+                FunctionExecutionStatus::Success,
+            )?,
+            record(
+                JobKind::Cron,
+                "cleanup",
+                4000,
+                5,
+                FunctionExecutionStatus::Failure,
+            )?,
+        ] {
+            model.insert(record).await?;
+        }
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = JobExecutionsModel::new(&mut tx);
+        assert_eq!(
+            query_ends(&mut model, filter()).await?,
+            vec![4000, 3000, 2000, 1000]
+        );
+        assert_eq!(
+            query_ends(
+                &mut model,
+                JobExecutionFilter {
+                    kind: Some(JobKind::Cron),
+                    since: Some(UnixTimestamp::from_millis(2000)),
+                    ..filter()
+                }
+            )
+            .await?,
+            vec![4000, 3000]
+        );
+        assert_eq!(
+            query_ends(
+                &mut model,
+                JobExecutionFilter {
+                    kind: Some(JobKind::Cron),
+                    job: Some("cleanup".to_string()),
+                    ..filter()
+                }
+            )
+            .await?,
+            vec![4000, 1000]
+        );
+        assert!(model
+            .query(JobExecutionFilter {
+                job: Some("cleanup".to_string()),
+                ..filter()
+            })
+            .await
+            .is_err());
+        let summaries = model
+            .summarize_crons(UnixTimestamp::from_millis(2000))
+            .await?;
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].name, "cleanup");
+        assert_eq!(summaries[0].num_runs, 1);
+
+        let expired = model.expired(UnixTimestamp::from_millis(3000), 100).await?;
+        assert_eq!(expired.len(), 2);
+        for id in expired {
+            model.delete(id).await?;
+        }
+        assert_eq!(query_ends(&mut model, filter()).await?, vec![4000, 3000]);
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::Unknown).await?;
+        assert!(JobExecutionsModel::new(&mut tx)
+            .query(filter())
+            .await
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    time::Duration,
+};
+
+use common::{
+    components::ComponentPath,
+    runtime::UnixTimestamp,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sync_types::CanonicalizedUdfPath;
+use value::codegen_convex_serialization;
+
+use crate::function_executions::types::{
+    FunctionExecutionStatus,
+    FunctionExecutionUsage,
+    SerializedFunctionExecutionUsage,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum JobKind {
+    Scheduled,
+    Cron,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::Scheduled => "scheduled",
+            JobKind::Cron => "cron",
+        }
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "scheduled" => Ok(JobKind::Scheduled),
+            "cron" => Ok(JobKind::Cron),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidJobKind",
+                format!("Unknown job kind {s}, expected \"scheduled\" or \"cron\""),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A finished run of a scheduled job or cron, retained in `_job_executions`
+/// for `JOB_EXECUTION_HISTORY_RETENTION`. Unlike a scheduled job's own
+/// document and the last few entries in `_cron_job_logs`, these are kept
+/// after the job is garbage collected so runs can be analyzed over time.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct JobExecutionRecord {
+    pub kind: JobKind,
+    // The scheduled job's ID, or the cron's name.
+    pub job: String,
+    pub component: ComponentPath,
+    pub udf_path: CanonicalizedUdfPath,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub start: UnixTimestamp,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub end: UnixTimestamp,
+    pub status: FunctionExecutionStatus,
+    pub error: Option<String>,
+    // Number of times the job was restarted before this run. Only workflows
+    // are restarted, so this is always 0 for crons.
+    pub attempts: u32,
+    pub usage: FunctionExecutionUsage,
+}
+
+impl JobExecutionRecord {
+    pub fn duration(&self) -> Duration {
+        self.end.checked_sub(self.start).unwrap_or(Duration::ZERO)
+    }
+}
+
+/// Which job executions to return from a query. Executions are returned
+/// newest first.
+#[derive(Clone, Debug)]
+pub struct JobExecutionFilter {
+    pub kind: Option<JobKind>,
+    /// Only set along with `kind`.
+    pub job: Option<String>,
+    /// Only runs that ended at or after this time.
+    pub since: Option<UnixTimestamp>,
+    pub limit: usize,
+}
+
+/// How a cron's recent runs went.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CronExecutionSummary {
+    pub component: ComponentPath,
+    pub name: String,
+    pub num_runs: u64,
+    pub num_failures: u64,
+    pub p95_duration: Duration,
+    pub last_run: UnixTimestamp,
+}
+
+impl CronExecutionSummary {
+    pub fn success_rate(&self) -> f64 {
+        if self.num_runs == 0 {
+            return 0.0;
+        }
+        (self.num_runs - self.num_failures) as f64 / self.num_runs as f64
+    }
+
+    /// Summarize cron runs by cron, in order of component and name.
+    pub fn summarize(executions: impl IntoIterator<Item = JobExecutionRecord>) -> Vec<Self> {
+        let mut by_cron: BTreeMap<(String, String), Vec<JobExecutionRecord>> = BTreeMap::new();
+        for execution in executions {
+            if execution.kind != JobKind::Cron {
+                continue;
+            }
+            by_cron
+                .entry((
+                    String::from(execution.component.clone()),
+                    execution.job.clone(),
+                ))
+                .or_default()
+                .push(execution);
+        }
+        by_cron
+            .into_values()
+            .filter_map(|runs| {
+                let first = runs.first()?;
+                let mut durations: Vec<_> = runs.iter().map(|run| run.duration()).collect();
+                durations.sort();
+                // The nearest-rank 95th percentile.
+                let p95_index = (durations.len() * 95).div_ceil(100).saturating_sub(1);
+                Some(Self {
+                    component: first.component.clone(),
+                    name: first.job.clone(),
+                    num_runs: runs.len() as u64,
+                    num_failures: runs
+                        .iter()
+                        .filter(|run| run.status == FunctionExecutionStatus::Failure)
+                        .count() as u64,
+                    p95_duration: durations[p95_index],
+                    last_run: runs.iter().map(|run| run.end).max()?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedJobExecutionRecord {
+    kind: String,
+    job: String,
+    component_path: String,
+    udf_path: String,
+    // Nanoseconds since the Unix epoch.
+    start: i64,
+    end: i64,
+    status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    attempts: i64,
+    usage: SerializedFunctionExecutionUsage,
+}
+
+impl TryFrom<JobExecutionRecord> for SerializedJobExecutionRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: JobExecutionRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            kind: record.kind.to_string(),
+            job: record.job,
+            component_path: record.component.into(),
+            udf_path: record.udf_path.into(),
+            start: record.start.as_nanos().try_into()?,
+            end: record.end.as_nanos().try_into()?,
+            status: record.status.to_string(),
+            error: record.error,
+            attempts: record.attempts.into(),
+            usage: record.usage.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedJobExecutionRecord> for JobExecutionRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: SerializedJobExecutionRecord) -> anyhow::Result<Self> {
+        Ok(Self {
+            kind: record.kind.parse()?,
+            job: record.job,
+            component: record.component_path.parse()?,
+            udf_path: record.udf_path.parse()?,
+            start: UnixTimestamp::from_nanos(record.start.try_into()?),
+            end: UnixTimestamp::from_nanos(record.end.try_into()?),
+            status: record.status.parse()?,
+            error: record.error,
+            attempts: record.attempts.try_into()?,
+            usage: record.usage.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(JobExecutionRecord, SerializedJobExecutionRecord);
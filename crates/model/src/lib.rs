@@ -53,6 +53,8 @@ pub use database::defaults::{
     SystemTable,
 };
 use database::{
+    sketches::SketchesTable,
+    trash::TrashTable,
     ComponentDefinitionsTable,
     ComponentsTable,
     Database,
@@ -78,41 +80,111 @@ use value::{
 };
 
 use crate::{
+    api_keys::ApiKeysTable,
     auth::AuthTable,
     backend_state::BackendStateModel,
+    built_in_auth::{
+        AuthAccountsTable,
+        AuthCodesTable,
+    },
     cron_jobs::{
         CronJobLogsTable,
         CronJobsTable,
     },
     deployment_audit_log::DeploymentAuditLogsTable,
+    deployment_clones::DeploymentClonesTable,
+    deployment_versions::DeploymentVersionsTable,
+    egress_policy::EgressPolicyTable,
     environment_variables::EnvironmentVariablesTable,
+    error_groups::ErrorGroupsTable,
     exports::ExportsTable,
     external_packages::ExternalPackagesTable,
+    file_lifecycle_rules::FileLifecycleRulesTable,
     file_storage::FileStorageTable,
+    file_upload_sessions::{
+        FileUploadPartsTable,
+        FileUploadSessionsTable,
+    },
+    function_executions::FunctionExecutionsTable,
+    function_canaries::FunctionCanariesTable,
+    function_flags::FunctionFlagsTable,
+    function_read_budgets::FunctionReadBudgetsTable,
+    idempotency_keys::IdempotencyKeysTable,
+    job_executions::JobExecutionsTable,
+    log_settings::LogSettingsTable,
+    log_sinks::LogSinksTable,
     modules::ModulesTable,
+    outbox::{
+        OutboxTable,
+        OutboxTargetsTable,
+    },
+    queues::{
+        QueueConsumersTable,
+        QueueMessagesTable,
+    },
+    revoked_sessions::RevokedSessionsTable,
+    scheduled_exports::{
+        ScheduledExportRunsTable,
+        ScheduledExportsTable,
+    },
+    scheduled_job_batches::ScheduledJobBatchesTable,
     scheduled_jobs::ScheduledJobsTable,
+    search_index_pins::SearchIndexPinsTable,
     session_requests::SessionRequestsTable,
+    slow_queries::SlowQueriesTable,
     snapshot_imports::SnapshotImportsTable,
     source_packages::SourcePackagesTable,
+    table_storage_snapshots::TableStorageSnapshotsTable,
+    traffic_shadowing::TrafficShadowingTable,
     udf_config::UdfConfigTable,
+    workflows::{
+        WorkflowStepsTable,
+        WorkflowsTable,
+    },
 };
 
+pub mod api_keys;
 pub mod auth;
 pub mod backend_state;
+pub mod built_in_auth;
 pub mod components;
 pub mod config;
 pub mod cron_jobs;
 pub mod deployment_audit_log;
+pub mod deployment_clones;
+pub mod deployment_versions;
+pub mod egress_policy;
 pub mod environment_variables;
+pub mod error_groups;
 pub mod exports;
 pub mod external_packages;
+pub mod file_lifecycle_rules;
 pub mod file_storage;
+pub mod file_upload_sessions;
+pub mod function_executions;
+pub mod function_canaries;
+pub mod function_flags;
+pub mod function_read_budgets;
+pub mod idempotency_keys;
+pub mod job_executions;
+pub mod log_settings;
+pub mod log_sinks;
 pub mod modules;
+pub mod outbox;
+pub mod queues;
+pub mod revoked_sessions;
+pub mod scheduled_exports;
+pub mod scheduled_job_batches;
 pub mod scheduled_jobs;
+pub mod search_index_pins;
 pub mod session_requests;
+pub mod slow_queries;
 pub mod snapshot_imports;
 pub mod source_packages;
+pub mod table_storage_snapshots;
+pub mod traffic_shadowing;
 pub mod udf_config;
+pub mod workflows;
 
 #[cfg(any(test, feature = "testing"))]
 pub mod test_helpers;
@@ -147,9 +219,43 @@ enum DefaultTableNumber {
     IndexWorkerMetadata = 30,
     ComponentDefinitionsTable = 31,
     ComponentsTable = 32,
+    WorkflowSteps = 33,
+    ScheduledJobBatches = 34,
+    QueueConsumers = 35,
+    QueueMessages = 36,
+    IdempotencyKeys = 37,
+    ApiKeys = 38,
+    RevokedSessions = 39,
+    AuthAccounts = 40,
+    AuthCodes = 41,
+    LogSinks = 42,
+    FunctionExecutions = 43,
+    ErrorGroups = 44,
+    SlowQueries = 45,
+    ScheduledExports = 46,
+    ScheduledExportRuns = 47,
+    DeploymentClones = 48,
+    FileUploadSessions = 49,
+    FileUploadParts = 50,
+    FileLifecycleRules = 51,
+    Trash = 52,
+    FunctionFlags = 53,
+    EgressPolicy = 54,
+    SearchIndexPins = 55,
+    TableStorageSnapshots = 56,
+    OutboxTargets = 57,
+    Outbox = 58,
+    TrafficShadowing = 59,
+    FunctionReadBudgets = 60,
+    Sketches = 61,
+    LogSettings = 62,
+    DeploymentVersions = 63,
+    FunctionCanaries = 64,
+    JobExecutions = 65,
+    Workflows = 66,
     // Keep this number and your user name up to date. The number makes it easy to know
     // what to use next. The username on the same line detects merge conflicts
-    // Next Number - 33 - lee
+    // Next Number - 67 - lee
 }
 
 impl From<DefaultTableNumber> for TableNumber {
@@ -187,6 +293,40 @@ impl From<DefaultTableNumber> for TableName {
             DefaultTableNumber::IndexWorkerMetadata => IndexWorkerMetadataTable.table_name(),
             DefaultTableNumber::ComponentDefinitionsTable => ComponentDefinitionsTable.table_name(),
             DefaultTableNumber::ComponentsTable => ComponentsTable.table_name(),
+            DefaultTableNumber::WorkflowSteps => WorkflowStepsTable.table_name(),
+            DefaultTableNumber::ScheduledJobBatches => ScheduledJobBatchesTable.table_name(),
+            DefaultTableNumber::QueueConsumers => QueueConsumersTable.table_name(),
+            DefaultTableNumber::QueueMessages => QueueMessagesTable.table_name(),
+            DefaultTableNumber::IdempotencyKeys => IdempotencyKeysTable.table_name(),
+            DefaultTableNumber::ApiKeys => ApiKeysTable.table_name(),
+            DefaultTableNumber::RevokedSessions => RevokedSessionsTable.table_name(),
+            DefaultTableNumber::AuthAccounts => AuthAccountsTable.table_name(),
+            DefaultTableNumber::AuthCodes => AuthCodesTable.table_name(),
+            DefaultTableNumber::LogSinks => LogSinksTable.table_name(),
+            DefaultTableNumber::FunctionExecutions => FunctionExecutionsTable.table_name(),
+            DefaultTableNumber::ErrorGroups => ErrorGroupsTable.table_name(),
+            DefaultTableNumber::SlowQueries => SlowQueriesTable.table_name(),
+            DefaultTableNumber::ScheduledExports => ScheduledExportsTable.table_name(),
+            DefaultTableNumber::ScheduledExportRuns => ScheduledExportRunsTable.table_name(),
+            DefaultTableNumber::DeploymentClones => DeploymentClonesTable.table_name(),
+            DefaultTableNumber::FileUploadSessions => FileUploadSessionsTable.table_name(),
+            DefaultTableNumber::FileUploadParts => FileUploadPartsTable.table_name(),
+            DefaultTableNumber::FileLifecycleRules => FileLifecycleRulesTable.table_name(),
+            DefaultTableNumber::Trash => TrashTable.table_name(),
+            DefaultTableNumber::FunctionFlags => FunctionFlagsTable.table_name(),
+            DefaultTableNumber::EgressPolicy => EgressPolicyTable.table_name(),
+            DefaultTableNumber::SearchIndexPins => SearchIndexPinsTable.table_name(),
+            DefaultTableNumber::TableStorageSnapshots => TableStorageSnapshotsTable.table_name(),
+            DefaultTableNumber::OutboxTargets => OutboxTargetsTable.table_name(),
+            DefaultTableNumber::Outbox => OutboxTable.table_name(),
+            DefaultTableNumber::TrafficShadowing => TrafficShadowingTable.table_name(),
+            DefaultTableNumber::FunctionReadBudgets => FunctionReadBudgetsTable.table_name(),
+            DefaultTableNumber::Sketches => SketchesTable.table_name(),
+            DefaultTableNumber::LogSettings => LogSettingsTable.table_name(),
+            DefaultTableNumber::DeploymentVersions => DeploymentVersionsTable.table_name(),
+            DefaultTableNumber::FunctionCanaries => FunctionCanariesTable.table_name(),
+            DefaultTableNumber::JobExecutions => JobExecutionsTable.table_name(),
+            DefaultTableNumber::Workflows => WorkflowsTable.table_name(),
         }
         .clone()
     }
@@ -315,6 +455,33 @@ pub fn app_system_tables() -> Vec<&'static dyn SystemTable> {
         &AuthTable,
         &ExternalPackagesTable,
         &SessionRequestsTable,
+        &IdempotencyKeysTable,
+        &ApiKeysTable,
+        &RevokedSessionsTable,
+        &AuthAccountsTable,
+        &AuthCodesTable,
+        &LogSinksTable,
+        &FunctionExecutionsTable,
+        &ErrorGroupsTable,
+        &SlowQueriesTable,
+        &ScheduledExportsTable,
+        &ScheduledExportRunsTable,
+        &DeploymentClonesTable,
+        &FileUploadSessionsTable,
+        &FileUploadPartsTable,
+        &FileLifecycleRulesTable,
+        &FunctionFlagsTable,
+        &EgressPolicyTable,
+        &SearchIndexPinsTable,
+        &TableStorageSnapshotsTable,
+        &OutboxTargetsTable,
+        &OutboxTable,
+        &TrafficShadowingTable,
+        &FunctionReadBudgetsTable,
+        &LogSettingsTable,
+        &DeploymentVersionsTable,
+        &FunctionCanariesTable,
+        &JobExecutionsTable,
         &BackendStateTable,
         &ExportsTable,
         &SnapshotImportsTable,
@@ -334,6 +501,13 @@ pub fn component_system_tables() -> Vec<&'static dyn SystemTable> {
         &ModulesTable,
         &UdfConfigTable,
         &SourcePackagesTable,
+        &WorkflowStepsTable,
+        &WorkflowsTable,
+        &ScheduledJobBatchesTable,
+        &QueueConsumersTable,
+        &QueueMessagesTable,
+        &TrashTable,
+        &SketchesTable,
     ]
 }
 
@@ -0,0 +1,328 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use sync_types::CanonicalizedUdfPath;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    LogSetting,
+    LogSettings,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Log level thresholds and sampling rates for function logs, applied when
+/// the function log collects an execution's lines.
+pub static LOG_SETTINGS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_log_settings"
+        .parse()
+        .expect("Invalid built-in log settings table")
+});
+
+pub struct LogSettingsTable;
+impl SystemTable for LogSettingsTable {
+    fn table_name(&self) -> &'static TableName {
+        &LOG_SETTINGS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<LogSetting>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct LogSettingsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> LogSettingsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<LogSetting>>> {
+        self.check_admin("list_log_settings")?;
+        let query = Query::full_table_scan(LOG_SETTINGS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut settings = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            settings.push(doc.try_into()?);
+        }
+        Ok(settings)
+    }
+
+    /// The setting for `udf_path`, or the deployment-wide setting if
+    /// `udf_path` is None.
+    pub async fn get(
+        &mut self,
+        udf_path: Option<&CanonicalizedUdfPath>,
+    ) -> anyhow::Result<Option<ParsedDocument<LogSetting>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|setting| setting.udf_path.as_ref() == udf_path))
+    }
+
+    pub async fn settings(&mut self) -> anyhow::Result<LogSettings> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .map(|setting| setting.into_value())
+            .collect())
+    }
+
+    /// Set the log setting for a function, or the deployment-wide setting,
+    /// replacing any existing one.
+    pub async fn set(&mut self, setting: LogSetting) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("set_log_setting")?;
+        setting.validate()?;
+        match self.get(setting.udf_path.as_ref()).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), setting.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&LOG_SETTINGS_TABLE, setting.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Remove a log setting, so every line the function logs is kept again
+    /// unless a deployment-wide setting applies.
+    pub async fn delete(&mut self, udf_path: Option<&CanonicalizedUdfPath>) -> anyhow::Result<()> {
+        self.check_admin("delete_log_setting")?;
+        let Some(existing) = self.get(udf_path).await? else {
+            let message = match udf_path {
+                Some(udf_path) => format!(
+                    "There is no log setting for function {:?}",
+                    String::from(udf_path.clone())
+                ),
+                None => "There is no deployment-wide log setting".to_string(),
+            };
+            anyhow::bail!(ErrorMetadata::not_found("LogSettingNotFound", message));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        execution_context::ExecutionId,
+        log_lines::{
+            LogLevel,
+            LogLine,
+            LogLines,
+        },
+        runtime::UnixTimestamp,
+    };
+    use database::test_helpers::DbFixtures;
+    use keybroker::Identity;
+    use proptest::prelude::*;
+    use runtime::testing::TestRuntime;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use crate::{
+        log_settings::{
+            types::{
+                LogSetting,
+                LogSettings,
+            },
+            LogSettingsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn setting(
+        udf_path: Option<&str>,
+        min_level: Option<LogLevel>,
+        sample_rate: Option<f64>,
+    ) -> anyhow::Result<LogSetting> {
+        Ok(LogSetting {
+            udf_path: udf_path.map(|path| path.parse()).transpose()?,
+            min_level,
+            sample_rate,
+        })
+    }
+
+    fn lines(levels: &[LogLevel]) -> LogLines {
+        levels
+            .iter()
+            .map(|level| {
+                LogLine::new_developer_log_line(
+                    level.clone(),
+                    vec!["message".to_string()],
+                    UnixTimestamp::from_millis(0),
+                )
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    fn levels(log_lines: LogLines) -> Vec<LogLevel> {
+        log_lines
+            .into_iter()
+            .map(|LogLine::Structured { level, .. }| level)
+            .collect()
+    }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_log_setting_roundtrips(v in any::<LogSetting>()) {
+            assert_roundtrips::<LogSetting, ConvexObject>(v);
+        }
+    }
+
+    #[test]
+    fn test_validate() -> anyhow::Result<()> {
+        assert!(setting(None, Some(LogLevel::Warn), None)?
+            .validate()
+            .is_ok());
+        assert!(setting(Some("messages.js:list"), None, Some(0.1))?
+            .validate()
+            .is_ok());
+        assert!(setting(None, None, None)?.validate().is_err());
+        assert!(setting(None, None, Some(1.5))?.validate().is_err());
+        assert!(setting(Some("_system/cli/tables.js"), None, Some(0.1))?
+            .validate()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter() -> anyhow::Result<()> {
+        let all = [
+            LogLevel::Debug,
+            LogLevel::Log,
+            LogLevel::Info,
+            LogLevel::Warn,
+            LogLevel::Error,
+        ];
+        let list = "messages.js:list".parse()?;
+        let send = "messages.js:send".parse()?;
+        let execution_id = ExecutionId::new();
+        let settings: LogSettings = [
+            setting(None, Some(LogLevel::Info), None)?,
+            setting(Some("messages.js:list"), None, Some(0.0))?,
+        ]
+        .into_iter()
+        .collect();
+
+        // The deployment-wide threshold applies to functions without their
+        // own setting, and to HTTP actions.
+        assert_eq!(
+            levels(settings.filter(Some(&send), &execution_id, lines(&all))),
+            vec![
+                LogLevel::Log,
+                LogLevel::Info,
+                LogLevel::Warn,
+                LogLevel::Error
+            ],
+        );
+        assert_eq!(
+            levels(settings.filter(None, &execution_id, lines(&all))).len(),
+            4
+        );
+
+        // A function sampled out of logging still keeps its errors.
+        assert_eq!(
+            levels(settings.filter(Some(&list), &execution_id, lines(&all))),
+            vec![LogLevel::Error],
+        );
+
+        // Without settings, every line is kept.
+        assert_eq!(
+            levels(LogSettings::default().filter(Some(&list), &execution_id, lines(&all))),
+            all.to_vec(),
+        );
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_log_settings(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = LogSettingsModel::new(&mut tx);
+        let default = setting(None, Some(LogLevel::Warn), None)?;
+        let id = model.set(default.clone()).await?;
+        let list = setting(Some("messages.js:list"), Some(LogLevel::Debug), Some(0.5))?;
+        model.set(list.clone()).await?;
+
+        // Setting the deployment-wide setting again replaces it.
+        let default = setting(None, Some(LogLevel::Error), None)?;
+        assert_eq!(model.set(default.clone()).await?, id);
+        assert_eq!(model.list().await?.len(), 2);
+        assert_eq!(
+            model.settings().await?,
+            [default.clone(), list.clone()].into_iter().collect()
+        );
+        db.commit(tx).await?;
+
+        let mut tx = db.begin(Identity::Unknown).await?;
+        assert!(LogSettingsModel::new(&mut tx).list().await.is_err());
+
+        let mut tx = db.begin_system().await?;
+        let mut model = LogSettingsModel::new(&mut tx);
+        model.delete(None).await?;
+        assert_eq!(
+            model.settings().await?,
+            LogSettings {
+                default: None,
+                functions: [(list.udf_path.clone().unwrap(), list.clone())].into(),
+            }
+        );
+        assert!(model.delete(None).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::BTreeMap,
+    hash::{
+        DefaultHasher,
+        Hash,
+        Hasher,
+    },
+};
+
+use common::{
+    execution_context::ExecutionId,
+    log_lines::{
+        LogLevel,
+        LogLine,
+        LogLines,
+    },
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sync_types::CanonicalizedUdfPath;
+use value::codegen_convex_serialization;
+
+/// How much of a function's `console.*` output is kept. A setting without a
+/// `udf_path` applies to every function that doesn't have its own.
+///
+/// Errors and system log lines are always kept, so a setting can quiet noisy
+/// debug logging without hiding failures.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct LogSetting {
+    pub udf_path: Option<CanonicalizedUdfPath>,
+    /// Lines below this level are dropped.
+    pub min_level: Option<LogLevel>,
+    /// The fraction of executions whose lines are kept, from 0 to 1.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(0.0..=1.0f64)")
+    )]
+    pub sample_rate: Option<f64>,
+}
+
+/// Where a level falls when compared to a `min_level`. `console.log` and
+/// `console.info` are equally severe.
+fn severity(level: &LogLevel) -> u8 {
+    match level {
+        LogLevel::Debug => 0,
+        LogLevel::Log | LogLevel::Info => 1,
+        LogLevel::Warn => 2,
+        LogLevel::Error => 3,
+    }
+}
+
+impl LogSetting {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid =
+            |msg: String| anyhow::anyhow!(ErrorMetadata::bad_request("InvalidLogSetting", msg));
+        if let Some(udf_path) = &self.udf_path
+            && udf_path.is_system()
+        {
+            return Err(invalid(format!(
+                "Cannot configure logs of system function {:?}",
+                String::from(udf_path.clone())
+            )));
+        }
+        if self.min_level.is_none() && self.sample_rate.is_none() {
+            return Err(invalid(
+                "A log setting needs minLevel, sampleRate or both".to_string(),
+            ));
+        }
+        if let Some(sample_rate) = self.sample_rate
+            && !(0.0..=1.0).contains(&sample_rate)
+        {
+            return Err(invalid(format!(
+                "sampleRate must be between 0 and 1, got {sample_rate}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Every log setting in the deployment, resolved for filtering log lines as
+/// they're collected.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogSettings {
+    pub default: Option<LogSetting>,
+    pub functions: BTreeMap<CanonicalizedUdfPath, LogSetting>,
+}
+
+impl FromIterator<LogSetting> for LogSettings {
+    fn from_iter<T: IntoIterator<Item = LogSetting>>(iter: T) -> Self {
+        let mut settings = Self::default();
+        for setting in iter {
+            match setting.udf_path.clone() {
+                Some(udf_path) => {
+                    settings.functions.insert(udf_path, setting);
+                },
+                None => settings.default = Some(setting),
+            }
+        }
+        settings
+    }
+}
+
+impl LogSettings {
+    /// Drop the lines from one execution of `udf_path` that its setting
+    /// excludes. HTTP actions are passed without a path, so only the
+    /// deployment-wide setting applies to them.
+    ///
+    /// Sampling is decided by `execution_id`, so lines streamed while an
+    /// action runs and lines logged when it completes are kept or dropped
+    /// together.
+    pub fn filter(
+        &self,
+        udf_path: Option<&CanonicalizedUdfPath>,
+        execution_id: &ExecutionId,
+        log_lines: LogLines,
+    ) -> LogLines {
+        let setting = udf_path.and_then(|udf_path| self.functions.get(udf_path));
+        // A function's own setting overrides the deployment-wide one field by
+        // field.
+        let min_level = setting
+            .and_then(|setting| setting.min_level.as_ref())
+            .or_else(|| self.default.as_ref()?.min_level.as_ref());
+        let sample_rate = setting
+            .and_then(|setting| setting.sample_rate)
+            .or_else(|| self.default.as_ref()?.sample_rate);
+        if min_level.is_none() && sample_rate.is_none() {
+            return log_lines;
+        }
+        let sampled_out = sample_rate.is_some_and(|rate| !is_sampled(execution_id, rate));
+        log_lines
+            .into_iter()
+            .filter(|line| {
+                let LogLine::Structured {
+                    level,
+                    system_metadata,
+                    ..
+                } = line;
+                if *level == LogLevel::Error || system_metadata.is_some() {
+                    return true;
+                }
+                !sampled_out && min_level.map_or(true, |min| severity(level) >= severity(min))
+            })
+            .collect()
+    }
+}
+
+fn is_sampled(execution_id: &ExecutionId, sample_rate: f64) -> bool {
+    let mut hasher = DefaultHasher::new();
+    execution_id.to_string().hash(&mut hasher);
+    (hasher.finish() as f64 / u64::MAX as f64) < sample_rate
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedLogSetting {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    udf_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sample_rate: Option<f64>,
+}
+
+impl TryFrom<LogSetting> for SerializedLogSetting {
+    type Error = anyhow::Error;
+
+    fn try_from(setting: LogSetting) -> anyhow::Result<Self> {
+        Ok(Self {
+            udf_path: setting.udf_path.map(String::from),
+            min_level: setting.min_level.map(|level| level.to_string()),
+            sample_rate: setting.sample_rate,
+        })
+    }
+}
+
+impl TryFrom<SerializedLogSetting> for LogSetting {
+    type Error = anyhow::Error;
+
+    fn try_from(setting: SerializedLogSetting) -> anyhow::Result<Self> {
+        Ok(Self {
+            udf_path: setting.udf_path.map(|path| path.parse()).transpose()?,
+            min_level: setting.min_level.map(|level| level.parse()).transpose()?,
+            sample_rate: setting.sample_rate,
+        })
+    }
+}
+
+codegen_convex_serialization!(LogSetting, SerializedLogSetting);
@@ -0,0 +1,183 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    LogRoutingRules,
+    LogSinksRow,
+    SinkConfig,
+    SinkState,
+    SinkType,
+};
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Table of the external services, like Datadog or Splunk, that the
+/// deployment's logs are streamed to.
+pub static LOG_SINKS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_log_sinks"
+        .parse()
+        .expect("Invalid built-in log sinks table")
+});
+
+pub struct LogSinksTable;
+impl SystemTable for LogSinksTable {
+    fn table_name(&self) -> &'static TableName {
+        &LOG_SINKS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<LogSinksRow>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct LogSinksModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> LogSinksModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn get_all(&mut self) -> anyhow::Result<Vec<ParsedDocument<LogSinksRow>>> {
+        self.check_admin("list_log_sinks")?;
+        let query = Query::full_table_scan(LOG_SINKS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut sinks = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            sinks.push(doc.try_into()?);
+        }
+        Ok(sinks)
+    }
+
+    pub async fn get_by_type(
+        &mut self,
+        sink_type: SinkType,
+    ) -> anyhow::Result<Option<ParsedDocument<LogSinksRow>>> {
+        Ok(self
+            .get_all()
+            .await?
+            .into_iter()
+            .find(|sink| sink.config.sink_type() == sink_type))
+    }
+
+    /// Configure a sink, replacing any existing sink of the same type. The
+    /// sink starts out pending until the log manager picks it up.
+    pub async fn add_or_update(
+        &mut self,
+        config: SinkConfig,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("add_log_sink")?;
+        let row = LogSinksRow {
+            config,
+            status: SinkState::Pending,
+        };
+        match self.get_by_type(row.config.sink_type()).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), row.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&LOG_SINKS_TABLE, row.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Change which events a sink receives, without restarting it.
+    pub async fn set_rules(
+        &mut self,
+        sink_type: SinkType,
+        rules: LogRoutingRules,
+    ) -> anyhow::Result<()> {
+        self.check_admin("set_log_sink_rules")?;
+        let Some(existing) = self.get_by_type(sink_type).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "LogSinkNotFound",
+                format!("There is no {sink_type} log sink"),
+            ));
+        };
+        let id = existing.id();
+        let mut row = existing.into_value();
+        row.rules = rules;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, row.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete(&mut self, sink_type: SinkType) -> anyhow::Result<()> {
+        self.check_admin("delete_log_sink")?;
+        let Some(existing) = self.get_by_type(sink_type).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "LogSinkNotFound",
+                format!("There is no {sink_type} log sink"),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_status(
+        &mut self,
+        id: ResolvedDocumentId,
+        status: SinkState,
+    ) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("set_log_sink_status"));
+        }
+        let Some(document) = self.tx.get(id).await? else {
+            anyhow::bail!("Log sink {id} not found");
+        };
+        let mut row: ParsedDocument<LogSinksRow> = document.try_into()?;
+        row.status = status;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, row.into_value().try_into()?)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,450 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use common::{
+    log_lines::LogLevel,
+    log_streaming::LogEventFormatVersion,
+};
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// The service a log sink delivers logs to. A deployment has at most one sink
+/// of each type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum SinkType {
+    Datadog,
+    Splunk,
+    Sentry,
+}
+
+impl SinkType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SinkType::Datadog => "datadog",
+            SinkType::Splunk => "splunk",
+            SinkType::Sentry => "sentry",
+        }
+    }
+}
+
+impl FromStr for SinkType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "datadog" => Ok(SinkType::Datadog),
+            "splunk" => Ok(SinkType::Splunk),
+            "sentry" => Ok(SinkType::Sentry),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSinkType",
+                format!(
+                    "Unknown log sink type {s}, expected \"datadog\", \"splunk\" or \"sentry\""
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SinkType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The Datadog site that logs are sent to, see
+/// <https://docs.datadoghq.com/getting_started/site/>.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum DatadogSiteLocation {
+    US1,
+    US3,
+    US5,
+    EU,
+    US1Fed,
+    AP1,
+}
+
+impl DatadogSiteLocation {
+    /// Host of the site's HTTP log intake.
+    pub fn intake_host(&self) -> &'static str {
+        match self {
+            DatadogSiteLocation::US1 => "http-intake.logs.datadoghq.com",
+            DatadogSiteLocation::US3 => "http-intake.logs.us3.datadoghq.com",
+            DatadogSiteLocation::US5 => "http-intake.logs.us5.datadoghq.com",
+            DatadogSiteLocation::EU => "http-intake.logs.datadoghq.eu",
+            DatadogSiteLocation::US1Fed => "http-intake.logs.ddog-gov.com",
+            DatadogSiteLocation::AP1 => "http-intake.logs.ap1.datadoghq.com",
+        }
+    }
+}
+
+impl FromStr for DatadogSiteLocation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "US1" => Ok(DatadogSiteLocation::US1),
+            "US3" => Ok(DatadogSiteLocation::US3),
+            "US5" => Ok(DatadogSiteLocation::US5),
+            "EU" => Ok(DatadogSiteLocation::EU),
+            "US1_FED" => Ok(DatadogSiteLocation::US1Fed),
+            "AP1" => Ok(DatadogSiteLocation::AP1),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidDatadogSiteLocation",
+                format!("Unknown Datadog site location {s}"),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DatadogSiteLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            DatadogSiteLocation::US1 => "US1",
+            DatadogSiteLocation::US3 => "US3",
+            DatadogSiteLocation::US5 => "US5",
+            DatadogSiteLocation::EU => "EU",
+            DatadogSiteLocation::US1Fed => "US1_FED",
+            DatadogSiteLocation::AP1 => "AP1",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Sends logs to Datadog's HTTP log intake.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct DatadogConfig {
+    pub site_location: DatadogSiteLocation,
+    pub dd_api_key: String,
+    pub dd_tags: Vec<String>,
+    // Reported as the `service` of each log. Defaults to the deployment name.
+    pub service: Option<String>,
+    pub version: LogEventFormatVersion,
+}
+
+/// Sends logs to a Splunk HTTP Event Collector.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SplunkConfig {
+    // URL of the collector's event endpoint, e.g.
+    // `https://splunk.example.com:8088/services/collector/event`.
+    pub hec_endpoint: String,
+    pub token: String,
+    pub index: Option<String>,
+    pub source_type: Option<String>,
+    pub version: LogEventFormatVersion,
+}
+
+/// Reports exceptions thrown by functions to a Sentry project. Other logs
+/// aren't sent to Sentry.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SentryConfig {
+    // The project's DSN, e.g. `https://<key>@o1.ingest.sentry.io/<project>`.
+    pub dsn: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum SinkConfig {
+    Datadog(DatadogConfig),
+    Splunk(SplunkConfig),
+    Sentry(SentryConfig),
+}
+
+impl SinkConfig {
+    pub fn sink_type(&self) -> SinkType {
+        match self {
+            SinkConfig::Datadog(_) => SinkType::Datadog,
+            SinkConfig::Splunk(_) => SinkType::Splunk,
+            SinkConfig::Sentry(_) => SinkType::Sentry,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum SinkState {
+    // Configured, but not yet picked up by the log manager.
+    Pending,
+    Active,
+    Failed { reason: String },
+}
+
+/// The kinds of log event that routing rules can select.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum LogEventType {
+    Console,
+    FunctionExecution,
+    Exception,
+    AuditLog,
+}
+
+impl LogEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogEventType::Console => "console",
+            LogEventType::FunctionExecution => "function_execution",
+            LogEventType::Exception => "exception",
+            LogEventType::AuditLog => "audit_log",
+        }
+    }
+}
+
+impl FromStr for LogEventType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "console" => Ok(LogEventType::Console),
+            "function_execution" => Ok(LogEventType::FunctionExecution),
+            "exception" => Ok(LogEventType::Exception),
+            "audit_log" => Ok(LogEventType::AuditLog),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidLogEventType",
+                format!(
+                    "Unknown log event type {s}, expected one of \"console\", \
+                     \"function_execution\", \"exception\" or \"audit_log\""
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LogEventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Which log events a sink receives. An event is routed to the sink if it
+/// matches every non-empty list, so the default rules route everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct LogRoutingRules {
+    pub event_types: Vec<LogEventType>,
+    /// Function paths like `messages:send`, or prefixes of function paths
+    /// ending in `*` like `admin/*`. Events that don't come from a function,
+    /// like audit logs, never match.
+    pub function_paths: Vec<String>,
+    /// Console logs match by their level. Exceptions and failed function
+    /// executions count as `ERROR`, and all other events as `INFO`.
+    pub log_levels: Vec<LogLevel>,
+}
+
+/// A configured log sink and whether the log manager is delivering to it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct LogSinksRow {
+    pub config: SinkConfig,
+    pub status: SinkState,
+    pub rules: LogRoutingRules,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedSinkConfig {
+    #[serde(rename_all = "camelCase")]
+    Datadog {
+        site_location: String,
+        dd_api_key: String,
+        dd_tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        service: Option<String>,
+        version: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Splunk {
+        hec_endpoint: String,
+        token: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        index: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_type: Option<String>,
+        version: String,
+    },
+    Sentry {
+        dsn: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedSinkState {
+    Pending,
+    Active,
+    Failed { reason: String },
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct SerializedLogRoutingRules {
+    #[serde(default)]
+    event_types: Vec<String>,
+    #[serde(default)]
+    function_paths: Vec<String>,
+    #[serde(default)]
+    log_levels: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedLogSinksRow {
+    config: SerializedSinkConfig,
+    status: SerializedSinkState,
+    // Sinks configured before routing rules existed receive every event.
+    #[serde(default)]
+    rules: SerializedLogRoutingRules,
+}
+
+impl From<SinkConfig> for SerializedSinkConfig {
+    fn from(config: SinkConfig) -> Self {
+        match config {
+            SinkConfig::Datadog(DatadogConfig {
+                site_location,
+                dd_api_key,
+                dd_tags,
+                service,
+                version,
+            }) => SerializedSinkConfig::Datadog {
+                site_location: site_location.to_string(),
+                dd_api_key,
+                dd_tags,
+                service,
+                version: version.to_string(),
+            },
+            SinkConfig::Splunk(SplunkConfig {
+                hec_endpoint,
+                token,
+                index,
+                source_type,
+                version,
+            }) => SerializedSinkConfig::Splunk {
+                hec_endpoint,
+                token,
+                index,
+                source_type,
+                version: version.to_string(),
+            },
+            SinkConfig::Sentry(SentryConfig { dsn }) => SerializedSinkConfig::Sentry { dsn },
+        }
+    }
+}
+
+impl TryFrom<SerializedSinkConfig> for SinkConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(config: SerializedSinkConfig) -> anyhow::Result<Self> {
+        Ok(match config {
+            SerializedSinkConfig::Datadog {
+                site_location,
+                dd_api_key,
+                dd_tags,
+                service,
+                version,
+            } => SinkConfig::Datadog(DatadogConfig {
+                site_location: site_location.parse()?,
+                dd_api_key,
+                dd_tags,
+                service,
+                version: version.parse()?,
+            }),
+            SerializedSinkConfig::Splunk {
+                hec_endpoint,
+                token,
+                index,
+                source_type,
+                version,
+            } => SinkConfig::Splunk(SplunkConfig {
+                hec_endpoint,
+                token,
+                index,
+                source_type,
+                version: version.parse()?,
+            }),
+            SerializedSinkConfig::Sentry { dsn } => SinkConfig::Sentry(SentryConfig { dsn }),
+        })
+    }
+}
+
+impl From<LogRoutingRules> for SerializedLogRoutingRules {
+    fn from(rules: LogRoutingRules) -> Self {
+        Self {
+            event_types: rules
+                .event_types
+                .iter()
+                .map(|event_type| event_type.to_string())
+                .collect(),
+            function_paths: rules.function_paths,
+            log_levels: rules
+                .log_levels
+                .iter()
+                .map(|level| level.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<SerializedLogRoutingRules> for LogRoutingRules {
+    type Error = anyhow::Error;
+
+    fn try_from(rules: SerializedLogRoutingRules) -> anyhow::Result<Self> {
+        Ok(Self {
+            event_types: rules
+                .event_types
+                .iter()
+                .map(|event_type| event_type.parse())
+                .collect::<anyhow::Result<_>>()?,
+            function_paths: rules.function_paths,
+            log_levels: rules
+                .log_levels
+                .iter()
+                .map(|level| level.parse())
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<LogSinksRow> for SerializedLogSinksRow {
+    type Error = anyhow::Error;
+
+    fn try_from(row: LogSinksRow) -> anyhow::Result<Self> {
+        let status = match row.status {
+            SinkState::Pending => SerializedSinkState::Pending,
+            SinkState::Active => SerializedSinkState::Active,
+            SinkState::Failed { reason } => SerializedSinkState::Failed { reason },
+        };
+        Ok(Self {
+            config: row.config.into(),
+            status,
+            rules: row.rules.into(),
+        })
+    }
+}
+
+impl TryFrom<SerializedLogSinksRow> for LogSinksRow {
+    type Error = anyhow::Error;
+
+    fn try_from(row: SerializedLogSinksRow) -> anyhow::Result<Self> {
+        let status = match row.status {
+            SerializedSinkState::Pending => SinkState::Pending,
+            SerializedSinkState::Active => SinkState::Active,
+            SerializedSinkState::Failed { reason } => SinkState::Failed { reason },
+        };
+        Ok(Self {
+            config: row.config.try_into()?,
+            status,
+            rules: row.rules.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(LogSinksRow, SerializedLogSinksRow);
@@ -0,0 +1,301 @@
+//! Static analysis of which tables a function reads and writes and which
+//! functions it calls or schedules, for impact analysis before schema
+//! changes.
+//!
+//! The analysis looks at the bundled source of each function's handler
+//! during module analysis, so it's best effort: accesses made through helper
+//! functions defined outside the handler aren't seen, and accesses with
+//! computed table names or function references are only recorded as unknown.
+
+use std::collections::BTreeSet;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use sync_types::{
+    CanonicalizedUdfPath,
+    UdfPath,
+};
+use value::TableName;
+
+/// Methods on `ctx.db` that read documents.
+const READ_METHODS: [&str; 2] = ["db.query(", "db.get("];
+/// Methods on `ctx.db` that write documents.
+const WRITE_METHODS: [&str; 4] = ["db.insert(", "db.patch(", "db.replace(", "db.delete("];
+/// Methods that call another function and wait for its result.
+const CALL_METHODS: [&str; 3] = ["runQuery(", "runMutation(", "runAction("];
+/// Methods on `ctx.scheduler` that take a time and then a function reference.
+const SCHEDULE_METHODS: [&str; 2] = ["runAfter(", "runAt("];
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct FunctionDependencies {
+    pub reads_tables: BTreeSet<TableName>,
+    pub writes_tables: BTreeSet<TableName>,
+    /// Functions called with `runQuery`, `runMutation` or `runAction`.
+    pub calls: BTreeSet<CanonicalizedUdfPath>,
+    /// Functions scheduled with `runAfter` or `runAt`.
+    pub schedules: BTreeSet<CanonicalizedUdfPath>,
+    /// The function reads documents without naming their table, e.g. with
+    /// `db.get(id)`.
+    pub reads_unknown_tables: bool,
+    /// The function writes documents without naming their table, e.g. with
+    /// `db.patch(id, ...)`.
+    pub writes_unknown_tables: bool,
+    /// The function calls or schedules a function that isn't referenced
+    /// through `api` or `internal`, e.g. a component function or one whose
+    /// reference is computed.
+    pub references_unknown_functions: bool,
+}
+
+impl FunctionDependencies {
+    pub fn from_source(source: &str) -> Self {
+        let mut dependencies = Self::default();
+        for method in READ_METHODS {
+            for args in method_calls(source, method) {
+                match table_argument(args) {
+                    Some(table) => {
+                        dependencies.reads_tables.insert(table);
+                    },
+                    None => dependencies.reads_unknown_tables = true,
+                }
+            }
+        }
+        for method in WRITE_METHODS {
+            for args in method_calls(source, method) {
+                match table_argument(args) {
+                    Some(table) => {
+                        dependencies.writes_tables.insert(table);
+                    },
+                    None => dependencies.writes_unknown_tables = true,
+                }
+            }
+        }
+        for method in CALL_METHODS {
+            for args in method_calls(source, method) {
+                match function_reference(args) {
+                    Some(path) => {
+                        dependencies.calls.insert(path);
+                    },
+                    None => dependencies.references_unknown_functions = true,
+                }
+            }
+        }
+        for method in SCHEDULE_METHODS {
+            for args in method_calls(source, method) {
+                match skip_argument(args).and_then(function_reference) {
+                    Some(path) => {
+                        dependencies.schedules.insert(path);
+                    },
+                    None => dependencies.references_unknown_functions = true,
+                }
+            }
+        }
+        dependencies
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// The source following each call of `method`, which ends with the opening
+/// parenthesis.
+fn method_calls<'a>(source: &'a str, method: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    source.match_indices(method).filter_map(move |(i, _)| {
+        // Skip e.g. `mydb.query(`.
+        if source[..i].ends_with(is_identifier_char) {
+            return None;
+        }
+        Some(&source[i + method.len()..])
+    })
+}
+
+/// A string literal without escapes or substitutions at the start of `s`.
+fn string_literal(s: &str) -> Option<&str> {
+    let s = s.trim_start();
+    let quote = s.chars().next().filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let literal = &s[1..s[1..].find(quote)? + 1];
+    if literal.contains('\\') || (quote == '`' && literal.contains("${")) {
+        return None;
+    }
+    Some(literal)
+}
+
+fn table_argument(args: &str) -> Option<TableName> {
+    string_literal(args)?.parse().ok()
+}
+
+/// A function reference like `api.messages.send`, `internal.foo.bar.baz` or
+/// `"messages:send"` at the start of `s`.
+fn function_reference(s: &str) -> Option<CanonicalizedUdfPath> {
+    if let Some(literal) = string_literal(s) {
+        return literal.parse::<UdfPath>().ok().map(UdfPath::canonicalize);
+    }
+    let s = s.trim_start();
+    let end = s
+        .find(|c: char| !(is_identifier_char(c) || c == '.'))
+        .unwrap_or(s.len());
+    let segments: Vec<&str> = s[..end].split('.').collect();
+    // Bundlers may qualify `api` with the name of the module it was imported
+    // from, e.g. `import_api.api.messages.send`.
+    let start = segments
+        .iter()
+        .position(|segment| *segment == "api" || *segment == "internal")?;
+    let (function, module) = segments[start + 1..].split_last()?;
+    if module.is_empty() {
+        return None;
+    }
+    format!("{}:{function}", module.join("/"))
+        .parse::<UdfPath>()
+        .ok()
+        .map(UdfPath::canonicalize)
+}
+
+/// The source after the first argument at the start of `args`, or `None` if
+/// there's only one argument.
+fn skip_argument(args: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in args.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' | '`' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                if depth == 0 {
+                    return None;
+                }
+                depth -= 1;
+            },
+            ',' if depth == 0 => return Some(&args[i + 1..]),
+            _ => {},
+        }
+    }
+    None
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SerializedFunctionDependencies {
+    reads_tables: Vec<String>,
+    writes_tables: Vec<String>,
+    calls: Vec<String>,
+    schedules: Vec<String>,
+    reads_unknown_tables: bool,
+    writes_unknown_tables: bool,
+    references_unknown_functions: bool,
+}
+
+impl TryFrom<FunctionDependencies> for SerializedFunctionDependencies {
+    type Error = anyhow::Error;
+
+    fn try_from(d: FunctionDependencies) -> anyhow::Result<Self> {
+        Ok(Self {
+            reads_tables: d.reads_tables.into_iter().map(String::from).collect(),
+            writes_tables: d.writes_tables.into_iter().map(String::from).collect(),
+            calls: d.calls.into_iter().map(String::from).collect(),
+            schedules: d.schedules.into_iter().map(String::from).collect(),
+            reads_unknown_tables: d.reads_unknown_tables,
+            writes_unknown_tables: d.writes_unknown_tables,
+            references_unknown_functions: d.references_unknown_functions,
+        })
+    }
+}
+
+impl TryFrom<SerializedFunctionDependencies> for FunctionDependencies {
+    type Error = anyhow::Error;
+
+    fn try_from(d: SerializedFunctionDependencies) -> anyhow::Result<Self> {
+        Ok(Self {
+            reads_tables: d
+                .reads_tables
+                .into_iter()
+                .map(|t| t.parse())
+                .collect::<anyhow::Result<_>>()?,
+            writes_tables: d
+                .writes_tables
+                .into_iter()
+                .map(|t| t.parse())
+                .collect::<anyhow::Result<_>>()?,
+            calls: d
+                .calls
+                .into_iter()
+                .map(|p| p.parse())
+                .collect::<anyhow::Result<_>>()?,
+            schedules: d
+                .schedules
+                .into_iter()
+                .map(|p| p.parse())
+                .collect::<anyhow::Result<_>>()?,
+            reads_unknown_tables: d.reads_unknown_tables,
+            writes_unknown_tables: d.writes_unknown_tables,
+            references_unknown_functions: d.references_unknown_functions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use sync_types::CanonicalizedUdfPath;
+    use value::TableName;
+
+    use super::FunctionDependencies;
+
+    fn tables(names: &[&str]) -> anyhow::Result<BTreeSet<TableName>> {
+        names.iter().map(|name| name.parse()).collect()
+    }
+
+    fn paths(paths: &[&str]) -> anyhow::Result<BTreeSet<CanonicalizedUdfPath>> {
+        paths.iter().map(|path| path.parse()).collect()
+    }
+
+    #[test]
+    fn test_from_source() -> anyhow::Result<()> {
+        let source = r#"async (ctx, { channel, body }) => {
+            const messages = await ctx.db.query("messages").collect();
+            const user = await ctx.db.get(messages[0].author);
+            await ctx.db.insert('messages', { channel, body });
+            await ctx.db.patch(user._id, { lastSeen: Date.now() });
+            await ctx.runQuery(internal.users.lookup, { id: user._id });
+            await ctx.scheduler.runAfter(
+                Math.max(1000, delay(channel, "x,y")),
+                import_api.api.notifications.push.send,
+                {},
+            );
+            await ctx.scheduler.runAt(when, getReference(), {});
+            const mydb = ctx.db; mydb.query(tableName);
+        }"#;
+        let dependencies = FunctionDependencies::from_source(source);
+        assert_eq!(dependencies.reads_tables, tables(&["messages"])?);
+        assert_eq!(dependencies.writes_tables, tables(&["messages"])?);
+        assert_eq!(dependencies.calls, paths(&["users.js:lookup"])?);
+        assert_eq!(
+            dependencies.schedules,
+            paths(&["notifications/push.js:send"])?
+        );
+        assert!(dependencies.reads_unknown_tables);
+        assert!(dependencies.writes_unknown_tables);
+        assert!(dependencies.references_unknown_functions);
+
+        let dependencies =
+            FunctionDependencies::from_source("async ({ db }) => db.query(`users`).take(10)");
+        assert_eq!(dependencies.reads_tables, tables(&["users"])?);
+        assert!(!dependencies.reads_unknown_tables);
+        assert!(dependencies.writes_tables.is_empty());
+        Ok(())
+    }
+}
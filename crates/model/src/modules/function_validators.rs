@@ -1,7 +1,13 @@
+use std::collections::BTreeMap;
+
 use common::{
-    errors::JsError,
+    errors::{
+        CustomDataSource,
+        JsError,
+    },
     schemas::validator::{
         ObjectValidator,
+        ValidationError,
         Validator,
     },
 };
@@ -10,7 +16,9 @@ use errors::ErrorMetadataAnyhowExt;
 use proptest::prelude::*;
 use serde_json::Value as JsonValue;
 use value::{
+    obj,
     ConvexArray,
+    ConvexObject,
     ConvexValue,
     NamespacedTableMapping,
     NamespacedVirtualTableMapping,
@@ -50,7 +58,13 @@ impl ArgsValidator {
                              Instead received {} arguments: {args}",
                             args.len()
                         );
-                        return Ok(Some(JsError::from_message(error_message)));
+                        return Ok(Some(js_error_with_data(
+                            error_message,
+                            "ArgumentValidationError",
+                            "",
+                            "1 argument",
+                            format!("{} arguments", args.len()),
+                        )));
                     },
                 };
                 let object_arg = match single_arg {
@@ -60,7 +74,13 @@ impl ArgsValidator {
                             "Expected to receive an object as the function's argument. Instead \
                              received: {single_arg}"
                         );
-                        return Ok(Some(JsError::from_message(error_message)));
+                        return Ok(Some(js_error_with_data(
+                            error_message,
+                            "ArgumentValidationError",
+                            "",
+                            &Validator::Object(object_validator.clone()).to_string(),
+                            single_arg.type_name().to_string(),
+                        )));
                     },
                 };
 
@@ -70,7 +90,11 @@ impl ArgsValidator {
                     virtual_table_mapping,
                 );
                 if let Err(error) = validation_error {
-                    Some(JsError::from_message(error.to_string()))
+                    Some(validation_js_error(
+                        error.to_string(),
+                        "ArgumentValidationError",
+                        &error,
+                    ))
                 } else {
                     None
                 }
@@ -141,9 +165,11 @@ impl ReturnsValidator {
                 let validation_error =
                     validator.check_value(output, table_mapping, virtual_table_mapping);
                 match validation_error {
-                    Err(error) => Some(JsError::from_message(format!(
-                        "ReturnsValidationError: {error}"
-                    ))),
+                    Err(error) => Some(validation_js_error(
+                        format!("ReturnsValidationError: {error}"),
+                        "ReturnsValidationError",
+                        &error,
+                    )),
                     Ok(()) => None,
                 }
             },
@@ -151,6 +177,155 @@ impl ReturnsValidator {
     }
 }
 
+/**
+ * The typed errors a UDF declares it can throw, from e.g.
+ * `mutation({ errors: { NotFound: v.object({ id: v.string() }) }, ... })`.
+ *
+ * A typed error is a `ConvexError` whose data is `{ code, data }`.
+ * Functions that declare their errors can only throw typed errors with a
+ * declared code and data that matches the code's validator, so clients can
+ * rely on the payload. An error thrown without `data` is checked as `null`.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum ErrorsValidator {
+    Unvalidated,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "prop::collection::btree_set(any::<value::TableName>(), \
+                             1..8).prop_flat_map(|tables| \
+                             prop::collection::btree_map(\"[A-Za-z][A-Za-z0-9_]{0,15}\", \
+                             any_with::<Validator>(tables), \
+                             0..4)).prop_map(ErrorsValidator::Validated)")
+    )]
+    Validated(BTreeMap<String, Validator>),
+}
+
+impl ErrorsValidator {
+    /// Returns the error to report instead of `error` if it's a typed error:
+    /// a validation error if the function doesn't declare its code or its
+    /// data doesn't match the code's validator, and otherwise `error` marked
+    /// as declared so clients can see it even when errors are redacted.
+    /// Other errors, like uncaught exceptions, are left as they are.
+    pub fn check_error(
+        &self,
+        error: &JsError,
+        table_mapping: &NamespacedTableMapping,
+        virtual_table_mapping: &NamespacedVirtualTableMapping,
+    ) -> Option<JsError> {
+        let ErrorsValidator::Validated(errors) = self else {
+            return None;
+        };
+        let typed_error = error.typed_error()?;
+        let code = typed_error.code;
+        let Some(validator) = errors.get(code) else {
+            let declared: Vec<_> = errors.keys().map(|code| format!("{code:?}")).collect();
+            let declared = declared.join(" | ");
+            return Some(js_error_with_data(
+                format!(
+                    "ErrorValidationError: Function threw error code {code:?}, which isn't one of \
+                     the codes it declares: {declared}"
+                ),
+                "ErrorValidationError",
+                ".code",
+                &declared,
+                format!("{code:?}"),
+            ));
+        };
+        let data = typed_error.data.cloned().unwrap_or(ConvexValue::Null);
+        let validation_error = validator.check_value(&data, table_mapping, virtual_table_mapping);
+        match validation_error {
+            Err(error) => Some(validation_js_error(
+                format!("ErrorValidationError: Invalid data for error code {code:?}: {error}"),
+                "ErrorValidationError",
+                &error,
+            )),
+            Ok(()) => Some(JsError {
+                custom_data_source: CustomDataSource::DeclaredTypedError,
+                ..error.clone()
+            }),
+        }
+    }
+}
+
+impl TryFrom<JsonValue> for ErrorsValidator {
+    type Error = anyhow::Error;
+
+    fn try_from(json: JsonValue) -> Result<Self, Self::Error> {
+        let errors = match json {
+            JsonValue::Null => return Ok(ErrorsValidator::Unvalidated),
+            JsonValue::Object(errors) => errors,
+            _ => anyhow::bail!("Errors validator must be an object or null"),
+        };
+        let errors = errors
+            .into_iter()
+            .map(|(code, validator)| {
+                anyhow::ensure!(!code.is_empty(), "Error codes can't be empty");
+                let validator = Validator::try_from(validator).map_err(|e| {
+                    e.wrap_error_message(|msg| {
+                        format!("Error in validator for error code {code:?}: {msg}")
+                    })
+                })?;
+                Ok((code, validator))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(ErrorsValidator::Validated(errors))
+    }
+}
+
+impl TryFrom<ErrorsValidator> for JsonValue {
+    type Error = anyhow::Error;
+
+    fn try_from(errors: ErrorsValidator) -> Result<Self, Self::Error> {
+        match errors {
+            ErrorsValidator::Unvalidated => Ok(JsonValue::Null),
+            ErrorsValidator::Validated(errors) => Ok(JsonValue::Object(
+                errors
+                    .into_iter()
+                    .map(|(code, validator)| Ok((code, JsonValue::try_from(validator)?)))
+                    .collect::<anyhow::Result<_>>()?,
+            )),
+        }
+    }
+}
+
+/// Builds the error for a value that doesn't match its validator. The
+/// error's data says where in the value validation failed, so clients can
+/// handle it without parsing the message, e.g.
+/// `{ code: "ArgumentValidationError", path: ".author", expected: "v.string()",
+/// actual: "Float64" }`. Like the message, it's redacted when the backend
+/// redacts errors.
+fn validation_js_error(message: String, code: &str, error: &ValidationError) -> JsError {
+    js_error_with_data(
+        message,
+        code,
+        &error.path(),
+        &error.expected(),
+        error.actual(),
+    )
+}
+
+fn js_error_with_data(
+    message: String,
+    code: &str,
+    path: &str,
+    expected: &str,
+    actual: String,
+) -> JsError {
+    let data: anyhow::Result<ConvexObject> = try {
+        obj!(
+            "code" => code,
+            "path" => path,
+            "expected" => expected,
+            "actual" => actual,
+        )?
+    };
+    match data {
+        Ok(data) => JsError::validation_error(message, ConvexValue::Object(data)),
+        Err(_) => JsError::from_message(message),
+    }
+}
+
 impl TryFrom<JsonValue> for ReturnsValidator {
     type Error = anyhow::Error;
 
@@ -181,12 +356,30 @@ impl TryFrom<ReturnsValidator> for JsonValue {
 
 #[cfg(test)]
 mod tests {
+    use common::{
+        errors::{
+            CustomDataSource,
+            JsError,
+        },
+        schemas::validator::Validator,
+    };
     use proptest::prelude::*;
-    use serde_json::Value as JsonValue;
+    use serde_json::{
+        json,
+        Value as JsonValue,
+    };
     use sync_types::testing::assert_roundtrips;
+    use value::{
+        assert_obj,
+        ConvexValue,
+        TableMapping,
+        TableNamespace,
+        VirtualTableMapping,
+    };
 
     use crate::modules::function_validators::{
         ArgsValidator,
+        ErrorsValidator,
         ReturnsValidator,
     };
 
@@ -209,4 +402,68 @@ mod tests {
             assert_roundtrips::<ReturnsValidator, JsonValue>(v);
         }
     }
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_errors_roundtrips(v in any::<ErrorsValidator>()) {
+            assert_roundtrips::<ErrorsValidator, JsonValue>(v);
+        }
+    }
+
+    #[test]
+    fn test_check_error() -> anyhow::Result<()> {
+        let errors = ErrorsValidator::try_from(json!({
+            "NotFound": { "type": "object", "value": {
+                "id": { "fieldType": { "type": "string" }, "optional": false },
+            }},
+            "RateLimited": { "type": "null" },
+        }))?;
+        let table_mapping = TableMapping::new().namespace(TableNamespace::test_user());
+        let virtual_table_mapping =
+            VirtualTableMapping::new().namespace(TableNamespace::test_user());
+        let check = |data: ConvexValue| {
+            errors.check_error(
+                &JsError::convex_error("Uncaught ConvexError".to_string(), data),
+                &table_mapping,
+                &virtual_table_mapping,
+            )
+        };
+
+        let err =
+            check(assert_obj!("code" => "NotFound", "data" => assert_obj!("id" => "abc")).into())
+                .unwrap();
+        assert_eq!(err.message, "Uncaught ConvexError");
+        assert_eq!(err.custom_data_source, CustomDataSource::DeclaredTypedError);
+        let err = check(assert_obj!("code" => "RateLimited").into()).unwrap();
+        assert_eq!(err.custom_data_source, CustomDataSource::DeclaredTypedError);
+        // ConvexErrors that aren't typed errors are left as they are.
+        assert!(check("Something went wrong".try_into()?).is_none());
+
+        let err =
+            check(assert_obj!("code" => "NotFound", "data" => assert_obj!("id" => 1.)).into())
+                .unwrap();
+        assert!(err.message.starts_with("ErrorValidationError"), "{err}");
+        let err = check(assert_obj!("code" => "Teapot").into()).unwrap();
+        assert!(err.message.contains("\"Teapot\""), "{err}");
+        // Validation errors aren't typed errors themselves.
+        assert!(err.typed_error().is_none());
+        assert_eq!(err.custom_data_source, CustomDataSource::Validation);
+
+        // Validators for every code must be valid.
+        let err = ErrorsValidator::try_from(json!({ "NotFound": { "type": "nope" } })).unwrap_err();
+        assert!(err.to_string().contains("\"NotFound\""), "{err}");
+        assert!(ErrorsValidator::try_from(json!([])).is_err());
+        assert_eq!(
+            ErrorsValidator::try_from(JsonValue::Null)?,
+            ErrorsValidator::Unvalidated
+        );
+        assert!(matches!(
+            ErrorsValidator::try_from(json!({ "RateLimited": { "type": "null" } }))?,
+            ErrorsValidator::Validated(errors) if errors["RateLimited"] == Validator::Null
+        ));
+        Ok(())
+    }
 }
@@ -79,6 +79,7 @@ use crate::{
     SystemTable,
 };
 
+pub mod function_dependencies;
 pub mod function_validators;
 mod metrics;
 pub mod module_versions;
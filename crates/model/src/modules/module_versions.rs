@@ -2,6 +2,7 @@ use std::{
     collections::BTreeMap,
     mem,
     str::FromStr,
+    time::Duration,
 };
 
 use async_lru::async_lru::SizedValue;
@@ -26,9 +27,16 @@ use value::heap_size::{
     WithHeapSize,
 };
 
-use super::function_validators::{
-    ArgsValidator,
-    ReturnsValidator,
+use super::{
+    function_dependencies::{
+        FunctionDependencies,
+        SerializedFunctionDependencies,
+    },
+    function_validators::{
+        ArgsValidator,
+        ErrorsValidator,
+        ReturnsValidator,
+    },
 };
 use crate::cron_jobs::types::{
     CronIdentifier,
@@ -247,6 +255,74 @@ pub struct AnalyzedFunction {
     pub visibility: Option<Visibility>,
     pub args: ArgsValidator,
     pub returns: ReturnsValidator,
+    pub errors: ErrorsValidator,
+    // Only set for queries that opted into the query result cache.
+    pub cache: Option<QueryCachePolicy>,
+    // Not set for Node actions or functions analyzed before dependency
+    // analysis was added.
+    pub dependencies: Option<FunctionDependencies>,
+}
+
+/// Maximum TTL a query can declare for its cached results.
+pub const MAX_QUERY_CACHE_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// A query's declared result caching, from `query({ cache: { ttlSeconds }
+/// })`.
+///
+/// Results of these queries are served from the cache for up to `ttl` after
+/// they're computed, even if the data they read has changed since, so the
+/// query runs at most once per TTL for each set of arguments and identity.
+/// Subscribers get the recomputed result when the TTL lapses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct QueryCachePolicy {
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(1..=86_400u64, Duration::from_secs)"
+        )
+    )]
+    pub ttl: Duration,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedQueryCachePolicy {
+    ttl_seconds: i64,
+}
+
+impl TryFrom<JsonValue> for QueryCachePolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(json: JsonValue) -> anyhow::Result<Self> {
+        let serialized: SerializedQueryCachePolicy = serde_json::from_value(json)?;
+        serialized.try_into()
+    }
+}
+
+impl TryFrom<SerializedQueryCachePolicy> for QueryCachePolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: SerializedQueryCachePolicy) -> anyhow::Result<Self> {
+        let ttl = Duration::from_secs(u64::try_from(policy.ttl_seconds)?);
+        anyhow::ensure!(
+            !ttl.is_zero() && ttl <= MAX_QUERY_CACHE_TTL,
+            "Query cache TTL must be between 1 second and {} hours, got {} seconds",
+            MAX_QUERY_CACHE_TTL.as_secs() / 3600,
+            policy.ttl_seconds
+        );
+        Ok(Self { ttl })
+    }
+}
+
+impl TryFrom<QueryCachePolicy> for SerializedQueryCachePolicy {
+    type Error = anyhow::Error;
+
+    fn try_from(policy: QueryCachePolicy) -> anyhow::Result<Self> {
+        Ok(Self {
+            ttl_seconds: policy.ttl.as_secs().try_into()?,
+        })
+    }
 }
 
 impl HeapSize for AnalyzedFunction {
@@ -268,6 +344,12 @@ struct SerializedAnalyzedFunction {
     visibility: Option<Visibility>,
     args: Option<String>,
     returns: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    errors: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cache: Option<SerializedQueryCachePolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dependencies: Option<SerializedFunctionDependencies>,
 }
 
 impl TryFrom<AnalyzedFunction> for SerializedAnalyzedFunction {
@@ -276,6 +358,10 @@ impl TryFrom<AnalyzedFunction> for SerializedAnalyzedFunction {
     fn try_from(f: AnalyzedFunction) -> anyhow::Result<Self> {
         let args_json = JsonValue::try_from(f.args)?;
         let returns_json = JsonValue::try_from(f.returns)?;
+        let errors = match f.errors {
+            ErrorsValidator::Unvalidated => None,
+            errors => Some(serde_json::to_string(&JsonValue::try_from(errors)?)?),
+        };
         Ok(Self {
             name: f.name.to_string(),
             pos: f.pos.map(TryFrom::try_from).transpose()?,
@@ -283,6 +369,9 @@ impl TryFrom<AnalyzedFunction> for SerializedAnalyzedFunction {
             visibility: f.visibility,
             args: Some(serde_json::to_string(&args_json)?),
             returns: Some(serde_json::to_string(&returns_json)?),
+            errors,
+            cache: f.cache.map(TryFrom::try_from).transpose()?,
+            dependencies: f.dependencies.map(TryFrom::try_from).transpose()?,
         })
     }
 }
@@ -310,6 +399,15 @@ impl TryFrom<SerializedAnalyzedFunction> for AnalyzedFunction {
                 },
                 None => ReturnsValidator::Unvalidated,
             },
+            errors: match f.errors {
+                Some(errors) => {
+                    let deserialized_value: JsonValue = serde_json::from_str(&errors)?;
+                    ErrorsValidator::try_from(deserialized_value)?
+                },
+                None => ErrorsValidator::Unvalidated,
+            },
+            cache: f.cache.map(TryFrom::try_from).transpose()?,
+            dependencies: f.dependencies.map(TryFrom::try_from).transpose()?,
         })
     }
 }
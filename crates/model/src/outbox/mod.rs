@@ -0,0 +1,653 @@
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    knobs::{
+        OUTBOX_INITIAL_BACKOFF,
+        OUTBOX_MAX_ATTEMPTS,
+        OUTBOX_MAX_BACKOFF,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use sync_types::Timestamp;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    OutboxRecord,
+    OutboxRecordState,
+    OutboxRecordStatus,
+    OutboxTarget,
+    OutboxTargetConfig,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static OUTBOX_TARGETS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_outbox_targets"
+        .parse()
+        .expect("_outbox_targets is not a valid system table name")
+});
+
+pub static OUTBOX_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_outbox"
+        .parse()
+        .expect("_outbox is not a valid system table name")
+});
+
+pub static OUTBOX_TARGETS_INDEX_BY_NAME: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&OUTBOX_TARGETS_TABLE, "by_name"));
+pub static OUTBOX_INDEX_BY_NEXT_ATTEMPT_TS: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&OUTBOX_TABLE, "by_next_attempt_ts"));
+pub static OUTBOX_INDEX_BY_STATE_AND_ENQUEUED_TS: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&OUTBOX_TABLE, "by_state_and_enqueued_ts"));
+
+static NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "name".parse().expect("invalid name field"));
+static NEXT_ATTEMPT_TS_FIELD: LazyLock<FieldPath> = LazyLock::new(|| {
+    "nextAttemptTs"
+        .parse()
+        .expect("invalid nextAttemptTs field")
+});
+static STATE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "state".parse().expect("invalid state field"));
+static ENQUEUED_TS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "enqueuedTs".parse().expect("invalid enqueuedTs field"));
+
+const MAX_TARGET_NAME_LENGTH: usize = 64;
+
+pub struct OutboxTargetsTable;
+impl SystemTable for OutboxTargetsTable {
+    fn table_name(&self) -> &'static TableName {
+        &OUTBOX_TARGETS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: OUTBOX_TARGETS_INDEX_BY_NAME.clone(),
+            fields: vec![NAME_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<OutboxTarget>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct OutboxTable;
+impl SystemTable for OutboxTable {
+    fn table_name(&self) -> &'static TableName {
+        &OUTBOX_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            // Used by the outbox worker to find records that are due for
+            // delivery.
+            SystemIndex {
+                name: OUTBOX_INDEX_BY_NEXT_ATTEMPT_TS.clone(),
+                fields: vec![NEXT_ATTEMPT_TS_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: OUTBOX_INDEX_BY_STATE_AND_ENQUEUED_TS.clone(),
+                fields: vec![STATE_FIELD.clone(), ENQUEUED_TS_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<OutboxRecord>::try_from(document).map(|_| ())
+    }
+}
+
+fn invalid_target(msg: impl Into<String>) -> ErrorMetadata {
+    ErrorMetadata::bad_request("InvalidOutboxTarget", msg.into())
+}
+
+fn validate_target(target: &OutboxTarget) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !target.name.is_empty()
+            && target.name.len() <= MAX_TARGET_NAME_LENGTH
+            && target
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'),
+        invalid_target(format!(
+            "Outbox target names must be between 1 and {MAX_TARGET_NAME_LENGTH} characters long \
+             and only contain letters, digits, \"_\" and \"-\""
+        ))
+    );
+    let url = match &target.config {
+        OutboxTargetConfig::Http { url, .. } => url,
+        OutboxTargetConfig::Kafka {
+            rest_proxy_url,
+            topic,
+            ..
+        } => {
+            anyhow::ensure!(
+                !topic.is_empty(),
+                invalid_target("Kafka targets need a topic")
+            );
+            rest_proxy_url
+        },
+    };
+    anyhow::ensure!(
+        url.starts_with("https://") || url.starts_with("http://"),
+        invalid_target(format!("{url:?} is not an http or https URL"))
+    );
+    anyhow::ensure!(
+        target
+            .config
+            .headers()
+            .iter()
+            .all(|header| !header.name.is_empty()),
+        invalid_target("Header names can't be empty")
+    );
+    Ok(())
+}
+
+/// How long to wait before attempting a delivery again after `attempts`
+/// failed deliveries.
+fn backoff(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(31);
+    OUTBOX_INITIAL_BACKOFF
+        .saturating_mul(1 << exponent)
+        .min(*OUTBOX_MAX_BACKOFF)
+}
+
+/// A transactional outbox. Mutations enqueue records in the same transaction
+/// as their other writes, so a record exists if and only if the mutation
+/// committed, and the outbox worker then delivers each record to its target
+/// at least once. Every delivery of a record carries the record's id so
+/// targets can drop repeated deliveries and process each record exactly once.
+pub struct OutboxModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> OutboxModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    fn check_system(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    /// Create the target, or replace it if one with the same name exists.
+    pub async fn set_target(&mut self, target: OutboxTarget) -> anyhow::Result<()> {
+        self.check_admin("set_outbox_target")?;
+        validate_target(&target)?;
+        match self.target(&target.name).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), target.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert_metadata(&OUTBOX_TARGETS_TABLE, target.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Delete the target. Its pending records fail when they're next due.
+    pub async fn delete_target(&mut self, name: &str) -> anyhow::Result<()> {
+        self.check_admin("delete_outbox_target")?;
+        let Some(existing) = self.target(name).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "OutboxTargetNotFound",
+                format!("There is no outbox target named {name:?}"),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn target(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<OutboxTarget>>> {
+        let query = Query::index_range(IndexRange {
+            index_name: OUTBOX_TARGETS_INDEX_BY_NAME.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                NAME_FIELD.clone(),
+                ConvexValue::try_from(name.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(|doc| doc.try_into())
+            .transpose()
+    }
+
+    pub async fn targets(&mut self) -> anyhow::Result<Vec<ParsedDocument<OutboxTarget>>> {
+        self.check_admin("list_outbox_targets")?;
+        let query = Query::full_table_scan(OUTBOX_TARGETS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut targets = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            targets.push(doc.try_into()?);
+        }
+        Ok(targets)
+    }
+
+    /// Add a record for `target` that's delivered once the transaction
+    /// commits.
+    pub async fn enqueue(
+        &mut self,
+        target: &str,
+        payload: ConvexValue,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        anyhow::ensure!(
+            self.target(target).await?.is_some(),
+            ErrorMetadata::bad_request(
+                "OutboxTargetNotFound",
+                format!("There is no outbox target named {target:?}")
+            )
+        );
+        let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+        let record = OutboxRecord {
+            target: target.to_string(),
+            payload,
+            enqueued_ts: now,
+            next_attempt_ts: Some(now),
+            attempts: 0,
+            state: OutboxRecordState::Pending { last_error: None },
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert_metadata(&OUTBOX_TABLE, record.try_into()?)
+            .await
+    }
+
+    /// Returns up to `limit` records that are due for delivery at `now`,
+    /// along with the time the next record is due if there are no more.
+    pub async fn due_records(
+        &mut self,
+        now: Timestamp,
+        limit: usize,
+    ) -> anyhow::Result<(Vec<ParsedDocument<OutboxRecord>>, Option<Timestamp>)> {
+        let query = Query::index_range(IndexRange {
+            index_name: OUTBOX_INDEX_BY_NEXT_ATTEMPT_TS.clone(),
+            range: vec![IndexRangeExpression::Gt(
+                NEXT_ATTEMPT_TS_FIELD.clone(),
+                ConvexValue::Null,
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut records = Vec::new();
+        while records.len() < limit {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            let record: ParsedDocument<OutboxRecord> = doc.try_into()?;
+            match record.next_attempt_ts {
+                Some(next_attempt_ts) if next_attempt_ts > now => {
+                    return Ok((records, Some(next_attempt_ts)))
+                },
+                _ => records.push(record),
+            }
+        }
+        Ok((records, None))
+    }
+
+    /// Start a delivery attempt of a due record. If the attempt's outcome
+    /// isn't recorded within `timeout`, the record is due again. Returns the
+    /// record's target, or `None` if the target was deleted, in which case
+    /// the record has failed instead.
+    pub async fn claim(
+        &mut self,
+        record: ParsedDocument<OutboxRecord>,
+        timeout: Duration,
+    ) -> anyhow::Result<Option<(OutboxRecord, OutboxTarget)>> {
+        self.check_system("claim_outbox_record")?;
+        let (id, mut record) = record.into_id_and_value();
+        let target = self.target(&record.target).await?;
+        match target {
+            Some(target) => {
+                let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+                record.attempts += 1;
+                record.next_attempt_ts = Some(now.add(timeout)?);
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, record.clone().try_into()?)
+                    .await?;
+                Ok(Some((record, target.into_value())))
+            },
+            None => {
+                record.next_attempt_ts = None;
+                record.state = OutboxRecordState::Failed {
+                    error: format!("The outbox target {:?} was deleted", record.target),
+                };
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(id, record.try_into()?)
+                    .await?;
+                Ok(None)
+            },
+        }
+    }
+
+    /// Record the outcome of the `attempt`th delivery of a record. Outcomes
+    /// of attempts that were abandoned after their timeout are ignored, so a
+    /// record is only ever marked as delivered once. Returns the record's
+    /// new status, or `None` if the outcome was ignored.
+    pub async fn record_attempt(
+        &mut self,
+        id: ResolvedDocumentId,
+        attempt: u32,
+        result: Result<(), String>,
+    ) -> anyhow::Result<Option<OutboxRecordStatus>> {
+        self.check_system("record_outbox_attempt")?;
+        let Some(document) = self.tx.get(id).await? else {
+            return Ok(None);
+        };
+        let mut record: ParsedDocument<OutboxRecord> = document.try_into()?;
+        if record.attempts != attempt || record.state.status() != OutboxRecordStatus::Pending {
+            return Ok(None);
+        }
+        let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+        match result {
+            Ok(()) => {
+                record.next_attempt_ts = None;
+                record.state = OutboxRecordState::Delivered { delivered_ts: now };
+            },
+            Err(error) if attempt >= *OUTBOX_MAX_ATTEMPTS => {
+                record.next_attempt_ts = None;
+                record.state = OutboxRecordState::Failed { error };
+            },
+            Err(error) => {
+                record.next_attempt_ts = Some(now.add(backoff(attempt))?);
+                record.state = OutboxRecordState::Pending {
+                    last_error: Some(error),
+                };
+            },
+        }
+        let status = record.state.status();
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, record.into_value().try_into()?)
+            .await?;
+        Ok(Some(status))
+    }
+
+    /// Up to `limit` records in `status`, oldest first.
+    pub async fn list(
+        &mut self,
+        status: OutboxRecordStatus,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<OutboxRecord>>> {
+        self.check_admin("list_outbox_records")?;
+        self.records_with_status(status, None, limit).await
+    }
+
+    async fn records_with_status(
+        &mut self,
+        status: OutboxRecordStatus,
+        enqueued_before: Option<Timestamp>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<OutboxRecord>>> {
+        let mut range = vec![IndexRangeExpression::Eq(
+            STATE_FIELD.clone(),
+            ConvexValue::try_from(status.to_string())?.into(),
+        )];
+        if let Some(enqueued_before) = enqueued_before {
+            range.push(IndexRangeExpression::Lt(
+                ENQUEUED_TS_FIELD.clone(),
+                ConvexValue::Int64(enqueued_before.into()),
+            ));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: OUTBOX_INDEX_BY_STATE_AND_ENQUEUED_TS.clone(),
+            range,
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut records = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            records.push(doc.try_into()?);
+        }
+        Ok(records)
+    }
+
+    /// Deliver a failed record again, with a fresh set of attempts.
+    pub async fn retry(&mut self, id: DeveloperDocumentId) -> anyhow::Result<()> {
+        self.check_admin("retry_outbox_record")?;
+        let not_found =
+            || ErrorMetadata::not_found("OutboxRecordNotFound", format!("No outbox record {id}"));
+        let table_mapping = self.tx.table_mapping().namespace(TableNamespace::Global);
+        let id = id.to_resolved(&table_mapping.number_to_tablet())?;
+        anyhow::ensure!(
+            table_mapping.tablet_matches_name(id.tablet_id, &OUTBOX_TABLE),
+            not_found()
+        );
+        let Some(document) = self.tx.get(id).await? else {
+            anyhow::bail!(not_found());
+        };
+        let mut record: ParsedDocument<OutboxRecord> = document.try_into()?;
+        let OutboxRecordState::Failed { error } = &record.state else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "OutboxRecordNotFailed",
+                format!("Outbox record {id} hasn't failed, so it can't be retried")
+            ));
+        };
+        record.state = OutboxRecordState::Pending {
+            last_error: Some(error.clone()),
+        };
+        record.attempts = 0;
+        record.next_attempt_ts = Some(self.tx.runtime().generate_timestamp()?);
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, record.into_value().try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` delivered records that were enqueued before `cutoff`.
+    pub async fn expired_delivered(
+        &mut self,
+        cutoff: Timestamp,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        Ok(self
+            .records_with_status(OutboxRecordStatus::Delivered, Some(cutoff), limit)
+            .await?
+            .into_iter()
+            .map(|record| record.id())
+            .collect())
+    }
+
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        self.check_system("delete_outbox_record")?;
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::{
+        knobs::OUTBOX_MAX_ATTEMPTS,
+        runtime::Runtime,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+    use value::{
+        id_v6::DeveloperDocumentId,
+        ConvexValue,
+    };
+
+    use crate::{
+        outbox::{
+            types::{
+                OutboxRecordState,
+                OutboxRecordStatus,
+                OutboxTarget,
+                OutboxTargetConfig,
+            },
+            OutboxModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn http_target(name: &str) -> OutboxTarget {
+        OutboxTarget {
+            name: name.to_string(),
+            config: OutboxTargetConfig::Http {
+                url: "https://example.com/webhook".to_string(),
+                headers: vec![],
+            },
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_outbox_delivery_bookkeeping(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = OutboxModel::new(&mut tx);
+        assert!(model
+            .enqueue("webhooks", ConvexValue::from(1i64))
+            .await
+            .is_err());
+        model.set_target(http_target("webhooks")).await?;
+        let id = model.enqueue("webhooks", ConvexValue::from(1i64)).await?;
+        db.commit(tx).await?;
+
+        // Claim the record and fail the delivery.
+        let mut tx = db.begin_system().await?;
+        let mut model = OutboxModel::new(&mut tx);
+        let (mut due, _) = model.due_records(rt.generate_timestamp()?, 10).await?;
+        assert_eq!(due.len(), 1);
+        let (record, target) = model
+            .claim(due.pop().unwrap(), Duration::from_secs(30))
+            .await?
+            .unwrap();
+        assert_eq!(record.attempts, 1);
+        assert_eq!(target, http_target("webhooks"));
+        let (due, next_attempt_ts) = model.due_records(rt.generate_timestamp()?, 10).await?;
+        assert!(due.is_empty());
+        assert!(next_attempt_ts.is_some());
+        assert_eq!(
+            model.record_attempt(id, 1, Err("503".to_string())).await?,
+            Some(OutboxRecordStatus::Pending)
+        );
+        // The outcome of an attempt is only recorded once.
+        assert_eq!(model.record_attempt(id, 1, Ok(())).await?, None);
+        db.commit(tx).await?;
+
+        // Deliver it on the next attempt.
+        rt.advance_time(Duration::from_secs(60)).await;
+        let mut tx = db.begin_system().await?;
+        let mut model = OutboxModel::new(&mut tx);
+        let (mut due, _) = model.due_records(rt.generate_timestamp()?, 10).await?;
+        let (record, _) = model
+            .claim(due.pop().unwrap(), Duration::from_secs(30))
+            .await?
+            .unwrap();
+        assert_eq!(record.attempts, 2);
+        assert_eq!(
+            model.record_attempt(id, 2, Ok(())).await?,
+            Some(OutboxRecordStatus::Delivered)
+        );
+        assert_eq!(
+            model.list(OutboxRecordStatus::Delivered, 10).await?.len(),
+            1
+        );
+        let (due, next_attempt_ts) = model.due_records(rt.generate_timestamp()?, 10).await?;
+        assert!(due.is_empty());
+        assert_eq!(next_attempt_ts, None);
+        let cutoff = rt.generate_timestamp()?.add(Duration::from_secs(1))?;
+        assert_eq!(model.expired_delivered(cutoff, 10).await?, vec![id]);
+        db.commit(tx).await?;
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_outbox_failed_records(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = OutboxModel::new(&mut tx);
+        model.set_target(http_target("webhooks")).await?;
+        let id = model.enqueue("webhooks", ConvexValue::from(1i64)).await?;
+        for attempt in 1..=*OUTBOX_MAX_ATTEMPTS {
+            let record = tx.get(id).await?.unwrap().try_into()?;
+            OutboxModel::new(&mut tx)
+                .claim(record, Duration::from_secs(30))
+                .await?;
+            OutboxModel::new(&mut tx)
+                .record_attempt(id, attempt, Err("503".to_string()))
+                .await?;
+        }
+        let mut model = OutboxModel::new(&mut tx);
+        let failed = model.list(OutboxRecordStatus::Failed, 10).await?;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(
+            failed[0].state,
+            OutboxRecordState::Failed {
+                error: "503".to_string()
+            }
+        );
+
+        model.retry(DeveloperDocumentId::from(id)).await?;
+        let (mut due, _) = model.due_records(rt.generate_timestamp()?, 10).await?;
+        assert_eq!(due.len(), 1);
+
+        // Records for deleted targets fail instead of being delivered.
+        model.delete_target("webhooks").await?;
+        assert!(model
+            .claim(due.pop().unwrap(), Duration::from_secs(30))
+            .await?
+            .is_none());
+        assert_eq!(model.list(OutboxRecordStatus::Failed, 10).await?.len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use sync_types::Timestamp;
+use value::{
+    codegen_convex_serialization,
+    ConvexValue,
+};
+
+/// An external system that outbox records are delivered to.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct OutboxTarget {
+    pub name: String,
+    pub config: OutboxTargetConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum OutboxTargetConfig {
+    /// Each record is POSTed to `url` as JSON.
+    Http {
+        url: String,
+        headers: Vec<OutboxHeader>,
+    },
+    /// Each record is produced to `topic` through a Kafka REST proxy, keyed
+    /// by the record's id.
+    Kafka {
+        rest_proxy_url: String,
+        topic: String,
+        headers: Vec<OutboxHeader>,
+    },
+}
+
+impl OutboxTargetConfig {
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            Self::Http { .. } => "http",
+            Self::Kafka { .. } => "kafka",
+        }
+    }
+
+    pub fn headers(&self) -> &[OutboxHeader] {
+        match self {
+            Self::Http { headers, .. } | Self::Kafka { headers, .. } => headers,
+        }
+    }
+}
+
+/// A header sent with every delivery to a target, e.g. for authentication.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct OutboxHeader {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedOutboxTargetConfig {
+    Http {
+        url: String,
+        headers: Vec<OutboxHeader>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Kafka {
+        rest_proxy_url: String,
+        topic: String,
+        headers: Vec<OutboxHeader>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedOutboxTarget {
+    name: String,
+    config: SerializedOutboxTargetConfig,
+}
+
+impl TryFrom<OutboxTarget> for SerializedOutboxTarget {
+    type Error = anyhow::Error;
+
+    fn try_from(target: OutboxTarget) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: target.name,
+            config: match target.config {
+                OutboxTargetConfig::Http { url, headers } => {
+                    SerializedOutboxTargetConfig::Http { url, headers }
+                },
+                OutboxTargetConfig::Kafka {
+                    rest_proxy_url,
+                    topic,
+                    headers,
+                } => SerializedOutboxTargetConfig::Kafka {
+                    rest_proxy_url,
+                    topic,
+                    headers,
+                },
+            },
+        })
+    }
+}
+
+impl TryFrom<SerializedOutboxTarget> for OutboxTarget {
+    type Error = anyhow::Error;
+
+    fn try_from(target: SerializedOutboxTarget) -> anyhow::Result<Self> {
+        Ok(Self {
+            name: target.name,
+            config: match target.config {
+                SerializedOutboxTargetConfig::Http { url, headers } => {
+                    OutboxTargetConfig::Http { url, headers }
+                },
+                SerializedOutboxTargetConfig::Kafka {
+                    rest_proxy_url,
+                    topic,
+                    headers,
+                } => OutboxTargetConfig::Kafka {
+                    rest_proxy_url,
+                    topic,
+                    headers,
+                },
+            },
+        })
+    }
+}
+
+codegen_convex_serialization!(OutboxTarget, SerializedOutboxTarget);
+
+/// A record enqueued by a mutation, committed atomically with the mutation's
+/// writes, and delivered to its target by the outbox worker afterwards.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct OutboxRecord {
+    pub target: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::arbitrary::any_with::<ConvexValue>((Default::default(), \
+                        value::proptest::ExcludeSetsAndMaps(true)))"
+        )
+    )]
+    pub payload: ConvexValue,
+    pub enqueued_ts: Timestamp,
+    // When delivery is next attempted. While an attempt is in flight this is
+    // when it's given up on and retried. None once the record is delivered
+    // or has failed.
+    pub next_attempt_ts: Option<Timestamp>,
+    pub attempts: u32,
+    pub state: OutboxRecordState,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum OutboxRecordState {
+    Pending {
+        last_error: Option<String>,
+    },
+    Delivered {
+        delivered_ts: Timestamp,
+    },
+    /// The record ran out of attempts, or its target was deleted.
+    Failed {
+        error: String,
+    },
+}
+
+impl OutboxRecordState {
+    pub fn status(&self) -> OutboxRecordStatus {
+        match self {
+            Self::Pending { .. } => OutboxRecordStatus::Pending,
+            Self::Delivered { .. } => OutboxRecordStatus::Delivered,
+            Self::Failed { .. } => OutboxRecordStatus::Failed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::EnumString, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum OutboxRecordStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedOutboxRecord {
+    target: String,
+    // Serialize the payload as binary since we restrict what field names can
+    // be used in a `Document`'s top-level object.
+    #[serde(with = "serde_bytes")]
+    payload: Vec<u8>,
+    enqueued_ts: i64,
+    next_attempt_ts: Option<i64>,
+    attempts: i64,
+    // The state is flattened so records can be indexed by it.
+    state: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    delivered_ts: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl TryFrom<OutboxRecord> for SerializedOutboxRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: OutboxRecord) -> anyhow::Result<Self> {
+        let payload_json = JsonValue::from(record.payload);
+        let state = record.state.status().to_string();
+        let (delivered_ts, error) = match record.state {
+            OutboxRecordState::Pending { last_error } => (None, last_error),
+            OutboxRecordState::Delivered { delivered_ts } => (Some(delivered_ts.into()), None),
+            OutboxRecordState::Failed { error } => (None, Some(error)),
+        };
+        Ok(Self {
+            target: record.target,
+            payload: serde_json::to_vec(&payload_json)?,
+            enqueued_ts: record.enqueued_ts.into(),
+            next_attempt_ts: record.next_attempt_ts.map(|ts| ts.into()),
+            attempts: record.attempts.into(),
+            state,
+            delivered_ts,
+            error,
+        })
+    }
+}
+
+impl TryFrom<SerializedOutboxRecord> for OutboxRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(record: SerializedOutboxRecord) -> anyhow::Result<Self> {
+        let payload_json: JsonValue = serde_json::from_slice(&record.payload)?;
+        let state = match record.state.parse()? {
+            OutboxRecordStatus::Pending => OutboxRecordState::Pending {
+                last_error: record.error,
+            },
+            OutboxRecordStatus::Delivered => OutboxRecordState::Delivered {
+                delivered_ts: record
+                    .delivered_ts
+                    .ok_or_else(|| anyhow::anyhow!("Delivered outbox record has no deliveredTs"))?
+                    .try_into()?,
+            },
+            OutboxRecordStatus::Failed => OutboxRecordState::Failed {
+                error: record
+                    .error
+                    .ok_or_else(|| anyhow::anyhow!("Failed outbox record has no error"))?,
+            },
+        };
+        Ok(Self {
+            target: record.target,
+            payload: payload_json.try_into()?,
+            enqueued_ts: record.enqueued_ts.try_into()?,
+            next_attempt_ts: record.next_attempt_ts.map(|ts| ts.try_into()).transpose()?,
+            attempts: record.attempts.try_into()?,
+            state,
+        })
+    }
+}
+
+mod outbox_record_serde {
+    use value::codegen_convex_serialization;
+
+    use super::{
+        OutboxRecord,
+        SerializedOutboxRecord,
+    };
+
+    codegen_convex_serialization!(OutboxRecord, SerializedOutboxRecord);
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use crate::outbox::types::{
+        OutboxRecord,
+        OutboxTarget,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_outbox_target_roundtrips(v in any::<OutboxTarget>()) {
+            assert_roundtrips::<OutboxTarget, ConvexObject>(v);
+        }
+
+        #[test]
+        fn test_outbox_record_roundtrips(v in any::<OutboxRecord>()) {
+            assert_roundtrips::<OutboxRecord, ConvexObject>(v);
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use std::{
+    sync::LazyLock,
+    time::Duration,
+};
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    execution_context::ExecutionContext,
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use maplit::btreemap;
+use sync_types::Timestamp;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexArray,
+    ConvexObject,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    QueueConsumer,
+    QueueDelivery,
+    QueueDeliveryOutcome,
+    QueueMessage,
+};
+use crate::{
+    scheduled_jobs::{
+        types::{
+            ScheduleOptions,
+            ScheduledJobState,
+        },
+        SchedulerModel,
+    },
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static QUEUE_CONSUMERS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_queue_consumers"
+        .parse()
+        .expect("_queue_consumers is not a valid system table name")
+});
+
+pub static QUEUE_MESSAGES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_queue_messages"
+        .parse()
+        .expect("_queue_messages is not a valid system table name")
+});
+
+pub static QUEUE_CONSUMERS_INDEX_BY_QUEUE_AND_GROUP: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&QUEUE_CONSUMERS_TABLE, "by_queue_and_group"));
+pub static QUEUE_MESSAGES_INDEX_BY_VISIBLE_TS: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&QUEUE_MESSAGES_TABLE, "by_visible_ts"));
+pub static QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_GROUP: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&QUEUE_MESSAGES_TABLE, "by_queue_and_group"));
+
+static QUEUE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "queue".parse().expect("invalid queue field"));
+static GROUP_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "group".parse().expect("invalid group field"));
+static VISIBLE_TS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "visibleTs".parse().expect("invalid visibleTs field"));
+
+const MAX_QUEUE_NAME_LENGTH: usize = 64;
+
+pub struct QueueConsumersTable;
+impl SystemTable for QueueConsumersTable {
+    fn table_name(&self) -> &'static TableName {
+        &QUEUE_CONSUMERS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: QUEUE_CONSUMERS_INDEX_BY_QUEUE_AND_GROUP.clone(),
+            fields: vec![QUEUE_FIELD.clone(), GROUP_FIELD.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<QueueConsumer>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct QueueMessagesTable;
+impl SystemTable for QueueMessagesTable {
+    fn table_name(&self) -> &'static TableName {
+        &QUEUE_MESSAGES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            // Used by the dispatcher to find messages that are ready to be
+            // delivered.
+            SystemIndex {
+                name: QUEUE_MESSAGES_INDEX_BY_VISIBLE_TS.clone(),
+                fields: vec![VISIBLE_TS_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_GROUP.clone(),
+                fields: vec![QUEUE_FIELD.clone(), GROUP_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<QueueMessage>::try_from(document).map(|_| ())
+    }
+}
+
+fn validate_queue_name(kind: &str, name: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !name.is_empty() && name.len() <= MAX_QUEUE_NAME_LENGTH,
+        ErrorMetadata::bad_request(
+            "InvalidQueueName",
+            format!("{kind} names must be between 1 and {MAX_QUEUE_NAME_LENGTH} characters long")
+        )
+    );
+    Ok(())
+}
+
+/// Queues with consumer groups, built on top of the scheduler. Messages are
+/// delivered at least once to every consumer group of their queue by
+/// scheduling the group's handler, and are delivered again if the handler
+/// neither succeeds nor acks the message within the visibility timeout.
+pub struct QueueModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> QueueModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Create the consumer group, or update it if it already exists. Messages
+    /// are only delivered to groups that existed when they were enqueued.
+    pub async fn register_consumer(&mut self, consumer: QueueConsumer) -> anyhow::Result<()> {
+        validate_queue_name("Queue", &consumer.queue)?;
+        validate_queue_name("Consumer group", &consumer.group)?;
+        anyhow::ensure!(
+            consumer.max_attempts > 0,
+            ErrorMetadata::bad_request("InvalidMaxAttempts", "maxAttempts must be greater than 0")
+        );
+        anyhow::ensure!(
+            !consumer.visibility_timeout.is_zero(),
+            ErrorMetadata::bad_request(
+                "InvalidVisibilityTimeout",
+                "visibilityTimeoutMs must be greater than 0"
+            )
+        );
+        anyhow::ensure!(
+            consumer.max_concurrency != Some(0),
+            ErrorMetadata::bad_request(
+                "InvalidMaxConcurrency",
+                "maxConcurrency must be greater than 0"
+            )
+        );
+        match self.consumer(&consumer.queue, &consumer.group).await? {
+            Some(existing) => {
+                SystemMetadataModel::new(self.tx, self.namespace)
+                    .replace(existing.id(), consumer.try_into()?)
+                    .await?;
+            },
+            None => {
+                SystemMetadataModel::new(self.tx, self.namespace)
+                    .insert_metadata(&QUEUE_CONSUMERS_TABLE, consumer.try_into()?)
+                    .await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Delete the consumer group along with the messages it hasn't processed.
+    /// Returns the number of deleted messages.
+    pub async fn unregister_consumer(&mut self, queue: &str, group: &str) -> anyhow::Result<usize> {
+        let Some(consumer) = self.consumer(queue, group).await? else {
+            return Ok(0);
+        };
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(consumer.id())
+            .await?;
+        let query = Query::index_range(IndexRange {
+            index_name: QUEUE_MESSAGES_INDEX_BY_QUEUE_AND_GROUP.clone(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    QUEUE_FIELD.clone(),
+                    ConvexValue::try_from(queue.to_string())?.into(),
+                ),
+                IndexRangeExpression::Eq(
+                    GROUP_FIELD.clone(),
+                    ConvexValue::try_from(group.to_string())?.into(),
+                ),
+            ],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut message_ids = Vec::new();
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            message_ids.push(doc.id());
+        }
+        for message_id in &message_ids {
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .delete(*message_id)
+                .await?;
+        }
+        Ok(message_ids.len())
+    }
+
+    pub async fn consumer(
+        &mut self,
+        queue: &str,
+        group: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<QueueConsumer>>> {
+        let mut consumers = self.consumers_inner(queue, Some(group)).await?;
+        Ok(consumers.pop())
+    }
+
+    pub async fn consumers(
+        &mut self,
+        queue: &str,
+    ) -> anyhow::Result<Vec<ParsedDocument<QueueConsumer>>> {
+        self.consumers_inner(queue, None).await
+    }
+
+    async fn consumers_inner(
+        &mut self,
+        queue: &str,
+        group: Option<&str>,
+    ) -> anyhow::Result<Vec<ParsedDocument<QueueConsumer>>> {
+        if !self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .name_exists(&QUEUE_CONSUMERS_TABLE)
+        {
+            return Ok(vec![]);
+        }
+        let mut range = vec![IndexRangeExpression::Eq(
+            QUEUE_FIELD.clone(),
+            ConvexValue::try_from(queue.to_string())?.into(),
+        )];
+        if let Some(group) = group {
+            range.push(IndexRangeExpression::Eq(
+                GROUP_FIELD.clone(),
+                ConvexValue::try_from(group.to_string())?.into(),
+            ));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: QUEUE_CONSUMERS_INDEX_BY_QUEUE_AND_GROUP.clone(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut consumers = Vec::new();
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            consumers.push(doc.try_into()?);
+        }
+        Ok(consumers)
+    }
+
+    /// Add a message to the queue for each of its consumer groups. The
+    /// message is first delivered once `delay` has passed. Returns the number
+    /// of consumer groups the message will be delivered to.
+    pub async fn enqueue(
+        &mut self,
+        queue: &str,
+        payload: ConvexValue,
+        delay: Duration,
+    ) -> anyhow::Result<usize> {
+        let consumers = self.consumers(queue).await?;
+        anyhow::ensure!(
+            !consumers.is_empty(),
+            ErrorMetadata::bad_request(
+                "QueueHasNoConsumers",
+                format!("Queue {queue:?} has no consumer groups to deliver the message to")
+            )
+        );
+        let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+        let visible_ts = now.add(delay)?;
+        for consumer in &consumers {
+            let message = QueueMessage {
+                queue: queue.to_string(),
+                group: consumer.group.clone(),
+                payload: payload.clone(),
+                enqueued_ts: now,
+                visible_ts: Some(visible_ts),
+                attempts: 0,
+                delivery: None,
+            };
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .insert_metadata(&QUEUE_MESSAGES_TABLE, message.try_into()?)
+                .await?;
+        }
+        Ok(consumers.len())
+    }
+
+    /// Returns up to `limit` messages that are visible at `now`, along with
+    /// the time the next message becomes visible if there are no more.
+    pub async fn visible_messages(
+        &mut self,
+        now: Timestamp,
+        limit: usize,
+    ) -> anyhow::Result<(Vec<ParsedDocument<QueueMessage>>, Option<Timestamp>)> {
+        if !self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .name_exists(&QUEUE_MESSAGES_TABLE)
+        {
+            return Ok((vec![], None));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: QUEUE_MESSAGES_INDEX_BY_VISIBLE_TS.clone(),
+            range: vec![IndexRangeExpression::Gt(
+                VISIBLE_TS_FIELD.clone(),
+                value::ConvexValue::Null,
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut messages = Vec::new();
+        while messages.len() < limit {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                break;
+            };
+            let message: ParsedDocument<QueueMessage> = doc.try_into()?;
+            match message.visible_ts {
+                Some(visible_ts) if visible_ts > now => return Ok((messages, Some(visible_ts))),
+                _ => messages.push(message),
+            }
+        }
+        Ok((messages, None))
+    }
+
+    /// Deliver a visible message to its consumer group by scheduling a run of
+    /// the group's handler. If the previous delivery of the message succeeded
+    /// the message is deleted instead.
+    pub async fn deliver(
+        &mut self,
+        message: ParsedDocument<QueueMessage>,
+        context: ExecutionContext,
+    ) -> anyhow::Result<QueueDeliveryOutcome> {
+        let (message_id, mut message) = message.into_id_and_value();
+        let Some(consumer) = self.consumer(&message.queue, &message.group).await? else {
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .delete(message_id)
+                .await?;
+            return Ok(QueueDeliveryOutcome::Dropped);
+        };
+        if let Some(delivery) = &message.delivery {
+            let table_mapping = self.tx.table_mapping();
+            let job_id = delivery
+                .job_id
+                .to_resolved(&table_mapping.namespace(self.namespace).number_to_tablet())?;
+            let state = SchedulerModel::new(self.tx, self.namespace)
+                .check_status(job_id)
+                .await?;
+            if state == Some(ScheduledJobState::Success) {
+                SystemMetadataModel::new(self.tx, self.namespace)
+                    .delete(message_id)
+                    .await?;
+                return Ok(QueueDeliveryOutcome::Acked);
+            }
+        }
+        if message.attempts >= consumer.max_attempts {
+            message.visible_ts = None;
+            message.delivery = None;
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .replace(message_id, message.try_into()?)
+                .await?;
+            return Ok(QueueDeliveryOutcome::DeadLettered);
+        }
+
+        let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+        let receipt = self.tx.runtime().new_uuid_v4().to_string();
+        message.attempts += 1;
+        let args = ConvexArray::try_from(vec![ConvexValue::Object(ConvexObject::try_from(
+            btreemap! {
+                "messageId".parse()? => ConvexValue::try_from(
+                    DeveloperDocumentId::from(message_id).encode(),
+                )?,
+                "receipt".parse()? => ConvexValue::try_from(receipt.clone())?,
+                "payload".parse()? => message.payload.clone(),
+                "attempt".parse()? => ConvexValue::Int64(message.attempts.into()),
+            },
+        )?)])?;
+        let job_id = SchedulerModel::new(self.tx, self.namespace)
+            .schedule(
+                consumer.handler.clone().into(),
+                args,
+                self.tx.runtime().unix_timestamp(),
+                ScheduleOptions {
+                    max_concurrency: consumer.max_concurrency,
+                    ..Default::default()
+                },
+                context,
+            )
+            .await?;
+        message.visible_ts = Some(now.add(consumer.visibility_timeout)?);
+        message.delivery = Some(QueueDelivery {
+            receipt,
+            job_id: job_id.into(),
+        });
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .replace(message_id, message.try_into()?)
+            .await?;
+        Ok(QueueDeliveryOutcome::Delivered)
+    }
+
+    /// Acknowledge that a delivery of the message was processed so it isn't
+    /// delivered again.
+    pub async fn ack(
+        &mut self,
+        message_id: DeveloperDocumentId,
+        receipt: &str,
+    ) -> anyhow::Result<()> {
+        let table_mapping = self.tx.table_mapping();
+        let message_id =
+            message_id.to_resolved(&table_mapping.namespace(self.namespace).number_to_tablet())?;
+        anyhow::ensure!(
+            self.tx
+                .table_mapping()
+                .namespace(self.namespace)
+                .tablet_matches_name(message_id.tablet_id, &QUEUE_MESSAGES_TABLE),
+            ErrorMetadata::bad_request("InvalidQueueMessageId", "Invalid queue message id")
+        );
+        let Some(message) = self
+            .tx
+            .get(message_id)
+            .await?
+            .map(ParsedDocument::<QueueMessage>::try_from)
+            .transpose()?
+        else {
+            // The message was already acked.
+            return Ok(());
+        };
+        anyhow::ensure!(
+            message
+                .delivery
+                .as_ref()
+                .is_some_and(|delivery| delivery.receipt == receipt),
+            ErrorMetadata::bad_request(
+                "QueueReceiptExpired",
+                "The message was delivered again after this receipt was issued"
+            )
+        );
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(message_id)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use std::time::Duration;
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use sync_types::{
+    CanonicalizedUdfPath,
+    Timestamp,
+};
+use value::{
+    codegen_convex_serialization,
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+};
+
+/// A consumer group of a queue. Every message enqueued on the queue is
+/// delivered to each of its consumer groups by scheduling a run of the group's
+/// handler, so runs of the handler compete for the messages of the group.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct QueueConsumer {
+    pub queue: String,
+    pub group: String,
+    pub handler: CanonicalizedUdfPath,
+    // How long a delivered message is hidden from the group before it is
+    // delivered again, unless the handler succeeds or acks it first.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::strategy::Strategy::prop_map(0..86_400_000u64, \
+                             Duration::from_millis)")
+    )]
+    pub visibility_timeout: Duration,
+    // Number of deliveries after which the message is dead-lettered.
+    pub max_attempts: u32,
+    // Maximum number of runs of the handler that execute at the same time.
+    pub max_concurrency: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedQueueConsumer {
+    queue: String,
+    group: String,
+    handler: String,
+    visibility_timeout_ms: i64,
+    max_attempts: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_concurrency: Option<i64>,
+}
+
+impl TryFrom<QueueConsumer> for SerializedQueueConsumer {
+    type Error = anyhow::Error;
+
+    fn try_from(consumer: QueueConsumer) -> anyhow::Result<Self> {
+        Ok(Self {
+            queue: consumer.queue,
+            group: consumer.group,
+            handler: String::from(consumer.handler),
+            visibility_timeout_ms: consumer.visibility_timeout.as_millis().try_into()?,
+            max_attempts: consumer.max_attempts.into(),
+            max_concurrency: consumer.max_concurrency.map(i64::from),
+        })
+    }
+}
+
+impl TryFrom<SerializedQueueConsumer> for QueueConsumer {
+    type Error = anyhow::Error;
+
+    fn try_from(consumer: SerializedQueueConsumer) -> anyhow::Result<Self> {
+        Ok(Self {
+            queue: consumer.queue,
+            group: consumer.group,
+            handler: consumer.handler.parse()?,
+            visibility_timeout: Duration::from_millis(consumer.visibility_timeout_ms.try_into()?),
+            max_attempts: consumer.max_attempts.try_into()?,
+            max_concurrency: consumer.max_concurrency.map(u32::try_from).transpose()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(QueueConsumer, SerializedQueueConsumer);
+
+/// A message waiting to be processed by one consumer group of a queue.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct QueueMessage {
+    pub queue: String,
+    pub group: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::arbitrary::any_with::<ConvexValue>((Default::default(), \
+                        value::proptest::ExcludeSetsAndMaps(true)))"
+        )
+    )]
+    pub payload: ConvexValue,
+    pub enqueued_ts: Timestamp,
+    // When the message can next be delivered. While a delivery is in flight
+    // this is the end of its visibility timeout. None once the message has
+    // been dead-lettered.
+    pub visible_ts: Option<Timestamp>,
+    pub attempts: u32,
+    pub delivery: Option<QueueDelivery>,
+}
+
+/// The latest delivery of a queue message.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct QueueDelivery {
+    // Passed to the handler, which must present it to ack the message. A
+    // receipt stops being valid once the message is delivered again.
+    pub receipt: String,
+    // The scheduled job that runs the handler for this delivery.
+    pub job_id: DeveloperDocumentId,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedQueueMessage {
+    queue: String,
+    group: String,
+    // Serialize the payload as binary since we restrict what field names can
+    // be used in a `Document`'s top-level object.
+    #[serde(with = "serde_bytes")]
+    payload: Vec<u8>,
+    enqueued_ts: i64,
+    visible_ts: Option<i64>,
+    attempts: i64,
+    delivery: Option<SerializedQueueDelivery>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedQueueDelivery {
+    receipt: String,
+    job_id: String,
+}
+
+impl TryFrom<QueueMessage> for SerializedQueueMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(message: QueueMessage) -> anyhow::Result<Self> {
+        let payload_json = JsonValue::from(message.payload);
+        Ok(Self {
+            queue: message.queue,
+            group: message.group,
+            payload: serde_json::to_vec(&payload_json)?,
+            enqueued_ts: message.enqueued_ts.into(),
+            visible_ts: message.visible_ts.map(|ts| ts.into()),
+            attempts: message.attempts.into(),
+            delivery: message.delivery.map(|delivery| SerializedQueueDelivery {
+                receipt: delivery.receipt,
+                job_id: delivery.job_id.encode(),
+            }),
+        })
+    }
+}
+
+impl TryFrom<SerializedQueueMessage> for QueueMessage {
+    type Error = anyhow::Error;
+
+    fn try_from(message: SerializedQueueMessage) -> anyhow::Result<Self> {
+        let payload_json: JsonValue = serde_json::from_slice(&message.payload)?;
+        Ok(Self {
+            queue: message.queue,
+            group: message.group,
+            payload: payload_json.try_into()?,
+            enqueued_ts: message.enqueued_ts.try_into()?,
+            visible_ts: message.visible_ts.map(|ts| ts.try_into()).transpose()?,
+            attempts: message.attempts.try_into()?,
+            delivery: message
+                .delivery
+                .map(|delivery| {
+                    anyhow::Ok(QueueDelivery {
+                        receipt: delivery.receipt,
+                        job_id: DeveloperDocumentId::decode(&delivery.job_id)?,
+                    })
+                })
+                .transpose()?,
+        })
+    }
+}
+
+mod queue_message_serde {
+    use value::codegen_convex_serialization;
+
+    use super::{
+        QueueMessage,
+        SerializedQueueMessage,
+    };
+
+    codegen_convex_serialization!(QueueMessage, SerializedQueueMessage);
+}
+
+/// What the dispatcher did with a message whose visibility timeout expired.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueDeliveryOutcome {
+    /// Scheduled a run of the consumer group's handler.
+    Delivered,
+    /// The previous delivery succeeded, so the message was deleted.
+    Acked,
+    /// The message ran out of attempts.
+    DeadLettered,
+    /// The consumer group no longer exists, so the message was deleted.
+    Dropped,
+}
+
+impl QueueDeliveryOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delivered => "delivered",
+            Self::Acked => "acked",
+            Self::DeadLettered => "dead_lettered",
+            Self::Dropped => "dropped",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+    };
+
+    use crate::queues::types::{
+        QueueConsumer,
+        QueueMessage,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+        #[test]
+        fn test_queue_consumer_roundtrips(v in any::<QueueConsumer>()) {
+            assert_roundtrips::<QueueConsumer, ConvexObject>(v);
+        }
+
+        #[test]
+        fn test_queue_message_roundtrips(v in any::<QueueMessage>()) {
+            assert_roundtrips::<QueueMessage, ConvexObject>(v);
+        }
+    }
+}
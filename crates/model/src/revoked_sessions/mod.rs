@@ -0,0 +1,134 @@
+use std::sync::LazyLock;
+
+use common::{
+    auth::SessionClaims,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::SessionRevocation;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Table of revoked user sessions, which is consulted whenever a user's token
+/// is validated.
+pub static REVOKED_SESSIONS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_revoked_sessions"
+        .parse()
+        .expect("Invalid built-in revoked sessions table")
+});
+
+static SUBJECT_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "subject".parse().expect("Invalid built-in field"));
+
+pub static REVOKED_SESSIONS_INDEX_BY_SUBJECT: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&REVOKED_SESSIONS_TABLE, "by_subject"));
+
+pub struct RevokedSessionsTable;
+impl SystemTable for RevokedSessionsTable {
+    fn table_name(&self) -> &'static TableName {
+        &REVOKED_SESSIONS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: REVOKED_SESSIONS_INDEX_BY_SUBJECT.clone(),
+            fields: vec![SUBJECT_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<SessionRevocation>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct RevokedSessionsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> RevokedSessionsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    /// Revoke the sessions of `subject` from `issuer`, or from every issuer if
+    /// `issuer` is `None`. Revokes only the token with the `jti` claim
+    /// `token_id` if it's given, and otherwise every token issued until now.
+    pub async fn revoke(
+        &mut self,
+        subject: String,
+        issuer: Option<String>,
+        token_id: Option<String>,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("revoke_sessions"));
+        }
+        let revocation = SessionRevocation {
+            subject,
+            issuer,
+            token_id,
+            revoked_at: self.tx.runtime().unix_timestamp(),
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&REVOKED_SESSIONS_TABLE, revocation.try_into()?)
+            .await
+    }
+
+    /// Whether a token for `subject` from `issuer` with `claims` has been
+    /// revoked.
+    pub async fn is_revoked(
+        &mut self,
+        issuer: &str,
+        subject: &str,
+        claims: &SessionClaims,
+    ) -> anyhow::Result<bool> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("is_session_revoked"));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: REVOKED_SESSIONS_INDEX_BY_SUBJECT.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                SUBJECT_FIELD.clone(),
+                ConvexValue::try_from(subject)?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            let revocation: ParsedDocument<SessionRevocation> = doc.try_into()?;
+            if revocation.revokes(issuer, claims) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,131 @@
+use common::{
+    auth::SessionClaims,
+    runtime::UnixTimestamp,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A revocation of a user's sessions. Tokens it matches fail to authenticate
+/// even though they haven't expired, and sessions already authenticated with
+/// them are cut off.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SessionRevocation {
+    /// The `sub` claim of the revoked tokens.
+    pub subject: String,
+    /// The `iss` claim of the revoked tokens, or `None` to revoke tokens from
+    /// every issuer.
+    pub issuer: Option<String>,
+    /// Only revoke the token with this `jti` claim, rather than every token
+    /// issued before the revocation.
+    pub token_id: Option<String>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub revoked_at: UnixTimestamp,
+}
+
+impl SessionRevocation {
+    /// Whether this revocation applies to a token for its subject from
+    /// `issuer`.
+    pub fn revokes(&self, issuer: &str, claims: &SessionClaims) -> bool {
+        if let Some(revoked_issuer) = &self.issuer
+            && revoked_issuer.trim_end_matches('/') != issuer.trim_end_matches('/')
+        {
+            return false;
+        }
+        match &self.token_id {
+            Some(token_id) => claims.token_id.as_ref() == Some(token_id),
+            // `iat` only has second precision, so tokens issued in the same
+            // second as the revocation are revoked too. Tokens without an
+            // `iat` can't be told apart from ones issued before the
+            // revocation.
+            None => claims.issued_at.map_or(true, |issued_at| {
+                issued_at.as_secs() <= self.revoked_at.as_secs()
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSessionRevocation {
+    subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    issuer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    token_id: Option<String>,
+    revoked_at_nanos: i64,
+}
+
+impl TryFrom<SessionRevocation> for SerializedSessionRevocation {
+    type Error = anyhow::Error;
+
+    fn try_from(revocation: SessionRevocation) -> anyhow::Result<Self> {
+        Ok(Self {
+            subject: revocation.subject,
+            issuer: revocation.issuer,
+            token_id: revocation.token_id,
+            revoked_at_nanos: revocation.revoked_at.as_nanos().try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedSessionRevocation> for SessionRevocation {
+    type Error = anyhow::Error;
+
+    fn try_from(revocation: SerializedSessionRevocation) -> anyhow::Result<Self> {
+        Ok(Self {
+            subject: revocation.subject,
+            issuer: revocation.issuer,
+            token_id: revocation.token_id,
+            revoked_at: UnixTimestamp::from_nanos(revocation.revoked_at_nanos.try_into()?),
+        })
+    }
+}
+
+codegen_convex_serialization!(SessionRevocation, SerializedSessionRevocation);
+
+#[cfg(test)]
+mod tests {
+    use common::{
+        auth::SessionClaims,
+        runtime::UnixTimestamp,
+    };
+
+    use crate::revoked_sessions::types::SessionRevocation;
+
+    #[test]
+    fn test_session_revocation_revokes() {
+        let claims = |issued_at: u64, token_id: &str| SessionClaims {
+            issued_at: Some(UnixTimestamp::from_millis(issued_at * 1000)),
+            token_id: Some(token_id.to_string()),
+        };
+        let all_sessions = SessionRevocation {
+            subject: "user-1".to_string(),
+            issuer: None,
+            token_id: None,
+            revoked_at: UnixTimestamp::from_millis(100_500),
+        };
+        assert!(all_sessions.revokes("https://issuer.dev", &claims(90, "a")));
+        assert!(all_sessions.revokes("https://issuer.dev", &claims(100, "a")));
+        assert!(!all_sessions.revokes("https://issuer.dev", &claims(101, "a")));
+        assert!(all_sessions.revokes("https://issuer.dev", &SessionClaims::default()));
+
+        let one_token = SessionRevocation {
+            issuer: Some("https://issuer.dev/".to_string()),
+            token_id: Some("a".to_string()),
+            ..all_sessions
+        };
+        assert!(one_token.revokes("https://issuer.dev", &claims(200, "a")));
+        assert!(!one_token.revokes("https://issuer.dev", &claims(90, "b")));
+        assert!(!one_token.revokes("https://other.dev", &claims(90, "a")));
+    }
+}
@@ -0,0 +1,411 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::{
+        IndexName,
+        Timestamp,
+    },
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use saffron::Cron;
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::{
+    ExportRun,
+    ExportRunState,
+    ExportSchedule,
+};
+
+use crate::{
+    cron_jobs::next_ts::next_cron_ts,
+    SystemIndex,
+    SystemTable,
+};
+
+/// Recurring exports of the deployment's tables to an external bucket.
+pub static SCHEDULED_EXPORTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_scheduled_exports"
+        .parse()
+        .expect("Invalid built-in scheduled exports table")
+});
+
+/// Progress and outcome of each run of a scheduled export.
+pub static SCHEDULED_EXPORT_RUNS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_scheduled_export_runs"
+        .parse()
+        .expect("Invalid built-in scheduled export runs table")
+});
+
+static SCHEDULE_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "schedule".parse().expect("Invalid built-in field"));
+
+static STARTED_TS_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "startedTs".parse().expect("Invalid built-in field"));
+
+pub static SCHEDULED_EXPORT_RUNS_INDEX_BY_SCHEDULE: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SCHEDULED_EXPORT_RUNS_TABLE, "by_schedule_and_started_ts"));
+
+pub struct ScheduledExportsTable;
+impl SystemTable for ScheduledExportsTable {
+    fn table_name(&self) -> &'static TableName {
+        &SCHEDULED_EXPORTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ExportSchedule>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct ScheduledExportRunsTable;
+impl SystemTable for ScheduledExportRunsTable {
+    fn table_name(&self) -> &'static TableName {
+        &SCHEDULED_EXPORT_RUNS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: SCHEDULED_EXPORT_RUNS_INDEX_BY_SCHEDULE.clone(),
+            fields: vec![SCHEDULE_FIELD.clone(), STARTED_TS_FIELD.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ExportRun>::try_from(document).map(|_| ())
+    }
+}
+
+/// The first time after `after` that `schedule` should run.
+pub fn next_schedule_ts(schedule: &ExportSchedule, after: Timestamp) -> anyhow::Result<Timestamp> {
+    let cron: Cron = schedule
+        .cron
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Invalid cron expression {:?}: {e}", schedule.cron))?;
+    next_cron_ts(&cron, schedule.timezone.unwrap_or(chrono_tz::UTC), after)
+}
+
+pub struct ScheduledExportsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> ScheduledExportsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    fn check_system(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<ExportSchedule>>> {
+        self.check_admin("list_export_schedules")?;
+        let query = Query::full_table_scan(SCHEDULED_EXPORTS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut schedules = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            schedules.push(doc.try_into()?);
+        }
+        Ok(schedules)
+    }
+
+    pub async fn get(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<ExportSchedule>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|schedule| schedule.name == name))
+    }
+
+    /// Configure a schedule, replacing any existing schedule with the same
+    /// name. Its first run is at the next time its cron expression matches.
+    pub async fn add_or_update(
+        &mut self,
+        mut schedule: ExportSchedule,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("add_export_schedule")?;
+        schedule.validate()?;
+        let now = self.tx.runtime().generate_timestamp()?;
+        schedule.next_ts = next_schedule_ts(&schedule, now)?;
+        match self.get(&schedule.name).await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), schedule.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&SCHEDULED_EXPORTS_TABLE, schedule.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Delete a schedule. Its runs, and the exports they uploaded, are kept.
+    pub async fn delete(&mut self, name: &str) -> anyhow::Result<()> {
+        self.check_admin("delete_export_schedule")?;
+        let Some(existing) = self.get(name).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "ExportScheduleNotFound",
+                format!("There is no export schedule named {name:?}"),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_next_ts(
+        &mut self,
+        id: ResolvedDocumentId,
+        next_ts: Timestamp,
+    ) -> anyhow::Result<()> {
+        self.check_system("set_export_schedule_next_ts")?;
+        let Some(document) = self.tx.get(id).await? else {
+            anyhow::bail!("Export schedule {id} not found");
+        };
+        let mut schedule: ParsedDocument<ExportSchedule> = document.try_into()?;
+        schedule.next_ts = next_ts;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, schedule.into_value().try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` of the runs of `schedule`, newest first.
+    pub async fn latest_runs(
+        &mut self,
+        schedule: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<ExportRun>>> {
+        self.check_admin("list_export_runs")?;
+        let query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_EXPORT_RUNS_INDEX_BY_SCHEDULE.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                SCHEDULE_FIELD.clone(),
+                ConvexValue::try_from(schedule.to_string())?.into(),
+            )],
+            order: Order::Desc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut runs = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            runs.push(doc.try_into()?);
+        }
+        Ok(runs)
+    }
+
+    /// Every run of `schedule`, oldest first.
+    pub async fn runs(&mut self, schedule: &str) -> anyhow::Result<Vec<ParsedDocument<ExportRun>>> {
+        let mut runs = self.latest_runs(schedule, usize::MAX).await?;
+        runs.reverse();
+        Ok(runs)
+    }
+
+    pub async fn start_run(
+        &mut self,
+        schedule: &str,
+        started_ts: Timestamp,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_system("start_export_run")?;
+        let run = ExportRun {
+            schedule: schedule.to_string(),
+            started_ts,
+            state: ExportRunState::Exporting,
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .insert(&SCHEDULED_EXPORT_RUNS_TABLE, run.try_into()?)
+            .await
+    }
+
+    pub async fn set_run_state(
+        &mut self,
+        id: ResolvedDocumentId,
+        state: ExportRunState,
+    ) -> anyhow::Result<()> {
+        self.check_system("set_export_run_state")?;
+        let Some(document) = self.tx.get(id).await? else {
+            anyhow::bail!("Export run {id} not found");
+        };
+        let mut run: ParsedDocument<ExportRun> = document.try_into()?;
+        run.state = state;
+        SystemMetadataModel::new_global(self.tx)
+            .replace(id, run.into_value().try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_run(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        self.check_system("delete_export_run")?;
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use common::types::Timestamp;
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        scheduled_exports::{
+            next_schedule_ts,
+            types::{
+                BucketProvider,
+                ExportDestination,
+                ExportRunState,
+                ExportSchedule,
+            },
+            ScheduledExportsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn schedule(name: &str, cron: &str) -> ExportSchedule {
+        ExportSchedule {
+            name: name.to_string(),
+            cron: cron.to_string(),
+            timezone: None,
+            include_storage: false,
+            destination: ExportDestination {
+                provider: BucketProvider::S3,
+                bucket: "backups".to_string(),
+                prefix: "convex/".to_string(),
+                region: "us-east-1".to_string(),
+                endpoint: None,
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+            },
+            retention_count: 3,
+            next_ts: Timestamp::MIN,
+        }
+    }
+
+    #[test]
+    fn test_next_schedule_ts() -> anyhow::Result<()> {
+        // 2024-01-01T00:00:00Z
+        let after = Timestamp::try_from(1_704_067_200_000_000_000i64)?;
+        let mut daily = schedule("daily", "30 3 * * *");
+        assert_eq!(
+            i64::from(next_schedule_ts(&daily, after)?),
+            1_704_079_800_000_000_000
+        );
+        daily.timezone = Some(chrono_tz::Tz::from_str("America/New_York").unwrap());
+        assert_eq!(
+            i64::from(next_schedule_ts(&daily, after)?),
+            1_704_097_800_000_000_000
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(schedule("nightly", "0 3 * * *").validate().is_ok());
+        assert!(schedule("nightly", "not a cron").validate().is_err());
+        assert!(schedule("a/b", "0 3 * * *").validate().is_err());
+        let mut invalid = schedule("nightly", "0 3 * * *");
+        invalid.retention_count = 0;
+        assert!(invalid.validate().is_err());
+        let mut invalid = schedule("nightly", "0 3 * * *");
+        invalid.destination.endpoint = Some("minio:9000".to_string());
+        assert!(invalid.validate().is_err());
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_schedules_and_runs(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = ScheduledExportsModel::new(&mut tx);
+        let id = model
+            .add_or_update(schedule("nightly", "0 3 * * *"))
+            .await?;
+        let mut updated = schedule("nightly", "0 4 * * *");
+        updated.retention_count = 7;
+        assert_eq!(model.add_or_update(updated).await?, id);
+        let schedules = model.list().await?;
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].cron, "0 4 * * *");
+        assert_eq!(schedules[0].retention_count, 7);
+        assert!(schedules[0].next_ts > Timestamp::MIN);
+
+        let first = model.start_run("nightly", Timestamp::must(1)).await?;
+        let second = model.start_run("nightly", Timestamp::must(2)).await?;
+        model
+            .set_run_state(
+                first,
+                ExportRunState::Failed {
+                    error: "Access denied".to_string(),
+                    failed_ts: Timestamp::must(3),
+                },
+            )
+            .await?;
+        model.start_run("weekly", Timestamp::must(4)).await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = ScheduledExportsModel::new(&mut tx);
+        let latest = model.latest_runs("nightly", 1).await?;
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].id(), second);
+        assert_eq!(latest[0].state, ExportRunState::Exporting);
+        let runs = model.runs("nightly").await?;
+        assert_eq!(runs.len(), 2);
+        assert!(matches!(runs[0].state, ExportRunState::Failed { .. }));
+
+        model.delete("nightly").await?;
+        assert!(model.list().await?.is_empty());
+        assert!(model.delete("nightly").await.is_err());
+        // Runs outlive their schedule so that their exports can still be found.
+        assert_eq!(model.runs("nightly").await?.len(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,395 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use chrono_tz::Tz;
+use common::types::Timestamp;
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// The service hosting the bucket that scheduled exports are uploaded to.
+/// Both are written to through the S3 API, which GCS supports with HMAC keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum BucketProvider {
+    S3,
+    Gcs,
+}
+
+impl BucketProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BucketProvider::S3 => "s3",
+            BucketProvider::Gcs => "gcs",
+        }
+    }
+}
+
+impl FromStr for BucketProvider {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "s3" => Ok(BucketProvider::S3),
+            "gcs" => Ok(BucketProvider::Gcs),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidBucketProvider",
+                format!("Unknown bucket provider {s}, expected \"s3\" or \"gcs\""),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for BucketProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The bucket that a schedule's exports are uploaded to, and the HMAC
+/// credentials to upload them with.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ExportDestination {
+    pub provider: BucketProvider,
+    pub bucket: String,
+    // Prepended to the object key of each export, e.g. `backups/`.
+    pub prefix: String,
+    // The bucket's region, e.g. `us-east-1`. GCS buckets use `auto`.
+    pub region: String,
+    // Overrides the provider's endpoint, e.g. for MinIO or another
+    // S3-compatible store.
+    pub endpoint: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+/// A recurring export of the deployment's tables as a ZIP snapshot, uploaded
+/// to a bucket that keeps the latest `retention_count` of them.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ExportSchedule {
+    // Unique name of the schedule, used in the object key of its exports.
+    pub name: String,
+    // Five field cron expression like `0 3 * * *`.
+    pub cron: String,
+    // IANA timezone that the hours and minutes of `cron` are in. Defaults to
+    // UTC.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::option::of(proptest::sample::select(&\
+                             chrono_tz::TZ_VARIANTS[..]))")
+    )]
+    pub timezone: Option<Tz>,
+    pub include_storage: bool,
+    pub destination: ExportDestination,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "1..=i64::MAX as u64")
+    )]
+    pub retention_count: u64,
+    // When the next export should start.
+    pub next_ts: Timestamp,
+}
+
+impl ExportSchedule {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid =
+            |msg: String| anyhow::anyhow!(ErrorMetadata::bad_request("InvalidExportSchedule", msg));
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid(format!(
+                "Invalid schedule name {:?}, expected letters, digits, `-` and `_`",
+                self.name
+            )));
+        }
+        self.cron
+            .parse::<saffron::Cron>()
+            .map_err(|e| invalid(format!("Invalid cron expression {:?}: {e}", self.cron)))?;
+        if self.retention_count == 0 {
+            return Err(invalid("retentionCount must be at least 1".to_string()));
+        }
+        let destination = &self.destination;
+        if destination.bucket.is_empty() || destination.bucket.contains('/') {
+            return Err(invalid(format!(
+                "Invalid bucket name {:?}",
+                destination.bucket
+            )));
+        }
+        if let Some(endpoint) = &destination.endpoint
+            && !(endpoint.starts_with("https://") || endpoint.starts_with("http://"))
+        {
+            return Err(invalid(format!(
+                "Endpoint {endpoint:?} must be an http or https URL"
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_timezone(tz: &str) -> anyhow::Result<Tz> {
+    tz.parse::<Tz>().map_err(|_| {
+        anyhow::anyhow!(ErrorMetadata::bad_request(
+            "InvalidExportSchedule",
+            format!("Unknown timezone {tz:?}, expected an IANA timezone like \"America/New_York\""),
+        ))
+    })
+}
+
+/// How far a scheduled export has gotten.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum ExportRunState {
+    // Writing the ZIP snapshot to the deployment's export storage.
+    Exporting,
+    // Copying the snapshot to the bucket.
+    Uploading {
+        snapshot_ts: Timestamp,
+    },
+    Completed {
+        snapshot_ts: Timestamp,
+        object_key: String,
+        #[cfg_attr(
+            any(test, feature = "testing"),
+            proptest(strategy = "0..=i64::MAX as u64")
+        )]
+        size_bytes: u64,
+        completed_ts: Timestamp,
+    },
+    Failed {
+        error: String,
+        failed_ts: Timestamp,
+    },
+}
+
+impl ExportRunState {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            ExportRunState::Completed { .. } | ExportRunState::Failed { .. }
+        )
+    }
+}
+
+/// One run of an export schedule. Runs of a schedule happen one at a time,
+/// so only its latest run can be unfinished.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ExportRun {
+    pub schedule: String,
+    pub started_ts: Timestamp,
+    pub state: ExportRunState,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedExportDestination {
+    provider: String,
+    bucket: String,
+    prefix: String,
+    region: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    endpoint: Option<String>,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedExportSchedule {
+    name: String,
+    cron: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timezone: Option<String>,
+    include_storage: bool,
+    destination: SerializedExportDestination,
+    retention_count: i64,
+    next_ts: i64,
+}
+
+impl TryFrom<ExportSchedule> for SerializedExportSchedule {
+    type Error = anyhow::Error;
+
+    fn try_from(schedule: ExportSchedule) -> anyhow::Result<Self> {
+        let ExportDestination {
+            provider,
+            bucket,
+            prefix,
+            region,
+            endpoint,
+            access_key_id,
+            secret_access_key,
+        } = schedule.destination;
+        Ok(Self {
+            name: schedule.name,
+            cron: schedule.cron,
+            timezone: schedule.timezone.map(|tz| tz.name().to_string()),
+            include_storage: schedule.include_storage,
+            destination: SerializedExportDestination {
+                provider: provider.to_string(),
+                bucket,
+                prefix,
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+            },
+            retention_count: schedule.retention_count.try_into()?,
+            next_ts: schedule.next_ts.into(),
+        })
+    }
+}
+
+impl TryFrom<SerializedExportSchedule> for ExportSchedule {
+    type Error = anyhow::Error;
+
+    fn try_from(schedule: SerializedExportSchedule) -> anyhow::Result<Self> {
+        let SerializedExportDestination {
+            provider,
+            bucket,
+            prefix,
+            region,
+            endpoint,
+            access_key_id,
+            secret_access_key,
+        } = schedule.destination;
+        Ok(Self {
+            name: schedule.name,
+            cron: schedule.cron,
+            timezone: schedule
+                .timezone
+                .map(|tz| parse_timezone(&tz))
+                .transpose()?,
+            include_storage: schedule.include_storage,
+            destination: ExportDestination {
+                provider: provider.parse()?,
+                bucket,
+                prefix,
+                region,
+                endpoint,
+                access_key_id,
+                secret_access_key,
+            },
+            retention_count: schedule.retention_count.try_into()?,
+            next_ts: schedule.next_ts.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(ExportSchedule, SerializedExportSchedule);
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum SerializedExportRunState {
+    Exporting,
+    #[serde(rename_all = "camelCase")]
+    Uploading {
+        snapshot_ts: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        snapshot_ts: i64,
+        object_key: String,
+        size_bytes: i64,
+        completed_ts: i64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Failed {
+        error: String,
+        failed_ts: i64,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedExportRun {
+    schedule: String,
+    started_ts: i64,
+    state: SerializedExportRunState,
+}
+
+impl TryFrom<ExportRun> for SerializedExportRun {
+    type Error = anyhow::Error;
+
+    fn try_from(run: ExportRun) -> anyhow::Result<Self> {
+        let state = match run.state {
+            ExportRunState::Exporting => SerializedExportRunState::Exporting,
+            ExportRunState::Uploading { snapshot_ts } => SerializedExportRunState::Uploading {
+                snapshot_ts: snapshot_ts.into(),
+            },
+            ExportRunState::Completed {
+                snapshot_ts,
+                object_key,
+                size_bytes,
+                completed_ts,
+            } => SerializedExportRunState::Completed {
+                snapshot_ts: snapshot_ts.into(),
+                object_key,
+                size_bytes: size_bytes.try_into()?,
+                completed_ts: completed_ts.into(),
+            },
+            ExportRunState::Failed { error, failed_ts } => SerializedExportRunState::Failed {
+                error,
+                failed_ts: failed_ts.into(),
+            },
+        };
+        Ok(Self {
+            schedule: run.schedule,
+            started_ts: run.started_ts.into(),
+            state,
+        })
+    }
+}
+
+impl TryFrom<SerializedExportRun> for ExportRun {
+    type Error = anyhow::Error;
+
+    fn try_from(run: SerializedExportRun) -> anyhow::Result<Self> {
+        let state = match run.state {
+            SerializedExportRunState::Exporting => ExportRunState::Exporting,
+            SerializedExportRunState::Uploading { snapshot_ts } => ExportRunState::Uploading {
+                snapshot_ts: snapshot_ts.try_into()?,
+            },
+            SerializedExportRunState::Completed {
+                snapshot_ts,
+                object_key,
+                size_bytes,
+                completed_ts,
+            } => ExportRunState::Completed {
+                snapshot_ts: snapshot_ts.try_into()?,
+                object_key,
+                size_bytes: size_bytes.try_into()?,
+                completed_ts: completed_ts.try_into()?,
+            },
+            SerializedExportRunState::Failed { error, failed_ts } => ExportRunState::Failed {
+                error,
+                failed_ts: failed_ts.try_into()?,
+            },
+        };
+        Ok(Self {
+            schedule: run.schedule,
+            started_ts: run.started_ts.try_into()?,
+            state,
+        })
+    }
+}
+
+mod export_run_serde {
+    use value::codegen_convex_serialization;
+
+    use super::{
+        ExportRun,
+        SerializedExportRun,
+    };
+
+    codegen_convex_serialization!(ExportRun, SerializedExportRun);
+}
@@ -0,0 +1,185 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    knobs::{
+        SCHEDULED_JOB_BATCH_CHUNK_MAX_SIZE_BYTES,
+        SCHEDULED_JOB_BATCH_CHUNK_SIZE,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    Size,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    BatchedJob,
+    ScheduledJobBatchChunk,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static SCHEDULED_JOB_BATCHES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_scheduled_job_batches"
+        .parse()
+        .expect("_scheduled_job_batches is not a valid system table name")
+});
+
+pub static SCHEDULED_JOB_BATCHES_INDEX_BY_BATCH_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SCHEDULED_JOB_BATCHES_TABLE, "by_batch_id"));
+static BATCH_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "batchId".parse().expect("invalid batchId field"));
+
+pub struct ScheduledJobBatchesTable;
+impl SystemTable for ScheduledJobBatchesTable {
+    fn table_name(&self) -> &'static TableName {
+        &SCHEDULED_JOB_BATCHES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: SCHEDULED_JOB_BATCHES_INDEX_BY_BATCH_ID.clone(),
+            fields: vec![BATCH_ID_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<ScheduledJobBatchChunk>::try_from(document).map(|_| ())
+    }
+}
+
+/// Stores batches of scheduled jobs until the scheduler has written them to
+/// the scheduled jobs table. See `SchedulerModel::schedule_batch`.
+pub struct ScheduledJobBatchModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> ScheduledJobBatchModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    /// Split the jobs into chunks of at most SCHEDULED_JOB_BATCH_CHUNK_SIZE
+    /// jobs and SCHEDULED_JOB_BATCH_CHUNK_MAX_SIZE_BYTES of arguments, and
+    /// store them under a new batch id.
+    pub async fn insert(&mut self, jobs: Vec<BatchedJob>) -> anyhow::Result<String> {
+        let batch_id = self.tx.runtime().new_uuid_v4().to_string();
+        let mut chunk = Vec::new();
+        let mut chunk_size = 0;
+        for job in jobs {
+            let job_size = job.udf_args.size();
+            if !chunk.is_empty()
+                && (chunk.len() >= *SCHEDULED_JOB_BATCH_CHUNK_SIZE
+                    || chunk_size + job_size > *SCHEDULED_JOB_BATCH_CHUNK_MAX_SIZE_BYTES)
+            {
+                self.insert_chunk(batch_id.clone(), std::mem::take(&mut chunk))
+                    .await?;
+                chunk_size = 0;
+            }
+            chunk_size += job_size;
+            chunk.push(job);
+        }
+        if !chunk.is_empty() {
+            self.insert_chunk(batch_id.clone(), chunk).await?;
+        }
+        Ok(batch_id)
+    }
+
+    async fn insert_chunk(
+        &mut self,
+        batch_id: String,
+        jobs: Vec<BatchedJob>,
+    ) -> anyhow::Result<()> {
+        let chunk = ScheduledJobBatchChunk { batch_id, jobs };
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .insert_metadata(&SCHEDULED_JOB_BATCHES_TABLE, chunk.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the oldest chunk that hasn't been expanded into scheduled jobs.
+    pub async fn next_chunk(
+        &mut self,
+    ) -> anyhow::Result<Option<ParsedDocument<ScheduledJobBatchChunk>>> {
+        if !self
+            .tx
+            .table_mapping()
+            .namespace(self.namespace)
+            .name_exists(&SCHEDULED_JOB_BATCHES_TABLE)
+        {
+            return Ok(None);
+        }
+        let query = Query::full_table_scan(SCHEDULED_JOB_BATCHES_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        query_stream
+            .next(self.tx, Some(1))
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    pub async fn delete_chunk(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .delete(id)
+            .await?;
+        Ok(())
+    }
+
+    /// Delete up to `limit` of the chunks of the batch that haven't been
+    /// expanded yet. Returns the number of jobs that will now never be
+    /// scheduled and the number of deleted chunks.
+    pub async fn delete_remaining_chunks(
+        &mut self,
+        batch_id: &str,
+        limit: usize,
+    ) -> anyhow::Result<(usize, usize)> {
+        let query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOB_BATCHES_INDEX_BY_BATCH_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                BATCH_ID_FIELD.clone(),
+                ConvexValue::try_from(batch_id.to_string())?.into(),
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut chunks = Vec::new();
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            let chunk: ParsedDocument<ScheduledJobBatchChunk> = doc.try_into()?;
+            chunks.push(chunk);
+        }
+        let num_chunks = chunks.len();
+        let mut num_jobs = 0;
+        for chunk in chunks {
+            num_jobs += chunk.jobs.len();
+            self.delete_chunk(chunk.id()).await?;
+        }
+        Ok((num_jobs, num_chunks))
+    }
+}
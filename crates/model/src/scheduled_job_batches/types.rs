@@ -0,0 +1,141 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use sync_types::{
+    CanonicalizedUdfPath,
+    Timestamp,
+};
+use value::{
+    codegen_convex_serialization,
+    ConvexArray,
+    Size,
+};
+
+use crate::scheduled_jobs::types::{
+    ScheduleOptions,
+    ScheduledJobPriority,
+};
+
+/// A job that was scheduled as part of a batch but hasn't been written to the
+/// scheduled jobs table yet.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct BatchedJob {
+    pub udf_path: CanonicalizedUdfPath,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::arbitrary::any_with::<ConvexArray>((0..4).into())")
+    )]
+    pub udf_args: ConvexArray,
+    pub original_scheduled_ts: Timestamp,
+    pub priority: ScheduledJobPriority,
+    pub max_concurrency: Option<u32>,
+}
+
+impl BatchedJob {
+    pub fn options(&self) -> ScheduleOptions {
+        ScheduleOptions {
+            priority: self.priority,
+            max_concurrency: self.max_concurrency,
+        }
+    }
+}
+
+/// Part of a batch of scheduled jobs. Scheduling a batch only writes a handful
+/// of chunks, and the scheduler expands each chunk into scheduled jobs in its
+/// own transaction, so a single mutation can schedule thousands of jobs.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ScheduledJobBatchChunk {
+    pub batch_id: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::collection::vec(proptest::prelude::any::<BatchedJob>(), 0..4)"
+        )
+    )]
+    pub jobs: Vec<BatchedJob>,
+}
+
+impl ScheduledJobBatchChunk {
+    pub fn args_size(&self) -> usize {
+        self.jobs.iter().map(|job| job.udf_args.size()).sum()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedBatchedJob {
+    udf_path: String,
+    udf_args: JsonValue,
+    original_scheduled_ts: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_concurrency: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedScheduledJobBatchChunk {
+    batch_id: String,
+    // Serialize the jobs as binary since we restrict what field names can be
+    // used in a `Document`'s top-level object.
+    #[serde(with = "serde_bytes")]
+    jobs: Vec<u8>,
+}
+
+impl TryFrom<ScheduledJobBatchChunk> for SerializedScheduledJobBatchChunk {
+    type Error = anyhow::Error;
+
+    fn try_from(chunk: ScheduledJobBatchChunk) -> anyhow::Result<Self> {
+        let jobs: Vec<_> = chunk
+            .jobs
+            .into_iter()
+            .map(|job| SerializedBatchedJob {
+                udf_path: String::from(job.udf_path),
+                udf_args: JsonValue::from(job.udf_args),
+                original_scheduled_ts: job.original_scheduled_ts.into(),
+                priority: (job.priority != ScheduledJobPriority::Normal)
+                    .then(|| job.priority.to_string()),
+                max_concurrency: job.max_concurrency,
+            })
+            .collect();
+        Ok(Self {
+            batch_id: chunk.batch_id,
+            jobs: serde_json::to_vec(&jobs)?,
+        })
+    }
+}
+
+impl TryFrom<SerializedScheduledJobBatchChunk> for ScheduledJobBatchChunk {
+    type Error = anyhow::Error;
+
+    fn try_from(chunk: SerializedScheduledJobBatchChunk) -> anyhow::Result<Self> {
+        let jobs: Vec<SerializedBatchedJob> = serde_json::from_slice(&chunk.jobs)?;
+        let jobs = jobs
+            .into_iter()
+            .map(|job| {
+                anyhow::Ok(BatchedJob {
+                    udf_path: job.udf_path.parse()?,
+                    udf_args: job.udf_args.try_into()?,
+                    original_scheduled_ts: job.original_scheduled_ts.try_into()?,
+                    priority: job
+                        .priority
+                        .map(|priority| priority.parse())
+                        .transpose()?
+                        .unwrap_or_default(),
+                    max_concurrency: job.max_concurrency,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            batch_id: chunk.batch_id,
+            jobs,
+        })
+    }
+}
+
+codegen_convex_serialization!(ScheduledJobBatchChunk, SerializedScheduledJobBatchChunk);
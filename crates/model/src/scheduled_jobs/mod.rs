@@ -9,11 +9,15 @@ use std::{
 use common::{
     components::CanonicalizedComponentFunctionPath,
     document::{
+        CreationTime,
         ParsedDocument,
         ResolvedDocument,
+        CREATION_TIME_FIELD_PATH,
     },
     execution_context::ExecutionContext,
     knobs::{
+        MAX_SCHEDULED_JOBS_SCANNED_PER_PAGE,
+        SCHEDULED_JOB_BATCH_MAX_SIZE,
         TRANSACTION_MAX_NUM_SCHEDULED,
         TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
     },
@@ -35,6 +39,7 @@ use common::{
 use database::{
     defaults::system_index,
     unauthorized_error,
+    BootstrapComponentsModel,
     ResolvedQuery,
     SystemMetadataModel,
     Transaction,
@@ -59,12 +64,22 @@ use value::{
 
 use self::{
     types::{
+        ScheduleOptions,
         ScheduledJob,
+        ScheduledJobFilter,
         ScheduledJobState,
+        ScheduledJobStateKind,
     },
     virtual_table::ScheduledJobsDocMapper,
 };
 use crate::{
+    scheduled_job_batches::{
+        types::{
+            BatchedJob,
+            ScheduledJobBatchChunk,
+        },
+        ScheduledJobBatchModel,
+    },
     SystemIndex,
     SystemTable,
 };
@@ -100,12 +115,24 @@ pub static SCHEDULED_JOBS_INDEX_BY_UDF_PATH: LazyLock<IndexName> =
     LazyLock::new(|| system_index(&SCHEDULED_JOBS_TABLE, "by_udf_path_and_next_event_ts"));
 pub static SCHEDULED_JOBS_INDEX_BY_COMPLETED_TS: LazyLock<IndexName> =
     LazyLock::new(|| system_index(&SCHEDULED_JOBS_TABLE, "by_completed_ts"));
+pub static SCHEDULED_JOBS_INDEX_BY_BATCH_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SCHEDULED_JOBS_TABLE, "by_batch_id_and_next_ts"));
 pub static NEXT_TS_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "nextTs".parse().expect("invalid nextTs field"));
 pub static COMPLETED_TS_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "completedTs".parse().expect("invalid completedTs field"));
 static UDF_PATH_FIELD: LazyLock<FieldPath> =
     LazyLock::new(|| "udfPath".parse().expect("invalid udfPath field"));
+static BATCH_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "batchId".parse().expect("invalid batchId field"));
+
+/// A page of scheduled jobs returned by [`SchedulerModel::list_page`].
+pub struct ScheduledJobPage {
+    pub jobs: Vec<ParsedDocument<ScheduledJob>>,
+    /// Creation time of the last job scanned, to continue listing from. None
+    /// once all jobs have been scanned.
+    pub cursor: Option<CreationTime>,
+}
 
 pub struct ScheduledJobsTable;
 impl SystemTable for ScheduledJobsTable {
@@ -133,6 +160,13 @@ impl SystemTable for ScheduledJobsTable {
                     .try_into()
                     .unwrap(),
             },
+            // By batch id and next ts. Used to cancel the pending jobs of a batch.
+            SystemIndex {
+                name: SCHEDULED_JOBS_INDEX_BY_BATCH_ID.clone(),
+                fields: vec![BATCH_ID_FIELD.clone(), NEXT_TS_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
         ]
     }
 
@@ -184,6 +218,10 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
             )
         );
         self.tx.scheduled_size.num_writes += 1;
+        self.check_scheduled_args_size(args)
+    }
+
+    fn check_scheduled_args_size(&mut self, args: &ConvexArray) -> anyhow::Result<()> {
         anyhow::ensure!(
             self.tx.scheduled_size.size + args.size()
                 <= *TRANSACTION_MAX_SCHEDULED_TOTAL_ARGUMENT_SIZE_BYTES,
@@ -200,11 +238,45 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         Ok(())
     }
 
+    /// Check that the component can have `num_new` more pending or
+    /// in-progress jobs. Jobs in batches that haven't been written to the
+    /// scheduled jobs table yet aren't counted.
+    async fn check_scheduled_jobs_quota(&mut self, num_new: usize) -> anyhow::Result<()> {
+        let quotas = BootstrapComponentsModel::new(self.tx)
+            .load_quotas(self.namespace)
+            .await?;
+        let Some(max_scheduled_jobs) = quotas.max_scheduled_jobs else {
+            return Ok(());
+        };
+        let index_query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOBS_INDEX.clone(),
+            range: vec![IndexRangeExpression::Gt(
+                NEXT_TS_FIELD.clone(),
+                value::ConvexValue::Null,
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let mut num_jobs = num_new as u64;
+        while num_jobs <= max_scheduled_jobs && query_stream.next(self.tx, None).await?.is_some() {
+            num_jobs += 1;
+        }
+        anyhow::ensure!(
+            num_jobs <= max_scheduled_jobs,
+            ErrorMetadata::bad_request(
+                "ComponentQuotaExceeded",
+                format!("Component is over its quota of {max_scheduled_jobs} scheduled functions"),
+            )
+        );
+        Ok(())
+    }
+
     pub async fn schedule(
         &mut self,
         udf_path: UdfPath,
         args: ConvexArray,
         ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<ResolvedDocumentId> {
         if udf_path.is_system()
@@ -212,8 +284,16 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         {
             anyhow::bail!(unauthorized_error("schedule"))
         }
+        anyhow::ensure!(
+            options.max_concurrency != Some(0),
+            ErrorMetadata::bad_request(
+                "InvalidMaxConcurrency",
+                "maxConcurrency must be greater than 0"
+            )
+        );
 
         self.check_scheduling_limits(&args)?;
+        self.check_scheduled_jobs_quota(1).await?;
 
         let now: Timestamp = self.tx.runtime().generate_timestamp()?;
         let original_scheduled_ts: Timestamp = ts.as_system_time().try_into()?;
@@ -227,6 +307,10 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
             next_ts: Some(original_scheduled_ts.max(now)),
             completed_ts: None,
             original_scheduled_ts,
+            attempts: 0,
+            priority: options.priority,
+            max_concurrency: options.max_concurrency,
+            batch_id: None,
         };
         let job = if let Some(parent_scheduled_job) = context.parent_scheduled_job {
             let table_mapping = self.tx.table_mapping();
@@ -249,6 +333,10 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
                             next_ts: None,
                             completed_ts: Some(*scheduled_ts),
                             original_scheduled_ts: *scheduled_ts,
+                            attempts: 0,
+                            priority: options.priority,
+                            max_concurrency: options.max_concurrency,
+                            batch_id: None,
                         }
                     },
                 }
@@ -265,6 +353,147 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         Ok(id)
     }
 
+    /// Schedule many jobs atomically. The jobs are stored in chunks and
+    /// written to the scheduled jobs table by the scheduler in the background,
+    /// so they don't count towards the number of functions a transaction can
+    /// schedule. Returns the id of the batch, which can be used to cancel it.
+    pub async fn schedule_batch(
+        &mut self,
+        jobs: Vec<(UdfPath, ConvexArray, UnixTimestamp, ScheduleOptions)>,
+        context: ExecutionContext,
+    ) -> anyhow::Result<String> {
+        anyhow::ensure!(
+            !jobs.is_empty(),
+            ErrorMetadata::bad_request(
+                "EmptyScheduledBatch",
+                "A scheduled batch must contain at least one function"
+            )
+        );
+        anyhow::ensure!(
+            jobs.len() <= *SCHEDULED_JOB_BATCH_MAX_SIZE,
+            ErrorMetadata::bad_request(
+                "ScheduledBatchTooLarge",
+                format!(
+                    "Too many functions scheduled in a single batch (limit: {})",
+                    *SCHEDULED_JOB_BATCH_MAX_SIZE,
+                )
+            )
+        );
+        self.check_scheduled_jobs_quota(jobs.len()).await?;
+        let mut batched_jobs = Vec::with_capacity(jobs.len());
+        for (udf_path, args, ts, options) in jobs {
+            if udf_path.is_system()
+                && !(self.tx.identity().is_admin() || self.tx.identity().is_system())
+            {
+                anyhow::bail!(unauthorized_error("schedule"))
+            }
+            anyhow::ensure!(
+                options.max_concurrency != Some(0),
+                ErrorMetadata::bad_request(
+                    "InvalidMaxConcurrency",
+                    "maxConcurrency must be greater than 0"
+                )
+            );
+            self.check_scheduled_args_size(&args)?;
+            batched_jobs.push(BatchedJob {
+                udf_path: udf_path.canonicalize(),
+                udf_args: args,
+                original_scheduled_ts: ts.as_system_time().try_into()?,
+                priority: options.priority,
+                max_concurrency: options.max_concurrency,
+            });
+        }
+        // Like with `schedule`, jobs scheduled by a canceled job are canceled
+        // too. Since they would never run, don't store them at all.
+        if let Some(parent_scheduled_job) = context.parent_scheduled_job {
+            let table_mapping = self.tx.table_mapping();
+            let parent_scheduled_job = parent_scheduled_job
+                .to_resolved(&table_mapping.namespace(self.namespace).number_to_tablet())?;
+            if let Some(ScheduledJobState::Canceled) =
+                self.check_status(parent_scheduled_job).await?
+            {
+                batched_jobs.clear();
+            }
+        }
+        ScheduledJobBatchModel::new(self.tx, self.namespace)
+            .insert(batched_jobs)
+            .await
+    }
+
+    /// Write the jobs in a chunk of a batch to the scheduled jobs table and
+    /// delete the chunk.
+    pub async fn expand_batch_chunk(
+        &mut self,
+        chunk: ParsedDocument<ScheduledJobBatchChunk>,
+    ) -> anyhow::Result<usize> {
+        let (chunk_id, chunk) = chunk.into_id_and_value();
+        let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+        let num_jobs = chunk.jobs.len();
+        for job in chunk.jobs {
+            let options = job.options();
+            let scheduled_job = ScheduledJob {
+                udf_path: job.udf_path,
+                udf_args: job.udf_args,
+                state: ScheduledJobState::Pending,
+                next_ts: Some(job.original_scheduled_ts.max(now)),
+                completed_ts: None,
+                original_scheduled_ts: job.original_scheduled_ts,
+                attempts: 0,
+                priority: options.priority,
+                max_concurrency: options.max_concurrency,
+                batch_id: Some(chunk.batch_id.clone()),
+            };
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .insert_metadata(&SCHEDULED_JOBS_TABLE, scheduled_job.try_into()?)
+                .await?;
+        }
+        ScheduledJobBatchModel::new(self.tx, self.namespace)
+            .delete_chunk(chunk_id)
+            .await?;
+        Ok(num_jobs)
+    }
+
+    /// Cancel the batch's jobs that haven't completed yet, including the ones
+    /// that haven't been written to the scheduled jobs table, with at most
+    /// `limit` writes so large batches don't exceed transaction limits.
+    /// Returns the number of canceled jobs and whether some are left, in which
+    /// case the caller should call this again in a new transaction.
+    pub async fn cancel_batch(
+        &mut self,
+        batch_id: &str,
+        limit: usize,
+    ) -> anyhow::Result<(usize, bool)> {
+        let (mut count, num_chunks) = ScheduledJobBatchModel::new(self.tx, self.namespace)
+            .delete_remaining_chunks(batch_id, limit)
+            .await?;
+        if num_chunks == limit {
+            return Ok((count, true));
+        }
+        let index_query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOBS_INDEX_BY_BATCH_ID.clone(),
+            range: vec![
+                IndexRangeExpression::Eq(
+                    BATCH_ID_FIELD.clone(),
+                    ConvexValue::try_from(batch_id.to_string())?.into(),
+                ),
+                IndexRangeExpression::Gt(NEXT_TS_FIELD.clone(), value::ConvexValue::Null),
+            ],
+            order: Order::Asc,
+        })
+        .limit(limit - num_chunks + 1);
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let mut job_ids = Vec::new();
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            job_ids.push(doc.id());
+        }
+        let has_more = job_ids.len() > limit - num_chunks;
+        for job_id in job_ids.into_iter().take(limit - num_chunks) {
+            self.cancel(job_id).await?;
+            count += 1;
+        }
+        Ok((count, has_more))
+    }
+
     pub async fn replace(
         &mut self,
         id: ResolvedDocumentId,
@@ -411,6 +640,120 @@ impl<'a, RT: Runtime> SchedulerModel<'a, RT> {
         Ok(scheduled_jobs)
     }
 
+    /// Returns up to `limit` jobs matching the filter in creation order,
+    /// starting after `cursor`. At most `MAX_SCHEDULED_JOBS_SCANNED_PER_PAGE`
+    /// jobs are read, so a page can hold fewer than `limit` jobs even if more
+    /// match. The page's cursor is None once all jobs have been scanned.
+    pub async fn list_page(
+        &mut self,
+        filter: &ScheduledJobFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<ScheduledJobPage> {
+        let range = match cursor {
+            Some(cursor) => vec![IndexRangeExpression::Gt(
+                CREATION_TIME_FIELD_PATH.clone(),
+                ConvexValue::Float64(cursor.into()),
+            )],
+            None => vec![],
+        };
+        let index_query = Query::index_range(IndexRange {
+            index_name: SCHEDULED_JOBS_INDEX_BY_CREATION_TIME.clone(),
+            range,
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, index_query)?;
+        let mut jobs = Vec::new();
+        let mut last_creation_time = cursor;
+        let mut num_scanned = 0;
+        while jobs.len() < limit && num_scanned < *MAX_SCHEDULED_JOBS_SCANNED_PER_PAGE {
+            let Some(doc) = query_stream.next(self.tx, None).await? else {
+                return Ok(ScheduledJobPage { jobs, cursor: None });
+            };
+            num_scanned += 1;
+            let job: ParsedDocument<ScheduledJob> = doc.try_into()?;
+            last_creation_time = job.creation_time().or(last_creation_time);
+            if filter.matches(&job) {
+                jobs.push(job);
+            }
+        }
+        Ok(ScheduledJobPage {
+            jobs,
+            cursor: last_creation_time,
+        })
+    }
+
+    /// Counts the jobs matching the filter by state, on one page of jobs.
+    /// Returns the counts and the cursor of the next page.
+    pub async fn count_page(
+        &mut self,
+        filter: &ScheduledJobFilter,
+        cursor: Option<CreationTime>,
+    ) -> anyhow::Result<(BTreeMap<ScheduledJobStateKind, usize>, Option<CreationTime>)> {
+        let page = self.list_page(filter, cursor, usize::MAX).await?;
+        let mut counts = BTreeMap::new();
+        for job in page.jobs {
+            *counts.entry(job.state.kind()).or_default() += 1;
+        }
+        Ok((counts, page.cursor))
+    }
+
+    /// Cancels the pending and in-progress jobs matching the filter, on one
+    /// page of up to `limit` jobs. Returns the number of jobs canceled and the
+    /// cursor of the next page.
+    pub async fn cancel_page(
+        &mut self,
+        filter: &ScheduledJobFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<(usize, Option<CreationTime>)> {
+        let page = self.list_page(filter, cursor, limit).await?;
+        let mut count = 0;
+        for job in page.jobs {
+            if !job.state.kind().is_completed() {
+                self.complete(job.id(), ScheduledJobState::Canceled).await?;
+                count += 1;
+            }
+        }
+        Ok((count, page.cursor))
+    }
+
+    /// Schedules a new run, due now, of each completed job matching the
+    /// filter, on one page of up to `limit` jobs. The completed jobs are kept
+    /// as they are. Returns the number of jobs re-run and the cursor of the
+    /// next page.
+    pub async fn rerun_page(
+        &mut self,
+        filter: &ScheduledJobFilter,
+        cursor: Option<CreationTime>,
+        limit: usize,
+    ) -> anyhow::Result<(usize, Option<CreationTime>)> {
+        let page = self.list_page(filter, cursor, limit).await?;
+        let now: Timestamp = self.tx.runtime().generate_timestamp()?;
+        let mut count = 0;
+        for job in page.jobs {
+            if !job.state.kind().is_completed() {
+                continue;
+            }
+            // The new jobs are pending, so they are skipped if a later page
+            // scans them.
+            let job = ScheduledJob {
+                state: ScheduledJobState::Pending,
+                next_ts: Some(now),
+                completed_ts: None,
+                original_scheduled_ts: now,
+                attempts: 0,
+                batch_id: None,
+                ..job.into_value()
+            };
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .insert_metadata(&SCHEDULED_JOBS_TABLE, job.try_into()?)
+                .await?;
+            count += 1;
+        }
+        Ok((count, page.cursor))
+    }
+
     /// Checks the status of the scheduled job. If it has been garbage collected
     /// and the scheduled job is no longer in the table, it returns None.
     pub async fn check_status(
@@ -445,10 +788,11 @@ impl<'a, RT: Runtime> VirtualSchedulerModel<'a, RT> {
         udf_path: UdfPath,
         args: ConvexArray,
         ts: UnixTimestamp,
+        options: ScheduleOptions,
         context: ExecutionContext,
     ) -> anyhow::Result<DeveloperDocumentId> {
         let system_id = SchedulerModel::new(self.tx, self.namespace)
-            .schedule(udf_path, args, ts, context)
+            .schedule(udf_path, args, ts, options, context)
             .await?;
         let table_mapping = self.tx.table_mapping().clone();
         let virtual_table_mapping = self.tx.virtual_table_mapping().clone();
@@ -1,4 +1,8 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+};
 
 use anyhow::Context;
 use common::types::Timestamp;
@@ -36,6 +40,69 @@ pub struct ScheduledJob {
     pub next_ts: Option<Timestamp>,
     pub completed_ts: Option<Timestamp>,
     pub original_scheduled_ts: Timestamp,
+
+    // Number of times the job has been restarted after a failed attempt. Only
+    // workflows (actions that checkpoint their steps) are ever restarted.
+    pub attempts: u32,
+
+    // Lane the job runs in and the maximum number of jobs for the same function
+    // that may run at once, as requested when the job was scheduled.
+    pub priority: ScheduledJobPriority,
+    pub max_concurrency: Option<u32>,
+
+    // Set if the job was scheduled as part of a batch, so the whole batch can
+    // be canceled at once.
+    pub batch_id: Option<String>,
+}
+
+/// Options that can be passed when scheduling a job.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScheduleOptions {
+    pub priority: ScheduledJobPriority,
+    pub max_concurrency: Option<u32>,
+}
+
+/// The scheduler executes jobs in lanes. Lower priority lanes only get a share
+/// of the executor's parallelism, so bulk work like backfills can't starve
+/// latency-sensitive scheduled functions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum ScheduledJobPriority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl ScheduledJobPriority {
+    pub const ALL: [ScheduledJobPriority; 3] = [Self::High, Self::Normal, Self::Low];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::High => "high",
+            Self::Normal => "normal",
+            Self::Low => "low",
+        }
+    }
+}
+
+impl fmt::Display for ScheduledJobPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ScheduledJobPriority {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "high" => Ok(Self::High),
+            "normal" => Ok(Self::Normal),
+            "low" => Ok(Self::Low),
+            _ => anyhow::bail!("Invalid priority {s:?}, expected \"high\", \"normal\" or \"low\""),
+        }
+    }
 }
 
 impl TryFrom<ScheduledJob> for ConvexObject {
@@ -66,6 +133,24 @@ impl TryFrom<ScheduledJob> for ConvexObject {
             "originalScheduledTs".parse()?,
             ConvexValue::Int64(job.original_scheduled_ts.into()),
         );
+        if job.attempts > 0 {
+            obj.insert("attempts".parse()?, ConvexValue::Int64(job.attempts.into()));
+        }
+        if job.priority != ScheduledJobPriority::Normal {
+            obj.insert(
+                "priority".parse()?,
+                ConvexValue::try_from(job.priority.as_str())?,
+            );
+        }
+        if let Some(max_concurrency) = job.max_concurrency {
+            obj.insert(
+                "maxConcurrency".parse()?,
+                ConvexValue::Int64(max_concurrency.into()),
+            );
+        }
+        if let Some(batch_id) = job.batch_id {
+            obj.insert("batchId".parse()?, ConvexValue::try_from(batch_id)?);
+        }
 
         ConvexObject::try_from(obj)
     }
@@ -130,6 +215,30 @@ impl TryFrom<ConvexObject> for ScheduledJob {
             ),
         };
 
+        let attempts = match fields.remove("attempts") {
+            Some(ConvexValue::Int64(attempts)) => attempts.try_into()?,
+            None => 0,
+            _ => anyhow::bail!("Invalid `attempts` field for ScheduledJob: {:?}", fields),
+        };
+        let priority = match fields.remove("priority") {
+            Some(ConvexValue::String(s)) => s.parse()?,
+            None => ScheduledJobPriority::Normal,
+            _ => anyhow::bail!("Invalid `priority` field for ScheduledJob: {:?}", fields),
+        };
+        let max_concurrency = match fields.remove("maxConcurrency") {
+            Some(ConvexValue::Int64(max_concurrency)) => Some(max_concurrency.try_into()?),
+            None => None,
+            _ => anyhow::bail!(
+                "Invalid `maxConcurrency` field for ScheduledJob: {:?}",
+                fields
+            ),
+        };
+        let batch_id = match fields.remove("batchId") {
+            Some(ConvexValue::String(s)) => Some(s.to_string()),
+            None => None,
+            _ => anyhow::bail!("Invalid `batchId` field for ScheduledJob: {:?}", fields),
+        };
+
         Ok(ScheduledJob {
             udf_path,
             udf_args,
@@ -137,6 +246,10 @@ impl TryFrom<ConvexObject> for ScheduledJob {
             next_ts,
             completed_ts,
             original_scheduled_ts,
+            attempts,
+            priority,
+            max_concurrency,
+            batch_id,
         })
     }
 }
@@ -215,6 +328,118 @@ impl TryFrom<ConvexObject> for ScheduledJobState {
     }
 }
 
+impl ScheduledJobState {
+    pub fn kind(&self) -> ScheduledJobStateKind {
+        match self {
+            Self::Pending => ScheduledJobStateKind::Pending,
+            Self::InProgress => ScheduledJobStateKind::InProgress,
+            Self::Success => ScheduledJobStateKind::Success,
+            Self::Failed(_) => ScheduledJobStateKind::Failed,
+            Self::Canceled => ScheduledJobStateKind::Canceled,
+        }
+    }
+}
+
+/// The state of a scheduled job without its payload, used to filter and count
+/// jobs by state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ScheduledJobStateKind {
+    Pending,
+    InProgress,
+    Success,
+    Failed,
+    Canceled,
+}
+
+impl ScheduledJobStateKind {
+    pub const ALL: [ScheduledJobStateKind; 5] = [
+        Self::Pending,
+        Self::InProgress,
+        Self::Success,
+        Self::Failed,
+        Self::Canceled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "inProgress",
+            Self::Success => "success",
+            Self::Failed => "failed",
+            Self::Canceled => "canceled",
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        match self {
+            Self::Pending | Self::InProgress => false,
+            Self::Success | Self::Failed | Self::Canceled => true,
+        }
+    }
+}
+
+impl fmt::Display for ScheduledJobStateKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for ScheduledJobStateKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "pending" => Ok(Self::Pending),
+            "inProgress" => Ok(Self::InProgress),
+            "success" => Ok(Self::Success),
+            "failed" => Ok(Self::Failed),
+            "canceled" => Ok(Self::Canceled),
+            _ => anyhow::bail!(
+                "Invalid state {s:?}, expected \"pending\", \"inProgress\", \"success\", \
+                 \"failed\" or \"canceled\""
+            ),
+        }
+    }
+}
+
+/// Selects scheduled jobs for the bulk management APIs. Every condition that
+/// is set must hold for a job to match.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScheduledJobFilter {
+    pub udf_path: Option<CanonicalizedUdfPath>,
+    pub states: Option<Vec<ScheduledJobStateKind>>,
+    // Bounds on the time the job was originally scheduled to run at, inclusive
+    // of the start and exclusive of the end.
+    pub scheduled_after: Option<Timestamp>,
+    pub scheduled_before: Option<Timestamp>,
+}
+
+impl ScheduledJobFilter {
+    pub fn matches(&self, job: &ScheduledJob) -> bool {
+        if let Some(ref udf_path) = self.udf_path
+            && job.udf_path != *udf_path
+        {
+            return false;
+        }
+        if let Some(ref states) = self.states
+            && !states.contains(&job.state.kind())
+        {
+            return false;
+        }
+        if let Some(scheduled_after) = self.scheduled_after
+            && job.original_scheduled_ts < scheduled_after
+        {
+            return false;
+        }
+        if let Some(scheduled_before) = self.scheduled_before
+            && job.original_scheduled_ts >= scheduled_before
+        {
+            return false;
+        }
+        true
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
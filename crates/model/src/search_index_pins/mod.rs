@@ -0,0 +1,220 @@
+use std::sync::LazyLock;
+
+use common::{
+    bootstrap_model::index::IndexConfig,
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    unauthorized_error,
+    IndexModel,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::SearchIndexPin;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Text and vector indexes whose segments the searcher keeps loaded.
+pub static SEARCH_INDEX_PINS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_search_index_pins"
+        .parse()
+        .expect("Invalid built-in search index pins table")
+});
+
+pub struct SearchIndexPinsTable;
+impl SystemTable for SearchIndexPinsTable {
+    fn table_name(&self) -> &'static TableName {
+        &SEARCH_INDEX_PINS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<SearchIndexPin>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct SearchIndexPinsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> SearchIndexPinsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    pub async fn list(&mut self) -> anyhow::Result<Vec<ParsedDocument<SearchIndexPin>>> {
+        self.check_admin("list_search_index_pins")?;
+        let query = Query::full_table_scan(SEARCH_INDEX_PINS_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut pins = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            pins.push(doc.try_into()?);
+        }
+        Ok(pins)
+    }
+
+    pub async fn get(
+        &mut self,
+        index_name: &IndexName,
+    ) -> anyhow::Result<Option<ParsedDocument<SearchIndexPin>>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .find(|pin| pin.index_name == *index_name))
+    }
+
+    /// Pin a text or vector index in the root component. Pinning an index
+    /// that's already pinned does nothing.
+    pub async fn pin(&mut self, index_name: IndexName) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("pin_search_index")?;
+        let namespace = TableNamespace::root_component();
+        let mut index_model = IndexModel::new(self.tx);
+        let metadata = match index_model.enabled_index_metadata(namespace, &index_name)? {
+            Some(metadata) => Some(metadata),
+            None => index_model.pending_index_metadata(namespace, &index_name)?,
+        };
+        let Some(metadata) = metadata else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "IndexNotFound",
+                format!("Index {index_name} not found"),
+            ));
+        };
+        if let IndexConfig::Database { .. } = metadata.config {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidSearchIndexPin",
+                format!("Index {index_name} is not a text or vector index"),
+            ));
+        }
+        if let Some(existing) = self.get(&index_name).await? {
+            return Ok(existing.id());
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert(
+                &SEARCH_INDEX_PINS_TABLE,
+                SearchIndexPin { index_name }.try_into()?,
+            )
+            .await
+    }
+
+    /// Unpin an index, so its segments can be evicted like any others.
+    pub async fn unpin(&mut self, index_name: &IndexName) -> anyhow::Result<()> {
+        self.check_admin("unpin_search_index")?;
+        let Some(existing) = self.get(index_name).await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "SearchIndexPinNotFound",
+                format!("Index {index_name} is not pinned"),
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use common::{
+        bootstrap_model::index::IndexMetadata,
+        types::IndexName,
+    };
+    use database::{
+        test_helpers::DbFixtures,
+        IndexModel,
+    };
+    use runtime::testing::TestRuntime;
+    use value::{
+        TableName,
+        TableNamespace,
+    };
+
+    use crate::{
+        search_index_pins::SearchIndexPinsModel,
+        test_helpers::DbFixturesWithModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_pins(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let table_name: TableName = "messages".parse()?;
+        let search_index = IndexName::new(table_name.clone(), "search_body".parse()?)?;
+        let database_index = IndexName::new(table_name, "by_author".parse()?)?;
+        let mut tx = db.begin_system().await?;
+        let mut index_model = IndexModel::new(&mut tx);
+        index_model
+            .add_application_index(
+                TableNamespace::root_component(),
+                IndexMetadata::new_backfilling_search_index(
+                    search_index.clone(),
+                    "body".parse()?,
+                    BTreeSet::new(),
+                ),
+            )
+            .await?;
+        index_model
+            .add_application_index(
+                TableNamespace::root_component(),
+                IndexMetadata::new_enabled(
+                    database_index.clone(),
+                    vec!["author".parse()?].try_into()?,
+                ),
+            )
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = SearchIndexPinsModel::new(&mut tx);
+        let id = model.pin(search_index.clone()).await?;
+        // Pinning an index twice keeps the existing pin.
+        assert_eq!(model.pin(search_index.clone()).await?, id);
+        let pins = model.list().await?;
+        assert_eq!(pins.len(), 1);
+        assert_eq!(pins[0].index_name, search_index);
+
+        assert!(model.pin(database_index).await.is_err());
+        assert!(model
+            .pin(IndexName::new("messages".parse()?, "missing".parse()?)?)
+            .await
+            .is_err());
+
+        model.unpin(&search_index).await?;
+        assert!(model.list().await?.is_empty());
+        assert!(model.unpin(&search_index).await.is_err());
+        Ok(())
+    }
+}
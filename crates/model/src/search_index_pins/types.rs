@@ -0,0 +1,45 @@
+use common::types::IndexName;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// A text or vector index whose segments the searcher keeps loaded, so
+/// queries against it never wait for segments to be fetched, e.g. after a
+/// deploy or restart.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SearchIndexPin {
+    pub index_name: IndexName,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSearchIndexPin {
+    table_name: String,
+    index_descriptor: String,
+}
+
+impl TryFrom<SearchIndexPin> for SerializedSearchIndexPin {
+    type Error = anyhow::Error;
+
+    fn try_from(pin: SearchIndexPin) -> anyhow::Result<Self> {
+        Ok(Self {
+            table_name: pin.index_name.table().to_string(),
+            index_descriptor: pin.index_name.descriptor().to_string(),
+        })
+    }
+}
+
+impl TryFrom<SerializedSearchIndexPin> for SearchIndexPin {
+    type Error = anyhow::Error;
+
+    fn try_from(pin: SerializedSearchIndexPin) -> anyhow::Result<Self> {
+        Ok(Self {
+            index_name: IndexName::new(pin.table_name.parse()?, pin.index_descriptor.parse()?)?,
+        })
+    }
+}
+
+codegen_convex_serialization!(SearchIndexPin, SerializedSearchIndexPin);
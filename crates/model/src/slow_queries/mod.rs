@@ -0,0 +1,260 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::{
+        Runtime,
+        UnixTimestamp,
+    },
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::SlowQuery;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Executions that took longer than `SLOW_QUERY_THRESHOLD`, along with the
+/// reads and syscalls that made them slow.
+pub static SLOW_QUERIES_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_slow_queries"
+        .parse()
+        .expect("Invalid built-in slow queries table")
+});
+
+static TIMESTAMP_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "timestamp".parse().expect("Invalid built-in field"));
+
+static FUNCTION_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "function".parse().expect("Invalid built-in field"));
+
+pub static SLOW_QUERIES_INDEX_BY_TIMESTAMP: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SLOW_QUERIES_TABLE, "by_timestamp"));
+
+pub static SLOW_QUERIES_INDEX_BY_FUNCTION: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&SLOW_QUERIES_TABLE, "by_function_and_timestamp"));
+
+pub struct SlowQueriesTable;
+impl SystemTable for SlowQueriesTable {
+    fn table_name(&self) -> &'static TableName {
+        &SLOW_QUERIES_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![
+            SystemIndex {
+                name: SLOW_QUERIES_INDEX_BY_TIMESTAMP.clone(),
+                fields: vec![TIMESTAMP_FIELD.clone()].try_into().unwrap(),
+            },
+            SystemIndex {
+                name: SLOW_QUERIES_INDEX_BY_FUNCTION.clone(),
+                fields: vec![FUNCTION_FIELD.clone(), TIMESTAMP_FIELD.clone()]
+                    .try_into()
+                    .unwrap(),
+            },
+        ]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<SlowQuery>::try_from(document).map(|_| ())
+    }
+}
+
+fn timestamp_value(timestamp: UnixTimestamp) -> anyhow::Result<ConvexValue> {
+    Ok(ConvexValue::Int64(timestamp.as_nanos().try_into()?))
+}
+
+pub struct SlowQueriesModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> SlowQueriesModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, query: SlowQuery) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("insert_slow_query"));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert_metadata(&SLOW_QUERIES_TABLE, query.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Up to `limit` slow executions, of `function` if given, newest first.
+    pub async fn list(
+        &mut self,
+        function: Option<String>,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ParsedDocument<SlowQuery>>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("list_slow_queries"));
+        }
+        let query = match function {
+            Some(function) => Query::index_range(IndexRange {
+                index_name: SLOW_QUERIES_INDEX_BY_FUNCTION.clone(),
+                range: vec![IndexRangeExpression::Eq(
+                    FUNCTION_FIELD.clone(),
+                    ConvexValue::try_from(function)?.into(),
+                )],
+                order: Order::Desc,
+            }),
+            None => Query::index_range(IndexRange {
+                index_name: SLOW_QUERIES_INDEX_BY_TIMESTAMP.clone(),
+                range: vec![],
+                order: Order::Desc,
+            }),
+        }
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut queries = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            queries.push(doc.try_into()?);
+        }
+        Ok(queries)
+    }
+
+    /// Up to `limit` slow executions that happened before `cutoff`, oldest
+    /// first.
+    pub async fn expired(
+        &mut self,
+        cutoff: UnixTimestamp,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        let query = Query::index_range(IndexRange {
+            index_name: SLOW_QUERIES_INDEX_BY_TIMESTAMP.clone(),
+            range: vec![IndexRangeExpression::Lt(
+                TIMESTAMP_FIELD.clone(),
+                timestamp_value(cutoff)?,
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut ids = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            ids.push(doc.id());
+        }
+        Ok(ids)
+    }
+
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("delete_slow_query"));
+        }
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use common::{
+        runtime::UnixTimestamp,
+        types::UdfType,
+    };
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        slow_queries::{
+            types::{
+                SlowQuery,
+                SlowQueryIndexRanges,
+                SlowQuerySyscall,
+                SlowQueryTableStats,
+            },
+            SlowQueriesModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn slow_query(function: &str, timestamp_ms: u64) -> SlowQuery {
+        SlowQuery {
+            timestamp: UnixTimestamp::from_millis(timestamp_ms),
+            function: function.to_string(),
+            udf_type: UdfType::Query,
+            request_id: "a1b2c3".to_string(),
+            execution_time: Duration::from_millis(2500),
+            database_read_bytes: 1 << 20,
+            tables: vec![SlowQueryTableStats {
+                table: "messages".to_string(),
+                rows_read: 10000,
+                rows_written: 0,
+            }],
+            index_ranges: vec![SlowQueryIndexRanges {
+                index: "messages.by_creation_time".to_string(),
+                fields: vec!["_creationTime".to_string()],
+                num_ranges: 1,
+            }],
+            syscalls: vec![SlowQuerySyscall {
+                name: "1.0/queryStream".to_string(),
+                invocations: 1,
+                errors: 0,
+                duration: Duration::from_millis(2400),
+            }],
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_slow_queries(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = SlowQueriesModel::new(&mut tx);
+        model.insert(slow_query("messages:list", 1000)).await?;
+        model.insert(slow_query("messages:search", 2000)).await?;
+        model.insert(slow_query("messages:list", 3000)).await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = SlowQueriesModel::new(&mut tx);
+        let all = model.list(None, 10).await?;
+        assert_eq!(all.len(), 3);
+        assert_eq!(
+            all[0].clone().into_value(),
+            slow_query("messages:list", 3000)
+        );
+        let list = model.list(Some("messages:list".to_string()), 1).await?;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].timestamp, UnixTimestamp::from_millis(3000));
+
+        let expired = model.expired(UnixTimestamp::from_millis(2500), 10).await?;
+        assert_eq!(expired.len(), 2);
+        for id in expired {
+            model.delete(id).await?;
+        }
+        assert_eq!(model.list(None, 10).await?.len(), 1);
+        Ok(())
+    }
+}
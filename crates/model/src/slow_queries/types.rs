@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use common::{
+    runtime::UnixTimestamp,
+    types::UdfType,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Rows a slow execution read from and wrote to a table.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SlowQueryTableStats {
+    pub table: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub rows_read: u64,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub rows_written: u64,
+}
+
+/// An index a slow execution read, and how many disjoint ranges of it were
+/// scanned.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SlowQueryIndexRanges {
+    // The index's name, e.g. `messages.by_channel`.
+    pub index: String,
+    pub fields: Vec<String>,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub num_ranges: u64,
+}
+
+/// How often a slow execution made a syscall, e.g. `1.0/queryStream`, and
+/// how long those calls took in total.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SlowQuerySyscall {
+    pub name: String,
+    pub invocations: u32,
+    pub errors: u32,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::strategy::Strategy::prop_map(0..86_400_000u64, \
+                             Duration::from_millis)")
+    )]
+    pub duration: Duration,
+}
+
+/// An execution that took longer than `SLOW_QUERY_THRESHOLD`, with enough of
+/// what it read and how long each part took to diagnose it without
+/// reproducing it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct SlowQuery {
+    // When the function's result was returned.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::strategy::Strategy::prop_map(0..=i64::MAX as u64, \
+                        UnixTimestamp::from_nanos)"
+        )
+    )]
+    pub timestamp: UnixTimestamp,
+    pub function: String,
+    pub udf_type: UdfType,
+    pub request_id: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "proptest::strategy::Strategy::prop_map(0..86_400_000u64, \
+                             Duration::from_millis)")
+    )]
+    pub execution_time: Duration,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub database_read_bytes: u64,
+    pub tables: Vec<SlowQueryTableStats>,
+    pub index_ranges: Vec<SlowQueryIndexRanges>,
+    pub syscalls: Vec<SlowQuerySyscall>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSlowQueryTableStats {
+    table: String,
+    rows_read: i64,
+    rows_written: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSlowQueryIndexRanges {
+    index: String,
+    fields: Vec<String>,
+    num_ranges: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSlowQuerySyscall {
+    name: String,
+    invocations: i64,
+    errors: i64,
+    duration_ms: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedSlowQuery {
+    // Nanoseconds since the Unix epoch.
+    timestamp: i64,
+    function: String,
+    udf_type: String,
+    request_id: String,
+    execution_time_ms: i64,
+    database_read_bytes: i64,
+    tables: Vec<SerializedSlowQueryTableStats>,
+    index_ranges: Vec<SerializedSlowQueryIndexRanges>,
+    syscalls: Vec<SerializedSlowQuerySyscall>,
+}
+
+impl TryFrom<SlowQuery> for SerializedSlowQuery {
+    type Error = anyhow::Error;
+
+    fn try_from(query: SlowQuery) -> anyhow::Result<Self> {
+        Ok(Self {
+            timestamp: query.timestamp.as_nanos().try_into()?,
+            function: query.function,
+            udf_type: query.udf_type.to_string(),
+            request_id: query.request_id,
+            execution_time_ms: query.execution_time.as_millis().try_into()?,
+            database_read_bytes: query.database_read_bytes.try_into()?,
+            tables: query
+                .tables
+                .into_iter()
+                .map(|stats| {
+                    anyhow::Ok(SerializedSlowQueryTableStats {
+                        table: stats.table,
+                        rows_read: stats.rows_read.try_into()?,
+                        rows_written: stats.rows_written.try_into()?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            index_ranges: query
+                .index_ranges
+                .into_iter()
+                .map(|ranges| {
+                    anyhow::Ok(SerializedSlowQueryIndexRanges {
+                        index: ranges.index,
+                        fields: ranges.fields,
+                        num_ranges: ranges.num_ranges.try_into()?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            syscalls: query
+                .syscalls
+                .into_iter()
+                .map(|syscall| {
+                    anyhow::Ok(SerializedSlowQuerySyscall {
+                        name: syscall.name,
+                        invocations: syscall.invocations.into(),
+                        errors: syscall.errors.into(),
+                        duration_ms: syscall.duration.as_millis().try_into()?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedSlowQuery> for SlowQuery {
+    type Error = anyhow::Error;
+
+    fn try_from(query: SerializedSlowQuery) -> anyhow::Result<Self> {
+        Ok(Self {
+            timestamp: UnixTimestamp::from_nanos(query.timestamp.try_into()?),
+            function: query.function,
+            udf_type: query.udf_type.parse()?,
+            request_id: query.request_id,
+            execution_time: Duration::from_millis(query.execution_time_ms.try_into()?),
+            database_read_bytes: query.database_read_bytes.try_into()?,
+            tables: query
+                .tables
+                .into_iter()
+                .map(|stats| {
+                    anyhow::Ok(SlowQueryTableStats {
+                        table: stats.table,
+                        rows_read: stats.rows_read.try_into()?,
+                        rows_written: stats.rows_written.try_into()?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            index_ranges: query
+                .index_ranges
+                .into_iter()
+                .map(|ranges| {
+                    anyhow::Ok(SlowQueryIndexRanges {
+                        index: ranges.index,
+                        fields: ranges.fields,
+                        num_ranges: ranges.num_ranges.try_into()?,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+            syscalls: query
+                .syscalls
+                .into_iter()
+                .map(|syscall| {
+                    anyhow::Ok(SlowQuerySyscall {
+                        name: syscall.name,
+                        invocations: syscall.invocations.try_into()?,
+                        errors: syscall.errors.try_into()?,
+                        duration: Duration::from_millis(syscall.duration_ms.try_into()?),
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(SlowQuery, SerializedSlowQuery);
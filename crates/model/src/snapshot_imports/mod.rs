@@ -36,6 +36,7 @@ use self::types::{
     ImportMode,
     ImportState,
     ImportTableCheckpoint,
+    ImportTableValidation,
     SnapshotImport,
 };
 use crate::{
@@ -95,6 +96,8 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         format: ImportFormat,
         mode: ImportMode,
         object_key: ObjectKey,
+        dry_run: bool,
+        remap_ids: bool,
     ) -> anyhow::Result<ResolvedDocumentId> {
         let snapshot_import = SnapshotImport {
             state: ImportState::Uploaded,
@@ -103,6 +106,9 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
             object_key,
             member_id: self.tx.identity().member_id(),
             checkpoints: None,
+            dry_run,
+            validation: None,
+            remap_ids,
         };
         let id = SystemMetadataModel::new_global(self.tx)
             .insert(
@@ -178,6 +184,7 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         info_message: String,
         require_manual_confirmation: bool,
         new_checkpoints: Vec<ImportTableCheckpoint>,
+        validation: Option<Vec<ImportTableValidation>>,
     ) -> anyhow::Result<()> {
         self.update_state(id, move |_| ImportState::WaitingForConfirmation {
             info_message,
@@ -187,7 +194,18 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         self.update_checkpoints(id, move |checkpoints| {
             *checkpoints = new_checkpoints;
         })
-        .await
+        .await?;
+        if validation.is_some() {
+            let mut import = self.get(id).await?.context(ErrorMetadata::not_found(
+                "ImportNotFound",
+                format!("import {id} not found"),
+            ))?;
+            import.validation = validation;
+            SystemMetadataModel::new_global(self.tx)
+                .replace(id, import.into_value().try_into()?)
+                .await?;
+        }
+        Ok(())
     }
 
     pub async fn confirm_import(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
@@ -340,9 +358,9 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
                 .iter_mut()
                 .find(|c| c.display_table_name == *display_table_name)
             {
-                if checkpoint.num_rows_written > 0
-                    && num_rows_written <= checkpoint.num_rows_written
-                {
+                // The chunk of rows may have been checkpointed already, but
+                // the progress message still needs updating.
+                if num_rows_written < checkpoint.num_rows_written {
                     *noop_ = true;
                     return;
                 }
@@ -369,6 +387,26 @@ impl<'a, RT: Runtime> SnapshotImportModel<'a, RT> {
         .await
     }
 
+    /// Record that `num_rows_written` rows of the table have been imported.
+    /// Called in the same transaction that writes them, so a resumed import
+    /// knows exactly how many rows to skip.
+    pub async fn checkpoint_rows_written(
+        &mut self,
+        id: ResolvedDocumentId,
+        display_table_name: &TableName,
+        num_rows_written: i64,
+    ) -> anyhow::Result<()> {
+        self.update_checkpoints(id, move |checkpoints| {
+            if let Some(checkpoint) = checkpoints
+                .iter_mut()
+                .find(|c| c.display_table_name == *display_table_name)
+            {
+                checkpoint.num_rows_written = checkpoint.num_rows_written.max(num_rows_written);
+            }
+        })
+        .await
+    }
+
     pub async fn import_in_state(
         &mut self,
         import_state: ImportState,
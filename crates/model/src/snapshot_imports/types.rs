@@ -1,8 +1,15 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+};
+
 use common::types::{
     MemberId,
     ObjectKey,
     TableName,
 };
+use errors::ErrorMetadata;
 use serde::{
     Deserialize,
     Serialize,
@@ -22,6 +29,13 @@ pub struct SnapshotImport {
     pub object_key: ObjectKey,
     pub member_id: Option<MemberId>,
     pub checkpoints: Option<Vec<ImportTableCheckpoint>>,
+    // Whether to validate every row before waiting for confirmation.
+    pub dry_run: bool,
+    // The result of the validation, once a dry run has been parsed.
+    pub validation: Option<Vec<ImportTableValidation>>,
+    // Whether to give imported documents new ids, rewriting the references to
+    // them in `v.id` fields of the schema.
+    pub remap_ids: bool,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
@@ -32,6 +46,9 @@ struct SerializedSnapshotImport {
     object_key: String,
     member_id: Option<i64>,
     checkpoints: Option<Vec<SerializedImportTableCheckpoint>>,
+    dry_run: Option<bool>,
+    validation: Option<Vec<SerializedImportTableValidation>>,
+    remap_ids: Option<bool>,
 }
 
 impl TryFrom<SnapshotImport> for SerializedSnapshotImport {
@@ -48,6 +65,12 @@ impl TryFrom<SnapshotImport> for SerializedSnapshotImport {
                 .checkpoints
                 .map(|checkpoints| checkpoints.into_iter().map(TryInto::try_into).try_collect())
                 .transpose()?,
+            dry_run: Some(import.dry_run),
+            validation: import
+                .validation
+                .map(|validation| validation.into_iter().map(TryInto::try_into).try_collect())
+                .transpose()?,
+            remap_ids: Some(import.remap_ids),
         })
     }
 }
@@ -66,6 +89,12 @@ impl TryFrom<SerializedSnapshotImport> for SnapshotImport {
                 .checkpoints
                 .map(|checkpoints| checkpoints.into_iter().map(TryInto::try_into).try_collect())
                 .transpose()?,
+            dry_run: import.dry_run.unwrap_or(false),
+            validation: import
+                .validation
+                .map(|validation| validation.into_iter().map(TryInto::try_into).try_collect())
+                .transpose()?,
+            remap_ids: import.remap_ids.unwrap_or(false),
         })
     }
 }
@@ -75,7 +104,9 @@ codegen_convex_serialization!(SnapshotImport, SerializedSnapshotImport);
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub enum ImportFormat {
-    Csv(TableName),
+    // The table to import into, and the types of the columns whose values
+    // shouldn't be inferred.
+    Csv(TableName, BTreeMap<String, CsvColumnType>),
     JsonLines(TableName),
     JsonArray(TableName),
     Zip,
@@ -85,7 +116,11 @@ pub enum ImportFormat {
 #[serde(tag = "format")]
 pub enum SerializedImportFormat {
     #[serde(rename = "csv")]
-    Csv { table: String },
+    Csv {
+        table: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        column_types: Vec<SerializedCsvColumnType>,
+    },
     #[serde(rename = "jsonl")]
     JsonLines { table: String },
     #[serde(rename = "json_array")]
@@ -99,8 +134,15 @@ impl TryFrom<ImportFormat> for SerializedImportFormat {
 
     fn try_from(format: ImportFormat) -> anyhow::Result<SerializedImportFormat> {
         match format {
-            ImportFormat::Csv(table) => Ok(SerializedImportFormat::Csv {
+            ImportFormat::Csv(table, column_types) => Ok(SerializedImportFormat::Csv {
                 table: table.to_string(),
+                column_types: column_types
+                    .into_iter()
+                    .map(|(column, column_type)| SerializedCsvColumnType {
+                        column,
+                        column_type: column_type.to_string(),
+                    })
+                    .collect(),
             }),
             ImportFormat::JsonLines(table) => Ok(SerializedImportFormat::JsonLines {
                 table: table.to_string(),
@@ -118,7 +160,21 @@ impl TryFrom<SerializedImportFormat> for ImportFormat {
 
     fn try_from(format: SerializedImportFormat) -> anyhow::Result<ImportFormat> {
         match format {
-            SerializedImportFormat::Csv { table } => Ok(ImportFormat::Csv(table.parse()?)),
+            SerializedImportFormat::Csv {
+                table,
+                column_types,
+            } => Ok(ImportFormat::Csv(
+                table.parse()?,
+                column_types
+                    .into_iter()
+                    .map(
+                        |SerializedCsvColumnType {
+                             column,
+                             column_type,
+                         }| { anyhow::Ok((column, column_type.parse()?)) },
+                    )
+                    .try_collect()?,
+            )),
             SerializedImportFormat::JsonLines { table } => {
                 Ok(ImportFormat::JsonLines(table.parse()?))
             },
@@ -130,6 +186,66 @@ impl TryFrom<SerializedImportFormat> for ImportFormat {
     }
 }
 
+/// The type that a CSV column's cells are parsed as, instead of inferring
+/// numbers and strings from each cell.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub enum CsvColumnType {
+    String,
+    // A float64.
+    Number,
+    Int64,
+    // `true` or `false`, in any case.
+    Boolean,
+    // Any JSON value, e.g. an object or array.
+    Json,
+}
+
+impl CsvColumnType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CsvColumnType::String => "string",
+            CsvColumnType::Number => "number",
+            CsvColumnType::Int64 => "int64",
+            CsvColumnType::Boolean => "boolean",
+            CsvColumnType::Json => "json",
+        }
+    }
+}
+
+impl FromStr for CsvColumnType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "string" => Ok(CsvColumnType::String),
+            "number" => Ok(CsvColumnType::Number),
+            "int64" => Ok(CsvColumnType::Int64),
+            "boolean" => Ok(CsvColumnType::Boolean),
+            "json" => Ok(CsvColumnType::Json),
+            _ => anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidCsvColumnType",
+                format!(
+                    "Unknown CSV column type {s:?}, expected \"string\", \"number\", \"int64\", \
+                     \"boolean\" or \"json\""
+                ),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for CsvColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SerializedCsvColumnType {
+    column: String,
+    column_type: String,
+}
+
 mod import_format_serde {
     use value::codegen_convex_serialization;
 
@@ -296,7 +412,8 @@ pub struct ImportTableCheckpoint {
     pub total_num_rows_to_write: i64,
     // For progress message, so we can say "wrote 40 of 100 documents"
     // Also for checkpointing, this is the number of rows we know we have written,
-    // so we can skip trying to insert them.
+    // so we can skip trying to insert them. It's updated in the same
+    // transaction as each chunk of rows, so it's exact after a crash.
     pub num_rows_written: i64,
     // For warning message, so we can say "this will delete 100 of 100 documents"
     // or "this will delete 0 of 100 documents"
@@ -356,6 +473,63 @@ impl TryFrom<SerializedImportTableCheckpoint> for ImportTableCheckpoint {
     }
 }
 
+/// What a dry run found in the rows to import into a table.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct ImportTableValidation {
+    pub display_table_name: TableName,
+    pub num_rows: i64,
+    // Rows that aren't valid Convex values or don't match the table's schema.
+    pub num_type_mismatches: i64,
+    // Rows whose `_id` is used by an earlier row, or by a document that the
+    // import wouldn't delete.
+    pub num_id_collisions: i64,
+    // Estimated bytes written by the import, counted as ingress bandwidth.
+    pub ingress_bytes: i64,
+    // Descriptions of the first few problems, e.g. "Row 3 ...".
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SerializedImportTableValidation {
+    pub display_table_name: String,
+    pub num_rows: i64,
+    pub num_type_mismatches: i64,
+    pub num_id_collisions: i64,
+    pub ingress_bytes: i64,
+    pub errors: Vec<String>,
+}
+
+impl TryFrom<ImportTableValidation> for SerializedImportTableValidation {
+    type Error = anyhow::Error;
+
+    fn try_from(validation: ImportTableValidation) -> anyhow::Result<Self> {
+        Ok(SerializedImportTableValidation {
+            display_table_name: validation.display_table_name.to_string(),
+            num_rows: validation.num_rows,
+            num_type_mismatches: validation.num_type_mismatches,
+            num_id_collisions: validation.num_id_collisions,
+            ingress_bytes: validation.ingress_bytes,
+            errors: validation.errors,
+        })
+    }
+}
+
+impl TryFrom<SerializedImportTableValidation> for ImportTableValidation {
+    type Error = anyhow::Error;
+
+    fn try_from(validation: SerializedImportTableValidation) -> anyhow::Result<Self> {
+        Ok(ImportTableValidation {
+            display_table_name: validation.display_table_name.parse()?,
+            num_rows: validation.num_rows,
+            num_type_mismatches: validation.num_type_mismatches,
+            num_id_collisions: validation.num_id_collisions,
+            ingress_bytes: validation.ingress_bytes,
+            errors: validation.errors,
+        })
+    }
+}
+
 #[derive(
     Debug, Default, Deserialize, Clone, Copy, Eq, PartialEq, strum::EnumString, strum::Display,
 )]
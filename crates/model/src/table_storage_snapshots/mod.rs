@@ -0,0 +1,222 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use value::{
+    ConvexValue,
+    FieldPath,
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::TableStorageSnapshot;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// A daily breakdown of each user table's storage into its documents, its
+/// indexes and the revisions retention is keeping around.
+pub static TABLE_STORAGE_SNAPSHOTS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_table_storage_snapshots"
+        .parse()
+        .expect("Invalid built-in table storage snapshots table")
+});
+
+static DAY_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "day".parse().expect("Invalid built-in field"));
+
+pub static TABLE_STORAGE_SNAPSHOTS_INDEX_BY_DAY: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&TABLE_STORAGE_SNAPSHOTS_TABLE, "by_day"));
+
+pub struct TableStorageSnapshotsTable;
+impl SystemTable for TableStorageSnapshotsTable {
+    fn table_name(&self) -> &'static TableName {
+        &TABLE_STORAGE_SNAPSHOTS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: TABLE_STORAGE_SNAPSHOTS_INDEX_BY_DAY.clone(),
+            fields: vec![DAY_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<TableStorageSnapshot>::try_from(document).map(|_| ())
+    }
+}
+
+fn day_value(day: u64) -> anyhow::Result<ConvexValue> {
+    Ok(ConvexValue::Int64(day.try_into()?))
+}
+
+pub struct TableStorageSnapshotsModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> TableStorageSnapshotsModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn insert(&mut self, snapshot: TableStorageSnapshot) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("insert_table_storage_snapshot"));
+        }
+        SystemMetadataModel::new_global(self.tx)
+            .insert_metadata(&TABLE_STORAGE_SNAPSHOTS_TABLE, snapshot.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// The most recent day with a snapshot, if any.
+    pub async fn latest_day(&mut self) -> anyhow::Result<Option<u64>> {
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_STORAGE_SNAPSHOTS_INDEX_BY_DAY.clone(),
+            range: vec![],
+            order: Order::Desc,
+        })
+        .limit(1);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let Some(doc) = query_stream.next(self.tx, None).await? else {
+            return Ok(None);
+        };
+        let snapshot: ParsedDocument<TableStorageSnapshot> = doc.try_into()?;
+        Ok(Some(snapshot.day))
+    }
+
+    /// The snapshots of every user table on `day`.
+    pub async fn list(
+        &mut self,
+        day: u64,
+    ) -> anyhow::Result<Vec<ParsedDocument<TableStorageSnapshot>>> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error("list_table_storage_snapshots"));
+        }
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_STORAGE_SNAPSHOTS_INDEX_BY_DAY.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                DAY_FIELD.clone(),
+                day_value(day)?.into(),
+            )],
+            order: Order::Asc,
+        });
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut snapshots = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            snapshots.push(doc.try_into()?);
+        }
+        Ok(snapshots)
+    }
+
+    /// Up to `limit` snapshots from before `cutoff_day`, oldest first.
+    pub async fn expired(
+        &mut self,
+        cutoff_day: u64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ResolvedDocumentId>> {
+        let query = Query::index_range(IndexRange {
+            index_name: TABLE_STORAGE_SNAPSHOTS_INDEX_BY_DAY.clone(),
+            range: vec![IndexRangeExpression::Lt(
+                DAY_FIELD.clone(),
+                day_value(cutoff_day)?,
+            )],
+            order: Order::Asc,
+        })
+        .limit(limit);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        let mut ids = vec![];
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            ids.push(doc.id());
+        }
+        Ok(ids)
+    }
+
+    pub async fn delete(&mut self, id: ResolvedDocumentId) -> anyhow::Result<()> {
+        if !self.tx.identity().is_system() {
+            anyhow::bail!(unauthorized_error("delete_table_storage_snapshot"));
+        }
+        SystemMetadataModel::new_global(self.tx).delete(id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        table_storage_snapshots::{
+            types::TableStorageSnapshot,
+            TableStorageSnapshotsModel,
+        },
+        test_helpers::DbFixturesWithModel,
+    };
+
+    fn snapshot(day: u64, table_name: &str) -> TableStorageSnapshot {
+        TableStorageSnapshot {
+            day,
+            table_name: table_name.to_string(),
+            document_count: 100,
+            document_size: 10_000,
+            index_size: 2_000,
+            historical_size: 5_000,
+        }
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_table_storage_snapshots(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = TableStorageSnapshotsModel::new(&mut tx);
+        assert_eq!(model.latest_day().await?, None);
+        model.insert(snapshot(10, "messages")).await?;
+        model.insert(snapshot(10, "users")).await?;
+        model.insert(snapshot(11, "messages")).await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = TableStorageSnapshotsModel::new(&mut tx);
+        assert_eq!(model.latest_day().await?, Some(11));
+        let day = model.list(10).await?;
+        assert_eq!(day.len(), 2);
+        assert_eq!(day[0].clone().into_value().day, 10);
+
+        let expired = model.expired(11, 10).await?;
+        assert_eq!(expired.len(), 2);
+        for id in expired {
+            model.delete(id).await?;
+        }
+        assert_eq!(model.list(10).await?.len(), 0);
+        assert_eq!(
+            model.list(11).await?[0].clone().into_value(),
+            snapshot(11, "messages")
+        );
+        Ok(())
+    }
+}
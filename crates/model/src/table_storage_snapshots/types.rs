@@ -0,0 +1,86 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// How much storage a user table used on a given day, broken down by what it's
+/// used for.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TableStorageSnapshot {
+    // Days since the Unix epoch, in UTC.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub day: u64,
+    pub table_name: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub document_count: u64,
+    // The size of the table's latest revisions.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub document_size: u64,
+    // The size of the table's developer-defined indexes.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub index_size: u64,
+    // The size of overwritten and deleted revisions that are still within
+    // retention.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "0..=i64::MAX as u64")
+    )]
+    pub historical_size: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedTableStorageSnapshot {
+    day: i64,
+    table_name: String,
+    document_count: i64,
+    document_size: i64,
+    index_size: i64,
+    historical_size: i64,
+}
+
+impl TryFrom<TableStorageSnapshot> for SerializedTableStorageSnapshot {
+    type Error = anyhow::Error;
+
+    fn try_from(snapshot: TableStorageSnapshot) -> anyhow::Result<Self> {
+        Ok(Self {
+            day: snapshot.day.try_into()?,
+            table_name: snapshot.table_name,
+            document_count: snapshot.document_count.try_into()?,
+            document_size: snapshot.document_size.try_into()?,
+            index_size: snapshot.index_size.try_into()?,
+            historical_size: snapshot.historical_size.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SerializedTableStorageSnapshot> for TableStorageSnapshot {
+    type Error = anyhow::Error;
+
+    fn try_from(snapshot: SerializedTableStorageSnapshot) -> anyhow::Result<Self> {
+        Ok(Self {
+            day: snapshot.day.try_into()?,
+            table_name: snapshot.table_name,
+            document_count: snapshot.document_count.try_into()?,
+            document_size: snapshot.document_size.try_into()?,
+            index_size: snapshot.index_size.try_into()?,
+            historical_size: snapshot.historical_size.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(TableStorageSnapshot, SerializedTableStorageSnapshot);
@@ -0,0 +1,188 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+};
+use database::{
+    unauthorized_error,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    ResolvedDocumentId,
+    TableName,
+    TableNamespace,
+};
+
+pub mod types;
+
+use types::TrafficShadowingConfig;
+
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+/// Where calls are shadowed to, if anywhere. Has at most one document.
+pub static TRAFFIC_SHADOWING_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_traffic_shadowing"
+        .parse()
+        .expect("Invalid built-in traffic shadowing table")
+});
+
+pub struct TrafficShadowingTable;
+impl SystemTable for TrafficShadowingTable {
+    fn table_name(&self) -> &'static TableName {
+        &TRAFFIC_SHADOWING_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<TrafficShadowingConfig>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct TrafficShadowingModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+}
+
+impl<'a, RT: Runtime> TrafficShadowingModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>) -> Self {
+        Self { tx }
+    }
+
+    fn check_admin(&mut self, operation: &'static str) -> anyhow::Result<()> {
+        if !(self.tx.identity().is_admin() || self.tx.identity().is_system()) {
+            anyhow::bail!(unauthorized_error(operation));
+        }
+        Ok(())
+    }
+
+    async fn get_document(
+        &mut self,
+    ) -> anyhow::Result<Option<ParsedDocument<TrafficShadowingConfig>>> {
+        let query = Query::full_table_scan(TRAFFIC_SHADOWING_TABLE.clone(), Order::Asc);
+        let mut query_stream = ResolvedQuery::new(self.tx, TableNamespace::Global, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// The config includes the target's admin key, so only admins can read
+    /// it.
+    pub async fn get(&mut self) -> anyhow::Result<Option<TrafficShadowingConfig>> {
+        self.check_admin("get_traffic_shadowing")?;
+        Ok(self.get_document().await?.map(ParsedDocument::into_value))
+    }
+
+    /// Start shadowing calls to a target, replacing the previous one.
+    pub async fn set(
+        &mut self,
+        config: TrafficShadowingConfig,
+    ) -> anyhow::Result<ResolvedDocumentId> {
+        self.check_admin("set_traffic_shadowing")?;
+        config.validate()?;
+        match self.get_document().await? {
+            Some(existing) => {
+                SystemMetadataModel::new_global(self.tx)
+                    .replace(existing.id(), config.try_into()?)
+                    .await?;
+                Ok(existing.id())
+            },
+            None => {
+                SystemMetadataModel::new_global(self.tx)
+                    .insert(&TRAFFIC_SHADOWING_TABLE, config.try_into()?)
+                    .await
+            },
+        }
+    }
+
+    /// Stop shadowing calls.
+    pub async fn clear(&mut self) -> anyhow::Result<()> {
+        self.check_admin("clear_traffic_shadowing")?;
+        let Some(existing) = self.get_document().await? else {
+            anyhow::bail!(ErrorMetadata::not_found(
+                "TrafficShadowingNotFound",
+                "This deployment isn't shadowing traffic",
+            ));
+        };
+        SystemMetadataModel::new_global(self.tx)
+            .delete(existing.id())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use runtime::testing::TestRuntime;
+
+    use crate::{
+        test_helpers::DbFixturesWithModel,
+        traffic_shadowing::{
+            types::TrafficShadowingConfig,
+            TrafficShadowingModel,
+        },
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_traffic_shadowing(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let mut tx = db.begin_system().await?;
+        let mut model = TrafficShadowingModel::new(&mut tx);
+        assert_eq!(model.get().await?, None);
+
+        let config = TrafficShadowingConfig {
+            target_url: "https://staging.example.com".to_string(),
+            target_admin_key: "staging|key".to_string(),
+            sample_percentage: 5.0,
+        };
+        let id = model.set(config.clone()).await?;
+        assert_eq!(model.get().await?, Some(config.clone()));
+
+        let config = TrafficShadowingConfig {
+            sample_percentage: 100.0,
+            ..config
+        };
+        assert_eq!(model.set(config.clone()).await?, id);
+        assert_eq!(model.get().await?, Some(config.clone()));
+
+        for invalid in [
+            TrafficShadowingConfig {
+                target_url: "staging.example.com".to_string(),
+                ..config.clone()
+            },
+            TrafficShadowingConfig {
+                sample_percentage: 101.0,
+                ..config.clone()
+            },
+            TrafficShadowingConfig {
+                sample_percentage: f64::NAN,
+                ..config.clone()
+            },
+        ] {
+            assert!(model.set(invalid).await.is_err());
+        }
+
+        model.clear().await?;
+        assert_eq!(model.get().await?, None);
+        assert!(model.clear().await.is_err());
+        Ok(())
+    }
+}
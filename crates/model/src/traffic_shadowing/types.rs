@@ -0,0 +1,83 @@
+use errors::ErrorMetadata;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use value::codegen_convex_serialization;
+
+/// Where a sample of this deployment's queries and mutations are replayed,
+/// e.g. a staging deployment running a backend upgrade, so its results and
+/// latencies can be compared against production's.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct TrafficShadowingConfig {
+    /// e.g. "https://happy-animal-123.convex.cloud".
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "\"https?://[a-z]{1,8}\\\\.com(:[0-9]{4})?\"")
+    )]
+    pub target_url: String,
+    /// An admin key for the target deployment, which replayed calls run as.
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(strategy = "\"[a-z0-9|]{1,32}\"")
+    )]
+    pub target_admin_key: String,
+    /// The percentage of calls that are replayed, from 0 to 100.
+    #[cfg_attr(any(test, feature = "testing"), proptest(strategy = "0.0..=100.0f64"))]
+    pub sample_percentage: f64,
+}
+
+impl TrafficShadowingConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid =
+            |msg: &str| ErrorMetadata::bad_request("InvalidTrafficShadowing", msg.to_string());
+        anyhow::ensure!(
+            self.target_url.starts_with("http://") || self.target_url.starts_with("https://"),
+            invalid("The target URL must start with http:// or https://")
+        );
+        anyhow::ensure!(
+            !self.target_admin_key.is_empty(),
+            invalid("The target's admin key can't be empty")
+        );
+        anyhow::ensure!(
+            (0.0..=100.0).contains(&self.sample_percentage),
+            invalid("The sample percentage must be between 0 and 100")
+        );
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedTrafficShadowingConfig {
+    target_url: String,
+    target_admin_key: String,
+    sample_percentage: f64,
+}
+
+impl TryFrom<TrafficShadowingConfig> for SerializedTrafficShadowingConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(config: TrafficShadowingConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            target_url: config.target_url,
+            target_admin_key: config.target_admin_key,
+            sample_percentage: config.sample_percentage,
+        })
+    }
+}
+
+impl TryFrom<SerializedTrafficShadowingConfig> for TrafficShadowingConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(config: SerializedTrafficShadowingConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            target_url: config.target_url,
+            target_admin_key: config.target_admin_key,
+            sample_percentage: config.sample_percentage,
+        })
+    }
+}
+
+codegen_convex_serialization!(TrafficShadowingConfig, SerializedTrafficShadowingConfig);
@@ -0,0 +1,345 @@
+use std::sync::LazyLock;
+
+use common::{
+    document::{
+        ParsedDocument,
+        ResolvedDocument,
+    },
+    query::{
+        IndexRange,
+        IndexRangeExpression,
+        Order,
+        Query,
+    },
+    runtime::Runtime,
+    types::IndexName,
+};
+use database::{
+    defaults::system_index,
+    ResolvedQuery,
+    SystemMetadataModel,
+    Transaction,
+};
+use errors::ErrorMetadata;
+use value::{
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+    FieldPath,
+    TableName,
+    TableNamespace,
+};
+
+use self::types::{
+    WorkflowMetadata,
+    WorkflowStep,
+};
+use crate::{
+    SystemIndex,
+    SystemTable,
+};
+
+pub mod types;
+
+pub static WORKFLOW_STEPS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_workflow_steps"
+        .parse()
+        .expect("_workflow_steps is not a valid system table name")
+});
+
+pub static WORKFLOWS_TABLE: LazyLock<TableName> = LazyLock::new(|| {
+    "_workflows"
+        .parse()
+        .expect("_workflows is not a valid system table name")
+});
+
+pub static WORKFLOWS_INDEX_BY_WORKFLOW_ID: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&WORKFLOWS_TABLE, "by_workflow_id"));
+pub static WORKFLOW_STEPS_INDEX_BY_WORKFLOW_ID_AND_STEP_NAME: LazyLock<IndexName> =
+    LazyLock::new(|| system_index(&WORKFLOW_STEPS_TABLE, "by_workflow_id_and_step_name"));
+static WORKFLOW_ID_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "workflowId".parse().expect("invalid workflowId field"));
+static STEP_NAME_FIELD: LazyLock<FieldPath> =
+    LazyLock::new(|| "stepName".parse().expect("invalid stepName field"));
+
+pub struct WorkflowsTable;
+impl SystemTable for WorkflowsTable {
+    fn table_name(&self) -> &'static TableName {
+        &WORKFLOWS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: WORKFLOWS_INDEX_BY_WORKFLOW_ID.clone(),
+            fields: vec![WORKFLOW_ID_FIELD.clone()].try_into().unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<WorkflowMetadata>::try_from(document).map(|_| ())
+    }
+}
+
+pub struct WorkflowStepsTable;
+impl SystemTable for WorkflowStepsTable {
+    fn table_name(&self) -> &'static TableName {
+        &WORKFLOW_STEPS_TABLE
+    }
+
+    fn indexes(&self) -> Vec<SystemIndex> {
+        vec![SystemIndex {
+            name: WORKFLOW_STEPS_INDEX_BY_WORKFLOW_ID_AND_STEP_NAME.clone(),
+            fields: vec![WORKFLOW_ID_FIELD.clone(), STEP_NAME_FIELD.clone()]
+                .try_into()
+                .unwrap(),
+        }]
+    }
+
+    fn validate_document(&self, document: ResolvedDocument) -> anyhow::Result<()> {
+        ParsedDocument::<WorkflowStep>::try_from(document).map(|_| ())
+    }
+}
+
+/// Checkpoints for durable workflows. A workflow is a scheduled action whose
+/// named steps are persisted here as they complete, so a retry of the action
+/// can skip the steps (and their side effects) that already ran.
+pub struct WorkflowModel<'a, RT: Runtime> {
+    tx: &'a mut Transaction<RT>,
+    namespace: TableNamespace,
+}
+
+impl<'a, RT: Runtime> WorkflowModel<'a, RT> {
+    pub fn new(tx: &'a mut Transaction<RT>, namespace: TableNamespace) -> Self {
+        Self { tx, namespace }
+    }
+
+    fn steps_query(
+        workflow_id: DeveloperDocumentId,
+        step_name: Option<&str>,
+    ) -> anyhow::Result<Query> {
+        let mut range = vec![IndexRangeExpression::Eq(
+            WORKFLOW_ID_FIELD.clone(),
+            ConvexValue::try_from(workflow_id.encode())?.into(),
+        )];
+        if let Some(step_name) = step_name {
+            range.push(IndexRangeExpression::Eq(
+                STEP_NAME_FIELD.clone(),
+                ConvexValue::try_from(step_name.to_string())?.into(),
+            ));
+        }
+        Ok(Query::index_range(IndexRange {
+            index_name: WORKFLOW_STEPS_INDEX_BY_WORKFLOW_ID_AND_STEP_NAME.clone(),
+            range,
+            order: Order::Asc,
+        }))
+    }
+
+    /// Returns the checkpointed result of a step, if the step has completed in
+    /// a previous attempt of the workflow.
+    pub async fn get_step(
+        &mut self,
+        workflow_id: DeveloperDocumentId,
+        step_name: &str,
+    ) -> anyhow::Result<Option<ParsedDocument<WorkflowStep>>> {
+        let query = Self::steps_query(workflow_id, Some(step_name))?;
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let Some(doc) = query_stream.next(self.tx, None).await? else {
+            return Ok(None);
+        };
+        anyhow::ensure!(
+            query_stream.next(self.tx, None).await?.is_none(),
+            "Expected at most one checkpoint for workflow step {step_name}"
+        );
+        Ok(Some(doc.try_into()?))
+    }
+
+    /// Persist the result of a completed step. Steps are immutable once
+    /// checkpointed, so checkpointing the same step twice is an error.
+    pub async fn checkpoint_step(
+        &mut self,
+        workflow_id: DeveloperDocumentId,
+        step_name: String,
+        result: ConvexValue,
+    ) -> anyhow::Result<()> {
+        if self.get_step(workflow_id, &step_name).await?.is_some() {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "WorkflowStepAlreadyCompleted",
+                format!("Workflow step \"{step_name}\" has already completed"),
+            ));
+        }
+        let step = WorkflowStep {
+            workflow_id,
+            step_name,
+            result,
+            completed_ts: *self.tx.begin_timestamp(),
+        };
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .insert_metadata(&WORKFLOW_STEPS_TABLE, step.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns all checkpointed steps of the workflow, ordered by step name.
+    pub async fn list_steps(
+        &mut self,
+        workflow_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Vec<ParsedDocument<WorkflowStep>>> {
+        let query = Self::steps_query(workflow_id, None)?;
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        let mut steps = Vec::new();
+        while let Some(doc) = query_stream.next(self.tx, None).await? {
+            steps.push(doc.try_into()?);
+        }
+        Ok(steps)
+    }
+
+    fn declaration_query(workflow_id: DeveloperDocumentId) -> anyhow::Result<Query> {
+        Ok(Query::index_range(IndexRange {
+            index_name: WORKFLOWS_INDEX_BY_WORKFLOW_ID.clone(),
+            range: vec![IndexRangeExpression::Eq(
+                WORKFLOW_ID_FIELD.clone(),
+                ConvexValue::try_from(workflow_id.encode())?.into(),
+            )],
+            order: Order::Asc,
+        }))
+    }
+
+    async fn get_declaration(
+        &mut self,
+        workflow_id: DeveloperDocumentId,
+    ) -> anyhow::Result<Option<ParsedDocument<WorkflowMetadata>>> {
+        let query = Self::declaration_query(workflow_id)?;
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        query_stream
+            .expect_at_most_one(self.tx)
+            .await?
+            .map(ParsedDocument::try_from)
+            .transpose()
+    }
+
+    /// Record that the scheduled action is a workflow, before it runs its
+    /// first step. Declaring a workflow again is a no-op.
+    pub async fn declare(&mut self, workflow_id: DeveloperDocumentId) -> anyhow::Result<()> {
+        if self.get_declaration(workflow_id).await?.is_some() {
+            return Ok(());
+        }
+        let workflow = WorkflowMetadata {
+            workflow_id,
+            declared_ts: *self.tx.begin_timestamp(),
+        };
+        SystemMetadataModel::new(self.tx, self.namespace)
+            .insert_metadata(&WORKFLOWS_TABLE, workflow.try_into()?)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the scheduled action has declared itself a workflow. Scheduled
+    /// actions that haven't are not workflows and keep their at-most-once
+    /// semantics.
+    pub async fn is_declared(&mut self, workflow_id: DeveloperDocumentId) -> anyhow::Result<bool> {
+        Ok(self.get_declaration(workflow_id).await?.is_some())
+    }
+
+    /// Whether any step of the workflow has been checkpointed.
+    pub async fn has_steps(&mut self, workflow_id: DeveloperDocumentId) -> anyhow::Result<bool> {
+        let query = Self::steps_query(workflow_id, None)?;
+        let mut query_stream = ResolvedQuery::new(self.tx, self.namespace, query)?;
+        Ok(query_stream.next(self.tx, Some(1)).await?.is_some())
+    }
+
+    /// Delete the checkpoints and declaration of a workflow that has finished.
+    /// Returns the number of deleted steps.
+    pub async fn delete_steps(
+        &mut self,
+        workflow_id: DeveloperDocumentId,
+    ) -> anyhow::Result<usize> {
+        let steps = self.list_steps(workflow_id).await?;
+        let num_steps = steps.len();
+        for step in steps {
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .delete(step.id())
+                .await?;
+        }
+        if let Some(declaration) = self.get_declaration(workflow_id).await? {
+            SystemMetadataModel::new(self.tx, self.namespace)
+                .delete(declaration.id())
+                .await?;
+        }
+        Ok(num_steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use database::test_helpers::DbFixtures;
+    use errors::ErrorMetadata;
+    use runtime::testing::TestRuntime;
+    use value::{
+        id_v6::DeveloperDocumentId,
+        ConvexValue,
+        InternalId,
+        TableNamespace,
+    };
+
+    use crate::{
+        test_helpers::DbFixturesWithModel,
+        workflows::WorkflowModel,
+    };
+
+    #[convex_macro::test_runtime]
+    async fn test_checkpoint_workflow_steps(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let workflow_id = DeveloperDocumentId::new(18.try_into()?, InternalId::MIN);
+        let mut tx = db.begin_system().await?;
+        let mut model = WorkflowModel::new(&mut tx, TableNamespace::test_user());
+        assert!(!model.has_steps(workflow_id).await?);
+        model
+            .checkpoint_step(workflow_id, "charge".to_string(), ConvexValue::from(1.0))
+            .await?;
+        model
+            .checkpoint_step(workflow_id, "email".to_string(), ConvexValue::Null)
+            .await?;
+        db.commit(tx).await?;
+
+        let mut tx = db.begin_system().await?;
+        let mut model = WorkflowModel::new(&mut tx, TableNamespace::test_user());
+        let step = model.get_step(workflow_id, "charge").await?.unwrap();
+        assert_eq!(step.result, ConvexValue::from(1.0));
+        assert!(model.get_step(workflow_id, "ship").await?.is_none());
+
+        // Completed steps can't be overwritten.
+        let err = model
+            .checkpoint_step(workflow_id, "charge".to_string(), ConvexValue::from(2.0))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ErrorMetadata>().unwrap().short_msg,
+            "WorkflowStepAlreadyCompleted"
+        );
+
+        assert_eq!(model.delete_steps(workflow_id).await?, 2);
+        assert!(!model.has_steps(workflow_id).await?);
+        Ok(())
+    }
+
+    #[convex_macro::test_runtime]
+    async fn test_declare_workflow(rt: TestRuntime) -> anyhow::Result<()> {
+        let db = DbFixtures::new(&rt).await?.with_model().await?.db;
+        let workflow_id = DeveloperDocumentId::new(18.try_into()?, InternalId::MIN);
+        let mut tx = db.begin_system().await?;
+        let mut model = WorkflowModel::new(&mut tx, TableNamespace::test_user());
+        assert!(!model.is_declared(workflow_id).await?);
+        model.declare(workflow_id).await?;
+        model.declare(workflow_id).await?;
+        db.commit(tx).await?;
+
+        // A workflow is declared even if none of its steps have completed.
+        let mut tx = db.begin_system().await?;
+        let mut model = WorkflowModel::new(&mut tx, TableNamespace::test_user());
+        assert!(model.is_declared(workflow_id).await?);
+        assert!(!model.has_steps(workflow_id).await?);
+
+        assert_eq!(model.delete_steps(workflow_id).await?, 0);
+        assert!(!model.is_declared(workflow_id).await?);
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value as JsonValue;
+use sync_types::Timestamp;
+use value::{
+    codegen_convex_serialization,
+    id_v6::DeveloperDocumentId,
+    ConvexValue,
+};
+
+/// The persisted result of a single completed step of a workflow. A workflow
+/// is a scheduled action, and is identified by the id of its scheduled job.
+/// When the action is retried after a failure or a backend restart, completed
+/// steps return their checkpointed result instead of being executed again.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct WorkflowStep {
+    pub workflow_id: DeveloperDocumentId,
+    pub step_name: String,
+    #[cfg_attr(
+        any(test, feature = "testing"),
+        proptest(
+            strategy = "proptest::arbitrary::any_with::<ConvexValue>((Default::default(), \
+                        value::proptest::ExcludeSetsAndMaps(true)))"
+        )
+    )]
+    pub result: ConvexValue,
+    pub completed_ts: Timestamp,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedWorkflowStep {
+    workflow_id: String,
+    step_name: String,
+    // Serialize the result as binary since we restrict what field names can be
+    // used in a `Document`'s top-level object.
+    #[serde(with = "serde_bytes")]
+    result: Vec<u8>,
+    completed_ts: i64,
+}
+
+impl TryFrom<WorkflowStep> for SerializedWorkflowStep {
+    type Error = anyhow::Error;
+
+    fn try_from(step: WorkflowStep) -> anyhow::Result<Self> {
+        let result_json = JsonValue::from(step.result);
+        Ok(Self {
+            workflow_id: step.workflow_id.encode(),
+            step_name: step.step_name,
+            result: serde_json::to_vec(&result_json)?,
+            completed_ts: step.completed_ts.into(),
+        })
+    }
+}
+
+impl TryFrom<SerializedWorkflowStep> for WorkflowStep {
+    type Error = anyhow::Error;
+
+    fn try_from(step: SerializedWorkflowStep) -> anyhow::Result<Self> {
+        let result_json: JsonValue = serde_json::from_slice(&step.result)?;
+        Ok(Self {
+            workflow_id: DeveloperDocumentId::decode(&step.workflow_id)?,
+            step_name: step.step_name,
+            result: result_json.try_into()?,
+            completed_ts: step.completed_ts.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(WorkflowStep, SerializedWorkflowStep);
+
+/// Records that a scheduled action declared itself a workflow by starting its
+/// first step. Only declared workflows are restarted when an attempt fails,
+/// even if it failed before any step completed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
+pub struct WorkflowMetadata {
+    pub workflow_id: DeveloperDocumentId,
+    pub declared_ts: Timestamp,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedWorkflowMetadata {
+    workflow_id: String,
+    declared_ts: i64,
+}
+
+impl From<WorkflowMetadata> for SerializedWorkflowMetadata {
+    fn from(workflow: WorkflowMetadata) -> Self {
+        Self {
+            workflow_id: workflow.workflow_id.encode(),
+            declared_ts: workflow.declared_ts.into(),
+        }
+    }
+}
+
+impl TryFrom<SerializedWorkflowMetadata> for WorkflowMetadata {
+    type Error = anyhow::Error;
+
+    fn try_from(workflow: SerializedWorkflowMetadata) -> anyhow::Result<Self> {
+        Ok(Self {
+            workflow_id: DeveloperDocumentId::decode(&workflow.workflow_id)?,
+            declared_ts: workflow.declared_ts.try_into()?,
+        })
+    }
+}
+
+codegen_convex_serialization!(WorkflowMetadata, SerializedWorkflowMetadata);
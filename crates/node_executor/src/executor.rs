@@ -14,7 +14,11 @@ use common::{
         FrameData,
         JsError,
     },
-    execution_context::ExecutionContext,
+    execution_context::{
+        ExecutionContext,
+        ExecutionId,
+    },
+    knobs::NODE_ACTION_RESULT_CHUNK_SIZE,
     log_lines::LogLine,
     sha256::Sha256Digest,
     types::{
@@ -37,6 +41,7 @@ use isolate::{
     ValidatedPathAndArgs,
 };
 use model::{
+    egress_policy::types::EgressPolicy,
     environment_variables::types::{
         EnvVarName,
         EnvVarValue,
@@ -46,6 +51,7 @@ use model::{
     modules::{
         function_validators::{
             ArgsValidator,
+            ErrorsValidator,
             ReturnsValidator,
         },
         module_versions::{
@@ -80,17 +86,20 @@ use value::{
     ConvexValue,
 };
 
-use crate::metrics::{
-    log_download_time,
-    log_external_deps_size_bytes_total,
-    log_function_execution,
-    log_import_time,
-    log_node_source_map_missing,
-    log_node_source_map_token_lookup_failed,
-    log_overhead,
-    log_total_executor_time,
-    log_udf_time,
-    node_executor,
+use crate::{
+    metrics::{
+        log_download_time,
+        log_external_deps_size_bytes_total,
+        log_function_execution,
+        log_import_time,
+        log_node_source_map_missing,
+        log_node_source_map_token_lookup_failed,
+        log_overhead,
+        log_total_executor_time,
+        log_udf_time,
+        node_executor,
+    },
+    result_chunks::ResultChunks,
 };
 
 pub fn error_response_json(message: &str) -> JsonValue {
@@ -129,6 +138,7 @@ pub struct Actions {
     executor: Arc<dyn NodeExecutor>,
     convex_origin: ConvexOrigin,
     user_timeout: Duration,
+    result_chunks: ResultChunks,
 }
 
 fn construct_js_error(
@@ -186,6 +196,7 @@ impl Actions {
             executor,
             convex_origin,
             user_timeout,
+            result_chunks: ResultChunks::default(),
         }
     }
 
@@ -197,6 +208,17 @@ impl Actions {
         self.executor.shutdown()
     }
 
+    /// Receive a chunk of the result of a running action, sent to the action
+    /// callback endpoint by the executor.
+    pub fn append_result_chunk(
+        &self,
+        execution_id: &ExecutionId,
+        index: usize,
+        chunk: String,
+    ) -> anyhow::Result<()> {
+        self.result_chunks.append(execution_id, index, chunk)
+    }
+
     pub async fn execute(
         &self,
         request: ExecuteRequest,
@@ -205,6 +227,7 @@ impl Actions {
     ) -> anyhow::Result<NodeActionOutcome> {
         let path = request.path_and_args.path().clone();
         let timer = node_executor("execute");
+        let pending_result = self.result_chunks.start(&request.context.execution_id);
         let request = ExecutorRequest::Execute {
             request,
             backend_address: self.convex_origin.clone(),
@@ -264,6 +287,10 @@ impl Actions {
             ExecuteResponseResult::Success { udf_return, .. } => {
                 deserialize_udf_result(&path, &udf_return)?
             },
+            ExecuteResponseResult::StreamedSuccess { num_chunks } => {
+                let udf_return = pending_result.finish(num_chunks)?;
+                deserialize_udf_result(&path, &udf_return)?
+            },
             ExecuteResponseResult::Error {
                 message,
                 name,
@@ -432,6 +459,16 @@ impl Actions {
                     Some(json_returns) => ReturnsValidator::try_from(json_returns.clone())?,
                     None => ReturnsValidator::Unvalidated,
                 };
+                let errors = match f.errors.clone() {
+                    Some(json_errors) => match ErrorsValidator::try_from(json_errors) {
+                        Ok(validator) => validator,
+                        Err(parse_error) => {
+                            let message = format!("Invalid errors for {}: {parse_error}", f.name);
+                            return Ok(Err(JsError::from_message(message)));
+                        },
+                    },
+                    None => ErrorsValidator::Unvalidated,
+                };
                 let visibility = f.visibility.clone().map(Visibility::from);
 
                 // Extract source position
@@ -463,6 +500,11 @@ impl Actions {
                     visibility,
                     args,
                     returns,
+                    errors,
+                    // Node actions can't be cached.
+                    cache: None,
+                    // Dependencies are only analyzed for functions run in V8.
+                    dependencies: None,
                 });
             }
 
@@ -538,6 +580,8 @@ impl TryFrom<ExecutorRequest> for JsonValue {
                     "npmVersion": npm_version.map(|v| v.to_string()),
                     "executionContext": JsonValue::from(r.context),
                     "encodedParentTrace": JsonValue::from(r.encoded_parent_trace),
+                    "resultChunkSize": *NODE_ACTION_RESULT_CHUNK_SIZE,
+                    "egressPolicy": JsonValue::from(ConvexObject::try_from(r.egress_policy)?),
                 })
             },
             ExecutorRequest::Analyze(r) => {
@@ -631,6 +675,7 @@ pub struct ExecuteRequest {
     pub user_identity: Option<UserIdentityAttributes>,
     pub auth_header: Option<String>,
     pub environment_variables: BTreeMap<EnvVarName, EnvVarValue>,
+    pub egress_policy: EgressPolicy,
 
     pub callback_token: ActionCallbackToken,
     pub context: ExecutionContext,
@@ -653,6 +698,11 @@ enum ExecuteResponseResult {
     Success {
         udf_return: String,
     },
+    // The result was too large for the response, so the executor sent it to
+    // the action callback endpoint in chunks.
+    StreamedSuccess {
+        num_chunks: usize,
+    },
     Error {
         message: String,
         name: String,
@@ -699,7 +749,8 @@ impl TryFrom<JsonValue> for ExecuteResponse {
         enum ExecuteResponseJson {
             #[serde(rename_all = "camelCase")]
             Success {
-                udf_return: String,
+                udf_return: Option<String>,
+                udf_return_chunks: Option<usize>,
                 num_invocations: usize,
                 download_time_ms: Option<f64>,
                 import_time_ms: Option<f64>,
@@ -725,6 +776,7 @@ impl TryFrom<JsonValue> for ExecuteResponse {
         let result = match resp_json {
             ExecuteResponseJson::Success {
                 udf_return,
+                udf_return_chunks,
                 num_invocations,
                 download_time_ms,
                 import_time_ms,
@@ -732,7 +784,13 @@ impl TryFrom<JsonValue> for ExecuteResponse {
                 total_executor_time_ms,
                 syscall_trace,
             } => ExecuteResponse {
-                result: ExecuteResponseResult::Success { udf_return },
+                result: match (udf_return, udf_return_chunks) {
+                    (Some(udf_return), None) => ExecuteResponseResult::Success { udf_return },
+                    (None, Some(num_chunks)) => {
+                        ExecuteResponseResult::StreamedSuccess { num_chunks }
+                    },
+                    _ => anyhow::bail!("Expected exactly one of udfReturn and udfReturnChunks"),
+                },
                 num_invocations: Some(num_invocations),
                 download_time: download_time_ms.map(duration_from_millis_float),
                 import_time: import_time_ms.map(duration_from_millis_float),
@@ -810,6 +868,8 @@ pub struct AnalyzedNodeFunction {
     visibility: Option<VisibilityJson>,
     args: Option<JsonValue>,
     returns: Option<JsonValue>,
+    #[serde(default)]
+    errors: Option<JsonValue>,
 }
 
 #[derive(Debug)]
@@ -11,22 +11,26 @@
 mod executor;
 pub mod local;
 mod metrics;
+mod result_chunks;
 pub mod source_package;
 
-pub use crate::executor::{
-    error_response_json,
-    parse_streamed_response,
-    Actions,
-    AnalyzeRequest,
-    AnalyzeResponse,
-    BuildDepsRequest,
-    ExecuteRequest,
-    ExecutorRequest,
-    InvokeResponse,
-    NodeActionOutcome,
-    NodeExecutor,
-    Package,
-    ResponsePart,
-    SourcePackage,
-    EXECUTE_TIMEOUT_RESPONSE_JSON,
+pub use crate::{
+    executor::{
+        error_response_json,
+        parse_streamed_response,
+        Actions,
+        AnalyzeRequest,
+        AnalyzeResponse,
+        BuildDepsRequest,
+        ExecuteRequest,
+        ExecutorRequest,
+        InvokeResponse,
+        NodeActionOutcome,
+        NodeExecutor,
+        Package,
+        ResponsePart,
+        SourcePackage,
+        EXECUTE_TIMEOUT_RESPONSE_JSON,
+    },
+    result_chunks::ResultChunks,
 };
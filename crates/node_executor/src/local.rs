@@ -219,9 +219,11 @@ mod tests {
     use maplit::btreemap;
     use model::{
         config::types::ModuleConfig,
+        egress_policy::types::EgressPolicy,
         modules::{
             function_validators::{
                 ArgsValidator,
+                ErrorsValidator,
                 ReturnsValidator,
             },
             module_versions::{
@@ -303,6 +305,7 @@ mod tests {
             user_identity: None,
             auth_header: None,
             environment_variables: btreemap! {},
+            egress_policy: EgressPolicy::default(),
             callback_token: "".to_owned(),
             context: ExecutionContext::new_for_test(),
             encoded_parent_trace: EncodedSpan::from_parent().0,
@@ -426,6 +429,7 @@ mod tests {
                 user_identity: Some(identity.attributes.clone()),
                 auth_header: None,
                 environment_variables: btreemap! {},
+                egress_policy: EgressPolicy::default(),
                 callback_token: "".to_owned(),
                 context: ExecutionContext::new_for_test(),
                 encoded_parent_trace: None,
@@ -684,6 +688,7 @@ mod tests {
                 user_identity: None,
                 auth_header: None,
                 environment_variables,
+                egress_policy: EgressPolicy::default(),
                 callback_token: "".to_owned(),
                 context: ExecutionContext::new_for_test(),
                 encoded_parent_trace: None,
@@ -937,6 +942,9 @@ export { hello, internalHello };
                     visibility: Some(Visibility::Public),
                     args: ArgsValidator::Unvalidated,
                     returns: ReturnsValidator::Unvalidated,
+                    errors: ErrorsValidator::Unvalidated,
+                    cache: None,
+                    dependencies: None,
                 },
                 AnalyzedFunction {
                     name: "internalHello".parse()?,
@@ -949,6 +957,9 @@ export { hello, internalHello };
                     visibility: Some(Visibility::Internal),
                     args: ArgsValidator::Unvalidated,
                     returns: ReturnsValidator::Unvalidated,
+                    errors: ErrorsValidator::Unvalidated,
+                    cache: None,
+                    dependencies: None,
                 },
             ]
         );
@@ -4,7 +4,9 @@ use metrics::{
     log_counter,
     log_distribution,
     log_distribution_with_labels,
+    log_gauge,
     register_convex_counter,
+    register_convex_gauge,
     register_convex_histogram,
     StaticMetricLabel,
     StatusTimer,
@@ -118,3 +120,19 @@ pub fn log_external_deps_size_bytes_total(pkg_size: PackageSize) {
         vec![unzipped_label],
     );
 }
+
+register_convex_histogram!(
+    NODE_EXECUTOR_STREAMED_RESULT_SIZE_BYTES,
+    "Size of action results sent by the Node executor in chunks"
+);
+pub fn log_streamed_result_size(size: usize) {
+    log_distribution(&NODE_EXECUTOR_STREAMED_RESULT_SIZE_BYTES, size as f64);
+}
+
+register_convex_gauge!(
+    NODE_EXECUTOR_BUFFERED_RESULT_BYTES,
+    "Total size of action results being reassembled from chunks"
+);
+pub fn log_buffered_result_bytes(size: usize) {
+    log_gauge(&NODE_EXECUTOR_BUFFERED_RESULT_BYTES, size as f64);
+}
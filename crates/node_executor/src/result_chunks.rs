@@ -0,0 +1,203 @@
+//! Reassembly of Node action results that are too large to return in the
+//! executor's response. The executor sends them to the action callback
+//! endpoint in chunks before it responds, and the response only says how
+//! many chunks it sent.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        Mutex,
+        MutexGuard,
+        PoisonError,
+    },
+};
+
+use anyhow::Context;
+use common::{
+    execution_context::ExecutionId,
+    knobs::{
+        NODE_ACTION_MAX_BUFFERED_RESULT_SIZE,
+        NODE_ACTION_MAX_STREAMED_RESULT_SIZE,
+    },
+};
+use errors::ErrorMetadata;
+
+use crate::metrics::{
+    log_buffered_result_bytes,
+    log_streamed_result_size,
+};
+
+#[derive(Default)]
+struct PendingResult {
+    result: String,
+    num_chunks: usize,
+}
+
+#[derive(Default)]
+struct Inner {
+    // Keyed by the execution id of the action.
+    results: BTreeMap<String, PendingResult>,
+    // The total size of `results`, which is bounded so that many actions
+    // returning large results at once can't exhaust the backend's memory.
+    buffered_bytes: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct ResultChunks {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl ResultChunks {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Accept result chunks for the action with `execution_id` until the
+    /// returned guard is finished or dropped.
+    pub fn start(&self, execution_id: &ExecutionId) -> PendingResultGuard {
+        let execution_id = execution_id.to_string();
+        self.lock()
+            .results
+            .insert(execution_id.clone(), PendingResult::default());
+        PendingResultGuard {
+            result_chunks: self.clone(),
+            execution_id,
+        }
+    }
+
+    /// Append the chunk at `index` to the result of a running action. Chunks
+    /// must be sent in order.
+    pub fn append(
+        &self,
+        execution_id: &ExecutionId,
+        index: usize,
+        chunk: String,
+    ) -> anyhow::Result<()> {
+        let mut inner = self.lock();
+        let Inner {
+            results,
+            buffered_bytes,
+        } = &mut *inner;
+        let Some(pending) = results.get_mut(&execution_id.to_string()) else {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidResultChunk",
+                format!("Action {execution_id} isn't waiting for its result"),
+            ));
+        };
+        if index != pending.num_chunks {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "InvalidResultChunk",
+                format!(
+                    "Expected result chunk {} but got {index}",
+                    pending.num_chunks
+                ),
+            ));
+        }
+        if pending.result.len() + chunk.len() > *NODE_ACTION_MAX_STREAMED_RESULT_SIZE {
+            anyhow::bail!(ErrorMetadata::bad_request(
+                "ActionResultTooLarge",
+                format!(
+                    "Action return value is too large (limit: {} bytes)",
+                    *NODE_ACTION_MAX_STREAMED_RESULT_SIZE
+                ),
+            ));
+        }
+        if *buffered_bytes + chunk.len() > *NODE_ACTION_MAX_BUFFERED_RESULT_SIZE {
+            anyhow::bail!(ErrorMetadata::overloaded(
+                "TooManyLargeActionResults",
+                "Too many actions are returning large values at once. Try again later.",
+            ));
+        }
+        *buffered_bytes += chunk.len();
+        pending.result.push_str(&chunk);
+        pending.num_chunks += 1;
+        log_buffered_result_bytes(*buffered_bytes);
+        Ok(())
+    }
+
+    fn remove(&self, execution_id: &str) -> Option<PendingResult> {
+        let mut inner = self.lock();
+        let pending = inner.results.remove(execution_id)?;
+        inner.buffered_bytes -= pending.result.len();
+        log_buffered_result_bytes(inner.buffered_bytes);
+        Some(pending)
+    }
+}
+
+/// Stops accepting result chunks for an action when dropped, so results of
+/// actions that fail or are cancelled aren't kept around.
+pub struct PendingResultGuard {
+    result_chunks: ResultChunks,
+    execution_id: String,
+}
+
+impl PendingResultGuard {
+    /// The reassembled result, after the executor reports that it sent
+    /// `num_chunks` chunks.
+    pub fn finish(self, num_chunks: usize) -> anyhow::Result<String> {
+        let pending = self
+            .result_chunks
+            .remove(&self.execution_id)
+            .context("Missing pending action result")?;
+        anyhow::ensure!(
+            pending.num_chunks == num_chunks,
+            "Executor sent {num_chunks} result chunks but {} were received",
+            pending.num_chunks
+        );
+        log_streamed_result_size(pending.result.len());
+        Ok(pending.result)
+    }
+}
+
+impl Drop for PendingResultGuard {
+    fn drop(&mut self) {
+        self.result_chunks.remove(&self.execution_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::execution_context::ExecutionId;
+
+    use super::ResultChunks;
+
+    #[test]
+    fn test_reassemble_result() -> anyhow::Result<()> {
+        let result_chunks = ResultChunks::default();
+        let execution_id = ExecutionId::new();
+        let other_execution_id = ExecutionId::new();
+
+        // Chunks are only accepted for running actions.
+        assert!(result_chunks
+            .append(&execution_id, 0, "[1,".to_string())
+            .is_err());
+
+        let guard = result_chunks.start(&execution_id);
+        result_chunks.append(&execution_id, 0, "[1,".to_string())?;
+        // Chunks must arrive in order.
+        assert!(result_chunks
+            .append(&execution_id, 2, "3]".to_string())
+            .is_err());
+        assert!(result_chunks
+            .append(&other_execution_id, 1, "2,".to_string())
+            .is_err());
+        result_chunks.append(&execution_id, 1, "2,".to_string())?;
+        result_chunks.append(&execution_id, 2, "3]".to_string())?;
+        assert_eq!(guard.finish(3)?, "[1,2,3]");
+        assert_eq!(result_chunks.lock().buffered_bytes, 0);
+
+        // Dropping the guard discards the chunks received so far.
+        let guard = result_chunks.start(&execution_id);
+        result_chunks.append(&execution_id, 0, "[1,".to_string())?;
+        drop(guard);
+        assert!(result_chunks.lock().results.is_empty());
+        assert_eq!(result_chunks.lock().buffered_bytes, 0);
+
+        // The executor and backend must agree on the number of chunks.
+        let guard = result_chunks.start(&execution_id);
+        result_chunks.append(&execution_id, 0, "[1]".to_string())?;
+        assert!(guard.finish(2).is_err());
+        Ok(())
+    }
+}
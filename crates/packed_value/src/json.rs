@@ -63,6 +63,20 @@ where
                     serializer.serialize_f64(*f)?
                 }
             },
+            OpenedValue::Decimal(d) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("$decimal", &d.to_string()[..])?;
+                map.end()?
+            },
+            OpenedValue::GeoPoint(p) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                let point = serde_json::json!({
+                    "latitude": p.latitude(),
+                    "longitude": p.longitude(),
+                });
+                map.serialize_entry("$geoPoint", &point)?;
+                map.end()?
+            },
             OpenedValue::Boolean(b) => serializer.serialize_bool(*b)?,
             OpenedValue::String(s) => serializer.serialize_str(&s[..])?,
             OpenedValue::Bytes(b) => {
@@ -22,7 +22,9 @@ use flexbuffers::{
 use value::{
     heap_size::HeapSize,
     ConvexValue,
+    Decimal,
     FieldPath,
+    GeoPoint,
 };
 
 mod buffer;
@@ -135,6 +137,19 @@ impl PackedValue<ByteBuffer> {
             ConvexValue::Float64(f) => {
                 builder.push(*f);
             },
+            ConvexValue::Decimal(d) => {
+                let mut map = builder.start_map();
+                map.push("$decimal", &d.to_string()[..]);
+                map.end_map();
+            },
+            ConvexValue::GeoPoint(p) => {
+                let mut map = builder.start_map();
+                let mut vector = map.start_vector("$geoPoint");
+                vector.push(p.latitude());
+                vector.push(p.longitude());
+                vector.end_vector();
+                map.end_map();
+            },
             ConvexValue::Boolean(b) => {
                 builder.push(*b);
             },
@@ -189,6 +204,8 @@ where
     Null,
     Int64(i64),
     Float64(f64),
+    Decimal(Decimal),
+    GeoPoint(GeoPoint),
     Boolean(bool),
     String(OpenedString<B>),
     Bytes(OpenedBytes<B>),
@@ -207,6 +224,8 @@ where
             OpenedValue::Null => OpenedValue::Null,
             OpenedValue::Int64(i) => OpenedValue::Int64(*i),
             OpenedValue::Float64(f) => OpenedValue::Float64(*f),
+            OpenedValue::Decimal(ref d) => OpenedValue::Decimal(d.clone()),
+            OpenedValue::GeoPoint(p) => OpenedValue::GeoPoint(*p),
             OpenedValue::Boolean(b) => OpenedValue::Boolean(*b),
             OpenedValue::String(ref s) => OpenedValue::String(s.clone()),
             OpenedValue::Bytes(ref b) => OpenedValue::Bytes(b.clone()),
@@ -240,7 +259,18 @@ where
             }),
             FlexBufferType::Map => {
                 let reader = reader.get_map()?;
-                if let Some(ix) = reader.index_key("$set") {
+                if let Some(ix) = reader.index_key("$decimal") {
+                    anyhow::ensure!(reader.len() == 1);
+                    OpenedValue::Decimal(reader.index(ix)?.get_str()?.parse()?)
+                } else if let Some(ix) = reader.index_key("$geoPoint") {
+                    anyhow::ensure!(reader.len() == 1);
+                    let reader = reader.index(ix)?.get_vector()?;
+                    anyhow::ensure!(reader.len() == 2);
+                    OpenedValue::GeoPoint(GeoPoint::new(
+                        reader.index(0)?.get_f64()?,
+                        reader.index(1)?.get_f64()?,
+                    )?)
+                } else if let Some(ix) = reader.index_key("$set") {
                     anyhow::ensure!(reader.len() == 1);
                     let reader = reader.index(ix)?.get_vector()?;
                     OpenedValue::Set(OpenedSet { reader })
@@ -509,6 +539,8 @@ where
             OpenedValue::Null => Self::Null,
             OpenedValue::Int64(i) => Self::from(i),
             OpenedValue::Float64(f) => Self::from(f),
+            OpenedValue::Decimal(d) => Self::from(d),
+            OpenedValue::GeoPoint(p) => Self::from(p),
             OpenedValue::Boolean(b) => Self::from(b),
             OpenedValue::String(s) => Self::try_from(s[..].to_owned())?,
             OpenedValue::Bytes(b) => Self::try_from(b[..].to_owned())?,
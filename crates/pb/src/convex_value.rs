@@ -0,0 +1,185 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use anyhow::Context;
+use value::{
+    ConvexArray,
+    ConvexMap,
+    ConvexObject,
+    ConvexSet,
+    ConvexValue,
+    FieldName,
+    GeoPoint,
+};
+
+use crate::convex_functions::{
+    convex_map::Entry as ConvexMapEntryProto,
+    convex_value::Value as ValueProto,
+    ConvexArray as ConvexArrayProto,
+    ConvexMap as ConvexMapProto,
+    ConvexObject as ConvexObjectProto,
+    ConvexValue as ConvexValueProto,
+    GeoPoint as GeoPointProto,
+};
+
+impl From<ConvexValue> for ConvexValueProto {
+    fn from(value: ConvexValue) -> Self {
+        let value = match value {
+            ConvexValue::Null => ValueProto::NullValue(()),
+            ConvexValue::Int64(i) => ValueProto::Int64Value(i),
+            ConvexValue::Float64(f) => ValueProto::Float64Value(f),
+            ConvexValue::Decimal(d) => ValueProto::DecimalValue(d.to_string()),
+            ConvexValue::GeoPoint(p) => ValueProto::GeoPointValue(GeoPointProto {
+                latitude: p.latitude(),
+                longitude: p.longitude(),
+            }),
+            ConvexValue::Boolean(b) => ValueProto::BooleanValue(b),
+            ConvexValue::String(s) => ValueProto::StringValue(s.into()),
+            ConvexValue::Bytes(b) => ValueProto::BytesValue(b.into()),
+            ConvexValue::Array(array) => ValueProto::ArrayValue(array.into()),
+            ConvexValue::Set(set) => ValueProto::SetValue(ConvexArrayProto {
+                values: set.into_iter().map(Self::from).collect(),
+            }),
+            ConvexValue::Map(map) => ValueProto::MapValue(map.into()),
+            ConvexValue::Object(object) => ValueProto::ObjectValue(object.into()),
+        };
+        Self { value: Some(value) }
+    }
+}
+
+impl TryFrom<ConvexValueProto> for ConvexValue {
+    type Error = anyhow::Error;
+
+    fn try_from(ConvexValueProto { value }: ConvexValueProto) -> anyhow::Result<Self> {
+        let value = match value.context("Missing `value` field")? {
+            ValueProto::NullValue(()) => ConvexValue::Null,
+            ValueProto::Int64Value(i) => ConvexValue::Int64(i),
+            ValueProto::Float64Value(f) => ConvexValue::Float64(f),
+            ValueProto::DecimalValue(d) => ConvexValue::Decimal(d.parse()?),
+            ValueProto::GeoPointValue(GeoPointProto {
+                latitude,
+                longitude,
+            }) => ConvexValue::GeoPoint(GeoPoint::new(latitude, longitude)?),
+            ValueProto::BooleanValue(b) => ConvexValue::Boolean(b),
+            ValueProto::StringValue(s) => ConvexValue::String(s.try_into()?),
+            ValueProto::BytesValue(b) => ConvexValue::Bytes(b.try_into()?),
+            ValueProto::ArrayValue(array) => ConvexValue::Array(array.try_into()?),
+            ValueProto::SetValue(ConvexArrayProto { values }) => {
+                let set = values
+                    .into_iter()
+                    .map(ConvexValue::try_from)
+                    .collect::<anyhow::Result<BTreeSet<_>>>()?;
+                ConvexValue::Set(ConvexSet::try_from(set)?)
+            },
+            ValueProto::MapValue(map) => ConvexValue::Map(map.try_into()?),
+            ValueProto::ObjectValue(object) => ConvexValue::Object(object.try_into()?),
+        };
+        Ok(value)
+    }
+}
+
+impl From<ConvexArray> for ConvexArrayProto {
+    fn from(array: ConvexArray) -> Self {
+        Self {
+            values: array.into_iter().map(ConvexValueProto::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<ConvexArrayProto> for ConvexArray {
+    type Error = anyhow::Error;
+
+    fn try_from(ConvexArrayProto { values }: ConvexArrayProto) -> anyhow::Result<Self> {
+        let values = values
+            .into_iter()
+            .map(ConvexValue::try_from)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        values.try_into()
+    }
+}
+
+impl From<ConvexObject> for ConvexObjectProto {
+    fn from(object: ConvexObject) -> Self {
+        Self {
+            fields: object
+                .into_iter()
+                .map(|(field, value)| (field.to_string(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<ConvexObjectProto> for ConvexObject {
+    type Error = anyhow::Error;
+
+    fn try_from(ConvexObjectProto { fields }: ConvexObjectProto) -> anyhow::Result<Self> {
+        let fields = fields
+            .into_iter()
+            .map(|(field, value)| Ok((field.parse::<FieldName>()?, value.try_into()?)))
+            .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+        fields.try_into()
+    }
+}
+
+impl From<ConvexMap> for ConvexMapProto {
+    fn from(map: ConvexMap) -> Self {
+        Self {
+            entries: map
+                .into_iter()
+                .map(|(key, value)| ConvexMapEntryProto {
+                    key: Some(key.into()),
+                    value: Some(value.into()),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<ConvexMapProto> for ConvexMap {
+    type Error = anyhow::Error;
+
+    fn try_from(ConvexMapProto { entries }: ConvexMapProto) -> anyhow::Result<Self> {
+        let entries = entries
+            .into_iter()
+            .map(|ConvexMapEntryProto { key, value }| {
+                let key = key.context("Missing `key` field")?.try_into()?;
+                let value = value.context("Missing `value` field")?.try_into()?;
+                Ok((key, value))
+            })
+            .collect::<anyhow::Result<BTreeMap<_, _>>>()?;
+        entries.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use value::{
+        testing::assert_roundtrips,
+        ConvexObject,
+        ConvexValue,
+    };
+
+    use crate::convex_functions::{
+        ConvexObject as ConvexObjectProto,
+        ConvexValue as ConvexValueProto,
+    };
+
+    proptest! {
+        #![proptest_config(
+            ProptestConfig { failure_persistence: None, ..ProptestConfig::default() }
+        )]
+
+        #[test]
+        fn test_convex_value_roundtrips(left in any::<ConvexValue>()) {
+            assert_roundtrips::<ConvexValue, ConvexValueProto>(left);
+        }
+
+        #[test]
+        fn test_convex_object_roundtrips(left in any::<ConvexObject>()) {
+            assert_roundtrips::<ConvexObject, ConvexObjectProto>(left);
+        }
+    }
+}
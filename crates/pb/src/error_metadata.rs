@@ -21,6 +21,7 @@ impl From<ErrorCode> for ErrorCodeProto {
             ErrorCode::Forbidden => ErrorCodeProto::Forbidden,
             ErrorCode::NotFound => ErrorCodeProto::NotFound,
             ErrorCode::ClientDisconnect => ErrorCodeProto::ClientDisconnect,
+            ErrorCode::RateLimited => ErrorCodeProto::RateLimited,
             ErrorCode::Overloaded => ErrorCodeProto::Overloaded,
             ErrorCode::RejectedBeforeExecution => ErrorCodeProto::RejectedBeforeExecution,
             ErrorCode::OCC => ErrorCodeProto::Occ,
@@ -41,6 +42,7 @@ impl From<ErrorCodeProto> for ErrorCode {
             ErrorCodeProto::Forbidden => ErrorCode::Forbidden,
             ErrorCodeProto::NotFound => ErrorCode::NotFound,
             ErrorCodeProto::ClientDisconnect => ErrorCode::ClientDisconnect,
+            ErrorCodeProto::RateLimited => ErrorCode::RateLimited,
             ErrorCodeProto::Overloaded => ErrorCode::Overloaded,
             ErrorCodeProto::RejectedBeforeExecution => ErrorCode::RejectedBeforeExecution,
             ErrorCodeProto::Occ => ErrorCode::OCC,
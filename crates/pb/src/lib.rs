@@ -1,6 +1,7 @@
 // @generated - do not modify. Modify build.rs instead.
 #![allow(clippy::match_single_binding)]
 pub mod authentication_token;
+pub mod convex_value;
 pub mod document_id;
 pub mod error_metadata;
 pub mod field_path;
@@ -14,6 +15,9 @@ pub mod convex_actions {
 pub mod convex_cursor {
     include!(concat!(env!("OUT_DIR"), "/convex_cursor.rs"));
 }
+pub mod convex_functions {
+    include!(concat!(env!("OUT_DIR"), "/convex_functions.rs"));
+}
 pub mod convex_identity {
     include!(concat!(env!("OUT_DIR"), "/convex_identity.rs"));
 }
@@ -26,6 +30,9 @@ pub mod convex_query_journal {
 pub mod errors {
     include!(concat!(env!("OUT_DIR"), "/errors.rs"));
 }
+pub mod funrun {
+    include!(concat!(env!("OUT_DIR"), "/funrun.rs"));
+}
 pub mod outcome {
     include!(concat!(env!("OUT_DIR"), "/outcome.rs"));
 }
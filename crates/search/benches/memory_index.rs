@@ -94,6 +94,8 @@ impl Dataset {
         let index_name = index_name.map_table(&|_| Ok(table_id.tablet_id))?;
         let config = DeveloperTextIndexConfig {
             search_field: "body".parse()?,
+            additional_search_fields: BTreeSet::new(),
+            search_field_boosts: BTreeMap::new(),
             filter_fields: BTreeSet::new(),
         };
 
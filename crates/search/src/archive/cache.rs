@@ -324,11 +324,49 @@ impl<RT: Runtime> ArchiveCacheManager<RT> {
         search_file_type: SearchFileType,
     ) -> anyhow::Result<PathBuf> {
         let timer = metrics::archive_get_timer(search_file_type);
-        let result = self.get_logged(search_storage, key, search_file_type).await;
+        let result = self
+            .get_logged(search_storage, key, search_file_type, false)
+            .await;
         timer.finish(result.is_ok());
         result
     }
 
+    /// Like `get`, but keeps the archive on disk until `unpin` is called, even
+    /// when the cache is over its max size.
+    pub async fn pin(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        key: &ObjectKey,
+        search_file_type: SearchFileType,
+    ) -> anyhow::Result<PathBuf> {
+        self.get_logged(search_storage, key, search_file_type, true)
+            .await
+    }
+
+    /// Returns whether the archive was pinned. It stays on disk until the cache
+    /// evicts it.
+    pub fn unpin(
+        &self,
+        search_storage: &Arc<dyn Storage>,
+        key: &ObjectKey,
+        search_file_type: SearchFileType,
+    ) -> bool {
+        self.cache
+            .unpin(&Self::cache_key(search_storage, key, search_file_type))
+    }
+
+    /// The path for the archive if it's already on disk. Never fetches it.
+    pub fn cached_path(
+        &self,
+        search_storage: &Arc<dyn Storage>,
+        key: &ObjectKey,
+        search_file_type: SearchFileType,
+    ) -> Option<PathBuf> {
+        self.cache
+            .peek(&Self::cache_key(search_storage, key, search_file_type))
+            .map(|meta| meta.path.clone())
+    }
+
     #[minitrace::trace]
     pub async fn get_single_file(
         &self,
@@ -339,6 +377,40 @@ impl<RT: Runtime> ArchiveCacheManager<RT> {
         // The archive cache always dumps things into directories, but we want a
         // specific file path.
         let parent_dir: PathBuf = self.get(search_storage, storage_path, file_type).await?;
+        self.single_file_in(parent_dir).await
+    }
+
+    /// Like `get_single_file`, but keeps the file on disk until `unpin` is
+    /// called.
+    pub async fn pin_single_file(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        storage_path: &ObjectKey,
+        file_type: SearchFileType,
+    ) -> anyhow::Result<PathBuf> {
+        let parent_dir: PathBuf = self.pin(search_storage, storage_path, file_type).await?;
+        self.single_file_in(parent_dir).await
+    }
+
+    /// The path for the file if it's already on disk. Never fetches it.
+    pub async fn cached_single_file(
+        &self,
+        search_storage: &Arc<dyn Storage>,
+        storage_path: &ObjectKey,
+        file_type: SearchFileType,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        // Hold on to the entry so the directory isn't cleaned up while it's read.
+        let Some(meta) = self
+            .cache
+            .peek(&Self::cache_key(search_storage, storage_path, file_type))
+        else {
+            return Ok(None);
+        };
+        let path = self.single_file_in(meta.path.clone()).await?;
+        Ok(Some(path))
+    }
+
+    async fn single_file_in(&self, parent_dir: PathBuf) -> anyhow::Result<PathBuf> {
         // tokio's async read_dir method punts to a thread pool too, but by using our
         // own, we can be runtime agnostic.
         let path = self
@@ -358,11 +430,23 @@ impl<RT: Runtime> ArchiveCacheManager<RT> {
         Ok(path)
     }
 
+    fn cache_key(
+        search_storage: &Arc<dyn Storage>,
+        key: &ObjectKey,
+        search_file_type: SearchFileType,
+    ) -> Key {
+        Key {
+            path: search_storage.cache_key(key),
+            search_file_type,
+        }
+    }
+
     async fn get_logged(
         &self,
         search_storage: Arc<dyn Storage>,
         key: &ObjectKey,
         search_file_type: SearchFileType,
+        pin: bool,
     ) -> anyhow::Result<PathBuf> {
         let archive_fetcher = ArchiveFetcher {
             cache_path: self.path.clone(),
@@ -370,22 +454,18 @@ impl<RT: Runtime> ArchiveCacheManager<RT> {
             blocking_thread_pool: self.blocking_thread_pool.clone(),
             cleaner: self.cleaner.clone(),
         };
-        let cache_key = Key {
-            path: search_storage.cache_key(key),
-            search_file_type,
+        let cache_key = Self::cache_key(&search_storage, key, search_file_type);
+        let value_generator = archive_fetcher
+            .generate_value(search_storage.clone(), key.clone(), search_file_type)
+            .boxed();
+        let result = if pin {
+            self.cache.pin(cache_key.clone(), value_generator).await
+        } else {
+            self.cache.get(cache_key.clone(), value_generator).await
         };
-        let result = self
-            .cache
-            .get(
-                cache_key.clone(),
-                archive_fetcher
-                    .generate_value(search_storage.clone(), key.clone(), search_file_type)
-                    .boxed(),
-            )
-            .await
-            .with_context(|| {
-                format!("Failed to get cache_key {cache_key:?} in {search_storage:?}")
-            })?;
+        let result = result.with_context(|| {
+            format!("Failed to get cache_key {cache_key:?} in {search_storage:?}")
+        })?;
 
         let path = result.path.clone();
         let current_size = self.cache.size();
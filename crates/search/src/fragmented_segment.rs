@@ -93,7 +93,8 @@ impl<RT: Runtime> FragmentedSegmentFetcher<RT> {
         anyhow::Error: From<T::Error>,
     {
         stream::iter(fragments.into_iter().map(move |fragment| {
-            self.fetch_fragmented_segment(search_storage.clone(), fragment).boxed()
+            self.fetch_fragmented_segment(search_storage.clone(), fragment)
+                .boxed()
         }))
         // Limit the parallel downloads a bit, we don't want to start and finish all downloads at
         // the same time. We want to be downloading and working with segments concurrently.
@@ -134,6 +135,91 @@ impl<RT: Runtime> FragmentedSegmentFetcher<RT> {
             segment, id_tracker, bitset,
         ))
     }
+
+    /// Like `fetch_fragmented_segment`, but keeps all of the segment's parts
+    /// on disk until `unpin_fragmented_segment` is called.
+    pub async fn pin_fragmented_segment(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        paths: &FragmentedSegmentStorageKeys,
+    ) -> anyhow::Result<UntarredVectorDiskSegmentPaths> {
+        let (segment, id_tracker, bitset) = futures::try_join!(
+            self.archive_cache.pin(
+                search_storage.clone(),
+                &paths.segment,
+                SearchFileType::FragmentedVectorSegment,
+            ),
+            self.archive_cache.pin_single_file(
+                search_storage.clone(),
+                &paths.id_tracker,
+                SearchFileType::VectorIdTracker,
+            ),
+            self.archive_cache.pin_single_file(
+                search_storage.clone(),
+                &paths.deleted_bitset,
+                SearchFileType::VectorDeletedBitset,
+            ),
+        )?;
+        Ok(UntarredVectorDiskSegmentPaths::new(
+            segment, id_tracker, bitset,
+        ))
+    }
+
+    pub fn unpin_fragmented_segment(
+        &self,
+        search_storage: &Arc<dyn Storage>,
+        paths: &FragmentedSegmentStorageKeys,
+    ) {
+        self.archive_cache.unpin(
+            search_storage,
+            &paths.segment,
+            SearchFileType::FragmentedVectorSegment,
+        );
+        self.archive_cache.unpin(
+            search_storage,
+            &paths.id_tracker,
+            SearchFileType::VectorIdTracker,
+        );
+        self.archive_cache.unpin(
+            search_storage,
+            &paths.deleted_bitset,
+            SearchFileType::VectorDeletedBitset,
+        );
+    }
+
+    /// The paths of the segment's parts if they're all already on disk. Never
+    /// fetches them.
+    pub async fn cached_fragmented_segment(
+        &self,
+        search_storage: &Arc<dyn Storage>,
+        paths: &FragmentedSegmentStorageKeys,
+    ) -> anyhow::Result<Option<UntarredVectorDiskSegmentPaths>> {
+        let Some(segment) = self.archive_cache.cached_path(
+            search_storage,
+            &paths.segment,
+            SearchFileType::FragmentedVectorSegment,
+        ) else {
+            return Ok(None);
+        };
+        let (id_tracker, bitset) = futures::try_join!(
+            self.archive_cache.cached_single_file(
+                search_storage,
+                &paths.id_tracker,
+                SearchFileType::VectorIdTracker,
+            ),
+            self.archive_cache.cached_single_file(
+                search_storage,
+                &paths.deleted_bitset,
+                SearchFileType::VectorDeletedBitset,
+            ),
+        )?;
+        let (Some(id_tracker), Some(bitset)) = (id_tracker, bitset) else {
+            return Ok(None);
+        };
+        Ok(Some(UntarredVectorDiskSegmentPaths::new(
+            segment, id_tracker, bitset,
+        )))
+    }
 }
 
 pub(crate) struct FragmentedSegmentCompactor<RT: Runtime> {
@@ -34,6 +34,7 @@ use std::{
         BTreeMap,
         BTreeSet,
     },
+    iter,
     sync::Arc,
 };
 
@@ -239,6 +240,10 @@ pub struct TantivySearchIndexSchema {
     creation_time_field: Field,
 
     search_field_path: FieldPath,
+    // All search fields are indexed into `search_field`, so queries match and
+    // score text from all of them together.
+    additional_search_fields: BTreeSet<FieldPath>,
+    search_field_boosts: BTreeMap<FieldPath, u32>,
     pub search_field: Field,
 
     pub filter_fields: BTreeMap<FieldPath, Field>,
@@ -250,6 +255,20 @@ impl From<&TantivySearchIndexSchema> for pb::searchlight::SearchIndexConfig {
     fn from(schema: &TantivySearchIndexSchema) -> Self {
         pb::searchlight::SearchIndexConfig {
             search_field_path: Some(schema.search_field_path.clone().into()),
+            additional_search_fields: schema
+                .additional_search_fields
+                .iter()
+                .cloned()
+                .map(|p| p.into())
+                .collect::<Vec<_>>(),
+            search_field_boosts: schema
+                .search_field_boosts
+                .iter()
+                .map(|(path, boost)| pb::searchlight::SearchFieldBoost {
+                    path: Some(path.clone().into()),
+                    boost: *boost,
+                })
+                .collect::<Vec<_>>(),
             filter_fields: schema
                 .filter_fields
                 .keys()
@@ -298,6 +317,8 @@ impl TantivySearchIndexSchema {
             creation_time_field,
 
             search_field_path,
+            additional_search_fields: index_config.additional_search_fields.clone(),
+            search_field_boosts: index_config.search_field_boosts.clone(),
             search_field,
 
             filter_fields,
@@ -325,10 +346,38 @@ impl TantivySearchIndexSchema {
     pub fn to_index_config(&self) -> DeveloperTextIndexConfig {
         DeveloperTextIndexConfig {
             search_field: self.search_field_path.clone(),
+            additional_search_fields: self.additional_search_fields.clone(),
+            search_field_boosts: self.search_field_boosts.clone(),
             filter_fields: self.filter_fields.keys().cloned().collect(),
         }
     }
 
+    fn search_field_paths(&self) -> impl Iterator<Item = &FieldPath> {
+        iter::once(&self.search_field_path).chain(&self.additional_search_fields)
+    }
+
+    /// The text of each of the document's search fields, repeated once per
+    /// unit of the field's boost. Boosts are applied at index time this way,
+    /// so a match in a boosted field has a higher term frequency when scoring.
+    fn search_field_texts<'a>(
+        &'a self,
+        document: &'a ResolvedDocument,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.search_field_paths().flat_map(move |field_path| {
+            let boost = self
+                .search_field_boosts
+                .get(field_path)
+                .copied()
+                .unwrap_or(1);
+            let text = match document.value().get_path(field_path) {
+                Some(ConvexValue::String(s)) => Some(&s[..]),
+                _ => None,
+            };
+            text.into_iter()
+                .flat_map(move |text| iter::repeat(text).take(boost as usize))
+        })
+    }
+
     fn filter_field_bytes(document: &ResolvedDocument, field_path: &FieldPath) -> Vec<u8> {
         let value = document.value().get_path(field_path);
         search_value_to_bytes(value)
@@ -339,13 +388,7 @@ impl TantivySearchIndexSchema {
     /// when a super rough estimate is sufficient (e.g. capping the maximum
     /// size of a new segment).
     pub fn estimate_size(&self, document: &ResolvedDocument) -> u64 {
-        let document_size = if let Some(ConvexValue::String(ref s)) =
-            document.value().get_path(&self.search_field_path)
-        {
-            s.len()
-        } else {
-            0
-        };
+        let document_size: usize = self.search_field_texts(document).map(str::len).sum();
         let mut filter_field_sizes = 0;
         for field_path in self.filter_fields.keys() {
             let value = TantivySearchIndexSchema::filter_field_bytes(document, field_path);
@@ -361,18 +404,24 @@ impl TantivySearchIndexSchema {
         let _timer = metrics::index_into_terms_timer();
 
         let mut doc_terms = vec![];
-        if let Some(ConvexValue::String(ref s)) = document.value().get_path(&self.search_field_path)
-        {
-            let mut token_stream = self.analyzer.token_stream(&s[..]);
-
+        // Each text's positions follow on from the previous one's, as they
+        // would for a multi-valued tantivy field.
+        let mut position_offset = 0;
+        for text in self.search_field_texts(document) {
+            let mut token_stream = self.analyzer.token_stream(text);
+            let mut next_offset = position_offset;
             while let Some(token) = token_stream.next() {
                 metrics::log_text_term(&token.text);
 
+                let pos =
+                    FieldPosition(position_offset + u32::from(FieldPosition::try_from(token)?));
+                next_offset = u32::from(pos) + 1;
                 doc_terms.push(DocumentTerm::Search {
                     term: Term::from_field_text(self.search_field, &token.text),
-                    pos: FieldPosition::try_from(token)?,
+                    pos,
                 });
             }
+            position_offset = next_offset;
         }
         for (field_path, tantivy_field) in &self.filter_fields {
             let value = TantivySearchIndexSchema::filter_field_bytes(document, field_path);
@@ -402,9 +451,8 @@ impl TantivySearchIndexSchema {
             .expect("Document should have creation time");
         tantivy_document.add_f64(self.creation_time_field, creation_time.into());
 
-        if let Some(ConvexValue::String(ref s)) = document.value().get_path(&self.search_field_path)
-        {
-            tantivy_document.add_text(self.search_field, s);
+        for text in self.search_field_texts(document) {
+            tantivy_document.add_text(self.search_field, text);
         }
         for (field_path, tantivy_field) in &self.filter_fields {
             let value = TantivySearchIndexSchema::filter_field_bytes(document, field_path);
@@ -416,8 +464,10 @@ impl TantivySearchIndexSchema {
 
     pub fn document_lengths(&self, document: &TantivyDocument) -> DocumentLengths {
         let mut search_field = 0;
-        if let Some(tantivy::schema::Value::Str(ref s)) = document.get_first(self.search_field) {
-            search_field += s.len();
+        for value in document.get_all(self.search_field) {
+            if let tantivy::schema::Value::Str(ref s) = value {
+                search_field += s.len();
+            }
         }
         let mut filter_fields = BTreeMap::new();
         for (field_path, tantivy_field) in &self.filter_fields {
@@ -797,16 +847,14 @@ impl TantivySearchIndexSchema {
             },
         };
 
-        let text_reads = text_query
-            .clone()
-            .into_iter()
-            .map(|t| {
-                anyhow::Ok(TextQueryTermRead::new(
-                    self.search_field_path.clone(),
-                    TextQueryTerm::try_from(t)?,
-                ))
-            })
-            .collect::<anyhow::Result<_>>()?;
+        // The query matches text in any search field, so it reads each of them.
+        let mut text_reads = vec![];
+        for t in text_query.clone() {
+            let term = TextQueryTerm::try_from(t)?;
+            for field_path in self.search_field_paths() {
+                text_reads.push(TextQueryTermRead::new(field_path.clone(), term.clone()));
+            }
+        }
 
         if filter_conditions.len() > MAX_FILTER_CONDITIONS {
             anyhow::bail!(ErrorMetadata::bad_request(
@@ -823,7 +871,7 @@ impl TantivySearchIndexSchema {
             text_query,
             filter_conditions,
         };
-        let reads = QueryReads::new(text_reads, filter_reads.into());
+        let reads = QueryReads::new(text_reads.into(), filter_reads.into());
         metrics::log_compiled_query(&query);
 
         timer.finish();
@@ -850,11 +898,29 @@ pub enum SearchFileType {
 
 #[cfg(test)]
 mod test {
-    use std::collections::BTreeSet;
+    use std::collections::{
+        BTreeMap,
+        BTreeSet,
+    };
 
-    use common::bootstrap_model::index::text_index::DeveloperTextIndexConfig;
+    use common::{
+        bootstrap_model::index::text_index::DeveloperTextIndexConfig,
+        document::{
+            CreationTime,
+            ResolvedDocument,
+        },
+        testing::TestIdGenerator,
+        types::Timestamp,
+    };
+    use maplit::{
+        btreemap,
+        btreeset,
+    };
+    use tantivy::Term;
+    use value::assert_obj;
 
     use crate::{
+        DocumentTerm,
         TantivySearchIndexSchema,
         SEARCH_FIELD_ID,
     };
@@ -866,6 +932,8 @@ mod test {
     fn test_field_ids_dont_change() -> anyhow::Result<()> {
         let schema = TantivySearchIndexSchema::new(&DeveloperTextIndexConfig {
             search_field: "mySearchField".parse()?,
+            additional_search_fields: BTreeSet::new(),
+            search_field_boosts: BTreeMap::new(),
             filter_fields: BTreeSet::new(),
         });
         assert_eq!(schema.internal_id_field.field_id(), 0);
@@ -874,4 +942,39 @@ mod test {
         assert_eq!(schema.search_field.field_id(), SEARCH_FIELD_ID);
         Ok(())
     }
+    #[test]
+    fn test_search_fields_are_indexed_with_boosts() -> anyhow::Result<()> {
+        let schema = TantivySearchIndexSchema::new(&DeveloperTextIndexConfig {
+            search_field: "title".parse()?,
+            additional_search_fields: btreeset! {"body".parse()?},
+            search_field_boosts: btreemap! {"title".parse()? => 3},
+            filter_fields: BTreeSet::new(),
+        });
+        let mut id_generator = TestIdGenerator::new();
+        let document = ResolvedDocument::new(
+            id_generator.user_generate(&"test".parse()?),
+            CreationTime::try_from(10.)?,
+            assert_obj!("title" => "Cats", "body" => "dogs chase cats"),
+        )?;
+
+        let term_count = |text: &str| -> anyhow::Result<usize> {
+            let term = Term::from_field_text(schema.search_field, text);
+            Ok(schema
+                .index_into_terms(&document)?
+                .into_iter()
+                .filter(|t| matches!(t, DocumentTerm::Search { term: t, .. } if *t == term))
+                .count())
+        };
+        // The title is indexed three times and the body once.
+        assert_eq!(term_count("cats")?, 4);
+        assert_eq!(term_count("dogs")?, 1);
+        assert_eq!(term_count("title")?, 0);
+
+        let tantivy_document = schema.index_into_tantivy_document(&document, Timestamp::MIN);
+        assert_eq!(
+            schema.document_lengths(&tantivy_document).search_field,
+            3 * "Cats".len() + "dogs chase cats".len()
+        );
+        Ok(())
+    }
 }
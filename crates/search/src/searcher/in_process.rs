@@ -33,6 +33,7 @@ use super::{
         Bm25Stats,
         PostingListMatch,
         PostingListQuery,
+        SegmentCacheStatus,
         TokenMatch,
         TokenQuery,
     },
@@ -243,6 +244,66 @@ impl<RT: Runtime> Searcher for InProcessSearcher<RT> {
             .execute_text_compaction(search_storage, segments)
             .await
     }
+
+    async fn text_segment_cache_status(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<Vec<SegmentCacheStatus>> {
+        self.searcher
+            .text_segment_cache_status(search_storage, segments)
+            .await
+    }
+
+    async fn vector_segment_cache_status(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<Vec<SegmentCacheStatus>> {
+        self.searcher
+            .vector_segment_cache_status(search_storage, segments)
+            .await
+    }
+
+    async fn pin_text_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<()> {
+        self.searcher
+            .pin_text_segments(search_storage, segments)
+            .await
+    }
+
+    async fn pin_vector_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<()> {
+        self.searcher
+            .pin_vector_segments(search_storage, segments)
+            .await
+    }
+
+    async fn unpin_text_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<()> {
+        self.searcher
+            .unpin_text_segments(search_storage, segments)
+            .await
+    }
+
+    async fn unpin_vector_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<()> {
+        self.searcher
+            .unpin_vector_segments(search_storage, segments)
+            .await
+    }
 }
 
 #[async_trait]
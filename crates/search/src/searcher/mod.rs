@@ -17,6 +17,8 @@ pub use searcher::{
     PostingListQuery,
     Searcher,
     SearcherImpl,
+    SegmentCacheStatus,
+    SegmentResidence,
     SegmentTermMetadataFetcher,
     Term,
     TermDeletionsByField,
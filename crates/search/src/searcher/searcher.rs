@@ -183,6 +183,78 @@ pub trait Searcher: VectorSearcher + Send + Sync + 'static {
         search_storage: Arc<dyn Storage>,
         segments: Vec<FragmentedTextStorageKeys>,
     ) -> anyhow::Result<FragmentedTextSegment>;
+
+    /// Where each of the segments is cached. Never fetches them.
+    async fn text_segment_cache_status(
+        &self,
+        _search_storage: Arc<dyn Storage>,
+        _segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<Vec<SegmentCacheStatus>> {
+        anyhow::bail!("This searcher doesn't report segment cache status")
+    }
+
+    async fn vector_segment_cache_status(
+        &self,
+        _search_storage: Arc<dyn Storage>,
+        _segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<Vec<SegmentCacheStatus>> {
+        anyhow::bail!("This searcher doesn't report segment cache status")
+    }
+
+    /// Fetches the segments and loads them into memory if they aren't already,
+    /// and keeps them there until they're unpinned, so queries never wait for
+    /// them to load.
+    async fn pin_text_segments(
+        &self,
+        _search_storage: Arc<dyn Storage>,
+        _segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("This searcher doesn't support pinning segments")
+    }
+
+    async fn pin_vector_segments(
+        &self,
+        _search_storage: Arc<dyn Storage>,
+        _segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("This searcher doesn't support pinning segments")
+    }
+
+    /// Lets the segments be evicted like any others. Segments that aren't
+    /// pinned are ignored.
+    async fn unpin_text_segments(
+        &self,
+        _search_storage: Arc<dyn Storage>,
+        _segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("This searcher doesn't support pinning segments")
+    }
+
+    async fn unpin_vector_segments(
+        &self,
+        _search_storage: Arc<dyn Storage>,
+        _segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("This searcher doesn't support pinning segments")
+    }
+}
+
+/// Where a search index segment is cached by the searcher.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentResidence {
+    /// Loaded in memory, so queries don't read it from disk.
+    Memory,
+    /// Downloaded to the searcher's disk cache, but not loaded.
+    Disk,
+    /// Only in search storage, so the next query has to download it.
+    Remote,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SegmentCacheStatus {
+    pub residence: SegmentResidence,
+    /// Pinned segments aren't evicted from memory or disk.
+    pub pinned: bool,
 }
 
 /// The value of a tantivy `Term`, should only be constructed from
@@ -445,6 +517,80 @@ impl<RT: Runtime> SearcherImpl<RT> {
         })
     }
 
+    async fn pin_text_segment_paths(
+        &self,
+        storage: Arc<dyn Storage>,
+        keys: &FragmentedTextStorageKeys,
+    ) -> anyhow::Result<TextDiskSegmentPaths> {
+        let (index_path, alive_bitset_path, deleted_terms_table_path, id_tracker_path) = try_join!(
+            self.archive_cache
+                .pin(storage.clone(), &keys.segment, SearchFileType::Text),
+            self.archive_cache.pin_single_file(
+                storage.clone(),
+                &keys.alive_bitset,
+                SearchFileType::TextAliveBitset
+            ),
+            self.archive_cache.pin_single_file(
+                storage.clone(),
+                &keys.deleted_terms_table,
+                SearchFileType::TextDeletedTerms
+            ),
+            self.archive_cache.pin_single_file(
+                storage.clone(),
+                &keys.id_tracker,
+                SearchFileType::TextIdTracker
+            )
+        )?;
+        Ok(TextDiskSegmentPaths {
+            index_path,
+            alive_bitset_path,
+            deleted_terms_table_path,
+            id_tracker_path,
+        })
+    }
+
+    /// The paths of the segment's files if they're all already on disk.
+    async fn cached_text_segment_paths(
+        &self,
+        storage: &Arc<dyn Storage>,
+        keys: &FragmentedTextStorageKeys,
+    ) -> anyhow::Result<Option<TextDiskSegmentPaths>> {
+        let Some(index_path) =
+            self.archive_cache
+                .cached_path(storage, &keys.segment, SearchFileType::Text)
+        else {
+            return Ok(None);
+        };
+        let (alive_bitset_path, deleted_terms_table_path, id_tracker_path) = try_join!(
+            self.archive_cache.cached_single_file(
+                storage,
+                &keys.alive_bitset,
+                SearchFileType::TextAliveBitset
+            ),
+            self.archive_cache.cached_single_file(
+                storage,
+                &keys.deleted_terms_table,
+                SearchFileType::TextDeletedTerms
+            ),
+            self.archive_cache.cached_single_file(
+                storage,
+                &keys.id_tracker,
+                SearchFileType::TextIdTracker
+            )
+        )?;
+        let (Some(alive_bitset_path), Some(deleted_terms_table_path), Some(id_tracker_path)) =
+            (alive_bitset_path, deleted_terms_table_path, id_tracker_path)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(TextDiskSegmentPaths {
+            index_path,
+            alive_bitset_path,
+            deleted_terms_table_path,
+            id_tracker_path,
+        }))
+    }
+
     async fn load_text_segment(
         &self,
         storage: Arc<dyn Storage>,
@@ -559,6 +705,140 @@ impl<RT: Runtime> Searcher for SearcherImpl<RT> {
         timer.finish();
         result
     }
+
+    async fn text_segment_cache_status(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<Vec<SegmentCacheStatus>> {
+        let mut statuses = Vec::with_capacity(segments.len());
+        for keys in segments {
+            let status = match self
+                .cached_text_segment_paths(&search_storage, &keys)
+                .await?
+            {
+                Some(paths) => SegmentCacheStatus {
+                    residence: if self.text_segment_cache.is_loaded(&paths) {
+                        SegmentResidence::Memory
+                    } else {
+                        SegmentResidence::Disk
+                    },
+                    pinned: self.text_segment_cache.is_pinned(&paths),
+                },
+                None => SegmentCacheStatus {
+                    residence: SegmentResidence::Remote,
+                    pinned: false,
+                },
+            };
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+
+    async fn vector_segment_cache_status(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<Vec<SegmentCacheStatus>> {
+        let mut statuses = Vec::with_capacity(segments.len());
+        for paths in segments {
+            let keys: FragmentedSegmentStorageKeys = paths.try_into()?;
+            let status = match self
+                .fragmented_segment_fetcher
+                .cached_fragmented_segment(&search_storage, &keys)
+                .await?
+            {
+                Some(paths) => SegmentCacheStatus {
+                    residence: if self.vector_segment_cache.is_loaded(&paths) {
+                        SegmentResidence::Memory
+                    } else {
+                        SegmentResidence::Disk
+                    },
+                    pinned: self.vector_segment_cache.is_pinned(&paths),
+                },
+                None => SegmentCacheStatus {
+                    residence: SegmentResidence::Remote,
+                    pinned: false,
+                },
+            };
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+
+    async fn pin_text_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<()> {
+        for keys in segments {
+            let paths = self
+                .pin_text_segment_paths(search_storage.clone(), &keys)
+                .await?;
+            self.text_segment_cache.pin(paths).await?;
+        }
+        Ok(())
+    }
+
+    async fn pin_vector_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<()> {
+        for paths in segments {
+            let keys: FragmentedSegmentStorageKeys = paths.try_into()?;
+            let paths = self
+                .fragmented_segment_fetcher
+                .pin_fragmented_segment(search_storage.clone(), &keys)
+                .await?;
+            self.vector_segment_cache.pin(paths).await?;
+        }
+        Ok(())
+    }
+
+    async fn unpin_text_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedTextStorageKeys>,
+    ) -> anyhow::Result<()> {
+        for keys in segments {
+            if let Some(paths) = self
+                .cached_text_segment_paths(&search_storage, &keys)
+                .await?
+            {
+                self.text_segment_cache.unpin(&paths);
+            }
+            for (key, file_type) in [
+                (&keys.segment, SearchFileType::Text),
+                (&keys.alive_bitset, SearchFileType::TextAliveBitset),
+                (&keys.deleted_terms_table, SearchFileType::TextDeletedTerms),
+                (&keys.id_tracker, SearchFileType::TextIdTracker),
+            ] {
+                self.archive_cache.unpin(&search_storage, key, file_type);
+            }
+        }
+        Ok(())
+    }
+
+    async fn unpin_vector_segments(
+        &self,
+        search_storage: Arc<dyn Storage>,
+        segments: Vec<FragmentedVectorSegmentPaths>,
+    ) -> anyhow::Result<()> {
+        for paths in segments {
+            let keys: FragmentedSegmentStorageKeys = paths.try_into()?;
+            if let Some(paths) = self
+                .fragmented_segment_fetcher
+                .cached_fragmented_segment(&search_storage, &keys)
+                .await?
+            {
+                self.vector_segment_cache.unpin(&paths);
+            }
+            self.fragmented_segment_fetcher
+                .unpin_fragmented_segment(&search_storage, &keys);
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -911,11 +1191,13 @@ impl<RT: Runtime> SearcherImpl<RT> {
                         let num_terms_deleted = deletion_tracker.num_terms_deleted(field);
                         let num_terms = total_num_tokens
                             .checked_sub(num_terms_deleted)
-                            // Tantivy's total_num_tokens count is only approximate, so we can't guarantee this won't underflow.
+                            // Tantivy's total_num_tokens count is only approximate, so we can't
+                            // guarantee this won't underflow.
                             .unwrap_or_else(|| {
                                 tracing::warn!(
-                                    "num_terms underflowed for field {field:?} in query_bm_25_stats_impl, subtracted num_terms_deleted: {num_terms_deleted} from \
-                                    total_num_tokens: {total_num_tokens}"
+                                    "num_terms underflowed for field {field:?} in \
+                                     query_bm_25_stats_impl, subtracted num_terms_deleted: \
+                                     {num_terms_deleted} from total_num_tokens: {total_num_tokens}"
                                 );
                                 0
                             });
@@ -1580,6 +1862,8 @@ mod tests {
         let field_path: FieldPath = "mySearchField".parse()?;
         let schema = TantivySearchIndexSchema::new(&DeveloperTextIndexConfig {
             search_field: field_path.clone(),
+            additional_search_fields: BTreeSet::new(),
+            search_field_boosts: BTreeMap::new(),
             filter_fields: BTreeSet::new(),
         });
 
@@ -1757,6 +2041,8 @@ mod tests {
         let field_path: FieldPath = "mySearchField".parse().unwrap();
         TantivySearchIndexSchema::new(&DeveloperTextIndexConfig {
             search_field: field_path.clone(),
+            additional_search_fields: BTreeSet::new(),
+            search_field_boosts: BTreeMap::new(),
             filter_fields: BTreeSet::new(),
         })
     }
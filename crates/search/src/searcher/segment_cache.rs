@@ -90,6 +90,29 @@ impl<RT: Runtime> VectorSegmentCache<RT> {
             )
             .await
     }
+
+    /// Loads the segment and keeps it in memory until `unpin` is called.
+    pub async fn pin(&self, paths: UntarredVectorDiskSegmentPaths) -> anyhow::Result<()> {
+        self.lru
+            .pin(
+                paths.clone(),
+                self.segment_generator.clone().generate_value(paths).boxed(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub fn unpin(&self, paths: &UntarredVectorDiskSegmentPaths) -> bool {
+        self.lru.unpin(paths)
+    }
+
+    pub fn is_pinned(&self, paths: &UntarredVectorDiskSegmentPaths) -> bool {
+        self.lru.is_pinned(paths)
+    }
+
+    pub fn is_loaded(&self, paths: &UntarredVectorDiskSegmentPaths) -> bool {
+        self.lru.peek(paths).is_some()
+    }
 }
 
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
@@ -183,4 +206,30 @@ impl<RT: Runtime> TextSegmentCache<RT> {
             )
             .await
     }
+
+    /// Loads the segment and keeps it in memory until `unpin` is called.
+    pub async fn pin(&self, paths: TextDiskSegmentPaths) -> anyhow::Result<()> {
+        self.lru
+            .pin(
+                paths.clone(),
+                self.text_segment_generator
+                    .clone()
+                    .generate_value(paths)
+                    .boxed(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub fn unpin(&self, paths: &TextDiskSegmentPaths) -> bool {
+        self.lru.unpin(paths)
+    }
+
+    pub fn is_pinned(&self, paths: &TextDiskSegmentPaths) -> bool {
+        self.lru.is_pinned(paths)
+    }
+
+    pub fn is_loaded(&self, paths: &TextDiskSegmentPaths) -> bool {
+        self.lru.peek(paths).is_some()
+    }
 }
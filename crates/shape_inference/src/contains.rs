@@ -28,6 +28,8 @@ impl<C: ShapeConfig> CountedShape<C> {
                 | ShapeEnum::NaN
                 | ShapeEnum::NormalFloat64,
             ) => Float64Shape::<C>::shape_of(*f) == *self.variant,
+            (ConvexValue::Decimal(..), ShapeEnum::Decimal) => true,
+            (ConvexValue::GeoPoint(..), ShapeEnum::GeoPoint) => true,
             (ConvexValue::Boolean(..), ShapeEnum::Boolean) => true,
             (ConvexValue::String(ref s), ShapeEnum::StringLiteral(ref literal)) => {
                 s[..] == literal[..]
@@ -27,6 +27,7 @@ use value::{
     id_v6::DeveloperDocumentId,
     ConvexObject,
     ConvexValue,
+    Decimal,
     FieldName,
     IdentifierFieldName,
 };
@@ -73,6 +74,8 @@ pub enum ExportContext {
         nan_le_bytes: [u8; 8],
     },
     Float64Inf,
+    Decimal,
+    GeoPoint,
     Bytes,
     Array(Vec<ExportContext>),
     Set,
@@ -236,6 +239,20 @@ impl ExportContext {
                     ExportContext::Infer
                 }
             },
+            ConvexValue::Decimal(_) => {
+                if Self::inferred_context_for_string(shape).is_some() {
+                    ExportContext::Infer
+                } else {
+                    ExportContext::Decimal
+                }
+            },
+            ConvexValue::GeoPoint(_) => {
+                if Self::inferred_geo_point_for_object(shape) {
+                    ExportContext::Infer
+                } else {
+                    ExportContext::GeoPoint
+                }
+            },
             ConvexValue::Boolean(_) => ExportContext::Infer,
             ConvexValue::String(_) => ExportContext::Infer,
             ConvexValue::Bytes(_) => {
@@ -311,7 +328,12 @@ impl ExportContext {
         {
             return true;
         }
-        // Part 2: object[key] where key doesn't appear in the shape may be ambiguous.
+        // Part 2: geo points and objects both export as objects.
+        let (geo_point, object) = Self::possible_values_for_object(shape_options);
+        if geo_point && object {
+            return true;
+        }
+        // Part 3: object[key] where key doesn't appear in the shape may be ambiguous.
         // e.g. record<k, string|int64>, or record<k1, string>|record<k2, int64>
         let unspecified_field_shape = shape_options
             .iter()
@@ -320,7 +342,7 @@ impl ExportContext {
         if Self::is_ambiguous_inner(&unspecified_field_shape) {
             return true;
         }
-        // Part 3: object[key] where key does appear in the shape may be ambiguous.
+        // Part 4: object[key] where key does appear in the shape may be ambiguous.
         // e.g. {k: string|int64}, or {k1: string}|record<k2, int64>
         let specified_fields: BTreeSet<_> = shape_options
             .iter()
@@ -353,6 +375,35 @@ impl ExportContext {
         }
     }
 
+    /// Given a set of possible shapes, knowing that the exported value is an
+    /// object, could it be a geo point, and could it be an object?
+    fn possible_values_for_object<C: ShapeConfig, S: ShapeCounter>(
+        shape_options: &BTreeSet<&Shape<C, S>>,
+    ) -> (bool, bool) {
+        let (mut geo_point, mut object) = (false, false);
+        for shape in shape_options
+            .iter()
+            .flat_map(|option| option.union_options())
+        {
+            match shape.variant() {
+                ShapeEnum::GeoPoint => geo_point = true,
+                ShapeEnum::Object(_) | ShapeEnum::Record(_) | ShapeEnum::Unknown => object = true,
+                _ => {},
+            }
+        }
+        (geo_point, object)
+    }
+
+    /// Whether an exported object must be a geo point, given the shapes.
+    fn inferred_geo_point_for_object<C: ShapeConfig, S: ShapeCounter>(
+        shape_options: &BTreeSet<&Shape<C, S>>,
+    ) -> bool {
+        matches!(
+            Self::possible_values_for_object(shape_options),
+            (true, false)
+        )
+    }
+
     /// Given a set of possible shapes, knowing that the exported value is a
     /// string, what are the possible ExportContexts?
     /// e.g. if the shapes are Union(Array(null), Int64, String), the possible
@@ -387,6 +438,7 @@ impl ExportContext {
                         | ShapeEnum::Id(_)
                         | ShapeEnum::FieldName
                         | ShapeEnum::String => yield ExportContext::Infer,
+                        ShapeEnum::Decimal => yield ExportContext::Decimal,
                         ShapeEnum::Bytes => yield ExportContext::Bytes,
                         // Unknown could have any ExportContext that can be a string.
                         ShapeEnum::Unknown => {
@@ -396,6 +448,7 @@ impl ExportContext {
                                 nan_le_bytes: f64::NAN.to_le_bytes(),
                             };
                             yield ExportContext::Int64;
+                            yield ExportContext::Decimal;
                             yield ExportContext::Bytes;
                         },
                         // coroutine cannot be recursive, so unions are already handled by
@@ -406,6 +459,7 @@ impl ExportContext {
                         | ShapeEnum::Null
                         | ShapeEnum::NegativeZero
                         | ShapeEnum::NormalFloat64
+                        | ShapeEnum::GeoPoint
                         | ShapeEnum::Boolean
                         | ShapeEnum::Array(_)
                         | ShapeEnum::Set(_)
@@ -460,6 +514,10 @@ impl ExportContext {
                         .parse::<i64>()
                         .map(ConvexValue::from)
                         .context("Unexpected string for i64"),
+                    Self::Decimal => value
+                        .parse::<Decimal>()
+                        .map(ConvexValue::from)
+                        .context("Unexpected string for decimal"),
                     Self::Float64NaN { nan_le_bytes } => {
                         let nan_value = f64::from_le_bytes(nan_le_bytes);
                         if !nan_value.is_nan() {
@@ -478,7 +536,7 @@ impl ExportContext {
                         _ => anyhow::bail!("Unexpected string for f64"),
                     },
                     Self::Bytes => ConvexValue::try_from(base64::decode(value)?),
-                    Self::Array(_) | Self::Map | Self::Set | Self::Object(_) => {
+                    Self::GeoPoint | Self::Array(_) | Self::Map | Self::Set | Self::Object(_) => {
                         anyhow::bail!("unexpected shape hint for string")
                     },
                 }
@@ -515,12 +573,21 @@ impl ExportContext {
                 | Self::Float64NaN { .. }
                 | Self::Float64Inf
                 | Self::Int64
+                | Self::Decimal
+                | Self::GeoPoint
                 | Self::Map
                 | Self::Object(_)
                 | Self::Set => anyhow::bail!("unsupported shape hint for array value"),
             },
             JsonValue::Object(exported_values) => {
                 match self {
+                    Self::Infer if Self::inferred_geo_point_for_object(shape) => {
+                        Self::GeoPoint.apply_inner(JsonValue::Object(exported_values), shape)
+                    },
+                    Self::GeoPoint => {
+                        ConvexValue::try_from(json!({ "$geoPoint": exported_values }))
+                            .context("Unexpected object for geo point")
+                    },
                     Self::Infer => {
                         let entries: BTreeMap<FieldName, ConvexValue> = exported_values
                             .into_iter()
@@ -557,6 +624,7 @@ impl ExportContext {
                     Self::Int64
                     | Self::Float64NaN { .. }
                     | Self::Float64Inf
+                    | Self::Decimal
                     | Self::Bytes
                     | Self::Array(_) => anyhow::bail!("unsupported shape hint for object value"),
                 }
@@ -571,6 +639,8 @@ impl From<ExportContext> for JsonValue {
             ExportContext::Infer => json!("infer"),
             ExportContext::Int64 => json!("int64"),
             ExportContext::Float64Inf => json!("float64inf"),
+            ExportContext::Decimal => json!("decimal"),
+            ExportContext::GeoPoint => json!("geoPoint"),
             ExportContext::Bytes => json!("bytes"),
             ExportContext::Set => json!("set"),
             ExportContext::Map => json!("map"),
@@ -597,6 +667,8 @@ impl TryFrom<JsonValue> for ExportContext {
                 "infer" => Self::Infer,
                 "int64" => Self::Int64,
                 "float64inf" => Self::Float64Inf,
+                "decimal" => Self::Decimal,
+                "geoPoint" => Self::GeoPoint,
                 "bytes" => Self::Bytes,
                 "set" => Self::Set,
                 "map" => Self::Map,
@@ -645,6 +717,7 @@ mod tests {
         assert_val,
         export::ValueFormat,
         ConvexValue,
+        GeoPoint,
     };
 
     use crate::{
@@ -760,6 +833,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_array_of_geo_points() {
+        let point1 = ConvexValue::GeoPoint(GeoPoint::new(51.5, -0.125).unwrap());
+        let point2 = ConvexValue::GeoPoint(GeoPoint::new(-33.9, 151.2).unwrap());
+        test_inferred(assert_val!([point1, point2]));
+    }
+
+    #[test]
+    fn test_array_of_geo_point_and_object() {
+        let point = ConvexValue::GeoPoint(GeoPoint::new(51.5, -0.125).unwrap());
+        test_export_context(
+            assert_val!([point, {"latitude" => 51.5, "longitude" => -0.125}]),
+            ExportContext::Array(vec![ExportContext::GeoPoint, ExportContext::Infer]),
+        );
+    }
+
     #[test]
     fn test_set() {
         let set = ConvexValue::Set(
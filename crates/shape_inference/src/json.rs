@@ -79,6 +79,8 @@ impl<C: ShapeConfig> TryFrom<JsonValue> for CountedShapeEnum<C> {
             NegativeZero,
             NaN,
             NormalFloat64,
+            Decimal,
+            GeoPoint,
             Boolean,
             StringLiteral {
                 literal: String,
@@ -128,6 +130,8 @@ impl<C: ShapeConfig> TryFrom<JsonValue> for CountedShapeEnum<C> {
             ShapeEnumJson::NegativeZero => ShapeEnum::NegativeZero,
             ShapeEnumJson::NaN => ShapeEnum::NaN,
             ShapeEnumJson::NormalFloat64 => ShapeEnum::NormalFloat64,
+            ShapeEnumJson::Decimal => ShapeEnum::Decimal,
+            ShapeEnumJson::GeoPoint => ShapeEnum::GeoPoint,
             ShapeEnumJson::Boolean => ShapeEnum::Boolean,
             ShapeEnumJson::StringLiteral { literal } => {
                 let t = StringLiteralShape::shape_of(&literal);
@@ -239,6 +243,8 @@ impl<C: ShapeConfig> CountedShapeEnum<C> {
             ShapeEnum::NegativeZero => json!({"kind": "NegativeZero"}),
             ShapeEnum::NaN => json!({"kind": "NaN"}),
             ShapeEnum::NormalFloat64 => json!({"kind": "NormalFloat64"}),
+            ShapeEnum::Decimal => json!({"kind": "Decimal"}),
+            ShapeEnum::GeoPoint => json!({"kind": "GeoPoint"}),
             ShapeEnum::Boolean => json!({"kind": "Boolean"}),
             ShapeEnum::StringLiteral(ref s) => {
                 if include_pii {
@@ -143,6 +143,12 @@ pub enum ShapeEnum<C: ShapeConfig, S: ShapeCounter> {
     /// The set of all `Value::Float64`s.
     Float64,
 
+    /// The set of all `Value::Decimal`s.
+    Decimal,
+
+    /// The set of all `Value::GeoPoint`s.
+    GeoPoint,
+
     /// The set of all `Value::Boolean`s (i.e. `{true, false}`).
     Boolean,
 
@@ -266,6 +272,8 @@ impl<C: ShapeConfig> CountedShape<C> {
             ConvexValue::Null => ShapeEnum::Null,
             ConvexValue::Int64(..) => ShapeEnum::Int64,
             ConvexValue::Float64(f) => Float64Shape::shape_of(*f),
+            ConvexValue::Decimal(..) => ShapeEnum::Decimal,
+            ConvexValue::GeoPoint(..) => ShapeEnum::GeoPoint,
             ConvexValue::Boolean(..) => ShapeEnum::Boolean,
             ConvexValue::String(ref s) => StringLiteralShape::shape_of(s),
             ConvexValue::Bytes(..) => ShapeEnum::Bytes,
@@ -361,6 +369,8 @@ impl<C: ShapeConfig> CountedShape<C> {
                     return None;
                 }
             },
+            (ConvexValue::Decimal(..), ShapeEnum::Decimal) => ShapeEnum::Decimal,
+            (ConvexValue::GeoPoint(..), ShapeEnum::GeoPoint) => ShapeEnum::GeoPoint,
             (ConvexValue::Boolean(..), ShapeEnum::Boolean) => ShapeEnum::Boolean,
             (ConvexValue::String(ref s1), ShapeEnum::StringLiteral(ref s2)) if s1[..] == s2[..] => {
                 ShapeEnum::StringLiteral(s2.clone())
@@ -539,6 +549,8 @@ impl<C: ShapeConfig, S: ShapeCounter> ShapeEnum<C, S> {
             | ShapeEnum::NegativeZero
             | ShapeEnum::NaN
             | ShapeEnum::NormalFloat64
+            | ShapeEnum::Decimal
+            | ShapeEnum::GeoPoint
             | ShapeEnum::Boolean
             | ShapeEnum::Id(_)
             | ShapeEnum::FieldName
@@ -580,6 +592,8 @@ impl<C: ShapeConfig> From<&CountedShapeEnum<C>> for StructuralShapeEnum<C> {
             ShapeEnum::NaN => Self::NaN,
             ShapeEnum::NormalFloat64 => Self::NormalFloat64,
             ShapeEnum::Float64 => Self::Float64,
+            ShapeEnum::Decimal => Self::Decimal,
+            ShapeEnum::GeoPoint => Self::GeoPoint,
             ShapeEnum::Boolean => Self::Boolean,
             ShapeEnum::StringLiteral(literal) => Self::StringLiteral(literal.clone()),
             ShapeEnum::Id(table_number) => Self::Id(*table_number),
@@ -48,6 +48,8 @@ impl<C: ShapeConfig, S: ShapeCounter> fmt::Display for ShapeEnum<C, S> {
             ShapeEnum::NegativeZero => write!(f, "-0"),
             ShapeEnum::NaN => write!(f, "NaN"),
             ShapeEnum::NormalFloat64 => write!(f, "normalfloat64"),
+            ShapeEnum::Decimal => write!(f, "decimal"),
+            ShapeEnum::GeoPoint => write!(f, "geo_point"),
             ShapeEnum::Boolean => write!(f, "boolean"),
             ShapeEnum::StringLiteral(ref s) => write!(f, "{:?}", &s[..]),
             ShapeEnum::Id(ref table) => write!(f, "id<{table}>"),
@@ -186,6 +188,8 @@ impl<C: ShapeConfig> StructuralShape<C> {
             ("inf", ShapeEnum::PositiveInf),
             ("NaN", ShapeEnum::NaN),
             ("normalfloat64", ShapeEnum::NormalFloat64),
+            ("decimal", ShapeEnum::Decimal),
+            ("geo_point", ShapeEnum::GeoPoint),
             ("boolean", ShapeEnum::Boolean),
             ("field_name", ShapeEnum::FieldName),
             ("string", ShapeEnum::String),
@@ -36,6 +36,8 @@ impl<C: ShapeConfig, S: ShapeCounter> ShapeEnum<C, S> {
             // Primitive types without nontrivial subtyping.
             (ShapeEnum::Null, ShapeEnum::Null) => true,
             (ShapeEnum::Int64, ShapeEnum::Int64) => true,
+            (ShapeEnum::Decimal, ShapeEnum::Decimal) => true,
+            (ShapeEnum::GeoPoint, ShapeEnum::GeoPoint) => true,
             (ShapeEnum::Boolean, ShapeEnum::Boolean) => true,
             (ShapeEnum::Bytes, ShapeEnum::Bytes) => true,
 
@@ -227,6 +229,8 @@ impl<C: ShapeConfig> CountedShape<C> {
             (ShapeEnum::Never, other_variant) => other_variant.clone(),
             (ShapeEnum::Null, ShapeEnum::Null) => ShapeEnum::Null,
             (ShapeEnum::Int64, ShapeEnum::Int64) => ShapeEnum::Int64,
+            (ShapeEnum::Decimal, ShapeEnum::Decimal) => ShapeEnum::Decimal,
+            (ShapeEnum::GeoPoint, ShapeEnum::GeoPoint) => ShapeEnum::GeoPoint,
             (ShapeEnum::Boolean, ShapeEnum::Boolean) => ShapeEnum::Boolean,
             (ShapeEnum::Bytes, ShapeEnum::Bytes) => ShapeEnum::Bytes,
 
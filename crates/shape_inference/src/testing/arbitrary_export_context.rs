@@ -24,6 +24,8 @@ impl Arbitrary for ExportContext {
             Just(ExportContext::Int64),
             (any::<[u8; 8]>()).prop_map(|nan_le_bytes| ExportContext::Float64NaN { nan_le_bytes }),
             Just(ExportContext::Float64Inf),
+            Just(ExportContext::Decimal),
+            Just(ExportContext::GeoPoint),
             Just(ExportContext::Bytes),
             Just(ExportContext::Set),
             Just(ExportContext::Map),
@@ -144,6 +144,10 @@ pub fn nonempty_shape_strategy<C: ShapeConfig>() -> impl Strategy<Value = Counte
         (1..MAX_NUM_VALUES)
             .prop_map(|num_values| CountedShape::new(ShapeEnum::PositiveInf, num_values)),
         (1..MAX_NUM_VALUES).prop_map(|num_values| CountedShape::new(ShapeEnum::NaN, num_values)),
+        (1..MAX_NUM_VALUES)
+            .prop_map(|num_values| CountedShape::new(ShapeEnum::Decimal, num_values)),
+        (1..MAX_NUM_VALUES)
+            .prop_map(|num_values| CountedShape::new(ShapeEnum::GeoPoint, num_values)),
         (1..MAX_NUM_VALUES)
             .prop_map(|num_values| CountedShape::new(ShapeEnum::Boolean, num_values)),
         (1..MAX_NUM_VALUES, any::<StringLiteralShape<C>>())
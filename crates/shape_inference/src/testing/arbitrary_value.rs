@@ -36,6 +36,12 @@ pub fn shape_member_strategy<C: ShapeConfig>(t: &CountedShape<C>) -> BoxedStrate
         ShapeEnum::PositiveInf => Just(ConvexValue::Float64(f64::INFINITY)).boxed(),
         ShapeEnum::NegativeZero => Just(ConvexValue::Float64(-0.0)).boxed(),
         ShapeEnum::NaN => Just(ConvexValue::Float64(f64::NAN)).boxed(),
+        ShapeEnum::Decimal => any::<value::Decimal>()
+            .prop_map(ConvexValue::Decimal)
+            .boxed(),
+        ShapeEnum::GeoPoint => any::<value::GeoPoint>()
+            .prop_map(ConvexValue::GeoPoint)
+            .boxed(),
         ShapeEnum::Boolean => any::<bool>().prop_map(ConvexValue::Boolean).boxed(),
         ShapeEnum::StringLiteral(ref s) => {
             Just(ConvexValue::String(s[..].try_into().unwrap())).boxed()
@@ -21,7 +21,10 @@ use std::{
         Write,
     },
     mem,
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
     pin::Pin,
     sync::Arc,
     task::{
@@ -77,6 +80,19 @@ use value::sha256::{
     Sha256Digest,
 };
 
+mod remote;
+pub mod sigv4;
+
+pub use remote::{
+    CredentialsProvider,
+    RemoteStorage,
+    RemoteStorageConfig,
+    RemoteUpload,
+    StorageCredentials,
+    StorageProvider,
+    REMOTE_MIN_PART_SIZE,
+};
+
 pub const LOCAL_DIR_MIN_PART_SIZE: usize = 5 * (1 << 20);
 pub const MAX_PART_SIZE: usize = 8 * (1 << 30);
 pub const MAX_NUM_PARTS: usize = 10000;
@@ -156,6 +172,9 @@ pub trait Storage: Send + Sync + Debug {
         &self,
         key: &ObjectKey,
     ) -> anyhow::Result<Option<ObjectAttributes>>;
+    /// Move an object to another storage class of the bucket, e.g. `GLACIER`
+    /// on S3 or `Cool` on Azure. Its key and contents stay the same.
+    async fn set_storage_class(&self, key: &ObjectKey, storage_class: &str) -> anyhow::Result<()>;
     /// Not intended to be called directly.
     /// Use get_range() or get() instead.
     fn get_small_range(
@@ -864,6 +883,14 @@ impl TryFrom<ClientDrivenUploadToken> for ClientDrivenUpload {
     }
 }
 
+/// Where part `part_number` of the client driven upload to `filepath` is kept
+/// until the upload finishes.
+fn part_path(filepath: &Path, part_number: u16) -> PathBuf {
+    let mut path = filepath.as_os_str().to_owned();
+    path.push(format!(".part{part_number:05}"));
+    PathBuf::from(path)
+}
+
 #[async_trait]
 impl<RT: Runtime> Storage for LocalDirStorage<RT> {
     async fn start_upload(&self) -> anyhow::Result<Box<BufferedUpload>> {
@@ -923,32 +950,47 @@ impl<RT: Runtime> Storage for LocalDirStorage<RT> {
     async fn upload_part(
         &self,
         token: ClientDrivenUploadToken,
-        _part_number: u16,
+        part_number: u16,
         part: Bytes,
     ) -> anyhow::Result<ClientDrivenUploadPartToken> {
         let ClientDrivenUpload {
             object_key,
             filepath,
         } = token.try_into()?;
-        let file = OpenOptions::new().append(true).open(filepath)?;
+        // Each part is written to its own file so that retrying a part
+        // replaces it, and parts can arrive in any order. They're appended to
+        // the object when the upload finishes.
+        let file = File::create(part_path(&filepath, part_number))?;
         let mut upload = LocalDirUpload {
             object_key,
             file: Some(file),
             num_parts: 0, // unused
         };
         upload.write(part).await?;
-        Ok(ClientDrivenUploadPartToken(String::new()))
+        Ok(ClientDrivenUploadPartToken(part_number.to_string()))
     }
 
     async fn finish_client_driven_upload(
         &self,
         token: ClientDrivenUploadToken,
-        _part_tokens: Vec<ClientDrivenUploadPartToken>,
+        part_tokens: Vec<ClientDrivenUploadPartToken>,
     ) -> anyhow::Result<ObjectKey> {
         let ClientDrivenUpload {
             object_key,
-            filepath: _,
+            filepath,
         } = token.try_into()?;
+        let mut file = OpenOptions::new().append(true).open(&filepath)?;
+        for ClientDrivenUploadPartToken(part_token) in part_tokens {
+            let part_number: u16 = part_token
+                .parse()
+                .with_context(|| format!("Invalid part token {part_token:?}"))?;
+            let path = part_path(&filepath, part_number);
+            let mut part = File::open(&path)
+                .with_context(|| format!("Part {part_number} was not uploaded"))?;
+            std::io::copy(&mut part, &mut file)?;
+            fs::remove_file(path)?;
+        }
+        file.sync_all()?;
         Ok(object_key)
     }
 
@@ -990,6 +1032,10 @@ impl<RT: Runtime> Storage for LocalDirStorage<RT> {
         ))
     }
 
+    async fn set_storage_class(&self, _key: &ObjectKey, storage_class: &str) -> anyhow::Result<()> {
+        anyhow::bail!("Local storage has no storage classes, can't move a file to {storage_class}")
+    }
+
     fn cache_key(&self, key: &ObjectKey) -> StorageCacheKey {
         let key = self.path_for_key(key.clone());
         let path = self.dir.join(key);
@@ -1212,6 +1258,33 @@ mod local_storage_tests {
         Ok(())
     }
 
+    #[convex_macro::test_runtime]
+    async fn test_client_driven_upload(rt: TestRuntime) -> anyhow::Result<()> {
+        let storage: Arc<dyn Storage> = Arc::new(LocalDirStorage::new(rt)?);
+        let token = storage.start_client_driven_upload().await?;
+        let second = storage
+            .upload_part(token.clone(), 2, Bytes::from_static(b" park"))
+            .await?;
+        storage
+            .upload_part(token.clone(), 1, Bytes::from_static(b"pinna pinna"))
+            .await?;
+        // Retrying a part replaces it.
+        let first = storage
+            .upload_part(token.clone(), 1, Bytes::from_static(b"pinna"))
+            .await?;
+        let key = storage
+            .finish_client_driven_upload(token, vec![first, second])
+            .await?;
+        let contents = storage
+            .get(&key)
+            .await?
+            .context("Not found")?
+            .collect_as_bytes()
+            .await?;
+        assert_eq!(&contents, "pinna park");
+        Ok(())
+    }
+
     #[convex_macro::test_runtime]
     async fn test_storage_get_paginated(rt: TestRuntime) -> anyhow::Result<()> {
         // Test that chunks are stitched together in the right order.
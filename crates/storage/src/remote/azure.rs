@@ -0,0 +1,185 @@
+//! Authentication of Azure Blob Storage requests with the storage account's
+//! key, see
+//! <https://learn.microsoft.com/en-us/rest/api/storageservices/authorize-with-shared-key>
+//! and <https://learn.microsoft.com/en-us/rest/api/storageservices/create-service-sas>.
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use url::Url;
+
+use crate::sigv4::hmac_sha256;
+
+/// The version of the Blob Storage API requests are made with.
+pub(super) const AZURE_VERSION: &str = "2021-08-06";
+
+/// The headers that authenticate a `method` request for `url` made at `now`
+/// with a body of `content_length` bytes. `headers` are the request's other
+/// `x-ms-` headers, which are signed too.
+pub(super) fn sign(
+    account: &str,
+    account_key: &str,
+    method: &str,
+    url: &Url,
+    mut headers: Vec<(&'static str, String)>,
+    content_length: usize,
+    now: DateTime<Utc>,
+) -> anyhow::Result<Vec<(&'static str, String)>> {
+    headers.push((
+        "x-ms-date",
+        now.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+    ));
+    headers.push(("x-ms-version", AZURE_VERSION.to_string()));
+    headers.sort();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let mut canonical_resource = format!("/{account}{}", url.path());
+    let mut params: Vec<_> = url
+        .query_pairs()
+        .map(|(name, value)| (name.to_lowercase(), value.into_owned()))
+        .collect();
+    params.sort();
+    for (name, value) in params {
+        canonical_resource.push_str(&format!("\n{name}:{value}"));
+    }
+    // The Content-Length line is empty for requests without a body.
+    let content_length = if content_length == 0 {
+        String::new()
+    } else {
+        content_length.to_string()
+    };
+    let string_to_sign = format!(
+        "{method}\n\n\n{content_length}\n\n\n\n\n\n\n\n\n{canonical_headers}{canonical_resource}"
+    );
+    let signature = signature(account_key, &string_to_sign)?;
+    headers.push(("authorization", format!("SharedKey {account}:{signature}")));
+    Ok(headers)
+}
+
+/// `url`, the URL of `blob` in `container`, with a service SAS granting
+/// `permissions` (e.g. `r` to read or `cw` to create and write) on the blob
+/// until `expires_at`.
+pub(super) fn presign(
+    account: &str,
+    account_key: &str,
+    container: &str,
+    blob: &str,
+    url: &Url,
+    permissions: &str,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<Url> {
+    let expiry = expires_at.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let canonical_resource = format!("/blob/{account}/{container}/{blob}");
+    // The start, identifier, IP, protocol, snapshot time, encryption scope
+    // and response header overrides are left empty.
+    let string_to_sign = format!(
+        "{permissions}\n\n{expiry}\n{canonical_resource}\n\n\n\n{AZURE_VERSION}\nb\n\n\n\n\n\n\n"
+    );
+    let mut url = url.clone();
+    url.query_pairs_mut()
+        .append_pair("sv", AZURE_VERSION)
+        .append_pair("se", &expiry)
+        .append_pair("sr", "b")
+        .append_pair("sp", permissions)
+        .append_pair("sig", &signature(account_key, &string_to_sign)?);
+    Ok(url)
+}
+
+fn signature(account_key: &str, string_to_sign: &str) -> anyhow::Result<String> {
+    let key = base64::decode(account_key)
+        .map_err(|e| anyhow::anyhow!("Azure storage account key isn't valid base64: {e}"))?;
+    Ok(base64::encode(hmac_sha256(&key, string_to_sign.as_bytes())))
+}
+
+/// The id of the block that holds part `part_number` of a blob. A blob's
+/// block ids must all have the same length.
+pub(super) fn block_id(part_number: u16) -> String {
+    base64::encode(format!("{part_number:05}"))
+}
+
+/// The body of a Put Block List request that commits `block_ids` in order.
+pub(super) fn block_list(block_ids: &[String]) -> String {
+    let blocks: String = block_ids
+        .iter()
+        .map(|block_id| format!("<Latest>{block_id}</Latest>"))
+        .collect();
+    format!("<?xml version=\"1.0\" encoding=\"utf-8\"?><BlockList>{blocks}</BlockList>")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{
+        TimeZone,
+        Utc,
+    };
+
+    use super::{
+        block_id,
+        block_list,
+        presign,
+        sign,
+    };
+
+    // Azurite's well-known development account.
+    const ACCOUNT: &str = "devstoreaccount1";
+    const ACCOUNT_KEY: &str =
+        "Eby8vdM02xNOcqFlqUwJPLlmEtlCDXJ1OUzFT50uSRZ6IFsuFq2UVErCz4I6tq/K1SZFPTOtr/KBHBeksoGMGw==";
+
+    #[test]
+    fn test_sign() -> anyhow::Result<()> {
+        let url =
+            "http://127.0.0.1:10000/devstoreaccount1/files/a.blob?comp=block&blockid=MDAwMDE%3D"
+                .parse()?;
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let headers = sign(ACCOUNT, ACCOUNT_KEY, "PUT", &url, vec![], 3, now)?;
+        assert_eq!(
+            headers,
+            vec![
+                ("x-ms-date", "Tue, 02 Jan 2024 03:04:05 GMT".to_string()),
+                ("x-ms-version", "2021-08-06".to_string()),
+                (
+                    "authorization",
+                    "SharedKey devstoreaccount1:sK5xt8pRTWbUjValI7RJ5KZi5EOVNcfxRsoaM3NsH5U="
+                        .to_string()
+                ),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_presign() -> anyhow::Result<()> {
+        let url = "https://devstoreaccount1.blob.core.windows.net/files/a.blob".parse()?;
+        let expires_at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        let presigned = presign(
+            ACCOUNT,
+            ACCOUNT_KEY,
+            "files",
+            "a.blob",
+            &url,
+            "r",
+            expires_at,
+        )?;
+        assert_eq!(
+            presigned.as_str(),
+            "https://devstoreaccount1.blob.core.windows.net/files/a.blob\
+             ?sv=2021-08-06&se=2024-01-02T03%3A04%3A05Z&sr=b&sp=r\
+             &sig=AjxowWM2bsRyBQrILTgSaTCDVKylafICxWTCClVad68%3D"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_block_list() {
+        assert_eq!(block_id(1), "MDAwMDE=");
+        assert_eq!(
+            block_list(&[block_id(1), block_id(2)]),
+            "<?xml version=\"1.0\" \
+             encoding=\"utf-8\"?><BlockList><Latest>MDAwMDE=</Latest><Latest>MDAwMDI=</Latest></\
+             BlockList>"
+        );
+    }
+}
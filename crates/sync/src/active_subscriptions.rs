@@ -0,0 +1,260 @@
+//! Registry of the queries clients are subscribed to across all sync workers,
+//! aggregated by function and arguments. Operators can use it to find which
+//! query fan-out is responsible for a spike in egress.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+    },
+};
+
+use common::{
+    runtime::UnixTimestamp,
+    sha256::Sha256,
+    value::{
+        ConvexValue,
+        Size,
+    },
+};
+use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
+use sync_types::{
+    CanonicalizedUdfPath,
+    QueryId,
+    StateModification,
+    UdfPath,
+};
+
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct SubscriptionKey {
+    udf_path: CanonicalizedUdfPath,
+    args_hash: String,
+}
+
+impl SubscriptionKey {
+    fn new(udf_path: UdfPath, args: &[JsonValue]) -> anyhow::Result<Self> {
+        let args_hash = Sha256::hash(&serde_json::to_vec(args)?).as_hex();
+        Ok(Self {
+            udf_path: udf_path.canonicalize(),
+            args_hash,
+        })
+    }
+}
+
+#[derive(Default)]
+struct SubscriptionEntry {
+    /// Number of subscriptions to the query on each connection.
+    connections: BTreeMap<u64, usize>,
+    last_update: Option<UnixTimestamp>,
+    bytes_pushed: u64,
+}
+
+/// A query with at least one subscribed connection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActiveSubscription {
+    pub udf_path: CanonicalizedUdfPath,
+    /// Hex-encoded SHA-256 of the query's JSON arguments.
+    pub args_hash: String,
+    pub num_connections: usize,
+    /// When a result was last sent to any of the connections.
+    pub last_update: Option<UnixTimestamp>,
+    /// Approximate size of all results sent for the query, summed over its
+    /// connections.
+    pub bytes_pushed: u64,
+}
+
+#[derive(Default)]
+pub struct ActiveSubscriptions {
+    next_connection_id: AtomicU64,
+    subscriptions: Mutex<BTreeMap<SubscriptionKey, SubscriptionEntry>>,
+}
+
+impl ActiveSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new sync connection. Its subscriptions are removed from the
+    /// registry when the returned handle is dropped.
+    pub fn connect(self: &Arc<Self>) -> ConnectionSubscriptions {
+        ConnectionSubscriptions {
+            registry: self.clone(),
+            connection_id: self.next_connection_id.fetch_add(1, Ordering::Relaxed),
+            queries: BTreeMap::new(),
+        }
+    }
+
+    /// All active subscriptions, with the most bytes pushed first.
+    pub fn list(&self) -> Vec<ActiveSubscription> {
+        let mut subscriptions: Vec<_> = self
+            .subscriptions
+            .lock()
+            .iter()
+            .map(|(key, entry)| ActiveSubscription {
+                udf_path: key.udf_path.clone(),
+                args_hash: key.args_hash.clone(),
+                num_connections: entry.connections.len(),
+                last_update: entry.last_update,
+                bytes_pushed: entry.bytes_pushed,
+            })
+            .collect();
+        subscriptions.sort_by(|a, b| b.bytes_pushed.cmp(&a.bytes_pushed));
+        subscriptions
+    }
+
+    fn remove(&self, connection_id: u64, key: &SubscriptionKey) {
+        let mut subscriptions = self.subscriptions.lock();
+        let Some(entry) = subscriptions.get_mut(key) else {
+            return;
+        };
+        if let Some(count) = entry.connections.get_mut(&connection_id) {
+            *count -= 1;
+            if *count == 0 {
+                entry.connections.remove(&connection_id);
+            }
+        }
+        if entry.connections.is_empty() {
+            subscriptions.remove(key);
+        }
+    }
+}
+
+/// A single sync connection's view of the registry, tracking which of its
+/// queries map to which subscription.
+pub struct ConnectionSubscriptions {
+    registry: Arc<ActiveSubscriptions>,
+    connection_id: u64,
+    queries: BTreeMap<QueryId, SubscriptionKey>,
+}
+
+impl ConnectionSubscriptions {
+    pub fn add(&mut self, query_id: QueryId, udf_path: UdfPath, args: &[JsonValue]) {
+        let key = match SubscriptionKey::new(udf_path, args) {
+            Ok(key) => key,
+            Err(e) => {
+                tracing::warn!("Failed to hash arguments for {query_id}: {e:?}");
+                return;
+            },
+        };
+        *self
+            .registry
+            .subscriptions
+            .lock()
+            .entry(key.clone())
+            .or_default()
+            .connections
+            .entry(self.connection_id)
+            .or_default() += 1;
+        if let Some(previous) = self.queries.insert(query_id, key) {
+            self.registry.remove(self.connection_id, &previous);
+        }
+    }
+
+    pub fn remove(&mut self, query_id: QueryId) {
+        if let Some(key) = self.queries.remove(&query_id) {
+            self.registry.remove(self.connection_id, &key);
+        }
+    }
+
+    /// Record that `modification` was sent to the client at `ts`.
+    pub fn record_update(
+        &self,
+        query_id: QueryId,
+        modification: &StateModification<ConvexValue>,
+        ts: UnixTimestamp,
+    ) {
+        let Some(key) = self.queries.get(&query_id) else {
+            return;
+        };
+        let bytes = match modification {
+            StateModification::QueryUpdated { value, .. } => value.size(),
+            StateModification::QueryPatched { patch, .. } => {
+                serde_json::to_vec(patch).map_or(0, |patch| patch.len())
+            },
+            StateModification::QueryFailed { error_message, .. } => error_message.len(),
+            StateModification::QueryRemoved { .. } => return,
+        };
+        if let Some(entry) = self.registry.subscriptions.lock().get_mut(key) {
+            entry.last_update = Some(ts);
+            entry.bytes_pushed += bytes as u64;
+        }
+    }
+}
+
+impl Drop for ConnectionSubscriptions {
+    fn drop(&mut self) {
+        for key in self.queries.values() {
+            self.registry.remove(self.connection_id, key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use common::{
+        runtime::UnixTimestamp,
+        value::ConvexValue,
+    };
+    use serde_json::json;
+    use sync_types::{
+        LogLinesMessage,
+        QueryId,
+        StateModification,
+    };
+
+    use super::ActiveSubscriptions;
+
+    #[test]
+    fn test_aggregates_connections() -> anyhow::Result<()> {
+        let registry = Arc::new(ActiveSubscriptions::new());
+        let mut conn1 = registry.connect();
+        let mut conn2 = registry.connect();
+        let args = vec![json!({"name": "orinoco"})];
+        conn1.add(QueryId::new(0), "sync:accountBalance".parse()?, &args);
+        conn1.add(QueryId::new(1), "sync.js:accountBalance".parse()?, &args);
+        conn2.add(QueryId::new(0), "sync:accountBalance".parse()?, &args);
+        conn2.add(QueryId::new(1), "sync:accountBalance".parse()?, &[]);
+
+        let subscriptions = registry.list();
+        assert_eq!(subscriptions.len(), 2);
+        assert_eq!(
+            subscriptions
+                .iter()
+                .map(|s| s.num_connections)
+                .sum::<usize>(),
+            3
+        );
+
+        conn1.record_update(
+            QueryId::new(0),
+            &StateModification::QueryUpdated {
+                query_id: QueryId::new(0),
+                value: ConvexValue::from(100.0),
+                log_lines: LogLinesMessage(vec![]),
+                journal: None,
+            },
+            UnixTimestamp::from_millis(1000),
+        );
+        let top = &registry.list()[0];
+        assert_eq!(top.args_hash.len(), 64);
+        assert_eq!(top.num_connections, 2);
+        assert_eq!(top.last_update, Some(UnixTimestamp::from_millis(1000)));
+        assert!(top.bytes_pushed > 0);
+
+        // A connection's subscription stays until all of its queries are removed.
+        conn1.remove(QueryId::new(0));
+        assert_eq!(registry.list()[0].num_connections, 2);
+        drop(conn1);
+        assert_eq!(registry.list()[0].num_connections, 1);
+        drop(conn2);
+        assert!(registry.list().is_empty());
+        Ok(())
+    }
+}
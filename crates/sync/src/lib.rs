@@ -4,10 +4,12 @@
 #![feature(let_chains)]
 #![feature(try_blocks)]
 
+pub mod active_subscriptions;
 mod metrics;
 mod state;
 pub mod worker;
 
+pub use active_subscriptions::ActiveSubscriptions;
 pub use worker::{
     SyncWorker,
     SyncWorkerConfig,
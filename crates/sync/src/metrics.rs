@@ -85,6 +85,38 @@ pub fn log_query_result_dedup(same_value: bool) {
     log_counter(&SYNC_QUERY_RESULT_DEDUP_TOTAL, sample);
 }
 
+register_convex_counter!(
+    SYNC_QUERY_RESULT_PATCHED_TOTAL,
+    "Number of query results sent as a patch against the previous result"
+);
+pub fn log_query_result_patched() {
+    log_counter(&SYNC_QUERY_RESULT_PATCHED_TOTAL, 1);
+}
+
+register_convex_counter!(
+    SYNC_QUERY_RESULT_PATCH_SAVED_BYTES_TOTAL,
+    "Number of bytes saved by sending query results as patches"
+);
+pub fn log_query_result_patch_saved_bytes(bytes: usize) {
+    log_counter(&SYNC_QUERY_RESULT_PATCH_SAVED_BYTES_TOTAL, bytes as u64);
+}
+
+register_convex_counter!(
+    SYNC_QUERY_UPDATE_THROTTLED_TOTAL,
+    "Number of query invalidations whose update was delayed by throttling"
+);
+pub fn log_query_update_throttled() {
+    log_counter(&SYNC_QUERY_UPDATE_THROTTLED_TOTAL, 1);
+}
+
+register_convex_counter!(
+    SYNC_IDLE_SUBSCRIPTIONS_EXPIRED_TOTAL,
+    "Number of query subscriptions dropped because their client was idle"
+);
+pub fn log_idle_subscriptions_expired(num_queries: usize) {
+    log_counter(&SYNC_IDLE_SUBSCRIPTIONS_EXPIRED_TOTAL, num_queries as u64);
+}
+
 register_convex_counter!(SYNC_EMPTY_TRANSITION_TOTAL, "Number of empty transitions");
 pub fn log_empty_transition() {
     log_counter(&SYNC_EMPTY_TRANSITION_TOTAL, 1);
@@ -12,12 +12,16 @@ use application::{
     },
 };
 use common::{
+    knobs::SYNC_QUERY_PATCH_MIN_SIZE,
     sha256::{
         Sha256,
         Sha256Digest,
     },
     types::SessionId,
-    value::ConvexValue,
+    value::{
+        ConvexValue,
+        Size,
+    },
 };
 use errors::ErrorMetadata;
 use futures::{
@@ -32,7 +36,9 @@ use futures::{
     StreamExt,
 };
 use keybroker::Identity;
+use serde_json::Value as JsonValue;
 use sync_types::{
+    patch,
     IdentityVersion,
     Query,
     QueryId,
@@ -41,6 +47,9 @@ use sync_types::{
     SerializedQueryJournal,
     StateModification,
     StateVersion,
+    INITIAL_SYNC_PROTOCOL_VERSION,
+    QUERY_PATCH_SYNC_PROTOCOL_VERSION,
+    SUBSCRIPTION_EXPIRY_SYNC_PROTOCOL_VERSION,
 };
 
 use crate::metrics;
@@ -69,6 +78,15 @@ pub struct SyncedQuery {
     ///   time.
     result_hash: Option<Result<ValueDigest, ErrorDigest>>,
 
+    /// The last successful result sent to the client, if it's large enough
+    /// that we'll send the next result as a patch against it.
+    ///
+    /// - Starts `None`: Query is newly inserted.
+    /// - `SyncState::complete_fetch` sets it to `Some` when the client supports
+    ///   patches and the new result is at least `SYNC_QUERY_PATCH_MIN_SIZE`
+    ///   bytes, and `None` otherwise.
+    last_value: Option<JsonValue>,
+
     /// Handle to the query's current invalidation future. This future completes
     /// when `self.subscription` is no longer valid and the query should be
     /// rerun.
@@ -122,6 +140,9 @@ pub struct SyncState {
     // ID for the current session. Will be None for old clients that connect
     // without specifying a session ID.
     session_id: Option<SessionId>,
+    // Version of the sync protocol the client speaks, from its `Connect`
+    // message.
+    protocol_version: u32,
     current_version: StateVersion,
     invalidation_futures:
         FuturesUnordered<BoxFuture<'static, Result<anyhow::Result<QueryId>, Aborted>>>,
@@ -131,8 +152,10 @@ pub struct SyncState {
     identity: Identity,
 
     // If this is true, it means we have invalidated but have not yet refilled
-    // some query subscription. `next_invalidated_query` blocks forever until
-    // `fill_invalidation_futures` is called to recreate the subscriptions.
+    // some query subscription, so `fill_invalidation_futures` needs to be
+    // called to recreate the subscriptions. `next_invalidated_query` still
+    // reports the other queries' invalidations in the meantime, so a query
+    // whose update is throttled doesn't hold up the rest.
     refill_needed: bool,
 
     /// Updates to the query set and identity requested by the
@@ -148,6 +171,7 @@ impl SyncState {
     pub fn new() -> Self {
         Self {
             session_id: None,
+            protocol_version: INITIAL_SYNC_PROTOCOL_VERSION,
             current_version: StateVersion::initial(),
             invalidation_futures: FuturesUnordered::new(),
             queries: BTreeMap::new(),
@@ -170,6 +194,16 @@ impl SyncState {
         self.session_id
     }
 
+    pub fn set_protocol_version(&mut self, protocol_version: u32) {
+        self.protocol_version = protocol_version;
+    }
+
+    /// Whether the client knows to expect
+    /// `ServerMessage::SubscriptionsExpired`.
+    pub fn supports_subscription_expiry(&self) -> bool {
+        self.protocol_version >= SUBSCRIPTION_EXPIRY_SYNC_PROTOCOL_VERSION
+    }
+
     /// What is the current state version?
     pub fn current_version(&self) -> StateVersion {
         self.current_version
@@ -280,7 +314,7 @@ impl SyncState {
             // never return. The layer above will select on this future and
             // receiving a new command from the client, and it'll drop this
             // future when it gets a new command.
-            if self.refill_needed || self.invalidation_futures.is_empty() {
+            if self.invalidation_futures.is_empty() {
                 future::pending().await
             }
             match self.invalidation_futures.next().await {
@@ -343,6 +377,13 @@ impl SyncState {
         newly_invalidated
     }
 
+    /// Drop every query's subscription and invalidation future, keeping their
+    /// last results. The next transition reruns all of the queries.
+    pub fn expire_subscriptions(&mut self) {
+        drop(self.take_subscriptions());
+        self.invalidation_futures = FuturesUnordered::new();
+    }
+
     /// Which queries do not have a token?
     pub fn need_fetch(&self) -> impl Iterator<Item = Query> + '_ {
         self.queries
@@ -386,6 +427,7 @@ impl SyncState {
                 query,
                 subscription: None,
                 result_hash: None,
+                last_value: None,
                 invalidation_future: None,
             };
             if self.queries.insert(query_id, sq).is_some() {
@@ -423,12 +465,51 @@ impl SyncState {
         let result = if same_result {
             None
         } else {
+            let last_value = query.last_value.take();
             let modification = match result {
-                Ok(value) => StateModification::QueryUpdated {
-                    query_id,
-                    value,
-                    log_lines: log_lines.into(),
-                    journal,
+                Ok(value) => {
+                    let supports_patches =
+                        self.protocol_version >= QUERY_PATCH_SYNC_PROTOCOL_VERSION;
+                    let value_size = value.size();
+                    if supports_patches && value_size >= *SYNC_QUERY_PATCH_MIN_SIZE {
+                        let new_value = JsonValue::from(value.clone());
+                        let patch = last_value
+                            .map(|last_value| patch::diff(&last_value, &new_value))
+                            .map(|patch| -> anyhow::Result<_> {
+                                let patch_size = serde_json::to_vec(&patch)?.len();
+                                Ok((patch, patch_size))
+                            })
+                            .transpose()?
+                            .filter(|(_, patch_size)| *patch_size < value_size);
+                        query.last_value = Some(new_value);
+                        match patch {
+                            Some((patch, patch_size)) => {
+                                metrics::log_query_result_patched();
+                                metrics::log_query_result_patch_saved_bytes(
+                                    value_size - patch_size,
+                                );
+                                StateModification::QueryPatched {
+                                    query_id,
+                                    patch,
+                                    log_lines: log_lines.into(),
+                                    journal,
+                                }
+                            },
+                            None => StateModification::QueryUpdated {
+                                query_id,
+                                value,
+                                log_lines: log_lines.into(),
+                                journal,
+                            },
+                        }
+                    } else {
+                        StateModification::QueryUpdated {
+                            query_id,
+                            value,
+                            log_lines: log_lines.into(),
+                            journal,
+                        }
+                    }
                 },
                 Err(error) => {
                     metrics::log_query_failed();
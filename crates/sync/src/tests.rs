@@ -1,5 +1,9 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::Duration,
+};
 
+use anyhow::Context;
 use application::{
     test_helpers::ApplicationTestExt,
     Application,
@@ -10,10 +14,12 @@ use common::{
         ComponentFunctionPath,
         ComponentPath,
     },
+    knobs::SYNC_QUERY_PATCH_MIN_SIZE,
     runtime::{
         shutdown_and_join,
         Runtime,
     },
+    testing::run_simulation,
     types::{
         FunctionCaller,
         MemberId,
@@ -30,7 +36,11 @@ use common::{
     RequestId,
 };
 use errors::ErrorMetadataAnyhowExt;
-use futures::channel::mpsc;
+use futures::{
+    channel::mpsc,
+    future,
+    FutureExt,
+};
 use isolate::test_helpers::TEST_SOURCE_ISOLATE_ONLY;
 use keybroker::{
     testing::TestUserIdentity,
@@ -46,21 +56,29 @@ use model::{
 };
 use must_let::must_let;
 use parking_lot::Mutex;
+use rand::Rng;
 use runtime::testing::{
     TestFutureHandle,
     TestRuntime,
 };
 use sync_types::{
+    types::ClientEvent,
     AuthenticationToken,
     ClientMessage,
+    PatchOperation,
     Query,
     QueryId,
     QuerySetModification,
     StateModification,
+    StateVersion,
+    UdfPath,
     UserIdentityAttributes,
+    INITIAL_SYNC_PROTOCOL_VERSION,
+    SYNC_PROTOCOL_VERSION,
 };
 
 use crate::{
+    active_subscriptions::ActiveSubscriptions,
     worker::{
         measurable_unbounded_channel,
         SingleFlightReceiver,
@@ -74,6 +92,7 @@ struct SyncTest {
     pub rt: TestRuntime,
     pub kb: KeyBroker,
     application: Application<TestRuntime>,
+    active_subscriptions: Arc<ActiveSubscriptions>,
 }
 
 impl SyncTest {
@@ -110,18 +129,20 @@ impl SyncTest {
             rt,
             kb,
             application,
+            active_subscriptions: Arc::new(ActiveSubscriptions::new()),
         })
     }
 
     fn new_worker(&self) -> anyhow::Result<TestSyncWorker> {
         let config = SyncWorkerConfig::default();
-        self.new_worker_with_config(config, None)
+        self.new_worker_with_config(config, None, SYNC_PROTOCOL_VERSION)
     }
 
     fn new_worker_with_config(
         &self,
         config: SyncWorkerConfig,
         max_observed_timestamp: Option<Timestamp>,
+        protocol_version: u32,
     ) -> anyhow::Result<TestSyncWorker> {
         let worker_failed = Arc::new(Mutex::new(None));
         let (client_tx, client_rx) = mpsc::unbounded();
@@ -130,12 +151,21 @@ impl SyncTest {
         let worker_failed_ = worker_failed.clone();
         let api = Arc::new(self.application.clone());
         let rt = self.rt.clone();
+        let active_subscriptions = self.active_subscriptions.clone();
         let future = async move {
             // TODO(CX-597): The panic in this future currently gets swallowed by
             // `futures::RemoteHandle`.
-            if let Err(e) = SyncWorker::new(api, rt, "".to_owned(), config, client_rx, server_tx)
-                .go()
-                .await
+            if let Err(e) = SyncWorker::new(
+                api,
+                rt,
+                "".to_owned(),
+                config,
+                active_subscriptions,
+                client_rx,
+                server_tx,
+            )
+            .go()
+            .await
             {
                 worker_failed_.lock().replace(e);
             }
@@ -148,6 +178,7 @@ impl SyncTest {
                 connection_count: 0,
                 last_close_reason: "InitialConnect".to_string(),
                 max_observed_timestamp,
+                protocol_version,
             },
             self.rt.monotonic_now(),
         ))?;
@@ -620,6 +651,32 @@ async fn test_acting_auth(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_admin_auth_not_allowed(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt).await?;
+    let config = SyncWorkerConfig {
+        allow_admin_auth: false,
+        ..SyncWorkerConfig::default()
+    };
+    let mut sync_worker = test.new_worker_with_config(config, None, SYNC_PROTOCOL_VERSION)?;
+    let admin_key = test.kb.issue_admin_key(MemberId(1));
+    sync_worker.send(ClientMessage::Authenticate {
+        token: AuthenticationToken::Admin(
+            admin_key.to_string(),
+            Some(UserIdentityAttributes::test()),
+        ),
+        base_version: 0,
+    })?;
+    let err = sync_worker.receive().await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Admin keys can't be used from this connection"),
+        "{err}"
+    );
+    sync_worker.with_worker_error(|e| assert!(e.as_ref().unwrap().is_forbidden()));
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_idempotent_mutations(rt: TestRuntime) -> anyhow::Result<()> {
     // A test that confirms that sending the same mutation twice only causes
@@ -677,6 +734,47 @@ async fn test_idempotent_mutations(rt: TestRuntime) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[convex_macro::test_runtime]
+async fn test_mutations_finish_after_client_messages_end(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt).await?;
+    let mut sync_worker = test.new_worker()?;
+    let name = ConvexValue::try_from("Alice")?;
+    sync_worker
+        .mutation(
+            "sync:initialize",
+            assert_obj!("name" => name.clone(), "balance" => 0.0),
+            0,
+        )
+        .await?;
+
+    // Close the client's messages right after sending a mutation, like the
+    // backend does when it's draining. The worker should still run it and
+    // send its result before exiting.
+    sync_worker.send(ClientMessage::Mutation {
+        request_id: 1,
+        udf_path: "sync:deposit".parse()?,
+        args: vec![assert_obj!("name" => name, "balance" => 5.0).into()],
+    })?;
+    sync_worker.tx.close_channel();
+    let mut result = None;
+    while let Some((message, _)) = sync_worker.rx.next().await {
+        if let ServerMessage::MutationResponse {
+            request_id: 1,
+            result: mutation_result,
+            ..
+        } = message
+        {
+            result = Some(mutation_result);
+        }
+    }
+    assert_eq!(
+        result.context("Worker exited without running the mutation")?,
+        Ok(ConvexValue::try_from("Alice's balance is now 5")?)
+    );
+    sync_worker.shutdown().await?;
+    Ok(())
+}
+
 #[convex_macro::test_runtime]
 async fn test_value_deduplication_success(rt: TestRuntime) -> anyhow::Result<()> {
     let test = SyncTest::new(rt).await?;
@@ -858,7 +956,8 @@ async fn test_max_observed_timestamp(rt: TestRuntime) -> anyhow::Result<()> {
     let test = SyncTest::new(rt).await?;
 
     let config = SyncWorkerConfig::default();
-    let mut sync_worker = test.new_worker_with_config(config, Some(Timestamp::MAX))?;
+    let mut sync_worker =
+        test.new_worker_with_config(config, Some(Timestamp::MAX), SYNC_PROTOCOL_VERSION)?;
     must_let!(let Err(err) = sync_worker.receive().await);
     assert!(
         format!("{err}")
@@ -868,3 +967,551 @@ async fn test_max_observed_timestamp(rt: TestRuntime) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Subscribe to `sync:listAccounts` after inserting an account large enough
+/// for the result to be patched, then insert a small account.
+async fn list_accounts_after_insert(
+    sync_worker: &mut TestSyncWorker,
+) -> anyhow::Result<Vec<StateModification<ConvexValue>>> {
+    let name = ConvexValue::try_from("a".repeat(*SYNC_QUERY_PATCH_MIN_SIZE))?;
+    sync_worker
+        .mutation(
+            "sync:initialize",
+            assert_obj!("name" => name, "balance" => 0.0),
+            0,
+        )
+        .await?;
+    must_let!(let ServerMessage::Transition { .. } = sync_worker.receive().await?);
+
+    let query = Query {
+        query_id: QueryId::new(0),
+        udf_path: "sync:listAccounts".parse()?,
+        args: vec![assert_obj!().into()],
+        journal: None,
+    };
+    sync_worker.send(ClientMessage::ModifyQuerySet {
+        base_version: 0,
+        new_version: 1,
+        modifications: vec![QuerySetModification::Add(query)],
+    })?;
+    must_let!(let ServerMessage::Transition { modifications, .. } = sync_worker.receive().await?);
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryUpdated { .. } = &modifications[0]);
+
+    sync_worker
+        .mutation(
+            "sync:initialize",
+            assert_obj!("name" => "Bob", "balance" => 0.0),
+            1,
+        )
+        .await?;
+    must_let!(let ServerMessage::Transition { modifications, .. } = sync_worker.receive().await?);
+    Ok(modifications)
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_patch(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt).await?;
+    let mut sync_worker = test.new_worker()?;
+
+    let modifications = list_accounts_after_insert(&mut sync_worker).await?;
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryPatched { query_id, patch, .. } = &modifications[0]);
+    assert_eq!(*query_id, QueryId::new(0));
+    assert_eq!(patch.len(), 1, "{patch:?}");
+    must_let!(let PatchOperation::Add { path, value } = &patch[0]);
+    assert_eq!(path, "/1");
+    assert_eq!(value["name"], "Bob");
+
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_patch_unsupported_protocol_version(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt).await?;
+    let config = SyncWorkerConfig::default();
+    let mut sync_worker =
+        test.new_worker_with_config(config, None, INITIAL_SYNC_PROTOCOL_VERSION)?;
+
+    let modifications = list_accounts_after_insert(&mut sync_worker).await?;
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryUpdated { value, .. } = &modifications[0]);
+    must_let!(let ConvexValue::Array(accounts) = value);
+    assert_eq!(accounts.len(), 2);
+
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_update_throttling(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt.clone()).await?;
+    let min_query_update_interval = Duration::from_secs(10);
+    let config = SyncWorkerConfig {
+        min_query_update_interval,
+        ..SyncWorkerConfig::default()
+    };
+    let mut sync_worker = test.new_worker_with_config(config, None, SYNC_PROTOCOL_VERSION)?;
+    let mut other_worker = test.new_worker()?;
+
+    let name = ConvexValue::try_from("Alice")?;
+    other_worker
+        .mutation(
+            "sync:initialize",
+            assert_obj!("name" => name.clone(), "balance" => 0.0),
+            0,
+        )
+        .await?;
+    must_let!(let ServerMessage::Transition { .. } = other_worker.receive().await?);
+
+    let query = Query {
+        query_id: QueryId::new(0),
+        udf_path: "sync:accountBalance".parse()?,
+        args: vec![assert_obj!("name" => name.clone()).into()],
+        journal: None,
+    };
+    let before_update = rt.monotonic_now();
+    sync_worker.send(ClientMessage::ModifyQuerySet {
+        base_version: 0,
+        new_version: 1,
+        modifications: vec![QuerySetModification::Add(query)],
+    })?;
+    must_let!(let ServerMessage::Transition { modifications, .. } = sync_worker.receive().await?);
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryUpdated { value, .. } = &modifications[0]);
+    assert_eq!(value, &ConvexValue::from(0.0));
+
+    // Deposit twice from another client right after the query was updated.
+    for request_id in 1..=2 {
+        other_worker
+            .mutation(
+                "sync:deposit",
+                assert_obj!("name" => name.clone(), "balance" => 5.0),
+                request_id,
+            )
+            .await?;
+        must_let!(let ServerMessage::Transition { .. } = other_worker.receive().await?);
+    }
+
+    // Both deposits are sent as a single update once the interval has passed.
+    must_let!(let ServerMessage::Transition { modifications, .. } = sync_worker.receive().await?);
+    assert!(rt.monotonic_now() - before_update >= min_query_update_interval);
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryUpdated { value, .. } = &modifications[0]);
+    assert_eq!(value, &ConvexValue::from(10.0));
+
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_query_update_throttling_per_query(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt.clone()).await?;
+    let min_query_update_interval = Duration::from_secs(10);
+    let config = SyncWorkerConfig {
+        min_query_update_interval,
+        ..SyncWorkerConfig::default()
+    };
+    let mut sync_worker = test.new_worker_with_config(config, None, SYNC_PROTOCOL_VERSION)?;
+    let mut other_worker = test.new_worker()?;
+
+    let alice = ConvexValue::try_from("Alice")?;
+    let bob = ConvexValue::try_from("Bob")?;
+    for (request_id, name) in [alice.clone(), bob.clone()].into_iter().enumerate() {
+        other_worker
+            .mutation(
+                "sync:initialize",
+                assert_obj!("name" => name, "balance" => 0.0),
+                request_id as u32,
+            )
+            .await?;
+        must_let!(let ServerMessage::Transition { .. } = other_worker.receive().await?);
+    }
+
+    // Add a query for each account, six seconds apart, so their updates are
+    // throttled until different times.
+    let start = rt.monotonic_now();
+    for (i, name) in [alice.clone(), bob.clone()].into_iter().enumerate() {
+        if i > 0 {
+            rt.wait(Duration::from_secs(6)).await;
+        }
+        let query = Query {
+            query_id: QueryId::new(i as u32),
+            udf_path: "sync:accountBalance".parse()?,
+            args: vec![assert_obj!("name" => name).into()],
+            journal: None,
+        };
+        sync_worker.send(ClientMessage::ModifyQuerySet {
+            base_version: i as u32,
+            new_version: i as u32 + 1,
+            modifications: vec![QuerySetModification::Add(query)],
+        })?;
+        let message = sync_worker.receive().await?;
+        must_let!(let ServerMessage::Transition { modifications, .. } = message);
+        assert_eq!(modifications.len(), 1, "{modifications:?}");
+    }
+
+    // Deposit into both accounts, invalidating both queries.
+    for (request_id, name) in [alice, bob].into_iter().enumerate() {
+        other_worker
+            .mutation(
+                "sync:deposit",
+                assert_obj!("name" => name, "balance" => 5.0),
+                request_id as u32 + 2,
+            )
+            .await?;
+        must_let!(let ServerMessage::Transition { .. } = other_worker.receive().await?);
+    }
+
+    // Alice's query is updated once its interval has passed, and the
+    // transition doesn't update Bob's query early.
+    must_let!(let ServerMessage::Transition { modifications, .. } = sync_worker.receive().await?);
+    let elapsed = rt.monotonic_now() - start;
+    assert!(elapsed >= min_query_update_interval, "{elapsed:?}");
+    assert!(
+        elapsed < min_query_update_interval + Duration::from_secs(6),
+        "{elapsed:?}"
+    );
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryUpdated { query_id, value, .. } = &modifications[0]);
+    assert_eq!(*query_id, QueryId::new(0));
+    assert_eq!(value, &ConvexValue::from(5.0));
+
+    // Bob's query is updated at its own deadline.
+    must_let!(let ServerMessage::Transition { modifications, .. } = sync_worker.receive().await?);
+    let elapsed = rt.monotonic_now() - start;
+    assert!(
+        elapsed >= min_query_update_interval + Duration::from_secs(6),
+        "{elapsed:?}"
+    );
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryUpdated { query_id, value, .. } = &modifications[0]);
+    assert_eq!(*query_id, QueryId::new(1));
+    assert_eq!(value, &ConvexValue::from(5.0));
+
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_idle_subscriptions_expire(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt.clone()).await?;
+    let config = SyncWorkerConfig {
+        idle_subscription_timeout: Duration::from_secs(10),
+        ..SyncWorkerConfig::default()
+    };
+    let mut sync_worker = test.new_worker_with_config(config, None, SYNC_PROTOCOL_VERSION)?;
+    let mut other_worker = test.new_worker()?;
+
+    let name = ConvexValue::try_from("Alice")?;
+    other_worker
+        .mutation(
+            "sync:initialize",
+            assert_obj!("name" => name.clone(), "balance" => 0.0),
+            0,
+        )
+        .await?;
+    must_let!(let ServerMessage::Transition { .. } = other_worker.receive().await?);
+
+    let query = Query {
+        query_id: QueryId::new(0),
+        udf_path: "sync:accountBalance".parse()?,
+        args: vec![assert_obj!("name" => name.clone()).into()],
+        journal: None,
+    };
+    sync_worker.send(ClientMessage::ModifyQuerySet {
+        base_version: 0,
+        new_version: 1,
+        modifications: vec![QuerySetModification::Add(query)],
+    })?;
+    must_let!(let ServerMessage::Transition { .. } = sync_worker.receive().await?);
+    must_let!(let ServerMessage::SubscriptionsExpired = sync_worker.receive().await?);
+
+    // The query isn't updated while its subscription is expired.
+    other_worker
+        .mutation(
+            "sync:deposit",
+            assert_obj!("name" => name.clone(), "balance" => 5.0),
+            1,
+        )
+        .await?;
+    must_let!(let ServerMessage::Transition { .. } = other_worker.receive().await?);
+    let received = future::select(
+        sync_worker.receive().boxed(),
+        rt.wait(Duration::from_secs(5)),
+    )
+    .await;
+    assert!(matches!(received, future::Either::Right(..)));
+    drop(received);
+
+    // The client's next message reruns the query.
+    sync_worker.send(ClientMessage::Event(ClientEvent {
+        event_type: "ResumeSubscriptions".to_string(),
+        event: serde_json::Value::Null,
+    }))?;
+    must_let!(let ServerMessage::Transition { modifications, .. } = sync_worker.receive().await?);
+    assert_eq!(modifications.len(), 1, "{modifications:?}");
+    must_let!(let StateModification::QueryUpdated { value, .. } = &modifications[0]);
+    assert_eq!(value, &ConvexValue::from(5.0));
+
+    Ok(())
+}
+
+#[convex_macro::test_runtime]
+async fn test_active_subscriptions(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt).await?;
+    let mut workers = vec![test.new_worker()?, test.new_worker()?];
+
+    let query = Query {
+        query_id: QueryId::new(0),
+        udf_path: "sync:accountBalance".parse()?,
+        args: vec![assert_obj!("name" => "Alice").into()],
+        journal: None,
+    };
+    for worker in &mut workers {
+        worker.send(ClientMessage::ModifyQuerySet {
+            base_version: 0,
+            new_version: 1,
+            modifications: vec![QuerySetModification::Add(query.clone())],
+        })?;
+        must_let!(let ServerMessage::Transition { .. } = worker.receive().await?);
+    }
+    let subscriptions = test.active_subscriptions.list();
+    assert_eq!(subscriptions.len(), 1, "{subscriptions:?}");
+    assert_eq!(
+        subscriptions[0].udf_path,
+        "sync:accountBalance".parse::<UdfPath>()?.canonicalize()
+    );
+    assert_eq!(subscriptions[0].num_connections, 2);
+    assert!(subscriptions[0].last_update.is_some());
+    assert!(subscriptions[0].bytes_pushed > 0);
+
+    workers[0].send(ClientMessage::ModifyQuerySet {
+        base_version: 1,
+        new_version: 2,
+        modifications: vec![QuerySetModification::Remove {
+            query_id: QueryId::new(0),
+        }],
+    })?;
+    must_let!(let ServerMessage::Transition { .. } = workers[0].receive().await?);
+    assert_eq!(test.active_subscriptions.list()[0].num_connections, 1);
+
+    let worker = workers.pop().unwrap();
+    worker.shutdown().await?;
+    assert!(test.active_subscriptions.list().is_empty());
+
+    Ok(())
+}
+
+const SIMULATION_SEEDS: u64 = 4;
+const SIMULATION_CLIENTS: usize = 3;
+const SIMULATION_TRANSFERS: usize = 10;
+const SIMULATION_ACCOUNTS: [&str; 2] = ["orinoco", "tizoncito"];
+const SIMULATION_TOTAL_BALANCE: f64 = 100.0;
+
+/// A client of a simulated sync worker that's subscribed to the balance of
+/// each account and checks every transition it receives.
+struct SimulatedClient {
+    rt: TestRuntime,
+    worker: TestSyncWorker,
+    version: StateVersion,
+    balances: [Option<f64>; SIMULATION_ACCOUNTS.len()],
+    next_request_id: SessionRequestSeqNumber,
+    mutations_in_flight: usize,
+    max_mutation_ts: Option<Timestamp>,
+}
+
+impl SimulatedClient {
+    fn new(test: &SyncTest) -> anyhow::Result<Self> {
+        // Query results are too small to be sent as patches, but keep every
+        // update self-contained anyway.
+        let worker = test.new_worker_with_config(
+            SyncWorkerConfig::default(),
+            None,
+            INITIAL_SYNC_PROTOCOL_VERSION,
+        )?;
+        let modifications = SIMULATION_ACCOUNTS
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                QuerySetModification::Add(Query {
+                    query_id: QueryId::new(i as u32),
+                    udf_path: "sync:accountBalance".parse().unwrap(),
+                    args: vec![assert_obj!("name" => *name).into()],
+                    journal: None,
+                })
+            })
+            .collect();
+        worker.send(ClientMessage::ModifyQuerySet {
+            base_version: 0,
+            new_version: 1,
+            modifications,
+        })?;
+        Ok(Self {
+            rt: test.rt.clone(),
+            worker,
+            version: StateVersion::initial(),
+            balances: [None; SIMULATION_ACCOUNTS.len()],
+            next_request_id: 0,
+            mutations_in_flight: 0,
+            max_mutation_ts: None,
+        })
+    }
+
+    async fn pause(&self) {
+        let pause = self
+            .rt
+            .with_rng(|rng| rng.gen_range(Duration::ZERO..=Duration::from_millis(20)));
+        self.rt.wait(pause).await;
+    }
+
+    fn send_transfer(&mut self) -> anyhow::Result<()> {
+        let (from, amount) = self
+            .rt
+            .with_rng(|rng| (rng.gen_range(0..2), rng.gen_range(1..=30) as f64));
+        self.worker.send(ClientMessage::Mutation {
+            request_id: self.next_request_id,
+            udf_path: "sync:transfer".parse()?,
+            args: vec![assert_obj!(
+                "from" => SIMULATION_ACCOUNTS[from],
+                "to" => SIMULATION_ACCOUNTS[1 - from],
+                "amount" => amount
+            )
+            .into()],
+        })?;
+        self.next_request_id += 1;
+        self.mutations_in_flight += 1;
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> anyhow::Result<()> {
+        match self.worker.receive().await? {
+            ServerMessage::Transition {
+                start_version,
+                end_version,
+                modifications,
+            } => {
+                anyhow::ensure!(
+                    start_version == self.version,
+                    "Transition from {start_version:?} but the client is at {:?}",
+                    self.version
+                );
+                anyhow::ensure!(start_version.ts <= end_version.ts);
+                for modification in modifications {
+                    let StateModification::QueryUpdated {
+                        query_id,
+                        value: ConvexValue::Float64(balance),
+                        ..
+                    } = modification
+                    else {
+                        anyhow::bail!("Unexpected modification {modification:?}");
+                    };
+                    self.balances[query_id.get_id() as usize] = Some(balance);
+                }
+                self.version = end_version;
+                // Both balances are updated in the same transition as every
+                // transfer, so their sum never changes.
+                if let [Some(a), Some(b)] = self.balances {
+                    anyhow::ensure!(
+                        a + b == SIMULATION_TOTAL_BALANCE,
+                        "Balances {a} and {b} at {:?} don't add up",
+                        end_version.ts
+                    );
+                }
+            },
+            ServerMessage::MutationResponse { result, ts, .. } => {
+                self.mutations_in_flight -= 1;
+                match result {
+                    Ok(_) => {
+                        let ts = ts.context("Successful mutation without a timestamp")?;
+                        self.max_mutation_ts = self.max_mutation_ts.max(Some(ts));
+                    },
+                    Err(e) => anyhow::ensure!(
+                        e.get_message().contains("Insufficient balance"),
+                        "Unexpected transfer error: {}",
+                        e.get_message()
+                    ),
+                }
+            },
+            ServerMessage::Ping => (),
+            message => anyhow::bail!("Unexpected message {message:?}"),
+        }
+        Ok(())
+    }
+
+    /// Send transfers, receiving messages between them at random, until they
+    /// have all been acknowledged.
+    async fn run_transfers(&mut self) -> anyhow::Result<()> {
+        for _ in 0..SIMULATION_TRANSFERS {
+            self.pause().await;
+            self.send_transfer()?;
+            while self.mutations_in_flight > 0 && self.rt.with_rng(|rng| rng.gen_bool(0.5)) {
+                self.receive().await?;
+            }
+        }
+        while self.mutations_in_flight > 0 {
+            self.receive().await?;
+        }
+        Ok(())
+    }
+
+    /// Receive transitions until the client has caught up to `ts`.
+    async fn catch_up(&mut self, ts: Timestamp) -> anyhow::Result<[f64; 2]> {
+        let mut deadline = self.rt.wait(Duration::from_secs(60));
+        loop {
+            if self.version.ts >= ts
+                && let [Some(a), Some(b)] = self.balances
+            {
+                return Ok([a, b]);
+            }
+            futures::select_biased! {
+                result = self.receive().fuse() => result?,
+                _ = deadline => {
+                    anyhow::bail!("Client at {:?} didn't catch up to {ts:?}", self.version)
+                },
+            }
+        }
+    }
+}
+
+async fn simulate_transfers(rt: TestRuntime) -> anyhow::Result<()> {
+    let test = SyncTest::new(rt).await?;
+    let mut setup = test.new_worker()?;
+    let mut initialization_ts = Timestamp::MIN;
+    for (i, name) in SIMULATION_ACCOUNTS.into_iter().enumerate() {
+        let balance = SIMULATION_TOTAL_BALANCE / SIMULATION_ACCOUNTS.len() as f64;
+        (_, initialization_ts) = setup
+            .mutation(
+                "sync:initialize",
+                assert_obj!("name" => name, "balance" => balance),
+                i as SessionRequestSeqNumber,
+            )
+            .await?;
+        must_let!(let ServerMessage::Transition { .. } = setup.receive().await?);
+    }
+    setup.shutdown().await?;
+
+    let mut clients = (0..SIMULATION_CLIENTS)
+        .map(|_| SimulatedClient::new(&test))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    future::try_join_all(clients.iter_mut().map(|client| client.run_transfers())).await?;
+
+    // Every client converges on the balances after the last transfer.
+    let final_ts = clients
+        .iter()
+        .filter_map(|client| client.max_mutation_ts)
+        .max()
+        .unwrap_or(initialization_ts);
+    let balances =
+        future::try_join_all(clients.iter_mut().map(|client| client.catch_up(final_ts))).await?;
+    anyhow::ensure!(
+        balances.iter().all(|b| *b == balances[0]),
+        "Clients didn't converge: {balances:?}"
+    );
+    for client in clients {
+        client.worker.shutdown().await?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_simulate_concurrent_transfers() -> anyhow::Result<()> {
+    run_simulation(SIMULATION_SEEDS, simulate_transfers)
+}
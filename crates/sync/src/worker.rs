@@ -1,5 +1,8 @@
 use std::{
-    collections::BTreeMap,
+    collections::{
+        BTreeMap,
+        BTreeSet,
+    },
     sync::{
         atomic::{
             AtomicUsize,
@@ -22,15 +25,22 @@ use application::{
         RedactedJsError,
         RedactedLogLines,
     },
+    QueryCacheExpiry,
     RedactedActionError,
     RedactedMutationError,
 };
 use cmd_util::env::env_config;
 use common::{
-    knobs::SYNC_MAX_SEND_TRANSITION_COUNT,
+    knobs::{
+        SYNC_IDLE_SUBSCRIPTION_TIMEOUT,
+        SYNC_MAX_SEND_TRANSITION_COUNT,
+        SYNC_QUERY_MIN_UPDATE_INTERVAL,
+        SYNC_SESSION_REVOCATION_CHECK_INTERVAL,
+    },
     minitrace_helpers::get_sampled_span,
     runtime::{
         Runtime,
+        RuntimeInstant,
         WithTimeout,
     },
     types::{
@@ -41,6 +51,7 @@ use common::{
     version::ClientVersion,
     RequestId,
 };
+use errors::ErrorMetadata;
 use futures::{
     channel::mpsc::{
         self,
@@ -52,11 +63,13 @@ use futures::{
         self,
         BoxFuture,
         Fuse,
+        Shared,
     },
     select_biased,
     stream::{
         self,
         Buffered,
+        FusedStream,
         FuturesUnordered,
     },
     Future,
@@ -65,8 +78,12 @@ use futures::{
 };
 use maplit::btreemap;
 use minitrace::prelude::*;
-use model::session_requests::types::SessionRequestIdentifier;
+use model::{
+    idempotency_keys::types::MutationIdentifier,
+    session_requests::types::SessionRequestIdentifier,
+};
 use sync_types::{
+    AuthenticationToken,
     ClientMessage,
     IdentityVersion,
     QueryId,
@@ -78,6 +95,10 @@ use sync_types::{
 };
 
 use crate::{
+    active_subscriptions::{
+        ActiveSubscriptions,
+        ConnectionSubscriptions,
+    },
     metrics::{
         self,
         connect_timer,
@@ -101,12 +122,25 @@ const SYNC_WORKER_PROCESS_TIMEOUT: Duration = Duration::from_secs(60);
 #[derive(Clone, Debug)]
 pub struct SyncWorkerConfig {
     pub client_version: ClientVersion,
+    /// Minimum time between updates sent for a single query. See
+    /// `SYNC_QUERY_MIN_UPDATE_INTERVAL`.
+    pub min_query_update_interval: Duration,
+    /// How long to keep the query subscriptions of a client that isn't
+    /// sending messages. See `SYNC_IDLE_SUBSCRIPTION_TIMEOUT`.
+    pub idle_subscription_timeout: Duration,
+    /// Whether the client may authenticate with an admin key, including to
+    /// act as a user. Connections the deployment's admin access policy
+    /// doesn't allow can't.
+    pub allow_admin_auth: bool,
 }
 
 impl Default for SyncWorkerConfig {
     fn default() -> Self {
         Self {
             client_version: ClientVersion::unknown(),
+            min_query_update_interval: *SYNC_QUERY_MIN_UPDATE_INTERVAL,
+            idle_subscription_timeout: *SYNC_IDLE_SUBSCRIPTION_TIMEOUT,
+            allow_admin_auth: true,
         }
     }
 }
@@ -211,6 +245,28 @@ pub struct SyncWorker<RT: Runtime> {
     // Has an update been scheduled for the future?
     update_scheduled: bool,
 
+    // When each query last had a modification sent to the client, used to
+    // throttle updates to `config.min_query_update_interval`.
+    query_update_times: BTreeMap<QueryId, RT::Instant>,
+    // Queries whose last result was an outdated one served from the query
+    // result cache, with when it expires. Rerunning them before then would
+    // return the same result.
+    query_cache_expiries: BTreeMap<QueryId, (RT::Instant, Shared<BoxFuture<'static, ()>>)>,
+    // Queries that were invalidated too soon after their last update, or
+    // before their cached result expires. Transitions keep their last result
+    // until they may be updated.
+    throttled_queries: BTreeSet<QueryId>,
+    // Each completes with a throttled query once it may be updated.
+    throttled_updates: FuturesUnordered<BoxFuture<'static, QueryId>>,
+
+    // This connection's queries in the deployment-wide registry of active
+    // subscriptions.
+    subscriptions: ConnectionSubscriptions,
+
+    // Whether the queries' subscriptions were dropped because the client was
+    // idle. They're rerun when the client sends its next message.
+    subscriptions_expired: bool,
+
     connect_timer: Option<StatusTimer>,
 }
 
@@ -219,6 +275,7 @@ enum QueryResult {
         result: Result<ConvexValue, RedactedJsError>,
         log_lines: RedactedLogLines,
         journal: SerializedQueryJournal,
+        cache_expiry: Option<QueryCacheExpiry>,
     },
     Refresh,
 }
@@ -237,6 +294,7 @@ impl<RT: Runtime> SyncWorker<RT> {
         rt: RT,
         host: String,
         config: SyncWorkerConfig,
+        active_subscriptions: Arc<ActiveSubscriptions>,
         rx: UnboundedReceiver<(ClientMessage, RT::Instant)>,
         tx: SingleFlightSender<RT>,
     ) -> Self {
@@ -255,6 +313,12 @@ impl<RT: Runtime> SyncWorker<RT> {
             action_futures: FuturesUnordered::new(),
             transition_future: None,
             update_scheduled: false,
+            query_update_times: BTreeMap::new(),
+            query_cache_expiries: BTreeMap::new(),
+            throttled_queries: BTreeSet::new(),
+            throttled_updates: FuturesUnordered::new(),
+            subscriptions: active_subscriptions.connect(),
+            subscriptions_expired: false,
             connect_timer: Some(connect_timer()),
         }
     }
@@ -266,22 +330,44 @@ impl<RT: Runtime> SyncWorker<RT> {
     /// Run the sync protocol worker, returning `Ok(())` on clean exit and `Err`
     /// if there's an exceptional protocol condition that should shutdown
     /// the WebSocket.
+    ///
+    /// Once the client's messages end, e.g. because the backend is draining,
+    /// the worker finishes the mutations and actions it has started and sends
+    /// their results before exiting, so they aren't aborted.
     pub async fn go(&mut self) -> anyhow::Result<()> {
         let mut ping_timeout = self.rt.wait(HEARTBEAT_INTERVAL);
+        let mut revocation_check = self.rt.wait(*SYNC_SESSION_REVOCATION_CHECK_INTERVAL);
+        let mut idle_timeout = self.idle_timeout();
         let mut pending = future::pending().boxed().fuse();
+        let mut draining = false;
 
         // Starts off as a future that is never ready, as there's no identity that may
         // expire.
         'top: loop {
+            if draining && self.mutation_futures.is_terminated() && self.action_futures.is_empty() {
+                break 'top;
+            }
             let rt = self.rt.clone();
             self.state.validate()?;
             let maybe_response = select_biased! {
                 message = self.rx.next() => {
                     let (message, received_time) = match message {
                         Some(m) => m,
-                        None => break 'top,
+                        None => {
+                            // Let queued mutations run, after which the
+                            // mutation stream ends.
+                            self.mutation_sender.close_channel();
+                            draining = true;
+                            continue 'top;
+                        },
                     };
+                    if self.subscriptions_expired {
+                        // Rerun the queries, which resubscribes them.
+                        self.subscriptions_expired = false;
+                        self.schedule_update();
+                    }
                     self.handle_message(message).await?;
+                    idle_timeout = self.idle_timeout();
                     let delay = self.rt.monotonic_now() - received_time;
                     metrics::log_process_client_message_delay(delay);
                     None
@@ -291,20 +377,25 @@ impl<RT: Runtime> SyncWorker<RT> {
                 // timestamp past a pending mutation or otherwise optimistic updates
                 // might be flaky. To do that, we need to behave differently if we
                 // have pending operation future or not.
-                result = self.mutation_futures.next().fuse() => {
-                    let message = match result {
-                        Some(m) => m?,
-                        None => panic!("mutation_futures sender dropped prematurely"),
+                result = self.mutation_futures.next() => {
+                    // The stream only ends after draining closes it.
+                    let Some(result) = result else {
+                        continue 'top;
                     };
                     self.schedule_update();
-                    Some(message)
+                    Some(result?)
                 },
                 result = self.action_futures.select_next_some() => {
                     self.schedule_update();
                     Some(result?)
                 },
                 result = self.state.next_invalidated_query().fuse() => {
-                    let _ = result?;
+                    let query_id = result?;
+                    self.schedule_update_after_invalidation(query_id);
+                    None
+                },
+                query_id = self.throttled_updates.select_next_some() => {
+                    self.throttled_queries.remove(&query_id);
                     self.schedule_update();
                     None
                 },
@@ -317,6 +408,21 @@ impl<RT: Runtime> SyncWorker<RT> {
                     // in case update_scheduled is True.
                     None
                 }
+                _ = revocation_check => {
+                    // Cut off sessions that were revoked after they
+                    // authenticated.
+                    let identity = self.state.identity(self.rt.system_time())?;
+                    self.api
+                        .ensure_session_not_revoked(self.host.as_str(), RequestId::new(), &identity)
+                        .await?;
+                    revocation_check = self.rt.wait(*SYNC_SESSION_REVOCATION_CHECK_INTERVAL);
+                    None
+                },
+                _ = idle_timeout => {
+                    let response = self.expire_idle_subscriptions();
+                    idle_timeout = self.idle_timeout();
+                    response
+                },
                 _ = ping_timeout => Some(ServerMessage::Ping {}),
             };
             // If there is a message to return to the client, send it.
@@ -371,6 +477,82 @@ impl<RT: Runtime> SyncWorker<RT> {
         Ok(())
     }
 
+    /// Completes once the client has been idle for `idle_subscription_timeout`,
+    /// or never if its subscriptions are already expired or can't be.
+    fn idle_timeout(&self) -> Fuse<BoxFuture<'static, ()>> {
+        let timeout = self.config.idle_subscription_timeout;
+        if timeout.is_zero() || self.subscriptions_expired {
+            return future::pending().boxed().fuse();
+        }
+        self.rt.wait(timeout).boxed().fuse()
+    }
+
+    /// Drop the subscriptions of an idle client, so they stop holding read
+    /// sets and rerunning queries for a client that may never look at the
+    /// results, e.g. a backgrounded tab. Returns the message telling the
+    /// client, if they were dropped.
+    fn expire_idle_subscriptions(&mut self) -> Option<ServerMessage> {
+        if !self.state.supports_subscription_expiry() || self.state.num_queries() == 0 {
+            return None;
+        }
+        // A transition in progress would resubscribe the queries it reruns,
+        // so wait for the next timeout.
+        if self.update_scheduled || self.transition_future.is_some() {
+            return None;
+        }
+        metrics::log_idle_subscriptions_expired(self.state.num_queries());
+        self.state.expire_subscriptions();
+        self.query_cache_expiries.clear();
+        self.throttled_queries.clear();
+        self.throttled_updates = FuturesUnordered::new();
+        self.subscriptions_expired = true;
+        Some(ServerMessage::SubscriptionsExpired)
+    }
+
+    /// Schedule an update for an invalidated query, waiting until
+    /// `min_query_update_interval` has passed since its last update, and
+    /// until its cached result expires if it has one. Transitions before then
+    /// keep the query's last result, so further changes in the meantime are
+    /// coalesced into the one update.
+    fn schedule_update_after_invalidation(&mut self, query_id: QueryId) {
+        // The query's subscription is still invalid after a transition that
+        // skipped it, so we hear about it again. Keep its pending deadline.
+        if self.throttled_queries.contains(&query_id) {
+            return;
+        }
+        let mut delay = match self.query_update_times.get(&query_id) {
+            Some(last_update) => self
+                .config
+                .min_query_update_interval
+                .saturating_sub(last_update.elapsed()),
+            None => Duration::ZERO,
+        };
+        let mut invalidated = None;
+        if let Some((expires_at, cache_invalidated)) = self.query_cache_expiries.get(&query_id) {
+            let now = self.rt.monotonic_now();
+            if *expires_at > now {
+                delay = delay.max(expires_at.clone() - now);
+                invalidated = Some(cache_invalidated.clone());
+            }
+        }
+        if delay.is_zero() {
+            self.schedule_update();
+            return;
+        }
+        metrics::log_query_update_throttled();
+        let wait = self.rt.wait(delay).boxed();
+        let throttled_update = match invalidated {
+            // Don't wait out the expiry if the cached result is invalidated
+            // on demand.
+            Some(invalidated) => future::select(wait, invalidated)
+                .map(move |_| query_id)
+                .boxed(),
+            None => wait.map(move |()| query_id).boxed(),
+        };
+        self.throttled_queries.insert(query_id);
+        self.throttled_updates.push(throttled_update);
+    }
+
     pub fn identity_version(&self) -> IdentityVersion {
         self.state.current_version().identity
     }
@@ -383,11 +565,13 @@ impl<RT: Runtime> SyncWorker<RT> {
                 last_close_reason,
                 max_observed_timestamp,
                 connection_count,
+                protocol_version,
             } => {
                 if let Some(timer) = self.connect_timer.take() {
                     timer.finish();
                 }
                 self.state.set_session_id(session_id);
+                self.state.set_protocol_version(protocol_version);
                 if let Some(max_observed_timestamp) = max_observed_timestamp {
                     let latest_timestamp = *self
                         .api
@@ -426,11 +610,12 @@ impl<RT: Runtime> SyncWorker<RT> {
                 args,
             } => {
                 let identity = self.state.identity(self.rt.system_time())?;
-                let mutation_identifier =
-                    self.state.session_id().map(|id| SessionRequestIdentifier {
+                let mutation_identifier = self.state.session_id().map(|id| {
+                    MutationIdentifier::Session(SessionRequestIdentifier {
                         session_id: id,
                         request_id,
-                    });
+                    })
+                });
                 let server_request_id = match self.state.session_id() {
                     Some(id) => RequestId::new_for_ws_session(id, request_id),
                     None => RequestId::new(),
@@ -554,6 +739,14 @@ impl<RT: Runtime> SyncWorker<RT> {
                 token: auth_token,
                 base_version,
             } => {
+                if !self.config.allow_admin_auth
+                    && matches!(auth_token, AuthenticationToken::Admin(..))
+                {
+                    anyhow::bail!(ErrorMetadata::forbidden(
+                        "AdminAuthNotAllowed",
+                        "Admin keys can't be used from this connection",
+                    ));
+                }
                 let identity = self
                     .api
                     .authenticate(self.host.as_str(), RequestId::new(), auth_token)
@@ -622,10 +815,13 @@ impl<RT: Runtime> SyncWorker<RT> {
         for modification in modifications {
             match modification {
                 QuerySetModification::Add(query) => {
+                    self.subscriptions
+                        .add(query.query_id, query.udf_path.clone(), &query.args);
                     self.state.insert(query)?;
                 },
                 QuerySetModification::Remove { query_id } => {
                     self.state.remove(query_id)?;
+                    self.subscriptions.remove(query_id);
                     state_modifications
                         .insert(query_id, StateModification::QueryRemoved { query_id });
                 },
@@ -644,6 +840,7 @@ impl<RT: Runtime> SyncWorker<RT> {
             let identity_ = identity.clone();
             let client_version = self.config.client_version.clone();
             let current_subscription = remaining_subscriptions.remove(&query.query_id);
+            let throttled = self.throttled_queries.contains(&query.query_id);
             let root = self.rt.with_rng(|rng| {
                 get_sampled_span(
                     "sync-worker/update-queries",
@@ -656,6 +853,11 @@ impl<RT: Runtime> SyncWorker<RT> {
             });
             let future = async move {
                 let new_subscription = match current_subscription {
+                    // Keep the throttled query's last result, and its
+                    // subscription so it's rerun once it may be updated.
+                    Some(subscription) if throttled => {
+                        return Ok((query.query_id, QueryResult::Refresh, subscription));
+                    },
                     Some(mut subscription) => {
                         if subscription.extend_validity(new_ts).await? {
                             Some(subscription)
@@ -692,6 +894,7 @@ impl<RT: Runtime> SyncWorker<RT> {
                                 result: udf_return.result,
                                 log_lines: udf_return.log_lines,
                                 journal: udf_return.journal,
+                                cache_expiry: udf_return.cache_expiry,
                             },
                             subscription,
                         )
@@ -733,13 +936,27 @@ impl<RT: Runtime> SyncWorker<RT> {
             timer,
         }: TransitionState,
     ) -> anyhow::Result<ServerMessage> {
+        let now = self.rt.monotonic_now();
         for (query_id, result, subscription) in udf_results {
             match result {
                 QueryResult::Rerun {
                     result,
                     log_lines,
                     journal,
+                    cache_expiry,
                 } => {
+                    match cache_expiry {
+                        Some(QueryCacheExpiry {
+                            expires_in,
+                            invalidated,
+                        }) => {
+                            self.query_cache_expiries
+                                .insert(query_id, (now.clone() + expires_in, invalidated));
+                        },
+                        None => {
+                            self.query_cache_expiries.remove(&query_id);
+                        },
+                    }
                     let modification = self.state.complete_fetch(
                         query_id,
                         result,
@@ -758,6 +975,24 @@ impl<RT: Runtime> SyncWorker<RT> {
             }
         }
 
+        let unix_now = self.rt.unix_timestamp();
+        for (query_id, modification) in &state_modifications {
+            self.subscriptions
+                .record_update(*query_id, modification, unix_now);
+            match modification {
+                StateModification::QueryRemoved { .. } => {
+                    self.query_update_times.remove(query_id);
+                    self.query_cache_expiries.remove(query_id);
+                    self.throttled_queries.remove(query_id);
+                },
+                StateModification::QueryUpdated { .. }
+                | StateModification::QueryFailed { .. }
+                | StateModification::QueryPatched { .. } => {
+                    self.query_update_times.insert(*query_id, now.clone());
+                },
+            }
+        }
+
         // Resubscribe for queries that don't have an active invalidation
         // future.
         self.state.fill_invalidation_futures()?;
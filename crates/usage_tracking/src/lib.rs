@@ -17,6 +17,7 @@ use common::{
     },
 };
 use events::usage::{
+    TableStorageBreakdown,
     UsageEvent,
     UsageEventLogger,
 };
@@ -39,6 +40,16 @@ impl UsageCounter {
     pub fn new(usage_logger: Arc<dyn UsageEventLogger>) -> Self {
         Self { usage_logger }
     }
+
+    /// Flush buffered usage events.
+    pub async fn shutdown(&self) -> anyhow::Result<()> {
+        self.usage_logger.shutdown().await
+    }
+
+    pub fn track_table_storage_snapshot(&self, tables: Vec<TableStorageBreakdown>) {
+        self.usage_logger
+            .record(vec![UsageEvent::TableStorageSnapshot { tables }]);
+    }
 }
 
 pub enum CallType {
@@ -226,7 +226,8 @@ impl InternalDocumentId {
 
 /// An internal ID serialized to 16 bytes.
 ///
-/// 14 bytes of randomness followed by the day encoded in 2 bytes.
+/// 14 bytes of randomness followed by the day encoded in 2 bytes, unless the
+/// table's schema picks another `IdStrategy`.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(any(test, feature = "testing"), derive(proptest_derive::Arbitrary))]
 pub struct InternalId(pub [u8; 16]);
@@ -107,6 +107,10 @@ impl ConvexValue {
                     value.into()
                 }
             },
+            ConvexValue::Decimal(value) => JsonValue::String(value.to_string()),
+            ConvexValue::GeoPoint(value) => {
+                json!({ "latitude": value.latitude(), "longitude": value.longitude() })
+            },
             ConvexValue::Boolean(value) => JsonValue::Bool(value),
             ConvexValue::String(value) => JsonValue::String(value.to_string()),
             ConvexValue::Bytes(value) => {
@@ -132,7 +136,10 @@ mod tests {
     use proptest::prelude::*;
 
     use super::*;
-    use crate::ExcludeSetsAndMaps;
+    use crate::{
+        ExcludeSetsAndMaps,
+        GeoPoint,
+    };
 
     proptest! {
         #![proptest_config(
@@ -165,4 +172,22 @@ mod tests {
         let value = ConvexValue::Int64(42);
         assert_eq!(value.export_clean(), JsonValue::String("42".to_string()));
     }
+
+    #[test]
+    fn export_of_a_simple_decimal() {
+        let value = ConvexValue::Decimal("1234.50".parse().unwrap());
+        assert_eq!(
+            value.export_clean(),
+            JsonValue::String("1234.5".to_string())
+        );
+    }
+
+    #[test]
+    fn export_of_a_simple_geo_point() {
+        let value = ConvexValue::GeoPoint(GeoPoint::new(51.5, -0.125).unwrap());
+        assert_eq!(
+            value.export_clean(),
+            json!({ "latitude": 51.5, "longitude": -0.125 })
+        );
+    }
 }
@@ -0,0 +1,391 @@
+//! Regions on the Earth's surface, and the cells of the [`GeoPoint`]s that may
+//! fall within them.
+
+use std::{
+    f64::consts::FRAC_PI_2,
+    ops::RangeInclusive,
+};
+
+use sync_types::geo::{
+    interleave,
+    quantize,
+    EARTH_RADIUS_METERS,
+};
+
+use crate::GeoPoint;
+
+/// Most cells used to cover a region. Each cell becomes one range scan of a
+/// geo index, so more cells read fewer points outside of the region at the
+/// cost of more scans.
+pub const MAX_COVERING_CELLS: usize = 32;
+
+/// Slack added to the boxes around circles so that rounding never excludes a
+/// point on the edge of the circle.
+const MARGIN_DEGREES: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GeoRegion {
+    /// Points within `meters` of `center`, measured along the Earth's surface.
+    Radius { center: GeoPoint, meters: f64 },
+    /// Points within a box of latitudes and longitudes. The box crosses the
+    /// antimeridian when `south_west` is further east than `north_east`.
+    BoundingBox {
+        south_west: GeoPoint,
+        north_east: GeoPoint,
+    },
+}
+
+impl GeoRegion {
+    pub fn radius(center: GeoPoint, meters: f64) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            meters.is_finite() && meters >= 0.0,
+            "Radius must be a nonnegative number of meters, got {meters}"
+        );
+        Ok(Self::Radius { center, meters })
+    }
+
+    pub fn bounding_box(south_west: GeoPoint, north_east: GeoPoint) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            south_west.latitude() <= north_east.latitude(),
+            "Bounding box's south west corner {south_west} is north of its north east corner \
+             {north_east}"
+        );
+        Ok(Self::BoundingBox {
+            south_west,
+            north_east,
+        })
+    }
+
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        match self {
+            Self::Radius { center, meters } => center.distance_meters(point) <= *meters,
+            Self::BoundingBox {
+                south_west,
+                north_east,
+            } => {
+                let (west, east) = (south_west.longitude(), north_east.longitude());
+                let longitude = point.longitude();
+                let within_longitude = if west <= east {
+                    west <= longitude && longitude <= east
+                } else {
+                    west <= longitude || longitude <= east
+                };
+                within_longitude
+                    && south_west.latitude() <= point.latitude()
+                    && point.latitude() <= north_east.latitude()
+            },
+        }
+    }
+
+    /// Sorted, disjoint ranges of [`GeoPoint::cell`]s that include the cell of
+    /// every point in the region. They may include cells of points outside
+    /// of the region too, so callers still need to check [`Self::contains`].
+    pub fn cell_ranges(&self) -> Vec<RangeInclusive<u64>> {
+        let boxes = self.boxes();
+        let budget = MAX_COVERING_CELLS / boxes.len();
+        let mut ranges: Vec<_> = boxes
+            .into_iter()
+            .flat_map(|b| cover(&b, budget))
+            .map(|cell| cell.range())
+            .collect();
+        ranges.sort_by_key(|r| *r.start());
+        let mut merged: Vec<RangeInclusive<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            if let Some(last) = merged.last_mut()
+                && last.end().saturating_add(1) >= *range.start()
+            {
+                *last = *last.start()..=*last.end().max(range.end());
+                continue;
+            }
+            merged.push(range);
+        }
+        merged
+    }
+
+    /// Boxes of latitudes and longitudes that together contain the region,
+    /// none of which cross the antimeridian.
+    fn boxes(&self) -> Vec<QuantizedBox> {
+        let (south, west, north, east) = match self {
+            Self::Radius { center, meters } => {
+                let angle = meters / EARTH_RADIUS_METERS;
+                let margin = angle.to_degrees() + MARGIN_DEGREES;
+                let south = (center.latitude() - margin).max(-90.0);
+                let north = (center.latitude() + margin).min(90.0);
+                // A circle that reaches a pole includes every longitude near it.
+                if south <= -90.0 || north >= 90.0 || angle >= FRAC_PI_2 {
+                    return vec![QuantizedBox::new(south, -180.0, north, 180.0)];
+                }
+                let longitude_margin = (angle.sin() / center.latitude().to_radians().cos())
+                    .min(1.0)
+                    .asin()
+                    .to_degrees()
+                    + MARGIN_DEGREES;
+                (
+                    south,
+                    center.longitude() - longitude_margin,
+                    north,
+                    center.longitude() + longitude_margin,
+                )
+            },
+            Self::BoundingBox {
+                south_west,
+                north_east,
+            } => {
+                let (west, east) = (south_west.longitude(), north_east.longitude());
+                (
+                    south_west.latitude(),
+                    west,
+                    north_east.latitude(),
+                    if west <= east { east } else { east + 360.0 },
+                )
+            },
+        };
+        if east - west >= 360.0 {
+            vec![QuantizedBox::new(south, -180.0, north, 180.0)]
+        } else if west < -180.0 {
+            vec![
+                QuantizedBox::new(south, west + 360.0, north, 180.0),
+                QuantizedBox::new(south, -180.0, north, east),
+            ]
+        } else if east > 180.0 {
+            vec![
+                QuantizedBox::new(south, west, north, 180.0),
+                QuantizedBox::new(south, -180.0, north, east - 360.0),
+            ]
+        } else {
+            vec![QuantizedBox::new(south, west, north, east)]
+        }
+    }
+}
+
+/// A box in the quantized coordinates used by [`GeoPoint::cell`].
+struct QuantizedBox {
+    x: RangeInclusive<u32>,
+    y: RangeInclusive<u32>,
+}
+
+impl QuantizedBox {
+    fn new(south: f64, west: f64, north: f64, east: f64) -> Self {
+        Self {
+            x: quantize(west, -180.0, 360.0)..=quantize(east, -180.0, 360.0),
+            y: quantize(south, -90.0, 180.0)..=quantize(north, -90.0, 180.0),
+        }
+    }
+}
+
+/// A cell of the quadtree over quantized coordinates: the cell at `level`
+/// spans `2^(32 - level)` values of each coordinate, starting at `x` and `y`.
+#[derive(Clone, Copy)]
+struct Cell {
+    level: u32,
+    x: u32,
+    y: u32,
+}
+
+impl Cell {
+    fn last(&self) -> (u32, u32) {
+        let extent = (u32::MAX as u64 >> self.level) as u32;
+        (self.x + extent, self.y + extent)
+    }
+
+    fn range(&self) -> RangeInclusive<u64> {
+        let (x, y) = self.last();
+        interleave(self.x, self.y)..=interleave(x, y)
+    }
+
+    fn within(&self, b: &QuantizedBox) -> bool {
+        let (x, y) = self.last();
+        b.x.contains(&self.x) && b.x.contains(&x) && b.y.contains(&self.y) && b.y.contains(&y)
+    }
+
+    fn intersects(&self, b: &QuantizedBox) -> bool {
+        let (x, y) = self.last();
+        self.x <= *b.x.end() && x >= *b.x.start() && self.y <= *b.y.end() && y >= *b.y.start()
+    }
+
+    fn children(&self) -> impl Iterator<Item = Cell> + '_ {
+        let half = 1u32 << (31 - self.level);
+        [(0, 0), (0, half), (half, 0), (half, half)]
+            .into_iter()
+            .map(move |(dx, dy)| Cell {
+                level: self.level + 1,
+                x: self.x + dx,
+                y: self.y + dy,
+            })
+    }
+}
+
+/// Cover `b` with at most `max_cells` quadtree cells (or one cell, if
+/// `max_cells` is zero), refining the cells that straddle the edge of the box
+/// one level at a time while the budget allows.
+fn cover(b: &QuantizedBox, max_cells: usize) -> Vec<Cell> {
+    let mut covering = vec![];
+    let mut frontier = vec![Cell {
+        level: 0,
+        x: 0,
+        y: 0,
+    }];
+    while !frontier.is_empty() {
+        let mut next = vec![];
+        let mut within = vec![];
+        for cell in &frontier {
+            if cell.level == 32 {
+                within.push(*cell);
+                continue;
+            }
+            for child in cell.children().filter(|c| c.intersects(b)) {
+                if child.within(b) {
+                    within.push(child);
+                } else {
+                    next.push(child);
+                }
+            }
+        }
+        if covering.len() + within.len() + next.len() > max_cells {
+            covering.extend(frontier);
+            break;
+        }
+        covering.extend(within);
+        frontier = next;
+    }
+    covering
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl proptest::arbitrary::Arbitrary for GeoRegion {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+        prop_oneof![
+            (any::<GeoPoint>(), 0.0..=2.5e7f64).prop_map(|(center, meters)| {
+                GeoRegion::radius(center, meters).expect("Generated an invalid radius")
+            }),
+            (any::<GeoPoint>(), any::<GeoPoint>()).prop_map(|(a, b)| {
+                let south_west = GeoPoint::new(a.latitude().min(b.latitude()), a.longitude())
+                    .expect("Generated an invalid point");
+                let north_east = GeoPoint::new(a.latitude().max(b.latitude()), b.longitude())
+                    .expect("Generated an invalid point");
+                GeoRegion::bounding_box(south_west, north_east)
+                    .expect("Generated an invalid bounding box")
+            }),
+        ]
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cmd_util::env::env_config;
+    use proptest::prelude::*;
+
+    use super::{
+        GeoRegion,
+        MAX_COVERING_CELLS,
+    };
+    use crate::GeoPoint;
+
+    fn point(latitude: f64, longitude: f64) -> GeoPoint {
+        GeoPoint::new(latitude, longitude).unwrap()
+    }
+
+    fn in_cell_ranges(region: &GeoRegion, p: &GeoPoint) -> bool {
+        region
+            .cell_ranges()
+            .iter()
+            .any(|range| range.contains(&p.cell()))
+    }
+
+    #[test]
+    fn test_radius() -> anyhow::Result<()> {
+        let london = point(51.5074, -0.1278);
+        let paris = point(48.8566, 2.3522);
+        let region = GeoRegion::radius(london, 350_000.0)?;
+        assert!(region.contains(&paris));
+        assert!(in_cell_ranges(&region, &paris));
+        let region = GeoRegion::radius(london, 340_000.0)?;
+        assert!(!region.contains(&paris));
+
+        assert!(GeoRegion::radius(london, -1.0).is_err());
+        assert!(GeoRegion::radius(london, f64::INFINITY).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bounding_box() -> anyhow::Result<()> {
+        let region = GeoRegion::bounding_box(point(-10.0, -10.0), point(10.0, 10.0))?;
+        assert!(region.contains(&point(0.0, 0.0)));
+        assert!(region.contains(&point(10.0, -10.0)));
+        assert!(!region.contains(&point(0.0, 11.0)));
+        assert!(!region.contains(&point(-11.0, 0.0)));
+
+        // Crossing the antimeridian.
+        let region = GeoRegion::bounding_box(point(-10.0, 170.0), point(10.0, -170.0))?;
+        assert!(region.contains(&point(0.0, 180.0)));
+        assert!(region.contains(&point(0.0, -175.0)));
+        assert!(!region.contains(&point(0.0, 0.0)));
+        assert!(in_cell_ranges(&region, &point(0.0, 175.0)));
+        assert!(in_cell_ranges(&region, &point(0.0, -175.0)));
+
+        assert!(GeoRegion::bounding_box(point(10.0, 0.0), point(-10.0, 0.0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_covering_is_tight() -> anyhow::Result<()> {
+        let region = GeoRegion::radius(point(37.7749, -122.4194), 1_000.0)?;
+        let ranges = region.cell_ranges();
+        let covered: u128 = ranges
+            .iter()
+            .map(|r| (r.end() - r.start()) as u128 + 1)
+            .sum();
+        // A few kilometers square is a tiny fraction of the globe.
+        assert!(covered < (u64::MAX >> 20) as u128, "{ranges:?}");
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig {
+            cases: 256 * env_config("CONVEX_PROPTEST_MULTIPLIER", 1),
+            failure_persistence: None,
+            ..ProptestConfig::default()
+        })]
+
+        #[test]
+        fn test_covering_includes_region(region in any::<GeoRegion>(), p in any::<GeoPoint>()) {
+            let ranges = region.cell_ranges();
+            prop_assert!(ranges.len() <= MAX_COVERING_CELLS);
+            for pair in ranges.windows(2) {
+                prop_assert!(pair[0].end() < pair[1].start());
+            }
+            if region.contains(&p) {
+                prop_assert!(in_cell_ranges(&region, &p));
+            }
+        }
+
+        #[test]
+        fn test_covering_includes_nearby_points(
+            center in any::<GeoPoint>(),
+            meters in 0.0..1e6f64,
+            bearing in 0.0..360.0f64,
+            fraction in 0.0..=1.0f64,
+        ) {
+            // Random points inside of the circle, since random points on the globe
+            // rarely are.
+            let region = GeoRegion::radius(center, meters).unwrap();
+            let angle = meters * fraction / sync_types::geo::EARTH_RADIUS_METERS;
+            let (lat, bearing) = (center.latitude().to_radians(), bearing.to_radians());
+            let latitude =
+                (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+            let longitude = center.longitude().to_radians()
+                + (bearing.sin() * angle.sin() * lat.cos())
+                    .atan2(angle.cos() - lat.sin() * latitude.sin());
+            let longitude = (longitude.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+            let p = GeoPoint::new(latitude.to_degrees().clamp(-90.0, 90.0), longitude).unwrap();
+            if region.contains(&p) {
+                prop_assert!(in_cell_ranges(&region, &p), "{p} not covered by {region:?}");
+            }
+        }
+    }
+}
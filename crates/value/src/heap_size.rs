@@ -36,9 +36,11 @@ use proptest::{
 use serde_json::Value as JsonValue;
 use sync_types::{
     CanonicalizedUdfPath,
+    Decimal,
     ErrorPayload,
     FunctionName,
     LogLinesMessage,
+    PatchOperation,
     ServerMessage,
     SessionId,
     StateModification,
@@ -443,6 +445,12 @@ impl HeapSize for u8 {
     }
 }
 
+impl HeapSize for Decimal {
+    fn heap_size(&self) -> usize {
+        self.digits().len()
+    }
+}
+
 impl HeapSize for bytes::Bytes {
     fn heap_size(&self) -> usize {
         self.len()
@@ -742,6 +750,17 @@ impl HeapSize for JsonValue {
     }
 }
 
+impl HeapSize for PatchOperation {
+    fn heap_size(&self) -> usize {
+        match self {
+            PatchOperation::Add { path, value } | PatchOperation::Replace { path, value } => {
+                path.heap_size() + value.heap_size()
+            },
+            PatchOperation::Remove { path } => path.heap_size(),
+        }
+    }
+}
+
 impl HeapSize for LogLinesMessage {
     fn heap_size(&self) -> usize {
         estimate_vec_size(&self.0)
@@ -779,6 +798,7 @@ impl<V: HeapSize> HeapSize for ServerMessage<V> {
             } => error_message.heap_size() + base_version.heap_size(),
             ServerMessage::FatalError { error_message } => error_message.heap_size(),
             ServerMessage::Ping => 0,
+            ServerMessage::SubscriptionsExpired => 0,
         }
     }
 }
@@ -804,6 +824,12 @@ impl<V: HeapSize> HeapSize for StateModification<V> {
                     + log_lines.heap_size()
                     + journal.heap_size()
             },
+            StateModification::QueryPatched {
+                query_id: _,
+                patch,
+                log_lines,
+                journal,
+            } => estimate_vec_size(patch) + log_lines.heap_size() + journal.heap_size(),
             StateModification::QueryRemoved { query_id: _ } => 0,
         }
     }
@@ -6,7 +6,10 @@
 //! 2) Int64 integers are encoded as their little endian representation in
 //!    base64: {"$integer": "..."}.
 //! 3) Blobs are encoded as base64: {"$binary": "..."}.
-//! 4) Objects are not allowed to have keys starting with "$".
+//! 4) Decimals are encoded as their normalized string: {"$decimal": "..."}.
+//! 5) Geo points are encoded as an object with their coordinates in degrees:
+//!    {"$geoPoint": {"latitude": ..., "longitude": ...}}.
+//! 6) Objects are not allowed to have keys starting with "$".
 
 pub mod bytes;
 pub mod float;
@@ -31,6 +34,7 @@ use anyhow::{
     Error,
     Result,
 };
+use serde::Deserialize;
 use serde_json::{
     json,
     Value as JsonValue,
@@ -47,8 +51,16 @@ use crate::{
     object::ConvexObject,
     ConvexArray,
     ConvexValue,
+    GeoPoint,
 };
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonGeoPoint {
+    latitude: f64,
+    longitude: f64,
+}
+
 impl From<ConvexValue> for JsonValue {
     fn from(value: ConvexValue) -> Self {
         match value {
@@ -66,6 +78,10 @@ impl From<ConvexValue> for JsonValue {
                     json!(n)
                 }
             },
+            ConvexValue::Decimal(d) => json!({ "$decimal": d.to_string() }),
+            ConvexValue::GeoPoint(p) => json!({
+                "$geoPoint": { "latitude": p.latitude(), "longitude": p.longitude() },
+            }),
             ConvexValue::Boolean(b) => json!(b),
             ConvexValue::String(s) => json!(String::from(s)),
             ConvexValue::Bytes(b) => json!({ "$bytes": JsonBytes::encode(&b) }),
@@ -139,6 +155,14 @@ impl TryFrom<JsonValue> for ConvexValue {
                             }
                             Self::from(n)
                         },
+                        "$decimal" => {
+                            let d: String = serde_json::from_value(value)?;
+                            Self::Decimal(d.parse()?)
+                        },
+                        "$geoPoint" => {
+                            let p: JsonGeoPoint = serde_json::from_value(value)?;
+                            Self::GeoPoint(GeoPoint::new(p.latitude, p.longitude)?)
+                        },
                         "$set" => {
                             metrics::log_deserialized_set();
                             let items = match value {
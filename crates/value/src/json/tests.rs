@@ -1,6 +1,9 @@
 use serde_json::json;
 
-use crate::ConvexValue;
+use crate::{
+    ConvexValue,
+    GeoPoint,
+};
 
 #[test]
 fn test_duplicates() {
@@ -30,6 +33,30 @@ fn test_unrecognized_system_key() {
     );
 }
 
+#[test]
+fn test_decimal() -> anyhow::Result<()> {
+    let value = ConvexValue::try_from(json!({"$decimal": "0012.50"}))?;
+    assert_eq!(value, ConvexValue::Decimal("12.5".parse()?));
+    assert_eq!(serde_json::Value::from(value), json!({"$decimal": "12.5"}));
+
+    assert!(ConvexValue::try_from(json!({"$decimal": "12.5.0"})).is_err());
+    assert!(ConvexValue::try_from(json!({"$decimal": 12.5})).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_geo_point() -> anyhow::Result<()> {
+    let json = json!({"$geoPoint": {"latitude": 51.5, "longitude": -0.125}});
+    let value = ConvexValue::try_from(json.clone())?;
+    assert_eq!(value, ConvexValue::GeoPoint(GeoPoint::new(51.5, -0.125)?));
+    assert_eq!(serde_json::Value::from(value), json);
+
+    assert!(ConvexValue::try_from(json!({"$geoPoint": {"latitude": 91, "longitude": 0}})).is_err());
+    assert!(ConvexValue::try_from(json!({"$geoPoint": {"latitude": 0}})).is_err());
+    assert!(ConvexValue::try_from(json!({"$geoPoint": [0, 0]})).is_err());
+    Ok(())
+}
+
 mod json_serialize_roundtrip {
     use proptest::prelude::*;
     use serde_json::Value as JsonValue;
@@ -17,6 +17,7 @@ mod document_id;
 pub mod export;
 mod field_name;
 mod field_path;
+pub mod geo;
 pub mod id_v6;
 mod json;
 mod map;
@@ -61,7 +62,14 @@ use anyhow::{
     Error,
 };
 use heap_size::HeapSize;
-pub use sync_types::identifier;
+pub use sync_types::{
+    decimal::{
+        Decimal,
+        MAX_DECIMAL_DIGITS,
+    },
+    geo::GeoPoint,
+    identifier,
+};
 
 pub use crate::{
     array::ConvexArray,
@@ -151,6 +159,12 @@ pub enum ConvexValue {
     /// and subnormal numbers supported.
     Float64(f64),
 
+    /// Arbitrary-precision decimal number.
+    Decimal(Decimal),
+
+    /// Point on the Earth's surface, in degrees of latitude and longitude.
+    GeoPoint(GeoPoint),
+
     /// Boolean value.
     Boolean(bool),
 
@@ -184,6 +198,8 @@ impl ConvexValue {
             ConvexValue::Null => "Null",
             ConvexValue::Int64(_) => "Int64",
             ConvexValue::Float64(_) => "Float64",
+            ConvexValue::Decimal(_) => "Decimal",
+            ConvexValue::GeoPoint(_) => "GeoPoint",
             ConvexValue::Boolean(_) => "Boolean",
             ConvexValue::String(_) => "String",
             ConvexValue::Bytes(_) => "Bytes",
@@ -242,6 +258,18 @@ impl From<f64> for ConvexValue {
     }
 }
 
+impl From<Decimal> for ConvexValue {
+    fn from(d: Decimal) -> Self {
+        Self::Decimal(d)
+    }
+}
+
+impl From<GeoPoint> for ConvexValue {
+    fn from(p: GeoPoint) -> Self {
+        Self::GeoPoint(p)
+    }
+}
+
 impl From<bool> for ConvexValue {
     fn from(i: bool) -> Self {
         Self::Boolean(i)
@@ -337,6 +365,28 @@ impl TryFrom<ConvexValue> for i64 {
     }
 }
 
+impl TryFrom<ConvexValue> for Decimal {
+    type Error = Error;
+
+    fn try_from(v: ConvexValue) -> anyhow::Result<Self> {
+        match v {
+            ConvexValue::Decimal(d) => Ok(d),
+            _ => bail!("Value must be a decimal"),
+        }
+    }
+}
+
+impl TryFrom<ConvexValue> for GeoPoint {
+    type Error = Error;
+
+    fn try_from(v: ConvexValue) -> anyhow::Result<Self> {
+        match v {
+            ConvexValue::GeoPoint(p) => Ok(p),
+            _ => bail!("Value must be a geo point"),
+        }
+    }
+}
+
 impl TryFrom<ConvexValue> for ConvexString {
     type Error = Error;
 
@@ -384,6 +434,8 @@ impl Display for ConvexValue {
             ConvexValue::Null => write!(f, "null"),
             ConvexValue::Int64(n) => write!(f, "{}", n),
             ConvexValue::Float64(n) => write!(f, "{:?}", n),
+            ConvexValue::Decimal(d) => write!(f, "decimal({d})"),
+            ConvexValue::GeoPoint(p) => write!(f, "geoPoint{p}"),
             ConvexValue::Boolean(b) => write!(f, "{:?}", b),
             ConvexValue::String(s) => write!(f, "{:?}", s),
             ConvexValue::Bytes(b) => write!(f, "{}", b),
@@ -401,6 +453,8 @@ impl Size for ConvexValue {
             ConvexValue::Null => 1,
             ConvexValue::Int64(_) => 1 + 8,
             ConvexValue::Float64(_) => 1 + 8,
+            ConvexValue::Decimal(d) => d.size(),
+            ConvexValue::GeoPoint(_) => 1 + 8 + 8,
             ConvexValue::Boolean(_) => 1,
             ConvexValue::String(s) => s.size(),
             ConvexValue::Bytes(b) => b.size(),
@@ -416,6 +470,8 @@ impl Size for ConvexValue {
             ConvexValue::Null => 0,
             ConvexValue::Int64(_) => 0,
             ConvexValue::Float64(_) => 0,
+            ConvexValue::Decimal(_) => 0,
+            ConvexValue::GeoPoint(_) => 0,
             ConvexValue::Boolean(_) => 0,
             ConvexValue::String(_) => 0,
             ConvexValue::Bytes(_) => 0,
@@ -433,6 +489,8 @@ impl HeapSize for ConvexValue {
             ConvexValue::Null => 0,
             ConvexValue::Int64(_) => 0,
             ConvexValue::Float64(_) => 0,
+            ConvexValue::Decimal(d) => d.heap_size(),
+            ConvexValue::GeoPoint(_) => 0,
             ConvexValue::Boolean(_) => 0,
             ConvexValue::String(s) => s.heap_size(),
             ConvexValue::Bytes(b) => b.heap_size(),
@@ -511,6 +569,15 @@ pub mod encode_for_hash {
                     w.write_u8(11)?;
                     o.encode_for_hash(w)?;
                 },
+                ConvexValue::Decimal(d) => {
+                    w.write_u8(12)?;
+                    write_escaped_bytes(d.to_string().as_bytes(), w)?;
+                },
+                ConvexValue::GeoPoint(p) => {
+                    w.write_u8(13)?;
+                    write_escaped_bytes(&p.latitude().to_le_bytes(), w)?;
+                    write_escaped_bytes(&p.longitude().to_le_bytes(), w)?;
+                },
             }
             Ok(())
         }
@@ -589,6 +656,8 @@ pub mod proptest {
         bytes::ConvexBytes,
         string::ConvexString,
         ConvexValue,
+        Decimal,
+        GeoPoint,
     };
     use crate::field_name::FieldName;
 
@@ -647,6 +716,8 @@ pub mod proptest {
             1 => any::<i64>().prop_map(ConvexValue::from),
             1 => (prop::num::f64::ANY | prop::num::f64::SIGNALING_NAN)
                 .prop_map(ConvexValue::from),
+            1 => any::<Decimal>().prop_map(ConvexValue::from),
+            1 => any::<GeoPoint>().prop_map(ConvexValue::from),
             1 => any::<bool>().prop_map(ConvexValue::from),
             1 => any::<ConvexString>().prop_filter_map("String ID", |s| match DeveloperDocumentId::decode(&s) {
                 Ok(_) => None,
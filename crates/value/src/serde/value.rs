@@ -27,6 +27,10 @@ impl Serialize for ConvexValue {
             ConvexValue::Null => serializer.serialize_unit(),
             ConvexValue::Int64(n) => serializer.serialize_i64(*n),
             ConvexValue::Float64(n) => serializer.serialize_f64(*n),
+            ConvexValue::Decimal(d) => serializer.collect_str(d),
+            ConvexValue::GeoPoint(p) => {
+                serializer.collect_map([("latitude", p.latitude()), ("longitude", p.longitude())])
+            },
             ConvexValue::Boolean(b) => serializer.serialize_bool(*b),
             ConvexValue::String(s) => serializer.serialize_str(s),
             ConvexValue::Bytes(b) => serializer.serialize_bytes(b),
@@ -4,6 +4,8 @@ use humansize::{
     BINARY,
 };
 
+use crate::Decimal;
+
 pub const MAX_SIZE: usize = 1 << 25; // 32 MB
 pub const MAX_USER_SIZE: usize = 1 << 20; // 1MB
 pub const MAX_NESTING: usize = 64;
@@ -23,6 +25,17 @@ pub trait Size {
     fn nesting(&self) -> usize;
 }
 
+impl Size for Decimal {
+    fn size(&self) -> usize {
+        // Tag, sign, exponent, digits and terminator, like its sort key.
+        1 + 1 + 4 + self.digits().len() + 1
+    }
+
+    fn nesting(&self) -> usize {
+        0
+    }
+}
+
 pub fn check_system_size(size: usize) -> anyhow::Result<()> {
     if size > MAX_SIZE {
         // TODO CX-4516 - differentiate this from the check_user_size
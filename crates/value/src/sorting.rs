@@ -18,12 +18,20 @@
 //! notes] for an explanation of the algorithm.
 //! 5) Compound types, like arrays, are stored sequentially, with a null
 //! terminator at the end.
+//! 6) Decimals are stored as a sign byte, then for nonzero decimals their
+//! exponent and their digits followed by a terminator, with the bits of both
+//! flipped for negative decimals so larger magnitudes sort first. Decimals were
+//! added after the other types, so they sort after objects.
+//! 7) Geo points are stored as their cell on a Z-order curve, followed by their
+//! latitude and longitude encoded like floats. So all of the points within a
+//! quadtree cell are contiguous in an index, which is what geo queries scan.
 use std::{
     cmp::Ordering,
     io::{
         self,
         Write,
     },
+    ops::RangeInclusive,
 };
 
 use byteorder::{
@@ -31,7 +39,11 @@ use byteorder::{
     WriteBytesExt,
 };
 
-use crate::ConvexValue;
+use crate::{
+    ConvexValue,
+    Decimal,
+    GeoPoint,
+};
 
 const UNDEFINED_TAG: u8 = 0x1;
 
@@ -68,6 +80,12 @@ const ARRAY_TAG: u8 = 0x12;
 const SET_TAG: u8 = 0x13;
 const MAP_TAG: u8 = 0x14;
 const OBJECT_TAG: u8 = 0x15;
+const DECIMAL_TAG: u8 = 0x16;
+const GEO_POINT_TAG: u8 = 0x17;
+
+const NEGATIVE_DECIMAL: u8 = 0x1;
+const ZERO_DECIMAL: u8 = 0x2;
+const POSITIVE_DECIMAL: u8 = 0x3;
 
 pub const TERMINATOR_BYTE: u8 = 0x0;
 const ESCAPE_BYTE: u8 = 0xFF;
@@ -112,6 +130,64 @@ fn write_tagged_int<W: Write>(n: i64, writer: &mut W) -> io::Result<()> {
     Ok(())
 }
 
+fn write_decimal<W: Write>(d: &Decimal, writer: &mut W) -> io::Result<()> {
+    writer.write_u8(DECIMAL_TAG)?;
+    if d.is_zero() {
+        return writer.write_u8(ZERO_DECIMAL);
+    }
+    // Flip the bits of negative decimals so that larger magnitudes sort first.
+    let mask = if d.is_negative() { 0xFF } else { 0 };
+    writer.write_u8(if d.is_negative() {
+        NEGATIVE_DECIMAL
+    } else {
+        POSITIVE_DECIMAL
+    })?;
+    // Digits have no leading zeros, so larger exponents are larger magnitudes.
+    let exponent = (d.exponent() as u32) ^ (1 << 31);
+    writer.write_u32::<BigEndian>(exponent ^ u32::from_be_bytes([mask; 4]))?;
+    // Shift the digits past the terminator so that a prefix of another
+    // decimal's digits sorts first.
+    for &digit in d.digits() {
+        writer.write_u8((digit + 1) ^ mask)?;
+    }
+    writer.write_u8(TERMINATOR_BYTE ^ mask)?;
+    Ok(())
+}
+
+fn write_geo_point<W: Write>(p: &GeoPoint, writer: &mut W) -> io::Result<()> {
+    writer.write_u8(GEO_POINT_TAG)?;
+    writer.write_u64::<BigEndian>(p.cell())?;
+    // Coordinates are finite and never negative zero, so flipping the sign bit
+    // (and every bit for negative numbers) orders them like floats.
+    for coordinate in [p.latitude(), p.longitude()] {
+        let bits = coordinate.to_bits();
+        let bits = if bits & (1 << 63) != 0 {
+            !bits
+        } else {
+            bits | (1 << 63)
+        };
+        writer.write_u64::<BigEndian>(bits)?;
+    }
+    Ok(())
+}
+
+/// Bounds on the sort keys of the geo points with cells in `cells`, as an
+/// inclusive start and an exclusive end. Since index keys start with the sort
+/// key of their first field, these also bound index keys whose first field is
+/// a geo point in `cells`.
+pub fn geo_cell_range_to_bytes(cells: &RangeInclusive<u64>) -> (Vec<u8>, Vec<u8>) {
+    let bound = |cell: u64| {
+        let mut out = vec![GEO_POINT_TAG];
+        out.extend_from_slice(&cell.to_be_bytes());
+        out
+    };
+    let end = match cells.end().checked_add(1) {
+        Some(end) => bound(end),
+        None => vec![GEO_POINT_TAG + 1],
+    };
+    (bound(*cells.start()), end)
+}
+
 /// Generate the sort key for a sequence of `Value`s.
 pub fn values_to_bytes(values: &[Option<ConvexValue>]) -> Vec<u8> {
     let mut out = vec![];
@@ -166,6 +242,27 @@ pub mod sorting_decode {
         Ok(())
     }
 
+    fn read_decimal<R: Read>(reader: &mut R) -> anyhow::Result<Decimal> {
+        let sign = reader.read_u8()?;
+        let mask = match sign {
+            ZERO_DECIMAL => return Ok(Decimal::ZERO),
+            NEGATIVE_DECIMAL => 0xFF,
+            POSITIVE_DECIMAL => 0,
+            _ => bail!("Invalid decimal sign: {sign}"),
+        };
+        let exponent = reader.read_u32::<BigEndian>()? ^ u32::from_be_bytes([mask; 4]);
+        let exponent = (exponent ^ (1 << 31)) as i32;
+        let mut digits = vec![];
+        loop {
+            let byte = reader.read_u8()? ^ mask;
+            if byte == TERMINATOR_BYTE {
+                break;
+            }
+            digits.push(byte - 1);
+        }
+        Decimal::from_parts(mask != 0, digits, exponent)
+    }
+
     fn read_tagged_int<R: Read>(tag: u8, reader: &mut R) -> io::Result<i64> {
         let is_negative = tag < ZERO_INT64_TAG;
         let tag_diff = cmp::max(tag, ZERO_INT64_TAG) - cmp::min(tag, ZERO_INT64_TAG);
@@ -326,6 +423,22 @@ pub mod sorting_decode {
                     })?;
                     ConvexValue::Object(ConvexObject::try_from(elements)?)
                 },
+                DECIMAL_TAG => ConvexValue::Decimal(read_decimal(reader)?),
+                GEO_POINT_TAG => {
+                    // The cell is derived from the coordinates.
+                    reader.read_u64::<BigEndian>()?;
+                    let mut coordinates = [0.; 2];
+                    for coordinate in &mut coordinates {
+                        let bits = reader.read_u64::<BigEndian>()?;
+                        let bits = if bits & (1 << 63) != 0 {
+                            bits & !(1 << 63)
+                        } else {
+                            !bits
+                        };
+                        *coordinate = f64::from_bits(bits);
+                    }
+                    ConvexValue::GeoPoint(GeoPoint::new(coordinates[0], coordinates[1])?)
+                },
 
                 ESCAPE_BYTE => bail!("Escape code used as tag"),
                 _ => bail!("Unrecognized tag: {}", tag),
@@ -369,6 +482,12 @@ impl ConvexValue {
                 writer.write_u8(FLOAT64_TAG)?;
                 writer.write_u64::<BigEndian>(f)?;
             },
+            ConvexValue::Decimal(d) => {
+                write_decimal(d, writer)?;
+            },
+            ConvexValue::GeoPoint(p) => {
+                write_geo_point(p, writer)?;
+            },
             ConvexValue::Boolean(false) => {
                 writer.write_u8(FALSE_BOOLEAN_TAG)?;
             },
@@ -436,6 +555,8 @@ impl Ord for ConvexValue {
                 ConvexValue::Set(..) => 8,
                 ConvexValue::Map(..) => 9,
                 ConvexValue::Object(..) => 10,
+                ConvexValue::Decimal(..) => 11,
+                ConvexValue::GeoPoint(..) => 12,
             }
         }
         let tag_cmp = type_tag(self).cmp(&type_tag(other));
@@ -461,6 +582,18 @@ impl Ord for ConvexValue {
                 };
                 self_.total_cmp(other_)
             },
+            ConvexValue::Decimal(self_) => {
+                let ConvexValue::Decimal(other_) = other else {
+                    panic!("Invalid value: {other:?}");
+                };
+                self_.cmp(other_)
+            },
+            ConvexValue::GeoPoint(self_) => {
+                let ConvexValue::GeoPoint(other_) = other else {
+                    panic!("Invalid value: {other:?}");
+                };
+                self_.cmp(other_)
+            },
             ConvexValue::Boolean(self_) => {
                 let ConvexValue::Boolean(other_) = other else {
                     panic!("Invalid value: {other:?}");
@@ -574,6 +707,7 @@ mod tests {
     use crate::{
         id_v6::DeveloperDocumentId,
         sorting::{
+            geo_cell_range_to_bytes,
             sorting_decode::bytes_to_values,
             TotalOrdF64,
         },
@@ -585,6 +719,8 @@ mod tests {
         ConvexSet,
         ConvexString,
         ConvexValue,
+        Decimal,
+        GeoPoint,
         InternalId,
         ResolvedDocumentId,
         TableIdentifier,
@@ -608,6 +744,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_decimal_sort_keys() -> anyhow::Result<()> {
+        // Decimals that share a prefix of digits, on both sides of zero.
+        let ordered = [
+            "-1e+30", "-0.1234", "-0.123", "-0.12", "0", "0.12", "0.123", "1e+30",
+        ];
+        let mut prev: Option<ConvexValue> = None;
+        for d in ordered {
+            let v = ConvexValue::from(d.parse::<Decimal>()?);
+            assert_eq!(ConvexValue::read_sort_key(&mut &v.sort_key()[..])?, v);
+            if let Some(prev) = prev {
+                assert!(prev.sort_key() < v.sort_key(), "{prev} < {v}");
+            }
+            prev = Some(v);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_geo_point_sort_keys() -> anyhow::Result<()> {
+        let points = [
+            (-90.0, -180.0),
+            (-1.5, -1.5),
+            (-1.0, 1.0),
+            (1.0, 1.0),
+            (90.0, 180.0),
+        ];
+        for (latitude, longitude) in points {
+            let p = GeoPoint::new(latitude, longitude)?;
+            let v = ConvexValue::from(p);
+            assert_eq!(ConvexValue::read_sort_key(&mut &v.sort_key()[..])?, v);
+            // Every point's sort key is within the bounds of its own cell.
+            let (start, end) = geo_cell_range_to_bytes(&(p.cell()..=p.cell()));
+            assert!(start <= v.sort_key() && v.sort_key() < end, "{v}");
+        }
+        let (_, end) = geo_cell_range_to_bytes(&(0..=u64::MAX));
+        assert!(ConvexValue::from(GeoPoint::new(90.0, 180.0)?).sort_key() < end);
+        Ok(())
+    }
+
     fn test_compatible_with_ord<F: Ord + TryInto<ConvexValue>>(l: F, r: F)
     where
         <F as TryInto<ConvexValue>>::Error: Debug,
@@ -667,6 +843,16 @@ mod tests {
             test_compatible_with_ord(TotalOrdF64(l), TotalOrdF64(r));
         }
 
+        #[test]
+        fn test_compatible_with_decimal(l in any::<Decimal>(), r in any::<Decimal>()) {
+            test_compatible_with_ord(l, r)
+        }
+
+        #[test]
+        fn test_compatible_with_geo_point(l in any::<GeoPoint>(), r in any::<GeoPoint>()) {
+            test_compatible_with_ord(l, r)
+        }
+
         #[test]
         fn test_compatible_with_bool(l in any::<bool>(), r in any::<bool>())  {
             test_compatible_with_ord(l, r)
@@ -22,6 +22,7 @@ import {
   QueryJournal,
   RequestId,
   ServerMessage,
+  SYNC_PROTOCOL_VERSION,
   TS,
   UserIdentityAttributes,
 } from "./protocol.js";
@@ -148,6 +149,7 @@ export class BaseConvexClient {
   private _nextRequestId: RequestId;
   private readonly _sessionId: string;
   private firstMessageReceived = false;
+  private waitingToResumeSubscriptions = false;
   private readonly verbose: boolean;
   private readonly debug: boolean;
   private maxObservedTimestamp: TS | undefined;
@@ -261,6 +263,7 @@ export class BaseConvexClient {
           type: "Connect",
           sessionId: this._sessionId,
           maxObservedTimestamp: this.maxObservedTimestamp,
+          protocolVersion: SYNC_PROTOCOL_VERSION,
         });
 
         // Throw out our remote query, reissue queries
@@ -326,6 +329,10 @@ export class BaseConvexClient {
             void this.webSocketManager.stop();
             throw error;
           }
+          case "SubscriptionsExpired": {
+            this.resumeSubscriptionsOnActivity();
+            break;
+          }
           case "Ping":
             break; // do nothing
           default: {
@@ -719,6 +726,39 @@ export class BaseConvexClient {
     }
   }
 
+  /**
+   * The server stops updating the queries of idle clients, and reruns them on
+   * the next message we send. In a browser, send one once the user comes back
+   * to the page. Elsewhere, the queries resume on the next mutation, action or
+   * query subscription.
+   */
+  private resumeSubscriptionsOnActivity() {
+    if (
+      this.waitingToResumeSubscriptions ||
+      typeof window === "undefined" ||
+      typeof window.addEventListener === "undefined" ||
+      typeof document === "undefined"
+    ) {
+      return;
+    }
+    this.waitingToResumeSubscriptions = true;
+    const resume = () => {
+      if (document.visibilityState === "hidden") {
+        return;
+      }
+      window.removeEventListener("focus", resume);
+      document.removeEventListener("visibilitychange", resume);
+      this.waitingToResumeSubscriptions = false;
+      this.webSocketManager.sendMessage({
+        type: "Event",
+        eventType: "ResumeSubscriptions",
+        event: null,
+      });
+    };
+    window.addEventListener("focus", resume);
+    document.addEventListener("visibilitychange", resume);
+  }
+
   private tryReportLongDisconnect() {
     if (!this.debug) {
       return;
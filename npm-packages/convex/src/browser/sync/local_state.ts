@@ -122,7 +122,8 @@ export class LocalSyncState {
     for (const modification of transition.modifications) {
       switch (modification.type) {
         case "QueryUpdated":
-        case "QueryFailed": {
+        case "QueryFailed":
+        case "QueryPatched": {
           this.outstandingQueriesOlderThanRestart.delete(modification.queryId);
           const journal = modification.journal;
           if (journal !== undefined) {
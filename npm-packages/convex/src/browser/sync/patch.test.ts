@@ -0,0 +1,45 @@
+import { test, expect } from "vitest";
+
+import { applyPatch } from "./patch.js";
+
+test("applies object patches", () => {
+  const value = { a: 1, b: { c: "d" }, "e/f": 2 };
+  expect(
+    applyPatch(value, [
+      { op: "remove", path: "/a" },
+      { op: "replace", path: "/b/c", value: "x" },
+      { op: "add", path: "/b/y", value: [1] },
+      { op: "replace", path: "/e~1f", value: 3 },
+    ]),
+  ).toEqual({ b: { c: "x", y: [1] }, "e/f": 3 });
+});
+
+test("applies array patches", () => {
+  const value = [{ _id: "b" }, { _id: "c" }, { _id: "d" }];
+  expect(
+    applyPatch(value, [
+      { op: "remove", path: "/2" },
+      { op: "add", path: "/0", value: { _id: "a" } },
+      { op: "replace", path: "/2/_id", value: "e" },
+      { op: "add", path: "/-", value: { _id: "f" } },
+    ]),
+  ).toEqual([{ _id: "a" }, { _id: "b" }, { _id: "e" }, { _id: "f" }]);
+});
+
+test("replaces the root", () => {
+  expect(applyPatch([1, 2], [{ op: "replace", path: "", value: "hi" }])).toBe(
+    "hi",
+  );
+});
+
+test("rejects invalid paths", () => {
+  expect(() =>
+    applyPatch({ a: 1 }, [{ op: "replace", path: "/b", value: 2 }]),
+  ).toThrow("Invalid patch path /b");
+  expect(() => applyPatch([1], [{ op: "remove", path: "/1" }])).toThrow(
+    "Invalid patch path /1",
+  );
+  expect(() => applyPatch([1], [{ op: "remove", path: "/01" }])).toThrow(
+    "Invalid patch path /01",
+  );
+});
@@ -0,0 +1,93 @@
+import { JSONValue } from "../../values/index.js";
+import { PatchOperation } from "./protocol.js";
+
+/**
+ * Apply a patch sent in a `QueryPatched` state modification.
+ *
+ * `value` is modified in place, so it must not be shared with anything that
+ * expects it to stay the same. Returns the patched value, which is a new
+ * value if the patch replaces the whole document.
+ */
+export function applyPatch(
+  value: JSONValue,
+  patch: PatchOperation[],
+): JSONValue {
+  let result = value;
+  for (const operation of patch) {
+    if (operation.path === "") {
+      if (operation.op === "remove") {
+        throw new Error("Can't remove the root of a query result");
+      }
+      result = operation.value;
+      continue;
+    }
+    const tokens = parsePath(operation.path);
+    const last = tokens.pop()!;
+    let parent: JSONValue = result;
+    for (const token of tokens) {
+      parent = child(parent, token, operation.path);
+    }
+    if (Array.isArray(parent)) {
+      const length = operation.op === "add" ? parent.length + 1 : parent.length;
+      const index =
+        operation.op === "add" && last === "-"
+          ? parent.length
+          : arrayIndex(last, length, operation.path);
+      switch (operation.op) {
+        case "add":
+          parent.splice(index, 0, operation.value);
+          break;
+        case "remove":
+          parent.splice(index, 1);
+          break;
+        case "replace":
+          parent[index] = operation.value;
+          break;
+      }
+    } else if (parent !== null && typeof parent === "object") {
+      if (operation.op !== "add" && !hasOwn(parent, last)) {
+        throw new Error(`Invalid patch path ${operation.path}`);
+      }
+      if (operation.op === "remove") {
+        delete parent[last];
+      } else {
+        parent[last] = operation.value;
+      }
+    } else {
+      throw new Error(`Invalid patch path ${operation.path}`);
+    }
+  }
+  return result;
+}
+
+function parsePath(path: string): string[] {
+  if (!path.startsWith("/")) {
+    throw new Error(`Invalid patch path ${path}`);
+  }
+  return path
+    .slice(1)
+    .split("/")
+    .map((token) => token.replace(/~1/g, "/").replace(/~0/g, "~"));
+}
+
+function child(value: JSONValue, token: string, path: string): JSONValue {
+  if (Array.isArray(value)) {
+    return value[arrayIndex(token, value.length, path)];
+  }
+  if (value !== null && typeof value === "object" && hasOwn(value, token)) {
+    return value[token];
+  }
+  throw new Error(`Invalid patch path ${path}`);
+}
+
+function hasOwn(object: object, key: string): boolean {
+  return Object.prototype.hasOwnProperty.call(object, key);
+}
+
+function arrayIndex(token: string, length: number, path: string): number {
+  const index = Number(token);
+  if (!/^(0|[1-9][0-9]*)$/.test(token) || index >= length) {
+    throw new Error(`Invalid patch path ${path}`);
+  }
+  return index;
+}
@@ -24,6 +24,7 @@ export function parseServerMessage(
     case "FatalError":
     case "AuthError":
     case "ActionResponse":
+    case "SubscriptionsExpired":
     case "Ping": {
       return { ...encoded };
     }
@@ -111,6 +112,13 @@ export type IdentityVersion = number; // nonnegative int
  */
 export type QueryJournal = string | null;
 
+/**
+ * The newest version of the sync protocol this client understands. Version 2
+ * adds `QueryPatched` state modifications, and version 3 adds
+ * `SubscriptionsExpired` messages.
+ */
+export const SYNC_PROTOCOL_VERSION = 3;
+
 /**
  * Client message schema
  */
@@ -121,6 +129,7 @@ type Connect = {
   connectionCount: number;
   lastCloseReason: string | null;
   maxObservedTimestamp?: TS;
+  protocolVersion?: number;
 };
 
 export type AddQuery = {
@@ -211,6 +220,15 @@ export type TS = U64;
 type EncodedTS = EncodedU64;
 type LogLines = string[];
 
+/**
+ * A change to a query result, as a JSON Patch (RFC 6902) operation limited to
+ * `add`, `remove` and `replace`.
+ */
+export type PatchOperation =
+  | { op: "add"; path: string; value: JSONValue }
+  | { op: "remove"; path: string }
+  | { op: "replace"; path: string; value: JSONValue };
+
 export type StateVersion = {
   querySet: QuerySetVersion;
   ts: TS;
@@ -236,6 +254,15 @@ type StateModification =
       // Optional because old backend versions don't send this.
       journal?: QueryJournal;
     }
+  | {
+      // The new result as a patch against the JSON encoding of the result
+      // from the query's last `QueryUpdated` or `QueryPatched`.
+      type: "QueryPatched";
+      queryId: QueryId;
+      patch: PatchOperation[];
+      logLines: LogLines;
+      journal: QueryJournal;
+    }
   | {
       type: "QueryRemoved";
       queryId: QueryId;
@@ -293,6 +320,13 @@ type FatalError = {
 type Ping = {
   type: "Ping";
 };
+/**
+ * The server stopped updating this client's queries because it was idle. They
+ * resume on the next message the client sends.
+ */
+type SubscriptionsExpired = {
+  type: "SubscriptionsExpired";
+};
 
 export type ServerMessage =
   | Transition
@@ -300,6 +334,7 @@ export type ServerMessage =
   | ActionResponse
   | FatalError
   | AuthError
+  | SubscriptionsExpired
   | Ping;
 
 type EncodedTransition = Omit<Transition, "startVersion" | "endVersion"> & {
@@ -315,4 +350,5 @@ type EncodedServerMessage =
   | ActionResponse
   | FatalError
   | AuthError
+  | SubscriptionsExpired
   | Ping;
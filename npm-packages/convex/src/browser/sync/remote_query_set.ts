@@ -1,8 +1,9 @@
-import { jsonToConvex } from "../../values/index.js";
+import { convexToJson, jsonToConvex } from "../../values/index.js";
 import { Long } from "../long.js";
 import { logToConsole } from "../logging.js";
 import { QueryId, StateVersion, Transition } from "./protocol.js";
 import { FunctionResult } from "./function_result.js";
+import { applyPatch } from "./patch.js";
 
 /**
  * A represention of the query results we've received on the current WebSocket
@@ -64,6 +65,29 @@ export class RemoteQuerySet {
           });
           break;
         }
+        case "QueryPatched": {
+          const queryPath = this.queryPath(modification.queryId);
+          if (queryPath) {
+            for (const line of modification.logLines) {
+              logToConsole("info", "query", queryPath, line);
+            }
+          }
+          const previous = this.remoteQuerySet.get(modification.queryId);
+          if (previous === undefined || !previous.success) {
+            throw new Error(
+              `Received a patch for query ${modification.queryId} without a previous result`,
+            );
+          }
+          const value = jsonToConvex(
+            applyPatch(convexToJson(previous.value), modification.patch),
+          );
+          this.remoteQuerySet.set(modification.queryId, {
+            success: true,
+            value,
+            logLines: modification.logLines,
+          });
+          break;
+        }
         case "QueryRemoved": {
           this.remoteQuerySet.delete(modification.queryId);
           break;
@@ -63,6 +63,15 @@ export const convexImport = new Command("import")
       "Append imported data to any existing tables",
     ).conflicts("--replace"),
   )
+  .option(
+    "--dry-run",
+    "Validate every row against your schema and existing documents, and report what the import would do without writing anything",
+  )
+  .option(
+    "--remap-ids",
+    "Give imported documents new IDs, updating references to them in `v.id` fields of your schema. " +
+      "Use this to import data exported from another deployment without conflicting with existing documents",
+  )
   .option(
     "-y, --yes",
     "Skip confirmation prompt when import leads to deleting existing documents",
@@ -77,6 +86,13 @@ export const convexImport = new Command("import")
         "- ZIP files must have one directory per table, containing <table>/documents.jsonl. Snapshot exports from the Convex dashboard have this format.",
     ).choices(["csv", "jsonLines", "jsonArray", "zip"]),
   )
+  .addOption(
+    new Option(
+      "--column-types <types>",
+      "Types of CSV columns whose values shouldn't be inferred, like `count:int64,done:boolean`. " +
+        "Types are string, number, int64, boolean, and json. Empty cells in columns that aren't strings are left out of the document.",
+    ),
+  )
   .addDeploymentSelectionOptions(actionDescription("Import data into"))
   .argument("<path>", "Path to the input file")
   .showHelpAfterError()
@@ -124,6 +140,15 @@ export const convexImport = new Command("import")
       }
     }
 
+    const columnTypes = options.columnTypes ?? null;
+    if (columnTypes !== null && format !== "csv") {
+      logFailure(
+        ctx,
+        `Error: The \`--column-types\` option is not allowed for format ${format}`,
+      );
+      return await ctx.crash(1, "fatal");
+    }
+
     await ensureHasConvexDependency(ctx, "import");
     const convexClient = new ConvexHttpClient(deploymentUrl);
     convexClient.setAdminAuth(adminKey);
@@ -162,6 +187,9 @@ export const convexImport = new Command("import")
       tableName: tableName === null ? undefined : tableName,
       mode,
       format,
+      columnTypes: columnTypes === null ? undefined : columnTypes,
+      dryRun: options.dryRun ?? false,
+      remapIds: options.remapIds ?? false,
     };
     const headers = {
       Authorization: `Convex ${adminKey}`,
@@ -252,6 +280,23 @@ export const convexImport = new Command("import")
         case "waiting_for_confirmation": {
           // Clear spinner state so we can log and prompt without clobbering lines.
           stopSpinner(ctx);
+          if (options.dryRun) {
+            logMessage(ctx, snapshotImportState.message_to_confirm ?? "");
+            try {
+              await fetch("/api/cancel_import", {
+                headers: { ...headers, "content-type": "application/json" },
+                method: "POST",
+                body: JSON.stringify({ importId }),
+              });
+            } catch (e) {
+              return await logAndHandleFetchError(ctx, e);
+            }
+            logFinishedStep(
+              ctx,
+              `Dry run finished, nothing was imported${tableNotice}${deploymentNotice}.`,
+            );
+            return;
+          }
           await askToConfirmImport(
             ctx,
             snapshotImportState.message_to_confirm,
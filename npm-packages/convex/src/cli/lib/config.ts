@@ -45,6 +45,8 @@ export interface AuthInfo {
   applicationID: string;
   // Domain used for authentication. Corresponds to the `iss` field in an OIDC token.
   domain: string;
+  // Other accepted values of the `iss` field, for providers with more than one issuer.
+  additionalIssuers?: string[];
 }
 
 /** Type representing Convex project configuration. */
@@ -49,6 +49,7 @@ export const analyzedFunction = z.object({
   visibility: z.nullable(visibility),
   args: z.nullable(z.string()),
   returns: z.nullable(z.string()),
+  cache: z.optional(z.object({ ttlSeconds: z.number() })),
 });
 export type AnalyzedFunction = z.infer<typeof analyzedFunction>;
 
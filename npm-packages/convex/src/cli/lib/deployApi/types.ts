@@ -6,6 +6,7 @@ export type Reference = z.infer<typeof reference>;
 export const authInfo = z.object({
   applicationID: z.string(),
   domain: z.string(),
+  additionalIssuers: z.optional(z.array(z.string())),
 });
 export type AuthInfo = z.infer<typeof authInfo>;
 
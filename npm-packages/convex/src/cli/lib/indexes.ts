@@ -23,6 +23,8 @@ type IndexMetadata = {
     | string[]
     | {
         searchField: string;
+        additionalSearchFields?: string[];
+        searchFieldBoosts?: Record<string, number>;
         filterFields: string[];
       };
   backfill: {
@@ -0,0 +1,86 @@
+import { Id } from "../values/value.js";
+import {
+  DocumentByName,
+  GenericDataModel,
+  TableNamesInDataModel,
+} from "./data_model.js";
+
+/**
+ * Options for reading a page of a table's changes with
+ * {@link GenericActionCtx.changes}.
+ * @public
+ */
+export interface TableChangesOptions {
+  /**
+   * The `cursor` of the previous page. Only changes after it are returned.
+   *
+   * If this is omitted or `null`, changes are read from the oldest change
+   * that's still retained.
+   */
+  cursor?: string | null;
+  /**
+   * The maximum number of changes to return. If specified, must be between 1
+   * and 1024 inclusive.
+   *
+   * @default 1024
+   */
+  numItems?: number;
+}
+
+/**
+ * A single change to a document.
+ * @public
+ */
+export interface TableChange<
+  DataModel extends GenericDataModel,
+  TableName extends TableNamesInDataModel<DataModel>,
+> {
+  /**
+   * The ID of the document that changed.
+   */
+  id: Id<TableName>;
+  /**
+   * When the change was committed, in milliseconds since the epoch.
+   */
+  ts: number;
+  /**
+   * The document after the change, or `null` if it was deleted.
+   */
+  document: DocumentByName<DataModel, TableName> | null;
+}
+
+/**
+ * A page of a table's changes.
+ * @public
+ */
+export interface TableChangesPage<
+  DataModel extends GenericDataModel,
+  TableName extends TableNamesInDataModel<DataModel>,
+> {
+  /**
+   * Changes in the order they were committed.
+   */
+  changes: TableChange<DataModel, TableName>[];
+  /**
+   * Pass this as the `cursor` of the next call to read the following changes.
+   *
+   * A page never splits the changes made by one transaction, so processing
+   * each page and then saving its cursor won't miss or repeat any changes.
+   */
+  cursor: string;
+  /**
+   * Whether more changes had already been committed when this page was read.
+   */
+  hasMore: boolean;
+}
+
+/**
+ * Read a page of the changes to a table.
+ * @public
+ */
+export type TableChanges<DataModel extends GenericDataModel> = <
+  TableName extends TableNamesInDataModel<DataModel>,
+>(
+  tableName: TableName,
+  options?: TableChangesOptions,
+) => Promise<TableChangesPage<DataModel, TableName>>;
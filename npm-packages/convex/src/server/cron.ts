@@ -133,6 +133,33 @@ export type Weekly = {
   minuteUTC: number;
 };
 
+/**
+ * Options that can be added to any cron job schedule.
+ *
+ * @public
+ */
+export type CronOptions = {
+  /**
+   * An IANA time zone like `"America/New_York"`. When set, the hours and
+   * minutes of the schedule are in this time zone instead of UTC, and runs
+   * follow daylight saving time changes.
+   */
+  timezone?: string;
+  /**
+   * Delay each run by a random number of seconds, up to this many, to spread
+   * out jobs that are scheduled for the same time.
+   */
+  jitterSeconds?: number;
+  /**
+   * What to do with runs that were missed while the deployment was paused or
+   * the previous run was still going:
+   * - `"skip"` skips them and waits for the next scheduled time.
+   * - `"runOnce"` runs once right away for all of them. This is the default.
+   * - `"runAll"` runs each of them, up to a limit.
+   */
+  catchUp?: "skip" | "runOnce" | "runAll";
+};
+
 /** @public */
 export type Schedule =
   | CronSchedule
@@ -153,6 +180,9 @@ export interface CronJob {
   name: string;
   args: JSONValue;
   schedule: Schedule;
+  timezone?: string;
+  jitterSeconds?: number;
+  catchUp?: "skip" | "runOnce" | "runAll";
 }
 
 /**
@@ -224,6 +254,28 @@ function validatedCronString(s: string) {
   return s;
 }
 
+function validatedCronOptions(options: CronOptions): CronOptions {
+  const { timezone, jitterSeconds, catchUp } = options;
+  if (timezone !== undefined && typeof timezone !== "string") {
+    throw new Error('Time zone must be a string like "America/New_York"');
+  }
+  if (
+    jitterSeconds !== undefined &&
+    (!Number.isInteger(jitterSeconds) || jitterSeconds <= 0)
+  ) {
+    throw new Error("Jitter seconds must be an integer greater than 0");
+  }
+  if (
+    catchUp !== undefined &&
+    catchUp !== "skip" &&
+    catchUp !== "runOnce" &&
+    catchUp !== "runAll"
+  ) {
+    throw new Error('Catch up must be one of "skip", "runOnce", or "runAll"');
+  }
+  return { timezone, jitterSeconds, catchUp };
+}
+
 function validatedCronIdentifier(s: string) {
   if (!s.match(/^[ -~]*$/)) {
     throw new Error(
@@ -252,11 +304,13 @@ export class Crons {
   schedule(
     cronIdentifier: string,
     schedule: Schedule,
+    options: CronOptions,
     functionReference: SchedulableFunctionReference,
     args?: Record<string, Value>,
   ) {
     const cronArgs = parseArgs(args);
     validatedCronIdentifier(cronIdentifier);
+    const { timezone, jitterSeconds, catchUp } = validatedCronOptions(options);
     if (cronIdentifier in this.crons) {
       throw new Error(`Cron identifier registered twice: ${cronIdentifier}`);
     }
@@ -264,6 +318,9 @@ export class Crons {
       name: getFunctionName(functionReference),
       args: [convexToJson(cronArgs)],
       schedule: schedule,
+      ...(timezone !== undefined ? { timezone } : {}),
+      ...(jitterSeconds !== undefined ? { jitterSeconds } : {}),
+      ...(catchUp !== undefined ? { catchUp } : {}),
     };
   }
 
@@ -275,18 +332,19 @@ export class Crons {
    * ```
    *
    * @param identifier - A unique name for this scheduled job.
-   * @param schedule - The time between runs for this scheduled job.
+   * @param schedule - The time between runs for this scheduled job, and any
+   * {@link CronOptions}.
    * @param functionReference - A {@link FunctionReference} for the function
    * to schedule.
    * @param args - The arguments to the function.
    */
   interval<FuncRef extends SchedulableFunctionReference>(
     cronIdentifier: string,
-    schedule: Interval,
+    schedule: Interval & CronOptions,
     functionReference: FuncRef,
     ...args: OptionalRestArgs<FuncRef>
   ) {
-    const s = schedule;
+    const { timezone, jitterSeconds, catchUp, ...s } = schedule;
     const hasSeconds = +("seconds" in s && s.seconds !== undefined);
     const hasMinutes = +("minutes" in s && s.minutes !== undefined);
     const hasHours = +("hours" in s && s.hours !== undefined);
@@ -295,15 +353,16 @@ export class Crons {
       throw new Error("Must specify one of seconds, minutes, or hours");
     }
     if (hasSeconds) {
-      validateIntervalNumber(schedule.seconds!);
+      validateIntervalNumber(s.seconds!);
     } else if (hasMinutes) {
-      validateIntervalNumber(schedule.minutes!);
+      validateIntervalNumber(s.minutes!);
     } else if (hasHours) {
-      validateIntervalNumber(schedule.hours!);
+      validateIntervalNumber(s.hours!);
     }
     this.schedule(
       cronIdentifier,
-      { ...schedule, type: "interval" },
+      { ...s, type: "interval" } as IntervalSchedule,
+      { timezone, jitterSeconds, catchUp },
       functionReference,
       ...args,
     );
@@ -324,14 +383,15 @@ export class Crons {
    * ```
    *
    * @param cronIdentifier - A unique name for this scheduled job.
-   * @param schedule - What time (UTC) each day to run this function.
+   * @param schedule - What minute of each hour to run this function, and
+   * any {@link CronOptions}.
    * @param functionReference - A {@link FunctionReference} for the function
    * to schedule.
    * @param args - The arguments to the function.
    */
   hourly<FuncRef extends SchedulableFunctionReference>(
     cronIdentifier: string,
-    schedule: Hourly,
+    schedule: Hourly & CronOptions,
     functionReference: FuncRef,
     ...args: OptionalRestArgs<FuncRef>
   ) {
@@ -339,6 +399,7 @@ export class Crons {
     this.schedule(
       cronIdentifier,
       { minuteUTC, type: "hourly" },
+      schedule,
       functionReference,
       ...args,
     );
@@ -359,14 +420,15 @@ export class Crons {
    * ```
    *
    * @param cronIdentifier - A unique name for this scheduled job.
-   * @param schedule - What time (UTC) each day to run this function.
+   * @param schedule - What time (UTC) each day to run this function, and
+   * any {@link CronOptions}.
    * @param functionReference - A {@link FunctionReference} for the function
    * to schedule.
    * @param args - The arguments to the function.
    */
   daily<FuncRef extends SchedulableFunctionReference>(
     cronIdentifier: string,
-    schedule: Daily,
+    schedule: Daily & CronOptions,
     functionReference: FuncRef,
     ...args: OptionalRestArgs<FuncRef>
   ) {
@@ -375,6 +437,7 @@ export class Crons {
     this.schedule(
       cronIdentifier,
       { hourUTC, minuteUTC, type: "daily" },
+      schedule,
       functionReference,
       ...args,
     );
@@ -395,13 +458,14 @@ export class Crons {
    * ```
    *
    * @param cronIdentifier - A unique name for this scheduled job.
-   * @param schedule - What day and time (UTC) each week to run this function.
+   * @param schedule - What day and time (UTC) each week to run this function,
+   * and any {@link CronOptions}.
    * @param functionReference - A {@link FunctionReference} for the function
    * to schedule.
    */
   weekly<FuncRef extends SchedulableFunctionReference>(
     cronIdentifier: string,
-    schedule: Weekly,
+    schedule: Weekly & CronOptions,
     functionReference: FuncRef,
     ...args: OptionalRestArgs<FuncRef>
   ) {
@@ -411,6 +475,7 @@ export class Crons {
     this.schedule(
       cronIdentifier,
       { dayOfWeek, hourUTC, minuteUTC, type: "weekly" },
+      schedule,
       functionReference,
       ...args,
     );
@@ -435,14 +500,15 @@ export class Crons {
    * ```
    *
    * @param cronIdentifier - A unique name for this scheduled job.
-   * @param schedule - What day and time (UTC) each month to run this function.
+   * @param schedule - What day and time (UTC) each month to run this function,
+   * and any {@link CronOptions}.
    * @param functionReference - A {@link FunctionReference} for the function
    * to schedule.
    * @param args - The arguments to the function.
    */
   monthly<FuncRef extends SchedulableFunctionReference>(
     cronIdentifier: string,
-    schedule: Monthly,
+    schedule: Monthly & CronOptions,
     functionReference: FuncRef,
     ...args: OptionalRestArgs<FuncRef>
   ) {
@@ -452,6 +518,7 @@ export class Crons {
     this.schedule(
       cronIdentifier,
       { day, hourUTC, minuteUTC, type: "monthly" },
+      schedule,
       functionReference,
       ...args,
     );
@@ -472,21 +539,25 @@ export class Crons {
    * ```
    *
    * @param cronIdentifier - A unique name for this scheduled job.
-   * @param cron - Cron string like `"15 7 * * *"` (Every day at 7:15 UTC)
+   * @param cron - Cron string like `"15 7 * * *"` (Every day at 7:15 UTC), or
+   * an object with the cron string and any {@link CronOptions} like
+   * `{ cron: "15 7 * * *", timezone: "Europe/Paris" }`.
    * @param functionReference - A {@link FunctionReference} for the function
    * to schedule.
    * @param args - The arguments to the function.
    */
   cron<FuncRef extends SchedulableFunctionReference>(
     cronIdentifier: string,
-    cron: CronString,
+    cron: CronString | ({ cron: CronString } & CronOptions),
     functionReference: FuncRef,
     ...args: OptionalRestArgs<FuncRef>
   ) {
-    const c = validatedCronString(cron);
+    const options = typeof cron === "string" ? {} : cron;
+    const c = validatedCronString(typeof cron === "string" ? cron : cron.cron);
     this.schedule(
       cronIdentifier,
       { cron: c, type: "cron" },
+      options,
       functionReference,
       ...args,
     );
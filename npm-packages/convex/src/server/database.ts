@@ -1,4 +1,4 @@
-import { GenericId } from "../values/index.js";
+import { GenericId, Value } from "../values/index.js";
 import {
   DocumentByName,
   GenericDataModel,
@@ -54,6 +54,20 @@ interface BaseDatabaseReader<DataModel extends GenericDataModel> {
   ): GenericId<TableName> | null;
 }
 
+/**
+ * A range of values of the first field of a sketch's index, like the bounds
+ * of an index range. Omitted bounds are unbounded.
+ *
+ * @public
+ */
+export type SketchRange = {
+  eq?: Value;
+  gt?: Value;
+  gte?: Value;
+  lt?: Value;
+  lte?: Value;
+};
+
 /**
  * An interface to read from the database within Convex query functions.
  *
@@ -80,6 +94,46 @@ export interface GenericDatabaseReader<DataModel extends GenericDataModel>
    * @public
    */
   system: BaseDatabaseReader<SystemDataModel>;
+
+  /**
+   * Estimate how many distinct values a field has, using a `"distinct"`
+   * sketch declared with {@link TableDefinition.sketch}.
+   *
+   * The estimate is usually within 2% of the true count. Values of deleted
+   * documents may still be counted.
+   *
+   * @param tableName - The name of the table.
+   * @param sketch - The name of the sketch.
+   * @param range - The values of the first field of the sketch's index to
+   * count documents for. Defaults to the whole table.
+   * @returns - The estimated number of distinct values.
+   */
+  approximateDistinctCount<TableName extends TableNamesInDataModel<DataModel>>(
+    tableName: TableName,
+    sketch: string,
+    range?: SketchRange,
+  ): Promise<number>;
+
+  /**
+   * Estimate the value of a numeric field at a quantile, using a
+   * `"quantile"` sketch declared with {@link TableDefinition.sketch}.
+   *
+   * The estimate is within 1% of the true value.
+   *
+   * @param tableName - The name of the table.
+   * @param sketch - The name of the sketch.
+   * @param quantile - The quantile, from 0 to 1, e.g. 0.99 for the 99th
+   * percentile.
+   * @param range - The values of the first field of the sketch's index to
+   * include documents for. Defaults to the whole table.
+   * @returns - The estimated value, or `null` if there are no numeric values.
+   */
+  approximateQuantile<TableName extends TableNamesInDataModel<DataModel>>(
+    tableName: TableName,
+    sketch: string,
+    quantile: number,
+    range?: SketchRange,
+  ): Promise<number | null>;
 }
 
 /**
@@ -0,0 +1,34 @@
+import { jsonToConvex } from "../../values/index.js";
+import { performAsyncSyscall } from "./syscall.js";
+import { version } from "../../index.js";
+import { TableChanges } from "../changes.js";
+import { GenericDataModel } from "../data_model.js";
+import { validateArg } from "./validate.js";
+
+export function setupActionChanges(
+  requestId: string,
+): TableChanges<GenericDataModel> {
+  return async (tableName: string, options?: any) => {
+    validateArg(tableName, 1, "changes", "tableName");
+    const { changes, cursor, hasMore } = await performAsyncSyscall(
+      "1.0/actions/changes",
+      {
+        requestId,
+        version,
+        table: tableName,
+        cursor: options?.cursor ?? null,
+        numItems: options?.numItems ?? null,
+      },
+    );
+    return {
+      changes: changes.map((change: any) => ({
+        id: change.id,
+        ts: change.ts,
+        document:
+          change.document === null ? null : jsonToConvex(change.document),
+      })),
+      cursor,
+      hasMore,
+    } as any;
+  };
+}
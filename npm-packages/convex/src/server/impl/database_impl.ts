@@ -5,13 +5,28 @@ import {
   Value,
 } from "../../values/index.js";
 import { performAsyncSyscall, performSyscall } from "./syscall.js";
-import { GenericDatabaseReader, GenericDatabaseWriter } from "../database.js";
+import {
+  GenericDatabaseReader,
+  GenericDatabaseWriter,
+  SketchRange,
+} from "../database.js";
 import { QueryInitializerImpl } from "./query_impl.js";
 import { GenericDataModel, GenericDocument } from "../data_model.js";
 import { validateArg } from "./validate.js";
 import { version } from "../../index.js";
 import { patchValueToJson } from "../../values/value.js";
 
+function sketchRangeToJson(range: SketchRange | undefined) {
+  if (range === undefined) {
+    return undefined;
+  }
+  return Object.fromEntries(
+    Object.entries(range)
+      .filter(([, value]) => value !== undefined)
+      .map(([bound, value]) => [bound, convexToJson(value as Value)]),
+  );
+}
+
 export function setupReader(): GenericDatabaseReader<GenericDataModel> {
   const reader = (
     isSystem = false,
@@ -71,6 +86,39 @@ export function setupReader(): GenericDatabaseReader<GenericDataModel> {
         const syscallResult = jsonToConvex(syscallJSON) as any;
         return syscallResult.id;
       },
+      approximateDistinctCount: async (
+        tableName: string,
+        sketch: string,
+        range?: SketchRange,
+      ) => {
+        validateArg(tableName, 1, "approximateDistinctCount", "tableName");
+        validateArg(sketch, 2, "approximateDistinctCount", "sketch");
+        const syscallJSON = await performAsyncSyscall(
+          "1.0/db/approximateDistinctCount",
+          { table: tableName, sketch, range: sketchRangeToJson(range) },
+        );
+        return jsonToConvex(syscallJSON) as number;
+      },
+      approximateQuantile: async (
+        tableName: string,
+        sketch: string,
+        quantile: number,
+        range?: SketchRange,
+      ) => {
+        validateArg(tableName, 1, "approximateQuantile", "tableName");
+        validateArg(sketch, 2, "approximateQuantile", "sketch");
+        validateArg(quantile, 3, "approximateQuantile", "quantile");
+        const syscallJSON = await performAsyncSyscall(
+          "1.0/db/approximateQuantile",
+          {
+            table: tableName,
+            sketch,
+            quantile,
+            range: sketchRangeToJson(range),
+          },
+        );
+        return jsonToConvex(syscallJSON) as number | null;
+      },
       // We set the system reader on the next line
       system: null as any,
     };
@@ -87,6 +135,8 @@ export function setupWriter(): GenericDatabaseWriter<GenericDataModel> {
     get: reader.get,
     query: reader.query,
     normalizeId: reader.normalizeId,
+    approximateDistinctCount: reader.approximateDistinctCount,
+    approximateQuantile: reader.approximateQuantile,
     system: reader.system,
     insert: async (table, value) => {
       if (table.startsWith("_")) {
@@ -0,0 +1,55 @@
+import { convexToJson, Value } from "../../values/index.js";
+import { performAsyncSyscall } from "./syscall.js";
+import { getFunctionName } from "../api.js";
+import { EnqueueOptions, Queue, QueueConsumerOptions } from "../queue.js";
+import { SchedulableFunctionReference } from "../scheduler.js";
+import { validateArg } from "./validate.js";
+
+export function setupMutationQueue(): Queue {
+  return {
+    enqueue: async (
+      queue: string,
+      payload: Value,
+      options?: EnqueueOptions,
+    ) => {
+      validateArg(queue, 1, "enqueue", "queue");
+      const { numGroups } = await performAsyncSyscall("1.0/queue/enqueue", {
+        queue,
+        payload: convexToJson(payload),
+        delayMs: options?.delayMs ?? null,
+      });
+      return numGroups;
+    },
+    registerConsumer: async (
+      queue: string,
+      group: string,
+      handler: SchedulableFunctionReference,
+      options?: QueueConsumerOptions,
+    ) => {
+      validateArg(queue, 1, "registerConsumer", "queue");
+      validateArg(group, 2, "registerConsumer", "group");
+      await performAsyncSyscall("1.0/queue/registerConsumer", {
+        queue,
+        group,
+        handler: getFunctionName(handler),
+        visibilityTimeoutMs: options?.visibilityTimeoutMs ?? null,
+        maxAttempts: options?.maxAttempts ?? null,
+        maxConcurrency: options?.maxConcurrency ?? null,
+      });
+    },
+    unregisterConsumer: async (queue: string, group: string) => {
+      validateArg(queue, 1, "unregisterConsumer", "queue");
+      validateArg(group, 2, "unregisterConsumer", "group");
+      const { numDeleted } = await performAsyncSyscall(
+        "1.0/queue/unregisterConsumer",
+        { queue, group },
+      );
+      return numDeleted;
+    },
+    ack: async (messageId: string, receipt: string) => {
+      validateArg(messageId, 1, "ack", "messageId");
+      validateArg(receipt, 2, "ack", "receipt");
+      await performAsyncSyscall("1.0/queue/ack", { messageId, receipt });
+    },
+  };
+}
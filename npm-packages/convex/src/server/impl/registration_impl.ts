@@ -11,18 +11,23 @@ import { GenericDataModel } from "../data_model.js";
 import {
   ActionBuilder,
   DefaultFunctionArgs,
+  ErrorValidators,
   GenericActionCtx,
   GenericMutationCtx,
   GenericQueryCtx,
   MutationBuilder,
   PublicHttpAction,
   QueryBuilder,
+  QueryCacheOptions,
   RegisteredAction,
   RegisteredMutation,
   RegisteredQuery,
 } from "../registration.js";
 import { setupActionCalls } from "./actions_impl.js";
 import { setupActionVectorSearch } from "./vector_search_impl.js";
+import { setupActionChanges } from "./changes_impl.js";
+import { setupMutationQueue } from "./queue_impl.js";
+import { setupActionWorkflowStep } from "./workflow_impl.js";
 import { setupAuth } from "./authentication_impl.js";
 import { setupReader, setupWriter } from "./database_impl.js";
 import { QueryImpl, QueryInitializerImpl } from "./query_impl.js";
@@ -53,6 +58,7 @@ async function invokeMutation<
     auth: setupAuth(requestId),
     storage: setupStorageWriter(requestId),
     scheduler: setupMutationScheduler(),
+    queue: setupMutationQueue(),
 
     runQuery: (reference: any, args?: any) => runUdf("query", reference, args),
     runMutation: (reference: any, args?: any) =>
@@ -130,6 +136,8 @@ type FunctionDefinition =
   | {
       args?: GenericValidator | Record<string, GenericValidator>;
       returns?: GenericValidator | Record<string, GenericValidator>;
+      cache?: QueryCacheOptions;
+      errors?: ErrorValidators;
       handler: (ctx: any, args: DefaultFunctionArgs) => any;
     };
 
@@ -159,6 +167,40 @@ function exportReturns(functionDefinition: FunctionDefinition) {
   };
 }
 
+function exportErrors(functionDefinition: FunctionDefinition) {
+  return () => {
+    if (typeof functionDefinition === "object" && functionDefinition.errors) {
+      const errors: Record<string, unknown> = {};
+      for (const [code, validator] of Object.entries(
+        functionDefinition.errors,
+      )) {
+        errors[code] = validator.json;
+      }
+      return JSON.stringify(errors);
+    }
+    return JSON.stringify(null);
+  };
+}
+
+function exportCache(functionDefinition: FunctionDefinition) {
+  if (typeof functionDefinition === "object" && functionDefinition.cache) {
+    const { ttlSeconds } = functionDefinition.cache;
+    if (!Number.isInteger(ttlSeconds) || ttlSeconds < 1) {
+      throw new Error(
+        `cache.ttlSeconds must be a positive integer, got ${ttlSeconds}`,
+      );
+    }
+  }
+  return () => {
+    if (typeof functionDefinition === "object" && functionDefinition.cache) {
+      return JSON.stringify({
+        ttlSeconds: functionDefinition.cache.ttlSeconds,
+      });
+    }
+    return JSON.stringify(null);
+  };
+}
+
 /**
  * Define a mutation in this Convex app's public API.
  *
@@ -192,6 +234,7 @@ export const mutationGeneric: MutationBuilder<any, "public"> = ((
   func.invokeMutation = (argsStr) => invokeMutation(func, argsStr);
   func.exportArgs = exportArgs(functionDefinition);
   func.exportReturns = exportReturns(functionDefinition);
+  func.exportErrors = exportErrors(functionDefinition);
   return func;
 }) as MutationBuilder<any, "public">;
 
@@ -228,6 +271,7 @@ export const internalMutationGeneric: MutationBuilder<any, "internal"> = ((
   func.invokeMutation = (argsStr) => invokeMutation(func, argsStr);
   func.exportArgs = exportArgs(functionDefinition);
   func.exportReturns = exportReturns(functionDefinition);
+  func.exportErrors = exportErrors(functionDefinition);
   return func;
 }) as MutationBuilder<any, "internal">;
 
@@ -282,6 +326,8 @@ export const queryGeneric: QueryBuilder<any, "public"> = ((
   func.invokeQuery = (argsStr) => invokeQuery(func, argsStr);
   func.exportArgs = exportArgs(functionDefinition);
   func.exportReturns = exportReturns(functionDefinition);
+  func.exportErrors = exportErrors(functionDefinition);
+  func.exportCache = exportCache(functionDefinition);
   return func;
 }) as QueryBuilder<any, "public">;
 
@@ -318,6 +364,8 @@ export const internalQueryGeneric: QueryBuilder<any, "internal"> = ((
   func.invokeQuery = (argsStr) => invokeQuery(func as any, argsStr);
   func.exportArgs = exportArgs(functionDefinition);
   func.exportReturns = exportReturns(functionDefinition);
+  func.exportErrors = exportErrors(functionDefinition);
+  func.exportCache = exportCache(functionDefinition);
   return func;
 }) as QueryBuilder<any, "internal">;
 
@@ -332,6 +380,8 @@ async function invokeAction<
     scheduler: setupActionScheduler(requestId),
     storage: setupStorageActionWriter(requestId),
     vectorSearch: setupActionVectorSearch(requestId) as any,
+    changes: setupActionChanges(requestId) as any,
+    step: setupActionWorkflowStep(),
   };
   const result = await invokeFunction(func, ctx, args as any);
   return JSON.stringify(convexToJson(result === undefined ? null : result));
@@ -369,6 +419,7 @@ export const actionGeneric: ActionBuilder<any, "public"> = ((
     invokeAction(func, requestId, argsStr);
   func.exportArgs = exportArgs(functionDefinition);
   func.exportReturns = exportReturns(functionDefinition);
+  func.exportErrors = exportErrors(functionDefinition);
   return func;
 }) as ActionBuilder<any, "public">;
 
@@ -404,6 +455,7 @@ export const internalActionGeneric: ActionBuilder<any, "internal"> = ((
     invokeAction(func, requestId, argsStr);
   func.exportArgs = exportArgs(functionDefinition);
   func.exportReturns = exportReturns(functionDefinition);
+  func.exportErrors = exportErrors(functionDefinition);
   return func;
 }) as ActionBuilder<any, "internal">;
 
@@ -420,6 +472,7 @@ async function invokeHttpAction<
     storage: setupStorageActionWriter(requestId),
     scheduler: setupActionScheduler(requestId),
     vectorSearch: setupActionVectorSearch(requestId) as any,
+    changes: setupActionChanges(requestId) as any,
   };
   return await invokeFunction(func, ctx, [request]);
 }
@@ -2,19 +2,30 @@ import { convexToJson, Value } from "../../values/index.js";
 import { version } from "../../index.js";
 import { performAsyncSyscall } from "./syscall.js";
 import { parseArgs } from "../../common/index.js";
-import { SchedulableFunctionReference, Scheduler } from "../scheduler.js";
+import {
+  MutationScheduler,
+  SchedulableFunctionReference,
+  ScheduledBatchJob,
+  ScheduleOptions,
+  Scheduler,
+} from "../scheduler.js";
 import { getFunctionName } from "../../server/api.js";
 import { Id } from "../../values/value.js";
 import { validateArg } from "./validate.js";
 
-export function setupMutationScheduler(): Scheduler {
+export function setupMutationScheduler(
+  options: ScheduleOptions = {},
+): MutationScheduler {
   return {
     runAfter: async (
       delayMs: number,
       functionReference: SchedulableFunctionReference,
       args?: Record<string, Value>,
     ) => {
-      const syscallArgs = runAfterSyscallArgs(delayMs, functionReference, args);
+      const syscallArgs = {
+        ...runAfterSyscallArgs(delayMs, functionReference, args),
+        ...options,
+      };
       return await performAsyncSyscall("1.0/schedule", syscallArgs);
     },
     runAt: async (
@@ -22,11 +33,10 @@ export function setupMutationScheduler(): Scheduler {
       functionReference: SchedulableFunctionReference,
       args?: Record<string, Value>,
     ) => {
-      const syscallArgs = runAtSyscallArgs(
-        ms_since_epoch_or_date,
-        functionReference,
-        args,
-      );
+      const syscallArgs = {
+        ...runAtSyscallArgs(ms_since_epoch_or_date, functionReference, args),
+        ...options,
+      };
       return await performAsyncSyscall("1.0/schedule", syscallArgs);
     },
     cancel: async (id: Id<"_scheduled_functions">) => {
@@ -34,10 +44,37 @@ export function setupMutationScheduler(): Scheduler {
       const args = { id: convexToJson(id) };
       await performAsyncSyscall("1.0/cancel_job", args);
     },
+    runBatch: async (jobs: ScheduledBatchJob[]) => {
+      if (!Array.isArray(jobs)) {
+        throw new Error("`jobs` must be an array");
+      }
+      const syscallJobs = jobs.map(
+        ({ functionReference, args, delayMs, ...jobOptions }) => ({
+          ...runAfterSyscallArgs(delayMs ?? 0, functionReference, args),
+          ...options,
+          ...validatedScheduleOptions(jobOptions),
+        }),
+      );
+      const { batchId } = await performAsyncSyscall("1.0/scheduleBatch", {
+        jobs: syscallJobs,
+      });
+      return batchId;
+    },
+    cancelBatch: async (batchId: string) => {
+      if (typeof batchId !== "string") {
+        throw new Error("`batchId` must be a string");
+      }
+      return await performAsyncSyscall("1.0/cancelBatch", { batchId });
+    },
+    withOptions: (newOptions: ScheduleOptions) =>
+      setupMutationScheduler(validatedScheduleOptions(newOptions)),
   };
 }
 
-export function setupActionScheduler(requestId: string): Scheduler {
+export function setupActionScheduler(
+  requestId: string,
+  options: ScheduleOptions = {},
+): Scheduler {
   return {
     runAfter: async (
       delayMs: number,
@@ -47,6 +84,7 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = {
         requestId,
         ...runAfterSyscallArgs(delayMs, functionReference, args),
+        ...options,
       };
       return await performAsyncSyscall("1.0/actions/schedule", syscallArgs);
     },
@@ -58,6 +96,7 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = {
         requestId,
         ...runAtSyscallArgs(ms_since_epoch_or_date, functionReference, args),
+        ...options,
       };
       return await performAsyncSyscall("1.0/actions/schedule", syscallArgs);
     },
@@ -66,6 +105,30 @@ export function setupActionScheduler(requestId: string): Scheduler {
       const syscallArgs = { id: convexToJson(id) };
       return await performAsyncSyscall("1.0/actions/cancel_job", syscallArgs);
     },
+    withOptions: (newOptions: ScheduleOptions) =>
+      setupActionScheduler(requestId, validatedScheduleOptions(newOptions)),
+  };
+}
+
+function validatedScheduleOptions(options: ScheduleOptions): ScheduleOptions {
+  const { priority, maxConcurrency } = options;
+  if (
+    priority !== undefined &&
+    priority !== "high" &&
+    priority !== "normal" &&
+    priority !== "low"
+  ) {
+    throw new Error('`priority` must be "high", "normal" or "low"');
+  }
+  if (
+    maxConcurrency !== undefined &&
+    (!Number.isInteger(maxConcurrency) || maxConcurrency <= 0)
+  ) {
+    throw new Error("`maxConcurrency` must be an integer greater than 0");
+  }
+  return {
+    ...(priority !== undefined ? { priority } : {}),
+    ...(maxConcurrency !== undefined ? { maxConcurrency } : {}),
   };
 }
 
@@ -4,6 +4,7 @@ import {
   FileStorageId,
   StorageReader,
   StorageWriter,
+  UploadUrlOptions,
 } from "../storage.js";
 import { version } from "../../index.js";
 import { performAsyncSyscall, performJsSyscall } from "./syscall.js";
@@ -32,10 +33,14 @@ export function setupStorageReader(requestId: string): StorageReader {
 export function setupStorageWriter(requestId: string): StorageWriter {
   const reader = setupStorageReader(requestId);
   return {
-    generateUploadUrl: async () => {
+    generateUploadUrl: async (options?: UploadUrlOptions) => {
       return await performAsyncSyscall("1.0/storageGenerateUploadUrl", {
         requestId,
         version,
+        resumable: options?.resumable,
+        maxBytes: options?.maxBytes,
+        allowedContentTypes: options?.allowedContentTypes,
+        expiresInMs: options?.expiresInMs,
       });
     },
     delete: async (storageId: FileStorageId) => {
@@ -45,6 +50,18 @@ export function setupStorageWriter(requestId: string): StorageWriter {
         storageId,
       });
     },
+    setMetadata: async (
+      storageId: FileStorageId,
+      fields: { tags?: string[]; metadata?: Record<string, string> },
+    ) => {
+      await performAsyncSyscall("1.0/storageSetMetadata", {
+        requestId,
+        version,
+        storageId,
+        tags: fields.tags,
+        metadata: fields.metadata,
+      });
+    },
     getUrl: reader.getUrl,
     getMetadata: reader.getMetadata,
   };
@@ -0,0 +1,24 @@
+import { convexToJson, jsonToConvex } from "../../values/index.js";
+import { performAsyncSyscall } from "./syscall.js";
+import { WorkflowStep } from "../workflow.js";
+import { validateArg } from "./validate.js";
+
+export function setupActionWorkflowStep(): WorkflowStep {
+  return async (name: string, run: () => Promise<any>) => {
+    validateArg(name, 1, "step", "name");
+    validateArg(run, 2, "step", "run");
+    const { completed, result } = await performAsyncSyscall(
+      "1.0/actions/workflow/getStep",
+      { name },
+    );
+    if (completed) {
+      return jsonToConvex(result) as any;
+    }
+    const value = await run();
+    await performAsyncSyscall("1.0/actions/workflow/checkpointStep", {
+      name,
+      result: convexToJson(value === undefined ? null : value),
+    });
+    return value;
+  };
+}
@@ -106,6 +106,8 @@ export type {
   ActionBuilder,
   MutationBuilder,
   QueryBuilder,
+  QueryCacheOptions,
+  ErrorValidators,
   HttpActionBuilder,
   GenericActionCtx,
   GenericMutationCtx,
@@ -123,7 +125,13 @@ export type {
 } from "./registration.js";
 export * from "./search_filter_builder.js";
 export * from "./storage.js";
-export type { Scheduler, SchedulableFunctionReference } from "./scheduler.js";
+export type {
+  Scheduler,
+  MutationScheduler,
+  SchedulableFunctionReference,
+  ScheduledBatchJob,
+  ScheduleOptions,
+} from "./scheduler.js";
 export { cronJobs } from "./cron.js";
 export type { CronJob, Crons } from "./cron.js";
 export type {
@@ -134,8 +142,16 @@ export type {
   SystemIndexes,
   IndexTiebreakerField,
 } from "./system_fields.js";
-export { httpRouter, HttpRouter, ROUTABLE_HTTP_METHODS } from "./router.js";
+export {
+  getPathParams,
+  httpRouter,
+  HttpRouter,
+  ROUTABLE_HTTP_METHODS,
+} from "./router.js";
 export type {
+  CorsConfig,
+  HttpRouterOptions,
+  PathParams,
   RoutableMethod,
   RouteSpec,
   RouteSpecWithPath,
@@ -203,6 +219,22 @@ export type {
   FilterExpression,
 } from "./vector_search.js";
 
+export type {
+  TableChanges,
+  TableChangesOptions,
+  TableChange,
+  TableChangesPage,
+} from "./changes.js";
+
+export type {
+  Queue,
+  EnqueueOptions,
+  QueueConsumerOptions,
+  QueueDelivery,
+} from "./queue.js";
+
+export type { WorkflowStep } from "./workflow.js";
+
 /**
  * @public
  */
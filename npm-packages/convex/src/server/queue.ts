@@ -0,0 +1,127 @@
+import { Value } from "../values/index.js";
+import { SchedulableFunctionReference } from "./scheduler.js";
+
+/**
+ * Options for enqueuing a message with {@link Queue.enqueue}.
+ * @public
+ */
+export interface EnqueueOptions {
+  /**
+   * How long to wait, in milliseconds, before first delivering the message.
+   *
+   * @default 0
+   */
+  delayMs?: number;
+}
+
+/**
+ * Options for a consumer group registered with {@link Queue.registerConsumer}.
+ * @public
+ */
+export interface QueueConsumerOptions {
+  /**
+   * How long, in milliseconds, a delivered message is hidden from the group
+   * before it's delivered again, unless the handler succeeds or acknowledges
+   * it with {@link Queue.ack} first.
+   */
+  visibilityTimeoutMs?: number;
+  /**
+   * The number of deliveries after which the group gives up on a message.
+   */
+  maxAttempts?: number;
+  /**
+   * The maximum number of runs of the handler that can be running at once.
+   */
+  maxConcurrency?: number;
+}
+
+/**
+ * The argument a queue consumer's handler is called with.
+ * @public
+ */
+export interface QueueDelivery<Payload extends Value = Value> {
+  /**
+   * The ID of the message, for acknowledging it with {@link Queue.ack}.
+   */
+  messageId: string;
+  /**
+   * Identifies this delivery of the message, for acknowledging it with
+   * {@link Queue.ack}.
+   */
+  receipt: string;
+  /**
+   * The payload the message was enqueued with.
+   */
+  payload: Payload;
+  /**
+   * Which delivery of the message this is, starting from 1.
+   */
+  attempt: bigint;
+}
+
+/**
+ * A durable message queue with consumer groups.
+ *
+ * Every message enqueued on a queue is delivered to each of the queue's
+ * consumer groups by scheduling a run of the group's handler with a
+ * {@link QueueDelivery}. A message is delivered again if the handler fails or
+ * doesn't finish within the group's visibility timeout, so handlers should be
+ * idempotent.
+ *
+ * @public
+ */
+export interface Queue {
+  /**
+   * Add a message to a queue.
+   *
+   * @param queue - The name of the queue.
+   * @param payload - The message, which is passed to the consumers' handlers.
+   * @param options - An {@link EnqueueOptions} object.
+   * @returns A promise of the number of consumer groups the message will be
+   * delivered to. Messages are only delivered to groups that are registered
+   * when they're enqueued.
+   */
+  enqueue(
+    queue: string,
+    payload: Value,
+    options?: EnqueueOptions,
+  ): Promise<number>;
+
+  /**
+   * Register a consumer group for a queue, or update it if it's already
+   * registered.
+   *
+   * @param queue - The name of the queue.
+   * @param group - The name of the consumer group.
+   * @param handler - A {@link FunctionReference} for the mutation or action
+   * that processes the group's messages. It's called with a
+   * {@link QueueDelivery}.
+   * @param options - A {@link QueueConsumerOptions} object.
+   */
+  registerConsumer(
+    queue: string,
+    group: string,
+    handler: SchedulableFunctionReference,
+    options?: QueueConsumerOptions,
+  ): Promise<void>;
+
+  /**
+   * Delete a consumer group along with the messages it hasn't processed.
+   *
+   * @param queue - The name of the queue.
+   * @param group - The name of the consumer group.
+   * @returns A promise of the number of messages that were deleted.
+   */
+  unregisterConsumer(queue: string, group: string): Promise<number>;
+
+  /**
+   * Acknowledge that a delivery of a message was processed, so it isn't
+   * delivered again. A handler that succeeds acknowledges its delivery
+   * automatically, so this is only needed by handlers that finish processing
+   * a message some other way, e.g. in a function they scheduled.
+   *
+   * @param messageId - The `messageId` of the {@link QueueDelivery}.
+   * @param receipt - The `receipt` of the {@link QueueDelivery}.
+   */
+  ack(messageId: string, receipt: string): Promise<void>;
+}
@@ -25,8 +25,11 @@ import {
   TableNamesInDataModel,
   VectorIndexNames,
 } from "./data_model.js";
-import { Scheduler } from "./scheduler.js";
+import { MutationScheduler, Scheduler } from "./scheduler.js";
+import { Queue } from "./queue.js";
 import { VectorSearchQuery } from "./vector_search.js";
+import { TableChangesOptions, TableChangesPage } from "./changes.js";
+import { WorkflowStep } from "./workflow.js";
 import { Expand } from "../type_utils.js";
 import { Validator } from "../values/validators.js";
 
@@ -60,7 +63,13 @@ export interface GenericMutationCtx<DataModel extends GenericDataModel> {
   /**
    * A utility for scheduling Convex functions to run in the future.
    */
-  scheduler: Scheduler;
+  scheduler: MutationScheduler;
+
+  /**
+   * A utility for sending messages to durable queues and registering the
+   * functions that consume them.
+   */
+  queue: Queue;
 
   /**
    * @internal
@@ -212,6 +221,39 @@ export interface GenericActionCtx<DataModel extends GenericDataModel> {
       VectorSearchQuery<NamedTableInfo<DataModel, TableName>, IndexName>
     >,
   ): Promise<Array<{ _id: Id<TableName>; _score: number }>>;
+
+  /**
+   * Read a page of the changes to the given table, in the order they were
+   * committed.
+   *
+   * Save the returned `cursor` and pass it back in to process a table
+   * incrementally. Changes are only available while they're within the
+   * deployment's retention window, so a cursor that's too old will throw.
+   *
+   * @param tableName - The name of the table to read changes to.
+   * @param options - A {@link TableChangesOptions} with the cursor to read
+   * from and the maximum number of changes to return.
+   * @returns A promise of a page of changes and the cursor to continue from.
+   */
+  changes<TableName extends TableNamesInDataModel<DataModel>>(
+    tableName: TableName,
+    options?: TableChangesOptions,
+  ): Promise<TableChangesPage<DataModel, TableName>>;
+
+  /**
+   * Run a named step of a workflow, checkpointing its result.
+   *
+   * Workflows are actions run by the {@link Scheduler}. If the action is
+   * retried, a step that already completed returns its checkpointed result
+   * instead of running again. Calling this from an action that wasn't
+   * scheduled throws. HTTP actions can't be workflows, so their `ctx` doesn't
+   * have `step`.
+   *
+   * @param name - The name of the step, unique within the workflow.
+   * @param run - A function that runs the step and returns its result.
+   * @returns A promise of the step's result.
+   */
+  step: WorkflowStep;
 }
 
 /**
@@ -310,6 +352,9 @@ export type RegisteredMutation<
 
   /** @internal */
   exportReturns(): string;
+
+  /** @internal */
+  exportErrors?(): string;
 } & VisibilityProperties<Visibility>;
 
 /**
@@ -339,8 +384,62 @@ export type RegisteredQuery<
 
   /** @internal */
   exportReturns(): string;
+
+  /** @internal */
+  exportErrors?(): string;
+
+  /** @internal */
+  exportCache?(): string;
 } & VisibilityProperties<Visibility>;
 
+/**
+ * Opts a query into the server-side result cache.
+ *
+ * The query's results are served from the cache for up to `ttlSeconds` after
+ * they're computed, even if the data they read has changed since. This bounds
+ * how often an expensive query runs, no matter how many clients subscribe to
+ * it, at the cost of results being up to `ttlSeconds` out of date. Subscribed
+ * clients get the recomputed result once the TTL has passed.
+ *
+ * Cached results can be invalidated early from the dashboard or the
+ * `/api/invalidate_query_cache` endpoint.
+ *
+ * @public
+ */
+export type QueryCacheOptions = {
+  /**
+   * How long a result may be served from the cache, in whole seconds, up to a
+   * day.
+   */
+  ttlSeconds: number;
+};
+
+/**
+ * The typed errors a function can throw, by error code.
+ *
+ * A typed error is a `ConvexError` whose data is `{ code, data }`.
+ * A function that declares its errors can only throw typed errors with one of
+ * the declared codes and data matching the code's validator, so clients can
+ * rely on `error.data.code` and `error.data.data`. An error thrown without
+ * `data` is checked against `v.null()`.
+ *
+ * Example:
+ *
+ * ```
+ * errors: {
+ *   NotFound: v.object({ id: v.id("messages") }),
+ *   RateLimited: v.object({ retryAfterMs: v.number() }),
+ * }
+ * ```
+ *
+ * ```
+ * throw new ConvexError({ code: "NotFound", data: { id } });
+ * ```
+ *
+ * @public
+ */
+export type ErrorValidators = Record<string, GenericValidator>;
+
 /**
  * An action that is part of this app.
  *
@@ -368,6 +467,9 @@ export type RegisteredAction<
 
   /** @internal */
   exportReturns(): string;
+
+  /** @internal */
+  exportErrors?(): string;
 } & VisibilityProperties<Visibility>;
 
 /**
@@ -590,6 +692,10 @@ export type MutationBuilder<
            * ```
            */
           returns?: ReturnsValidator;
+          /**
+           * The typed errors this function can throw. See {@link ErrorValidators}.
+           */
+          errors?: ErrorValidators;
           /**
            * The implementation of this function.
            *
@@ -677,6 +783,20 @@ export type QueryBuilder<
            * ```
            */
           returns?: ReturnsValidator;
+          /**
+           * The typed errors this function can throw. See {@link ErrorValidators}.
+           */
+          errors?: ErrorValidators;
+          /**
+           * Serve results from the server-side result cache for up to a TTL.
+           *
+           * Example:
+           *
+           * ```
+           * cache: { ttlSeconds: 60 }
+           * ```
+           */
+          cache?: QueryCacheOptions;
           /**
            * The implementation of this function.
            *
@@ -761,6 +881,10 @@ export type ActionBuilder<
            * ```
            */
           returns?: ReturnsValidator;
+          /**
+           * The typed errors this function can throw. See {@link ErrorValidators}.
+           */
+          errors?: ErrorValidators;
           /**
            * The implementation of this function.
            *
@@ -1,6 +1,6 @@
 import { httpActionGeneric } from "./impl/registration_impl.js";
 import { HttpActionBuilder } from "./registration.js";
-import { httpRouter } from "./router.js";
+import { corsPreflightHeaders, httpRouter } from "./router.js";
 import { expect, test } from "vitest";
 
 const httpAction = httpActionGeneric as HttpActionBuilder;
@@ -135,3 +135,230 @@ test("HttpRouter pathPrefix", () => {
   // Not shadowed: last path segment is different
   http.route({ pathPrefix: "/path11/", method: "GET", handler: action1 });
 });
+
+test("HttpRouter path templates", () => {
+  const http = httpRouter();
+  http.route({ path: "/users/:id", method: "GET", handler: action1 });
+  http.route({ path: "/users/me", method: "GET", handler: action2 });
+  http.route({
+    path: "/users/:id/posts/*rest",
+    method: "GET",
+    handler: action3,
+  });
+  http.route({ pathPrefix: "/users/", method: "GET", handler: action4 });
+
+  // Exact paths take precedence over templates.
+  expect(http.lookup("/users/me", "GET")).toEqual([
+    action2,
+    "GET",
+    "/users/me",
+  ]);
+  expect(http.lookup("/users/abc", "GET")).toEqual([
+    action1,
+    "GET",
+    "/users/:id",
+  ]);
+  expect(http.lookup("/users/abc/posts/2024/01", "GET")).toEqual([
+    action3,
+    "GET",
+    "/users/:id/posts/*rest",
+  ]);
+  expect(http.lookup("/users/abc/posts/", "GET")).toEqual([
+    action3,
+    "GET",
+    "/users/:id/posts/*rest",
+  ]);
+
+  // Templates take precedence over prefixes.
+  expect(http.lookup("/users/abc/posts", "GET")).toEqual([
+    action4,
+    "GET",
+    "/users/*",
+  ]);
+  expect(http.lookup("/users/", "GET")).toEqual([action4, "GET", "/users/*"]);
+  expect(http.lookup("/users/abc", "POST")).toEqual(null);
+
+  expect(http.getRoutes()).toEqual([
+    ["/users/me", "GET", action2],
+    ["/users/:id", "GET", action1],
+    ["/users/:id/posts/*rest", "GET", action3],
+    ["/users/*", "GET", action4],
+  ]);
+});
+
+test("HttpRouter path template precedence", () => {
+  const http = httpRouter();
+  http.route({ path: "/:a/b", method: "GET", handler: action1 });
+  http.route({ path: "/a/:b", method: "GET", handler: action2 });
+  http.route({ path: "/a/*rest", method: "GET", handler: action3 });
+
+  // Earlier static segments win.
+  expect(http.lookup("/a/b", "GET")).toEqual([action2, "GET", "/a/:b"]);
+  expect(http.lookup("/c/b", "GET")).toEqual([action1, "GET", "/:a/b"]);
+  expect(http.lookup("/a/b/c", "GET")).toEqual([action3, "GET", "/a/*rest"]);
+});
+
+test("HttpRouter path template conflicts", () => {
+  const http = httpRouter();
+  http.route({ path: "/users/:id", method: "GET", handler: action1 });
+  http.route({ path: "/files/*path", method: "GET", handler: action2 });
+  http.route({ pathPrefix: "/static/", method: "GET", handler: action3 });
+
+  // Same path
+  expect(() =>
+    http.route({ path: "/users/:id", method: "GET", handler: action1 }),
+  ).toThrow("already in use");
+  // Same shape with different parameter names
+  expect(() =>
+    http.route({ path: "/users/:userId", method: "GET", handler: action1 }),
+  ).toThrow("conflicts with path '/users/:id'");
+  // Wildcards and path prefixes that match the same paths
+  expect(() =>
+    http.route({ pathPrefix: "/files/", method: "GET", handler: action1 }),
+  ).toThrow("conflicts with path '/files/*path'");
+  expect(() =>
+    http.route({ path: "/static/*rest", method: "GET", handler: action1 }),
+  ).toThrow("conflicts with pathPrefix /static/");
+
+  // Different methods don't conflict.
+  http.route({ path: "/users/:userId", method: "POST", handler: action4 });
+
+  // Invalid templates
+  expect(() =>
+    http.route({ path: "/a/*rest/b", method: "GET", handler: action1 }),
+  ).toThrow("must be the last segment");
+  expect(() =>
+    http.route({ path: "/a/:id/:id", method: "GET", handler: action1 }),
+  ).toThrow("Duplicate parameter name");
+  expect(() =>
+    http.route({ path: "/a/:", method: "GET", handler: action1 }),
+  ).toThrow("Invalid parameter name");
+});
+
+test("HttpRouter cors preflight routing", () => {
+  const http = httpRouter({ cors: { allowedOrigins: ["https://a.com"] } });
+  http.route({ path: "/path1", method: "POST", handler: action1 });
+  http.route({ pathPrefix: "/path2/", method: "PUT", handler: action2 });
+  http.route({ path: "/path3", method: "OPTIONS", handler: action3 });
+
+  // Routed paths without an OPTIONS route get the built-in preflight handler.
+  const [preflight, method, path] = http.lookup("/path1", "OPTIONS")!;
+  expect([method, path]).toEqual(["OPTIONS", "/path1"]);
+  expect(preflight).not.toBe(action1);
+  expect(http.lookup("/path2/foo", "OPTIONS")).toEqual([
+    preflight,
+    "OPTIONS",
+    "/path2/*",
+  ]);
+
+  // Explicit OPTIONS routes take precedence.
+  expect(http.lookup("/path3", "OPTIONS")).toEqual([
+    action3,
+    "OPTIONS",
+    "/path3",
+  ]);
+
+  // Unrouted paths still 404.
+  expect(http.lookup("/path4", "OPTIONS")).toEqual(null);
+
+  // The preflight handler isn't listed as a route.
+  expect(http.getRoutes().map(([path, method]) => [path, method])).toEqual([
+    ["/path1", "POST"],
+    ["/path3", "OPTIONS"],
+    ["/path2/*", "PUT"],
+  ]);
+
+  // Without a CORS policy there's no preflight handling.
+  const noCors = httpRouter();
+  noCors.route({ path: "/path1", method: "POST", handler: action4 });
+  expect(noCors.lookup("/path1", "OPTIONS")).toEqual(null);
+});
+
+test("HttpRouter cors config validation", () => {
+  expect(() =>
+    httpRouter({ cors: { allowedOrigins: "*", allowCredentials: true } }),
+  ).toThrow();
+  expect(() => httpRouter({ cors: { allowedOrigins: ["*"] } })).toThrow();
+  expect(() =>
+    httpRouter({ cors: { allowedOrigins: ["https://a.com/"] } }),
+  ).toThrow();
+  expect(() =>
+    httpRouter({ cors: { allowedOrigins: "*", maxAge: -1 } }),
+  ).toThrow();
+});
+
+test("corsPreflightHeaders", () => {
+  const request =
+    (headers: Record<string, string>) =>
+    (name: string): string | null =>
+      headers[name] ?? null;
+
+  const cors = {
+    allowedOrigins: ["https://a.com"],
+    allowCredentials: true,
+    maxAge: 600,
+  };
+  expect(
+    corsPreflightHeaders(
+      cors,
+      request({
+        Origin: "https://a.com",
+        "Access-Control-Request-Method": "POST",
+        "Access-Control-Request-Headers": "content-type",
+      }),
+      ["GET", "POST"],
+    ),
+  ).toEqual({
+    "Access-Control-Allow-Origin": "https://a.com",
+    "Access-Control-Allow-Methods": "GET, POST",
+    "Access-Control-Allow-Headers": "content-type",
+    "Access-Control-Allow-Credentials": "true",
+    "Access-Control-Max-Age": "600",
+    Vary: "Origin, Access-Control-Request-Headers",
+  });
+
+  // Disallowed origin
+  expect(
+    corsPreflightHeaders(
+      cors,
+      request({
+        Origin: "https://b.com",
+        "Access-Control-Request-Method": "POST",
+      }),
+      ["POST"],
+    ),
+  ).toEqual(null);
+
+  // Method not routed for the path
+  expect(
+    corsPreflightHeaders(
+      cors,
+      request({
+        Origin: "https://a.com",
+        "Access-Control-Request-Method": "DELETE",
+      }),
+      ["GET", "POST"],
+    ),
+  ).toEqual(null);
+
+  // Configured methods and headers override the defaults.
+  expect(
+    corsPreflightHeaders(
+      {
+        allowedOrigins: "*",
+        allowedMethods: ["GET", "PUT"],
+        allowedHeaders: ["authorization"],
+      },
+      request({
+        Origin: "https://b.com",
+        "Access-Control-Request-Method": "PUT",
+        "Access-Control-Request-Headers": "x-custom",
+      }),
+      ["POST"],
+    ),
+  ).toEqual({
+    "Access-Control-Allow-Origin": "*",
+    "Access-Control-Allow-Methods": "GET, PUT",
+    "Access-Control-Allow-Headers": "authorization",
+  });
+});
@@ -1,3 +1,4 @@
+import { httpActionGeneric } from "./impl/registration_impl.js";
 import { performJsSyscall } from "./impl/syscall.js";
 import { PublicHttpAction } from "./registration.js";
 
@@ -39,15 +40,71 @@ export function normalizeMethod(
   return method;
 }
 
+/**
+ * A CORS policy applied by an {@link HttpRouter} to all of its routes.
+ *
+ * With a CORS policy configured, the router answers preflight `OPTIONS`
+ * requests for every routed path that doesn't have its own `OPTIONS` route,
+ * and adds CORS headers to the responses of HTTP actions that don't set
+ * `Access-Control-Allow-Origin` themselves.
+ *
+ * @public
+ */
+export type CorsConfig = {
+  /**
+   * Origins allowed to make cross-origin requests, like
+   * `"https://example.com"`, or `"*"` to allow any origin.
+   */
+  allowedOrigins: string[] | "*";
+  /**
+   * Methods allowed in cross-origin requests. Defaults to the methods routed
+   * for the requested path.
+   */
+  allowedMethods?: RoutableMethod[];
+  /**
+   * Request headers allowed in cross-origin requests. Defaults to the headers
+   * asked for by the preflight request.
+   */
+  allowedHeaders?: string[];
+  /**
+   * Response headers, besides the CORS-safelisted ones, that browsers should
+   * expose to the page.
+   */
+  exposedHeaders?: string[];
+  /**
+   * Whether to allow requests that include credentials like cookies. Can't be
+   * combined with allowing any origin.
+   */
+  allowCredentials?: boolean;
+  /**
+   * How long in seconds browsers may cache the result of a preflight request.
+   */
+  maxAge?: number;
+};
+
+/**
+ * Options for {@link httpRouter}.
+ *
+ * @public
+ */
+export type HttpRouterOptions = {
+  /**
+   * CORS policy to apply to all routes of the router.
+   */
+  cors?: CorsConfig;
+};
+
 /**
  * Return a new {@link HttpRouter} object.
  *
  * @public
  */
-export const httpRouter = () => new HttpRouter();
+export const httpRouter = (options?: HttpRouterOptions) =>
+  new HttpRouter(options);
 
 /**
- * A type representing a route to an HTTP action using an exact request URL path match.
+ * A type representing a route to an HTTP action using an exact request URL
+ * path match or a path template.
  *
  * Used by {@link HttpRouter} to route requests to HTTP actions.
  *
@@ -55,7 +112,12 @@ export const httpRouter = () => new HttpRouter();
  */
 export type RouteSpecWithPath = {
   /**
-   * Exact HTTP request path to route.
+   * Exact HTTP request path to route, or a path template like
+   * `/users/:id/posts/*rest`.
+   *
+   * In a path template, a `:name` segment matches any single non-empty path
+   * segment, and a final `*name` segment matches the rest of the path. The
+   * matched values can be read with {@link getPathParams}.
    */
   path: string;
   /**
@@ -100,6 +162,157 @@ export type RouteSpecWithPathPrefix = {
  */
 export type RouteSpec = RouteSpecWithPath | RouteSpecWithPathPrefix;
 
+/**
+ * The parameters of a path template, like `{ id: string; rest: string }` for
+ * `/users/:id/posts/*rest`.
+ *
+ * @public
+ */
+export type PathParams<Path extends string> = string extends Path
+  ? Record<string, string>
+  : Expand<PathSegmentsParams<Path>>;
+
+type PathSegmentsParams<Path extends string> =
+  Path extends `${infer Segment}/${infer Rest}`
+    ? PathSegmentParams<Segment> & PathSegmentsParams<Rest>
+    : PathSegmentParams<Path>;
+
+type PathSegmentParams<Segment extends string> =
+  Segment extends `${":" | "*"}${infer Name}`
+    ? { [Key in Name]: string }
+    : // eslint-disable-next-line @typescript-eslint/ban-types
+      {};
+
+type Expand<T> = T extends infer O ? { [K in keyof O]: O[K] } : never;
+
+const requestPathParams = new WeakMap<Request, Record<string, string>>();
+
+/**
+ * Return the parameters matched by the path template of the route that
+ * `request` was routed to.
+ *
+ * ```js
+ * http.route({
+ *   path: "/users/:id/posts/*rest",
+ *   method: "GET",
+ *   handler: httpAction(async (ctx, request) => {
+ *     const { id, rest } = getPathParams<"/users/:id/posts/*rest">(request);
+ *     ...
+ *   }),
+ * });
+ * ```
+ *
+ * Parameter values are URL-decoded. Requests routed by an exact path or a
+ * path prefix have no parameters.
+ *
+ * @public
+ */
+export function getPathParams<Path extends string = string>(
+  request: Request,
+): PathParams<Path> {
+  return (requestPathParams.get(request) ?? {}) as PathParams<Path>;
+}
+
+type PathSegment =
+  | { kind: "static"; value: string }
+  | { kind: "param"; name: string }
+  | { kind: "wildcard"; name: string };
+
+type PathTemplateRoute = {
+  segments: PathSegment[];
+  handler: PublicHttpAction;
+};
+
+const PATH_PARAM_NAME = /^[A-Za-z_][A-Za-z0-9_]*$/;
+
+function isPathTemplate(path: string) {
+  return path.split("/").some((s) => s.startsWith(":") || s.startsWith("*"));
+}
+
+function parsePathTemplate(path: string): PathSegment[] {
+  const names = new Set<string>();
+  const segments = path.slice(1).split("/");
+  return segments.map((segment, i): PathSegment => {
+    if (!segment.startsWith(":") && !segment.startsWith("*")) {
+      return { kind: "static", value: segment };
+    }
+    const name = segment.slice(1);
+    if (!PATH_PARAM_NAME.test(name)) {
+      throw new Error(`Invalid parameter name '${name}' in path '${path}'`);
+    }
+    if (names.has(name)) {
+      throw new Error(`Duplicate parameter name '${name}' in path '${path}'`);
+    }
+    names.add(name);
+    if (segment.startsWith(":")) {
+      return { kind: "param", name };
+    }
+    if (i !== segments.length - 1) {
+      throw new Error(
+        `Wildcard parameter '${segment}' must be the last segment of path '${path}'`,
+      );
+    }
+    return { kind: "wildcard", name };
+  });
+}
+
+// Templates with the same shape match exactly the same paths.
+function pathTemplateShape(segments: PathSegment[]) {
+  const shape = segments
+    .map((segment) =>
+      segment.kind === "static"
+        ? segment.value
+        : segment.kind === "param"
+          ? ":"
+          : "*",
+    )
+    .join("/");
+  return `/${shape}`;
+}
+
+// Static segments are more specific than parameters, which are more specific
+// than wildcards. Earlier segments take precedence over later ones.
+function comparePathTemplates(a: PathSegment[], b: PathSegment[]) {
+  const rank = (segment: PathSegment) =>
+    segment.kind === "static" ? 0 : segment.kind === "param" ? 1 : 2;
+  for (let i = 0; i < Math.min(a.length, b.length); i++) {
+    const diff = rank(a[i]) - rank(b[i]);
+    if (diff !== 0) return diff;
+  }
+  return a.length - b.length;
+}
+
+function decodePathSegment(segment: string) {
+  try {
+    return decodeURIComponent(segment);
+  } catch {
+    return segment;
+  }
+}
+
+function matchPathTemplate(
+  segments: PathSegment[],
+  path: string,
+): Record<string, string> | null {
+  const parts = path.slice(1).split("/");
+  const params: Record<string, string> = {};
+  for (let i = 0; i < segments.length; i++) {
+    if (i >= parts.length) return null;
+    const segment = segments[i];
+    if (segment.kind === "wildcard") {
+      params[segment.name] = parts.slice(i).map(decodePathSegment).join("/");
+      return params;
+    }
+    if (segment.kind === "static") {
+      if (parts[i] !== segment.value) return null;
+    } else {
+      if (parts[i] === "") return null;
+      params[segment.name] = decodePathSegment(parts[i]);
+    }
+  }
+  return parts.length === segments.length ? params : null;
+}
+
 /**
  * HTTP router for specifying the paths and methods of {@link httpActionGeneric}s
  *
@@ -137,13 +350,44 @@ export type RouteSpec = RouteSpecWithPath | RouteSpecWithPathPrefix;
  * export default http;
  * ```
  *
+ * To allow browsers on other origins to call the HTTP actions, pass a
+ * {@link CorsConfig} instead of writing `OPTIONS` handlers for each route:
+ *
+ * ```js
+ * const http = httpRouter({
+ *   cors: { allowedOrigins: ["https://example.com"], allowCredentials: true },
+ * });
+ * ```
+ *
  * @public
  */
 export class HttpRouter {
   exactRoutes: Map<string, Map<RoutableMethod, PublicHttpAction>> = new Map();
   prefixRoutes: Map<RoutableMethod, Map<string, PublicHttpAction>> = new Map();
+  templateRoutes: Map<RoutableMethod, Map<string, PathTemplateRoute>> =
+    new Map();
+  cors: CorsConfig | null;
   isRouter = true;
 
+  private preflightHandler: PublicHttpAction | null = null;
+
+  constructor(options?: HttpRouterOptions) {
+    this.cors = options?.cors ?? null;
+    if (this.cors) {
+      validateCorsConfig(this.cors);
+      const cors = this.cors;
+      this.preflightHandler = httpActionGeneric(async (_ctx, request) => {
+        const pathname = new URL(request.url).pathname;
+        const headers = corsPreflightHeaders(
+          cors,
+          (name) => request.headers.get(name),
+          this.routedMethods(pathname),
+        );
+        return new Response(null, { status: 204, headers: headers ?? {} });
+      });
+    }
+  }
+
   /**
    * Specify an HttpAction to be used to respond to requests
    * for an HTTP method (e.g. "GET") and a path or pathPrefix.
@@ -156,7 +400,14 @@ export class HttpRouter {
    *
    * // matches `/profiles/`, `/profiles/abc`, and `/profiles/a/c/b` (but not `/profile`)
    * http.route({ pathPrefix: "/profile/", method: "GET", handler: getProfile})
+   *
+   * // matches `/users/abc/posts/` and `/users/abc/posts/2024/01` (but not `/users/abc/posts`)
+   * http.route({ path: "/users/:id/posts/*rest", method: "GET", handler: getPosts})
    * ```
+   *
+   * Exact paths take precedence over path templates, which take precedence
+   * over path prefixes. Routing two path templates that match exactly the
+   * same paths, like `/users/:id` and `/users/:userId`, is an error.
    */
   route = (spec: RouteSpec) => {
     if (!spec.handler) throw new Error(`route requires handler`);
@@ -177,6 +428,10 @@ export class HttpRouter {
       if (!spec.path.startsWith("/")) {
         throw new Error(`path '${spec.path}' does not start with a /`);
       }
+      if (isPathTemplate(spec.path)) {
+        this.routeTemplate(spec.path, method, handler);
+        return;
+      }
       const methods: Map<RoutableMethod, PublicHttpAction> =
         this.exactRoutes.has(spec.path)
           ? this.exactRoutes.get(spec.path)!
@@ -204,6 +459,12 @@ export class HttpRouter {
           `${spec.method} pathPrefix ${spec.pathPrefix} is already defined`,
         );
       }
+      const conflict = this.conflictingTemplate(method, `${spec.pathPrefix}*`);
+      if (conflict) {
+        throw new Error(
+          `${spec.method} pathPrefix ${spec.pathPrefix} conflicts with path '${conflict}'`,
+        );
+      }
       prefixes.set(spec.pathPrefix, handler);
       this.prefixRoutes.set(method, prefixes);
     } else {
@@ -213,6 +474,46 @@ export class HttpRouter {
     }
   };
 
+  private routeTemplate = (
+    path: string,
+    method: RoutableMethod,
+    handler: PublicHttpAction,
+  ) => {
+    const segments = parsePathTemplate(path);
+    const shape = pathTemplateShape(segments);
+    const conflict = this.conflictingTemplate(method, shape);
+    if (conflict) {
+      throw new Error(
+        conflict === path
+          ? `Path '${path}' for method ${method} already in use`
+          : `Path '${path}' for method ${method} conflicts with path '${conflict}'`,
+      );
+    }
+    const last = segments[segments.length - 1];
+    if (last.kind === "wildcard") {
+      const pathPrefix = shape.slice(0, -1);
+      if (this.prefixRoutes.get(method)?.has(pathPrefix)) {
+        throw new Error(
+          `Path '${path}' for method ${method} conflicts with pathPrefix ${pathPrefix}`,
+        );
+      }
+    }
+    const templates =
+      this.templateRoutes.get(method) || new Map<string, PathTemplateRoute>();
+    templates.set(path, { segments, handler });
+    this.templateRoutes.set(method, templates);
+  };
+
+  private conflictingTemplate = (
+    method: RoutableMethod,
+    shape: string,
+  ): string | null => {
+    for (const [path, { segments }] of this.templateRoutes.get(method) ?? []) {
+      if (pathTemplateShape(segments) === shape) return path;
+    }
+    return null;
+  };
+
   /**
    * Returns a list of routed HTTP actions.
    *
@@ -233,6 +534,20 @@ export class HttpRouter {
         ),
     );
 
+    const templateMethods = [...this.templateRoutes.keys()].sort();
+    const templates = templateMethods.flatMap((method) =>
+      [...this.templateRoutes.get(method)!.keys()]
+        .sort()
+        .map(
+          (path) =>
+            [
+              path,
+              method,
+              this.templateRoutes.get(method)!.get(path)!.handler,
+            ] as const,
+        ),
+    );
+
     const prefixPathMethods = [...this.prefixRoutes.keys()].sort();
     const prefixes = prefixPathMethods.flatMap((method) =>
       [...this.prefixRoutes.get(method)!.keys()]
@@ -247,7 +562,7 @@ export class HttpRouter {
         ),
     );
 
-    return [...exact, ...prefixes];
+    return [...exact, ...templates, ...prefixes];
   };
 
   /**
@@ -262,8 +577,15 @@ export class HttpRouter {
    * http.route({ pathPrefix: "/profile/", method: "GET", handler: getProfile});
    *
    * http.lookup("/profile/abc", "GET") // returns [getProfile, "GET", "/profile/*"]
+   *
+   * http.route({ path: "/users/:id", method: "GET", handler: getUser});
+   *
+   * http.lookup("/users/abc", "GET") // returns [getUser, "GET", "/users/:id"]
    *```
    *
+   * If the router has a CORS policy, `OPTIONS` requests for paths without
+   * their own `OPTIONS` route are routed to a built-in preflight handler.
+   *
    * @returns - a tuple [{@link PublicHttpAction}, method, path] or null.
    */
   lookup = (
@@ -271,9 +593,37 @@ export class HttpRouter {
     method: RoutableMethod | "HEAD",
   ): Readonly<[PublicHttpAction, RoutableMethod, string]> | null => {
     method = normalizeMethod(method);
+    const match = this.lookupRoute(path, method);
+    if (match || method !== "OPTIONS" || !this.preflightHandler) {
+      return match;
+    }
+    for (const routedMethod of ROUTABLE_HTTP_METHODS) {
+      const routed = this.lookupRoute(path, routedMethod);
+      if (routed) {
+        return [this.preflightHandler, "OPTIONS", routed[2]];
+      }
+    }
+    return null;
+  };
+
+  private lookupRoute = (
+    path: string,
+    method: RoutableMethod,
+  ): Readonly<[PublicHttpAction, RoutableMethod, string]> | null => {
     const exactMatch = this.exactRoutes.get(path)?.get(method);
     if (exactMatch) return [exactMatch, method, path];
 
+    const templates =
+      this.templateRoutes.get(method) || new Map<string, PathTemplateRoute>();
+    const templatesSorted = [...templates.entries()].sort(([, a], [, b]) =>
+      comparePathTemplates(a.segments, b.segments),
+    );
+    for (const [template, { segments, handler }] of templatesSorted) {
+      if (matchPathTemplate(segments, path)) {
+        return [handler, method, template];
+      }
+    }
+
     const prefixes = this.prefixRoutes.get(method) || new Map();
     const prefixesSorted = [...prefixes.entries()].sort(
       ([prefixA, _a], [prefixB, _b]) => prefixB.length - prefixA.length,
@@ -312,10 +662,144 @@ export class HttpRouter {
         performJsSyscall("convexJsonFromResponse", { response }),
       );
     }
-    const [endpoint, _method, _path] = match;
-    const response = await endpoint.invokeHttpAction(request);
+    const [endpoint, routedMethod, routedPath] = match;
+    const template = this.templateRoutes.get(routedMethod)?.get(routedPath);
+    if (template) {
+      const params = matchPathTemplate(template.segments, pathname);
+      requestPathParams.set(request, params ?? {});
+    }
+    let response = await endpoint.invokeHttpAction(request);
+    if (this.cors && endpoint !== this.preflightHandler) {
+      response = withCorsHeaders(this.cors, request, response);
+    }
     return JSON.stringify(
       performJsSyscall("convexJsonFromResponse", { response }),
     );
   };
+
+  private routedMethods = (path: string): RoutableMethod[] =>
+    ROUTABLE_HTTP_METHODS.filter(
+      (method) => this.lookupRoute(path, method) !== null,
+    );
+}
+
+function validateCorsConfig(cors: CorsConfig) {
+  if (cors.allowedOrigins !== "*") {
+    for (const origin of cors.allowedOrigins) {
+      if (origin === "*") {
+        throw new Error(
+          `Invalid CORS config: use allowedOrigins: "*" to allow any origin`,
+        );
+      }
+      if (origin.endsWith("/")) {
+        throw new Error(
+          `Invalid CORS config: origin '${origin}' must not end with a /`,
+        );
+      }
+    }
+  } else if (cors.allowCredentials) {
+    throw new Error(
+      `Invalid CORS config: allowCredentials can't be combined with allowedOrigins: "*"`,
+    );
+  }
+  for (const method of cors.allowedMethods ?? []) {
+    if (!ROUTABLE_HTTP_METHODS.includes(method)) {
+      throw new Error(
+        `Invalid CORS config: '${method}' is not an allowed HTTP method`,
+      );
+    }
+  }
+  if (
+    cors.maxAge !== undefined &&
+    (!Number.isInteger(cors.maxAge) || cors.maxAge < 0)
+  ) {
+    throw new Error(
+      `Invalid CORS config: maxAge must be a non-negative integer`,
+    );
+  }
+}
+
+function allowedOrigin(cors: CorsConfig, origin: string | null) {
+  if (origin === null) return null;
+  if (cors.allowedOrigins === "*") return "*";
+  return cors.allowedOrigins.includes(origin) ? origin : null;
+}
+
+/**
+ * Returns the headers to respond to a CORS preflight request with, or null if
+ * the request isn't allowed by the policy.
+ *
+ * @param getHeader - returns the value of a request header.
+ * @param routedMethods - the methods routed for the requested path.
+ */
+export function corsPreflightHeaders(
+  cors: CorsConfig,
+  getHeader: (name: string) => string | null,
+  routedMethods: RoutableMethod[],
+): Record<string, string> | null {
+  const origin = allowedOrigin(cors, getHeader("Origin"));
+  const requestMethod = getHeader("Access-Control-Request-Method");
+  if (origin === null || requestMethod === null) return null;
+  const methods = cors.allowedMethods ?? routedMethods;
+  if (!methods.includes(normalizeMethod(requestMethod as RoutableMethod))) {
+    return null;
+  }
+  const headers: Record<string, string> = {
+    "Access-Control-Allow-Origin": origin,
+    "Access-Control-Allow-Methods": methods.join(", "),
+  };
+  const vary = ["Origin"];
+  if (cors.allowedHeaders !== undefined) {
+    if (cors.allowedHeaders.length > 0) {
+      headers["Access-Control-Allow-Headers"] = cors.allowedHeaders.join(", ");
+    }
+  } else {
+    const requestHeaders = getHeader("Access-Control-Request-Headers");
+    if (requestHeaders) {
+      headers["Access-Control-Allow-Headers"] = requestHeaders;
+    }
+    vary.push("Access-Control-Request-Headers");
+  }
+  if (cors.allowCredentials) {
+    headers["Access-Control-Allow-Credentials"] = "true";
+  }
+  if (cors.maxAge !== undefined) {
+    headers["Access-Control-Max-Age"] = `${cors.maxAge}`;
+  }
+  if (origin !== "*") {
+    headers["Vary"] = vary.join(", ");
+  }
+  return headers;
+}
+
+function withCorsHeaders(
+  cors: CorsConfig,
+  request: Request,
+  response: Response,
+): Response {
+  const origin = allowedOrigin(cors, request.headers.get("Origin"));
+  if (origin === null || response.headers.has("Access-Control-Allow-Origin")) {
+    return response;
+  }
+  // The response's headers may be immutable (e.g. if it came from `fetch`),
+  // so build a new response rather than modifying them.
+  const headers = new Headers(response.headers);
+  headers.set("Access-Control-Allow-Origin", origin);
+  if (cors.allowCredentials) {
+    headers.set("Access-Control-Allow-Credentials", "true");
+  }
+  if (cors.exposedHeaders && cors.exposedHeaders.length > 0) {
+    headers.set(
+      "Access-Control-Expose-Headers",
+      cors.exposedHeaders.join(", "),
+    );
+  }
+  if (origin !== "*") {
+    headers.append("Vary", "Origin");
+  }
+  return new Response(response.body, {
+    status: response.status,
+    statusText: response.statusText,
+    headers,
+  });
 }
@@ -1,5 +1,5 @@
 import { FunctionReference, OptionalRestArgs } from "../server/api.js";
-import { Id } from "../values/value.js";
+import { Id, Value } from "../values/value.js";
 
 /**
  * A {@link FunctionReference} that can be scheduled to run in the future.
@@ -13,6 +13,26 @@ export type SchedulableFunctionReference = FunctionReference<
   "public" | "internal"
 >;
 
+/**
+ * Options for scheduling functions, passed to {@link Scheduler.withOptions}.
+ *
+ * @public
+ */
+export interface ScheduleOptions {
+  /**
+   * The lane the scheduled function runs in: `"high"`, `"normal"` or `"low"`.
+   * Lower priority functions only get a share of the scheduler's parallelism,
+   * so bulk work like backfills can't hold up more urgent functions. Defaults
+   * to `"normal"`.
+   */
+  priority?: "high" | "normal" | "low";
+  /**
+   * The maximum number of runs of the scheduled function that can be running
+   * at once. Runs over the limit wait until one of the others finishes.
+   */
+  maxConcurrency?: number;
+}
+
 /**
  * An interface to schedule Convex functions.
  *
@@ -69,4 +89,74 @@ export interface Scheduler {
    * @param id
    */
   cancel(id: Id<"_scheduled_functions">): Promise<void>;
+
+  /**
+   * A scheduler that schedules functions with the given options.
+   *
+   * ```js
+   * await ctx.scheduler
+   *   .withOptions({ priority: "low", maxConcurrency: 5 })
+   *   .runAfter(0, internal.backfill.processPage, { cursor });
+   * ```
+   *
+   * @param options - A {@link ScheduleOptions} for the functions scheduled
+   * with the returned scheduler.
+   */
+  withOptions(options: ScheduleOptions): Scheduler;
+}
+
+/**
+ * A function to schedule as part of a batch with
+ * {@link MutationScheduler.runBatch}.
+ *
+ * @public
+ */
+export interface ScheduledBatchJob extends ScheduleOptions {
+  /**
+   * A {@link FunctionReference} for the function to schedule.
+   */
+  functionReference: SchedulableFunctionReference;
+  /**
+   * Arguments to call the scheduled function with.
+   */
+  args?: Record<string, Value>;
+  /**
+   * Delay in milliseconds before the function runs. Defaults to zero.
+   */
+  delayMs?: number;
+}
+
+/**
+ * The scheduler available in mutations, which can also schedule many
+ * functions at once.
+ *
+ * @public
+ */
+export interface MutationScheduler extends Scheduler {
+  /**
+   * Schedule many functions at once. The functions are scheduled atomically
+   * with the mutation, but are written to the `_scheduled_functions` table in
+   * the background, so a batch can be much larger than the number of
+   * functions a mutation can schedule one at a time.
+   *
+   * @param jobs - The functions to schedule.
+   * @returns A promise of the batch's ID, for canceling it with
+   * {@link MutationScheduler.cancelBatch}.
+   */
+  runBatch(jobs: ScheduledBatchJob[]): Promise<string>;
+
+  /**
+   * Cancel the functions in a batch that haven't started yet. A single call
+   * only cancels up to a page of functions, so keep calling it, e.g. from a
+   * mutation that reschedules itself, until `hasMore` is false.
+   *
+   * @param batchId - The ID returned by {@link MutationScheduler.runBatch}.
+   * @returns A promise of the number of functions canceled and whether there
+   * are more left to cancel.
+   */
+  cancelBatch(
+    batchId: string,
+  ): Promise<{ numCanceled: number; hasMore: boolean }>;
+
+  withOptions(options: ScheduleOptions): MutationScheduler;
 }
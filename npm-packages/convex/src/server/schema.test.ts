@@ -3,6 +3,7 @@ import { GenericId } from "../values/index.js";
 import { describe, expect, test } from "vitest";
 import { assert, Equals } from "../test/type_testing.js";
 import { SystemIndexes } from "./system_fields.js";
+import { makeFunctionReference } from "./api.js";
 import {
   defineSchema,
   defineTable,
@@ -507,6 +508,33 @@ test("defineSchema generates search index types", () => {
   assert<Equals<DataModel, ExpectedDataModel>>();
 });
 
+test("searchIndex exports additional search fields and boosts", () => {
+  const table = defineTable({
+    title: v.string(),
+    body: v.string(),
+  })
+    .searchIndex("search_title", { searchField: "title" })
+    .searchIndex("search_all", {
+      searchField: "title",
+      additionalSearchFields: ["body"],
+      searchFieldBoosts: { title: 3 },
+    });
+  expect(table.export().searchIndexes).toEqual([
+    {
+      indexDescriptor: "search_title",
+      searchField: "title",
+      filterFields: [],
+    },
+    {
+      indexDescriptor: "search_all",
+      searchField: "title",
+      additionalSearchFields: ["body"],
+      searchFieldBoosts: { title: 3 },
+      filterFields: [],
+    },
+  ]);
+});
+
 test("defineSchema generates vector search index types", () => {
   const schema = defineSchema({
     table: defineTable({
@@ -587,6 +615,88 @@ test("defineTable collects indexes", () => {
   ]);
 });
 
+test("defineTable collects encrypted fields", () => {
+  const table = defineTable({
+    name: v.string(),
+    ssn: v.string(),
+  }).encrypted(["ssn"]);
+
+  expect(table.export().encryptedFields).toEqual(["ssn"]);
+  expect(defineTable({ a: v.string() }).export().encryptedFields).toEqual(
+    undefined,
+  );
+});
+
+test("defineTable collects triggers", () => {
+  const table = defineTable({
+    body: v.string(),
+  })
+    .trigger("before", "messages:validate")
+    .trigger("after", makeFunctionReference<"mutation">("messages:count"));
+
+  expect(table.export().triggers).toEqual([
+    { timing: "before", function: "messages:validate" },
+    { timing: "after", function: "messages:count" },
+  ]);
+  expect(defineTable({ a: v.string() }).export().triggers).toEqual(undefined);
+});
+
+test("defineTable exports trackUpdates", () => {
+  const table = defineTable({ body: v.string() }).trackUpdates();
+
+  expect(table.export().trackUpdates).toEqual(true);
+  expect(defineTable({ a: v.string() }).export().trackUpdates).toEqual(
+    undefined,
+  );
+});
+
+test("defineTable exports softDelete", () => {
+  expect(
+    defineTable({ body: v.string() }).softDelete().export().softDelete,
+  ).toEqual({ retentionDays: 30 });
+  expect(
+    defineTable({ body: v.string() })
+      .softDelete({ retentionDays: 7 })
+      .export().softDelete,
+  ).toEqual({ retentionDays: 7 });
+  expect(defineTable({ a: v.string() }).export().softDelete).toEqual(
+    undefined,
+  );
+});
+
+test("defineTable exports idStrategy", () => {
+  expect(
+    defineTable({ body: v.string() }).idStrategy("ulid").export().idStrategy,
+  ).toEqual({ type: "ulid" });
+  expect(
+    defineTable({ orderNumber: v.string() })
+      .idStrategy({ external: "orderNumber" })
+      .export().idStrategy,
+  ).toEqual({ type: "external", field: "orderNumber" });
+  expect(
+    defineTable({ a: v.string() }).idStrategy("random").export().idStrategy,
+  ).toEqual(undefined);
+});
+
+test("defineTable exports sketches", () => {
+  const table = defineTable({ route: v.string(), latencyMs: v.number() })
+    .index("by_route", ["route"])
+    .sketch("latency", {
+      index: "by_route",
+      field: "latencyMs",
+      type: "quantile",
+    });
+  expect(table.export().sketches).toEqual([
+    {
+      name: "latency",
+      index: "by_route",
+      field: "latencyMs",
+      type: "quantile",
+    },
+  ]);
+  expect(defineTable({ a: v.string() }).export().sketches).toEqual(undefined);
+});
+
 describe("JsonTypesFromSchema", () => {
   test("TableDefinition includes field types", () => {
     const table = defineTable({
@@ -42,6 +42,7 @@ import {
   SystemIndexes,
 } from "../server/system_fields.js";
 import { Expand } from "../type_utils.js";
+import { FunctionReference, getFunctionName } from "./api.js";
 import {
   GenericValidator,
   ObjectType,
@@ -82,6 +83,7 @@ type ExtractDocument<T extends Validator<any, any, any>> =
 export interface SearchIndexConfig<
   SearchField extends string,
   FilterFields extends string,
+  AdditionalSearchFields extends string = never,
 > {
   /**
    * The field to index for full text search.
@@ -90,6 +92,25 @@ export interface SearchIndexConfig<
    */
   searchField: SearchField;
 
+  /**
+   * More fields to index for full text search. Search queries against the
+   * index match text in any of its search fields, and are still written as
+   * `q.search(searchField, query)`.
+   *
+   * These must be fields of type `string`.
+   */
+  additionalSearchFields?: AdditionalSearchFields[];
+
+  /**
+   * How many times more a match in a search field counts towards a
+   * document's relevance than a match in an unboosted one. Each boost must
+   * be an integer between 1 and 10, and search fields without a boost have
+   * a boost of 1.
+   */
+  searchFieldBoosts?: Partial<
+    Record<SearchField | AdditionalSearchFields, number>
+  >;
+
   /**
    * Additional fields to index for fast filtering when running search queries.
    */
@@ -131,6 +152,29 @@ export type VectorIndex = {
   filterFields: string[];
 };
 
+/**
+ * @internal
+ */
+export type Trigger = {
+  timing: "before" | "after";
+  function: string;
+};
+
+/**
+ * @internal
+ */
+export type IdStrategy = { type: "ulid" } | { type: "external"; field: string };
+
+/**
+ * @internal
+ */
+export type Sketch = {
+  name: string;
+  index: string;
+  field: string;
+  type: "distinct" | "quantile";
+};
+
 /**
  * @internal
  */
@@ -145,6 +189,8 @@ export type Index = {
 export type SearchIndex = {
   indexDescriptor: string;
   searchField: string;
+  additionalSearchFields?: string[];
+  searchFieldBoosts?: Record<string, number>;
   filterFields: string[];
 };
 /**
@@ -165,6 +211,12 @@ export class TableDefinition<
   private indexes: Index[];
   private searchIndexes: SearchIndex[];
   private vectorIndexes: VectorIndex[];
+  private encryptedFields: string[];
+  private triggers: Trigger[];
+  private trackUpdatesEnabled: boolean;
+  private softDeleteConfig: { retentionDays: number } | undefined;
+  private idStrategyConfig: IdStrategy | undefined;
+  private sketches: Sketch[];
   // The type of documents stored in this table.
   validator: DocumentType;
 
@@ -175,6 +227,12 @@ export class TableDefinition<
     this.indexes = [];
     this.searchIndexes = [];
     this.vectorIndexes = [];
+    this.encryptedFields = [];
+    this.triggers = [];
+    this.trackUpdatesEnabled = false;
+    this.softDeleteConfig = undefined;
+    this.idStrategyConfig = undefined;
+    this.sketches = [];
     this.validator = documentType;
   }
 
@@ -226,9 +284,12 @@ export class TableDefinition<
     IndexName extends string,
     SearchField extends ExtractFieldPaths<DocumentType>,
     FilterFields extends ExtractFieldPaths<DocumentType> = never,
+    AdditionalSearchFields extends ExtractFieldPaths<DocumentType> = never,
   >(
     name: IndexName,
-    indexConfig: Expand<SearchIndexConfig<SearchField, FilterFields>>,
+    indexConfig: Expand<
+      SearchIndexConfig<SearchField, FilterFields, AdditionalSearchFields>
+    >,
   ): TableDefinition<
     DocumentType,
     Indexes,
@@ -249,6 +310,17 @@ export class TableDefinition<
     this.searchIndexes.push({
       indexDescriptor: name,
       searchField: indexConfig.searchField,
+      ...(indexConfig.additionalSearchFields !== undefined
+        ? { additionalSearchFields: indexConfig.additionalSearchFields }
+        : {}),
+      ...(indexConfig.searchFieldBoosts !== undefined
+        ? {
+            searchFieldBoosts: indexConfig.searchFieldBoosts as Record<
+              string,
+              number
+            >,
+          }
+        : {}),
       filterFields: indexConfig.filterFields || [],
     });
     return this;
@@ -295,6 +367,162 @@ export class TableDefinition<
     return this;
   }
 
+  /**
+   * Mark top-level fields of this table as encrypted.
+   *
+   * The values of encrypted fields are encrypted before they're stored and
+   * decrypted when your functions read them. Encrypted fields can't be used
+   * in indexes.
+   *
+   * @param fields - The top-level fields to encrypt.
+   * @returns A {@link TableDefinition} with these fields encrypted.
+   */
+  encrypted(
+    fields: ExtractFieldPaths<DocumentType>[],
+  ): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.encryptedFields.push(...fields);
+    return this;
+  }
+
+  /**
+   * Run a mutation whenever a document in this table is inserted, replaced
+   * or deleted.
+   *
+   * Triggers run inside the transaction that made the write, just before it
+   * commits. They're called with `{ id, oldDoc, newDoc }`, where `oldDoc` is
+   * `null` for inserts and `newDoc` is `null` for deletes. All `"before"`
+   * triggers for a set of writes run before any `"after"` triggers, so use
+   * `"before"` triggers to validate writes (throwing rejects the whole
+   * mutation) and `"after"` triggers to keep denormalized data up to date.
+   *
+   * Writes made by triggers run triggers too, up to a fixed depth.
+   *
+   * @param timing - Either `"before"` or `"after"`.
+   * @param mutation - The mutation to run, e.g. `internal.messages.onWrite`.
+   * @returns A {@link TableDefinition} with this trigger.
+   */
+  trigger(
+    timing: "before" | "after",
+    mutation: FunctionReference<"mutation", "public" | "internal"> | string,
+  ): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.triggers.push({
+      timing,
+      function:
+        typeof mutation === "string" ? mutation : getFunctionName(mutation),
+    });
+    return this;
+  }
+
+  /**
+   * Have Convex maintain the `_updatedTime` and `_updatedBy` system fields of
+   * this table's documents.
+   *
+   * `_updatedTime` is set whenever a document is inserted, patched or
+   * replaced, and `_updatedBy` is set to the `tokenIdentifier` of the user
+   * who made the write, or `null` if there isn't one. Both fields can be
+   * used in indexes.
+   *
+   * @returns A {@link TableDefinition} that tracks updates.
+   */
+  trackUpdates(): TableDefinition<
+    DocumentType,
+    Indexes,
+    SearchIndexes,
+    VectorIndexes
+  > {
+    this.trackUpdatesEnabled = true;
+    return this;
+  }
+
+  /**
+   * Move documents deleted from this table to the trash instead of deleting
+   * them.
+   *
+   * Documents in the trash don't show up in queries or indexes. They can be
+   * restored with their original IDs from the dashboard until the retention
+   * window ends, after which they're deleted for good.
+   *
+   * @param options.retentionDays - How many days deleted documents are kept,
+   * from 1 to 365. Defaults to 30.
+   * @returns A {@link TableDefinition} with soft delete enabled.
+   */
+  softDelete(options?: {
+    retentionDays?: number;
+  }): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.softDeleteConfig = { retentionDays: options?.retentionDays ?? 30 };
+    return this;
+  }
+
+  /**
+   * Choose how the `_id`s of documents inserted into this table are generated.
+   *
+   * - `"random"`, the default, generates random IDs.
+   * - `"ulid"` generates IDs that start with the time they were generated,
+   *   like ULIDs, so ordering by `_id` orders documents by when they were
+   *   inserted.
+   * - `{ external: field }` derives each document's ID from the string it
+   *   stores in `field`, e.g. a key from an external system. Inserting a
+   *   document whose key is already in the table throws, and the field can't
+   *   be changed after the document is inserted.
+   *
+   * @param strategy - How IDs are generated.
+   * @returns A {@link TableDefinition} with the ID strategy.
+   */
+  idStrategy(
+    strategy:
+      | "random"
+      | "ulid"
+      | { external: ExtractFieldPaths<DocumentType> },
+  ): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    if (strategy === "random") {
+      this.idStrategyConfig = undefined;
+    } else if (strategy === "ulid") {
+      this.idStrategyConfig = { type: "ulid" };
+    } else {
+      this.idStrategyConfig = { type: "external", field: strategy.external };
+    }
+    return this;
+  }
+
+  /**
+   * Have Convex maintain an approximate aggregate over a field of this
+   * table's documents, which can be read without scanning them.
+   *
+   * - `"distinct"` sketches estimate how many distinct values the field has,
+   *   with `db.approximateDistinctCount`.
+   * - `"quantile"` sketches estimate quantiles of the field's numeric values,
+   *   like the median or 99th percentile, with `db.approximateQuantile`.
+   *
+   * Sketches are kept per value of the first field of `index`, so they can be
+   * read for a range of that field. Pick an index whose first field doesn't
+   * have too many values, since reading a range merges a sketch per value.
+   * Writes with the same value of that field conflict with each other.
+   *
+   * Documents written before the sketch is added aren't counted.
+   *
+   * @param name - The name of the sketch.
+   * @param options.index - The index whose first field the sketch is kept by.
+   * @param options.field - The field the sketch aggregates.
+   * @param options.type - `"distinct"` or `"quantile"`.
+   * @returns A {@link TableDefinition} with the sketch.
+   */
+  sketch(
+    name: string,
+    options: {
+      index: keyof Indexes & string;
+      field: ExtractFieldPaths<DocumentType>;
+      type: "distinct" | "quantile";
+    },
+  ): TableDefinition<DocumentType, Indexes, SearchIndexes, VectorIndexes> {
+    this.sketches.push({
+      name,
+      index: options.index,
+      field: options.field,
+      type: options.type,
+    });
+    return this;
+  }
+
   /**
    * Work around for https://github.com/microsoft/TypeScript/issues/57035
    */
@@ -318,6 +546,13 @@ export class TableDefinition<
       searchIndexes: this.searchIndexes,
       vectorIndexes: this.vectorIndexes,
       documentType: this.validator.json,
+      encryptedFields:
+        this.encryptedFields.length > 0 ? this.encryptedFields : undefined,
+      triggers: this.triggers.length > 0 ? this.triggers : undefined,
+      trackUpdates: this.trackUpdatesEnabled ? true : undefined,
+      softDelete: this.softDeleteConfig,
+      idStrategy: this.idStrategyConfig,
+      sketches: this.sketches.length > 0 ? this.sketches : undefined,
     };
   }
 }
@@ -434,14 +669,30 @@ export class SchemaDefinition<
   export(): string {
     return JSON.stringify({
       tables: Object.entries(this.tables).map(([tableName, definition]) => {
-        const { indexes, searchIndexes, vectorIndexes, documentType } =
-          definition.export();
+        const {
+          indexes,
+          searchIndexes,
+          vectorIndexes,
+          documentType,
+          encryptedFields,
+          triggers,
+          trackUpdates,
+          softDelete,
+          idStrategy,
+          sketches,
+        } = definition.export();
         return {
           tableName,
           indexes,
           searchIndexes,
           vectorIndexes,
           documentType,
+          encryptedFields,
+          triggers,
+          trackUpdates,
+          softDelete,
+          idStrategy,
+          sketches,
         };
       }),
       schemaValidation: this.schemaValidation,
@@ -582,6 +833,10 @@ const systemSchema = defineSchema({
     sha256: v.string(),
     size: v.float64(),
     contentType: v.optional(v.string()),
+    tags: v.optional(v.array(v.string())),
+    metadata: v.optional(v.record(v.string(), v.string())),
+    scanStatus: v.optional(v.union(v.literal("clean"), v.literal("blocked"))),
+    scanSignature: v.optional(v.string()),
   }),
 });
 
@@ -32,6 +32,40 @@ export type FileMetadata = {
    * ContentType of the file if it was provided on upload
    */
   contentType: string | null;
+  /**
+   * Tags set on the file with {@link StorageWriter.setMetadata | storage.setMetadata}
+   */
+  tags: string[];
+  /**
+   * Metadata set on the file with {@link StorageWriter.setMetadata | storage.setMetadata}
+   */
+  metadata: Record<string, string>;
+};
+
+/**
+ * Options for {@link StorageWriter.generateUploadUrl | storage.generateUploadUrl}.
+ *
+ * @public
+ */
+export type UploadUrlOptions = {
+  /**
+   * Start a resumable upload instead of uploading the file in one request.
+   */
+  resumable?: boolean;
+  /**
+   * The largest file in bytes that can be uploaded to the URL.
+   */
+  maxBytes?: number;
+  /**
+   * The Content-Types that can be uploaded to the URL, like `"image/png"`, or
+   * `"image/*"` for all images. Defaults to allowing any Content-Type.
+   */
+  allowedContentTypes?: string[];
+  /**
+   * How long the URL is valid for in milliseconds, at most 24 hours.
+   * Defaults to 1 hour.
+   */
+  expiresInMs?: number;
 };
 
 /**
@@ -45,6 +79,11 @@ export interface StorageReader {
    *
    * The GET response includes a standard HTTP Digest header with a sha256 checksum.
    *
+   * Images can be resized or converted by adding `width`, `height`, `format`
+   * (`png`, `jpeg` or `webp`) and `quality` (1-100, for JPEG) query parameters
+   * to the URL, e.g. `${url}?width=200&format=webp`. Images are scaled down to
+   * fit within the width and height, keeping their aspect ratio.
+   *
    * @param storageId - The `Id<"_storage">` of the file to fetch from Convex storage.
    * @returns - A url which fetches the file via an HTTP GET, or `null` if it no longer exists.
    */
@@ -100,9 +139,26 @@ export interface StorageWriter extends StorageReader {
    *
    * The POST URL accepts an optional standard HTTP Digest header with a sha256 checksum.
    *
+   * With `resumable: true`, the URL instead starts a resumable upload for
+   * large files on unreliable connections. A POST request to it, with the
+   * file's Content-Type and optional Digest headers, returns `{ uploadId }`.
+   * The file is then uploaded in parts numbered from 1 with
+   * `PUT /api/storage/upload_session/<uploadId>/<partNumber>`, where every
+   * part but the last is at least 5 MiB and each part is at most 64 MiB.
+   * Failed parts can be uploaded again, and
+   * `GET /api/storage/upload_session/<uploadId>` lists the parts uploaded so
+   * far. Finally `POST /api/storage/upload_session/<uploadId>` assembles the
+   * parts and returns `{ storageId }`. Sessions expire after 24 hours.
+   *
+   * The options can also restrict what may be uploaded to the URL. The
+   * restrictions are part of the URL and checked by the server, so clients
+   * can't bypass them: uploads larger than `maxBytes`, or whose Content-Type
+   * doesn't match `allowedContentTypes`, fail with a 400 response.
+   *
+   * @param options - See {@link UploadUrlOptions}.
    * @returns - A url that allows file upload via an HTTP POST.
    */
-  generateUploadUrl(): Promise<string>;
+  generateUploadUrl(options?: UploadUrlOptions): Promise<string>;
   /**
    * Delete a file from Convex storage.
    *
@@ -124,6 +180,24 @@ export interface StorageWriter extends StorageReader {
   delete<T extends StorageId>(
     storageId: T extends { __tableName: any } ? never : T,
   ): Promise<void>;
+
+  /**
+   * Set the tags and metadata of a file in Convex storage, which are returned
+   * by {@link StorageReader.getMetadata} and can be used to filter files in
+   * the dashboard.
+   *
+   * Each field that's provided replaces the file's existing value, and fields
+   * that are omitted are left unchanged. A file can have up to 32 tags of up
+   * to 64 characters each, and up to 32 metadata entries whose keys are valid
+   * field names and whose values are up to 1024 characters.
+   *
+   * @param storageId - The `Id<"_storage">` of the file.
+   * @param fields - The tags and metadata to set.
+   */
+  setMetadata(
+    storageId: GenericId<"_storage">,
+    fields: { tags?: string[]; metadata?: Record<string, string> },
+  ): Promise<void>;
 }
 
 /**
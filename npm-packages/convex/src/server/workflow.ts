@@ -0,0 +1,19 @@
+import { Value } from "../values/value.js";
+
+/**
+ * Run a named step of a workflow, or return the step's result if it already
+ * completed in a previous attempt.
+ *
+ * A workflow is an action run by the {@link Scheduler}. When it's retried,
+ * steps that completed return their checkpointed result instead of running
+ * again, so their side effects happen at most once per step that completes.
+ *
+ * @param name - The name of the step, unique within the workflow.
+ * @param run - A function that runs the step and returns its result.
+ * @returns A promise of the step's result.
+ * @public
+ */
+export type WorkflowStep = <T extends Value>(
+  name: string,
+  run: () => Promise<T>,
+) => Promise<T>;
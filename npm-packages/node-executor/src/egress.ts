@@ -0,0 +1,166 @@
+import net from "node:net";
+import { AsyncLocalStorage } from "node:async_hooks";
+import { fetch as undiciFetch, ProxyAgent } from "undici";
+
+// Restrictions on the requests actions can make. The backend enforces the same
+// policy on `fetch` in the isolate.
+export type EgressPolicy = {
+  // If nonempty, only these domains and their subdomains are allowed.
+  allowedDomains: string[];
+  deniedDomains: string[];
+  // If set, all requests must go through this proxy.
+  proxy?: {
+    url: string;
+    username?: string;
+    password?: string;
+  };
+};
+
+let currentPolicy: EgressPolicy | null = null;
+let proxyAgent: { key: string; agent: ProxyAgent } | null = null;
+let installed = false;
+
+// Set while the executor talks to the backend on the action's behalf, e.g. for
+// syscalls and file storage, which aren't subject to the policy.
+const internalRequests = new AsyncLocalStorage<boolean>();
+const originalFetch = globalThis.fetch;
+
+export function internalFetch(
+  ...args: Parameters<typeof fetch>
+): ReturnType<typeof fetch> {
+  return internalRequests.run(true, () => originalFetch(...args));
+}
+
+function normalizeHost(host: string): string {
+  let normalized = host.toLowerCase().replace(/\.+$/, "");
+  if (normalized.startsWith("[") && normalized.endsWith("]")) {
+    normalized = normalized.slice(1, -1);
+  }
+  return normalized;
+}
+
+function matchesDomain(host: string, domain: string): boolean {
+  return host === domain || host.endsWith(`.${domain}`);
+}
+
+export function allowsHost(policy: EgressPolicy, host: string): boolean {
+  const normalized = normalizeHost(host);
+  if (policy.deniedDomains.some((d) => matchesDomain(normalized, d))) {
+    return false;
+  }
+  return (
+    policy.allowedDomains.length === 0 ||
+    policy.allowedDomains.some((d) => matchesDomain(normalized, d))
+  );
+}
+
+function egressDeniedError(host: string): Error {
+  return new Error(
+    `Requests to ${host} are blocked by this deployment's egress policy`,
+  );
+}
+
+// With a proxy, actions can only open connections to the proxy itself, so
+// clients that don't go through it fail instead of bypassing it.
+function allowsConnection(policy: EgressPolicy, host: string): boolean {
+  if (policy.proxy) {
+    const proxyHost = new URL(policy.proxy.url).hostname;
+    return normalizeHost(host) === normalizeHost(proxyHost);
+  }
+  return allowsHost(policy, host);
+}
+
+// The host of a `net.Socket.connect` call, or null for IPC connections.
+function connectHost(args: any[]): string | null {
+  const [first, second] = Array.isArray(args[0]) ? args[0] : args;
+  if (typeof first === "object" && first !== null) {
+    if (first.path !== undefined) {
+      return null;
+    }
+    return first.host ?? "localhost";
+  }
+  if (typeof first === "string" && isNaN(Number(first))) {
+    return null;
+  }
+  return typeof second === "string" ? second : "localhost";
+}
+
+function getProxyAgent(proxy: NonNullable<EgressPolicy["proxy"]>) {
+  const key = JSON.stringify(proxy);
+  if (proxyAgent?.key !== key) {
+    const token =
+      proxy.username !== undefined
+        ? `Basic ${Buffer.from(
+            `${proxy.username}:${proxy.password ?? ""}`,
+          ).toString("base64")}`
+        : undefined;
+    proxyAgent = { key, agent: new ProxyAgent({ uri: proxy.url, token }) };
+  }
+  return proxyAgent.agent;
+}
+
+function installEgressHooks() {
+  if (installed) {
+    return;
+  }
+  installed = true;
+
+  // Every TCP connection, including those made by `http`, `https` and `tls`,
+  // goes through `net.Socket.prototype.connect`.
+  const originalConnect = net.Socket.prototype.connect;
+  net.Socket.prototype.connect = function (this: net.Socket, ...args: any[]) {
+    const policy = currentPolicy;
+    const host = connectHost(args);
+    if (
+      policy !== null &&
+      host !== null &&
+      !internalRequests.getStore() &&
+      !allowsConnection(policy, host)
+    ) {
+      process.nextTick(() => this.destroy(egressDeniedError(host)));
+      return this;
+    }
+    return (originalConnect as any).apply(this, args);
+  } as any;
+
+  globalThis.fetch = async (input: any, init?: any) => {
+    const policy = currentPolicy;
+    if (policy === null || internalRequests.getStore()) {
+      return originalFetch(input, init);
+    }
+    const request = new Request(input, init);
+    const host = new URL(request.url).hostname;
+    if (!allowsHost(policy, host)) {
+      throw new TypeError("fetch failed", { cause: egressDeniedError(host) });
+    }
+    if (!policy.proxy) {
+      return originalFetch(request);
+    }
+    // Node's `fetch` doesn't support proxies, so use the bundled undici.
+    const response = await undiciFetch(request.url, {
+      method: request.method,
+      headers: request.headers as any,
+      body: request.body as any,
+      duplex: "half",
+      redirect: request.redirect,
+      signal: request.signal as any,
+      dispatcher: getProxyAgent(policy.proxy),
+    });
+    return response as any;
+  };
+}
+
+// Apply `policy` to the requests the action makes until it's cleared.
+export function setEgressPolicy(policy: EgressPolicy | null) {
+  if (
+    policy === null ||
+    (policy.allowedDomains.length === 0 &&
+      policy.deniedDomains.length === 0 &&
+      !policy.proxy)
+  ) {
+    currentPolicy = null;
+    return;
+  }
+  installEgressHooks();
+  currentPolicy = policy;
+}
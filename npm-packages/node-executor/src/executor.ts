@@ -28,6 +28,8 @@ import { SourcePackage, maybeDownloadAndLinkPackages } from "./source_package";
 import { buildDeps, BuildDepsRequest } from "./build_deps";
 import { ConvexError, JSONValue } from "convex/values";
 import { logDebug, logDurationMs } from "./log";
+import { EgressPolicy, setEgressPolicy } from "./egress";
+import { setTraceparent } from "./trace";
 
 // When we bundle commonJS modules as ESM with esbuild, the bundled code might still use
 // `require`, exports, module, __dirname or __filename despite being in ESM.
@@ -119,6 +121,10 @@ export type ExecuteRequest = {
   npmVersion: string | null;
   executionContext: ExecutionContext;
   encodedParentTrace: string | null;
+  // Results longer than this are sent to the backend in chunks of this size
+  // instead of in the response.
+  resultChunkSize?: number;
+  egressPolicy?: EgressPolicy;
 };
 
 export type ExecutionContext = {
@@ -126,6 +132,8 @@ export type ExecutionContext = {
   executionId: string | undefined;
   isRoot: boolean | undefined;
   parentScheduledJob: string | null;
+  // The W3C `traceparent` for requests the action makes.
+  traceparent?: string;
 };
 
 export type ExecuteResponseInner =
@@ -136,6 +144,14 @@ export type ExecuteResponseInner =
       udfTimeMs: number;
       importTimeMs: number;
     }
+  | {
+      type: "success";
+      // The number of chunks the result was sent to the backend in.
+      udfReturnChunks: number;
+      logLines: string[];
+      udfTimeMs: number;
+      importTimeMs: number;
+    }
   | {
       type: "error";
       message: string;
@@ -188,6 +204,8 @@ export async function execute(
   );
 
   let innerResult: ExecuteResponseInner;
+  setEgressPolicy(request.egressPolicy ?? null);
+  setTraceparent(request.executionContext.traceparent ?? null);
   try {
     if (!local.modules.has(request.udfPath.canonicalizedPath)) {
       throw new Error(
@@ -204,6 +222,20 @@ export async function execute(
       request.timeoutSecs,
       syscalls,
     );
+    if (
+      innerResult.type === "success" &&
+      "udfReturn" in innerResult &&
+      request.resultChunkSize !== undefined &&
+      innerResult.udfReturn.length > request.resultChunkSize
+    ) {
+      const { udfReturn, ...rest } = innerResult;
+      const udfReturnChunks = await syscalls.sendResultChunks(
+        udfReturn,
+        request.resultChunkSize,
+        request.npmVersion ?? "unknown",
+      );
+      innerResult = { ...rest, udfReturnChunks };
+    }
   } catch (e: any) {
     innerResult = {
       type: "error",
@@ -212,6 +244,9 @@ export async function execute(
       // Log lines should be streamed, but send an empty array for backwards compatibility
       logLines: [],
     };
+  } finally {
+    setEgressPolicy(null);
+    setTraceparent(null);
   }
 
   const totalExecutorTimeMs = logDurationMs("totalExecutorTime", start);
@@ -400,6 +435,7 @@ export type AnalyzedFunctions = Array<{
   visibility: Visibility | null;
   args: JSONValue | null;
   output: JSONValue | null;
+  errors: JSONValue | null;
 }>;
 
 async function analyzeModule(filePath: string): Promise<AnalyzedFunctions> {
@@ -414,6 +450,7 @@ async function analyzeModule(filePath: string): Promise<AnalyzedFunctions> {
       visibility: Visibility | null;
       args: JSONValue | null;
       output: JSONValue | null;
+      errors: JSONValue | null;
     }
   > = new Map();
   for (const [name, value] of Object.entries(module)) {
@@ -475,6 +512,16 @@ async function analyzeModule(filePath: string): Promise<AnalyzedFunctions> {
         output = JSON.parse(exportedOutput);
       }
     }
+    let errors: JSONValue | null = null;
+    if (
+      Object.prototype.hasOwnProperty.call(value, "exportErrors") &&
+      typeof (value as any).exportErrors === "function"
+    ) {
+      const exportedErrors = (value as any).exportErrors();
+      if (typeof exportedErrors === "string") {
+        errors = JSON.parse(exportedErrors);
+      }
+    }
 
     if (isPublic && isInternal) {
       logDebug(`Skipping function marked as both public and internal: ${name}`);
@@ -485,6 +532,7 @@ async function analyzeModule(filePath: string): Promise<AnalyzedFunctions> {
         visibility: { kind: "public" },
         args,
         output,
+        errors,
       });
     } else if (isInternal) {
       functions.set(name, {
@@ -492,9 +540,16 @@ async function analyzeModule(filePath: string): Promise<AnalyzedFunctions> {
         visibility: { kind: "internal" },
         args,
         output,
+        errors,
       });
     } else {
-      functions.set(name, { udfType, visibility: null, args, output });
+      functions.set(name, {
+        udfType,
+        visibility: null,
+        args,
+        output,
+        errors,
+      });
     }
   }
   // Do an awful, regex based line match that assumes that moduleConfig.source originates from
@@ -4,6 +4,7 @@ import { UserIdentity } from "convex/server";
 import { ExecutionContext, SyscallStats } from "./executor";
 import { ConvexError, JSONValue } from "convex/values";
 import { UdfPath } from "./convex";
+import { internalFetch } from "./egress";
 
 const MAX_PENDING_SYSCALLS = 1000;
 
@@ -43,6 +44,13 @@ const storageGetSchema = z.object({
   version: z.string(),
 });
 
+const storageSetMetadataSchema = z.object({
+  storageId: z.string(),
+  tags: z.optional(z.array(z.string())),
+  metadata: z.optional(z.record(z.string())),
+  version: z.string(),
+});
+
 export type ScheduledJob = z.infer<typeof scheduleSchema>;
 
 export interface Syscalls {
@@ -121,7 +129,7 @@ export class SyscallsImpl {
   }): Promise<z.infer<ResponseValidator>> {
     const headers = this.headers(args.version);
     const url = new URL(args.path, this.backendAddress);
-    const response = await fetch(url, {
+    const response = await internalFetch(url, {
       body: JSON.stringify(args.body),
       method: "POST",
       headers,
@@ -141,6 +149,41 @@ export class SyscallsImpl {
     }
   }
 
+  /**
+   * Send a result that's too large for the executor's response to the
+   * backend in chunks, returning the number of chunks sent.
+   */
+  async sendResultChunks(
+    udfReturn: string,
+    chunkSize: number,
+    version: string,
+  ): Promise<number> {
+    let index = 0;
+    let start = 0;
+    while (start < udfReturn.length) {
+      let end = Math.min(start + chunkSize, udfReturn.length);
+      // Don't split a surrogate pair across chunks.
+      const lastCode = udfReturn.charCodeAt(end - 1);
+      if (
+        end < udfReturn.length &&
+        lastCode >= 0xd800 &&
+        lastCode <= 0xdbff
+      ) {
+        end -= 1;
+      }
+      await this.actionCallback({
+        version,
+        body: { index, chunk: udfReturn.slice(start, end) },
+        path: "/api/actions/result_chunk",
+        operationName: "return result",
+        responseValidator: z.any(),
+      });
+      index += 1;
+      start = end;
+    }
+    return index;
+  }
+
   headers(version: string): Record<string, string> {
     const headers: Record<string, string> = {
       "Content-Type": "application/json",
@@ -159,6 +202,9 @@ export class SyscallsImpl {
     if (this.executionContext.isRoot !== undefined) {
       headers["Convex-Root-Request"] = this.executionContext.isRoot.toString();
     }
+    if (this.executionContext.traceparent !== undefined) {
+      headers["traceparent"] = this.executionContext.traceparent;
+    }
     if (this.authHeader !== null) {
       headers["Authorization"] = this.authHeader;
     }
@@ -304,6 +350,8 @@ export class SyscallsImpl {
           return JSON.stringify(await this.syscallStorageGetMetadata(jsonArgs));
         case "1.0/storageDelete":
           return JSON.stringify(await this.syscallStorageDelete(jsonArgs));
+        case "1.0/storageSetMetadata":
+          return JSON.stringify(await this.syscallStorageSetMetadata(jsonArgs));
         default:
           throw new Error(`Unknown operation ${op}`);
       }
@@ -523,24 +571,36 @@ export class SyscallsImpl {
   async syscallStorageGenerateUploadUrl(rawArgs: string): Promise<JSONValue> {
     const storageGenerateUploadUrlArgs = z.object({
       version: z.string(),
+      resumable: z.optional(z.boolean()),
+      maxBytes: z.optional(z.number()),
+      allowedContentTypes: z.optional(z.array(z.string())),
+      expiresInMs: z.optional(z.number()),
     });
     const operationName = "generate upload url";
-    const args = this.validateArgs(
+    const { version, ...options } = this.validateArgs(
       rawArgs,
       storageGenerateUploadUrlArgs,
       operationName,
     );
-    return this._storageGenerateUploadUrl(args.version);
+    return this._storageGenerateUploadUrl(version, options);
   }
 
-  async _storageGenerateUploadUrl(version: string): Promise<string> {
+  async _storageGenerateUploadUrl(
+    version: string,
+    options?: {
+      resumable?: boolean;
+      maxBytes?: number;
+      allowedContentTypes?: string[];
+      expiresInMs?: number;
+    },
+  ): Promise<string> {
     const storageGenerateUploadUrlReturn = z.object({
       url: z.string(),
     });
     const operationName = "generate upload url";
     const result = await this.actionCallback({
       version,
-      body: {},
+      body: options ?? {},
       path: "/api/actions/storage_generate_upload_url",
       operationName,
       responseValidator: storageGenerateUploadUrlReturn,
@@ -599,6 +659,26 @@ export class SyscallsImpl {
     });
   }
 
+  async syscallStorageSetMetadata(rawArgs: string): Promise<JSONValue> {
+    const operationName = "storage set metadata";
+    const args = this.validateArgs(
+      rawArgs,
+      storageSetMetadataSchema,
+      operationName,
+    );
+    return this.actionCallback({
+      version: args.version,
+      body: {
+        storageId: args.storageId,
+        tags: args.tags,
+        metadata: args.metadata,
+      },
+      path: "/api/actions/storage_set_metadata",
+      operationName,
+      responseValidator: z.any(),
+    });
+  }
+
   async syscallStoreBlob(args: Record<string, any>): Promise<any> {
     if (
       args["requestId"] === undefined ||
@@ -624,7 +704,7 @@ export class SyscallsImpl {
     }
 
     const uploadUrl = await this._storageGenerateUploadUrl(args["version"]);
-    const response = await fetch(uploadUrl, {
+    const response = await internalFetch(uploadUrl, {
       method: "POST",
       body: blob,
       headers: headers,
@@ -660,7 +740,7 @@ export class SyscallsImpl {
     if (getUrl === null) {
       return null;
     }
-    const getResult = await fetch(getUrl);
+    const getResult = await internalFetch(getUrl);
     return await getResult.blob();
   }
 }
@@ -0,0 +1,44 @@
+import diagnosticsChannel from "node:diagnostics_channel";
+
+// The W3C `traceparent` of the running action, which its `fetch` requests
+// carry so the services it calls can join the backend's distributed trace.
+let currentTraceparent: string | null = null;
+let installed = false;
+
+// Undici stores a request's headers as a string in older versions and as a
+// flat array of names and values in newer ones.
+function hasTraceparent(headers: string | string[]): boolean {
+  if (typeof headers === "string") {
+    return /(^|\r\n)traceparent:/i.test(headers);
+  }
+  return headers.some(
+    (header, i) => i % 2 === 0 && header.toLowerCase() === "traceparent",
+  );
+}
+
+// Both Node's `fetch` and the bundled undici publish each request they create
+// to this channel before sending it.
+function installTraceHooks() {
+  if (installed) {
+    return;
+  }
+  installed = true;
+  diagnosticsChannel
+    .channel("undici:request:create")
+    .subscribe((message: any) => {
+      const traceparent = currentTraceparent;
+      const request = message.request;
+      if (traceparent === null || hasTraceparent(request.headers)) {
+        return;
+      }
+      request.addHeader("traceparent", traceparent);
+    });
+}
+
+// Add `traceparent` to the requests the action makes until it's cleared.
+export function setTraceparent(traceparent: string | null) {
+  if (traceparent !== null) {
+    installTraceHooks();
+  }
+  currentTraceparent = traceparent;
+}
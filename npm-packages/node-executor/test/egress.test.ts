@@ -0,0 +1,22 @@
+import { allowsHost } from "../src/egress";
+import { describe, test, expect } from "vitest";
+
+describe("egress policy", () => {
+  test("allowlist", () => {
+    const policy = {
+      allowedDomains: ["example.com"],
+      deniedDomains: ["internal.example.com"],
+    };
+    expect(allowsHost(policy, "example.com")).toBe(true);
+    expect(allowsHost(policy, "API.Example.com.")).toBe(true);
+    expect(allowsHost(policy, "internal.example.com")).toBe(false);
+    expect(allowsHost(policy, "db.internal.example.com")).toBe(false);
+    expect(allowsHost(policy, "badexample.com")).toBe(false);
+  });
+
+  test("denylist", () => {
+    const policy = { allowedDomains: [], deniedDomains: ["evil.net"] };
+    expect(allowsHost(policy, "example.com")).toBe(true);
+    expect(allowsHost(policy, "www.evil.net")).toBe(false);
+  });
+});
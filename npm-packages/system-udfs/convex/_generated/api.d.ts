@@ -14,6 +14,7 @@ import type {
   FilterApi,
   FunctionReference,
 } from "convex/server";
+import type * as _system_cli_auditLog from "../_system/cli/auditLog.js";
 import type * as _system_cli_exports from "../_system/cli/exports.js";
 import type * as _system_cli_queryEnvironmentVariables from "../_system/cli/queryEnvironmentVariables.js";
 import type * as _system_cli_queryImport from "../_system/cli/queryImport.js";
@@ -32,6 +33,7 @@ import type * as _system_frontend_getById from "../_system/frontend/getById.js";
 import type * as _system_frontend_getSchemas from "../_system/frontend/getSchemas.js";
 import type * as _system_frontend_getTableMapping from "../_system/frontend/getTableMapping.js";
 import type * as _system_frontend_getVersion from "../_system/frontend/getVersion.js";
+import type * as _system_frontend_indexBackfillProgress from "../_system/frontend/indexBackfillProgress.js";
 import type * as _system_frontend_latestExport from "../_system/frontend/latestExport.js";
 import type * as _system_frontend_lib_filters from "../_system/frontend/lib/filters.js";
 import type * as _system_frontend_listAuthProviders from "../_system/frontend/listAuthProviders.js";
@@ -50,6 +52,8 @@ import type * as _system_frontend_patchDocumentsFields from "../_system/frontend
 import type * as _system_frontend_replaceDocument from "../_system/frontend/replaceDocument.js";
 import type * as _system_frontend_snapshotImport from "../_system/frontend/snapshotImport.js";
 import type * as _system_frontend_tableSize from "../_system/frontend/tableSize.js";
+import type * as _system_frontend_tableStats from "../_system/frontend/tableStats.js";
+import type * as _system_frontend_vectorIndexStats from "../_system/frontend/vectorIndexStats.js";
 import type * as _system_paginationLimits from "../_system/paginationLimits.js";
 import type * as _system_repl_wrappers from "../_system/repl/wrappers.js";
 import type * as _system_secretSystemTables from "../_system/secretSystemTables.js";
@@ -67,6 +71,7 @@ import type * as tableDefs_snapshotImport from "../tableDefs/snapshotImport.js";
  * ```
  */
 declare const fullApi: ApiFromModules<{
+  "_system/cli/auditLog": typeof _system_cli_auditLog;
   "_system/cli/exports": typeof _system_cli_exports;
   "_system/cli/queryEnvironmentVariables": typeof _system_cli_queryEnvironmentVariables;
   "_system/cli/queryImport": typeof _system_cli_queryImport;
@@ -85,6 +90,7 @@ declare const fullApi: ApiFromModules<{
   "_system/frontend/getSchemas": typeof _system_frontend_getSchemas;
   "_system/frontend/getTableMapping": typeof _system_frontend_getTableMapping;
   "_system/frontend/getVersion": typeof _system_frontend_getVersion;
+  "_system/frontend/indexBackfillProgress": typeof _system_frontend_indexBackfillProgress;
   "_system/frontend/latestExport": typeof _system_frontend_latestExport;
   "_system/frontend/lib/filters": typeof _system_frontend_lib_filters;
   "_system/frontend/listAuthProviders": typeof _system_frontend_listAuthProviders;
@@ -103,6 +109,8 @@ declare const fullApi: ApiFromModules<{
   "_system/frontend/replaceDocument": typeof _system_frontend_replaceDocument;
   "_system/frontend/snapshotImport": typeof _system_frontend_snapshotImport;
   "_system/frontend/tableSize": typeof _system_frontend_tableSize;
+  "_system/frontend/tableStats": typeof _system_frontend_tableStats;
+  "_system/frontend/vectorIndexStats": typeof _system_frontend_vectorIndexStats;
   "_system/paginationLimits": typeof _system_paginationLimits;
   "_system/repl/wrappers": typeof _system_repl_wrappers;
   "_system/secretSystemTables": typeof _system_secretSystemTables;
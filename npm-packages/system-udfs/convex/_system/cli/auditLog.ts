@@ -0,0 +1,34 @@
+import { v } from "convex/values";
+import { Doc } from "../../_generated/dataModel";
+import { queryPrivateSystem } from "../secretSystemTables";
+
+/**
+ * List deployment audit log entries from most recent to least recent,
+ * optionally only those before `beforeTimestamp` and with one of `actions`.
+ */
+export default queryPrivateSystem({
+  args: {
+    beforeTimestamp: v.optional(v.number()),
+    actions: v.optional(v.array(v.string())),
+    limit: v.optional(v.number()),
+  },
+  handler: async function (
+    { db },
+    { beforeTimestamp, actions, limit },
+  ): Promise<Doc<"_deployment_audit_log">[]> {
+    return await db
+      .query("_deployment_audit_log")
+      .withIndex("by_creation_time", (q) =>
+        beforeTimestamp === undefined
+          ? q
+          : q.lt("_creationTime", beforeTimestamp),
+      )
+      .order("desc")
+      .filter((q) =>
+        actions === undefined
+          ? true
+          : q.or(...actions.map((action) => q.eq(q.field("action"), action))),
+      )
+      .take(Math.min(limit ?? 100, 1000));
+  },
+});
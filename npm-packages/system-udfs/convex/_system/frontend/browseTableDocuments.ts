@@ -0,0 +1,206 @@
+import {
+  GenericDocument,
+  Index,
+  PaginationResult,
+  paginationOptsValidator,
+} from "convex/server";
+import { jsonToConvex, v } from "convex/values";
+import {
+  BrowsePlan,
+  BrowseSort,
+  DEFAULT_SORT,
+  SortKey,
+  chooseBrowsePlan,
+  compareSortKeys,
+  decodeCursor,
+  encodeCursor,
+  isAfterCursor,
+  matchesFilterTree,
+  parseFilterTree,
+  sortKey,
+} from "./lib/browse";
+import { parseAndFilterToSingleTable } from "./lib/filters";
+import { queryGeneric } from "../secretSystemTables";
+import { getSchemaByState } from "./getSchemas";
+import { maximumRowsRead } from "../paginationLimits";
+
+export type BrowseResult = PaginationResult<GenericDocument> & {
+  plan: BrowsePlan["type"];
+  // The index the page was read from, for the "index" plan.
+  index?: string;
+  // The number of documents read to produce the page.
+  rowsRead: number;
+  // Sorting by a field without an index reads the table in memory, up to a
+  // budget. When this is true the table has more documents than the budget,
+  // so the results only cover the most recently created ones.
+  scanLimitReached: boolean;
+};
+
+/**
+ * A page of a table's documents matching an arbitrary filter expression,
+ * sorted by any field. Filtering, sorting and pagination all happen here, so
+ * the dashboard doesn't have to over-fetch.
+ *
+ * When the sort field is the last field of an index whose other fields have
+ * equality filters, the page is read from that index and stops as soon as it
+ * has `numItems` documents or has read `maximumRowsRead` documents, so pages
+ * of a sparse filter may be short but always make progress. Otherwise the
+ * table is read up to `maximumRowsRead` documents and sorted in memory.
+ *
+ * @param filters - A b64-encoded JSON {@link FilterTree}. The dashboard's
+ *   `FilterExpression` is also accepted.
+ * @param sort - Defaults to newest first.
+ */
+export default queryGeneric({
+  args: {
+    paginationOpts: paginationOptsValidator,
+    table: v.string(),
+    filters: v.union(v.string(), v.null()),
+    sort: v.optional(
+      v.object({
+        field: v.string(),
+        order: v.union(v.literal("asc"), v.literal("desc")),
+      }),
+    ),
+    componentId: v.optional(v.union(v.string(), v.null())),
+  },
+  handler: async (
+    { db },
+    { paginationOpts, table, filters, sort },
+  ): Promise<BrowseResult> => {
+    const tree = parseFilterTree(filters);
+    const browseSort: BrowseSort = sort ?? DEFAULT_SORT;
+    const numItems = Math.max(paginationOpts.numItems, 1);
+    const cursor = decodeCursor(paginationOpts.cursor);
+    const matches = (doc: GenericDocument) =>
+      tree === null || matchesFilterTree(doc, tree);
+
+    const schemaData = await getSchemaByState(
+      (db as any).privateSystem,
+      "active",
+    );
+    const indexes: Index[] = schemaData?.schema
+      ? parseAndFilterToSingleTable(table, schemaData.schema)?.tables[0]
+          ?.indexes || []
+      : [];
+    const plan = chooseBrowsePlan(tree, browseSort, indexes);
+
+    if (plan.type === "get") {
+      const id = db.normalizeId(table, plan.id);
+      const doc = id === null ? null : await db.get(id);
+      return {
+        page: doc !== null && cursor === null && matches(doc) ? [doc] : [],
+        isDone: true,
+        continueCursor: "",
+        plan: plan.type,
+        rowsRead: doc === null ? 0 : 1,
+        scanLimitReached: false,
+      };
+    }
+
+    if (plan.type === "index") {
+      const query = db
+        .query(table)
+        .withIndex(plan.index, (q: any) => {
+          let range = q;
+          for (const filter of plan.eqFilters) {
+            range = range.eq(filter.field, jsonToConvex(filter.value));
+          }
+          // Start at the cursor's sort value. Documents with the same value
+          // that were on the previous page are skipped below.
+          if (cursor !== null && cursor.value !== undefined) {
+            range =
+              browseSort.order === "asc"
+                ? range.gte(browseSort.field, cursor.value)
+                : range.lte(browseSort.field, cursor.value);
+          }
+          return range;
+        })
+        .order(browseSort.order);
+      const page: GenericDocument[] = [];
+      let rowsRead = 0;
+      let lastKey: SortKey | null = cursor;
+      let isDone = true;
+      try {
+        for await (const doc of query) {
+          if (rowsRead >= maximumRowsRead) {
+            isDone = false;
+            break;
+          }
+          rowsRead += 1;
+          const key = sortKey(doc, browseSort.field);
+          if (!isAfterCursor(key, cursor, browseSort.order)) {
+            continue;
+          }
+          lastKey = key;
+          if (matches(doc)) {
+            page.push(doc);
+          }
+          if (page.length >= numItems) {
+            isDone = false;
+            break;
+          }
+        }
+      } catch (error: any) {
+        // Stop early if the documents read so far are too large for one
+        // transaction, like hitting the row budget.
+        if (!isReadLimitError(error)) {
+          throw error;
+        }
+        isDone = false;
+      }
+      const continueCursor =
+        !isDone && lastKey !== null ? encodeCursor(lastKey) : "";
+      return {
+        page,
+        isDone,
+        continueCursor,
+        plan: plan.type,
+        index: plan.index,
+        rowsRead,
+        scanLimitReached: false,
+      };
+    }
+
+    const matching: { key: SortKey; doc: GenericDocument }[] = [];
+    let rowsRead = 0;
+    let scanLimitReached = false;
+    try {
+      for await (const doc of db.query(table).order("desc")) {
+        if (rowsRead >= maximumRowsRead) {
+          scanLimitReached = true;
+          break;
+        }
+        rowsRead += 1;
+        if (matches(doc)) {
+          matching.push({ key: sortKey(doc, browseSort.field), doc });
+        }
+      }
+    } catch (error: any) {
+      if (!isReadLimitError(error)) {
+        throw error;
+      }
+      scanLimitReached = true;
+    }
+    const direction = browseSort.order === "asc" ? 1 : -1;
+    const remaining = matching
+      .filter(({ key }) => isAfterCursor(key, cursor, browseSort.order))
+      .sort((a, b) => direction * compareSortKeys(a.key, b.key));
+    const page = remaining.slice(0, numItems);
+    const isDone = remaining.length <= numItems;
+    return {
+      page: page.map(({ doc }) => doc),
+      isDone,
+      continueCursor: isDone ? "" : encodeCursor(page[page.length - 1].key),
+      plan: plan.type,
+      rowsRead,
+      scanLimitReached,
+    };
+  },
+});
+
+function isReadLimitError(error: any) {
+  return (
+    typeof error?.message === "string" && error.message.includes("too large")
+  );
+}
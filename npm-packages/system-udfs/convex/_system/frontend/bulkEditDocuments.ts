@@ -0,0 +1,122 @@
+import { GenericDatabaseReader, GenericDocument } from "convex/server";
+import { ConvexError, GenericId, Value, v } from "convex/values";
+import { mutationGeneric, queryGeneric } from "../server";
+import { matchesFilterTree, parseFilterTree } from "./lib/browse";
+import { UNDEFINED_PLACEHOLDER } from "./patchDocumentsFields";
+
+export const MAX_BULK_EDIT_BATCH_SIZE = 4000;
+
+export type BulkEditBatchResult = {
+  // The number of documents read in this batch.
+  scanned: number;
+  // The number of them that matched the filters, and were edited unless this
+  // was a preview.
+  matched: number;
+  continueCursor: string;
+  isDone: boolean;
+};
+
+const batchArgs = {
+  table: v.string(),
+  // A b64-encoded JSON `FilterTree`, like `browseTableDocuments` takes.
+  filters: v.union(v.string(), v.null()),
+  cursor: v.union(v.string(), v.null()),
+  batchSize: v.number(),
+  componentId: v.optional(v.union(v.string(), v.null())),
+};
+
+async function readBatch(
+  db: GenericDatabaseReader<any>,
+  args: {
+    table: string;
+    filters: string | null;
+    cursor: string | null;
+    batchSize: number;
+  },
+) {
+  const tree = parseFilterTree(args.filters);
+  const numItems = Math.min(
+    Math.max(Math.floor(args.batchSize), 1),
+    MAX_BULK_EDIT_BATCH_SIZE,
+  );
+  // Documents are read in creation order, which edits don't change, so the
+  // cursor stays valid between batches.
+  const { page, continueCursor, isDone } = await db
+    .query(args.table)
+    .withIndex("by_creation_time")
+    .order("asc")
+    .paginate({
+      numItems,
+      cursor: args.cursor,
+      // Like `clearTablePage`, leave room for writing every document read.
+      maximumBytesRead: 3000000,
+    });
+  const matching = page.filter(
+    (doc: GenericDocument) => tree === null || matchesFilterTree(doc, tree),
+  );
+  return { scanned: page.length, matching, continueCursor, isDone };
+}
+
+/**
+ * Counts the documents in one batch of a table that match the filters, for
+ * previewing a bulk edit. Call it again with `continueCursor` until `isDone`
+ * to count the whole table.
+ */
+export const preview = queryGeneric({
+  args: batchArgs,
+  handler: async ({ db }, args): Promise<BulkEditBatchResult> => {
+    const { scanned, matching, continueCursor, isDone } = await readBatch(
+      db,
+      args,
+    );
+    return { scanned, matched: matching.length, continueCursor, isDone };
+  },
+});
+
+/**
+ * Deletes or patches the documents in one batch of a table that match the
+ * filters. Call it again with `continueCursor` until `isDone` to edit the
+ * whole table.
+ *
+ * Patched fields set to `UNDEFINED_PLACEHOLDER` are removed.
+ */
+export default mutationGeneric({
+  args: {
+    ...batchArgs,
+    operation: v.union(
+      v.object({ type: v.literal("delete") }),
+      v.object({ type: v.literal("patch"), fields: v.any() }),
+    ),
+  },
+  handler: async ({ db }, args): Promise<BulkEditBatchResult> => {
+    const { scanned, matching, continueCursor, isDone } = await readBatch(
+      db,
+      args,
+    );
+    const { operation } = args;
+    try {
+      if (operation.type === "delete") {
+        await Promise.all(
+          matching.map((doc) => db.delete(doc._id as GenericId<string>)),
+        );
+      } else {
+        const fields = operation.fields as Record<string, Value>;
+        const patchFields: Record<string, Value | undefined> = {};
+        for (const key in fields) {
+          const value = fields[key];
+          patchFields[key] =
+            value === UNDEFINED_PLACEHOLDER ? undefined : value;
+        }
+        await Promise.all(
+          matching.map((doc) =>
+            db.patch(doc._id as GenericId<string>, patchFields),
+          ),
+        );
+      }
+    } catch (e: any) {
+      // Rewrapping this error because it could be a schema validation error.
+      throw new ConvexError(e.message);
+    }
+    return { scanned, matched: matching.length, continueCursor, isDone };
+  },
+});
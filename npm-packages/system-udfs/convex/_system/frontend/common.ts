@@ -98,6 +98,10 @@ export type CronJobWithLastRun = Doc<"_cron_jobs"> & {
   lastRun: CronJobLog | null | undefined;
 };
 
+export type ErrorGroup = Doc<"_error_groups">;
+
+export type SlowQuery = Doc<"_slow_queries">;
+
 export type Modules = Map<string, Module>;
 
 export type CompletedExport = Infer<typeof completedExport>;
@@ -78,3 +78,22 @@ export const generateUploadUrl = mutationGeneric({
     return await storage.generateUploadUrl();
   },
 });
+
+export const quarantinedFiles = queryGeneric({
+  args: {
+    paginationOpts: paginationOptsValidator,
+    componentId: v.optional(v.union(v.string(), v.null())),
+  },
+  handler: async (
+    { db },
+    { paginationOpts },
+  ): Promise<PaginationResult<SystemDataModel["_storage"]["document"]>> => {
+    // Files the file scanner flagged on upload. They're kept, but can't be
+    // downloaded.
+    return await db.system
+      .query("_storage")
+      .order("desc")
+      .filter((q) => q.eq(q.field("scanStatus"), "blocked"))
+      .paginate(paginationOpts);
+  },
+});
@@ -0,0 +1,68 @@
+import { v } from "convex/values";
+import { queryPrivateSystem } from "../secretSystemTables";
+import { UdfType } from "./common";
+
+type FunctionDependencies = {
+  // e.g. "messages.js:send"
+  path: string;
+  udfType: UdfType;
+  readsTables: string[];
+  writesTables: string[];
+  calls: string[];
+  schedules: string[];
+  // Functions that call or schedule this one.
+  calledBy: string[];
+  readsUnknownTables: boolean;
+  writesUnknownTables: boolean;
+  referencesUnknownFunctions: boolean;
+};
+
+/**
+ * The tables each function reads and writes and the functions it calls and
+ * schedules, from static analysis of the pushed code.
+ *
+ * With `table`, only returns the functions that might use it: those that name
+ * it, and those that access documents without naming their table.
+ */
+export const list = queryPrivateSystem({
+  args: { table: v.optional(v.string()) },
+  handler: async ({ db }, { table }): Promise<FunctionDependencies[]> => {
+    const functions: FunctionDependencies[] = [];
+    for await (const module of db.query("_modules")) {
+      if (module.path.startsWith("_")) {
+        continue;
+      }
+      for (const f of module.analyzeResult?.functions ?? []) {
+        // Functions analyzed before dependency analysis was added, and Node
+        // actions, don't have dependencies.
+        if (!f.dependencies) {
+          continue;
+        }
+        functions.push({
+          path: `${module.path}:${f.name}`,
+          udfType: f.udfType,
+          ...f.dependencies,
+          calledBy: [],
+        });
+      }
+    }
+
+    const byPath = new Map(functions.map((f) => [f.path, f]));
+    for (const f of functions) {
+      for (const callee of new Set([...f.calls, ...f.schedules])) {
+        byPath.get(callee)?.calledBy.push(f.path);
+      }
+    }
+
+    if (table === undefined) {
+      return functions;
+    }
+    return functions.filter(
+      (f) =>
+        f.readsTables.includes(table) ||
+        f.writesTables.includes(table) ||
+        f.readsUnknownTables ||
+        f.writesUnknownTables,
+    );
+  },
+});
@@ -0,0 +1,16 @@
+import { v } from "convex/values";
+import { queryPrivateSystem } from "../secretSystemTables";
+import { ErrorGroup } from "./common";
+
+export default queryPrivateSystem({
+  args: { fingerprint: v.string() },
+  handler: async function (
+    { db },
+    { fingerprint },
+  ): Promise<ErrorGroup | null> {
+    return await db
+      .query("_error_groups")
+      .withIndex("by_fingerprint", (q) => q.eq("fingerprint", fingerprint))
+      .unique();
+  },
+});
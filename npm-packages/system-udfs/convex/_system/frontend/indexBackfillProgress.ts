@@ -0,0 +1,25 @@
+import { v } from "convex/values";
+import { queryGeneric } from "../secretSystemTables";
+import { IndexBackfillProgress, tableStats } from "./tableStats";
+
+/**
+ * Progress of the database indexes on a table that are backfilling. Progress
+ * covers the pass over the table's current documents, and the ETA assumes it
+ * continues at its average rate so far.
+ */
+export default queryGeneric({
+  args: {
+    tableName: v.string(),
+    componentId: v.optional(v.union(v.string(), v.null())),
+  },
+  handler: async function (
+    _ctx,
+    { tableName },
+  ): Promise<{ name: string; backfill: IndexBackfillProgress | null }[]> {
+    const { indexes } = await tableStats(tableName);
+    return indexes
+      .filter((index) => index.type === "database")
+      .filter((index) => index.state === "backfilling")
+      .map(({ name, backfill }) => ({ name, backfill }));
+  },
+});
@@ -0,0 +1,222 @@
+import { Index } from "convex/server";
+import {
+  FilterTree,
+  FilterTreeSchema,
+  chooseBrowsePlan,
+  compareValues,
+  decodeCursor,
+  encodeCursor,
+  findErrorsInFilterTree,
+  isAfterCursor,
+  matchesFilterTree,
+  parseFilterTree,
+} from "./browse";
+import { encode } from "js-base64";
+
+const index = (name: string, fields: string[]): Index => ({
+  indexDescriptor: name,
+  fields,
+});
+
+describe("browse", () => {
+  describe("compareValues", () => {
+    it("should order values like indexes do", () => {
+      const ordered = [
+        undefined,
+        null,
+        BigInt(-1),
+        BigInt(5),
+        -1,
+        2.5,
+        false,
+        true,
+        "a",
+        "b",
+        new ArrayBuffer(1),
+        [1],
+        [1, 2],
+        { a: 1 },
+        { b: 0 },
+      ];
+      for (let i = 0; i < ordered.length; i++) {
+        expect(compareValues(ordered[i], ordered[i])).toEqual(0);
+        for (let j = i + 1; j < ordered.length; j++) {
+          expect(compareValues(ordered[i], ordered[j])).toBeLessThan(0);
+          expect(compareValues(ordered[j], ordered[i])).toBeGreaterThan(0);
+        }
+      }
+    });
+  });
+
+  describe("matchesFilterTree", () => {
+    const doc = {
+      _id: "abc",
+      _creationTime: 1,
+      name: "ada",
+      age: BigInt(36),
+      address: { city: "London" },
+    };
+
+    it("should evaluate nested groups", () => {
+      const tree: FilterTree = {
+        op: "or",
+        clauses: [
+          { op: "eq", field: "name", value: "grace" },
+          {
+            clauses: [
+              { op: "gte", field: "age", value: { $integer: "JAAAAAAAAAA=" } },
+              { op: "eq", field: "address.city", value: "London" },
+            ],
+          },
+        ],
+      };
+      expect(matchesFilterTree(doc, tree)).toBe(true);
+      expect(matchesFilterTree(doc, { op: "not", clause: tree })).toBe(false);
+    });
+
+    it("should support type and list filters", () => {
+      expect(
+        matchesFilterTree(doc, { op: "type", field: "age", value: "bigint" }),
+      ).toBe(true);
+      expect(
+        matchesFilterTree(doc, {
+          op: "notype",
+          field: "missing",
+          value: "unset",
+        }),
+      ).toBe(false);
+      expect(
+        matchesFilterTree(doc, {
+          op: "anyOf",
+          field: "name",
+          value: ["ada", "grace"],
+        }),
+      ).toBe(true);
+      expect(
+        matchesFilterTree(doc, { op: "noneOf", field: "name", value: ["ada"] }),
+      ).toBe(false);
+    });
+
+    it("should ignore incomplete filters", () => {
+      expect(matchesFilterTree(doc, { op: "eq", field: "name" })).toBe(true);
+    });
+  });
+
+  describe("FilterTreeSchema", () => {
+    it("should accept filter expressions and nested trees", () => {
+      expect(
+        FilterTreeSchema.safeParse({
+          clauses: [{ op: "eq", field: "a", value: 1 }],
+        }).success,
+      ).toBe(true);
+      expect(
+        FilterTreeSchema.safeParse({
+          op: "not",
+          clause: { op: "or", clauses: [{ op: "lt", field: "a", value: 1 }] },
+        }).success,
+      ).toBe(true);
+      expect(
+        FilterTreeSchema.safeParse({ op: "xor", clauses: [] }).success,
+      ).toBe(false);
+    });
+
+    it("should parse b64-encoded filter arguments", () => {
+      const tree = { clauses: [{ op: "eq", field: "a", value: 1 }] };
+      expect(parseFilterTree(encode(JSON.stringify(tree)))).toEqual(tree);
+      expect(parseFilterTree(null)).toBeNull();
+      expect(() =>
+        parseFilterTree(encode(JSON.stringify({ op: "xor", clauses: [] }))),
+      ).toThrow("Invalid filters");
+    });
+
+    it("should find invalid values", () => {
+      expect(
+        findErrorsInFilterTree({
+          op: "not",
+          clause: { op: "eq", field: "a", value: { $bad: 1 } },
+        }),
+      ).toEqual(['Invalid value: {"$bad":1}.']);
+    });
+  });
+
+  describe("chooseBrowsePlan", () => {
+    const indexes = [
+      index("by_author", ["author"]),
+      index("by_channel_and_author", ["channel", "author"]),
+      index("by_score", ["score"]),
+    ];
+    const eq = (field: string, value: string) => ({
+      op: "eq" as const,
+      field,
+      value,
+    });
+
+    it("should use the creation time index by default", () => {
+      expect(
+        chooseBrowsePlan(null, { field: "_creationTime", order: "desc" }, []),
+      ).toEqual({ type: "index", index: "by_creation_time", eqFilters: [] });
+    });
+
+    it("should use the longest index covered by equality filters", () => {
+      const tree = { clauses: [eq("author", "ada"), eq("channel", "c")] };
+      expect(
+        chooseBrowsePlan(
+          tree,
+          { field: "_creationTime", order: "desc" },
+          indexes,
+        ),
+      ).toEqual({
+        type: "index",
+        index: "by_channel_and_author",
+        eqFilters: [eq("channel", "c"), eq("author", "ada")],
+      });
+      expect(
+        chooseBrowsePlan(tree, { field: "author", order: "asc" }, indexes),
+      ).toEqual({
+        type: "index",
+        index: "by_channel_and_author",
+        eqFilters: [eq("channel", "c")],
+      });
+    });
+
+    it("should scan without a matching index", () => {
+      expect(
+        chooseBrowsePlan(null, { field: "score", order: "asc" }, indexes),
+      ).toEqual({ type: "index", index: "by_score", eqFilters: [] });
+      expect(
+        chooseBrowsePlan(null, { field: "title", order: "asc" }, indexes),
+      ).toEqual({ type: "scan" });
+      // Equality filters under "or" don't narrow the index range.
+      const tree = { op: "or" as const, clauses: [eq("a", "1"), eq("b", "2")] };
+      expect(
+        chooseBrowsePlan(tree, { field: "author", order: "asc" }, indexes),
+      ).toEqual({ type: "index", index: "by_author", eqFilters: [] });
+    });
+
+    it("should get documents by ID", () => {
+      expect(
+        chooseBrowsePlan(
+          { clauses: [eq("_id", "abc")] },
+          { field: "score", order: "asc" },
+          indexes,
+        ),
+      ).toEqual({ type: "get", id: "abc" });
+    });
+  });
+
+  describe("cursors", () => {
+    it("should round trip and compare sort keys", () => {
+      const key = { value: BigInt(3), creationTime: 10, id: "b" };
+      expect(decodeCursor(encodeCursor(key))).toEqual(key);
+      const missing = { value: undefined, creationTime: 10, id: "b" };
+      expect(decodeCursor(encodeCursor(missing))).toEqual(missing);
+      expect(decodeCursor("")).toBeNull();
+
+      const later = { value: BigInt(3), creationTime: 11, id: "a" };
+      expect(isAfterCursor(later, key, "asc")).toBe(true);
+      expect(isAfterCursor(later, key, "desc")).toBe(false);
+      expect(isAfterCursor(key, key, "asc")).toBe(false);
+      expect(isAfterCursor(key, null, "desc")).toBe(true);
+    });
+  });
+});
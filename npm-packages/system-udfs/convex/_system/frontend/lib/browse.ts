@@ -0,0 +1,395 @@
+import {
+  ConvexError,
+  convexToJson,
+  jsonToConvex,
+  JSONValue,
+  Value,
+} from "convex/values";
+import type { GenericDocument, Index } from "convex/server";
+import { decode, encode } from "js-base64";
+import { z } from "zod";
+import {
+  Filter,
+  FilterClauseSchema,
+  TypeFilterValue,
+  ValidFilterByBuiltin,
+  isType,
+  isTypeFilterOp,
+  isValidFilter,
+} from "./filters";
+
+// A filter expression for browsing a table. Clauses can be grouped with "and"
+// and "or" and negated with "not" to any depth. The dashboard's existing
+// `FilterExpression` is an "and" group.
+export type FilterTree =
+  | Filter
+  | { op?: "and" | "or"; clauses: FilterTree[] }
+  | { op: "not"; clause: FilterTree };
+
+export const FilterTreeSchema: z.ZodType<FilterTree> = z.lazy(() =>
+  z.union([
+    z.object({
+      op: z.union([z.literal("and"), z.literal("or")]).optional(),
+      clauses: z.array(FilterTreeSchema),
+    }),
+    z.object({ op: z.literal("not"), clause: FilterTreeSchema }),
+    FilterClauseSchema,
+  ]),
+);
+
+export type SortOrder = "asc" | "desc";
+
+export type BrowseSort = {
+  // A field path like "author.name". Defaults to "_creationTime".
+  field: string;
+  order: SortOrder;
+};
+
+export const DEFAULT_SORT: BrowseSort = {
+  field: "_creationTime",
+  order: "desc",
+};
+
+// How documents are read for a page.
+export type BrowsePlan =
+  | { type: "get"; id: string }
+  // Read an index in sort order, with equality filters on its leading fields.
+  | { type: "index"; index: string; eqFilters: ValidFilterByBuiltin[] }
+  // Read up to the scan budget by creation time and sort in memory.
+  | { type: "scan" };
+
+// The position of a document in the sort order. Index order breaks ties with
+// `_creationTime` and then `_id`, so sorting in memory does too.
+export type SortKey = {
+  value: Value | undefined;
+  creationTime: number;
+  id: string;
+};
+
+function isGroup(
+  tree: FilterTree,
+): tree is { op?: "and" | "or"; clauses: FilterTree[] } {
+  return "clauses" in tree;
+}
+
+function isNot(tree: FilterTree): tree is { op: "not"; clause: FilterTree } {
+  return tree.op === "not";
+}
+
+function leaves(tree: FilterTree): Filter[] {
+  if (isGroup(tree)) {
+    return tree.clauses.flatMap(leaves);
+  }
+  if (isNot(tree)) {
+    return leaves(tree.clause);
+  }
+  return [tree];
+}
+
+// Messages describing filter values that aren't valid Convex values.
+export function findErrorsInFilterTree(tree: FilterTree): string[] {
+  const errors = [];
+  for (const filter of leaves(tree)) {
+    const values = Array.isArray(filter.value) ? filter.value : [filter.value];
+    for (const value of values) {
+      if (value === undefined || isTypeFilterOp(filter.op)) {
+        continue;
+      }
+      try {
+        jsonToConvex(value as JSONValue);
+      } catch (e) {
+        errors.push(`Invalid value: ${JSON.stringify(value)}.`);
+      }
+    }
+  }
+  return errors;
+}
+
+// Parse a b64-encoded JSON filter tree argument, throwing a `ConvexError` if
+// it's invalid.
+export function parseFilterTree(filters: string | null): FilterTree | null {
+  if (!filters) {
+    return null;
+  }
+  const parsed = FilterTreeSchema.safeParse(JSON.parse(decode(filters)));
+  if (!parsed.success) {
+    throw new ConvexError(`Invalid filters: ${parsed.error.message}`);
+  }
+  const errors = findErrorsInFilterTree(parsed.data);
+  if (errors.length > 0) {
+    throw new ConvexError(errors.join(" "));
+  }
+  return parsed.data;
+}
+
+// The ordering of values in Convex indexes.
+function typeRank(value: Value | undefined): number {
+  if (value === undefined) {
+    return 0;
+  } else if (value === null) {
+    return 1;
+  } else if (typeof value === "bigint") {
+    return 2;
+  } else if (typeof value === "number") {
+    return 3;
+  } else if (typeof value === "boolean") {
+    return 4;
+  } else if (typeof value === "string") {
+    return 5;
+  } else if (value instanceof ArrayBuffer) {
+    return 6;
+  } else if (Array.isArray(value)) {
+    return 7;
+  }
+  return 8;
+}
+
+function compareScalars<T extends number | bigint | string | boolean>(
+  a: T,
+  b: T,
+): number {
+  return a < b ? -1 : a > b ? 1 : 0;
+}
+
+export function compareValues(
+  a: Value | undefined,
+  b: Value | undefined,
+): number {
+  const rankDiff = typeRank(a) - typeRank(b);
+  if (rankDiff !== 0) {
+    return rankDiff;
+  }
+  if (a instanceof ArrayBuffer && b instanceof ArrayBuffer) {
+    const x = new Uint8Array(a);
+    const y = new Uint8Array(b);
+    for (let i = 0; i < Math.min(x.length, y.length); i++) {
+      if (x[i] !== y[i]) {
+        return x[i] - y[i];
+      }
+    }
+    return x.length - y.length;
+  }
+  if (Array.isArray(a) && Array.isArray(b)) {
+    for (let i = 0; i < Math.min(a.length, b.length); i++) {
+      const diff = compareValues(a[i], b[i]);
+      if (diff !== 0) {
+        return diff;
+      }
+    }
+    return a.length - b.length;
+  }
+  if (typeof a === "object" && a !== null && typeof b === "object") {
+    const x = Object.entries(a).sort(([k1], [k2]) => compareScalars(k1, k2));
+    const y = Object.entries(b as object).sort(([k1], [k2]) =>
+      compareScalars(k1, k2),
+    );
+    for (let i = 0; i < Math.min(x.length, y.length); i++) {
+      const diff =
+        compareScalars(x[i][0], y[i][0]) || compareValues(x[i][1], y[i][1]);
+      if (diff !== 0) {
+        return diff;
+      }
+    }
+    return x.length - y.length;
+  }
+  return compareScalars(a as any, b as any);
+}
+
+// The value at a field path like "author.name", or undefined if it's missing.
+export function getField(
+  doc: GenericDocument,
+  fieldPath: string,
+): Value | undefined {
+  let value: Value | undefined = doc;
+  for (const field of fieldPath.split(".")) {
+    if (
+      typeof value !== "object" ||
+      value === null ||
+      Array.isArray(value) ||
+      value instanceof ArrayBuffer
+    ) {
+      return undefined;
+    }
+    value = value[field];
+  }
+  return value;
+}
+
+const COMPARISONS: Record<
+  "eq" | "neq" | "lt" | "lte" | "gt" | "gte",
+  (diff: number) => boolean
+> = {
+  eq: (diff) => diff === 0,
+  neq: (diff) => diff !== 0,
+  lt: (diff) => diff < 0,
+  lte: (diff) => diff <= 0,
+  gt: (diff) => diff > 0,
+  gte: (diff) => diff >= 0,
+};
+
+function matchesFilter(doc: GenericDocument, filter: Filter): boolean {
+  // Incomplete filters from the filter editor match everything.
+  if (!isValidFilter(filter)) {
+    return true;
+  }
+  const value = getField(doc, filter.field);
+  switch (filter.op) {
+    case "type":
+      return isType(value as Value, filter.value as TypeFilterValue);
+    case "notype":
+      return !isType(value as Value, filter.value as TypeFilterValue);
+    case "anyOf":
+    case "noneOf": {
+      const values = filter.value as JSONValue[];
+      // Like the dashboard's filters, an empty list doesn't filter anything.
+      if (values.length === 0) {
+        return true;
+      }
+      const found = values.some(
+        (v) => compareValues(value, jsonToConvex(v)) === 0,
+      );
+      return filter.op === "anyOf" ? found : !found;
+    }
+    default: {
+      const diff = compareValues(
+        value,
+        jsonToConvex(filter.value as JSONValue),
+      );
+      return COMPARISONS[filter.op](diff);
+    }
+  }
+}
+
+export function matchesFilterTree(
+  doc: GenericDocument,
+  tree: FilterTree,
+): boolean {
+  if (isGroup(tree)) {
+    return tree.op === "or"
+      ? tree.clauses.length === 0 ||
+          tree.clauses.some((c) => matchesFilterTree(doc, c))
+      : tree.clauses.every((c) => matchesFilterTree(doc, c));
+  }
+  if (isNot(tree)) {
+    return !matchesFilterTree(doc, tree.clause);
+  }
+  return matchesFilter(doc, tree);
+}
+
+// Equality filters every matching document must satisfy, which can narrow
+// the range of an index.
+function requiredEqFilters(tree: FilterTree): ValidFilterByBuiltin[] {
+  if (isGroup(tree)) {
+    if (tree.op === "or") {
+      return tree.clauses.length === 1
+        ? requiredEqFilters(tree.clauses[0])
+        : [];
+    }
+    return tree.clauses.flatMap(requiredEqFilters);
+  }
+  if (!isNot(tree) && tree.op === "eq" && isValidFilter(tree)) {
+    return [tree as ValidFilterByBuiltin];
+  }
+  return [];
+}
+
+// Choose how to read a page of documents matching `tree` in `sort` order.
+// Prefers an index whose leading fields all have equality filters and whose
+// last field is the sort field, because it returns matching documents in
+// order without reading the rest of the table. Sorting by `_creationTime` can
+// use the built-in creation time index, and any index whose fields all have
+// equality filters.
+export function chooseBrowsePlan(
+  tree: FilterTree | null,
+  sort: BrowseSort,
+  indexes: Index[],
+): BrowsePlan {
+  const eqFilters = tree ? requiredEqFilters(tree) : [];
+  const idFilter = eqFilters.find((f) => f.field === "_id");
+  if (idFilter && typeof idFilter.value === "string") {
+    return { type: "get", id: idFilter.value };
+  }
+  const eqByField = new Map(eqFilters.map((f) => [f.field, f]));
+  let best: BrowsePlan | null = null;
+  let bestLength = -1;
+  for (const index of indexes) {
+    const prefix =
+      sort.field === "_creationTime" ? index.fields : index.fields.slice(0, -1);
+    if (
+      sort.field !== "_creationTime" &&
+      index.fields[index.fields.length - 1] !== sort.field
+    ) {
+      continue;
+    }
+    if (!prefix.every((field) => eqByField.has(field))) {
+      continue;
+    }
+    if (prefix.length > bestLength) {
+      bestLength = prefix.length;
+      best = {
+        type: "index",
+        index: index.indexDescriptor,
+        eqFilters: prefix.map((field) => eqByField.get(field)!),
+      };
+    }
+  }
+  if (best !== null) {
+    return best;
+  }
+  if (sort.field === "_creationTime") {
+    return { type: "index", index: "by_creation_time", eqFilters: [] };
+  }
+  return { type: "scan" };
+}
+
+export function sortKey(doc: GenericDocument, field: string): SortKey {
+  return {
+    value: getField(doc, field),
+    creationTime: doc._creationTime as number,
+    id: doc._id as string,
+  };
+}
+
+export function compareSortKeys(a: SortKey, b: SortKey): number {
+  return (
+    compareValues(a.value, b.value) ||
+    compareScalars(a.creationTime, b.creationTime) ||
+    compareScalars(a.id, b.id)
+  );
+}
+
+// Whether `key` comes after `cursor` in `order`, i.e. belongs on a later page.
+export function isAfterCursor(
+  key: SortKey,
+  cursor: SortKey | null,
+  order: SortOrder,
+): boolean {
+  if (cursor === null) {
+    return true;
+  }
+  const diff = compareSortKeys(key, cursor);
+  return order === "asc" ? diff > 0 : diff < 0;
+}
+
+export function encodeCursor(key: SortKey): string {
+  return encode(
+    JSON.stringify({
+      value: key.value === undefined ? null : convexToJson(key.value),
+      missing: key.value === undefined,
+      creationTime: key.creationTime,
+      id: key.id,
+    }),
+  );
+}
+
+export function decodeCursor(cursor: string | null): SortKey | null {
+  if (!cursor) {
+    return null;
+  }
+  const parsed = JSON.parse(decode(cursor));
+  return {
+    value: parsed.missing ? undefined : jsonToConvex(parsed.value),
+    creationTime: parsed.creationTime,
+    id: parsed.id,
+  };
+}
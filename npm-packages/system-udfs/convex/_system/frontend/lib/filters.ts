@@ -81,35 +81,35 @@ const TypeFilterSchema = TypeFilterValueKeys.map<ZodLiteral<string>>((k) =>
   z.literal(k),
 );
 
-const FilterSchema = z.array(
-  z.union([
-    z.object({
-      op: z.union([
-        z.literal("eq"),
-        z.literal("neq"),
-        z.literal("gte"),
-        z.literal("lte"),
-        z.literal("gt"),
-        z.literal("lt"),
-      ]),
-      field: z.string().optional(),
-      value: z.any().optional(),
-      id: z.string().optional(),
-    }),
-    z.object({
-      op: z.union([z.literal("type"), z.literal("notype")]),
-      field: z.string().optional(),
-      // @ts-expect-error I don't know how to fix this type error,
-      // but i'll test to make sure this works.
-      value: z.union(TypeFilterSchema).optional(),
-    }),
-    z.object({
-      op: z.union([z.literal("anyOf"), z.literal("noneOf")]),
-      field: z.string().optional(),
-      value: z.array(z.any()).optional(),
-    }),
-  ]),
-);
+export const FilterClauseSchema = z.union([
+  z.object({
+    op: z.union([
+      z.literal("eq"),
+      z.literal("neq"),
+      z.literal("gte"),
+      z.literal("lte"),
+      z.literal("gt"),
+      z.literal("lt"),
+    ]),
+    field: z.string().optional(),
+    value: z.any().optional(),
+    id: z.string().optional(),
+  }),
+  z.object({
+    op: z.union([z.literal("type"), z.literal("notype")]),
+    field: z.string().optional(),
+    // @ts-expect-error I don't know how to fix this type error,
+    // but i'll test to make sure this works.
+    value: z.union(TypeFilterSchema).optional(),
+  }),
+  z.object({
+    op: z.union([z.literal("anyOf"), z.literal("noneOf")]),
+    field: z.string().optional(),
+    value: z.array(z.any()).optional(),
+  }),
+]);
+
+const FilterSchema = z.array(FilterClauseSchema);
 
 export const FilterExpressionSchema: z.ZodType<FilterExpression> = z.lazy(() =>
   z.object({
@@ -126,7 +126,7 @@ export type ValidFilter =
 export const isValidFilter = (f: Filter): f is ValidFilter =>
   f.field !== undefined && f.value !== undefined;
 
-const isType = (value: Value, type: TypeFilterValue): boolean => {
+export const isType = (value: Value, type: TypeFilterValue): boolean => {
   switch (type) {
     case "null":
       return value === null;
@@ -0,0 +1,32 @@
+import { PaginationResult, paginationOptsValidator } from "convex/server";
+import { v } from "convex/values";
+import { queryPrivateSystem } from "../secretSystemTables";
+import { maximumBytesRead, maximumRowsRead } from "../paginationLimits";
+import { ErrorGroup } from "./common";
+
+/**
+ * Paginated query for groups of function errors, most recently seen first.
+ * `minLastSeen` is in nanoseconds since the Unix epoch.
+ */
+export default queryPrivateSystem({
+  args: {
+    paginationOpts: paginationOptsValidator,
+    minLastSeen: v.optional(v.int64()),
+  },
+  handler: async function (
+    { db },
+    { paginationOpts, minLastSeen },
+  ): Promise<PaginationResult<ErrorGroup>> {
+    return await db
+      .query("_error_groups")
+      .withIndex("by_last_seen", (q) =>
+        minLastSeen === undefined ? q : q.gte("lastSeen", minLastSeen),
+      )
+      .order("desc")
+      .paginate({
+        ...paginationOpts,
+        maximumBytesRead,
+        maximumRowsRead,
+      });
+  },
+});
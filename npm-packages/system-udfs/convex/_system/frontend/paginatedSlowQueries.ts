@@ -0,0 +1,34 @@
+import { PaginationResult, paginationOptsValidator } from "convex/server";
+import { v } from "convex/values";
+import { queryPrivateSystem } from "../secretSystemTables";
+import { maximumBytesRead, maximumRowsRead } from "../paginationLimits";
+import { SlowQuery } from "./common";
+
+/**
+ * Paginated query for executions that took longer than the slow query
+ * threshold, newest first, optionally only those of `functionName`.
+ */
+export default queryPrivateSystem({
+  args: {
+    paginationOpts: paginationOptsValidator,
+    functionName: v.optional(v.string()),
+  },
+  handler: async function (
+    { db },
+    { paginationOpts, functionName },
+  ): Promise<PaginationResult<SlowQuery>> {
+    const query =
+      functionName === undefined
+        ? db.query("_slow_queries").withIndex("by_timestamp")
+        : db
+            .query("_slow_queries")
+            .withIndex("by_function_and_timestamp", (q) =>
+              q.eq("function", functionName),
+            );
+    return await query.order("desc").paginate({
+      ...paginationOpts,
+      maximumBytesRead,
+      maximumRowsRead,
+    });
+  },
+});
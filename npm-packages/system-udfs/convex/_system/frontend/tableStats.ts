@@ -0,0 +1,73 @@
+import { v } from "convex/values";
+import { queryGeneric } from "../secretSystemTables";
+import { DatabaseReader } from "../../_generated/server";
+import { performAsyncSyscall } from "../../syscall";
+
+export type IndexBackfillProgress = {
+  numDocsIndexed: number;
+  totalDocs: number;
+  startedAt: number;
+  // Unknown until the first documents have been indexed.
+  etaMs: number | null;
+};
+
+export type IndexStats = {
+  name: string;
+  type: "database" | "search" | "vector";
+  state: "backfilling" | "backfilled" | "enabled";
+  // Database index sizes are estimated as the size of the table's documents.
+  sizeBytes: number;
+  // Only set for database indexes this backend is backfilling.
+  backfill: IndexBackfillProgress | null;
+  // Only set for vector indexes. Deleted vectors stay in the index's segments
+  // until they're compacted away.
+  vectors: { live: number; deleted: number } | null;
+};
+
+export type TableStats = {
+  documentCount: number;
+  totalBytes: number;
+  // Averaged over the last few minutes.
+  writesPerMinute: number;
+  indexes: IndexStats[];
+};
+
+export async function tableStats(tableName: string): Promise<TableStats> {
+  return await performAsyncSyscall("1.0/tableStats", { table: tableName });
+}
+
+/**
+ * Document count, size, indexes and write rate of a table. These are kept up
+ * to date by the database, so this doesn't scan the table.
+ */
+export default queryGeneric({
+  args: {
+    tableName: v.string(),
+    componentId: v.optional(v.union(v.string(), v.null())),
+  },
+  handler: async function (_ctx, { tableName }): Promise<TableStats> {
+    return await tableStats(tableName);
+  },
+});
+
+/**
+ * Stats of every user table, by table name.
+ */
+export const allTableStats = queryGeneric({
+  args: { componentId: v.optional(v.union(v.string(), v.null())) },
+  handler: async function ({ db }): Promise<Record<string, TableStats>> {
+    // Getting private system table here is OK because there are no args to this
+    // system UDF.
+    const tables = await ((db as any).privateSystem as DatabaseReader)
+      .query("_tables")
+      .filter((q) => q.eq(q.field("state"), "active"))
+      .collect();
+    const tableNames = tables
+      .map((table) => table.name)
+      .filter((tableName) => !tableName.startsWith("_"));
+    const stats = await Promise.all(tableNames.map(tableStats));
+    return Object.fromEntries(
+      tableNames.map((tableName, i) => [tableName, stats[i]]),
+    );
+  },
+});
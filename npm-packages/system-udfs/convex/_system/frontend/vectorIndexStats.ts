@@ -0,0 +1,36 @@
+import { v } from "convex/values";
+import { queryGeneric } from "../secretSystemTables";
+import { tableStats } from "./tableStats";
+
+/**
+ * Live and deleted vector counts of the vector indexes on a table. Deleted
+ * vectors stay in an index's segments, using memory and hurting recall, until
+ * compaction removes them.
+ */
+export default queryGeneric({
+  args: {
+    tableName: v.string(),
+    componentId: v.optional(v.union(v.string(), v.null())),
+  },
+  handler: async function (
+    _ctx,
+    { tableName },
+  ): Promise<
+    {
+      name: string;
+      state: "backfilling" | "backfilled" | "enabled";
+      liveVectors: number;
+      deletedVectors: number;
+    }[]
+  > {
+    const { indexes } = await tableStats(tableName);
+    return indexes
+      .filter((index) => index.type === "vector")
+      .map(({ name, state, vectors }) => ({
+        name,
+        state,
+        liveVectors: vectors?.live ?? 0,
+        deletedVectors: vectors?.deleted ?? 0,
+      }));
+  },
+});
@@ -39,6 +39,16 @@ const analyzedSourcePosition = v.object({
   start_col: v.int64(),
 });
 
+const functionDependencies = v.object({
+  readsTables: v.array(v.string()),
+  writesTables: v.array(v.string()),
+  calls: v.array(v.string()),
+  schedules: v.array(v.string()),
+  readsUnknownTables: v.boolean(),
+  writesUnknownTables: v.boolean(),
+  referencesUnknownFunctions: v.boolean(),
+});
+
 const analyzedFunction = v.object({
   name: v.string(),
   // Exactly one of these two will be defined
@@ -46,6 +56,7 @@ const analyzedFunction = v.object({
   pos: v.optional(analyzedSourcePosition),
   udfType,
   visibility: v.optional(v.union(v.null(), udfVisibility)),
+  dependencies: v.optional(functionDependencies),
 });
 
 const analyzedHttpRoute = v.object({
@@ -137,6 +148,7 @@ export const completedExport = v.object({
   start_ts: v.int64(),
   tables: v.optional(v.array(v.array(v.string()))),
   zip_object_key: v.optional(v.string()),
+  manifest_object_key: v.optional(v.string()),
   format: v.optional(
     v.union(
       v.literal("internal_json"),
@@ -145,9 +157,20 @@ export const completedExport = v.object({
       v.object({
         format: v.literal("zip"),
         include_storage: v.boolean(),
+        storage_layout: v.optional(
+          v.union(v.literal("in_zip"), v.literal("separate_objects")),
+        ),
       }),
+      v.literal("ndjson"),
+      v.literal("parquet"),
     ),
   ),
+  // Unset when every user table was exported.
+  selected_tables: v.optional(v.array(v.string())),
+  // [table, fields] pairs of fields left out of the export.
+  excluded_fields: v.optional(
+    v.array(v.array(v.union(v.string(), v.array(v.string())))),
+  ),
 });
 
 const cronJobStatus = v.union(
@@ -194,9 +217,19 @@ export const datadogConfig = v.object({
   ),
   ddApiKey: v.string(),
   ddTags: v.array(v.string()),
+  service: v.optional(v.string()),
   version: v.optional(v.union(v.literal("1"), v.literal("2"))),
 });
 
+export const splunkConfig = v.object({
+  type: v.literal("splunk"),
+  hecEndpoint: v.string(),
+  token: v.string(),
+  index: v.optional(v.string()),
+  sourceType: v.optional(v.string()),
+  version: v.union(v.literal("1"), v.literal("2")),
+});
+
 export const webhookConfig = v.object({
   type: v.literal("webhook"),
   url: v.string(),
@@ -222,20 +255,330 @@ export const sentryConfig = v.object({
 
 export const sinkConfig = v.union(
   datadogConfig,
+  splunkConfig,
   webhookConfig,
   axiomConfig,
   sentryConfig,
 );
 
+export const logRoutingRules = v.object({
+  eventTypes: v.array(v.string()),
+  functionPaths: v.array(v.string()),
+  logLevels: v.array(v.string()),
+});
+
 const logSinksTable = defineTable({
   status: sinkState,
   config: sinkConfig,
+  // Missing for sinks configured before routing rules existed.
+  rules: v.optional(logRoutingRules),
 });
 
 const backendStateTable = defineTable({
   state: deploymentState,
 });
 
+const errorGroupsTable = defineTable({
+  fingerprint: v.string(),
+  messageTemplate: v.string(),
+  lastMessage: v.string(),
+  lastStack: v.array(v.string()),
+  // Nanoseconds since the Unix epoch.
+  firstSeen: v.int64(),
+  lastSeen: v.int64(),
+  count: v.int64(),
+  functions: v.array(v.string()),
+})
+  .index("by_fingerprint", ["fingerprint"])
+  .index("by_last_seen", ["lastSeen"]);
+
+const slowQueriesTable = defineTable({
+  // Nanoseconds since the Unix epoch.
+  timestamp: v.int64(),
+  function: v.string(),
+  udfType: v.string(),
+  requestId: v.string(),
+  executionTimeMs: v.int64(),
+  databaseReadBytes: v.int64(),
+  tables: v.array(
+    v.object({
+      table: v.string(),
+      rowsRead: v.int64(),
+      rowsWritten: v.int64(),
+    }),
+  ),
+  indexRanges: v.array(
+    v.object({
+      index: v.string(),
+      fields: v.array(v.string()),
+      numRanges: v.int64(),
+    }),
+  ),
+  syscalls: v.array(
+    v.object({
+      name: v.string(),
+      invocations: v.int64(),
+      errors: v.int64(),
+      durationMs: v.int64(),
+    }),
+  ),
+})
+  .index("by_timestamp", ["timestamp"])
+  .index("by_function_and_timestamp", ["function", "timestamp"]);
+
+const scheduledExportsTable = defineTable({
+  name: v.string(),
+  cron: v.string(),
+  timezone: v.optional(v.string()),
+  includeStorage: v.boolean(),
+  destination: v.object({
+    provider: v.union(v.literal("s3"), v.literal("gcs")),
+    bucket: v.string(),
+    prefix: v.string(),
+    region: v.string(),
+    endpoint: v.optional(v.string()),
+    accessKeyId: v.string(),
+    secretAccessKey: v.string(),
+  }),
+  retentionCount: v.int64(),
+  nextTs: v.int64(),
+});
+
+const scheduledExportRunsTable = defineTable({
+  schedule: v.string(),
+  startedTs: v.int64(),
+  state: v.union(
+    v.object({ type: v.literal("exporting") }),
+    v.object({ type: v.literal("uploading"), snapshotTs: v.int64() }),
+    v.object({
+      type: v.literal("completed"),
+      snapshotTs: v.int64(),
+      objectKey: v.string(),
+      sizeBytes: v.int64(),
+      completedTs: v.int64(),
+    }),
+    v.object({
+      type: v.literal("failed"),
+      error: v.string(),
+      failedTs: v.int64(),
+    }),
+  ),
+}).index("by_schedule_and_started_ts", ["schedule", "startedTs"]);
+
+const deploymentClonesTable = defineTable({
+  targetUrl: v.string(),
+  targetAdminKey: v.optional(v.string()),
+  includeStorage: v.boolean(),
+  startedTs: v.int64(),
+  state: v.union(
+    v.object({ type: v.literal("pending") }),
+    v.object({ type: v.literal("copyingCode") }),
+    v.object({ type: v.literal("copyingEnvironmentVariables") }),
+    v.object({ type: v.literal("exporting") }),
+    v.object({ type: v.literal("importing"), snapshotTs: v.int64() }),
+    v.object({
+      type: v.literal("copyingScheduledJobs"),
+      snapshotTs: v.int64(),
+    }),
+    v.object({
+      type: v.literal("completed"),
+      snapshotTs: v.int64(),
+      completedTs: v.int64(),
+    }),
+    v.object({
+      type: v.literal("failed"),
+      error: v.string(),
+      failedTs: v.int64(),
+    }),
+  ),
+});
+
+const fileUploadSessionsTable = defineTable({
+  uploadId: v.string(),
+  uploadToken: v.string(),
+  contentType: v.optional(v.string()),
+  sha256: v.optional(v.string()),
+  maxBytes: v.optional(v.int64()),
+  expiresTs: v.int64(),
+  state: v.union(
+    v.object({ type: v.literal("uploading") }),
+    v.object({ type: v.literal("completed"), storageId: v.string() }),
+  ),
+})
+  .index("by_upload_id", ["uploadId"])
+  .index("by_expires_ts", ["expiresTs"]);
+
+const fileUploadPartsTable = defineTable({
+  uploadId: v.string(),
+  partNumber: v.int64(),
+  partToken: v.string(),
+  size: v.int64(),
+}).index("by_upload_id_and_part_number", ["uploadId", "partNumber"]);
+
+const fileLifecycleRulesTable = defineTable({
+  name: v.string(),
+  minAgeDays: v.int64(),
+  onlyOrphaned: v.boolean(),
+  action: v.union(
+    v.object({ type: v.literal("delete") }),
+    v.object({ type: v.literal("transition"), storageClass: v.string() }),
+  ),
+  dryRun: v.boolean(),
+  lastRun: v.optional(
+    v.object({
+      startedTs: v.int64(),
+      finishedTs: v.int64(),
+      dryRun: v.boolean(),
+      filesMatched: v.int64(),
+      bytesMatched: v.int64(),
+      filesApplied: v.int64(),
+      sampleStorageIds: v.array(v.string()),
+      error: v.optional(v.string()),
+    }),
+  ),
+});
+
+const functionFlagsTable = defineTable({
+  componentPath: v.string(),
+  udfPath: v.string(),
+  mode: v.union(
+    v.object({ type: v.literal("disabled") }),
+    v.object({ type: v.literal("canary"), rolloutPercentage: v.int64() }),
+  ),
+  reason: v.optional(v.string()),
+});
+
+const egressPolicyTable = defineTable({
+  allowedDomains: v.array(v.string()),
+  deniedDomains: v.array(v.string()),
+  proxy: v.optional(
+    v.object({
+      url: v.string(),
+      username: v.optional(v.string()),
+      password: v.optional(v.string()),
+    }),
+  ),
+});
+
+const searchIndexPinsTable = defineTable({
+  tableName: v.string(),
+  indexDescriptor: v.string(),
+});
+
+const tableStorageSnapshotsTable = defineTable({
+  // Days since the Unix epoch, in UTC.
+  day: v.int64(),
+  tableName: v.string(),
+  documentCount: v.int64(),
+  documentSize: v.int64(),
+  indexSize: v.int64(),
+  historicalSize: v.int64(),
+}).index("by_day", ["day"]);
+
+const outboxHeader = v.object({
+  name: v.string(),
+  value: v.string(),
+});
+
+const outboxTargetsTable = defineTable({
+  name: v.string(),
+  config: v.union(
+    v.object({
+      type: v.literal("http"),
+      url: v.string(),
+      headers: v.array(outboxHeader),
+    }),
+    v.object({
+      type: v.literal("kafka"),
+      restProxyUrl: v.string(),
+      topic: v.string(),
+      headers: v.array(outboxHeader),
+    }),
+  ),
+}).index("by_name", ["name"]);
+
+const outboxTable = defineTable({
+  target: v.string(),
+  // JSON-serialized payload.
+  payload: v.bytes(),
+  enqueuedTs: v.int64(),
+  // Null once the record is delivered or has failed.
+  nextAttemptTs: v.union(v.int64(), v.null()),
+  attempts: v.int64(),
+  state: v.union(
+    v.literal("pending"),
+    v.literal("delivered"),
+    v.literal("failed"),
+  ),
+  deliveredTs: v.optional(v.int64()),
+  error: v.optional(v.string()),
+})
+  .index("by_next_attempt_ts", ["nextAttemptTs"])
+  .index("by_state_and_enqueued_ts", ["state", "enqueuedTs"]);
+
+const trafficShadowingTable = defineTable({
+  targetUrl: v.string(),
+  targetAdminKey: v.string(),
+  samplePercentage: v.float64(),
+});
+
+const functionReadBudgetsTable = defineTable({
+  componentPath: v.string(),
+  udfPath: v.string(),
+  maxDocuments: v.optional(v.int64()),
+  maxBytes: v.optional(v.int64()),
+});
+
+const logSettingsTable = defineTable({
+  udfPath: v.optional(v.string()),
+  minLevel: v.optional(
+    v.union(
+      v.literal("DEBUG"),
+      v.literal("LOG"),
+      v.literal("INFO"),
+      v.literal("WARN"),
+      v.literal("ERROR"),
+    ),
+  ),
+  sampleRate: v.optional(v.float64()),
+});
+
+const deploymentVersionsTable = defineTable({
+  storageKey: v.string(),
+  udfServerVersion: v.string(),
+});
+
+const functionCanariesTable = defineTable({
+  componentPath: v.string(),
+  udfPath: v.string(),
+  sourcePackageId: v.string(),
+  rolloutPercentage: v.int64(),
+});
+
+const jobExecutionsTable = defineTable({
+  kind: v.union(v.literal("scheduled"), v.literal("cron")),
+  // The scheduled job's ID, or the cron's name.
+  job: v.string(),
+  componentPath: v.string(),
+  udfPath: v.string(),
+  // Nanoseconds since the Unix epoch.
+  start: v.int64(),
+  end: v.int64(),
+  status: v.union(v.literal("success"), v.literal("failure")),
+  error: v.optional(v.string()),
+  attempts: v.int64(),
+  usage: v.object({
+    databaseReadBytes: v.int64(),
+    databaseWriteBytes: v.int64(),
+    storageReadBytes: v.int64(),
+    storageWriteBytes: v.int64(),
+    actionMemoryUsedMb: v.optional(v.int64()),
+  }),
+})
+  .index("by_end", ["end"])
+  .index("by_kind_and_end", ["kind", "end"])
+  .index("by_kind_job_and_end", ["kind", "job", "end"]);
+
 export default defineSchema({
   _tables: defineTable({
     name: v.string(),
@@ -243,6 +586,7 @@ export default defineSchema({
       v.literal("active"),
       v.literal("deleting"),
       v.literal("hidden"),
+      v.literal("archived"),
     ),
   }),
   _modules: defineTable({
@@ -321,4 +665,24 @@ export default defineSchema({
   _log_sinks: logSinksTable,
   _backend_state: backendStateTable,
   _snapshot_imports: snapshotImportsTable,
+  _error_groups: errorGroupsTable,
+  _slow_queries: slowQueriesTable,
+  _scheduled_exports: scheduledExportsTable,
+  _scheduled_export_runs: scheduledExportRunsTable,
+  _deployment_clones: deploymentClonesTable,
+  _file_upload_sessions: fileUploadSessionsTable,
+  _file_upload_parts: fileUploadPartsTable,
+  _file_lifecycle_rules: fileLifecycleRulesTable,
+  _function_flags: functionFlagsTable,
+  _egress_policy: egressPolicyTable,
+  _search_index_pins: searchIndexPinsTable,
+  _table_storage_snapshots: tableStorageSnapshotsTable,
+  _outbox_targets: outboxTargetsTable,
+  _outbox: outboxTable,
+  _traffic_shadowing: trafficShadowingTable,
+  _function_read_budgets: functionReadBudgetsTable,
+  _log_settings: logSettingsTable,
+  _deployment_versions: deploymentVersionsTable,
+  _function_canaries: functionCanariesTable,
+  _job_executions: jobExecutionsTable,
 });
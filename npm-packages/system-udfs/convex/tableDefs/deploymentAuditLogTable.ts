@@ -2,9 +2,30 @@ import { defineTable } from "convex/server";
 import { v } from "convex/values";
 import { snapshotImportFormat, snapshotImportMode } from "./snapshotImport";
 
+// Who performed the action. Missing on events recorded before actors were
+// tracked.
+export const auditLogActor = v.optional(
+  v.union(
+    v.object({
+      type: v.literal("admin"),
+      member_id: v.union(v.int64(), v.null()),
+      team_id: v.union(v.int64(), v.null()),
+      role: v.union(
+        v.literal("admin"),
+        v.literal("deploy"),
+        v.literal("read_only"),
+        v.null(),
+      ),
+    }),
+    v.object({ type: v.literal("system") }),
+    v.object({ type: v.literal("unknown") }),
+  ),
+);
+
 const createEnvironmentVariable = v.object({
   action: v.literal("create_environment_variable"),
   member_id: v.int64(),
+  actor: auditLogActor,
   metadata: v.object({
     variable_name: v.string(),
   }),
@@ -13,6 +34,7 @@ const createEnvironmentVariable = v.object({
 const deleteEnvironmentVariable = v.object({
   action: v.literal("delete_environment_variable"),
   member_id: v.int64(),
+  actor: auditLogActor,
   metadata: v.object({
     variable_name: v.string(),
   }),
@@ -21,6 +43,7 @@ const deleteEnvironmentVariable = v.object({
 const updateEnvironmentVariable = v.object({
   action: v.literal("update_environment_variable"),
   member_id: v.int64(),
+  actor: auditLogActor,
   metadata: v.object({
     variable_name: v.string(),
   }),
@@ -29,6 +52,7 @@ const updateEnvironmentVariable = v.object({
 const replaceEnvironmentVariable = v.object({
   action: v.literal("replace_environment_variable"),
   member_id: v.int64(),
+  actor: auditLogActor,
   metadata: v.object({
     previous_variable_name: v.string(),
     variable_name: v.string(),
@@ -45,6 +69,10 @@ const searchIndex = v.object({
   name: v.optional(v.string()),
   type: v.literal("search"),
   searchField: v.string(),
+  additionalSearchFields: v.optional(v.array(v.string())),
+  searchFieldBoosts: v.optional(
+    v.array(v.object({ fieldPath: v.string(), boost: v.int64() })),
+  ),
   filterFields: v.array(v.string()),
 });
 
@@ -61,6 +89,7 @@ export const indexMetadata = v.union(databaseIndex, searchIndex, vectorIndex);
 export const buildIndexes = v.object({
   action: v.literal("build_indexes"),
   member_id: v.int64(),
+  actor: auditLogActor,
   metadata: v.object({
     added_indexes: v.array(v.union(databaseIndex, searchIndex, vectorIndex)),
     removed_indexes: v.array(v.union(databaseIndex, searchIndex, vectorIndex)),
@@ -70,6 +99,7 @@ export const buildIndexes = v.object({
 export const pushConfig = v.object({
   action: v.literal("push_config"),
   member_id: v.int64(),
+  actor: auditLogActor,
   metadata: v.object({
     auth: v.object({
       added: v.array(v.string()),
@@ -111,11 +141,13 @@ export const deploymentState = v.union(
   v.literal("paused"),
   v.literal("running"),
   v.literal("disabled"),
+  v.literal("read_only"),
 );
 
 export const changeDeploymentState = v.object({
   action: v.literal("change_deployment_state"),
   member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
   metadata: v.object({
     old_state: deploymentState,
     new_state: deploymentState,
@@ -125,12 +157,14 @@ export const changeDeploymentState = v.object({
 export const clearTables = v.object({
   action: v.literal("clear_tables"),
   member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
   metadata: v.object({}),
 });
 
 export const snapshotImport = v.object({
   action: v.literal("snapshot_import"),
   member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
   metadata: v.object({
     table_names: v.array(v.string()),
     table_count: v.int64(),
@@ -139,6 +173,319 @@ export const snapshotImport = v.object({
   }),
 });
 
+export const createApiKey = v.object({
+  action: v.literal("create_api_key"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    api_key_name: v.string(),
+  }),
+});
+
+export const deleteApiKey = v.object({
+  action: v.literal("delete_api_key"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    api_key_name: v.string(),
+  }),
+});
+
+const logSinkType = v.union(
+  v.literal("datadog"),
+  v.literal("splunk"),
+  v.literal("sentry"),
+);
+
+export const createLogSink = v.object({
+  action: v.literal("create_log_sink"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    sink_type: logSinkType,
+  }),
+});
+
+export const updateLogSinkRules = v.object({
+  action: v.literal("update_log_sink_rules"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    sink_type: logSinkType,
+  }),
+});
+
+export const deleteLogSink = v.object({
+  action: v.literal("delete_log_sink"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    sink_type: logSinkType,
+  }),
+});
+
+export const revokeSessions = v.object({
+  action: v.literal("revoke_sessions"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    subject: v.string(),
+    issuer: v.union(v.string(), v.null()),
+    token_id: v.union(v.string(), v.null()),
+  }),
+});
+
+export const requestExport = v.object({
+  action: v.literal("request_export"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    format: v.union(
+      v.literal("internal_json"),
+      v.literal("clean_jsonl"),
+      v.object({
+        format: v.literal("zip"),
+        include_storage: v.boolean(),
+        storage_layout: v.optional(
+          v.union(v.literal("in_zip"), v.literal("separate_objects")),
+        ),
+      }),
+      v.literal("ndjson"),
+      v.literal("parquet"),
+    ),
+  }),
+});
+
+export const createExportSchedule = v.object({
+  action: v.literal("create_export_schedule"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    schedule_name: v.string(),
+  }),
+});
+
+export const deleteExportSchedule = v.object({
+  action: v.literal("delete_export_schedule"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    schedule_name: v.string(),
+  }),
+});
+
+export const cloneDeployment = v.object({
+  action: v.literal("clone_deployment"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    target_url: v.string(),
+  }),
+});
+
+export const dashboardEdit = v.object({
+  action: v.literal("dashboard_edit"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    function: v.string(),
+    request_id: v.string(),
+  }),
+});
+
+export const createFileLifecycleRule = v.object({
+  action: v.literal("create_file_lifecycle_rule"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    rule_name: v.string(),
+  }),
+});
+
+export const deleteFileLifecycleRule = v.object({
+  action: v.literal("delete_file_lifecycle_rule"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    rule_name: v.string(),
+  }),
+});
+
+export const updateBackendSetting = v.object({
+  action: v.literal("update_backend_setting"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    setting_name: v.string(),
+    previous_value: v.string(),
+    value: v.string(),
+  }),
+});
+
+export const restoreDocument = v.object({
+  action: v.literal("restore_document"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    table_name: v.string(),
+    document_id: v.string(),
+  }),
+});
+
+export const setFunctionFlag = v.object({
+  action: v.literal("set_function_flag"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    component_path: v.string(),
+    udf_path: v.string(),
+    mode: v.string(),
+  }),
+});
+
+export const deleteFunctionFlag = v.object({
+  action: v.literal("delete_function_flag"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    component_path: v.string(),
+    udf_path: v.string(),
+  }),
+});
+
+export const updateEgressPolicy = v.object({
+  action: v.literal("update_egress_policy"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    allowed_domains: v.array(v.string()),
+    denied_domains: v.array(v.string()),
+    proxy_url: v.union(v.string(), v.null()),
+  }),
+});
+
+export const deleteEgressPolicy = v.object({
+  action: v.literal("delete_egress_policy"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({}),
+});
+
+export const bulkEditDocuments = v.object({
+  action: v.literal("bulk_edit_documents"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    table_name: v.string(),
+    operation: v.union(v.literal("delete"), v.literal("patch")),
+    document_count: v.int64(),
+  }),
+});
+
+export const pinSearchIndex = v.object({
+  action: v.literal("pin_search_index"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    table_name: v.string(),
+    index_name: v.string(),
+  }),
+});
+
+export const unpinSearchIndex = v.object({
+  action: v.literal("unpin_search_index"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    table_name: v.string(),
+    index_name: v.string(),
+  }),
+});
+
+export const renameTable = v.object({
+  action: v.literal("rename_table"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    table_name: v.string(),
+    new_name: v.string(),
+  }),
+});
+
+export const archiveTable = v.object({
+  action: v.literal("archive_table"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    table_name: v.string(),
+  }),
+});
+
+export const restoreTable = v.object({
+  action: v.literal("restore_table"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    table_name: v.string(),
+    new_name: v.string(),
+  }),
+});
+
+export const setOutboxTarget = v.object({
+  action: v.literal("set_outbox_target"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    name: v.string(),
+    target_type: v.string(),
+  }),
+});
+
+export const deleteOutboxTarget = v.object({
+  action: v.literal("delete_outbox_target"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    name: v.string(),
+  }),
+});
+
+export const updateTrafficShadowing = v.object({
+  action: v.literal("update_traffic_shadowing"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    target_url: v.string(),
+  }),
+});
+
+export const deleteTrafficShadowing = v.object({
+  action: v.literal("delete_traffic_shadowing"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({}),
+});
+
+export const setFunctionReadBudget = v.object({
+  action: v.literal("set_function_read_budget"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    component_path: v.string(),
+    udf_path: v.string(),
+  }),
+});
+
+export const deleteFunctionReadBudget = v.object({
+  action: v.literal("delete_function_read_budget"),
+  member_id: v.union(v.int64(), v.null()),
+  actor: auditLogActor,
+  metadata: v.object({
+    component_path: v.string(),
+    udf_path: v.string(),
+  }),
+});
+
 const deploymentAuditLogTable = defineTable(
   v.union(
     createEnvironmentVariable,
@@ -150,6 +497,37 @@ const deploymentAuditLogTable = defineTable(
     changeDeploymentState,
     clearTables,
     snapshotImport,
+    createApiKey,
+    deleteApiKey,
+    createLogSink,
+    updateLogSinkRules,
+    deleteLogSink,
+    revokeSessions,
+    requestExport,
+    createExportSchedule,
+    deleteExportSchedule,
+    cloneDeployment,
+    dashboardEdit,
+    createFileLifecycleRule,
+    deleteFileLifecycleRule,
+    updateBackendSetting,
+    restoreDocument,
+    setFunctionFlag,
+    deleteFunctionFlag,
+    updateEgressPolicy,
+    deleteEgressPolicy,
+    bulkEditDocuments,
+    pinSearchIndex,
+    unpinSearchIndex,
+    renameTable,
+    archiveTable,
+    restoreTable,
+    setOutboxTarget,
+    deleteOutboxTarget,
+    updateTrafficShadowing,
+    deleteTrafficShadowing,
+    setFunctionReadBudget,
+    deleteFunctionReadBudget,
   ),
 );
 
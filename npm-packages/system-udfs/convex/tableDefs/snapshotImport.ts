@@ -1,13 +1,29 @@
 import { defineTable } from "convex/server";
 import { v } from "convex/values";
 
+export const csvColumnType = v.union(
+  v.literal("string"),
+  v.literal("number"),
+  v.literal("int64"),
+  v.literal("boolean"),
+  v.literal("json"),
+);
+
 export const snapshotImportFormat = v.union(
   v.object({
-    format: v.union(
-      v.literal("csv"),
-      v.literal("jsonl"),
-      v.literal("json_array"),
+    format: v.literal("csv"),
+    table: v.string(),
+    column_types: v.optional(
+      v.array(
+        v.object({
+          column: v.string(),
+          column_type: csvColumnType,
+        }),
+      ),
     ),
+  }),
+  v.object({
+    format: v.union(v.literal("jsonl"), v.literal("json_array")),
     table: v.string(),
   }),
   v.object({
@@ -65,4 +81,21 @@ export const snapshotImportsTable = defineTable({
       ),
     ),
   ),
+  dry_run: v.optional(v.boolean()),
+  validation: v.optional(
+    v.union(
+      v.null(),
+      v.array(
+        v.object({
+          display_table_name: v.string(),
+          num_rows: v.int64(),
+          num_type_mismatches: v.int64(),
+          num_id_collisions: v.int64(),
+          ingress_bytes: v.int64(),
+          errors: v.array(v.string()),
+        }),
+      ),
+    ),
+  ),
+  remap_ids: v.optional(v.boolean()),
 });
@@ -1,6 +1,6 @@
 import { performAsyncOp, performOp } from "./syscall";
 import { Blob, File } from "./09_file";
-import { constructStreamId } from "./06_streams";
+import { constructStreamId, ReadableStream } from "./06_streams";
 
 type FormDataEntryValue = string | File;
 
@@ -117,7 +117,7 @@ export type MimeType = {
 };
 
 export const parseFormData = async (
-  body: Blob | null,
+  body: ReadableStream | null,
   contentType: string | null,
 ) => {
   if (contentType === null) {
@@ -134,10 +134,12 @@ export const parseFormData = async (
         "Missing boundary parameter in mime type of multipart formdata.",
       );
     }
+    // Hand the body stream straight to the parser rather than reading it into
+    // a blob first.
     const entries = await performAsyncOp(
       "form/parseMultiPart",
       contentType,
-      constructStreamId((body ?? new Blob()).stream()),
+      constructStreamId(body ?? new Blob().stream()),
     );
     const formData = new FormData();
     for (const formEntry of entries) {
@@ -157,7 +159,7 @@ export const parseFormData = async (
   } else if (mimeType.essence === "application/x-www-form-urlencoded") {
     const entries = performOp(
       "url/getUrlSearchParamPairs",
-      await (body ?? new Blob()).text(),
+      await readStreamToText(body),
     );
     const formData = new FormData();
     for (const [k, v] of entries) {
@@ -168,6 +170,128 @@ export const parseFormData = async (
   throw new TypeError("Body cannot be decoded as form data");
 };
 
+const readStreamToText = async (stream: ReadableStream | null) => {
+  const chunks: Uint8Array[] = [];
+  if (stream !== null) {
+    const reader = stream.getReader();
+    // eslint-disable-next-line no-constant-condition
+    while (true) {
+      const { done, value } = await reader.read();
+      if (done) {
+        break;
+      }
+      chunks.push(value);
+    }
+  }
+  return new Blob(chunks).text();
+};
+
+export type StreamedFormDataEntry = {
+  name: string;
+  fileName: string | null;
+  contentType: string | null;
+  body: ReadableStream<Uint8Array>;
+};
+
+type MultiPartEvent =
+  | {
+      kind: "field";
+      name: string;
+      fileName: string | null;
+      contentType: string | null;
+    }
+  | { kind: "chunk"; value: Uint8Array }
+  | { kind: "done" };
+
+/**
+ * Parse a multipart/form-data body one entry at a time. Each entry's `body`
+ * streams the entry's data as the request body is read, so large files can be
+ * passed along (e.g. to `fetch`) without holding them in memory.
+ *
+ * Moving on to the next entry discards whatever is left of the current
+ * entry's body.
+ */
+export async function* streamMultiPartFormData(
+  body: ReadableStream | null,
+  contentType: string | null,
+): AsyncGenerator<StreamedFormDataEntry> {
+  if (contentType === null) {
+    throw new TypeError("Missing content type");
+  }
+  const mimeType = performOp("headers/getMimeType", contentType);
+  if (mimeType === null) {
+    throw new TypeError("Invalid content type");
+  }
+  if (mimeType.essence !== "multipart/form-data") {
+    throw new TypeError("Body cannot be streamed as multipart form data");
+  }
+  const readerId: string = performOp("form/newMultiPartReader", contentType);
+  const bodyReader = (body ?? new Blob().stream()).getReader();
+
+  // Events parsed from the body but not consumed yet. A "done" event is never
+  // consumed, so nothing reads the body after the end of the form.
+  const pending: MultiPartEvent[] = [];
+  let feeding: Promise<void> | null = null;
+  const feed = async () => {
+    const { done, value } = await bodyReader.read();
+    pending.push(
+      ...performOp("form/feedMultiPartReader", readerId, done ? null : value),
+    );
+  };
+  // Wait for the next event and consume it if it's one `accept` wants. Both
+  // the generator and the body of the current entry read events, so only one
+  // of them feeds the body to the parser at a time.
+  const nextEvent = async (
+    accept: (event: MultiPartEvent) => boolean,
+  ): Promise<MultiPartEvent | null> => {
+    while (pending.length === 0) {
+      if (feeding === null) {
+        feeding = feed().finally(() => {
+          feeding = null;
+        });
+      }
+      await feeding;
+    }
+    return accept(pending[0]) ? pending.shift()! : null;
+  };
+
+  let currentEntry = 0;
+  try {
+    // eslint-disable-next-line no-constant-condition
+    while (true) {
+      const event = await nextEvent((e) => e.kind !== "done");
+      if (event === null) {
+        return;
+      }
+      if (event.kind !== "field") {
+        // The rest of an entry the caller moved past.
+        continue;
+      }
+      const entry = ++currentEntry;
+      const entryBody = new ReadableStream<Uint8Array>({
+        async pull(controller) {
+          const next = await nextEvent(
+            (e) => e.kind === "chunk" && entry === currentEntry,
+          );
+          if (next !== null && next.kind === "chunk") {
+            controller.enqueue(next.value);
+          } else {
+            controller.close();
+          }
+        },
+      });
+      yield {
+        name: event.name,
+        fileName: event.fileName,
+        contentType: event.contentType,
+        body: entryBody,
+      };
+    }
+  } finally {
+    performOp("form/cleanupMultiPartReader", readerId);
+  }
+}
+
 const CRLF = "\r\n";
 const LONELY_CR_OR_LF = /\r(?!\n)|(?<!\r)\n/g;
 const fixLineEndings = (s: string) => {
@@ -1,7 +1,13 @@
 import { throwNotImplementedMethodError } from "./helpers.js";
 import { Blob, isSupportedBlobPart } from "./09_file.js";
 import inspect from "object-inspect";
-import { parseFormData, FormData, formDataToBlob } from "./21_formdata.js";
+import {
+  parseFormData,
+  FormData,
+  formDataToBlob,
+  streamMultiPartFormData,
+  StreamedFormDataEntry,
+} from "./21_formdata.js";
 import {
   constructStreamId,
   extractStream,
@@ -234,8 +240,19 @@ export class Request {
 
   async formData(): Promise<FormData> {
     this._markBodyUsed("formData");
-    const blob = await this._blob();
-    return parseFormData(blob, this._headers.get("content-type"));
+    return parseFormData(this._bodyStream, this._headers.get("content-type"));
+  }
+
+  /**
+   * Read a multipart/form-data body one entry at a time, streaming each
+   * entry's data instead of buffering the whole form like `formData()`.
+   */
+  streamFormData(): AsyncGenerator<StreamedFormDataEntry> {
+    this._markBodyUsed("streamFormData");
+    return streamMultiPartFormData(
+      this._bodyStream,
+      this._headers.get("content-type"),
+    );
   }
 
   get body() {
@@ -225,8 +225,7 @@ export class Response {
 
   async formData(): Promise<FormData> {
     this._markBodyUsed("formData");
-    const blob = await this._blob();
-    return parseFormData(blob, this._headers.get("content-type"));
+    return parseFormData(this._bodyStream, this._headers.get("content-type"));
   }
 
   get body() {
@@ -65,6 +65,7 @@ import type * as logging from "../logging.js";
 import type * as name from "../name.js";
 import type * as node_actions from "../node_actions.js";
 import type * as query from "../query.js";
+import type * as query_cache from "../query_cache.js";
 import type * as returns_validation from "../returns_validation.js";
 import type * as scheduler from "../scheduler.js";
 import type * as search from "../search.js";
@@ -77,6 +78,7 @@ import type * as unicode from "../unicode.js";
 import type * as userError from "../userError.js";
 import type * as values from "../values.js";
 import type * as vector_search from "../vector_search.js";
+import type * as workflow from "../workflow.js";
 
 /**
  * A utility for referencing Convex functions in your app's API.
@@ -138,6 +140,7 @@ declare const fullApi: ApiFromModules<{
   name: typeof name;
   node_actions: typeof node_actions;
   query: typeof query;
+  query_cache: typeof query_cache;
   returns_validation: typeof returns_validation;
   scheduler: typeof scheduler;
   search: typeof search;
@@ -150,6 +153,7 @@ declare const fullApi: ApiFromModules<{
   userError: typeof userError;
   values: typeof values;
   vector_search: typeof vector_search;
+  workflow: typeof workflow;
 }>;
 export declare const api: FilterApi<
   typeof fullApi,
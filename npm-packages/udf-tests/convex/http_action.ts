@@ -1,4 +1,4 @@
-import { httpRouter } from "convex/server";
+import { getPathParams, httpRouter } from "convex/server";
 import { imported } from "./http_no_default";
 import { api } from "./_generated/api";
 import { httpAction, query } from "./_generated/server";
@@ -258,6 +258,38 @@ http.route({
   }),
 });
 
+http.route({
+  method: "GET",
+  path: "/users/:id/posts/*rest",
+  handler: httpAction(async (_ctx, request) => {
+    return new Response(
+      JSON.stringify(getPathParams<"/users/:id/posts/*rest">(request)),
+    );
+  }),
+});
+
+http.route({
+  method: "POST",
+  path: "/serverSentEvents",
+  handler: httpAction(async (_ctx, request) => {
+    const { pauseMs } = await request.json();
+    const { readable, writable } = new TransformStream();
+    const writer = writable.getWriter();
+    const encoder = new TextEncoder();
+    const streamEvents = async () => {
+      await writer.write(encoder.encode("data: 1\n\n"));
+      await sleep(pauseMs);
+      await writer.write(encoder.encode("data: 2\n\n"));
+      await writer.close();
+    };
+    void streamEvents();
+    return new Response(readable, {
+      status: 200,
+      headers: { "Content-Type": "text/event-stream" },
+    });
+  }),
+});
+
 export const erroringQuery = query(() => {
   throw new Error("Oh no! Called erroring query");
 });
@@ -120,6 +120,58 @@ async function requestReadableStream() {
   assert.strictEqual(await request.text(), "part1part2");
 }
 
+async function streamMultiPartFormData() {
+  const encoder = new TextEncoder();
+  const body =
+    "--boundary\r\n" +
+    'Content-Disposition: form-data; name="field_1"\r\n\r\n' +
+    "value_1\r\n" +
+    "--boundary\r\n" +
+    'Content-Disposition: form-data; name="skipped"\r\n\r\n' +
+    "not read\r\n" +
+    "--boundary\r\n" +
+    'Content-Disposition: form-data; name="file"; filename="file.txt"\r\n' +
+    "Content-Type: text/plain\r\n\r\n" +
+    "file contents\r\n" +
+    "--boundary--\r\n";
+  // Split the body into small chunks so entries span several reads.
+  const request = new Request("http://foo/", {
+    method: "POST",
+    headers: { "Content-Type": "multipart/form-data; boundary=boundary" },
+    body: new ReadableStream({
+      start(controller) {
+        for (let i = 0; i < body.length; i += 7) {
+          controller.enqueue(encoder.encode(body.slice(i, i + 7)));
+        }
+        controller.close();
+      },
+    }),
+  });
+  const entries: any[] = [];
+  for await (const entry of (request as any).streamFormData()) {
+    entries.push({
+      name: entry.name,
+      fileName: entry.fileName,
+      contentType: entry.contentType,
+      text:
+        entry.name === "skipped"
+          ? null
+          : await new Response(entry.body).text(),
+    });
+  }
+  assert.deepEqual(entries, [
+    { name: "field_1", fileName: null, contentType: null, text: "value_1" },
+    { name: "skipped", fileName: null, contentType: null, text: null },
+    {
+      name: "file",
+      fileName: "file.txt",
+      contentType: "text/plain",
+      text: "file contents",
+    },
+  ]);
+  assert.strictEqual(request.bodyUsed, true);
+}
+
 export default query(async () => {
   return await wrapInTests({
     fromInit,
@@ -132,5 +184,6 @@ export default query(async () => {
     submitURLEncodedForData,
     requestMethod,
     requestReadableStream,
+    streamMultiPartFormData,
   });
 });
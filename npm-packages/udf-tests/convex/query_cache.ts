@@ -0,0 +1,10 @@
+import { query } from "./_generated/server";
+
+export const countObjects = query({
+  args: {},
+  cache: { ttlSeconds: 60 },
+  handler: async ({ db }) => {
+    const objects = await db.query("objects").collect();
+    return objects.length;
+  },
+});
@@ -1,4 +1,4 @@
-import { v } from "convex/values";
+import { ConvexError, v } from "convex/values";
 import { action, mutation, query } from "./_generated/server";
 
 export const extraOutputFields = query({
@@ -56,3 +56,29 @@ export const stringOutputReturnsStringAction = action({
     return "hello";
   },
 });
+
+const typedErrors = { NotFound: v.object({ id: v.string() }) };
+
+export const throwsDeclaredError = query({
+  args: {},
+  errors: typedErrors,
+  handler: () => {
+    throw new ConvexError({ code: "NotFound", data: { id: "abc" } });
+  },
+});
+
+export const throwsUndeclaredErrorCode = query({
+  args: {},
+  errors: typedErrors,
+  handler: () => {
+    throw new ConvexError({ code: "Forbidden", data: { id: "abc" } });
+  },
+});
+
+export const throwsInvalidErrorData = query({
+  args: {},
+  errors: typedErrors,
+  handler: () => {
+    throw new ConvexError({ code: "NotFound", data: { id: 1 } });
+  },
+});
@@ -0,0 +1,17 @@
+import { v } from "convex/values";
+import { action } from "./_generated/server";
+
+export const runSteps = action({
+  args: { value: v.string(), failSecondStep: v.boolean() },
+  handler: async (ctx, { value, failSecondStep }) => {
+    // On a retry, this returns the value from the attempt that ran the step.
+    const first = await ctx.step("first", async () => value);
+    const second = await ctx.step("second", async () => {
+      if (failSecondStep) {
+        throw new Error("Failed the second step");
+      }
+      return value;
+    });
+    return [first, second];
+  },
+});